@@ -22,6 +22,7 @@ function syncConfig() {
     const envContent = fs.readFileSync(envPath, 'utf8');
     const appName = getEnvValue(envContent, 'APP_NAME');
     const apiUrl = getEnvValue(envContent, 'VITE_API_URL');
+    const updaterPubkey = getEnvValue(envContent, 'UPDATER_PUBKEY');
 
     // Read tauri.conf.json
     const config = JSON.parse(fs.readFileSync(configPath, 'utf8'));
@@ -71,6 +72,15 @@ function syncConfig() {
       }
     }
 
+    // Sync the key update bundles are verified with
+    if (updaterPubkey && config.plugins?.updater?.pubkey !== updaterPubkey) {
+      console.log('ℹ Syncing UPDATER_PUBKEY');
+      if (!config.plugins) config.plugins = {};
+      if (!config.plugins.updater) config.plugins.updater = {};
+      config.plugins.updater.pubkey = updaterPubkey;
+      changed = true;
+    }
+
     if (changed) {
       fs.writeFileSync(configPath, JSON.stringify(config, null, 2), 'utf8');
       console.log('✓ tauri.conf.json updated successfully.');
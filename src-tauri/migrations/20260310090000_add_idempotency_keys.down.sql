@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS public.idx_idempotency_keys_expires;
+DROP TABLE IF EXISTS public.idempotency_keys;
@@ -0,0 +1,8 @@
+-- Release-level ledger of applied migrations (sqlx keeps its own in _sqlx_migrations).
+CREATE TABLE IF NOT EXISTS public.schema_migrations (
+    version BIGINT PRIMARY KEY,
+    description TEXT NOT NULL,
+    checksum TEXT NOT NULL,
+    app_version TEXT NOT NULL,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
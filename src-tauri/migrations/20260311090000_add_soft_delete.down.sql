@@ -0,0 +1,14 @@
+DELETE FROM public.isp_packages WHERE deleted_at IS NOT NULL;
+DROP INDEX IF EXISTS public.isp_packages_tenant_name_active_unique;
+ALTER TABLE public.isp_packages
+    ADD CONSTRAINT isp_packages_tenant_name_unique UNIQUE (tenant_id, name);
+
+DROP INDEX IF EXISTS public.idx_isp_packages_deleted_at;
+DROP INDEX IF EXISTS public.idx_users_deleted_at;
+DROP INDEX IF EXISTS public.idx_mikrotik_routers_deleted_at;
+DROP INDEX IF EXISTS public.idx_customers_deleted_at;
+
+ALTER TABLE public.isp_packages DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE public.users DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE public.mikrotik_routers DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE public.customers DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,19 @@
+-- Soft delete: rows with deleted_at set are in the trash and purged after the retention window.
+ALTER TABLE public.customers ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE public.mikrotik_routers ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE public.users ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE public.isp_packages ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_customers_deleted_at
+    ON public.customers (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_mikrotik_routers_deleted_at
+    ON public.mikrotik_routers (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_users_deleted_at
+    ON public.users (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_isp_packages_deleted_at
+    ON public.isp_packages (deleted_at) WHERE deleted_at IS NOT NULL;
+
+-- Trashed packages must not block reusing their name.
+ALTER TABLE public.isp_packages DROP CONSTRAINT IF EXISTS isp_packages_tenant_name_unique;
+CREATE UNIQUE INDEX IF NOT EXISTS isp_packages_tenant_name_active_unique
+    ON public.isp_packages (tenant_id, name) WHERE deleted_at IS NULL;
@@ -0,0 +1,119 @@
+-- Convert the partitioned time-series tables back to plain tables.
+
+-- audit_logs
+ALTER TABLE public.audit_logs RENAME TO audit_logs_partitioned;
+ALTER TABLE public.audit_logs_partitioned DROP CONSTRAINT audit_logs_pkey;
+DROP INDEX IF EXISTS public.idx_audit_logs_action;
+DROP INDEX IF EXISTS public.idx_audit_logs_created;
+DROP INDEX IF EXISTS public.idx_audit_logs_tenant;
+DROP INDEX IF EXISTS public.idx_audit_logs_user;
+
+CREATE TABLE public.audit_logs (
+    id uuid NOT NULL,
+    user_id uuid,
+    tenant_id uuid,
+    action character varying(255) NOT NULL,
+    resource character varying(255) NOT NULL,
+    resource_id text,
+    details text,
+    ip_address character varying(45),
+    created_at timestamp with time zone NOT NULL
+);
+INSERT INTO public.audit_logs SELECT * FROM public.audit_logs_partitioned;
+DROP TABLE public.audit_logs_partitioned CASCADE;
+
+ALTER TABLE ONLY public.audit_logs ADD CONSTRAINT audit_logs_pkey PRIMARY KEY (id);
+CREATE INDEX idx_audit_logs_action ON public.audit_logs USING btree (action);
+CREATE INDEX idx_audit_logs_created ON public.audit_logs USING btree (created_at DESC);
+CREATE INDEX idx_audit_logs_tenant ON public.audit_logs USING btree (tenant_id);
+CREATE INDEX idx_audit_logs_user ON public.audit_logs USING btree (user_id);
+
+-- mikrotik_router_metrics
+ALTER TABLE public.mikrotik_router_metrics RENAME TO mikrotik_router_metrics_partitioned;
+ALTER TABLE public.mikrotik_router_metrics_partitioned
+    DROP CONSTRAINT mikrotik_router_metrics_pkey;
+DROP INDEX IF EXISTS public.idx_mikrotik_router_metrics_router_ts;
+
+CREATE TABLE public.mikrotik_router_metrics (
+    id text PRIMARY KEY NOT NULL,
+    router_id text NOT NULL REFERENCES public.mikrotik_routers(id) ON DELETE CASCADE,
+    ts timestamp with time zone NOT NULL,
+    cpu_load integer,
+    total_memory_bytes bigint,
+    free_memory_bytes bigint,
+    total_hdd_bytes bigint,
+    free_hdd_bytes bigint,
+    uptime_seconds bigint,
+    rx_bps bigint,
+    tx_bps bigint
+);
+INSERT INTO public.mikrotik_router_metrics
+    SELECT * FROM public.mikrotik_router_metrics_partitioned;
+DROP TABLE public.mikrotik_router_metrics_partitioned CASCADE;
+
+CREATE INDEX idx_mikrotik_router_metrics_router_ts
+    ON public.mikrotik_router_metrics (router_id, ts DESC);
+
+-- mikrotik_interface_metrics
+ALTER TABLE public.mikrotik_interface_metrics RENAME TO mikrotik_interface_metrics_partitioned;
+ALTER TABLE public.mikrotik_interface_metrics_partitioned
+    DROP CONSTRAINT mikrotik_interface_metrics_pkey;
+DROP INDEX IF EXISTS public.idx_mikrotik_interface_metrics_router_iface_ts;
+
+CREATE TABLE public.mikrotik_interface_metrics (
+    id text PRIMARY KEY NOT NULL,
+    router_id text NOT NULL REFERENCES public.mikrotik_routers(id) ON DELETE CASCADE,
+    interface_name text NOT NULL,
+    ts timestamp with time zone NOT NULL,
+    rx_byte bigint,
+    tx_byte bigint,
+    rx_bps bigint,
+    tx_bps bigint,
+    running boolean,
+    disabled boolean,
+    link_downs bigint
+);
+INSERT INTO public.mikrotik_interface_metrics
+    SELECT * FROM public.mikrotik_interface_metrics_partitioned;
+DROP TABLE public.mikrotik_interface_metrics_partitioned CASCADE;
+
+CREATE INDEX idx_mikrotik_interface_metrics_router_iface_ts
+    ON public.mikrotik_interface_metrics (router_id, interface_name, ts DESC);
+
+-- mikrotik_logs
+ALTER TABLE public.mikrotik_logs RENAME TO mikrotik_logs_partitioned;
+ALTER TABLE public.mikrotik_logs_partitioned DROP CONSTRAINT mikrotik_logs_pkey;
+DROP INDEX IF EXISTS public.idx_mikrotik_logs_tenant_logged_at;
+DROP INDEX IF EXISTS public.idx_mikrotik_logs_router_logged_at;
+DROP INDEX IF EXISTS public.idx_mikrotik_logs_router_log_id;
+
+CREATE TABLE public.mikrotik_logs (
+    id text PRIMARY KEY,
+    tenant_id text NOT NULL,
+    router_id text NOT NULL REFERENCES public.mikrotik_routers(id) ON DELETE CASCADE,
+    router_log_id text,
+    logged_at timestamptz NOT NULL,
+    router_time text,
+    topics text,
+    level text,
+    message text NOT NULL,
+    created_at timestamptz NOT NULL,
+    updated_at timestamptz NOT NULL
+);
+-- Without the unique index duplicates may have crept in; keep the latest copy.
+INSERT INTO public.mikrotik_logs
+    SELECT DISTINCT ON (router_id, COALESCE(router_log_id, id)) *
+    FROM public.mikrotik_logs_partitioned
+    ORDER BY router_id, COALESCE(router_log_id, id), updated_at DESC;
+DROP TABLE public.mikrotik_logs_partitioned CASCADE;
+
+CREATE INDEX idx_mikrotik_logs_tenant_logged_at
+    ON public.mikrotik_logs (tenant_id, logged_at DESC);
+CREATE INDEX idx_mikrotik_logs_router_logged_at
+    ON public.mikrotik_logs (router_id, logged_at DESC);
+CREATE UNIQUE INDEX uq_mikrotik_logs_router_log_id
+    ON public.mikrotik_logs (router_id, router_log_id)
+    WHERE router_log_id IS NOT NULL;
+
+DROP FUNCTION IF EXISTS public.ensure_monthly_partitions(text, text, timestamptz, integer);
+DROP FUNCTION IF EXISTS public.ensure_monthly_partition(text, text, date);
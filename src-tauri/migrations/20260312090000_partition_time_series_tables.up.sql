@@ -0,0 +1,219 @@
+-- Monthly range partitioning for the high-volume time-series tables.
+--
+-- Each table becomes a partitioned parent keyed on its timestamp column with
+-- children named <table>_pYYYYMM (UTC months). A <table>_default partition catches
+-- rows outside the pre-created range (e.g. restored backups); creating that month's
+-- partition later moves them out. Retention drops whole partitions instead of
+-- deleting rows.
+
+CREATE OR REPLACE FUNCTION public.ensure_monthly_partition(
+    parent text,
+    key_column text,
+    month_start date
+) RETURNS boolean
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    start_ts timestamptz := date_trunc('month', month_start::timestamp) AT TIME ZONE 'UTC';
+    end_ts timestamptz := (date_trunc('month', month_start::timestamp) + interval '1 month') AT TIME ZONE 'UTC';
+    part text := format('%s_p%s', parent, to_char(month_start, 'YYYYMM'));
+    default_part text := parent || '_default';
+BEGIN
+    IF to_regclass(format('public.%I', part)) IS NOT NULL THEN
+        RETURN false;
+    END IF;
+
+    EXECUTE format('CREATE TABLE public.%I (LIKE public.%I INCLUDING DEFAULTS)', part, parent);
+
+    -- Attaching fails while the default partition still holds rows for the range.
+    IF to_regclass(format('public.%I', default_part)) IS NOT NULL THEN
+        EXECUTE format(
+            'WITH moved AS (DELETE FROM public.%I WHERE %I >= $1 AND %I < $2 RETURNING *) '
+            'INSERT INTO public.%I SELECT * FROM moved',
+            default_part, key_column, key_column, part
+        ) USING start_ts, end_ts;
+    END IF;
+
+    EXECUTE format(
+        'ALTER TABLE public.%I ATTACH PARTITION public.%I FOR VALUES FROM (%L) TO (%L)',
+        parent, part, start_ts, end_ts
+    );
+    RETURN true;
+END;
+$$;
+
+-- Ensure partitions exist from the month of `from_ts` through `months_ahead` months
+-- past the current one. Returns the number of partitions created.
+CREATE OR REPLACE FUNCTION public.ensure_monthly_partitions(
+    parent text,
+    key_column text,
+    from_ts timestamptz,
+    months_ahead integer
+) RETURNS integer
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    m date;
+    created integer := 0;
+BEGIN
+    FOR m IN
+        SELECT generate_series(
+            date_trunc('month', LEAST(from_ts, now()) AT TIME ZONE 'UTC'),
+            date_trunc('month', now() AT TIME ZONE 'UTC') + make_interval(months => months_ahead),
+            interval '1 month'
+        )::date
+    LOOP
+        IF public.ensure_monthly_partition(parent, key_column, m) THEN
+            created := created + 1;
+        END IF;
+    END LOOP;
+    RETURN created;
+END;
+$$;
+
+-- audit_logs ---------------------------------------------------------------
+
+ALTER TABLE public.audit_logs RENAME TO audit_logs_unpartitioned;
+
+CREATE TABLE public.audit_logs (
+    id uuid NOT NULL,
+    user_id uuid,
+    tenant_id uuid,
+    action character varying(255) NOT NULL,
+    resource character varying(255) NOT NULL,
+    resource_id text,
+    details text,
+    ip_address character varying(45),
+    created_at timestamp with time zone NOT NULL
+) PARTITION BY RANGE (created_at);
+
+SELECT public.ensure_monthly_partitions(
+    'audit_logs', 'created_at',
+    COALESCE((SELECT MIN(created_at) FROM public.audit_logs_unpartitioned), now()), 2
+);
+CREATE TABLE public.audit_logs_default PARTITION OF public.audit_logs DEFAULT;
+
+INSERT INTO public.audit_logs SELECT * FROM public.audit_logs_unpartitioned;
+DROP TABLE public.audit_logs_unpartitioned;
+
+ALTER TABLE public.audit_logs ADD CONSTRAINT audit_logs_pkey PRIMARY KEY (id, created_at);
+CREATE INDEX idx_audit_logs_action ON public.audit_logs USING btree (action);
+CREATE INDEX idx_audit_logs_created ON public.audit_logs USING btree (created_at DESC);
+CREATE INDEX idx_audit_logs_tenant ON public.audit_logs USING btree (tenant_id);
+CREATE INDEX idx_audit_logs_user ON public.audit_logs USING btree (user_id);
+
+-- mikrotik_router_metrics --------------------------------------------------
+
+ALTER TABLE public.mikrotik_router_metrics RENAME TO mikrotik_router_metrics_unpartitioned;
+
+CREATE TABLE public.mikrotik_router_metrics (
+    id text NOT NULL,
+    router_id text NOT NULL,
+    ts timestamp with time zone NOT NULL,
+    cpu_load integer,
+    total_memory_bytes bigint,
+    free_memory_bytes bigint,
+    total_hdd_bytes bigint,
+    free_hdd_bytes bigint,
+    uptime_seconds bigint,
+    rx_bps bigint,
+    tx_bps bigint
+) PARTITION BY RANGE (ts);
+
+SELECT public.ensure_monthly_partitions(
+    'mikrotik_router_metrics', 'ts',
+    COALESCE((SELECT MIN(ts) FROM public.mikrotik_router_metrics_unpartitioned), now()), 2
+);
+CREATE TABLE public.mikrotik_router_metrics_default
+    PARTITION OF public.mikrotik_router_metrics DEFAULT;
+
+INSERT INTO public.mikrotik_router_metrics
+    SELECT * FROM public.mikrotik_router_metrics_unpartitioned;
+DROP TABLE public.mikrotik_router_metrics_unpartitioned;
+
+ALTER TABLE public.mikrotik_router_metrics
+    ADD CONSTRAINT mikrotik_router_metrics_pkey PRIMARY KEY (id, ts);
+ALTER TABLE public.mikrotik_router_metrics
+    ADD CONSTRAINT mikrotik_router_metrics_router_id_fkey
+    FOREIGN KEY (router_id) REFERENCES public.mikrotik_routers(id) ON DELETE CASCADE;
+CREATE INDEX idx_mikrotik_router_metrics_router_ts
+    ON public.mikrotik_router_metrics (router_id, ts DESC);
+
+-- mikrotik_interface_metrics -----------------------------------------------
+
+ALTER TABLE public.mikrotik_interface_metrics RENAME TO mikrotik_interface_metrics_unpartitioned;
+
+CREATE TABLE public.mikrotik_interface_metrics (
+    id text NOT NULL,
+    router_id text NOT NULL,
+    interface_name text NOT NULL,
+    ts timestamp with time zone NOT NULL,
+    rx_byte bigint,
+    tx_byte bigint,
+    rx_bps bigint,
+    tx_bps bigint,
+    running boolean,
+    disabled boolean,
+    link_downs bigint
+) PARTITION BY RANGE (ts);
+
+SELECT public.ensure_monthly_partitions(
+    'mikrotik_interface_metrics', 'ts',
+    COALESCE((SELECT MIN(ts) FROM public.mikrotik_interface_metrics_unpartitioned), now()), 2
+);
+CREATE TABLE public.mikrotik_interface_metrics_default
+    PARTITION OF public.mikrotik_interface_metrics DEFAULT;
+
+INSERT INTO public.mikrotik_interface_metrics
+    SELECT * FROM public.mikrotik_interface_metrics_unpartitioned;
+DROP TABLE public.mikrotik_interface_metrics_unpartitioned;
+
+ALTER TABLE public.mikrotik_interface_metrics
+    ADD CONSTRAINT mikrotik_interface_metrics_pkey PRIMARY KEY (id, ts);
+ALTER TABLE public.mikrotik_interface_metrics
+    ADD CONSTRAINT mikrotik_interface_metrics_router_id_fkey
+    FOREIGN KEY (router_id) REFERENCES public.mikrotik_routers(id) ON DELETE CASCADE;
+CREATE INDEX idx_mikrotik_interface_metrics_router_iface_ts
+    ON public.mikrotik_interface_metrics (router_id, interface_name, ts DESC);
+
+-- mikrotik_logs ------------------------------------------------------------
+-- The (router_id, router_log_id) unique index cannot survive partitioning (unique
+-- keys must include logged_at), so log sync now updates-then-inserts instead of
+-- relying on ON CONFLICT.
+
+ALTER TABLE public.mikrotik_logs RENAME TO mikrotik_logs_unpartitioned;
+
+CREATE TABLE public.mikrotik_logs (
+    id text NOT NULL,
+    tenant_id text NOT NULL,
+    router_id text NOT NULL,
+    router_log_id text,
+    logged_at timestamptz NOT NULL,
+    router_time text,
+    topics text,
+    level text,
+    message text NOT NULL,
+    created_at timestamptz NOT NULL,
+    updated_at timestamptz NOT NULL
+) PARTITION BY RANGE (logged_at);
+
+SELECT public.ensure_monthly_partitions(
+    'mikrotik_logs', 'logged_at',
+    COALESCE((SELECT MIN(logged_at) FROM public.mikrotik_logs_unpartitioned), now()), 2
+);
+CREATE TABLE public.mikrotik_logs_default PARTITION OF public.mikrotik_logs DEFAULT;
+
+INSERT INTO public.mikrotik_logs SELECT * FROM public.mikrotik_logs_unpartitioned;
+DROP TABLE public.mikrotik_logs_unpartitioned;
+
+ALTER TABLE public.mikrotik_logs ADD CONSTRAINT mikrotik_logs_pkey PRIMARY KEY (id, logged_at);
+ALTER TABLE public.mikrotik_logs
+    ADD CONSTRAINT mikrotik_logs_router_id_fkey
+    FOREIGN KEY (router_id) REFERENCES public.mikrotik_routers(id) ON DELETE CASCADE;
+CREATE INDEX idx_mikrotik_logs_tenant_logged_at
+    ON public.mikrotik_logs (tenant_id, logged_at DESC);
+CREATE INDEX idx_mikrotik_logs_router_logged_at
+    ON public.mikrotik_logs (router_id, logged_at DESC);
+CREATE INDEX idx_mikrotik_logs_router_log_id
+    ON public.mikrotik_logs (router_id, router_log_id)
+    WHERE router_log_id IS NOT NULL;
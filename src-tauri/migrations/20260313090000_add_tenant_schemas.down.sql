@@ -0,0 +1,6 @@
+-- Tenant schemas themselves are left in place; drop them by hand after moving
+-- their data back to public.
+DROP FUNCTION IF EXISTS public.sync_tenant_schemas();
+DROP FUNCTION IF EXISTS public.provision_tenant_schema(text, text);
+DROP FUNCTION IF EXISTS public.tenant_isolated_tables();
+DROP TABLE IF EXISTS public.tenant_schemas;
@@ -0,0 +1,159 @@
+-- Optional per-tenant schema isolation (DB_TENANT_ISOLATION=schema).
+--
+-- An isolated tenant keeps its ISP operational data (customers, packages,
+-- routers, PPPoE, network mapping, work orders) in its own schema. Shared tables
+-- (tenants, users, settings, invoices, audit logs, ...) stay in public. The app
+-- routes a request by putting the tenant schema in front of public on the
+-- search_path, so queries keep using unqualified table names.
+
+CREATE TABLE IF NOT EXISTS public.tenant_schemas (
+    tenant_id text PRIMARY KEY NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    schema_name text NOT NULL UNIQUE,
+    created_at timestamp with time zone NOT NULL DEFAULT now()
+);
+
+-- Tables copied into every tenant schema. Foreign keys between them stay inside
+-- the schema; references to shared tables point at public.
+CREATE OR REPLACE FUNCTION public.tenant_isolated_tables() RETURNS text[]
+LANGUAGE sql
+IMMUTABLE
+AS $$
+    SELECT ARRAY[
+        'customers',
+        'customer_locations',
+        'customer_subscriptions',
+        'customer_users',
+        'customer_service_assignments',
+        'installation_work_orders',
+        'work_order_reschedule_requests',
+        'isp_packages',
+        'isp_package_router_mappings',
+        'mikrotik_routers',
+        'mikrotik_router_metrics',
+        'mikrotik_interface_metrics',
+        'mikrotik_alerts',
+        'mikrotik_incidents',
+        'mikrotik_logs',
+        'mikrotik_ip_pools',
+        'mikrotik_ppp_profiles',
+        'pppoe_profiles',
+        'pppoe_accounts',
+        'network_nodes',
+        'network_links',
+        'service_zones',
+        'zone_node_bindings',
+        'zone_offers'
+    ]::text[]
+$$;
+
+-- Create the schema for a tenant that has no ISP data yet and register it.
+CREATE OR REPLACE FUNCTION public.provision_tenant_schema(p_tenant_id text, p_schema text)
+RETURNS void
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    t text;
+    n bigint;
+    fk record;
+    fks text[] := '{}';
+    stmt text;
+BEGIN
+    -- Constraint definitions below must print unqualified names.
+    PERFORM set_config('search_path', 'public', true);
+
+    IF EXISTS (SELECT 1 FROM public.tenant_schemas WHERE tenant_id = p_tenant_id) THEN
+        RAISE EXCEPTION 'Tenant is already isolated'
+            USING ERRCODE = 'object_not_in_prerequisite_state';
+    END IF;
+
+    FOREACH t IN ARRAY public.tenant_isolated_tables() LOOP
+        IF EXISTS (
+            SELECT 1 FROM information_schema.columns
+            WHERE table_schema = 'public' AND table_name = t AND column_name = 'tenant_id'
+        ) THEN
+            EXECUTE format('SELECT count(*) FROM public.%I WHERE tenant_id = $1', t)
+                INTO n USING p_tenant_id;
+            IF n > 0 THEN
+                RAISE EXCEPTION 'Tenant already has rows in %; only tenants without ISP data can be isolated', t
+                    USING ERRCODE = 'object_not_in_prerequisite_state';
+            END IF;
+        END IF;
+    END LOOP;
+
+    EXECUTE format('CREATE SCHEMA %I', p_schema);
+
+    FOREACH t IN ARRAY public.tenant_isolated_tables() LOOP
+        EXECUTE format(
+            'CREATE TABLE %I.%I (LIKE public.%I INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING INDEXES)',
+            p_schema, t, t
+        );
+        FOR fk IN
+            SELECT conname, pg_get_constraintdef(oid) AS def
+            FROM pg_constraint
+            WHERE conrelid = format('public.%I', t)::regclass AND contype = 'f'
+        LOOP
+            fks := fks || format('ALTER TABLE %I.%I ADD CONSTRAINT %I %s', p_schema, t, fk.conname, fk.def);
+        END LOOP;
+    END LOOP;
+
+    -- Resolve the unqualified references against the tenant schema first.
+    PERFORM set_config('search_path', format('%I, public', p_schema), true);
+    FOREACH stmt IN ARRAY fks LOOP
+        EXECUTE stmt;
+    END LOOP;
+    PERFORM set_config('search_path', 'public', true);
+
+    INSERT INTO public.tenant_schemas (tenant_id, schema_name) VALUES (p_tenant_id, p_schema);
+END;
+$$;
+
+-- Bring tenant schemas up to date with public after migrations: create missing
+-- tables and add missing columns. Returns the number of objects added.
+CREATE OR REPLACE FUNCTION public.sync_tenant_schemas() RETURNS integer
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    s text;
+    t text;
+    col record;
+    changed integer := 0;
+BEGIN
+    FOR s IN SELECT schema_name FROM public.tenant_schemas LOOP
+        FOREACH t IN ARRAY public.tenant_isolated_tables() LOOP
+            IF to_regclass(format('%I.%I', s, t)) IS NULL THEN
+                EXECUTE format(
+                    'CREATE TABLE %I.%I (LIKE public.%I INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING INDEXES)',
+                    s, t, t
+                );
+                changed := changed + 1;
+                CONTINUE;
+            END IF;
+
+            FOR col IN
+                SELECT a.attname,
+                       format_type(a.atttypid, a.atttypmod) AS typ,
+                       pg_get_expr(d.adbin, d.adrelid) AS def,
+                       a.attnotnull
+                FROM pg_attribute a
+                LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+                WHERE a.attrelid = format('public.%I', t)::regclass
+                  AND a.attnum > 0
+                  AND NOT a.attisdropped
+                  AND NOT EXISTS (
+                      SELECT 1 FROM information_schema.columns c
+                      WHERE c.table_schema = s AND c.table_name = t AND c.column_name = a.attname
+                  )
+            LOOP
+                EXECUTE format(
+                    'ALTER TABLE %I.%I ADD COLUMN %I %s%s%s',
+                    s, t, col.attname, col.typ,
+                    CASE WHEN col.def IS NOT NULL THEN ' DEFAULT ' || col.def ELSE '' END,
+                    CASE WHEN col.attnotnull AND col.def IS NOT NULL THEN ' NOT NULL' ELSE '' END
+                );
+                changed := changed + 1;
+            END LOOP;
+        END LOOP;
+    END LOOP;
+    RETURN changed;
+END;
+$$;
@@ -0,0 +1,24 @@
+-- Event outbox: WebSocket/webhook events written in the same transaction as the
+-- change that caused them, then delivered at-least-once by the dispatcher.
+
+CREATE TABLE IF NOT EXISTS event_outbox (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NULL,
+  event_type TEXT NOT NULL,
+  payload TEXT NOT NULL,
+  status TEXT NOT NULL DEFAULT 'pending', -- pending | delivered | failed
+  attempts INTEGER NOT NULL DEFAULT 0,
+  next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  ws_delivered_at TIMESTAMPTZ NULL,
+  last_error TEXT NULL,
+  delivered_at TIMESTAMPTZ NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_outbox_status_next
+  ON event_outbox (status, next_attempt_at);
+
+CREATE INDEX IF NOT EXISTS idx_event_outbox_delivered
+  ON event_outbox (delivered_at)
+  WHERE delivered_at IS NOT NULL;
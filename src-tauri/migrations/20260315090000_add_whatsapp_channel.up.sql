@@ -0,0 +1,38 @@
+-- WhatsApp notification channel: message templates per tenant and a delivery log
+-- that provider status callbacks update (sent -> delivered -> read, or failed).
+
+CREATE TABLE IF NOT EXISTS whatsapp_templates (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  language TEXT NOT NULL DEFAULT 'id',
+  body TEXT NOT NULL,
+  is_active BOOLEAN NOT NULL DEFAULT true,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_whatsapp_templates_tenant_name_lang
+  ON whatsapp_templates (tenant_id, name, language);
+
+CREATE TABLE IF NOT EXISTS whatsapp_messages (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+  event TEXT NOT NULL,
+  template_name TEXT NOT NULL,
+  to_phone TEXT NOT NULL,
+  customer_id TEXT NULL,
+  provider TEXT NOT NULL, -- meta | gateway
+  provider_message_id TEXT NULL,
+  status TEXT NOT NULL DEFAULT 'pending', -- pending | sent | delivered | read | failed
+  error TEXT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_whatsapp_messages_tenant_created
+  ON whatsapp_messages (tenant_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_whatsapp_messages_provider_id
+  ON whatsapp_messages (provider_message_id)
+  WHERE provider_message_id IS NOT NULL;
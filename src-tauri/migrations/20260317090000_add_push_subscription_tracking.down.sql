@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS public.idx_push_subscriptions_user_id;
+
+ALTER TABLE public.push_subscriptions DROP COLUMN IF EXISTS updated_at;
+ALTER TABLE public.push_subscriptions DROP COLUMN IF EXISTS last_success_at;
+ALTER TABLE public.push_subscriptions DROP COLUMN IF EXISTS failure_count;
+ALTER TABLE public.push_subscriptions DROP COLUMN IF EXISTS vapid_public_key;
+ALTER TABLE public.push_subscriptions DROP COLUMN IF EXISTS expiration_time;
@@ -0,0 +1,10 @@
+-- Web Push: remember the key each browser subscribed under, its expiry and delivery health,
+-- so stale or repeatedly failing subscriptions can be dropped.
+ALTER TABLE public.push_subscriptions ADD COLUMN IF NOT EXISTS expiration_time TIMESTAMPTZ;
+ALTER TABLE public.push_subscriptions ADD COLUMN IF NOT EXISTS vapid_public_key TEXT;
+ALTER TABLE public.push_subscriptions ADD COLUMN IF NOT EXISTS failure_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE public.push_subscriptions ADD COLUMN IF NOT EXISTS last_success_at TIMESTAMPTZ;
+ALTER TABLE public.push_subscriptions ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_push_subscriptions_user_id
+    ON public.push_subscriptions (user_id);
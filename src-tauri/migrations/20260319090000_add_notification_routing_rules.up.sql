@@ -0,0 +1,21 @@
+-- Per-tenant alert routing: match (category, minimum severity, business hours)
+-- and send to specific channels/recipients instead of the producer's defaults.
+
+CREATE TABLE IF NOT EXISTS notification_routing_rules (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  priority INTEGER NOT NULL DEFAULT 100,
+  is_enabled BOOLEAN NOT NULL DEFAULT true,
+  category TEXT NULL, -- NULL = any category
+  min_severity TEXT NOT NULL DEFAULT 'info', -- info | warning | critical
+  time_window TEXT NOT NULL DEFAULT 'any', -- any | business_hours | after_hours
+  channels TEXT NOT NULL, -- comma-separated: in_app,email,telegram
+  recipients TEXT NOT NULL DEFAULT 'default', -- comma-separated: default,user:<id>,role:<id>
+  created_by TEXT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_routing_rules_tenant
+  ON notification_routing_rules (tenant_id, priority);
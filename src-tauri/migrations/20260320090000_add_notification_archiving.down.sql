@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS public.idx_notifications_user_created;
+
+ALTER TABLE public.notifications DROP COLUMN IF EXISTS archived_at;
@@ -0,0 +1,6 @@
+-- Notification center: archived rows are hidden from the default list and purged
+-- sooner by retention cleanup. Index backs filtered, newest-first listing per user.
+ALTER TABLE public.notifications ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_created
+    ON public.notifications (user_id, created_at DESC);
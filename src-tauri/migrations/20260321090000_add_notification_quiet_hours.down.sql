@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS notification_deferred_deliveries;
+DROP TABLE IF EXISTS notification_quiet_hours;
@@ -0,0 +1,24 @@
+-- Per-user quiet hours: non-critical email/push is held until the window ends.
+-- `timezone` NULL means the tenant's app_timezone.
+
+CREATE TABLE IF NOT EXISTS notification_quiet_hours (
+  user_id TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+  is_enabled BOOLEAN NOT NULL DEFAULT false,
+  start_time TEXT NOT NULL DEFAULT '22:00', -- HH:MM, local
+  end_time TEXT NOT NULL DEFAULT '07:00', -- HH:MM, local; earlier than start = overnight
+  timezone TEXT NULL,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Channels held back by quiet hours, sent by the deferred delivery worker.
+CREATE TABLE IF NOT EXISTS notification_deferred_deliveries (
+  id TEXT PRIMARY KEY,
+  notification_id TEXT NOT NULL REFERENCES notifications(id) ON DELETE CASCADE,
+  user_id TEXT NOT NULL,
+  channels TEXT NOT NULL, -- comma-separated: email,push
+  deliver_after TIMESTAMPTZ NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deferred_deliveries_due
+  ON notification_deferred_deliveries (deliver_after);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS public.customer_tags;
+DROP TABLE IF EXISTS announcement_recipients;
+ALTER TABLE announcements DROP COLUMN IF EXISTS audience_targets;
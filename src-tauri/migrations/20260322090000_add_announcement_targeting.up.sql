@@ -0,0 +1,28 @@
+-- Segment audiences for announcements.
+-- audience = 'segment' targets the union of the roles, packages, customer tags
+-- and routers in `audience_targets`; recipients are resolved when the
+-- announcement is sent and recorded in announcement_recipients.
+
+ALTER TABLE announcements ADD COLUMN IF NOT EXISTS audience_targets jsonb NULL;
+
+CREATE TABLE IF NOT EXISTS announcement_recipients (
+  announcement_id text NOT NULL REFERENCES announcements(id) ON DELETE CASCADE,
+  user_id text NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  created_at timestamp with time zone NOT NULL DEFAULT now(),
+  PRIMARY KEY (announcement_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_announcement_recipients_user
+  ON announcement_recipients (user_id, announcement_id);
+
+-- Free-form labels on customers (e.g. "vip", "rt-05"), used for targeting.
+CREATE TABLE IF NOT EXISTS public.customer_tags (
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    customer_id text NOT NULL REFERENCES public.customers(id) ON DELETE CASCADE,
+    tag text NOT NULL,
+    created_at timestamp with time zone NOT NULL DEFAULT now(),
+    CONSTRAINT customer_tags_pkey PRIMARY KEY (customer_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_customer_tags_tenant_tag
+    ON public.customer_tags (tenant_id, tag);
@@ -0,0 +1,27 @@
+-- Per-notification, per-channel delivery status for the admin delivery report.
+-- No FK to notifications: the record must survive the user deleting the
+-- notification, so title/category are copied in. Purged with notification retention.
+
+CREATE TABLE IF NOT EXISTS notification_deliveries (
+  id TEXT PRIMARY KEY,
+  notification_id TEXT NOT NULL,
+  tenant_id TEXT NULL,
+  user_id TEXT NOT NULL,
+  category TEXT NOT NULL,
+  notification_type TEXT NOT NULL,
+  title TEXT NOT NULL,
+  channel TEXT NOT NULL, -- in_app|email|push
+  status TEXT NOT NULL, -- queued|sent|delivered|failed|read
+  error TEXT NULL,
+  email_outbox_id TEXT NULL, -- set while the email waits in email_outbox
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  UNIQUE (notification_id, channel)
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_tenant_created
+  ON notification_deliveries (tenant_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_outbox
+  ON notification_deliveries (email_outbox_id)
+  WHERE email_outbox_id IS NOT NULL;
@@ -0,0 +1,14 @@
+-- Per-tenant DKIM signing keys for outbound SMTP email. The private key is
+-- encrypted with the app secret; the public key is what goes into DNS.
+
+CREATE TABLE IF NOT EXISTS email_dkim_keys (
+  tenant_id TEXT PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+  domain TEXT NOT NULL,
+  selector TEXT NOT NULL,
+  private_key_enc TEXT NOT NULL,
+  public_key TEXT NOT NULL, -- base64 SubjectPublicKeyInfo, the DNS `p=` value
+  is_enabled BOOLEAN NOT NULL DEFAULT true,
+  created_by TEXT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,23 @@
+-- Addresses that bounced or complained, per tenant (NULL = mail sent with the
+-- global email settings). Soft bounces are counted and only suppress once they
+-- repeat; hard bounces, complaints and manual entries suppress right away.
+
+CREATE TABLE IF NOT EXISTS email_suppressions (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+  email TEXT NOT NULL, -- lower-cased
+  reason TEXT NOT NULL, -- hard_bounce | soft_bounce | complaint | manual
+  source TEXT NOT NULL, -- smtp | ses | sendgrid | mailgun | manual
+  detail TEXT NULL,
+  bounce_count INTEGER NOT NULL DEFAULT 1,
+  is_suppressed BOOLEAN NOT NULL DEFAULT true,
+  last_event_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_email_suppressions_tenant_email
+  ON email_suppressions ((COALESCE(tenant_id, '')), email);
+
+CREATE INDEX IF NOT EXISTS idx_email_suppressions_tenant_event
+  ON email_suppressions (tenant_id, last_event_at DESC);
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_email_outbox_tenant_sent;
+DROP INDEX IF EXISTS idx_email_outbox_queue;
+
+ALTER TABLE IF EXISTS email_outbox
+  DROP COLUMN IF EXISTS priority;
@@ -0,0 +1,13 @@
+-- Priority lanes for the email outbox: 0 = priority (OTP, password reset),
+-- 5 = normal, 9 = bulk. Lower values are sent first and are never held back
+-- by the per-tenant hourly cap.
+
+ALTER TABLE IF EXISTS email_outbox
+  ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 5;
+
+CREATE INDEX IF NOT EXISTS idx_email_outbox_queue
+  ON email_outbox (status, priority, scheduled_at);
+
+-- Hourly cap accounting: mail sent per tenant in the last hour.
+CREATE INDEX IF NOT EXISTS idx_email_outbox_tenant_sent
+  ON email_outbox (tenant_id, sent_at);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS public.support_ticket_inbound_emails;
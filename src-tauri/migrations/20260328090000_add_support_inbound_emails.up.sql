@@ -0,0 +1,19 @@
+-- Inbound email-to-ticket gateway: every email turned into a ticket message is
+-- recorded by its Message-ID so replies can be threaded and provider retries
+-- are not posted twice.
+
+CREATE TABLE IF NOT EXISTS public.support_ticket_inbound_emails (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    ticket_id text NOT NULL REFERENCES public.support_tickets(id) ON DELETE CASCADE,
+    ticket_message_id text REFERENCES public.support_ticket_messages(id) ON DELETE CASCADE,
+    email_message_id text NOT NULL,
+    from_email text NOT NULL,
+    created_at timestamp with time zone NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_support_ticket_inbound_emails_message
+    ON public.support_ticket_inbound_emails (tenant_id, email_message_id);
+
+CREATE INDEX IF NOT EXISTS idx_support_ticket_inbound_emails_ticket
+    ON public.support_ticket_inbound_emails (ticket_id, created_at DESC);
@@ -0,0 +1,9 @@
+DROP INDEX IF EXISTS idx_support_tickets_tenant_queue;
+DROP INDEX IF EXISTS idx_support_tickets_tenant_assigned;
+
+ALTER TABLE public.support_tickets
+    DROP COLUMN IF EXISTS queue_id,
+    DROP COLUMN IF EXISTS category;
+
+DROP TABLE IF EXISTS public.support_queue_members;
+DROP TABLE IF EXISTS public.support_queues;
@@ -0,0 +1,42 @@
+-- Ticket ownership: queues (teams) with category routing and round-robin
+-- auto-assignment among queue members.
+
+CREATE TABLE IF NOT EXISTS public.support_queues (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    name text NOT NULL,
+    description text,
+    -- Ticket categories routed to this queue (lowercase).
+    categories text[] NOT NULL DEFAULT '{}',
+    -- Catch-all for tickets whose category matches no queue. One per tenant.
+    is_default boolean NOT NULL DEFAULT false,
+    auto_assign boolean NOT NULL DEFAULT true,
+    -- When no member is online: false = assign to any member, true = leave unassigned.
+    online_only boolean NOT NULL DEFAULT false,
+    last_assigned_to text REFERENCES public.users(id) ON DELETE SET NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_support_queues_tenant_name
+    ON public.support_queues (tenant_id, lower(name));
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_support_queues_tenant_default
+    ON public.support_queues (tenant_id) WHERE is_default;
+
+CREATE TABLE IF NOT EXISTS public.support_queue_members (
+    queue_id text NOT NULL REFERENCES public.support_queues(id) ON DELETE CASCADE,
+    user_id text NOT NULL REFERENCES public.users(id) ON DELETE CASCADE,
+    created_at timestamp with time zone NOT NULL,
+    PRIMARY KEY (queue_id, user_id)
+);
+
+ALTER TABLE public.support_tickets
+    ADD COLUMN IF NOT EXISTS category text,
+    ADD COLUMN IF NOT EXISTS queue_id text REFERENCES public.support_queues(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_tenant_assigned
+    ON public.support_tickets (tenant_id, assigned_to, status);
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_tenant_queue
+    ON public.support_tickets (tenant_id, queue_id, status);
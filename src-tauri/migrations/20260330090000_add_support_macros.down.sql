@@ -0,0 +1 @@
+DROP TABLE IF EXISTS public.support_macros;
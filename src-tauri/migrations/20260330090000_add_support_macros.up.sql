@@ -0,0 +1,24 @@
+-- Canned replies and macros for support agents. A macro with only a body is a
+-- canned response; set_status / set_priority / assign_to turn it into a
+-- one-click "reply + update" action.
+
+CREATE TABLE IF NOT EXISTS public.support_macros (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    name text NOT NULL,
+    -- Free-form grouping for the picker (e.g. billing, connection).
+    category text,
+    -- Reply text with {{variable}} placeholders; empty = no reply.
+    body text NOT NULL DEFAULT '',
+    is_internal boolean NOT NULL DEFAULT false,
+    set_status text CHECK (set_status IN ('open', 'pending', 'closed')),
+    set_priority text CHECK (set_priority IN ('low', 'normal', 'high', 'urgent')),
+    -- User id, or 'me' for the agent applying the macro.
+    assign_to text,
+    created_by text REFERENCES public.users(id) ON DELETE SET NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_support_macros_tenant_name
+    ON public.support_macros (tenant_id, lower(name));
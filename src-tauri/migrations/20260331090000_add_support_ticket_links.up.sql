@@ -0,0 +1,19 @@
+-- Links from support tickets to other records (mikrotik incidents, installation
+-- work orders, invoices). entity_id has no foreign key because the target table
+-- depends on entity_type; links to deleted records are shown without a label.
+
+CREATE TABLE IF NOT EXISTS public.support_ticket_links (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    ticket_id text NOT NULL REFERENCES public.support_tickets(id) ON DELETE CASCADE,
+    entity_type text NOT NULL CHECK (entity_type IN ('incident', 'work_order', 'invoice')),
+    entity_id text NOT NULL,
+    note text,
+    created_by text REFERENCES public.users(id) ON DELETE SET NULL,
+    created_at timestamp with time zone NOT NULL,
+    UNIQUE (ticket_id, entity_type, entity_id)
+);
+
+-- Back-references: tickets linked to a given record.
+CREATE INDEX IF NOT EXISTS idx_support_ticket_links_entity
+    ON public.support_ticket_links (tenant_id, entity_type, entity_id);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS public.support_ticket_escalations;
+DROP TABLE IF EXISTS public.support_escalation_rules;
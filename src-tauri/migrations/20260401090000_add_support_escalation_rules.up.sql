@@ -0,0 +1,29 @@
+-- Escalation rules: act on tickets that have gone quiet for too long.
+
+CREATE TABLE IF NOT EXISTS public.support_escalation_rules (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    name text NOT NULL,
+    is_active boolean NOT NULL DEFAULT true,
+    -- Minutes since the last public message (or ticket creation).
+    idle_minutes integer NOT NULL CHECK (idle_minutes > 0),
+    -- Ticket statuses the rule watches (open, pending).
+    statuses text[] NOT NULL DEFAULT '{open}',
+    -- Only tickets in this queue; NULL = any queue.
+    queue_id text REFERENCES public.support_queues(id) ON DELETE CASCADE,
+    bump_priority boolean NOT NULL DEFAULT false,
+    reassign_to text REFERENCES public.users(id) ON DELETE SET NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_support_escalation_rules_tenant
+    ON public.support_escalation_rules (tenant_id) WHERE is_active;
+
+-- A rule fires once per idle period: a new public message starts a new period.
+CREATE TABLE IF NOT EXISTS public.support_ticket_escalations (
+    ticket_id text NOT NULL REFERENCES public.support_tickets(id) ON DELETE CASCADE,
+    rule_id text NOT NULL REFERENCES public.support_escalation_rules(id) ON DELETE CASCADE,
+    escalated_at timestamp with time zone NOT NULL,
+    PRIMARY KEY (ticket_id, rule_id)
+);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS public.idx_installation_work_orders_assignee_schedule;
+
+ALTER TABLE public.installation_work_orders
+    DROP COLUMN IF EXISTS scheduled_end_at;
@@ -0,0 +1,9 @@
+-- Explicit end of a technician time slot; NULL falls back to the tenant default
+-- slot length (work_order_slot_minutes) when detecting overlaps.
+
+ALTER TABLE public.installation_work_orders
+    ADD COLUMN IF NOT EXISTS scheduled_end_at timestamp with time zone NULL;
+
+CREATE INDEX IF NOT EXISTS idx_installation_work_orders_assignee_schedule
+    ON public.installation_work_orders (tenant_id, assigned_to, scheduled_at)
+    WHERE scheduled_at IS NOT NULL;
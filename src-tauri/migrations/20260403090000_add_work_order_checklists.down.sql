@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS public.installation_work_order_checklist_items;
+DROP TABLE IF EXISTS public.work_order_template_items;
+DROP TABLE IF EXISTS public.work_order_templates;
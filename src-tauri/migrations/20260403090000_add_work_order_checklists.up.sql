@@ -0,0 +1,54 @@
+-- Checklist templates for installation work orders. The default template is
+-- copied onto a work order the first time its checklist is opened; the copy
+-- is what the technician fills in, so later template edits don't change
+-- orders already in progress.
+
+CREATE TABLE IF NOT EXISTS public.work_order_templates (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    name text NOT NULL,
+    description text,
+    is_default boolean NOT NULL DEFAULT false,
+    is_active boolean NOT NULL DEFAULT true,
+    created_by text REFERENCES public.users(id) ON DELETE SET NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_work_order_templates_tenant_name
+    ON public.work_order_templates (tenant_id, lower(name));
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_work_order_templates_tenant_default
+    ON public.work_order_templates (tenant_id) WHERE is_default;
+
+CREATE TABLE IF NOT EXISTS public.work_order_template_items (
+    id text PRIMARY KEY NOT NULL,
+    template_id text NOT NULL REFERENCES public.work_order_templates(id) ON DELETE CASCADE,
+    position integer NOT NULL,
+    label text NOT NULL,
+    -- check: tick box; photo: image upload; text: free-form value.
+    kind text NOT NULL CHECK (kind IN ('check', 'photo', 'text')),
+    is_required boolean NOT NULL DEFAULT true
+);
+
+CREATE INDEX IF NOT EXISTS idx_work_order_template_items_template
+    ON public.work_order_template_items (template_id, position);
+
+CREATE TABLE IF NOT EXISTS public.installation_work_order_checklist_items (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    work_order_id text NOT NULL REFERENCES public.installation_work_orders(id) ON DELETE CASCADE,
+    template_id text REFERENCES public.work_order_templates(id) ON DELETE SET NULL,
+    position integer NOT NULL,
+    label text NOT NULL,
+    kind text NOT NULL CHECK (kind IN ('check', 'photo', 'text')),
+    is_required boolean NOT NULL DEFAULT true,
+    is_done boolean NOT NULL DEFAULT false,
+    value text,
+    file_id text REFERENCES public.file_records(id) ON DELETE SET NULL,
+    completed_by text REFERENCES public.users(id) ON DELETE SET NULL,
+    completed_at timestamp with time zone
+);
+
+CREATE INDEX IF NOT EXISTS idx_installation_work_order_checklist_items_work_order
+    ON public.installation_work_order_checklist_items (tenant_id, work_order_id, position);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS public.installation_work_order_visits;
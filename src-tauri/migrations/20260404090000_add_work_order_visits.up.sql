@@ -0,0 +1,28 @@
+-- Technician on-site visits: GPS check-in/check-out against a work order.
+-- Distances are measured from the work order's customer location at the time
+-- of the check-in/out and stay NULL when the location has no coordinates.
+
+CREATE TABLE IF NOT EXISTS public.installation_work_order_visits (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    work_order_id text NOT NULL REFERENCES public.installation_work_orders(id) ON DELETE CASCADE,
+    technician_id text REFERENCES public.users(id) ON DELETE SET NULL,
+    check_in_at timestamp with time zone NOT NULL,
+    check_in_latitude double precision NOT NULL CHECK (check_in_latitude BETWEEN -90 AND 90),
+    check_in_longitude double precision NOT NULL CHECK (check_in_longitude BETWEEN -180 AND 180),
+    check_in_accuracy_m double precision,
+    check_in_distance_m double precision,
+    check_out_at timestamp with time zone,
+    check_out_latitude double precision CHECK (check_out_latitude BETWEEN -90 AND 90),
+    check_out_longitude double precision CHECK (check_out_longitude BETWEEN -180 AND 180),
+    check_out_accuracy_m double precision,
+    check_out_distance_m double precision,
+    notes text
+);
+
+CREATE INDEX IF NOT EXISTS idx_installation_work_order_visits_work_order
+    ON public.installation_work_order_visits (tenant_id, work_order_id, check_in_at DESC);
+
+-- A work order has at most one open visit at a time.
+CREATE UNIQUE INDEX IF NOT EXISTS uq_installation_work_order_visits_open
+    ON public.installation_work_order_visits (work_order_id) WHERE check_out_at IS NULL;
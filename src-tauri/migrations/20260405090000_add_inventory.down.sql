@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS public.inventory_movements;
+DROP TABLE IF EXISTS public.inventory_stock;
+DROP TABLE IF EXISTS public.inventory_locations;
+DROP TABLE IF EXISTS public.inventory_items;
@@ -0,0 +1,75 @@
+-- Materials inventory: items, stock per location (warehouse or technician van)
+-- and an append-only movement ledger. Work orders consume stock through
+-- movements of kind 'consume'.
+
+CREATE TABLE IF NOT EXISTS public.inventory_items (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    sku text NOT NULL,
+    name text NOT NULL,
+    -- Unit of measure shown next to quantities (pcs, m, roll...).
+    unit text NOT NULL DEFAULT 'pcs',
+    -- Alert when total stock across all locations drops below this; NULL = never.
+    low_stock_threshold numeric(14,3) CHECK (low_stock_threshold IS NULL OR low_stock_threshold >= 0),
+    is_active boolean NOT NULL DEFAULT true,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_inventory_items_tenant_sku
+    ON public.inventory_items (tenant_id, lower(sku));
+
+CREATE TABLE IF NOT EXISTS public.inventory_locations (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    name text NOT NULL,
+    kind text NOT NULL CHECK (kind IN ('warehouse', 'technician')),
+    -- Set for kind = 'technician': the stock the technician carries.
+    technician_id text REFERENCES public.users(id) ON DELETE SET NULL,
+    is_active boolean NOT NULL DEFAULT true,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CHECK (kind = 'technician' OR technician_id IS NULL)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_inventory_locations_tenant_name
+    ON public.inventory_locations (tenant_id, lower(name));
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_inventory_locations_technician
+    ON public.inventory_locations (tenant_id, technician_id) WHERE technician_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS public.inventory_stock (
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    item_id text NOT NULL REFERENCES public.inventory_items(id) ON DELETE CASCADE,
+    location_id text NOT NULL REFERENCES public.inventory_locations(id) ON DELETE CASCADE,
+    quantity numeric(14,3) NOT NULL DEFAULT 0 CHECK (quantity >= 0),
+    updated_at timestamp with time zone NOT NULL,
+    PRIMARY KEY (item_id, location_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_stock_location
+    ON public.inventory_stock (tenant_id, location_id);
+
+CREATE TABLE IF NOT EXISTS public.inventory_movements (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    item_id text NOT NULL REFERENCES public.inventory_items(id) ON DELETE CASCADE,
+    -- receive: -> to; transfer: from -> to; adjust: either side; consume: from -> work order.
+    kind text NOT NULL CHECK (kind IN ('receive', 'transfer', 'adjust', 'consume')),
+    from_location_id text REFERENCES public.inventory_locations(id) ON DELETE SET NULL,
+    to_location_id text REFERENCES public.inventory_locations(id) ON DELETE SET NULL,
+    quantity numeric(14,3) NOT NULL CHECK (quantity > 0),
+    work_order_id text REFERENCES public.installation_work_orders(id) ON DELETE SET NULL,
+    note text,
+    created_by text REFERENCES public.users(id) ON DELETE SET NULL,
+    created_at timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_movements_tenant_created
+    ON public.inventory_movements (tenant_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_movements_item
+    ON public.inventory_movements (item_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_movements_work_order
+    ON public.inventory_movements (work_order_id) WHERE work_order_id IS NOT NULL;
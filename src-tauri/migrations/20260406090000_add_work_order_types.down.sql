@@ -0,0 +1,11 @@
+DROP INDEX IF EXISTS public.idx_installation_work_orders_subscription_type;
+
+ALTER TABLE public.installation_work_orders
+    DROP CONSTRAINT IF EXISTS installation_work_orders_work_type_check;
+
+-- Without a type every row would read as an installation.
+DELETE FROM public.installation_work_orders WHERE work_type <> 'installation';
+
+ALTER TABLE public.installation_work_orders
+    DROP COLUMN IF EXISTS details,
+    DROP COLUMN IF EXISTS work_type;
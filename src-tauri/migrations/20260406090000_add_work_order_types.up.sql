@@ -0,0 +1,18 @@
+-- Work orders beyond installations: repair, relocation and dismantle jobs share
+-- the installation_work_orders table. Type-specific fields (problem description,
+-- relocation target, dismantle reason) live in `details`. Repairs may also be
+-- put on hold ('on_hold') while waiting for parts or access.
+
+ALTER TABLE public.installation_work_orders
+    ADD COLUMN IF NOT EXISTS work_type text NOT NULL DEFAULT 'installation',
+    ADD COLUMN IF NOT EXISTS details jsonb NOT NULL DEFAULT '{}'::jsonb;
+
+ALTER TABLE public.installation_work_orders
+    DROP CONSTRAINT IF EXISTS installation_work_orders_work_type_check;
+
+ALTER TABLE public.installation_work_orders
+    ADD CONSTRAINT installation_work_orders_work_type_check
+    CHECK (work_type IN ('installation', 'repair', 'relocation', 'dismantle'));
+
+CREATE INDEX IF NOT EXISTS idx_installation_work_orders_subscription_type
+    ON public.installation_work_orders (tenant_id, subscription_id, work_type, created_at DESC);
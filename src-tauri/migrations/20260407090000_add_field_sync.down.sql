@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS public.idx_installation_work_orders_tenant_updated;
+DROP TABLE IF EXISTS public.field_sync_mutations;
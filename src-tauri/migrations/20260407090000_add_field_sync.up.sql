@@ -0,0 +1,26 @@
+-- Offline field sync: every mutation a technician device pushes is recorded
+-- under the id the device generated, so a push retried after a dropped
+-- connection is answered from here instead of being applied twice.
+
+CREATE TABLE IF NOT EXISTS public.field_sync_mutations (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    user_id text NOT NULL REFERENCES public.users(id) ON DELETE CASCADE,
+    client_mutation_id text NOT NULL,
+    kind text NOT NULL,
+    work_order_id text,
+    status text NOT NULL CHECK (status IN ('applied', 'conflict', 'rejected')),
+    error text,
+    data jsonb,
+    created_at timestamp with time zone NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_field_sync_mutations_client
+    ON public.field_sync_mutations (tenant_id, user_id, client_mutation_id);
+
+CREATE INDEX IF NOT EXISTS idx_field_sync_mutations_created
+    ON public.field_sync_mutations (created_at);
+
+-- Pulls walk work orders by (updated_at, id).
+CREATE INDEX IF NOT EXISTS idx_installation_work_orders_tenant_updated
+    ON public.installation_work_orders (tenant_id, updated_at, id);
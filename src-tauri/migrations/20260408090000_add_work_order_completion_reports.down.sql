@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS public.idx_work_order_completion_reports_customer;
+DROP INDEX IF EXISTS public.idx_work_order_completion_reports_work_order;
+DROP TABLE IF EXISTS public.work_order_completion_reports;
@@ -0,0 +1,29 @@
+-- Completion reports (berita acara) signed by the customer when a work order
+-- is finished. The generated PDF is kept in file storage; a work order that is
+-- reopened and completed again gets a new report, the old ones stay on record.
+
+CREATE TABLE IF NOT EXISTS public.work_order_completion_reports (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    work_order_id text NOT NULL REFERENCES public.installation_work_orders(id) ON DELETE CASCADE,
+    customer_id text NOT NULL REFERENCES public.customers(id) ON DELETE CASCADE,
+    report_number text NOT NULL,
+    signer_name text NOT NULL,
+    -- strokes: pen strokes from a signature pad; image: uploaded JPEG.
+    signature_kind text NOT NULL CHECK (signature_kind IN ('strokes', 'image')),
+    signature_strokes jsonb,
+    signature_file_id text REFERENCES public.file_records(id) ON DELETE SET NULL,
+    file_id text REFERENCES public.file_records(id) ON DELETE SET NULL,
+    signed_at timestamp with time zone NOT NULL,
+    emailed_to text,
+    emailed_at timestamp with time zone,
+    email_error text,
+    created_by text REFERENCES public.users(id) ON DELETE SET NULL,
+    created_at timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_work_order_completion_reports_work_order
+    ON public.work_order_completion_reports (tenant_id, work_order_id, created_at);
+
+CREATE INDEX IF NOT EXISTS idx_work_order_completion_reports_customer
+    ON public.work_order_completion_reports (tenant_id, customer_id, created_at);
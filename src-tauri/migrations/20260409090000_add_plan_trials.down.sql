@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS public.idx_tenant_subscriptions_trial_ends;
+ALTER TABLE public.tenant_subscriptions DROP COLUMN IF EXISTS trial_notice_days;
+ALTER TABLE public.plans DROP CONSTRAINT IF EXISTS plans_trial_expiry_action_check;
+ALTER TABLE public.plans DROP CONSTRAINT IF EXISTS plans_trial_days_check;
+ALTER TABLE public.plans DROP COLUMN IF EXISTS trial_expiry_action;
+ALTER TABLE public.plans DROP COLUMN IF EXISTS trial_days;
@@ -0,0 +1,25 @@
+-- Free trials per plan. A tenant put on a plan with trial_days > 0 runs in
+-- status 'trial' until trial_ends_at; afterwards it is either moved to the free
+-- plan ('downgrade') or kept on the plan with every feature switched off
+-- ('lock') until a paid plan is assigned. trial_notice_days remembers the last
+-- pre-expiry reminder sent so each one goes out only once.
+
+ALTER TABLE public.plans
+    ADD COLUMN IF NOT EXISTS trial_days integer DEFAULT 0 NOT NULL,
+    ADD COLUMN IF NOT EXISTS trial_expiry_action text DEFAULT 'downgrade'::text NOT NULL;
+
+ALTER TABLE public.plans
+    DROP CONSTRAINT IF EXISTS plans_trial_days_check,
+    ADD CONSTRAINT plans_trial_days_check CHECK (trial_days >= 0 AND trial_days <= 365);
+
+ALTER TABLE public.plans
+    DROP CONSTRAINT IF EXISTS plans_trial_expiry_action_check,
+    ADD CONSTRAINT plans_trial_expiry_action_check
+        CHECK (trial_expiry_action IN ('downgrade', 'lock'));
+
+ALTER TABLE public.tenant_subscriptions
+    ADD COLUMN IF NOT EXISTS trial_notice_days integer;
+
+CREATE INDEX IF NOT EXISTS idx_tenant_subscriptions_trial_ends
+    ON public.tenant_subscriptions (trial_ends_at)
+    WHERE status = 'trial';
@@ -0,0 +1,14 @@
+-- Metered usage that cannot be counted from existing rows (emails sent per
+-- month). One row per tenant, metric and period ('YYYY-MM' for monthly
+-- meters); services bump `value` as they go.
+
+CREATE TABLE IF NOT EXISTS public.tenant_usage_counters (
+    tenant_id text NOT NULL,
+    metric text NOT NULL,
+    period text NOT NULL,
+    value bigint DEFAULT 0 NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT tenant_usage_counters_pkey PRIMARY KEY (tenant_id, metric, period),
+    CONSTRAINT tenant_usage_counters_tenant_id_fkey FOREIGN KEY (tenant_id)
+        REFERENCES public.tenants(id) ON DELETE CASCADE
+);
@@ -0,0 +1,31 @@
+-- Operational feature flags, kept apart from billing plan features. A flag is
+-- on for a tenant when it is enabled (the kill switch) and either the tenant
+-- has an override or falls inside the rollout percentage.
+
+CREATE TABLE IF NOT EXISTS public.feature_flags (
+    key text NOT NULL,
+    description text,
+    enabled boolean DEFAULT true NOT NULL,
+    rollout_percentage integer DEFAULT 100 NOT NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT feature_flags_pkey PRIMARY KEY (key),
+    CONSTRAINT feature_flags_rollout_percentage_check
+        CHECK (rollout_percentage BETWEEN 0 AND 100)
+);
+
+CREATE TABLE IF NOT EXISTS public.feature_flag_overrides (
+    flag_key text NOT NULL,
+    tenant_id text NOT NULL,
+    enabled boolean NOT NULL,
+    created_at timestamp with time zone NOT NULL,
+    CONSTRAINT feature_flag_overrides_pkey PRIMARY KEY (flag_key, tenant_id),
+    CONSTRAINT feature_flag_overrides_flag_key_fkey FOREIGN KEY (flag_key)
+        REFERENCES public.feature_flags(key) ON DELETE CASCADE,
+    CONSTRAINT feature_flag_overrides_tenant_id_fkey FOREIGN KEY (tenant_id)
+        REFERENCES public.tenants(id) ON DELETE CASCADE
+);
+
+INSERT INTO public.feature_flags (key, description, enabled, rollout_percentage, created_at, updated_at)
+VALUES ('plan_self_serve_upgrade', 'Tenants can check out plan upgrades themselves', true, 100, now(), now())
+ON CONFLICT (key) DO NOTHING;
@@ -0,0 +1,2 @@
+ALTER TABLE public.users DROP COLUMN IF EXISTS locale;
+ALTER TABLE public.announcements DROP COLUMN IF EXISTS translations;
@@ -0,0 +1,9 @@
+-- Per-locale variants of announcements.
+-- `translations` maps a locale ("id", "en-US") to {"title", "body"}; the base
+-- title/body are the default language and are served when no variant matches
+-- the reader's locale. `users.locale` is the reader's preference, falling back
+-- to the `default_locale` setting when unset.
+
+ALTER TABLE public.announcements ADD COLUMN IF NOT EXISTS translations jsonb NULL;
+
+ALTER TABLE public.users ADD COLUMN IF NOT EXISTS locale text NULL;
@@ -0,0 +1,11 @@
+DELETE FROM public.announcement_dismissals WHERE occurrence <> 0;
+ALTER TABLE public.announcement_dismissals DROP CONSTRAINT IF EXISTS announcement_dismissals_occurrence_key;
+ALTER TABLE public.announcement_dismissals
+    ADD CONSTRAINT announcement_dismissals_user_id_announcement_id_key UNIQUE (user_id, announcement_id);
+ALTER TABLE public.announcement_dismissals DROP COLUMN IF EXISTS occurrence;
+
+DROP INDEX IF EXISTS public.idx_announcements_next_occurrence;
+ALTER TABLE public.announcements DROP COLUMN IF EXISTS occurrence_ends_at;
+ALTER TABLE public.announcements DROP COLUMN IF EXISTS next_occurrence_at;
+ALTER TABLE public.announcements DROP COLUMN IF EXISTS occurrence;
+ALTER TABLE public.announcements DROP COLUMN IF EXISTS recurrence;
@@ -0,0 +1,19 @@
+-- Recurring announcements.
+-- `recurrence` holds the rule of a series; the scheduler publishes it each
+-- time `next_occurrence_at` comes due and bumps `occurrence`. Dismissals are
+-- recorded per occurrence so the next one shows up again.
+
+ALTER TABLE public.announcements ADD COLUMN IF NOT EXISTS recurrence jsonb NULL;
+ALTER TABLE public.announcements ADD COLUMN IF NOT EXISTS occurrence integer NOT NULL DEFAULT 0;
+ALTER TABLE public.announcements ADD COLUMN IF NOT EXISTS next_occurrence_at timestamp with time zone NULL;
+ALTER TABLE public.announcements ADD COLUMN IF NOT EXISTS occurrence_ends_at timestamp with time zone NULL;
+
+CREATE INDEX IF NOT EXISTS idx_announcements_next_occurrence
+    ON public.announcements (next_occurrence_at)
+    WHERE recurrence IS NOT NULL;
+
+ALTER TABLE public.announcement_dismissals ADD COLUMN IF NOT EXISTS occurrence integer NOT NULL DEFAULT 0;
+ALTER TABLE public.announcement_dismissals
+    DROP CONSTRAINT IF EXISTS announcement_dismissals_user_id_announcement_id_key;
+ALTER TABLE public.announcement_dismissals
+    ADD CONSTRAINT announcement_dismissals_occurrence_key UNIQUE (user_id, announcement_id, occurrence);
@@ -0,0 +1,13 @@
+-- Read-rate tracking for announcements.
+-- One row per reader and occurrence: `delivered_at` when an in-app or email
+-- delivery went out to them, `viewed_at` the first time the announcement was
+-- served to them in the app. Dismissals stay in announcement_dismissals.
+
+CREATE TABLE IF NOT EXISTS public.announcement_receipts (
+    announcement_id text NOT NULL REFERENCES public.announcements(id) ON DELETE CASCADE,
+    user_id text NOT NULL REFERENCES public.users(id) ON DELETE CASCADE,
+    occurrence integer NOT NULL DEFAULT 0,
+    delivered_at timestamp with time zone NULL,
+    viewed_at timestamp with time zone NULL,
+    CONSTRAINT announcement_receipts_pkey PRIMARY KEY (announcement_id, occurrence, user_id)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE public.file_records DROP COLUMN IF EXISTS quota_counted;
+ALTER TABLE public.file_records DROP COLUMN IF EXISTS storage_bucket;
@@ -0,0 +1,10 @@
+-- Where each file's bytes live.
+-- `storage_bucket` records the S3 bucket an object was written to, so reads
+-- keep working after a tenant changes its storage settings. `quota_counted`
+-- tells whether the file is included in `tenants.storage_usage`; files in a
+-- tenant's own bucket are not (uploads there already bypassed the quota).
+
+ALTER TABLE public.file_records ADD COLUMN IF NOT EXISTS storage_bucket text NULL;
+ALTER TABLE public.file_records ADD COLUMN IF NOT EXISTS quota_counted boolean DEFAULT true NOT NULL;
+
+UPDATE public.file_records SET quota_counted = false WHERE storage_provider IN ('s3', 'r2');
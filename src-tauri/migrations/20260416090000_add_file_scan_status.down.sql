@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS public.idx_file_records_quarantined;
+ALTER TABLE public.file_records DROP COLUMN IF EXISTS scanned_at;
+ALTER TABLE public.file_records DROP COLUMN IF EXISTS scan_signature;
+ALTER TABLE public.file_records DROP COLUMN IF EXISTS scan_status;
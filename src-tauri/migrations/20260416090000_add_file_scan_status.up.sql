@@ -0,0 +1,11 @@
+-- Malware scan results of uploaded files.
+-- `scan_status` is unscanned, clean, infected or error; infected files are
+-- quarantined (kept, but no longer served).
+
+ALTER TABLE public.file_records ADD COLUMN IF NOT EXISTS scan_status text DEFAULT 'unscanned' NOT NULL;
+ALTER TABLE public.file_records ADD COLUMN IF NOT EXISTS scan_signature text NULL;
+ALTER TABLE public.file_records ADD COLUMN IF NOT EXISTS scanned_at timestamp with time zone NULL;
+
+CREATE INDEX IF NOT EXISTS idx_file_records_quarantined
+    ON public.file_records USING btree (tenant_id)
+    WHERE scan_status = 'infected';
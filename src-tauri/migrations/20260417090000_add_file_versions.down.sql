@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS public.file_versions;
+DROP INDEX IF EXISTS public.idx_file_records_logical_path;
+ALTER TABLE public.file_records DROP COLUMN IF EXISTS version;
+ALTER TABLE public.file_records DROP COLUMN IF EXISTS logical_path;
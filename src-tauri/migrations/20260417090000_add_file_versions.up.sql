@@ -0,0 +1,35 @@
+-- Versions of uploaded files.
+-- An upload that names a `logical_path` already used in its tenant becomes a
+-- new version of that file instead of a new file: the record keeps its id and
+-- points to the new object, `version` goes up, and the content it replaced is
+-- kept in file_versions (until pruned by `storage_max_versions`).
+
+ALTER TABLE public.file_records ADD COLUMN IF NOT EXISTS logical_path text NULL;
+ALTER TABLE public.file_records ADD COLUMN IF NOT EXISTS version integer DEFAULT 1 NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_file_records_logical_path
+    ON public.file_records USING btree (tenant_id, logical_path)
+    WHERE logical_path IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS public.file_versions (
+    id text NOT NULL,
+    file_id text NOT NULL REFERENCES public.file_records(id) ON DELETE CASCADE,
+    tenant_id text NOT NULL,
+    version integer NOT NULL,
+    name text NOT NULL,
+    original_name text NOT NULL,
+    path text NOT NULL,
+    size bigint NOT NULL,
+    content_type text NOT NULL,
+    storage_provider text NOT NULL,
+    storage_bucket text NULL,
+    quota_counted boolean DEFAULT true NOT NULL,
+    scan_status text DEFAULT 'unscanned' NOT NULL,
+    scan_signature text NULL,
+    scanned_at timestamp with time zone NULL,
+    uploaded_by text NULL,
+    created_at timestamp with time zone NOT NULL,
+    replaced_at timestamp with time zone NOT NULL,
+    CONSTRAINT file_versions_pkey PRIMARY KEY (id),
+    CONSTRAINT file_versions_file_version_key UNIQUE (file_id, version)
+);
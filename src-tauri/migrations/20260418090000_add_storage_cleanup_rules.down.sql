@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS public.storage_cleanup_rules;
+ALTER TABLE public.tenants DROP COLUMN IF EXISTS storage_alert_threshold;
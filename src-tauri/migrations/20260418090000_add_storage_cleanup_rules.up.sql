@@ -0,0 +1,31 @@
+-- Storage quota alerts and cleanup rules.
+-- `storage_alert_threshold` is the last usage level (percent of the plan's
+-- storage quota: 0, 80 or 95) the tenant's admins were told about, so each
+-- level is announced once per crossing.
+-- A cleanup rule deletes a tenant's files matching a name pattern and/or
+-- content type (`*` and `?` wildcards) once they are older than
+-- `older_than_days`.
+
+ALTER TABLE public.tenants ADD COLUMN IF NOT EXISTS storage_alert_threshold integer DEFAULT 0 NOT NULL;
+
+CREATE TABLE IF NOT EXISTS public.storage_cleanup_rules (
+    id text NOT NULL,
+    tenant_id text NOT NULL,
+    name text NOT NULL,
+    name_pattern text NULL,
+    content_type text NULL,
+    older_than_days integer NOT NULL,
+    enabled boolean DEFAULT true NOT NULL,
+    last_run_at timestamp with time zone NULL,
+    last_deleted_count integer DEFAULT 0 NOT NULL,
+    last_deleted_bytes bigint DEFAULT 0 NOT NULL,
+    created_by text NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT storage_cleanup_rules_pkey PRIMARY KEY (id),
+    CONSTRAINT storage_cleanup_rules_tenant_id_fkey FOREIGN KEY (tenant_id)
+        REFERENCES public.tenants(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_storage_cleanup_rules_tenant
+    ON public.storage_cleanup_rules USING btree (tenant_id);
@@ -0,0 +1,2 @@
+ALTER TABLE public.audit_logs DROP COLUMN IF EXISTS new_value;
+ALTER TABLE public.audit_logs DROP COLUMN IF EXISTS old_value;
@@ -0,0 +1,8 @@
+-- Structured before/after values for update entries.
+-- Both hold only the top-level fields that changed, with secret fields
+-- replaced by "[redacted]". NULL on entries written without a diff.
+-- audit_logs is postgres-only (partitioned by month), so there is no sqlite
+-- counterpart.
+
+ALTER TABLE public.audit_logs ADD COLUMN IF NOT EXISTS old_value jsonb NULL;
+ALTER TABLE public.audit_logs ADD COLUMN IF NOT EXISTS new_value jsonb NULL;
@@ -0,0 +1,8 @@
+DROP TABLE IF EXISTS public.audit_filter_presets;
+
+DROP INDEX IF EXISTS public.idx_audit_logs_ip;
+DROP INDEX IF EXISTS public.idx_audit_logs_resource;
+DROP INDEX IF EXISTS public.idx_audit_logs_user_created;
+DROP INDEX IF EXISTS public.idx_audit_logs_tenant_created;
+CREATE INDEX IF NOT EXISTS idx_audit_logs_tenant ON public.audit_logs USING btree (tenant_id);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_user ON public.audit_logs USING btree (user_id);
@@ -0,0 +1,35 @@
+-- Audit search: indexes for the list/export filters and saved filter presets.
+-- Tenant and actor lookups are always ordered by time, so their single-column
+-- indexes are replaced by (column, created_at DESC) ones. Created on the
+-- partitioned parent, they cascade to every partition.
+
+DROP INDEX IF EXISTS public.idx_audit_logs_tenant;
+DROP INDEX IF EXISTS public.idx_audit_logs_user;
+CREATE INDEX IF NOT EXISTS idx_audit_logs_tenant_created
+    ON public.audit_logs USING btree (tenant_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_user_created
+    ON public.audit_logs USING btree (user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_resource
+    ON public.audit_logs USING btree (resource, resource_id);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_ip
+    ON public.audit_logs USING btree (ip_address varchar_pattern_ops);
+
+-- A user's named filter sets. `tenant_id` is NULL for presets saved from the
+-- super admin (all tenants) view.
+CREATE TABLE IF NOT EXISTS public.audit_filter_presets (
+    id text NOT NULL,
+    user_id text NOT NULL,
+    tenant_id text NULL,
+    name text NOT NULL,
+    filters jsonb DEFAULT '{}'::jsonb NOT NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT audit_filter_presets_pkey PRIMARY KEY (id),
+    CONSTRAINT audit_filter_presets_user_id_fkey FOREIGN KEY (user_id)
+        REFERENCES public.users(id) ON DELETE CASCADE,
+    CONSTRAINT audit_filter_presets_tenant_id_fkey FOREIGN KEY (tenant_id)
+        REFERENCES public.tenants(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_audit_filter_presets_name
+    ON public.audit_filter_presets USING btree (user_id, COALESCE(tenant_id, ''), lower(name));
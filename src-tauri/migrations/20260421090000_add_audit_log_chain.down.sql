@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS public.audit_chain_head;
+DROP INDEX IF EXISTS public.idx_audit_logs_chain_seq;
+ALTER TABLE public.audit_logs DROP COLUMN IF EXISTS entry_hash;
+ALTER TABLE public.audit_logs DROP COLUMN IF EXISTS prev_hash;
+ALTER TABLE public.audit_logs DROP COLUMN IF EXISTS chain_seq;
@@ -0,0 +1,25 @@
+-- Tamper-evident audit log: every entry stores its position in a single
+-- global chain, the hash of the entry before it and its own hash over both
+-- plus its content. Rows written before this migration stay unchained.
+
+ALTER TABLE public.audit_logs ADD COLUMN IF NOT EXISTS chain_seq bigint NULL;
+ALTER TABLE public.audit_logs ADD COLUMN IF NOT EXISTS prev_hash varchar(64) NULL;
+ALTER TABLE public.audit_logs ADD COLUMN IF NOT EXISTS entry_hash varchar(64) NULL;
+
+CREATE INDEX IF NOT EXISTS idx_audit_logs_chain_seq
+    ON public.audit_logs USING btree (chain_seq);
+
+-- The chain tip. Writers lock this single row to append, which also catches
+-- entries deleted from the end of the chain.
+CREATE TABLE IF NOT EXISTS public.audit_chain_head (
+    id integer NOT NULL,
+    last_seq bigint NOT NULL,
+    last_hash varchar(64) NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT audit_chain_head_pkey PRIMARY KEY (id),
+    CONSTRAINT audit_chain_head_single_row CHECK (id = 1)
+);
+
+INSERT INTO public.audit_chain_head (id, last_seq, last_hash, updated_at)
+VALUES (1, 0, repeat('0', 64), now())
+ON CONFLICT (id) DO NOTHING;
@@ -0,0 +1,36 @@
+-- Background job queue. Workers claim due rows (`status` queued or failed,
+-- `run_at` passed) and record the outcome on the row.
+-- Recurring jobs have `recurring_secs` set and keep a single row per
+-- `job_type` that is put back in the queue after every run; one-off jobs end
+-- as `succeeded`, or `dead` once `max_attempts` runs have failed.
+
+CREATE TABLE IF NOT EXISTS public.background_jobs (
+    id text NOT NULL,
+    job_type text NOT NULL,
+    payload jsonb DEFAULT '{}'::jsonb NOT NULL,
+    status text DEFAULT 'queued' NOT NULL,
+    recurring_secs integer NULL,
+    attempts integer DEFAULT 0 NOT NULL,
+    max_attempts integer DEFAULT 5 NOT NULL,
+    run_at timestamp with time zone NOT NULL,
+    locked_at timestamp with time zone NULL,
+    locked_by text NULL,
+    last_error text NULL,
+    last_run_at timestamp with time zone NULL,
+    finished_at timestamp with time zone NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT background_jobs_pkey PRIMARY KEY (id),
+    CONSTRAINT background_jobs_status_check
+        CHECK (status IN ('queued', 'running', 'succeeded', 'failed', 'dead'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_background_jobs_due
+    ON public.background_jobs USING btree (status, run_at);
+
+CREATE INDEX IF NOT EXISTS idx_background_jobs_type
+    ON public.background_jobs USING btree (job_type, created_at DESC);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_background_jobs_recurring
+    ON public.background_jobs USING btree (job_type)
+    WHERE recurring_secs IS NOT NULL;
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS public.system_alert_rules;
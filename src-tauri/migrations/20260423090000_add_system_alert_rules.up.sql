@@ -0,0 +1,55 @@
+-- Deployment-wide alert rules. Each rule watches one metric, fires when it
+-- goes above `threshold` and notifies its `channels` (email, telegram,
+-- webhook) at most once per `cooldown_minutes`. `last_triggered_at` is the
+-- cooldown, shared by every instance that evaluates the rule.
+
+CREATE TABLE IF NOT EXISTS public.system_alert_rules (
+    id text NOT NULL,
+    name text NOT NULL,
+    metric text NOT NULL,
+    threshold double precision NOT NULL,
+    channels jsonb DEFAULT '["email"]'::jsonb NOT NULL,
+    cooldown_minutes integer DEFAULT 15 NOT NULL,
+    enabled boolean DEFAULT true NOT NULL,
+    last_triggered_at timestamp with time zone NULL,
+    last_value double precision NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT system_alert_rules_pkey PRIMARY KEY (id),
+    CONSTRAINT system_alert_rules_metric_check CHECK (metric IN (
+        'error_rate', 'latency_p95', 'rate_limited', 'db_pool_saturation',
+        'disk_usage', 'outbox_backlog'
+    )),
+    CONSTRAINT system_alert_rules_cooldown_check CHECK (cooldown_minutes >= 1)
+);
+
+-- Default rules. The first three replace the alerting_*_threshold settings
+-- and take over their values.
+WITH legacy AS (
+    SELECT key, trim(value)::double precision AS value
+    FROM public.settings
+    WHERE tenant_id IS NULL
+      AND key IN ('alerting_error_threshold', 'alerting_response_time_threshold',
+                  'alerting_rate_limit_threshold', 'alerting_cooldown_minutes')
+      AND trim(value) ~ '^[0-9]+(\.[0-9]+)?$'
+),
+cooldown AS (
+    SELECT GREATEST(COALESCE(
+        (SELECT value FROM legacy WHERE key = 'alerting_cooldown_minutes'), 15
+    ), 1)::integer AS minutes
+)
+INSERT INTO public.system_alert_rules
+    (id, name, metric, threshold, channels, cooldown_minutes, enabled, created_at, updated_at)
+SELECT d.id, d.name, d.metric,
+       COALESCE((SELECT value FROM legacy WHERE key = d.legacy_key), d.threshold),
+       '["email"]'::jsonb, cooldown.minutes, true, now(), now()
+FROM (VALUES
+    ('alert_error_rate', 'High error rate', 'error_rate', 5.0, 'alerting_error_threshold'),
+    ('alert_latency_p95', 'Slow responses', 'latency_p95', 3000.0, 'alerting_response_time_threshold'),
+    ('alert_rate_limited', 'Rate limiting spike', 'rate_limited', 50.0, 'alerting_rate_limit_threshold'),
+    ('alert_db_pool', 'Database pool saturation', 'db_pool_saturation', 90.0, NULL),
+    ('alert_disk_usage', 'Disk almost full', 'disk_usage', 90.0, NULL),
+    ('alert_outbox_backlog', 'Email outbox backlog', 'outbox_backlog', 500.0, NULL)
+) AS d (id, name, metric, threshold, legacy_key)
+CROSS JOIN cooldown
+ON CONFLICT (id) DO NOTHING;
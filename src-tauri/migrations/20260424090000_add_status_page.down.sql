@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS public.status_post_updates;
+DROP TABLE IF EXISTS public.status_posts;
+DROP TABLE IF EXISTS public.status_components;
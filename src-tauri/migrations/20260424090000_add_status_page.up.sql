@@ -0,0 +1,72 @@
+-- Per-tenant public status page. Components are what the page lists (API,
+-- billing, network regions); a network component is a group of routers and
+-- takes its health from them. Posts are incidents and scheduled maintenance,
+-- each with a timeline of updates.
+
+CREATE TABLE IF NOT EXISTS public.status_components (
+    id text NOT NULL,
+    tenant_id text NOT NULL,
+    name text NOT NULL,
+    description text NULL,
+    kind text DEFAULT 'custom' NOT NULL,
+    router_ids text DEFAULT '' NOT NULL, -- comma-separated; network components only
+    status_override text NULL,
+    position integer DEFAULT 0 NOT NULL,
+    is_visible boolean DEFAULT true NOT NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT status_components_pkey PRIMARY KEY (id),
+    CONSTRAINT status_components_tenant_id_fkey FOREIGN KEY (tenant_id)
+        REFERENCES public.tenants(id) ON DELETE CASCADE,
+    CONSTRAINT status_components_kind_check
+        CHECK (kind IN ('api', 'billing', 'network', 'custom')),
+    CONSTRAINT status_components_status_override_check CHECK (status_override IS NULL
+        OR status_override IN ('operational', 'maintenance', 'degraded', 'partial_outage', 'major_outage'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_status_components_tenant
+    ON public.status_components USING btree (tenant_id, position);
+
+CREATE TABLE IF NOT EXISTS public.status_posts (
+    id text NOT NULL,
+    tenant_id text NOT NULL,
+    kind text NOT NULL,
+    title text NOT NULL,
+    impact text DEFAULT 'minor' NOT NULL,
+    status text NOT NULL,
+    component_ids text DEFAULT '' NOT NULL, -- comma-separated
+    scheduled_start timestamp with time zone NULL,
+    scheduled_end timestamp with time zone NULL,
+    resolved_at timestamp with time zone NULL,
+    created_by text NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT status_posts_pkey PRIMARY KEY (id),
+    CONSTRAINT status_posts_tenant_id_fkey FOREIGN KEY (tenant_id)
+        REFERENCES public.tenants(id) ON DELETE CASCADE,
+    CONSTRAINT status_posts_kind_check CHECK (kind IN ('incident', 'maintenance')),
+    CONSTRAINT status_posts_impact_check
+        CHECK (impact IN ('none', 'minor', 'major', 'critical')),
+    CONSTRAINT status_posts_status_check CHECK (status IN (
+        'investigating', 'identified', 'monitoring', 'resolved',
+        'scheduled', 'in_progress', 'completed'
+    ))
+);
+
+CREATE INDEX IF NOT EXISTS idx_status_posts_tenant
+    ON public.status_posts USING btree (tenant_id, resolved_at, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS public.status_post_updates (
+    id text NOT NULL,
+    post_id text NOT NULL,
+    status text NOT NULL,
+    message text NOT NULL,
+    created_by text NULL,
+    created_at timestamp with time zone NOT NULL,
+    CONSTRAINT status_post_updates_pkey PRIMARY KEY (id),
+    CONSTRAINT status_post_updates_post_id_fkey FOREIGN KEY (post_id)
+        REFERENCES public.status_posts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_status_post_updates_post
+    ON public.status_post_updates USING btree (post_id, created_at);
@@ -0,0 +1,4 @@
+ALTER TABLE public.email_outbox
+    DROP COLUMN IF EXISTS attachments;
+
+DROP TABLE IF EXISTS public.report_schedules;
@@ -0,0 +1,44 @@
+-- Scheduled report delivery. Each schedule renders one report (revenue,
+-- aging, sla, incidents) as CSV or PDF for the period that just ended and
+-- emails it to `recipients` through the outbox. `next_run_at` is claimed by
+-- the instance that runs it, so a report goes out once per period.
+
+CREATE TABLE IF NOT EXISTS public.report_schedules (
+    id text NOT NULL,
+    tenant_id text NOT NULL,
+    name text NOT NULL,
+    report text NOT NULL,
+    format text DEFAULT 'pdf'::text NOT NULL,
+    cadence text DEFAULT 'monthly'::text NOT NULL,
+    -- Local hour (tenant app_timezone) the report is sent at.
+    send_hour integer DEFAULT 7 NOT NULL,
+    recipients jsonb DEFAULT '[]'::jsonb NOT NULL,
+    enabled boolean DEFAULT true NOT NULL,
+    next_run_at timestamp with time zone NOT NULL,
+    last_run_at timestamp with time zone NULL,
+    last_status text NULL,
+    last_error text NULL,
+    created_by text NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT report_schedules_pkey PRIMARY KEY (id),
+    CONSTRAINT report_schedules_tenant_id_fkey FOREIGN KEY (tenant_id)
+        REFERENCES public.tenants(id) ON DELETE CASCADE,
+    CONSTRAINT report_schedules_created_by_fkey FOREIGN KEY (created_by)
+        REFERENCES public.users(id) ON DELETE SET NULL,
+    CONSTRAINT report_schedules_report_check CHECK (report IN ('revenue', 'aging', 'sla', 'incidents')),
+    CONSTRAINT report_schedules_format_check CHECK (format IN ('csv', 'pdf')),
+    CONSTRAINT report_schedules_cadence_check CHECK (cadence IN ('daily', 'weekly', 'monthly')),
+    CONSTRAINT report_schedules_send_hour_check CHECK (send_hour BETWEEN 0 AND 23),
+    CONSTRAINT report_schedules_last_status_check CHECK (last_status IS NULL OR last_status IN ('sent', 'failed'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_report_schedules_tenant
+    ON public.report_schedules (tenant_id, created_at);
+
+CREATE INDEX IF NOT EXISTS idx_report_schedules_due
+    ON public.report_schedules (next_run_at) WHERE enabled;
+
+-- Files sent with a queued email: [{"filename", "content_type", "data" (base64)}].
+ALTER TABLE public.email_outbox
+    ADD COLUMN IF NOT EXISTS attachments jsonb NULL;
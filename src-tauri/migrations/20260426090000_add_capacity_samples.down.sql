@@ -0,0 +1 @@
+DROP TABLE IF EXISTS public.system_capacity_samples;
@@ -0,0 +1,14 @@
+-- Hourly database size and free disk space, kept for 30 days. System
+-- diagnostics compare the newest sample with one from about a week earlier
+-- to estimate growth and the days left before the disk is full.
+
+CREATE TABLE IF NOT EXISTS public.system_capacity_samples (
+    id text NOT NULL,
+    sampled_at timestamp with time zone NOT NULL,
+    database_bytes bigint NOT NULL,
+    disk_available_bytes bigint NULL,
+    CONSTRAINT system_capacity_samples_pkey PRIMARY KEY (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_system_capacity_samples_sampled_at
+    ON public.system_capacity_samples (sampled_at);
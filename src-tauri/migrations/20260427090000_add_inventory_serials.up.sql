@@ -0,0 +1,28 @@
+-- Serial-numbered units of an inventory item (ONUs, routers), registered by
+-- scanning the label barcode at warehouse intake. Stock quantities stay in
+-- inventory_stock; this table records which physical unit is where and which
+-- work order installed it.
+
+CREATE TABLE IF NOT EXISTS public.inventory_serials (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    item_id text NOT NULL REFERENCES public.inventory_items(id) ON DELETE CASCADE,
+    -- Upper-cased, without whitespace, as printed on the label.
+    serial_number text NOT NULL,
+    status text NOT NULL DEFAULT 'in_stock' CHECK (status IN ('in_stock', 'installed')),
+    -- Where the unit is kept while in stock; NULL once installed.
+    location_id text REFERENCES public.inventory_locations(id) ON DELETE SET NULL,
+    work_order_id text REFERENCES public.installation_work_orders(id) ON DELETE SET NULL,
+    created_by text REFERENCES public.users(id) ON DELETE SET NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_inventory_serials_tenant_serial
+    ON public.inventory_serials (tenant_id, serial_number);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_serials_item
+    ON public.inventory_serials (item_id, status);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_serials_work_order
+    ON public.inventory_serials (work_order_id) WHERE work_order_id IS NOT NULL;
@@ -0,0 +1,17 @@
+ALTER TABLE public.isp_package_router_mappings
+    DROP COLUMN IF EXISTS burst_limit_up_kbps,
+    DROP COLUMN IF EXISTS burst_limit_down_kbps,
+    DROP COLUMN IF EXISTS burst_threshold_up_kbps,
+    DROP COLUMN IF EXISTS burst_threshold_down_kbps,
+    DROP COLUMN IF EXISTS burst_time_secs,
+    DROP COLUMN IF EXISTS burst_priority;
+
+ALTER TABLE public.isp_packages
+    DROP COLUMN IF EXISTS rate_limit_up_kbps,
+    DROP COLUMN IF EXISTS rate_limit_down_kbps,
+    DROP COLUMN IF EXISTS burst_limit_up_kbps,
+    DROP COLUMN IF EXISTS burst_limit_down_kbps,
+    DROP COLUMN IF EXISTS burst_threshold_up_kbps,
+    DROP COLUMN IF EXISTS burst_threshold_down_kbps,
+    DROP COLUMN IF EXISTS burst_time_secs,
+    DROP COLUMN IF EXISTS burst_priority;
@@ -0,0 +1,23 @@
+-- Package speeds for the MikroTik PPP profile `rate-limit`, in kbps. From the
+-- router's point of view `up` is rx and `down` is tx. Burst lets a client go
+-- up to `burst_limit` while its average over `burst_time_secs` stays below
+-- `burst_threshold`; `burst_priority` is 1 (highest) to 8.
+
+ALTER TABLE public.isp_packages
+    ADD COLUMN IF NOT EXISTS rate_limit_up_kbps integer NULL,
+    ADD COLUMN IF NOT EXISTS rate_limit_down_kbps integer NULL,
+    ADD COLUMN IF NOT EXISTS burst_limit_up_kbps integer NULL,
+    ADD COLUMN IF NOT EXISTS burst_limit_down_kbps integer NULL,
+    ADD COLUMN IF NOT EXISTS burst_threshold_up_kbps integer NULL,
+    ADD COLUMN IF NOT EXISTS burst_threshold_down_kbps integer NULL,
+    ADD COLUMN IF NOT EXISTS burst_time_secs integer NULL,
+    ADD COLUMN IF NOT EXISTS burst_priority integer NULL;
+
+-- Per-router burst overrides; NULL keeps the package value.
+ALTER TABLE public.isp_package_router_mappings
+    ADD COLUMN IF NOT EXISTS burst_limit_up_kbps integer NULL,
+    ADD COLUMN IF NOT EXISTS burst_limit_down_kbps integer NULL,
+    ADD COLUMN IF NOT EXISTS burst_threshold_up_kbps integer NULL,
+    ADD COLUMN IF NOT EXISTS burst_threshold_down_kbps integer NULL,
+    ADD COLUMN IF NOT EXISTS burst_time_secs integer NULL,
+    ADD COLUMN IF NOT EXISTS burst_priority integer NULL;
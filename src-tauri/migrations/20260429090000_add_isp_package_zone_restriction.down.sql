@@ -0,0 +1,2 @@
+ALTER TABLE public.isp_packages
+    DROP COLUMN IF EXISTS zone_restricted;
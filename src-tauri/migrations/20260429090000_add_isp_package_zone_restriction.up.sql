@@ -0,0 +1,6 @@
+-- A zone-restricted package is only sold at locations inside an active
+-- service zone that has an active zone offer for it. Other packages are sold
+-- everywhere, as before.
+
+ALTER TABLE public.isp_packages
+    ADD COLUMN IF NOT EXISTS zone_restricted boolean NOT NULL DEFAULT false;
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS public.isp_package_price_history;
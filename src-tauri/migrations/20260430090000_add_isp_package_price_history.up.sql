@@ -0,0 +1,34 @@
+-- List price history of ISP packages. A row is written when a package is
+-- created and whenever its monthly or yearly price changes; each row holds
+-- the prices in effect from `effective_from` until the next row. Billing
+-- uses it to price older periods at the list price of the time.
+
+CREATE TABLE IF NOT EXISTS public.isp_package_price_history (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    package_id text NOT NULL REFERENCES public.isp_packages(id) ON DELETE CASCADE,
+    price_monthly numeric(12,2) NOT NULL,
+    price_yearly numeric(12,2) NOT NULL,
+    effective_from timestamp with time zone NOT NULL,
+    changed_by text REFERENCES public.users(id) ON DELETE SET NULL,
+    created_at timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_isp_package_price_history_package
+    ON public.isp_package_price_history (package_id, effective_from DESC);
+
+-- Existing packages start their history with the current prices.
+INSERT INTO public.isp_package_price_history (
+    id, tenant_id, package_id, price_monthly, price_yearly, effective_from, changed_by, created_at
+)
+SELECT
+    'pph_' || p.id,
+    p.tenant_id,
+    p.id,
+    p.price_monthly,
+    p.price_yearly,
+    p.created_at,
+    NULL,
+    NOW()
+FROM public.isp_packages p
+ON CONFLICT (id) DO NOTHING;
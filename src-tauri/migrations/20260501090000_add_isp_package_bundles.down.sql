@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS public.customer_subscription_components;
+DROP TABLE IF EXISTS public.isp_package_components;
@@ -0,0 +1,39 @@
+-- Bundle packages combine one internet package with add-on services
+-- (static IP, IPTV, VoIP). The bundle carries the combined price and is
+-- billed as a single subscription line; each component is provisioned on
+-- its own and tracked per subscription.
+
+CREATE TABLE IF NOT EXISTS public.isp_package_components (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    bundle_id text NOT NULL REFERENCES public.isp_packages(id) ON DELETE CASCADE,
+    component_id text NOT NULL REFERENCES public.isp_packages(id) ON DELETE RESTRICT,
+    sort_order integer NOT NULL DEFAULT 0,
+    created_at timestamp with time zone NOT NULL,
+    CONSTRAINT isp_package_components_unique UNIQUE (bundle_id, component_id),
+    CONSTRAINT isp_package_components_not_self CHECK (bundle_id <> component_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_isp_package_components_component
+    ON public.isp_package_components (component_id);
+
+CREATE TABLE IF NOT EXISTS public.customer_subscription_components (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    subscription_id text NOT NULL REFERENCES public.customer_subscriptions(id) ON DELETE CASCADE,
+    component_package_id text NOT NULL REFERENCES public.isp_packages(id) ON DELETE RESTRICT,
+    service_type text NOT NULL,
+    status text NOT NULL DEFAULT 'pending',
+    -- Static IP address, IPTV account or VoIP number handed to the customer.
+    detail text,
+    last_error text,
+    provisioned_at timestamp with time zone,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT customer_subscription_components_unique UNIQUE (subscription_id, component_package_id),
+    CONSTRAINT customer_subscription_components_status_check
+        CHECK (status IN ('pending', 'provisioned', 'failed', 'disabled'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_customer_subscription_components_subscription
+    ON public.customer_subscription_components (subscription_id);
@@ -0,0 +1,9 @@
+DROP TABLE IF EXISTS public.pppoe_usage_periods;
+
+ALTER TABLE public.pppoe_accounts
+    DROP COLUMN IF EXISTS fup_throttled_at;
+
+ALTER TABLE public.isp_packages
+    DROP COLUMN IF EXISTS fup_quota_gb,
+    DROP COLUMN IF EXISTS fup_rate_up_kbps,
+    DROP COLUMN IF EXISTS fup_rate_down_kbps;
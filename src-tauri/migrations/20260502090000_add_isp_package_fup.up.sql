@@ -0,0 +1,33 @@
+-- Fair-usage policy: once a subscriber downloads and uploads more than
+-- `fup_quota_gb` in a usage period, their PPPoE account moves to a throttled
+-- profile with the `fup_rate_*` speeds (kbps) until the period resets.
+
+ALTER TABLE public.isp_packages
+    ADD COLUMN IF NOT EXISTS fup_quota_gb integer NULL,
+    ADD COLUMN IF NOT EXISTS fup_rate_up_kbps integer NULL,
+    ADD COLUMN IF NOT EXISTS fup_rate_down_kbps integer NULL;
+
+ALTER TABLE public.pppoe_accounts
+    ADD COLUMN IF NOT EXISTS fup_throttled_at timestamp with time zone NULL;
+
+-- Bytes per account and usage period, seen from the subscriber (the router's
+-- rx is their upload). The session columns hold the last counters read from
+-- the `<pppoe-username>` interface so only the growth since the previous
+-- poll is added.
+CREATE TABLE IF NOT EXISTS public.pppoe_usage_periods (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    account_id text NOT NULL REFERENCES public.pppoe_accounts(id) ON DELETE CASCADE,
+    period_start timestamp with time zone NOT NULL,
+    upload_bytes bigint NOT NULL DEFAULT 0,
+    download_bytes bigint NOT NULL DEFAULT 0,
+    session_key text,
+    session_upload_bytes bigint,
+    session_download_bytes bigint,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT pppoe_usage_periods_unique UNIQUE (account_id, period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_pppoe_usage_periods_tenant
+    ON public.pppoe_usage_periods (tenant_id, period_start DESC);
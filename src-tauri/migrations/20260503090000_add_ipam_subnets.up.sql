@@ -0,0 +1,22 @@
+-- IP address plan: IPv4 subnets with VLAN, purpose and free-form tags. The
+-- hierarchy is not stored; a subnet's parent is the smallest other subnet
+-- of the tenant that contains it, so supernets can be added later.
+
+CREATE TABLE IF NOT EXISTS public.ipam_subnets (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    -- Network address and prefix, e.g. `10.20.0.0/22`.
+    cidr text NOT NULL,
+    name text NOT NULL,
+    vlan_id integer,
+    purpose text,
+    tags text[] NOT NULL DEFAULT '{}',
+    router_id text REFERENCES public.mikrotik_routers(id) ON DELETE SET NULL,
+    notes text,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT ipam_subnets_tenant_cidr_unique UNIQUE (tenant_id, cidr),
+    CONSTRAINT ipam_subnets_vlan_check CHECK (vlan_id IS NULL OR vlan_id BETWEEN 1 AND 4094)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ipam_subnets_tenant ON public.ipam_subnets (tenant_id);
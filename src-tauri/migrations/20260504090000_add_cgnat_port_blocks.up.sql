@@ -0,0 +1,30 @@
+-- CGNAT port-block assignments imported from router/NAT logs, kept to
+-- answer "who used public IP X, port Y at time T" for abuse and lawful
+-- requests. Username and customer name are copied at import so records
+-- outlive the account.
+
+CREATE TABLE IF NOT EXISTS public.cgnat_port_blocks (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    router_id text REFERENCES public.mikrotik_routers(id) ON DELETE SET NULL,
+    public_ip text NOT NULL,
+    port_start integer NOT NULL,
+    port_end integer NOT NULL,
+    private_ip text NOT NULL,
+    pppoe_account_id text REFERENCES public.pppoe_accounts(id) ON DELETE SET NULL,
+    customer_id text REFERENCES public.customers(id) ON DELETE SET NULL,
+    username text,
+    customer_name text,
+    assigned_at timestamp with time zone NOT NULL,
+    -- Open until released, or until the ports are assigned to someone else.
+    released_at timestamp with time zone,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT cgnat_port_blocks_unique UNIQUE (tenant_id, public_ip, port_start, assigned_at),
+    CONSTRAINT cgnat_port_blocks_ports_check CHECK (port_start BETWEEN 1 AND 65535 AND port_end BETWEEN port_start AND 65535)
+);
+
+CREATE INDEX IF NOT EXISTS idx_cgnat_port_blocks_lookup
+    ON public.cgnat_port_blocks (tenant_id, public_ip, assigned_at DESC);
+CREATE INDEX IF NOT EXISTS idx_cgnat_port_blocks_customer
+    ON public.cgnat_port_blocks (customer_id, assigned_at DESC);
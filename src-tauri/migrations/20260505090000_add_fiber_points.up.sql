@@ -0,0 +1,29 @@
+-- Fiber distribution points: ODCs (cabinets) and the ODPs (closures) fed
+-- from them. Each point is mirrored as a system-managed network node so
+-- links can be drawn to it in the topology editor.
+
+CREATE TABLE IF NOT EXISTS public.fiber_points (
+    id uuid PRIMARY KEY,
+    tenant_id uuid NOT NULL,
+    kind text NOT NULL,
+    code text NOT NULL,
+    name text NOT NULL,
+    status text NOT NULL DEFAULT 'active',
+    geom geometry(Point, 4326) NOT NULL,
+    -- ODC an ODP is fed from.
+    parent_id uuid REFERENCES public.fiber_points(id) ON DELETE SET NULL,
+    -- Router/OLT site an ODC hangs off.
+    router_id text,
+    port_capacity integer,
+    notes text,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT uq_fiber_points_tenant_code UNIQUE (tenant_id, code),
+    CONSTRAINT chk_fiber_points_kind CHECK (kind IN ('odc', 'odp')),
+    CONSTRAINT chk_fiber_points_status
+      CHECK (status IN ('active', 'inactive', 'maintenance', 'planning')),
+    CONSTRAINT chk_fiber_points_ports CHECK (port_capacity IS NULL OR port_capacity > 0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_fiber_points_tenant ON public.fiber_points (tenant_id, kind);
+CREATE INDEX IF NOT EXISTS idx_fiber_points_geom ON public.fiber_points USING GIST (geom);
@@ -0,0 +1,23 @@
+-- Uplink interfaces with the bandwidth bought for them. Capacity reports
+-- take the 95th percentile of the interface's recorded throughput and
+-- compare it against `capacity_mbps`; the poller always records these
+-- interfaces so the reports have data.
+
+CREATE TABLE IF NOT EXISTS public.mikrotik_uplinks (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    router_id text NOT NULL REFERENCES public.mikrotik_routers(id) ON DELETE CASCADE,
+    interface_name text NOT NULL,
+    label text,
+    capacity_mbps integer NOT NULL,
+    warning_pct integer NOT NULL DEFAULT 70,
+    critical_pct integer NOT NULL DEFAULT 90,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT mikrotik_uplinks_router_iface_unique UNIQUE (router_id, interface_name),
+    CONSTRAINT mikrotik_uplinks_capacity_check CHECK (capacity_mbps > 0),
+    CONSTRAINT mikrotik_uplinks_thresholds_check
+        CHECK (warning_pct BETWEEN 1 AND 100 AND critical_pct BETWEEN 1 AND 100 AND warning_pct < critical_pct)
+);
+
+CREATE INDEX IF NOT EXISTS idx_mikrotik_uplinks_tenant ON public.mikrotik_uplinks (tenant_id);
@@ -0,0 +1,8 @@
+DROP INDEX IF EXISTS public.idx_mikrotik_incidents_tenant_impact;
+DROP TABLE IF EXISTS public.mikrotik_incident_impacts;
+
+ALTER TABLE public.mikrotik_incidents
+    DROP COLUMN IF EXISTS impact_computed_at,
+    DROP COLUMN IF EXISTS affected_areas,
+    DROP COLUMN IF EXISTS affected_customers,
+    DROP COLUMN IF EXISTS affected_subscriptions;
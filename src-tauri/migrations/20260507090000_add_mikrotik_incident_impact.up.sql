@@ -0,0 +1,29 @@
+-- Blast radius of an incident: the active subscriptions behind its router,
+-- resolved when the incident opens. The counts sit on the incident so the
+-- NOC can sort by them; the list is a snapshot of who was affected at the
+-- time, kept after customers move or cancel.
+
+ALTER TABLE public.mikrotik_incidents
+    ADD COLUMN IF NOT EXISTS affected_subscriptions integer,
+    ADD COLUMN IF NOT EXISTS affected_customers integer,
+    -- [{ "area": "...", "subscriptions": n, "customers": n }], largest first.
+    ADD COLUMN IF NOT EXISTS affected_areas jsonb,
+    ADD COLUMN IF NOT EXISTS impact_computed_at timestamp with time zone;
+
+CREATE TABLE IF NOT EXISTS public.mikrotik_incident_impacts (
+    incident_id text NOT NULL REFERENCES public.mikrotik_incidents(id) ON DELETE CASCADE,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    subscription_id text NOT NULL,
+    customer_id text NOT NULL,
+    customer_name text NOT NULL,
+    location_id text NOT NULL,
+    location_label text,
+    -- Service zone containing the location, else its city.
+    area text NOT NULL,
+    via text NOT NULL, -- router | pppoe | topology
+    CONSTRAINT mikrotik_incident_impacts_pkey PRIMARY KEY (incident_id, subscription_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_mikrotik_incidents_tenant_impact
+    ON public.mikrotik_incidents (tenant_id, affected_subscriptions DESC)
+    WHERE resolved_at IS NULL;
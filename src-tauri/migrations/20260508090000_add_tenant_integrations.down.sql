@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS public.tenant_webhook_deliveries;
+DROP TABLE IF EXISTS public.tenant_webhooks;
+DROP TABLE IF EXISTS public.tenant_api_keys;
@@ -0,0 +1,64 @@
+-- Tenant integrations against the public API: API keys, webhook
+-- subscriptions fed from the event outbox, and a log of their deliveries.
+
+CREATE TABLE IF NOT EXISTS public.tenant_api_keys (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    -- Requests made with the key act as this user, with their permissions.
+    user_id text NOT NULL REFERENCES public.users(id) ON DELETE CASCADE,
+    name text NOT NULL,
+    -- Start of the key, to tell keys apart. The key itself is only kept hashed.
+    key_prefix text NOT NULL,
+    key_hash text NOT NULL,
+    expires_at timestamp with time zone,
+    last_used_at timestamp with time zone,
+    revoked_at timestamp with time zone,
+    created_at timestamp with time zone NOT NULL,
+    CONSTRAINT tenant_api_keys_hash_unique UNIQUE (key_hash)
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenant_api_keys_tenant
+    ON public.tenant_api_keys (tenant_id, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS public.tenant_webhooks (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    url text NOT NULL,
+    description text,
+    -- Event types sent (the `X-Event-Type` header); empty sends all of them.
+    event_types text[] NOT NULL DEFAULT '{}',
+    secret_enc text NOT NULL,
+    is_active boolean NOT NULL DEFAULT true,
+    created_by text REFERENCES public.users(id) ON DELETE SET NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenant_webhooks_tenant
+    ON public.tenant_webhooks (tenant_id);
+
+CREATE TABLE IF NOT EXISTS public.tenant_webhook_deliveries (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    webhook_id text NOT NULL REFERENCES public.tenant_webhooks(id) ON DELETE CASCADE,
+    -- The outbox event; copied so deliveries outlive its retention.
+    event_id text NOT NULL,
+    event_type text NOT NULL,
+    payload text NOT NULL,
+    status text NOT NULL DEFAULT 'pending', -- pending | delivered | failed
+    attempts integer NOT NULL DEFAULT 0,
+    next_attempt_at timestamp with time zone NOT NULL DEFAULT now(),
+    -- Of the last attempt.
+    response_status integer,
+    duration_ms integer,
+    last_error text,
+    delivered_at timestamp with time zone,
+    created_at timestamp with time zone NOT NULL DEFAULT now(),
+    updated_at timestamp with time zone NOT NULL DEFAULT now(),
+    CONSTRAINT tenant_webhook_deliveries_event_unique UNIQUE (webhook_id, event_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenant_webhook_deliveries_due
+    ON public.tenant_webhook_deliveries (status, next_attempt_at);
+CREATE INDEX IF NOT EXISTS idx_tenant_webhook_deliveries_tenant
+    ON public.tenant_webhook_deliveries (tenant_id, created_at DESC);
@@ -0,0 +1,14 @@
+-- Onboarding checklist state per tenant. A step gets a row once it is
+-- detected as done (kept even if the thing is deleted later) or skipped by
+-- the tenant; steps without a row are still to do.
+
+CREATE TABLE IF NOT EXISTS public.tenant_onboarding_steps (
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    step text NOT NULL,
+    status text NOT NULL,
+    updated_by text REFERENCES public.users(id) ON DELETE SET NULL,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    PRIMARY KEY (tenant_id, step),
+    CONSTRAINT tenant_onboarding_steps_status_check CHECK (status IN ('done', 'skipped'))
+);
@@ -0,0 +1,48 @@
+DELETE FROM public.mikrotik_incidents WHERE source <> 'routeros';
+DELETE FROM public.mikrotik_alerts WHERE source <> 'routeros';
+
+ALTER TABLE public.mikrotik_incidents DROP COLUMN IF EXISTS source;
+ALTER TABLE public.mikrotik_alerts DROP COLUMN IF EXISTS source;
+
+ALTER TABLE public.mikrotik_incidents
+    ADD CONSTRAINT mikrotik_incidents_router_id_fkey
+    FOREIGN KEY (router_id) REFERENCES public.mikrotik_routers(id) ON DELETE CASCADE;
+ALTER TABLE public.mikrotik_alerts
+    ADD CONSTRAINT mikrotik_alerts_router_id_fkey
+    FOREIGN KEY (router_id) REFERENCES public.mikrotik_routers(id) ON DELETE CASCADE;
+
+CREATE OR REPLACE FUNCTION public.tenant_isolated_tables() RETURNS text[]
+LANGUAGE sql
+IMMUTABLE
+AS $$
+    SELECT ARRAY[
+        'customers',
+        'customer_locations',
+        'customer_subscriptions',
+        'customer_users',
+        'customer_service_assignments',
+        'installation_work_orders',
+        'work_order_reschedule_requests',
+        'isp_packages',
+        'isp_package_router_mappings',
+        'mikrotik_routers',
+        'mikrotik_router_metrics',
+        'mikrotik_interface_metrics',
+        'mikrotik_alerts',
+        'mikrotik_incidents',
+        'mikrotik_logs',
+        'mikrotik_ip_pools',
+        'mikrotik_ppp_profiles',
+        'pppoe_profiles',
+        'pppoe_accounts',
+        'network_nodes',
+        'network_links',
+        'service_zones',
+        'zone_node_bindings',
+        'zone_offers'
+    ]::text[]
+$$;
+
+DROP INDEX IF EXISTS public.uq_monitored_devices_router;
+DROP INDEX IF EXISTS public.idx_monitored_devices_tenant;
+DROP TABLE IF EXISTS public.monitored_devices;
@@ -0,0 +1,111 @@
+-- Devices the NOC poller monitors besides RouterOS routers: OLTs, switches and
+-- anything else that answers SNMP. A device can also stand for a MikroTik
+-- router (protocol 'routeros'), which keeps being polled over its API.
+
+CREATE TABLE IF NOT EXISTS public.monitored_devices (
+    id text PRIMARY KEY NOT NULL,
+    tenant_id text NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+    name text NOT NULL,
+    host text NOT NULL,
+    device_type text NOT NULL DEFAULT 'other', -- olt | switch | router | other
+    protocol text NOT NULL,                    -- snmp | routeros
+    router_id text REFERENCES public.mikrotik_routers(id) ON DELETE CASCADE,
+    snmp_version text,                         -- v2c | v3
+    snmp_port integer NOT NULL DEFAULT 161,
+    -- Secrets are encrypted at rest, empty when unused.
+    snmp_community text NOT NULL DEFAULT '',
+    snmp_username text,
+    snmp_auth_protocol text,                   -- md5 | sha1
+    snmp_auth_password text NOT NULL DEFAULT '',
+    snmp_priv_protocol text,                   -- des | aes128
+    snmp_priv_password text NOT NULL DEFAULT '',
+    -- Vendor OID answering the CPU load in percent.
+    cpu_oid text,
+    enabled boolean NOT NULL DEFAULT true,
+    maintenance_until timestamp with time zone,
+    -- Written by the SNMP poller; 'routeros' devices read their router's.
+    is_online boolean NOT NULL DEFAULT false,
+    last_seen_at timestamp with time zone,
+    latency_ms integer,
+    last_error text,
+    sys_name text,
+    sys_descr text,
+    uptime_seconds bigint,
+    cpu_load integer,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT monitored_devices_protocol_check CHECK (
+        (protocol = 'snmp' AND snmp_version IN ('v2c', 'v3') AND router_id IS NULL)
+        OR (protocol = 'routeros' AND router_id IS NOT NULL)
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_monitored_devices_tenant
+    ON public.monitored_devices (tenant_id, name);
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_monitored_devices_router
+    ON public.monitored_devices (router_id)
+    WHERE router_id IS NOT NULL;
+
+-- Isolated tenants keep their devices next to their routers; the table is
+-- created in their schemas on the next sync.
+CREATE OR REPLACE FUNCTION public.tenant_isolated_tables() RETURNS text[]
+LANGUAGE sql
+IMMUTABLE
+AS $$
+    SELECT ARRAY[
+        'customers',
+        'customer_locations',
+        'customer_subscriptions',
+        'customer_users',
+        'customer_service_assignments',
+        'installation_work_orders',
+        'work_order_reschedule_requests',
+        'isp_packages',
+        'isp_package_router_mappings',
+        'mikrotik_routers',
+        'mikrotik_router_metrics',
+        'mikrotik_interface_metrics',
+        'mikrotik_alerts',
+        'mikrotik_incidents',
+        'mikrotik_logs',
+        'mikrotik_ip_pools',
+        'mikrotik_ppp_profiles',
+        'monitored_devices',
+        'pppoe_profiles',
+        'pppoe_accounts',
+        'network_nodes',
+        'network_links',
+        'service_zones',
+        'zone_node_bindings',
+        'zone_offers'
+    ]::text[]
+$$;
+
+-- Alerts and incidents of SNMP devices go through the same pipeline as the
+-- routers': `router_id` holds the device id and `source` says which it is.
+ALTER TABLE public.mikrotik_alerts
+    DROP CONSTRAINT IF EXISTS mikrotik_alerts_router_id_fkey,
+    ADD COLUMN IF NOT EXISTS source text NOT NULL DEFAULT 'routeros';
+
+ALTER TABLE public.mikrotik_incidents
+    DROP CONSTRAINT IF EXISTS mikrotik_incidents_router_id_fkey,
+    ADD COLUMN IF NOT EXISTS source text NOT NULL DEFAULT 'routeros';
+
+-- Isolated tenants carry their own copies of both tables.
+DO $$
+DECLARE
+    s text;
+BEGIN
+    FOR s IN SELECT schema_name FROM public.tenant_schemas LOOP
+        EXECUTE format(
+            'ALTER TABLE %I.mikrotik_alerts DROP CONSTRAINT IF EXISTS mikrotik_alerts_router_id_fkey',
+            s
+        );
+        EXECUTE format(
+            'ALTER TABLE %I.mikrotik_incidents DROP CONSTRAINT IF EXISTS mikrotik_incidents_router_id_fkey',
+            s
+        );
+    END LOOP;
+END;
+$$;
@@ -0,0 +1,32 @@
+-- Traffic per PPPoE account and poll, for usage charts. Each row holds the
+-- bytes an account moved since the previous poll, seen from the subscriber
+-- like `pppoe_usage_periods`, and the customer the account belonged to at
+-- the time. Partitioned by month like the other time-series tables;
+-- `usage_records_retention_days` decides how many months are kept.
+
+CREATE TABLE IF NOT EXISTS public.usage_records (
+    id text NOT NULL,
+    tenant_id text NOT NULL,
+    account_id text NOT NULL,
+    customer_id text NOT NULL,
+    ts timestamptz NOT NULL,
+    upload_bytes bigint NOT NULL DEFAULT 0,
+    download_bytes bigint NOT NULL DEFAULT 0
+) PARTITION BY RANGE (ts);
+
+SELECT public.ensure_monthly_partitions('usage_records', 'ts', now(), 2);
+CREATE TABLE IF NOT EXISTS public.usage_records_default
+    PARTITION OF public.usage_records DEFAULT;
+
+ALTER TABLE public.usage_records ADD CONSTRAINT usage_records_pkey PRIMARY KEY (id, ts);
+ALTER TABLE public.usage_records
+    ADD CONSTRAINT usage_records_tenant_id_fkey
+    FOREIGN KEY (tenant_id) REFERENCES public.tenants(id) ON DELETE CASCADE;
+ALTER TABLE public.usage_records
+    ADD CONSTRAINT usage_records_account_id_fkey
+    FOREIGN KEY (account_id) REFERENCES public.pppoe_accounts(id) ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS idx_usage_records_customer_ts
+    ON public.usage_records (tenant_id, customer_id, ts DESC);
+
+-- Quotas are now checked on every usage poll; the daily job is gone.
+DELETE FROM public.background_jobs WHERE job_type = 'pppoe.fup_enforce';
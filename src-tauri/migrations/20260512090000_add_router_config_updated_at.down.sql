@@ -0,0 +1 @@
+ALTER TABLE public.mikrotik_routers DROP COLUMN IF EXISTS config_updated_at;
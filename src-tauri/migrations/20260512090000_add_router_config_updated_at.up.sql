@@ -0,0 +1,10 @@
+-- The poller touches mikrotik_routers.updated_at on every pass, so edits are
+-- guarded by a timestamp only user edits move.
+ALTER TABLE public.mikrotik_routers
+    ADD COLUMN IF NOT EXISTS config_updated_at timestamp with time zone;
+
+UPDATE public.mikrotik_routers SET config_updated_at = updated_at WHERE config_updated_at IS NULL;
+
+ALTER TABLE public.mikrotik_routers
+    ALTER COLUMN config_updated_at SET DEFAULT now(),
+    ALTER COLUMN config_updated_at SET NOT NULL;
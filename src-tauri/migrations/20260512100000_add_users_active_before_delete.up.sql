@@ -0,0 +1,3 @@
+-- Whether a trashed user was active when deleted, so restoring does not
+-- reactivate an account an admin had deactivated.
+ALTER TABLE public.users ADD COLUMN IF NOT EXISTS active_before_delete boolean;
@@ -0,0 +1,24 @@
+DROP TABLE IF EXISTS trusted_devices;
+DROP TABLE IF EXISTS push_subscriptions;
+DROP TABLE IF EXISTS notification_preferences;
+DROP TABLE IF EXISTS notifications;
+DROP TABLE IF EXISTS fx_rates;
+DROP TABLE IF EXISTS bank_accounts;
+DROP TABLE IF EXISTS customer_registration_invites;
+DROP TABLE IF EXISTS billing_collection_logs;
+DROP TABLE IF EXISTS invoice_reminder_logs;
+DROP TABLE IF EXISTS invoices;
+DROP TABLE IF EXISTS file_records;
+DROP TABLE IF EXISTS tenant_subscriptions;
+DROP TABLE IF EXISTS plan_features;
+DROP TABLE IF EXISTS feature_definitions;
+DROP TABLE IF EXISTS plans;
+DROP TABLE IF EXISTS role_permissions;
+DROP TABLE IF EXISTS roles;
+DROP TABLE IF EXISTS permissions;
+DROP TABLE IF EXISTS sessions;
+DROP TABLE IF EXISTS settings;
+DROP TABLE IF EXISTS tenant_members;
+DROP TABLE IF EXISTS user_addresses;
+DROP TABLE IF EXISTS users;
+DROP TABLE IF EXISTS tenants;
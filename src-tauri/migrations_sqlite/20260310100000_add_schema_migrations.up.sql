@@ -0,0 +1,8 @@
+-- Release-level ledger of applied migrations (sqlx keeps its own in _sqlx_migrations).
+CREATE TABLE IF NOT EXISTS schema_migrations (
+    version INTEGER PRIMARY KEY NOT NULL,
+    description TEXT NOT NULL,
+    checksum TEXT NOT NULL,
+    app_version TEXT NOT NULL,
+    applied_at TEXT NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Soft delete (SQLite mode only ships the users table of the soft-deletable resources).
+ALTER TABLE users ADD COLUMN deleted_at TEXT;
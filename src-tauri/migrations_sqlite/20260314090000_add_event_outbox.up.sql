@@ -0,0 +1,19 @@
+-- Event outbox: WebSocket/webhook events written in the same transaction as the
+-- change that caused them, then delivered at-least-once by the dispatcher.
+CREATE TABLE IF NOT EXISTS event_outbox (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT,
+  event_type TEXT NOT NULL,
+  payload TEXT NOT NULL,
+  status TEXT NOT NULL DEFAULT 'pending',
+  attempts INTEGER NOT NULL DEFAULT 0,
+  next_attempt_at TEXT NOT NULL,
+  ws_delivered_at TEXT,
+  last_error TEXT,
+  delivered_at TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_outbox_status_next
+  ON event_outbox (status, next_attempt_at);
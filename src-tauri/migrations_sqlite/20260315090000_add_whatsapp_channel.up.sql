@@ -0,0 +1,36 @@
+-- WhatsApp notification channel: message templates per tenant and a delivery log
+-- that provider status callbacks update (sent -> delivered -> read, or failed).
+CREATE TABLE IF NOT EXISTS whatsapp_templates (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  language TEXT NOT NULL DEFAULT 'id',
+  body TEXT NOT NULL,
+  is_active INTEGER NOT NULL DEFAULT 1,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_whatsapp_templates_tenant_name_lang
+  ON whatsapp_templates (tenant_id, name, language);
+
+CREATE TABLE IF NOT EXISTS whatsapp_messages (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL,
+  event TEXT NOT NULL,
+  template_name TEXT NOT NULL,
+  to_phone TEXT NOT NULL,
+  customer_id TEXT,
+  provider TEXT NOT NULL,
+  provider_message_id TEXT,
+  status TEXT NOT NULL DEFAULT 'pending',
+  error TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_whatsapp_messages_tenant_created
+  ON whatsapp_messages (tenant_id, created_at);
+
+CREATE INDEX IF NOT EXISTS idx_whatsapp_messages_provider_id
+  ON whatsapp_messages (provider_message_id);
@@ -0,0 +1,27 @@
+-- Telegram bot: tenant members link a chat with a one-time code (`/start <code>`),
+-- then receive incident/payment alerts there and can run a few bot commands.
+CREATE TABLE IF NOT EXISTS telegram_links (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  chat_id TEXT NULL,
+  telegram_username TEXT NULL,
+  link_code TEXT NULL,
+  link_code_expires_at TEXT NULL,
+  alert_incidents INTEGER NOT NULL DEFAULT 1,
+  alert_payments INTEGER NOT NULL DEFAULT 1,
+  linked_at TEXT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_telegram_links_tenant_user
+  ON telegram_links (tenant_id, user_id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_telegram_links_code
+  ON telegram_links (link_code)
+  WHERE link_code IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_telegram_links_chat
+  ON telegram_links (chat_id)
+  WHERE chat_id IS NOT NULL;
@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS idx_push_subscriptions_user_id;
+
+ALTER TABLE push_subscriptions DROP COLUMN updated_at;
+ALTER TABLE push_subscriptions DROP COLUMN last_success_at;
+ALTER TABLE push_subscriptions DROP COLUMN failure_count;
+ALTER TABLE push_subscriptions DROP COLUMN vapid_public_key;
+ALTER TABLE push_subscriptions DROP COLUMN expiration_time;
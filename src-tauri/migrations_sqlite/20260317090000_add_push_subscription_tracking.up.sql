@@ -0,0 +1,10 @@
+-- Web Push: remember the key each browser subscribed under, its expiry and delivery health,
+-- so stale or repeatedly failing subscriptions can be dropped.
+ALTER TABLE push_subscriptions ADD COLUMN expiration_time TEXT;
+ALTER TABLE push_subscriptions ADD COLUMN vapid_public_key TEXT;
+ALTER TABLE push_subscriptions ADD COLUMN failure_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE push_subscriptions ADD COLUMN last_success_at TEXT;
+ALTER TABLE push_subscriptions ADD COLUMN updated_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_push_subscriptions_user_id
+  ON push_subscriptions (user_id);
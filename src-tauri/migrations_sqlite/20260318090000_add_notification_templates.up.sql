@@ -0,0 +1,16 @@
+-- Per-tenant overrides of the built-in notification texts. A code without a row
+-- here renders the default compiled into the server.
+
+CREATE TABLE IF NOT EXISTS notification_templates (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL,
+  code TEXT NOT NULL,
+  title TEXT NOT NULL,
+  body TEXT NOT NULL,
+  updated_by TEXT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_notification_templates_tenant_code
+  ON notification_templates (tenant_id, code);
@@ -0,0 +1,21 @@
+-- Per-tenant alert routing: match (category, minimum severity, business hours)
+-- and send to specific channels/recipients instead of the producer's defaults.
+
+CREATE TABLE IF NOT EXISTS notification_routing_rules (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  priority INTEGER NOT NULL DEFAULT 100,
+  is_enabled INTEGER NOT NULL DEFAULT 1,
+  category TEXT NULL,
+  min_severity TEXT NOT NULL DEFAULT 'info',
+  time_window TEXT NOT NULL DEFAULT 'any',
+  channels TEXT NOT NULL,
+  recipients TEXT NOT NULL DEFAULT 'default',
+  created_by TEXT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_routing_rules_tenant
+  ON notification_routing_rules (tenant_id, priority);
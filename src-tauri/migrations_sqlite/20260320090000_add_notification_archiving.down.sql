@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_notifications_user_created;
+
+ALTER TABLE notifications DROP COLUMN archived_at;
@@ -0,0 +1,24 @@
+-- Per-user quiet hours: non-critical email/push is held until the window ends.
+-- `timezone` NULL means the tenant's app_timezone.
+
+CREATE TABLE IF NOT EXISTS notification_quiet_hours (
+  user_id TEXT PRIMARY KEY,
+  is_enabled INTEGER NOT NULL DEFAULT 0,
+  start_time TEXT NOT NULL DEFAULT '22:00',
+  end_time TEXT NOT NULL DEFAULT '07:00',
+  timezone TEXT NULL,
+  updated_at TEXT NOT NULL
+);
+
+-- Channels held back by quiet hours, sent by the deferred delivery worker.
+CREATE TABLE IF NOT EXISTS notification_deferred_deliveries (
+  id TEXT PRIMARY KEY,
+  notification_id TEXT NOT NULL,
+  user_id TEXT NOT NULL,
+  channels TEXT NOT NULL,
+  deliver_after TEXT NOT NULL,
+  created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deferred_deliveries_due
+  ON notification_deferred_deliveries (deliver_after);
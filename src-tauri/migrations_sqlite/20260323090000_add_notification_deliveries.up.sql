@@ -0,0 +1,25 @@
+-- Per-notification, per-channel delivery status for the admin delivery report.
+-- Kept independently of notifications; purged with notification retention.
+
+CREATE TABLE IF NOT EXISTS notification_deliveries (
+  id TEXT PRIMARY KEY,
+  notification_id TEXT NOT NULL,
+  tenant_id TEXT NULL,
+  user_id TEXT NOT NULL,
+  category TEXT NOT NULL,
+  notification_type TEXT NOT NULL,
+  title TEXT NOT NULL,
+  channel TEXT NOT NULL,
+  status TEXT NOT NULL,
+  error TEXT NULL,
+  email_outbox_id TEXT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  UNIQUE (notification_id, channel)
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_tenant_created
+  ON notification_deliveries (tenant_id, created_at);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_outbox
+  ON notification_deliveries (email_outbox_id);
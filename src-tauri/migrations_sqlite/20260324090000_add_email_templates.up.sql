@@ -0,0 +1,18 @@
+-- Per-tenant HTML email templates (Handlebars-style `{{var}}` placeholders).
+-- An email type without a row here is sent with the default compiled into the
+-- server.
+
+CREATE TABLE IF NOT EXISTS email_templates (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL,
+  code TEXT NOT NULL,
+  subject TEXT NOT NULL,
+  body_html TEXT NOT NULL,
+  body_text TEXT NULL,
+  updated_by TEXT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_email_templates_tenant_code
+  ON email_templates (tenant_id, code);
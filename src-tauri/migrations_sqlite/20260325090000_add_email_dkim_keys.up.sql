@@ -0,0 +1,14 @@
+-- Per-tenant DKIM signing keys for outbound SMTP email. The private key is
+-- encrypted with the app secret; the public key is what goes into DNS.
+
+CREATE TABLE IF NOT EXISTS email_dkim_keys (
+  tenant_id TEXT PRIMARY KEY,
+  domain TEXT NOT NULL,
+  selector TEXT NOT NULL,
+  private_key_enc TEXT NOT NULL,
+  public_key TEXT NOT NULL,
+  is_enabled INTEGER NOT NULL DEFAULT 1,
+  created_by TEXT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
@@ -0,0 +1,23 @@
+-- Addresses that bounced or complained, per tenant (NULL = mail sent with the
+-- global email settings). Soft bounces are counted and only suppress once they
+-- repeat; hard bounces, complaints and manual entries suppress right away.
+
+CREATE TABLE IF NOT EXISTS email_suppressions (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NULL,
+  email TEXT NOT NULL,
+  reason TEXT NOT NULL,
+  source TEXT NOT NULL,
+  detail TEXT NULL,
+  bounce_count INTEGER NOT NULL DEFAULT 1,
+  is_suppressed INTEGER NOT NULL DEFAULT 1,
+  last_event_at TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_email_suppressions_tenant_email
+  ON email_suppressions (COALESCE(tenant_id, ''), email);
+
+CREATE INDEX IF NOT EXISTS idx_email_suppressions_tenant_event
+  ON email_suppressions (tenant_id, last_event_at DESC);
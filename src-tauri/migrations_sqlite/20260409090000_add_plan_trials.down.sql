@@ -0,0 +1,3 @@
+ALTER TABLE tenant_subscriptions DROP COLUMN trial_notice_days;
+ALTER TABLE plans DROP COLUMN trial_expiry_action;
+ALTER TABLE plans DROP COLUMN trial_days;
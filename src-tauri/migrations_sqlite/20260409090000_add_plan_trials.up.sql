@@ -0,0 +1,5 @@
+-- Free trials per plan; see the postgres migration for the semantics.
+
+ALTER TABLE plans ADD COLUMN trial_days INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE plans ADD COLUMN trial_expiry_action TEXT NOT NULL DEFAULT 'downgrade';
+ALTER TABLE tenant_subscriptions ADD COLUMN trial_notice_days INTEGER NULL;
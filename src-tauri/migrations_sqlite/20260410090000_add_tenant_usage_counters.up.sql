@@ -0,0 +1,11 @@
+-- Metered usage that cannot be counted from existing rows (emails per month).
+
+CREATE TABLE IF NOT EXISTS tenant_usage_counters (
+  tenant_id TEXT NOT NULL,
+  metric TEXT NOT NULL,
+  period TEXT NOT NULL,
+  value INTEGER NOT NULL DEFAULT 0,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY (tenant_id, metric, period),
+  FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
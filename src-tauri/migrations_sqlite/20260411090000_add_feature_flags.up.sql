@@ -0,0 +1,23 @@
+-- Operational feature flags (kill switch, rollout percentage, tenant overrides).
+
+CREATE TABLE IF NOT EXISTS feature_flags (
+  key TEXT PRIMARY KEY,
+  description TEXT,
+  enabled INTEGER NOT NULL DEFAULT 1,
+  rollout_percentage INTEGER NOT NULL DEFAULT 100 CHECK (rollout_percentage BETWEEN 0 AND 100),
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS feature_flag_overrides (
+  flag_key TEXT NOT NULL,
+  tenant_id TEXT NOT NULL,
+  enabled INTEGER NOT NULL,
+  created_at TEXT NOT NULL,
+  PRIMARY KEY (flag_key, tenant_id),
+  FOREIGN KEY (flag_key) REFERENCES feature_flags(key) ON DELETE CASCADE,
+  FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
+
+INSERT OR IGNORE INTO feature_flags (key, description, enabled, rollout_percentage, created_at, updated_at)
+VALUES ('plan_self_serve_upgrade', 'Tenants can check out plan upgrades themselves', 1, 100, datetime('now'), datetime('now'));
@@ -0,0 +1,4 @@
+-- User locale preference; announcements (and their translations) are
+-- postgres-only, see the postgres migration.
+
+ALTER TABLE users ADD COLUMN locale TEXT NULL;
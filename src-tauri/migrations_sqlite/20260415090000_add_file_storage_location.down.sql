@@ -0,0 +1,2 @@
+ALTER TABLE file_records DROP COLUMN quota_counted;
+ALTER TABLE file_records DROP COLUMN storage_bucket;
@@ -0,0 +1,7 @@
+-- Bucket of S3-stored files and whether a file counts against the tenant's
+-- storage quota; see the postgres migration.
+
+ALTER TABLE file_records ADD COLUMN storage_bucket TEXT NULL;
+ALTER TABLE file_records ADD COLUMN quota_counted BOOLEAN NOT NULL DEFAULT 1;
+
+UPDATE file_records SET quota_counted = 0 WHERE storage_provider IN ('s3', 'r2');
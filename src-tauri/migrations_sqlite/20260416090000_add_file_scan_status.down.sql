@@ -0,0 +1,3 @@
+ALTER TABLE file_records DROP COLUMN scanned_at;
+ALTER TABLE file_records DROP COLUMN scan_signature;
+ALTER TABLE file_records DROP COLUMN scan_status;
@@ -0,0 +1,5 @@
+-- Malware scan results of uploaded files; see the postgres migration.
+
+ALTER TABLE file_records ADD COLUMN scan_status TEXT NOT NULL DEFAULT 'unscanned';
+ALTER TABLE file_records ADD COLUMN scan_signature TEXT NULL;
+ALTER TABLE file_records ADD COLUMN scanned_at TEXT NULL;
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS file_versions;
+DROP INDEX IF EXISTS idx_file_records_logical_path;
+ALTER TABLE file_records DROP COLUMN version;
+ALTER TABLE file_records DROP COLUMN logical_path;
@@ -0,0 +1,31 @@
+-- Versions of uploaded files; see the postgres migration.
+
+ALTER TABLE file_records ADD COLUMN logical_path TEXT NULL;
+ALTER TABLE file_records ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_file_records_logical_path
+  ON file_records (tenant_id, logical_path)
+  WHERE logical_path IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS file_versions (
+  id TEXT PRIMARY KEY,
+  file_id TEXT NOT NULL,
+  tenant_id TEXT NOT NULL,
+  version INTEGER NOT NULL,
+  name TEXT NOT NULL,
+  original_name TEXT NOT NULL,
+  path TEXT NOT NULL,
+  size INTEGER NOT NULL,
+  content_type TEXT NOT NULL,
+  storage_provider TEXT NOT NULL,
+  storage_bucket TEXT NULL,
+  quota_counted INTEGER NOT NULL DEFAULT 1,
+  scan_status TEXT NOT NULL DEFAULT 'unscanned',
+  scan_signature TEXT NULL,
+  scanned_at TEXT NULL,
+  uploaded_by TEXT NULL,
+  created_at TEXT NOT NULL,
+  replaced_at TEXT NOT NULL,
+  UNIQUE (file_id, version),
+  FOREIGN KEY (file_id) REFERENCES file_records(id) ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS storage_cleanup_rules;
+ALTER TABLE tenants DROP COLUMN storage_alert_threshold;
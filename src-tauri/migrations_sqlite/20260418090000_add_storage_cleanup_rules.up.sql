@@ -0,0 +1,23 @@
+-- Storage quota alerts and cleanup rules; see the postgres migration.
+
+ALTER TABLE tenants ADD COLUMN storage_alert_threshold INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS storage_cleanup_rules (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  name_pattern TEXT NULL,
+  content_type TEXT NULL,
+  older_than_days INTEGER NOT NULL,
+  enabled INTEGER NOT NULL DEFAULT 1,
+  last_run_at TEXT NULL,
+  last_deleted_count INTEGER NOT NULL DEFAULT 0,
+  last_deleted_bytes INTEGER NOT NULL DEFAULT 0,
+  created_by TEXT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_storage_cleanup_rules_tenant
+  ON storage_cleanup_rules (tenant_id);
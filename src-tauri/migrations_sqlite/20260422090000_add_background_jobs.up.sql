@@ -0,0 +1,30 @@
+-- Background job queue; see the postgres migration.
+
+CREATE TABLE IF NOT EXISTS background_jobs (
+  id TEXT PRIMARY KEY,
+  job_type TEXT NOT NULL,
+  payload TEXT NOT NULL DEFAULT '{}',
+  status TEXT NOT NULL DEFAULT 'queued'
+    CHECK (status IN ('queued', 'running', 'succeeded', 'failed', 'dead')),
+  recurring_secs INTEGER NULL,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  max_attempts INTEGER NOT NULL DEFAULT 5,
+  run_at TEXT NOT NULL,
+  locked_at TEXT NULL,
+  locked_by TEXT NULL,
+  last_error TEXT NULL,
+  last_run_at TEXT NULL,
+  finished_at TEXT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_background_jobs_due
+  ON background_jobs (status, run_at);
+
+CREATE INDEX IF NOT EXISTS idx_background_jobs_type
+  ON background_jobs (job_type, created_at);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_background_jobs_recurring
+  ON background_jobs (job_type)
+  WHERE recurring_secs IS NOT NULL;
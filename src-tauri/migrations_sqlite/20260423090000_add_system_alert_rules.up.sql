@@ -0,0 +1,46 @@
+-- Deployment-wide alert rules; see the postgres migration.
+
+CREATE TABLE IF NOT EXISTS system_alert_rules (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  metric TEXT NOT NULL CHECK (metric IN (
+    'error_rate', 'latency_p95', 'rate_limited', 'db_pool_saturation',
+    'disk_usage', 'outbox_backlog'
+  )),
+  threshold REAL NOT NULL,
+  channels TEXT NOT NULL DEFAULT '["email"]',
+  cooldown_minutes INTEGER NOT NULL DEFAULT 15 CHECK (cooldown_minutes >= 1),
+  enabled INTEGER NOT NULL DEFAULT 1,
+  last_triggered_at TEXT NULL,
+  last_value REAL NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+INSERT OR IGNORE INTO system_alert_rules
+  (id, name, metric, threshold, channels, cooldown_minutes, enabled, created_at, updated_at)
+SELECT d.id, d.name, d.metric,
+       COALESCE(
+         (SELECT CAST(trim(value) AS REAL) FROM settings
+           WHERE tenant_id IS NULL AND key = d.legacy_key AND CAST(trim(value) AS REAL) > 0),
+         d.threshold
+       ),
+       '["email"]',
+       COALESCE(
+         (SELECT CAST(trim(value) AS INTEGER) FROM settings
+           WHERE tenant_id IS NULL AND key = 'alerting_cooldown_minutes'
+             AND CAST(trim(value) AS INTEGER) >= 1),
+         15
+       ),
+       1, datetime('now'), datetime('now')
+FROM (
+  SELECT 'alert_error_rate' AS id, 'High error rate' AS name, 'error_rate' AS metric,
+         5.0 AS threshold, 'alerting_error_threshold' AS legacy_key
+  UNION ALL SELECT 'alert_latency_p95', 'Slow responses', 'latency_p95', 3000.0,
+         'alerting_response_time_threshold'
+  UNION ALL SELECT 'alert_rate_limited', 'Rate limiting spike', 'rate_limited', 50.0,
+         'alerting_rate_limit_threshold'
+  UNION ALL SELECT 'alert_db_pool', 'Database pool saturation', 'db_pool_saturation', 90.0, NULL
+  UNION ALL SELECT 'alert_disk_usage', 'Disk almost full', 'disk_usage', 90.0, NULL
+  UNION ALL SELECT 'alert_outbox_backlog', 'Email outbox backlog', 'outbox_backlog', 500.0, NULL
+) AS d;
@@ -0,0 +1,53 @@
+-- Per-tenant public status page; see the postgres migration.
+
+CREATE TABLE IF NOT EXISTS status_components (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  description TEXT NULL,
+  kind TEXT NOT NULL DEFAULT 'custom' CHECK (kind IN ('api', 'billing', 'network', 'custom')),
+  router_ids TEXT NOT NULL DEFAULT '',
+  status_override TEXT NULL CHECK (status_override IS NULL
+    OR status_override IN ('operational', 'maintenance', 'degraded', 'partial_outage', 'major_outage')),
+  position INTEGER NOT NULL DEFAULT 0,
+  is_visible INTEGER NOT NULL DEFAULT 1,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_status_components_tenant
+  ON status_components (tenant_id, position);
+
+CREATE TABLE IF NOT EXISTS status_posts (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+  kind TEXT NOT NULL CHECK (kind IN ('incident', 'maintenance')),
+  title TEXT NOT NULL,
+  impact TEXT NOT NULL DEFAULT 'minor' CHECK (impact IN ('none', 'minor', 'major', 'critical')),
+  status TEXT NOT NULL CHECK (status IN (
+    'investigating', 'identified', 'monitoring', 'resolved',
+    'scheduled', 'in_progress', 'completed'
+  )),
+  component_ids TEXT NOT NULL DEFAULT '',
+  scheduled_start TEXT NULL,
+  scheduled_end TEXT NULL,
+  resolved_at TEXT NULL,
+  created_by TEXT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_status_posts_tenant
+  ON status_posts (tenant_id, resolved_at, created_at);
+
+CREATE TABLE IF NOT EXISTS status_post_updates (
+  id TEXT PRIMARY KEY,
+  post_id TEXT NOT NULL REFERENCES status_posts(id) ON DELETE CASCADE,
+  status TEXT NOT NULL,
+  message TEXT NOT NULL,
+  created_by TEXT NULL,
+  created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_status_post_updates_post
+  ON status_post_updates (post_id, created_at);
@@ -0,0 +1,27 @@
+-- Scheduled report delivery; see the postgres migration. The email outbox is
+-- postgres-only, so reports are sent directly here.
+
+CREATE TABLE IF NOT EXISTS report_schedules (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  report TEXT NOT NULL CHECK (report IN ('revenue', 'aging', 'sla', 'incidents')),
+  format TEXT NOT NULL DEFAULT 'pdf' CHECK (format IN ('csv', 'pdf')),
+  cadence TEXT NOT NULL DEFAULT 'monthly' CHECK (cadence IN ('daily', 'weekly', 'monthly')),
+  send_hour INTEGER NOT NULL DEFAULT 7 CHECK (send_hour BETWEEN 0 AND 23),
+  recipients TEXT NOT NULL DEFAULT '[]',
+  enabled INTEGER NOT NULL DEFAULT 1,
+  next_run_at TEXT NOT NULL,
+  last_run_at TEXT NULL,
+  last_status TEXT NULL CHECK (last_status IS NULL OR last_status IN ('sent', 'failed')),
+  last_error TEXT NULL,
+  created_by TEXT NULL REFERENCES users(id) ON DELETE SET NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_report_schedules_tenant
+  ON report_schedules (tenant_id, created_at);
+
+CREATE INDEX IF NOT EXISTS idx_report_schedules_due
+  ON report_schedules (enabled, next_run_at);
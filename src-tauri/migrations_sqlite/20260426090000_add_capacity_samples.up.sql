@@ -0,0 +1,11 @@
+-- Capacity samples for growth projections; see the postgres migration.
+
+CREATE TABLE IF NOT EXISTS system_capacity_samples (
+  id TEXT PRIMARY KEY,
+  sampled_at TEXT NOT NULL,
+  database_bytes INTEGER NOT NULL,
+  disk_available_bytes INTEGER NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_system_capacity_samples_sampled_at
+  ON system_capacity_samples (sampled_at);
@@ -0,0 +1,2 @@
+-- Whether a trashed user was active when deleted (see the Postgres migration).
+ALTER TABLE users ADD COLUMN active_before_delete INTEGER;
@@ -0,0 +1,21 @@
+-- Idempotency-Key reservations and stored responses; see the postgres migration.
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+  id TEXT PRIMARY KEY,
+  scope TEXT NOT NULL,
+  idempotency_key TEXT NOT NULL,
+  request_method TEXT NOT NULL,
+  request_path TEXT NOT NULL,
+  request_hash TEXT NOT NULL,
+  status TEXT NOT NULL DEFAULT 'in_progress' CHECK (status IN ('in_progress', 'completed')),
+  response_status INTEGER NULL,
+  response_content_type TEXT NULL,
+  response_body BLOB NULL,
+  created_at TEXT NOT NULL,
+  completed_at TEXT NULL,
+  expires_at TEXT NOT NULL,
+  UNIQUE (scope, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_expires
+  ON idempotency_keys (expires_at);
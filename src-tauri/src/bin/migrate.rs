@@ -2,9 +2,14 @@
 //!
 //! This binary is intentionally small so it can be used in dev/CI without
 //! pulling in the full server/Tauri runtime.
+//!
+//! Usage: `migrate` applies pending migrations; `migrate status` prints the
+//! migration status as JSON without changing the database.
 
 use std::env;
 
+#[cfg(feature = "postgres")]
+use saas_tauri_lib::db::migrations;
 #[cfg(feature = "postgres")]
 use sqlx::PgPool;
 
@@ -51,8 +56,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         let pool = PgPool::connect(&database_url).await?;
 
-        static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations");
-        MIGRATOR.run(&pool).await?;
+        if env::args().nth(1).as_deref() == Some("status") {
+            let status = migrations::migration_status(&pool).await?;
+            println!("{}", serde_json::to_string_pretty(&status)?);
+            if !status.up_to_date {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+
+        migrations::run_migrations(&pool).await?;
 
         println!("Migrations applied successfully.");
     }
@@ -8,7 +8,7 @@ fn parse_args() -> SeedOptions {
     let mut opts = SeedOptions::default();
 
     // Usage:
-    //   seed [dev|prod] [--email x] [--password y] [--name z] [--tenant-name n] [--tenant-slug s] [--tz Asia/Jakarta]
+    //   seed [dev|prod|demo] [--customers 300] [--email x] [--password y] [--name z] [--tenant-name n] [--tenant-slug s] [--tz Asia/Jakarta]
     let argv: Vec<String> = env::args().skip(1).collect();
     let mut i = 0usize;
     if let Some(first) = argv.first() {
@@ -18,6 +18,9 @@ fn parse_args() -> SeedOptions {
         } else if first == "prod" {
             opts.mode = SeedMode::Prod;
             i = 1;
+        } else if first == "demo" {
+            opts.mode = SeedMode::Demo;
+            i = 1;
         }
     }
 
@@ -50,6 +53,11 @@ fn parse_args() -> SeedOptions {
                     opts.tenant_slug = v;
                 }
             }
+            "--customers" => {
+                if let Some(v) = it.next().and_then(|v| v.parse().ok()) {
+                    opts.demo_customers = v;
+                }
+            }
             "--tz" | "--timezone" => {
                 if let Some(v) = it.next() {
                     opts.app_timezone = v;
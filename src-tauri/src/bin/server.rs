@@ -1,187 +1,147 @@
-use saas_tauri_lib::{
-    db::connection::{init_db, seed_defaults},
-    http::{self, WsHub},
-    services::backup::BackupScheduler,
-    services::{
-        metrics_service::MetricsService, AnnouncementScheduler, AuditService, AuthService,
-        BackupService, CustomerService, EmailOutboxService, EmailService, IspPackageService,
-        MikrotikService, NetworkMappingService, NotificationService, PaymentService, PlanService,
-        PppoeService, RoleService, SettingsService, StorageService, SystemService, TeamService,
-        UserService,
-    },
-};
+//! Headless server: database, background jobs and the HTTP API without the
+//! webview, for a Linux VPS that desktop apps connect to as remote clients.
+//!
+//! Run `server --help` for the flags; each falls back to an environment variable.
+//! SIGTERM stops accepting connections, drains open requests and exits, so the
+//! binary runs cleanly under Docker, Kubernetes or systemd.
+
+use saas_tauri_lib::bootstrap::{self, LogFormat, ServerCommand, ServerConfig};
+use saas_tauri_lib::http::ServerControl;
 use std::env;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::time::Duration;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // 1. Initialize Logging
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // `--env-file` has to be loaded before the other flags fall back to the
+    // environment, so look for it first without consulting the environment.
+    let env_file = match ServerConfig::from_args(args.clone(), |_| None) {
+        Ok(ServerCommand::Run(config)) => config.env_file,
+        _ => None,
+    };
+    let loaded = match &env_file {
+        Some(path) => dotenvy::from_path(path).map_err(|e| format!("{:?}: {}", path, e)),
+        None => {
+            dotenvy::dotenv().ok();
+            Ok(())
+        }
+    };
+
+    let config = match ServerConfig::from_args(args, |key| env::var(key).ok()) {
+        Ok(ServerCommand::Run(config)) => config,
+        Ok(ServerCommand::Help) => {
+            println!("{}", bootstrap::SERVER_USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, bootstrap::SERVER_USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = loaded {
+        eprintln!("Failed to load env file {}", e);
+        std::process::exit(2);
+    }
+    // init_db reads the connection string from the environment. Set it before
+    // the runtime spawns any threads.
+    if let Some(url) = &config.database_url {
+        env::set_var("DATABASE_URL", url);
+    }
+
+    let json_logs = config.log_format == LogFormat::Json;
     tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
+        .with((!json_logs).then(tracing_subscriber::fmt::layer))
+        .with(json_logs.then(|| tracing_subscriber::fmt::layer().json().flatten_event(true)))
         .with(
             tracing_subscriber::EnvFilter::from_default_env()
                 .add_directive("info".parse().unwrap()),
         )
         .init();
 
-    info!("Starting SaaS Standalone Server...");
-
-    // 2. Load .env
-    dotenvy::dotenv().ok();
-
-    // 3. Database Setup
-    // For the server, we don't use AppData. We use the current directory or a configured path.
-    // However, init_db expects a "base path" to create the SQLite DB file IF using SQLite.
-    // But for a VPS, you typically use Postgres.
-    // If Postgres is used, the path argument to init_db might be ignored for the DB connection itself,
-    // but it might be used for other file storage?
-    // Let's check init_db implementation later if needed. For now, we pass current dir.
-    let app_data_dir = env::var("APP_DATA_DIR")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
-    std::fs::create_dir_all(&app_data_dir)?;
-
-    info!("Initializing database connection...");
-    let pool = init_db(app_data_dir.clone()).await?;
-    info!("Database initialized.");
-
-    // 4. Seed Defaults
-    seed_defaults(&pool).await?;
-
-    // 5. Initialize Services (Copied logic from lib.rs)
-    let plan_service = PlanService::new(pool.clone());
-    let audit_service = AuditService::new(pool.clone(), Some(plan_service.clone()));
-    let role_service = RoleService::new(pool.clone(), audit_service.clone());
-
-    // Seed RBAC
-    role_service.seed_permissions().await?;
-    role_service.seed_roles().await?;
-
-    // JWT Secret
-    let jwt_secret = sqlx::query_scalar::<_, String>(
-        "SELECT value FROM settings WHERE key = 'jwt_secret' AND tenant_id IS NULL",
-    )
-    .fetch_one(&pool)
-    .await
-    .unwrap_or_else(|_| uuid::Uuid::new_v4().to_string());
-
-    let settings_service = SettingsService::new(pool.clone(), audit_service.clone());
-    let email_service = EmailService::new(settings_service.clone());
-    let auth_service = AuthService::new(
-        pool.clone(),
-        jwt_secret,
-        email_service.clone(),
-        audit_service.clone(),
-        settings_service.clone(),
-    );
-    let user_service = UserService::new(pool.clone(), audit_service.clone());
-    let pppoe_service = PppoeService::new(
-        pool.clone(),
-        auth_service.clone(),
-        audit_service.clone(),
-        settings_service.clone(),
-    );
-    let isp_package_service =
-        IspPackageService::new(pool.clone(), auth_service.clone(), audit_service.clone());
-    let network_mapping_service = NetworkMappingService::new(pool.clone(), auth_service.clone());
-    let team_service = TeamService::new(
-        pool.clone(),
-        auth_service.clone(),
-        audit_service.clone(),
-        plan_service.clone(),
-    );
-    let metrics_service = Arc::new(MetricsService::new());
-    let system_service = SystemService::new(pool.clone(), metrics_service.clone());
-    // Use a specific "storage" folder for uploads on the server
-    let storage_dir = app_data_dir.join("storage");
-    if !storage_dir.exists() {
-        std::fs::create_dir_all(&storage_dir)?;
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to start the async runtime");
+    if let Err(e) = runtime.block_on(serve(config)) {
+        tracing::error!("{}", e);
+        std::process::exit(1);
     }
-    let storage_service = StorageService::new(pool.clone(), plan_service.clone(), storage_dir);
-
-    let ws_hub = Arc::new(WsHub::new());
-    let email_outbox_service = EmailOutboxService::new(
-        pool.clone(),
-        settings_service.clone(),
-        email_service.clone(),
-    );
-    email_outbox_service.start_sender().await;
-    let notification_service =
-        NotificationService::new(pool.clone(), ws_hub.clone(), email_outbox_service.clone());
-    let customer_service = CustomerService::new(
-        pool.clone(),
-        auth_service.clone(),
-        audit_service.clone(),
-        notification_service.clone(),
-        pppoe_service.clone(),
-        user_service.clone(),
-    );
-    customer_service.start_installation_sla_scheduler();
-    let payment_service = PaymentService::new(
-        pool.clone(),
-        notification_service.clone(),
-        pppoe_service.clone(),
-    );
-    payment_service.start_customer_invoice_scheduler();
-    let backup_service = BackupService::new(pool.clone(), app_data_dir.clone());
+}
 
-    // MikroTik monitoring (tenant-scoped)
-    let mikrotik_service = MikrotikService::new(
-        pool.clone(),
-        notification_service.clone(),
-        audit_service.clone(),
-        settings_service.clone(),
-    );
-    Arc::new(mikrotik_service.clone()).start_poller();
+async fn serve(config: ServerConfig) -> Result<(), String> {
+    info!("Starting SaaS Standalone Server...");
+    info!("Data directory: {:?}", config.data_dir);
+
+    // Uploads get their own folder so backups and SQLite files stay apart
+    let storage_dir = config.data_dir.join("storage");
+    let backend = bootstrap::init_backend(&config.data_dir, storage_dir).await?;
+    info!("Services initialized successfully");
+
+    let pool = backend.pool.clone();
+    let control = ServerControl::new(config.binding);
+    let mut server = tokio::spawn(backend.serve(control.clone()));
+
+    // Nothing can fix the binding at runtime here, so fail fast and let the
+    // supervisor restart or report us instead of idling without a listener.
+    for _ in 0..50 {
+        let status = control.status();
+        if let Some(e) = status.error {
+            return Err(format!("HTTP API failed to start: {}", e));
+        }
+        if status.listening_on.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
 
-    // Scheduled broadcasts -> notifications
-    let announcement_scheduler = AnnouncementScheduler::new(
-        pool.clone(),
-        notification_service.clone(),
-        audit_service.clone(),
-    );
-    announcement_scheduler.start().await;
+    tokio::select! {
+        _ = shutdown_signal() => {}
+        result = &mut server => {
+            return Err(format!("HTTP API stopped unexpectedly: {:?}", result));
+        }
+    }
 
-    let scheduler = BackupScheduler::new(
-        pool.clone(),
-        backup_service.clone(),
-        settings_service.clone(),
+    info!(
+        "Shutting down, draining requests for up to {}s",
+        config.shutdown_timeout.as_secs()
     );
-    scheduler.start().await;
-
-    plan_service.seed_default_features().await?;
-
-    // 6. Start HTTP Server
-    // Default to port 3000 if PORT env not set
-    http::start_server(
-        auth_service,
-        user_service,
-        settings_service,
-        email_service,
-        team_service,
-        role_service,
-        audit_service,
-        system_service,
-        plan_service,
-        storage_service,
-        payment_service,
-        notification_service,
-        mikrotik_service,
-        customer_service,
-        pppoe_service,
-        isp_package_service,
-        network_mapping_service,
-        backup_service,
-        ws_hub,
-        app_data_dir,
-        3000,
-        pool,
-        metrics_service,
-    )
-    .await;
-
+    control.shutdown();
+    if tokio::time::timeout(config.shutdown_timeout, server)
+        .await
+        .is_err()
+    {
+        tracing::warn!("Requests still open after the shutdown timeout, closing them");
+    }
+    pool.close().await;
+    info!("Shutdown complete");
     Ok(())
 }
+
+/// Ctrl+C everywhere, plus SIGTERM from Docker, Kubernetes and systemd.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received Ctrl+C"),
+        _ = terminate => info!("Received SIGTERM"),
+    }
+}
@@ -0,0 +1,552 @@
+//! Backend startup shared by the desktop app and the headless `server` binary.
+//!
+//! [`init_backend`] opens the database, seeds it, builds every service and
+//! schedules the background jobs. The caller decides where the data lives and
+//! how the HTTP server is bound, so nothing here depends on Tauri.
+
+use crate::db::connection::{init_db, init_read_replica, seed_defaults};
+use crate::db::{DbPool, QueryRouter};
+use crate::http::{self, ServerBinding, ServerControl, WsHub};
+use crate::services::backup::BackupScheduler;
+use crate::services::metrics_service::MetricsService;
+use crate::services::{
+    AnnouncementScheduler, AuditService, AuditStream, AuthService, BackupService, CustomerService,
+    DbMaintenanceService, EmailDkimService, EmailOutboxService, EmailService, EmailTemplateService,
+    EventOutboxService, FeatureFlagService, IspPackageService, JobQueue, MikrotikService,
+    NetworkMappingService, NotificationRoutingService, NotificationService,
+    PartitionMaintenanceScheduler, PaymentService, PlanService, PlanTrialScheduler, PppoeService,
+    QuietHoursService, RoleService, SettingsService, StoragePolicyService, StorageService,
+    SupportEscalationService, SystemService, TeamService, TelegramService, TrashPurgeScheduler,
+    UserService, WebPushService, WhatsappService,
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+const DEFAULT_PORT: u16 = 3000;
+/// Fits inside the 30 s Kubernetes grants between SIGTERM and SIGKILL.
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 20;
+
+pub const SERVER_USAGE: &str = "\
+Usage: server [OPTIONS]
+
+Runs the database, background jobs and HTTP API without the desktop app.
+Flags win over the environment; the environment wins over the defaults.
+
+Options:
+  --data-dir <PATH>       Uploads, backups and SQLite files [env: APP_DATA_DIR, default: .]
+  --bind <ADDRESS>        Listen address [env: BIND_ADDRESS, default: 0.0.0.0]
+  --port <PORT>           Listen port [env: PORT, default: 3000]
+  --database-url <URL>    Database connection string [env: DATABASE_URL]
+  --env-file <PATH>       Load variables from this file first [default: .env]
+  --log-format <FORMAT>   text or json (one object per line) [env: LOG_FORMAT, default: text]
+  --shutdown-timeout <S>  Seconds to drain requests after SIGTERM [env: SHUTDOWN_TIMEOUT_SECS, default: 20]
+  -h, --help              Print this help";
+
+/// Configuration of the headless server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    pub data_dir: PathBuf,
+    pub binding: ServerBinding,
+    /// Exported as `DATABASE_URL` before the pool is opened.
+    pub database_url: Option<String>,
+    pub env_file: Option<PathBuf>,
+    pub log_format: LogFormat,
+    pub shutdown_timeout: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    /// One JSON object per line, for log collectors.
+    Json,
+}
+
+/// What the command line asked for.
+#[derive(Debug, PartialEq)]
+pub enum ServerCommand {
+    Run(ServerConfig),
+    Help,
+}
+
+impl ServerConfig {
+    /// Parses the flags, filling the gaps from `env`. Flags take the
+    /// `--flag value` or `--flag=value` form.
+    pub fn from_args<I, F>(args: I, env: F) -> Result<ServerCommand, String>
+    where
+        I: IntoIterator<Item = String>,
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut data_dir = None;
+        let mut bind_address = None;
+        let mut port = None;
+        let mut database_url = None;
+        let mut env_file = None;
+        let mut log_format = None;
+        let mut shutdown_timeout = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+                _ => (arg.clone(), None),
+            };
+            if flag == "-h" || flag == "--help" {
+                return Ok(ServerCommand::Help);
+            }
+            let slot = match flag.as_str() {
+                "--data-dir" => &mut data_dir,
+                "--bind" => &mut bind_address,
+                "--port" => &mut port,
+                "--database-url" => &mut database_url,
+                "--env-file" => &mut env_file,
+                "--log-format" => &mut log_format,
+                "--shutdown-timeout" => &mut shutdown_timeout,
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            };
+            let value = match inline {
+                Some(value) => value.to_string(),
+                None => args
+                    .next()
+                    .ok_or_else(|| format!("{} expects a value", flag))?,
+            };
+            if value.trim().is_empty() {
+                return Err(format!("{} expects a value", flag));
+            }
+            *slot = Some(value);
+        }
+
+        let from_env = |key: &str| env(key).filter(|v| !v.trim().is_empty());
+        let port = match port.or_else(|| from_env("PORT")) {
+            Some(p) => p
+                .trim()
+                .parse::<u16>()
+                .ok()
+                .filter(|p| *p != 0)
+                .ok_or_else(|| format!("Invalid port: {}", p))?,
+            None => DEFAULT_PORT,
+        };
+        let binding = ServerBinding {
+            enabled: true,
+            bind_address: bind_address
+                .or_else(|| from_env("BIND_ADDRESS"))
+                .unwrap_or_else(|| http::binding::DEFAULT_BIND_ADDRESS.to_string()),
+            port,
+        };
+        binding.socket_addr().map_err(|e| e.to_string())?;
+
+        let log_format = match log_format.or_else(|| from_env("LOG_FORMAT")) {
+            None => LogFormat::Text,
+            Some(f) => match f.trim().to_ascii_lowercase().as_str() {
+                "text" => LogFormat::Text,
+                "json" => LogFormat::Json,
+                _ => return Err(format!("Invalid log format: {} (expected text or json)", f)),
+            },
+        };
+        let shutdown_timeout = match shutdown_timeout.or_else(|| from_env("SHUTDOWN_TIMEOUT_SECS"))
+        {
+            Some(secs) => secs
+                .trim()
+                .parse::<u64>()
+                .map(Duration::from_secs)
+                .map_err(|_| format!("Invalid shutdown timeout: {}", secs))?,
+            None => Duration::from_secs(DEFAULT_SHUTDOWN_TIMEOUT_SECS),
+        };
+
+        Ok(ServerCommand::Run(ServerConfig {
+            data_dir: data_dir
+                .or_else(|| from_env("APP_DATA_DIR"))
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(".")),
+            binding,
+            database_url,
+            env_file: env_file.map(PathBuf::from),
+            log_format,
+            shutdown_timeout,
+        }))
+    }
+}
+
+/// Every long-lived service, ready to be served over HTTP or managed by Tauri.
+pub struct Backend {
+    pub pool: DbPool,
+    pub data_dir: PathBuf,
+    pub auth_service: AuthService,
+    pub user_service: UserService,
+    pub settings_service: SettingsService,
+    pub email_service: EmailService,
+    pub email_outbox_service: EmailOutboxService,
+    pub team_service: TeamService,
+    pub role_service: RoleService,
+    pub audit_service: AuditService,
+    pub system_service: SystemService,
+    pub plan_service: PlanService,
+    pub storage_service: StorageService,
+    pub backup_service: BackupService,
+    pub payment_service: PaymentService,
+    pub notification_service: NotificationService,
+    pub mikrotik_service: MikrotikService,
+    pub customer_service: CustomerService,
+    pub pppoe_service: PppoeService,
+    pub isp_package_service: IspPackageService,
+    pub network_mapping_service: NetworkMappingService,
+    pub support_escalation: SupportEscalationService,
+    pub db_maintenance_service: DbMaintenanceService,
+    pub event_outbox: EventOutboxService,
+    pub job_queue: JobQueue,
+    pub feature_flag_service: FeatureFlagService,
+    pub ws_hub: Arc<WsHub>,
+    pub metrics_service: Arc<MetricsService>,
+}
+
+/// Opens the database under `data_dir`, seeds it, builds the services and
+/// starts the background jobs. Uploads go to `storage_dir`.
+pub async fn init_backend(data_dir: &Path, storage_dir: PathBuf) -> Result<Backend, String> {
+    std::fs::create_dir_all(data_dir)
+        .map_err(|e| format!("Failed to create data directory at {:?}: {}", data_dir, e))?;
+    std::fs::create_dir_all(&storage_dir).map_err(|e| {
+        format!(
+            "Failed to create storage directory at {:?}: {}",
+            storage_dir, e
+        )
+    })?;
+    let data_dir = data_dir.to_path_buf();
+
+    info!("Attempting to initialize database...");
+    let pool = init_db(data_dir.clone())
+        .await
+        .map_err(|e| format!("Failed to initialize database: {}", e))?;
+    info!("Database initialized.");
+
+    seed_defaults(&pool)
+        .await
+        .map_err(|e| format!("Failed to seed default settings: {}", e))?;
+    info!("Default settings seeded.");
+
+    // Optional read replica for heavy, lag-tolerant reads
+    let query_router = QueryRouter::new(pool.clone(), init_read_replica().await);
+
+    // AuditService must be first, RoleService needs it
+    let plan_service = PlanService::new(pool.clone());
+    let audit_service = AuditService::new(pool.clone(), Some(plan_service.clone()))
+        .with_query_router(query_router.clone())
+        .with_stream(AuditStream::from_env());
+    let role_service = RoleService::new(pool.clone(), audit_service.clone());
+
+    role_service
+        .seed_permissions()
+        .await
+        .map_err(|e| format!("Failed to seed permissions: {}", e))?;
+    role_service
+        .seed_roles()
+        .await
+        .map_err(|e| format!("Failed to seed roles: {}", e))?;
+    info!("RBAC permissions and roles seeded.");
+
+    let jwt_secret = sqlx::query_scalar::<_, String>(
+        "SELECT value FROM settings WHERE key = 'jwt_secret' AND tenant_id IS NULL",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap_or_else(|_| uuid::Uuid::new_v4().to_string());
+    info!("JWT Secret loaded.");
+
+    let settings_service = SettingsService::new(pool.clone(), audit_service.clone());
+    let email_service = EmailService::new(settings_service.clone()).with_dkim(
+        EmailDkimService::new(pool.clone(), settings_service.clone()),
+    );
+    let auth_service = AuthService::new(
+        pool.clone(),
+        jwt_secret,
+        email_service.clone(),
+        audit_service.clone(),
+        settings_service.clone(),
+    );
+    let user_service = UserService::new(pool.clone(), audit_service.clone());
+    let pppoe_service = PppoeService::new(
+        pool.clone(),
+        auth_service.clone(),
+        audit_service.clone(),
+        settings_service.clone(),
+    );
+    let isp_package_service =
+        IspPackageService::new(pool.clone(), auth_service.clone(), audit_service.clone());
+    let network_mapping_service = NetworkMappingService::new(pool.clone(), auth_service.clone());
+    let team_service = TeamService::new(
+        pool.clone(),
+        auth_service.clone(),
+        audit_service.clone(),
+        plan_service.clone(),
+    );
+    let metrics_service = Arc::new(MetricsService::new());
+    metrics_service.clone().start_pool_sampler(pool.clone());
+    let system_service = SystemService::new(pool.clone(), metrics_service.clone())
+        .with_query_router(query_router.clone())
+        .with_data_dir(data_dir.clone());
+    let backup_service = BackupService::new(pool.clone(), data_dir.clone());
+
+    // Periodic work (backups, outbox, poller, ...) runs as background jobs
+    let job_queue = JobQueue::new(pool.clone());
+
+    let scheduler = BackupScheduler::new(
+        pool.clone(),
+        backup_service.clone(),
+        settings_service.clone(),
+        audit_service.clone(),
+    );
+    scheduler.schedule(&job_queue).await;
+
+    // WebSocket hub for real-time sync (shared between HTTP and Tauri)
+    let ws_hub = Arc::new(WsHub::new());
+
+    let email_outbox_service = EmailOutboxService::new(
+        pool.clone(),
+        settings_service.clone(),
+        email_service.clone(),
+    );
+    email_outbox_service.schedule_sender(&job_queue).await;
+
+    // Deliver events recorded in the outbox to WebSocket clients and the event webhook
+    let event_outbox =
+        EventOutboxService::new(pool.clone(), ws_hub.clone(), settings_service.clone());
+    event_outbox.start_dispatcher().await;
+
+    let whatsapp_service = WhatsappService::new(pool.clone(), settings_service.clone());
+    let notification_service =
+        NotificationService::new(pool.clone(), ws_hub.clone(), email_outbox_service.clone())
+            .with_whatsapp(whatsapp_service)
+            .with_telegram(TelegramService::new(pool.clone(), settings_service.clone()))
+            .with_web_push(WebPushService::new(pool.clone(), settings_service.clone()))
+            .with_routing(NotificationRoutingService::new(
+                pool.clone(),
+                settings_service.clone(),
+            ))
+            .with_quiet_hours(QuietHoursService::new(
+                pool.clone(),
+                settings_service.clone(),
+            ))
+            .with_email_templates(EmailTemplateService::new(
+                pool.clone(),
+                settings_service.clone(),
+                email_service.clone(),
+            ));
+    notification_service.start_retention_cleanup(settings_service.clone());
+    notification_service.start_deferred_delivery();
+    let storage_service = StorageService::new(pool.clone(), plan_service.clone(), storage_dir)
+        .with_notifications(notification_service.clone());
+    storage_service.start_migration_worker();
+    StoragePolicyService::new(
+        pool.clone(),
+        storage_service.clone(),
+        notification_service.clone(),
+    )
+    .start_worker();
+    let customer_service = CustomerService::new(
+        pool.clone(),
+        auth_service.clone(),
+        audit_service.clone(),
+        notification_service.clone(),
+        pppoe_service.clone(),
+        user_service.clone(),
+    );
+    customer_service.start_installation_sla_scheduler();
+    let support_escalation = SupportEscalationService::new(
+        pool.clone(),
+        settings_service.clone(),
+        notification_service.clone(),
+    );
+    support_escalation.start_scheduler();
+    let payment_service = PaymentService::new(
+        pool.clone(),
+        notification_service.clone(),
+        pppoe_service.clone(),
+    )
+    .with_query_router(query_router.clone());
+    payment_service.start_customer_invoice_scheduler();
+
+    // MikroTik monitoring (tenant-scoped)
+    let mikrotik_service = MikrotikService::new(
+        pool.clone(),
+        notification_service.clone(),
+        audit_service.clone(),
+        settings_service.clone(),
+    )
+    .with_query_router(query_router.clone());
+    mikrotik_service.schedule_poller(&job_queue).await;
+
+    // PPPoE usage accounting and daily fair-usage enforcement
+    pppoe_service.schedule_usage_jobs(&job_queue).await;
+
+    // Scheduled broadcasts -> notifications
+    let announcement_scheduler = AnnouncementScheduler::new(
+        pool.clone(),
+        notification_service.clone(),
+        audit_service.clone(),
+    );
+    announcement_scheduler.schedule(&job_queue).await;
+
+    // Permanently purge soft-deleted records past the trash retention window
+    let trash_purge_scheduler = TrashPurgeScheduler::new(pool.clone(), settings_service.clone());
+    trash_purge_scheduler.schedule(&job_queue).await;
+
+    // Trial reminders and expiry (downgrade or lock per plan)
+    let plan_trial_scheduler =
+        PlanTrialScheduler::new(plan_service.clone(), email_outbox_service.clone());
+    plan_trial_scheduler.schedule(&job_queue).await;
+
+    // Keep time-series partitions ahead of time and drop expired ones
+    let partition_scheduler =
+        PartitionMaintenanceScheduler::new(pool.clone(), settings_service.clone());
+    partition_scheduler.schedule(&job_queue).await;
+
+    // ANALYZE/VACUUM or PRAGMA optimize on a schedule
+    let db_maintenance_service = DbMaintenanceService::new(pool.clone(), settings_service.clone());
+    db_maintenance_service.schedule(&job_queue).await;
+
+    // Hourly database size / free disk samples for the capacity report
+    system_service.schedule_capacity_sampler(&job_queue).await;
+    job_queue.start().await;
+
+    plan_service
+        .seed_default_features()
+        .await
+        .map_err(|e| format!("Failed to seed default features: {}", e))?;
+    info!("Default features seeded.");
+
+    Ok(Backend {
+        feature_flag_service: FeatureFlagService::new(pool.clone()),
+        pool,
+        data_dir,
+        auth_service,
+        user_service,
+        settings_service,
+        email_service,
+        email_outbox_service,
+        team_service,
+        role_service,
+        audit_service,
+        system_service,
+        plan_service,
+        storage_service,
+        backup_service,
+        payment_service,
+        notification_service,
+        mikrotik_service,
+        customer_service,
+        pppoe_service,
+        isp_package_service,
+        network_mapping_service,
+        support_escalation,
+        db_maintenance_service,
+        event_outbox,
+        job_queue,
+        ws_hub,
+        metrics_service,
+    })
+}
+
+impl Backend {
+    /// Runs the HTTP API until the process exits; `server_control` carries
+    /// the binding and lets it be changed at runtime.
+    pub async fn serve(self, server_control: Arc<ServerControl>) {
+        http::start_server(
+            self.auth_service,
+            self.user_service,
+            self.settings_service,
+            self.email_service,
+            self.team_service,
+            self.role_service,
+            self.audit_service,
+            self.system_service,
+            self.plan_service,
+            self.storage_service,
+            self.payment_service,
+            self.notification_service,
+            self.mikrotik_service,
+            self.customer_service,
+            self.pppoe_service,
+            self.isp_package_service,
+            self.network_mapping_service,
+            self.backup_service,
+            self.db_maintenance_service,
+            self.event_outbox,
+            self.job_queue,
+            self.feature_flag_service,
+            self.ws_hub,
+            self.data_dir,
+            server_control,
+            self.pool,
+            self.metrics_service,
+        )
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn parse(args: &[&str], env: &[(&str, &str)]) -> Result<ServerCommand, String> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        ServerConfig::from_args(args.iter().map(|a| a.to_string()), |key| {
+            env.get(key).cloned()
+        })
+    }
+
+    fn config(args: &[&str], env: &[(&str, &str)]) -> ServerConfig {
+        match parse(args, env).unwrap() {
+            ServerCommand::Run(config) => config,
+            ServerCommand::Help => panic!("expected a config"),
+        }
+    }
+
+    #[test]
+    fn defaults_without_flags_or_env() {
+        let c = config(&[], &[]);
+        assert_eq!(c.data_dir, PathBuf::from("."));
+        assert_eq!(c.binding.bind_address, http::binding::DEFAULT_BIND_ADDRESS);
+        assert_eq!(c.binding.port, DEFAULT_PORT);
+        assert_eq!(c.database_url, None);
+        assert_eq!(c.log_format, LogFormat::Text);
+        assert_eq!(c.shutdown_timeout, Duration::from_secs(20));
+    }
+
+    #[test]
+    fn flags_win_over_env() {
+        let env = [
+            ("PORT", "8080"),
+            ("APP_DATA_DIR", "/var/lib/x"),
+            ("BIND_ADDRESS", "::1"),
+        ];
+        let c = config(&["--port", "9000", "--data-dir=/srv/isp"], &env);
+        assert_eq!(c.binding.port, 9000);
+        assert_eq!(c.data_dir, PathBuf::from("/srv/isp"));
+        assert_eq!(c.binding.bind_address, "::1");
+
+        let c = config(&["--log-format", "text"], &[("LOG_FORMAT", "json")]);
+        assert_eq!(c.log_format, LogFormat::Text);
+        let c = config(
+            &[],
+            &[("LOG_FORMAT", "JSON"), ("SHUTDOWN_TIMEOUT_SECS", "5")],
+        );
+        assert_eq!(c.log_format, LogFormat::Json);
+        assert_eq!(c.shutdown_timeout, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(parse(&["--port", "0"], &[]).is_err());
+        assert!(parse(&["--port"], &[]).is_err());
+        assert!(parse(&["--bind", "example.com"], &[]).is_err());
+        assert!(parse(&["--verbose"], &[]).is_err());
+        assert!(parse(&["--log-format", "xml"], &[]).is_err());
+        assert!(parse(&["--shutdown-timeout", "-1"], &[]).is_err());
+        assert_eq!(parse(&["--port", "x"], &[]).unwrap_err(), "Invalid port: x");
+        assert_eq!(parse(&["-h"], &[]).unwrap(), ServerCommand::Help);
+    }
+}
@@ -1,10 +1,15 @@
 //! Announcements / Broadcasts (tenant + global)
 
-use crate::http::{WsEvent, WsHub};
+use crate::http::WsEvent;
 use crate::models::{
-    Announcement, CreateAnnouncementDto, PaginatedResponse, UpdateAnnouncementDto,
+    Announcement, AnnouncementStats, CreateAnnouncementDto, PaginatedResponse,
+    UpdateAnnouncementDto,
+};
+use crate::services::announcement_translations::RecipientTexts;
+use crate::services::{
+    announcement_audience, announcement_recurrence, announcement_stats, announcement_translations,
+    encode_unsubscribe_token, AuditService, AuthService, EventOutboxService, NotificationService,
 };
-use crate::services::{encode_unsubscribe_token, AuditService, AuthService, NotificationService};
 use chrono::Utc;
 use std::collections::HashSet;
 use tauri::State;
@@ -33,6 +38,7 @@ fn ann_snapshot_json(ann: &Announcement) -> serde_json::Value {
         "title": ann.title,
         "severity": ann.severity,
         "audience": ann.audience,
+        "audience_targets": ann.audience_targets,
         "mode": ann.mode,
         "format": ann.format,
         "deliver_in_app": ann.deliver_in_app,
@@ -41,6 +47,8 @@ fn ann_snapshot_json(ann: &Announcement) -> serde_json::Value {
         "starts_at": ann.starts_at.to_rfc3339(),
         "ends_at": ann.ends_at.map(|d| d.to_rfc3339()),
         "notified_at": ann.notified_at.map(|d| d.to_rfc3339()),
+        "recurrence": ann.recurrence,
+        "next_occurrence_at": ann.next_occurrence_at.map(|d| d.to_rfc3339()),
         "created_at": ann.created_at.to_rfc3339(),
         "updated_at": ann.updated_at.to_rfc3339(),
     })
@@ -63,6 +71,12 @@ fn ann_changed_fields(before: &Announcement, after: &Announcement) -> Vec<&'stat
     if before.audience != after.audience {
         out.push("audience");
     }
+    if before.audience_targets != after.audience_targets {
+        out.push("audience_targets");
+    }
+    if before.translations != after.translations {
+        out.push("translations");
+    }
     if before.mode != after.mode {
         out.push("mode");
     }
@@ -84,6 +98,9 @@ fn ann_changed_fields(before: &Announcement, after: &Announcement) -> Vec<&'stat
     if before.ends_at != after.ends_at {
         out.push("ends_at");
     }
+    if before.recurrence != after.recurrence {
+        out.push("recurrence");
+    }
     out
 }
 
@@ -96,7 +113,7 @@ fn norm_severity(s: Option<String>) -> String {
 
 fn norm_audience(a: Option<String>) -> String {
     match a.as_deref() {
-        Some("all") | Some("admins") => a.unwrap(),
+        Some("all") | Some("admins") | Some("segment") => a.unwrap(),
         _ => "all".to_string(),
     }
 }
@@ -160,7 +177,13 @@ async fn send_announcement_notifications(
 
     #[cfg(feature = "postgres")]
     {
-        if let Some(tid) = announcement.tenant_id.as_deref() {
+        if announcement.audience == "segment" {
+            recipients.extend(
+                announcement_audience::resolve_and_record(pool, announcement)
+                    .await
+                    .unwrap_or_default(),
+            );
+        } else if let Some(tid) = announcement.tenant_id.as_deref() {
             if announcement.audience == "admins" {
                 recipients.extend(tenant_admin_user_ids(pool, tid).await.unwrap_or_default());
             } else {
@@ -176,26 +199,30 @@ async fn send_announcement_notifications(
         }
     }
 
-    let title = announcement.title.clone();
-    let plain = if announcement.format == "html" {
-        strip_html_tags(&announcement.body)
-    } else {
-        announcement.body.clone()
-    };
-    let msg = if plain.chars().count() > 180 {
-        let short: String = plain.chars().take(180).collect();
-        format!("{}…", short)
-    } else {
-        plain
-    };
+    let recipients: Vec<String> = recipients.into_iter().collect();
+    let texts = RecipientTexts::load(pool, announcement, &recipients).await;
+    #[cfg(feature = "postgres")]
+    announcement_stats::record_delivered(pool, announcement, &recipients).await;
 
     for uid in recipients {
+        let (title, body) = texts.for_user(&uid);
+        let plain = if announcement.format == "html" {
+            strip_html_tags(body)
+        } else {
+            body.to_string()
+        };
+        let msg = if plain.chars().count() > 180 {
+            let short: String = plain.chars().take(180).collect();
+            format!("{}…", short)
+        } else {
+            plain
+        };
         let _ = notification_service
             .create_notification(
                 uid,
                 announcement.tenant_id.clone(),
-                title.clone(),
-                msg.clone(),
+                title.to_string(),
+                msg,
                 announcement.severity.clone(),
                 "announcement".to_string(),
                 Some(format!("/announcements/{}", announcement.id)),
@@ -216,7 +243,13 @@ async fn send_announcement_emails(
 
     let mut recipients: HashSet<String> = HashSet::new();
 
-    if let Some(tid) = announcement.tenant_id.as_deref() {
+    if announcement.audience == "segment" {
+        recipients.extend(
+            announcement_audience::resolve_and_record(pool, announcement)
+                .await
+                .unwrap_or_default(),
+        );
+    } else if let Some(tid) = announcement.tenant_id.as_deref() {
         if announcement.audience == "admins" {
             recipients.extend(tenant_admin_user_ids(pool, tid).await.unwrap_or_default());
         } else {
@@ -258,8 +291,6 @@ async fn send_announcement_emails(
         return;
     }
 
-    let subject = format!("[Announcement] {}", announcement.title);
-
     let main_domain: Option<String> = sqlx::query_scalar(
         "SELECT value FROM settings WHERE tenant_id IS NULL AND key = 'app_main_domain' LIMIT 1",
     )
@@ -284,7 +315,14 @@ async fn send_announcement_emails(
             .await
             .unwrap_or_default();
 
+    let user_ids: Vec<String> = users.iter().map(|(id, _)| id.clone()).collect();
+    let texts = RecipientTexts::load(pool, announcement, &user_ids).await;
+    announcement_stats::record_delivered(pool, announcement, &user_ids).await;
+
     for (user_id, email) in users {
+        let (title, body) = texts.for_user(&user_id);
+        let subject = format!("[Announcement] {}", title);
+
         let open_url = match (main_domain.as_deref(), slug.as_deref()) {
             (Some(domain), Some(sl)) => Some(format!(
                 "https://{}/{}/announcements/{}",
@@ -318,12 +356,12 @@ async fn send_announcement_emails(
 
         let plain_body = {
             let mut b = String::new();
-            b.push_str(&announcement.title);
+            b.push_str(title);
             b.push_str("\n\n");
             if announcement.format == "html" {
-                b.push_str(&strip_html_tags(&announcement.body));
+                b.push_str(&strip_html_tags(body));
             } else {
-                b.push_str(&announcement.body);
+                b.push_str(body);
             }
             if let Some(url) = open_url.as_deref() {
                 b.push_str("\n\nOpen in app:\n");
@@ -340,10 +378,9 @@ async fn send_announcement_emails(
 
         let html_body = {
             let content = if announcement.format == "html" {
-                announcement.body.clone()
+                body.to_string()
             } else {
-                let esc = announcement
-                    .body
+                let esc = body
                     .replace('&', "&amp;")
                     .replace('<', "&lt;")
                     .replace('>', "&gt;");
@@ -374,7 +411,7 @@ async fn send_announcement_emails(
   </div>
 </body>
 </html>"#,
-                announcement.title, content, open, unsub
+                title, content, open, unsub
             )
         };
 
@@ -415,20 +452,31 @@ pub async fn list_active_announcements(
     let now = Utc::now();
 
     #[cfg(feature = "postgres")]
-    let rows: Vec<Announcement> = sqlx::query_as(
+    let mut rows: Vec<Announcement> = sqlx::query_as(
         r#"
         SELECT a.*
         FROM announcements a
         LEFT JOIN announcement_dismissals d
-          ON d.announcement_id = a.id AND d.user_id = $1
+          ON d.announcement_id = a.id AND d.user_id = $1 AND d.occurrence = a.occurrence
         WHERE d.id IS NULL
           AND ($2::text IS NULL OR a.tenant_id IS NULL OR a.tenant_id = $2)
           AND a.deliver_in_app = true
           AND a.starts_at <= $3
           AND (a.ends_at IS NULL OR a.ends_at > $3)
+          AND (
+            a.recurrence IS NULL
+            OR (a.occurrence > 0 AND (a.occurrence_ends_at IS NULL OR a.occurrence_ends_at > $3))
+          )
           AND (
             a.audience = 'all'
             OR (a.audience = 'admins' AND $4 = true)
+            OR (
+              a.audience = 'segment'
+              AND EXISTS (
+                SELECT 1 FROM announcement_recipients r
+                WHERE r.announcement_id = a.id AND r.user_id = $1
+              )
+            )
           )
         ORDER BY a.starts_at DESC
         LIMIT 5
@@ -442,6 +490,18 @@ pub async fn list_active_announcements(
     .await
     .map_err(|e| e.to_string())?;
 
+    #[cfg(feature = "postgres")]
+    announcement_stats::record_views(&auth_service.pool, &user_id, &rows).await;
+
+    #[cfg(feature = "postgres")]
+    announcement_translations::localize_for_reader(
+        &auth_service.pool,
+        &user_id,
+        tenant_id.as_deref(),
+        &mut rows,
+    )
+    .await;
+
     #[cfg(not(feature = "postgres"))]
     let rows: Vec<Announcement> = Vec::new();
 
@@ -498,17 +558,19 @@ pub async fn list_recent_announcements(
 
         let mut qb_count: QueryBuilder<Postgres> = QueryBuilder::new(
             "SELECT COUNT(*) FROM announcements a \
-             LEFT JOIN announcement_dismissals d ON d.announcement_id = a.id AND d.user_id = ",
+             LEFT JOIN announcement_dismissals d \
+             ON d.announcement_id = a.id AND d.occurrence = a.occurrence AND d.user_id = ",
         );
         qb_count.push_bind(&user_id);
-        qb_count.push(" WHERE d.id IS NULL");
+        qb_count.push(" WHERE d.id IS NULL AND (a.recurrence IS NULL OR a.occurrence > 0)");
 
         let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(
             "SELECT a.* FROM announcements a \
-             LEFT JOIN announcement_dismissals d ON d.announcement_id = a.id AND d.user_id = ",
+             LEFT JOIN announcement_dismissals d \
+             ON d.announcement_id = a.id AND d.occurrence = a.occurrence AND d.user_id = ",
         );
         qb.push_bind(&user_id);
-        qb.push(" WHERE d.id IS NULL");
+        qb.push(" WHERE d.id IS NULL AND (a.recurrence IS NULL OR a.occurrence > 0)");
 
         if let Some(tid) = tenant_id.as_deref() {
             qb_count.push(" AND (a.tenant_id IS NULL OR a.tenant_id = ");
@@ -527,13 +589,21 @@ pub async fn list_recent_announcements(
         qb_count.push_bind(now);
         qb_count.push(" AND (a.audience = 'all' OR (a.audience = 'admins' AND ");
         qb_count.push_bind(is_admin);
-        qb_count.push(" = true))");
+        qb_count.push(
+            " = true) OR (a.audience = 'segment' AND EXISTS (SELECT 1 FROM announcement_recipients r WHERE r.announcement_id = a.id AND r.user_id = ",
+        );
+        qb_count.push_bind(&user_id);
+        qb_count.push(")))");
 
         qb.push(" AND a.deliver_in_app = true AND a.starts_at <= ");
         qb.push_bind(now);
         qb.push(" AND (a.audience = 'all' OR (a.audience = 'admins' AND ");
         qb.push_bind(is_admin);
-        qb.push(" = true))");
+        qb.push(
+            " = true) OR (a.audience = 'segment' AND EXISTS (SELECT 1 FROM announcement_recipients r WHERE r.announcement_id = a.id AND r.user_id = ",
+        );
+        qb.push_bind(&user_id);
+        qb.push(")))");
 
         if let Some(sev) = severity.as_deref() {
             qb_count.push(" AND a.severity = ");
@@ -576,11 +646,18 @@ pub async fn list_recent_announcements(
         qb.push(" OFFSET ");
         qb.push_bind(offset);
 
-        let rows: Vec<Announcement> = qb
+        let mut rows: Vec<Announcement> = qb
             .build_query_as()
             .fetch_all(&auth_service.pool)
             .await
             .map_err(|e| e.to_string())?;
+        announcement_translations::localize_for_reader(
+            &auth_service.pool,
+            &user_id,
+            tenant_id.as_deref(),
+            &mut rows,
+        )
+        .await;
         (rows, total)
     };
 
@@ -633,7 +710,7 @@ pub async fn get_announcement(
     let now = Utc::now();
 
     #[cfg(feature = "postgres")]
-    let row: Announcement = if can_manage {
+    let mut row: Announcement = if can_manage {
         sqlx::query_as(
             r#"
             SELECT *
@@ -657,9 +734,17 @@ pub async fn get_announcement(
               AND ($2::text IS NULL OR tenant_id IS NULL OR tenant_id = $2)
               AND starts_at <= $3
               AND (ends_at IS NULL OR ends_at > $3 OR notified_at IS NOT NULL)
+              AND (recurrence IS NULL OR occurrence > 0)
               AND (
                 audience = 'all'
                 OR (audience = 'admins' AND $4 = true)
+                OR (
+                  audience = 'segment'
+                  AND EXISTS (
+                    SELECT 1 FROM announcement_recipients r
+                    WHERE r.announcement_id = announcements.id AND r.user_id = $5
+                  )
+                )
               )
         "#,
         )
@@ -667,11 +752,28 @@ pub async fn get_announcement(
         .bind(tenant_id.as_deref())
         .bind(now)
         .bind(is_admin)
+        .bind(&user_id)
         .fetch_one(&auth_service.pool)
         .await
         .map_err(|e| e.to_string())?
     };
 
+    // Managers open drafts and scheduled posts too; only reader fetches count.
+    #[cfg(feature = "postgres")]
+    if !can_manage {
+        announcement_stats::record_views(&auth_service.pool, &user_id, std::slice::from_ref(&row))
+            .await;
+    }
+
+    #[cfg(feature = "postgres")]
+    announcement_translations::localize_for_reader(
+        &auth_service.pool,
+        &user_id,
+        tenant_id.as_deref(),
+        std::slice::from_mut(&mut row),
+    )
+    .await;
+
     #[cfg(not(feature = "postgres"))]
     let row: Announcement = Announcement {
         id,
@@ -682,6 +784,8 @@ pub async fn get_announcement(
         body: "".into(),
         severity: "info".into(),
         audience: "all".into(),
+        audience_targets: None,
+        translations: None,
         mode: "post".into(),
         format: "plain".into(),
         deliver_in_app: true,
@@ -690,6 +794,10 @@ pub async fn get_announcement(
         starts_at: now,
         ends_at: None,
         notified_at: None,
+        recurrence: None,
+        occurrence: 0,
+        next_occurrence_at: None,
+        occurrence_ends_at: None,
         created_at: now,
         updated_at: now,
     };
@@ -715,9 +823,9 @@ pub async fn dismiss_announcement(
     {
         let _ = sqlx::query(
             r#"
-            INSERT INTO announcement_dismissals (id, announcement_id, user_id, dismissed_at)
-            VALUES ($1,$2,$3,$4)
-            ON CONFLICT (user_id, announcement_id) DO NOTHING
+            INSERT INTO announcement_dismissals (id, announcement_id, user_id, dismissed_at, occurrence)
+            VALUES ($1,$2,$3,$4,(SELECT occurrence FROM announcements WHERE id = $2))
+            ON CONFLICT (user_id, announcement_id, occurrence) DO NOTHING
         "#,
         )
         .bind(&did)
@@ -949,6 +1057,24 @@ pub async fn create_announcement_admin(
     let id = Uuid::new_v4().to_string();
     let severity = norm_severity(dto.severity);
     let audience = norm_audience(dto.audience);
+    let audience_targets = announcement_audience::prepare_targets(
+        &auth_service.pool,
+        target_tenant_id.as_deref(),
+        &audience,
+        dto.audience_targets,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    let translations = announcement_translations::prepare_translations(dto.translations)
+        .map_err(|e| e.to_string())?;
+    let (recurrence, next_occurrence_at) = announcement_recurrence::prepare_recurrence(
+        &auth_service.pool,
+        target_tenant_id.as_deref(),
+        dto.recurrence,
+        starts_at,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
     let mode = norm_mode(dto.mode);
     let format = norm_format(dto.format);
     let deliver_in_app = dto.deliver_in_app.unwrap_or(true);
@@ -960,9 +1086,9 @@ pub async fn create_announcement_admin(
     let mut ann: Announcement = sqlx::query_as(
         r#"
         INSERT INTO announcements
-          (id, tenant_id, created_by, cover_file_id, title, body, severity, audience, mode, format, deliver_in_app, deliver_email, deliver_email_force, starts_at, ends_at, notified_at, created_at, updated_at)
+          (id, tenant_id, created_by, cover_file_id, title, body, severity, audience, audience_targets, mode, format, deliver_in_app, deliver_email, deliver_email_force, starts_at, ends_at, notified_at, created_at, updated_at, translations, recurrence, next_occurrence_at)
         VALUES
-          ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,NULL,$16,$17)
+          ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,NULL,$17,$18,$19,$20,$21)
         RETURNING *
     "#,
     )
@@ -974,6 +1100,7 @@ pub async fn create_announcement_admin(
     .bind(dto.body.trim())
     .bind(&severity)
     .bind(&audience)
+    .bind(&audience_targets)
     .bind(&mode)
     .bind(&format)
     .bind(deliver_in_app)
@@ -983,6 +1110,9 @@ pub async fn create_announcement_admin(
     .bind(ends_at)
     .bind(now)
     .bind(now)
+    .bind(&translations)
+    .bind(&recurrence)
+    .bind(next_occurrence_at)
     .fetch_one(&auth_service.pool)
     .await
     .map_err(|e| e.to_string())?;
@@ -997,6 +1127,8 @@ pub async fn create_announcement_admin(
         body: dto.body,
         severity,
         audience,
+        audience_targets,
+        translations,
         mode,
         format,
         deliver_in_app,
@@ -1005,11 +1137,16 @@ pub async fn create_announcement_admin(
         starts_at,
         ends_at,
         notified_at: None,
+        recurrence,
+        occurrence: 0,
+        next_occurrence_at,
+        occurrence_ends_at: None,
         created_at: now,
         updated_at: now,
     };
 
-    if starts_at <= now
+    if ann.recurrence.is_none()
+        && starts_at <= now
         && ends_at.map(|e| e > now).unwrap_or(true)
         && (deliver_in_app || deliver_email)
     {
@@ -1120,6 +1257,21 @@ pub async fn update_announcement_admin(
     } else {
         existing.audience
     };
+    let audience_targets = announcement_audience::prepare_targets(
+        &auth_service.pool,
+        before.tenant_id.as_deref(),
+        &audience,
+        dto.audience_targets
+            .or_else(|| Some(announcement_audience::targets_of(&before))),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    let translations = match dto.translations {
+        Some(t) => {
+            announcement_translations::prepare_translations(Some(t)).map_err(|e| e.to_string())?
+        }
+        None => before.translations.clone(),
+    };
     let mode = if dto.mode.is_some() {
         norm_mode(dto.mode)
     } else {
@@ -1143,6 +1295,36 @@ pub async fn update_announcement_admin(
             return Err("ends_at must be after starts_at".to_string());
         }
     }
+    // An edited rule restarts the series from starts_at; an unchanged one only
+    // moves when starts_at does.
+    let (recurrence, next_occurrence_at) = match dto.recurrence {
+        Some(rule) => announcement_recurrence::prepare_recurrence(
+            &auth_service.pool,
+            before.tenant_id.as_deref(),
+            rule,
+            starts_at,
+        )
+        .await
+        .map_err(|e| e.to_string())?,
+        None => match announcement_recurrence::recurrence_of(&before) {
+            Some(rule) if starts_at != before.starts_at => {
+                announcement_recurrence::prepare_recurrence(
+                    &auth_service.pool,
+                    before.tenant_id.as_deref(),
+                    Some(rule),
+                    starts_at,
+                )
+                .await
+                .map_err(|e| e.to_string())?
+            }
+            _ => (before.recurrence.clone(), before.next_occurrence_at),
+        },
+    };
+    let occurrence_ends_at = if recurrence.is_some() {
+        before.occurrence_ends_at
+    } else {
+        None
+    };
 
     #[cfg(feature = "postgres")]
     let ann: Announcement = sqlx::query_as(
@@ -1160,7 +1342,12 @@ pub async fn update_announcement_admin(
             deliver_email_force = $10,
             starts_at = $11,
             ends_at = $12,
-            updated_at = $13
+            updated_at = $13,
+            audience_targets = $15,
+            translations = $16,
+            recurrence = $17,
+            next_occurrence_at = $18,
+            occurrence_ends_at = $19
         WHERE id = $14
         RETURNING *
     "#,
@@ -1179,6 +1366,11 @@ pub async fn update_announcement_admin(
     .bind(ends_at)
     .bind(now)
     .bind(&id)
+    .bind(&audience_targets)
+    .bind(&translations)
+    .bind(&recurrence)
+    .bind(next_occurrence_at)
+    .bind(occurrence_ends_at)
     .fetch_one(&auth_service.pool)
     .await
     .map_err(|e| e.to_string())?;
@@ -1271,12 +1463,58 @@ pub async fn delete_announcement_admin(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_announcement_stats_admin(
+    token: String,
+    id: String,
+    occurrence: Option<i32>,
+    auth_service: State<'_, AuthService>,
+) -> Result<AnnouncementStats, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "announcements", "manage")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    #[cfg(feature = "postgres")]
+    {
+        let ann: Announcement = sqlx::query_as(
+            "SELECT * FROM announcements WHERE id = $1 AND (tenant_id = $2 OR ($3 = true AND tenant_id IS NULL))",
+        )
+        .bind(&id)
+        .bind(&tenant_id)
+        .bind(claims.is_super_admin)
+        .fetch_one(&auth_service.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        announcement_stats::stats(&auth_service.pool, &ann, occurrence)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    {
+        let _ = (id, occurrence);
+        Err("Not supported".to_string())
+    }
+}
+
 #[cfg(feature = "postgres")]
 #[tauri::command]
 pub async fn process_due_announcements_command(
     auth_service: State<'_, AuthService>,
     notification_service: State<'_, NotificationService>,
-    ws_hub: State<'_, std::sync::Arc<WsHub>>,
+    event_outbox: State<'_, EventOutboxService>,
 ) -> Result<(), String> {
     let now = Utc::now();
     let due: Vec<Announcement> = sqlx::query_as(
@@ -1285,6 +1523,7 @@ pub async fn process_due_announcements_command(
         FROM announcements
         WHERE starts_at <= $1
           AND notified_at IS NULL
+          AND recurrence IS NULL
           AND (ends_at IS NULL OR ends_at > $1)
           AND (deliver_in_app = true OR deliver_email = true)
         ORDER BY starts_at ASC
@@ -1309,7 +1548,9 @@ pub async fn process_due_announcements_command(
 
         // Nudge clients via WS so banner can refresh quickly (client-side filter still applies).
         // We only send a broad hint; individual users will refresh via NotificationReceived too.
-        ws_hub.broadcast(WsEvent::PermissionsChanged);
+        event_outbox
+            .publish(ann.tenant_id.as_deref(), vec![WsEvent::PermissionsChanged])
+            .await;
     }
 
     Ok(())
@@ -27,6 +27,8 @@ pub async fn list_audit_logs(
     date_from: Option<String>,
     date_to: Option<String>,
     search: Option<String>,
+    actor: Option<String>,
+    ip_address: Option<String>,
     audit_service: State<'_, AuditService>,
     auth_service: State<'_, AuthService>,
 ) -> Result<PaginatedResponse<crate::models::AuditLogResponse>, String> {
@@ -62,6 +64,8 @@ pub async fn list_audit_logs(
         date_from: date_from_parsed,
         date_to: date_to_parsed,
         search,
+        actor,
+        ip_address,
     };
 
     let (logs, total) = audit_service
@@ -91,6 +95,8 @@ pub async fn list_tenant_audit_logs(
     date_from: Option<String>,
     date_to: Option<String>,
     search: Option<String>,
+    actor: Option<String>,
+    ip_address: Option<String>,
     audit_service: State<'_, AuditService>,
     auth_service: State<'_, AuthService>,
 ) -> Result<PaginatedResponse<crate::models::AuditLogResponse>, String> {
@@ -134,6 +140,8 @@ pub async fn list_tenant_audit_logs(
         date_from: date_from_parsed,
         date_to: date_to_parsed,
         search,
+        actor,
+        ip_address,
     };
 
     let (logs, total) = audit_service
@@ -1,5 +1,10 @@
 use crate::error::AppResult;
-use crate::services::backup::{BackupRecord, BackupService};
+use crate::services::backup::{
+    BackupRecord, BackupRunTarget, BackupSchedulePreview, BackupService, CronPreview,
+    ScratchRestoreReport,
+};
+use crate::services::backup_remote::{RemoteBackupRecord, RemoteBackupStore};
+use crate::services::backup_validation::RestoreValidationReport;
 use serde::Deserialize;
 use tauri::State;
 
@@ -261,3 +266,311 @@ pub async fn restore_local_backup_command(
             .await
     }
 }
+
+#[tauri::command]
+pub async fn list_remote_backups(
+    settings_service: State<'_, crate::services::SettingsService>,
+    auth_service: State<'_, crate::services::AuthService>,
+    token: String,
+) -> AppResult<Vec<RemoteBackupRecord>> {
+    let claims = auth_service.validate_token(&token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    let store = RemoteBackupStore::from_settings(&settings_service).await?;
+    store.list().await
+}
+
+#[tauri::command]
+pub async fn upload_backup_offsite(
+    service: State<'_, BackupService>,
+    settings_service: State<'_, crate::services::SettingsService>,
+    auth_service: State<'_, crate::services::AuthService>,
+    token: String,
+    filename: String,
+) -> AppResult<String> {
+    let claims = auth_service.validate_token(&token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    let path = service.get_backup_path(&filename)?;
+    let store = RemoteBackupStore::from_settings(&settings_service).await?;
+    store.upload(&path).await
+}
+
+#[tauri::command]
+pub async fn restore_remote_backup_command(
+    service: State<'_, BackupService>,
+    settings_service: State<'_, crate::services::SettingsService>,
+    auth_service: State<'_, crate::services::AuthService>,
+    token: String,
+    filename: String,
+) -> AppResult<()> {
+    let claims = auth_service.validate_token(&token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    // Tenant archives restore into their own tenant only
+    let tenant_id = if filename.starts_with("tenant_") {
+        let parts: Vec<&str> = filename.split('_').collect();
+        if parts.len() >= 3 {
+            Some(parts[1].to_string())
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    let store = RemoteBackupStore::from_settings(&settings_service).await?;
+    service
+        .restore_remote_backup(&store, filename, tenant_id.as_deref())
+        .await
+}
+
+#[tauri::command]
+pub async fn pin_backup(
+    service: State<'_, BackupService>,
+    auth_service: State<'_, crate::services::AuthService>,
+    token: String,
+    filename: String,
+) -> AppResult<()> {
+    set_pinned(&service, &auth_service, &token, &filename, true).await
+}
+
+#[tauri::command]
+pub async fn unpin_backup(
+    service: State<'_, BackupService>,
+    auth_service: State<'_, crate::services::AuthService>,
+    token: String,
+    filename: String,
+) -> AppResult<()> {
+    set_pinned(&service, &auth_service, &token, &filename, false).await
+}
+
+async fn set_pinned(
+    service: &BackupService,
+    auth_service: &crate::services::AuthService,
+    token: &str,
+    filename: &str,
+    pinned: bool,
+) -> AppResult<()> {
+    let claims = auth_service.validate_token(token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    service.set_pinned(filename, pinned).await
+}
+
+/// Tenant a restore of `filename` is scoped to when run by a super admin.
+fn restore_scope(filename: &str) -> Option<String> {
+    let parts: Vec<&str> = filename.split('_').collect();
+    if filename.starts_with("tenant_") && parts.len() >= 3 {
+        Some(parts[1].to_string())
+    } else {
+        None
+    }
+}
+
+#[tauri::command]
+pub async fn validate_backup_file(
+    service: State<'_, BackupService>,
+    auth_service: State<'_, crate::services::AuthService>,
+    token: String,
+    path: String,
+) -> AppResult<RestoreValidationReport> {
+    let claims = auth_service.validate_token(&token).await?;
+
+    let zip_path = std::path::PathBuf::from(path);
+    if !zip_path.exists() {
+        return Err(crate::error::AppError::NotFound(
+            "File not found".to_string(),
+        ));
+    }
+
+    // Same scoping as restore_backup_from_file
+    let tenant_id = if claims.is_super_admin {
+        zip_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(restore_scope)
+    } else {
+        Some(
+            claims
+                .tenant_id
+                .clone()
+                .ok_or(crate::error::AppError::Forbidden(
+                    "Tenant context missing".to_string(),
+                ))?,
+        )
+    };
+
+    service
+        .validate_backup(&zip_path, tenant_id.as_deref())
+        .await
+}
+
+#[tauri::command]
+pub async fn validate_local_backup(
+    service: State<'_, BackupService>,
+    auth_service: State<'_, crate::services::AuthService>,
+    token: String,
+    filename: String,
+) -> AppResult<RestoreValidationReport> {
+    let claims = auth_service.validate_token(&token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    let path = service.get_backup_path(&filename)?;
+    service
+        .validate_backup(&path, restore_scope(&filename).as_deref())
+        .await
+}
+
+#[tauri::command]
+pub async fn dry_run_restore_backup(
+    service: State<'_, BackupService>,
+    auth_service: State<'_, crate::services::AuthService>,
+    token: String,
+    filename: String,
+) -> AppResult<ScratchRestoreReport> {
+    let claims = auth_service.validate_token(&token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    let path = service.get_backup_path(&filename)?;
+    service
+        .restore_to_scratch(&path, restore_scope(&filename).as_deref())
+        .await
+}
+
+#[tauri::command]
+pub async fn drop_restore_scratch(
+    service: State<'_, BackupService>,
+    auth_service: State<'_, crate::services::AuthService>,
+    token: String,
+    schema: String,
+) -> AppResult<()> {
+    let claims = auth_service.validate_token(&token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    service.drop_scratch_schema(&schema).await
+}
+
+#[tauri::command]
+pub async fn get_backup_schedule(
+    settings_service: State<'_, crate::services::SettingsService>,
+    auth_service: State<'_, crate::services::AuthService>,
+    token: String,
+) -> AppResult<BackupSchedulePreview> {
+    let claims = auth_service.validate_token(&token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    crate::services::backup::backup_schedule_preview(&settings_service).await
+}
+
+#[tauri::command]
+pub async fn preview_backup_cron(
+    settings_service: State<'_, crate::services::SettingsService>,
+    auth_service: State<'_, crate::services::AuthService>,
+    token: String,
+    expression: String,
+    count: Option<usize>,
+) -> AppResult<CronPreview> {
+    let claims = auth_service.validate_token(&token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    crate::services::backup::preview_cron_expression(
+        &settings_service,
+        &expression,
+        count.unwrap_or(5),
+    )
+    .await
+}
+
+/// Returns the run id; progress arrives as `backup_run_progress` WebSocket events.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn run_backup_now(
+    service: State<'_, BackupService>,
+    settings_service: State<'_, crate::services::SettingsService>,
+    audit_service: State<'_, crate::services::AuditService>,
+    auth_service: State<'_, crate::services::AuthService>,
+    ws_hub: State<'_, std::sync::Arc<crate::http::WsHub>>,
+    token: String,
+    target: BackupRunTarget,
+    tenant_id: Option<String>,
+) -> AppResult<String> {
+    let claims = auth_service.validate_token(&token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+    if tenant_id.is_some() && target != BackupRunTarget::Tenants {
+        return Err(crate::error::AppError::Validation(
+            "Tenant ID is only valid for tenant backups".to_string(),
+        ));
+    }
+
+    let hub = ws_hub.inner().clone();
+    let run_id = crate::services::backup::start_manual_run(
+        auth_service.pool.clone(),
+        service.inner().clone(),
+        settings_service.inner().clone(),
+        audit_service.inner().clone(),
+        target,
+        tenant_id.clone(),
+        move |progress| hub.broadcast(progress.into()),
+    )?;
+
+    let details = serde_json::json!({
+        "run_id": run_id,
+        "target": target.as_str(),
+        "tenant_id": tenant_id,
+    })
+    .to_string();
+    audit_service
+        .log(
+            Some(&claims.sub),
+            None,
+            "run_now",
+            "backups",
+            None,
+            Some(details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(run_id)
+}
@@ -1,16 +1,28 @@
 use crate::models::{
-    AddCustomerPortalUserRequest, CreateCustomerLocationRequest, CreateCustomerPortalUserRequest,
+    AddCustomerPortalUserRequest, ApplyWorkOrderTemplateRequest,
+    AssignInstallationWorkOrderRequest, CompleteWorkOrderRequest, ConsumeWorkOrderMaterialsRequest,
+    CreateCustomerLocationRequest, CreateCustomerPortalUserRequest,
     CreateCustomerRegistrationInviteRequest, CreateCustomerRequest,
     CreateCustomerSubscriptionRequest, CreateCustomerWithPortalRequest,
-    CreateMyCustomerLocationRequest, Customer, CustomerLocation, CustomerPortalSubscriptionStats,
-    CustomerPortalUser, CustomerRegistrationInviteCreateResponse, CustomerRegistrationInvitePolicy,
-    CustomerRegistrationInviteSummary, CustomerRegistrationInviteView, CustomerSubscription,
-    CustomerSubscriptionView, InstallationWorkOrder, InstallationWorkOrderView, Invoice,
-    IspPackage, PaginatedResponse, PortalCheckoutSubscriptionRequest, TeamMemberWithUser,
-    UpdateCustomerLocationRequest, UpdateCustomerRegistrationInvitePolicyRequest,
-    UpdateCustomerRequest, UpdateCustomerSubscriptionRequest, WorkOrderRescheduleRequestView,
+    CreateMyCustomerLocationRequest, CreateWorkOrderRequest, Customer, CustomerLocation,
+    CustomerPortalSubscriptionStats, CustomerPortalUser, CustomerRegistrationInviteCreateResponse,
+    CustomerRegistrationInvitePolicy, CustomerRegistrationInviteSummary,
+    CustomerRegistrationInviteView, CustomerSubscription, CustomerSubscriptionComponent,
+    CustomerSubscriptionView, CustomerTagSummary, InstallInventorySerialRequest,
+    InstallationWorkOrder, InstallationWorkOrderView, InventoryMovement, InventorySerial, Invoice,
+    IspPackage, PaginatedResponse, PortalCheckoutSubscriptionRequest, SetCustomerTagsRequest,
+    TeamMemberWithUser, TrashItem, UpdateCustomerLocationRequest,
+    UpdateCustomerRegistrationInvitePolicyRequest, UpdateCustomerRequest,
+    UpdateCustomerSubscriptionRequest, UpdateSubscriptionComponentRequest,
+    UpdateWorkOrderChecklistItemRequest, UpsertWorkOrderTemplateRequest, WorkOrderAgendaItem,
+    WorkOrderCheckRequest, WorkOrderChecklist, WorkOrderCompletionReport,
+    WorkOrderRescheduleRequestView, WorkOrderRoutePlan, WorkOrderSignatureInput, WorkOrderTemplate,
+    WorkOrderVisit,
+};
+use crate::services::{
+    AuditService, AuthService, CompletionReportService, CustomerService, InventoryService,
+    PaymentService, WorkOrderChecklistService,
 };
-use crate::services::{AuthService, CustomerService, PaymentService};
 use tauri::State;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -152,6 +164,75 @@ pub async fn update_customer(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn list_customer_tags(
+    token: String,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<Vec<CustomerTagSummary>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .list_customer_tags(&claims.sub, &tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_customer_tags(
+    token: String,
+    customer_id: String,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<Vec<String>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .get_customer_tags(&claims.sub, &tenant_id, &customer_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_customer_tags(
+    token: String,
+    customer_id: String,
+    tags: Vec<String>,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<Vec<String>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .set_customer_tags(
+            &claims.sub,
+            &tenant_id,
+            &customer_id,
+            SetCustomerTagsRequest { tags },
+            Some("127.0.0.1"),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn delete_customer(
     token: String,
@@ -173,6 +254,47 @@ pub async fn delete_customer(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn list_deleted_customers(
+    token: String,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<Vec<TrashItem>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .list_deleted_customers(&claims.sub, &tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restore_customer(
+    token: String,
+    customer_id: String,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<Customer, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .restore_customer(&claims.sub, &tenant_id, &customer_id, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn create_customer_registration_invite(
     token: String,
@@ -576,6 +698,7 @@ pub async fn delete_my_customer_location(
 #[tauri::command]
 pub async fn list_my_customer_packages(
     token: String,
+    location_id: Option<String>,
     auth: State<'_, AuthService>,
     customers: State<'_, CustomerService>,
 ) -> Result<Vec<IspPackage>, String> {
@@ -588,7 +711,7 @@ pub async fn list_my_customer_packages(
         .ok_or_else(|| "No tenant ID in token".to_string())?;
 
     customers
-        .list_my_packages(&claims.sub, &tenant_id)
+        .list_my_packages(&claims.sub, &tenant_id, location_id)
         .await
         .map_err(|e| e.to_string())
 }
@@ -839,11 +962,63 @@ pub async fn delete_customer_subscription(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn list_customer_subscription_components(
+    token: String,
+    subscription_id: String,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<Vec<CustomerSubscriptionComponent>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .list_subscription_components(&claims.sub, &tenant_id, &subscription_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_customer_subscription_component(
+    token: String,
+    component_id: String,
+    status: Option<String>,
+    detail: Option<String>,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<CustomerSubscriptionComponent, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    let dto = UpdateSubscriptionComponentRequest { status, detail };
+    customers
+        .update_subscription_component(
+            &claims.sub,
+            &tenant_id,
+            &component_id,
+            dto,
+            Some("127.0.0.1"),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn list_installation_work_orders(
     token: String,
     status: Option<String>,
     assigned_to: Option<String>,
+    work_type: Option<String>,
     include_closed: Option<bool>,
     limit: Option<u32>,
     auth: State<'_, AuthService>,
@@ -863,6 +1038,7 @@ pub async fn list_installation_work_orders(
             &tenant_id,
             status,
             assigned_to,
+            work_type,
             include_closed.unwrap_or(false),
             limit.unwrap_or(200),
         )
@@ -891,12 +1067,15 @@ pub async fn list_installation_assignees(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn assign_installation_work_order(
     token: String,
     id: String,
     assigned_to: String,
     scheduled_at: Option<String>,
+    scheduled_end_at: Option<String>,
     notes: Option<String>,
+    allow_overlap: Option<bool>,
     auth: State<'_, AuthService>,
     customers: State<'_, CustomerService>,
 ) -> Result<InstallationWorkOrder, String> {
@@ -913,15 +1092,73 @@ pub async fn assign_installation_work_order(
             &claims.sub,
             &tenant_id,
             &id,
-            &assigned_to,
-            scheduled_at,
-            notes,
+            AssignInstallationWorkOrderRequest {
+                assigned_to,
+                scheduled_at,
+                scheduled_end_at,
+                notes,
+                allow_overlap,
+            },
             Some("127.0.0.1"),
         )
         .await
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn get_work_order_agenda(
+    token: String,
+    from: Option<String>,
+    to: Option<String>,
+    technician_id: Option<String>,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<Vec<WorkOrderAgendaItem>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .list_work_order_agenda(&claims.sub, &tenant_id, from, to, technician_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn plan_technician_route(
+    token: String,
+    technician_id: Option<String>,
+    date: Option<String>,
+    start_latitude: Option<f64>,
+    start_longitude: Option<f64>,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<WorkOrderRoutePlan, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .plan_technician_route(
+            &claims.sub,
+            &tenant_id,
+            technician_id,
+            date,
+            start_latitude,
+            start_longitude,
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn claim_installation_work_order(
     token: String,
@@ -966,6 +1203,27 @@ pub async fn release_installation_work_order(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn create_work_order(
+    token: String,
+    dto: CreateWorkOrderRequest,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<InstallationWorkOrder, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .create_work_order(&claims.sub, &tenant_id, dto, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn start_installation_work_order(
     token: String,
@@ -989,7 +1247,7 @@ pub async fn start_installation_work_order(
 }
 
 #[tauri::command]
-pub async fn complete_installation_work_order(
+pub async fn hold_work_order(
     token: String,
     id: String,
     notes: Option<String>,
@@ -1005,18 +1263,19 @@ pub async fn complete_installation_work_order(
         .ok_or_else(|| "No tenant ID in token".to_string())?;
 
     customers
-        .complete_installation_work_order(&claims.sub, &tenant_id, &id, notes, Some("127.0.0.1"))
+        .hold_work_order(&claims.sub, &tenant_id, &id, notes, Some("127.0.0.1"))
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn cancel_installation_work_order(
+pub async fn complete_installation_work_order(
     token: String,
     id: String,
     notes: Option<String>,
+    signature: Option<WorkOrderSignatureInput>,
     auth: State<'_, AuthService>,
-    customers: State<'_, CustomerService>,
+    reports: State<'_, CompletionReportService>,
 ) -> Result<InstallationWorkOrder, String> {
     let claims = auth
         .validate_token(&token)
@@ -1026,8 +1285,543 @@ pub async fn cancel_installation_work_order(
         .tenant_id
         .ok_or_else(|| "No tenant ID in token".to_string())?;
 
-    customers
-        .cancel_installation_work_order(&claims.sub, &tenant_id, &id, notes, Some("127.0.0.1"))
+    reports
+        .complete_work_order(
+            &claims.sub,
+            &tenant_id,
+            &id,
+            CompleteWorkOrderRequest { notes, signature },
+            Some("127.0.0.1"),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_work_order_completion_reports(
+    token: String,
+    id: String,
+    auth: State<'_, AuthService>,
+    reports: State<'_, CompletionReportService>,
+) -> Result<Vec<WorkOrderCompletionReport>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    reports
+        .list_for_work_order(&claims.sub, &tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn sign_work_order_completion(
+    token: String,
+    id: String,
+    dto: WorkOrderSignatureInput,
+    auth: State<'_, AuthService>,
+    reports: State<'_, CompletionReportService>,
+) -> Result<WorkOrderCompletionReport, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    reports
+        .sign_completed(&claims.sub, &tenant_id, &id, dto, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn email_work_order_completion_report(
+    token: String,
+    report_id: String,
+    auth: State<'_, AuthService>,
+    reports: State<'_, CompletionReportService>,
+) -> Result<WorkOrderCompletionReport, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    reports
+        .resend(&claims.sub, &tenant_id, &report_id, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_customer_completion_reports(
+    token: String,
+    customer_id: String,
+    auth: State<'_, AuthService>,
+    reports: State<'_, CompletionReportService>,
+) -> Result<Vec<WorkOrderCompletionReport>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    reports
+        .list_for_customer(&claims.sub, &tenant_id, &customer_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn cancel_installation_work_order(
+    token: String,
+    id: String,
+    notes: Option<String>,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<InstallationWorkOrder, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .cancel_installation_work_order(&claims.sub, &tenant_id, &id, notes, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_work_order_visits(
+    token: String,
+    id: String,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<Vec<WorkOrderVisit>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .list_work_order_visits(&claims.sub, &tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn check_in_work_order(
+    token: String,
+    id: String,
+    latitude: f64,
+    longitude: f64,
+    accuracy_m: Option<f64>,
+    notes: Option<String>,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<WorkOrderVisit, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .check_in_work_order(
+            &claims.sub,
+            &tenant_id,
+            &id,
+            WorkOrderCheckRequest {
+                latitude,
+                longitude,
+                accuracy_m,
+                notes,
+                recorded_at: None,
+            },
+            Some("127.0.0.1"),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn check_out_work_order(
+    token: String,
+    id: String,
+    latitude: f64,
+    longitude: f64,
+    accuracy_m: Option<f64>,
+    notes: Option<String>,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+) -> Result<WorkOrderVisit, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .check_out_work_order(
+            &claims.sub,
+            &tenant_id,
+            &id,
+            WorkOrderCheckRequest {
+                latitude,
+                longitude,
+                accuracy_m,
+                notes,
+                recorded_at: None,
+            },
+            Some("127.0.0.1"),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_work_order_templates(
+    token: String,
+    auth: State<'_, AuthService>,
+    checklists: State<'_, WorkOrderChecklistService>,
+) -> Result<Vec<WorkOrderTemplate>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    auth.check_permission(&claims.sub, &tenant_id, "work_orders", "read")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    checklists
+        .list_templates(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_work_order_template(
+    token: String,
+    dto: UpsertWorkOrderTemplateRequest,
+    auth: State<'_, AuthService>,
+    audit: State<'_, AuditService>,
+    checklists: State<'_, WorkOrderChecklistService>,
+) -> Result<WorkOrderTemplate, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    auth.check_permission(&claims.sub, &tenant_id, "work_orders", "templates")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let template = checklists
+        .create_template(&tenant_id, &claims.sub, dto)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let details = serde_json::json!({
+        "name": template.name,
+        "items": template.items.len(),
+        "is_default": template.is_default,
+    })
+    .to_string();
+    audit
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "WORK_ORDER_TEMPLATE_CREATE",
+            "work_order_template",
+            Some(&template.id),
+            Some(details.as_str()),
+            Some("127.0.0.1"),
+        )
+        .await;
+
+    Ok(template)
+}
+
+#[tauri::command]
+pub async fn update_work_order_template(
+    token: String,
+    id: String,
+    dto: UpsertWorkOrderTemplateRequest,
+    auth: State<'_, AuthService>,
+    audit: State<'_, AuditService>,
+    checklists: State<'_, WorkOrderChecklistService>,
+) -> Result<WorkOrderTemplate, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    auth.check_permission(&claims.sub, &tenant_id, "work_orders", "templates")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let template = checklists
+        .update_template(&tenant_id, &id, dto)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let details = serde_json::json!({
+        "name": template.name,
+        "items": template.items.len(),
+        "is_default": template.is_default,
+        "is_active": template.is_active,
+    })
+    .to_string();
+    audit
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "WORK_ORDER_TEMPLATE_UPDATE",
+            "work_order_template",
+            Some(&template.id),
+            Some(details.as_str()),
+            Some("127.0.0.1"),
+        )
+        .await;
+
+    Ok(template)
+}
+
+#[tauri::command]
+pub async fn delete_work_order_template(
+    token: String,
+    id: String,
+    auth: State<'_, AuthService>,
+    audit: State<'_, AuditService>,
+    checklists: State<'_, WorkOrderChecklistService>,
+) -> Result<(), String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    auth.check_permission(&claims.sub, &tenant_id, "work_orders", "templates")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    checklists
+        .delete_template(&tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    audit
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "WORK_ORDER_TEMPLATE_DELETE",
+            "work_order_template",
+            Some(&id),
+            None,
+            Some("127.0.0.1"),
+        )
+        .await;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_work_order_checklist(
+    token: String,
+    id: String,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+    checklists: State<'_, WorkOrderChecklistService>,
+) -> Result<WorkOrderChecklist, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .authorize_work_order_access(&claims.sub, &tenant_id, &id, false)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    checklists
+        .get_checklist(&tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn apply_work_order_checklist_template(
+    token: String,
+    id: String,
+    dto: ApplyWorkOrderTemplateRequest,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+    checklists: State<'_, WorkOrderChecklistService>,
+) -> Result<WorkOrderChecklist, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .authorize_work_order_access(&claims.sub, &tenant_id, &id, true)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    checklists
+        .apply_template(&tenant_id, &id, &dto.template_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_work_order_checklist_item(
+    token: String,
+    id: String,
+    item_id: String,
+    dto: UpdateWorkOrderChecklistItemRequest,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+    checklists: State<'_, WorkOrderChecklistService>,
+) -> Result<WorkOrderChecklist, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .authorize_work_order_access(&claims.sub, &tenant_id, &id, true)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    checklists
+        .update_item(&tenant_id, &id, &item_id, &claims.sub, dto)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_work_order_materials(
+    token: String,
+    id: String,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+    inventory: State<'_, InventoryService>,
+) -> Result<Vec<InventoryMovement>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .authorize_work_order_access(&claims.sub, &tenant_id, &id, false)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    inventory
+        .list_work_order_materials(&tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn consume_work_order_materials(
+    token: String,
+    id: String,
+    dto: ConsumeWorkOrderMaterialsRequest,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+    inventory: State<'_, InventoryService>,
+) -> Result<Vec<InventoryMovement>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .authorize_work_order_access(&claims.sub, &tenant_id, &id, true)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    inventory
+        .consume_for_work_order(&claims.sub, &tenant_id, &id, dto, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn install_work_order_serial(
+    token: String,
+    id: String,
+    dto: InstallInventorySerialRequest,
+    auth: State<'_, AuthService>,
+    customers: State<'_, CustomerService>,
+    inventory: State<'_, InventoryService>,
+) -> Result<InventorySerial, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    customers
+        .authorize_work_order_access(&claims.sub, &tenant_id, &id, true)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    inventory
+        .install_serial(&claims.sub, &tenant_id, &id, dto, Some("127.0.0.1"))
         .await
         .map_err(|e| e.to_string())
 }
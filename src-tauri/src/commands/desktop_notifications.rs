@@ -0,0 +1,42 @@
+//! Desktop Notification Commands
+
+use crate::desktop_notify::{DesktopNotificationPrefs, DesktopNotifier};
+use crate::services::AuthService;
+use tauri::State;
+
+/// Who OS notifications are shown for; `None` on sign-out.
+#[tauri::command]
+pub async fn set_desktop_notification_session(
+    token: Option<String>,
+    auth_service: State<'_, AuthService>,
+    notifier: State<'_, DesktopNotifier>,
+) -> Result<(), String> {
+    let user_id = match token {
+        Some(token) => Some(
+            auth_service
+                .validate_token(&token)
+                .await
+                .map_err(|e| e.to_string())?
+                .sub,
+        ),
+        None => None,
+    };
+    notifier.set_user(user_id);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_desktop_notification_prefs(
+    notifier: State<'_, DesktopNotifier>,
+) -> DesktopNotificationPrefs {
+    notifier.prefs()
+}
+
+#[tauri::command]
+pub fn update_desktop_notification_prefs(
+    prefs: DesktopNotificationPrefs,
+    notifier: State<'_, DesktopNotifier>,
+) -> DesktopNotificationPrefs {
+    notifier.set_prefs(prefs);
+    notifier.prefs()
+}
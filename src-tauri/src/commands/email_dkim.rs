@@ -0,0 +1,91 @@
+//! DKIM signing key for outbound SMTP email
+
+use crate::models::{EmailDkimStatus, GenerateDkimKeyRequest, UpdateDkimRequest};
+use crate::services::{AuthService, EmailDkimService, EmailService};
+use tauri::State;
+
+/// Returns `(user_id, tenant_id)` after checking the settings permission.
+async fn authorize(
+    auth_service: &AuthService,
+    token: &str,
+    action: &str,
+) -> Result<(String, String), String> {
+    let claims = auth_service
+        .validate_token(token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "settings", action)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok((claims.sub, tenant_id))
+}
+
+fn dkim(email_service: &EmailService) -> Result<&EmailDkimService, String> {
+    email_service
+        .dkim()
+        .ok_or_else(|| "DKIM signing is not initialized".to_string())
+}
+
+#[tauri::command]
+pub async fn get_email_dkim(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    email_service: State<'_, EmailService>,
+) -> Result<EmailDkimStatus, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "read").await?;
+    dkim(&email_service)?
+        .status(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn generate_email_dkim_key(
+    token: String,
+    domain: Option<String>,
+    selector: Option<String>,
+    auth_service: State<'_, AuthService>,
+    email_service: State<'_, EmailService>,
+) -> Result<EmailDkimStatus, String> {
+    let (user_id, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    dkim(&email_service)?
+        .generate(
+            &tenant_id,
+            GenerateDkimKeyRequest { domain, selector },
+            Some(&user_id),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_email_dkim(
+    token: String,
+    enabled: bool,
+    auth_service: State<'_, AuthService>,
+    email_service: State<'_, EmailService>,
+) -> Result<EmailDkimStatus, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    dkim(&email_service)?
+        .update(&tenant_id, UpdateDkimRequest { enabled })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_email_dkim_key(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    email_service: State<'_, EmailService>,
+) -> Result<(), String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    dkim(&email_service)?
+        .delete(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,8 +1,8 @@
 //! Email Outbox (admin monitor + retry)
 
 use crate::models::{EmailOutboxItem, EmailOutboxStats, PaginatedResponse};
-use crate::services::AuthService;
-use chrono::Utc;
+use crate::services::{AuthService, EmailOutboxService};
+use chrono::{DateTime, Utc};
 use tauri::State;
 
 #[tauri::command]
@@ -68,6 +68,7 @@ pub async fn list_email_outbox(
               eo.status,
               eo.attempts,
               eo.max_attempts,
+              eo.priority,
               eo.scheduled_at,
               eo.last_error,
               eo.sent_at,
@@ -317,6 +318,41 @@ pub async fn retry_email_outbox(
     Ok(serde_json::json!({ "success": true }))
 }
 
+#[tauri::command]
+pub async fn schedule_email_outbox(
+    token: String,
+    id: String,
+    scheduled_at: DateTime<Utc>,
+    auth_service: State<'_, AuthService>,
+    email_outbox: State<'_, EmailOutboxService>,
+) -> Result<serde_json::Value, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(tid) = claims.tenant_id.as_deref() {
+        auth_service
+            .check_permission(&claims.sub, tid, "email_outbox", "retry")
+            .await
+            .map_err(|e| e.to_string())?;
+    } else if !claims.is_super_admin {
+        return Err("Tenant context required".to_string());
+    }
+
+    let tenant_scope = if claims.is_super_admin {
+        None
+    } else {
+        claims.tenant_id.as_deref()
+    };
+    email_outbox
+        .reschedule(tenant_scope, &id, scheduled_at)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(serde_json::json!({ "success": true }))
+}
+
 #[tauri::command]
 pub async fn delete_email_outbox(
     token: String,
@@ -399,6 +435,7 @@ pub async fn get_email_outbox(
               eo.status,
               eo.attempts,
               eo.max_attempts,
+              eo.priority,
               eo.scheduled_at,
               eo.last_error,
               eo.sent_at,
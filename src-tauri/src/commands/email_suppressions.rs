@@ -0,0 +1,88 @@
+//! Email suppression list (bounced and complaining addresses)
+
+use crate::models::{AddEmailSuppressionRequest, EmailSuppression, PaginatedResponse};
+use crate::services::{AuthService, EmailOutboxService};
+use tauri::State;
+
+/// Resolves the list to work on: the caller's tenant (checked against the
+/// `email_outbox` permission) or, for a super admin outside any tenant, the
+/// global list (`None`).
+async fn authorize(
+    auth_service: &AuthService,
+    token: &str,
+    action: &str,
+) -> Result<Option<String>, String> {
+    let claims = auth_service
+        .validate_token(token)
+        .await
+        .map_err(|e| e.to_string())?;
+    match claims.tenant_id.clone() {
+        Some(tenant_id) => {
+            auth_service
+                .check_permission(&claims.sub, &tenant_id, "email_outbox", action)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(Some(tenant_id))
+        }
+        None if claims.is_super_admin => Ok(None),
+        None => Err("Forbidden".to_string()),
+    }
+}
+
+#[tauri::command]
+pub async fn list_email_suppressions(
+    token: String,
+    search: Option<String>,
+    reason: Option<String>,
+    page: Option<u32>,
+    per_page: Option<u32>,
+    auth_service: State<'_, AuthService>,
+    email_outbox_service: State<'_, EmailOutboxService>,
+) -> Result<PaginatedResponse<EmailSuppression>, String> {
+    let tenant_id = authorize(&auth_service, &token, "read").await?;
+    email_outbox_service
+        .suppressions()
+        .list(
+            tenant_id.as_deref(),
+            search.as_deref(),
+            reason.as_deref(),
+            page.unwrap_or(1),
+            per_page.unwrap_or(25),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn add_email_suppression(
+    token: String,
+    email: String,
+    detail: Option<String>,
+    auth_service: State<'_, AuthService>,
+    email_outbox_service: State<'_, EmailOutboxService>,
+) -> Result<EmailSuppression, String> {
+    let tenant_id = authorize(&auth_service, &token, "delete").await?;
+    email_outbox_service
+        .suppressions()
+        .add(
+            tenant_id.as_deref(),
+            AddEmailSuppressionRequest { email, detail },
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_email_suppression(
+    token: String,
+    id: String,
+    auth_service: State<'_, AuthService>,
+    email_outbox_service: State<'_, EmailOutboxService>,
+) -> Result<(), String> {
+    let tenant_id = authorize(&auth_service, &token, "delete").await?;
+    email_outbox_service
+        .suppressions()
+        .remove(tenant_id.as_deref(), &id)
+        .await
+        .map_err(|e| e.to_string())
+}
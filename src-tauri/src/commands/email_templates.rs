@@ -0,0 +1,169 @@
+//! Email templates (per-tenant HTML emails with preview and test-send)
+
+use crate::models::{
+    EmailTemplate, EmailTemplateVariable, PreviewEmailTemplateRequest, RenderedEmail,
+    TestSendEmailTemplateRequest, UpdateEmailTemplateRequest,
+};
+use crate::services::{AuthService, EmailTemplateService, NotificationService};
+use tauri::State;
+
+/// Returns `(user_id, tenant_id)` after checking the settings permission.
+async fn authorize(
+    auth_service: &AuthService,
+    token: &str,
+    action: &str,
+) -> Result<(String, String), String> {
+    let claims = auth_service
+        .validate_token(token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "settings", action)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok((claims.sub, tenant_id))
+}
+
+fn templates(notification_service: &NotificationService) -> Result<&EmailTemplateService, String> {
+    notification_service
+        .email_templates()
+        .ok_or_else(|| "Email templates are not initialized".to_string())
+}
+
+#[tauri::command]
+pub async fn list_email_templates(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<Vec<EmailTemplate>, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "read").await?;
+    templates(&notification_service)?
+        .list(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_email_template(
+    token: String,
+    code: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<EmailTemplate, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "read").await?;
+    templates(&notification_service)?
+        .get(&tenant_id, &code)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_email_template_variables(
+    token: String,
+    code: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<Vec<EmailTemplateVariable>, String> {
+    authorize(&auth_service, &token, "read").await?;
+    templates(&notification_service)?
+        .variables(&code)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_email_template(
+    token: String,
+    code: String,
+    subject: String,
+    body_html: String,
+    body_text: Option<String>,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<EmailTemplate, String> {
+    let (user_id, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    templates(&notification_service)?
+        .update(
+            &tenant_id,
+            &code,
+            UpdateEmailTemplateRequest {
+                subject,
+                body_html,
+                body_text,
+            },
+            Some(&user_id),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn reset_email_template(
+    token: String,
+    code: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<EmailTemplate, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    templates(&notification_service)?
+        .reset(&tenant_id, &code)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn preview_email_template(
+    token: String,
+    code: String,
+    subject: Option<String>,
+    body_html: Option<String>,
+    body_text: Option<String>,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<RenderedEmail, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "read").await?;
+    templates(&notification_service)?
+        .preview(
+            &tenant_id,
+            &code,
+            PreviewEmailTemplateRequest {
+                subject,
+                body_html,
+                body_text,
+            },
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn test_send_email_template(
+    token: String,
+    code: String,
+    to: Option<String>,
+    subject: Option<String>,
+    body_html: Option<String>,
+    body_text: Option<String>,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<RenderedEmail, String> {
+    let (user_id, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    templates(&notification_service)?
+        .test_send(
+            &tenant_id,
+            &code,
+            &user_id,
+            TestSendEmailTemplateRequest {
+                to,
+                subject,
+                body_html,
+                body_text,
+            },
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,115 @@
+//! Runtime feature flags
+
+use crate::models::{
+    FeatureFlag, FeatureFlagOverride, FeatureFlagWithOverrides, UpsertFeatureFlagRequest,
+};
+use crate::services::{AuthService, FeatureFlagService};
+use std::collections::HashMap;
+use tauri::State;
+
+async fn require_super_admin(auth_service: &AuthService, token: &str) -> Result<(), String> {
+    let claims = auth_service
+        .validate_token(token)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !claims.is_super_admin {
+        return Err("Unauthorized: Superadmin access required".to_string());
+    }
+    Ok(())
+}
+
+/// Every flag evaluated for the caller's tenant.
+#[tauri::command]
+pub async fn evaluate_feature_flags(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    feature_flag_service: State<'_, FeatureFlagService>,
+) -> Result<HashMap<String, bool>, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    feature_flag_service
+        .evaluate_all(claims.tenant_id.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_feature_flags(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    feature_flag_service: State<'_, FeatureFlagService>,
+) -> Result<Vec<FeatureFlagWithOverrides>, String> {
+    require_super_admin(&auth_service, &token).await?;
+    feature_flag_service.list().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn upsert_feature_flag(
+    token: String,
+    key: String,
+    description: Option<String>,
+    enabled: bool,
+    rollout_percentage: i32,
+    auth_service: State<'_, AuthService>,
+    feature_flag_service: State<'_, FeatureFlagService>,
+) -> Result<FeatureFlag, String> {
+    require_super_admin(&auth_service, &token).await?;
+    feature_flag_service
+        .upsert(
+            &key,
+            UpsertFeatureFlagRequest {
+                description,
+                enabled,
+                rollout_percentage,
+            },
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_feature_flag(
+    token: String,
+    key: String,
+    auth_service: State<'_, AuthService>,
+    feature_flag_service: State<'_, FeatureFlagService>,
+) -> Result<(), String> {
+    require_super_admin(&auth_service, &token).await?;
+    feature_flag_service
+        .delete(&key)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_feature_flag_override(
+    token: String,
+    key: String,
+    tenant_id: String,
+    enabled: bool,
+    auth_service: State<'_, AuthService>,
+    feature_flag_service: State<'_, FeatureFlagService>,
+) -> Result<FeatureFlagOverride, String> {
+    require_super_admin(&auth_service, &token).await?;
+    feature_flag_service
+        .set_override(&key, &tenant_id, enabled)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_feature_flag_override(
+    token: String,
+    key: String,
+    tenant_id: String,
+    auth_service: State<'_, AuthService>,
+    feature_flag_service: State<'_, FeatureFlagService>,
+) -> Result<(), String> {
+    require_super_admin(&auth_service, &token).await?;
+    feature_flag_service
+        .remove_override(&key, &tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
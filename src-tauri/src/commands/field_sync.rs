@@ -0,0 +1,45 @@
+use crate::models::{
+    FieldSyncChanges, FieldSyncPullRequest, FieldSyncPushRequest, FieldSyncPushResponse,
+};
+use crate::services::{AuthService, FieldSyncService};
+use tauri::State;
+
+#[tauri::command]
+pub async fn field_sync_pull(
+    token: String,
+    dto: FieldSyncPullRequest,
+    auth: State<'_, AuthService>,
+    svc: State<'_, FieldSyncService>,
+) -> Result<FieldSyncChanges, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.pull(&claims.sub, &tenant_id, dto)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn field_sync_push(
+    token: String,
+    dto: FieldSyncPushRequest,
+    auth: State<'_, AuthService>,
+    svc: State<'_, FieldSyncService>,
+) -> Result<FieldSyncPushResponse, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.push(&claims.sub, &tenant_id, dto, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -58,6 +58,7 @@ pub async fn install_app(
                     key: "app_name".to_string(),
                     value: name,
                     description: Some("Application name".to_string()),
+                    expected_updated_at: None,
                 },
                 None,
                 Some("127.0.0.1"),
@@ -73,6 +74,7 @@ pub async fn install_app(
                     key: "app_public_url".to_string(),
                     value: url,
                     description: Some("Public URL of the application".to_string()),
+                    expected_updated_at: None,
                 },
                 None,
                 Some("127.0.0.1"),
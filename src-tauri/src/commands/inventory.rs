@@ -0,0 +1,281 @@
+use crate::models::{
+    CreateInventoryMovementRequest, InventoryItem, InventoryLocation, InventoryMovement,
+    InventoryScanResult, InventorySerial, InventorySerialBatchResult, InventoryStockLevel,
+    ReceiveInventorySerialsRequest, TransferInventorySerialsRequest, UpsertInventoryItemRequest,
+    UpsertInventoryLocationRequest,
+};
+use crate::services::{AuthService, InventoryService};
+use tauri::State;
+
+#[tauri::command]
+pub async fn list_inventory_items(
+    token: String,
+    include_inactive: Option<bool>,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<Vec<InventoryItem>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.list_items(&claims.sub, &tenant_id, include_inactive.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_inventory_item(
+    token: String,
+    dto: UpsertInventoryItemRequest,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<InventoryItem, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.create_item(&claims.sub, &tenant_id, dto, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_inventory_item(
+    token: String,
+    id: String,
+    dto: UpsertInventoryItemRequest,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<InventoryItem, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.update_item(&claims.sub, &tenant_id, &id, dto, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_inventory_locations(
+    token: String,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<Vec<InventoryLocation>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.list_locations(&claims.sub, &tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_inventory_location(
+    token: String,
+    dto: UpsertInventoryLocationRequest,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<InventoryLocation, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.create_location(&claims.sub, &tenant_id, dto, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_inventory_location(
+    token: String,
+    id: String,
+    dto: UpsertInventoryLocationRequest,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<InventoryLocation, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.update_location(&claims.sub, &tenant_id, &id, dto, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_inventory_stock(
+    token: String,
+    location_id: Option<String>,
+    item_id: Option<String>,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<Vec<InventoryStockLevel>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.list_stock(&claims.sub, &tenant_id, location_id, item_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_inventory_movements(
+    token: String,
+    item_id: Option<String>,
+    location_id: Option<String>,
+    limit: Option<u32>,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<Vec<InventoryMovement>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.list_movements(
+        &claims.sub,
+        &tenant_id,
+        item_id,
+        location_id,
+        limit.unwrap_or(100),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_inventory_movement(
+    token: String,
+    dto: CreateInventoryMovementRequest,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<InventoryMovement, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.create_movement(&claims.sub, &tenant_id, dto, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Resolve a scanned barcode to a registered serial or an item SKU.
+#[tauri::command]
+pub async fn resolve_inventory_scan(
+    token: String,
+    code: String,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<InventoryScanResult, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.resolve_scan(&claims.sub, &tenant_id, &code)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_inventory_serials(
+    token: String,
+    item_id: Option<String>,
+    location_id: Option<String>,
+    status: Option<String>,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<Vec<InventorySerial>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.list_serials(&claims.sub, &tenant_id, item_id, location_id, status)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn receive_inventory_serials(
+    token: String,
+    dto: ReceiveInventorySerialsRequest,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<InventorySerialBatchResult, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.receive_serials(&claims.sub, &tenant_id, dto, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn transfer_inventory_serials(
+    token: String,
+    dto: TransferInventorySerialsRequest,
+    auth: State<'_, AuthService>,
+    svc: State<'_, InventoryService>,
+) -> Result<InventorySerialBatchResult, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.transfer_serials(&claims.sub, &tenant_id, dto, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
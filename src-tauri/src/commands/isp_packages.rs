@@ -1,6 +1,7 @@
 use crate::models::{
-    CreateIspPackageRequest, IspPackage, IspPackageRouterMapping, IspPackageRouterMappingView,
-    PaginatedResponse, UpdateIspPackageRequest, UpsertIspPackageRouterMappingRequest,
+    BandwidthBurst, CreateIspPackageRequest, IspPackage, IspPackageBandwidth,
+    IspPackagePriceHistory, IspPackageRouterMapping, IspPackageRouterMappingView,
+    PaginatedResponse, TrashItem, UpdateIspPackageRequest, UpsertIspPackageRouterMappingRequest,
 };
 use crate::services::{AuthService, IspPackageService};
 use tauri::State;
@@ -45,8 +46,11 @@ pub async fn create_isp_package(
     description: Option<String>,
     features: Option<Vec<String>>,
     is_active: Option<bool>,
+    zone_restricted: Option<bool>,
     price_monthly: Option<f64>,
     price_yearly: Option<f64>,
+    bandwidth: Option<IspPackageBandwidth>,
+    components: Option<Vec<String>>,
     auth: State<'_, AuthService>,
     svc: State<'_, IspPackageService>,
 ) -> Result<IspPackage, String> {
@@ -64,8 +68,11 @@ pub async fn create_isp_package(
         description,
         features,
         is_active,
+        zone_restricted,
         price_monthly,
         price_yearly,
+        bandwidth,
+        components,
     };
     svc.create_package(&claims.sub, &tenant_id, dto, Some("127.0.0.1"))
         .await
@@ -81,8 +88,11 @@ pub async fn update_isp_package(
     description: Option<String>,
     features: Option<Vec<String>>,
     is_active: Option<bool>,
+    zone_restricted: Option<bool>,
     price_monthly: Option<f64>,
     price_yearly: Option<f64>,
+    bandwidth: Option<IspPackageBandwidth>,
+    components: Option<Vec<String>>,
     auth: State<'_, AuthService>,
     svc: State<'_, IspPackageService>,
 ) -> Result<IspPackage, String> {
@@ -100,8 +110,11 @@ pub async fn update_isp_package(
         description,
         features,
         is_active,
+        zone_restricted,
         price_monthly,
         price_yearly,
+        bandwidth,
+        components,
     };
 
     svc.update_package(&claims.sub, &tenant_id, &id, dto, Some("127.0.0.1"))
@@ -129,6 +142,65 @@ pub async fn delete_isp_package(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn list_deleted_isp_packages(
+    token: String,
+    auth: State<'_, AuthService>,
+    svc: State<'_, IspPackageService>,
+) -> Result<Vec<TrashItem>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.list_deleted_packages(&claims.sub, &tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restore_isp_package(
+    token: String,
+    id: String,
+    auth: State<'_, AuthService>,
+    svc: State<'_, IspPackageService>,
+) -> Result<(), String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.restore_package(&claims.sub, &tenant_id, &id, Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_isp_package_price_history(
+    token: String,
+    id: String,
+    auth: State<'_, AuthService>,
+    svc: State<'_, IspPackageService>,
+) -> Result<Vec<IspPackagePriceHistory>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    svc.list_price_history(&claims.sub, &tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn list_isp_package_router_mappings(
     token: String,
@@ -156,6 +228,7 @@ pub async fn upsert_isp_package_router_mapping(
     package_id: String,
     router_profile_name: String,
     address_pool: Option<String>,
+    burst: Option<BandwidthBurst>,
     auth: State<'_, AuthService>,
     svc: State<'_, IspPackageService>,
 ) -> Result<IspPackageRouterMapping, String> {
@@ -172,6 +245,7 @@ pub async fn upsert_isp_package_router_mapping(
         package_id,
         router_profile_name,
         address_pool,
+        burst: burst.unwrap_or_default(),
     };
 
     svc.upsert_router_mapping(&claims.sub, &tenant_id, dto, Some("127.0.0.1"))
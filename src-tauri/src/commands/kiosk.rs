@@ -0,0 +1,83 @@
+//! Wallboard Kiosk Window Commands
+//!
+//! The NOC wallboard can run in a second frameless, always-on-top window,
+//! e.g. on an office TV, while the main window stays usable.
+
+use tauri::{AppHandle, Manager, Monitor, WebviewUrl, WebviewWindowBuilder};
+
+pub const WALLBOARD_WINDOW: &str = "wallboard";
+const WALLBOARD_PATH: &str = "/admin/network/noc/wallboard";
+
+/// Only a tenant's wallboard route may be opened: `/<slug>/admin/network/noc/wallboard`,
+/// or the bare path on a tenant's own domain.
+fn is_wallboard_route(route: &str) -> bool {
+    let Some(prefix) = route.strip_suffix(WALLBOARD_PATH) else {
+        return false;
+    };
+    match prefix.strip_prefix('/') {
+        Some(slug) => {
+            !slug.is_empty()
+                && slug
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        }
+        None => prefix.is_empty(),
+    }
+}
+
+/// A screen other than the main window's (the TV), else the main window's.
+fn kiosk_monitor(app: &AppHandle) -> Option<Monitor> {
+    let main = app
+        .get_webview_window("main")
+        .and_then(|w| w.current_monitor().ok().flatten());
+    let main_position = main.as_ref().map(|m| *m.position());
+    app.available_monitors()
+        .ok()?
+        .into_iter()
+        .find(|m| Some(*m.position()) != main_position)
+        .or(main)
+}
+
+#[tauri::command]
+pub async fn open_wallboard_window(app: AppHandle, route: String) -> Result<(), String> {
+    let route = route.trim();
+    if !is_wallboard_route(route) {
+        return Err("Not a wallboard route".to_string());
+    }
+
+    let window = match app.get_webview_window(WALLBOARD_WINDOW) {
+        Some(window) => window,
+        None => {
+            let window =
+                WebviewWindowBuilder::new(&app, WALLBOARD_WINDOW, WebviewUrl::App(route.into()))
+                    .title("NOC Wallboard")
+                    .decorations(false)
+                    .always_on_top(true)
+                    .visible(false)
+                    .build()
+                    .map_err(|e| e.to_string())?;
+            match kiosk_monitor(&app) {
+                Some(monitor) => {
+                    let _ = window.set_position(*monitor.position());
+                    let _ = window.set_size(*monitor.size());
+                }
+                None => {
+                    let _ = window.maximize();
+                }
+            }
+            window
+        }
+    };
+    let _ = window.show();
+    let _ = window.unminimize();
+    let _ = window.set_focus();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn close_wallboard_window(app: AppHandle) -> Result<(), String> {
+    match app.get_webview_window(WALLBOARD_WINDOW) {
+        Some(window) => window.close().map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
@@ -1,11 +1,11 @@
 //! MikroTik router inventory + monitoring commands (tenant admin).
 
 use crate::models::{
-    CreateMikrotikRouterRequest, MikrotikAlert, MikrotikIncident, MikrotikInterfaceCounter,
-    MikrotikInterfaceMetric, MikrotikIpPool, MikrotikLogEntry, MikrotikLogSyncResult,
-    MikrotikPppProfile, MikrotikRouter, MikrotikRouterMetric, MikrotikRouterNocRow,
-    MikrotikTestResult, PaginatedResponse, SimulateMikrotikIncidentRequest,
-    UpdateMikrotikIncidentRequest, UpdateMikrotikRouterRequest,
+    CreateMikrotikRouterRequest, MikrotikAlert, MikrotikIncident, MikrotikIncidentImpact,
+    MikrotikInterfaceCounter, MikrotikInterfaceMetric, MikrotikIpPool, MikrotikLogEntry,
+    MikrotikLogSyncResult, MikrotikPppProfile, MikrotikRouter, MikrotikRouterMetric,
+    MikrotikRouterNocRow, MikrotikTestResult, PaginatedResponse, SimulateMikrotikIncidentRequest,
+    TrashItem, UpdateMikrotikIncidentRequest, UpdateMikrotikRouterRequest,
 };
 use crate::services::{AuditService, AuthService, MikrotikService};
 use tauri::State;
@@ -95,6 +95,7 @@ pub async fn list_mikrotik_incidents(
     mikrotik: State<'_, MikrotikService>,
     active_only: Option<bool>,
     limit: Option<u32>,
+    sort: Option<String>,
 ) -> Result<Vec<MikrotikIncident>, String> {
     let claims = auth
         .validate_token(&token)
@@ -112,6 +113,7 @@ pub async fn list_mikrotik_incidents(
         .list_incidents(
             &tenant_id,
             active_only.unwrap_or(true),
+            sort.as_deref() == Some("impact"),
             limit.unwrap_or(200),
         )
         .await
@@ -288,6 +290,56 @@ pub async fn resolve_mikrotik_incident(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn get_mikrotik_incident_impact(
+    token: String,
+    id: String,
+    auth: State<'_, AuthService>,
+    mikrotik: State<'_, MikrotikService>,
+) -> Result<Vec<MikrotikIncidentImpact>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    auth.check_permission(&claims.sub, &tenant_id, "network_routers", "read")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    mikrotik
+        .list_incident_impact(&tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn refresh_mikrotik_incident_impact(
+    token: String,
+    id: String,
+    auth: State<'_, AuthService>,
+    mikrotik: State<'_, MikrotikService>,
+) -> Result<MikrotikIncident, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    auth.check_permission(&claims.sub, &tenant_id, "network_routers", "manage")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    mikrotik
+        .refresh_incident_impact(&tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn update_mikrotik_incident(
     token: String,
@@ -628,6 +680,7 @@ pub async fn update_mikrotik_router(
     longitude: Option<f64>,
     maintenance_until: Option<String>,
     maintenance_reason: Option<String>,
+    expected_updated_at: Option<String>,
     auth: State<'_, AuthService>,
     mikrotik: State<'_, MikrotikService>,
     audit: State<'_, AuditService>,
@@ -644,7 +697,7 @@ pub async fn update_mikrotik_router(
         .await
         .map_err(|e| e.to_string())?;
 
-    let router = mikrotik
+    let (router, change) = mikrotik
         .update_router(
             &tenant_id,
             &id,
@@ -663,13 +716,19 @@ pub async fn update_mikrotik_router(
                     .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
                     .map(|dt| dt.with_timezone(&chrono::Utc)),
                 maintenance_reason,
+                expected_updated_at: expected_updated_at
+                    .as_deref()
+                    .map(chrono::DateTime::parse_from_rfc3339)
+                    .transpose()
+                    .map_err(|e| format!("Invalid expected_updated_at: {}", e))?
+                    .map(|dt| dt.with_timezone(&chrono::Utc)),
             },
         )
         .await
         .map_err(|e| e.to_string())?;
 
     audit
-        .log(
+        .log_change(
             Some(&claims.sub),
             Some(&tenant_id),
             "update",
@@ -680,6 +739,7 @@ pub async fn update_mikrotik_router(
                 router.name, router.host
             )),
             None,
+            &change,
         )
         .await;
 
@@ -734,6 +794,71 @@ pub async fn delete_mikrotik_router(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn list_deleted_mikrotik_routers(
+    token: String,
+    auth: State<'_, AuthService>,
+    mikrotik: State<'_, MikrotikService>,
+) -> Result<Vec<TrashItem>, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    auth.check_permission(&claims.sub, &tenant_id, "network_routers", "manage")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    mikrotik
+        .list_deleted_routers(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restore_mikrotik_router(
+    token: String,
+    id: String,
+    auth: State<'_, AuthService>,
+    mikrotik: State<'_, MikrotikService>,
+    audit: State<'_, AuditService>,
+) -> Result<MikrotikRouter, String> {
+    let claims = auth
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "No tenant ID in token".to_string())?;
+
+    auth.check_permission(&claims.sub, &tenant_id, "network_routers", "manage")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let router = mikrotik
+        .restore_router(&tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let details = format!("Restored router '{}' ({})", router.name, router.host);
+    audit
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "restore",
+            "mikrotik_router",
+            Some(&id),
+            Some(&details),
+            None,
+        )
+        .await;
+
+    Ok(router)
+}
+
 #[tauri::command]
 pub async fn test_mikrotik_router(
     token: String,
@@ -5,23 +5,38 @@ pub mod audit;
 pub mod auth;
 pub mod backup;
 pub mod customers;
+pub mod desktop_notifications;
+pub mod email_dkim;
 pub mod email_outbox;
+pub mod email_suppressions;
+pub mod email_templates;
+pub mod feature_flags;
+pub mod field_sync;
 pub mod install;
+pub mod inventory;
 pub mod isp_packages;
+pub mod kiosk;
 pub mod mikrotik;
+pub mod notification_routing;
+pub mod notification_templates;
 pub mod notifications;
 pub mod payment;
 pub mod plans;
 pub mod pppoe;
+pub mod printing;
 pub mod roles;
+pub mod server;
 pub mod settings;
 pub mod storage;
 pub mod superadmin;
 pub mod support;
 pub mod system;
 pub mod team;
+pub mod telegram;
 pub mod tenant;
+pub mod updater;
 pub mod users;
+pub mod whatsapp;
 
 #[tauri::command]
 pub fn get_app_version() -> String {
@@ -32,20 +47,35 @@ pub use announcements::*;
 pub use auth::*;
 pub use backup::*;
 pub use customers::*;
+pub use desktop_notifications::*;
+pub use email_dkim::*;
 pub use email_outbox::*;
+pub use email_suppressions::*;
+pub use email_templates::*;
+pub use feature_flags::*;
+pub use field_sync::*;
 pub use install::*;
+pub use inventory::*;
 pub use isp_packages::*;
+pub use kiosk::*;
 pub use mikrotik::*;
+pub use notification_routing::*;
+pub use notification_templates::*;
 pub use notifications::*;
 pub use payment::*;
 pub use plans::*;
 pub use pppoe::*;
+pub use printing::*;
 pub use roles::*;
+pub use server::*;
 pub use settings::*;
 pub use storage::*;
 pub use superadmin::*;
 pub use support::*;
 pub use system::*;
 pub use team::*;
+pub use telegram::*;
 pub use tenant::*;
+pub use updater::*;
 pub use users::*;
+pub use whatsapp::*;
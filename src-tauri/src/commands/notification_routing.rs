@@ -0,0 +1,153 @@
+//! Notification routing rules (per-tenant channel/recipient overrides for admin alerts)
+
+use crate::models::{
+    EvaluateNotificationRoutingRequest, NotificationRoutingEvaluation, NotificationRoutingRule,
+    UpsertNotificationRoutingRuleRequest,
+};
+use crate::services::{AuthService, NotificationRoutingService, NotificationService};
+use chrono::{DateTime, Utc};
+use tauri::State;
+
+/// Returns `(user_id, tenant_id)` after checking the settings permission.
+async fn authorize(
+    auth_service: &AuthService,
+    token: &str,
+    action: &str,
+) -> Result<(String, String), String> {
+    let claims = auth_service
+        .validate_token(token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "settings", action)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok((claims.sub, tenant_id))
+}
+
+fn routing(
+    notification_service: &NotificationService,
+) -> Result<&NotificationRoutingService, String> {
+    notification_service
+        .routing()
+        .ok_or_else(|| "Notification routing is not initialized".to_string())
+}
+
+#[tauri::command]
+pub async fn list_notification_rules(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<Vec<NotificationRoutingRule>, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "read").await?;
+    routing(&notification_service)?
+        .list(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_notification_rule(
+    token: String,
+    name: String,
+    priority: Option<i32>,
+    is_enabled: Option<bool>,
+    category: Option<String>,
+    min_severity: Option<String>,
+    time_window: Option<String>,
+    channels: Vec<String>,
+    recipients: Option<Vec<String>>,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<NotificationRoutingRule, String> {
+    let (user_id, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    let req = UpsertNotificationRoutingRuleRequest {
+        name,
+        priority,
+        is_enabled,
+        category,
+        min_severity,
+        time_window,
+        channels,
+        recipients,
+    };
+    routing(&notification_service)?
+        .create(&tenant_id, req, Some(&user_id))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn update_notification_rule(
+    token: String,
+    id: String,
+    name: String,
+    priority: Option<i32>,
+    is_enabled: Option<bool>,
+    category: Option<String>,
+    min_severity: Option<String>,
+    time_window: Option<String>,
+    channels: Vec<String>,
+    recipients: Option<Vec<String>>,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<NotificationRoutingRule, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    let req = UpsertNotificationRoutingRuleRequest {
+        name,
+        priority,
+        is_enabled,
+        category,
+        min_severity,
+        time_window,
+        channels,
+        recipients,
+    };
+    routing(&notification_service)?
+        .update(&tenant_id, &id, req)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_notification_rule(
+    token: String,
+    id: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<(), String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    routing(&notification_service)?
+        .delete(&tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn evaluate_notification_routing(
+    token: String,
+    category: String,
+    severity: String,
+    at: Option<DateTime<Utc>>,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<NotificationRoutingEvaluation, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "read").await?;
+    routing(&notification_service)?
+        .evaluate(
+            &tenant_id,
+            EvaluateNotificationRoutingRequest {
+                category,
+                severity,
+                at,
+            },
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
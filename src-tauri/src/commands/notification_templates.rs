@@ -0,0 +1,101 @@
+//! Notification templates (per-tenant overrides of built-in notification texts)
+
+use crate::models::{
+    NotificationTemplate, PreviewNotificationTemplateRequest, RenderedNotification,
+    UpdateNotificationTemplateRequest,
+};
+use crate::services::{AuthService, NotificationService};
+use tauri::State;
+
+/// Returns `(user_id, tenant_id)` after checking the settings permission.
+async fn authorize(
+    auth_service: &AuthService,
+    token: &str,
+    action: &str,
+) -> Result<(String, String), String> {
+    let claims = auth_service
+        .validate_token(token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "settings", action)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok((claims.sub, tenant_id))
+}
+
+#[tauri::command]
+pub async fn list_notification_templates(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<Vec<NotificationTemplate>, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "read").await?;
+    notification_service
+        .templates()
+        .list(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_notification_template(
+    token: String,
+    code: String,
+    title: String,
+    body: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<NotificationTemplate, String> {
+    let (user_id, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    notification_service
+        .templates()
+        .update(
+            &tenant_id,
+            &code,
+            UpdateNotificationTemplateRequest { title, body },
+            Some(&user_id),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn reset_notification_template(
+    token: String,
+    code: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<NotificationTemplate, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    notification_service
+        .templates()
+        .reset(&tenant_id, &code)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn preview_notification_template(
+    token: String,
+    code: String,
+    title: Option<String>,
+    body: Option<String>,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<RenderedNotification, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "read").await?;
+    notification_service
+        .templates()
+        .preview(
+            &tenant_id,
+            &code,
+            PreviewNotificationTemplateRequest { title, body },
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
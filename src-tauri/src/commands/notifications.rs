@@ -1,16 +1,26 @@
 use crate::models::{
-    CreatePushSubscriptionRequest, Notification, NotificationPreference, PaginatedResponse,
-    UpdatePreferenceRequest,
+    CreatePushSubscriptionRequest, Notification, NotificationDeliveryFilter,
+    NotificationDeliveryReport, NotificationFilter, NotificationPreference, NotificationQuietHours,
+    PaginatedResponse, UpdatePreferenceRequest, UpdateQuietHoursRequest, VapidPublicKeyResponse,
 };
 use crate::services::{AuthService, NotificationService};
+use chrono::{DateTime, Utc};
 use tauri::State;
 
-/// List notifications with pagination
+/// List notifications with pagination and filters
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn list_notifications(
     token: String,
     page: Option<u32>,
     per_page: Option<u32>,
+    category: Option<String>,
+    notification_type: Option<String>,
+    is_read: Option<bool>,
+    archived: Option<bool>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    search: Option<String>,
     notification_service: State<'_, NotificationService>,
     auth_service: State<'_, AuthService>,
 ) -> Result<PaginatedResponse<Notification>, String> {
@@ -21,9 +31,18 @@ pub async fn list_notifications(
 
     let page = page.unwrap_or(1);
     let per_page = per_page.unwrap_or(20);
+    let filter = NotificationFilter {
+        category,
+        notification_type,
+        is_read,
+        archived,
+        from,
+        to,
+        search,
+    };
 
     notification_service
-        .list_notifications(&claims.sub, page, per_page)
+        .list_notifications(&claims.sub, page, per_page, &filter)
         .await
         .map_err(|e| e.to_string())
 }
@@ -67,6 +86,28 @@ pub async fn mark_as_read(
         .map_err(|e| e.to_string())
 }
 
+/// Archive notifications by id (`archived = false` restores them)
+#[tauri::command]
+pub async fn archive_notifications(
+    token: String,
+    ids: Vec<String>,
+    archived: Option<bool>,
+    notification_service: State<'_, NotificationService>,
+    auth_service: State<'_, AuthService>,
+) -> Result<serde_json::Value, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let count = notification_service
+        .archive_notifications(&claims.sub, &ids, archived.unwrap_or(true))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(serde_json::json!({ "count": count }))
+}
+
 /// Mark all notifications as read
 #[tauri::command]
 pub async fn mark_all_as_read(
@@ -122,6 +163,112 @@ pub async fn get_preferences(
         .map_err(|e| e.to_string())
 }
 
+/// Get the caller's quiet hours
+#[tauri::command]
+pub async fn get_quiet_hours(
+    token: String,
+    notification_service: State<'_, NotificationService>,
+    auth_service: State<'_, AuthService>,
+) -> Result<NotificationQuietHours, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    notification_service
+        .quiet_hours()
+        .ok_or_else(|| "Quiet hours are not initialized".to_string())?
+        .get(&claims.sub, claims.tenant_id.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Update the caller's quiet hours
+#[tauri::command]
+pub async fn update_quiet_hours(
+    token: String,
+    enabled: bool,
+    start: String,
+    end: String,
+    timezone: Option<String>,
+    notification_service: State<'_, NotificationService>,
+    auth_service: State<'_, AuthService>,
+) -> Result<NotificationQuietHours, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    notification_service
+        .quiet_hours()
+        .ok_or_else(|| "Quiet hours are not initialized".to_string())?
+        .update(
+            &claims.sub,
+            claims.tenant_id.as_deref(),
+            UpdateQuietHoursRequest {
+                enabled,
+                start,
+                end,
+                timezone,
+            },
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Per-channel delivery report for the caller's tenant
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_notification_delivery_report(
+    token: String,
+    page: Option<u32>,
+    per_page: Option<u32>,
+    channel: Option<String>,
+    status: Option<String>,
+    category: Option<String>,
+    user_id: Option<String>,
+    notification_id: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    notification_service: State<'_, NotificationService>,
+    auth_service: State<'_, AuthService>,
+) -> Result<NotificationDeliveryReport, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "notification_reports", "read")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let filter = NotificationDeliveryFilter {
+        channel,
+        status,
+        category,
+        user_id,
+        notification_id,
+        from,
+        to,
+    };
+    notification_service
+        .deliveries()
+        .report(
+            &tenant_id,
+            &filter,
+            page.unwrap_or(1),
+            per_page.unwrap_or(25),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Update notification preference
 #[tauri::command]
 pub async fn update_preference(
@@ -149,6 +296,25 @@ pub async fn update_preference(
         .map_err(|e| e.to_string())
 }
 
+/// Application server key the browser must subscribe with
+#[tauri::command]
+pub async fn get_vapid_public_key(
+    token: String,
+    notification_service: State<'_, NotificationService>,
+    auth_service: State<'_, AuthService>,
+) -> Result<VapidPublicKeyResponse, String> {
+    auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let web_push = notification_service
+        .web_push()
+        .ok_or_else(|| "Web Push is not initialized".to_string())?;
+    let public_key = web_push.public_key().await.map_err(|e| e.to_string())?;
+    Ok(VapidPublicKeyResponse { public_key })
+}
+
 /// Subscribe to push notifications
 #[tauri::command]
 pub async fn subscribe_push(
@@ -156,6 +322,7 @@ pub async fn subscribe_push(
     endpoint: String,
     p256dh: String,
     auth: String,
+    expiration_time: Option<i64>,
     notification_service: State<'_, NotificationService>,
     auth_service: State<'_, AuthService>,
 ) -> Result<(), String> {
@@ -168,6 +335,7 @@ pub async fn subscribe_push(
         endpoint,
         p256dh,
         auth,
+        expiration_time,
     };
 
     notification_service
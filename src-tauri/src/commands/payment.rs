@@ -4,9 +4,10 @@ use crate::models::{
     BankAccount, BillingCollectionLogView, CreateBankAccountRequest, Invoice,
     InvoiceReminderLogView,
 };
+use crate::services::feature_flag_service::FLAG_PLAN_SELF_SERVE_UPGRADE;
 use crate::services::{
-    AuthService, BillingCollectionRunResult, BulkGenerateInvoicesResult, Claims, PaymentService,
-    PlanService,
+    AuthService, BillingCollectionRunResult, BulkGenerateInvoicesResult, Claims,
+    FeatureFlagService, PaymentService, PlanService, PlanUpgradeCheckout, PlanUpgradeQuote,
 };
 use chrono::{DateTime, Utc};
 use serde::Serialize;
@@ -21,7 +22,7 @@ pub struct FxRateResponse {
     pub fetched_at: DateTime<Utc>,
 }
 
-async fn require_payment_read_access(
+pub(crate) async fn require_payment_read_access(
     auth_service: &AuthService,
     claims: &Claims,
 ) -> Result<(), String> {
@@ -72,7 +73,7 @@ async fn require_work_order_manage_access(
         .map_err(|e| e.to_string())
 }
 
-async fn authorize_invoice_access(
+pub(crate) async fn authorize_invoice_access(
     claims: &Claims,
     payment_service: &PaymentService,
     invoice_id: &str,
@@ -188,6 +189,53 @@ pub async fn create_invoice_for_plan(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn quote_plan_upgrade(
+    token: String,
+    plan_id: String,
+    billing_cycle: String,
+    auth_service: State<'_, AuthService>,
+    payment_service: State<'_, PaymentService>,
+) -> Result<PlanUpgradeQuote, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    require_payment_read_access(&auth_service, &claims).await?;
+    let tenant_id = claims.tenant_id.ok_or("No tenant context")?;
+
+    payment_service
+        .quote_plan_upgrade(&tenant_id, &plan_id, &billing_cycle)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn checkout_plan_upgrade(
+    token: String,
+    plan_id: String,
+    billing_cycle: String,
+    auth_service: State<'_, AuthService>,
+    feature_flag_service: State<'_, FeatureFlagService>,
+    payment_service: State<'_, PaymentService>,
+) -> Result<PlanUpgradeCheckout, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    require_payment_manage_access(&auth_service, &claims).await?;
+    let tenant_id = claims.tenant_id.ok_or("No tenant context")?;
+    feature_flag_service
+        .require(FLAG_PLAN_SELF_SERVE_UPGRADE, Some(&tenant_id))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    payment_service
+        .checkout_plan_upgrade(&tenant_id, &plan_id, &billing_cycle)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_invoice(
     token: String,
@@ -559,6 +607,27 @@ pub async fn verify_payment(
         .map_err(|e| e.to_string())
 }
 
+/// Recomputes a pending package invoice from the price in effect for its period.
+#[tauri::command]
+pub async fn reprice_customer_package_invoice(
+    token: String,
+    invoice_id: String,
+    auth_service: State<'_, AuthService>,
+    payment_service: State<'_, PaymentService>,
+) -> Result<Invoice, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    require_payment_manage_access(&auth_service, &claims).await?;
+    let invoice = authorize_invoice_access(&claims, &payment_service, &invoice_id).await?;
+
+    payment_service
+        .reprice_customer_package_invoice(&invoice.tenant_id, &invoice_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn verify_customer_package_payment(
     token: String,
@@ -114,6 +114,8 @@ pub async fn create_plan(
     is_active: Option<bool>,
     is_default: Option<bool>,
     sort_order: Option<i32>,
+    trial_days: Option<i32>,
+    trial_expiry_action: Option<String>,
     auth_service: State<'_, AuthService>,
     plan_service: State<'_, PlanService>,
 ) -> Result<Plan, String> {
@@ -135,6 +137,8 @@ pub async fn create_plan(
         is_active,
         is_default,
         sort_order,
+        trial_days,
+        trial_expiry_action,
     };
 
     plan_service
@@ -157,6 +161,8 @@ pub async fn update_plan(
     is_active: Option<bool>,
     is_default: Option<bool>,
     sort_order: Option<i32>,
+    trial_days: Option<i32>,
+    trial_expiry_action: Option<String>,
     auth_service: State<'_, AuthService>,
     plan_service: State<'_, PlanService>,
 ) -> Result<Plan, String> {
@@ -178,6 +184,8 @@ pub async fn update_plan(
         is_active,
         is_default,
         sort_order,
+        trial_days,
+        trial_expiry_action,
     };
 
     plan_service
@@ -282,6 +290,30 @@ pub async fn assign_plan_to_tenant(
         .map_err(|e| e.to_string())
 }
 
+/// Start a trial of a plan for a tenant
+#[tauri::command]
+pub async fn start_tenant_trial(
+    token: String,
+    tenant_id: String,
+    plan_id: String,
+    auth_service: State<'_, AuthService>,
+    plan_service: State<'_, PlanService>,
+) -> Result<TenantSubscription, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !claims.is_super_admin {
+        return Err("Unauthorized: Superadmin access required".to_string());
+    }
+
+    plan_service
+        .start_trial(&tenant_id, &plan_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Get tenant subscription
 #[tauri::command]
 pub async fn get_tenant_subscription(
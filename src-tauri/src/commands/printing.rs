@@ -0,0 +1,69 @@
+//! Receipt Printing Commands
+
+use super::payment::{authorize_invoice_access, require_payment_read_access};
+use crate::services::receipt_printer::PrintResult;
+use crate::services::{AuthService, Claims, PaymentService, ReceiptPrinterService};
+use tauri::State;
+
+async fn cashier_claims(auth_service: &AuthService, token: &str) -> Result<Claims, String> {
+    let claims = auth_service
+        .validate_token(token)
+        .await
+        .map_err(|e| e.to_string())?;
+    require_payment_read_access(auth_service, &claims).await?;
+    Ok(claims)
+}
+
+/// The receipt or short invoice (`kind`) as plain text, as it would print.
+#[tauri::command]
+pub async fn preview_invoice_print(
+    token: String,
+    invoice_id: String,
+    kind: String,
+    auth_service: State<'_, AuthService>,
+    payment_service: State<'_, PaymentService>,
+    printer: State<'_, ReceiptPrinterService>,
+) -> Result<String, String> {
+    let claims = cashier_claims(&auth_service, &token).await?;
+    let invoice = authorize_invoice_access(&claims, &payment_service, &invoice_id).await?;
+    printer
+        .layout(&invoice.tenant_id, &invoice, &kind, Some(&claims.sub))
+        .await
+        .map(|layout| layout.to_text())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn print_invoice(
+    token: String,
+    invoice_id: String,
+    kind: String,
+    auth_service: State<'_, AuthService>,
+    payment_service: State<'_, PaymentService>,
+    printer: State<'_, ReceiptPrinterService>,
+) -> Result<PrintResult, String> {
+    let claims = cashier_claims(&auth_service, &token).await?;
+    let invoice = authorize_invoice_access(&claims, &payment_service, &invoice_id).await?;
+    let layout = printer
+        .layout(&invoice.tenant_id, &invoice, &kind, Some(&claims.sub))
+        .await
+        .map_err(|e| e.to_string())?;
+    printer
+        .print(&invoice.tenant_id, &layout)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn print_test_receipt(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    printer: State<'_, ReceiptPrinterService>,
+) -> Result<PrintResult, String> {
+    let claims = cashier_claims(&auth_service, &token).await?;
+    let tenant_id = claims.tenant_id.ok_or("No tenant context")?;
+    printer
+        .print_test(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,9 +1,7 @@
 //! RBAC (Roles and Permissions) commands
 
-use crate::http::{websocket::WsEvent, WsHub};
 use crate::models::{CreateRoleDto, Permission, RoleWithPermissions, UpdateRoleDto};
 use crate::services::{AuthService, RoleService};
-use std::sync::Arc;
 use tauri::State;
 
 /// List all roles (global + tenant-specific)
@@ -73,7 +71,6 @@ pub async fn create_new_role(
     permissions: Vec<String>,
     auth: State<'_, AuthService>,
     role_service: State<'_, RoleService>,
-    ws_hub: State<'_, Arc<WsHub>>,
 ) -> Result<RoleWithPermissions, String> {
     let claims = auth
         .validate_token(&token)
@@ -103,11 +100,6 @@ pub async fn create_new_role(
         .await
         .map_err(|e| e.to_string())?;
 
-    // Broadcast role created event
-    ws_hub.broadcast(WsEvent::RoleCreated {
-        role_id: role.id.clone(),
-    });
-
     Ok(role)
 }
 
@@ -121,9 +113,9 @@ pub async fn update_existing_role(
     description: Option<String>,
     level: Option<i32>,
     permissions: Option<Vec<String>>,
+    expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
     auth: State<'_, AuthService>,
     role_service: State<'_, RoleService>,
-    ws_hub: State<'_, Arc<WsHub>>,
 ) -> Result<RoleWithPermissions, String> {
     let claims = auth
         .validate_token(&token)
@@ -146,6 +138,7 @@ pub async fn update_existing_role(
         description,
         level,
         permissions,
+        expected_updated_at,
     };
 
     let role = role_service
@@ -159,11 +152,6 @@ pub async fn update_existing_role(
         .await
         .map_err(|e| e.to_string())?;
 
-    // Broadcast role updated event
-    ws_hub.broadcast(WsEvent::RoleUpdated {
-        role_id: role.id.clone(),
-    });
-
     Ok(role)
 }
 
@@ -174,7 +162,6 @@ pub async fn delete_existing_role(
     role_id: String,
     auth: State<'_, AuthService>,
     role_service: State<'_, RoleService>,
-    ws_hub: State<'_, Arc<WsHub>>,
 ) -> Result<bool, String> {
     let claims = auth
         .validate_token(&token)
@@ -202,10 +189,5 @@ pub async fn delete_existing_role(
         .await
         .map_err(|e| e.to_string())?;
 
-    // Broadcast role deleted event
-    if deleted {
-        ws_hub.broadcast(WsEvent::RoleDeleted { role_id });
-    }
-
     Ok(deleted)
 }
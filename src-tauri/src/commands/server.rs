@@ -0,0 +1,84 @@
+//! Embedded HTTP Server Commands
+
+use crate::http::binding::ServerStatus;
+use crate::http::{ServerBinding, ServerControl};
+use crate::models::UpsertSettingDto;
+use crate::services::{AuthService, SettingsService};
+use std::sync::Arc;
+use tauri::State;
+
+async fn require_super_admin(auth_service: &AuthService, token: &str) -> Result<String, String> {
+    let claims = auth_service
+        .validate_token(token)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !claims.is_super_admin {
+        return Err("Unauthorized: Super Admin access required".to_string());
+    }
+    Ok(claims.sub)
+}
+
+#[tauri::command]
+pub async fn get_http_server_config(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    server_control: State<'_, Arc<ServerControl>>,
+) -> Result<ServerStatus, String> {
+    require_super_admin(&auth_service, &token).await?;
+    Ok(server_control.status())
+}
+
+/// Saves the binding and restarts the listener on it right away.
+#[tauri::command]
+pub async fn update_http_server_config(
+    token: String,
+    enabled: bool,
+    bind_address: String,
+    port: u16,
+    auth_service: State<'_, AuthService>,
+    settings_service: State<'_, SettingsService>,
+    server_control: State<'_, Arc<ServerControl>>,
+) -> Result<ServerStatus, String> {
+    let actor_id = require_super_admin(&auth_service, &token).await?;
+    let binding = ServerBinding {
+        enabled,
+        bind_address: bind_address.trim().to_string(),
+        port,
+    };
+    binding.socket_addr().map_err(|e| e.to_string())?;
+
+    for (key, value, description) in [
+        (
+            "http_server_enabled",
+            binding.enabled.to_string(),
+            "Run the embedded HTTP API",
+        ),
+        (
+            "http_server_bind_address",
+            binding.bind_address.clone(),
+            "Address the embedded HTTP API listens on",
+        ),
+        (
+            "http_server_port",
+            binding.port.to_string(),
+            "Port the embedded HTTP API listens on",
+        ),
+    ] {
+        settings_service
+            .upsert(
+                None,
+                UpsertSettingDto {
+                    key: key.to_string(),
+                    value,
+                    description: Some(description.to_string()),
+                    expected_updated_at: None,
+                },
+                Some(&actor_id),
+                Some("127.0.0.1"),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(server_control.apply(binding).await)
+}
@@ -1,12 +1,11 @@
 //! Settings Commands
 
-use crate::http::websocket::{WsEvent, WsHub};
+use crate::http::websocket::WsEvent;
 use crate::models::{Setting, UpsertSettingDto};
 use crate::services::auth_service::AuthSettings;
-use crate::services::{AuthService, SettingsService};
+use crate::services::{AuthService, EventOutboxService, SettingsService};
 use base64::{engine::general_purpose, Engine as _};
 use std::fs;
-use std::sync::Arc;
 use tauri::{AppHandle, Manager, State};
 
 async fn require_settings_read_access(
@@ -285,14 +284,16 @@ pub async fn get_email_verification_readiness(
 
 /// Upsert (create or update) setting
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn upsert_setting(
     token: String,
     key: String,
     value: String,
     description: Option<String>,
+    expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
     settings_service: State<'_, SettingsService>,
     auth_service: State<'_, AuthService>,
-    ws_hub: State<'_, Arc<WsHub>>,
+    event_outbox: State<'_, EventOutboxService>,
 ) -> Result<Setting, String> {
     let claims = auth_service
         .validate_token(&token)
@@ -323,6 +324,7 @@ pub async fn upsert_setting(
         key,
         value,
         description,
+        expected_updated_at,
     };
     let setting = settings_service
         .upsert(
@@ -343,10 +345,15 @@ pub async fn upsert_setting(
             .ok()
             .flatten();
 
-        ws_hub.broadcast(WsEvent::MaintenanceModeChanged {
-            enabled: maintenance_enabled,
-            message: maintenance_message,
-        });
+        event_outbox
+            .publish(
+                None,
+                vec![WsEvent::MaintenanceModeChanged {
+                    enabled: maintenance_enabled,
+                    message: maintenance_message,
+                }],
+            )
+            .await;
         println!(
             "DEBUG: [Tauri] Broadcasted MaintenanceModeChanged event (enabled: {})",
             maintenance_enabled
@@ -448,6 +455,7 @@ pub async fn upload_logo(
         key: "app_logo_path".to_string(),
         value: path_str.clone(),
         description: Some("Path to application logo".to_string()),
+        expected_updated_at: None,
     };
     settings_service
         .upsert(claims.tenant_id, dto, Some(&claims.sub), Some("127.0.0.1"))
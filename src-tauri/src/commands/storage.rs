@@ -178,3 +178,92 @@ pub async fn delete_file_tenant(
         .map(|_| true)
         .map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub async fn list_file_versions(
+    token: String,
+    state: State<'_, StorageService>,
+    auth_service: State<'_, AuthService>,
+    file_id: String,
+) -> Result<Vec<crate::models::FileVersion>, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let file = state.get_file(&file_id).await.map_err(|e| e.to_string())?;
+    if !claims.is_super_admin {
+        if claims.tenant_id.as_deref() != Some(file.tenant_id.as_str()) {
+            return Err("Unauthorized".to_string());
+        }
+        auth_service
+            .check_permission(&claims.sub, &file.tenant_id, "storage", "read")
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    state
+        .list_versions(&file_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restore_file_version(
+    token: String,
+    state: State<'_, StorageService>,
+    auth_service: State<'_, AuthService>,
+    file_id: String,
+    version_id: String,
+) -> Result<crate::models::FileRecord, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let file = state.get_file(&file_id).await.map_err(|e| e.to_string())?;
+    if !claims.is_super_admin {
+        if claims.tenant_id.as_deref() != Some(file.tenant_id.as_str()) {
+            return Err("Unauthorized".to_string());
+        }
+        auth_service
+            .check_permission(&claims.sub, &file.tenant_id, "storage", "upload")
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    state
+        .restore_version(&file_id, &version_id, Some(&claims.sub))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_file_link(
+    token: String,
+    state: State<'_, StorageService>,
+    auth_service: State<'_, AuthService>,
+    file_id: String,
+    expires_in_minutes: Option<i64>,
+) -> Result<crate::models::SignedFileLink, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let file = state.get_file(&file_id).await.map_err(|e| e.to_string())?;
+    if !claims.is_super_admin {
+        if claims.tenant_id.as_deref() != Some(file.tenant_id.as_str()) {
+            return Err("Unauthorized".to_string());
+        }
+        auth_service
+            .check_permission(&claims.sub, &file.tenant_id, "storage", "read")
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    state
+        .create_signed_link(&file_id, expires_in_minutes)
+        .await
+        .map_err(|e| e.to_string())
+}
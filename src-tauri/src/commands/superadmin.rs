@@ -1,6 +1,9 @@
 use crate::models::Tenant;
-use crate::services::{AuditService, AuthService, PlanService};
-use tauri::State;
+use crate::services::tenant_transfer::{
+    TenantExportSummary, TenantImportOptions, TenantImportReport,
+};
+use crate::services::{AuditService, AuthService, PlanService, TenantTransferService};
+use tauri::{AppHandle, Manager, State};
 
 #[derive(serde::Serialize)]
 pub struct TenantListResponse {
@@ -240,8 +243,9 @@ pub async fn create_tenant(
         default_plan_id
     };
 
+    // Plans with a trial period start the new tenant in trial.
     if let Some(pid) = plan_id_to_assign {
-        if let Err(e) = plan_service.assign_plan_to_tenant(&tenant.id, &pid).await {
+        if let Err(e) = plan_service.start_trial(&tenant.id, &pid).await {
             // Log error but don't fail the request since tenant is created
             tracing::error!(
                 "Failed to assign plan {} to tenant {}: {}",
@@ -352,3 +356,110 @@ pub async fn update_tenant(
 
     Ok(tenant)
 }
+
+/// Export a tenant to `<dir>/tenant_export_<slug>_<timestamp>.zip`; defaults to
+/// the app data `exports` directory.
+#[tauri::command]
+pub async fn export_tenant_archive(
+    token: String,
+    id: String,
+    dir: Option<String>,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    transfer_service: State<'_, TenantTransferService>,
+    app_handle: AppHandle,
+) -> Result<TenantExportSummary, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !claims.is_super_admin {
+        return Err("Unauthorized".to_string());
+    }
+
+    let dest = match dir {
+        Some(d) => std::path::PathBuf::from(d),
+        None => app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| e.to_string())?
+            .join("exports"),
+    };
+
+    let summary = transfer_service
+        .export_tenant(&id, &dest)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&id),
+            "export",
+            "tenant",
+            Some(&id),
+            Some(&format!(
+                "Exported tenant to {} ({} rows)",
+                summary.filename, summary.rows
+            )),
+            None,
+        )
+        .await;
+
+    Ok(summary)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn import_tenant_archive(
+    token: String,
+    path: String,
+    as_copy: Option<bool>,
+    name: Option<String>,
+    slug: Option<String>,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    transfer_service: State<'_, TenantTransferService>,
+) -> Result<TenantImportReport, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !claims.is_super_admin {
+        return Err("Unauthorized".to_string());
+    }
+
+    let zip_path = std::path::PathBuf::from(path);
+    if !zip_path.exists() {
+        return Err("File not found".to_string());
+    }
+
+    let options = TenantImportOptions {
+        as_copy: as_copy.unwrap_or(false),
+        name,
+        slug,
+    };
+    let report = transfer_service
+        .import_tenant(&zip_path, &options)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&report.tenant_id),
+            "import",
+            "tenant",
+            Some(&report.tenant_id),
+            Some(&format!(
+                "Imported tenant {} from {} (copy: {}, {} users created)",
+                report.slug, report.source_tenant_id, options.as_copy, report.users_created
+            )),
+            None,
+        )
+        .await;
+
+    Ok(report)
+}
@@ -1,11 +1,20 @@
 //! Support Tickets (Tenant scoped)
 
-use crate::http::{WsEvent, WsHub};
+use crate::http::WsEvent;
 use crate::models::{
-    FileRecord, PaginatedResponse, SupportTicket, SupportTicketDetail, SupportTicketListItem,
-    SupportTicketMessage, SupportTicketMessageWithAttachments,
+    ApplySupportMacroResult, CreateSupportTicketLinkDto, FileRecord, LinkedSupportTicket,
+    PaginatedResponse, RenderedSupportMacro, SupportEscalationRule, SupportMacro, SupportQueue,
+    SupportTicket, SupportTicketDetail, SupportTicketLink, SupportTicketListItem,
+    SupportTicketMessage, SupportTicketMessageWithAttachments, UpsertSupportEscalationRuleDto,
+    UpsertSupportMacroDto, UpsertSupportQueueDto,
+};
+use crate::services::event_outbox_service::{self, DomainEvent};
+use crate::services::support_escalation_service::status_after_reply;
+use crate::services::support_routing_service::normalize_category;
+use crate::services::{
+    AuditService, AuthService, EventOutboxService, NotificationService, SupportEscalationService,
+    SupportLinkService, SupportMacroService, SupportRoutingService,
 };
-use crate::services::{AuditService, AuthService, NotificationService};
 use chrono::Utc;
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -152,7 +161,7 @@ async fn notify_support_ticket_reply(
 #[cfg(feature = "postgres")]
 async fn broadcast_support_ticket_message_created(
     pool: &sqlx::Pool<sqlx::Postgres>,
-    ws_hub: &std::sync::Arc<WsHub>,
+    event_outbox: &EventOutboxService,
     tenant_id: &str,
     ticket: &SupportTicket,
     author_id: &str,
@@ -179,13 +188,55 @@ async fn broadcast_support_ticket_message_created(
         recipients.insert(uid);
     }
 
-    for uid in recipients {
-        ws_hub.broadcast(WsEvent::SupportTicketMessageCreated {
+    let events = recipients
+        .into_iter()
+        .map(|uid| WsEvent::SupportTicketMessageCreated {
             user_id: uid,
             tenant_id: Some(tenant_id.to_string()),
             ticket_id: ticket.id.clone(),
             message_id: message_id.to_string(),
-        });
+        })
+        .collect();
+    event_outbox.publish(Some(tenant_id), events).await;
+}
+
+/// Route a freshly created ticket to its queue (best-effort) and tell the
+/// auto-assigned agent about it.
+async fn route_new_ticket(
+    routing: &SupportRoutingService,
+    notification_service: &NotificationService,
+    tenant_id: &str,
+    ticket: &mut SupportTicket,
+) {
+    let route = match routing
+        .route_ticket(tenant_id, &ticket.id, ticket.category.as_deref())
+        .await
+    {
+        Ok(Some(route)) => route,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!("Support ticket {} routing failed: {}", ticket.id, e);
+            return;
+        }
+    };
+
+    ticket.queue_id = Some(route.queue_id);
+    if ticket.assigned_to == route.assigned_to {
+        return;
+    }
+    ticket.assigned_to = route.assigned_to;
+    if let Some(assignee) = ticket.assigned_to.clone() {
+        let _ = notification_service
+            .create_notification(
+                assignee,
+                Some(tenant_id.to_string()),
+                "Ticket assigned".to_string(),
+                ticket.subject.clone(),
+                "info".to_string(),
+                "support".to_string(),
+                Some(format!("/admin/support/{}", ticket.id)),
+            )
+            .await;
     }
 }
 
@@ -195,6 +246,10 @@ pub struct SupportTicketStats {
     pub open: i64,
     pub pending: i64,
     pub closed: i64,
+    /// Open or pending tickets assigned to the caller (staff only).
+    pub mine: i64,
+    /// Open or pending tickets with no assignee (staff only).
+    pub unassigned: i64,
 }
 
 fn normalize_priority(p: Option<String>) -> String {
@@ -212,12 +267,15 @@ fn normalize_status(s: Option<String>) -> Option<String> {
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn list_support_tickets(
     token: String,
     status: Option<String>,
     search: Option<String>,
     page: Option<u32>,
     per_page: Option<u32>,
+    queue_id: Option<String>,
+    assigned: Option<String>, // me | unassigned (staff only)
     auth_service: State<'_, AuthService>,
 ) -> Result<PaginatedResponse<SupportTicketListItem>, String> {
     let claims = auth_service
@@ -261,6 +319,11 @@ pub async fn list_support_tickets(
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty());
 
+    // "My tickets" / "Unassigned" views and queue filter (staff list only).
+    let queue_id = queue_id.filter(|q| !q.trim().is_empty());
+    let assignee = (assigned.as_deref() == Some("me")).then(|| claims.sub.clone());
+    let unassigned_only = assigned.as_deref() == Some("unassigned");
+
     let (rows, total): (Vec<SupportTicketListItem>, i64) = if can_all {
         let total: i64 = sqlx::query_scalar(
             r#"
@@ -274,11 +337,17 @@ pub async fn list_support_tickets(
                 OR LOWER(t.subject) LIKE '%' || LOWER($3) || '%'
                 OR LOWER(COALESCE(u.name, '')) LIKE '%' || LOWER($3) || '%'
               )
+              AND ($4::text IS NULL OR t.queue_id = $4)
+              AND ($5::text IS NULL OR t.assigned_to = $5)
+              AND (NOT $6 OR t.assigned_to IS NULL)
         "#,
         )
         .bind(&tenant_id)
         .bind(st.clone())
         .bind(search.clone())
+        .bind(queue_id.clone())
+        .bind(assignee.clone())
+        .bind(unassigned_only)
         .fetch_one(&auth_service.pool)
         .await
         .map_err(|e| e.to_string())?;
@@ -299,13 +368,19 @@ pub async fn list_support_tickets(
                 OR LOWER(t.subject) LIKE '%' || LOWER($3) || '%'
                 OR LOWER(COALESCE(u.name, '')) LIKE '%' || LOWER($3) || '%'
               )
+              AND ($4::text IS NULL OR t.queue_id = $4)
+              AND ($5::text IS NULL OR t.assigned_to = $5)
+              AND (NOT $6 OR t.assigned_to IS NULL)
             ORDER BY COALESCE((SELECT MAX(created_at) FROM support_ticket_messages m WHERE m.ticket_id = t.id), t.updated_at) DESC
-            LIMIT $4 OFFSET $5
+            LIMIT $7 OFFSET $8
         "#,
         )
         .bind(&tenant_id)
         .bind(st)
         .bind(search)
+        .bind(queue_id)
+        .bind(assignee)
+        .bind(unassigned_only)
         .bind(per_page as i64)
         .bind(offset)
         .fetch_all(&auth_service.pool)
@@ -412,6 +487,8 @@ pub async fn get_support_ticket_stats(
         open: i64,
         pending: i64,
         closed: i64,
+        mine: i64,
+        unassigned: i64,
     }
 
     let row: Row = if can_all {
@@ -421,12 +498,15 @@ pub async fn get_support_ticket_stats(
               COUNT(*) AS all,
               COALESCE(SUM(CASE WHEN status = 'open' THEN 1 ELSE 0 END), 0) AS open,
               COALESCE(SUM(CASE WHEN status = 'pending' THEN 1 ELSE 0 END), 0) AS pending,
-              COALESCE(SUM(CASE WHEN status = 'closed' THEN 1 ELSE 0 END), 0) AS closed
+              COALESCE(SUM(CASE WHEN status = 'closed' THEN 1 ELSE 0 END), 0) AS closed,
+              COALESCE(SUM(CASE WHEN status <> 'closed' AND assigned_to = $2 THEN 1 ELSE 0 END), 0) AS mine,
+              COALESCE(SUM(CASE WHEN status <> 'closed' AND assigned_to IS NULL THEN 1 ELSE 0 END), 0) AS unassigned
             FROM support_tickets
             WHERE tenant_id = $1
         "#,
         )
         .bind(&tenant_id)
+        .bind(&claims.sub)
         .fetch_one(&auth_service.pool)
         .await
         .map_err(|e| e.to_string())?
@@ -437,7 +517,9 @@ pub async fn get_support_ticket_stats(
               COUNT(*) AS all,
               COALESCE(SUM(CASE WHEN status = 'open' THEN 1 ELSE 0 END), 0) AS open,
               COALESCE(SUM(CASE WHEN status = 'pending' THEN 1 ELSE 0 END), 0) AS pending,
-              COALESCE(SUM(CASE WHEN status = 'closed' THEN 1 ELSE 0 END), 0) AS closed
+              COALESCE(SUM(CASE WHEN status = 'closed' THEN 1 ELSE 0 END), 0) AS closed,
+              0::bigint AS mine,
+              0::bigint AS unassigned
             FROM support_tickets
             WHERE tenant_id = $1 AND created_by = $2
         "#,
@@ -454,6 +536,8 @@ pub async fn get_support_ticket_stats(
         open: row.open,
         pending: row.pending,
         closed: row.closed,
+        mine: row.mine,
+        unassigned: row.unassigned,
     })
 }
 
@@ -464,10 +548,12 @@ pub async fn create_support_ticket(
     subject: String,
     message: String,
     priority: Option<String>,
+    category: Option<String>,
     attachment_ids: Option<Vec<String>>,
     auth_service: State<'_, AuthService>,
     notification_service: State<'_, NotificationService>,
     audit_service: State<'_, AuditService>,
+    routing: State<'_, SupportRoutingService>,
 ) -> Result<SupportTicketDetail, String> {
     let claims = auth_service
         .validate_token(&token)
@@ -499,9 +585,9 @@ pub async fn create_support_ticket(
         r#"
         INSERT INTO support_tickets (
             id, tenant_id, created_by, subject, status, priority, assigned_to,
-            created_at, updated_at, closed_at
+            category, created_at, updated_at, closed_at
         )
-        VALUES ($1,$2,$3,$4,'open',$5,NULL,$6,$7,NULL)
+        VALUES ($1,$2,$3,$4,'open',$5,NULL,$6,$7,$8,NULL)
     "#,
     )
     .bind(&ticket_id)
@@ -509,6 +595,7 @@ pub async fn create_support_ticket(
     .bind(&claims.sub)
     .bind(subject.trim())
     .bind(&priority)
+    .bind(normalize_category(category.as_deref()))
     .bind(now)
     .bind(now)
     .execute(&mut *tx)
@@ -537,7 +624,7 @@ pub async fn create_support_ticket(
             .map_err(|e| e.to_string())?;
     }
 
-    let ticket: SupportTicket = sqlx::query_as("SELECT * FROM support_tickets WHERE id = $1")
+    let mut ticket: SupportTicket = sqlx::query_as("SELECT * FROM support_tickets WHERE id = $1")
         .bind(&ticket_id)
         .fetch_one(&mut *tx)
         .await
@@ -551,7 +638,17 @@ pub async fn create_support_ticket(
     .await
     .map_err(|e| e.to_string())?;
 
+    EventOutboxService::stage(
+        &mut tx,
+        Some(tenant_id.as_str()),
+        &DomainEvent::ticket_created(&ticket),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
     tx.commit().await.map_err(|e| e.to_string())?;
+    event_outbox_service::wake();
+
+    route_new_ticket(&routing, &notification_service, &tenant_id, &mut ticket).await;
 
     // Audit (best-effort)
     let audit_details = serde_json::json!({
@@ -732,7 +829,8 @@ pub async fn reply_support_ticket(
     auth_service: State<'_, AuthService>,
     notification_service: State<'_, NotificationService>,
     audit_service: State<'_, AuditService>,
-    ws_hub: State<'_, std::sync::Arc<WsHub>>,
+    event_outbox: State<'_, EventOutboxService>,
+    escalation: State<'_, SupportEscalationService>,
 ) -> Result<SupportTicketMessageWithAttachments, String> {
     let claims = auth_service
         .validate_token(&token)
@@ -761,16 +859,13 @@ pub async fn reply_support_ticket(
             .await
             .map_err(|e| e.to_string())?;
 
-    if ticket.status == "closed" {
-        return Err("Ticket is closed".to_string());
-    }
-
     let can_all = auth_service
         .has_permission(&claims.sub, &tenant_id, "support", "read_all")
         .await
         .unwrap_or(false);
 
-    if !can_all && ticket.created_by.as_deref() != Some(claims.sub.as_str()) {
+    let is_owner = ticket.created_by.as_deref() == Some(claims.sub.as_str());
+    if !can_all && !is_owner {
         return Err("Forbidden".to_string());
     }
 
@@ -783,6 +878,14 @@ pub async fn reply_support_ticket(
     }
 
     let now = Utc::now();
+    let by_customer = is_owner && !is_internal;
+    let reopen_window = if by_customer && ticket.status == "closed" {
+        escalation.reopen_window_days(&tenant_id).await
+    } else {
+        0
+    };
+    let new_status =
+        status_after_reply(&ticket, by_customer, reopen_window, now).map_err(|e| e.to_string())?;
     let msg_id = Uuid::new_v4().to_string();
 
     let mut tx = auth_service.pool.begin().await.map_err(|e| e.to_string())?;
@@ -810,12 +913,27 @@ pub async fn reply_support_ticket(
             .map_err(|e| e.to_string())?;
     }
 
-    sqlx::query("UPDATE support_tickets SET updated_at = $1 WHERE id = $2")
-        .bind(now)
-        .bind(&id)
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| e.to_string())?;
+    match new_status {
+        Some(status) => {
+            sqlx::query(
+                "UPDATE support_tickets SET status = $1, closed_at = NULL, updated_at = $2 WHERE id = $3",
+            )
+            .bind(status)
+            .bind(now)
+            .bind(&id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        None => {
+            sqlx::query("UPDATE support_tickets SET updated_at = $1 WHERE id = $2")
+                .bind(now)
+                .bind(&id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
 
     let msg: SupportTicketMessage =
         sqlx::query_as("SELECT * FROM support_ticket_messages WHERE id = $1")
@@ -831,6 +949,7 @@ pub async fn reply_support_ticket(
         "message_id": msg_id,
         "internal": is_internal,
         "attachments": attachment_ids.as_ref().map(|v| v.len()).unwrap_or(0),
+        "status": new_status,
     })
     .to_string();
     audit_service
@@ -860,7 +979,7 @@ pub async fn reply_support_ticket(
     #[cfg(feature = "postgres")]
     broadcast_support_ticket_message_created(
         &auth_service.pool,
-        ws_hub.inner(),
+        event_outbox.inner(),
         &tenant_id,
         &ticket,
         &claims.sub,
@@ -906,9 +1025,12 @@ pub async fn update_support_ticket(
     status: Option<String>,
     priority: Option<String>,
     assigned_to: Option<String>,
+    queue_id: Option<String>,
+    category: Option<String>,
     auth_service: State<'_, AuthService>,
     audit_service: State<'_, AuditService>,
     notification_service: State<'_, NotificationService>,
+    routing: State<'_, SupportRoutingService>,
 ) -> Result<SupportTicket, String> {
     let claims = auth_service
         .validate_token(&token)
@@ -936,14 +1058,14 @@ pub async fn update_support_ticket(
         }
     });
 
-    if status.is_some() || priority.is_some() {
+    if status.is_some() || priority.is_some() || category.is_some() {
         auth_service
             .check_permission(&claims.sub, &tenant_id, "support", "update")
             .await
             .map_err(|e| e.to_string())?;
     }
 
-    if assigned_to.is_some() {
+    if assigned_to.is_some() || queue_id.is_some() {
         auth_service
             .check_permission(&claims.sub, &tenant_id, "support", "assign")
             .await
@@ -962,10 +1084,41 @@ pub async fn update_support_ticket(
     let old_status = existing.status.clone();
     let old_priority = existing.priority.clone();
     let old_assigned_to = existing.assigned_to.clone();
+    let old_queue_id = existing.queue_id.clone();
+    let old_category = existing.category.clone();
+
+    // Empty string clears the assignee / queue.
+    let assigned_to = match assigned_to.map(|a| a.trim().to_string()) {
+        Some(a) if a.is_empty() => None,
+        Some(a) => {
+            if existing.assigned_to.as_deref() != Some(a.as_str()) {
+                routing
+                    .ensure_agent(&tenant_id, &a)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            Some(a)
+        }
+        None => existing.assigned_to,
+    };
+    let queue_id = match queue_id.map(|q| q.trim().to_string()) {
+        Some(q) if q.is_empty() => None,
+        Some(q) => {
+            routing
+                .ensure_queue(&tenant_id, &q)
+                .await
+                .map_err(|e| e.to_string())?;
+            Some(q)
+        }
+        None => existing.queue_id,
+    };
+    let category = match category {
+        Some(c) => normalize_category(Some(&c)),
+        None => existing.category,
+    };
 
     let new_status = status.unwrap_or(existing.status);
     let new_priority = priority.unwrap_or(existing.priority);
-    let assigned_to = assigned_to.or(existing.assigned_to);
     let closed_at = if new_status == "closed" {
         Some(now)
     } else {
@@ -978,15 +1131,19 @@ pub async fn update_support_ticket(
         SET status = $1,
             priority = $2,
             assigned_to = $3,
-            updated_at = $4,
-            closed_at = $5
-        WHERE id = $6 AND tenant_id = $7
+            queue_id = $4,
+            category = $5,
+            updated_at = $6,
+            closed_at = $7
+        WHERE id = $8 AND tenant_id = $9
         RETURNING *
     "#,
     )
     .bind(new_status)
     .bind(new_priority)
     .bind(assigned_to)
+    .bind(queue_id)
+    .bind(category)
     .bind(now)
     .bind(closed_at)
     .bind(&id)
@@ -1013,11 +1170,15 @@ pub async fn update_support_ticket(
             "status": old_status,
             "priority": old_priority,
             "assigned_to": old_assigned_to,
+            "queue_id": old_queue_id,
+            "category": old_category,
         },
         "to": {
             "status": ticket.status,
             "priority": ticket.priority,
             "assigned_to": ticket.assigned_to,
+            "queue_id": ticket.queue_id,
+            "category": ticket.category,
         }
     })
     .to_string();
@@ -1108,6 +1269,850 @@ pub async fn update_support_ticket(
     Ok(ticket)
 }
 
+#[tauri::command]
+pub async fn list_support_queues(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    routing: State<'_, SupportRoutingService>,
+) -> Result<Vec<SupportQueue>, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    routing
+        .list_queues(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_support_queue(
+    token: String,
+    dto: UpsertSupportQueueDto,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    routing: State<'_, SupportRoutingService>,
+) -> Result<SupportQueue, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "queues")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let queue = routing
+        .create_queue(&tenant_id, dto)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let audit_details = serde_json::json!({
+        "name": queue.name,
+        "categories": queue.categories,
+        "members": queue.member_ids.len(),
+    })
+    .to_string();
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "create",
+            "support_queue",
+            Some(&queue.id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(queue)
+}
+
+#[tauri::command]
+pub async fn update_support_queue(
+    token: String,
+    id: String,
+    dto: UpsertSupportQueueDto,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    routing: State<'_, SupportRoutingService>,
+) -> Result<SupportQueue, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "queues")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let queue = routing
+        .update_queue(&tenant_id, &id, dto)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let audit_details = serde_json::json!({
+        "name": queue.name,
+        "categories": queue.categories,
+        "members": queue.member_ids.len(),
+    })
+    .to_string();
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "update",
+            "support_queue",
+            Some(&id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(queue)
+}
+
+#[tauri::command]
+pub async fn delete_support_queue(
+    token: String,
+    id: String,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    routing: State<'_, SupportRoutingService>,
+) -> Result<(), String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "queues")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    routing
+        .delete_queue(&tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "delete",
+            "support_queue",
+            Some(&id),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_support_escalation_rules(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    escalation: State<'_, SupportEscalationService>,
+) -> Result<Vec<SupportEscalationRule>, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    escalation
+        .list_rules(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_support_escalation_rule(
+    token: String,
+    dto: UpsertSupportEscalationRuleDto,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    escalation: State<'_, SupportEscalationService>,
+) -> Result<SupportEscalationRule, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "queues")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let rule = escalation
+        .create_rule(&tenant_id, dto)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let audit_details = serde_json::json!({
+        "name": rule.name,
+        "idle_minutes": rule.idle_minutes,
+        "bump_priority": rule.bump_priority,
+        "reassign_to": rule.reassign_to,
+    })
+    .to_string();
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "create",
+            "support_escalation_rule",
+            Some(&rule.id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(rule)
+}
+
+#[tauri::command]
+pub async fn update_support_escalation_rule(
+    token: String,
+    id: String,
+    dto: UpsertSupportEscalationRuleDto,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    escalation: State<'_, SupportEscalationService>,
+) -> Result<SupportEscalationRule, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "queues")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let rule = escalation
+        .update_rule(&tenant_id, &id, dto)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let audit_details = serde_json::json!({
+        "name": rule.name,
+        "is_active": rule.is_active,
+        "idle_minutes": rule.idle_minutes,
+        "bump_priority": rule.bump_priority,
+        "reassign_to": rule.reassign_to,
+    })
+    .to_string();
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "update",
+            "support_escalation_rule",
+            Some(&id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(rule)
+}
+
+#[tauri::command]
+pub async fn delete_support_escalation_rule(
+    token: String,
+    id: String,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    escalation: State<'_, SupportEscalationService>,
+) -> Result<(), String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "queues")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    escalation
+        .delete_rule(&tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "delete",
+            "support_escalation_rule",
+            Some(&id),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_support_macros(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    macros: State<'_, SupportMacroService>,
+) -> Result<Vec<SupportMacro>, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    macros.list(&tenant_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_support_macro(
+    token: String,
+    dto: UpsertSupportMacroDto,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    macros: State<'_, SupportMacroService>,
+) -> Result<SupportMacro, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "macros")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let m = macros
+        .create(&tenant_id, &claims.sub, dto)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let audit_details = serde_json::json!({ "name": m.name }).to_string();
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "create",
+            "support_macro",
+            Some(&m.id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(m)
+}
+
+#[tauri::command]
+pub async fn update_support_macro(
+    token: String,
+    id: String,
+    dto: UpsertSupportMacroDto,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    macros: State<'_, SupportMacroService>,
+) -> Result<SupportMacro, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "macros")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let m = macros
+        .update(&tenant_id, &id, dto)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let audit_details = serde_json::json!({ "name": m.name }).to_string();
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "update",
+            "support_macro",
+            Some(&id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(m)
+}
+
+#[tauri::command]
+pub async fn delete_support_macro(
+    token: String,
+    id: String,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    macros: State<'_, SupportMacroService>,
+) -> Result<(), String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "macros")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    macros
+        .delete(&tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "delete",
+            "support_macro",
+            Some(&id),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn preview_support_macro(
+    token: String,
+    id: String,
+    macro_id: String,
+    auth_service: State<'_, AuthService>,
+    macros: State<'_, SupportMacroService>,
+) -> Result<RenderedSupportMacro, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let ticket: SupportTicket =
+        sqlx::query_as("SELECT * FROM support_tickets WHERE id = $1 AND tenant_id = $2")
+            .bind(&id)
+            .bind(&tenant_id)
+            .fetch_one(&auth_service.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    let m = macros
+        .get(&tenant_id, &macro_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let body = macros
+        .render(&tenant_id, &m, &ticket, &claims.sub)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(RenderedSupportMacro { body })
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn apply_support_macro(
+    token: String,
+    id: String,
+    macro_id: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+    audit_service: State<'_, AuditService>,
+    event_outbox: State<'_, EventOutboxService>,
+    macros: State<'_, SupportMacroService>,
+) -> Result<ApplySupportMacroResult, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let m = macros
+        .get(&tenant_id, &macro_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Same permissions as doing each step by hand.
+    if !m.body.trim().is_empty() {
+        auth_service
+            .check_permission(&claims.sub, &tenant_id, "support", "reply")
+            .await
+            .map_err(|e| e.to_string())?;
+        if m.is_internal {
+            auth_service
+                .check_permission(&claims.sub, &tenant_id, "support", "internal")
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    if m.set_status.is_some() || m.set_priority.is_some() {
+        auth_service
+            .check_permission(&claims.sub, &tenant_id, "support", "update")
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    if m.assign_to.is_some() {
+        auth_service
+            .check_permission(&claims.sub, &tenant_id, "support", "assign")
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let applied = macros
+        .apply(&tenant_id, &m, &id, &claims.sub)
+        .await
+        .map_err(|e| e.to_string())?;
+    let ticket = applied.ticket;
+
+    let audit_details = serde_json::json!({
+        "macro_id": m.id,
+        "macro": m.name,
+        "message_id": applied.message.as_ref().map(|msg| msg.id.clone()),
+        "from": {
+            "status": applied.previous.status,
+            "priority": applied.previous.priority,
+            "assigned_to": applied.previous.assigned_to,
+        },
+        "to": {
+            "status": ticket.status,
+            "priority": ticket.priority,
+            "assigned_to": ticket.assigned_to,
+        }
+    })
+    .to_string();
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "apply_macro",
+            "support_ticket",
+            Some(&id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    #[cfg(feature = "postgres")]
+    if let Some(msg) = applied.message.as_ref() {
+        notify_support_ticket_reply(
+            &auth_service.pool,
+            &auth_service,
+            &notification_service,
+            &tenant_id,
+            &ticket,
+            &claims.sub,
+            msg.is_internal,
+        )
+        .await;
+        broadcast_support_ticket_message_created(
+            &auth_service.pool,
+            event_outbox.inner(),
+            &tenant_id,
+            &ticket,
+            &claims.sub,
+            msg.is_internal,
+            &msg.id,
+        )
+        .await;
+    }
+
+    if ticket.assigned_to != applied.previous.assigned_to {
+        if let Some(assignee) = ticket.assigned_to.clone() {
+            if assignee != claims.sub {
+                let _ = notification_service
+                    .create_notification(
+                        assignee,
+                        Some(tenant_id.clone()),
+                        "Ticket assigned".to_string(),
+                        ticket.subject.clone(),
+                        "info".to_string(),
+                        "support".to_string(),
+                        Some(format!("/admin/support/{id}")),
+                    )
+                    .await;
+            }
+        }
+    }
+
+    Ok(ApplySupportMacroResult {
+        ticket,
+        message: applied.message,
+    })
+}
+
+#[tauri::command]
+pub async fn list_support_ticket_links(
+    token: String,
+    id: String,
+    auth_service: State<'_, AuthService>,
+    links: State<'_, SupportLinkService>,
+) -> Result<Vec<SupportTicketLink>, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    links
+        .list_for_ticket(&tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn link_support_ticket(
+    token: String,
+    id: String,
+    entity_type: String,
+    entity_id: String,
+    note: Option<String>,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    links: State<'_, SupportLinkService>,
+) -> Result<SupportTicketLink, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "update")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let dto = CreateSupportTicketLinkDto {
+        entity_type,
+        entity_id,
+        note,
+    };
+    let link = links
+        .link(&tenant_id, &id, &claims.sub, dto)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let audit_details = serde_json::json!({
+        "link_id": link.id,
+        "entity_type": link.entity_type,
+        "entity_id": link.entity_id,
+    })
+    .to_string();
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "link",
+            "support_ticket",
+            Some(&id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(link)
+}
+
+#[tauri::command]
+pub async fn unlink_support_ticket(
+    token: String,
+    id: String,
+    link_id: String,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    links: State<'_, SupportLinkService>,
+) -> Result<(), String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "update")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let link = links
+        .unlink(&tenant_id, &id, &link_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let audit_details = serde_json::json!({
+        "link_id": link.id,
+        "entity_type": link.entity_type,
+        "entity_id": link.entity_id,
+    })
+    .to_string();
+    audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "unlink",
+            "support_ticket",
+            Some(&id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_linked_support_tickets(
+    token: String,
+    entity_type: String,
+    entity_id: String,
+    auth_service: State<'_, AuthService>,
+    links: State<'_, SupportLinkService>,
+) -> Result<Vec<LinkedSupportTicket>, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    links
+        .tickets_for_entity(&tenant_id, &entity_type, &entity_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[cfg(feature = "postgres")]
 async fn attach_files_pg(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
@@ -1163,6 +2168,13 @@ async fn fetch_attachments_map_pg(
         size: i64,
         content_type: String,
         storage_provider: String,
+        storage_bucket: Option<String>,
+        quota_counted: bool,
+        scan_status: String,
+        scan_signature: Option<String>,
+        scanned_at: Option<chrono::DateTime<chrono::Utc>>,
+        logical_path: Option<String>,
+        version: i32,
         uploaded_by: Option<String>,
         created_at: chrono::DateTime<chrono::Utc>,
         updated_at: chrono::DateTime<chrono::Utc>,
@@ -1177,7 +2189,9 @@ async fn fetch_attachments_map_pg(
         SELECT
             a.message_id,
             f.id, f.tenant_id, f.name, f.original_name, f.path, f.size, f.content_type,
-            f.storage_provider, f.uploaded_by, f.created_at, f.updated_at
+            f.storage_provider, f.storage_bucket, f.quota_counted, f.scan_status,
+            f.scan_signature, f.scanned_at, f.logical_path, f.version, f.uploaded_by,
+            f.created_at, f.updated_at
         FROM support_ticket_attachments a
         JOIN support_ticket_messages m ON m.id = a.message_id
         JOIN support_tickets t ON t.id = m.ticket_id
@@ -1206,6 +2220,13 @@ async fn fetch_attachments_map_pg(
             size: r.size,
             content_type: r.content_type,
             storage_provider: r.storage_provider,
+            storage_bucket: r.storage_bucket,
+            quota_counted: r.quota_counted,
+            scan_status: r.scan_status,
+            scan_signature: r.scan_signature,
+            scanned_at: r.scanned_at,
+            logical_path: r.logical_path,
+            version: r.version,
             uploaded_by: r.uploaded_by,
             created_at: r.created_at,
             updated_at: r.updated_at,
@@ -1,10 +1,15 @@
 //! System Health Tauri Commands
 
+use crate::db::migrations::MigrationStatus;
+use crate::diagnostics::{DiagnosticsBundleSummary, RecentLogs};
+use crate::services::db_maintenance_service::MaintenanceReport;
 use crate::services::metrics_service::MetricsService;
 use crate::services::system_service::{SystemDiagnostics, SystemHealth};
-use crate::services::{AuthService, SettingsService, SystemService};
+use crate::services::{
+    AuditService, AuthService, DbMaintenanceService, JobQueue, SettingsService, SystemService,
+};
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 
 #[tauri::command]
 pub async fn get_system_health(
@@ -28,8 +33,9 @@ pub async fn get_system_health(
         .await
         .map_err(|e| e.to_string())?;
 
-    // Inject request metrics
+    // Inject request and pool metrics
     health.request_metrics = Some(metrics_service.get_metrics());
+    health.pool_metrics = metrics_service.get_pool_metrics();
 
     Ok(health)
 }
@@ -40,6 +46,7 @@ pub async fn get_system_diagnostics(
     auth_service: State<'_, AuthService>,
     system_service: State<'_, SystemService>,
     settings_service: State<'_, SettingsService>,
+    db_maintenance_service: State<'_, DbMaintenanceService>,
 ) -> Result<SystemDiagnostics, String> {
     let claims = auth_service
         .validate_token(&token)
@@ -50,8 +57,134 @@ pub async fn get_system_diagnostics(
         return Err("Unauthorized: Super Admin access required".to_string());
     }
 
-    system_service
+    let mut diag = system_service
         .get_system_diagnostics(&settings_service)
         .await
+        .map_err(|e| e.to_string())?;
+    diag.maintenance = Some(db_maintenance_service.snapshot().await);
+
+    Ok(diag)
+}
+
+#[tauri::command]
+pub async fn run_db_maintenance(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    db_maintenance_service: State<'_, DbMaintenanceService>,
+) -> Result<MaintenanceReport, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !claims.is_super_admin {
+        return Err("Unauthorized: Super Admin access required".to_string());
+    }
+
+    let report = db_maintenance_service
+        .run("manual")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let details = serde_json::json!({
+        "operations": report.operations,
+        "errors": report.errors,
+        "duration_ms": report.duration_ms,
+    })
+    .to_string();
+    audit_service
+        .log(
+            Some(&claims.sub),
+            None,
+            "run",
+            "db_maintenance",
+            None,
+            Some(details.as_str()),
+            Some("127.0.0.1"),
+        )
+        .await;
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn get_migration_status(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    system_service: State<'_, SystemService>,
+) -> Result<MigrationStatus, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !claims.is_super_admin {
+        return Err("Unauthorized: Super Admin access required".to_string());
+    }
+
+    system_service
+        .get_migration_status()
+        .await
         .map_err(|e| e.to_string())
 }
+
+/// Writes a ZIP with recent logs, version, redacted settings, a database
+/// check and scheduler state into `dir` (default: `<app data>/diagnostics`).
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_diagnostics_bundle(
+    token: String,
+    dir: Option<String>,
+    app_handle: AppHandle,
+    auth_service: State<'_, AuthService>,
+    audit_service: State<'_, AuditService>,
+    system_service: State<'_, SystemService>,
+    settings_service: State<'_, SettingsService>,
+    db_maintenance_service: State<'_, DbMaintenanceService>,
+    job_queue: State<'_, JobQueue>,
+    recent_logs: State<'_, RecentLogs>,
+) -> Result<DiagnosticsBundleSummary, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !claims.is_super_admin {
+        return Err("Unauthorized: Super Admin access required".to_string());
+    }
+
+    let dest = match dir {
+        Some(d) => std::path::PathBuf::from(d),
+        None => app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| e.to_string())?
+            .join("diagnostics"),
+    };
+
+    let summary = crate::diagnostics::export_bundle(
+        &dest,
+        &recent_logs,
+        &system_service,
+        &settings_service,
+        &db_maintenance_service,
+        &job_queue,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    audit_service
+        .log(
+            Some(&claims.sub),
+            None,
+            "export",
+            "diagnostics",
+            None,
+            Some(&format!("Exported diagnostics bundle to {}", summary.path)),
+            Some("127.0.0.1"),
+        )
+        .await;
+
+    Ok(summary)
+}
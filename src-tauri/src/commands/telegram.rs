@@ -0,0 +1,84 @@
+//! Telegram bot chat linking for the current tenant member
+
+use crate::models::{TelegramLinkStatus, UpdateTelegramLinkRequest};
+use crate::services::{AuthService, NotificationService, TelegramService};
+use tauri::State;
+
+/// Returns `(user_id, tenant_id)`; any tenant member may link their own chat.
+async fn current_member(
+    auth_service: &AuthService,
+    token: &str,
+) -> Result<(String, String), String> {
+    let claims = auth_service
+        .validate_token(token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| "Tenant context required".to_string())?;
+    Ok((claims.sub, tenant_id))
+}
+
+fn telegram(notification_service: &NotificationService) -> Result<&TelegramService, String> {
+    notification_service
+        .telegram()
+        .ok_or_else(|| "Telegram bot is not initialized".to_string())
+}
+
+#[tauri::command]
+pub async fn get_telegram_link(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<TelegramLinkStatus, String> {
+    let (user_id, tenant_id) = current_member(&auth_service, &token).await?;
+    telegram(&notification_service)?
+        .get_link(&tenant_id, &user_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_telegram_link_code(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<TelegramLinkStatus, String> {
+    let (user_id, tenant_id) = current_member(&auth_service, &token).await?;
+    telegram(&notification_service)?
+        .create_link_code(&tenant_id, &user_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_telegram_link(
+    token: String,
+    alert_incidents: Option<bool>,
+    alert_payments: Option<bool>,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<TelegramLinkStatus, String> {
+    let (user_id, tenant_id) = current_member(&auth_service, &token).await?;
+    let req = UpdateTelegramLinkRequest {
+        alert_incidents,
+        alert_payments,
+    };
+    telegram(&notification_service)?
+        .update_link(&tenant_id, &user_id, req)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn unlink_telegram(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<(), String> {
+    let (user_id, tenant_id) = current_member(&auth_service, &token).await?;
+    telegram(&notification_service)?
+        .unlink(&tenant_id, &user_id)
+        .await
+        .map_err(|e| e.to_string())
+}
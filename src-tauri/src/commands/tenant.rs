@@ -1,5 +1,5 @@
-use crate::models::Tenant;
-use crate::services::{AuthService, PlanService};
+use crate::models::{Tenant, TenantUsage};
+use crate::services::{AuthService, PlanService, UsageService};
 use chrono::Utc;
 use tauri::State;
 
@@ -23,6 +23,28 @@ pub async fn get_current_tenant(
     Ok(tenant)
 }
 
+#[tauri::command]
+pub async fn get_tenant_usage(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    usage_service: State<'_, UsageService>,
+) -> Result<TenantUsage, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims.tenant_id.ok_or("Not a tenant user")?;
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "billing", "read")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    usage_service
+        .tenant_usage(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn update_current_tenant(
     token: String,
@@ -0,0 +1,77 @@
+//! Desktop Update Commands
+
+use crate::models::ClientCompatibility;
+use crate::services::client_version::{client_compatibility, UPDATE_CHANNELS};
+use crate::services::SettingsService;
+use tauri::{AppHandle, State, Url};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+/// Where update manifests live; `{channel}` is replaced with the release
+/// channel, the `{{...}}` parts are filled in by the updater.
+const DEFAULT_UPDATE_ENDPOINT: &str =
+    "https://releases.tridigitals.com/ispmanagement/{channel}/{{target}}/{{arch}}/{{current_version}}";
+
+#[derive(serde::Serialize)]
+pub struct AvailableUpdate {
+    pub version: String,
+    pub current_version: String,
+    pub channel: String,
+    pub date: Option<String>,
+    pub notes: Option<String>,
+}
+
+async fn find_update(app: &AppHandle, channel: &str) -> Result<Option<Update>, String> {
+    if !UPDATE_CHANNELS.contains(&channel) {
+        return Err(format!("Unknown update channel: {}", channel));
+    }
+    let template = option_env!("UPDATE_ENDPOINT").unwrap_or(DEFAULT_UPDATE_ENDPOINT);
+    let endpoint =
+        Url::parse(&template.replace("{channel}", channel)).map_err(|e| e.to_string())?;
+    app.updater_builder()
+        .endpoints(vec![endpoint])
+        .map_err(|e| e.to_string())?
+        .build()
+        .map_err(|e| e.to_string())?
+        .check()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn check_client_compatibility(
+    version: Option<String>,
+    settings_service: State<'_, SettingsService>,
+) -> Result<ClientCompatibility, String> {
+    client_compatibility(&settings_service, version.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn check_for_update(
+    app: AppHandle,
+    channel: String,
+) -> Result<Option<AvailableUpdate>, String> {
+    Ok(find_update(&app, &channel)
+        .await?
+        .map(|update| AvailableUpdate {
+            version: update.version,
+            current_version: update.current_version,
+            channel,
+            date: update.date.map(|d| d.to_string()),
+            notes: update.body,
+        }))
+}
+
+/// Downloads and installs the channel's latest release, then restarts the app.
+#[tauri::command]
+pub async fn install_update(app: AppHandle, channel: String) -> Result<(), String> {
+    let update = find_update(&app, &channel)
+        .await?
+        .ok_or_else(|| "No update available".to_string())?;
+    update
+        .download_and_install(|_, _| {}, || {})
+        .await
+        .map_err(|e| e.to_string())?;
+    app.restart()
+}
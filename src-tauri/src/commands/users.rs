@@ -1,8 +1,8 @@
 //! User Management Commands
 
 use crate::models::{
-    CreateUserAddressDto, CreateUserDto, PaginatedResponse, UpdateUserAddressDto, UpdateUserDto,
-    UserAddress, UserResponse,
+    CreateUserAddressDto, CreateUserDto, PaginatedResponse, TrashItem, UpdateUserAddressDto,
+    UpdateUserDto, UserAddress, UserLocale, UserResponse,
 };
 use crate::security::access_rules;
 use crate::services::{AuthService, UserService};
@@ -172,6 +172,85 @@ pub async fn delete_user(
         .map_err(|e| e.to_string())
 }
 
+/// List soft-deleted users (Super Admin Only)
+#[tauri::command]
+pub async fn list_deleted_users(
+    token: String,
+    user_service: State<'_, UserService>,
+    auth_service: State<'_, AuthService>,
+) -> Result<Vec<TrashItem>, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !access_rules::can_access_global_user_management(claims.is_super_admin) {
+        return Err("Unauthorized".to_string());
+    }
+
+    user_service.list_deleted().await.map_err(|e| e.to_string())
+}
+
+/// Restore a soft-deleted user (Super Admin Only)
+#[tauri::command]
+pub async fn restore_user(
+    token: String,
+    id: String,
+    user_service: State<'_, UserService>,
+    auth_service: State<'_, AuthService>,
+) -> Result<UserResponse, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !access_rules::can_access_global_user_management(claims.is_super_admin) {
+        return Err("Unauthorized".to_string());
+    }
+
+    user_service
+        .restore(&id, Some(&claims.sub), Some("127.0.0.1"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get current user's locale preference
+#[tauri::command]
+pub async fn get_my_locale(
+    token: String,
+    user_service: State<'_, UserService>,
+    auth_service: State<'_, AuthService>,
+) -> Result<UserLocale, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    user_service
+        .get_locale(&claims.sub)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Set current user's locale preference (None clears it)
+#[tauri::command]
+pub async fn set_my_locale(
+    token: String,
+    locale: Option<String>,
+    user_service: State<'_, UserService>,
+    auth_service: State<'_, AuthService>,
+) -> Result<UserLocale, String> {
+    let claims = auth_service
+        .validate_token(&token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    user_service
+        .set_locale(&claims.sub, locale)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// List current user's addresses
 #[tauri::command]
 pub async fn list_my_addresses(
@@ -0,0 +1,170 @@
+//! WhatsApp channel (templates, per-event routing, delivery log)
+
+use crate::models::{
+    UpsertWhatsappTemplateRequest, WhatsappMessage, WhatsappRoute, WhatsappTemplate,
+};
+use crate::services::{AuthService, NotificationService, WhatsappService};
+use tauri::State;
+
+/// Returns `(user_id, tenant_id)` after checking the settings permission.
+async fn authorize(
+    auth_service: &AuthService,
+    token: &str,
+    action: &str,
+) -> Result<(String, String), String> {
+    let claims = auth_service
+        .validate_token(token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or_else(|| "Tenant context required".to_string())?;
+    auth_service
+        .check_permission(&claims.sub, &tenant_id, "settings", action)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok((claims.sub, tenant_id))
+}
+
+fn whatsapp(notification_service: &NotificationService) -> Result<&WhatsappService, String> {
+    notification_service
+        .whatsapp()
+        .ok_or_else(|| "WhatsApp channel is not initialized".to_string())
+}
+
+#[tauri::command]
+pub async fn list_whatsapp_templates(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<Vec<WhatsappTemplate>, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "read").await?;
+    whatsapp(&notification_service)?
+        .list_templates(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_whatsapp_template(
+    token: String,
+    name: String,
+    language: Option<String>,
+    body: String,
+    is_active: Option<bool>,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<WhatsappTemplate, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    let req = UpsertWhatsappTemplateRequest {
+        name,
+        language,
+        body,
+        is_active,
+    };
+    whatsapp(&notification_service)?
+        .create_template(&tenant_id, req)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_whatsapp_template(
+    token: String,
+    id: String,
+    name: String,
+    language: Option<String>,
+    body: String,
+    is_active: Option<bool>,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<WhatsappTemplate, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    let req = UpsertWhatsappTemplateRequest {
+        name,
+        language,
+        body,
+        is_active,
+    };
+    whatsapp(&notification_service)?
+        .update_template(&tenant_id, &id, req)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_whatsapp_template(
+    token: String,
+    id: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<(), String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    whatsapp(&notification_service)?
+        .delete_template(&tenant_id, &id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn send_whatsapp_test(
+    token: String,
+    id: String,
+    phone: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<WhatsappMessage, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    whatsapp(&notification_service)?
+        .send_test(&tenant_id, &id, &phone)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_whatsapp_routes(
+    token: String,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<Vec<WhatsappRoute>, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "read").await?;
+    whatsapp(&notification_service)?
+        .list_routes(&tenant_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_whatsapp_route(
+    token: String,
+    event: String,
+    template_name: Option<String>,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<WhatsappRoute, String> {
+    let (user_id, tenant_id) = authorize(&auth_service, &token, "update").await?;
+    whatsapp(&notification_service)?
+        .set_route(&tenant_id, &event, template_name.as_deref(), Some(&user_id))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_whatsapp_messages(
+    token: String,
+    status: Option<String>,
+    limit: Option<u32>,
+    auth_service: State<'_, AuthService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<Vec<WhatsappMessage>, String> {
+    let (_, tenant_id) = authorize(&auth_service, &token, "read").await?;
+    let status = status
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty() && *s != "all");
+    whatsapp(&notification_service)?
+        .list_messages(&tenant_id, status, limit.unwrap_or(100))
+        .await
+        .map_err(|e| e.to_string())
+}
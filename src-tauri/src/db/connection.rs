@@ -6,14 +6,16 @@
 compile_error!("Features 'postgres' and 'sqlite' are mutually exclusive. Use default (postgres) OR --no-default-features --features sqlite.");
 
 #[cfg(feature = "postgres")]
-use sqlx::{PgPool, Pool, Postgres};
+use sqlx::{Pool, Postgres};
 
 #[cfg(feature = "sqlite")]
-use sqlx::{Pool, Sqlite, SqlitePool};
+use sqlx::{Pool, Sqlite};
 
+use sqlx::pool::PoolOptions;
 use std::env;
 use std::path::PathBuf;
-use tracing::info;
+use std::time::Duration;
+use tracing::{info, warn};
 
 #[cfg(feature = "postgres")]
 pub type DbPool = Pool<Postgres>;
@@ -75,6 +77,105 @@ fn build_postgres_url_from_env() -> Result<String, sqlx::Error> {
     Ok(url)
 }
 
+/// Connection pool tuning.
+#[derive(Clone, Debug)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout: Duration,
+    pub idle_timeout: Duration,
+    /// Server-side statement timeout (Postgres only). `None` disables it.
+    pub statement_timeout: Option<Duration>,
+}
+
+impl PoolConfig {
+    /// Read pool settings from env, falling back to defaults:
+    /// - DB_POOL_MAX_CONNECTIONS (10), DB_POOL_MIN_CONNECTIONS (0)
+    /// - DB_POOL_ACQUIRE_TIMEOUT_SECS (30), DB_POOL_IDLE_TIMEOUT_SECS (600)
+    /// - DB_STATEMENT_TIMEOUT_MS (0 = disabled)
+    ///
+    /// Env only: the settings table lives behind this very pool, and a pool
+    /// cannot be resized once built. System diagnostics show the values in use.
+    pub fn from_env() -> Self {
+        fn env_u64(key: &str, default: u64, min: u64, max: u64) -> u64 {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(|v| v.clamp(min, max))
+                .unwrap_or(default)
+        }
+
+        let max_connections = env_u64("DB_POOL_MAX_CONNECTIONS", 10, 1, 500) as u32;
+        let min_connections =
+            (env_u64("DB_POOL_MIN_CONNECTIONS", 0, 0, 500) as u32).min(max_connections);
+        let statement_timeout_ms = env_u64("DB_STATEMENT_TIMEOUT_MS", 0, 0, 3_600_000);
+
+        Self {
+            max_connections,
+            min_connections,
+            acquire_timeout: Duration::from_secs(env_u64(
+                "DB_POOL_ACQUIRE_TIMEOUT_SECS",
+                30,
+                1,
+                600,
+            )),
+            idle_timeout: Duration::from_secs(env_u64(
+                "DB_POOL_IDLE_TIMEOUT_SECS",
+                600,
+                10,
+                86_400,
+            )),
+            statement_timeout: (statement_timeout_ms > 0)
+                .then(|| Duration::from_millis(statement_timeout_ms)),
+        }
+    }
+
+    fn pool_options<DB: sqlx::Database>(&self) -> PoolOptions<DB> {
+        PoolOptions::<DB>::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(Some(self.idle_timeout))
+    }
+}
+
+#[cfg(feature = "postgres")]
+fn pg_connect_options(
+    url: &str,
+    config: &PoolConfig,
+) -> Result<sqlx::postgres::PgConnectOptions, sqlx::Error> {
+    use std::str::FromStr;
+
+    let options = sqlx::postgres::PgConnectOptions::from_str(url)?;
+    Ok(match config.statement_timeout {
+        Some(t) => options.options([("statement_timeout", format!("{}ms", t.as_millis()))]),
+        None => options,
+    })
+}
+
+/// In schema isolation mode, point every connection handed out by the pool at
+/// the current task's tenant schema (see `factory::TenantDbRouter`).
+#[cfg(feature = "postgres")]
+fn with_tenant_routing(options: PoolOptions<Postgres>) -> PoolOptions<Postgres> {
+    use super::factory::{current_search_path, TenantIsolation};
+
+    if TenantIsolation::from_env() != TenantIsolation::Schema {
+        return options;
+    }
+
+    async fn apply(conn: &mut sqlx::PgConnection) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT set_config('search_path', $1, false)")
+            .bind(current_search_path())
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    options
+        .after_connect(|conn, _meta| Box::pin(apply(conn)))
+        .before_acquire(|conn, _meta| Box::pin(async move { apply(conn).await.map(|_| true) }))
+}
+
 /// Initialize database connection
 pub async fn init_db(app_data_dir: PathBuf) -> Result<DbPool, sqlx::Error> {
     #[cfg(feature = "postgres")]
@@ -89,8 +190,16 @@ pub async fn init_db(app_data_dir: PathBuf) -> Result<DbPool, sqlx::Error> {
 
         info!("Connecting to PostgreSQL database");
 
-        let pool = PgPool::connect(&database_url).await?;
-        run_migrations_pg(&pool).await?;
+        let config = PoolConfig::from_env();
+        let pool = with_tenant_routing(config.pool_options::<Postgres>())
+            .connect_with(pg_connect_options(&database_url, &config)?)
+            .await?;
+        super::migrations::run_migrations(&pool).await?;
+
+        let synced = super::factory::sync_tenant_schemas(&pool).await?;
+        if synced > 0 {
+            info!("Updated {} table/column(s) in tenant schemas", synced);
+        }
 
         info!("PostgreSQL database initialized successfully");
 
@@ -110,8 +219,11 @@ pub async fn init_db(app_data_dir: PathBuf) -> Result<DbPool, sqlx::Error> {
 
         info!("Connecting to SQLite database: {}", database_url);
 
-        let pool = SqlitePool::connect(&database_url).await?;
-        run_migrations_sqlite(&pool).await?;
+        let pool = PoolConfig::from_env()
+            .pool_options::<Sqlite>()
+            .connect(&database_url)
+            .await?;
+        super::migrations::run_migrations(&pool).await?;
 
         info!("SQLite database initialized successfully");
 
@@ -123,718 +235,50 @@ pub async fn init_db(app_data_dir: PathBuf) -> Result<DbPool, sqlx::Error> {
     }
 }
 
-#[cfg(feature = "postgres")]
-async fn run_migrations_pg(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
-    // Postgres schema is managed exclusively via SQLx migrations.
-    static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations");
-
-    MIGRATOR
-        .run(pool)
-        .await
-        .map_err(|e| sqlx::Error::Migrate(Box::new(e)))?;
-
-    info!("PostgreSQL migrations completed");
-    Ok(())
-}
-#[cfg(feature = "sqlite")]
-async fn run_migrations_sqlite(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
-    // Create tenants table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS tenants (
-            id TEXT PRIMARY KEY NOT NULL,
-            name TEXT NOT NULL,
-            slug TEXT UNIQUE NOT NULL,
-            custom_domain TEXT UNIQUE,
-            logo_url TEXT,
-            is_active INTEGER NOT NULL DEFAULT 1,
-            storage_usage INTEGER NOT NULL DEFAULT 0,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Add storage_usage column if it doesn't exist (SQLite)
-    let _ = sqlx::query("ALTER TABLE tenants ADD COLUMN storage_usage INTEGER NOT NULL DEFAULT 0")
-        .execute(pool)
-        .await;
-
-    // Add enforce_2fa column if it doesn't exist (SQLite)
-    let _ = sqlx::query("ALTER TABLE tenants ADD COLUMN enforce_2fa INTEGER NOT NULL DEFAULT 0")
-        .execute(pool)
-        .await;
-
-    // Add storage_usage column if it doesn't exist (SQLite)
-    let _ = sqlx::query("ALTER TABLE tenants ADD COLUMN storage_usage INTEGER NOT NULL DEFAULT 0")
-        .execute(pool)
-        .await;
-
-    // Create users table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS users (
-            id TEXT PRIMARY KEY NOT NULL,
-            email TEXT NOT NULL UNIQUE,
-            password_hash TEXT NOT NULL,
-            name TEXT NOT NULL,
-            role TEXT NOT NULL DEFAULT 'user',
-            is_super_admin INTEGER NOT NULL DEFAULT 0,
-            avatar_url TEXT,
-            is_active INTEGER NOT NULL DEFAULT 1,
-            email_verified_at TEXT,
-            failed_login_attempts INTEGER NOT NULL DEFAULT 0,
-            locked_until TEXT,
-            verification_token TEXT,
-            reset_token TEXT,
-            reset_token_expires TEXT,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create user_addresses table (multi address support)
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS user_addresses (
-            id TEXT PRIMARY KEY NOT NULL,
-            user_id TEXT NOT NULL,
-            label TEXT,
-            recipient_name TEXT,
-            phone TEXT,
-            line1 TEXT NOT NULL,
-            line2 TEXT,
-            city TEXT,
-            state TEXT,
-            postal_code TEXT,
-            country_code TEXT NOT NULL DEFAULT 'ID',
-            is_default_shipping INTEGER NOT NULL DEFAULT 0,
-            is_default_billing INTEGER NOT NULL DEFAULT 0,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    let _ = sqlx::query(
-        "CREATE INDEX IF NOT EXISTS user_addresses_user_id_idx ON user_addresses(user_id)",
-    )
-    .execute(pool)
-    .await;
-
-    // Create tenant_members table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS tenant_members (
-            id TEXT PRIMARY KEY NOT NULL,
-            tenant_id TEXT NOT NULL,
-            user_id TEXT NOT NULL,
-            role TEXT NOT NULL DEFAULT 'member',
-            created_at TEXT NOT NULL,
-            UNIQUE(tenant_id, user_id),
-            FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create settings table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS settings (
-            id TEXT PRIMARY KEY NOT NULL,
-            tenant_id TEXT,
-            key TEXT NOT NULL,
-            value TEXT NOT NULL,
-            description TEXT,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL,
-            FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create sessions table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS sessions (
-            id TEXT PRIMARY KEY NOT NULL,
-            user_id TEXT NOT NULL,
-            tenant_id TEXT,
-            token TEXT NOT NULL UNIQUE,
-            expires_at TEXT NOT NULL,
-            created_at TEXT NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
-            FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create permissions table (RBAC)
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS permissions (
-            id TEXT PRIMARY KEY NOT NULL,
-            resource TEXT NOT NULL,
-            action TEXT NOT NULL,
-            description TEXT,
-            UNIQUE(resource, action)
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create roles table (RBAC)
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS roles (
-            id TEXT PRIMARY KEY NOT NULL,
-            tenant_id TEXT,
-            name TEXT NOT NULL,
-            description TEXT,
-            is_system INTEGER NOT NULL DEFAULT 0,
-            level INTEGER NOT NULL DEFAULT 0,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL,
-            FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create role_permissions pivot table (RBAC)
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS role_permissions (
-            role_id TEXT NOT NULL,
-            permission_id TEXT NOT NULL,
-            PRIMARY KEY (role_id, permission_id),
-            FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE,
-            FOREIGN KEY (permission_id) REFERENCES permissions(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Migration: Add level column to roles if not exists for SQLite
-    // SQLite doesn't support IF NOT EXISTS in ADD COLUMN directly in all versions or easy check,
-    // but newer versions do. Or we can just try/catch.
-    let _ = sqlx::query("ALTER TABLE roles ADD COLUMN level INTEGER NOT NULL DEFAULT 0")
-        .execute(pool)
-        .await;
-
-    // Create indexes
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_users_email ON users(email)")
-        .execute(pool)
-        .await
-        .ok();
-    // Unique partial indexes for SQLite
-    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_settings_global_key ON settings(key) WHERE tenant_id IS NULL").execute(pool).await.ok();
-    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_settings_tenant_key ON settings(tenant_id, key) WHERE tenant_id IS NOT NULL").execute(pool).await.ok();
-
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_sessions_token ON sessions(token)")
-        .execute(pool)
-        .await
-        .ok();
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tenants_slug ON tenants(slug)")
-        .execute(pool)
-        .await
-        .ok();
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_roles_tenant ON roles(tenant_id)")
-        .execute(pool)
-        .await
-        .ok();
-
-    // ==================== SUBSCRIPTION PLANS (SQLite) ====================
-
-    // Create plans table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS plans (
-            id TEXT PRIMARY KEY NOT NULL,
-            name TEXT NOT NULL,
-            slug TEXT UNIQUE NOT NULL,
-            description TEXT,
-            price_monthly REAL DEFAULT 0,
-            price_yearly REAL DEFAULT 0,
-            is_active INTEGER DEFAULT 1,
-            is_default INTEGER DEFAULT 0,
-            sort_order INTEGER DEFAULT 0,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create feature_definitions table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS feature_definitions (
-            id TEXT PRIMARY KEY NOT NULL,
-            code TEXT UNIQUE NOT NULL,
-            name TEXT NOT NULL,
-            description TEXT,
-            value_type TEXT NOT NULL DEFAULT 'boolean',
-            category TEXT DEFAULT 'general',
-            default_value TEXT DEFAULT 'false',
-            sort_order INTEGER DEFAULT 0,
-            created_at TEXT NOT NULL
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create plan_features table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS plan_features (
-            id TEXT PRIMARY KEY NOT NULL,
-            plan_id TEXT NOT NULL,
-            feature_id TEXT NOT NULL,
-            value TEXT NOT NULL,
-            UNIQUE(plan_id, feature_id),
-            FOREIGN KEY (plan_id) REFERENCES plans(id) ON DELETE CASCADE,
-            FOREIGN KEY (feature_id) REFERENCES feature_definitions(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create tenant_subscriptions table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS tenant_subscriptions (
-            id TEXT PRIMARY KEY NOT NULL,
-            tenant_id TEXT NOT NULL,
-            plan_id TEXT NOT NULL,
-            status TEXT DEFAULT 'active',
-            trial_ends_at TEXT,
-            current_period_start TEXT,
-            current_period_end TEXT,
-            feature_overrides TEXT DEFAULT '{}',
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL,
-            UNIQUE(tenant_id),
-            FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE,
-            FOREIGN KEY (plan_id) REFERENCES plans(id)
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create file_records table (SQLite)
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS file_records (
-            id TEXT PRIMARY KEY NOT NULL,
-            tenant_id TEXT NOT NULL,
-            name TEXT NOT NULL,
-            original_name TEXT NOT NULL,
-            path TEXT NOT NULL,
-            size INTEGER NOT NULL,
-            content_type TEXT NOT NULL,
-            storage_provider TEXT NOT NULL DEFAULT 'local',
-            uploaded_by TEXT,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL,
-            FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE,
-            FOREIGN KEY (uploaded_by) REFERENCES users(id) ON DELETE SET NULL
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create invoices table (SQLite)
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS invoices (
-            id TEXT PRIMARY KEY NOT NULL,
-            tenant_id TEXT NOT NULL,
-            invoice_number TEXT UNIQUE NOT NULL,
-            amount REAL NOT NULL,
-            currency_code TEXT NOT NULL DEFAULT 'IDR',
-            base_currency_code TEXT NOT NULL DEFAULT 'IDR',
-            fx_rate REAL,
-            fx_source TEXT,
-            fx_fetched_at TEXT,
-            status TEXT NOT NULL DEFAULT 'pending',
-            description TEXT,
-            due_date TEXT NOT NULL,
-            paid_at TEXT,
-            payment_method TEXT,
-            external_id TEXT,
-            merchant_id TEXT,
-            rejection_reason TEXT,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL,
-            FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE,
-            FOREIGN KEY (merchant_id) REFERENCES tenants(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Billing collection logs (SQLite)
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS invoice_reminder_logs (
-            id TEXT PRIMARY KEY NOT NULL,
-            tenant_id TEXT NOT NULL,
-            invoice_id TEXT NOT NULL,
-            reminder_code TEXT NOT NULL,
-            channel TEXT NOT NULL DEFAULT 'email',
-            recipient TEXT,
-            status TEXT NOT NULL DEFAULT 'sent',
-            detail TEXT,
-            created_at TEXT NOT NULL,
-            FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE,
-            FOREIGN KEY (invoice_id) REFERENCES invoices(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS billing_collection_logs (
-            id TEXT PRIMARY KEY NOT NULL,
-            tenant_id TEXT NOT NULL,
-            invoice_id TEXT NOT NULL,
-            subscription_id TEXT,
-            action TEXT NOT NULL,
-            result TEXT NOT NULL,
-            reason TEXT,
-            actor_type TEXT NOT NULL DEFAULT 'system',
-            actor_id TEXT,
-            created_at TEXT NOT NULL,
-            FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE,
-            FOREIGN KEY (invoice_id) REFERENCES invoices(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Customer registration invites (SQLite)
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS customer_registration_invites (
-            id TEXT PRIMARY KEY NOT NULL,
-            tenant_id TEXT NOT NULL,
-            token_hash TEXT NOT NULL UNIQUE,
-            created_by TEXT,
-            max_uses INTEGER NOT NULL DEFAULT 1,
-            used_count INTEGER NOT NULL DEFAULT 0,
-            expires_at TEXT NOT NULL,
-            is_revoked INTEGER NOT NULL DEFAULT 0,
-            revoked_at TEXT,
-            last_used_at TEXT,
-            note TEXT,
-            created_at TEXT NOT NULL,
-            FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE,
-            FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create bank_accounts table (SQLite)
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS bank_accounts (
-            id TEXT PRIMARY KEY NOT NULL,
-            bank_name TEXT NOT NULL,
-            account_number TEXT NOT NULL,
-            account_holder TEXT NOT NULL,
-            is_active INTEGER NOT NULL DEFAULT 1,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Migration: Add storage_provider to file_records if not exists (SQLite)
-    let _ = sqlx::query(
-        "ALTER TABLE file_records ADD COLUMN storage_provider TEXT NOT NULL DEFAULT 'local'",
-    )
-    .execute(pool)
-    .await?;
-
-    // Migration: Add merchant_id and proof_attachment to invoices (SQLite)
-    let _ = sqlx::query(
-        "ALTER TABLE invoices ADD COLUMN merchant_id TEXT REFERENCES tenants(id) ON DELETE CASCADE",
-    )
-    .execute(pool)
-    .await;
-
-    let _ =
-        sqlx::query("ALTER TABLE invoices ADD COLUMN currency_code TEXT NOT NULL DEFAULT 'IDR'")
-            .execute(pool)
-            .await;
-
-    let _ = sqlx::query(
-        "ALTER TABLE invoices ADD COLUMN base_currency_code TEXT NOT NULL DEFAULT 'IDR'",
-    )
-    .execute(pool)
-    .await;
-    let _ = sqlx::query("ALTER TABLE invoices ADD COLUMN fx_rate REAL")
-        .execute(pool)
-        .await;
-    let _ = sqlx::query("ALTER TABLE invoices ADD COLUMN fx_source TEXT")
-        .execute(pool)
-        .await;
-    let _ = sqlx::query("ALTER TABLE invoices ADD COLUMN fx_fetched_at TEXT")
-        .execute(pool)
-        .await;
-
-    let _ = sqlx::query("ALTER TABLE invoices ADD COLUMN proof_attachment TEXT")
-        .execute(pool)
-        .await;
-    let _ = sqlx::query("ALTER TABLE invoices ADD COLUMN rejection_reason TEXT")
-        .execute(pool)
-        .await;
-
-    // FX cache table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS fx_rates (
-            base_currency TEXT NOT NULL,
-            quote_currency TEXT NOT NULL,
-            rate REAL NOT NULL,
-            fetched_at TEXT NOT NULL,
-            source TEXT NOT NULL,
-            PRIMARY KEY (base_currency, quote_currency)
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create indexes for plans (SQLite)
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_plans_slug ON plans(slug)")
-        .execute(pool)
-        .await
-        .ok();
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_feature_definitions_code ON feature_definitions(code)",
-    )
-    .execute(pool)
-    .await
-    .ok();
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_plan_features_plan ON plan_features(plan_id)")
-        .execute(pool)
-        .await
-        .ok();
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tenant_subscriptions_tenant ON tenant_subscriptions(tenant_id)").execute(pool).await.ok();
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_invoice_reminder_logs_tenant_created ON invoice_reminder_logs(tenant_id, created_at DESC)",
-    )
-    .execute(pool)
-    .await
-    .ok();
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_invoice_reminder_logs_invoice_created ON invoice_reminder_logs(invoice_id, created_at DESC)",
-    )
-    .execute(pool)
-    .await
-    .ok();
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_billing_collection_logs_tenant_created ON billing_collection_logs(tenant_id, created_at DESC)",
-    )
-    .execute(pool)
-    .await
-    .ok();
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_billing_collection_logs_invoice_created ON billing_collection_logs(invoice_id, created_at DESC)",
-    )
-    .execute(pool)
-    .await
-    .ok();
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_customer_registration_invites_tenant_created ON customer_registration_invites(tenant_id, created_at DESC)",
-    )
-    .execute(pool)
-    .await
-    .ok();
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_customer_registration_invites_tenant_expires ON customer_registration_invites(tenant_id, expires_at DESC)",
-    )
-    .execute(pool)
-    .await
-    .ok();
-
-    info!("SQLite migrations completed");
-
-    // Create notifications table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS notifications (
-            id TEXT PRIMARY KEY NOT NULL,
-            user_id TEXT NOT NULL,
-            tenant_id TEXT,
-            title TEXT NOT NULL,
-            message TEXT NOT NULL,
-            notification_type TEXT DEFAULT 'info',
-            category TEXT DEFAULT 'system',
-            action_url TEXT,
-            is_read INTEGER NOT NULL DEFAULT 0,
-            created_at TEXT NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
-            FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Migration: Add columns to notifications if not exists (SQLite)
-    // We ignore errors if columns already exist
-    let _ =
-        sqlx::query("ALTER TABLE notifications ADD COLUMN notification_type TEXT DEFAULT 'info'")
-            .execute(pool)
-            .await;
-    let _ = sqlx::query("ALTER TABLE notifications ADD COLUMN category TEXT DEFAULT 'system'")
-        .execute(pool)
-        .await;
-    let _ = sqlx::query("ALTER TABLE notifications ADD COLUMN action_url TEXT")
-        .execute(pool)
-        .await;
-
-    // Create notification_preferences table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS notification_preferences (
-            id TEXT PRIMARY KEY NOT NULL,
-            user_id TEXT NOT NULL,
-            channel TEXT NOT NULL,
-            category TEXT NOT NULL,
-            enabled INTEGER NOT NULL DEFAULT 1,
-            updated_at TEXT NOT NULL,
-            UNIQUE(user_id, channel, category),
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Create push_subscriptions table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS push_subscriptions (
-            id TEXT PRIMARY KEY NOT NULL,
-            user_id TEXT NOT NULL,
-            endpoint TEXT UNIQUE NOT NULL,
-            p256dh TEXT NOT NULL,
-            auth TEXT NOT NULL,
-            created_at TEXT NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
+/// Connect to the optional read replica (`DATABASE_REPLICA_URL`).
+///
+/// Replica sessions are forced read-only. A missing or unreachable replica is not
+/// fatal: queries fall back to the primary.
+pub async fn init_read_replica() -> Option<DbPool> {
+    let url = env::var("DATABASE_REPLICA_URL")
+        .ok()
+        .filter(|v| !v.trim().is_empty())?;
 
-    // Migration: Add 2FA columns to users if not exists (SQLite)
-    let _ =
-        sqlx::query("ALTER TABLE users ADD COLUMN two_factor_enabled INTEGER NOT NULL DEFAULT 0")
-            .execute(pool)
-            .await;
-    let _ = sqlx::query("ALTER TABLE users ADD COLUMN two_factor_secret TEXT")
-        .execute(pool)
-        .await;
-    let _ = sqlx::query("ALTER TABLE users ADD COLUMN two_factor_recovery_codes TEXT")
-        .execute(pool)
-        .await;
-    let _ = sqlx::query("ALTER TABLE users ADD COLUMN email_otp_code TEXT")
-        .execute(pool)
-        .await;
-    let _ = sqlx::query("ALTER TABLE users ADD COLUMN email_otp_expires TEXT")
-        .execute(pool)
-        .await;
-    let _ = sqlx::query("ALTER TABLE users ADD COLUMN preferred_2fa_method TEXT DEFAULT 'totp'")
-        .execute(pool)
-        .await;
-    let _ = sqlx::query("ALTER TABLE users ADD COLUMN totp_enabled INTEGER NOT NULL DEFAULT 0")
-        .execute(pool)
-        .await;
-    let _ =
-        sqlx::query("ALTER TABLE users ADD COLUMN email_2fa_enabled INTEGER NOT NULL DEFAULT 0")
-            .execute(pool)
-            .await;
-
-    // Data migration: Set totp_enabled=1 for existing users who have TOTP secret (SQLite uses INTEGER)
-    let _ = sqlx::query("UPDATE users SET totp_enabled = 1 WHERE two_factor_secret IS NOT NULL AND totp_enabled = 0")
-        .execute(pool)
-        .await;
-
-    // Data migration: Set email_2fa_enabled=1 for existing users with email 2FA preference
-    let _ = sqlx::query("UPDATE users SET email_2fa_enabled = 1 WHERE two_factor_enabled = 1 AND preferred_2fa_method = 'email' AND email_2fa_enabled = 0")
-        .execute(pool)
-        .await;
-
-    // Create trusted_devices table for 2FA device trust (SQLite)
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS trusted_devices (
-            id TEXT PRIMARY KEY NOT NULL,
-            user_id TEXT NOT NULL,
-            device_fingerprint TEXT NOT NULL,
-            ip_address TEXT,
-            user_agent TEXT,
-            trusted_at TEXT NOT NULL,
-            expires_at TEXT NOT NULL,
-            last_used_at TEXT NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )
-    "#,
-    )
-    .execute(pool)
-    .await?;
-
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_trusted_devices_user ON trusted_devices(user_id)")
-        .execute(pool)
-        .await
-        .ok();
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_trusted_devices_expires ON trusted_devices(expires_at)",
-    )
-    .execute(pool)
-    .await
-    .ok();
+    #[cfg(feature = "postgres")]
+    {
+        let config = PoolConfig::from_env();
+        let options = match pg_connect_options(url.trim(), &config) {
+            Ok(o) => o.options([("default_transaction_read_only", "on")]),
+            Err(e) => {
+                warn!(
+                    "Invalid DATABASE_REPLICA_URL, using primary for reads: {}",
+                    e
+                );
+                return None;
+            }
+        };
 
-    seed_defaults(pool).await?;
-    seed_roles(pool).await?;
+        match with_tenant_routing(config.pool_options::<Postgres>())
+            .connect_with(options)
+            .await
+        {
+            Ok(pool) => {
+                info!("Connected to PostgreSQL read replica");
+                Some(pool)
+            }
+            Err(e) => {
+                warn!("Read replica unavailable, using primary for reads: {}", e);
+                None
+            }
+        }
+    }
 
-    Ok(())
+    #[cfg(feature = "sqlite")]
+    {
+        let _ = url;
+        warn!("DATABASE_REPLICA_URL is ignored in SQLite mode");
+        None
+    }
 }
 
 /// Seed default settings
@@ -870,7 +314,12 @@ pub async fn seed_defaults(pool: &DbPool) -> Result<(), sqlx::Error> {
         ("enable_ip_blocking", "false", "Enable automatic IP blocking on suspicious activity"),
         ("ip_block_threshold", "5", "How many rate-limit hits within a window will trigger blocking"),
         ("ip_block_duration_minutes", "15", "How long an IP stays blocked after triggering"),
+        ("trusted_proxies", "", "Comma-separated CIDR ranges of reverse proxies whose X-Forwarded-For/Forwarded headers are trusted (empty = TRUSTED_PROXIES env or loopback)"),
+        ("idempotency_window_hours", "24", "How long responses to Idempotency-Key requests are kept for replay"),
         ("maintenance_mode", "false", "System maintenance mode"),
+        // Desktop client updates
+        ("update_channel", "stable", "Release channel desktop clients update from: stable or beta"),
+        ("client_min_version", "", "Oldest desktop client version allowed to use the API (empty = any)"),
         ("maintenance_message", "The system is currently under maintenance. Please try again later.", "Maintenance message displayed to users"),
         ("storage_max_file_size_mb", "500", "Maximum file upload size in Megabytes"),
         ("storage_allowed_extensions", "jpg,jpeg,png,gif,pdf,doc,docx,xls,xlsx,zip,rar,7z,mp4,mov,avi,mp3,wav", "Comma-separated list of allowed file extensions"),
@@ -882,6 +331,13 @@ pub async fn seed_defaults(pool: &DbPool) -> Result<(), sqlx::Error> {
         ("storage_s3_access_key", "", "S3 Access Key ID"),
         ("storage_s3_secret_key", "", "S3 Secret Access Key"),
         ("storage_s3_public_url", "", "Public CDN URL for S3 files (optional)"),
+        ("storage_s3_prefix", "", "Key prefix inside the S3 bucket (optional)"),
+        ("storage_s3_path_style", "false", "Use path-style S3 URLs (required for MinIO)"),
+        // Malware scanning (ClamAV)
+        ("storage_scan_enabled", "false", "Scan uploads for malware with ClamAV"),
+        ("storage_clamd_address", "127.0.0.1:3310", "clamd address: host:port or a unix socket path"),
+        ("storage_scan_fail_closed", "false", "Reject uploads when the malware scanner is unavailable"),
+        ("storage_max_versions", "10", "Earlier versions kept per file (0 = keep all)"),
         // Payment Settings
         ("payment_midtrans_enabled", "false", "Enable Midtrans Payment Gateway"),
         ("payment_midtrans_merchant_id", "", "Midtrans Merchant ID"),
@@ -902,15 +358,23 @@ pub async fn seed_defaults(pool: &DbPool) -> Result<(), sqlx::Error> {
         ("installation_sla_overdue_minutes", "120", "Minutes after schedule when installation work order is considered overdue"),
         ("installation_sla_reminder_cooldown_minutes", "180", "Cooldown in minutes before repeating the same installation SLA reminder"),
         ("installation_sla_scheduler_interval_minutes", "15", "How often installation SLA scheduler scans for overdue work orders (minutes)"),
+        ("work_order_slot_minutes", "120", "Default length of a technician time slot when a work order has no explicit end (minutes)"),
+        ("work_order_auto_dismantle_on_cancel", "true", "Create a dismantle work order when an installed subscription is cancelled"),
         // Alerting Settings
-        ("alerting_enabled", "false", "Enable error alerting via email"),
+        ("alerting_enabled", "false", "Evaluate system alert rules and send alerts"),
         ("alerting_email", "", "Email address to receive alerts"),
-        ("alerting_error_threshold", "5.0", "Error rate threshold percentage to trigger alert"),
-        ("alerting_rate_limit_threshold", "50", "Rate limit count threshold to trigger alert"),
-        ("alerting_response_time_threshold", "3000.0", "P95 response time threshold in ms"),
-        ("alerting_cooldown_minutes", "15", "Minutes to wait before sending same alert type again"),
+        ("alerting_telegram_chat_id", "", "Telegram chat that receives alerts (uses the Telegram bot)"),
+        ("alerting_webhook_url", "", "URL system alerts are POSTed to as JSON"),
+        ("alerting_webhook_secret", "", "HMAC secret for the X-Alert-Signature header of alert webhooks"),
         // MikroTik Metrics Retention
         ("mikrotik_metrics_retention_days", "14", "Retention days for mikrotik_router_metrics and mikrotik_interface_metrics (0 = disable cleanup)"),
+        ("mikrotik_logs_retention_days", "30", "Retention days for mikrotik_logs partitions (0 = keep forever)"),
+        ("audit_logs_retention_days", "0", "Retention days for audit_logs partitions (0 = keep forever). Dropping old entries also drops the anchor of the audit hash chain"),
+        ("usage_records_retention_days", "0", "Retention days for usage_records partitions behind the customer usage charts and reports (0 = keep forever)"),
+        ("trash_retention_days", "30", "Days to keep soft-deleted customers, routers, users and packages before permanent purge (0 = never purge)"),
+        // Database Maintenance
+        ("db_maintenance_enabled", "true", "Run scheduled ANALYZE/VACUUM (Postgres) or PRAGMA optimize/WAL checkpoint (SQLite)"),
+        ("db_maintenance_interval_hours", "24", "Hours between scheduled database maintenance runs"),
         // Timezone (IANA TZ database name, e.g. Asia/Jakarta). Used for schedules shown in the UI.
         ("app_timezone", "UTC", "Application timezone for schedules (IANA, e.g. Asia/Jakarta)"),
         // Backup Scheduler
@@ -920,7 +384,11 @@ pub async fn seed_defaults(pool: &DbPool) -> Result<(), sqlx::Error> {
         ("backup_global_at", "02:00", "Global backup time (HH:MM) for day/week modes (app_timezone)"),
         ("backup_global_weekday", "sun", "Global backup weekday for weekly mode (mon..sun)"),
         ("backup_global_schedule", "0 2 * * *", "Legacy global backup schedule in cron (min hour * * *) or HH:MM (app_timezone)"),
+        ("backup_global_cron", "", "Global backup cron expression (min hour day month weekday, app_timezone); overrides the mode settings when set"),
         ("backup_global_retention_days", "30", "Retention days for global backups"),
+        ("backup_global_keep_daily", "0", "Also keep the newest global backup of each of the last N days"),
+        ("backup_global_keep_weekly", "0", "Also keep the newest global backup of each of the last N weeks"),
+        ("backup_global_keep_monthly", "0", "Also keep the newest global backup of each of the last N months"),
         ("backup_global_trigger", "false", "Manual trigger for global backup"),
         ("backup_tenant_enabled", "false", "Enable automatic tenant backups"),
         ("backup_tenant_mode", "day", "Tenant backup schedule mode: minute, hour, day, week"),
@@ -928,12 +396,66 @@ pub async fn seed_defaults(pool: &DbPool) -> Result<(), sqlx::Error> {
         ("backup_tenant_at", "02:30", "Tenant backup time (HH:MM) for day/week modes (app_timezone)"),
         ("backup_tenant_weekday", "sun", "Tenant backup weekday for weekly mode (mon..sun)"),
         ("backup_tenant_schedule", "30 2 * * *", "Legacy tenant backup schedule in cron (min hour * * *) or HH:MM (app_timezone)"),
+        ("backup_tenant_cron", "", "Default tenant backup cron expression (app_timezone); overrides the mode settings when set"),
         ("backup_tenant_retention_days", "14", "Retention days for tenant backups"),
+        ("backup_tenant_keep_daily", "0", "Also keep the newest tenant backup of each of the last N days"),
+        ("backup_tenant_keep_weekly", "0", "Also keep the newest tenant backup of each of the last N weeks"),
+        ("backup_tenant_keep_monthly", "0", "Also keep the newest tenant backup of each of the last N months"),
         ("backup_tenant_trigger", "false", "Manual trigger for tenant backups"),
+        ("backup_remote_enabled", "false", "Copy new backups to an S3-compatible bucket (S3, MinIO, Backblaze B2)"),
+        ("backup_remote_endpoint", "", "Off-site backup endpoint URL (empty = AWS S3)"),
+        ("backup_remote_region", "us-east-1", "Off-site backup bucket region"),
+        ("backup_remote_bucket", "", "Off-site backup bucket name"),
+        ("backup_remote_access_key", "", "Off-site backup access key ID"),
+        ("backup_remote_secret_key", "", "Off-site backup secret access key"),
+        ("backup_remote_prefix", "backups", "Key prefix for off-site backups"),
+        ("backup_remote_path_style", "false", "Use path-style bucket URLs (required by MinIO)"),
+        ("backup_remote_retention_days", "90", "Delete off-site backups older than this many days (0 = keep forever)"),
+        ("backup_remote_min_keep", "3", "Always keep at least this many off-site backups per scope"),
+        ("backup_remote_cron", "", "Off-site upload cron expression (app_timezone); empty = upload right after each backup"),
         // Email Outbox
         ("email_outbox_enabled", "true", "Queue outgoing emails and retry failures"),
         ("email_outbox_max_attempts", "5", "Max retry attempts for queued emails"),
         ("email_outbox_base_delay_seconds", "30", "Base retry delay in seconds for queued emails (exponential backoff)"),
+        ("email_outbox_hourly_limit", "0", "Max emails sent per tenant per hour, 0 = unlimited (priority mail is never held)"),
+        ("email_events_webhook_token", "", "Secret path token for bounce/complaint webhooks (SES, SendGrid, Mailgun); empty = disabled"),
+        ("email_mailgun_webhook_secret", "", "Mailgun webhook signing key; when set, Mailgun events must carry a valid signature"),
+        ("support_inbound_token", "", "Per-tenant path token for inbound support email (POST raw MIME to /api/support/inbound/{token}); empty = disabled"),
+        ("support_auto_close_days", "0", "Close pending support tickets after this many days without a public reply (0 = never)"),
+        ("support_reopen_window_days", "7", "Days after closing during which a customer reply reopens a support ticket (0 = never)"),
+        // Event Outbox
+        ("event_webhook_url", "", "URL that receives every outbox event as a signed POST (empty = WebSocket only)"),
+        ("event_webhook_secret", "", "HMAC-SHA256 secret used to sign event webhook payloads"),
+        ("event_webhook_max_attempts", "10", "Max delivery attempts for an event webhook before it is marked failed"),
+        ("event_outbox_retention_days", "7", "Days to keep delivered outbox events"),
+        // WhatsApp channel (tenants override; events are routed per tenant via whatsapp_route_<event>)
+        ("whatsapp_enabled", "false", "Send customer notifications over WhatsApp"),
+        ("whatsapp_provider", "meta", "WhatsApp provider: meta (Cloud API) or gateway (generic HTTP gateway)"),
+        ("whatsapp_default_language", "id", "Preferred template language code"),
+        ("whatsapp_meta_phone_number_id", "", "Meta Cloud API phone number ID"),
+        ("whatsapp_meta_access_token", "", "Meta Cloud API access token"),
+        ("whatsapp_meta_api_version", "v21.0", "Meta Graph API version"),
+        ("whatsapp_meta_app_secret", "", "Meta app secret used to verify X-Hub-Signature-256 on status webhooks"),
+        ("whatsapp_meta_verify_token", "", "Token Meta echoes when subscribing the status webhook"),
+        ("whatsapp_gateway_url", "", "WhatsApp gateway send endpoint (POST JSON)"),
+        ("whatsapp_gateway_token", "", "Bearer token for the WhatsApp gateway"),
+        ("whatsapp_gateway_webhook_secret", "", "HMAC-SHA256 secret the gateway signs status callbacks with"),
+        // Telegram bot (platform-wide; tenant members link their own chats)
+        ("telegram_enabled", "false", "Enable the Telegram bot for admin alerts and commands"),
+        ("telegram_bot_token", "", "Telegram bot token from @BotFather"),
+        ("telegram_bot_username", "", "Bot username, used for t.me link buttons"),
+        ("telegram_webhook_secret", "", "Secret token of a registered bot webhook (empty = long polling)"),
+        // Web Push (VAPID_PRIVATE_KEY overrides; an empty key is generated on first push)
+        ("vapid_subject", "", "Contact for push services in the VAPID token (mailto: or https: URI)"),
+        ("vapid_public_key", "", "Web Push application server public key (base64url)"),
+        ("vapid_private_key", "", "Web Push application server private key (base64url); generated when empty"),
+        // Business hours for notification routing rules (tenants override; app_timezone)
+        ("notification_business_hours_start", "08:00", "Start of business hours (HH:MM) for alert routing rules"),
+        ("notification_business_hours_end", "17:00", "End of business hours (HH:MM); earlier than the start means overnight"),
+        ("notification_business_days", "mon,tue,wed,thu,fri", "Business days for alert routing rules (comma-separated, e.g. mon,tue)"),
+        // In-app notification retention (0 = keep forever)
+        ("notification_retention_days", "180", "Delete in-app notifications older than this many days"),
+        ("notification_archived_retention_days", "30", "Delete archived notifications this many days after archiving"),
     ];
 
     for (key, value, description) in defaults {
@@ -1183,6 +705,8 @@ pub async fn seed_roles(pool: &DbPool) -> Result<(), sqlx::Error> {
             "Update support tickets (status/priority)",
         ),
         ("support", "assign", "Assign support tickets"),
+        ("support", "queues", "Manage support queues and routing"),
+        ("support", "macros", "Manage canned responses and macros"),
         ("support", "internal", "Post internal support notes"),
         // Audit Logs (tenant-scoped; subject to plan feature access)
         ("audit_logs", "read", "View audit logs"),
@@ -1199,13 +723,43 @@ pub async fn seed_roles(pool: &DbPool) -> Result<(), sqlx::Error> {
         ("network_topology", "manage", "Manage network topology map"),
         ("service_zones", "read", "View service zones"),
         ("service_zones", "manage", "Manage service zones"),
+        ("ipam", "read", "View the IP address plan"),
+        ("ipam", "manage", "Manage IP address plan subnets"),
+        (
+            "cgnat",
+            "read",
+            "Look up subscribers by CGNAT public IP and port",
+        ),
+        ("cgnat", "manage", "Import CGNAT port-block logs"),
         ("coverage", "read", "Read coverage checks"),
         ("pppoe", "read", "View PPPoE sessions and users"),
         ("pppoe", "manage", "Manage PPPoE sessions and users"),
         ("isp_packages", "read", "View ISP packages"),
         ("isp_packages", "manage", "Manage ISP packages"),
+        (
+            "legacy_import",
+            "manage",
+            "Import customers, packages and balances from another billing system",
+        ),
+        (
+            "integrations",
+            "read",
+            "View API keys, webhooks and their deliveries",
+        ),
+        ("integrations", "manage", "Manage API keys and webhooks"),
         ("work_orders", "read", "View installation work orders"),
         ("work_orders", "manage", "Manage installation work orders"),
+        (
+            "work_orders",
+            "templates",
+            "Manage work order checklist templates",
+        ),
+        ("inventory", "read", "View materials inventory and stock"),
+        (
+            "inventory",
+            "manage",
+            "Manage inventory items, locations and stock",
+        ),
         // Billing
         ("billing", "read", "View billing and subscription data"),
         ("billing", "manage", "Manage billing actions"),
@@ -1216,6 +770,12 @@ pub async fn seed_roles(pool: &DbPool) -> Result<(), sqlx::Error> {
         ("email_outbox", "read", "View email outbox"),
         ("email_outbox", "retry", "Retry failed email outbox"),
         ("email_outbox", "delete", "Delete email outbox records"),
+        // Notification delivery reports
+        (
+            "notification_reports",
+            "read",
+            "View notification delivery reports",
+        ),
     ];
 
     // Cleanup: Remove permissions with non-standard IDs (e.g. random UUIDs)
@@ -1327,6 +887,8 @@ pub async fn seed_roles(pool: &DbPool) -> Result<(), sqlx::Error> {
         "support:reply",
         "support:update",
         "support:assign",
+        "support:queues",
+        "support:macros",
         "support:internal",
         "customers:read",
         "customers:manage",
@@ -1338,13 +900,23 @@ pub async fn seed_roles(pool: &DbPool) -> Result<(), sqlx::Error> {
         "network_topology:manage",
         "service_zones:read",
         "service_zones:manage",
+        "ipam:read",
+        "ipam:manage",
+        "cgnat:read",
+        "cgnat:manage",
         "coverage:read",
         "pppoe:read",
         "pppoe:manage",
         "isp_packages:read",
         "isp_packages:manage",
+        "legacy_import:manage",
+        "integrations:read",
+        "integrations:manage",
         "work_orders:read",
         "work_orders:manage",
+        "work_orders:templates",
+        "inventory:read",
+        "inventory:manage",
         "billing:read",
         "billing:manage",
         "announcements:read",
@@ -1352,6 +924,7 @@ pub async fn seed_roles(pool: &DbPool) -> Result<(), sqlx::Error> {
         "email_outbox:read",
         "email_outbox:retry",
         "email_outbox:delete",
+        "notification_reports:read",
     ];
     for p in admin_perms {
         assign_perm(pool, "Admin", p).await?;
@@ -1368,12 +941,15 @@ pub async fn seed_roles(pool: &DbPool) -> Result<(), sqlx::Error> {
         "network_topology:manage",
         "service_zones:read",
         "service_zones:manage",
+        "ipam:read",
+        "ipam:manage",
         "coverage:read",
         "pppoe:read",
         "pppoe:manage",
         "isp_packages:read",
         "isp_packages:manage",
         "work_orders:read",
+        "inventory:read",
         "billing:read",
         "support:read",
         "support:read_all",
@@ -1396,6 +972,8 @@ pub async fn seed_roles(pool: &DbPool) -> Result<(), sqlx::Error> {
         "network_topology:manage",
         "service_zones:read",
         "service_zones:manage",
+        "ipam:read",
+        "ipam:manage",
         "coverage:read",
         "isp_packages:read",
         "work_orders:read",
@@ -1416,12 +994,14 @@ pub async fn seed_roles(pool: &DbPool) -> Result<(), sqlx::Error> {
         "billing:read",
         "billing:manage",
         "announcements:read",
+        "notification_reports:read",
         "support:create",
         "support:read",
         "support:read_all",
         "support:reply",
         "support:update",
         "support:assign",
+        "support:macros",
         "support:internal",
     ];
     for p in cs_perms {
@@ -1440,6 +1020,7 @@ pub async fn seed_roles(pool: &DbPool) -> Result<(), sqlx::Error> {
         "coverage:read",
         "work_orders:read",
         "work_orders:manage",
+        "inventory:read",
         "support:read",
         "support:read_all",
         "support:reply",
@@ -0,0 +1,593 @@
+//! Demo tenant generator (Postgres only).
+//!
+//! `seed demo` fills a tenant with a believable ISP: a handful of routers,
+//! a package catalogue, a few hundred customers with locations and
+//! subscriptions, several months of subscription invoices in every status and
+//! a NOC incident history. Output is deterministic for a given `rng_seed` so
+//! screenshots and bug reports can be reproduced.
+//!
+//! The generator refuses to run against a tenant that already has customers,
+//! so it never mixes fake rows into real data.
+
+use crate::db::DbPool;
+use crate::security::secret::encrypt_secret;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration, Months, Utc};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+const FIRST_NAMES: &[&str] = &[
+    "Agus", "Budi", "Citra", "Dewi", "Eko", "Fajar", "Gita", "Hendra", "Indah", "Joko", "Kartika",
+    "Lestari", "Made", "Nur", "Oktavia", "Putri", "Rizky", "Sari", "Teguh", "Utami", "Wahyu",
+    "Yuni",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Santoso", "Wijaya", "Saputra", "Pratama", "Hidayat", "Kusuma", "Nugroho", "Setiawan",
+    "Wibowo", "Siregar", "Purnomo", "Rahayu", "Gunawan", "Halim", "Susanto",
+];
+
+const STREETS: &[&str] = &[
+    "Jl. Merdeka",
+    "Jl. Sudirman",
+    "Jl. Diponegoro",
+    "Jl. Gatot Subroto",
+    "Jl. Ahmad Yani",
+    "Jl. Pemuda",
+    "Jl. Pahlawan",
+    "Jl. Veteran",
+];
+
+/// (name, area, latitude, longitude)
+const SITES: &[(&str, &str, f64, f64)] = &[
+    ("Core Jakarta", "Jakarta Selatan", -6.2615, 106.8106),
+    ("POP Depok", "Depok", -6.4025, 106.7942),
+    ("POP Bekasi", "Bekasi", -6.2383, 106.9756),
+    ("POP Tangerang", "Tangerang", -6.1783, 106.6319),
+    ("POP Bogor", "Bogor", -6.5950, 106.8166),
+    ("POP Cikarang", "Cikarang", -6.2850, 107.1710),
+];
+
+/// (name, speed description, monthly price in IDR)
+const PACKAGES: &[(&str, &str, f64)] = &[
+    ("Home 10", "10 Mbps unlimited", 150_000.0),
+    ("Home 20", "20 Mbps unlimited", 200_000.0),
+    ("Home 50", "50 Mbps unlimited", 300_000.0),
+    ("Home 100", "100 Mbps unlimited", 450_000.0),
+    ("Business 200", "200 Mbps with static IP", 1_250_000.0),
+];
+
+/// How many months of invoice history to generate per subscription.
+const INVOICE_MONTHS: u32 = 6;
+
+#[derive(Debug, Clone)]
+pub struct DemoOptions {
+    pub customers: usize,
+    pub routers: usize,
+    pub rng_seed: u64,
+}
+
+impl Default for DemoOptions {
+    fn default() -> Self {
+        Self {
+            customers: 300,
+            routers: 4,
+            rng_seed: 42,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct DemoSummary {
+    pub routers: usize,
+    pub packages: usize,
+    pub customers: usize,
+    pub subscriptions: usize,
+    pub invoices: usize,
+    pub incidents: usize,
+}
+
+struct Package {
+    id: String,
+    price_monthly: f64,
+}
+
+struct Router {
+    id: String,
+    name: String,
+    lat: f64,
+    lng: f64,
+    area: &'static str,
+}
+
+pub async fn seed_demo(pool: &DbPool, tenant_id: &str, opts: &DemoOptions) -> Result<DemoSummary> {
+    if opts.routers == 0 || opts.routers > SITES.len() {
+        bail!("demo routers must be between 1 and {}", SITES.len());
+    }
+
+    let existing: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM customers WHERE tenant_id = $1")
+        .bind(tenant_id)
+        .fetch_one(pool)
+        .await
+        .context("seed_demo customer count failed")?;
+    if existing > 0 {
+        bail!("tenant already has {existing} customers; demo data is only generated for an empty tenant");
+    }
+
+    let mut rng = StdRng::seed_from_u64(opts.rng_seed);
+    let mut summary = DemoSummary::default();
+    let now = Utc::now();
+    let mut tx = pool.begin().await.context("seed_demo begin failed")?;
+
+    let routers = insert_routers(&mut tx, tenant_id, opts.routers, now).await?;
+    summary.routers = routers.len();
+
+    let packages = insert_packages(&mut tx, tenant_id, now).await?;
+    summary.packages = packages.len();
+
+    let tag = &tenant_id[..tenant_id.len().min(8)];
+    for n in 0..opts.customers {
+        let router = routers
+            .choose(&mut rng)
+            .ok_or_else(|| anyhow!("no demo routers"))?;
+        let package = pick_package(&mut rng, &packages);
+        let joined_at = now - Duration::days(rng.gen_range(20..720));
+
+        let customer_id = insert_customer(&mut tx, tenant_id, n, &mut rng, joined_at).await?;
+        let location_id = insert_location(
+            &mut tx,
+            tenant_id,
+            &customer_id,
+            router,
+            &mut rng,
+            joined_at,
+        )
+        .await?;
+        summary.customers += 1;
+
+        let status = subscription_status(rng.gen_range(0..100));
+        let subscription_id = Uuid::new_v4().to_string();
+        let ends_at = (status == "cancelled").then(|| now - Duration::days(rng.gen_range(5..90)));
+        sqlx::query(
+            r#"
+            INSERT INTO customer_subscriptions (
+                id, tenant_id, customer_id, location_id, package_id, router_id,
+                billing_cycle, price, currency_code, status, starts_at, ends_at,
+                created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, 'monthly', $7, 'IDR', $8, $9, $10, $11, $11)
+            "#,
+        )
+        .bind(&subscription_id)
+        .bind(tenant_id)
+        .bind(&customer_id)
+        .bind(&location_id)
+        .bind(&package.id)
+        .bind(&router.id)
+        .bind(package.price_monthly)
+        .bind(status)
+        .bind(joined_at)
+        .bind(ends_at)
+        .bind(joined_at)
+        .execute(&mut *tx)
+        .await
+        .context("seed_demo subscription insert failed")?;
+        summary.subscriptions += 1;
+
+        if status == "pending_installation" {
+            continue;
+        }
+
+        for months_ago in (0..INVOICE_MONTHS).rev() {
+            let Some(period_start) = now.checked_sub_months(Months::new(months_ago)) else {
+                continue;
+            };
+            if period_start < joined_at || ends_at.is_some_and(|e| period_start > e) {
+                continue;
+            }
+            let invoice_status = invoice_status(status, months_ago, rng.gen_range(0..100));
+            insert_invoice(
+                &mut tx,
+                tenant_id,
+                &format!("INV-DEMO-{}-{:06}", tag, summary.invoices + 1),
+                &subscription_id,
+                package.price_monthly,
+                period_start,
+                invoice_status,
+                &mut rng,
+            )
+            .await?;
+            summary.invoices += 1;
+        }
+    }
+
+    summary.incidents = insert_incidents(&mut tx, tenant_id, &routers, &mut rng, now).await?;
+
+    tx.commit().await.context("seed_demo commit failed")?;
+    Ok(summary)
+}
+
+async fn insert_routers(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    count: usize,
+    now: DateTime<Utc>,
+) -> Result<Vec<Router>> {
+    // Demo routers point at TEST-NET addresses and are disabled so the poller
+    // never tries to reach them. The last one is shown offline.
+    let password = encrypt_secret("demo-password").map_err(|e| anyhow!("{e}"))?;
+    let mut routers = Vec::with_capacity(count);
+    for (i, (name, area, lat, lng)) in SITES.iter().take(count).enumerate() {
+        let id = Uuid::new_v4().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO mikrotik_routers (
+                id, tenant_id, name, host, port, username, password, use_tls, enabled,
+                identity, ros_version, is_online, last_seen_at, latency_ms,
+                latitude, longitude, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, 8728, 'api', $5, false, false, $3, '7.14.3', $6, $7, $8, $9, $10, $11, $11)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(name)
+        .bind(format!("192.0.2.{}", 10 + i))
+        .bind(&password)
+        .bind(i != count - 1)
+        .bind(now - Duration::minutes(i as i64))
+        .bind(4 + (i as i32) * 3)
+        .bind(lat)
+        .bind(lng)
+        .bind(now - Duration::days(730))
+        .execute(&mut **tx)
+        .await
+        .context("seed_demo router insert failed")?;
+
+        routers.push(Router {
+            id,
+            name: name.to_string(),
+            lat: *lat,
+            lng: *lng,
+            area,
+        });
+    }
+    Ok(routers)
+}
+
+async fn insert_packages(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    now: DateTime<Utc>,
+) -> Result<Vec<Package>> {
+    let mut packages = Vec::with_capacity(PACKAGES.len());
+    for (name, description, price_monthly) in PACKAGES {
+        let id = Uuid::new_v4().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO isp_packages (
+                id, tenant_id, name, description, features, is_active,
+                price_monthly, price_yearly, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, true, $6, $7, $8, $8)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(name)
+        .bind(description)
+        .bind(vec![description.to_string(), "24/7 support".to_string()])
+        .bind(price_monthly)
+        .bind(price_monthly * 10.0)
+        .bind(now - Duration::days(730))
+        .execute(&mut **tx)
+        .await
+        .context("seed_demo package insert failed")?;
+
+        packages.push(Package {
+            id,
+            price_monthly: *price_monthly,
+        });
+    }
+    Ok(packages)
+}
+
+async fn insert_customer(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    n: usize,
+    rng: &mut StdRng,
+    joined_at: DateTime<Utc>,
+) -> Result<String> {
+    let name = demo_customer_name(rng);
+    let email = format!(
+        "{}.{}@demo.example",
+        name.to_ascii_lowercase().replace(' ', "."),
+        n + 1
+    );
+    let id = Uuid::new_v4().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO customers (id, tenant_id, name, email, phone, is_active, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, true, $6, $6)
+        "#,
+    )
+    .bind(&id)
+    .bind(tenant_id)
+    .bind(&name)
+    .bind(email)
+    .bind(format!("+62812{:07}", rng.gen_range(0..10_000_000)))
+    .bind(joined_at)
+    .execute(&mut **tx)
+    .await
+    .context("seed_demo customer insert failed")?;
+    Ok(id)
+}
+
+async fn insert_location(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    customer_id: &str,
+    router: &Router,
+    rng: &mut StdRng,
+    joined_at: DateTime<Utc>,
+) -> Result<String> {
+    let street = STREETS.choose(rng).copied().unwrap_or("Jl. Merdeka");
+    let id = Uuid::new_v4().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO customer_locations (
+            id, tenant_id, customer_id, label, address_line1, city, country,
+            latitude, longitude, created_at, updated_at
+        )
+        VALUES ($1, $2, $3, 'Home', $4, $5, 'ID', $6, $7, $8, $8)
+        "#,
+    )
+    .bind(&id)
+    .bind(tenant_id)
+    .bind(customer_id)
+    .bind(format!("{} No. {}", street, rng.gen_range(1..250)))
+    .bind(router.area)
+    // Scatter customers within roughly 3 km of their POP.
+    .bind(router.lat + rng.gen_range(-0.03..0.03))
+    .bind(router.lng + rng.gen_range(-0.03..0.03))
+    .bind(joined_at)
+    .execute(&mut **tx)
+    .await
+    .context("seed_demo location insert failed")?;
+    Ok(id)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn insert_invoice(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    invoice_number: &str,
+    subscription_id: &str,
+    amount: f64,
+    period_start: DateTime<Utc>,
+    status: &str,
+    rng: &mut StdRng,
+) -> Result<()> {
+    let period_key = period_start.format("%Y-%m").to_string();
+    let due_date = period_start + Duration::days(7);
+    let paid_at = (status == "paid").then(|| period_start + Duration::days(rng.gen_range(0..10)));
+    let payment_method = match status {
+        "paid" => Some(if rng.gen_bool(0.6) {
+            "bank_transfer"
+        } else {
+            "midtrans"
+        }),
+        "verification_pending" => Some("bank_transfer"),
+        _ => None,
+    };
+    let rejection_reason =
+        (status == "failed" && payment_method.is_none()).then_some("Payment expired");
+
+    sqlx::query(
+        r#"
+        INSERT INTO invoices (
+            id, tenant_id, invoice_number, amount, currency_code, base_currency_code,
+            status, description, due_date, paid_at, payment_method, external_id,
+            rejection_reason, created_at, updated_at
+        )
+        VALUES ($1, $2, $3, $4, 'IDR', 'IDR', $5, $6, $7, $8, $9, $10, $11, $12, $12)
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(tenant_id)
+    .bind(invoice_number)
+    .bind(amount)
+    .bind(status)
+    .bind(format!(
+        "Internet subscription (monthly billing, period {})",
+        period_key
+    ))
+    .bind(due_date)
+    .bind(paid_at)
+    .bind(payment_method)
+    .bind(format!("pkgsub:{}:{}", subscription_id, period_key))
+    .bind(rejection_reason)
+    .bind(period_start)
+    .execute(&mut **tx)
+    .await
+    .context("seed_demo invoice insert failed")?;
+    Ok(())
+}
+
+async fn insert_incidents(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    routers: &[Router],
+    rng: &mut StdRng,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    let mut count = 0;
+    for (i, router) in routers.iter().enumerate() {
+        // A resolved history for every router...
+        for _ in 0..rng.gen_range(2..6) {
+            let first_seen = now - Duration::hours(rng.gen_range(24..24 * 60));
+            let resolved = first_seen + Duration::minutes(rng.gen_range(5..240));
+            let (kind, severity, title) = if rng.gen_bool(0.5) {
+                ("offline", "critical", format!("{} is offline", router.name))
+            } else {
+                ("cpu", "warning", format!("High CPU on {}", router.name))
+            };
+            insert_incident(
+                tx,
+                tenant_id,
+                router,
+                kind,
+                severity,
+                "resolved",
+                &title,
+                first_seen,
+                Some(resolved),
+            )
+            .await?;
+            count += 1;
+        }
+
+        // ...and one active incident on the last router so the NOC view is not empty.
+        if i == routers.len() - 1 {
+            let first_seen = now - Duration::minutes(rng.gen_range(10..90));
+            let title = format!("{} is offline", router.name);
+            insert_incident(
+                tx, tenant_id, router, "offline", "critical", "open", &title, first_seen, None,
+            )
+            .await?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn insert_incident(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    router: &Router,
+    kind: &str,
+    severity: &str,
+    status: &str,
+    title: &str,
+    first_seen: DateTime<Utc>,
+    resolved_at: Option<DateTime<Utc>>,
+) -> Result<()> {
+    let (message, value, threshold) = match kind {
+        "cpu" => (
+            "CPU load above threshold".to_string(),
+            Some(96.0),
+            Some(90.0),
+        ),
+        _ => ("Router did not answer API polls".to_string(), None, None),
+    };
+    let last_seen = resolved_at.unwrap_or(first_seen);
+    sqlx::query(
+        r#"
+        INSERT INTO mikrotik_incidents (
+            id, tenant_id, router_id, incident_type, dedup_key, severity, status,
+            title, message, value_num, threshold_num, first_seen_at, last_seen_at,
+            resolved_at, created_at, updated_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $12, $13)
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(tenant_id)
+    .bind(&router.id)
+    .bind(kind)
+    .bind(format!("{}:{}", kind, router.id))
+    .bind(severity)
+    .bind(status)
+    .bind(title)
+    .bind(message)
+    .bind(value)
+    .bind(threshold)
+    .bind(first_seen)
+    .bind(last_seen)
+    .bind(resolved_at)
+    .execute(&mut **tx)
+    .await
+    .context("seed_demo incident insert failed")?;
+    Ok(())
+}
+
+fn demo_customer_name(rng: &mut StdRng) -> String {
+    let first = FIRST_NAMES.choose(rng).copied().unwrap_or("Budi");
+    let last = LAST_NAMES.choose(rng).copied().unwrap_or("Santoso");
+    format!("{} {}", first, last)
+}
+
+/// Cheaper packages are more popular.
+fn pick_package<'p>(rng: &mut StdRng, packages: &'p [Package]) -> &'p Package {
+    let weights = [30, 35, 20, 10, 5];
+    let roll = rng.gen_range(0..weights.iter().sum::<u32>());
+    let mut acc = 0;
+    for (package, weight) in packages.iter().zip(weights) {
+        acc += weight;
+        if roll < acc {
+            return package;
+        }
+    }
+    &packages[0]
+}
+
+/// Subscription status for a roll in 0..100.
+fn subscription_status(roll: u32) -> &'static str {
+    match roll {
+        0..=79 => "active",
+        80..=87 => "suspended",
+        88..=93 => "pending_installation",
+        _ => "cancelled",
+    }
+}
+
+/// Invoice status for one billing period. Older periods are mostly settled;
+/// the current one is still open for many customers. Suspended subscriptions
+/// carry unpaid recent invoices.
+fn invoice_status(subscription_status: &str, months_ago: u32, roll: u32) -> &'static str {
+    match (subscription_status, months_ago) {
+        ("suspended", 0..=1) => "pending",
+        (_, 0) => match roll {
+            0..=54 => "paid",
+            55..=84 => "pending",
+            _ => "verification_pending",
+        },
+        _ => match roll {
+            0..=91 => "paid",
+            _ => "failed",
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let mut a = StdRng::seed_from_u64(7);
+        let mut b = StdRng::seed_from_u64(7);
+        let names_a: Vec<String> = (0..20).map(|_| demo_customer_name(&mut a)).collect();
+        let names_b: Vec<String> = (0..20).map(|_| demo_customer_name(&mut b)).collect();
+        assert_eq!(names_a, names_b);
+    }
+
+    #[test]
+    fn test_invoice_status_mix() {
+        assert_eq!(invoice_status("suspended", 0, 0), "pending");
+        assert_eq!(invoice_status("suspended", 1, 0), "pending");
+        assert_eq!(invoice_status("active", 0, 90), "verification_pending");
+        assert_eq!(invoice_status("active", 3, 0), "paid");
+        assert_eq!(invoice_status("cancelled", 3, 99), "failed");
+
+        assert_eq!(subscription_status(0), "active");
+        assert_eq!(subscription_status(85), "suspended");
+        assert_eq!(subscription_status(90), "pending_installation");
+        assert_eq!(subscription_status(99), "cancelled");
+    }
+}
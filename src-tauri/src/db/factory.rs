@@ -1,7 +1,11 @@
 use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
 use crate::services::AuthService;
 use anyhow::{anyhow, Context, Result};
 use chrono::{Duration, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
 
 #[derive(Clone)]
 pub struct DbFactory<'a> {
@@ -372,3 +376,184 @@ pub fn slugify(input: &str) -> String {
     }
     out.trim_matches('-').to_string()
 }
+
+/// How tenant data is laid out (`DB_TENANT_ISOLATION`, Postgres only).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TenantIsolation {
+    /// Every tenant lives in the shared `public` tables (default).
+    Shared,
+    /// Tenants can be given a dedicated schema for their ISP data.
+    Schema,
+}
+
+impl TenantIsolation {
+    pub fn from_env() -> Self {
+        let raw = std::env::var("DB_TENANT_ISOLATION").unwrap_or_default();
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "schema" if cfg!(feature = "postgres") => Self::Schema,
+            _ => Self::Shared,
+        }
+    }
+}
+
+/// Schema name used for an isolated tenant.
+pub fn tenant_schema_name(tenant_id: &str) -> String {
+    let id: String = tenant_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .take(48)
+        .collect();
+    format!("tenant_{}", id)
+}
+
+tokio::task_local! {
+    static TENANT_SCHEMA: Option<String>;
+}
+
+/// `search_path` for connections acquired by the current task. Outside a
+/// [`TenantDbRouter::scope`] (background jobs, shared tenants) this is `public`.
+pub fn current_search_path() -> String {
+    match TENANT_SCHEMA.try_with(|s| s.clone()).ok().flatten() {
+        Some(schema) => format!("\"{}\", public", schema),
+        None => "public".to_string(),
+    }
+}
+
+/// Routes tenant-scoped queries to the tenant's schema.
+///
+/// In schema mode the pool sets `search_path` from [`current_search_path`] every
+/// time a connection is handed out, so services keep using unqualified table
+/// names and the shared pool. Tenants without a registered schema (and the
+/// whole of shared mode) resolve to `public`.
+///
+/// Only work running inside [`scope`](Self::scope) is routed. Background jobs
+/// that scan every tenant, and tasks spawned off a request, see the shared
+/// tables only.
+#[derive(Clone)]
+pub struct TenantDbRouter {
+    pool: DbPool,
+    mode: TenantIsolation,
+    schemas: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl TenantDbRouter {
+    pub fn new(pool: DbPool, mode: TenantIsolation) -> Self {
+        Self {
+            pool,
+            mode,
+            schemas: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn mode(&self) -> TenantIsolation {
+        self.mode
+    }
+
+    /// Reload the tenant -> schema registry (other instances may have isolated
+    /// tenants since the last load).
+    pub async fn reload(&self) -> AppResult<()> {
+        if self.mode != TenantIsolation::Schema {
+            return Ok(());
+        }
+
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT tenant_id, schema_name FROM tenant_schemas")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut schemas = self.schemas.write().unwrap_or_else(|e| e.into_inner());
+        *schemas = rows.into_iter().collect();
+        Ok(())
+    }
+
+    pub fn schema_for(&self, tenant_id: &str) -> Option<String> {
+        if self.mode != TenantIsolation::Schema {
+            return None;
+        }
+        self.schemas
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(tenant_id)
+            .cloned()
+    }
+
+    /// Run `fut` with connections routed to `tenant_id`'s schema.
+    pub async fn scope<F: Future>(&self, tenant_id: Option<&str>, fut: F) -> F::Output {
+        let schema = tenant_id.and_then(|id| self.schema_for(id));
+        TENANT_SCHEMA.scope(schema, fut).await
+    }
+
+    /// Give a tenant its own schema. Only tenants without ISP data can be
+    /// isolated; moving existing rows is an offline operation.
+    pub async fn isolate(&self, tenant_id: &str) -> AppResult<String> {
+        if self.mode != TenantIsolation::Schema {
+            return Err(AppError::Validation(
+                "Schema isolation is disabled (set DB_TENANT_ISOLATION=schema)".to_string(),
+            ));
+        }
+
+        let schema = tenant_schema_name(tenant_id);
+
+        #[cfg(feature = "postgres")]
+        {
+            sqlx::query("SELECT public.provision_tenant_schema($1, $2)")
+                .bind(tenant_id)
+                .bind(&schema)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| match &e {
+                    sqlx::Error::Database(db) if db.code().as_deref() == Some("55000") => {
+                        AppError::Conflict(db.message().to_string())
+                    }
+                    _ => AppError::from(e),
+                })?;
+        }
+
+        self.schemas
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(tenant_id.to_string(), schema.clone());
+        Ok(schema)
+    }
+}
+
+/// Apply pending table/column changes from `public` to every tenant schema.
+/// Runs after migrations; returns how many objects were added.
+#[cfg(feature = "postgres")]
+pub async fn sync_tenant_schemas(pool: &DbPool) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar("SELECT public.sync_tenant_schemas()")
+        .fetch_one(pool)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenant_schema_name() {
+        assert_eq!(
+            tenant_schema_name("3F2504E0-4F89-11D3-9A0C-0305E82C3301"),
+            "tenant_3f2504e04f8911d39a0c0305e82c3301"
+        );
+        assert_eq!(tenant_schema_name("acme\"; DROP"), "tenant_acmedrop");
+    }
+
+    #[tokio::test]
+    async fn test_current_search_path() {
+        assert_eq!(current_search_path(), "public");
+
+        let path = TENANT_SCHEMA
+            .scope(Some("tenant_abc".to_string()), async {
+                current_search_path()
+            })
+            .await;
+        assert_eq!(path, "\"tenant_abc\", public");
+
+        let path = TENANT_SCHEMA
+            .scope(None, async { current_search_path() })
+            .await;
+        assert_eq!(path, "public");
+    }
+}
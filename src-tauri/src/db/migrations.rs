@@ -0,0 +1,292 @@
+//! Versioned schema migrations and status reporting.
+//!
+//! Both backends use embedded sqlx migrations:
+//! - PostgreSQL: `./migrations`
+//! - SQLite: `./migrations_sqlite`
+//!
+//! sqlx tracks applied versions in `_sqlx_migrations`. After each run we also record
+//! them in `schema_migrations`, together with the app version that applied them.
+
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::migrate::Migrator;
+use std::collections::{HashMap, HashSet};
+use tracing::info;
+
+#[cfg(feature = "postgres")]
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+#[cfg(feature = "sqlite")]
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations_sqlite");
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MigrationItem {
+    pub version: i64,
+    pub description: String,
+    pub installed_on: DateTime<Utc>,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_time_ms: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PendingMigration {
+    pub version: i64,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MigrationStatus {
+    pub backend: String,
+    pub up_to_date: bool,
+    pub resolved_count: i64,
+    pub applied: Vec<MigrationItem>,
+    pub pending: Vec<PendingMigration>,
+    /// Applied in the database but unknown to this build (newer binary ran against it).
+    pub missing_versions: Vec<i64>,
+    /// Recorded as failed (`success = false`); needs manual attention.
+    pub failed_versions: Vec<i64>,
+    /// File content changed after the migration was applied.
+    pub checksum_mismatch_versions: Vec<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_applied_version: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_available_version: Option<i64>,
+}
+
+#[derive(sqlx::FromRow)]
+struct AppliedRow {
+    version: i64,
+    description: String,
+    installed_on: DateTime<Utc>,
+    success: bool,
+    checksum: Vec<u8>,
+    execution_time: Option<i64>,
+}
+
+pub(crate) fn backend_name() -> &'static str {
+    if cfg!(feature = "postgres") {
+        "PostgreSQL"
+    } else {
+        "SQLite"
+    }
+}
+
+fn is_missing_table(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::Database(db) => {
+            db.code().as_deref() == Some("42P01") || db.message().contains("no such table")
+        }
+        _ => false,
+    }
+}
+
+fn checksum_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Apply all pending migrations for the active backend.
+pub async fn run_migrations(pool: &DbPool) -> Result<(), sqlx::Error> {
+    #[cfg(feature = "sqlite")]
+    bootstrap_legacy_sqlite(pool).await?;
+
+    MIGRATOR
+        .run(pool)
+        .await
+        .map_err(|e| sqlx::Error::Migrate(Box::new(e)))?;
+
+    record_schema_migrations(pool).await?;
+
+    info!("{} migrations completed", backend_name());
+    Ok(())
+}
+
+/// Copy applied versions into `schema_migrations` (idempotent).
+async fn record_schema_migrations(pool: &DbPool) -> Result<(), sqlx::Error> {
+    let applied: HashSet<i64> =
+        sqlx::query_scalar::<_, i64>("SELECT version FROM _sqlx_migrations WHERE success = true")
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+
+    let app_version = env!("CARGO_PKG_VERSION");
+    let now = Utc::now();
+
+    for m in MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+    {
+        if !applied.contains(&m.version) {
+            continue;
+        }
+
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            r#"
+            INSERT INTO schema_migrations (version, description, checksum, app_version, applied_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (version) DO NOTHING
+        "#,
+        )
+        .bind(m.version)
+        .bind(m.description.as_ref())
+        .bind(checksum_hex(&m.checksum))
+        .bind(app_version)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO schema_migrations (version, description, checksum, app_version, applied_at)
+            VALUES (?, ?, ?, ?, ?)
+        "#,
+        )
+        .bind(m.version)
+        .bind(m.description.as_ref())
+        .bind(checksum_hex(&m.checksum))
+        .bind(app_version)
+        .bind(now.to_rfc3339())
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Compare embedded migrations with what the database has applied.
+pub async fn migration_status(pool: &DbPool) -> Result<MigrationStatus, sqlx::Error> {
+    let rows: Vec<AppliedRow> = match sqlx::query_as::<_, AppliedRow>(
+        "SELECT version, description, installed_on, success, checksum, execution_time FROM _sqlx_migrations ORDER BY version",
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(r) => r,
+        // Fresh database before the first run: nothing applied yet.
+        Err(e) if is_missing_table(&e) => Vec::new(),
+        Err(e) => return Err(e),
+    };
+
+    let resolved: Vec<_> = MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .collect();
+    let resolved_by_version: HashMap<i64, &[u8]> = resolved
+        .iter()
+        .map(|m| (m.version, m.checksum.as_ref()))
+        .collect();
+    let applied_versions: HashSet<i64> = rows.iter().map(|r| r.version).collect();
+
+    let mut pending: Vec<PendingMigration> = resolved
+        .iter()
+        .filter(|m| !applied_versions.contains(&m.version))
+        .map(|m| PendingMigration {
+            version: m.version,
+            description: m.description.to_string(),
+        })
+        .collect();
+    pending.sort_by_key(|m| m.version);
+
+    let mut missing_versions = Vec::new();
+    let mut failed_versions = Vec::new();
+    let mut checksum_mismatch_versions = Vec::new();
+    for r in &rows {
+        match resolved_by_version.get(&r.version) {
+            None => missing_versions.push(r.version),
+            Some(sum) if *sum != r.checksum.as_slice() => {
+                checksum_mismatch_versions.push(r.version)
+            }
+            Some(_) => {}
+        }
+        if !r.success {
+            failed_versions.push(r.version);
+        }
+    }
+
+    let applied: Vec<MigrationItem> = rows
+        .into_iter()
+        .map(|r| MigrationItem {
+            version: r.version,
+            description: r.description,
+            installed_on: r.installed_on,
+            success: r.success,
+            execution_time_ms: r.execution_time.map(|v| v / 1_000_000),
+        })
+        .collect();
+
+    let up_to_date = pending.is_empty() && failed_versions.is_empty();
+
+    Ok(MigrationStatus {
+        backend: backend_name().to_string(),
+        up_to_date,
+        resolved_count: resolved.len() as i64,
+        latest_applied_version: applied.last().map(|m| m.version),
+        latest_available_version: resolved.iter().map(|m| m.version).max(),
+        applied,
+        pending,
+        missing_versions,
+        failed_versions,
+        checksum_mismatch_versions,
+    })
+}
+
+/// Statements that bring a pre-migration SQLite database up to the baseline.
+/// Errors are ignored: each one is a no-op when the column already exists.
+#[cfg(feature = "sqlite")]
+const LEGACY_SQLITE_UPGRADES: &[&str] = &[
+    "ALTER TABLE tenants ADD COLUMN storage_usage INTEGER NOT NULL DEFAULT 0",
+    "ALTER TABLE tenants ADD COLUMN enforce_2fa INTEGER NOT NULL DEFAULT 0",
+    "ALTER TABLE roles ADD COLUMN level INTEGER NOT NULL DEFAULT 0",
+    "ALTER TABLE file_records ADD COLUMN storage_provider TEXT NOT NULL DEFAULT 'local'",
+    "ALTER TABLE invoices ADD COLUMN merchant_id TEXT REFERENCES tenants(id) ON DELETE CASCADE",
+    "ALTER TABLE invoices ADD COLUMN currency_code TEXT NOT NULL DEFAULT 'IDR'",
+    "ALTER TABLE invoices ADD COLUMN base_currency_code TEXT NOT NULL DEFAULT 'IDR'",
+    "ALTER TABLE invoices ADD COLUMN fx_rate REAL",
+    "ALTER TABLE invoices ADD COLUMN fx_source TEXT",
+    "ALTER TABLE invoices ADD COLUMN fx_fetched_at TEXT",
+    "ALTER TABLE invoices ADD COLUMN proof_attachment TEXT",
+    "ALTER TABLE invoices ADD COLUMN rejection_reason TEXT",
+    "ALTER TABLE notifications ADD COLUMN notification_type TEXT DEFAULT 'info'",
+    "ALTER TABLE notifications ADD COLUMN category TEXT DEFAULT 'system'",
+    "ALTER TABLE notifications ADD COLUMN action_url TEXT",
+    "ALTER TABLE users ADD COLUMN two_factor_enabled INTEGER NOT NULL DEFAULT 0",
+    "ALTER TABLE users ADD COLUMN two_factor_secret TEXT",
+    "ALTER TABLE users ADD COLUMN two_factor_recovery_codes TEXT",
+    "ALTER TABLE users ADD COLUMN email_otp_code TEXT",
+    "ALTER TABLE users ADD COLUMN email_otp_expires TEXT",
+    "ALTER TABLE users ADD COLUMN preferred_2fa_method TEXT DEFAULT 'totp'",
+    "ALTER TABLE users ADD COLUMN totp_enabled INTEGER NOT NULL DEFAULT 0",
+    "ALTER TABLE users ADD COLUMN email_2fa_enabled INTEGER NOT NULL DEFAULT 0",
+    "UPDATE users SET totp_enabled = 1 WHERE two_factor_secret IS NOT NULL AND totp_enabled = 0",
+    "UPDATE users SET email_2fa_enabled = 1 WHERE two_factor_enabled = 1 AND preferred_2fa_method = 'email' AND email_2fa_enabled = 0",
+];
+
+/// SQLite databases created before versioned migrations have tables but no
+/// `_sqlx_migrations` ledger. Patch their columns so the baseline (which uses
+/// `CREATE TABLE IF NOT EXISTS`) leaves them in the expected shape.
+#[cfg(feature = "sqlite")]
+async fn bootstrap_legacy_sqlite(pool: &DbPool) -> Result<(), sqlx::Error> {
+    let table_exists = |name: &'static str| async move {
+        sqlx::query_scalar::<_, String>(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?",
+        )
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+        .map(|r| r.is_some())
+    };
+
+    if table_exists("_sqlx_migrations").await? || !table_exists("users").await? {
+        return Ok(());
+    }
+
+    info!("Legacy SQLite schema detected; upgrading before versioned migrations");
+    for stmt in LEGACY_SQLITE_UPGRADES {
+        let _ = sqlx::query(stmt).execute(pool).await;
+    }
+    Ok(())
+}
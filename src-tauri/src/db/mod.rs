@@ -1,8 +1,15 @@
 //! Database module
 
 pub mod connection;
+#[cfg(feature = "postgres")]
+pub mod demo;
 pub mod factory;
+pub mod migrations;
+#[cfg(feature = "postgres")]
+pub mod partitions;
+pub mod router;
 pub mod seed;
 pub use connection::*;
 pub use factory::*;
+pub use router::QueryRouter;
 pub use seed::*;
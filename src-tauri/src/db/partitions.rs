@@ -0,0 +1,133 @@
+//! Monthly range partitions for time-series tables (Postgres only).
+//!
+//! `audit_logs`, `mikrotik_router_metrics`, `mikrotik_interface_metrics`,
+//! `mikrotik_logs` and `usage_records` are partitioned by UTC month. Children are named
+//! `<table>_pYYYYMM` and a `<table>_default` child catches anything outside the
+//! pre-created range. Retention drops whole children, and only deletes rows in
+//! the month the cutoff falls in.
+
+use crate::db::DbPool;
+use chrono::{DateTime, Months, NaiveDate, Utc};
+
+/// Partitioned tables and their partition key column.
+pub const PARTITIONED_TABLES: &[(&str, &str)] = &[
+    ("audit_logs", "created_at"),
+    ("mikrotik_router_metrics", "ts"),
+    ("mikrotik_interface_metrics", "ts"),
+    ("mikrotik_logs", "logged_at"),
+    ("usage_records", "ts"),
+];
+
+fn key_column(table: &str) -> Option<&'static str> {
+    PARTITIONED_TABLES
+        .iter()
+        .find(|(t, _)| *t == table)
+        .map(|(_, column)| *column)
+}
+
+/// Create any missing partitions for the current month and the next
+/// `months_ahead` months. Returns how many were created.
+pub async fn ensure_upcoming(pool: &DbPool, months_ahead: i32) -> Result<i32, sqlx::Error> {
+    let mut created = 0;
+    for (table, column) in PARTITIONED_TABLES {
+        let n: i32 =
+            sqlx::query_scalar("SELECT public.ensure_monthly_partitions($1, $2, now(), $3)")
+                .bind(table)
+                .bind(column)
+                .bind(months_ahead)
+                .fetch_one(pool)
+                .await?;
+        created += n;
+    }
+    Ok(created)
+}
+
+/// First day of the month covered by a `<table>_pYYYYMM` partition.
+pub fn partition_month(table: &str, partition: &str) -> Option<NaiveDate> {
+    let suffix = partition.strip_prefix(table)?.strip_prefix("_p")?;
+    if suffix.len() != 6 || !suffix.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let year: i32 = suffix[..4].parse().ok()?;
+    let month: u32 = suffix[4..].parse().ok()?;
+    NaiveDate::from_ymd_opt(year, month, 1)
+}
+
+/// Drop every partition of `table` whose month ends on or before `cutoff`, then
+/// delete the expired rows left in the month the cutoff falls in and in the
+/// default partition. Returns the number of partitions dropped.
+pub async fn drop_expired(
+    pool: &DbPool,
+    table: &str,
+    cutoff: DateTime<Utc>,
+) -> Result<u32, sqlx::Error> {
+    let Some(column) = key_column(table) else {
+        return Err(sqlx::Error::Protocol(format!(
+            "{} is not a partitioned table",
+            table
+        )));
+    };
+
+    let children: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT c.relname::text
+        FROM pg_inherits i
+        JOIN pg_class c ON c.oid = i.inhrelid
+        JOIN pg_class p ON p.oid = i.inhparent
+        JOIN pg_namespace n ON n.oid = p.relnamespace
+        WHERE n.nspname = 'public' AND p.relname = $1
+        "#,
+    )
+    .bind(table)
+    .fetch_all(pool)
+    .await?;
+
+    let cutoff_day = cutoff.date_naive();
+    let mut dropped = 0u32;
+    for child in children {
+        let Some(month) = partition_month(table, &child) else {
+            continue;
+        };
+        let Some(month_end) = month.checked_add_months(Months::new(1)) else {
+            continue;
+        };
+        if month_end <= cutoff_day {
+            sqlx::query(&format!("DROP TABLE IF EXISTS public.{}", child))
+                .execute(pool)
+                .await?;
+            dropped += 1;
+        }
+    }
+
+    // Partition pruning limits this to the cutoff's month and the default
+    // partition; everything older is gone already.
+    sqlx::query(&format!(
+        "DELETE FROM public.{} WHERE {} < $1",
+        table, column
+    ))
+    .bind(cutoff)
+    .execute(pool)
+    .await?;
+
+    Ok(dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_month() {
+        assert_eq!(
+            partition_month("audit_logs", "audit_logs_p202603"),
+            NaiveDate::from_ymd_opt(2026, 3, 1)
+        );
+        assert_eq!(
+            partition_month("mikrotik_logs", "mikrotik_logs_p202512"),
+            NaiveDate::from_ymd_opt(2025, 12, 1)
+        );
+        assert_eq!(partition_month("audit_logs", "audit_logs_default"), None);
+        assert_eq!(partition_month("audit_logs", "audit_logs_p202613"), None);
+        assert_eq!(partition_month("mikrotik_logs", "audit_logs_p202603"), None);
+    }
+}
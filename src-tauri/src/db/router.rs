@@ -0,0 +1,34 @@
+//! Read/write query routing.
+//!
+//! Writes always go to the primary. Heavy, lag-tolerant reads (reporting, audit
+//! lists, metrics history, NOC views) can be sent to an optional read replica
+//! configured with `DATABASE_REPLICA_URL`. Without a replica every query uses
+//! the primary.
+
+use crate::db::DbPool;
+
+#[derive(Clone)]
+pub struct QueryRouter {
+    primary: DbPool,
+    replica: Option<DbPool>,
+}
+
+impl QueryRouter {
+    pub fn new(primary: DbPool, replica: Option<DbPool>) -> Self {
+        Self { primary, replica }
+    }
+
+    /// Pool for writes and read-your-writes queries.
+    pub fn primary(&self) -> &DbPool {
+        &self.primary
+    }
+
+    /// Pool for read-only queries that tolerate replication lag.
+    pub fn reader(&self) -> &DbPool {
+        self.replica.as_ref().unwrap_or(&self.primary)
+    }
+
+    pub fn has_replica(&self) -> bool {
+        self.replica.is_some()
+    }
+}
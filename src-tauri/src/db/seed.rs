@@ -5,6 +5,8 @@ use anyhow::Result;
 pub enum SeedMode {
     Dev,
     Prod,
+    /// Dev setup plus a generated demo ISP in the seeded tenant (Postgres only).
+    Demo,
 }
 
 #[derive(Debug, Clone)]
@@ -16,6 +18,7 @@ pub struct SeedOptions {
     pub tenant_name: String,
     pub tenant_slug: String,
     pub app_timezone: String,
+    pub demo_customers: usize,
 }
 
 impl Default for SeedOptions {
@@ -29,6 +32,7 @@ impl Default for SeedOptions {
             tenant_slug: slugify(&tenant_name),
             tenant_name,
             app_timezone: "Asia/Jakarta".to_string(),
+            demo_customers: 300,
         }
     }
 }
@@ -80,5 +84,35 @@ pub async fn run_seed(pool: &DbPool, opts: SeedOptions) -> Result<()> {
         .await?;
     f.ensure_tenant_subscription_default(&tenant_id).await?;
 
+    if opts.mode == SeedMode::Demo {
+        seed_demo_tenant(pool, &tenant_id, opts.demo_customers).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "postgres")]
+async fn seed_demo_tenant(pool: &DbPool, tenant_id: &str, customers: usize) -> Result<()> {
+    use crate::db::demo::{seed_demo, DemoOptions};
+
+    let opts = DemoOptions {
+        customers,
+        ..DemoOptions::default()
+    };
+    let summary = seed_demo(pool, tenant_id, &opts).await?;
+    tracing::info!(
+        "Demo tenant seeded: {} routers, {} packages, {} customers, {} subscriptions, {} invoices, {} incidents",
+        summary.routers,
+        summary.packages,
+        summary.customers,
+        summary.subscriptions,
+        summary.invoices,
+        summary.incidents
+    );
     Ok(())
 }
+
+#[cfg(feature = "sqlite")]
+async fn seed_demo_tenant(_pool: &DbPool, _tenant_id: &str, _customers: usize) -> Result<()> {
+    anyhow::bail!("demo data needs the ISP tables, which only exist in Postgres builds")
+}
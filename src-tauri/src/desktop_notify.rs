@@ -0,0 +1,224 @@
+//! Native desktop notifications.
+//!
+//! Notifications addressed to the user signed in on this desktop are shown
+//! as OS toasts whenever the window is hidden, minimized or not focused, so
+//! new incidents, received payments and work order assignments get noticed
+//! while the app runs in the tray. Each category can be turned off; the
+//! choice is a per-device preference saved in the app data dir.
+
+use crate::http::WsEvent;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::broadcast;
+
+const PREFS_FILE: &str = "desktop_notifications.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DesktopCategory {
+    Incidents,
+    Payments,
+    WorkOrders,
+    Other,
+}
+
+/// Maps a notification to its desktop category. Network notifications are
+/// incidents and router alerts; successful billing ones are payments; work
+/// order notifications link to the installations board.
+pub fn classify(
+    category: &str,
+    notification_type: &str,
+    action_url: Option<&str>,
+) -> DesktopCategory {
+    match category {
+        "network" => DesktopCategory::Incidents,
+        "billing" if notification_type == "success" => DesktopCategory::Payments,
+        "operations"
+            if action_url.is_some_and(|url| url.starts_with("/admin/network/installations")) =>
+        {
+            DesktopCategory::WorkOrders
+        }
+        _ => DesktopCategory::Other,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DesktopNotificationPrefs {
+    pub enabled: bool,
+    pub incidents: bool,
+    pub payments: bool,
+    pub work_orders: bool,
+    pub other: bool,
+}
+
+impl Default for DesktopNotificationPrefs {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            incidents: true,
+            payments: true,
+            work_orders: true,
+            other: true,
+        }
+    }
+}
+
+impl DesktopNotificationPrefs {
+    pub fn allows(&self, category: DesktopCategory) -> bool {
+        self.enabled
+            && match category {
+                DesktopCategory::Incidents => self.incidents,
+                DesktopCategory::Payments => self.payments,
+                DesktopCategory::WorkOrders => self.work_orders,
+                DesktopCategory::Other => self.other,
+            }
+    }
+}
+
+pub struct DesktopNotifier {
+    /// The user signed in on this desktop; nothing is shown while signed out.
+    user_id: RwLock<Option<String>>,
+    prefs: RwLock<DesktopNotificationPrefs>,
+    file: PathBuf,
+}
+
+impl DesktopNotifier {
+    pub fn load(app_data_dir: &Path) -> Self {
+        let file = app_data_dir.join(PREFS_FILE);
+        let prefs = std::fs::read_to_string(&file)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Self {
+            user_id: RwLock::new(None),
+            prefs: RwLock::new(prefs),
+            file,
+        }
+    }
+
+    pub fn prefs(&self) -> DesktopNotificationPrefs {
+        self.prefs.read().unwrap().clone()
+    }
+
+    pub fn set_prefs(&self, prefs: DesktopNotificationPrefs) {
+        if let Err(e) = serde_json::to_string(&prefs)
+            .map_err(std::io::Error::other)
+            .and_then(|raw| std::fs::write(&self.file, raw))
+        {
+            tracing::warn!("Failed to save {:?}: {}", self.file, e);
+        }
+        *self.prefs.write().unwrap() = prefs;
+    }
+
+    pub fn set_user(&self, user_id: Option<String>) {
+        *self.user_id.write().unwrap() = user_id;
+    }
+
+    fn wants(&self, user_id: &str, category: DesktopCategory) -> bool {
+        self.user_id.read().unwrap().as_deref() == Some(user_id)
+            && self.prefs.read().unwrap().allows(category)
+    }
+}
+
+/// The window shows in-app toasts itself while the user is looking at it.
+fn window_in_use(app: &AppHandle) -> bool {
+    app.get_webview_window("main").is_some_and(|w| {
+        w.is_visible().unwrap_or(false)
+            && !w.is_minimized().unwrap_or(false)
+            && w.is_focused().unwrap_or(false)
+    })
+}
+
+/// Shows hub notifications for the signed-in user until the hub closes.
+pub fn spawn(app: AppHandle, mut events: broadcast::Receiver<WsEvent>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (user_id, title, message, category) = match events.recv().await {
+                Ok(WsEvent::NotificationReceived {
+                    user_id,
+                    title,
+                    message,
+                    notification_type,
+                    category,
+                    action_url,
+                    ..
+                }) => {
+                    let category = classify(&category, &notification_type, action_url.as_deref());
+                    (user_id, title, message, category)
+                }
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Desktop notifications skipped {} events", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            let Some(notifier) = app.try_state::<DesktopNotifier>() else {
+                continue;
+            };
+            if !notifier.wants(&user_id, category) || window_in_use(&app) {
+                continue;
+            }
+            if let Err(e) = app
+                .notification()
+                .builder()
+                .title(title)
+                .body(message)
+                .show()
+            {
+                tracing::warn!("Failed to show desktop notification: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify, DesktopCategory, DesktopNotificationPrefs};
+
+    #[test]
+    fn notifications_map_to_desktop_categories() {
+        let url = Some("/admin/network/incidents?incident=1");
+        assert_eq!(
+            classify("network", "warning", url),
+            DesktopCategory::Incidents
+        );
+        assert_eq!(
+            classify("billing", "success", Some("/admin/invoices")),
+            DesktopCategory::Payments
+        );
+        assert_eq!(
+            classify("billing", "warning", Some("/dashboard/invoices")),
+            DesktopCategory::Other
+        );
+        assert_eq!(
+            classify("operations", "info", Some("/admin/network/installations")),
+            DesktopCategory::WorkOrders
+        );
+        assert_eq!(
+            classify("operations", "warning", Some("/admin/inventory")),
+            DesktopCategory::Other
+        );
+        assert_eq!(classify("system", "info", None), DesktopCategory::Other);
+    }
+
+    #[test]
+    fn prefs_opt_out_per_category() {
+        let mut prefs = DesktopNotificationPrefs::default();
+        assert!(prefs.allows(DesktopCategory::Payments));
+        prefs.payments = false;
+        assert!(!prefs.allows(DesktopCategory::Payments));
+        assert!(prefs.allows(DesktopCategory::Incidents));
+        prefs.enabled = false;
+        assert!(!prefs.allows(DesktopCategory::Incidents));
+
+        // Files from older versions miss the newer keys.
+        let saved: DesktopNotificationPrefs =
+            serde_json::from_str(r#"{"payments":false}"#).unwrap();
+        assert!(saved.enabled && saved.work_orders && !saved.payments);
+    }
+}
@@ -0,0 +1,250 @@
+//! Diagnostics bundle for support requests.
+//!
+//! The most recent log lines are kept in memory so they can be exported
+//! together with the app version, the global settings (credentials
+//! redacted), a database check and the scheduler state as one ZIP.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::Setting;
+use crate::services::settings_service::is_sensitive_setting_key;
+use crate::services::{DbMaintenanceService, JobQueue, SettingsService, SystemService};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use sysinfo::System;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Log lines kept for the bundle.
+const MAX_LOG_LINES: usize = 5000;
+const REDACTED: &str = "[redacted]";
+
+/// Ring buffer of formatted log lines, written by a `tracing` fmt layer.
+#[derive(Clone, Default)]
+pub struct RecentLogs {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl RecentLogs {
+    fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        if lines.len() == MAX_LOG_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    pub fn text(&self) -> String {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        let mut text = String::new();
+        for line in lines.iter() {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Collects the output of one event and stores it once the event is done.
+pub struct LogLineWriter {
+    logs: RecentLogs,
+    buf: Vec<u8>,
+}
+
+impl Write for LogLineWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogLineWriter {
+    fn drop(&mut self) {
+        let line = String::from_utf8_lossy(&self.buf);
+        let line = line.trim_end();
+        if !line.is_empty() {
+            self.logs.push(line.to_string());
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for RecentLogs {
+    type Writer = LogLineWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogLineWriter {
+            logs: self.clone(),
+            buf: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiagnosticsBundleSummary {
+    pub filename: String,
+    pub path: String,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct DatabaseCheck {
+    connected: bool,
+    latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+async fn check_database(pool: &DbPool) -> DatabaseCheck {
+    let started = Instant::now();
+    let result = sqlx::query_scalar::<_, i32>("SELECT 1")
+        .fetch_one(pool)
+        .await;
+    DatabaseCheck {
+        connected: result.is_ok(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: result.err().map(|e| e.to_string()),
+    }
+}
+
+/// Settings as `key → value`. API keys are redacted on top of the keys the
+/// audit log already treats as credentials.
+fn redact_settings(settings: Vec<Setting>) -> Value {
+    settings
+        .into_iter()
+        .map(|s| {
+            let secret = is_sensitive_setting_key(&s.key) || s.key.trim().ends_with("_key");
+            let value = if secret && !s.value.is_empty() {
+                REDACTED.to_string()
+            } else {
+                s.value
+            };
+            (s.key, Value::String(value))
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// A section that could not be collected records why instead of failing the bundle.
+fn section<T: Serialize, E: std::fmt::Display>(result: Result<T, E>) -> Value {
+    match result {
+        Ok(value) => serde_json::to_value(value).unwrap_or(Value::Null),
+        Err(e) => json!({ "error": e.to_string() }),
+    }
+}
+
+/// Writes `diagnostics_<timestamp>.zip` into `dest_dir`.
+pub async fn export_bundle(
+    dest_dir: &Path,
+    logs: &RecentLogs,
+    system_service: &SystemService,
+    settings_service: &SettingsService,
+    db_maintenance_service: &DbMaintenanceService,
+    job_queue: &JobQueue,
+) -> AppResult<DiagnosticsBundleSummary> {
+    let now = Utc::now();
+    let app = json!({
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "os_version": System::long_os_version(),
+        "generated_at": now,
+    });
+    let database = check_database(&system_service.pool).await;
+    let system = match system_service
+        .get_system_diagnostics(settings_service)
+        .await
+    {
+        Ok(mut diag) => {
+            diag.maintenance = Some(db_maintenance_service.snapshot().await);
+            serde_json::to_value(diag).unwrap_or(Value::Null)
+        }
+        Err(e) => json!({ "error": e.to_string() }),
+    };
+    let schedulers = json!({
+        "recurring": section(job_queue.recurring().await),
+        "stats": section(job_queue.stats().await),
+    });
+    let settings = section(settings_service.get_all(None).await.map(redact_settings));
+
+    let files = [
+        ("app.json", serde_json::to_string_pretty(&app)),
+        ("database.json", serde_json::to_string_pretty(&database)),
+        ("system.json", serde_json::to_string_pretty(&system)),
+        ("schedulers.json", serde_json::to_string_pretty(&schedulers)),
+        ("settings.json", serde_json::to_string_pretty(&settings)),
+    ];
+
+    std::fs::create_dir_all(dest_dir).map_err(|e| AppError::Internal(e.to_string()))?;
+    let filename = format!("diagnostics_{}.zip", now.format("%Y%m%d_%H%M%S"));
+    let path = dest_dir.join(&filename);
+    let file = std::fs::File::create(&path).map_err(|e| AppError::Internal(e.to_string()))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let zip_err = |e: zip::result::ZipError| AppError::Internal(e.to_string());
+    let io_err = |e: std::io::Error| AppError::Internal(e.to_string());
+    for (name, content) in files {
+        let content = content.map_err(|e| AppError::Internal(e.to_string()))?;
+        zip.start_file(name, options).map_err(zip_err)?;
+        zip.write_all(content.as_bytes()).map_err(io_err)?;
+    }
+    zip.start_file("logs.txt", options).map_err(zip_err)?;
+    zip.write_all(logs.text().as_bytes()).map_err(io_err)?;
+    zip.finish().map_err(zip_err)?;
+
+    let size_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    Ok(DiagnosticsBundleSummary {
+        filename,
+        path: path.to_string_lossy().to_string(),
+        size_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{redact_settings, RecentLogs, MAX_LOG_LINES};
+    use crate::models::Setting;
+    use std::io::Write;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    #[test]
+    fn recent_logs_keep_the_newest_lines() {
+        let logs = RecentLogs::default();
+        for i in 0..MAX_LOG_LINES + 2 {
+            let mut writer = logs.make_writer();
+            writeln!(writer, "line {}", i).unwrap();
+        }
+        let text = logs.text();
+        assert!(!text.contains("line 1\n"));
+        assert!(text.starts_with("line 2\n"));
+        assert!(text.ends_with(&format!("line {}\n", MAX_LOG_LINES + 1)));
+    }
+
+    #[test]
+    fn settings_are_redacted() {
+        let setting = |key: &str, value: &str| Setting::new(None, key.into(), value.into(), None);
+        let redacted = redact_settings(vec![
+            setting("app_name", "ISP"),
+            setting("email_smtp_password", "hunter2"),
+            setting("email_api_key", "re_123"),
+            setting("telegram_bot_token", "123:abc"),
+            setting("payment_stripe_secret_key", ""),
+        ]);
+        assert_eq!(redacted["app_name"], "ISP");
+        assert_eq!(redacted["email_smtp_password"], "[redacted]");
+        assert_eq!(redacted["email_api_key"], "[redacted]");
+        assert_eq!(redacted["telegram_bot_token"], "[redacted]");
+        // An empty value shows the setting is simply not configured.
+        assert_eq!(redacted["payment_stripe_secret_key"], "");
+    }
+}
@@ -56,6 +56,9 @@ pub enum AppError {
 
     #[error("Conflict: {0}")]
     Conflict(String),
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
 }
 
 impl serde::Serialize for AppError {
@@ -1,8 +1,12 @@
 use super::AppState;
 use crate::models::{
-    Announcement, CreateAnnouncementDto, PaginatedResponse, UpdateAnnouncementDto,
+    Announcement, AnnouncementStats, CreateAnnouncementDto, PaginatedResponse,
+    UpdateAnnouncementDto,
+};
+use crate::services::announcement_translations::{self, RecipientTexts};
+use crate::services::{
+    announcement_audience, announcement_recurrence, announcement_stats, encode_unsubscribe_token,
 };
-use crate::services::encode_unsubscribe_token;
 use axum::{
     extract::{Path, Query, State},
     http::HeaderMap,
@@ -37,6 +41,7 @@ fn ann_snapshot_json(ann: &Announcement) -> serde_json::Value {
         "title": ann.title,
         "severity": ann.severity,
         "audience": ann.audience,
+        "audience_targets": ann.audience_targets,
         "mode": ann.mode,
         "format": ann.format,
         "deliver_in_app": ann.deliver_in_app,
@@ -45,6 +50,8 @@ fn ann_snapshot_json(ann: &Announcement) -> serde_json::Value {
         "starts_at": ann.starts_at.to_rfc3339(),
         "ends_at": ann.ends_at.map(|d| d.to_rfc3339()),
         "notified_at": ann.notified_at.map(|d| d.to_rfc3339()),
+        "recurrence": ann.recurrence,
+        "next_occurrence_at": ann.next_occurrence_at.map(|d| d.to_rfc3339()),
         "created_at": ann.created_at.to_rfc3339(),
         "updated_at": ann.updated_at.to_rfc3339(),
     })
@@ -67,6 +74,12 @@ fn ann_changed_fields(before: &Announcement, after: &Announcement) -> Vec<&'stat
     if before.audience != after.audience {
         out.push("audience");
     }
+    if before.audience_targets != after.audience_targets {
+        out.push("audience_targets");
+    }
+    if before.translations != after.translations {
+        out.push("translations");
+    }
     if before.mode != after.mode {
         out.push("mode");
     }
@@ -88,6 +101,9 @@ fn ann_changed_fields(before: &Announcement, after: &Announcement) -> Vec<&'stat
     if before.ends_at != after.ends_at {
         out.push("ends_at");
     }
+    if before.recurrence != after.recurrence {
+        out.push("recurrence");
+    }
     out
 }
 
@@ -100,7 +116,7 @@ fn norm_severity(s: Option<String>) -> String {
 
 fn norm_audience(a: Option<String>) -> String {
     match a.as_deref() {
-        Some("all") | Some("admins") => a.unwrap(),
+        Some("all") | Some("admins") | Some("segment") => a.unwrap(),
         _ => "all".to_string(),
     }
 }
@@ -184,6 +200,12 @@ pub struct ListRecentParams {
     pub mode: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct StatsParams {
+    /// Defaults to the current occurrence.
+    pub occurrence: Option<i32>,
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/active", get(list_active))
@@ -195,6 +217,7 @@ pub fn router() -> Router<AppState> {
             "/admin/{id}",
             put(update_announcement).delete(delete_announcement),
         )
+        .route("/admin/{id}/stats", get(get_stats))
 }
 
 pub async fn get_one(
@@ -229,7 +252,7 @@ pub async fn get_one(
     let now = Utc::now();
 
     #[cfg(feature = "postgres")]
-    let row: Announcement = if can_manage {
+    let mut row: Announcement = if can_manage {
         sqlx::query_as(
             r#"
             SELECT *
@@ -252,9 +275,17 @@ pub async fn get_one(
               AND ($2::text IS NULL OR tenant_id IS NULL OR tenant_id = $2)
               AND starts_at <= $3
               AND (ends_at IS NULL OR ends_at > $3 OR notified_at IS NOT NULL)
+              AND (recurrence IS NULL OR occurrence > 0)
               AND (
                 audience = 'all'
                 OR (audience = 'admins' AND $4 = true)
+                OR (
+                  audience = 'segment'
+                  AND EXISTS (
+                    SELECT 1 FROM announcement_recipients r
+                    WHERE r.announcement_id = announcements.id AND r.user_id = $5
+                  )
+                )
               )
         "#,
         )
@@ -262,10 +293,31 @@ pub async fn get_one(
         .bind(tenant_id.as_deref())
         .bind(now)
         .bind(is_admin)
+        .bind(&user_id)
         .fetch_one(&state.auth_service.pool)
         .await?
     };
 
+    // Managers open drafts and scheduled posts too; only reader fetches count.
+    #[cfg(feature = "postgres")]
+    if !can_manage {
+        announcement_stats::record_views(
+            &state.auth_service.pool,
+            &user_id,
+            std::slice::from_ref(&row),
+        )
+        .await;
+    }
+
+    #[cfg(feature = "postgres")]
+    announcement_translations::localize_for_reader(
+        &state.auth_service.pool,
+        &user_id,
+        tenant_id.as_deref(),
+        std::slice::from_mut(&mut row),
+    )
+    .await;
+
     #[cfg(not(feature = "postgres"))]
     let row: Announcement = Announcement {
         id,
@@ -276,6 +328,8 @@ pub async fn get_one(
         body: "".into(),
         severity: "info".into(),
         audience: "all".into(),
+        audience_targets: None,
+        translations: None,
         mode: "post".into(),
         format: "plain".into(),
         deliver_in_app: true,
@@ -284,6 +338,10 @@ pub async fn get_one(
         starts_at: now,
         ends_at: None,
         notified_at: None,
+        recurrence: None,
+        occurrence: 0,
+        next_occurrence_at: None,
+        occurrence_ends_at: None,
         created_at: now,
         updated_at: now,
     };
@@ -312,20 +370,31 @@ pub async fn list_active(
     let now = Utc::now();
 
     #[cfg(feature = "postgres")]
-    let rows: Vec<Announcement> = sqlx::query_as(
+    let mut rows: Vec<Announcement> = sqlx::query_as(
         r#"
         SELECT a.*
         FROM announcements a
         LEFT JOIN announcement_dismissals d
-          ON d.announcement_id = a.id AND d.user_id = $1
+          ON d.announcement_id = a.id AND d.user_id = $1 AND d.occurrence = a.occurrence
         WHERE d.id IS NULL
           AND ($2::text IS NULL OR a.tenant_id IS NULL OR a.tenant_id = $2)
           AND a.deliver_in_app = true
           AND a.starts_at <= $3
           AND (a.ends_at IS NULL OR a.ends_at > $3)
+          AND (
+            a.recurrence IS NULL
+            OR (a.occurrence > 0 AND (a.occurrence_ends_at IS NULL OR a.occurrence_ends_at > $3))
+          )
           AND (
             a.audience = 'all'
             OR (a.audience = 'admins' AND $4 = true)
+            OR (
+              a.audience = 'segment'
+              AND EXISTS (
+                SELECT 1 FROM announcement_recipients r
+                WHERE r.announcement_id = a.id AND r.user_id = $1
+              )
+            )
           )
         ORDER BY a.starts_at DESC
         LIMIT 5
@@ -338,6 +407,18 @@ pub async fn list_active(
     .fetch_all(&state.auth_service.pool)
     .await?;
 
+    #[cfg(feature = "postgres")]
+    announcement_stats::record_views(&state.auth_service.pool, &user_id, &rows).await;
+
+    #[cfg(feature = "postgres")]
+    announcement_translations::localize_for_reader(
+        &state.auth_service.pool,
+        &user_id,
+        tenant_id.as_deref(),
+        &mut rows,
+    )
+    .await;
+
     #[cfg(not(feature = "postgres"))]
     let rows: Vec<Announcement> = Vec::new();
 
@@ -392,17 +473,19 @@ pub async fn list_recent(
 
         let mut qb_count: QueryBuilder<Postgres> = QueryBuilder::new(
             "SELECT COUNT(*) FROM announcements a \
-             LEFT JOIN announcement_dismissals d ON d.announcement_id = a.id AND d.user_id = ",
+             LEFT JOIN announcement_dismissals d \
+             ON d.announcement_id = a.id AND d.occurrence = a.occurrence AND d.user_id = ",
         );
         qb_count.push_bind(&user_id);
-        qb_count.push(" WHERE d.id IS NULL");
+        qb_count.push(" WHERE d.id IS NULL AND (a.recurrence IS NULL OR a.occurrence > 0)");
 
         let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(
             "SELECT a.* FROM announcements a \
-             LEFT JOIN announcement_dismissals d ON d.announcement_id = a.id AND d.user_id = ",
+             LEFT JOIN announcement_dismissals d \
+             ON d.announcement_id = a.id AND d.occurrence = a.occurrence AND d.user_id = ",
         );
         qb.push_bind(&user_id);
-        qb.push(" WHERE d.id IS NULL");
+        qb.push(" WHERE d.id IS NULL AND (a.recurrence IS NULL OR a.occurrence > 0)");
 
         // Tenant scoping: tenant users see global + their tenant. No tenant context sees global only.
         if let Some(tid) = tenant_id.as_deref() {
@@ -422,13 +505,21 @@ pub async fn list_recent(
         qb_count.push_bind(now);
         qb_count.push(" AND (a.audience = 'all' OR (a.audience = 'admins' AND ");
         qb_count.push_bind(is_admin);
-        qb_count.push(" = true))");
+        qb_count.push(
+            " = true) OR (a.audience = 'segment' AND EXISTS (SELECT 1 FROM announcement_recipients r WHERE r.announcement_id = a.id AND r.user_id = ",
+        );
+        qb_count.push_bind(&user_id);
+        qb_count.push(")))");
 
         qb.push(" AND a.deliver_in_app = true AND a.starts_at <= ");
         qb.push_bind(now);
         qb.push(" AND (a.audience = 'all' OR (a.audience = 'admins' AND ");
         qb.push_bind(is_admin);
-        qb.push(" = true))");
+        qb.push(
+            " = true) OR (a.audience = 'segment' AND EXISTS (SELECT 1 FROM announcement_recipients r WHERE r.announcement_id = a.id AND r.user_id = ",
+        );
+        qb.push_bind(&user_id);
+        qb.push(")))");
 
         if let Some(sev) = severity.as_deref() {
             qb_count.push(" AND a.severity = ");
@@ -470,10 +561,17 @@ pub async fn list_recent(
         qb.push(" OFFSET ");
         qb.push_bind(offset);
 
-        let rows: Vec<Announcement> = qb
+        let mut rows: Vec<Announcement> = qb
             .build_query_as()
             .fetch_all(&state.auth_service.pool)
             .await?;
+        announcement_translations::localize_for_reader(
+            &state.auth_service.pool,
+            &user_id,
+            tenant_id.as_deref(),
+            &mut rows,
+        )
+        .await;
         (rows, total)
     };
 
@@ -504,9 +602,9 @@ pub async fn dismiss(
     {
         let _ = sqlx::query(
             r#"
-            INSERT INTO announcement_dismissals (id, announcement_id, user_id, dismissed_at)
-            VALUES ($1,$2,$3,$4)
-            ON CONFLICT (user_id, announcement_id) DO NOTHING
+            INSERT INTO announcement_dismissals (id, announcement_id, user_id, dismissed_at, occurrence)
+            VALUES ($1,$2,$3,$4,(SELECT occurrence FROM announcements WHERE id = $2))
+            ON CONFLICT (user_id, announcement_id, occurrence) DO NOTHING
         "#,
         )
         .bind(&did)
@@ -696,7 +794,12 @@ async fn send_announcement_notifications(
 
     #[cfg(feature = "postgres")]
     {
-        if let Some(tid) = announcement.tenant_id.as_deref() {
+        if announcement.audience == "segment" {
+            recipients.extend(
+                announcement_audience::resolve_and_record(&state.auth_service.pool, announcement)
+                    .await?,
+            );
+        } else if let Some(tid) = announcement.tenant_id.as_deref() {
             if announcement.audience == "admins" {
                 recipients.extend(tenant_admin_user_ids(&state.auth_service.pool, tid).await?);
             } else {
@@ -713,27 +816,31 @@ async fn send_announcement_notifications(
         }
     }
 
-    let title = announcement.title.clone();
-    let plain = if announcement.format == "html" {
-        strip_html_tags(&announcement.body)
-    } else {
-        announcement.body.clone()
-    };
-    let msg = if plain.chars().count() > 180 {
-        let short: String = plain.chars().take(180).collect();
-        format!("{}…", short)
-    } else {
-        plain
-    };
+    let recipients: Vec<String> = recipients.into_iter().collect();
+    let texts = RecipientTexts::load(&state.auth_service.pool, announcement, &recipients).await;
+    #[cfg(feature = "postgres")]
+    announcement_stats::record_delivered(&state.auth_service.pool, announcement, &recipients).await;
 
     for uid in recipients {
+        let (title, body) = texts.for_user(&uid);
+        let plain = if announcement.format == "html" {
+            strip_html_tags(body)
+        } else {
+            body.to_string()
+        };
+        let msg = if plain.chars().count() > 180 {
+            let short: String = plain.chars().take(180).collect();
+            format!("{}…", short)
+        } else {
+            plain
+        };
         let _ = state
             .notification_service
             .create_notification(
                 uid,
                 announcement.tenant_id.clone(),
-                title.clone(),
-                msg.clone(),
+                title.to_string(),
+                msg,
                 announcement.severity.clone(),
                 "announcement".to_string(),
                 Some(format!("/announcements/{}", announcement.id)),
@@ -755,7 +862,12 @@ async fn send_announcement_emails(
 
     let mut recipients: HashSet<String> = HashSet::new();
 
-    if let Some(tid) = announcement.tenant_id.as_deref() {
+    if announcement.audience == "segment" {
+        recipients.extend(
+            announcement_audience::resolve_and_record(&state.auth_service.pool, announcement)
+                .await?,
+        );
+    } else if let Some(tid) = announcement.tenant_id.as_deref() {
         if announcement.audience == "admins" {
             recipients.extend(tenant_admin_user_ids(&state.auth_service.pool, tid).await?);
         } else {
@@ -797,8 +909,6 @@ async fn send_announcement_emails(
         return Ok(());
     }
 
-    let subject = format!("[Announcement] {}", announcement.title);
-
     let main_domain: Option<String> = sqlx::query_scalar(
         "SELECT value FROM settings WHERE tenant_id IS NULL AND key = 'app_main_domain' LIMIT 1",
     )
@@ -823,7 +933,14 @@ async fn send_announcement_emails(
             .await
             .unwrap_or_default();
 
+    let user_ids: Vec<String> = users.iter().map(|(id, _)| id.clone()).collect();
+    let texts = RecipientTexts::load(&state.auth_service.pool, announcement, &user_ids).await;
+    announcement_stats::record_delivered(&state.auth_service.pool, announcement, &user_ids).await;
+
     for (user_id, email) in users {
+        let (title, body) = texts.for_user(&user_id);
+        let subject = format!("[Announcement] {}", title);
+
         let open_url = match (main_domain.as_deref(), slug.as_deref()) {
             (Some(domain), Some(sl)) => Some(format!(
                 "https://{}/{}/announcements/{}",
@@ -857,12 +974,12 @@ async fn send_announcement_emails(
 
         let plain_body = {
             let mut b = String::new();
-            b.push_str(&announcement.title);
+            b.push_str(title);
             b.push_str("\n\n");
             if announcement.format == "html" {
-                b.push_str(&strip_html_tags(&announcement.body));
+                b.push_str(&strip_html_tags(body));
             } else {
-                b.push_str(&announcement.body);
+                b.push_str(body);
             }
             if let Some(url) = open_url.as_deref() {
                 b.push_str("\n\nOpen in app:\n");
@@ -879,10 +996,9 @@ async fn send_announcement_emails(
 
         let html_body = {
             let content = if announcement.format == "html" {
-                announcement.body.clone()
+                body.to_string()
             } else {
-                let esc = announcement
-                    .body
+                let esc = body
                     .replace('&', "&amp;")
                     .replace('<', "&lt;")
                     .replace('>', "&gt;");
@@ -913,7 +1029,7 @@ async fn send_announcement_emails(
   </div>
 </body>
 </html>"#,
-                announcement.title, content, open, unsub
+                title, content, open, unsub
             )
         };
 
@@ -980,6 +1096,21 @@ pub async fn create_announcement(
     let id = Uuid::new_v4().to_string();
     let severity = norm_severity(dto.severity);
     let audience = norm_audience(dto.audience);
+    let audience_targets = announcement_audience::prepare_targets(
+        &state.auth_service.pool,
+        target_tenant_id.as_deref(),
+        &audience,
+        dto.audience_targets,
+    )
+    .await?;
+    let translations = announcement_translations::prepare_translations(dto.translations)?;
+    let (recurrence, next_occurrence_at) = announcement_recurrence::prepare_recurrence(
+        &state.auth_service.pool,
+        target_tenant_id.as_deref(),
+        dto.recurrence,
+        starts_at,
+    )
+    .await?;
     let mode = norm_mode(dto.mode);
     let format = norm_format(dto.format);
     let deliver_in_app = dto.deliver_in_app.unwrap_or(true);
@@ -991,9 +1122,9 @@ pub async fn create_announcement(
     let mut ann: Announcement = sqlx::query_as(
         r#"
         INSERT INTO announcements
-          (id, tenant_id, created_by, cover_file_id, title, body, severity, audience, mode, format, deliver_in_app, deliver_email, deliver_email_force, starts_at, ends_at, notified_at, created_at, updated_at)
+          (id, tenant_id, created_by, cover_file_id, title, body, severity, audience, audience_targets, mode, format, deliver_in_app, deliver_email, deliver_email_force, starts_at, ends_at, notified_at, created_at, updated_at, translations, recurrence, next_occurrence_at)
         VALUES
-          ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,NULL,$16,$17)
+          ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,NULL,$17,$18,$19,$20,$21)
         RETURNING *
     "#,
     )
@@ -1005,6 +1136,7 @@ pub async fn create_announcement(
     .bind(dto.body.trim())
     .bind(&severity)
     .bind(&audience)
+    .bind(&audience_targets)
     .bind(&mode)
     .bind(&format)
     .bind(deliver_in_app)
@@ -1014,6 +1146,9 @@ pub async fn create_announcement(
     .bind(ends_at)
     .bind(now)
     .bind(now)
+    .bind(&translations)
+    .bind(&recurrence)
+    .bind(next_occurrence_at)
     .fetch_one(&state.auth_service.pool)
     .await?;
 
@@ -1027,6 +1162,8 @@ pub async fn create_announcement(
         body: dto.body,
         severity,
         audience,
+        audience_targets,
+        translations,
         mode,
         format,
         deliver_in_app,
@@ -1035,12 +1172,17 @@ pub async fn create_announcement(
         starts_at,
         ends_at,
         notified_at: None,
+        recurrence,
+        occurrence: 0,
+        next_occurrence_at,
+        occurrence_ends_at: None,
         created_at: now,
         updated_at: now,
     };
 
     // If active immediately, deliver now and set notified_at.
-    if starts_at <= now
+    if ann.recurrence.is_none()
+        && starts_at <= now
         && ends_at.map(|e| e > now).unwrap_or(true)
         && (deliver_in_app || deliver_email)
     {
@@ -1147,6 +1289,8 @@ pub async fn update_announcement(
         body: "".into(),
         severity: "info".into(),
         audience: "all".into(),
+        audience_targets: None,
+        translations: None,
         mode: "post".into(),
         format: "plain".into(),
         deliver_in_app: true,
@@ -1155,6 +1299,10 @@ pub async fn update_announcement(
         starts_at: Utc::now(),
         ends_at: None,
         notified_at: None,
+        recurrence: None,
+        occurrence: 0,
+        next_occurrence_at: None,
+        occurrence_ends_at: None,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
@@ -1174,6 +1322,18 @@ pub async fn update_announcement(
     } else {
         existing.audience
     };
+    let audience_targets = announcement_audience::prepare_targets(
+        &state.auth_service.pool,
+        before.tenant_id.as_deref(),
+        &audience,
+        dto.audience_targets
+            .or_else(|| Some(announcement_audience::targets_of(&before))),
+    )
+    .await?;
+    let translations = match dto.translations {
+        Some(t) => announcement_translations::prepare_translations(Some(t))?,
+        None => before.translations.clone(),
+    };
     let mode = if dto.mode.is_some() {
         norm_mode(dto.mode)
     } else {
@@ -1198,6 +1358,36 @@ pub async fn update_announcement(
             ));
         }
     }
+    // An edited rule restarts the series from starts_at; an unchanged one only
+    // moves when starts_at does.
+    let (recurrence, next_occurrence_at) = match dto.recurrence {
+        Some(rule) => {
+            announcement_recurrence::prepare_recurrence(
+                &state.auth_service.pool,
+                before.tenant_id.as_deref(),
+                rule,
+                starts_at,
+            )
+            .await?
+        }
+        None => match announcement_recurrence::recurrence_of(&before) {
+            Some(rule) if starts_at != before.starts_at => {
+                announcement_recurrence::prepare_recurrence(
+                    &state.auth_service.pool,
+                    before.tenant_id.as_deref(),
+                    Some(rule),
+                    starts_at,
+                )
+                .await?
+            }
+            _ => (before.recurrence.clone(), before.next_occurrence_at),
+        },
+    };
+    let occurrence_ends_at = if recurrence.is_some() {
+        before.occurrence_ends_at
+    } else {
+        None
+    };
 
     #[cfg(feature = "postgres")]
     let ann: Announcement = sqlx::query_as(
@@ -1215,7 +1405,12 @@ pub async fn update_announcement(
             deliver_email_force = $10,
             starts_at = $11,
             ends_at = $12,
-            updated_at = $13
+            updated_at = $13,
+            audience_targets = $15,
+            translations = $16,
+            recurrence = $17,
+            next_occurrence_at = $18,
+            occurrence_ends_at = $19
         WHERE id = $14
         RETURNING *
     "#,
@@ -1234,6 +1429,11 @@ pub async fn update_announcement(
     .bind(ends_at)
     .bind(now)
     .bind(&id)
+    .bind(&audience_targets)
+    .bind(&translations)
+    .bind(&recurrence)
+    .bind(next_occurrence_at)
+    .bind(occurrence_ends_at)
     .fetch_one(&state.auth_service.pool)
     .await?;
 
@@ -1323,6 +1523,50 @@ pub async fn delete_announcement(
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
+pub async fn get_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(params): Query<StatsParams>,
+) -> Result<Json<AnnouncementStats>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "announcements", "manage")
+        .await?;
+
+    #[cfg(feature = "postgres")]
+    {
+        let ann: Announcement = sqlx::query_as(
+            "SELECT * FROM announcements WHERE id = $1 AND (tenant_id = $2 OR ($3 = true AND tenant_id IS NULL))",
+        )
+        .bind(&id)
+        .bind(&tenant_id)
+        .bind(claims.is_super_admin)
+        .fetch_one(&state.auth_service.pool)
+        .await?;
+
+        let stats =
+            announcement_stats::stats(&state.auth_service.pool, &ann, params.occurrence).await?;
+        Ok(Json(stats))
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    {
+        let _ = (id, params);
+        Err(crate::error::AppError::NotFound(
+            "Not supported".to_string(),
+        ))
+    }
+}
+
 // --- Scheduler support ---
 
 #[cfg(feature = "postgres")]
@@ -1334,6 +1578,7 @@ pub async fn process_due_announcements(state: &AppState) -> Result<(), String> {
         FROM announcements
         WHERE starts_at <= $1
           AND notified_at IS NULL
+          AND recurrence IS NULL
           AND (ends_at IS NULL OR ends_at > $1)
           AND (deliver_in_app = true OR deliver_email = true)
         ORDER BY starts_at ASC
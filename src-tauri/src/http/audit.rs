@@ -1,11 +1,17 @@
+use crate::error::AppError;
 use crate::http::AppState;
-use crate::models::PaginatedResponse;
+use crate::models::{
+    AuditChainReport, AuditExportFormat, AuditFilterPreset, AuditLogExport, PaginatedResponse,
+    SaveAuditFilterPresetDto,
+};
 use axum::{
-    extract::{Query, State},
-    http::HeaderMap,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     Json,
 };
 
+type ApiError = (StatusCode, String);
+
 #[derive(serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct AuditLogQuery {
@@ -26,6 +32,10 @@ pub struct AuditLogQuery {
     // However, safest for query is String and parse manually or use chrono defaults.
     date_to: Option<String>,
     search: Option<String>,
+    actor: Option<String>,
+    ip_address: Option<String>,
+    /// Export only: `csv` (default) or `ndjson`.
+    format: Option<String>,
 }
 
 // Map Query to Filter
@@ -54,18 +64,20 @@ impl From<AuditLogQuery> for crate::models::AuditLogFilter {
             date_from,
             date_to,
             search: val.search,
+            actor: val.actor,
+            ip_address: val.ip_address,
         }
     }
 }
 
-fn extract_token(headers: &HeaderMap) -> Result<String, (axum::http::StatusCode, String)> {
+fn extract_token(headers: &HeaderMap) -> Result<String, ApiError> {
     headers
         .get("Authorization")
         .and_then(|h| h.to_str().ok())
         .and_then(|h| h.strip_prefix("Bearer "))
         .map(|s| s.to_string())
         .ok_or((
-            axum::http::StatusCode::UNAUTHORIZED,
+            StatusCode::UNAUTHORIZED,
             "Missing or invalid Authorization header".to_string(),
         ))
 }
@@ -78,37 +90,79 @@ fn has_permission(perms: &[String], resource: &str, action: &str) -> bool {
         .any(|p| p == "*" || p == &perm || p == &wildcard)
 }
 
-pub async fn list_audit_logs(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-    Query(query): Query<AuditLogQuery>,
-) -> Result<
-    Json<PaginatedResponse<crate::models::AuditLogResponse>>,
-    (axum::http::StatusCode, String),
-> {
+fn api_error(e: AppError) -> ApiError {
+    let status = match &e {
+        AppError::Validation(_) => StatusCode::BAD_REQUEST,
+        AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
+/// Who is asking and which tenant the request is limited to. `global`
+/// requests (super admin pages) see every tenant; the others are pinned to
+/// the caller's tenant and need `audit_logs:read`.
+async fn audit_scope(
+    state: &AppState,
+    headers: &HeaderMap,
+    global: bool,
+) -> Result<(String, Option<String>), ApiError> {
     let auth_service = &state.auth_service;
-    let audit_service = &state.audit_service;
 
-    let token = extract_token(&headers)?;
+    let token = extract_token(headers)?;
     let claims = auth_service
         .validate_token(&token)
         .await
-        .map_err(|e| (axum::http::StatusCode::UNAUTHORIZED, e.to_string()))?;
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+
+    if global {
+        if !claims.is_super_admin {
+            return Err((StatusCode::FORBIDDEN, "Unauthorized".to_string()));
+        }
+        return Ok((claims.sub, None));
+    }
+
+    let tenant_id = claims
+        .tenant_id
+        .ok_or((StatusCode::FORBIDDEN, "Tenant context missing".to_string()))?;
 
-    if !claims.is_super_admin {
+    let perms = auth_service
+        .get_user_permissions(&claims.sub, &tenant_id)
+        .await
+        .map_err(|e| (StatusCode::FORBIDDEN, e.to_string()))?;
+    if !has_permission(&perms, "audit_logs", "read") {
         return Err((
-            axum::http::StatusCode::FORBIDDEN,
-            "Unauthorized".to_string(),
+            StatusCode::FORBIDDEN,
+            "Missing permission audit_logs:read".to_string(),
         ));
     }
+    Ok((claims.sub, Some(tenant_id)))
+}
+
+/// The query as a filter; tenant requests are forced to the caller's tenant
+/// regardless of a client-provided tenant_id.
+fn scoped_filter(query: AuditLogQuery, tenant_id: Option<String>) -> crate::models::AuditLogFilter {
+    let mut filter: crate::models::AuditLogFilter = query.into();
+    if tenant_id.is_some() {
+        filter.tenant_id = tenant_id;
+    }
+    filter
+}
 
-    let filter: crate::models::AuditLogFilter = query.into();
+async fn list_logs(
+    state: AppState,
+    headers: HeaderMap,
+    query: AuditLogQuery,
+    global: bool,
+) -> Result<Json<PaginatedResponse<crate::models::AuditLogResponse>>, ApiError> {
+    let (_, tenant_id) = audit_scope(&state, &headers, global).await?;
+    let filter = scoped_filter(query, tenant_id);
     let page = filter.page.unwrap_or(1); // Keep for response
     let per_page = filter.per_page.unwrap_or(20);
 
-    let (logs, total) = audit_service.list(filter).await.map_err(|e| {
+    let (logs, total) = state.audit_service.list(filter).await.map_err(|e| {
         tracing::error!("Failed to list audit logs: {}", e);
-        (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
     })?;
 
     Ok(Json(PaginatedResponse {
@@ -119,55 +173,194 @@ pub async fn list_audit_logs(
     }))
 }
 
+pub async fn list_audit_logs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<PaginatedResponse<crate::models::AuditLogResponse>>, ApiError> {
+    list_logs(state, headers, query, true).await
+}
+
 pub async fn list_tenant_audit_logs(
     State(state): State<AppState>,
     headers: HeaderMap,
     Query(query): Query<AuditLogQuery>,
-) -> Result<
-    Json<PaginatedResponse<crate::models::AuditLogResponse>>,
-    (axum::http::StatusCode, String),
-> {
-    let auth_service = &state.auth_service;
-    let audit_service = &state.audit_service;
+) -> Result<Json<PaginatedResponse<crate::models::AuditLogResponse>>, ApiError> {
+    list_logs(state, headers, query, false).await
+}
 
-    let token = extract_token(&headers)?;
-    let claims = auth_service
-        .validate_token(&token)
+async fn export_logs(
+    state: AppState,
+    headers: HeaderMap,
+    query: AuditLogQuery,
+    global: bool,
+) -> Result<Json<AuditLogExport>, ApiError> {
+    let (user_id, tenant_id) = audit_scope(&state, &headers, global).await?;
+    let format = AuditExportFormat::parse(query.format.as_deref()).ok_or((
+        StatusCode::BAD_REQUEST,
+        "format must be csv or ndjson".to_string(),
+    ))?;
+    let filter = scoped_filter(query, tenant_id);
+    let audit_tenant = filter.tenant_id.clone();
+
+    let export = state
+        .audit_service
+        .export(filter, format)
         .await
-        .map_err(|e| (axum::http::StatusCode::UNAUTHORIZED, e.to_string()))?;
+        .map_err(api_error)?;
 
-    let tenant_id = claims.tenant_id.ok_or((
-        axum::http::StatusCode::FORBIDDEN,
-        "Tenant context missing".to_string(),
-    ))?;
+    state
+        .audit_service
+        .log(
+            Some(&user_id),
+            audit_tenant.as_deref(),
+            "export",
+            "audit_logs",
+            None,
+            Some(&format!(
+                "Exported {} audit log entries as {}",
+                export.rows, export.format
+            )),
+            None,
+        )
+        .await;
+    Ok(Json(export))
+}
 
-    let perms = auth_service
-        .get_user_permissions(&claims.sub, &tenant_id)
+pub async fn export_audit_logs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<AuditLogExport>, ApiError> {
+    export_logs(state, headers, query, true).await
+}
+
+pub async fn export_tenant_audit_logs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<AuditLogExport>, ApiError> {
+    export_logs(state, headers, query, false).await
+}
+
+async fn list_presets(
+    state: AppState,
+    headers: HeaderMap,
+    global: bool,
+) -> Result<Json<Vec<AuditFilterPreset>>, ApiError> {
+    let (user_id, tenant_id) = audit_scope(&state, &headers, global).await?;
+    let presets = state
+        .audit_service
+        .list_presets(&user_id, tenant_id.as_deref())
         .await
-        .map_err(|e| (axum::http::StatusCode::FORBIDDEN, e.to_string()))?;
-    if !has_permission(&perms, "audit_logs", "read") {
-        return Err((
-            axum::http::StatusCode::FORBIDDEN,
-            "Missing permission audit_logs:read".to_string(),
-        ));
-    }
+        .map_err(api_error)?;
+    Ok(Json(presets))
+}
 
-    // Force tenant scoping regardless of client-provided tenant_id.
-    let mut filter: crate::models::AuditLogFilter = query.into();
-    filter.tenant_id = Some(tenant_id);
+async fn save_preset(
+    state: AppState,
+    headers: HeaderMap,
+    payload: SaveAuditFilterPresetDto,
+    global: bool,
+) -> Result<Json<AuditFilterPreset>, ApiError> {
+    let (user_id, tenant_id) = audit_scope(&state, &headers, global).await?;
+    let preset = state
+        .audit_service
+        .save_preset(&user_id, tenant_id.as_deref(), payload)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(preset))
+}
 
-    let page = filter.page.unwrap_or(1);
-    let per_page = filter.per_page.unwrap_or(20);
+async fn delete_preset(
+    state: AppState,
+    headers: HeaderMap,
+    id: String,
+    global: bool,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let (user_id, tenant_id) = audit_scope(&state, &headers, global).await?;
+    state
+        .audit_service
+        .delete_preset(&user_id, tenant_id.as_deref(), &id)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+pub async fn list_audit_filter_presets(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<AuditFilterPreset>>, ApiError> {
+    list_presets(state, headers, true).await
+}
+
+pub async fn list_tenant_audit_filter_presets(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<AuditFilterPreset>>, ApiError> {
+    list_presets(state, headers, false).await
+}
 
-    let (logs, total) = audit_service.list(filter).await.map_err(|e| {
-        tracing::error!("Failed to list tenant audit logs: {}", e);
-        (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+pub async fn save_audit_filter_preset(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<SaveAuditFilterPresetDto>,
+) -> Result<Json<AuditFilterPreset>, ApiError> {
+    save_preset(state, headers, payload, true).await
+}
+
+pub async fn save_tenant_audit_filter_preset(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<SaveAuditFilterPresetDto>,
+) -> Result<Json<AuditFilterPreset>, ApiError> {
+    save_preset(state, headers, payload, false).await
+}
+
+pub async fn delete_audit_filter_preset(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    delete_preset(state, headers, id, true).await
+}
+
+pub async fn delete_tenant_audit_filter_preset(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    delete_preset(state, headers, id, false).await
+}
+
+/// Checks the tamper-evident chain over all audit entries (super admin only).
+pub async fn verify_audit_chain(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<AuditChainReport>, ApiError> {
+    let (user_id, _) = audit_scope(&state, &headers, true).await?;
+
+    let report = state.audit_service.verify_chain().await.map_err(|e| {
+        tracing::error!("Failed to verify audit chain: {}", e);
+        api_error(e)
     })?;
 
-    Ok(Json(PaginatedResponse {
-        data: logs,
-        total,
-        page,
-        per_page,
-    }))
+    let details = format!(
+        "Verified {} chained audit entries: {} issue(s)",
+        report.checked,
+        report.issues.len()
+    );
+    state
+        .audit_service
+        .log(
+            Some(&user_id),
+            None,
+            "verify",
+            "audit_logs",
+            None,
+            Some(&details),
+            None,
+        )
+        .await;
+    Ok(Json(report))
 }
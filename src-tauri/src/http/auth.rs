@@ -12,14 +12,9 @@ use axum::{
 use serde_json::json;
 use std::net::SocketAddr;
 
-// Helper to extract IP
+// Helper to extract IP (honors forwarding headers only from trusted proxies)
 pub fn extract_ip(headers: &HeaderMap, addr: SocketAddr) -> String {
-    if let Some(forwarded) = headers.get("X-Forwarded-For") {
-        if let Ok(s) = forwarded.to_str() {
-            return s.split(',').next().unwrap_or(s).trim().to_string();
-        }
-    }
-    addr.ip().to_string()
+    super::middleware::extract_client_ip(headers, Some(addr))
 }
 
 // Helper to map AppError to Axum Response
@@ -60,13 +55,18 @@ impl IntoResponse for crate::error::AppError {
             crate::error::AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
             crate::error::AppError::Authentication(msg) => (StatusCode::UNAUTHORIZED, msg),
             crate::error::AppError::Configuration(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            crate::error::AppError::QuotaExceeded(msg) => (StatusCode::PAYMENT_REQUIRED, msg),
         };
 
         let body = Json(json!({
             "error": message
         }));
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        response
+            .extensions_mut()
+            .insert(super::middleware::ErrorMessage(message));
+        response
     }
 }
 
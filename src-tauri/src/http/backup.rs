@@ -1,6 +1,10 @@
 use crate::error::AppResult;
 use crate::http::AppState;
-use crate::services::backup::BackupRecord;
+use crate::services::backup::{
+    BackupRecord, BackupRunTarget, BackupSchedulePreview, CronPreview, ScratchRestoreReport,
+};
+use crate::services::backup_remote::{RemoteBackupRecord, RemoteBackupStore};
+use crate::services::backup_validation::RestoreValidationReport;
 use axum::{
     extract::Query,
     extract::{Path, State},
@@ -16,9 +20,20 @@ pub fn router() -> Router<AppState> {
         .route("/", get(list_backups))
         .route("/", post(create_backup))
         .route("/restore", post(restore_backup))
+        .route("/validate", post(validate_uploaded_backup))
+        .route("/schedule", get(get_backup_schedule))
+        .route("/schedule/preview", get(preview_backup_cron))
+        .route("/run", post(run_backup_now))
+        .route("/scratch/{schema}", delete(drop_restore_scratch))
         .route("/{filename}/restore", post(restore_local_backup))
         .route("/{filename}", delete(delete_backup))
         .route("/{filename}/download", get(download_backup))
+        .route("/{filename}/upload", post(upload_backup_offsite))
+        .route("/{filename}/pin", post(pin_backup).delete(unpin_backup))
+        .route("/{filename}/validate", post(validate_local_backup))
+        .route("/{filename}/dry-run", post(dry_run_restore_backup))
+        .route("/remote", get(list_remote_backups))
+        .route("/remote/{filename}/restore", post(restore_remote_backup))
 }
 
 fn extract_token(headers: &HeaderMap) -> Result<String, crate::error::AppError> {
@@ -247,16 +262,43 @@ async fn restore_backup(
         ));
     }
 
-    // Use a local scope to ensure multipart is processed
-    let mut multipart = multipart;
+    let temp_path = save_upload_to_temp(multipart).await?;
+
+    let res = state
+        .backup_service
+        .restore_from_zip(temp_path.clone(), None)
+        .await;
 
-    // Create a temporary file for the uploaded zip
-    let temp_dir = std::env::temp_dir();
-    let temp_path = temp_dir.join(format!("restore_{}.zip", uuid::Uuid::new_v4()));
+    // Cleanup
+    let _ = tokio::fs::remove_file(temp_path).await;
 
-    let mut file_saved = false;
+    if res.is_ok() {
+        // Audit (best-effort)
+        let details = serde_json::json!({ "source": "upload" }).to_string();
+        state
+            .audit_service
+            .log(
+                Some(&claims.sub),
+                None,
+                "restore",
+                "backups",
+                None,
+                Some(details.as_str()),
+                None,
+            )
+            .await;
+    }
+
+    res.map(|_| Json(()))
+}
+
+/// Save the `file` field of a multipart upload to a temporary zip.
+async fn save_upload_to_temp(
+    mut multipart: axum::extract::Multipart,
+) -> AppResult<std::path::PathBuf> {
+    let temp_path = std::env::temp_dir().join(format!("restore_{}.zip", uuid::Uuid::new_v4()));
 
-    // Save uploaded file
+    let mut file_saved = false;
     while let Some(field) = multipart
         .next_field()
         .await
@@ -280,18 +322,53 @@ async fn restore_backup(
             "No file uploaded".to_string(),
         ));
     }
+    Ok(temp_path)
+}
+
+/// Tenant a restore of `filename` is scoped to: tenant archives only touch their tenant.
+fn restore_scope(filename: &str) -> Option<&str> {
+    let parts: Vec<&str> = filename.split('_').collect();
+    if filename.starts_with("tenant_") && parts.len() >= 3 {
+        Some(parts[1])
+    } else {
+        None
+    }
+}
+
+async fn restore_local_backup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(filename): Path<String>,
+) -> AppResult<Json<()>> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    // If a superadmin restores a tenant backup, scope it to that tenant
+    let tenant_id = if filename.starts_with("tenant_") {
+        let parts: Vec<&str> = filename.split('_').collect();
+        if parts.len() >= 3 {
+            Some(parts[1])
+        } else {
+            None
+        }
+    } else {
+        None
+    };
 
     let res = state
         .backup_service
-        .restore_from_zip(temp_path.clone(), None)
+        .restore_local_backup(filename.clone(), tenant_id)
         .await;
 
-    // Cleanup
-    let _ = tokio::fs::remove_file(temp_path).await;
-
     if res.is_ok() {
         // Audit (best-effort)
-        let details = serde_json::json!({ "source": "upload" }).to_string();
+        let details = serde_json::json!({ "source": "local", "filename": filename }).to_string();
         state
             .audit_service
             .log(
@@ -309,11 +386,28 @@ async fn restore_backup(
     res.map(|_| Json(()))
 }
 
-async fn restore_local_backup(
+async fn list_remote_backups(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<RemoteBackupRecord>>> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    let store = RemoteBackupStore::from_settings(&state.settings_service).await?;
+    Ok(Json(store.list().await?))
+}
+
+async fn upload_backup_offsite(
     State(state): State<AppState>,
     headers: HeaderMap,
     Path(filename): Path<String>,
-) -> AppResult<Json<()>> {
+) -> AppResult<Json<String>> {
     let token = extract_token(&headers)?;
     let claims = state.auth_service.validate_token(&token).await?;
 
@@ -323,26 +417,52 @@ async fn restore_local_backup(
         ));
     }
 
-    // If a superadmin restores a tenant backup, scope it to that tenant
-    let tenant_id = if filename.starts_with("tenant_") {
-        let parts: Vec<&str> = filename.split('_').collect();
-        if parts.len() >= 3 {
-            Some(parts[1])
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+    let path = state.backup_service.get_backup_path(&filename)?;
+    let store = RemoteBackupStore::from_settings(&state.settings_service).await?;
+    let key = store.upload(&path).await?;
+
+    // Audit (best-effort)
+    let details = serde_json::json!({ "filename": filename, "key": key }).to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            None,
+            "upload_offsite",
+            "backups",
+            None,
+            Some(details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(key))
+}
+
+async fn restore_remote_backup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(filename): Path<String>,
+) -> AppResult<Json<()>> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
 
+    let tenant_id = restore_scope(&filename);
+    let store = RemoteBackupStore::from_settings(&state.settings_service).await?;
     let res = state
         .backup_service
-        .restore_local_backup(filename.clone(), tenant_id)
+        .restore_remote_backup(&store, filename.clone(), tenant_id)
         .await;
 
     if res.is_ok() {
         // Audit (best-effort)
-        let details = serde_json::json!({ "source": "local", "filename": filename }).to_string();
+        let details = serde_json::json!({ "source": "remote", "filename": filename }).to_string();
         state
             .audit_service
             .log(
@@ -359,3 +479,260 @@ async fn restore_local_backup(
 
     res.map(|_| Json(()))
 }
+
+async fn pin_backup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(filename): Path<String>,
+) -> AppResult<Json<()>> {
+    set_backup_pinned(state, headers, filename, true).await
+}
+
+async fn unpin_backup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(filename): Path<String>,
+) -> AppResult<Json<()>> {
+    set_backup_pinned(state, headers, filename, false).await
+}
+
+async fn set_backup_pinned(
+    state: AppState,
+    headers: HeaderMap,
+    filename: String,
+    pinned: bool,
+) -> AppResult<Json<()>> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    state.backup_service.set_pinned(&filename, pinned).await?;
+
+    // Audit (best-effort)
+    let details = serde_json::json!({ "filename": filename }).to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            None,
+            if pinned { "pin" } else { "unpin" },
+            "backups",
+            None,
+            Some(details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(()))
+}
+
+async fn validate_uploaded_backup(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    multipart: axum::extract::Multipart,
+) -> AppResult<Json<RestoreValidationReport>> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    let temp_path = save_upload_to_temp(multipart).await?;
+    // Validated as the upload restore would run it: globally.
+    let res = state.backup_service.validate_backup(&temp_path, None).await;
+    let _ = tokio::fs::remove_file(temp_path).await;
+
+    res.map(Json)
+}
+
+async fn validate_local_backup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(filename): Path<String>,
+) -> AppResult<Json<RestoreValidationReport>> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    let path = state.backup_service.get_backup_path(&filename)?;
+    let report = state
+        .backup_service
+        .validate_backup(&path, restore_scope(&filename))
+        .await?;
+    Ok(Json(report))
+}
+
+async fn dry_run_restore_backup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(filename): Path<String>,
+) -> AppResult<Json<ScratchRestoreReport>> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    let path = state.backup_service.get_backup_path(&filename)?;
+    let report = state
+        .backup_service
+        .restore_to_scratch(&path, restore_scope(&filename))
+        .await?;
+
+    // Audit (best-effort)
+    let details = serde_json::json!({ "filename": filename, "schema": report.schema }).to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            None,
+            "dry_run_restore",
+            "backups",
+            None,
+            Some(details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(report))
+}
+
+async fn drop_restore_scratch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(schema): Path<String>,
+) -> AppResult<Json<()>> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    state.backup_service.drop_scratch_schema(&schema).await?;
+    Ok(Json(()))
+}
+
+async fn get_backup_schedule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<BackupSchedulePreview>> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    let preview = crate::services::backup::backup_schedule_preview(&state.settings_service).await?;
+    Ok(Json(preview))
+}
+
+#[derive(Deserialize)]
+struct CronPreviewQuery {
+    expression: String,
+    count: Option<usize>,
+}
+
+async fn preview_backup_cron(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<CronPreviewQuery>,
+) -> AppResult<Json<CronPreview>> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+
+    let preview = crate::services::backup::preview_cron_expression(
+        &state.settings_service,
+        &query.expression,
+        query.count.unwrap_or(5),
+    )
+    .await?;
+    Ok(Json(preview))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+struct RunBackupRequest {
+    target: BackupRunTarget,
+    tenant_id: Option<String>,
+}
+
+/// Start a backup immediately and return its run id. Progress is broadcast as
+/// `backup_run_progress` WebSocket events carrying that id.
+async fn run_backup_now(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<RunBackupRequest>,
+) -> AppResult<Json<String>> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    if !claims.is_super_admin {
+        return Err(crate::error::AppError::Forbidden(
+            "Backups are managed by Super Admin".to_string(),
+        ));
+    }
+    if payload.tenant_id.is_some() && payload.target != BackupRunTarget::Tenants {
+        return Err(crate::error::AppError::Validation(
+            "Tenant ID is only valid for tenant backups".to_string(),
+        ));
+    }
+
+    let ws_hub = state.ws_hub.clone();
+    let run_id = crate::services::backup::start_manual_run(
+        state.auth_service.pool.clone(),
+        (*state.backup_service).clone(),
+        (*state.settings_service).clone(),
+        (*state.audit_service).clone(),
+        payload.target,
+        payload.tenant_id.clone(),
+        move |progress| ws_hub.broadcast(progress.into()),
+    )?;
+
+    // Audit (best-effort)
+    let details = serde_json::json!({
+        "run_id": run_id,
+        "target": payload.target.as_str(),
+        "tenant_id": payload.tenant_id,
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            None,
+            "run_now",
+            "backups",
+            None,
+            Some(details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(run_id))
+}
@@ -0,0 +1,312 @@
+//! Where the embedded HTTP API listens. The desktop app can move it to another
+//! address or port, or switch it off, without a restart.
+
+use crate::error::{AppError, AppResult};
+use crate::services::SettingsService;
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use std::future::IntoFuture;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tracing::info;
+
+pub const DEFAULT_BIND_ADDRESS: &str = "0.0.0.0";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerBinding {
+    pub enabled: bool,
+    pub bind_address: String,
+    pub port: u16,
+}
+
+impl ServerBinding {
+    /// `BIND_ADDRESS` / `PORT` from the environment, falling back to all interfaces.
+    pub fn from_env(default_port: u16) -> Self {
+        Self {
+            enabled: true,
+            bind_address: std::env::var("BIND_ADDRESS")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_BIND_ADDRESS.to_string()),
+            port: std::env::var("PORT")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(default_port),
+        }
+    }
+
+    /// The `http_server_*` settings, each falling back to the environment when unset.
+    pub async fn load(settings: &SettingsService, default_port: u16) -> Self {
+        let fallback = Self::from_env(default_port);
+        let value = |key: &'static str| async move {
+            settings
+                .get_value(None, key)
+                .await
+                .ok()
+                .flatten()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        Self {
+            enabled: value("http_server_enabled").await.as_deref() != Some("false"),
+            bind_address: value("http_server_bind_address")
+                .await
+                .unwrap_or(fallback.bind_address),
+            port: value("http_server_port")
+                .await
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(fallback.port),
+        }
+    }
+
+    pub fn socket_addr(&self) -> AppResult<SocketAddr> {
+        let ip: IpAddr = self.bind_address.trim().parse().map_err(|_| {
+            AppError::Validation(format!("Invalid bind address: {}", self.bind_address))
+        })?;
+        if self.port == 0 {
+            return Err(AppError::Validation("Port must be 1-65535".to_string()));
+        }
+        Ok(SocketAddr::new(ip, self.port))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerStatus {
+    #[serde(flatten)]
+    pub binding: ServerBinding,
+    /// Set while the listener is up.
+    pub listening_on: Option<String>,
+    /// Why the last bind or serve attempt failed.
+    pub error: Option<String>,
+}
+
+/// Holds the wanted binding; `serve` follows it, rebinding whenever it changes.
+pub struct ServerControl {
+    desired: watch::Sender<ServerBinding>,
+    status: RwLock<ServerStatus>,
+    /// Set once by `shutdown`; `serve` drains and returns.
+    stopping: watch::Sender<bool>,
+}
+
+impl ServerControl {
+    pub fn new(binding: ServerBinding) -> Arc<Self> {
+        let status = ServerStatus {
+            binding: binding.clone(),
+            listening_on: None,
+            error: None,
+        };
+        Arc::new(Self {
+            desired: watch::channel(binding).0,
+            status: RwLock::new(status),
+            stopping: watch::channel(false).0,
+        })
+    }
+
+    pub fn status(&self) -> ServerStatus {
+        self.status.read().expect("server status lock").clone()
+    }
+
+    /// Restarts the listener with `binding` and waits briefly for the outcome.
+    pub async fn apply(&self, binding: ServerBinding) -> ServerStatus {
+        self.desired.send_replace(binding.clone());
+        let deadline = Instant::now() + Duration::from_secs(3);
+        loop {
+            let status = self.status();
+            let settled = status.binding == binding
+                && (!binding.enabled || status.listening_on.is_some() || status.error.is_some());
+            if settled || Instant::now() >= deadline {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    /// Stops accepting connections and lets `serve` return once the
+    /// in-flight requests have finished.
+    pub fn shutdown(&self) {
+        self.stopping.send_replace(true);
+    }
+
+    fn set_status(&self, binding: &ServerBinding, addr: Option<SocketAddr>, error: Option<String>) {
+        *self.status.write().expect("server status lock") = ServerStatus {
+            binding: binding.clone(),
+            listening_on: addr.map(|a| a.to_string()),
+            error,
+        };
+    }
+
+    /// Serves `app` until `shutdown` is called.
+    pub async fn serve(&self, app: Router) {
+        let mut desired = self.desired.subscribe();
+        let mut stopping = self.stopping.subscribe();
+        loop {
+            if *stopping.borrow() {
+                info!("HTTP API stopped");
+                self.set_status(&desired.borrow().clone(), None, None);
+                return;
+            }
+            let binding = desired.borrow_and_update().clone();
+            if binding.enabled {
+                match binding.socket_addr() {
+                    Ok(addr) => self.serve_on(addr, &binding, &app, desired.clone()).await,
+                    Err(e) => self.set_status(&binding, None, Some(e.to_string())),
+                }
+            } else {
+                info!("HTTP API disabled");
+                self.set_status(&binding, None, None);
+            }
+            // Changes that came in while serving are picked up right away.
+            if *stopping.borrow() || desired.has_changed().unwrap_or(false) {
+                continue;
+            }
+            tokio::select! {
+                changed = desired.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                }
+                _ = stopping.wait_for(|stop| *stop) => {}
+            }
+        }
+    }
+
+    /// Runs until the wanted binding changes or the server fails.
+    async fn serve_on(
+        &self,
+        addr: SocketAddr,
+        binding: &ServerBinding,
+        app: &Router,
+        mut changes: watch::Receiver<ServerBinding>,
+    ) {
+        let listener = match TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to bind to {}: {}. Is another instance running?",
+                    addr,
+                    e
+                );
+                self.set_status(binding, None, Some(e.to_string()));
+                return;
+            }
+        };
+        info!("HTTP API listening on {}", addr);
+        self.set_status(binding, Some(addr), None);
+
+        let mut stopping = self.stopping.subscribe();
+        let server = axum::serve(
+            listener,
+            app.clone()
+                .into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move {
+            let _ = stopping.wait_for(|stop| *stop).await;
+        });
+        // On shutdown the server returns once open connections are done. On a
+        // binding change it is dropped, which closes the listener; open
+        // connections finish on their own.
+        tokio::select! {
+            result = server.into_future() => {
+                if let Err(e) = result {
+                    tracing::error!("HTTP API server error: {}", e);
+                    self.set_status(binding, None, Some(e.to_string()));
+                }
+            }
+            _ = changes.changed() => info!("HTTP API binding changed, restarting listener"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(address: &str, port: u16) -> ServerBinding {
+        ServerBinding {
+            enabled: true,
+            bind_address: address.to_string(),
+            port,
+        }
+    }
+
+    #[test]
+    fn socket_addr_validates_address_and_port() {
+        assert_eq!(
+            binding("127.0.0.1", 8080).socket_addr().unwrap(),
+            "127.0.0.1:8080".parse().unwrap()
+        );
+        assert!(binding("::1", 3000).socket_addr().is_ok());
+        assert!(binding("localhost", 3000).socket_addr().is_err());
+        assert!(binding("0.0.0.0", 0).socket_addr().is_err());
+    }
+
+    #[tokio::test]
+    async fn rebinds_when_binding_changes() {
+        let control = ServerControl::new(binding("127.0.0.1", 0));
+        let app = Router::new().route("/", axum::routing::get(|| async { "ok" }));
+        let server = {
+            let control = control.clone();
+            tokio::spawn(async move { control.serve(app).await })
+        };
+
+        // Port 0 is refused up front.
+        let status = control.apply(binding("127.0.0.1", 0)).await;
+        assert!(status.error.is_some());
+
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let status = control.apply(binding("127.0.0.1", port)).await;
+        assert_eq!(status.listening_on, Some(format!("127.0.0.1:{}", port)));
+        assert!(tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_ok());
+
+        let mut off = binding("127.0.0.1", port);
+        off.enabled = false;
+        let status = control.apply(off).await;
+        assert_eq!(status.listening_on, None);
+        assert!(tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err());
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn shutdown_stops_serving() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let control = ServerControl::new(binding("127.0.0.1", port));
+        let app = Router::new().route("/", axum::routing::get(|| async { "ok" }));
+        let server = {
+            let control = control.clone();
+            tokio::spawn(async move { control.serve(app).await })
+        };
+        for _ in 0..100 {
+            if control.status().listening_on.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(control.status().listening_on.is_some());
+
+        control.shutdown();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("serve returns after shutdown")
+            .unwrap();
+        assert_eq!(control.status().listening_on, None);
+        assert!(tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err());
+    }
+}
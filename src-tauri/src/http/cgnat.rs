@@ -0,0 +1,68 @@
+use crate::error::{AppError, AppResult};
+use crate::http::auth::extract_ip;
+use crate::http::AppState;
+use crate::models::{CgnatImportResult, CgnatLookupQuery, CgnatPortBlock, ImportCgnatLogRequest};
+use axum::{
+    extract::{ConnectInfo, Query, State},
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+use std::net::SocketAddr;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/import", post(import_log))
+        .route("/lookup", get(lookup))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+async fn tenant_and_claims(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> AppResult<(String, crate::services::auth_service::Claims)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    Ok((tenant_id, claims))
+}
+
+// POST /api/admin/cgnat/import
+async fn import_log(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<ImportCgnatLogRequest>,
+) -> AppResult<Json<CgnatImportResult>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .cgnat_service
+        .import_log(&claims.sub, &tenant_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// GET /api/admin/cgnat/lookup?public_ip=&port=&at=
+async fn lookup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(q): Query<CgnatLookupQuery>,
+) -> AppResult<Json<Vec<CgnatPortBlock>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .cgnat_service
+        .lookup(&claims.sub, &tenant_id, q, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
@@ -8,10 +8,12 @@ use crate::models::{
     CreateMyCustomerLocationRequest, Customer, CustomerLocation, CustomerPortalSubscriptionStats,
     CustomerPortalUser, CustomerRegistrationInviteCreateResponse, CustomerRegistrationInvitePolicy,
     CustomerRegistrationInviteSummary, CustomerRegistrationInviteView, CustomerSubscription,
-    CustomerSubscriptionView, InstallationWorkOrder, InstallationWorkOrderView, Invoice,
-    IspPackage, PaginatedResponse, PortalCheckoutSubscriptionRequest,
+    CustomerSubscriptionComponent, CustomerSubscriptionView, CustomerTagSummary, CustomerUsage,
+    InstallationWorkOrder, InstallationWorkOrderView, Invoice, IspPackage, PaginatedResponse,
+    PortalCheckoutSubscriptionRequest, SetCustomerTagsRequest, TrashItem,
     UpdateCustomerLocationRequest, UpdateCustomerRegistrationInvitePolicyRequest,
-    UpdateCustomerRequest, UpdateCustomerSubscriptionRequest, WorkOrderRescheduleRequestView,
+    UpdateCustomerRequest, UpdateCustomerSubscriptionRequest, UpdateSubscriptionComponentRequest,
+    WorkOrderCompletionReport, WorkOrderRescheduleRequestView,
 };
 use axum::{
     extract::{ConnectInfo, Path, Query, State},
@@ -27,6 +29,8 @@ pub fn router() -> Router<AppState> {
         // Admin
         .route("/", get(list_customers).post(create_customer))
         .route("/with-portal", post(create_customer_with_portal))
+        .route("/trash", get(list_deleted_customers))
+        .route("/tags", get(list_customer_tags))
         .route(
             "/invites",
             get(list_customer_registration_invites).post(create_customer_registration_invite),
@@ -50,8 +54,12 @@ pub fn router() -> Router<AppState> {
                 .put(update_customer)
                 .delete(delete_customer),
         )
+        .route("/{id}/restore", post(restore_customer))
         .route("/{id}/locations", get(list_locations))
+        .route("/{id}/completion-reports", get(list_completion_reports))
         .route("/{id}/portal-users", get(list_portal_users))
+        .route("/{id}/usage", get(get_customer_usage))
+        .route("/{id}/tags", get(get_customer_tags).put(set_customer_tags))
         .route(
             "/{id}/subscriptions",
             get(list_subscriptions).post(create_subscription),
@@ -73,6 +81,14 @@ pub fn router() -> Router<AppState> {
             "/subscriptions/{subscription_id}",
             axum::routing::put(update_subscription).delete(delete_subscription),
         )
+        .route(
+            "/subscriptions/{subscription_id}/components",
+            get(list_subscription_components),
+        )
+        .route(
+            "/subscription-components/{component_id}",
+            axum::routing::put(update_subscription_component),
+        )
         // Customer portal
         .route(
             "/portal/my-locations",
@@ -139,6 +155,11 @@ struct ListSubscriptionQuery {
     per_page: Option<u32>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ListMyPackagesQuery {
+    location_id: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct ListMySubscriptionQuery {
     page: Option<u32>,
@@ -148,6 +169,11 @@ struct ListMySubscriptionQuery {
     sort_dir: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct UsageQuery {
+    days: Option<u32>,
+}
+
 #[derive(Debug, Deserialize)]
 struct ListCustomerInviteQuery {
     include_inactive: Option<bool>,
@@ -272,6 +298,79 @@ async fn delete_customer(
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
+// GET /api/customers/trash
+async fn list_deleted_customers(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<TrashItem>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let rows = state
+        .customer_service
+        .list_deleted_customers(&claims.sub, &tenant_id)
+        .await?;
+    Ok(Json(rows))
+}
+
+// POST /api/customers/{id}/restore
+async fn restore_customer(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Customer>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let customer = state
+        .customer_service
+        .restore_customer(&claims.sub, &tenant_id, &id, Some(&ip))
+        .await?;
+    Ok(Json(customer))
+}
+
+// GET /api/customers/tags
+async fn list_customer_tags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<CustomerTagSummary>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let rows = state
+        .customer_service
+        .list_customer_tags(&claims.sub, &tenant_id)
+        .await?;
+    Ok(Json(rows))
+}
+
+// GET /api/customers/{id}/tags
+async fn get_customer_tags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<String>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let tags = state
+        .customer_service
+        .get_customer_tags(&claims.sub, &tenant_id, &id)
+        .await?;
+    Ok(Json(tags))
+}
+
+// PUT /api/customers/{id}/tags
+async fn set_customer_tags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<SetCustomerTagsRequest>,
+) -> AppResult<Json<Vec<String>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let tags = state
+        .customer_service
+        .set_customer_tags(&claims.sub, &tenant_id, &id, dto, Some(&ip))
+        .await?;
+    Ok(Json(tags))
+}
+
 // POST /api/customers/invites
 async fn create_customer_registration_invite(
     State(state): State<AppState>,
@@ -379,6 +478,35 @@ async fn list_locations(
     Ok(Json(rows))
 }
 
+// GET /api/customers/{id}/completion-reports
+async fn list_completion_reports(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<WorkOrderCompletionReport>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let rows = state
+        .completion_reports
+        .list_for_customer(&claims.sub, &tenant_id, &id)
+        .await?;
+    Ok(Json(rows))
+}
+
+// GET /api/customers/{id}/usage?days=30
+async fn get_customer_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(q): Query<UsageQuery>,
+) -> AppResult<Json<CustomerUsage>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let usage = state
+        .pppoe_service
+        .customer_usage(&claims.sub, &tenant_id, &id, q.days)
+        .await?;
+    Ok(Json(usage))
+}
+
 // POST /api/customers/locations
 async fn create_location(
     State(state): State<AppState>,
@@ -556,11 +684,12 @@ async fn delete_my_location(
 async fn list_my_packages(
     State(state): State<AppState>,
     headers: HeaderMap,
+    Query(q): Query<ListMyPackagesQuery>,
 ) -> AppResult<Json<Vec<IspPackage>>> {
     let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
     let rows = state
         .customer_service
-        .list_my_packages(&claims.sub, &tenant_id)
+        .list_my_packages(&claims.sub, &tenant_id, q.location_id)
         .await?;
     Ok(Json(rows))
 }
@@ -805,3 +934,34 @@ async fn delete_subscription(
         .await?;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
+
+// GET /api/customers/subscriptions/{subscription_id}/components
+async fn list_subscription_components(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(subscription_id): Path<String>,
+) -> AppResult<Json<Vec<CustomerSubscriptionComponent>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let rows = state
+        .customer_service
+        .list_subscription_components(&claims.sub, &tenant_id, &subscription_id)
+        .await?;
+    Ok(Json(rows))
+}
+
+// PUT /api/customers/subscription-components/{component_id}
+async fn update_subscription_component(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(component_id): Path<String>,
+    Json(dto): Json<UpdateSubscriptionComponentRequest>,
+) -> AppResult<Json<CustomerSubscriptionComponent>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let row = state
+        .customer_service
+        .update_subscription_component(&claims.sub, &tenant_id, &component_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(row))
+}
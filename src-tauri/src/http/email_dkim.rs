@@ -0,0 +1,88 @@
+use crate::error::{AppError, AppResult};
+use crate::http::AppState;
+use crate::models::{EmailDkimStatus, GenerateDkimKeyRequest, UpdateDkimRequest};
+use crate::services::EmailDkimService;
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_dkim).put(update_dkim).delete(delete_dkim))
+        .route("/generate", post(generate_key))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+/// Returns `(user_id, tenant_id)` after checking the settings permission.
+async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    action: &str,
+) -> AppResult<(String, String)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "settings", action)
+        .await?;
+    Ok((claims.sub, tenant_id))
+}
+
+fn dkim(state: &AppState) -> AppResult<&EmailDkimService> {
+    state
+        .email_service
+        .dkim()
+        .ok_or_else(|| AppError::Internal("DKIM signing is not initialized".to_string()))
+}
+
+// GET /api/email-dkim
+async fn get_dkim(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<EmailDkimStatus>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(dkim(&state)?.status(&tenant_id).await?))
+}
+
+// POST /api/email-dkim/generate
+async fn generate_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<GenerateDkimKeyRequest>,
+) -> AppResult<Json<EmailDkimStatus>> {
+    let (user_id, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        dkim(&state)?
+            .generate(&tenant_id, req, Some(&user_id))
+            .await?,
+    ))
+}
+
+// PUT /api/email-dkim
+async fn update_dkim(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateDkimRequest>,
+) -> AppResult<Json<EmailDkimStatus>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(dkim(&state)?.update(&tenant_id, req).await?))
+}
+
+// DELETE /api/email-dkim
+async fn delete_dkim(State(state): State<AppState>, headers: HeaderMap) -> AppResult<Json<()>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    dkim(&state)?.delete(&tenant_id).await?;
+    Ok(Json(()))
+}
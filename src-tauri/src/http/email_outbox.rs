@@ -30,6 +30,12 @@ pub struct BulkIdsRequest {
     pub ids: Vec<String>,
 }
 
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleEmailRequest {
+    pub scheduled_at: chrono::DateTime<Utc>,
+}
+
 #[derive(Deserialize)]
 pub struct ExportEmailOutboxQuery {
     pub scope: Option<String>, // tenant | global | all (super admin)
@@ -46,6 +52,7 @@ pub fn router() -> Router<AppState> {
         .route("/bulk/retry", post(bulk_retry_email_outbox))
         .route("/bulk/delete", post(bulk_delete_email_outbox))
         .route("/{id}/retry", post(retry_email_outbox))
+        .route("/{id}/schedule", post(schedule_email_outbox))
         .route("/{id}", get(get_email_outbox).delete(delete_email_outbox))
 }
 
@@ -120,6 +127,7 @@ async fn list_email_outbox(
               eo.status,
               eo.attempts,
               eo.max_attempts,
+              eo.priority,
               eo.scheduled_at,
               eo.last_error,
               eo.sent_at,
@@ -237,6 +245,7 @@ async fn get_email_outbox(
               eo.status,
               eo.attempts,
               eo.max_attempts,
+              eo.priority,
               eo.scheduled_at,
               eo.last_error,
               eo.sent_at,
@@ -608,6 +617,57 @@ async fn retry_email_outbox(
     Ok(Json(serde_json::json!({ "success": true })))
 }
 
+// POST /api/email-outbox/:id/schedule
+async fn schedule_email_outbox(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<ScheduleEmailRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let token = bearer_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+
+    if let Some(tid) = claims.tenant_id.as_deref() {
+        state
+            .auth_service
+            .check_permission(&claims.sub, tid, "email_outbox", "retry")
+            .await?;
+    } else if !claims.is_super_admin {
+        return Err(AppError::Unauthorized);
+    }
+
+    let tenant_scope = if claims.is_super_admin {
+        None
+    } else {
+        claims.tenant_id.as_deref()
+    };
+    state
+        .notification_service
+        .email_outbox()
+        .reschedule(tenant_scope, &id, payload.scheduled_at)
+        .await?;
+
+    let details = serde_json::json!({
+        "id": id,
+        "scheduled_at": payload.scheduled_at.to_rfc3339(),
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            claims.tenant_id.as_deref(),
+            "schedule",
+            "email_outbox",
+            Some(&id),
+            Some(details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
 // POST /api/email-outbox/bulk/retry
 async fn bulk_retry_email_outbox(
     State(state): State<AppState>,
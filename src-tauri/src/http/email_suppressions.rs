@@ -0,0 +1,124 @@
+use crate::error::{AppError, AppResult};
+use crate::http::AppState;
+use crate::models::{
+    AddEmailSuppressionRequest, EmailEventsResult, EmailSuppression, PaginatedResponse,
+};
+use crate::services::EmailSuppressionService;
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ListSuppressionsQuery {
+    pub search: Option<String>,
+    pub reason: Option<String>,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_suppressions).post(add_suppression))
+        .route("/{id}", delete(remove_suppression))
+        // Provider callbacks (unauthenticated, identified by the token in the path)
+        .route("/webhooks/{provider}/{token}", post(provider_webhook))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+/// Resolves the list to work on: the caller's tenant (checked against the
+/// `email_outbox` permission) or, for a super admin outside any tenant, the
+/// global list (`None`).
+async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    action: &str,
+) -> AppResult<Option<String>> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    match claims.tenant_id.clone() {
+        Some(tenant_id) => {
+            state
+                .auth_service
+                .check_permission(&claims.sub, &tenant_id, "email_outbox", action)
+                .await?;
+            Ok(Some(tenant_id))
+        }
+        None if claims.is_super_admin => Ok(None),
+        None => Err(AppError::Forbidden("Forbidden".to_string())),
+    }
+}
+
+fn suppressions(state: &AppState) -> &EmailSuppressionService {
+    state.notification_service.email_outbox().suppressions()
+}
+
+// GET /api/email-suppressions
+async fn list_suppressions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<ListSuppressionsQuery>,
+) -> AppResult<Json<PaginatedResponse<EmailSuppression>>> {
+    let tenant_id = authorize(&state, &headers, "read").await?;
+    Ok(Json(
+        suppressions(&state)
+            .list(
+                tenant_id.as_deref(),
+                q.search.as_deref(),
+                q.reason.as_deref(),
+                q.page.unwrap_or(1),
+                q.per_page.unwrap_or(25),
+            )
+            .await?,
+    ))
+}
+
+// POST /api/email-suppressions
+async fn add_suppression(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<AddEmailSuppressionRequest>,
+) -> AppResult<Json<EmailSuppression>> {
+    let tenant_id = authorize(&state, &headers, "delete").await?;
+    Ok(Json(
+        suppressions(&state).add(tenant_id.as_deref(), req).await?,
+    ))
+}
+
+// DELETE /api/email-suppressions/{id}
+async fn remove_suppression(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<()>> {
+    let tenant_id = authorize(&state, &headers, "delete").await?;
+    suppressions(&state)
+        .remove(tenant_id.as_deref(), &id)
+        .await?;
+    Ok(Json(()))
+}
+
+// POST /api/email-suppressions/webhooks/{provider}/{token}
+async fn provider_webhook(
+    State(state): State<AppState>,
+    Path((provider, token)): Path<(String, String)>,
+    body: Bytes,
+) -> AppResult<Json<EmailEventsResult>> {
+    Ok(Json(
+        suppressions(&state)
+            .handle_webhook(&provider, &token, &body)
+            .await?,
+    ))
+}
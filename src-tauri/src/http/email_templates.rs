@@ -0,0 +1,141 @@
+use crate::error::{AppError, AppResult};
+use crate::http::AppState;
+use crate::models::{
+    EmailTemplate, EmailTemplateVariable, PreviewEmailTemplateRequest, RenderedEmail,
+    TestSendEmailTemplateRequest, UpdateEmailTemplateRequest,
+};
+use crate::services::EmailTemplateService;
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_templates))
+        .route(
+            "/{code}",
+            get(get_template)
+                .put(update_template)
+                .delete(reset_template),
+        )
+        .route("/{code}/variables", get(list_variables))
+        .route("/{code}/preview", post(preview_template))
+        .route("/{code}/test-send", post(test_send_template))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+/// Returns `(user_id, tenant_id)` after checking the settings permission.
+async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    action: &str,
+) -> AppResult<(String, String)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "settings", action)
+        .await?;
+    Ok((claims.sub, tenant_id))
+}
+
+fn templates(state: &AppState) -> AppResult<&EmailTemplateService> {
+    state
+        .notification_service
+        .email_templates()
+        .ok_or_else(|| AppError::Internal("Email templates are not initialized".to_string()))
+}
+
+// GET /api/email-templates
+async fn list_templates(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<EmailTemplate>>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(templates(&state)?.list(&tenant_id).await?))
+}
+
+// GET /api/email-templates/{code}
+async fn get_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(code): Path<String>,
+) -> AppResult<Json<EmailTemplate>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(templates(&state)?.get(&tenant_id, &code).await?))
+}
+
+// GET /api/email-templates/{code}/variables
+async fn list_variables(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(code): Path<String>,
+) -> AppResult<Json<Vec<EmailTemplateVariable>>> {
+    authorize(&state, &headers, "read").await?;
+    Ok(Json(templates(&state)?.variables(&code)?))
+}
+
+// PUT /api/email-templates/{code}
+async fn update_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(code): Path<String>,
+    Json(req): Json<UpdateEmailTemplateRequest>,
+) -> AppResult<Json<EmailTemplate>> {
+    let (user_id, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        templates(&state)?
+            .update(&tenant_id, &code, req, Some(&user_id))
+            .await?,
+    ))
+}
+
+// DELETE /api/email-templates/{code}
+async fn reset_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(code): Path<String>,
+) -> AppResult<Json<EmailTemplate>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(templates(&state)?.reset(&tenant_id, &code).await?))
+}
+
+// POST /api/email-templates/{code}/preview
+async fn preview_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(code): Path<String>,
+    Json(req): Json<PreviewEmailTemplateRequest>,
+) -> AppResult<Json<RenderedEmail>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(
+        templates(&state)?.preview(&tenant_id, &code, req).await?,
+    ))
+}
+
+// POST /api/email-templates/{code}/test-send
+async fn test_send_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(code): Path<String>,
+    Json(req): Json<TestSendEmailTemplateRequest>,
+) -> AppResult<Json<RenderedEmail>> {
+    let (user_id, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        templates(&state)?
+            .test_send(&tenant_id, &code, &user_id, req)
+            .await?,
+    ))
+}
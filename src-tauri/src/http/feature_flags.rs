@@ -0,0 +1,124 @@
+use crate::error::{AppError, AppResult};
+use crate::http::AppState;
+use crate::models::{
+    FeatureFlag, FeatureFlagOverride, FeatureFlagWithOverrides, SetFeatureFlagOverrideRequest,
+    UpsertFeatureFlagRequest,
+};
+use crate::services::Claims;
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    routing::{get, put},
+    Json, Router,
+};
+use std::collections::HashMap;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_flags))
+        .route("/evaluate", get(evaluate_flags))
+        .route("/{key}", put(upsert_flag).delete(delete_flag))
+        .route(
+            "/{key}/overrides/{tenant_id}",
+            put(set_override).delete(remove_override),
+        )
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+async fn claims(state: &AppState, headers: &HeaderMap) -> AppResult<Claims> {
+    let token = bearer_token(headers)?;
+    state.auth_service.validate_token(&token).await
+}
+
+async fn require_super_admin(state: &AppState, headers: &HeaderMap) -> AppResult<()> {
+    if claims(state, headers).await?.is_super_admin {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "Super admin access required".to_string(),
+        ))
+    }
+}
+
+// GET /api/feature-flags/evaluate
+async fn evaluate_flags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<HashMap<String, bool>>> {
+    let claims = claims(&state, &headers).await?;
+    Ok(Json(
+        state
+            .feature_flags
+            .evaluate_all(claims.tenant_id.as_deref())
+            .await?,
+    ))
+}
+
+// GET /api/feature-flags
+async fn list_flags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<FeatureFlagWithOverrides>>> {
+    require_super_admin(&state, &headers).await?;
+    Ok(Json(state.feature_flags.list().await?))
+}
+
+// PUT /api/feature-flags/{key}
+async fn upsert_flag(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(key): Path<String>,
+    Json(req): Json<UpsertFeatureFlagRequest>,
+) -> AppResult<Json<FeatureFlag>> {
+    require_super_admin(&state, &headers).await?;
+    Ok(Json(state.feature_flags.upsert(&key, req).await?))
+}
+
+// DELETE /api/feature-flags/{key}
+async fn delete_flag(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(key): Path<String>,
+) -> AppResult<Json<()>> {
+    require_super_admin(&state, &headers).await?;
+    state.feature_flags.delete(&key).await?;
+    Ok(Json(()))
+}
+
+// PUT /api/feature-flags/{key}/overrides/{tenant_id}
+async fn set_override(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((key, tenant_id)): Path<(String, String)>,
+    Json(req): Json<SetFeatureFlagOverrideRequest>,
+) -> AppResult<Json<FeatureFlagOverride>> {
+    require_super_admin(&state, &headers).await?;
+    Ok(Json(
+        state
+            .feature_flags
+            .set_override(&key, &tenant_id, req.enabled)
+            .await?,
+    ))
+}
+
+// DELETE /api/feature-flags/{key}/overrides/{tenant_id}
+async fn remove_override(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((key, tenant_id)): Path<(String, String)>,
+) -> AppResult<Json<()>> {
+    require_super_admin(&state, &headers).await?;
+    state
+        .feature_flags
+        .remove_override(&key, &tenant_id)
+        .await?;
+    Ok(Json(()))
+}
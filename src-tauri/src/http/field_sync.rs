@@ -0,0 +1,68 @@
+use crate::error::{AppError, AppResult};
+use crate::http::auth::extract_ip;
+use crate::http::AppState;
+use crate::models::{
+    FieldSyncChanges, FieldSyncPullRequest, FieldSyncPushRequest, FieldSyncPushResponse,
+};
+use axum::{
+    extract::{ConnectInfo, State},
+    http::HeaderMap,
+    routing::post,
+    Json, Router,
+};
+use std::net::SocketAddr;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/pull", post(pull))
+        .route("/push", post(push))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+async fn tenant_and_claims(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> AppResult<(String, crate::services::auth_service::Claims)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    Ok((tenant_id, claims))
+}
+
+// POST /api/admin/field-sync/pull
+async fn pull(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(dto): Json<FieldSyncPullRequest>,
+) -> AppResult<Json<FieldSyncChanges>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .field_sync_service
+        .pull(&claims.sub, &tenant_id, dto)
+        .await?;
+    Ok(Json(out))
+}
+
+// POST /api/admin/field-sync/push
+async fn push(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<FieldSyncPushRequest>,
+) -> AppResult<Json<FieldSyncPushResponse>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .field_sync_service
+        .push(&claims.sub, &tenant_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
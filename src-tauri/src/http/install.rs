@@ -87,6 +87,7 @@ pub async fn install_app(
                     key: "app_name".to_string(),
                     value: app_name,
                     description: Some("Application name".to_string()),
+                    expected_updated_at: None,
                 },
                 None,
                 None,
@@ -104,6 +105,7 @@ pub async fn install_app(
                     key: "app_public_url".to_string(),
                     value: app_url,
                     description: Some("Public URL of the application".to_string()),
+                    expected_updated_at: None,
                 },
                 None,
                 None,
@@ -0,0 +1,239 @@
+use crate::error::{AppError, AppResult};
+use crate::http::auth::extract_ip;
+use crate::http::AppState;
+use crate::models::{
+    ApiKeyRateLimit, CreateApiKeyRequest, CreateWebhookRequest, CreatedApiKey, CreatedWebhook,
+    IntegrationsOverview, TenantApiKey, TenantWebhook, UpdateWebhookRequest, WebhookDelivery,
+    WebhookDeliveryQuery,
+};
+use crate::services::auth_service::API_KEY_PREFIX;
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::HeaderMap,
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use std::net::SocketAddr;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(overview))
+        .route("/api-keys", get(list_api_keys).post(create_api_key))
+        .route("/api-keys/{id}", delete(revoke_api_key))
+        .route("/webhooks", get(list_webhooks).post(create_webhook))
+        .route("/webhooks/{id}", put(update_webhook).delete(delete_webhook))
+        .route("/webhooks/{id}/test", post(test_webhook))
+        .route("/deliveries", get(list_deliveries))
+        .route("/deliveries/{id}/redeliver", post(redeliver))
+        .route("/rate-limits", get(rate_limits))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+async fn tenant_and_claims(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> AppResult<(String, crate::services::auth_service::Claims)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    Ok((tenant_id, claims))
+}
+
+async fn api_limit_per_minute(state: &AppState) -> u32 {
+    state.security_config.read().await.api_rate_limit_per_minute
+}
+
+// GET /api/integrations
+async fn overview(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<IntegrationsOverview>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let limit = api_limit_per_minute(&state).await;
+    let out = state
+        .integration_service
+        .overview(&claims.sub, &tenant_id, limit)
+        .await?;
+    Ok(Json(out))
+}
+
+// GET /api/integrations/api-keys
+async fn list_api_keys(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<TenantApiKey>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .integration_service
+        .list_api_keys(&claims.sub, &tenant_id)
+        .await?;
+    Ok(Json(out))
+}
+
+// POST /api/integrations/api-keys
+async fn create_api_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<CreateApiKeyRequest>,
+) -> AppResult<Json<CreatedApiKey>> {
+    // A leaked key must not be able to mint its own replacements.
+    if bearer_token(&headers)?.starts_with(API_KEY_PREFIX) {
+        return Err(AppError::Forbidden(
+            "API keys cannot create API keys".to_string(),
+        ));
+    }
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .integration_service
+        .create_api_key(&claims.sub, &tenant_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// DELETE /api/integrations/api-keys/{id}
+async fn revoke_api_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> AppResult<Json<TenantApiKey>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .integration_service
+        .revoke_api_key(&claims.sub, &tenant_id, &id, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// GET /api/integrations/webhooks
+async fn list_webhooks(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<TenantWebhook>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .integration_service
+        .list_webhooks(&claims.sub, &tenant_id)
+        .await?;
+    Ok(Json(out))
+}
+
+// POST /api/integrations/webhooks
+async fn create_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<CreateWebhookRequest>,
+) -> AppResult<Json<CreatedWebhook>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .integration_service
+        .create_webhook(&claims.sub, &tenant_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// PUT /api/integrations/webhooks/{id}
+async fn update_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<UpdateWebhookRequest>,
+) -> AppResult<Json<TenantWebhook>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .integration_service
+        .update_webhook(&claims.sub, &tenant_id, &id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// DELETE /api/integrations/webhooks/{id}
+async fn delete_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    state
+        .integration_service
+        .delete_webhook(&claims.sub, &tenant_id, &id, Some(&ip))
+        .await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+// POST /api/integrations/webhooks/{id}/test
+async fn test_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> AppResult<Json<WebhookDelivery>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .integration_service
+        .test_webhook(&claims.sub, &tenant_id, &id, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// GET /api/integrations/deliveries
+async fn list_deliveries(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<WebhookDeliveryQuery>,
+) -> AppResult<Json<Vec<WebhookDelivery>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .integration_service
+        .list_deliveries(&claims.sub, &tenant_id, q)
+        .await?;
+    Ok(Json(out))
+}
+
+// POST /api/integrations/deliveries/{id}/redeliver
+async fn redeliver(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> AppResult<Json<WebhookDelivery>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .integration_service
+        .redeliver(&claims.sub, &tenant_id, &id, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// GET /api/integrations/rate-limits
+async fn rate_limits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<ApiKeyRateLimit>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let limit = api_limit_per_minute(&state).await;
+    let out = state
+        .integration_service
+        .rate_limits(&claims.sub, &tenant_id, limit)
+        .await?;
+    Ok(Json(out))
+}
@@ -0,0 +1,282 @@
+use crate::error::{AppError, AppResult};
+use crate::http::auth::extract_ip;
+use crate::http::AppState;
+use crate::models::{
+    CreateInventoryMovementRequest, InventoryItem, InventoryLocation, InventoryMovement,
+    InventoryScanResult, InventorySerial, InventorySerialBatchResult, InventoryStockLevel,
+    ReceiveInventorySerialsRequest, TransferInventorySerialsRequest, UpsertInventoryItemRequest,
+    UpsertInventoryLocationRequest,
+};
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::HeaderMap,
+    routing::{get, post, put},
+    Json, Router,
+};
+use serde::Deserialize;
+use std::net::SocketAddr;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/items", get(list_items).post(create_item))
+        .route("/items/{id}", put(update_item))
+        .route("/locations", get(list_locations).post(create_location))
+        .route("/locations/{id}", put(update_location))
+        .route("/stock", get(list_stock))
+        .route("/movements", get(list_movements).post(create_movement))
+        .route("/scan", get(resolve_scan))
+        .route("/serials", get(list_serials).post(receive_serials))
+        .route("/serials/transfer", post(transfer_serials))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+async fn tenant_and_claims(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> AppResult<(String, crate::services::auth_service::Claims)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    Ok((tenant_id, claims))
+}
+
+#[derive(Debug, Deserialize)]
+struct ListItemsQuery {
+    include_inactive: Option<bool>,
+}
+
+// GET /api/admin/inventory/items
+async fn list_items(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<ListItemsQuery>,
+) -> AppResult<Json<Vec<InventoryItem>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .inventory_service
+        .list_items(&claims.sub, &tenant_id, q.include_inactive.unwrap_or(false))
+        .await?;
+    Ok(Json(out))
+}
+
+// POST /api/admin/inventory/items
+async fn create_item(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<UpsertInventoryItemRequest>,
+) -> AppResult<Json<InventoryItem>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .inventory_service
+        .create_item(&claims.sub, &tenant_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// PUT /api/admin/inventory/items/{id}
+async fn update_item(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<UpsertInventoryItemRequest>,
+) -> AppResult<Json<InventoryItem>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .inventory_service
+        .update_item(&claims.sub, &tenant_id, &id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// GET /api/admin/inventory/locations
+async fn list_locations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<InventoryLocation>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .inventory_service
+        .list_locations(&claims.sub, &tenant_id)
+        .await?;
+    Ok(Json(out))
+}
+
+// POST /api/admin/inventory/locations
+async fn create_location(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<UpsertInventoryLocationRequest>,
+) -> AppResult<Json<InventoryLocation>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .inventory_service
+        .create_location(&claims.sub, &tenant_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// PUT /api/admin/inventory/locations/{id}
+async fn update_location(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<UpsertInventoryLocationRequest>,
+) -> AppResult<Json<InventoryLocation>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .inventory_service
+        .update_location(&claims.sub, &tenant_id, &id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+#[derive(Debug, Deserialize)]
+struct StockQuery {
+    location_id: Option<String>,
+    item_id: Option<String>,
+}
+
+// GET /api/admin/inventory/stock
+async fn list_stock(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<StockQuery>,
+) -> AppResult<Json<Vec<InventoryStockLevel>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .inventory_service
+        .list_stock(&claims.sub, &tenant_id, q.location_id, q.item_id)
+        .await?;
+    Ok(Json(out))
+}
+
+#[derive(Debug, Deserialize)]
+struct MovementsQuery {
+    item_id: Option<String>,
+    location_id: Option<String>,
+    limit: Option<u32>,
+}
+
+// GET /api/admin/inventory/movements
+async fn list_movements(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<MovementsQuery>,
+) -> AppResult<Json<Vec<InventoryMovement>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .inventory_service
+        .list_movements(
+            &claims.sub,
+            &tenant_id,
+            q.item_id,
+            q.location_id,
+            q.limit.unwrap_or(100),
+        )
+        .await?;
+    Ok(Json(out))
+}
+
+// POST /api/admin/inventory/movements
+async fn create_movement(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<CreateInventoryMovementRequest>,
+) -> AppResult<Json<InventoryMovement>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .inventory_service
+        .create_movement(&claims.sub, &tenant_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+#[derive(Debug, Deserialize)]
+struct ScanQuery {
+    code: String,
+}
+
+// GET /api/admin/inventory/scan?code=
+async fn resolve_scan(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<ScanQuery>,
+) -> AppResult<Json<InventoryScanResult>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .inventory_service
+        .resolve_scan(&claims.sub, &tenant_id, &q.code)
+        .await?;
+    Ok(Json(out))
+}
+
+#[derive(Debug, Deserialize)]
+struct SerialsQuery {
+    item_id: Option<String>,
+    location_id: Option<String>,
+    status: Option<String>,
+}
+
+// GET /api/admin/inventory/serials
+async fn list_serials(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<SerialsQuery>,
+) -> AppResult<Json<Vec<InventorySerial>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .inventory_service
+        .list_serials(&claims.sub, &tenant_id, q.item_id, q.location_id, q.status)
+        .await?;
+    Ok(Json(out))
+}
+
+// POST /api/admin/inventory/serials
+async fn receive_serials(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<ReceiveInventorySerialsRequest>,
+) -> AppResult<Json<InventorySerialBatchResult>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .inventory_service
+        .receive_serials(&claims.sub, &tenant_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// POST /api/admin/inventory/serials/transfer
+async fn transfer_serials(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<TransferInventorySerialsRequest>,
+) -> AppResult<Json<InventorySerialBatchResult>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .inventory_service
+        .transfer_serials(&claims.sub, &tenant_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
@@ -0,0 +1,106 @@
+use crate::error::{AppError, AppResult};
+use crate::http::auth::extract_ip;
+use crate::http::AppState;
+use crate::models::{IpamPlan, IpamSubnet, UpsertIpamSubnetRequest};
+use axum::{
+    extract::{ConnectInfo, Path, State},
+    http::HeaderMap,
+    routing::{get, put},
+    Json, Router,
+};
+use std::net::SocketAddr;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/subnets", get(list_subnets).post(create_subnet))
+        .route("/subnets/{id}", put(update_subnet).delete(delete_subnet))
+        .route("/plan", get(get_plan))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+async fn tenant_and_claims(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> AppResult<(String, crate::services::auth_service::Claims)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    Ok((tenant_id, claims))
+}
+
+// GET /api/admin/ipam/subnets
+async fn list_subnets(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<IpamSubnet>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .ipam_service
+        .list_subnets(&claims.sub, &tenant_id)
+        .await?;
+    Ok(Json(out))
+}
+
+// POST /api/admin/ipam/subnets
+async fn create_subnet(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<UpsertIpamSubnetRequest>,
+) -> AppResult<Json<IpamSubnet>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .ipam_service
+        .create_subnet(&claims.sub, &tenant_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// PUT /api/admin/ipam/subnets/{id}
+async fn update_subnet(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<UpsertIpamSubnetRequest>,
+) -> AppResult<Json<IpamSubnet>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .ipam_service
+        .update_subnet(&claims.sub, &tenant_id, &id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// DELETE /api/admin/ipam/subnets/{id}
+async fn delete_subnet(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    state
+        .ipam_service
+        .delete_subnet(&claims.sub, &tenant_id, &id, Some(&ip))
+        .await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+// GET /api/admin/ipam/plan
+async fn get_plan(State(state): State<AppState>, headers: HeaderMap) -> AppResult<Json<IpamPlan>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state.ipam_service.plan(&claims.sub, &tenant_id).await?;
+    Ok(Json(out))
+}
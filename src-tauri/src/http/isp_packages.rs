@@ -1,13 +1,14 @@
 use crate::error::{AppError, AppResult};
 use crate::http::AppState;
 use crate::models::{
-    CreateIspPackageRequest, IspPackage, IspPackageRouterMapping, IspPackageRouterMappingView,
-    PaginatedResponse, UpdateIspPackageRequest, UpsertIspPackageRouterMappingRequest,
+    CreateIspPackageRequest, IspPackage, IspPackagePriceHistory, IspPackageRouterMapping,
+    IspPackageRouterMappingView, PaginatedResponse, TrashItem, UpdateIspPackageRequest,
+    UpsertIspPackageRouterMappingRequest,
 };
 use axum::{
     extract::{Path, Query, State},
     http::HeaderMap,
-    routing::{get, put},
+    routing::{get, post, put},
     Json, Router,
 };
 use serde::Deserialize;
@@ -15,7 +16,10 @@ use serde::Deserialize;
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/packages", get(list_packages).post(create_package))
+        .route("/packages/trash", get(list_deleted_packages))
         .route("/packages/{id}", put(update_package).delete(delete_package))
+        .route("/packages/{id}/restore", post(restore_package))
+        .route("/packages/{id}/price-history", get(list_price_history))
         .route(
             "/router-mappings",
             get(list_router_mappings).post(upsert_router_mapping),
@@ -115,6 +119,47 @@ async fn delete_package(
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
+// GET /api/admin/isp-packages/packages/trash
+async fn list_deleted_packages(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<TrashItem>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let rows = state
+        .isp_package_service
+        .list_deleted_packages(&claims.sub, &tenant_id)
+        .await?;
+    Ok(Json(rows))
+}
+
+// POST /api/admin/isp-packages/packages/{id}/restore
+async fn restore_package(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .isp_package_service
+        .restore_package(&claims.sub, &tenant_id, &id, None)
+        .await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+// GET /api/admin/isp-packages/packages/{id}/price-history
+async fn list_price_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<IspPackagePriceHistory>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let rows = state
+        .isp_package_service
+        .list_price_history(&claims.sub, &tenant_id, &id)
+        .await?;
+    Ok(Json(rows))
+}
+
 #[derive(Debug, Deserialize)]
 struct ListMappingsQuery {
     router_id: Option<String>,
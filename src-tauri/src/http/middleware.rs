@@ -1,12 +1,13 @@
-//! HTTP Middleware - Rate Limiting, Security, and Metrics
+//! HTTP Middleware - Rate Limiting, Security, Metrics and Error Localization
 //!
-//! Provides middleware layers for rate limiting, security headers, and request metrics.
+//! Provides middleware layers for rate limiting, security headers, request metrics and
+//! translated error messages.
 
 use axum::{
     body::Body,
     extract::ConnectInfo,
     extract::State,
-    http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+    http::{header, HeaderMap, HeaderValue, Method, Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
@@ -16,6 +17,11 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Instant;
 
+use crate::db::{DbPool, TenantIsolation};
+use crate::services::auth_service::{api_key_hash, API_KEY_PREFIX};
+use crate::services::i18n;
+use crate::services::idempotency_service::IdempotencyBegin;
+use crate::services::locale::tenant_locale;
 use crate::services::metrics_service::MetricsService;
 use crate::services::rate_limiter::RateLimiter;
 use crate::{http::AppState, services::rate_limiter::RateLimitInfo};
@@ -60,6 +66,10 @@ async fn rate_limit_key_for_request(
 
     if let Some(tok) = auth_header {
         if let Ok(claims) = state.auth_service.validate_token(tok).await {
+            // Each API key has a budget of its own, apart from its user's sessions.
+            if tok.starts_with(API_KEY_PREFIX) {
+                return Some(format!("key:{}", api_key_hash(tok)));
+            }
             return Some(format!("user:{}", claims.sub));
         }
     }
@@ -98,7 +108,18 @@ fn policy_for_path(path: &str, default_limit: u32) -> (u32, u64) {
         return (30, 60);
     }
 
-    (default_limit.max(10), 60)
+    api_rate_limit(default_limit)
+}
+
+/// `(limit, window_secs)` of regular API routes for the configured
+/// per-minute limit.
+pub(crate) fn api_rate_limit(configured_per_minute: u32) -> (u32, u64) {
+    (configured_per_minute.max(10), 60)
+}
+
+/// Limiter key an API key's requests to regular API routes count against.
+pub(crate) fn api_key_rate_limit_key(key_hash: &str) -> String {
+    format!("api:key:{}", key_hash)
 }
 
 fn is_wallboard_live_path(path: &str) -> bool {
@@ -115,6 +136,8 @@ fn rate_limit_scope(path: &str) -> &'static str {
 
 fn should_bypass_rate_limit(path: &str) -> bool {
     path == "/"
+        || path == "/healthz"
+        || path == "/readyz"
         || path == "/api/version"
         || path == "/api/ws"
         || (path.starts_with("/api/public/") && path != "/api/public/customer-register")
@@ -162,14 +185,12 @@ pub async fn security_enforcer_middleware(
     // Blocked IP check (best-effort).
     let enable_ip_blocking = { state.security_config.read().await.enable_ip_blocking };
     if enable_ip_blocking {
-        if let Some(until) = state.ip_blocklist.read().await.get(&client_ip).copied() {
-            if until > Utc::now() {
-                let body = Json(json!({
-                    "error": "IP temporarily blocked",
-                    "blocked_until": until.to_rfc3339(),
-                }));
-                return (StatusCode::FORBIDDEN, body).into_response();
-            }
+        if let Some(until) = state.rate_limiter.blocked_until(&client_ip).await {
+            let body = Json(json!({
+                "error": "IP temporarily blocked",
+                "blocked_until": until.to_rfc3339(),
+            }));
+            return (StatusCode::FORBIDDEN, body).into_response();
         }
     }
 
@@ -183,7 +204,7 @@ pub async fn security_enforcer_middleware(
         .unwrap_or_else(|| format!("ip:{client_ip}"));
     let scoped_key = format!("{}:{}", rate_limit_scope(&path), key);
 
-    match state.rate_limiter.check(&scoped_key, limit, window) {
+    match state.rate_limiter.check(&scoped_key, limit, window).await {
         Ok(info) => {
             let mut response = next.run(request).await;
             let headers = response.headers_mut();
@@ -207,22 +228,12 @@ pub async fn security_enforcer_middleware(
 
             // Optional IP blocking escalation
             if cfg.enable_ip_blocking {
-                let now = Utc::now();
-                let mut abuse = state.ip_abuse.write().await;
-                let entry = abuse.entry(client_ip.clone()).or_insert((0, now));
-                // Reset the window if old
-                if now - entry.1 > chrono::Duration::minutes(10) {
-                    *entry = (0, now);
-                }
-                entry.0 = entry.0.saturating_add(1);
-                if entry.0 >= cfg.ip_block_threshold {
-                    let until = now + chrono::Duration::minutes(cfg.ip_block_duration_minutes);
-                    state
-                        .ip_blocklist
-                        .write()
-                        .await
-                        .insert(client_ip.clone(), until);
-                }
+                // Strikes count within 10 minutes of the first one.
+                let until = Utc::now() + chrono::Duration::minutes(cfg.ip_block_duration_minutes);
+                state
+                    .rate_limiter
+                    .strike(&client_ip, 10 * 60, cfg.ip_block_threshold, until)
+                    .await;
             }
 
             into_rate_limited_response(info)
@@ -230,27 +241,265 @@ pub async fn security_enforcer_middleware(
     }
 }
 
-/// Extract client IP from request headers or socket address
-pub fn extract_client_ip(headers: &HeaderMap, addr: Option<SocketAddr>) -> String {
-    // Check X-Forwarded-For header first (for proxies/load balancers)
-    if let Some(forwarded) = headers.get("X-Forwarded-For") {
-        if let Ok(s) = forwarded.to_str() {
-            // Take the first IP (original client)
-            if let Some(ip) = s.split(',').next() {
-                return ip.trim().to_string();
+/// Route authenticated requests to their tenant's schema when schema isolation
+/// is enabled. A no-op in shared mode.
+pub async fn tenant_schema_middleware(
+    State(state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if state.tenant_db.mode() != TenantIsolation::Schema {
+        return next.run(request).await;
+    }
+
+    let bearer = request
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+    let tenant_id = match bearer {
+        Some(tok) => state
+            .auth_service
+            .validate_token(tok)
+            .await
+            .ok()
+            .and_then(|claims| claims.tenant_id),
+        None => None,
+    };
+
+    state
+        .tenant_db
+        .scope(tenant_id.as_deref(), next.run(request))
+        .await
+}
+
+/// Max request body buffered for idempotent endpoints (payment proofs are the largest).
+const IDEMPOTENCY_MAX_REQUEST_BYTES: usize = 16 * 1024 * 1024;
+/// Max response body persisted for replay.
+const IDEMPOTENCY_MAX_RESPONSE_BYTES: usize = 2 * 1024 * 1024;
+const IDEMPOTENCY_MAX_KEY_LEN: usize = 255;
+
+/// POST endpoints that honor the `Idempotency-Key` header.
+fn is_idempotent_path(path: &str) -> bool {
+    if matches!(
+        path,
+        "/api/auth/register"
+            | "/api/public/customer-register"
+            | "/api/payment/invoices/plan"
+            | "/api/payment/invoices/customer-package/create"
+            | "/api/payment/invoices/installation/create"
+            | "/api/customers"
+            | "/api/customers/with-portal"
+            | "/api/customers/portal/checkout"
+            | "/api/customers/portal/order-request"
+            | "/api/customers/locations"
+            | "/api/support/tickets"
+            | "/api/admin/work-orders"
+    ) {
+        return true;
+    }
+
+    // Payment submission, plus the ticket replies and work order steps the
+    // desktop client queues while offline and replays with the same key.
+    matches!(
+        item_action(path, "/api/payment/invoices/"),
+        Some("proof" | "midtrans")
+    ) || item_action(path, "/api/support/tickets/") == Some("messages")
+        || matches!(
+            item_action(path, "/api/admin/work-orders/"),
+            Some("start" | "hold" | "complete" | "check-in" | "check-out")
+        )
+}
+
+/// `action` of a `{prefix}{id}/{action}` path.
+fn item_action<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    let mut parts = path.strip_prefix(prefix)?.split('/');
+    let id = parts.next()?;
+    let action = parts.next()?;
+    (!id.is_empty() && parts.next().is_none()).then_some(action)
+}
+
+/// Whether a response is kept and replayed for retries of its key: successes
+/// and client errors a retry would only repeat. Timeouts, throttling and
+/// server errors free the key so the retry runs the request again.
+fn is_replayable(status: StatusCode) -> bool {
+    match status {
+        StatusCode::REQUEST_TIMEOUT
+        | StatusCode::LOCKED
+        | StatusCode::TOO_EARLY
+        | StatusCode::TOO_MANY_REQUESTS => false,
+        s => s.is_success() || s.is_client_error(),
+    }
+}
+
+fn idempotency_error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+/// Idempotency middleware
+///
+/// For selected POST endpoints, a request carrying `Idempotency-Key` is executed at most
+/// once per key (scoped to the caller). Retries within the window replay the stored response.
+pub async fn idempotency_middleware(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if request.method() != Method::POST {
+        return next.run(request).await;
+    }
+
+    let path = request.uri().path().to_string();
+    if !is_idempotent_path(&path) {
+        return next.run(request).await;
+    }
+
+    let key = match request
+        .headers()
+        .get("Idempotency-Key")
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+    {
+        Some(k) => k,
+        None => return next.run(request).await,
+    };
+
+    if key.len() > IDEMPOTENCY_MAX_KEY_LEN {
+        return idempotency_error(StatusCode::BAD_REQUEST, "Idempotency-Key is too long");
+    }
+
+    // Scope keys per user when authenticated, per client IP otherwise.
+    let client_ip = extract_client_ip(request.headers(), Some(addr));
+    let bearer = request
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string());
+    let scope = match bearer {
+        Some(tok) => match state.auth_service.validate_token(&tok).await {
+            Ok(claims) => format!("user:{}", claims.sub),
+            Err(_) => format!("ip:{client_ip}"),
+        },
+        None => format!("ip:{client_ip}"),
+    };
+
+    let (parts, body) = request.into_parts();
+    let body_bytes = match axum::body::to_bytes(body, IDEMPOTENCY_MAX_REQUEST_BYTES).await {
+        Ok(b) => b,
+        Err(_) => {
+            return idempotency_error(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "Request body too large for idempotent processing",
+            )
+        }
+    };
+
+    let method = parts.method.as_str().to_string();
+    let request_hash =
+        crate::services::IdempotencyService::fingerprint(&method, &path, &body_bytes);
+    let window_hours = { state.security_config.read().await.idempotency_window_hours };
+
+    let begin = state
+        .idempotency_service
+        .begin(&scope, &key, &method, &path, &request_hash, window_hours)
+        .await;
+
+    let reservation_id = match begin {
+        Ok(IdempotencyBegin::Started(id)) => id,
+        Ok(IdempotencyBegin::Replay(stored)) => {
+            let status = StatusCode::from_u16(stored.status_code).unwrap_or(StatusCode::OK);
+            let mut response = Response::new(Body::from(stored.body));
+            *response.status_mut() = status;
+            let headers = response.headers_mut();
+            if let Some(ct) = stored
+                .content_type
+                .as_deref()
+                .and_then(|v| HeaderValue::from_str(v).ok())
+            {
+                headers.insert(header::CONTENT_TYPE, ct);
             }
+            headers.insert("Idempotent-Replayed", HeaderValue::from_static("true"));
+            return response;
+        }
+        Ok(IdempotencyBegin::InProgress) => {
+            return idempotency_error(
+                StatusCode::CONFLICT,
+                "A request with this Idempotency-Key is already being processed",
+            )
+        }
+        Ok(IdempotencyBegin::Mismatch) => {
+            return idempotency_error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Idempotency-Key was already used with a different request",
+            )
+        }
+        Err(e) => {
+            // Degrade gracefully: better to process than to reject legitimate traffic.
+            tracing::warn!(
+                "Idempotency reservation failed, processing without it: {}",
+                e
+            );
+            return next
+                .run(Request::from_parts(parts, Body::from(body_bytes)))
+                .await;
         }
+    };
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(body_bytes)))
+        .await;
+
+    if !is_replayable(response.status()) {
+        if let Err(e) = state.idempotency_service.release(&reservation_id).await {
+            tracing::warn!("Failed to release idempotency key: {}", e);
+        }
+        return response;
     }
 
-    // Check X-Real-IP header (used by nginx)
-    if let Some(real_ip) = headers.get("X-Real-IP") {
-        if let Ok(s) = real_ip.to_str() {
-            return s.trim().to_string();
+    let (res_parts, res_body) = response.into_parts();
+    let res_bytes = match axum::body::to_bytes(res_body, IDEMPOTENCY_MAX_RESPONSE_BYTES).await {
+        Ok(b) => b,
+        Err(e) => {
+            tracing::warn!("Failed to buffer idempotent response: {}", e);
+            let _ = state.idempotency_service.release(&reservation_id).await;
+            return idempotency_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to read response body",
+            );
         }
+    };
+
+    let content_type = res_parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok());
+    if let Err(e) = state
+        .idempotency_service
+        .complete(
+            &reservation_id,
+            res_parts.status.as_u16(),
+            content_type,
+            &res_bytes,
+        )
+        .await
+    {
+        tracing::warn!("Failed to persist idempotent response: {}", e);
+        let _ = state.idempotency_service.release(&reservation_id).await;
     }
 
-    // Fall back to socket address
-    addr.map(|a| a.ip().to_string())
+    Response::from_parts(res_parts, Body::from(res_bytes))
+}
+
+/// Extract client IP from request headers or socket address.
+///
+/// Forwarding headers are only honored when the peer is a trusted proxy
+/// (see `security::trusted_proxy`).
+pub fn extract_client_ip(headers: &HeaderMap, addr: Option<SocketAddr>) -> String {
+    crate::security::trusted_proxy::resolve_client_ip(headers, addr)
+        .map(|ip| ip.to_string())
         .unwrap_or_else(|| "unknown".to_string())
 }
 
@@ -269,6 +518,7 @@ pub async fn rate_limit_middleware(
     match config
         .limiter
         .check(&client_ip, config.limit, config.window_secs)
+        .await
     {
         Ok(info) => {
             // Request allowed - add rate limit headers and continue
@@ -384,3 +634,118 @@ pub async fn metrics_middleware(request: Request<Body>, next: Next) -> Response
 
     response
 }
+
+/// English message of an `AppError` response, kept so it can be translated
+/// after the handler ran.
+#[derive(Clone)]
+pub struct ErrorMessage(pub String);
+
+/// Error localization middleware
+///
+/// Rewrites `{"error": ...}` bodies of `AppError` responses in the caller's
+/// language: their own locale, else `Accept-Language`, else the tenant or
+/// platform `default_locale`. Messages missing from the catalog stay English.
+pub async fn localize_errors_middleware(
+    State((state, pool)): State<(AppState, DbPool)>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let accepted = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|h| h.to_str().ok())
+        .and_then(i18n::language_from_accept);
+    let bearer = request
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(str::to_string);
+
+    let mut response = next.run(request).await;
+    let Some(ErrorMessage(message)) = response.extensions_mut().remove::<ErrorMessage>() else {
+        return response;
+    };
+
+    let claims = match bearer {
+        Some(tok) => state.auth_service.validate_token(&tok).await.ok(),
+        None => None,
+    };
+    #[cfg(feature = "postgres")]
+    let user_locale = match &claims {
+        Some(claims) => {
+            crate::services::locale::reader_locale(&pool, &claims.sub, claims.tenant_id.as_deref())
+                .await
+                .unwrap_or_default()
+        }
+        None => None,
+    };
+    #[cfg(not(feature = "postgres"))]
+    let user_locale: Option<String> = None;
+    let locale = match user_locale.or_else(|| accepted.map(str::to_string)) {
+        Some(locale) => Some(locale),
+        None => tenant_locale(&pool, claims.and_then(|c| c.tenant_id).as_deref()).await,
+    };
+
+    let translated = i18n::translate(locale.as_deref(), &message);
+    if translated == message {
+        return response;
+    }
+    let (mut parts, _) = response.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(
+        parts,
+        Json(json!({ "error": translated }))
+            .into_response()
+            .into_body(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_errors_keep_their_message_for_localization() {
+        let response =
+            crate::error::AppError::NotFound("Invoice not found".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            response
+                .extensions()
+                .get::<ErrorMessage>()
+                .map(|m| m.0.as_str()),
+            Some("Invoice not found")
+        );
+    }
+
+    #[test]
+    fn idempotent_paths() {
+        assert!(is_idempotent_path("/api/customers"));
+        assert!(is_idempotent_path("/api/payment/invoices/inv-1/proof"));
+        assert!(is_idempotent_path("/api/support/tickets/t-1/messages"));
+        assert!(is_idempotent_path("/api/admin/work-orders/wo-1/check-in"));
+
+        assert!(!is_idempotent_path(
+            "/api/payment/invoices/inv-1/proof/extra"
+        ));
+        assert!(!is_idempotent_path("/api/payment/invoices//proof"));
+        assert!(!is_idempotent_path("/api/admin/work-orders/wo-1/assign"));
+        assert!(!is_idempotent_path("/api/support/tickets/t-1"));
+    }
+
+    #[test]
+    fn only_final_responses_are_replayed() {
+        assert!(is_replayable(StatusCode::OK));
+        assert!(is_replayable(StatusCode::CREATED));
+        assert!(is_replayable(StatusCode::BAD_REQUEST));
+        assert!(is_replayable(StatusCode::NOT_FOUND));
+        assert!(is_replayable(StatusCode::UNPROCESSABLE_ENTITY));
+
+        assert!(!is_replayable(StatusCode::REQUEST_TIMEOUT));
+        assert!(!is_replayable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_replayable(StatusCode::FOUND));
+        assert!(!is_replayable(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_replayable(StatusCode::SERVICE_UNAVAILABLE));
+    }
+}
@@ -1,11 +1,12 @@
 use crate::error::{AppError, AppResult};
 use crate::http::AppState;
 use crate::models::{
-    CreateMikrotikRouterRequest, MikrotikAlert, MikrotikIncident, MikrotikInterfaceCounter,
-    MikrotikInterfaceMetric, MikrotikIpPool, MikrotikLogEntry, MikrotikLogSyncResult,
-    MikrotikPppProfile, MikrotikRouter, MikrotikRouterMetric, MikrotikTestResult,
-    PaginatedResponse, SimulateMikrotikIncidentRequest, UpdateMikrotikIncidentRequest,
-    UpdateMikrotikRouterRequest,
+    CreateMikrotikRouterRequest, CreateMonitoredDeviceRequest, MikrotikAlert, MikrotikIncident,
+    MikrotikIncidentImpact, MikrotikInterfaceCounter, MikrotikInterfaceMetric, MikrotikIpPool,
+    MikrotikLogEntry, MikrotikLogSyncResult, MikrotikPppProfile, MikrotikRouter,
+    MikrotikRouterMetric, MikrotikTestResult, MonitoredDevice, MonitoredDeviceTestResult,
+    PaginatedResponse, SimulateMikrotikIncidentRequest, TrashItem, UpdateMikrotikIncidentRequest,
+    UpdateMikrotikRouterRequest, UpdateMonitoredDeviceRequest,
 };
 use axum::{
     extract::{Path, Query, State},
@@ -30,12 +31,25 @@ pub fn router() -> Router<AppState> {
         .route("/incidents/{id}", put(update_incident))
         .route("/incidents/{id}/ack", post(ack_incident))
         .route("/incidents/{id}/resolve", post(resolve_incident))
+        .route("/incidents/{id}/impact", get(get_incident_impact))
+        .route(
+            "/incidents/{id}/impact/refresh",
+            post(refresh_incident_impact),
+        )
         .route("/logs", get(list_logs))
+        .route("/devices", get(list_devices).post(create_device))
+        .route(
+            "/devices/{id}",
+            get(get_device).put(update_device).delete(delete_device),
+        )
+        .route("/devices/{id}/test", post(test_device))
         .route("/routers", get(list_routers).post(create_router))
+        .route("/routers/trash", get(list_deleted_routers))
         .route(
             "/routers/{id}",
             get(get_router).put(update_router).delete(delete_router),
         )
+        .route("/routers/{id}/restore", post(restore_router))
         .route("/routers/{id}/logs/sync", post(sync_logs))
         .route("/routers/{id}/ppp-profiles", get(list_ppp_profiles))
         .route("/routers/{id}/ppp-profiles/sync", post(sync_ppp_profiles))
@@ -80,6 +94,14 @@ struct AlertsQuery {
     limit: Option<u32>,
 }
 
+#[derive(Debug, Deserialize)]
+struct IncidentsQuery {
+    active_only: Option<bool>,
+    limit: Option<u32>,
+    /// `impact` puts the most affected subscriptions first.
+    sort: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct LogsQuery {
     router_id: Option<String>,
@@ -159,11 +181,11 @@ async fn resolve_alert(
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
-// GET /api/admin/mikrotik/incidents?active_only=true&limit=200
+// GET /api/admin/mikrotik/incidents?active_only=true&limit=200&sort=impact
 async fn list_incidents(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Query(q): Query<AlertsQuery>,
+    Query(q): Query<IncidentsQuery>,
 ) -> AppResult<Json<Vec<MikrotikIncident>>> {
     let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
     state
@@ -176,6 +198,7 @@ async fn list_incidents(
         .list_incidents(
             &tenant_id,
             q.active_only.unwrap_or(true),
+            q.sort.as_deref() == Some("impact"),
             q.limit.unwrap_or(200),
         )
         .await?;
@@ -290,6 +313,44 @@ async fn resolve_incident(
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
+// GET /api/admin/mikrotik/incidents/{id}/impact
+async fn get_incident_impact(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<MikrotikIncidentImpact>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "network_routers", "read")
+        .await?;
+
+    let rows = state
+        .mikrotik_service
+        .list_incident_impact(&tenant_id, &id)
+        .await?;
+    Ok(Json(rows))
+}
+
+// POST /api/admin/mikrotik/incidents/{id}/impact/refresh
+async fn refresh_incident_impact(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<MikrotikIncident>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "network_routers", "manage")
+        .await?;
+
+    let row = state
+        .mikrotik_service
+        .refresh_incident_impact(&tenant_id, &id)
+        .await?;
+    Ok(Json(row))
+}
+
 // GET /api/admin/mikrotik/routers
 async fn list_routers(
     State(state): State<AppState>,
@@ -518,14 +579,14 @@ async fn update_router(
         .check_permission(&claims.sub, &tenant_id, "network_routers", "manage")
         .await?;
 
-    let router = state
+    let (router, change) = state
         .mikrotik_service
         .update_router(&tenant_id, &id, payload)
         .await?;
 
     state
         .audit_service
-        .log(
+        .log_change(
             Some(&claims.sub),
             Some(&tenant_id),
             "update",
@@ -536,6 +597,7 @@ async fn update_router(
                 router.name, router.host
             )),
             None,
+            &change,
         )
         .await;
     Ok(Json(router))
@@ -577,6 +639,56 @@ async fn delete_router(
     Ok(())
 }
 
+// GET /api/admin/mikrotik/routers/trash
+async fn list_deleted_routers(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<TrashItem>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "network_routers", "manage")
+        .await?;
+    let rows = state
+        .mikrotik_service
+        .list_deleted_routers(&tenant_id)
+        .await?;
+    Ok(Json(rows))
+}
+
+// POST /api/admin/mikrotik/routers/{id}/restore
+async fn restore_router(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<MikrotikRouter>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "network_routers", "manage")
+        .await?;
+
+    let router = state
+        .mikrotik_service
+        .restore_router(&tenant_id, &id)
+        .await?;
+
+    let details = format!("Restored router '{}' ({})", router.name, router.host);
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "restore",
+            "mikrotik_router",
+            Some(&id),
+            Some(&details),
+            None,
+        )
+        .await;
+    Ok(Json(router))
+}
+
 // POST /api/admin/mikrotik/routers/{id}/test
 async fn test_router(
     State(state): State<AppState>,
@@ -740,3 +852,182 @@ async fn get_snapshot(
     let snap = state.mikrotik_service.get_snapshot(&tenant_id, &id).await?;
     Ok(Json(snap))
 }
+
+// GET /api/admin/mikrotik/devices
+async fn list_devices(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<MonitoredDevice>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "network_routers", "read")
+        .await?;
+
+    let rows = state.mikrotik_service.list_devices(&tenant_id).await?;
+    Ok(Json(rows))
+}
+
+// GET /api/admin/mikrotik/devices/{id}
+async fn get_device(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<MonitoredDevice>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "network_routers", "read")
+        .await?;
+
+    let device = state
+        .mikrotik_service
+        .get_device(&tenant_id, &id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Device not found".to_string()))?;
+    Ok(Json(device))
+}
+
+// POST /api/admin/mikrotik/devices
+async fn create_device(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateMonitoredDeviceRequest>,
+) -> AppResult<Json<MonitoredDevice>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "network_routers", "manage")
+        .await?;
+
+    let device = state
+        .mikrotik_service
+        .create_device(&tenant_id, payload)
+        .await?;
+
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "create",
+            "monitored_device",
+            Some(&device.id),
+            Some(&format!(
+                "Created {} device '{}' ({})",
+                device.protocol, device.name, device.host
+            )),
+            None,
+        )
+        .await;
+    Ok(Json(device))
+}
+
+// PUT /api/admin/mikrotik/devices/{id}
+async fn update_device(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateMonitoredDeviceRequest>,
+) -> AppResult<Json<MonitoredDevice>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "network_routers", "manage")
+        .await?;
+
+    let (device, change) = state
+        .mikrotik_service
+        .update_device(&tenant_id, &id, payload)
+        .await?;
+
+    state
+        .audit_service
+        .log_change(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "update",
+            "monitored_device",
+            Some(&device.id),
+            Some(&format!(
+                "Updated device '{}' ({})",
+                device.name, device.host
+            )),
+            None,
+            &change,
+        )
+        .await;
+    Ok(Json(device))
+}
+
+// DELETE /api/admin/mikrotik/devices/{id}
+async fn delete_device(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<()> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "network_routers", "manage")
+        .await?;
+
+    let device = state
+        .mikrotik_service
+        .delete_device(&tenant_id, &id)
+        .await?;
+
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "delete",
+            "monitored_device",
+            Some(&id),
+            Some(&format!(
+                "Deleted device '{}' ({})",
+                device.name, device.host
+            )),
+            None,
+        )
+        .await;
+    Ok(())
+}
+
+// POST /api/admin/mikrotik/devices/{id}/test
+async fn test_device(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<MonitoredDeviceTestResult>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "network_routers", "read")
+        .await?;
+
+    let res = state.mikrotik_service.test_device(&tenant_id, &id).await?;
+
+    let details = if res.ok {
+        format!(
+            "Tested device: ok name={:?} latency_ms={:?}",
+            res.sys_name, res.latency_ms
+        )
+    } else {
+        format!("Tested device: failed error={:?}", res.error)
+    };
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "test_connection",
+            "monitored_device",
+            Some(&id),
+            Some(&details),
+            None,
+        )
+        .await;
+    Ok(Json(res))
+}
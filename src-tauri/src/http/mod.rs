@@ -10,62 +10,118 @@ use axum::{
     Router,
 };
 use std::env;
-use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::net::TcpListener;
 use tokio::sync::RwLock as TokioRwLock;
 use tower_http::{
     cors::{AllowOrigin, Any, CorsLayer},
     timeout::TimeoutLayer,
 };
-use tracing::info;
 
 use std::path::PathBuf;
-use std::{collections::HashMap, time::Instant};
+use std::time::Instant;
 
 pub mod announcements;
 pub mod audit;
 pub mod auth;
 pub mod backup;
+pub mod binding;
+pub mod cgnat;
 pub mod customers;
+pub mod email_dkim;
 pub mod email_outbox;
+pub mod email_suppressions;
+pub mod email_templates;
+pub mod feature_flags;
+pub mod field_sync;
 pub mod install;
+pub mod integrations;
+pub mod inventory;
+pub mod ipam;
 pub mod isp_packages;
+pub mod legacy_import;
 pub mod middleware;
 pub mod mikrotik;
 pub mod network_mapping;
+pub mod notification_routing;
+pub mod notification_templates;
 pub mod notifications;
+pub mod onboarding;
 pub mod payment;
 pub mod plans;
 pub mod pppoe;
 pub mod public;
+pub mod report_schedules;
 pub mod roles;
 pub mod settings;
+pub mod status_page;
 pub mod storage;
 pub mod superadmin;
 pub mod support;
 pub mod system;
 pub mod team;
+pub mod telegram;
 pub mod tenant;
+pub mod uplink_capacity;
 pub mod users;
 pub mod websocket;
+pub mod whatsapp;
 pub mod work_orders;
 
+pub use binding::{ServerBinding, ServerControl};
 pub use websocket::{WsEvent, WsHub};
 
-type IpBlockMap = HashMap<String, chrono::DateTime<chrono::Utc>>;
-type IpAbuseMap = HashMap<String, (u32, chrono::DateTime<chrono::Utc>)>;
-
 #[derive(Clone, Debug)]
 pub struct SecurityRuntimeConfig {
     pub api_rate_limit_per_minute: u32,
     pub enable_ip_blocking: bool,
     pub ip_block_threshold: u32,
     pub ip_block_duration_minutes: i64,
+    pub idempotency_window_hours: i64,
     pub refreshed_at: Instant,
 }
 
+/// Request body limits and timeouts per route group.
+#[derive(Clone, Debug)]
+pub struct HttpLimits {
+    pub api_body_bytes: usize,
+    pub api_timeout: Duration,
+    pub logo_body_bytes: usize,
+    pub upload_body_bytes: usize,
+    pub upload_timeout: Duration,
+}
+
+impl HttpLimits {
+    /// Read limits from env, falling back to defaults:
+    /// - HTTP_API_BODY_LIMIT_MB (2), HTTP_API_TIMEOUT_SECS (30)
+    /// - HTTP_LOGO_BODY_LIMIT_MB (10)
+    /// - HTTP_UPLOAD_BODY_LIMIT_MB (1024), HTTP_UPLOAD_TIMEOUT_SECS (3600)
+    pub fn from_env() -> Self {
+        fn env_u64(key: &str, default: u64, min: u64, max: u64) -> u64 {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(|v| v.clamp(min, max))
+                .unwrap_or(default)
+        }
+        const MB: usize = 1024 * 1024;
+
+        Self {
+            api_body_bytes: env_u64("HTTP_API_BODY_LIMIT_MB", 2, 1, 100) as usize * MB,
+            api_timeout: Duration::from_secs(env_u64("HTTP_API_TIMEOUT_SECS", 30, 5, 600)),
+            logo_body_bytes: env_u64("HTTP_LOGO_BODY_LIMIT_MB", 10, 1, 100) as usize * MB,
+            upload_body_bytes: env_u64("HTTP_UPLOAD_BODY_LIMIT_MB", 1024, 1, 10 * 1024) as usize
+                * MB,
+            upload_timeout: Duration::from_secs(env_u64(
+                "HTTP_UPLOAD_TIMEOUT_SECS",
+                3600,
+                60,
+                24 * 3600,
+            )),
+        }
+    }
+}
+
 // App State to share services with Axum handlers
 #[derive(Clone)]
 #[allow(dead_code)]
@@ -79,7 +135,26 @@ pub struct AppState {
     pub role_service: Arc<RoleService>,
     pub system_service: Arc<SystemService>,
     pub plan_service: Arc<PlanService>,
+    pub usage_service: Arc<crate::services::UsageService>,
+    pub tenant_analytics: Arc<crate::services::TenantAnalyticsService>,
+    pub feature_flags: Arc<crate::services::FeatureFlagService>,
     pub storage_service: Arc<StorageService>,
+    pub storage_policies: Arc<crate::services::StoragePolicyService>,
+    pub support_inbound: Arc<crate::services::SupportInboundService>,
+    pub support_routing: Arc<crate::services::SupportRoutingService>,
+    pub support_macros: Arc<crate::services::SupportMacroService>,
+    pub support_links: Arc<crate::services::SupportLinkService>,
+    pub support_escalation: Arc<crate::services::SupportEscalationService>,
+    pub work_order_checklists: Arc<crate::services::WorkOrderChecklistService>,
+    pub inventory_service: Arc<crate::services::InventoryService>,
+    pub ipam_service: Arc<crate::services::IpamService>,
+    pub cgnat_service: Arc<crate::services::CgnatService>,
+    pub legacy_import_service: Arc<crate::services::LegacyImportService>,
+    pub integration_service: Arc<crate::services::IntegrationService>,
+    pub onboarding_service: Arc<crate::services::OnboardingService>,
+    pub uplink_capacity_service: Arc<crate::services::UplinkCapacityService>,
+    pub field_sync_service: Arc<crate::services::FieldSyncService>,
+    pub completion_reports: Arc<crate::services::CompletionReportService>,
     pub payment_service: Arc<PaymentService>,
     pub notification_service: Arc<NotificationService>,
     pub mikrotik_service: Arc<MikrotikService>,
@@ -88,13 +163,21 @@ pub struct AppState {
     pub isp_package_service: Arc<IspPackageService>,
     pub network_mapping_service: Arc<NetworkMappingService>,
     pub backup_service: Arc<crate::services::BackupService>,
+    pub idempotency_service: Arc<crate::services::IdempotencyService>,
+    pub tenant_db: Arc<crate::db::TenantDbRouter>,
+    pub tenant_transfer_service: Arc<crate::services::TenantTransferService>,
+    pub db_maintenance_service: Arc<crate::services::DbMaintenanceService>,
+    pub event_outbox: Arc<crate::services::EventOutboxService>,
+    pub job_queue: Arc<crate::services::JobQueue>,
+    pub alert_service: Arc<crate::services::AlertService>,
+    pub status_page: Arc<crate::services::StatusPageService>,
+    pub report_schedules: Arc<crate::services::ReportScheduleService>,
+    pub telegram_bot: Option<Arc<crate::services::TelegramBot>>,
     pub ws_hub: Arc<WsHub>,
     pub app_data_dir: PathBuf,
     pub rate_limiter: Arc<crate::services::rate_limiter::RateLimiter>,
     pub metrics_service: Arc<crate::services::metrics_service::MetricsService>,
     pub security_config: Arc<TokioRwLock<SecurityRuntimeConfig>>,
-    pub ip_blocklist: Arc<TokioRwLock<IpBlockMap>>,
-    pub ip_abuse: Arc<TokioRwLock<IpAbuseMap>>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -117,22 +200,26 @@ pub async fn start_server(
     isp_package_service: IspPackageService,
     network_mapping_service: NetworkMappingService,
     backup_service: crate::services::BackupService,
+    db_maintenance_service: crate::services::DbMaintenanceService,
+    event_outbox: crate::services::EventOutboxService,
+    job_queue: crate::services::JobQueue,
+    feature_flags: crate::services::FeatureFlagService,
     ws_hub: Arc<WsHub>,
     app_data_dir: PathBuf,
-    default_port: u16,
+    server_control: Arc<ServerControl>,
     pool: crate::db::DbPool,
     metrics_service: Arc<crate::services::metrics_service::MetricsService>,
 ) {
-    // Initialize rate limiter
-    let rate_limiter = Arc::new(crate::services::rate_limiter::RateLimiter::default());
+    // Initialize rate limiter (shared through Redis when REDIS_URL is set)
+    let rate_limiter = Arc::new(crate::services::rate_limiter::RateLimiter::from_env());
 
-    // Spawn background task to cleanup expired rate limit entries every minute
+    // Spawn background task to cleanup expired rate limit entries and IP blocks every minute
     let cleanup_limiter = rate_limiter.clone();
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(Duration::from_secs(60));
         loop {
             interval.tick().await;
-            cleanup_limiter.cleanup();
+            cleanup_limiter.cleanup().await;
         }
     });
 
@@ -141,10 +228,9 @@ pub async fn start_server(
         enable_ip_blocking: false,
         ip_block_threshold: 5,
         ip_block_duration_minutes: 15,
+        idempotency_window_hours: 24,
         refreshed_at: Instant::now(),
     }));
-    let ip_blocklist: Arc<TokioRwLock<IpBlockMap>> = Arc::new(TokioRwLock::new(HashMap::new()));
-    let ip_abuse: Arc<TokioRwLock<IpAbuseMap>> = Arc::new(TokioRwLock::new(HashMap::new()));
 
     // Refresh security config from DB every 30 seconds (best-effort, cached).
     {
@@ -190,42 +276,184 @@ pub async fn start_server(
                     .filter(|v| *v >= 1 && *v <= 24 * 60)
                     .unwrap_or(15);
 
+                let idempotency_window_hours = settings
+                    .get_value(None, "idempotency_window_hours")
+                    .await
+                    .ok()
+                    .flatten()
+                    .and_then(|s| s.parse::<i64>().ok())
+                    .filter(|v| *v >= 1 && *v <= 24 * 7)
+                    .unwrap_or(24);
+
+                // Trusted proxy ranges: setting overrides TRUSTED_PROXIES env.
+                let trusted_proxies = settings
+                    .get_value(None, "trusted_proxies")
+                    .await
+                    .ok()
+                    .flatten()
+                    .filter(|s| !s.trim().is_empty())
+                    .map(|s| crate::security::trusted_proxy::TrustedProxies::parse(&s))
+                    .filter(|p| !p.is_empty())
+                    .unwrap_or_else(crate::security::trusted_proxy::TrustedProxies::from_env);
+                crate::security::trusted_proxy::configure(trusted_proxies);
+
                 let mut lock = cfg.write().await;
                 lock.api_rate_limit_per_minute = api_rate;
                 lock.enable_ip_blocking = enable_ip_blocking;
                 lock.ip_block_threshold = ip_block_threshold;
                 lock.ip_block_duration_minutes = ip_block_duration_minutes;
+                lock.idempotency_window_hours = idempotency_window_hours;
                 lock.refreshed_at = Instant::now();
             }
         });
     }
 
-    // Cleanup IP blocklist periodically
+    // Purge expired idempotency keys hourly
+    let idempotency_service = Arc::new(crate::services::IdempotencyService::new(pool.clone()));
+    {
+        let svc = idempotency_service.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                match svc.purge_expired().await {
+                    Ok(n) if n > 0 => tracing::debug!("Purged {} expired idempotency keys", n),
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Failed to purge idempotency keys: {}", e),
+                }
+            }
+        });
+    }
+
+    // Per-tenant schema routing (DB_TENANT_ISOLATION=schema). The registry is
+    // reloaded periodically so tenants isolated on another instance are picked up.
+    let tenant_db = Arc::new(crate::db::TenantDbRouter::new(
+        pool.clone(),
+        crate::db::TenantIsolation::from_env(),
+    ));
+    if tenant_db.mode() == crate::db::TenantIsolation::Schema {
+        let router = tenant_db.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                if let Err(e) = router.reload().await {
+                    tracing::warn!("Failed to reload tenant schema registry: {}", e);
+                }
+            }
+        });
+    }
+
+    // System alert rules. Request metrics are per instance, so every instance
+    // runs its own check rather than a queued job.
+    let alert_service = Arc::new(crate::services::AlertService::new(
+        pool.clone(),
+        email_service.clone(),
+        settings_service.clone(),
+        notification_service.telegram().cloned(),
+        metrics_service.clone(),
+        app_data_dir.clone(),
+    ));
     {
-        let bl = ip_blocklist.clone();
+        let alerts = alert_service.clone();
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(60));
             loop {
                 interval.tick().await;
-                let now = chrono::Utc::now();
-                bl.write().await.retain(|_, until| *until > now);
+                alerts.check().await;
             }
         });
     }
 
-    // Initialize and spawn AlertService for error alerting via email
-    let alert_service =
-        crate::services::AlertService::new(email_service.clone(), settings_service.clone());
-    let alert_metrics = metrics_service.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_secs(60));
-        loop {
-            interval.tick().await;
-            let metrics = alert_metrics.get_metrics();
-            alert_service.check_and_alert(&metrics).await;
-        }
+    let status_page = Arc::new(crate::services::StatusPageService::new(
+        pool.clone(),
+        settings_service.clone(),
+    ));
+
+    let report_schedules = crate::services::ReportScheduleService::new(
+        pool.clone(),
+        notification_service.email_outbox().clone(),
+    );
+    report_schedules.clone().schedule(&job_queue).await;
+    let report_schedules = Arc::new(report_schedules);
+
+    // Telegram bot commands act on routers and incidents, so the bot is assembled
+    // here where the router monitor is available.
+    let telegram_bot = notification_service.telegram().cloned().map(|telegram| {
+        let bot = crate::services::TelegramBot::new(
+            telegram,
+            auth_service.clone(),
+            mikrotik_service.clone(),
+        );
+        bot.start_polling();
+        Arc::new(bot)
     });
 
+    let support_escalation = Arc::new(crate::services::SupportEscalationService::new(
+        pool.clone(),
+        settings_service.clone(),
+        notification_service.clone(),
+    ));
+
+    let inventory_service = Arc::new(crate::services::InventoryService::new(
+        pool.clone(),
+        auth_service.clone(),
+        audit_service.clone(),
+        notification_service.clone(),
+    ));
+
+    let ipam_service = Arc::new(crate::services::IpamService::new(
+        pool.clone(),
+        auth_service.clone(),
+        audit_service.clone(),
+    ));
+
+    let cgnat_service = Arc::new(crate::services::CgnatService::new(
+        pool.clone(),
+        auth_service.clone(),
+        audit_service.clone(),
+    ));
+
+    let legacy_import_service = Arc::new(crate::services::LegacyImportService::new(
+        pool.clone(),
+        auth_service.clone(),
+        audit_service.clone(),
+    ));
+
+    let integration_service = Arc::new(crate::services::IntegrationService::new(
+        pool.clone(),
+        auth_service.clone(),
+        audit_service.clone(),
+        rate_limiter.clone(),
+    ));
+
+    let onboarding_service = Arc::new(crate::services::OnboardingService::new(
+        pool.clone(),
+        auth_service.clone(),
+        audit_service.clone(),
+    ));
+
+    let uplink_capacity_service = Arc::new(crate::services::UplinkCapacityService::new(
+        pool.clone(),
+        auth_service.clone(),
+        audit_service.clone(),
+    ));
+
+    let field_sync_service = Arc::new(crate::services::FieldSyncService::new(
+        pool.clone(),
+        auth_service.clone(),
+        customer_service.clone(),
+    ));
+
+    let completion_reports = Arc::new(crate::services::CompletionReportService::new(
+        pool.clone(),
+        auth_service.clone(),
+        audit_service.clone(),
+        customer_service.clone(),
+        storage_service.clone(),
+        email_service.clone(),
+    ));
+
     let state = AppState {
         auth_service: Arc::new(auth_service),
         user_service: Arc::new(user_service),
@@ -236,6 +464,35 @@ pub async fn start_server(
         role_service: Arc::new(role_service),
         system_service: Arc::new(system_service),
         plan_service: Arc::new(plan_service.clone()),
+        usage_service: Arc::new(crate::services::UsageService::new(pool.clone())),
+        tenant_analytics: Arc::new(crate::services::TenantAnalyticsService::new(pool.clone())),
+        feature_flags: Arc::new(feature_flags),
+        support_inbound: Arc::new(crate::services::SupportInboundService::new(
+            pool.clone(),
+            storage_service.clone(),
+            (*support_escalation).clone(),
+        )),
+        support_routing: Arc::new(crate::services::SupportRoutingService::new(pool.clone())),
+        support_macros: Arc::new(crate::services::SupportMacroService::new(pool.clone())),
+        support_links: Arc::new(crate::services::SupportLinkService::new(pool.clone())),
+        support_escalation,
+        work_order_checklists: Arc::new(crate::services::WorkOrderChecklistService::new(
+            pool.clone(),
+        )),
+        inventory_service,
+        ipam_service,
+        cgnat_service,
+        legacy_import_service,
+        integration_service,
+        onboarding_service,
+        uplink_capacity_service,
+        field_sync_service,
+        completion_reports,
+        storage_policies: Arc::new(crate::services::StoragePolicyService::new(
+            pool.clone(),
+            storage_service.clone(),
+            notification_service.clone(),
+        )),
         storage_service: Arc::new(storage_service),
         payment_service: Arc::new(payment_service.clone()),
         notification_service: Arc::new(notification_service),
@@ -244,14 +501,25 @@ pub async fn start_server(
         pppoe_service: Arc::new(pppoe_service),
         isp_package_service: Arc::new(isp_package_service),
         network_mapping_service: Arc::new(network_mapping_service),
+        tenant_transfer_service: Arc::new(crate::services::TenantTransferService::new(
+            pool.clone(),
+            backup_service.clone(),
+        )),
         backup_service: Arc::new(backup_service),
+        idempotency_service,
+        tenant_db,
+        db_maintenance_service: Arc::new(db_maintenance_service),
+        event_outbox: Arc::new(event_outbox),
+        job_queue: Arc::new(job_queue),
+        alert_service,
+        status_page,
+        report_schedules,
+        telegram_bot,
         ws_hub,
         app_data_dir,
         rate_limiter,
         metrics_service,
         security_config,
-        ip_blocklist,
-        ip_abuse,
     };
 
     // --- Dynamic CORS Implementation ---
@@ -281,10 +549,32 @@ pub async fn start_server(
 
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(Duration::from_secs(30));
-        let mut warned_missing_schema = false;
+        let mut schema_ready = false;
+        let mut warned_pending_schema = false;
         loop {
             interval.tick().await;
 
+            // Only query tenants once the schema is known to be fully migrated.
+            if !schema_ready {
+                match crate::db::migrations::migration_status(&pool_for_task).await {
+                    Ok(status) if status.up_to_date => schema_ready = true,
+                    Ok(status) => {
+                        if !warned_pending_schema {
+                            warned_pending_schema = true;
+                            tracing::warn!(
+                                "CORS domain refresh skipped: {} pending migration(s).",
+                                status.pending.len()
+                            );
+                        }
+                        continue;
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to read migration status for CORS refresh: {}", e);
+                        continue;
+                    }
+                }
+            }
+
             // Re-fetch custom domains
             let rows: Result<Vec<(String,)>, _> = sqlx::query_as("SELECT custom_domain FROM tenants WHERE custom_domain IS NOT NULL AND custom_domain != ''")
                 .fetch_all(&pool_for_task)
@@ -292,7 +582,6 @@ pub async fn start_server(
 
             match rows {
                 Ok(domains) => {
-                    warned_missing_schema = false;
                     let mut new_custom_domains = static_origins_for_task.clone();
                     for (d,) in domains {
                         let url_str = if d.starts_with("http") {
@@ -322,21 +611,7 @@ pub async fn start_server(
                     }
                 }
                 Err(e) => {
-                    let is_undefined_table = e
-                        .as_database_error()
-                        .and_then(|db| db.code().map(|c| c == "42P01"))
-                        .unwrap_or(false);
-
-                    if is_undefined_table {
-                        if !warned_missing_schema {
-                            warned_missing_schema = true;
-                            tracing::warn!(
-                                "CORS domain refresh skipped: database schema not migrated yet (missing tenants table)."
-                            );
-                        }
-                    } else {
-                        tracing::error!("Failed to refresh CORS domains: {}", e);
-                    }
+                    tracing::error!("Failed to refresh CORS domains: {}", e);
                 }
             }
         }
@@ -365,12 +640,18 @@ pub async fn start_server(
             USER_AGENT,
             HeaderName::from_static("x-requested-with"),
             HeaderName::from_static("x-csrf-token"),
+            HeaderName::from_static("idempotency-key"),
         ])
-        .expose_headers([HeaderName::from_static("content-disposition")]);
+        .expose_headers([
+            HeaderName::from_static("content-disposition"),
+            HeaderName::from_static("idempotent-replayed"),
+        ]);
 
     // Build router
+    let limits = HttpLimits::from_env();
 
-    let app = Router::new()
+    // JSON APIs: small bodies and short timeouts.
+    let api_routes = Router::new()
         .route("/", get(root_handler))
         // Install Routes
         .route("/api/install/check", get(install::check_installed))
@@ -417,6 +698,10 @@ pub async fn start_server(
             "/api/users",
             get(users::list_users).post(users::create_user),
         )
+        .route(
+            "/api/users/me/locale",
+            get(users::get_my_locale).put(users::set_my_locale),
+        )
         .route(
             "/api/users/me/addresses",
             get(users::list_my_addresses).post(users::create_my_address),
@@ -425,6 +710,8 @@ pub async fn start_server(
             "/api/users/me/addresses/{address_id}",
             put(users::update_my_address).delete(users::delete_my_address),
         )
+        .route("/api/users/trash", get(users::list_deleted_users))
+        .route("/api/users/{id}/restore", post(users::restore_user))
         .route(
             "/api/users/{id}/addresses",
             get(users::list_user_addresses_admin),
@@ -444,13 +731,75 @@ pub async fn start_server(
             "/api/superadmin/tenants/{id}",
             delete(superadmin::delete_tenant).put(superadmin::update_tenant),
         )
+        .route(
+            "/api/superadmin/tenants/{id}/isolate",
+            post(superadmin::isolate_tenant),
+        )
         .route("/api/superadmin/audit-logs", get(audit::list_audit_logs))
+        .route(
+            "/api/superadmin/audit-logs/export",
+            get(audit::export_audit_logs),
+        )
+        .route(
+            "/api/superadmin/audit-logs/presets",
+            get(audit::list_audit_filter_presets).post(audit::save_audit_filter_preset),
+        )
+        .route(
+            "/api/superadmin/audit-logs/presets/{id}",
+            delete(audit::delete_audit_filter_preset),
+        )
+        .route(
+            "/api/superadmin/audit-logs/verify",
+            get(audit::verify_audit_chain),
+        )
         .route("/api/admin/audit-logs", get(audit::list_tenant_audit_logs))
+        .route(
+            "/api/admin/audit-logs/export",
+            get(audit::export_tenant_audit_logs),
+        )
+        .route(
+            "/api/admin/audit-logs/presets",
+            get(audit::list_tenant_audit_filter_presets)
+                .post(audit::save_tenant_audit_filter_preset),
+        )
+        .route(
+            "/api/admin/audit-logs/presets/{id}",
+            delete(audit::delete_tenant_audit_filter_preset),
+        )
         .route("/api/superadmin/system", get(system::get_system_health))
         .route(
             "/api/superadmin/diagnostics",
             get(system::get_system_diagnostics),
         )
+        .route(
+            "/api/superadmin/migrations",
+            get(system::get_migration_status),
+        )
+        .route(
+            "/api/superadmin/maintenance",
+            post(system::run_db_maintenance),
+        )
+        .route("/api/superadmin/jobs", get(system::list_background_jobs))
+        .route(
+            "/api/superadmin/jobs/stats",
+            get(system::get_background_job_stats),
+        )
+        .route(
+            "/api/superadmin/jobs/{id}/retry",
+            post(system::retry_background_job),
+        )
+        .route(
+            "/api/superadmin/alert-rules",
+            get(system::list_alert_rules).post(system::create_alert_rule),
+        )
+        .route(
+            "/api/superadmin/alert-rules/{id}",
+            put(system::update_alert_rule).delete(system::delete_alert_rule),
+        )
+        .route(
+            "/api/superadmin/alert-rules/{id}/test",
+            post(system::test_alert_rule),
+        )
         // Support Tickets (tenant scoped; authorization derives tenant from token)
         .route(
             "/api/support/tickets",
@@ -468,14 +817,82 @@ pub async fn start_server(
             "/api/support/tickets/{id}/messages",
             post(support::reply_support_ticket),
         )
+        .route(
+            "/api/support/queues",
+            get(support::list_support_queues).post(support::create_support_queue),
+        )
+        .route(
+            "/api/support/queues/{id}",
+            put(support::update_support_queue).delete(support::delete_support_queue),
+        )
+        .route(
+            "/api/support/escalation-rules",
+            get(support::list_support_escalation_rules)
+                .post(support::create_support_escalation_rule),
+        )
+        .route(
+            "/api/support/escalation-rules/{id}",
+            put(support::update_support_escalation_rule)
+                .delete(support::delete_support_escalation_rule),
+        )
+        .route(
+            "/api/support/macros",
+            get(support::list_support_macros).post(support::create_support_macro),
+        )
+        .route(
+            "/api/support/macros/{id}",
+            put(support::update_support_macro).delete(support::delete_support_macro),
+        )
+        .route(
+            "/api/support/tickets/{id}/macros/{macro_id}",
+            post(support::apply_support_macro),
+        )
+        .route(
+            "/api/support/tickets/{id}/macros/{macro_id}/preview",
+            get(support::preview_support_macro),
+        )
+        .route(
+            "/api/support/tickets/{id}/links",
+            get(support::list_support_ticket_links).post(support::link_support_ticket),
+        )
+        .route(
+            "/api/support/tickets/{id}/links/{link_id}",
+            delete(support::unlink_support_ticket),
+        )
+        .route(
+            "/api/support/links",
+            get(support::list_linked_support_tickets),
+        )
         // Plans Routes
         .nest("/api/plans", plans::plan_routes())
         // Payment Routes
         .nest("/api/payment", payment::router())
         // Notification Routes
         .nest("/api/notifications", notifications::router())
+        // Per-tenant notification texts (defaults + overrides, preview)
+        .nest(
+            "/api/notification-templates",
+            notification_templates::router(),
+        )
+        // Per-tenant alert routing rules (category/severity/business hours -> channels)
+        .nest("/api/notification-rules", notification_routing::router())
+        .nest("/api/status-page", status_page::router())
+        // Reports (revenue, aging, SLA, incidents) emailed as CSV/PDF on a cadence
+        .nest("/api/report-schedules", report_schedules::router())
+        // Per-tenant HTML emails (verification, billing, alerts): edit, preview, test-send
+        .nest("/api/email-templates", email_templates::router())
+        // DKIM key for SMTP mail: generate/rotate, DNS record to publish, on/off
+        .nest("/api/email-dkim", email_dkim::router())
         // Email Outbox (admin monitor)
         .nest("/api/email-outbox", email_outbox::router())
+        // Bounce/complaint suppression list and the provider webhooks that feed it
+        .nest("/api/email-suppressions", email_suppressions::router())
+        // Runtime feature flags: evaluation for everyone, management for super admins
+        .nest("/api/feature-flags", feature_flags::router())
+        // WhatsApp channel: templates, routing, delivery log and provider callbacks
+        .nest("/api/whatsapp", whatsapp::router())
+        // Telegram bot: chat linking and the bot webhook
+        .nest("/api/telegram", telegram::router())
         // MikroTik routers (tenant admin)
         .nest("/api/admin/mikrotik", mikrotik::router())
         // Announcements (banner + admin broadcast)
@@ -484,12 +901,26 @@ pub async fn start_server(
         .nest("/api/customers", customers::router())
         // Installation work orders (tenant scoped)
         .nest("/api/admin/work-orders", work_orders::router())
+        // Materials inventory: items, locations, stock and movements (tenant scoped)
+        .nest("/api/admin/inventory", inventory::router())
+        // Offline field sync for technician devices (tenant scoped)
+        .nest("/api/admin/field-sync", field_sync::router())
         // PPPoE accounts (tenant scoped)
         .nest("/api/admin/pppoe", pppoe::router())
         // ISP packages + router mapping (tenant scoped)
         .nest("/api/admin/isp-packages", isp_packages::router())
         // Network topology mapping (tenant scoped)
         .nest("/api/admin/network-mapping", network_mapping::router())
+        // IP address plan: subnets and the planner view (tenant scoped)
+        .nest("/api/admin/ipam", ipam::router())
+        // CGNAT port-block log import and abuse lookup (tenant scoped)
+        .nest("/api/admin/cgnat", cgnat::router())
+        // Uplink capacities and 95th percentile utilization reports (tenant scoped)
+        .nest("/api/admin/uplink-capacity", uplink_capacity::router())
+        // Tenant API keys, webhooks, deliveries and rate-limit use (tenant scoped)
+        .nest("/api/integrations", integrations::router())
+        // Onboarding checklist for new tenants (tenant scoped)
+        .nest("/api/onboarding", onboarding::router())
         // Settings Routes
         .route(
             "/api/settings",
@@ -502,7 +933,10 @@ pub async fn start_server(
         )
         .route(
             "/api/settings/logo",
-            get(settings::get_logo).post(settings::upload_logo),
+            get(settings::get_logo)
+                .post(settings::upload_logo)
+                // Logo is sent as base64 JSON.
+                .layer(DefaultBodyLimit::max(limits.logo_body_bytes)),
         )
         .route("/api/settings/test-email", post(settings::send_test_email))
         .route(
@@ -531,6 +965,8 @@ pub async fn start_server(
             "/api/tenant/me",
             get(tenant::get_current_tenant).put(tenant::update_current_tenant),
         )
+        .route("/api/tenant/usage", get(tenant::get_tenant_usage))
+        .route("/api/tenant/analytics", get(tenant::get_tenant_analytics))
         // Roles Routes
         .route(
             "/api/roles",
@@ -545,23 +981,35 @@ pub async fn start_server(
         .route("/api/permissions", get(roles::get_permissions))
         // WebSocket Route
         .route("/api/ws", get(websocket::ws_handler))
-        // Backup Routes
-        .nest("/api/backups", backup::router())
-        // Storage Routes
+        // Storage Routes (metadata only; transfers live in `transfer_routes`)
         .route("/api/storage/files", get(storage::list_files))
         .route("/api/storage/files/{id}", delete(storage::delete_file))
-        .route("/api/storage/files/{id}/content", get(storage::serve_file))
         .route(
-            "/api/storage/files/{id}/download",
-            get(storage::download_file),
+            "/api/storage/files/{id}/versions",
+            get(storage::list_file_versions),
         )
-        .route("/api/storage/upload", post(storage::upload_file_http))
-        .route("/api/storage/upload/init", post(storage::init_upload))
-        .route("/api/storage/upload/chunk", post(storage::upload_chunk))
         .route(
-            "/api/storage/upload/complete",
-            post(storage::complete_upload),
+            "/api/storage/files/{id}/versions/{version_id}/restore",
+            post(storage::restore_file_version),
         )
+        .route(
+            "/api/storage/files/{id}/links",
+            post(storage::create_file_link),
+        )
+        .route("/api/storage/usage", get(storage::get_storage_usage))
+        .route(
+            "/api/storage/cleanup-rules",
+            get(storage::list_cleanup_rules).post(storage::create_cleanup_rule),
+        )
+        .route(
+            "/api/storage/cleanup-rules/{id}",
+            put(storage::update_cleanup_rule).delete(storage::delete_cleanup_rule),
+        )
+        .route(
+            "/api/storage/cleanup-rules/{id}/run",
+            post(storage::run_cleanup_rule),
+        )
+        .route("/api/storage/upload/init", post(storage::init_upload))
         // Public Routes
         .route(
             "/api/public/tenant-lookup",
@@ -592,14 +1040,84 @@ pub async fn start_server(
             "/api/public/domain/{domain}",
             get(public::get_tenant_by_domain),
         )
+        .route("/api/public/status/{slug}", get(public::get_status_page))
+        .route(
+            "/api/public/client-compatibility",
+            get(public::get_client_compatibility),
+        )
         .route("/api/public/unsubscribe/{token}", get(public::unsubscribe))
         // Version Route
         .route("/api/version", get(get_app_version))
-        .layer(DefaultBodyLimit::max(1024 * 1024 * 1024)) // 1GB Upload Limit
+        // Container probes: liveness only needs the process, readiness the database
+        .route("/healthz", get(healthz))
+        .route(
+            "/readyz",
+            get({
+                let pool = pool.clone();
+                move || readyz(pool.clone())
+            }),
+        )
+        .layer(DefaultBodyLimit::max(limits.api_body_bytes))
         .layer({
             #[allow(deprecated)]
-            TimeoutLayer::new(Duration::from_secs(3600))
-        }) // 1 Hour Timeout for large uploads
+            TimeoutLayer::new(limits.api_timeout)
+        });
+
+    // File transfers and backup/restore: large bodies and long timeouts.
+    let transfer_routes = Router::new()
+        .route("/api/storage/files/{id}/content", get(storage::serve_file))
+        .route(
+            "/api/storage/files/{id}/download",
+            get(storage::download_file),
+        )
+        .route("/api/storage/upload", post(storage::upload_file_http))
+        .route(
+            "/api/storage/shared/{id}",
+            get(storage::download_shared_file),
+        )
+        // Inbound support email (raw MIME from the mail provider, attachments included)
+        .route(
+            "/api/support/inbound/{token}",
+            post(support::inbound_support_email),
+        )
+        .route("/api/storage/upload/chunk", post(storage::upload_chunk))
+        .route(
+            "/api/storage/upload/complete",
+            post(storage::complete_upload),
+        )
+        .nest("/api/backups", backup::router())
+        .route(
+            "/api/superadmin/tenants/{id}/export",
+            get(superadmin::export_tenant),
+        )
+        .route(
+            "/api/superadmin/tenants/import",
+            post(superadmin::import_tenant),
+        )
+        // Migration from another billing system: whole-table CSV exports
+        .nest("/api/admin/legacy-import", legacy_import::router())
+        .layer(DefaultBodyLimit::max(limits.upload_body_bytes))
+        .layer({
+            #[allow(deprecated)]
+            TimeoutLayer::new(limits.upload_timeout)
+        });
+
+    let app = Router::new()
+        .merge(api_routes)
+        .merge(transfer_routes)
+        // Innermost, so idempotent replays are stored with the message the caller saw
+        .layer(axum::middleware::from_fn_with_state(
+            (state.clone(), pool.clone()),
+            middleware::localize_errors_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::tenant_schema_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::idempotency_middleware,
+        ))
         .layer(axum::middleware::from_fn(middleware::metrics_middleware))
         .layer(axum::Extension(state.metrics_service.clone()))
         .layer(axum::middleware::from_fn_with_state(
@@ -612,41 +1130,28 @@ pub async fn start_server(
         .layer(cors)
         .with_state(state);
 
-    // Determine port
-    let port = env::var("PORT")
-        .ok()
-        .and_then(|p| p.parse().ok())
-        .unwrap_or(default_port);
-
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    info!("HTTP API listening on {}", addr);
-
-    let listener = match TcpListener::bind(addr).await {
-        Ok(l) => l,
-        Err(e) => {
-            tracing::error!(
-                "Failed to bind to {}: {}. Is another instance running?",
-                addr,
-                e
-            );
-            return;
-        }
-    };
-
-    if let Err(e) = axum::serve(
-        listener,
-        app.into_make_service_with_connect_info::<SocketAddr>(),
-    )
-    .await
-    {
-        tracing::error!("HTTP API server error: {}", e);
-    }
+    server_control.serve(app).await;
 }
 
 async fn root_handler() -> &'static str {
     "SaaS API is running. Use the frontend to interact."
 }
 
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+async fn readyz(pool: crate::db::DbPool) -> (axum::http::StatusCode, &'static str) {
+    let ping = sqlx::query("SELECT 1").execute(&pool);
+    match tokio::time::timeout(Duration::from_secs(2), ping).await {
+        Ok(Ok(_)) => (axum::http::StatusCode::OK, "ok"),
+        _ => (
+            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            "database unavailable",
+        ),
+    }
+}
+
 async fn get_app_version() -> axum::Json<serde_json::Value> {
     axum::Json(serde_json::json!({
         "version": env!("CARGO_PKG_VERSION")
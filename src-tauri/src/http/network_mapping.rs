@@ -2,11 +2,11 @@ use crate::error::{AppError, AppResult};
 use crate::http::AppState;
 use crate::models::{
     ComputePathRequest, ConnectNodeToLinkRequest, ConnectNodeToLinkResponse, CoverageCheckRequest,
-    CreateNetworkLinkRequest, CreateNetworkNodeRequest, CreateServiceZoneRequest,
-    CreateZoneNodeBindingRequest, CreateZoneOfferRequest, NetworkImpactResponse, PaginatedResponse,
-    RankCandidateNodesRequest, ResolveZoneRequest, SyncTopologyAssetsResponse,
-    UpdateNetworkLinkRequest, UpdateNetworkNodeRequest, UpdateServiceZoneRequest,
-    UpdateZoneOfferRequest,
+    CreateFiberPointRequest, CreateNetworkLinkRequest, CreateNetworkNodeRequest,
+    CreateServiceZoneRequest, CreateZoneNodeBindingRequest, CreateZoneOfferRequest, FiberPoint,
+    NetworkImpactResponse, PaginatedResponse, RankCandidateNodesRequest, ResolveZoneRequest,
+    SyncTopologyAssetsResponse, UpdateFiberPointRequest, UpdateNetworkLinkRequest,
+    UpdateNetworkNodeRequest, UpdateServiceZoneRequest, UpdateZoneOfferRequest,
 };
 use crate::services::network_mapping_service::ListQuery;
 use axum::{
@@ -32,6 +32,15 @@ pub fn router() -> Router<AppState> {
         .route("/assets/sync", post(sync_topology_assets))
         .route("/coverage/check", post(check_coverage))
         .route("/impact/customers", get(list_impacted_customers))
+        .route(
+            "/fiber-points",
+            get(list_fiber_points).post(create_fiber_point),
+        )
+        .route(
+            "/fiber-points/{id}",
+            patch(update_fiber_point).delete(delete_fiber_point),
+        )
+        .route("/map", get(asset_map))
         .route(
             "/zone-offers",
             get(list_zone_offers).post(create_zone_offer),
@@ -76,6 +85,17 @@ struct ImpactCustomersParams {
     router_id: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct FiberPointParams {
+    kind: Option<String>,
+    bbox: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssetMapParams {
+    bbox: Option<String>,
+}
+
 fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
     headers
         .get("Authorization")
@@ -356,6 +376,72 @@ async fn list_impacted_customers(
     Ok(Json(out))
 }
 
+async fn list_fiber_points(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<FiberPointParams>,
+) -> AppResult<Json<Vec<FiberPoint>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .network_mapping_service
+        .list_fiber_points(&claims.sub, &tenant_id, q.kind, parse_bbox(q.bbox)?)
+        .await?;
+    Ok(Json(out))
+}
+
+async fn create_fiber_point(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(dto): Json<CreateFiberPointRequest>,
+) -> AppResult<Json<FiberPoint>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .network_mapping_service
+        .create_fiber_point(&claims.sub, &tenant_id, dto)
+        .await?;
+    Ok(Json(out))
+}
+
+async fn update_fiber_point(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(dto): Json<UpdateFiberPointRequest>,
+) -> AppResult<Json<FiberPoint>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .network_mapping_service
+        .update_fiber_point(&claims.sub, &tenant_id, &id, dto)
+        .await?;
+    Ok(Json(out))
+}
+
+async fn delete_fiber_point(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .network_mapping_service
+        .delete_fiber_point(&claims.sub, &tenant_id, &id)
+        .await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+async fn asset_map(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<AssetMapParams>,
+) -> AppResult<Json<serde_json::Value>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .network_mapping_service
+        .asset_map(&claims.sub, &tenant_id, parse_bbox(q.bbox)?)
+        .await?;
+    Ok(Json(out))
+}
+
 async fn compute_path(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -0,0 +1,107 @@
+use crate::error::{AppError, AppResult};
+use crate::http::AppState;
+use crate::models::{
+    EvaluateNotificationRoutingRequest, NotificationRoutingEvaluation, NotificationRoutingRule,
+    UpsertNotificationRoutingRuleRequest,
+};
+use crate::services::NotificationRoutingService;
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    routing::{get, post, put},
+    Json, Router,
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_rules).post(create_rule))
+        .route("/evaluate", post(evaluate_routing))
+        .route("/{id}", put(update_rule).delete(delete_rule))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+/// Returns `(user_id, tenant_id)` after checking the settings permission.
+async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    action: &str,
+) -> AppResult<(String, String)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "settings", action)
+        .await?;
+    Ok((claims.sub, tenant_id))
+}
+
+fn routing(state: &AppState) -> AppResult<&NotificationRoutingService> {
+    state
+        .notification_service
+        .routing()
+        .ok_or_else(|| AppError::Internal("Notification routing is not initialized".to_string()))
+}
+
+// GET /api/notification-rules
+async fn list_rules(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<NotificationRoutingRule>>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(routing(&state)?.list(&tenant_id).await?))
+}
+
+// POST /api/notification-rules
+async fn create_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<UpsertNotificationRoutingRuleRequest>,
+) -> AppResult<Json<NotificationRoutingRule>> {
+    let (user_id, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        routing(&state)?
+            .create(&tenant_id, req, Some(&user_id))
+            .await?,
+    ))
+}
+
+// PUT /api/notification-rules/{id}
+async fn update_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<UpsertNotificationRoutingRuleRequest>,
+) -> AppResult<Json<NotificationRoutingRule>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(routing(&state)?.update(&tenant_id, &id, req).await?))
+}
+
+// DELETE /api/notification-rules/{id}
+async fn delete_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<()>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    routing(&state)?.delete(&tenant_id, &id).await?;
+    Ok(Json(()))
+}
+
+// POST /api/notification-rules/evaluate
+async fn evaluate_routing(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<EvaluateNotificationRoutingRequest>,
+) -> AppResult<Json<NotificationRoutingEvaluation>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(routing(&state)?.evaluate(&tenant_id, req).await?))
+}
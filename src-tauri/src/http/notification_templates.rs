@@ -0,0 +1,109 @@
+use crate::error::{AppError, AppResult};
+use crate::http::AppState;
+use crate::models::{
+    NotificationTemplate, PreviewNotificationTemplateRequest, RenderedNotification,
+    UpdateNotificationTemplateRequest,
+};
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    routing::{get, post, put},
+    Json, Router,
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_templates))
+        .route("/{code}", put(update_template).delete(reset_template))
+        .route("/{code}/preview", post(preview_template))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+/// Returns `(user_id, tenant_id)` after checking the settings permission.
+async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    action: &str,
+) -> AppResult<(String, String)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "settings", action)
+        .await?;
+    Ok((claims.sub, tenant_id))
+}
+
+// GET /api/notification-templates
+async fn list_templates(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<NotificationTemplate>>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(
+        state
+            .notification_service
+            .templates()
+            .list(&tenant_id)
+            .await?,
+    ))
+}
+
+// PUT /api/notification-templates/{code}
+async fn update_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(code): Path<String>,
+    Json(req): Json<UpdateNotificationTemplateRequest>,
+) -> AppResult<Json<NotificationTemplate>> {
+    let (user_id, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        state
+            .notification_service
+            .templates()
+            .update(&tenant_id, &code, req, Some(&user_id))
+            .await?,
+    ))
+}
+
+// DELETE /api/notification-templates/{code}
+async fn reset_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(code): Path<String>,
+) -> AppResult<Json<NotificationTemplate>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        state
+            .notification_service
+            .templates()
+            .reset(&tenant_id, &code)
+            .await?,
+    ))
+}
+
+// POST /api/notification-templates/{code}/preview
+async fn preview_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(code): Path<String>,
+    Json(req): Json<PreviewNotificationTemplateRequest>,
+) -> AppResult<Json<RenderedNotification>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(
+        state
+            .notification_service
+            .templates()
+            .preview(&tenant_id, &code, req)
+            .await?,
+    ))
+}
@@ -1,14 +1,18 @@
 use crate::error::AppResult;
 use crate::http::AppState;
 use crate::models::{
-    CreatePushSubscriptionRequest, UnsubscribePushRequest, UpdatePreferenceRequest, UserResponse,
+    ArchiveNotificationsRequest, CreatePushSubscriptionRequest, NotificationDeliveryFilter,
+    NotificationDeliveryReport, NotificationFilter, NotificationQuietHours, UnsubscribePushRequest,
+    UpdatePreferenceRequest, UpdateQuietHoursRequest, UserResponse, VapidPublicKeyResponse,
 };
+use crate::services::QuietHoursService;
 use axum::{
     extract::{Path, Query, State},
     http::HeaderMap,
     routing::{delete, get, post},
     Json, Router,
 };
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
 #[derive(Deserialize)]
@@ -16,6 +20,27 @@ use serde::Deserialize;
 pub struct ListNotificationsQuery {
     pub page: Option<u32>,
     pub per_page: Option<u32>,
+    pub category: Option<String>,
+    pub notification_type: Option<String>,
+    pub is_read: Option<bool>,
+    pub archived: Option<bool>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub search: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeliveryReportQuery {
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+    pub channel: Option<String>,
+    pub status: Option<String>,
+    pub category: Option<String>,
+    pub user_id: Option<String>,
+    pub notification_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
 }
 
 pub fn router() -> Router<AppState> {
@@ -24,8 +49,12 @@ pub fn router() -> Router<AppState> {
         .route("/unread-count", get(get_unread_count))
         .route("/{id}/read", post(mark_as_read))
         .route("/read-all", post(mark_all_as_read))
+        .route("/archive", post(archive_notifications))
         .route("/{id}", delete(delete_notification))
         .route("/preferences", get(get_preferences).put(update_preference))
+        .route("/quiet-hours", get(get_quiet_hours).put(update_quiet_hours))
+        .route("/delivery-report", get(get_delivery_report))
+        .route("/push/vapid-public-key", get(get_vapid_public_key))
         .route("/push/subscribe", post(subscribe_push))
         .route("/push/unsubscribe", post(unsubscribe_push))
         .route("/test", post(send_test_notification))
@@ -47,19 +76,48 @@ async fn get_current_user(state: &AppState, headers: &HeaderMap) -> AppResult<Us
     Ok(user_response)
 }
 
+/// Returns `(user_id, tenant_id)` from the bearer token.
+async fn current_user_ids(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> AppResult<(String, Option<String>)> {
+    let token = headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or_else(|| crate::error::AppError::Unauthorized)?;
+    let claims = state.auth_service.validate_token(token).await?;
+    Ok((claims.sub, claims.tenant_id))
+}
+
+fn quiet_hours(state: &AppState) -> AppResult<&QuietHoursService> {
+    state.notification_service.quiet_hours().ok_or_else(|| {
+        crate::error::AppError::Internal("Quiet hours are not initialized".to_string())
+    })
+}
+
 // GET /api/notifications
 async fn list_notifications(
     State(state): State<AppState>,
     headers: HeaderMap,
-    query: Query<ListNotificationsQuery>,
+    Query(query): Query<ListNotificationsQuery>,
 ) -> AppResult<Json<crate::models::PaginatedResponse<crate::models::Notification>>> {
     let user = get_current_user(&state, &headers).await?;
     let page = query.page.unwrap_or(1);
     let per_page = query.per_page.unwrap_or(20);
+    let filter = NotificationFilter {
+        category: query.category,
+        notification_type: query.notification_type,
+        is_read: query.is_read,
+        archived: query.archived,
+        from: query.from,
+        to: query.to,
+        search: query.search,
+    };
 
     let result = state
         .notification_service
-        .list_notifications(&user.id, page, per_page)
+        .list_notifications(&user.id, page, per_page, &filter)
         .await?;
     Ok(Json(result))
 }
@@ -104,6 +162,20 @@ async fn mark_all_as_read(
     Ok(Json(serde_json::json!({ "success": true })))
 }
 
+// POST /api/notifications/archive
+async fn archive_notifications(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<ArchiveNotificationsRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let user = get_current_user(&state, &headers).await?;
+    let count = state
+        .notification_service
+        .archive_notifications(&user.id, &req.ids, req.archived.unwrap_or(true))
+        .await?;
+    Ok(Json(serde_json::json!({ "count": count })))
+}
+
 // DELETE /api/notifications/:id
 async fn delete_notification(
     State(state): State<AppState>,
@@ -131,6 +203,68 @@ async fn get_preferences(
     Ok(Json(prefs))
 }
 
+// GET /api/notifications/quiet-hours
+async fn get_quiet_hours(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<NotificationQuietHours>> {
+    let (user_id, tenant_id) = current_user_ids(&state, &headers).await?;
+    Ok(Json(
+        quiet_hours(&state)?
+            .get(&user_id, tenant_id.as_deref())
+            .await?,
+    ))
+}
+
+// GET /api/notifications/delivery-report
+async fn get_delivery_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<DeliveryReportQuery>,
+) -> AppResult<Json<NotificationDeliveryReport>> {
+    let (user_id, tenant_id) = current_user_ids(&state, &headers).await?;
+    let tenant_id = tenant_id.ok_or(crate::error::AppError::Unauthorized)?;
+    state
+        .auth_service
+        .check_permission(&user_id, &tenant_id, "notification_reports", "read")
+        .await?;
+    let filter = NotificationDeliveryFilter {
+        channel: query.channel,
+        status: query.status,
+        category: query.category,
+        user_id: query.user_id,
+        notification_id: query.notification_id,
+        from: query.from,
+        to: query.to,
+    };
+    Ok(Json(
+        state
+            .notification_service
+            .deliveries()
+            .report(
+                &tenant_id,
+                &filter,
+                query.page.unwrap_or(1),
+                query.per_page.unwrap_or(25),
+            )
+            .await?,
+    ))
+}
+
+// PUT /api/notifications/quiet-hours
+async fn update_quiet_hours(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateQuietHoursRequest>,
+) -> AppResult<Json<NotificationQuietHours>> {
+    let (user_id, tenant_id) = current_user_ids(&state, &headers).await?;
+    Ok(Json(
+        quiet_hours(&state)?
+            .update(&user_id, tenant_id.as_deref(), req)
+            .await?,
+    ))
+}
+
 // PUT /api/notifications/preferences
 async fn update_preference(
     State(state): State<AppState>,
@@ -145,6 +279,20 @@ async fn update_preference(
     Ok(Json(serde_json::json!({ "success": true })))
 }
 
+// GET /api/notifications/push/vapid-public-key
+async fn get_vapid_public_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<VapidPublicKeyResponse>> {
+    get_current_user(&state, &headers).await?;
+    let web_push = state.notification_service.web_push().ok_or_else(|| {
+        crate::error::AppError::Internal("Web Push is not initialized".to_string())
+    })?;
+    Ok(Json(VapidPublicKeyResponse {
+        public_key: web_push.public_key().await?,
+    }))
+}
+
 // POST /api/notifications/push/subscribe
 async fn subscribe_push(
     State(state): State<AppState>,
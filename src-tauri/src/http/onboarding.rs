@@ -0,0 +1,81 @@
+use crate::error::{AppError, AppResult};
+use crate::http::auth::extract_ip;
+use crate::http::AppState;
+use crate::models::OnboardingProgress;
+use axum::{
+    extract::{ConnectInfo, Path, State},
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+use std::net::SocketAddr;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(progress))
+        .route("/steps/{step}/skip", post(skip_step).delete(resume_step))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+async fn tenant_and_claims(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> AppResult<(String, crate::services::auth_service::Claims)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    Ok((tenant_id, claims))
+}
+
+// GET /api/onboarding
+async fn progress(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<OnboardingProgress>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .onboarding_service
+        .progress(&claims.sub, &tenant_id)
+        .await?;
+    Ok(Json(out))
+}
+
+// POST /api/onboarding/steps/{step}/skip
+async fn skip_step(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(step): Path<String>,
+) -> AppResult<Json<OnboardingProgress>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .onboarding_service
+        .skip_step(&claims.sub, &tenant_id, &step, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// DELETE /api/onboarding/steps/{step}/skip
+async fn resume_step(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(step): Path<String>,
+) -> AppResult<Json<OnboardingProgress>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .onboarding_service
+        .resume_step(&claims.sub, &tenant_id, &step, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
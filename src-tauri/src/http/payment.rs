@@ -1,11 +1,16 @@
 //! Payment HTTP Handlers (Webhooks)
 
+use crate::error::AppError;
 use crate::http::AppState;
 use crate::models::{
     BankAccount, BillingCollectionLogView, CreateBankAccountRequest, Invoice,
     InvoiceReminderLogView,
 };
-use crate::services::{BillingCollectionRunResult, BulkGenerateInvoicesResult, Claims};
+use crate::services::feature_flag_service::FLAG_PLAN_SELF_SERVE_UPGRADE;
+use crate::services::{
+    BillingCollectionRunResult, BulkGenerateInvoicesResult, Claims, PlanUpgradeCheckout,
+    PlanUpgradeQuote,
+};
 use axum::{
     extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
@@ -22,6 +27,8 @@ pub fn router() -> Router<AppState> {
         .route("/invoices/all", get(list_all_invoices))
         .route("/fx-rate", get(get_fx_rate))
         .route("/invoices/plan", post(create_invoice_for_plan))
+        .route("/upgrade/quote", post(quote_plan_upgrade))
+        .route("/upgrade/checkout", post(checkout_plan_upgrade))
         .route(
             "/invoices/customer-package",
             get(list_customer_package_invoices),
@@ -54,6 +61,10 @@ pub fn router() -> Router<AppState> {
             "/invoices/{id}/customer-package/verify",
             post(verify_customer_package_payment),
         )
+        .route(
+            "/invoices/{id}/customer-package/reprice",
+            post(reprice_customer_package_invoice),
+        )
         .route("/invoices/{id}/verify", post(verify_invoice_payment))
         .route("/invoices/{id}/proof", post(submit_payment_proof))
         .route("/invoices/{id}", get(get_invoice))
@@ -594,6 +605,81 @@ async fn create_invoice_for_plan(
         })
 }
 
+fn upgrade_error(e: AppError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match &e {
+        AppError::Validation(_) => StatusCode::BAD_REQUEST,
+        AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        AppError::Conflict(_) => StatusCode::CONFLICT,
+        AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+        AppError::Configuration(_) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (
+        status,
+        Json(ErrorResponse {
+            error: e.to_string(),
+        }),
+    )
+}
+
+fn require_tenant_id(claims: Claims) -> Result<String, (StatusCode, Json<ErrorResponse>)> {
+    claims.tenant_id.ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "No tenant context".to_string(),
+            }),
+        )
+    })
+}
+
+async fn quote_plan_upgrade(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<CreateInvoiceForPlanBody>,
+) -> Result<Json<PlanUpgradeQuote>, (StatusCode, Json<ErrorResponse>)> {
+    let claims = authenticate(&state, &headers).await?;
+    let scope = resolve_payment_read_scope(&state, &claims).await?;
+    if !matches!(scope, PaymentReadScope::Billing) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: "Billing read access required".to_string(),
+            }),
+        ));
+    }
+    let tenant_id = require_tenant_id(claims)?;
+
+    state
+        .payment_service
+        .quote_plan_upgrade(&tenant_id, &body.plan_id, &body.billing_cycle)
+        .await
+        .map(Json)
+        .map_err(upgrade_error)
+}
+
+async fn checkout_plan_upgrade(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<CreateInvoiceForPlanBody>,
+) -> Result<Json<PlanUpgradeCheckout>, (StatusCode, Json<ErrorResponse>)> {
+    let claims = authenticate(&state, &headers).await?;
+    require_payment_manage_access(&state, &claims).await?;
+    let tenant_id = require_tenant_id(claims)?;
+    state
+        .feature_flags
+        .require(FLAG_PLAN_SELF_SERVE_UPGRADE, Some(&tenant_id))
+        .await
+        .map_err(upgrade_error)?;
+
+    state
+        .payment_service
+        .checkout_plan_upgrade(&tenant_id, &body.plan_id, &body.billing_cycle)
+        .await
+        .map(Json)
+        .map_err(upgrade_error)
+}
+
 async fn get_invoice(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -918,6 +1004,31 @@ async fn verify_customer_package_payment(
         })
 }
 
+async fn reprice_customer_package_invoice(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Invoice>, (StatusCode, Json<ErrorResponse>)> {
+    let claims = authenticate(&state, &headers).await?;
+    require_payment_manage_access(&state, &claims).await?;
+    let invoice =
+        authorize_invoice_access(&state, &claims, &PaymentReadScope::Billing, &id).await?;
+
+    state
+        .payment_service
+        .reprice_customer_package_invoice(&invoice.tenant_id, &id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })
+}
+
 async fn verify_invoice_payment(
     State(state): State<AppState>,
     headers: HeaderMap,
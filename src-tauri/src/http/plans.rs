@@ -33,6 +33,7 @@ pub fn plan_routes() -> Router<AppState> {
         .route("/subscriptions/details", get(get_subscription_details))
         .route("/subscriptions/{tenant_id}", get(get_subscription))
         .route("/subscriptions/{tenant_id}/assign", post(assign_plan))
+        .route("/subscriptions/{tenant_id}/trial", post(start_trial))
         // Feature access check
         .route("/access/{tenant_id}/{feature_code}", get(check_access))
 }
@@ -462,6 +463,30 @@ async fn assign_plan(
         })
 }
 
+async fn start_trial(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<AssignPlanBody>,
+) -> Result<Json<TenantSubscription>, (StatusCode, Json<ErrorResponse>)> {
+    let claims = authenticate(&state, &headers).await?;
+    require_superadmin(&claims)?;
+
+    state
+        .plan_service
+        .start_trial(&tenant_id, &body.plan_id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })
+}
+
 // ==================== FEATURE ACCESS ====================
 
 async fn check_access(
@@ -1,6 +1,10 @@
 use super::AppState;
 use crate::http::auth::extract_ip;
-use crate::models::{CustomerRegistrationInviteValidationView, RegisterDto, Tenant, User};
+use crate::models::{
+    ClientCompatibility, CustomerRegistrationInviteValidationView, PublicStatusPage, RegisterDto,
+    Tenant, User,
+};
+use crate::services::client_version::client_compatibility;
 use crate::services::decode_unsubscribe_token;
 use axum::{
     extract::{ConnectInfo, Path, Query, State},
@@ -43,6 +47,30 @@ pub async fn get_tenant_by_domain(
     }
 }
 
+// GET /api/public/status/{slug}
+pub async fn get_status_page(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<Json<PublicStatusPage>, crate::error::AppError> {
+    Ok(Json(state.status_page.get_public(&slug).await?))
+}
+
+#[derive(serde::Deserialize)]
+pub struct ClientVersionQuery {
+    pub version: Option<String>,
+}
+
+/// Desktop clients call this before anything else to learn whether they are
+/// still allowed to use the API and which release channel to update from.
+pub async fn get_client_compatibility(
+    State(state): State<AppState>,
+    Query(query): Query<ClientVersionQuery>,
+) -> Result<Json<ClientCompatibility>, crate::error::AppError> {
+    Ok(Json(
+        client_compatibility(&state.settings_service, query.version.as_deref()).await?,
+    ))
+}
+
 #[derive(serde::Deserialize)]
 pub struct DomainQuery {
     pub domain: String,
@@ -0,0 +1,121 @@
+use crate::error::{AppError, AppResult};
+use crate::http::AppState;
+use crate::models::{ReportSchedule, ReportSendResult, UpsertReportScheduleRequest};
+use crate::services::report_schedule_service::report_permission;
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    routing::{get, post, put},
+    Json, Router,
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_schedules).post(create_schedule))
+        .route("/{id}", put(update_schedule).delete(delete_schedule))
+        .route("/{id}/send", post(send_schedule))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+/// Returns `(user_id, tenant_id)` after checking the settings permission.
+async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    action: &str,
+) -> AppResult<(String, String)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "settings", action)
+        .await?;
+    Ok((claims.sub, tenant_id))
+}
+
+/// Schedules mail the report's data out, so whoever sets one up or sends it
+/// must be able to read that data themselves.
+async fn authorize_report(
+    state: &AppState,
+    user_id: &str,
+    tenant_id: &str,
+    report: &str,
+) -> AppResult<()> {
+    let (resource, action) = report_permission(report);
+    state
+        .auth_service
+        .check_permission(user_id, tenant_id, resource, action)
+        .await
+}
+
+// GET /api/report-schedules
+async fn list_schedules(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<ReportSchedule>>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(state.report_schedules.list(&tenant_id).await?))
+}
+
+// POST /api/report-schedules
+async fn create_schedule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<UpsertReportScheduleRequest>,
+) -> AppResult<Json<ReportSchedule>> {
+    let (user_id, tenant_id) = authorize(&state, &headers, "update").await?;
+    authorize_report(&state, &user_id, &tenant_id, &req.report).await?;
+    Ok(Json(
+        state
+            .report_schedules
+            .create(&tenant_id, req, Some(&user_id))
+            .await?,
+    ))
+}
+
+// PUT /api/report-schedules/{id}
+async fn update_schedule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<UpsertReportScheduleRequest>,
+) -> AppResult<Json<ReportSchedule>> {
+    let (user_id, tenant_id) = authorize(&state, &headers, "update").await?;
+    authorize_report(&state, &user_id, &tenant_id, &req.report).await?;
+    Ok(Json(
+        state.report_schedules.update(&tenant_id, &id, req).await?,
+    ))
+}
+
+// DELETE /api/report-schedules/{id}
+async fn delete_schedule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<()>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    state.report_schedules.delete(&tenant_id, &id).await?;
+    Ok(Json(()))
+}
+
+// POST /api/report-schedules/{id}/send
+async fn send_schedule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<ReportSendResult>> {
+    let (user_id, tenant_id) = authorize(&state, &headers, "update").await?;
+    let schedule = state.report_schedules.get(&tenant_id, &id).await?;
+    authorize_report(&state, &user_id, &tenant_id, &schedule.report).await?;
+    Ok(Json(
+        state.report_schedules.send_now(&tenant_id, &id).await?,
+    ))
+}
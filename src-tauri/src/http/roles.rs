@@ -1,6 +1,6 @@
 //! Roles and permissions HTTP handlers
 
-use super::{websocket::WsEvent, AppState};
+use super::AppState;
 use crate::http::auth::extract_ip;
 use crate::models::{CreateRoleDto, Permission, RoleWithPermissions, UpdateRoleDto};
 use axum::{
@@ -127,11 +127,6 @@ pub async fn create_new_role(
         .create_role(tenant_id, dto, Some(&claims.sub), Some(&ip))
         .await?;
 
-    // Broadcast role created event to all connected clients
-    state.ws_hub.broadcast(WsEvent::RoleCreated {
-        role_id: role.id.clone(),
-    });
-
     Ok(Json(role))
 }
 
@@ -142,6 +137,8 @@ pub struct UpdateRolePayload {
     description: Option<String>,
     level: Option<i32>,
     permissions: Option<Vec<String>>,
+    #[serde(alias = "expectedUpdatedAt")]
+    expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 /// Update an existing role
@@ -169,6 +166,7 @@ pub async fn update_existing_role(
         description: payload.description,
         level: payload.level,
         permissions: payload.permissions,
+        expected_updated_at: payload.expected_updated_at,
     };
 
     let role = state
@@ -182,11 +180,6 @@ pub async fn update_existing_role(
         )
         .await?;
 
-    // Broadcast role updated event to all connected clients
-    state.ws_hub.broadcast(WsEvent::RoleUpdated {
-        role_id: role.id.clone(),
-    });
-
     Ok(Json(role))
 }
 
@@ -214,10 +207,5 @@ pub async fn delete_existing_role(
         .delete_role(&id, claims.is_super_admin, Some(&claims.sub), Some(&ip))
         .await?;
 
-    // Broadcast role deleted event to all connected clients
-    if deleted {
-        state.ws_hub.broadcast(WsEvent::RoleDeleted { role_id: id });
-    }
-
     Ok(Json(serde_json::json!({"success": deleted})))
 }
@@ -286,6 +286,8 @@ pub struct UpsertSettingRequest {
     key: String,
     value: String,
     description: Option<String>,
+    #[serde(alias = "expectedUpdatedAt")]
+    expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 pub async fn upsert_setting(
@@ -331,6 +333,7 @@ pub async fn upsert_setting(
         key,
         value,
         description,
+        expected_updated_at: payload.expected_updated_at,
     };
 
     println!(
@@ -357,10 +360,16 @@ pub async fn upsert_setting(
             .ok()
             .flatten();
 
-        state.ws_hub.broadcast(WsEvent::MaintenanceModeChanged {
-            enabled: maintenance_enabled,
-            message: maintenance_message,
-        });
+        state
+            .event_outbox
+            .publish(
+                None,
+                vec![WsEvent::MaintenanceModeChanged {
+                    enabled: maintenance_enabled,
+                    message: maintenance_message,
+                }],
+            )
+            .await;
         println!(
             "DEBUG: [HTTP] Broadcasted MaintenanceModeChanged event (enabled: {})",
             maintenance_enabled
@@ -468,6 +477,7 @@ pub async fn upload_logo(
         key: "app_logo_path".to_string(),
         value: path_str.clone(),
         description: Some("Path to application logo".to_string()),
+        expected_updated_at: None,
     };
     state
         .settings_service
@@ -0,0 +1,183 @@
+use crate::error::{AppError, AppResult};
+use crate::http::AppState;
+use crate::models::{
+    AddStatusPostUpdateRequest, CreateStatusPostRequest, PublicStatusPage, StatusComponent,
+    StatusPost, UpdateStatusPostRequest, UpsertStatusComponentRequest,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    routing::{get, post, put},
+    Json, Router,
+};
+use serde::Deserialize;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/preview", get(preview))
+        .route("/components", get(list_components).post(create_component))
+        .route(
+            "/components/{id}",
+            put(update_component).delete(delete_component),
+        )
+        .route("/posts", get(list_posts).post(create_post))
+        .route("/posts/{id}", put(update_post).delete(delete_post))
+        .route("/posts/{id}/updates", post(add_post_update))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+/// Returns `(user_id, tenant_id)` after checking the settings permission.
+async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    action: &str,
+) -> AppResult<(String, String)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "settings", action)
+        .await?;
+    Ok((claims.sub, tenant_id))
+}
+
+#[derive(Debug, Deserialize)]
+struct ListPostsQuery {
+    include_closed: Option<bool>,
+}
+
+// GET /api/status-page/preview
+async fn preview(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<PublicStatusPage>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(state.status_page.preview(&tenant_id).await?))
+}
+
+// GET /api/status-page/components
+async fn list_components(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<StatusComponent>>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(state.status_page.list_components(&tenant_id).await?))
+}
+
+// POST /api/status-page/components
+async fn create_component(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<UpsertStatusComponentRequest>,
+) -> AppResult<Json<StatusComponent>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        state.status_page.create_component(&tenant_id, req).await?,
+    ))
+}
+
+// PUT /api/status-page/components/{id}
+async fn update_component(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<UpsertStatusComponentRequest>,
+) -> AppResult<Json<StatusComponent>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        state
+            .status_page
+            .update_component(&tenant_id, &id, req)
+            .await?,
+    ))
+}
+
+// DELETE /api/status-page/components/{id}
+async fn delete_component(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<()>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    state.status_page.delete_component(&tenant_id, &id).await?;
+    Ok(Json(()))
+}
+
+// GET /api/status-page/posts
+async fn list_posts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<ListPostsQuery>,
+) -> AppResult<Json<Vec<StatusPost>>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(
+        state
+            .status_page
+            .list_posts(&tenant_id, q.include_closed.unwrap_or(true))
+            .await?,
+    ))
+}
+
+// POST /api/status-page/posts
+async fn create_post(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateStatusPostRequest>,
+) -> AppResult<Json<StatusPost>> {
+    let (user_id, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        state
+            .status_page
+            .create_post(&tenant_id, req, Some(&user_id))
+            .await?,
+    ))
+}
+
+// PUT /api/status-page/posts/{id}
+async fn update_post(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateStatusPostRequest>,
+) -> AppResult<Json<StatusPost>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        state.status_page.update_post(&tenant_id, &id, req).await?,
+    ))
+}
+
+// DELETE /api/status-page/posts/{id}
+async fn delete_post(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<()>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    state.status_page.delete_post(&tenant_id, &id).await?;
+    Ok(Json(()))
+}
+
+// POST /api/status-page/posts/{id}/updates
+async fn add_post_update(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<AddStatusPostUpdateRequest>,
+) -> AppResult<Json<StatusPost>> {
+    let (user_id, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        state
+            .status_page
+            .add_update(&tenant_id, &id, req, Some(&user_id))
+            .await?,
+    ))
+}
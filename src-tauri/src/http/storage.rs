@@ -1,3 +1,4 @@
+use crate::error::AppError;
 use crate::http::auth::extract_ip;
 use crate::http::AppState;
 use crate::services::storage_service::StorageContent;
@@ -46,7 +47,7 @@ async fn authorize_file_access(
     state: &AppState,
     token: &str,
     file_id: &str,
-) -> Result<(), Response> {
+) -> Result<crate::services::Claims, Response> {
     let claims = state
         .auth_service
         .validate_token(token)
@@ -60,7 +61,7 @@ async fn authorize_file_access(
         .map_err(|_| StatusCode::NOT_FOUND.into_response())?;
 
     if claims.is_super_admin {
-        return Ok(());
+        return Ok(claims);
     }
 
     let tenant_id = claims
@@ -78,7 +79,7 @@ async fn authorize_file_access(
         .await
         .map_err(|_| (StatusCode::FORBIDDEN, "Forbidden").into_response())?;
 
-    Ok(())
+    Ok(claims)
 }
 
 #[derive(serde::Deserialize)]
@@ -91,6 +92,8 @@ pub struct ListFileParams {
 #[derive(serde::Deserialize, Default)]
 pub struct UploadFileQuery {
     pub payment_invoice_id: Option<String>,
+    /// Logical path of the file; re-uploading to it adds a version.
+    pub path: Option<String>,
 }
 
 async fn can_upload_payment_proof(
@@ -345,6 +348,7 @@ pub async fn serve_file(
 
     let (record, content) = match state.storage_service.get_file_content(&id).await {
         Ok(res) => res,
+        Err(AppError::Forbidden(msg)) => return (StatusCode::FORBIDDEN, msg).into_response(),
         Err(_) => return StatusCode::NOT_FOUND.into_response(),
     };
 
@@ -442,11 +446,40 @@ pub async fn download_file(
         return resp;
     }
 
-    let (record, content) = match state.storage_service.get_file_content(&id).await {
-        Ok(res) => res,
-        Err(_) => return StatusCode::NOT_FOUND.into_response(),
-    };
+    match state.storage_service.get_file_content(&id).await {
+        Ok((record, content)) => attachment_response(record, content).await,
+        Err(AppError::Forbidden(msg)) => (StatusCode::FORBIDDEN, msg).into_response(),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
 
+#[derive(serde::Deserialize)]
+pub struct SharedFileQuery {
+    pub expires: i64,
+    pub signature: String,
+}
+
+/// Download through a signed link; no session needed.
+pub async fn download_shared_file(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(q): Query<SharedFileQuery>,
+) -> Response {
+    match state
+        .storage_service
+        .get_signed_file_content(&id, q.expires, &q.signature)
+        .await
+    {
+        Ok((record, content)) => attachment_response(record, content).await,
+        Err(AppError::Forbidden(msg)) => (StatusCode::FORBIDDEN, msg).into_response(),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn attachment_response(
+    record: crate::models::FileRecord,
+    content: StorageContent,
+) -> Response {
     let body = match content {
         StorageContent::Local(path) => {
             let file = match File::open(path).await {
@@ -612,11 +645,7 @@ pub async fn upload_file_http(
                     .into_response();
             }
 
-            let (path, safe_name, file_id) = match state
-                .storage_service
-                .prepare_upload_path(&tenant_id, &file_name)
-                .await
-            {
+            let path = match state.storage_service.temp_upload_path().await {
                 Ok(p) => p,
                 Err(e) => {
                     error!("[Upload] ❌ Path preparation failed: {}", e);
@@ -675,24 +704,22 @@ pub async fn upload_file_http(
             }
 
             let _ = file.flush().await;
+            drop(file);
             info!(
                 "[Upload] ✅ Write finished. Total size: {} MB. Registering...",
                 current_size / (1024 * 1024)
             );
 
+            // Moves the file onto the tenant's storage backend and registers it.
             let result = state
                 .storage_service
-                .register_upload(
+                .store_file(
                     &tenant_id,
-                    &file_id,
                     &file_name,
-                    &safe_name,
-                    &path.to_string_lossy(),
                     &content_type,
-                    current_size as i64,
-                    "local", // Direct HTTP upload defaults to local for now
+                    path,
+                    query.path.as_deref(),
                     Some(&claims.sub),
-                    false, // Count quota
                 )
                 .await;
 
@@ -706,6 +733,8 @@ pub async fn upload_file_http(
                         "original_name": record.original_name,
                         "size": record.size,
                         "storage_provider": record.storage_provider,
+                        "scan_status": record.scan_status,
+                        "version": record.version,
                     })
                     .to_string();
                     state
@@ -724,9 +753,8 @@ pub async fn upload_file_http(
                     Json(record).into_response()
                 }
                 Err(e) => {
-                    error!("[Upload] ❌ Database registration failed: {}", e);
-                    let _ = tokio::fs::remove_file(&path).await;
-                    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+                    error!("[Upload] ❌ Storing upload failed: {}", e);
+                    e.into_response()
                 }
             };
         }
@@ -747,6 +775,8 @@ pub struct CompleteRequest {
     pub upload_id: String,
     pub file_name: String,
     pub content_type: String,
+    #[serde(default)]
+    pub path: Option<String>,
 }
 
 pub async fn init_upload(State(state): State<AppState>, headers: HeaderMap) -> Response {
@@ -902,6 +932,7 @@ pub async fn complete_upload(
             &payload.upload_id,
             &payload.file_name,
             &payload.content_type,
+            payload.path.as_deref(),
             Some(&claims.sub),
         )
         .await
@@ -913,6 +944,8 @@ pub async fn complete_upload(
                 "original_name": record.original_name,
                 "size": record.size,
                 "storage_provider": record.storage_provider,
+                "scan_status": record.scan_status,
+                "version": record.version,
                 "upload_id": payload.upload_id,
             })
             .to_string();
@@ -931,6 +964,305 @@ pub async fn complete_upload(
 
             Json(record).into_response()
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn list_file_versions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    let token = match extract_auth_token(&headers, None) {
+        Ok(t) => t,
+        Err(resp) => return resp,
+    };
+
+    if let Err(resp) = authorize_file_access(&state, &token, &id).await {
+        return resp;
+    }
+
+    match state.storage_service.list_versions(&id).await {
+        Ok(versions) => Json(versions).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn restore_file_version(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path((id, version_id)): Path<(String, String)>,
+) -> Response {
+    let token = match extract_auth_token(&headers, None) {
+        Ok(t) => t,
+        Err(resp) => return resp,
+    };
+    let claims = match state.auth_service.validate_token(&token).await {
+        Ok(c) => c,
+        Err(_) => return (StatusCode::UNAUTHORIZED, "Invalid Token").into_response(),
+    };
+    let ip = extract_ip(&headers, addr);
+
+    let record = match state.storage_service.get_file(&id).await {
+        Ok(r) => r,
+        Err(_) => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    if !claims.is_super_admin {
+        if claims.tenant_id.as_deref() != Some(record.tenant_id.as_str()) {
+            return (StatusCode::FORBIDDEN, "No Tenant Context").into_response();
+        }
+        if state
+            .auth_service
+            .check_permission(&claims.sub, &record.tenant_id, "storage", "upload")
+            .await
+            .is_err()
+        {
+            return (StatusCode::FORBIDDEN, "Forbidden").into_response();
+        }
+    }
+
+    match state
+        .storage_service
+        .restore_version(&id, &version_id, Some(&claims.sub))
+        .await
+    {
+        Ok(restored) => {
+            let details = serde_json::json!({
+                "file_id": restored.id,
+                "tenant_id": restored.tenant_id,
+                "original_name": restored.original_name,
+                "version_id": version_id,
+                "replaced_version": record.version,
+                "version": restored.version,
+            })
+            .to_string();
+            state
+                .audit_service
+                .log(
+                    Some(&claims.sub),
+                    claims.tenant_id.as_deref(),
+                    "restore",
+                    "file_records",
+                    Some(&id),
+                    Some(details.as_str()),
+                    Some(&ip),
+                )
+                .await;
+
+            Json(restored).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFileLinkRequest {
+    pub expires_in_minutes: Option<i64>,
+}
+
+/// Signed, time-limited download link to share a file outside the app.
+pub async fn create_file_link(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(payload): Json<CreateFileLinkRequest>,
+) -> Response {
+    let token = match extract_auth_token(&headers, None) {
+        Ok(t) => t,
+        Err(resp) => return resp,
+    };
+    let claims = match authorize_file_access(&state, &token, &id).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+    let ip = extract_ip(&headers, addr);
+
+    match state
+        .storage_service
+        .create_signed_link(&id, payload.expires_in_minutes)
+        .await
+    {
+        Ok(link) => {
+            let details = serde_json::json!({
+                "file_id": link.file_id,
+                "expires_at": link.expires_at,
+            })
+            .to_string();
+            state
+                .audit_service
+                .log(
+                    Some(&claims.sub),
+                    claims.tenant_id.as_deref(),
+                    "share",
+                    "file_records",
+                    Some(&id),
+                    Some(details.as_str()),
+                    Some(&ip),
+                )
+                .await;
+
+            Json(link).into_response()
+        }
+        Err(e) => e.into_response(),
     }
 }
+
+/// Claims and tenant of a tenant user allowed to `storage:{action}`.
+async fn tenant_storage_claims(
+    state: &AppState,
+    headers: &HeaderMap,
+    action: &str,
+) -> Result<(crate::services::Claims, String), AppError> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or(AppError::Unauthorized)?;
+    let claims = state.auth_service.validate_token(token).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(AppError::Validation("Tenant context required".to_string()))?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "storage", action)
+        .await?;
+    Ok((claims, tenant_id))
+}
+
+// GET /api/storage/usage
+pub async fn get_storage_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<crate::models::StorageUsageReport>, AppError> {
+    let (_, tenant_id) = tenant_storage_claims(&state, &headers, "read").await?;
+    Ok(Json(state.storage_policies.usage_report(&tenant_id).await?))
+}
+
+// GET /api/storage/cleanup-rules
+pub async fn list_cleanup_rules(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<crate::models::StorageCleanupRule>>, AppError> {
+    let (_, tenant_id) = tenant_storage_claims(&state, &headers, "read").await?;
+    Ok(Json(state.storage_policies.list_rules(&tenant_id).await?))
+}
+
+// POST /api/storage/cleanup-rules
+pub async fn create_cleanup_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<crate::models::UpsertStorageCleanupRuleDto>,
+) -> Result<Json<crate::models::StorageCleanupRule>, AppError> {
+    let (claims, tenant_id) = tenant_storage_claims(&state, &headers, "delete").await?;
+    let rule = state
+        .storage_policies
+        .create_rule(&tenant_id, &claims.sub, dto)
+        .await?;
+
+    let details = serde_json::to_string(&rule).ok();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "create",
+            "storage_cleanup_rule",
+            Some(&rule.id),
+            details.as_deref(),
+            Some(&extract_ip(&headers, addr)),
+        )
+        .await;
+
+    Ok(Json(rule))
+}
+
+// PUT /api/storage/cleanup-rules/{id}
+pub async fn update_cleanup_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<crate::models::UpsertStorageCleanupRuleDto>,
+) -> Result<Json<crate::models::StorageCleanupRule>, AppError> {
+    let (claims, tenant_id) = tenant_storage_claims(&state, &headers, "delete").await?;
+    let rule = state
+        .storage_policies
+        .update_rule(&tenant_id, &id, dto)
+        .await?;
+
+    let details = serde_json::to_string(&rule).ok();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "update",
+            "storage_cleanup_rule",
+            Some(&id),
+            details.as_deref(),
+            Some(&extract_ip(&headers, addr)),
+        )
+        .await;
+
+    Ok(Json(rule))
+}
+
+// DELETE /api/storage/cleanup-rules/{id}
+pub async fn delete_cleanup_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> Result<Json<()>, AppError> {
+    let (claims, tenant_id) = tenant_storage_claims(&state, &headers, "delete").await?;
+    state.storage_policies.delete_rule(&tenant_id, &id).await?;
+
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "delete",
+            "storage_cleanup_rule",
+            Some(&id),
+            None,
+            Some(&extract_ip(&headers, addr)),
+        )
+        .await;
+
+    Ok(Json(()))
+}
+
+// POST /api/storage/cleanup-rules/{id}/run
+pub async fn run_cleanup_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> Result<Json<crate::models::StorageCleanupResult>, AppError> {
+    let (claims, tenant_id) = tenant_storage_claims(&state, &headers, "delete").await?;
+    let rule = state.storage_policies.get_rule(&tenant_id, &id).await?;
+    let result = state.storage_policies.run_rule(&rule).await?;
+
+    let details = serde_json::to_string(&result).ok();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "run",
+            "storage_cleanup_rule",
+            Some(&id),
+            details.as_deref(),
+            Some(&extract_ip(&headers, addr)),
+        )
+        .await;
+
+    Ok(Json(result))
+}
@@ -1,6 +1,7 @@
 use super::AppState;
 use crate::http::auth::extract_ip;
 use crate::models::Tenant;
+use crate::services::tenant_transfer::{TenantImportOptions, TenantImportReport};
 use axum::{
     extract::ConnectInfo,
     extract::{Path, State},
@@ -191,6 +192,45 @@ pub async fn create_tenant(
     Ok(Json(tenant))
 }
 
+/// Move a tenant onto its own Postgres schema (schema isolation mode only).
+pub async fn isolate_tenant(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, crate::error::AppError> {
+    let claims = check_super_admin(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+
+    let exists: bool = sqlx::query_scalar("SELECT count(*) > 0 FROM tenants WHERE id = $1")
+        .bind(&id)
+        .fetch_one(&state.auth_service.pool)
+        .await?;
+    if !exists {
+        return Err(crate::error::AppError::NotFound(
+            "Tenant not found".to_string(),
+        ));
+    }
+
+    let schema = state.tenant_db.isolate(&id).await?;
+
+    let details = json!({ "message": "Isolated tenant", "schema": schema }).to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&id),
+            "isolate",
+            "tenant",
+            Some(&id),
+            Some(details.as_str()),
+            Some(&ip),
+        )
+        .await;
+
+    Ok(Json(json!({ "tenant_id": id, "schema": schema })))
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
@@ -290,3 +330,151 @@ pub async fn update_tenant(
 
     Ok(Json(tenant))
 }
+
+/// Download a portable archive of one tenant's data (see `services::tenant_transfer`).
+pub async fn export_tenant(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> Result<impl axum::response::IntoResponse, crate::error::AppError> {
+    let claims = check_super_admin(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+
+    let dir = std::env::temp_dir().join(format!("tenant_export_{}", uuid::Uuid::new_v4()));
+    let export = state
+        .tenant_db
+        .scope(
+            Some(&id),
+            state.tenant_transfer_service.export_tenant(&id, &dir),
+        )
+        .await;
+    let bytes = match &export {
+        Ok(summary) => tokio::fs::read(&summary.path)
+            .await
+            .map_err(|e| crate::error::AppError::Internal(e.to_string())),
+        Err(_) => Ok(Vec::new()),
+    };
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+    let (summary, bytes) = (export?, bytes?);
+
+    let details = json!({
+        "message": "Exported tenant",
+        "filename": summary.filename,
+        "tables": summary.tables,
+        "rows": summary.rows,
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&id),
+            "export",
+            "tenant",
+            Some(&id),
+            Some(details.as_str()),
+            Some(&ip),
+        )
+        .await;
+
+    let disposition = format!("attachment; filename=\"{}\"", summary.filename);
+    Ok((
+        [
+            (
+                axum::http::header::CONTENT_TYPE,
+                axum::http::HeaderValue::from_static("application/zip"),
+            ),
+            (
+                axum::http::header::CONTENT_DISPOSITION,
+                axum::http::HeaderValue::from_str(&disposition).map_err(|_| {
+                    crate::error::AppError::Internal("Invalid header value".to_string())
+                })?,
+            ),
+        ],
+        bytes,
+    ))
+}
+
+/// Import a tenant export archive. Multipart fields: `file`, and optionally
+/// `asCopy` ("true"), `name` and `slug`.
+pub async fn import_tenant(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    mut multipart: axum::extract::Multipart,
+) -> Result<Json<TenantImportReport>, crate::error::AppError> {
+    let claims = check_super_admin(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+
+    let temp_path =
+        std::env::temp_dir().join(format!("tenant_import_{}.zip", uuid::Uuid::new_v4()));
+    let mut options = TenantImportOptions::default();
+    let mut file_saved = false;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| crate::error::AppError::Validation(e.to_string()))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "file" {
+            let data = field
+                .bytes()
+                .await
+                .map_err(|e| crate::error::AppError::Internal(e.to_string()))?;
+            tokio::fs::write(&temp_path, data)
+                .await
+                .map_err(|e| crate::error::AppError::Internal(e.to_string()))?;
+            file_saved = true;
+            continue;
+        }
+
+        let value = field
+            .text()
+            .await
+            .map_err(|e| crate::error::AppError::Validation(e.to_string()))?;
+        match name.as_str() {
+            "asCopy" => options.as_copy = value.trim() == "true",
+            "name" => options.name = Some(value),
+            "slug" => options.slug = Some(value),
+            _ => {}
+        }
+    }
+    if !file_saved {
+        return Err(crate::error::AppError::Validation(
+            "No file uploaded".to_string(),
+        ));
+    }
+
+    let res = state
+        .tenant_transfer_service
+        .import_tenant(&temp_path, &options)
+        .await;
+    let _ = tokio::fs::remove_file(&temp_path).await;
+    let report = res?;
+
+    let details = json!({
+        "message": "Imported tenant",
+        "source_tenant_id": report.source_tenant_id,
+        "as_copy": options.as_copy,
+        "slug": report.slug,
+        "users_created": report.users_created,
+        "users_matched": report.users_matched,
+        "warnings": report.warnings.len(),
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&report.tenant_id),
+            "import",
+            "tenant",
+            Some(&report.tenant_id),
+            Some(details.as_str()),
+            Some(&ip),
+        )
+        .await;
+
+    Ok(Json(report))
+}
@@ -1,12 +1,20 @@
 use super::AppState;
 use crate::models::{
-    CreateSupportTicketDto, FileRecord, PaginatedResponse, ReplySupportTicketDto, SupportTicket,
-    SupportTicketDetail, SupportTicketListItem, SupportTicketMessage,
-    SupportTicketMessageWithAttachments, UpdateSupportTicketDto,
+    ApplySupportMacroResult, CreateSupportTicketDto, CreateSupportTicketLinkDto, FileRecord,
+    InboundEmailResult, LinkedSupportTicket, PaginatedResponse, RenderedSupportMacro,
+    ReplySupportTicketDto, SupportEscalationRule, SupportMacro, SupportQueue, SupportTicket,
+    SupportTicketDetail, SupportTicketLink, SupportTicketListItem, SupportTicketMessage,
+    SupportTicketMessageWithAttachments, UpdateSupportTicketDto, UpsertSupportEscalationRuleDto,
+    UpsertSupportMacroDto, UpsertSupportQueueDto,
 };
+use crate::services::event_outbox_service::{self, DomainEvent, EventOutboxService};
+use crate::services::support_escalation_service::status_after_reply;
+use crate::services::support_inbound_service::{ticket_ref, InboundOutcome};
+use crate::services::support_routing_service::normalize_category;
 use axum::{
-    extract::{Path, Query, State},
-    http::HeaderMap,
+    body::Bytes,
+    extract::{FromRequest, Multipart, Path, Query, Request, State},
+    http::{header::CONTENT_TYPE, HeaderMap},
     Json,
 };
 use chrono::Utc;
@@ -116,12 +124,13 @@ async fn notify_support_ticket_reply(
             } else {
                 "Ticket updated"
             };
+            // The [#ref] token lets an emailed reply find its way back to this ticket.
             let _ = state
                 .notification_service
                 .create_notification(
                     owner,
                     Some(tenant_id.to_string()),
-                    title.to_string(),
+                    format!("{title} [#{}]", ticket_ref(&ticket.id)),
                     ticket.subject.clone(),
                     "info".to_string(),
                     "support".to_string(),
@@ -195,15 +204,52 @@ async fn broadcast_support_ticket_message_created(
         recipients.insert(uid);
     }
 
-    for uid in recipients {
-        state
-            .ws_hub
-            .broadcast(crate::http::WsEvent::SupportTicketMessageCreated {
-                user_id: uid,
-                tenant_id: Some(tenant_id.to_string()),
-                ticket_id: ticket.id.clone(),
-                message_id: message_id.to_string(),
-            });
+    let events = recipients
+        .into_iter()
+        .map(|uid| crate::http::WsEvent::SupportTicketMessageCreated {
+            user_id: uid,
+            tenant_id: Some(tenant_id.to_string()),
+            ticket_id: ticket.id.clone(),
+            message_id: message_id.to_string(),
+        })
+        .collect();
+    state.event_outbox.publish(Some(tenant_id), events).await;
+}
+
+/// Route a freshly created ticket to its queue (best-effort) and tell the
+/// auto-assigned agent about it.
+async fn route_new_ticket(state: &AppState, tenant_id: &str, ticket: &mut SupportTicket) {
+    let route = match state
+        .support_routing
+        .route_ticket(tenant_id, &ticket.id, ticket.category.as_deref())
+        .await
+    {
+        Ok(Some(route)) => route,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!("Support ticket {} routing failed: {}", ticket.id, e);
+            return;
+        }
+    };
+
+    ticket.queue_id = Some(route.queue_id);
+    if ticket.assigned_to == route.assigned_to {
+        return;
+    }
+    ticket.assigned_to = route.assigned_to;
+    if let Some(assignee) = ticket.assigned_to.clone() {
+        let _ = state
+            .notification_service
+            .create_notification(
+                assignee,
+                Some(tenant_id.to_string()),
+                format!("Ticket assigned [#{}]", ticket_ref(&ticket.id)),
+                ticket.subject.clone(),
+                "info".to_string(),
+                "support".to_string(),
+                Some(format!("/admin/support/{}", ticket.id)),
+            )
+            .await;
     }
 }
 
@@ -213,6 +259,10 @@ pub struct SupportTicketStats {
     pub open: i64,
     pub pending: i64,
     pub closed: i64,
+    /// Open or pending tickets assigned to the caller (staff only).
+    pub mine: i64,
+    /// Open or pending tickets with no assignee (staff only).
+    pub unassigned: i64,
 }
 
 #[derive(Deserialize)]
@@ -221,6 +271,8 @@ pub struct ListParams {
     pub search: Option<String>,
     pub page: Option<u32>,
     pub per_page: Option<u32>,
+    pub queue_id: Option<String>,
+    pub assigned: Option<String>, // me | unassigned (staff only)
 }
 
 async fn auth_claims(
@@ -277,6 +329,11 @@ pub async fn list_support_tickets(
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty());
 
+    // "My tickets" / "Unassigned" views and queue filter (staff list only).
+    let queue_id = params.queue_id.filter(|q| !q.trim().is_empty());
+    let assignee = (params.assigned.as_deref() == Some("me")).then(|| claims.sub.clone());
+    let unassigned_only = params.assigned.as_deref() == Some("unassigned");
+
     let (rows, total): (Vec<SupportTicketListItem>, i64) = if can_all {
         let total: i64 = sqlx::query_scalar(
             r#"
@@ -290,11 +347,17 @@ pub async fn list_support_tickets(
                 OR LOWER(t.subject) LIKE '%' || LOWER($3) || '%'
                 OR LOWER(COALESCE(u.name, '')) LIKE '%' || LOWER($3) || '%'
               )
+              AND ($4::text IS NULL OR t.queue_id = $4)
+              AND ($5::text IS NULL OR t.assigned_to = $5)
+              AND (NOT $6 OR t.assigned_to IS NULL)
         "#,
         )
         .bind(&tenant_id)
         .bind(st.clone())
         .bind(search.clone())
+        .bind(queue_id.clone())
+        .bind(assignee.clone())
+        .bind(unassigned_only)
         .fetch_one(&state.auth_service.pool)
         .await?;
 
@@ -314,13 +377,19 @@ pub async fn list_support_tickets(
                 OR LOWER(t.subject) LIKE '%' || LOWER($3) || '%'
                 OR LOWER(COALESCE(u.name, '')) LIKE '%' || LOWER($3) || '%'
               )
+              AND ($4::text IS NULL OR t.queue_id = $4)
+              AND ($5::text IS NULL OR t.assigned_to = $5)
+              AND (NOT $6 OR t.assigned_to IS NULL)
             ORDER BY COALESCE((SELECT MAX(created_at) FROM support_ticket_messages m WHERE m.ticket_id = t.id), t.updated_at) DESC
-            LIMIT $4 OFFSET $5
+            LIMIT $7 OFFSET $8
         "#,
         )
         .bind(&tenant_id)
         .bind(st)
         .bind(search)
+        .bind(queue_id)
+        .bind(assignee)
+        .bind(unassigned_only)
         .bind(per_page as i64)
         .bind(offset)
         .fetch_all(&state.auth_service.pool)
@@ -422,6 +491,8 @@ pub async fn get_support_ticket_stats(
         open: i64,
         pending: i64,
         closed: i64,
+        mine: i64,
+        unassigned: i64,
     }
 
     let row: Row = if can_all {
@@ -431,12 +502,15 @@ pub async fn get_support_ticket_stats(
               COUNT(*) AS all,
               COALESCE(SUM(CASE WHEN status = 'open' THEN 1 ELSE 0 END), 0) AS open,
               COALESCE(SUM(CASE WHEN status = 'pending' THEN 1 ELSE 0 END), 0) AS pending,
-              COALESCE(SUM(CASE WHEN status = 'closed' THEN 1 ELSE 0 END), 0) AS closed
+              COALESCE(SUM(CASE WHEN status = 'closed' THEN 1 ELSE 0 END), 0) AS closed,
+              COALESCE(SUM(CASE WHEN status <> 'closed' AND assigned_to = $2 THEN 1 ELSE 0 END), 0) AS mine,
+              COALESCE(SUM(CASE WHEN status <> 'closed' AND assigned_to IS NULL THEN 1 ELSE 0 END), 0) AS unassigned
             FROM support_tickets
             WHERE tenant_id = $1
         "#,
         )
         .bind(&tenant_id)
+        .bind(&claims.sub)
         .fetch_one(&state.auth_service.pool)
         .await?
     } else {
@@ -446,7 +520,9 @@ pub async fn get_support_ticket_stats(
               COUNT(*) AS all,
               COALESCE(SUM(CASE WHEN status = 'open' THEN 1 ELSE 0 END), 0) AS open,
               COALESCE(SUM(CASE WHEN status = 'pending' THEN 1 ELSE 0 END), 0) AS pending,
-              COALESCE(SUM(CASE WHEN status = 'closed' THEN 1 ELSE 0 END), 0) AS closed
+              COALESCE(SUM(CASE WHEN status = 'closed' THEN 1 ELSE 0 END), 0) AS closed,
+              0::bigint AS mine,
+              0::bigint AS unassigned
             FROM support_tickets
             WHERE tenant_id = $1 AND created_by = $2
         "#,
@@ -462,6 +538,8 @@ pub async fn get_support_ticket_stats(
         open: row.open,
         pending: row.pending,
         closed: row.closed,
+        mine: row.mine,
+        unassigned: row.unassigned,
     }))
 }
 
@@ -507,9 +585,9 @@ pub async fn create_support_ticket(
         r#"
         INSERT INTO support_tickets (
             id, tenant_id, created_by, subject, status, priority, assigned_to,
-            created_at, updated_at, closed_at
+            category, created_at, updated_at, closed_at
         )
-        VALUES ($1,$2,$3,$4,'open',$5,NULL,$6,$7,NULL)
+        VALUES ($1,$2,$3,$4,'open',$5,NULL,$6,$7,$8,NULL)
     "#,
     )
     .bind(&ticket_id)
@@ -517,6 +595,7 @@ pub async fn create_support_ticket(
     .bind(&claims.sub)
     .bind(dto.subject.trim())
     .bind(&priority)
+    .bind(normalize_category(dto.category.as_deref()))
     .bind(now)
     .bind(now)
     .execute(&mut *tx)
@@ -541,7 +620,7 @@ pub async fn create_support_ticket(
         attach_files_pg(&mut tx, &tenant_id, &msg_id, file_ids).await?;
     }
 
-    let ticket: SupportTicket = sqlx::query_as("SELECT * FROM support_tickets WHERE id = $1")
+    let mut ticket: SupportTicket = sqlx::query_as("SELECT * FROM support_tickets WHERE id = $1")
         .bind(&ticket_id)
         .fetch_one(&mut *tx)
         .await?;
@@ -553,7 +632,16 @@ pub async fn create_support_ticket(
     .fetch_all(&mut *tx)
     .await?;
 
+    EventOutboxService::stage(
+        &mut tx,
+        Some(tenant_id.as_str()),
+        &DomainEvent::ticket_created(&ticket),
+    )
+    .await?;
     tx.commit().await?;
+    event_outbox_service::wake();
+
+    route_new_ticket(&state, &tenant_id, &mut ticket).await;
 
     // Audit (best-effort; does not fail request on error)
     let audit_details = serde_json::json!({
@@ -586,7 +674,7 @@ pub async fn create_support_ticket(
         .create_notification(
             claims.sub.clone(),
             Some(tenant_id.clone()),
-            "Ticket created".to_string(),
+            format!("Ticket created [#{}]", ticket_ref(&ticket_id)),
             ticket.subject.clone(),
             "success".to_string(),
             "support".to_string(),
@@ -750,19 +838,14 @@ pub async fn reply_support_ticket(
             .fetch_one(&state.auth_service.pool)
             .await?;
 
-    if ticket.status == "closed" {
-        return Err(crate::error::AppError::Validation(
-            "Ticket is closed".to_string(),
-        ));
-    }
-
     let can_all = state
         .auth_service
         .has_permission(&claims.sub, &tenant_id, "support", "read_all")
         .await
         .unwrap_or(false);
 
-    if !can_all && ticket.created_by.as_deref() != Some(claims.sub.as_str()) {
+    let is_owner = ticket.created_by.as_deref() == Some(claims.sub.as_str());
+    if !can_all && !is_owner {
         return Err(crate::error::AppError::Forbidden("Forbidden".to_string()));
     }
 
@@ -775,6 +858,16 @@ pub async fn reply_support_ticket(
     }
 
     let now = Utc::now();
+    let by_customer = is_owner && !is_internal;
+    let reopen_window = if by_customer && ticket.status == "closed" {
+        state
+            .support_escalation
+            .reopen_window_days(&tenant_id)
+            .await
+    } else {
+        0
+    };
+    let new_status = status_after_reply(&ticket, by_customer, reopen_window, now)?;
     let msg_id = Uuid::new_v4().to_string();
 
     let mut tx = state.auth_service.pool.begin().await?;
@@ -803,11 +896,25 @@ pub async fn reply_support_ticket(
         attach_files_pg(&mut tx, &tenant_id, &msg_id, file_ids).await?;
     }
 
-    sqlx::query("UPDATE support_tickets SET updated_at = $1 WHERE id = $2")
-        .bind(now)
-        .bind(&id)
-        .execute(&mut *tx)
-        .await?;
+    match new_status {
+        Some(status) => {
+            sqlx::query(
+                "UPDATE support_tickets SET status = $1, closed_at = NULL, updated_at = $2 WHERE id = $3",
+            )
+            .bind(status)
+            .bind(now)
+            .bind(&id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        None => {
+            sqlx::query("UPDATE support_tickets SET updated_at = $1 WHERE id = $2")
+                .bind(now)
+                .bind(&id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
 
     let msg: SupportTicketMessage =
         sqlx::query_as("SELECT * FROM support_ticket_messages WHERE id = $1")
@@ -822,6 +929,7 @@ pub async fn reply_support_ticket(
         "message_id": msg_id,
         "internal": is_internal,
         "attachments": dto.attachment_ids.as_ref().map(|v| v.len()).unwrap_or(0),
+        "status": new_status,
     })
     .to_string();
     state
@@ -912,14 +1020,14 @@ pub async fn update_support_ticket(
         }
     });
 
-    if status.is_some() || priority.is_some() {
+    if status.is_some() || priority.is_some() || dto.category.is_some() {
         state
             .auth_service
             .check_permission(&claims.sub, &tenant_id, "support", "update")
             .await?;
     }
 
-    if dto.assigned_to.is_some() {
+    if dto.assigned_to.is_some() || dto.queue_id.is_some() {
         state
             .auth_service
             .check_permission(&claims.sub, &tenant_id, "support", "assign")
@@ -936,10 +1044,34 @@ pub async fn update_support_ticket(
     let old_status = existing.status.clone();
     let old_priority = existing.priority.clone();
     let old_assigned_to = existing.assigned_to.clone();
+    let old_queue_id = existing.queue_id.clone();
+    let old_category = existing.category.clone();
+
+    let assigned_to = match dto.assigned_to.map(|a| a.trim().to_string()) {
+        Some(a) if a.is_empty() => None,
+        Some(a) => {
+            if existing.assigned_to.as_deref() != Some(a.as_str()) {
+                state.support_routing.ensure_agent(&tenant_id, &a).await?;
+            }
+            Some(a)
+        }
+        None => existing.assigned_to,
+    };
+    let queue_id = match dto.queue_id.map(|q| q.trim().to_string()) {
+        Some(q) if q.is_empty() => None,
+        Some(q) => {
+            state.support_routing.ensure_queue(&tenant_id, &q).await?;
+            Some(q)
+        }
+        None => existing.queue_id,
+    };
+    let category = match dto.category {
+        Some(c) => normalize_category(Some(&c)),
+        None => existing.category,
+    };
 
     let new_status = status.unwrap_or(existing.status);
     let new_priority = priority.unwrap_or(existing.priority);
-    let assigned_to = dto.assigned_to.or(existing.assigned_to);
     let closed_at = if new_status == "closed" {
         Some(now)
     } else {
@@ -952,15 +1084,19 @@ pub async fn update_support_ticket(
         SET status = $1,
             priority = $2,
             assigned_to = $3,
-            updated_at = $4,
-            closed_at = $5
-        WHERE id = $6 AND tenant_id = $7
+            queue_id = $4,
+            category = $5,
+            updated_at = $6,
+            closed_at = $7
+        WHERE id = $8 AND tenant_id = $9
         RETURNING *
     "#,
     )
     .bind(new_status)
     .bind(new_priority)
     .bind(assigned_to)
+    .bind(queue_id)
+    .bind(category)
     .bind(now)
     .bind(closed_at)
     .bind(&id)
@@ -984,11 +1120,15 @@ pub async fn update_support_ticket(
             "status": old_status,
             "priority": old_priority,
             "assigned_to": old_assigned_to,
+            "queue_id": old_queue_id,
+            "category": old_category,
         },
         "to": {
             "status": ticket.status,
             "priority": ticket.priority,
             "assigned_to": ticket.assigned_to,
+            "queue_id": ticket.queue_id,
+            "category": ticket.category,
         }
     })
     .to_string();
@@ -1082,6 +1222,875 @@ pub async fn update_support_ticket(
     Ok(Json(ticket))
 }
 
+/// Inbound support email from the mail provider: raw MIME, or a form with an
+/// `email` (SendGrid) or `body-mime` (Mailgun) field. The path token picks the
+/// tenant; see `SupportInboundService` for threading.
+// POST /api/support/inbound/{token}
+pub async fn inbound_support_email(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    request: Request,
+) -> Result<Json<InboundEmailResult>, crate::error::AppError> {
+    let is_form = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.starts_with("multipart/form-data"))
+        .unwrap_or(false);
+
+    let raw: Vec<u8> = if is_form {
+        let mut multipart = Multipart::from_request(request, &state)
+            .await
+            .map_err(|e| crate::error::AppError::Validation(e.to_string()))?;
+        let mut raw = None;
+        while let Some(field) = multipart
+            .next_field()
+            .await
+            .map_err(|e| crate::error::AppError::Validation(e.to_string()))?
+        {
+            if matches!(field.name(), Some("email") | Some("body-mime")) {
+                let data = field
+                    .bytes()
+                    .await
+                    .map_err(|e| crate::error::AppError::Validation(e.to_string()))?;
+                raw = Some(data.to_vec());
+                break;
+            }
+        }
+        raw.ok_or_else(|| {
+            crate::error::AppError::Validation(
+                "Form has no 'email' or 'body-mime' field".to_string(),
+            )
+        })?
+    } else {
+        Bytes::from_request(request, &state)
+            .await
+            .map_err(|e| crate::error::AppError::Validation(e.to_string()))?
+            .to_vec()
+    };
+
+    let (mut ticket, message_id, author_id, created) =
+        match state.support_inbound.handle(&token, &raw).await? {
+            InboundOutcome::Posted {
+                ticket,
+                message_id,
+                author_id,
+                created,
+            } => (ticket, message_id, author_id, created),
+            InboundOutcome::Ignored(reason) => {
+                return Ok(Json(InboundEmailResult {
+                    status: "ignored".to_string(),
+                    ticket_id: None,
+                    reason: Some(reason),
+                }));
+            }
+        };
+
+    if created {
+        let tenant_id = ticket.tenant_id.clone();
+        route_new_ticket(&state, &tenant_id, &mut ticket).await;
+    }
+
+    let audit_details = serde_json::json!({
+        "message_id": message_id,
+        "via": "email",
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&author_id),
+            Some(&ticket.tenant_id),
+            if created { "create" } else { "reply" },
+            "support_ticket",
+            Some(&ticket.id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    #[cfg(feature = "postgres")]
+    if created {
+        notify_support_admins_new_ticket(
+            &state,
+            &ticket.tenant_id,
+            &ticket.id,
+            &author_id,
+            &ticket.subject,
+        )
+        .await;
+    } else {
+        notify_support_ticket_reply(&state, &ticket.tenant_id, &ticket, &author_id, false).await;
+        broadcast_support_ticket_message_created(
+            &state,
+            &ticket.tenant_id,
+            &ticket,
+            &author_id,
+            false,
+            &message_id,
+        )
+        .await;
+    }
+
+    Ok(Json(InboundEmailResult {
+        status: if created { "created" } else { "replied" }.to_string(),
+        ticket_id: Some(ticket.id),
+        reason: None,
+    }))
+}
+
+// GET /api/support/queues
+pub async fn list_support_queues(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SupportQueue>>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await?;
+
+    Ok(Json(state.support_routing.list_queues(&tenant_id).await?))
+}
+
+// POST /api/support/queues
+pub async fn create_support_queue(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(dto): Json<UpsertSupportQueueDto>,
+) -> Result<Json<SupportQueue>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "queues")
+        .await?;
+
+    let queue = state.support_routing.create_queue(&tenant_id, dto).await?;
+
+    let audit_details = serde_json::json!({
+        "name": queue.name,
+        "categories": queue.categories,
+        "members": queue.member_ids.len(),
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "create",
+            "support_queue",
+            Some(&queue.id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(queue))
+}
+
+// PUT /api/support/queues/{id}
+pub async fn update_support_queue(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(dto): Json<UpsertSupportQueueDto>,
+) -> Result<Json<SupportQueue>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "queues")
+        .await?;
+
+    let queue = state
+        .support_routing
+        .update_queue(&tenant_id, &id, dto)
+        .await?;
+
+    let audit_details = serde_json::json!({
+        "name": queue.name,
+        "categories": queue.categories,
+        "members": queue.member_ids.len(),
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "update",
+            "support_queue",
+            Some(&id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(queue))
+}
+
+// DELETE /api/support/queues/{id}
+pub async fn delete_support_queue(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<()>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "queues")
+        .await?;
+
+    state.support_routing.delete_queue(&tenant_id, &id).await?;
+
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "delete",
+            "support_queue",
+            Some(&id),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(()))
+}
+
+// GET /api/support/escalation-rules
+pub async fn list_support_escalation_rules(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SupportEscalationRule>>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await?;
+
+    Ok(Json(state.support_escalation.list_rules(&tenant_id).await?))
+}
+
+// POST /api/support/escalation-rules
+pub async fn create_support_escalation_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(dto): Json<UpsertSupportEscalationRuleDto>,
+) -> Result<Json<SupportEscalationRule>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "queues")
+        .await?;
+
+    let rule = state
+        .support_escalation
+        .create_rule(&tenant_id, dto)
+        .await?;
+
+    let audit_details = serde_json::json!({
+        "name": rule.name,
+        "idle_minutes": rule.idle_minutes,
+        "bump_priority": rule.bump_priority,
+        "reassign_to": rule.reassign_to,
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "create",
+            "support_escalation_rule",
+            Some(&rule.id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(rule))
+}
+
+// PUT /api/support/escalation-rules/{id}
+pub async fn update_support_escalation_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(dto): Json<UpsertSupportEscalationRuleDto>,
+) -> Result<Json<SupportEscalationRule>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "queues")
+        .await?;
+
+    let rule = state
+        .support_escalation
+        .update_rule(&tenant_id, &id, dto)
+        .await?;
+
+    let audit_details = serde_json::json!({
+        "name": rule.name,
+        "is_active": rule.is_active,
+        "idle_minutes": rule.idle_minutes,
+        "bump_priority": rule.bump_priority,
+        "reassign_to": rule.reassign_to,
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "update",
+            "support_escalation_rule",
+            Some(&id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(rule))
+}
+
+// DELETE /api/support/escalation-rules/{id}
+pub async fn delete_support_escalation_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<()>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "queues")
+        .await?;
+
+    state
+        .support_escalation
+        .delete_rule(&tenant_id, &id)
+        .await?;
+
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "delete",
+            "support_escalation_rule",
+            Some(&id),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(()))
+}
+
+// GET /api/support/macros
+pub async fn list_support_macros(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SupportMacro>>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await?;
+
+    Ok(Json(state.support_macros.list(&tenant_id).await?))
+}
+
+// POST /api/support/macros
+pub async fn create_support_macro(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(dto): Json<UpsertSupportMacroDto>,
+) -> Result<Json<SupportMacro>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "macros")
+        .await?;
+
+    let m = state
+        .support_macros
+        .create(&tenant_id, &claims.sub, dto)
+        .await?;
+
+    let audit_details = serde_json::json!({ "name": m.name }).to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "create",
+            "support_macro",
+            Some(&m.id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(m))
+}
+
+// PUT /api/support/macros/{id}
+pub async fn update_support_macro(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(dto): Json<UpsertSupportMacroDto>,
+) -> Result<Json<SupportMacro>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "macros")
+        .await?;
+
+    let m = state.support_macros.update(&tenant_id, &id, dto).await?;
+
+    let audit_details = serde_json::json!({ "name": m.name }).to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "update",
+            "support_macro",
+            Some(&id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(m))
+}
+
+// DELETE /api/support/macros/{id}
+pub async fn delete_support_macro(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<()>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "macros")
+        .await?;
+
+    state.support_macros.delete(&tenant_id, &id).await?;
+
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "delete",
+            "support_macro",
+            Some(&id),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(()))
+}
+
+/// Canned response text for one ticket, so the agent can edit it before sending.
+// GET /api/support/tickets/{id}/macros/{macro_id}/preview
+pub async fn preview_support_macro(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((id, macro_id)): Path<(String, String)>,
+) -> Result<Json<RenderedSupportMacro>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await?;
+
+    let ticket: SupportTicket =
+        sqlx::query_as("SELECT * FROM support_tickets WHERE id = $1 AND tenant_id = $2")
+            .bind(&id)
+            .bind(&tenant_id)
+            .fetch_one(&state.auth_service.pool)
+            .await?;
+    let m = state.support_macros.get(&tenant_id, &macro_id).await?;
+    let body = state
+        .support_macros
+        .render(&tenant_id, &m, &ticket, &claims.sub)
+        .await?;
+
+    Ok(Json(RenderedSupportMacro { body }))
+}
+
+/// Run a macro: post its reply and apply its status/priority/assignee.
+// POST /api/support/tickets/{id}/macros/{macro_id}
+pub async fn apply_support_macro(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((id, macro_id)): Path<(String, String)>,
+) -> Result<Json<ApplySupportMacroResult>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await?;
+
+    let m = state.support_macros.get(&tenant_id, &macro_id).await?;
+
+    // Same permissions as doing each step by hand.
+    if !m.body.trim().is_empty() {
+        state
+            .auth_service
+            .check_permission(&claims.sub, &tenant_id, "support", "reply")
+            .await?;
+        if m.is_internal {
+            state
+                .auth_service
+                .check_permission(&claims.sub, &tenant_id, "support", "internal")
+                .await?;
+        }
+    }
+    if m.set_status.is_some() || m.set_priority.is_some() {
+        state
+            .auth_service
+            .check_permission(&claims.sub, &tenant_id, "support", "update")
+            .await?;
+    }
+    if m.assign_to.is_some() {
+        state
+            .auth_service
+            .check_permission(&claims.sub, &tenant_id, "support", "assign")
+            .await?;
+    }
+
+    let applied = state
+        .support_macros
+        .apply(&tenant_id, &m, &id, &claims.sub)
+        .await?;
+    let ticket = applied.ticket;
+
+    let audit_details = serde_json::json!({
+        "macro_id": m.id,
+        "macro": m.name,
+        "message_id": applied.message.as_ref().map(|msg| msg.id.clone()),
+        "from": {
+            "status": applied.previous.status,
+            "priority": applied.previous.priority,
+            "assigned_to": applied.previous.assigned_to,
+        },
+        "to": {
+            "status": ticket.status,
+            "priority": ticket.priority,
+            "assigned_to": ticket.assigned_to,
+        }
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "apply_macro",
+            "support_ticket",
+            Some(&id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    #[cfg(feature = "postgres")]
+    if let Some(msg) = applied.message.as_ref() {
+        notify_support_ticket_reply(&state, &tenant_id, &ticket, &claims.sub, msg.is_internal)
+            .await;
+        broadcast_support_ticket_message_created(
+            &state,
+            &tenant_id,
+            &ticket,
+            &claims.sub,
+            msg.is_internal,
+            &msg.id,
+        )
+        .await;
+    }
+
+    if ticket.assigned_to != applied.previous.assigned_to {
+        if let Some(assignee) = ticket.assigned_to.clone() {
+            if assignee != claims.sub {
+                let _ = state
+                    .notification_service
+                    .create_notification(
+                        assignee,
+                        Some(tenant_id.clone()),
+                        "Ticket assigned".to_string(),
+                        ticket.subject.clone(),
+                        "info".to_string(),
+                        "support".to_string(),
+                        Some(format!("/admin/support/{id}")),
+                    )
+                    .await;
+            }
+        }
+    }
+
+    Ok(Json(ApplySupportMacroResult {
+        ticket,
+        message: applied.message,
+    }))
+}
+
+// GET /api/support/tickets/{id}/links
+pub async fn list_support_ticket_links(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<SupportTicketLink>>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await?;
+
+    Ok(Json(
+        state.support_links.list_for_ticket(&tenant_id, &id).await?,
+    ))
+}
+
+// POST /api/support/tickets/{id}/links
+pub async fn link_support_ticket(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(dto): Json<CreateSupportTicketLinkDto>,
+) -> Result<Json<SupportTicketLink>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "update")
+        .await?;
+
+    let link = state
+        .support_links
+        .link(&tenant_id, &id, &claims.sub, dto)
+        .await?;
+
+    let audit_details = serde_json::json!({
+        "link_id": link.id,
+        "entity_type": link.entity_type,
+        "entity_id": link.entity_id,
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "link",
+            "support_ticket",
+            Some(&id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(link))
+}
+
+// DELETE /api/support/tickets/{id}/links/{link_id}
+pub async fn unlink_support_ticket(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((id, link_id)): Path<(String, String)>,
+) -> Result<Json<()>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "update")
+        .await?;
+
+    let link = state
+        .support_links
+        .unlink(&tenant_id, &id, &link_id)
+        .await?;
+
+    let audit_details = serde_json::json!({
+        "link_id": link.id,
+        "entity_type": link.entity_type,
+        "entity_id": link.entity_id,
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "unlink",
+            "support_ticket",
+            Some(&id),
+            Some(audit_details.as_str()),
+            None,
+        )
+        .await;
+
+    Ok(Json(()))
+}
+
+#[derive(Deserialize)]
+pub struct LinkedTicketsParams {
+    pub entity_type: String, // incident | work_order | invoice
+    pub entity_id: String,
+}
+
+/// Back-references: tickets linked to an incident, work order or invoice.
+// GET /api/support/links?entity_type=..&entity_id=..
+pub async fn list_linked_support_tickets(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<LinkedTicketsParams>,
+) -> Result<Json<Vec<LinkedSupportTicket>>, crate::error::AppError> {
+    let claims = auth_claims(&state, &headers).await?;
+    let tenant_id = claims
+        .tenant_id
+        .clone()
+        .ok_or(crate::error::AppError::Validation(
+            "Tenant context required".to_string(),
+        ))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "support", "read_all")
+        .await?;
+
+    Ok(Json(
+        state
+            .support_links
+            .tickets_for_entity(&tenant_id, &params.entity_type, &params.entity_id)
+            .await?,
+    ))
+}
+
 #[cfg(feature = "postgres")]
 async fn attach_files_pg(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
@@ -1136,6 +2145,13 @@ async fn fetch_attachments_map_pg(
         size: i64,
         content_type: String,
         storage_provider: String,
+        storage_bucket: Option<String>,
+        quota_counted: bool,
+        scan_status: String,
+        scan_signature: Option<String>,
+        scanned_at: Option<chrono::DateTime<chrono::Utc>>,
+        logical_path: Option<String>,
+        version: i32,
         uploaded_by: Option<String>,
         created_at: chrono::DateTime<chrono::Utc>,
         updated_at: chrono::DateTime<chrono::Utc>,
@@ -1150,7 +2166,9 @@ async fn fetch_attachments_map_pg(
         SELECT
             a.message_id,
             f.id, f.tenant_id, f.name, f.original_name, f.path, f.size, f.content_type,
-            f.storage_provider, f.uploaded_by, f.created_at, f.updated_at
+            f.storage_provider, f.storage_bucket, f.quota_counted, f.scan_status,
+            f.scan_signature, f.scanned_at, f.logical_path, f.version, f.uploaded_by,
+            f.created_at, f.updated_at
         FROM support_ticket_attachments a
         JOIN support_ticket_messages m ON m.id = a.message_id
         JOIN support_tickets t ON t.id = m.ticket_id
@@ -1179,6 +2197,13 @@ async fn fetch_attachments_map_pg(
             size: r.size,
             content_type: r.content_type,
             storage_provider: r.storage_provider,
+            storage_bucket: r.storage_bucket,
+            quota_counted: r.quota_counted,
+            scan_status: r.scan_status,
+            scan_signature: r.scan_signature,
+            scanned_at: r.scanned_at,
+            logical_path: r.logical_path,
+            version: r.version,
             uploaded_by: r.uploaded_by,
             created_at: r.created_at,
             updated_at: r.updated_at,
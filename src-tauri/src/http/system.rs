@@ -1,14 +1,30 @@
 //! System Health HTTP Endpoints
 
 use super::AppState;
+use crate::db::migrations::MigrationStatus;
+use crate::http::auth::extract_ip;
+use crate::models::{
+    AlertDeliveryResult, BackgroundJob, BackgroundJobStats, PaginatedResponse, SystemAlertRule,
+    UpsertSystemAlertRuleRequest,
+};
+use crate::services::auth_service::Claims;
+use crate::services::db_maintenance_service::MaintenanceReport;
 use crate::services::system_service::{SystemDiagnostics, SystemHealth};
-use axum::{extract::State, http::HeaderMap, Json};
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::Deserialize;
+use std::net::SocketAddr;
+
+const JOB_STATUSES: &[&str] = &["queued", "running", "succeeded", "failed", "dead"];
 
 // Helper to check super admin permission
 async fn check_super_admin(
     state: &AppState,
     headers: &HeaderMap,
-) -> Result<(), crate::error::AppError> {
+) -> Result<Claims, crate::error::AppError> {
     let token = headers
         .get("Authorization")
         .and_then(|h| h.to_str().ok())
@@ -21,7 +37,7 @@ async fn check_super_admin(
         return Err(crate::error::AppError::Unauthorized);
     }
 
-    Ok(())
+    Ok(claims)
 }
 
 pub async fn get_system_health(
@@ -32,8 +48,9 @@ pub async fn get_system_health(
 
     let mut health = state.system_service.get_system_health().await?;
 
-    // Inject request metrics from metrics service
+    // Inject request and pool metrics from metrics service
     health.request_metrics = Some(state.metrics_service.get_metrics());
+    health.pool_metrics = state.metrics_service.get_pool_metrics();
 
     Ok(Json(health))
 }
@@ -44,10 +61,239 @@ pub async fn get_system_diagnostics(
 ) -> Result<Json<SystemDiagnostics>, crate::error::AppError> {
     check_super_admin(&state, &headers).await?;
 
-    let diag = state
+    let mut diag = state
         .system_service
         .get_system_diagnostics(&state.settings_service)
         .await?;
+    diag.maintenance = Some(state.db_maintenance_service.snapshot().await);
 
     Ok(Json(diag))
 }
+
+pub async fn run_db_maintenance(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+) -> Result<Json<MaintenanceReport>, crate::error::AppError> {
+    let claims = check_super_admin(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+
+    let report = state.db_maintenance_service.run("manual").await?;
+
+    let details = serde_json::json!({
+        "operations": report.operations,
+        "errors": report.errors,
+        "duration_ms": report.duration_ms,
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            None,
+            "run",
+            "db_maintenance",
+            None,
+            Some(details.as_str()),
+            Some(&ip),
+        )
+        .await;
+
+    Ok(Json(report))
+}
+
+pub async fn get_migration_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<MigrationStatus>, crate::error::AppError> {
+    check_super_admin(&state, &headers).await?;
+
+    let status = state.system_service.get_migration_status().await?;
+
+    Ok(Json(status))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListJobsQuery {
+    pub status: Option<String>,
+    pub job_type: Option<String>,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+pub async fn list_background_jobs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ListJobsQuery>,
+) -> Result<Json<PaginatedResponse<BackgroundJob>>, crate::error::AppError> {
+    check_super_admin(&state, &headers).await?;
+
+    let status = query
+        .status
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty() && *s != "all");
+    if let Some(status) = status {
+        if !JOB_STATUSES.contains(&status) {
+            return Err(crate::error::AppError::Validation(format!(
+                "Unknown job status: {}",
+                status
+            )));
+        }
+    }
+    let job_type = query
+        .job_type
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+
+    let jobs = state
+        .job_queue
+        .list(
+            status,
+            job_type,
+            query.page.unwrap_or(1),
+            query.per_page.unwrap_or(25),
+        )
+        .await?;
+
+    Ok(Json(jobs))
+}
+
+pub async fn get_background_job_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<BackgroundJobStats>, crate::error::AppError> {
+    check_super_admin(&state, &headers).await?;
+
+    Ok(Json(state.job_queue.stats().await?))
+}
+
+pub async fn retry_background_job(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> Result<Json<BackgroundJob>, crate::error::AppError> {
+    let claims = check_super_admin(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+
+    let job = state.job_queue.retry(&id).await?;
+
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            None,
+            "retry",
+            "background_job",
+            Some(&job.id),
+            Some(job.job_type.as_str()),
+            Some(&ip),
+        )
+        .await;
+
+    Ok(Json(job))
+}
+
+pub async fn list_alert_rules(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SystemAlertRule>>, crate::error::AppError> {
+    check_super_admin(&state, &headers).await?;
+
+    Ok(Json(state.alert_service.list_rules().await?))
+}
+
+pub async fn create_alert_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(payload): Json<UpsertSystemAlertRuleRequest>,
+) -> Result<Json<SystemAlertRule>, crate::error::AppError> {
+    let claims = check_super_admin(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+
+    let rule = state.alert_service.create_rule(payload).await?;
+
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            None,
+            "create",
+            "system_alert_rule",
+            Some(&rule.id),
+            Some(rule.name.as_str()),
+            Some(&ip),
+        )
+        .await;
+
+    Ok(Json(rule))
+}
+
+pub async fn update_alert_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpsertSystemAlertRuleRequest>,
+) -> Result<Json<SystemAlertRule>, crate::error::AppError> {
+    let claims = check_super_admin(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+
+    let rule = state.alert_service.update_rule(&id, payload).await?;
+
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            None,
+            "update",
+            "system_alert_rule",
+            Some(&rule.id),
+            Some(rule.name.as_str()),
+            Some(&ip),
+        )
+        .await;
+
+    Ok(Json(rule))
+}
+
+pub async fn delete_alert_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, crate::error::AppError> {
+    let claims = check_super_admin(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+
+    state.alert_service.delete_rule(&id).await?;
+
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            None,
+            "delete",
+            "system_alert_rule",
+            Some(&id),
+            None,
+            Some(&ip),
+        )
+        .await;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// Sends a test alert on the rule's channels and reports how each one went.
+pub async fn test_alert_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<AlertDeliveryResult>>, crate::error::AppError> {
+    check_super_admin(&state, &headers).await?;
+
+    Ok(Json(state.alert_service.test_rule(&id).await?))
+}
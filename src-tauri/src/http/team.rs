@@ -113,9 +113,15 @@ pub async fn add_team_member(
         .map_err(crate::error::AppError::Internal)?;
 
     // Broadcast member added event
-    state.ws_hub.broadcast(WsEvent::MemberUpdated {
-        user_id: member.user_id.clone(),
-    });
+    state
+        .event_outbox
+        .publish(
+            Some(tenant_id.as_str()),
+            vec![WsEvent::MemberUpdated {
+                user_id: member.user_id.clone(),
+            }],
+        )
+        .await;
 
     Ok(Json(member))
 }
@@ -161,7 +167,10 @@ pub async fn update_team_member(
         .map_err(map_team_service_error)?;
 
     // Broadcast member updated event - permissions may have changed
-    state.ws_hub.broadcast(WsEvent::PermissionsChanged);
+    state
+        .event_outbox
+        .publish(Some(tenant_id.as_str()), vec![WsEvent::PermissionsChanged])
+        .await;
 
     Ok(Json(serde_json::json!({"success": true})))
 }
@@ -193,7 +202,10 @@ pub async fn remove_team_member(
         .map_err(map_team_service_error)?;
 
     // Broadcast member removed event
-    state.ws_hub.broadcast(WsEvent::PermissionsChanged);
+    state
+        .event_outbox
+        .publish(Some(tenant_id.as_str()), vec![WsEvent::PermissionsChanged])
+        .await;
 
     Ok(Json(serde_json::json!({"success": true})))
 }
@@ -0,0 +1,106 @@
+use crate::error::{AppError, AppResult};
+use crate::http::AppState;
+use crate::models::{TelegramLinkStatus, UpdateTelegramLinkRequest};
+use crate::services::TelegramService;
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+use serde_json::Value;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/link", get(get_link).put(update_link).delete(unlink))
+        .route("/link/code", post(create_link_code))
+        // Bot updates (unauthenticated, verified by the secret token header)
+        .route("/webhook", post(webhook))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+fn telegram(state: &AppState) -> AppResult<&TelegramService> {
+    state
+        .notification_service
+        .telegram()
+        .ok_or_else(|| AppError::Internal("Telegram bot is not initialized".to_string()))
+}
+
+/// Returns `(user_id, tenant_id)`; any tenant member may link their own chat.
+async fn current_member(state: &AppState, headers: &HeaderMap) -> AppResult<(String, String)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.ok_or(AppError::Unauthorized)?;
+    Ok((claims.sub, tenant_id))
+}
+
+// GET /api/telegram/link
+async fn get_link(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<TelegramLinkStatus>> {
+    let (user_id, tenant_id) = current_member(&state, &headers).await?;
+    Ok(Json(
+        telegram(&state)?.get_link(&tenant_id, &user_id).await?,
+    ))
+}
+
+// POST /api/telegram/link/code
+async fn create_link_code(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<TelegramLinkStatus>> {
+    let (user_id, tenant_id) = current_member(&state, &headers).await?;
+    Ok(Json(
+        telegram(&state)?
+            .create_link_code(&tenant_id, &user_id)
+            .await?,
+    ))
+}
+
+// PUT /api/telegram/link
+async fn update_link(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateTelegramLinkRequest>,
+) -> AppResult<Json<TelegramLinkStatus>> {
+    let (user_id, tenant_id) = current_member(&state, &headers).await?;
+    Ok(Json(
+        telegram(&state)?
+            .update_link(&tenant_id, &user_id, req)
+            .await?,
+    ))
+}
+
+// DELETE /api/telegram/link
+async fn unlink(State(state): State<AppState>, headers: HeaderMap) -> AppResult<Json<()>> {
+    let (user_id, tenant_id) = current_member(&state, &headers).await?;
+    telegram(&state)?.unlink(&tenant_id, &user_id).await?;
+    Ok(Json(()))
+}
+
+// POST /api/telegram/webhook
+async fn webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(update): Json<Value>,
+) -> AppResult<Json<()>> {
+    let bot = state
+        .telegram_bot
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("Telegram bot is not initialized".to_string()))?;
+    let secret = headers
+        .get("X-Telegram-Bot-Api-Secret-Token")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or_default();
+    bot.handle_webhook(secret, &update).await?;
+    Ok(Json(()))
+}
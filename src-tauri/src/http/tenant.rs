@@ -1,8 +1,8 @@
 use super::AppState;
 use crate::error::AppError;
 use crate::http::auth::extract_ip;
-use crate::models::Tenant;
-use axum::{extract::ConnectInfo, extract::State, http::HeaderMap, Json};
+use crate::models::{Tenant, TenantAnalytics, TenantUsage};
+use axum::{extract::ConnectInfo, extract::Query, extract::State, http::HeaderMap, Json};
 use chrono::Utc;
 use serde::Deserialize;
 use std::net::SocketAddr;
@@ -30,6 +30,78 @@ pub async fn get_current_tenant(
     Ok(Json(tenant))
 }
 
+pub async fn get_tenant_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<TenantUsage>, AppError> {
+    let auth_header = headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or_else(|| AppError::Unauthorized)?;
+
+    let claims = state.auth_service.validate_token(auth_header).await?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| AppError::Validation("Not a tenant user".to_string()))?;
+
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "billing", "read")
+        .await?;
+
+    let usage = state.usage_service.tenant_usage(&tenant_id).await?;
+    Ok(Json(usage))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TenantAnalyticsQuery {
+    /// Skip the cache and recompute.
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+pub async fn get_tenant_analytics(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TenantAnalyticsQuery>,
+) -> Result<Json<TenantAnalytics>, AppError> {
+    let auth_header = headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or_else(|| AppError::Unauthorized)?;
+
+    let claims = state.auth_service.validate_token(auth_header).await?;
+    let tenant_id = claims
+        .tenant_id
+        .ok_or_else(|| AppError::Validation("Not a tenant user".to_string()))?;
+
+    // Not `dashboard:read`: the customer portal role has that one.
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "customers", "read")
+        .await?;
+
+    let mut analytics = state
+        .tenant_analytics
+        .get(&tenant_id, query.refresh)
+        .await?;
+
+    // Revenue is billing data; the rest of the dashboard isn't.
+    let can_see_billing = state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "billing", "read")
+        .await
+        .is_ok();
+    if !can_see_billing {
+        analytics.revenue.clear();
+    }
+
+    Ok(Json(analytics))
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
@@ -0,0 +1,119 @@
+use crate::error::{AppError, AppResult};
+use crate::http::auth::extract_ip;
+use crate::http::AppState;
+use crate::models::{UplinkCapacityReport, UplinkInterface, UpsertUplinkInterfaceRequest};
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::HeaderMap,
+    routing::{get, put},
+    Json, Router,
+};
+use serde::Deserialize;
+use std::net::SocketAddr;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/uplinks", get(list_uplinks).post(create_uplink))
+        .route("/uplinks/{id}", put(update_uplink).delete(delete_uplink))
+        .route("/report", get(get_report))
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportParams {
+    days: Option<i64>,
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+async fn tenant_and_claims(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> AppResult<(String, crate::services::auth_service::Claims)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    Ok((tenant_id, claims))
+}
+
+// GET /api/admin/uplink-capacity/uplinks
+async fn list_uplinks(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<UplinkInterface>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .uplink_capacity_service
+        .list_uplinks(&claims.sub, &tenant_id)
+        .await?;
+    Ok(Json(out))
+}
+
+// POST /api/admin/uplink-capacity/uplinks
+async fn create_uplink(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<UpsertUplinkInterfaceRequest>,
+) -> AppResult<Json<UplinkInterface>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .uplink_capacity_service
+        .create_uplink(&claims.sub, &tenant_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// PUT /api/admin/uplink-capacity/uplinks/{id}
+async fn update_uplink(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<UpsertUplinkInterfaceRequest>,
+) -> AppResult<Json<UplinkInterface>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let out = state
+        .uplink_capacity_service
+        .update_uplink(&claims.sub, &tenant_id, &id, dto, Some(&ip))
+        .await?;
+    Ok(Json(out))
+}
+
+// DELETE /api/admin/uplink-capacity/uplinks/{id}
+async fn delete_uplink(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    state
+        .uplink_capacity_service
+        .delete_uplink(&claims.sub, &tenant_id, &id, Some(&ip))
+        .await?;
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+// GET /api/admin/uplink-capacity/report?days=7
+async fn get_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<ReportParams>,
+) -> AppResult<Json<UplinkCapacityReport>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let out = state
+        .uplink_capacity_service
+        .report(&claims.sub, &tenant_id, q.days)
+        .await?;
+    Ok(Json(out))
+}
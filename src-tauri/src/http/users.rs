@@ -1,8 +1,8 @@
 use super::AppState;
 use crate::http::auth::extract_ip;
 use crate::models::{
-    CreateUserAddressDto, CreateUserDto, PaginatedResponse, UpdateUserAddressDto, UpdateUserDto,
-    UserAddress, UserResponse,
+    CreateUserAddressDto, CreateUserDto, PaginatedResponse, TrashItem, UpdateUserAddressDto,
+    UpdateUserDto, UserAddress, UserLocale, UserResponse,
 };
 use crate::security::access_rules;
 use axum::{
@@ -186,8 +186,68 @@ pub async fn delete_user(
     Ok(Json(json!({"message": "User deleted"})))
 }
 
+pub async fn list_deleted_users(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<TrashItem>>, crate::error::AppError> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    if !access_rules::can_access_global_user_management(claims.is_super_admin) {
+        return Err(crate::error::AppError::Unauthorized);
+    }
+
+    let users = state.user_service.list_deleted().await?;
+    Ok(Json(users))
+}
+
+pub async fn restore_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> Result<Json<UserResponse>, crate::error::AppError> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    if !access_rules::can_access_global_user_management(claims.is_super_admin) {
+        return Err(crate::error::AppError::Unauthorized);
+    }
+    let ip = extract_ip(&headers, addr);
+
+    let user = state
+        .user_service
+        .restore(&id, Some(&claims.sub), Some(&ip))
+        .await?;
+    Ok(Json(user))
+}
+
 // --- User Addresses (Self) ---
 
+pub async fn get_my_locale(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<UserLocale>, crate::error::AppError> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+
+    let locale = state.user_service.get_locale(&claims.sub).await?;
+    Ok(Json(locale))
+}
+
+pub async fn set_my_locale(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<UserLocale>,
+) -> Result<Json<UserLocale>, crate::error::AppError> {
+    let token = extract_token(&headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+
+    let locale = state
+        .user_service
+        .set_locale(&claims.sub, payload.locale)
+        .await?;
+    Ok(Json(locale))
+}
+
 pub async fn list_my_addresses(
     State(state): State<AppState>,
     headers: HeaderMap,
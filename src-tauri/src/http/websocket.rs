@@ -65,6 +65,31 @@ pub enum WsEvent {
         ticket_id: String,
         message_id: String,
     },
+
+    /// Progress of a manual backup run started from the backup settings
+    BackupRunProgress {
+        run_id: String,
+        target: String,
+        message: String,
+        completed: usize,
+        total: usize,
+        done: bool,
+        error: Option<String>,
+    },
+}
+
+impl From<crate::services::backup::BackupRunProgress> for WsEvent {
+    fn from(p: crate::services::backup::BackupRunProgress) -> Self {
+        WsEvent::BackupRunProgress {
+            run_id: p.run_id,
+            target: p.target.as_str().to_string(),
+            message: p.message,
+            completed: p.completed,
+            total: p.total,
+            done: p.done,
+            error: p.error,
+        }
+    }
 }
 
 /// WebSocket connection manager
@@ -0,0 +1,248 @@
+use crate::error::{AppError, AppResult};
+use crate::http::AppState;
+use crate::models::{
+    UpsertWhatsappTemplateRequest, WhatsappMessage, WhatsappRoute, WhatsappTemplate,
+};
+use crate::services::WhatsappService;
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    routing::{get, post, put},
+    Json, Router,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ListMessagesQuery {
+    pub status: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetRouteRequest {
+    pub template_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestSendRequest {
+    pub phone: String,
+}
+
+#[derive(Deserialize)]
+pub struct MetaVerifyQuery {
+    #[serde(rename = "hub.mode")]
+    pub mode: Option<String>,
+    #[serde(rename = "hub.verify_token")]
+    pub verify_token: Option<String>,
+    #[serde(rename = "hub.challenge")]
+    pub challenge: Option<String>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/templates", get(list_templates).post(create_template))
+        .route(
+            "/templates/{id}",
+            put(update_template).delete(delete_template),
+        )
+        .route("/templates/{id}/test", post(send_test))
+        .route("/routes", get(list_routes))
+        .route("/routes/{event}", put(set_route))
+        .route("/messages", get(list_messages))
+        // Provider callbacks (unauthenticated, verified by signature)
+        .route("/webhooks/meta", get(meta_verify).post(meta_webhook))
+        .route("/webhooks/gateway", post(gateway_webhook))
+}
+
+fn bearer_token(headers: &HeaderMap) -> AppResult<String> {
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .ok_or(AppError::Unauthorized)
+}
+
+fn whatsapp(state: &AppState) -> AppResult<&WhatsappService> {
+    state
+        .notification_service
+        .whatsapp()
+        .ok_or_else(|| AppError::Internal("WhatsApp channel is not initialized".to_string()))
+}
+
+/// Returns `(user_id, tenant_id)` after checking the settings permission.
+async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    action: &str,
+) -> AppResult<(String, String)> {
+    let token = bearer_token(headers)?;
+    let claims = state.auth_service.validate_token(&token).await?;
+    let tenant_id = claims.tenant_id.clone().ok_or(AppError::Unauthorized)?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "settings", action)
+        .await?;
+    Ok((claims.sub, tenant_id))
+}
+
+// GET /api/whatsapp/templates
+async fn list_templates(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<WhatsappTemplate>>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(whatsapp(&state)?.list_templates(&tenant_id).await?))
+}
+
+// POST /api/whatsapp/templates
+async fn create_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<UpsertWhatsappTemplateRequest>,
+) -> AppResult<Json<WhatsappTemplate>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        whatsapp(&state)?.create_template(&tenant_id, req).await?,
+    ))
+}
+
+// PUT /api/whatsapp/templates/{id}
+async fn update_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<UpsertWhatsappTemplateRequest>,
+) -> AppResult<Json<WhatsappTemplate>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        whatsapp(&state)?
+            .update_template(&tenant_id, &id, req)
+            .await?,
+    ))
+}
+
+// DELETE /api/whatsapp/templates/{id}
+async fn delete_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<()>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    whatsapp(&state)?.delete_template(&tenant_id, &id).await?;
+    Ok(Json(()))
+}
+
+// POST /api/whatsapp/templates/{id}/test
+async fn send_test(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<TestSendRequest>,
+) -> AppResult<Json<WhatsappMessage>> {
+    let (_, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        whatsapp(&state)?
+            .send_test(&tenant_id, &id, &req.phone)
+            .await?,
+    ))
+}
+
+// GET /api/whatsapp/routes
+async fn list_routes(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<WhatsappRoute>>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    Ok(Json(whatsapp(&state)?.list_routes(&tenant_id).await?))
+}
+
+// PUT /api/whatsapp/routes/{event}
+async fn set_route(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(event): Path<String>,
+    Json(req): Json<SetRouteRequest>,
+) -> AppResult<Json<WhatsappRoute>> {
+    let (user_id, tenant_id) = authorize(&state, &headers, "update").await?;
+    Ok(Json(
+        whatsapp(&state)?
+            .set_route(
+                &tenant_id,
+                &event,
+                req.template_name.as_deref(),
+                Some(&user_id),
+            )
+            .await?,
+    ))
+}
+
+// GET /api/whatsapp/messages
+async fn list_messages(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<ListMessagesQuery>,
+) -> AppResult<Json<Vec<WhatsappMessage>>> {
+    let (_, tenant_id) = authorize(&state, &headers, "read").await?;
+    let status = q
+        .status
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty() && *s != "all");
+    Ok(Json(
+        whatsapp(&state)?
+            .list_messages(&tenant_id, status, q.limit.unwrap_or(100))
+            .await?,
+    ))
+}
+
+// GET /api/whatsapp/webhooks/meta (subscription handshake)
+async fn meta_verify(
+    State(state): State<AppState>,
+    Query(q): Query<MetaVerifyQuery>,
+) -> AppResult<String> {
+    whatsapp(&state)?
+        .verify_meta_subscription(
+            q.mode.as_deref().unwrap_or_default(),
+            q.verify_token.as_deref().unwrap_or_default(),
+            q.challenge.as_deref().unwrap_or_default(),
+        )
+        .await
+}
+
+// POST /api/whatsapp/webhooks/meta
+async fn meta_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> AppResult<Json<usize>> {
+    let signature = headers
+        .get("X-Hub-Signature-256")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or_default();
+    Ok(Json(
+        whatsapp(&state)?
+            .handle_meta_callback(&body, signature)
+            .await?,
+    ))
+}
+
+// POST /api/whatsapp/webhooks/gateway
+async fn gateway_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> AppResult<Json<usize>> {
+    let signature = headers
+        .get("X-Gateway-Signature")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or_default();
+    Ok(Json(
+        whatsapp(&state)?
+            .handle_gateway_callback(&body, signature)
+            .await?,
+    ))
+}
@@ -2,14 +2,19 @@ use crate::error::{AppError, AppResult};
 use crate::http::auth::extract_ip;
 use crate::http::AppState;
 use crate::models::{
-    AssignInstallationWorkOrderRequest, InstallationWorkOrder, InstallationWorkOrderView,
+    ApplyWorkOrderTemplateRequest, AssignInstallationWorkOrderRequest, CompleteWorkOrderRequest,
+    ConsumeWorkOrderMaterialsRequest, CreateWorkOrderRequest, InstallInventorySerialRequest,
+    InstallationWorkOrder, InstallationWorkOrderView, InventoryMovement, InventorySerial,
     TeamMemberWithUser, UpdateInstallationWorkOrderStatusRequest,
-    WorkOrderRescheduleDecisionRequest, WorkOrderRescheduleRequestView,
+    UpdateWorkOrderChecklistItemRequest, UpsertWorkOrderTemplateRequest, WorkOrderAgendaItem,
+    WorkOrderCheckRequest, WorkOrderChecklist, WorkOrderCompletionReport,
+    WorkOrderRescheduleDecisionRequest, WorkOrderRescheduleRequestView, WorkOrderRoutePlan,
+    WorkOrderSignatureInput, WorkOrderTemplate, WorkOrderVisit,
 };
 use axum::{
     extract::{ConnectInfo, Path, Query, State},
     http::HeaderMap,
-    routing::{get, post},
+    routing::{get, post, put},
     Json, Router,
 };
 use serde::Deserialize;
@@ -17,15 +22,51 @@ use std::net::SocketAddr;
 
 pub fn router() -> Router<AppState> {
     Router::new()
-        .route("/", get(list_work_orders))
+        .route("/", get(list_work_orders).post(create_work_order))
         .route("/assignees", get(list_work_order_assignees))
+        .route("/agenda", get(get_work_order_agenda))
+        .route("/route-plan", get(plan_technician_route))
+        .route(
+            "/templates",
+            get(list_work_order_templates).post(create_work_order_template),
+        )
+        .route(
+            "/templates/{id}",
+            put(update_work_order_template).delete(delete_work_order_template),
+        )
         .route("/{id}/assign", post(assign_work_order))
         .route("/{id}/claim", post(claim_work_order))
         .route("/{id}/release", post(release_work_order))
         .route("/{id}/start", post(start_work_order))
+        .route("/{id}/hold", post(hold_work_order))
         .route("/{id}/complete", post(complete_work_order))
         .route("/{id}/cancel", post(cancel_work_order))
         .route("/{id}/reopen", post(reopen_work_order))
+        .route(
+            "/{id}/completion-reports",
+            get(list_completion_reports).post(sign_completed_work_order),
+        )
+        .route(
+            "/completion-reports/{report_id}/email",
+            post(email_completion_report),
+        )
+        .route("/{id}/visits", get(list_work_order_visits))
+        .route("/{id}/check-in", post(check_in_work_order))
+        .route("/{id}/check-out", post(check_out_work_order))
+        .route("/{id}/checklist", get(get_work_order_checklist))
+        .route(
+            "/{id}/checklist/template",
+            post(apply_work_order_checklist_template),
+        )
+        .route(
+            "/{id}/checklist/items/{item_id}",
+            put(update_work_order_checklist_item),
+        )
+        .route(
+            "/{id}/materials",
+            get(list_work_order_materials).post(consume_work_order_materials),
+        )
+        .route("/{id}/materials/serial", post(install_work_order_serial))
         .route(
             "/{id}/reschedule-request",
             get(get_pending_reschedule_request),
@@ -63,6 +104,7 @@ async fn tenant_and_claims(
 struct ListWorkOrderQuery {
     status: Option<String>,
     assigned_to: Option<String>,
+    work_type: Option<String>,
     include_closed: Option<bool>,
     limit: Option<u32>,
 }
@@ -80,6 +122,7 @@ async fn list_work_orders(
             &tenant_id,
             q.status,
             q.assigned_to,
+            q.work_type,
             q.include_closed.unwrap_or(false),
             q.limit.unwrap_or(200),
         )
@@ -87,6 +130,21 @@ async fn list_work_orders(
     Ok(Json(rows))
 }
 
+async fn create_work_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<CreateWorkOrderRequest>,
+) -> AppResult<Json<InstallationWorkOrder>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let row = state
+        .customer_service
+        .create_work_order(&claims.sub, &tenant_id, dto, Some(&ip))
+        .await?;
+    Ok(Json(row))
+}
+
 async fn list_work_order_assignees(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -99,6 +157,55 @@ async fn list_work_order_assignees(
     Ok(Json(rows))
 }
 
+#[derive(Debug, Deserialize)]
+struct AgendaQuery {
+    from: Option<String>,
+    to: Option<String>,
+    technician_id: Option<String>,
+}
+
+async fn get_work_order_agenda(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<AgendaQuery>,
+) -> AppResult<Json<Vec<WorkOrderAgendaItem>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let rows = state
+        .customer_service
+        .list_work_order_agenda(&claims.sub, &tenant_id, q.from, q.to, q.technician_id)
+        .await?;
+    Ok(Json(rows))
+}
+
+#[derive(Debug, Deserialize)]
+struct RoutePlanQuery {
+    technician_id: Option<String>,
+    /// YYYY-MM-DD in the app timezone; defaults to today.
+    date: Option<String>,
+    start_latitude: Option<f64>,
+    start_longitude: Option<f64>,
+}
+
+async fn plan_technician_route(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<RoutePlanQuery>,
+) -> AppResult<Json<WorkOrderRoutePlan>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let plan = state
+        .customer_service
+        .plan_technician_route(
+            &claims.sub,
+            &tenant_id,
+            q.technician_id,
+            q.date,
+            q.start_latitude,
+            q.start_longitude,
+        )
+        .await?;
+    Ok(Json(plan))
+}
+
 async fn assign_work_order(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -110,15 +217,7 @@ async fn assign_work_order(
     let ip = extract_ip(&headers, addr);
     let row = state
         .customer_service
-        .assign_installation_work_order(
-            &claims.sub,
-            &tenant_id,
-            &id,
-            &dto.assigned_to,
-            dto.scheduled_at,
-            dto.notes,
-            Some(&ip),
-        )
+        .assign_installation_work_order(&claims.sub, &tenant_id, &id, dto, Some(&ip))
         .await?;
     Ok(Json(row))
 }
@@ -171,7 +270,7 @@ async fn start_work_order(
     Ok(Json(row))
 }
 
-async fn complete_work_order(
+async fn hold_work_order(
     State(state): State<AppState>,
     headers: HeaderMap,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
@@ -182,11 +281,71 @@ async fn complete_work_order(
     let ip = extract_ip(&headers, addr);
     let row = state
         .customer_service
-        .complete_installation_work_order(&claims.sub, &tenant_id, &id, dto.notes, Some(&ip))
+        .hold_work_order(&claims.sub, &tenant_id, &id, dto.notes, Some(&ip))
+        .await?;
+    Ok(Json(row))
+}
+
+async fn complete_work_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<CompleteWorkOrderRequest>,
+) -> AppResult<Json<InstallationWorkOrder>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let row = state
+        .completion_reports
+        .complete_work_order(&claims.sub, &tenant_id, &id, dto, Some(&ip))
         .await?;
     Ok(Json(row))
 }
 
+async fn list_completion_reports(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<WorkOrderCompletionReport>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let rows = state
+        .completion_reports
+        .list_for_work_order(&claims.sub, &tenant_id, &id)
+        .await?;
+    Ok(Json(rows))
+}
+
+async fn sign_completed_work_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<WorkOrderSignatureInput>,
+) -> AppResult<Json<WorkOrderCompletionReport>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let report = state
+        .completion_reports
+        .sign_completed(&claims.sub, &tenant_id, &id, dto, Some(&ip))
+        .await?;
+    Ok(Json(report))
+}
+
+async fn email_completion_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(report_id): Path<String>,
+) -> AppResult<Json<WorkOrderCompletionReport>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let report = state
+        .completion_reports
+        .resend(&claims.sub, &tenant_id, &report_id, Some(&ip))
+        .await?;
+    Ok(Json(report))
+}
+
 async fn cancel_work_order(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -219,6 +378,51 @@ async fn reopen_work_order(
     Ok(Json(row))
 }
 
+async fn list_work_order_visits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<WorkOrderVisit>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let rows = state
+        .customer_service
+        .list_work_order_visits(&claims.sub, &tenant_id, &id)
+        .await?;
+    Ok(Json(rows))
+}
+
+async fn check_in_work_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<WorkOrderCheckRequest>,
+) -> AppResult<Json<WorkOrderVisit>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let visit = state
+        .customer_service
+        .check_in_work_order(&claims.sub, &tenant_id, &id, dto, Some(&ip))
+        .await?;
+    Ok(Json(visit))
+}
+
+async fn check_out_work_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<WorkOrderCheckRequest>,
+) -> AppResult<Json<WorkOrderVisit>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    let visit = state
+        .customer_service
+        .check_out_work_order(&claims.sub, &tenant_id, &id, dto, Some(&ip))
+        .await?;
+    Ok(Json(visit))
+}
+
 async fn get_pending_reschedule_request(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -263,3 +467,242 @@ async fn reject_reschedule_request(
         .await?;
     Ok(Json(row))
 }
+
+async fn list_work_order_templates(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AppResult<Json<Vec<WorkOrderTemplate>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "work_orders", "read")
+        .await?;
+    let rows = state
+        .work_order_checklists
+        .list_templates(&tenant_id)
+        .await?;
+    Ok(Json(rows))
+}
+
+async fn create_work_order_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(dto): Json<UpsertWorkOrderTemplateRequest>,
+) -> AppResult<Json<WorkOrderTemplate>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "work_orders", "templates")
+        .await?;
+    let ip = extract_ip(&headers, addr);
+    let template = state
+        .work_order_checklists
+        .create_template(&tenant_id, &claims.sub, dto)
+        .await?;
+
+    let details = serde_json::json!({
+        "name": template.name,
+        "items": template.items.len(),
+        "is_default": template.is_default,
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "WORK_ORDER_TEMPLATE_CREATE",
+            "work_order_template",
+            Some(&template.id),
+            Some(details.as_str()),
+            Some(&ip),
+        )
+        .await;
+
+    Ok(Json(template))
+}
+
+async fn update_work_order_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<UpsertWorkOrderTemplateRequest>,
+) -> AppResult<Json<WorkOrderTemplate>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "work_orders", "templates")
+        .await?;
+    let ip = extract_ip(&headers, addr);
+    let template = state
+        .work_order_checklists
+        .update_template(&tenant_id, &id, dto)
+        .await?;
+
+    let details = serde_json::json!({
+        "name": template.name,
+        "items": template.items.len(),
+        "is_default": template.is_default,
+        "is_active": template.is_active,
+    })
+    .to_string();
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "WORK_ORDER_TEMPLATE_UPDATE",
+            "work_order_template",
+            Some(&template.id),
+            Some(details.as_str()),
+            Some(&ip),
+        )
+        .await;
+
+    Ok(Json(template))
+}
+
+async fn delete_work_order_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .auth_service
+        .check_permission(&claims.sub, &tenant_id, "work_orders", "templates")
+        .await?;
+    let ip = extract_ip(&headers, addr);
+    state
+        .work_order_checklists
+        .delete_template(&tenant_id, &id)
+        .await?;
+
+    state
+        .audit_service
+        .log(
+            Some(&claims.sub),
+            Some(&tenant_id),
+            "WORK_ORDER_TEMPLATE_DELETE",
+            "work_order_template",
+            Some(&id),
+            None,
+            Some(&ip),
+        )
+        .await;
+
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+async fn get_work_order_checklist(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<WorkOrderChecklist>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .customer_service
+        .authorize_work_order_access(&claims.sub, &tenant_id, &id, false)
+        .await?;
+    let checklist = state
+        .work_order_checklists
+        .get_checklist(&tenant_id, &id)
+        .await?;
+    Ok(Json(checklist))
+}
+
+async fn apply_work_order_checklist_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(dto): Json<ApplyWorkOrderTemplateRequest>,
+) -> AppResult<Json<WorkOrderChecklist>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .customer_service
+        .authorize_work_order_access(&claims.sub, &tenant_id, &id, true)
+        .await?;
+    let checklist = state
+        .work_order_checklists
+        .apply_template(&tenant_id, &id, &dto.template_id)
+        .await?;
+    Ok(Json(checklist))
+}
+
+async fn update_work_order_checklist_item(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((id, item_id)): Path<(String, String)>,
+    Json(dto): Json<UpdateWorkOrderChecklistItemRequest>,
+) -> AppResult<Json<WorkOrderChecklist>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .customer_service
+        .authorize_work_order_access(&claims.sub, &tenant_id, &id, true)
+        .await?;
+    let checklist = state
+        .work_order_checklists
+        .update_item(&tenant_id, &id, &item_id, &claims.sub, dto)
+        .await?;
+    Ok(Json(checklist))
+}
+
+async fn list_work_order_materials(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<InventoryMovement>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    state
+        .customer_service
+        .authorize_work_order_access(&claims.sub, &tenant_id, &id, false)
+        .await?;
+    let rows = state
+        .inventory_service
+        .list_work_order_materials(&tenant_id, &id)
+        .await?;
+    Ok(Json(rows))
+}
+
+async fn consume_work_order_materials(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<ConsumeWorkOrderMaterialsRequest>,
+) -> AppResult<Json<Vec<InventoryMovement>>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    state
+        .customer_service
+        .authorize_work_order_access(&claims.sub, &tenant_id, &id, true)
+        .await?;
+    let rows = state
+        .inventory_service
+        .consume_for_work_order(&claims.sub, &tenant_id, &id, dto, Some(&ip))
+        .await?;
+    Ok(Json(rows))
+}
+
+async fn install_work_order_serial(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(id): Path<String>,
+    Json(dto): Json<InstallInventorySerialRequest>,
+) -> AppResult<Json<InventorySerial>> {
+    let (tenant_id, claims) = tenant_and_claims(&state, &headers).await?;
+    let ip = extract_ip(&headers, addr);
+    state
+        .customer_service
+        .authorize_work_order_access(&claims.sub, &tenant_id, &id, true)
+        .await?;
+    let serial = state
+        .inventory_service
+        .install_serial(&claims.sub, &tenant_id, &id, dto, Some(&ip))
+        .await?;
+    Ok(Json(serial))
+}
@@ -3,6 +3,7 @@
 //! This is the core library for the Tauri application.
 //! It wires together all modules: database, services, and commands.
 
+pub mod bootstrap;
 pub mod db;
 pub mod error;
 pub mod http;
@@ -12,20 +13,13 @@ pub mod services;
 
 #[cfg(feature = "desktop")]
 pub mod commands;
-
-#[cfg(feature = "desktop")]
-use db::connection::{init_db, seed_defaults};
 #[cfg(feature = "desktop")]
-use services::backup::BackupScheduler;
+mod desktop_notify;
 #[cfg(feature = "desktop")]
-use services::metrics_service::MetricsService;
+mod diagnostics;
 #[cfg(feature = "desktop")]
-use services::{
-    AnnouncementScheduler, AuditService, AuthService, BackupService, CustomerService,
-    EmailOutboxService, EmailService, IspPackageService, MikrotikService, NetworkMappingService,
-    NotificationService, PaymentService, PlanService, PppoeService, RoleService, SettingsService,
-    SystemService, TeamService, UserService,
-};
+mod tray;
+
 #[cfg(feature = "desktop")]
 use tracing::info;
 #[cfg(feature = "desktop")]
@@ -51,16 +45,23 @@ fn init_linux_webview_fallbacks() {
     }
 }
 
-/// Initialize logging
+/// Initialize logging. Recent lines are also kept for diagnostics bundles.
 #[cfg(feature = "desktop")]
-fn init_logging() {
+fn init_logging() -> diagnostics::RecentLogs {
+    let recent_logs = diagnostics::RecentLogs::default();
     tracing_subscriber::registry()
         .with(tracing_subscriber::fmt::layer())
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(recent_logs.clone()),
+        )
         .with(
             tracing_subscriber::EnvFilter::from_default_env()
                 .add_directive("saas_tauri=debug".parse().unwrap()),
         )
         .init();
+    recent_logs
 }
 
 #[cfg_attr(all(feature = "desktop", mobile), tauri::mobile_entry_point)]
@@ -79,38 +80,34 @@ pub fn run() {
     #[cfg(target_os = "linux")]
     init_linux_webview_fallbacks();
 
-    init_logging();
+    let recent_logs = init_logging();
     info!("Starting Application");
 
     #[allow(unused_mut)]
-    let mut builder = tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_fs::init())
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_notification::init());
+    let mut builder = tauri::Builder::default();
 
-    // Only enable single-instance in production to allow dev and prod to run simultaneously
+    // Only enable single-instance in production to allow dev and prod to run simultaneously.
+    // It goes first so deep links opened from a second launch reach this instance.
     #[cfg(not(debug_assertions))]
     {
         builder = builder.plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            let _ = app
-                .get_webview_window("main")
-                .expect("no main window")
-                .set_focus();
+            // The window may be hidden in the tray.
+            tray::show_main_window(app);
         }));
     }
 
     builder
+        .manage(recent_logs)
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
         .setup(|app| {
             let app_handle = app.handle().clone();
 
             // Get app data directory
-
-            // Force Open DevTools (Debugging Production Issue)
-            if let Some(window) = app.get_webview_window("main") {
-                window.open_devtools();
-            }
-
             let app_data_dir = match app_handle.path().app_data_dir() {
                 Ok(path) => path,
                 Err(e) => {
@@ -131,6 +128,23 @@ pub fn run() {
 
             info!("App data directory: {:?}", app_data_dir);
 
+            if let Err(e) = tray::init(app, &app_data_dir) {
+                tracing::warn!("System tray unavailable, closing the window will quit: {}", e);
+            }
+            app.manage(desktop_notify::DesktopNotifier::load(&app_data_dir));
+
+            // Invite, password reset and payment links; the frontend routes them.
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                // Installers register the scheme; this covers portable and dev builds.
+                #[cfg(any(windows, target_os = "linux"))]
+                if let Err(e) = app.deep_link().register_all() {
+                    tracing::warn!("Failed to register deep link scheme: {}", e);
+                }
+                let handle = app_handle.clone();
+                app.deep_link().on_open_url(move |_| tray::show_main_window(&handle));
+            }
+
             // =========================================================
             // CONFIGURATION LOADING STRATEGY
             // =========================================================
@@ -187,172 +201,74 @@ pub fn run() {
             // We use block_on here to ensure services are ready before the window starts
             // and potentially calls commands that require these managed states.
             let init_result: Result<(), String> = tauri::async_runtime::block_on(async {
-                // Initialize database
-                info!("Attempting to initialize database...");
-                let pool = init_db(app_data_dir.clone())
-                    .await
-                    .map_err(|e| format!("Failed to initialize database: {}", e))?;
-                info!("Database initialized.");
-
-                // Seed default settings
-                seed_defaults(&pool)
-                    .await
-                    .map_err(|e| format!("Failed to seed default settings: {}", e))?;
-                info!("Default settings seeded.");
-
-                // Create services - AuditService must be first
-                let plan_service = PlanService::new(pool.clone());
-                let audit_service = AuditService::new(pool.clone(), Some(plan_service.clone()));
-                // RoleService needs AuditService
-                let role_service = RoleService::new(pool.clone(), audit_service.clone());
-
-                // Seed RBAC permissions and roles using RoleService instance
-                role_service.seed_permissions()
-                    .await
-                    .map_err(|e| format!("Failed to seed permissions: {}", e))?;
-                role_service.seed_roles()
-                    .await
-                    .map_err(|e| format!("Failed to seed roles: {}", e))?;
-                info!("RBAC permissions and roles seeded.");
-
-                // Get JWT secret from settings
-                let jwt_secret = sqlx::query_scalar::<_, String>(
-                    "SELECT value FROM settings WHERE key = 'jwt_secret' AND tenant_id IS NULL"
-                )
-                .fetch_one(&pool)
-                .await
-                .unwrap_or_else(|_| uuid::Uuid::new_v4().to_string());
-                info!("JWT Secret loaded.");
-
-                // Initialize App Data Dir for Storage
-                let app_data_dir = app_handle.path().app_data_dir().unwrap_or(std::path::PathBuf::from("app_data"));
-
-                let settings_service = SettingsService::new(pool.clone(), audit_service.clone());
-                let email_service = EmailService::new(settings_service.clone());
-                let auth_service = AuthService::new(pool.clone(), jwt_secret, email_service.clone(), audit_service.clone(), settings_service.clone());
-                let user_service = UserService::new(pool.clone(), audit_service.clone());
-                let pppoe_service =
-                    PppoeService::new(
-                        pool.clone(),
-                        auth_service.clone(),
-                        audit_service.clone(),
-                        settings_service.clone(),
-                    );
-                let isp_package_service =
-                    IspPackageService::new(pool.clone(), auth_service.clone(), audit_service.clone());
-                let network_mapping_service =
-                    NetworkMappingService::new(pool.clone(), auth_service.clone());
-                let team_service = TeamService::new(pool.clone(), auth_service.clone(), audit_service.clone(), plan_service.clone());
-                let metrics_service = std::sync::Arc::new(MetricsService::new());
-                let system_service = SystemService::new(pool.clone(), metrics_service.clone());
-                let storage_service = crate::services::StorageService::new(pool.clone(), plan_service.clone(), app_data_dir.clone());
-                let backup_service = BackupService::new(pool.clone(), app_data_dir.clone());
-
-                // Start Backup Scheduler
-                let scheduler = BackupScheduler::new(pool.clone(), backup_service.clone(), settings_service.clone());
-                scheduler.start().await;
-
-                // Create WebSocket hub for real-time sync (shared between HTTP and Tauri)
-                let ws_hub = std::sync::Arc::new(http::WsHub::new());
-
-                let email_outbox_service =
-                    EmailOutboxService::new(pool.clone(), settings_service.clone(), email_service.clone());
-                email_outbox_service.start_sender().await;
-
-                let notification_service = NotificationService::new(
+                let backend = bootstrap::init_backend(&app_data_dir, app_data_dir.clone()).await?;
+                desktop_notify::spawn(app_handle.clone(), backend.ws_hub.subscribe());
+                let pool = backend.pool.clone();
+
+                // Manage state - Crucial: This must happen before setup returns
+                app_handle.manage(backend.auth_service.clone());
+                app_handle.manage(backend.user_service.clone());
+                app_handle.manage(backend.customer_service.clone());
+                app_handle.manage(backend.pppoe_service.clone());
+                app_handle.manage(backend.isp_package_service.clone());
+                app_handle.manage(backend.network_mapping_service.clone());
+                app_handle.manage(backend.settings_service.clone());
+                app_handle.manage(backend.email_service.clone());
+                app_handle.manage(backend.team_service.clone());
+                app_handle.manage(backend.audit_service.clone());
+                app_handle.manage(backend.role_service.clone());
+                app_handle.manage(backend.system_service.clone());
+                app_handle.manage(backend.db_maintenance_service.clone());
+                app_handle.manage(backend.job_queue.clone());
+                app_handle.manage(backend.plan_service.clone());
+                app_handle.manage(crate::services::UsageService::new(pool.clone()));
+                app_handle.manage(backend.feature_flag_service.clone());
+                app_handle.manage(backend.storage_service.clone());
+                app_handle.manage(backend.backup_service.clone());
+                app_handle.manage(crate::services::TenantTransferService::new(pool.clone(), backend.backup_service.clone()));
+                app_handle.manage(crate::services::SupportRoutingService::new(pool.clone()));
+                app_handle.manage(crate::services::SupportMacroService::new(pool.clone()));
+                app_handle.manage(crate::services::SupportLinkService::new(pool.clone()));
+                app_handle.manage(backend.support_escalation.clone());
+                app_handle.manage(crate::services::WorkOrderChecklistService::new(pool.clone()));
+                app_handle.manage(crate::services::InventoryService::new(
                     pool.clone(),
-                    ws_hub.clone(),
-                    email_outbox_service.clone(),
-                );
-                let customer_service = CustomerService::new(
+                    backend.auth_service.clone(),
+                    backend.audit_service.clone(),
+                    backend.notification_service.clone(),
+                ));
+                app_handle.manage(crate::services::FieldSyncService::new(
+                    pool.clone(),
+                    backend.auth_service.clone(),
+                    backend.customer_service.clone(),
+                ));
+                app_handle.manage(crate::services::CompletionReportService::new(
                     pool.clone(),
-                    auth_service.clone(),
-                    audit_service.clone(),
-                    notification_service.clone(),
-                    pppoe_service.clone(),
-                    user_service.clone(),
+                    backend.auth_service.clone(),
+                    backend.audit_service.clone(),
+                    backend.customer_service.clone(),
+                    backend.storage_service.clone(),
+                    backend.email_service.clone(),
+                ));
+                app_handle.manage(backend.payment_service.clone());
+                app_handle.manage(crate::services::ReceiptPrinterService::new(
+                    pool.clone(),
+                    backend.settings_service.clone(),
+                ));
+                app_handle.manage(backend.notification_service.clone());
+                app_handle.manage(backend.email_outbox_service.clone());
+                app_handle.manage(backend.mikrotik_service.clone());
+                app_handle.manage(backend.event_outbox.clone());
+                app_handle.manage(backend.ws_hub.clone());
+                app_handle.manage(backend.metrics_service.clone());
+                let server_control = http::ServerControl::new(
+                    http::ServerBinding::load(&backend.settings_service, 3000).await,
                 );
-                customer_service.start_installation_sla_scheduler();
-                let payment_service =
-                    PaymentService::new(pool.clone(), notification_service.clone(), pppoe_service.clone());
-                payment_service.start_customer_invoice_scheduler();
-
-                // MikroTik monitoring (tenant-scoped)
-                let mikrotik_service =
-                    MikrotikService::new(
-                        pool.clone(),
-                        notification_service.clone(),
-                        audit_service.clone(),
-                        settings_service.clone(),
-                    );
-                std::sync::Arc::new(mikrotik_service.clone()).start_poller();
-
-                // Start Announcement Scheduler (scheduled broadcasts -> notifications)
-                let announcement_scheduler =
-                    AnnouncementScheduler::new(pool.clone(), notification_service.clone(), audit_service.clone());
-                announcement_scheduler.start().await;
-
-                // Seed default features
-                plan_service.seed_default_features()
-                    .await
-                    .map_err(|e| format!("Failed to seed default features: {}", e))?;
-                info!("Default features seeded.");
-
-                // Manage state - Crucial: This must happen before setup returns
-                app_handle.manage(auth_service.clone());
-                app_handle.manage(user_service.clone());
-                app_handle.manage(customer_service.clone());
-                app_handle.manage(pppoe_service.clone());
-                app_handle.manage(isp_package_service.clone());
-                app_handle.manage(network_mapping_service.clone());
-                app_handle.manage(settings_service.clone());
-                app_handle.manage(email_service.clone());
-                app_handle.manage(team_service.clone());
-                app_handle.manage(audit_service.clone());
-                app_handle.manage(role_service.clone());
-                app_handle.manage(system_service.clone());
-                app_handle.manage(plan_service.clone());
-                app_handle.manage(storage_service.clone());
-                app_handle.manage(backup_service.clone());
-                app_handle.manage(payment_service.clone());
-                app_handle.manage(notification_service.clone());
-                app_handle.manage(email_outbox_service.clone());
-                app_handle.manage(mikrotik_service.clone());
-                app_handle.manage(ws_hub.clone());
-                app_handle.manage(metrics_service.clone());
+                app_handle.manage(server_control.clone());
                 info!("Services added to Tauri state.");
 
-
                 // Start HTTP Server (This can run in background)
-                let app_dir = app_data_dir.clone();
-                tauri::async_runtime::spawn(async move {
-                    http::start_server(
-                        auth_service,
-                        user_service,
-                        settings_service,
-                        email_service,
-                        team_service,
-                        role_service,
-                        audit_service,
-                        system_service,
-                        plan_service,
-                        storage_service,
-                        payment_service,
-                        notification_service,
-                        mikrotik_service,
-                        customer_service,
-                        pppoe_service,
-                        isp_package_service,
-                        network_mapping_service,
-                        backup_service,
-                        ws_hub,
-                        app_dir,
-                        3000,
-                        pool.clone(),
-                        metrics_service,
-                    ).await;
-                });
-
+                tauri::async_runtime::spawn(backend.serve(server_control));
 
                 info!("Services initialized successfully");
                 Ok(())
@@ -370,12 +286,7 @@ pub fn run() {
 
             Ok(())
         })
-        .on_window_event(|window, event| {
-            if let tauri::WindowEvent::CloseRequested { .. } = event {
-                // Force exit to ensure background tasks (like HTTP server) are killed
-                window.app_handle().exit(0);
-            }
-        })
+        .on_window_event(tray::on_window_event)
         .invoke_handler(tauri::generate_handler![
             // Auth commands
             register,
@@ -407,6 +318,10 @@ pub fn run() {
             create_user,
             update_user,
             delete_user,
+            list_deleted_users,
+            restore_user,
+            get_my_locale,
+            set_my_locale,
             list_my_addresses,
             create_my_address,
             update_my_address,
@@ -433,6 +348,8 @@ pub fn run() {
                                     delete_tenant,
                                     create_tenant,
                                     update_tenant,
+                                    export_tenant_archive,
+                                    import_tenant_archive,
                                     // Roles commands
                                     get_roles,
                                     get_permissions,
@@ -451,6 +368,12 @@ pub fn run() {
                                     // System Health commands
                                     get_system_health,
                                     get_system_diagnostics,
+                                    get_migration_status,
+                                    run_db_maintenance,
+                                    export_diagnostics_bundle,
+                                    // Embedded HTTP server
+                                    get_http_server_config,
+                                    update_http_server_config,
                                     // Plan commands
                                     list_plans,
                                     get_plan,
@@ -462,6 +385,7 @@ pub fn run() {
                                     // delete_feature, // System Managed
                                     set_plan_feature,
                                     assign_plan_to_tenant,
+                                    start_tenant_trial,
                                     get_tenant_subscription,
                                     get_tenant_subscription_details,
                                     check_feature_access,
@@ -471,11 +395,16 @@ pub fn run() {
                                     delete_file_admin,
                                     list_files_tenant,
                                     delete_file_tenant,
+                                    list_file_versions,
+                                    restore_file_version,
+                                    create_file_link,
                                     // Payment commands
                                     list_bank_accounts,
                                     create_bank_account,
                                     delete_bank_account,
                                     create_invoice_for_plan,
+                                    quote_plan_upgrade,
+                                    checkout_plan_upgrade,
                                     create_invoice_for_customer_subscription,
                                     create_invoice_for_installation_work_order,
                                     generate_due_customer_package_invoices,
@@ -492,31 +421,99 @@ pub fn run() {
                                     submit_payment_proof,
                                     verify_payment,
                                     verify_customer_package_payment,
+                                    reprice_customer_package_invoice,
+                                    // Receipt printing
+                                    preview_invoice_print,
+                                    print_invoice,
+                                    print_test_receipt,
                                     // Tenant Self-Management
                                     get_current_tenant,
                                     update_current_tenant,
+                                    get_tenant_usage,
                                     // General
                                     get_app_version,
+                                    check_client_compatibility,
+                                    check_for_update,
+                                    install_update,
                                     // Notifications
                                     list_notifications,
                                     get_unread_count,
                                     mark_as_read,
                                     mark_all_as_read,
+                                    archive_notifications,
                                     delete_notification,
                                     get_preferences,
                                     update_preference,
+                                    get_quiet_hours,
+                                    update_quiet_hours,
+                                    get_notification_delivery_report,
+                                    get_vapid_public_key,
                                     subscribe_push,
                                     unsubscribe_push,
                                     send_test,
+                                    // Desktop notifications
+                                    set_desktop_notification_session,
+                                    get_desktop_notification_prefs,
+                                    update_desktop_notification_prefs,
+                                    // Notification templates
+                                    list_notification_templates,
+                                    update_notification_template,
+                                    reset_notification_template,
+                                    preview_notification_template,
+                                    // Email templates
+                                    list_email_templates,
+                                    get_email_template,
+                                    list_email_template_variables,
+                                    update_email_template,
+                                    reset_email_template,
+                                    preview_email_template,
+                                    test_send_email_template,
+                                    // Email DKIM signing
+                                    get_email_dkim,
+                                    generate_email_dkim_key,
+                                    update_email_dkim,
+                                    delete_email_dkim_key,
+                                    // Notification routing rules
+                                    list_notification_rules,
+                                    create_notification_rule,
+                                    update_notification_rule,
+                                    delete_notification_rule,
+                                    evaluate_notification_routing,
                                     // Email Outbox (Admin)
                                     list_email_outbox,
                                     get_email_outbox_stats,
                                     retry_email_outbox,
+                                    schedule_email_outbox,
                                     delete_email_outbox,
                                     get_email_outbox,
                                     bulk_retry_email_outbox,
                                     bulk_delete_email_outbox,
                                     export_email_outbox_csv,
+                                    // Email suppression list
+                                    list_email_suppressions,
+                                    add_email_suppression,
+                                    remove_email_suppression,
+                                    // Feature flags
+                                    evaluate_feature_flags,
+                                    list_feature_flags,
+                                    upsert_feature_flag,
+                                    delete_feature_flag,
+                                    set_feature_flag_override,
+                                    remove_feature_flag_override,
+                                    // WhatsApp channel
+                                    list_whatsapp_templates,
+                                    create_whatsapp_template,
+                                    update_whatsapp_template,
+                                    delete_whatsapp_template,
+                                    send_whatsapp_test,
+                                    list_whatsapp_routes,
+                                    set_whatsapp_route,
+                                    list_whatsapp_messages,
+                                    // Telegram bot
+                                    get_telegram_link,
+                                    create_telegram_link_code,
+                                    update_telegram_link,
+                                    unlink_telegram,
                                     // Backup commands
                                     list_backups,
                                     create_backup,
@@ -524,6 +521,18 @@ pub fn run() {
                                     save_backup_to_disk,
                                     restore_backup_from_file,
                                     restore_local_backup_command,
+                                    list_remote_backups,
+                                    upload_backup_offsite,
+                                    restore_remote_backup_command,
+                                    pin_backup,
+                                    unpin_backup,
+                                    validate_backup_file,
+                                    validate_local_backup,
+                                    dry_run_restore_backup,
+                                    drop_restore_scratch,
+                                    get_backup_schedule,
+                                    preview_backup_cron,
+                                    run_backup_now,
                                     // Support tickets
                                     list_support_tickets,
                                     get_support_ticket_stats,
@@ -531,6 +540,24 @@ pub fn run() {
                                     get_support_ticket,
                                     reply_support_ticket,
                                     update_support_ticket,
+                                    list_support_queues,
+                                    create_support_queue,
+                                    update_support_queue,
+                                    delete_support_queue,
+                                    list_support_escalation_rules,
+                                    create_support_escalation_rule,
+                                    update_support_escalation_rule,
+                                    delete_support_escalation_rule,
+                                    list_support_macros,
+                                    create_support_macro,
+                                    update_support_macro,
+                                    delete_support_macro,
+                                    preview_support_macro,
+                                    apply_support_macro,
+                                    list_support_ticket_links,
+                                    link_support_ticket,
+                                    unlink_support_ticket,
+                                    list_linked_support_tickets,
                                     // Customers (tenant scoped)
                                     list_customers,
                                     get_customer,
@@ -538,7 +565,12 @@ pub fn run() {
                                     create_customer_with_portal,
                                     create_customer_registration_invite,
                                     update_customer,
+                                    list_customer_tags,
+                                    get_customer_tags,
+                                    set_customer_tags,
                                     delete_customer,
+                                    list_deleted_customers,
+                                    restore_customer,
                                     list_customer_registration_invites,
                                     get_customer_registration_invite_policy,
                                     update_customer_registration_invite_policy,
@@ -566,14 +598,54 @@ pub fn run() {
                                     create_customer_subscription,
                                     update_customer_subscription,
                                     delete_customer_subscription,
+                                    list_customer_subscription_components,
+                                    update_customer_subscription_component,
                                     list_installation_work_orders,
                                     list_installation_assignees,
                                     assign_installation_work_order,
+                                    get_work_order_agenda,
+                                    plan_technician_route,
+                                    list_work_order_visits,
+                                    check_in_work_order,
+                                    check_out_work_order,
+                                    list_work_order_templates,
+                                    create_work_order_template,
+                                    update_work_order_template,
+                                    delete_work_order_template,
+                                    get_work_order_checklist,
+                                    apply_work_order_checklist_template,
+                                    update_work_order_checklist_item,
+                                    list_work_order_materials,
+                                    consume_work_order_materials,
+                                    install_work_order_serial,
+                                    create_work_order,
                                     claim_installation_work_order,
                                     release_installation_work_order,
                                     start_installation_work_order,
+                                    hold_work_order,
                                     complete_installation_work_order,
                                     cancel_installation_work_order,
+                                    list_work_order_completion_reports,
+                                    sign_work_order_completion,
+                                    email_work_order_completion_report,
+                                    list_customer_completion_reports,
+                                    // Inventory (tenant scoped)
+                                    list_inventory_items,
+                                    create_inventory_item,
+                                    update_inventory_item,
+                                    list_inventory_locations,
+                                    create_inventory_location,
+                                    update_inventory_location,
+                                    list_inventory_stock,
+                                    list_inventory_movements,
+                                    create_inventory_movement,
+                                    resolve_inventory_scan,
+                                    list_inventory_serials,
+                                    receive_inventory_serials,
+                                    transfer_inventory_serials,
+                                    // Offline field sync (tenant scoped)
+                                    field_sync_pull,
+                                    field_sync_push,
                                     // PPPoE (tenant scoped)
                                     list_pppoe_accounts,
                                     get_pppoe_account,
@@ -589,6 +661,9 @@ pub fn run() {
                                     create_isp_package,
                                     update_isp_package,
                                     delete_isp_package,
+                                    list_deleted_isp_packages,
+                                    restore_isp_package,
+                                    list_isp_package_price_history,
                                     list_isp_package_router_mappings,
                                     upsert_isp_package_router_mapping,
                                     // MikroTik / Routers
@@ -601,12 +676,16 @@ pub fn run() {
                                     resolve_mikrotik_alert,
                                     ack_mikrotik_incident,
                                     resolve_mikrotik_incident,
+                                    get_mikrotik_incident_impact,
+                                    refresh_mikrotik_incident_impact,
                                     update_mikrotik_incident,
                                     simulate_mikrotik_incident,
                                     run_mikrotik_incident_auto_escalation,
                                     create_mikrotik_router,
                                     update_mikrotik_router,
                                     delete_mikrotik_router,
+                                    list_deleted_mikrotik_routers,
+                                    restore_mikrotik_router,
                                     test_mikrotik_router,
                                     get_mikrotik_router,
                                     get_mikrotik_router_snapshot,
@@ -619,6 +698,9 @@ pub fn run() {
                                     list_mikrotik_ip_pools,
                                     sync_mikrotik_ip_pools,
                                     sync_mikrotik_logs,
+                                    // Wallboard kiosk window
+                                    open_wallboard_window,
+                                    close_wallboard_window,
                                     // Announcements
                                     list_active_announcements,
                                     list_recent_announcements,
@@ -628,6 +710,7 @@ pub fn run() {
                                     create_announcement_admin,
                                     update_announcement_admin,
                                     delete_announcement_admin,
+                                    get_announcement_stats_admin,
                                 ])
                                 .run(tauri::generate_context!())
                                 .expect("error while running tauri application");
@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::FromRow)]
 pub struct Announcement {
@@ -10,6 +11,10 @@ pub struct Announcement {
     pub body: String,
     pub severity: String,
     pub audience: String,
+    /// `AudienceTargets` when `audience` is "segment".
+    pub audience_targets: Option<serde_json::Value>,
+    /// Locale -> `AnnouncementTranslation`; `title`/`body` are the default language.
+    pub translations: Option<serde_json::Value>,
     pub mode: String,   // post|banner
     pub format: String, // plain|markdown
     pub deliver_in_app: bool,
@@ -18,6 +23,14 @@ pub struct Announcement {
     pub starts_at: DateTime<Utc>,
     pub ends_at: Option<DateTime<Utc>>,
     pub notified_at: Option<DateTime<Utc>>,
+    /// `AnnouncementRecurrence` for a recurring series; `None` is one-shot.
+    pub recurrence: Option<serde_json::Value>,
+    /// Occurrences published so far. Dismissals apply to one occurrence.
+    pub occurrence: i32,
+    pub next_occurrence_at: Option<DateTime<Utc>>,
+    /// When the current occurrence stops showing; `None` keeps it up until
+    /// the next one (or `ends_at`).
+    pub occurrence_ends_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -31,14 +44,17 @@ pub struct CreateAnnouncementDto {
     pub title: String,
     pub body: String,
     pub severity: Option<String>, // info|success|warning|error
-    pub audience: Option<String>, // all|admins
-    pub mode: Option<String>,     // post|banner
-    pub format: Option<String>,   // plain|markdown
+    pub audience: Option<String>, // all|admins|segment
+    pub audience_targets: Option<AudienceTargets>,
+    pub translations: Option<BTreeMap<String, AnnouncementTranslation>>,
+    pub mode: Option<String>,   // post|banner
+    pub format: Option<String>, // plain|markdown
     pub deliver_in_app: Option<bool>,
     pub deliver_email: Option<bool>,
     pub deliver_email_force: Option<bool>,
     pub starts_at: Option<DateTime<Utc>>,
     pub ends_at: Option<DateTime<Utc>>,
+    pub recurrence: Option<AnnouncementRecurrence>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -49,6 +65,9 @@ pub struct UpdateAnnouncementDto {
     pub body: Option<String>,
     pub severity: Option<String>,
     pub audience: Option<String>,
+    pub audience_targets: Option<AudienceTargets>,
+    /// Replaces every variant; an empty map removes them.
+    pub translations: Option<BTreeMap<String, AnnouncementTranslation>>,
     pub mode: Option<String>,
     pub format: Option<String>,
     pub deliver_in_app: Option<bool>,
@@ -56,4 +75,113 @@ pub struct UpdateAnnouncementDto {
     pub deliver_email_force: Option<bool>,
     pub starts_at: Option<DateTime<Utc>>,
     pub ends_at: Option<DateTime<Utc>>,
+    /// `null` turns a series back into a one-shot announcement.
+    #[serde(default, deserialize_with = "present")]
+    pub recurrence: Option<Option<AnnouncementRecurrence>>,
+}
+
+/// Tells an explicit `null` (`Some(None)`) apart from a missing field (`None`).
+pub(crate) fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Segment selectors for `audience = "segment"`. A user is included when any
+/// selector matches.
+///
+/// Tenant announcements pick from the tenant: a role the member holds, or (for
+/// portal users) an active subscription on one of the packages, a customer tag,
+/// or service on one of the routers. Global announcements pick across active
+/// tenants: every member of a tenant on one of the plans or listed by id, a
+/// member holding one of the global roles, or a portal user whose customer
+/// carries one of the tags.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AudienceTargets {
+    #[serde(default)]
+    pub role_ids: Vec<String>,
+    #[serde(default)]
+    pub package_ids: Vec<String>,
+    #[serde(default)]
+    pub customer_tags: Vec<String>,
+    #[serde(default)]
+    pub router_ids: Vec<String>,
+    #[serde(default)]
+    pub plan_ids: Vec<String>,
+    #[serde(default)]
+    pub tenant_ids: Vec<String>,
+}
+
+impl AudienceTargets {
+    pub fn is_empty(&self) -> bool {
+        self.role_ids.is_empty()
+            && self.package_ids.is_empty()
+            && self.customer_tags.is_empty()
+            && self.router_ids.is_empty()
+            && self.plan_ids.is_empty()
+            && self.tenant_ids.is_empty()
+    }
+}
+
+/// Title and body of an announcement in one locale.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnnouncementTranslation {
+    pub title: String,
+    pub body: String,
+}
+
+/// When a recurring announcement is published again. Times are local to the
+/// tenant's `app_timezone` (the global one for platform announcements).
+///
+/// - `daily`: every day at `time`.
+/// - `weekly`: on each of `weekdays` (0 = Sunday).
+/// - `monthly`: on `day_of_month`, or on the `week_of_month`-th `weekday`
+///   (`-1` for the last one), e.g. the first Sunday.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnnouncementRecurrence {
+    pub frequency: String, // daily|weekly|monthly
+    pub time: String,      // HH:MM
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weekdays: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day_of_month: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub week_of_month: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekday: Option<u8>,
+    /// How long each occurrence stays up; unset keeps it until the next one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_minutes: Option<u32>,
+}
+
+/// Reach of one announcement occurrence, overall and per segment.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AnnouncementStats {
+    pub announcement_id: String,
+    pub occurrence: i32,
+    pub delivered: i64,
+    /// Readers it was served to in the app (dismissing counts as viewing).
+    pub viewed: i64,
+    pub dismissed: i64,
+    /// `viewed / delivered`; `None` until something was delivered.
+    pub view_rate: Option<f64>,
+    /// "role" for tenant announcements, "tenant" for global ones.
+    pub segment_by: String,
+    pub segments: Vec<AnnouncementSegmentStats>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::FromRow)]
+pub struct AnnouncementSegmentStats {
+    pub key: String,
+    pub label: String,
+    pub delivered: i64,
+    pub viewed: i64,
+    pub dismissed: i64,
+    #[sqlx(skip)]
+    pub view_rate: Option<f64>,
 }
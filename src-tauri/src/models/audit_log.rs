@@ -54,6 +54,23 @@ pub struct AuditLogResponse {
     pub details: Option<String>,
     pub ip_address: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// Fields an update changed, as they were before (secrets redacted).
+    pub old_value: Option<serde_json::Value>,
+    /// The same fields after the update.
+    pub new_value: Option<serde_json::Value>,
+    /// Position in the tamper-evident chain; `None` for entries written before it.
+    pub chain_seq: Option<i64>,
+    pub entry_hash: Option<String>,
+    /// `old_value`/`new_value` paired up per field.
+    #[sqlx(skip)]
+    pub changes: Vec<AuditFieldChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditFieldChange {
+    pub field: String,
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
 }
 
 #[derive(Debug, Deserialize)]
@@ -69,4 +86,103 @@ pub struct AuditLogFilter {
     pub date_from: Option<DateTime<Utc>>,
     pub date_to: Option<DateTime<Utc>>,
     pub search: Option<String>, // Generic search for resource, details, user name
+    /// User id, or part of the user's name or email.
+    pub actor: Option<String>,
+    /// Exact address, or a prefix ending in `*` (`10.0.*`).
+    pub ip_address: Option<String>,
+}
+
+/// Formats `list_audit_logs` results can be exported in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl AuditExportFormat {
+    pub fn parse(value: Option<&str>) -> Option<Self> {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("csv") => Some(Self::Csv),
+            Some("ndjson") | Some("jsonl") => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogExport {
+    pub format: String,
+    pub content: String,
+    pub rows: usize,
+    /// More entries matched than an export holds.
+    pub truncated: bool,
+}
+
+/// A saved set of audit filters (`audit_filter_presets`).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditFilterPreset {
+    pub id: String,
+    pub user_id: String,
+    pub tenant_id: Option<String>,
+    pub name: String,
+    /// Query parameters of the audit list, e.g. `{"action": "update"}`.
+    pub filters: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SaveAuditFilterPresetDto {
+    pub name: String,
+    pub filters: serde_json::Value,
+}
+
+/// What `verify_chain` found wrong with one chain entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditChainIssueKind {
+    /// The stored hash doesn't match the entry's content.
+    Modified,
+    /// Sequence numbers are missing before this entry.
+    Gap,
+    /// The entry doesn't point at the hash of the one before it.
+    BrokenLink,
+    /// Another entry has the same sequence number.
+    Duplicate,
+    /// The chain head is ahead of the newest entry, or disagrees with it.
+    Truncated,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditChainIssue {
+    pub kind: AuditChainIssueKind,
+    pub seq: i64,
+    pub log_id: Option<String>,
+    pub detail: String,
+}
+
+/// Result of walking the audit chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditChainReport {
+    pub valid: bool,
+    /// Chained entries checked.
+    pub checked: i64,
+    /// Oldest surviving entry. Anything before it was removed by retention
+    /// (or is covered by a `gap` issue).
+    pub first_seq: Option<i64>,
+    pub last_seq: Option<i64>,
+    pub head_seq: i64,
+    /// Entries written before chaining was enabled.
+    pub unchained: i64,
+    /// At most `MAX_CHAIN_ISSUES`; `issues_truncated` says whether there were more.
+    pub issues: Vec<AuditChainIssue>,
+    pub issues_truncated: bool,
+    pub verified_at: DateTime<Utc>,
 }
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A public IP port range handed to one subscriber for a period of time.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CgnatPortBlock {
+    pub id: String,
+    pub router_id: Option<String>,
+    pub router_name: Option<String>,
+    pub public_ip: String,
+    pub port_start: i32,
+    pub port_end: i32,
+    pub private_ip: String,
+    pub pppoe_account_id: Option<String>,
+    pub customer_id: Option<String>,
+    /// PPPoE username and customer name as they were at import.
+    pub username: Option<String>,
+    pub customer_name: Option<String>,
+    pub assigned_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+/// One assignment (or release) line from a NAT log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CgnatLogEntry {
+    pub public_ip: String,
+    pub port_start: i32,
+    pub port_end: i32,
+    pub private_ip: String,
+    pub assigned_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+    /// PPPoE username when the log has it; otherwise the subscriber is
+    /// matched by the account's remote address.
+    pub username: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImportCgnatLogRequest {
+    /// Router the log came from.
+    pub router_id: Option<String>,
+    pub entries: Vec<CgnatLogEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedCgnatLogEntry {
+    /// Position in `entries`, from 0.
+    pub index: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CgnatImportResult {
+    pub imported: u64,
+    /// Entries already stored; only their release time was filled in.
+    pub updated: u64,
+    /// Stored without a subscriber because no PPPoE account matched.
+    pub unmatched: u64,
+    pub skipped: Vec<SkippedCgnatLogEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CgnatLookupQuery {
+    pub public_ip: String,
+    pub port: i32,
+    pub at: DateTime<Utc>,
+    /// Case or ticket number of the request, kept in the audit log.
+    pub reference: Option<String>,
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -148,6 +148,21 @@ pub struct UpdateCustomerRequest {
     pub phone: Option<String>,
     pub notes: Option<String>,
     pub is_active: Option<bool>,
+    /// `updated_at` the client last saw; a mismatch is rejected with 409.
+    pub expected_updated_at: Option<DateTime<Utc>>,
+}
+
+/// A customer tag with how many customers carry it.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CustomerTagSummary {
+    pub tag: String,
+    pub customer_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetCustomerTagsRequest {
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -269,6 +284,25 @@ pub struct CustomerSubscriptionView {
     pub latest_reschedule_requested_at: Option<String>,
 }
 
+/// A component of a bundle subscription. Components are provisioned one by
+/// one while the subscription is billed as a single line.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CustomerSubscriptionComponent {
+    pub id: String,
+    pub tenant_id: String,
+    pub subscription_id: String,
+    pub component_package_id: String,
+    pub package_name: Option<String>,
+    pub service_type: String,
+    pub status: String, // pending | provisioned | failed | disabled
+    /// Static IP address, IPTV account or VoIP number.
+    pub detail: Option<String>,
+    pub last_error: Option<String>,
+    pub provisioned_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct CustomerPortalSubscriptionStats {
     pub total: i64,
@@ -286,9 +320,14 @@ pub struct InstallationWorkOrder {
     pub customer_id: String,
     pub location_id: String,
     pub router_id: Option<String>,
-    pub status: String, // pending | in_progress | completed | cancelled
+    pub work_type: String, // installation | repair | relocation | dismantle
+    /// Type-specific fields, see `CreateWorkOrderRequest`.
+    pub details: serde_json::Value,
+    pub status: String, // pending | in_progress | on_hold (repair) | completed | cancelled
     pub assigned_to: Option<String>,
     pub scheduled_at: Option<DateTime<Utc>>,
+    /// End of the time slot; `None` means the tenant's default slot length.
+    pub scheduled_end_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub notes: Option<String>,
     pub created_at: DateTime<Utc>,
@@ -305,9 +344,12 @@ pub struct InstallationWorkOrderView {
     pub location_id: String,
     pub package_id: Option<String>,
     pub router_id: Option<String>,
+    pub work_type: String,
+    pub details: serde_json::Value,
     pub status: String,
     pub assigned_to: Option<String>,
     pub scheduled_at: Option<DateTime<Utc>>,
+    pub scheduled_end_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub notes: Option<String>,
     pub created_at: DateTime<Utc>,
@@ -332,12 +374,52 @@ pub struct InstallationWorkOrderView {
     pub path_link_ids: Option<serde_json::Value>,
 }
 
+/// A repair, relocation or dismantle job on an existing subscription.
+/// Installations are created from customer orders instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateWorkOrderRequest {
+    pub work_type: String, // repair | relocation | dismantle
+    pub subscription_id: String,
+    /// repair: what the customer reported (required).
+    pub problem: Option<String>,
+    /// repair: the support ticket that raised it.
+    pub support_ticket_id: Option<String>,
+    /// relocation: the customer's new location (required).
+    pub target_location_id: Option<String>,
+    /// dismantle: why the equipment is collected.
+    pub reason: Option<String>,
+    pub notes: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct AssignInstallationWorkOrderRequest {
     pub assigned_to: String,
     pub scheduled_at: Option<String>,
+    /// End of the time slot (RFC3339); defaults to `work_order_slot_minutes` after the start.
+    pub scheduled_end_at: Option<String>,
     pub notes: Option<String>,
+    /// Book the slot even if the technician already has work at that time (admin/owner only).
+    pub allow_overlap: Option<bool>,
+}
+
+/// One booked time slot on the dispatch calendar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkOrderAgendaItem {
+    pub work_order_id: String,
+    pub status: String,
+    pub assigned_to: Option<String>,
+    pub assigned_to_name: Option<String>,
+    pub scheduled_at: DateTime<Utc>,
+    /// Stored end, or start plus the tenant's default slot length.
+    pub scheduled_end_at: DateTime<Utc>,
+    pub customer_id: String,
+    pub customer_name: Option<String>,
+    pub location_label: Option<String>,
+    pub package_name: Option<String>,
+    /// Overlaps another pending/in-progress work order of the same technician.
+    pub has_conflict: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -385,6 +467,217 @@ pub struct WorkOrderRescheduleDecisionRequest {
     pub notes: Option<String>,
 }
 
+/// A technician's on-site visit, from GPS check-in to check-out.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WorkOrderVisit {
+    pub id: String,
+    pub work_order_id: String,
+    pub technician_id: Option<String>,
+    pub technician_name: Option<String>,
+    pub check_in_at: DateTime<Utc>,
+    pub check_in_latitude: f64,
+    pub check_in_longitude: f64,
+    pub check_in_accuracy_m: Option<f64>,
+    /// Metres from the customer location; `None` if the location has no coordinates.
+    pub check_in_distance_m: Option<f64>,
+    pub check_out_at: Option<DateTime<Utc>>,
+    pub check_out_latitude: Option<f64>,
+    pub check_out_longitude: Option<f64>,
+    pub check_out_accuracy_m: Option<f64>,
+    pub check_out_distance_m: Option<f64>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkOrderCheckRequest {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Reported GPS accuracy in metres.
+    pub accuracy_m: Option<f64>,
+    pub notes: Option<String>,
+    /// When the fix was taken (RFC3339), for visits queued on a device while
+    /// offline. Defaults to the time the request arrives.
+    #[serde(default)]
+    pub recorded_at: Option<String>,
+}
+
+/// A stop on a technician's planned route, in visit order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkOrderRouteStop {
+    /// 1-based position in the planned sequence.
+    pub sequence: u32,
+    pub work_order_id: String,
+    pub status: String,
+    pub scheduled_at: DateTime<Utc>,
+    pub customer_id: String,
+    pub customer_name: Option<String>,
+    pub location_label: Option<String>,
+    pub address: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// From the previous stop (or the start point).
+    pub leg_distance_m: f64,
+    pub cumulative_distance_m: f64,
+}
+
+/// A work order of the day that can't be routed because its location has no
+/// coordinates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkOrderRouteSkipped {
+    pub work_order_id: String,
+    pub scheduled_at: DateTime<Utc>,
+    pub customer_name: Option<String>,
+    pub location_label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkOrderRoutePlan {
+    pub technician_id: String,
+    pub technician_name: Option<String>,
+    /// Day in the app timezone.
+    pub date: NaiveDate,
+    pub start_latitude: Option<f64>,
+    pub start_longitude: Option<f64>,
+    pub stops: Vec<WorkOrderRouteStop>,
+    pub skipped: Vec<WorkOrderRouteSkipped>,
+    pub total_distance_m: f64,
+    /// Distance when visiting in scheduled order, for comparison.
+    pub scheduled_order_distance_m: f64,
+    /// GeoJSON LineString through the start point and stops; null with
+    /// fewer than two points.
+    pub path: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WorkOrderTemplateItem {
+    pub id: String,
+    pub position: i32,
+    pub label: String,
+    pub kind: String, // check | photo | text
+    pub is_required: bool,
+}
+
+/// Checklist template for installation work orders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkOrderTemplate {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Copied onto work orders that don't have a checklist yet.
+    pub is_default: bool,
+    pub is_active: bool,
+    pub items: Vec<WorkOrderTemplateItem>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkOrderTemplateItemInput {
+    pub label: String,
+    /// Defaults to `check`.
+    pub kind: Option<String>,
+    /// Defaults to `true`.
+    pub is_required: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertWorkOrderTemplateRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub is_default: Option<bool>,
+    pub is_active: Option<bool>,
+    pub items: Vec<WorkOrderTemplateItemInput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WorkOrderChecklistItem {
+    pub id: String,
+    pub work_order_id: String,
+    pub template_id: Option<String>,
+    pub position: i32,
+    pub label: String,
+    pub kind: String,
+    pub is_required: bool,
+    pub is_done: bool,
+    pub value: Option<String>,
+    pub file_id: Option<String>,
+    pub file_name: Option<String>,
+    pub completed_by: Option<String>,
+    pub completed_by_name: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkOrderChecklist {
+    pub work_order_id: String,
+    pub items: Vec<WorkOrderChecklistItem>,
+    /// Labels of required items that still block completion.
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApplyWorkOrderTemplateRequest {
+    pub template_id: String,
+}
+
+/// Fields a technician fills in; which one counts depends on the item kind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateWorkOrderChecklistItemRequest {
+    pub is_done: Option<bool>,
+    pub value: Option<String>,
+    /// Uploaded image (file_records id); an empty string clears it.
+    pub file_id: Option<String>,
+}
+
+/// Customer signature captured on site when a work order is finished.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkOrderSignatureInput {
+    pub signer_name: String,
+    /// Pen strokes from a signature pad, each a list of `[x, y]` points in the
+    /// pad's own coordinates (y grows downwards, as on a canvas).
+    #[serde(default)]
+    pub strokes: Vec<Vec<[f64; 2]>>,
+    /// JPEG picture of the signature, base64 or a `data:` URL. Used when no
+    /// strokes are sent.
+    pub image_base64: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompleteWorkOrderRequest {
+    pub notes: Option<String>,
+    pub signature: Option<WorkOrderSignatureInput>,
+}
+
+/// Signed completion report (berita acara) of a work order.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WorkOrderCompletionReport {
+    pub id: String,
+    pub work_order_id: String,
+    pub customer_id: String,
+    pub report_number: String,
+    pub signer_name: String,
+    pub signature_kind: String,
+    pub signature_file_id: Option<String>,
+    /// The generated PDF (file_records id).
+    pub file_id: Option<String>,
+    pub signed_at: DateTime<Utc>,
+    pub emailed_to: Option<String>,
+    pub emailed_at: Option<DateTime<Utc>>,
+    /// Why the copy could not be emailed; `None` when sent or not attempted.
+    pub email_error: Option<String>,
+    pub created_by: Option<String>,
+    pub created_by_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct CreateCustomerSubscriptionRequest {
@@ -416,6 +709,13 @@ pub struct UpdateCustomerSubscriptionRequest {
     pub notes: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateSubscriptionComponentRequest {
+    pub status: Option<String>,
+    pub detail: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct PortalCheckoutSubscriptionRequest {
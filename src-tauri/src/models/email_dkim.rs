@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A tenant's DKIM signing key. The private key never leaves the server.
+#[derive(Debug, Clone, FromRow)]
+pub struct EmailDkimKey {
+    pub tenant_id: String,
+    pub domain: String,
+    pub selector: String,
+    pub private_key_enc: String,
+    /// Base64 DER public key, i.e. the `p=` value of the DNS record.
+    pub public_key: String,
+    pub is_enabled: bool,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The TXT record a tenant has to publish for receivers to verify signatures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkimDnsRecord {
+    pub record_type: String,
+    /// Fully qualified name, e.g. `isp202603._domainkey.example.com`.
+    pub name: String,
+    pub value: String,
+    /// `value` split into strings of at most 255 characters, for DNS panels
+    /// that want a long TXT record entered piece by piece.
+    pub chunks: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailDkimStatus {
+    pub configured: bool,
+    pub enabled: bool,
+    pub domain: Option<String>,
+    pub selector: Option<String>,
+    pub algorithm: String,
+    pub dns_record: Option<DkimDnsRecord>,
+    /// Domain of the configured sender address.
+    pub from_domain: Option<String>,
+    /// Whether the key's domain matches the sender's, which DMARC alignment needs.
+    pub domain_matches_from: bool,
+    pub provider: String,
+    /// Outgoing mail is actually being signed: key enabled and provider is SMTP.
+    pub signing_active: bool,
+    pub instructions: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenerateDkimKeyRequest {
+    /// Defaults to the domain of the sender address.
+    pub domain: Option<String>,
+    /// Defaults to `isp` plus the current year and month.
+    pub selector: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateDkimRequest {
+    pub enabled: bool,
+}
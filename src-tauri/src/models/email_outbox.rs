@@ -13,6 +13,7 @@ pub struct EmailOutboxItem {
     pub status: String, // queued | sending | sent | failed
     pub attempts: i32,
     pub max_attempts: i32,
+    pub priority: i32, // 0 = priority, 5 = normal, 9 = bulk
     pub scheduled_at: DateTime<Utc>,
     pub last_error: Option<String>,
     pub sent_at: Option<DateTime<Utc>>,
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// An address that bounced or complained. Only rows with `is_suppressed` stop
+/// mail; soft bounces are counted until they repeat often enough.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EmailSuppression {
+    pub id: String,
+    pub tenant_id: Option<String>,
+    pub email: String,
+    pub reason: String, // hard_bounce | soft_bounce | complaint | manual
+    pub source: String, // smtp | ses | sendgrid | mailgun | manual
+    pub detail: Option<String>,
+    pub bounce_count: i32,
+    pub is_suppressed: bool,
+    pub last_event_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AddEmailSuppressionRequest {
+    pub email: String,
+    pub detail: Option<String>,
+}
+
+/// What a provider webhook call changed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmailEventsResult {
+    pub received: usize,
+    pub suppressed: usize,
+    pub ignored: usize,
+}
@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A tenant's override of a built-in HTML email.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EmailTemplateOverride {
+    pub id: String,
+    pub tenant_id: String,
+    pub code: String,
+    pub subject: String,
+    pub body_html: String,
+    /// Plain-text part; derived from the HTML when empty.
+    pub body_text: Option<String>,
+    pub updated_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Documentation for one placeholder an email type fills in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailTemplateVariable {
+    pub name: String,
+    pub description: String,
+    /// Value used by previews and test sends.
+    pub sample: String,
+}
+
+/// The email a type is sent with: the tenant's override when present,
+/// otherwise the built-in default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailTemplate {
+    pub code: String,
+    pub description: String,
+    pub subject: String,
+    pub body_html: String,
+    pub body_text: Option<String>,
+    pub default_subject: String,
+    pub default_body_html: String,
+    pub default_body_text: String,
+    pub variables: Vec<EmailTemplateVariable>,
+    pub is_customized: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateEmailTemplateRequest {
+    pub subject: String,
+    pub body_html: String,
+    pub body_text: Option<String>,
+}
+
+/// Unsaved content to preview or test-send; omitted fields use the current
+/// template.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PreviewEmailTemplateRequest {
+    pub subject: Option<String>,
+    pub body_html: Option<String>,
+    pub body_text: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestSendEmailTemplateRequest {
+    /// Recipient; defaults to the caller's own address.
+    pub to: Option<String>,
+    pub subject: Option<String>,
+    pub body_html: Option<String>,
+    pub body_text: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Operational flag (rollout, kill switch). Unlike plan features these are
+/// not sold; they only gate code paths while something ships or misbehaves.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FeatureFlag {
+    pub key: String,
+    pub description: Option<String>,
+    pub enabled: bool, // false = killed everywhere, overrides included
+    pub rollout_percentage: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FeatureFlagOverride {
+    pub flag_key: String,
+    pub tenant_id: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A flag with its per-tenant overrides, as listed for super admins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlagWithOverrides {
+    #[serde(flatten)]
+    pub flag: FeatureFlag,
+    pub overrides: Vec<FeatureFlagOverride>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UpsertFeatureFlagRequest {
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub rollout_percentage: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetFeatureFlagOverrideRequest {
+    pub enabled: bool,
+}
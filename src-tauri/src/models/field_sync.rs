@@ -0,0 +1,77 @@
+use crate::models::{Customer, CustomerLocation, InstallationWorkOrderView, WorkOrderChecklist};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldSyncPullRequest {
+    /// `next_cursor` of the previous pull; omit for a full download.
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    /// Work orders the device holds. Those it should no longer keep are
+    /// listed in `revoked_work_order_ids` on the last page.
+    #[serde(default)]
+    pub known_work_order_ids: Vec<String>,
+}
+
+/// One page of changes for a field device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldSyncChanges {
+    pub work_orders: Vec<InstallationWorkOrderView>,
+    pub checklists: Vec<WorkOrderChecklist>,
+    pub customers: Vec<Customer>,
+    pub locations: Vec<CustomerLocation>,
+    /// Reassigned or closed work orders the device should drop.
+    pub revoked_work_order_ids: Vec<String>,
+    pub next_cursor: String,
+    /// More pages follow; pull again with `next_cursor` right away.
+    pub has_more: bool,
+    pub server_time: DateTime<Utc>,
+}
+
+/// A change made on the device while offline, replayed in queue order.
+///
+/// `kind` is one of `note`, `start`, `hold`, `complete`, `check_in`,
+/// `check_out`, `checklist_item` or `location`. `payload` carries the same
+/// fields as the matching online request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldSyncMutation {
+    /// Generated by the device; a mutation is applied at most once.
+    pub client_mutation_id: String,
+    pub kind: String,
+    pub work_order_id: Option<String>,
+    /// `checklist_item`: the item to update.
+    pub item_id: Option<String>,
+    /// `location`: the customer location to update.
+    pub location_id: Option<String>,
+    /// `updated_at` of the record as the device last saw it.
+    pub base_updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldSyncPushRequest {
+    pub mutations: Vec<FieldSyncMutation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldSyncMutationResult {
+    pub client_mutation_id: String,
+    /// `applied`, `conflict` (the server copy changed; `data` holds it) or
+    /// `rejected` (the change can never be applied).
+    pub status: String,
+    pub error: Option<String>,
+    /// The record after the change, or the server copy on conflict.
+    pub data: Option<serde_json::Value>,
+    /// Answered from an earlier push of the same mutation.
+    pub replayed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldSyncPushResponse {
+    pub results: Vec<FieldSyncMutationResult>,
+    pub server_time: DateTime<Utc>,
+}
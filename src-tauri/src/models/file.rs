@@ -13,7 +13,140 @@ pub struct FileRecord {
     pub content_type: String,
     #[sqlx(default)]
     pub storage_provider: String,
+    /// Bucket holding the object; `None` on local disk (and for S3 files
+    /// uploaded before buckets were recorded).
+    #[sqlx(default)]
+    pub storage_bucket: Option<String>,
+    /// Whether `size` is included in the tenant's `storage_usage`.
+    #[sqlx(default)]
+    pub quota_counted: bool,
+    /// Malware scan result: `unscanned`, `clean`, `infected` (quarantined,
+    /// not downloadable) or `error`.
+    #[sqlx(default)]
+    pub scan_status: String,
+    /// Signature that matched, for infected files.
+    #[sqlx(default)]
+    pub scan_signature: Option<String>,
+    #[sqlx(default)]
+    pub scanned_at: Option<DateTime<Utc>>,
+    /// Name the uploader gave the file (e.g. `contracts/acme.pdf`). Uploading
+    /// to a taken path adds a version instead of a new file.
+    #[sqlx(default)]
+    pub logical_path: Option<String>,
+    /// Current version number, starting at 1.
+    #[sqlx(default)]
+    pub version: i32,
+    pub uploaded_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// When the current version was uploaded.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Earlier content of a versioned file, kept when a newer version replaced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(any(feature = "postgres", feature = "sqlite"), derive(sqlx::FromRow))]
+pub struct FileVersion {
+    pub id: String,
+    pub file_id: String,
+    pub tenant_id: String,
+    pub version: i32,
+    pub name: String,
+    pub original_name: String,
+    pub path: String,
+    pub size: i64,
+    pub content_type: String,
+    pub storage_provider: String,
+    pub storage_bucket: Option<String>,
+    pub quota_counted: bool,
+    pub scan_status: String,
+    pub scan_signature: Option<String>,
+    pub scanned_at: Option<DateTime<Utc>>,
     pub uploaded_by: Option<String>,
+    /// When this version was uploaded.
+    pub created_at: DateTime<Utc>,
+    /// When a newer version replaced it.
+    pub replaced_at: DateTime<Utc>,
+}
+
+/// Time-limited download link for a file that works without a session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedFileLink {
+    pub file_id: String,
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Tenant rule deleting files that match `name_pattern` and/or `content_type`
+/// (`*` and `?` wildcards, case-insensitive) once they are older than
+/// `older_than_days`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(any(feature = "postgres", feature = "sqlite"), derive(sqlx::FromRow))]
+pub struct StorageCleanupRule {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    /// Matched against the original file name, e.g. `*.csv`.
+    pub name_pattern: Option<String>,
+    /// e.g. `text/csv` or `image/*`.
+    pub content_type: Option<String>,
+    pub older_than_days: i32,
+    pub enabled: bool,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_deleted_count: i32,
+    pub last_deleted_bytes: i64,
+    pub created_by: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertStorageCleanupRuleDto {
+    pub name: String,
+    #[serde(alias = "name_pattern")]
+    pub name_pattern: Option<String>,
+    #[serde(alias = "content_type")]
+    pub content_type: Option<String>,
+    #[serde(alias = "older_than_days")]
+    pub older_than_days: i32,
+    pub enabled: Option<bool>,
+}
+
+/// What one run of a cleanup rule removed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageCleanupResult {
+    pub rule_id: String,
+    pub deleted_count: i64,
+    pub deleted_bytes: i64,
+    /// Matching files that could not be deleted (retried on the next run).
+    pub failed: i64,
+}
+
+/// Files counted against the quota, grouped by kind (image, video, audio,
+/// document, other).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(any(feature = "postgres", feature = "sqlite"), derive(sqlx::FromRow))]
+pub struct StorageUsageCategory {
+    pub category: String,
+    pub files: i64,
+    pub bytes: i64,
+}
+
+/// A tenant's storage use against its plan quota.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageUsageReport {
+    pub tenant_id: String,
+    /// `tenants.storage_usage`, what quota checks use.
+    pub used_bytes: i64,
+    /// None when the plan has no storage limit.
+    pub limit_bytes: Option<i64>,
+    pub percent: Option<f64>,
+    /// Last alert level sent to the tenant's admins (0, 80 or 95).
+    pub alert_threshold: i32,
+    pub file_count: i64,
+    pub file_bytes: i64,
+    /// Earlier versions kept for versioned files.
+    pub version_count: i64,
+    pub version_bytes: i64,
+    pub categories: Vec<StorageUsageCategory>,
+}
@@ -0,0 +1,127 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A key for the public API. The key itself is shown once, at creation.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TenantApiKey {
+    pub id: String,
+    pub name: String,
+    /// Start of the key, to tell keys apart.
+    pub key_prefix: String,
+    /// User the key acts as; requests have their permissions.
+    pub user_id: String,
+    pub user_name: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    /// Never expires when absent.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub key: TenantApiKey,
+    /// Send as `Authorization: Bearer <token>`. Not retrievable later.
+    pub token: String,
+}
+
+/// Where and which events are POSTed, signed like the system event webhook
+/// (`X-Event-Signature: sha256=<hex hmac of the body>`).
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TenantWebhook {
+    pub id: String,
+    pub url: String,
+    pub description: Option<String>,
+    /// Event types sent (the `X-Event-Type` header); empty sends all of them.
+    /// Business events are `customer.created`, `invoice.paid`,
+    /// `router.offline` and `ticket.created`.
+    pub event_types: Vec<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub event_types: Vec<String>,
+}
+
+/// Fields left out keep their value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub description: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: TenantWebhook,
+    /// Signing secret. Not retrievable later.
+    pub secret: String,
+}
+
+/// One event sent (or being sent) to a webhook; fields describe the last
+/// attempt.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event_id: String,
+    pub event_type: String,
+    pub payload: String,
+    /// `pending`, `delivered` or `failed` (gave up retrying).
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub duration_ms: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDeliveryQuery {
+    pub webhook_id: Option<String>,
+    pub status: Option<String>,
+    /// 50 by default, at most 200.
+    pub limit: Option<i64>,
+}
+
+/// Requests an API key has left in the current rate-limit window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyRateLimit {
+    pub api_key_id: String,
+    pub name: String,
+    pub limit: u32,
+    pub used: u32,
+    pub remaining: u32,
+    pub window_secs: u64,
+    /// Until the oldest request in the window stops counting.
+    pub reset_in_secs: u64,
+}
+
+/// Everything a tenant needs to build against the public API, in one call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrationsOverview {
+    pub api_keys: Vec<TenantApiKey>,
+    pub webhooks: Vec<TenantWebhook>,
+    pub recent_deliveries: Vec<WebhookDelivery>,
+    pub rate_limits: Vec<ApiKeyRateLimit>,
+}
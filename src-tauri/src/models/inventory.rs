@@ -0,0 +1,182 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct InventoryItem {
+    pub id: String,
+    pub tenant_id: String,
+    pub sku: String,
+    pub name: String,
+    pub unit: String,
+    pub low_stock_threshold: Option<f64>,
+    pub is_active: bool,
+    /// Stock across all locations.
+    pub total_quantity: f64,
+    pub is_low_stock: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertInventoryItemRequest {
+    pub sku: String,
+    pub name: String,
+    /// Defaults to `pcs`.
+    pub unit: Option<String>,
+    pub low_stock_threshold: Option<f64>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct InventoryLocation {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub kind: String, // warehouse | technician
+    pub technician_id: Option<String>,
+    pub technician_name: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertInventoryLocationRequest {
+    pub name: String,
+    /// `warehouse` (default) or `technician`; fixed after creation.
+    pub kind: Option<String>,
+    pub technician_id: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct InventoryStockLevel {
+    pub item_id: String,
+    pub sku: String,
+    pub item_name: String,
+    pub unit: String,
+    pub location_id: String,
+    pub location_name: String,
+    pub location_kind: String,
+    pub quantity: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct InventoryMovement {
+    pub id: String,
+    pub item_id: String,
+    pub sku: Option<String>,
+    pub item_name: Option<String>,
+    pub unit: Option<String>,
+    pub kind: String, // receive | transfer | adjust | consume
+    pub from_location_id: Option<String>,
+    pub from_location_name: Option<String>,
+    pub to_location_id: Option<String>,
+    pub to_location_name: Option<String>,
+    pub quantity: f64,
+    pub work_order_id: Option<String>,
+    pub note: Option<String>,
+    pub created_by: Option<String>,
+    pub created_by_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A stock movement entered by hand. `receive` needs `to_location_id`,
+/// `transfer` both locations, `adjust` exactly one of them (`from` to write
+/// stock off, `to` to add found stock).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateInventoryMovementRequest {
+    pub item_id: String,
+    pub kind: String,
+    pub from_location_id: Option<String>,
+    pub to_location_id: Option<String>,
+    pub quantity: f64,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkOrderMaterialLine {
+    pub item_id: String,
+    pub quantity: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConsumeWorkOrderMaterialsRequest {
+    /// Stock to take from; defaults to the acting technician's own location.
+    pub location_id: Option<String>,
+    pub items: Vec<WorkOrderMaterialLine>,
+    pub note: Option<String>,
+}
+
+/// A serial-numbered unit of an item, e.g. one ONU.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct InventorySerial {
+    pub id: String,
+    pub item_id: String,
+    pub sku: String,
+    pub item_name: String,
+    pub serial_number: String,
+    pub status: String, // in_stock | installed
+    pub location_id: Option<String>,
+    pub location_name: Option<String>,
+    pub work_order_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What a scanned barcode refers to: a registered serial, an item SKU, or
+/// nothing yet (`matched` is `serial`, `item` or `unknown`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryScanResult {
+    pub code: String,
+    pub matched: String,
+    pub serial: Option<InventorySerial>,
+    pub item: Option<InventoryItem>,
+}
+
+/// Warehouse intake: register scanned serials of one item and receive them
+/// into `location_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReceiveInventorySerialsRequest {
+    pub item_id: String,
+    pub location_id: String,
+    pub serials: Vec<String>,
+    pub note: Option<String>,
+}
+
+/// Move scanned in-stock serials, e.g. onto a technician's van.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransferInventorySerialsRequest {
+    pub to_location_id: String,
+    pub serials: Vec<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedInventorySerial {
+    pub serial_number: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventorySerialBatchResult {
+    pub serials: Vec<InventorySerial>,
+    pub skipped: Vec<SkippedInventorySerial>,
+}
+
+/// Install a scanned unit on a work order: one unit of its item is consumed
+/// from the location it is kept at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InstallInventorySerialRequest {
+    pub serial_number: String,
+    pub note: Option<String>,
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct IpamSubnet {
+    pub id: String,
+    pub tenant_id: String,
+    /// Network address and prefix, e.g. `10.20.0.0/22`.
+    pub cidr: String,
+    pub name: String,
+    pub vlan_id: Option<i32>,
+    /// customer | pppoe | static | infrastructure | management | transit | reserved
+    pub purpose: Option<String>,
+    pub tags: Vec<String>,
+    pub router_id: Option<String>,
+    pub router_name: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertIpamSubnetRequest {
+    pub cidr: String,
+    pub name: String,
+    pub vlan_id: Option<i32>,
+    pub purpose: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub router_id: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// One block of a subnet's heatmap. A subnet is split into at most 256
+/// equal blocks (a /16 into /24s, a /24 into single addresses).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IpamHeatCell {
+    pub cidr: String,
+    pub size: u64,
+    pub assigned: u64,
+    pub utilization_pct: f64,
+    /// Child subnet the block falls in, if any.
+    pub subnet_id: Option<String>,
+}
+
+/// A subnet with its place in the plan and how full it is. The parent is
+/// the smallest other subnet containing it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpamSubnetNode {
+    #[serde(flatten)]
+    pub subnet: IpamSubnet,
+    pub parent_id: Option<String>,
+    pub depth: u32,
+    pub first_address: String,
+    pub last_address: String,
+    pub size: u64,
+    /// Host addresses: the size minus network and broadcast for /30 and larger.
+    pub usable: u64,
+    /// Addresses held by PPPoE accounts and provisioned static IPs.
+    pub assigned: u64,
+    /// Addresses inside router IP pools.
+    pub pooled: u64,
+    /// Addresses covered by child subnets.
+    pub allocated: u64,
+    /// `assigned` against `usable`.
+    pub utilization_pct: f64,
+    /// `allocated` against `size`.
+    pub allocation_pct: f64,
+    /// Largest blocks not covered by a child subnet, lowest first.
+    pub free_blocks: Vec<String>,
+    pub cells: Vec<IpamHeatCell>,
+    pub children: Vec<IpamSubnetNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpamPlan {
+    /// Top-level subnets; the rest hang under `children`.
+    pub subnets: Vec<IpamSubnetNode>,
+    /// Assigned addresses outside every planned subnet.
+    pub unplanned_assigned: u64,
+    pub generated_at: DateTime<Utc>,
+}
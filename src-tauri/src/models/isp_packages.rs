@@ -2,6 +2,80 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// MikroTik burst settings, rendered into the PPP profile `rate-limit`.
+/// Speeds are in kbps; `burst_priority` is 1 (highest) to 8. Queries that
+/// don't select these columns leave them unset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BandwidthBurst {
+    #[sqlx(default)]
+    pub burst_limit_up_kbps: Option<i32>,
+    #[sqlx(default)]
+    pub burst_limit_down_kbps: Option<i32>,
+    #[sqlx(default)]
+    pub burst_threshold_up_kbps: Option<i32>,
+    #[sqlx(default)]
+    pub burst_threshold_down_kbps: Option<i32>,
+    #[sqlx(default)]
+    pub burst_time_secs: Option<i32>,
+    #[sqlx(default)]
+    pub burst_priority: Option<i32>,
+}
+
+impl BandwidthBurst {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Fields set in `self` win over `base`.
+    pub fn or(&self, base: &BandwidthBurst) -> BandwidthBurst {
+        BandwidthBurst {
+            burst_limit_up_kbps: self.burst_limit_up_kbps.or(base.burst_limit_up_kbps),
+            burst_limit_down_kbps: self.burst_limit_down_kbps.or(base.burst_limit_down_kbps),
+            burst_threshold_up_kbps: self
+                .burst_threshold_up_kbps
+                .or(base.burst_threshold_up_kbps),
+            burst_threshold_down_kbps: self
+                .burst_threshold_down_kbps
+                .or(base.burst_threshold_down_kbps),
+            burst_time_secs: self.burst_time_secs.or(base.burst_time_secs),
+            burst_priority: self.burst_priority.or(base.burst_priority),
+        }
+    }
+}
+
+/// Fair-usage policy: past `fup_quota_gb` in a usage period the subscriber
+/// is throttled to the `fup_rate_*` speeds (kbps) until the period resets.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FairUsagePolicy {
+    #[sqlx(default)]
+    pub fup_quota_gb: Option<i32>,
+    #[sqlx(default)]
+    pub fup_rate_up_kbps: Option<i32>,
+    #[sqlx(default)]
+    pub fup_rate_down_kbps: Option<i32>,
+}
+
+impl FairUsagePolicy {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Package speeds. Unset rates leave the router profile's speed alone.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct IspPackageBandwidth {
+    #[sqlx(default)]
+    pub rate_limit_up_kbps: Option<i32>,
+    #[sqlx(default)]
+    pub rate_limit_down_kbps: Option<i32>,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub burst: BandwidthBurst,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub fup: FairUsagePolicy,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct IspPackage {
     pub id: String,
@@ -11,10 +85,20 @@ pub struct IspPackage {
     pub description: Option<String>,
     pub features: Vec<String>,
     pub is_active: bool,
+    /// Only sold inside service zones that have an active zone offer for it.
+    #[sqlx(default)]
+    pub zone_restricted: bool,
     #[sqlx(try_from = "f64")]
     pub price_monthly: f64,
     #[sqlx(try_from = "f64")]
     pub price_yearly: f64,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub bandwidth: IspPackageBandwidth,
+    /// Packages combined by a `bundle`, the internet package first.
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub component_ids: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -42,8 +126,11 @@ impl IspPackage {
             description,
             features: features.unwrap_or_default(),
             is_active: is_active.unwrap_or(true),
+            zone_restricted: false,
             price_monthly: price_monthly.unwrap_or(0.0),
             price_yearly: price_yearly.unwrap_or(0.0),
+            bandwidth: IspPackageBandwidth::default(),
+            component_ids: Vec::new(),
             created_at: now,
             updated_at: now,
         }
@@ -57,8 +144,12 @@ pub struct CreateIspPackageRequest {
     pub description: Option<String>,
     pub features: Option<Vec<String>>,
     pub is_active: Option<bool>,
+    pub zone_restricted: Option<bool>,
     pub price_monthly: Option<f64>,
     pub price_yearly: Option<f64>,
+    pub bandwidth: Option<IspPackageBandwidth>,
+    /// Component package ids; required for bundles.
+    pub components: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,8 +159,29 @@ pub struct UpdateIspPackageRequest {
     pub description: Option<String>,
     pub features: Option<Vec<String>>,
     pub is_active: Option<bool>,
+    pub zone_restricted: Option<bool>,
     pub price_monthly: Option<f64>,
     pub price_yearly: Option<f64>,
+    /// Replaces all speeds when present.
+    pub bandwidth: Option<IspPackageBandwidth>,
+    /// Replaces the bundle components when present.
+    pub components: Option<Vec<String>>,
+}
+
+/// List prices of a package from `effective_from` until the next entry.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct IspPackagePriceHistory {
+    pub id: String,
+    pub tenant_id: String,
+    pub package_id: String,
+    #[sqlx(try_from = "f64")]
+    pub price_monthly: f64,
+    #[sqlx(try_from = "f64")]
+    pub price_yearly: f64,
+    pub effective_from: DateTime<Utc>,
+    pub changed_by: Option<String>,
+    pub changed_by_name: Option<String>,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -80,6 +192,10 @@ pub struct IspPackageRouterMapping {
     pub package_id: String,
     pub router_profile_name: String,
     pub address_pool: Option<String>,
+    /// Overrides the package burst on this router; unset fields keep the package value.
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub burst: BandwidthBurst,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -100,6 +216,7 @@ impl IspPackageRouterMapping {
             package_id,
             router_profile_name,
             address_pool,
+            burst: BandwidthBurst::default(),
             created_at: now,
             updated_at: now,
         }
@@ -112,6 +229,8 @@ pub struct UpsertIspPackageRouterMappingRequest {
     pub package_id: String,
     pub router_profile_name: String,
     pub address_pool: Option<String>,
+    #[serde(default)]
+    pub burst: BandwidthBurst,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -124,6 +243,9 @@ pub struct IspPackageRouterMappingView {
     pub router_name: Option<String>,
     pub router_profile_name: String,
     pub address_pool: Option<String>,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub burst: BandwidthBurst,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
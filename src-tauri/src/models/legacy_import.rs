@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+/// Exports of another billing system to bring into the tenant. Each file is
+/// the CSV text of one table; which tables a source uses is listed with its
+/// adapter (`GET /api/admin/legacy-import/sources`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LegacyImportRequest {
+    /// `phpnuxbill`, `mikbill` or `csv`.
+    pub source: String,
+    pub customers_csv: Option<String>,
+    pub packages_csv: Option<String>,
+    /// PPPoE logins, for sources that keep them apart from customers.
+    pub secrets_csv: Option<String>,
+    /// Unpaid amounts, for sources that keep them apart from customers.
+    pub balances_csv: Option<String>,
+    /// Router the PPPoE secrets belong to. Without it secrets are skipped.
+    pub router_id: Option<String>,
+    /// Write the data. Without it only the validation report is returned.
+    #[serde(default)]
+    pub commit: bool,
+}
+
+/// A billing system the importer reads, with the tables it expects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegacyImportSource {
+    pub code: String,
+    pub name: String,
+    pub files: Vec<LegacyImportFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegacyImportFile {
+    /// Request field without `_csv`: `customers`, `packages`, ...
+    pub file: String,
+    /// Table or export of the source system that goes there.
+    pub table: String,
+    /// Columns read, first match wins where several are listed.
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegacyImportIssue {
+    /// `customers`, `packages`, `secrets` or `balances`.
+    pub file: String,
+    /// Line in the CSV, the header being line 1; 0 for the file as a whole.
+    pub line: usize,
+    /// `error` blocks the import; `warning` rows are imported or skipped as
+    /// the message says.
+    pub severity: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LegacyImportCounts {
+    pub customers: u32,
+    pub packages: u32,
+    pub subscriptions: u32,
+    pub secrets: u32,
+    /// Unpaid invoices.
+    pub invoices: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegacyImportReport {
+    pub source: String,
+    /// Whether the data was written. False for a validation run and when the
+    /// report has errors.
+    pub committed: bool,
+    /// Records that are (or would be) created.
+    pub created: LegacyImportCounts,
+    /// Records that already exist and are reused or left alone.
+    pub existing: LegacyImportCounts,
+    /// Sum of the unpaid invoices, in `currency_code`.
+    pub unpaid_total: f64,
+    pub currency_code: String,
+    pub issues: Vec<LegacyImportIssue>,
+}
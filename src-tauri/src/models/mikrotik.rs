@@ -28,6 +28,9 @@ pub struct MikrotikRouter {
     pub longitude: Option<f64>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Last edit of the router's settings; unlike `updated_at`, polling
+    /// leaves it alone.
+    pub config_updated_at: DateTime<Utc>,
 }
 
 impl MikrotikRouter {
@@ -66,6 +69,7 @@ impl MikrotikRouter {
             longitude,
             created_at: now,
             updated_at: now,
+            config_updated_at: now,
         }
     }
 }
@@ -111,6 +115,9 @@ pub struct UpdateMikrotikRouterRequest {
     pub latitude: Option<f64>,
     #[serde(alias = "longitude")]
     pub longitude: Option<f64>,
+    /// Optimistic concurrency precondition (the router's last seen `config_updated_at`).
+    #[serde(alias = "expectedUpdatedAt")]
+    pub expected_updated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -340,7 +347,10 @@ pub struct MikrotikIpPool {
 pub struct MikrotikAlert {
     pub id: String,
     pub tenant_id: String,
+    /// The router, or the monitored device when `source` is `snmp`.
     pub router_id: String,
+    /// `routeros` or `snmp`.
+    pub source: String,
     pub alert_type: String, // offline | cpu | latency
     pub severity: String,   // info | warning | critical
     pub status: String,     // open | ack | resolved
@@ -361,7 +371,10 @@ pub struct MikrotikAlert {
 pub struct MikrotikIncident {
     pub id: String,
     pub tenant_id: String,
+    /// The router, or the monitored device when `source` is `snmp`.
     pub router_id: String,
+    /// `routeros` or `snmp`.
+    pub source: String,
     pub interface_name: Option<String>,
     pub incident_type: String,
     pub dedup_key: String,
@@ -384,10 +397,46 @@ pub struct MikrotikIncident {
     #[serde(default)]
     #[sqlx(default)]
     pub escalated_at: Option<DateTime<Utc>>,
+    /// Active subscriptions behind the router when the incident opened.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub affected_subscriptions: Option<i32>,
+    #[serde(default)]
+    #[sqlx(default)]
+    pub affected_customers: Option<i32>,
+    /// `MikrotikIncidentArea` entries, largest first.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub affected_areas: Option<serde_json::Value>,
+    #[serde(default)]
+    #[sqlx(default)]
+    pub impact_computed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// A subscription caught in an incident; `via` is how it was traced to the
+/// router: `router` (subscription router), `pppoe` (PPPoE account on the
+/// router) or `topology` (service path through the router's node).
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MikrotikIncidentImpact {
+    pub incident_id: String,
+    pub subscription_id: String,
+    pub customer_id: String,
+    pub customer_name: String,
+    pub location_id: String,
+    pub location_label: Option<String>,
+    pub area: String,
+    pub via: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MikrotikIncidentArea {
+    pub area: String,
+    pub subscriptions: i32,
+    pub customers: i32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct MikrotikLogEntry {
     pub id: String,
@@ -425,6 +474,7 @@ impl MikrotikAlert {
             id: Uuid::new_v4().to_string(),
             tenant_id,
             router_id,
+            source: "routeros".to_string(),
             alert_type,
             severity,
             status: "open".to_string(),
@@ -470,6 +520,7 @@ impl MikrotikIncident {
             id: Uuid::new_v4().to_string(),
             tenant_id,
             router_id,
+            source: "routeros".to_string(),
             interface_name,
             incident_type,
             dedup_key,
@@ -488,6 +539,10 @@ impl MikrotikIncident {
             notes: None,
             is_auto_escalated: false,
             escalated_at: None,
+            affected_subscriptions: None,
+            affected_customers: None,
+            affected_areas: None,
+            impact_computed_at: None,
             created_at: now,
             updated_at: now,
         }
@@ -2,43 +2,87 @@
 
 pub mod announcements;
 pub mod audit_log;
+pub mod background_job;
+pub mod cgnat;
 pub mod customer;
+pub mod email_dkim;
 pub mod email_outbox;
+pub mod email_suppression;
+pub mod email_template;
+pub mod feature_flag;
+pub mod field_sync;
 pub mod file;
+pub mod integration;
+pub mod inventory;
 pub mod invoice;
+pub mod ipam;
 pub mod isp_packages;
+pub mod legacy_import;
 pub mod mikrotik;
+pub mod monitored_device;
 pub mod network_mapping;
 pub mod notification;
+pub mod notification_routing;
+pub mod notification_template;
+pub mod onboarding;
 pub mod plan;
 pub mod pppoe;
+pub mod report_schedule;
 pub mod role;
 pub mod settings;
+pub mod status_page;
 pub mod support;
+pub mod system_alert;
+pub mod telegram;
 pub mod tenant;
+pub mod trash;
 pub mod trusted_device;
+pub mod uplink_capacity;
 pub mod user;
 pub mod user_address;
+pub mod whatsapp;
 
 pub use announcements::*;
 pub use audit_log::*;
+pub use background_job::*;
+pub use cgnat::*;
 pub use customer::*;
+pub use email_dkim::*;
 pub use email_outbox::*;
+pub use email_suppression::*;
+pub use email_template::*;
+pub use feature_flag::*;
+pub use field_sync::*;
 pub use file::*;
+pub use integration::*;
+pub use inventory::*;
 pub use invoice::*;
+pub use ipam::*;
 pub use isp_packages::*;
+pub use legacy_import::*;
 pub use mikrotik::*;
+pub use monitored_device::*;
 pub use network_mapping::*;
 pub use notification::*;
+pub use notification_routing::*;
+pub use notification_template::*;
+pub use onboarding::*;
 pub use plan::*;
 pub use pppoe::*;
+pub use report_schedule::*;
 pub use role::*;
 pub use settings::*;
+pub use status_page::*;
 pub use support::*;
+pub use system_alert::*;
+pub use telegram::*;
 pub use tenant::*;
+pub use trash::*;
 pub use trusted_device::*;
+pub use uplink_capacity::*;
 pub use user::*;
 pub use user_address::*;
+pub use whatsapp::*;
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct PaginatedResponse<T> {
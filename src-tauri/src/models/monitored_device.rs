@@ -0,0 +1,143 @@
+//! Network devices monitored next to the MikroTik routers: OLTs, switches and
+//! other gear polled over SNMP, or a MikroTik router itself.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MonitoredDevice {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub host: String,
+    /// `olt`, `switch`, `router` or `other`.
+    pub device_type: String,
+    /// `snmp`, or `routeros` for a MikroTik router polled over its API.
+    pub protocol: String,
+    /// The router a `routeros` device is.
+    pub router_id: Option<String>,
+    /// `v2c` or `v3`.
+    pub snmp_version: Option<String>,
+    pub snmp_port: i32,
+    #[serde(skip_serializing)]
+    pub snmp_community: String,
+    pub snmp_username: Option<String>,
+    /// `md5` or `sha1`.
+    pub snmp_auth_protocol: Option<String>,
+    #[serde(skip_serializing)]
+    pub snmp_auth_password: String,
+    /// `des` or `aes128`.
+    pub snmp_priv_protocol: Option<String>,
+    #[serde(skip_serializing)]
+    pub snmp_priv_password: String,
+    /// Vendor OID answering the CPU load in percent.
+    pub cpu_oid: Option<String>,
+    pub enabled: bool,
+    pub maintenance_until: Option<DateTime<Utc>>,
+    pub is_online: bool,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub latency_ms: Option<i32>,
+    pub last_error: Option<String>,
+    /// sysName, or the identity of a `routeros` device.
+    pub sys_name: Option<String>,
+    /// sysDescr, or the RouterOS version of a `routeros` device.
+    pub sys_descr: Option<String>,
+    pub uptime_seconds: Option<i64>,
+    pub cpu_load: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MonitoredDevice {
+    pub fn new(tenant_id: String, name: String, host: String, protocol: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            tenant_id,
+            name,
+            host,
+            device_type: "other".to_string(),
+            protocol,
+            router_id: None,
+            snmp_version: None,
+            snmp_port: 161,
+            snmp_community: String::new(),
+            snmp_username: None,
+            snmp_auth_protocol: None,
+            snmp_auth_password: String::new(),
+            snmp_priv_protocol: None,
+            snmp_priv_password: String::new(),
+            cpu_oid: None,
+            enabled: true,
+            maintenance_until: None,
+            is_online: false,
+            last_seen_at: None,
+            latency_ms: None,
+            last_error: None,
+            sys_name: None,
+            sys_descr: None,
+            uptime_seconds: None,
+            cpu_load: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// The SNMP settings, `enabled` and `maintenance_until` only apply to `snmp`
+/// devices; a `routeros` device follows its router.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateMonitoredDeviceRequest {
+    pub name: String,
+    /// Required for `snmp`; a `routeros` device takes its router's.
+    pub host: Option<String>,
+    pub device_type: Option<String>,
+    pub protocol: String,
+    pub router_id: Option<String>,
+    pub snmp_version: Option<String>,
+    pub snmp_port: Option<i32>,
+    pub snmp_community: Option<String>,
+    pub snmp_username: Option<String>,
+    pub snmp_auth_protocol: Option<String>,
+    pub snmp_auth_password: Option<String>,
+    pub snmp_priv_protocol: Option<String>,
+    pub snmp_priv_password: Option<String>,
+    pub cpu_oid: Option<String>,
+    pub enabled: Option<bool>,
+    pub maintenance_until: Option<DateTime<Utc>>,
+}
+
+/// Omitted fields keep their value; an omitted or empty secret keeps the
+/// stored one. The protocol of a device cannot change, and as on create only
+/// the name and type of a `routeros` device are its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateMonitoredDeviceRequest {
+    pub name: Option<String>,
+    pub host: Option<String>,
+    pub device_type: Option<String>,
+    pub snmp_version: Option<String>,
+    pub snmp_port: Option<i32>,
+    pub snmp_community: Option<String>,
+    pub snmp_username: Option<String>,
+    pub snmp_auth_protocol: Option<String>,
+    pub snmp_auth_password: Option<String>,
+    pub snmp_priv_protocol: Option<String>,
+    pub snmp_priv_password: Option<String>,
+    /// An empty string clears it.
+    pub cpu_oid: Option<String>,
+    pub enabled: Option<bool>,
+    /// Sent on every update; null ends the maintenance.
+    pub maintenance_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitoredDeviceTestResult {
+    pub ok: bool,
+    pub sys_name: Option<String>,
+    pub sys_descr: Option<String>,
+    pub latency_ms: Option<i32>,
+    pub error: Option<String>,
+}
@@ -342,3 +342,59 @@ pub struct NetworkImpactResponse {
     pub link_ids: Vec<String>,
     pub customers: Vec<NetworkImpactCustomer>,
 }
+
+/// An ODC (street cabinet) or ODP (distribution closure).
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FiberPoint {
+    pub id: String,
+    pub tenant_id: String,
+    pub kind: String, // odc | odp
+    pub code: String,
+    pub name: String,
+    pub status: String,
+    pub lat: f64,
+    pub lng: f64,
+    /// ODC an ODP is fed from.
+    pub parent_id: Option<String>,
+    pub router_id: Option<String>,
+    pub port_capacity: Option<i32>,
+    /// Customer premises linked to the point in the topology.
+    pub ports_used: i64,
+    /// The point's system-managed topology node.
+    pub node_id: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateFiberPointRequest {
+    pub kind: String,
+    pub code: String,
+    pub name: String,
+    pub status: Option<String>,
+    pub lat: f64,
+    pub lng: f64,
+    pub parent_id: Option<String>,
+    pub router_id: Option<String>,
+    pub port_capacity: Option<i32>,
+    pub notes: Option<String>,
+}
+
+/// The kind is fixed after creation; `null` clears the optional fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateFiberPointRequest {
+    pub code: Option<String>,
+    pub name: Option<String>,
+    pub status: Option<String>,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    #[serde(default, deserialize_with = "crate::models::announcements::present")]
+    pub parent_id: Option<Option<String>>,
+    #[serde(default, deserialize_with = "crate::models::announcements::present")]
+    pub router_id: Option<Option<String>>,
+    #[serde(default, deserialize_with = "crate::models::announcements::present")]
+    pub port_capacity: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "crate::models::announcements::present")]
+    pub notes: Option<Option<String>>,
+}
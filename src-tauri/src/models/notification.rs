@@ -16,6 +16,8 @@ pub struct Notification {
     pub action_url: Option<String>, // URL to navigate when clicked
     pub is_read: bool,
     pub created_at: DateTime<Utc>,
+    /// Set when the user archives it; archived rows are hidden by default.
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl Notification {
@@ -39,6 +41,7 @@ impl Notification {
             action_url,
             is_read: false,
             created_at: Utc::now(),
+            archived_at: None,
         }
     }
 }
@@ -77,6 +80,13 @@ pub struct PushSubscription {
     pub p256dh: String,
     pub auth: String,
     pub created_at: DateTime<Utc>,
+    /// `PushSubscription.expirationTime` reported by the browser, if any.
+    pub expiration_time: Option<DateTime<Utc>>,
+    /// Application server key the browser subscribed with.
+    pub vapid_public_key: Option<String>,
+    pub failure_count: i32,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 impl PushSubscription {
@@ -89,12 +99,122 @@ impl PushSubscription {
             p256dh,
             auth,
             created_at: Utc::now(),
+            expiration_time: None,
+            vapid_public_key: None,
+            failure_count: 0,
+            last_success_at: None,
+            updated_at: None,
         }
     }
 }
 
+/// Stored quiet-hours window for a user.
+#[derive(Debug, Clone, FromRow)]
+pub struct NotificationQuietHoursRow {
+    pub user_id: String,
+    pub is_enabled: bool,
+    pub start_time: String,
+    pub end_time: String,
+    pub timezone: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A user's quiet hours as shown in settings.
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationQuietHours {
+    pub enabled: bool,
+    pub start: String, // HH:MM
+    pub end: String,   // HH:MM
+    /// Explicit IANA zone; `None` follows the tenant's `app_timezone`.
+    pub timezone: Option<String>,
+    /// Zone the window is evaluated in.
+    pub effective_timezone: String,
+    /// When quiet hours are in effect now, the moment they end.
+    pub resumes_at: Option<DateTime<Utc>>,
+}
+
+/// Delivery status of one notification on one channel. Title and category are
+/// copied from the notification so the record outlives it.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct NotificationDelivery {
+    pub id: String,
+    pub notification_id: String,
+    pub tenant_id: Option<String>,
+    pub user_id: String,
+    pub user_email: Option<String>,
+    pub category: String,
+    pub notification_type: String,
+    pub title: String,
+    pub channel: String, // in_app|email|push
+    pub status: String,  // queued|sent|delivered|failed|read
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct NotificationDeliveryStat {
+    pub channel: String,
+    pub status: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationDeliveryReport {
+    /// Counts per channel and status across the whole filtered range.
+    pub summary: Vec<NotificationDeliveryStat>,
+    pub data: Vec<NotificationDelivery>,
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+}
+
 // Request DTOs
 
+/// Server-side filters for the notification center. Unset fields don't filter;
+/// archived notifications are excluded unless `archived` is set.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NotificationFilter {
+    pub category: Option<String>,
+    pub notification_type: Option<String>,
+    pub is_read: Option<bool>,
+    /// `true` lists only archived notifications.
+    pub archived: Option<bool>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Case-insensitive match on title or message.
+    pub search: Option<String>,
+}
+
+/// Filters for the delivery report. Unset fields don't filter.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NotificationDeliveryFilter {
+    pub channel: Option<String>,
+    pub status: Option<String>,
+    pub category: Option<String>,
+    pub user_id: Option<String>,
+    pub notification_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateQuietHoursRequest {
+    pub enabled: bool,
+    pub start: String,
+    pub end: String,
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArchiveNotificationsRequest {
+    pub ids: Vec<String>,
+    /// `false` restores them to the inbox. Defaults to `true`.
+    pub archived: Option<bool>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct UpdatePreferenceRequest {
@@ -109,6 +229,14 @@ pub struct CreatePushSubscriptionRequest {
     pub endpoint: String,
     pub p256dh: String,
     pub auth: String,
+    /// Milliseconds since the epoch, as in `PushSubscription.expirationTime`.
+    #[serde(default)]
+    pub expiration_time: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VapidPublicKeyResponse {
+    pub public_key: String,
 }
 
 #[derive(Debug, Deserialize)]
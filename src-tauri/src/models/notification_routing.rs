@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Stored form of a routing rule; `channels` and `recipients` are comma-separated.
+#[derive(Debug, Clone, FromRow)]
+pub struct NotificationRoutingRuleRow {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub priority: i32,
+    pub is_enabled: bool,
+    pub category: Option<String>,
+    pub min_severity: String,
+    pub time_window: String,
+    pub channels: String,
+    pub recipients: String,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Sends matching tenant alerts to `channels` and `recipients` instead of the
+/// producer's defaults. Rules are tried by ascending `priority`; the first match wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationRoutingRule {
+    pub id: String,
+    pub name: String,
+    pub priority: i32,
+    pub is_enabled: bool,
+    /// `None` matches every category.
+    pub category: Option<String>,
+    pub min_severity: String,  // info | warning | critical
+    pub time_window: String,   // any | business_hours | after_hours
+    pub channels: Vec<String>, // in_app | email | telegram
+    /// `default` (the alert's usual audience), `user:<id>` or `role:<id>`.
+    pub recipients: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<NotificationRoutingRuleRow> for NotificationRoutingRule {
+    fn from(row: NotificationRoutingRuleRow) -> Self {
+        let split = |s: &str| -> Vec<String> {
+            s.split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        Self {
+            channels: split(&row.channels),
+            recipients: split(&row.recipients),
+            id: row.id,
+            name: row.name,
+            priority: row.priority,
+            is_enabled: row.is_enabled,
+            category: row.category,
+            min_severity: row.min_severity,
+            time_window: row.time_window,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertNotificationRoutingRuleRequest {
+    pub name: String,
+    pub priority: Option<i32>,
+    pub is_enabled: Option<bool>,
+    pub category: Option<String>,
+    pub min_severity: Option<String>,
+    pub time_window: Option<String>,
+    pub channels: Vec<String>,
+    pub recipients: Option<Vec<String>>,
+}
+
+/// Which rule an alert would hit, for checking a rule set before relying on it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvaluateNotificationRoutingRequest {
+    pub category: String,
+    pub severity: String,
+    /// Defaults to now.
+    pub at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationRoutingEvaluation {
+    pub rule: Option<NotificationRoutingRule>,
+    pub is_business_hours: bool,
+    /// Channels used; the producer's defaults when no rule matches.
+    pub channels: Vec<String>,
+}
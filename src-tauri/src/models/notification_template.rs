@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A tenant's override of a built-in notification text.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NotificationTemplateOverride {
+    pub id: String,
+    pub tenant_id: String,
+    pub code: String,
+    pub title: String,
+    pub body: String,
+    pub updated_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The text a notification is sent with: the tenant's override when present,
+/// otherwise the built-in default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationTemplate {
+    pub code: String,
+    pub category: String,
+    pub description: String,
+    pub title: String,
+    pub body: String,
+    pub default_title: String,
+    pub default_body: String,
+    /// Variables the event fills in, usable as `{{name}}` in title and body.
+    pub variables: Vec<String>,
+    pub is_customized: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateNotificationTemplateRequest {
+    pub title: String,
+    pub body: String,
+}
+
+/// Unsaved text to preview; omitted fields use the current template.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PreviewNotificationTemplateRequest {
+    pub title: Option<String>,
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderedNotification {
+    pub title: String,
+    pub body: String,
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One item of the onboarding checklist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnboardingStep {
+    /// `smtp`, `router`, `packages`, `payment_gateway` or `customers`.
+    pub step: String,
+    /// `done`, `skipped`, or `pending` while still to do.
+    pub status: String,
+    /// When the step was found done or skipped.
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnboardingProgress {
+    /// In the order they are meant to be done.
+    pub steps: Vec<OnboardingStep>,
+    /// Steps done or skipped.
+    pub completed: u32,
+    pub total: u32,
+    pub percent: u32,
+    /// First pending step; `None` once the checklist is through.
+    pub next_step: Option<String>,
+}
@@ -19,6 +19,10 @@ pub struct Plan {
     pub is_active: bool,
     pub is_default: bool,
     pub sort_order: i32,
+    /// Length of the free trial a tenant gets on this plan; 0 means no trial.
+    pub trial_days: i32,
+    /// What happens when the trial runs out: "downgrade" or "lock".
+    pub trial_expiry_action: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -71,7 +75,7 @@ pub struct TenantSubscription {
     pub id: String,
     pub tenant_id: String,
     pub plan_id: String,
-    pub status: String, // "active", "cancelled", "past_due", "trial"
+    pub status: String, // "active", "cancelled", "past_due", "trial", "expired"
     pub trial_ends_at: Option<DateTime<Utc>>,
     pub current_period_start: Option<DateTime<Utc>>,
     pub current_period_end: Option<DateTime<Utc>>,
@@ -101,6 +105,8 @@ pub struct CreatePlanRequest {
     pub is_active: Option<bool>,
     pub is_default: Option<bool>,
     pub sort_order: Option<i32>,
+    pub trial_days: Option<i32>,
+    pub trial_expiry_action: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -114,6 +120,8 @@ pub struct UpdatePlanRequest {
     pub is_active: Option<bool>,
     pub is_default: Option<bool>,
     pub sort_order: Option<i32>,
+    pub trial_days: Option<i32>,
+    pub trial_expiry_action: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -171,9 +179,30 @@ pub struct TenantSubscriptionDetails {
     pub plan_name: String,
     pub plan_slug: String,
     pub status: String,
+    pub trial_ends_at: Option<DateTime<Utc>>,
     pub current_period_end: Option<DateTime<Utc>>,
     pub storage_usage: i64,
     pub storage_limit: Option<i64>,
     pub member_usage: i64,
     pub member_limit: Option<i64>,
 }
+
+/// One metered quota: current usage against the plan limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageMeter {
+    pub metric: String, // "users", "customers", "routers", "storage", "emails_month"
+    pub unit: String,   // "count" or "bytes"
+    pub used: i64,
+    pub limit: Option<i64>, // None = unlimited
+    pub status: String,     // "ok", "warning", "exceeded"
+}
+
+/// Usage vs limits for every metered quota of a tenant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantUsage {
+    pub plan_name: String,
+    pub plan_slug: String,
+    pub enforcement: String, // "hard" or "soft"
+    pub period: String,      // month covered by monthly meters, "YYYY-MM"
+    pub meters: Vec<UsageMeter>,
+}
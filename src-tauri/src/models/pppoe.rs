@@ -57,6 +57,9 @@ pub struct PppoeAccount {
     pub router_secret_id: Option<String>,
     pub last_sync_at: Option<DateTime<Utc>>,
     pub last_error: Option<String>,
+    /// Set while the account runs on the package's fair-usage speeds.
+    #[sqlx(default)]
+    pub fup_throttled_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -98,6 +101,7 @@ impl PppoeAccount {
             router_secret_id: None,
             last_sync_at: None,
             last_error: None,
+            fup_throttled_at: None,
             created_at: now,
             updated_at: now,
         }
@@ -154,6 +158,7 @@ pub struct PppoeAccountPublic {
     pub router_secret_id: Option<String>,
     pub last_sync_at: Option<DateTime<Utc>>,
     pub last_error: Option<String>,
+    pub fup_throttled_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -178,6 +183,7 @@ impl From<PppoeAccount> for PppoeAccountPublic {
             router_secret_id: a.router_secret_id,
             last_sync_at: a.last_sync_at,
             last_error: a.last_error,
+            fup_throttled_at: a.fup_throttled_at,
             created_at: a.created_at,
             updated_at: a.updated_at,
         }
@@ -229,3 +235,36 @@ pub struct PppoeImportResult {
     pub used_customer_id: String,
     pub used_location_id: String,
 }
+
+/// Traffic of a customer's PPPoE accounts in one hour or day, seen from the
+/// subscriber.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UsagePoint {
+    pub ts: DateTime<Utc>,
+    pub upload_bytes: i64,
+    pub download_bytes: i64,
+}
+
+/// Where a PPPoE account stands in its current usage period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PppoeAccountUsage {
+    pub account_id: String,
+    pub username: String,
+    pub package_name: Option<String>,
+    pub period_start: DateTime<Utc>,
+    pub upload_bytes: i64,
+    pub download_bytes: i64,
+    /// The package's fair-usage quota, if it has one.
+    pub quota_gb: Option<i32>,
+    pub throttled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomerUsage {
+    pub customer_id: String,
+    pub days: u32,
+    /// `hour` or `day`: what each point covers.
+    pub bucket: String,
+    pub points: Vec<UsagePoint>,
+    pub accounts: Vec<PppoeAccountUsage>,
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A report emailed to a list of addresses on a cadence.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ReportSchedule {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub report: String,  // revenue | aging | sla | incidents
+    pub format: String,  // csv | pdf
+    pub cadence: String, // daily | weekly | monthly
+    /// Local hour (tenant `app_timezone`) the report goes out.
+    pub send_hour: i32,
+    #[sqlx(json)]
+    pub recipients: Vec<String>,
+    pub enabled: bool,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_status: Option<String>, // sent | failed
+    pub last_error: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertReportScheduleRequest {
+    pub name: String,
+    pub report: String,
+    pub format: String,
+    pub cadence: String,
+    pub send_hour: Option<i32>,
+    pub recipients: Vec<String>,
+    pub enabled: Option<bool>,
+}
+
+/// What a manual send produced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSendResult {
+    pub filename: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub rows: usize,
+    pub recipients: usize,
+}
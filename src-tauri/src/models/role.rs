@@ -120,4 +120,6 @@ pub struct UpdateRoleDto {
     pub description: Option<String>,
     pub level: Option<i32>,
     pub permissions: Option<Vec<String>>,
+    /// If set, the update is refused unless the role is still at this `updated_at`.
+    pub expected_updated_at: Option<DateTime<Utc>>,
 }
@@ -37,10 +37,24 @@ impl Setting {
     }
 }
 
+/// Answer to a desktop client asking whether it may use the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientCompatibility {
+    pub server_version: String,
+    pub client_version: Option<String>,
+    /// `None` when any client version is accepted.
+    pub minimum_version: Option<String>,
+    pub supported: bool,
+    /// Release channel clients take updates from: stable | beta.
+    pub update_channel: String,
+}
+
 /// DTO for creating/updating settings
 #[derive(Debug, Deserialize)]
 pub struct UpsertSettingDto {
     pub key: String,
     pub value: String,
     pub description: Option<String>,
+    /// When updating, the `updated_at` the editor loaded; stale values yield a conflict.
+    pub expected_updated_at: Option<DateTime<Utc>>,
 }
@@ -0,0 +1,195 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Stored form of a status component; `router_ids` is comma-separated.
+#[derive(Debug, Clone, FromRow)]
+pub struct StatusComponentRow {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: String,
+    pub router_ids: String,
+    pub status_override: Option<String>,
+    pub position: i32,
+    pub is_visible: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Something the public status page reports on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusComponent {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: String, // api | billing | network | custom
+    /// Routers of a network component; empty means all of the tenant's routers.
+    pub router_ids: Vec<String>,
+    /// Set by an admin in place of the status derived from the routers.
+    pub status_override: Option<String>,
+    pub position: i32,
+    pub is_visible: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<StatusComponentRow> for StatusComponent {
+    fn from(row: StatusComponentRow) -> Self {
+        Self {
+            router_ids: split_ids(&row.router_ids),
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            kind: row.kind,
+            status_override: row.status_override,
+            position: row.position,
+            is_visible: row.is_visible,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertStatusComponentRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: String,
+    pub router_ids: Option<Vec<String>>,
+    pub status_override: Option<String>,
+    pub position: Option<i32>,
+    pub is_visible: Option<bool>,
+}
+
+/// Stored form of an incident or maintenance post; `component_ids` is comma-separated.
+#[derive(Debug, Clone, FromRow)]
+pub struct StatusPostRow {
+    pub id: String,
+    pub tenant_id: String,
+    pub kind: String,
+    pub title: String,
+    pub impact: String,
+    pub status: String,
+    pub component_ids: String,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub scheduled_end: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StatusPostUpdate {
+    pub id: String,
+    pub post_id: String,
+    pub status: String,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An incident or a scheduled maintenance, with its updates oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusPost {
+    pub id: String,
+    pub kind: String, // incident | maintenance
+    pub title: String,
+    pub impact: String, // none | minor | major | critical
+    /// Incidents: investigating | identified | monitoring | resolved.
+    /// Maintenance: scheduled | in_progress | completed.
+    pub status: String,
+    pub component_ids: Vec<String>,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub scheduled_end: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub updates: Vec<StatusPostUpdate>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl StatusPost {
+    pub fn from_row(row: StatusPostRow, updates: Vec<StatusPostUpdate>) -> Self {
+        Self {
+            component_ids: split_ids(&row.component_ids),
+            id: row.id,
+            kind: row.kind,
+            title: row.title,
+            impact: row.impact,
+            status: row.status,
+            scheduled_start: row.scheduled_start,
+            scheduled_end: row.scheduled_end,
+            resolved_at: row.resolved_at,
+            updates,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateStatusPostRequest {
+    pub kind: String,
+    pub title: String,
+    pub impact: Option<String>,
+    pub status: Option<String>,
+    /// First entry of the timeline.
+    pub message: String,
+    pub component_ids: Option<Vec<String>>,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub scheduled_end: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateStatusPostRequest {
+    pub title: Option<String>,
+    pub impact: Option<String>,
+    pub component_ids: Option<Vec<String>>,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub scheduled_end: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AddStatusPostUpdateRequest {
+    pub status: String,
+    pub message: String,
+}
+
+/// A component as the public sees it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicStatusComponent {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: String,
+    /// operational | maintenance | degraded | partial_outage | major_outage
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicStatusPage {
+    pub tenant_name: String,
+    pub slug: String,
+    /// Worst status among the components.
+    pub status: String,
+    pub components: Vec<PublicStatusComponent>,
+    /// Incidents not resolved yet.
+    pub incidents: Vec<StatusPost>,
+    /// Maintenance in progress or still to come.
+    pub maintenance: Vec<StatusPost>,
+    pub generated_at: DateTime<Utc>,
+}
+
+fn split_ids(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
@@ -11,6 +11,8 @@ pub struct SupportTicket {
     pub status: String,
     pub priority: String,
     pub assigned_to: Option<String>,
+    pub category: Option<String>,
+    pub queue_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub closed_at: Option<DateTime<Utc>>,
@@ -26,6 +28,8 @@ pub struct SupportTicketListItem {
     pub status: String,
     pub priority: String,
     pub assigned_to: Option<String>,
+    pub category: Option<String>,
+    pub queue_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub closed_at: Option<DateTime<Utc>>,
@@ -67,6 +71,8 @@ pub struct CreateSupportTicketDto {
     pub subject: String,
     pub message: String,
     pub priority: Option<String>, // low|normal|high|urgent
+    /// Routes the ticket to the queue that lists this category.
+    pub category: Option<String>,
     #[serde(alias = "attachment_ids")]
     pub attachment_ids: Option<Vec<String>>,
 }
@@ -82,12 +88,182 @@ pub struct ReplySupportTicketDto {
     pub attachment_ids: Option<Vec<String>>,
 }
 
+/// Response to the mail provider for one inbound email.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboundEmailResult {
+    pub status: String, // created | replied | ignored
+    pub ticket_id: Option<String>,
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 pub struct UpdateSupportTicketDto {
     pub status: Option<String>,   // open|pending|closed
     pub priority: Option<String>, // low|normal|high|urgent
+    /// Empty string unassigns.
     #[serde(alias = "assigned_to")]
     pub assigned_to: Option<String>,
+    /// Empty string removes the ticket from its queue.
+    #[serde(alias = "queue_id")]
+    pub queue_id: Option<String>,
+    pub category: Option<String>,
+}
+
+/// A team of agents that tickets are routed to.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SupportQueue {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub categories: Vec<String>,
+    pub is_default: bool,
+    pub auto_assign: bool,
+    pub online_only: bool,
+    pub last_assigned_to: Option<String>,
+    pub member_ids: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct UpsertSupportQueueDto {
+    pub name: String,
+    pub description: Option<String>,
+    pub categories: Option<Vec<String>>,
+    #[serde(alias = "is_default")]
+    pub is_default: Option<bool>,
+    #[serde(alias = "auto_assign")]
+    pub auto_assign: Option<bool>,
+    #[serde(alias = "online_only")]
+    pub online_only: Option<bool>,
+    #[serde(alias = "member_ids")]
+    pub member_ids: Option<Vec<String>>,
+}
+
+/// A canned reply, optionally bundled with ticket updates (a macro).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SupportMacro {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub category: Option<String>,
+    pub body: String,
+    pub is_internal: bool,
+    pub set_status: Option<String>,
+    pub set_priority: Option<String>,
+    /// User id, or `"me"` for whoever applies the macro.
+    pub assign_to: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct UpsertSupportMacroDto {
+    pub name: String,
+    pub category: Option<String>,
+    pub body: Option<String>,
+    #[serde(alias = "is_internal")]
+    pub is_internal: Option<bool>,
+    #[serde(alias = "set_status")]
+    pub set_status: Option<String>,
+    #[serde(alias = "set_priority")]
+    pub set_priority: Option<String>,
+    #[serde(alias = "assign_to")]
+    pub assign_to: Option<String>,
+}
+
+/// Macro body rendered for one ticket, for pasting into the reply box.
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedSupportMacro {
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApplySupportMacroResult {
+    pub ticket: SupportTicket,
+    /// The reply the macro posted, if it has a body.
+    pub message: Option<SupportTicketMessage>,
+}
+
+/// A support ticket's link to an incident, work order or invoice.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SupportTicketLink {
+    pub id: String,
+    pub tenant_id: String,
+    pub ticket_id: String,
+    pub entity_type: String, // incident | work_order | invoice
+    pub entity_id: String,
+    pub note: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Incident title, customer name or invoice number; `None` once the record is gone.
+    pub entity_label: Option<String>,
+    pub entity_status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct CreateSupportTicketLinkDto {
+    #[serde(alias = "entity_type")]
+    pub entity_type: String,
+    #[serde(alias = "entity_id")]
+    pub entity_id: String,
+    pub note: Option<String>,
+}
+
+/// Back-reference: a ticket linked to some record.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LinkedSupportTicket {
+    pub link_id: String,
+    pub ticket_id: String,
+    pub subject: String,
+    pub status: String,
+    pub priority: String,
+    pub assigned_to: Option<String>,
+    pub note: Option<String>,
+    pub linked_at: DateTime<Utc>,
+}
+
+/// Bumps priority and/or reassigns tickets that stay idle past a threshold.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SupportEscalationRule {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub is_active: bool,
+    pub idle_minutes: i32,
+    pub statuses: Vec<String>,
+    pub queue_id: Option<String>,
+    pub bump_priority: bool,
+    pub reassign_to: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct UpsertSupportEscalationRuleDto {
+    pub name: String,
+    #[serde(alias = "is_active")]
+    pub is_active: Option<bool>,
+    #[serde(alias = "idle_minutes")]
+    pub idle_minutes: i32,
+    /// Defaults to `["open"]`.
+    pub statuses: Option<Vec<String>>,
+    #[serde(alias = "queue_id")]
+    pub queue_id: Option<String>,
+    #[serde(alias = "bump_priority")]
+    pub bump_priority: Option<bool>,
+    #[serde(alias = "reassign_to")]
+    pub reassign_to: Option<String>,
 }
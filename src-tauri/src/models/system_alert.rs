@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Deployment-wide alert rule (`system_alert_rules`), managed by super admins.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SystemAlertRule {
+    pub id: String,
+    pub name: String,
+    pub metric: String, // see `AlertMetric`
+    /// Fires when the metric goes above this value.
+    pub threshold: f64,
+    /// Where it is delivered: `email`, `telegram` and/or `webhook`.
+    #[sqlx(json)]
+    pub channels: Vec<String>,
+    pub cooldown_minutes: i32,
+    pub enabled: bool,
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    /// Metric value the last time the rule fired.
+    pub last_value: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UpsertSystemAlertRuleRequest {
+    pub name: String,
+    pub metric: String,
+    pub threshold: f64,
+    pub channels: Vec<String>,
+    pub cooldown_minutes: i32,
+    pub enabled: bool,
+}
+
+/// Outcome of delivering an alert on one channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertDeliveryResult {
+    pub channel: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
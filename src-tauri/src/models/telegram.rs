@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A tenant member's Telegram chat. `chat_id` is set once the member sends the
+/// bot `/start <link_code>`; until then only the pending code is stored.
+#[derive(Debug, Clone, FromRow)]
+pub struct TelegramLink {
+    pub id: String,
+    pub tenant_id: String,
+    pub user_id: String,
+    pub chat_id: Option<String>,
+    pub telegram_username: Option<String>,
+    pub link_code: Option<String>,
+    pub link_code_expires_at: Option<DateTime<Utc>>,
+    pub alert_incidents: bool,
+    pub alert_payments: bool,
+    pub linked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What the settings page shows for the current user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelegramLinkStatus {
+    /// The bot is enabled and has a token.
+    pub bot_enabled: bool,
+    pub bot_username: Option<String>,
+    pub linked: bool,
+    pub telegram_username: Option<String>,
+    pub linked_at: Option<DateTime<Utc>>,
+    pub alert_incidents: bool,
+    pub alert_payments: bool,
+    /// Pending one-time code, sent to the bot as `/start <code>`.
+    pub link_code: Option<String>,
+    pub link_code_expires_at: Option<DateTime<Utc>>,
+    /// `https://t.me/<bot>?start=<code>` when the bot username is configured.
+    pub link_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateTelegramLinkRequest {
+    pub alert_incidents: Option<bool>,
+    pub alert_payments: Option<bool>,
+}
@@ -70,3 +70,66 @@ pub struct TeamMemberWithUser {
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
 }
+
+/// Tenant dashboard figures, computed together by `TenantAnalyticsService`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantAnalytics {
+    pub tenant_id: String,
+    /// Month the monthly figures cover, "YYYY-MM" (UTC).
+    pub period: String,
+    pub customers: CustomerAnalytics,
+    pub subscribers: SubscriberAnalytics,
+    pub incidents: IncidentAnalytics,
+    /// Customer package invoices per currency. Empty for users without
+    /// `billing:read`.
+    pub revenue: Vec<RevenueAnalytics>,
+    pub tickets: TicketAnalytics,
+    pub storage: StorageAnalytics,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomerAnalytics {
+    pub total: i64,
+    pub active: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriberAnalytics {
+    /// Subscriptions with status `active`.
+    pub active_subscriptions: i64,
+    /// Enabled PPPoE accounts present on a router that is online.
+    pub online: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentAnalytics {
+    /// Not yet resolved.
+    pub open: i64,
+    pub critical: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RevenueAnalytics {
+    pub currency_code: String,
+    /// Paid this month.
+    pub paid: f64,
+    pub paid_invoices: i64,
+    /// Issued and not paid yet, regardless of month.
+    pub outstanding: f64,
+    pub outstanding_invoices: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketAnalytics {
+    pub open: i64,
+    pub created_this_month: i64,
+    pub closed_this_month: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageAnalytics {
+    pub used_bytes: i64,
+    /// Plan quota; None means unlimited.
+    pub limit_bytes: Option<i64>,
+}
@@ -0,0 +1,14 @@
+//! Soft-deleted ("trashed") records
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A soft-deleted record as shown in a trash listing.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TrashItem {
+    pub id: String,
+    pub name: String,
+    /// Secondary label (email, host, ...), if any.
+    pub detail: Option<String>,
+    pub deleted_at: DateTime<Utc>,
+}
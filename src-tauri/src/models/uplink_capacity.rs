@@ -0,0 +1,69 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UplinkInterface {
+    pub id: String,
+    pub tenant_id: String,
+    pub router_id: String,
+    pub router_name: Option<String>,
+    pub interface_name: String,
+    pub label: Option<String>,
+    /// Bandwidth bought for the link, the same in both directions.
+    pub capacity_mbps: i32,
+    pub warning_pct: i32,
+    pub critical_pct: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertUplinkInterfaceRequest {
+    pub router_id: String,
+    pub interface_name: String,
+    pub label: Option<String>,
+    pub capacity_mbps: i32,
+    pub warning_pct: Option<i32>,
+    pub critical_pct: Option<i32>,
+}
+
+/// 95th percentile throughput of one UTC day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UplinkDailyPercentile {
+    pub day: NaiveDate,
+    pub samples: i64,
+    pub rx_p95_bps: Option<f64>,
+    pub tx_p95_bps: Option<f64>,
+    /// Busier direction against the capacity.
+    pub utilization_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UplinkCapacityRow {
+    #[serde(flatten)]
+    pub uplink: UplinkInterface,
+    pub samples: i64,
+    pub rx_p95_bps: Option<f64>,
+    pub tx_p95_bps: Option<f64>,
+    pub rx_peak_bps: Option<i64>,
+    pub tx_peak_bps: Option<i64>,
+    /// 95th percentile of the busier direction against the capacity.
+    pub utilization_pct: Option<f64>,
+    /// ok | warning | critical | no_data
+    pub level: String,
+    /// Trailing run of days whose own 95th percentile is over the warning threshold.
+    pub days_over_warning: u32,
+    /// Days until the daily trend reaches the capacity; unset when flat or falling.
+    pub days_to_capacity: Option<f64>,
+    pub daily: Vec<UplinkDailyPercentile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UplinkCapacityReport {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub days: i64,
+    pub uplinks: Vec<UplinkCapacityRow>,
+    pub generated_at: DateTime<Utc>,
+}
@@ -164,6 +164,13 @@ pub struct UpdateUserDto {
     pub is_active: Option<bool>,
 }
 
+/// A user's locale preference (`None` follows the `default_locale` setting).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserLocale {
+    pub locale: Option<String>,
+}
+
 /// DTO for user login
 #[derive(Debug, Deserialize, Validate)]
 #[serde(deny_unknown_fields)]
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A WhatsApp message template. For the Meta Cloud API `name` and `language` must
+/// match an approved template, and the `{{variable}}` placeholders in `body` map to
+/// its positional parameters in order of first appearance.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WhatsappTemplate {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub language: String,
+    pub body: String,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertWhatsappTemplateRequest {
+    pub name: String,
+    pub language: Option<String>,
+    pub body: String,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WhatsappMessage {
+    pub id: String,
+    pub tenant_id: String,
+    pub event: String,
+    pub template_name: String,
+    pub to_phone: String,
+    pub customer_id: Option<String>,
+    pub provider: String,                    // meta | gateway
+    pub provider_message_id: Option<String>, // wamid or gateway id
+    pub status: String,                      // pending | sent | delivered | read | failed
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Which template an event is sent with; `None` means the event is not sent on WhatsApp.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhatsappRoute {
+    pub event: String,
+    pub template_name: Option<String>,
+    /// Variables the event fills in, usable as `{{name}}` in the template body.
+    pub variables: Vec<String>,
+}
@@ -1,2 +1,3 @@
 pub mod access_rules;
 pub mod secret;
+pub mod trusted_proxy;
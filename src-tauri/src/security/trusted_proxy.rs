@@ -0,0 +1,321 @@
+//! Trusted proxy handling for client IP resolution.
+//!
+//! Forwarding headers (`Forwarded`, `X-Forwarded-For`, `X-Real-IP`) are only honored
+//! when the socket peer is a configured trusted proxy. The forwarded chain is walked
+//! right-to-left, skipping trusted hops, so a client can't spoof its address by
+//! prepending entries.
+
+use axum::http::HeaderMap;
+use once_cell::sync::Lazy;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::RwLock;
+
+/// Used when neither the `trusted_proxies` setting nor `TRUSTED_PROXIES` env is set:
+/// a reverse proxy on the same host.
+pub const DEFAULT_TRUSTED_PROXIES: &str = "127.0.0.1/32,::1/128";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpNet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNet {
+    /// Parse `a.b.c.d/nn`, `::1/128` or a bare address (treated as a host route).
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return None;
+        }
+        let (addr_part, prefix_part) = match raw.split_once('/') {
+            Some((a, p)) => (a, Some(p)),
+            None => (raw, None),
+        };
+        let addr: IpAddr = addr_part.trim().parse().ok()?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix_part {
+            Some(p) => p.trim().parse::<u8>().ok().filter(|p| *p <= max)?,
+            None => max,
+        };
+        Some(Self { addr, prefix })
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, normalize(*ip)) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = if self.prefix == 0 {
+                    0
+                } else {
+                    u32::MAX << (32 - self.prefix)
+                };
+                (u32::from(net) & mask) == (u32::from(ip) & mask)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = if self.prefix == 0 {
+                    0
+                } else {
+                    u128::MAX << (128 - self.prefix)
+                };
+                (u128::from(net) & mask) == (u128::from(ip) & mask)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// IPv4-mapped IPv6 addresses (dual-stack sockets) compare as IPv4.
+fn normalize(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6
+            .to_ipv4_mapped()
+            .map(IpAddr::V4)
+            .unwrap_or(IpAddr::V6(v6)),
+        v4 => v4,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    nets: Vec<IpNet>,
+}
+
+impl TrustedProxies {
+    /// Parse a comma/whitespace separated list of CIDR ranges. Invalid entries are skipped.
+    pub fn parse(raw: &str) -> Self {
+        let nets = raw
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter_map(IpNet::parse)
+            .collect();
+        Self { nets }
+    }
+
+    pub fn from_env() -> Self {
+        let raw = std::env::var("TRUSTED_PROXIES")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_TRUSTED_PROXIES.to_string());
+        Self::parse(&raw)
+    }
+
+    pub fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.nets.iter().any(|n| n.contains(ip))
+    }
+
+    pub fn len(&self) -> usize {
+        self.nets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nets.is_empty()
+    }
+
+    /// Resolve the originating client IP for a request received from `peer`.
+    pub fn resolve(&self, headers: &HeaderMap, peer: IpAddr) -> IpAddr {
+        if !self.is_trusted(&peer) {
+            return normalize(peer);
+        }
+
+        // RFC 7239 `Forwarded` takes precedence over the de-facto headers.
+        let mut chain = forwarded_chain(headers);
+        if chain.is_empty() {
+            chain = x_forwarded_for_chain(headers);
+        }
+
+        if !chain.is_empty() {
+            // Walk right-to-left: the right-most untrusted hop is the client.
+            // An obfuscated hop hides everything to its left, so the address
+            // of the last trusted proxy before it is the best we know.
+            let mut last_trusted = peer;
+            for hop in chain.iter().rev() {
+                match hop {
+                    None => return normalize(last_trusted),
+                    Some(ip) if !self.is_trusted(ip) => return normalize(*ip),
+                    Some(ip) => last_trusted = *ip,
+                }
+            }
+            // Every hop is a trusted proxy; the left-most entry is the best we know.
+            return normalize(last_trusted);
+        }
+
+        if let Some(ip) = headers
+            .get("X-Real-IP")
+            .and_then(|h| h.to_str().ok())
+            .and_then(parse_node)
+        {
+            return normalize(ip);
+        }
+
+        normalize(peer)
+    }
+}
+
+/// Parse a single node from `Forwarded: for=` or `X-Forwarded-For`,
+/// accepting quoted values, bracketed IPv6 and optional ports.
+fn parse_node(raw: &str) -> Option<IpAddr> {
+    let v = raw.trim().trim_matches('"');
+    if v.is_empty() {
+        return None;
+    }
+    if let Ok(ip) = v.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    if let Ok(sa) = v.parse::<SocketAddr>() {
+        return Some(sa.ip());
+    }
+    // "[2001:db8::1]" without port
+    if let Some(inner) = v.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        return inner.parse().ok();
+    }
+    // "1.2.3.4:5678"
+    if let Some((host, _port)) = v.rsplit_once(':') {
+        if !host.contains(':') {
+            return host.parse().ok();
+        }
+    }
+    None
+}
+
+/// The `for=` hops of every `Forwarded` header, left to right. Obfuscated
+/// identifiers ("unknown", "_hidden") and unparseable nodes are `None`.
+fn forwarded_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let mut out = Vec::new();
+    for value in headers.get_all("Forwarded") {
+        let Ok(s) = value.to_str() else { continue };
+        for element in s.split(',') {
+            for pair in element.split(';') {
+                let Some((k, v)) = pair.split_once('=') else {
+                    continue;
+                };
+                if k.trim().eq_ignore_ascii_case("for") {
+                    out.push(parse_node(v));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The hops of every `X-Forwarded-For` header, left to right, with the same
+/// `None` for entries that are not addresses. Empty entries are dropped.
+fn x_forwarded_for_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let mut out = Vec::new();
+    for value in headers.get_all("X-Forwarded-For") {
+        let Ok(s) = value.to_str() else { continue };
+        out.extend(
+            s.split(',')
+                .filter(|node| !node.trim().is_empty())
+                .map(parse_node),
+        );
+    }
+    out
+}
+
+static TRUSTED_PROXIES: Lazy<RwLock<TrustedProxies>> =
+    Lazy::new(|| RwLock::new(TrustedProxies::from_env()));
+
+/// Replace the active trusted proxy list (called when settings change).
+pub fn configure(proxies: TrustedProxies) {
+    if let Ok(mut lock) = TRUSTED_PROXIES.write() {
+        *lock = proxies;
+    }
+}
+
+/// Resolve the client IP using the active trusted proxy list.
+pub fn resolve_client_ip(headers: &HeaderMap, peer: Option<SocketAddr>) -> Option<IpAddr> {
+    let peer = peer?.ip();
+    let ip = match TRUSTED_PROXIES.read() {
+        Ok(lock) => lock.resolve(headers, peer),
+        Err(_) => normalize(peer),
+    };
+    Some(ip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        for (k, v) in pairs {
+            h.append(*k, HeaderValue::from_str(v).unwrap());
+        }
+        h
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn cidr_matching() {
+        let net = IpNet::parse("10.0.0.0/8").unwrap();
+        assert!(net.contains(&ip("10.1.2.3")));
+        assert!(!net.contains(&ip("11.0.0.1")));
+        assert!(net.contains(&ip("::ffff:10.0.0.1")));
+
+        let v6 = IpNet::parse("fd00::/8").unwrap();
+        assert!(v6.contains(&ip("fd12::1")));
+        assert!(!v6.contains(&ip("2001:db8::1")));
+
+        assert!(IpNet::parse("10.0.0.0/33").is_none());
+        assert_eq!(IpNet::parse("1.2.3.4").unwrap().prefix, 32);
+    }
+
+    #[test]
+    fn untrusted_peer_ignores_headers() {
+        let proxies = TrustedProxies::parse("127.0.0.1/32");
+        let h = headers(&[("X-Forwarded-For", "1.1.1.1")]);
+        assert_eq!(proxies.resolve(&h, ip("203.0.113.9")), ip("203.0.113.9"));
+    }
+
+    #[test]
+    fn spoofed_left_entries_are_skipped() {
+        let proxies = TrustedProxies::parse("127.0.0.1/32, 10.0.0.0/8");
+        // Client sent a fake XFF; the edge proxy appended the real address.
+        let h = headers(&[("X-Forwarded-For", "6.6.6.6, 198.51.100.7, 10.0.0.2")]);
+        assert_eq!(proxies.resolve(&h, ip("127.0.0.1")), ip("198.51.100.7"));
+    }
+
+    #[test]
+    fn forwarded_header_is_preferred() {
+        let proxies = TrustedProxies::parse("127.0.0.1");
+        let h = headers(&[
+            (
+                "Forwarded",
+                "for=unknown, for=\"[2001:db8::5]:4711\";proto=https",
+            ),
+            ("X-Forwarded-For", "9.9.9.9"),
+        ]);
+        assert_eq!(proxies.resolve(&h, ip("127.0.0.1")), ip("2001:db8::5"));
+    }
+
+    #[test]
+    fn obfuscated_hop_stops_the_walk() {
+        let proxies = TrustedProxies::parse("10.0.0.0/8,127.0.0.1");
+        // Nothing left of "unknown" can be checked, so the client-supplied
+        // 1.2.3.4 must not win; the trusted peer is the best we know.
+        let h = headers(&[("Forwarded", "for=1.2.3.4, for=unknown")]);
+        assert_eq!(proxies.resolve(&h, ip("127.0.0.1")), ip("127.0.0.1"));
+
+        let h = headers(&[("Forwarded", "for=1.2.3.4, for=_hidden, for=10.0.0.7")]);
+        assert_eq!(proxies.resolve(&h, ip("127.0.0.1")), ip("10.0.0.7"));
+
+        let h = headers(&[("X-Forwarded-For", "1.2.3.4, unknown")]);
+        assert_eq!(proxies.resolve(&h, ip("127.0.0.1")), ip("127.0.0.1"));
+    }
+
+    #[test]
+    fn all_trusted_falls_back_to_leftmost() {
+        let proxies = TrustedProxies::parse("10.0.0.0/8,127.0.0.1");
+        let h = headers(&[("X-Forwarded-For", "10.0.0.5:1234, 10.0.0.6")]);
+        assert_eq!(proxies.resolve(&h, ip("127.0.0.1")), ip("10.0.0.5"));
+    }
+
+    #[test]
+    fn real_ip_used_without_forwarded_chain() {
+        let proxies = TrustedProxies::parse("127.0.0.1");
+        let h = headers(&[("X-Real-IP", "192.0.2.44")]);
+        assert_eq!(proxies.resolve(&h, ip("127.0.0.1")), ip("192.0.2.44"));
+    }
+}
@@ -1,72 +1,345 @@
-//! Alert Service - Error and Performance Alerting via Email
+//! Alert Service - configurable system alert rules
 //!
-//! Monitors metrics and sends email alerts when thresholds are exceeded.
+//! Every check reads the metrics the rules in `system_alert_rules` watch and
+//! fires the enabled rules whose metric is above their threshold, on email,
+//! Telegram and/or a webhook. Request metrics are per instance, so each
+//! instance checks its own; a rule still fires at most once per cooldown
+//! across the deployment because firing claims it in the database first.
 
-use crate::error::AppResult;
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{AlertDeliveryResult, SystemAlertRule, UpsertSystemAlertRuleRequest};
 use crate::services::email_service::EmailService;
-use crate::services::metrics_service::RequestMetrics;
+use crate::services::event_outbox_service::hmac_sha256_hex;
+use crate::services::metrics_service::{MetricsService, RequestMetrics};
 use crate::services::settings_service::SettingsService;
-use std::collections::HashMap;
-use std::sync::RwLock;
-use std::time::{Duration, Instant};
+use crate::services::TelegramService;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 use tracing::{info, warn};
+use uuid::Uuid;
 
-/// Alert types for cooldown tracking
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum AlertType {
-    HighErrorRate,
-    RateLimitSpike,
-    SlowResponse,
+/// Fewer requests than this since the last check say nothing about the error rate.
+const MIN_REQUESTS_FOR_ERROR_RATE: u64 = 10;
+const MAX_COOLDOWN_MINUTES: i32 = 7 * 24 * 60;
+const MAX_NAME_LEN: usize = 100;
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// What a rule can watch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertMetric {
+    /// Percentage of requests since the last check that failed.
+    ErrorRate,
+    /// P95 response time in milliseconds.
+    LatencyP95,
+    /// Requests rejected by the rate limiter since the last check.
+    RateLimited,
+    /// Checked-out connections as a percentage of the pool size.
+    DbPoolSaturation,
+    /// Used space on the disk holding the app data directory, in percent.
+    DiskUsage,
+    /// Emails queued and due but not sent yet.
+    OutboxBacklog,
 }
 
-impl AlertType {
-    fn as_str(&self) -> &'static str {
+impl AlertMetric {
+    pub const ALL: [AlertMetric; 6] = [
+        AlertMetric::ErrorRate,
+        AlertMetric::LatencyP95,
+        AlertMetric::RateLimited,
+        AlertMetric::DbPoolSaturation,
+        AlertMetric::DiskUsage,
+        AlertMetric::OutboxBacklog,
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.as_str() == value)
+    }
+
+    pub fn as_str(&self) -> &'static str {
         match self {
-            AlertType::HighErrorRate => "high_error_rate",
-            AlertType::RateLimitSpike => "rate_limit_spike",
-            AlertType::SlowResponse => "slow_response",
+            AlertMetric::ErrorRate => "error_rate",
+            AlertMetric::LatencyP95 => "latency_p95",
+            AlertMetric::RateLimited => "rate_limited",
+            AlertMetric::DbPoolSaturation => "db_pool_saturation",
+            AlertMetric::DiskUsage => "disk_usage",
+            AlertMetric::OutboxBacklog => "outbox_backlog",
         }
     }
 
-    fn subject(&self) -> &'static str {
+    fn is_percentage(&self) -> bool {
+        matches!(
+            self,
+            AlertMetric::ErrorRate | AlertMetric::DbPoolSaturation | AlertMetric::DiskUsage
+        )
+    }
+
+    fn describe(&self, value: f64, threshold: f64) -> String {
         match self {
-            AlertType::HighErrorRate => "⚠️ High Error Rate Alert",
-            AlertType::RateLimitSpike => "🛡️ Rate Limiting Spike Detected",
-            AlertType::SlowResponse => "🐢 Slow Response Time Alert",
+            AlertMetric::ErrorRate => format!(
+                "Error rate is {:.1}% (threshold: {:.1}%).\n\nPlease investigate the application logs.",
+                value, threshold
+            ),
+            AlertMetric::LatencyP95 => format!(
+                "P95 response time is {:.0}ms (threshold: {:.0}ms).\n\n\
+                Consider optimizing database queries or scaling resources.",
+                value, threshold
+            ),
+            AlertMetric::RateLimited => format!(
+                "{:.0} requests were rate limited in the last check (threshold: {:.0}).\n\n\
+                This may indicate a DDoS attack or misconfigured client.",
+                value, threshold
+            ),
+            AlertMetric::DbPoolSaturation => format!(
+                "Database pool is {:.0}% in use (threshold: {:.0}%).\n\n\
+                Requests may queue for connections; check for slow queries or raise the pool size.",
+                value, threshold
+            ),
+            AlertMetric::DiskUsage => format!(
+                "Disk holding the data directory is {:.1}% full (threshold: {:.1}%).\n\n\
+                Free up space or grow the volume before uploads and backups start failing.",
+                value, threshold
+            ),
+            AlertMetric::OutboxBacklog => format!(
+                "{:.0} emails are waiting in the outbox (threshold: {:.0}).\n\n\
+                Check the SMTP settings and the email outbox for failures.",
+                value, threshold
+            ),
         }
     }
 }
 
-/// Alert service for monitoring and email notifications
-#[derive(Clone)]
-pub struct AlertService {
-    email_service: EmailService,
-    settings_service: SettingsService,
-    /// Track last alert time per type to implement cooldown
-    last_alert_times: std::sync::Arc<RwLock<HashMap<String, Instant>>>,
-    /// Previous metrics snapshot for calculating rates
-    #[allow(dead_code)]
-    previous_metrics: std::sync::Arc<RwLock<Option<MetricsSnapshot>>>,
+/// Where an alert can be delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertChannel {
+    Email,
+    Telegram,
+    Webhook,
+}
+
+impl AlertChannel {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "email" => Some(AlertChannel::Email),
+            "telegram" => Some(AlertChannel::Telegram),
+            "webhook" => Some(AlertChannel::Webhook),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertChannel::Email => "email",
+            AlertChannel::Telegram => "telegram",
+            AlertChannel::Webhook => "webhook",
+        }
+    }
 }
 
-/// Snapshot of metrics for rate calculation
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
+/// Request counters at a check, to work out what happened since the previous one.
+#[derive(Debug, Clone, Copy, Default)]
 struct MetricsSnapshot {
     total_requests: u64,
     error_count: u64,
     rate_limited_count: u64,
-    timestamp: Instant,
+}
+
+impl From<&RequestMetrics> for MetricsSnapshot {
+    fn from(metrics: &RequestMetrics) -> Self {
+        Self {
+            total_requests: metrics.total_requests,
+            error_count: metrics.error_count,
+            rate_limited_count: metrics.rate_limited_count,
+        }
+    }
+}
+
+/// Counters since `previous`. The counters only reset with the process, so a
+/// drop means this is the first check after a restart.
+fn window(previous: Option<MetricsSnapshot>, current: MetricsSnapshot) -> MetricsSnapshot {
+    match previous {
+        Some(prev) if current.total_requests >= prev.total_requests => MetricsSnapshot {
+            total_requests: current.total_requests - prev.total_requests,
+            error_count: current.error_count.saturating_sub(prev.error_count),
+            rate_limited_count: current
+                .rate_limited_count
+                .saturating_sub(prev.rate_limited_count),
+        },
+        _ => current,
+    }
+}
+
+fn error_rate(window: &MetricsSnapshot) -> Option<f64> {
+    (window.total_requests >= MIN_REQUESTS_FOR_ERROR_RATE)
+        .then(|| window.error_count as f64 / window.total_requests as f64 * 100.0)
+}
+
+/// Mount point, total and available bytes of every disk.
+pub(crate) fn list_disks() -> Vec<(PathBuf, u64, u64)> {
+    sysinfo::Disks::new_with_refreshed_list()
+        .list()
+        .iter()
+        .map(|d| {
+            (
+                d.mount_point().to_path_buf(),
+                d.total_space(),
+                d.available_space(),
+            )
+        })
+        .collect()
+}
+
+/// The disk `path` is on: the one with the longest mount point `path` starts with.
+pub(crate) fn disk_holding<'a>(
+    disks: &'a [(PathBuf, u64, u64)],
+    path: &Path,
+) -> Option<&'a (PathBuf, u64, u64)> {
+    disks
+        .iter()
+        .filter(|(mount, total, _)| *total > 0 && path.starts_with(mount))
+        .max_by_key(|(mount, _, _)| mount.as_os_str().len())
+}
+
+/// Used space, in percent, of the disk `path` is on.
+fn disk_usage_pct(disks: &[(PathBuf, u64, u64)], path: &Path) -> Option<f64> {
+    disk_holding(disks, path).map(|(_, total, available)| {
+        total.saturating_sub(*available) as f64 / *total as f64 * 100.0
+    })
+}
+
+/// Metric values at one check; `None` when this instance can't tell.
+#[derive(Debug, Clone, Copy, Default)]
+struct MetricReadings {
+    error_rate: Option<f64>,
+    latency_p95: Option<f64>,
+    rate_limited: Option<f64>,
+    db_pool_saturation: Option<f64>,
+    disk_usage: Option<f64>,
+    outbox_backlog: Option<f64>,
+}
+
+impl MetricReadings {
+    fn get(&self, metric: AlertMetric) -> Option<f64> {
+        match metric {
+            AlertMetric::ErrorRate => self.error_rate,
+            AlertMetric::LatencyP95 => self.latency_p95,
+            AlertMetric::RateLimited => self.rate_limited,
+            AlertMetric::DbPoolSaturation => self.db_pool_saturation,
+            AlertMetric::DiskUsage => self.disk_usage,
+            AlertMetric::OutboxBacklog => self.outbox_backlog,
+        }
+    }
+}
+
+/// The metric value `rule` fires on, if it should fire now.
+fn firing_value(
+    rule: &SystemAlertRule,
+    readings: &MetricReadings,
+    now: DateTime<Utc>,
+) -> Option<f64> {
+    if !rule.enabled {
+        return None;
+    }
+    let value = readings.get(AlertMetric::parse(&rule.metric)?)?;
+    if value <= rule.threshold {
+        return None;
+    }
+    match rule.last_triggered_at {
+        Some(last) if now - last < cooldown(rule.cooldown_minutes) => None,
+        _ => Some(value),
+    }
+}
+
+fn cooldown(minutes: i32) -> chrono::Duration {
+    chrono::Duration::minutes(i64::from(minutes.max(1)))
+}
+
+/// Checks a rule and returns its channels, deduplicated and in a stable order.
+fn validate_rule(req: &UpsertSystemAlertRuleRequest) -> AppResult<Vec<String>> {
+    let name = req.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "Rule name must be 1-{} characters",
+            MAX_NAME_LEN
+        )));
+    }
+    let metric = AlertMetric::parse(&req.metric)
+        .ok_or_else(|| AppError::Validation(format!("Unknown alert metric: {}", req.metric)))?;
+    if !req.threshold.is_finite() || req.threshold < 0.0 {
+        return Err(AppError::Validation(
+            "Threshold must be zero or more".to_string(),
+        ));
+    }
+    if metric.is_percentage() && req.threshold > 100.0 {
+        return Err(AppError::Validation(format!(
+            "Threshold of {} is a percentage and must be at most 100",
+            metric.as_str()
+        )));
+    }
+    if !(1..=MAX_COOLDOWN_MINUTES).contains(&req.cooldown_minutes) {
+        return Err(AppError::Validation(format!(
+            "Cooldown must be between 1 and {} minutes",
+            MAX_COOLDOWN_MINUTES
+        )));
+    }
+
+    let mut channels = Vec::new();
+    for channel in &req.channels {
+        let channel = AlertChannel::parse(channel.trim())
+            .ok_or_else(|| AppError::Validation(format!("Unknown alert channel: {}", channel)))?;
+        if !channels.contains(&channel) {
+            channels.push(channel);
+        }
+    }
+    if channels.is_empty() {
+        return Err(AppError::Validation(
+            "Pick at least one channel".to_string(),
+        ));
+    }
+    channels.sort_by_key(|c| *c as u8);
+    Ok(channels.iter().map(|c| c.as_str().to_string()).collect())
+}
+
+/// Alert service for rule evaluation and delivery
+#[derive(Clone)]
+pub struct AlertService {
+    pool: DbPool,
+    email_service: EmailService,
+    settings_service: SettingsService,
+    telegram: Option<TelegramService>,
+    metrics_service: Arc<MetricsService>,
+    /// Disk usage is measured on the volume holding this directory.
+    data_dir: PathBuf,
+    http: reqwest::Client,
+    /// Request counters at the previous check
+    previous_metrics: Arc<RwLock<Option<MetricsSnapshot>>>,
 }
 
 impl AlertService {
     /// Create a new alert service
-    pub fn new(email_service: EmailService, settings_service: SettingsService) -> Self {
+    pub fn new(
+        pool: DbPool,
+        email_service: EmailService,
+        settings_service: SettingsService,
+        telegram: Option<TelegramService>,
+        metrics_service: Arc<MetricsService>,
+        data_dir: PathBuf,
+    ) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default();
         Self {
+            pool,
             email_service,
             settings_service,
-            last_alert_times: std::sync::Arc::new(RwLock::new(HashMap::new())),
-            previous_metrics: std::sync::Arc::new(RwLock::new(None)),
+            telegram,
+            metrics_service,
+            data_dir,
+            http,
+            previous_metrics: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -76,162 +349,484 @@ impl AlertService {
             .get_value(None, key)
             .await
             .unwrap_or(None)
+            .map(|v| v.trim().to_string())
             .unwrap_or_else(|| default.to_string())
     }
 
-    /// Check metrics and send alerts if thresholds exceeded
-    pub async fn check_and_alert(&self, metrics: &RequestMetrics) {
-        // Check if alerting is enabled
-        let enabled = self.get_setting("alerting_enabled", "false").await;
+    pub async fn list_rules(&self) -> AppResult<Vec<SystemAlertRule>> {
+        let rules = sqlx::query_as("SELECT * FROM system_alert_rules ORDER BY created_at, name")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rules)
+    }
 
-        if enabled != "true" {
-            return;
+    async fn get_rule(&self, id: &str) -> AppResult<SystemAlertRule> {
+        sqlx::query_as("SELECT * FROM system_alert_rules WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Alert rule {} not found", id)))
+    }
+
+    pub async fn create_rule(
+        &self,
+        req: UpsertSystemAlertRuleRequest,
+    ) -> AppResult<SystemAlertRule> {
+        let channels = validate_rule(&req)?;
+        let now = Utc::now();
+        let rule = sqlx::query_as(
+            r#"
+            INSERT INTO system_alert_rules
+                (id, name, metric, threshold, channels, cooldown_minutes, enabled, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(req.name.trim())
+        .bind(&req.metric)
+        .bind(req.threshold)
+        .bind(sqlx::types::Json(&channels))
+        .bind(req.cooldown_minutes)
+        .bind(req.enabled)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(rule)
+    }
+
+    pub async fn update_rule(
+        &self,
+        id: &str,
+        req: UpsertSystemAlertRuleRequest,
+    ) -> AppResult<SystemAlertRule> {
+        let channels = validate_rule(&req)?;
+        let rule = sqlx::query_as(
+            r#"
+            UPDATE system_alert_rules
+            SET name = $1, metric = $2, threshold = $3, channels = $4,
+                cooldown_minutes = $5, enabled = $6, updated_at = $7
+            WHERE id = $8
+            RETURNING *
+            "#,
+        )
+        .bind(req.name.trim())
+        .bind(&req.metric)
+        .bind(req.threshold)
+        .bind(sqlx::types::Json(&channels))
+        .bind(req.cooldown_minutes)
+        .bind(req.enabled)
+        .bind(Utc::now())
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Alert rule {} not found", id)))?;
+        Ok(rule)
+    }
+
+    pub async fn delete_rule(&self, id: &str) -> AppResult<()> {
+        let res = sqlx::query("DELETE FROM system_alert_rules WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Alert rule {} not found", id)));
         }
+        Ok(())
+    }
 
-        // Get thresholds from settings
-        let error_threshold: f64 = self
-            .get_setting("alerting_error_threshold", "5.0")
-            .await
-            .parse()
-            .unwrap_or(5.0);
+    /// Send a test alert for the rule on its channels. Leaves the cooldown alone.
+    pub async fn test_rule(&self, id: &str) -> AppResult<Vec<AlertDeliveryResult>> {
+        let rule = self.get_rule(id).await?;
+        let metric = AlertMetric::parse(&rule.metric).unwrap_or(AlertMetric::ErrorRate);
+        let body = format!(
+            "This is a test of the \"{}\" alert rule. When it fires it reads:\n\n{}",
+            rule.name,
+            metric.describe(rule.threshold, rule.threshold)
+        );
+        Ok(self.deliver(&rule, None, &body).await)
+    }
 
-        let rate_limit_threshold: u64 = self
-            .get_setting("alerting_rate_limit_threshold", "50")
-            .await
-            .parse()
-            .unwrap_or(50);
+    /// Evaluate the rules against the current metrics and send the alerts due.
+    pub async fn check(&self) {
+        let readings = self.readings().await;
 
-        let response_time_threshold: f64 = self
-            .get_setting("alerting_response_time_threshold", "3000.0")
-            .await
-            .parse()
-            .unwrap_or(3000.0);
+        if self.get_setting("alerting_enabled", "false").await != "true" {
+            return;
+        }
 
-        // Calculate error rate
-        let error_rate = if metrics.total_requests > 0 {
-            (metrics.error_count as f64 / metrics.total_requests as f64) * 100.0
-        } else {
-            0.0
+        let rules = match self.list_rules().await {
+            Ok(rules) => rules,
+            Err(e) => {
+                warn!("Failed to load alert rules: {}", e);
+                return;
+            }
         };
 
-        // Check High Error Rate
-        if error_rate > error_threshold && metrics.total_requests > 10 {
-            self.maybe_send_alert(
-                AlertType::HighErrorRate,
-                &format!(
-                    "Error rate is {:.1}% (threshold: {:.1}%)\n\n\
-                    Total Requests: {}\n\
-                    Errors: {}\n\
-                    Rate Limited: {}\n\n\
-                    Please investigate the application logs.",
-                    error_rate,
-                    error_threshold,
-                    metrics.total_requests,
-                    metrics.error_count,
-                    metrics.rate_limited_count
-                ),
-            )
-            .await;
-        }
+        let now = Utc::now();
+        for rule in rules {
+            let Some(value) = firing_value(&rule, &readings, now) else {
+                continue;
+            };
+            match self.claim(&rule, value, now).await {
+                Ok(true) => {}
+                Ok(false) => continue, // Another instance fired it.
+                Err(e) => {
+                    warn!("Failed to claim alert rule {}: {}", rule.id, e);
+                    continue;
+                }
+            }
 
-        // Check Rate Limit Spike
-        if metrics.rate_limited_count > rate_limit_threshold {
-            self.maybe_send_alert(
-                AlertType::RateLimitSpike,
-                &format!(
-                    "Rate limiting spike detected!\n\n\
-                    Rate Limited Requests: {}\n\
-                    Threshold: {}\n\n\
-                    This may indicate a DDoS attack or misconfigured client.",
-                    metrics.rate_limited_count, rate_limit_threshold
-                ),
-            )
-            .await;
+            let metric = AlertMetric::parse(&rule.metric).unwrap_or(AlertMetric::ErrorRate);
+            let body = metric.describe(value, rule.threshold);
+            let results = self.deliver(&rule, Some(value), &body).await;
+            for failed in results.iter().filter(|r| !r.ok) {
+                warn!(
+                    "Failed to send alert \"{}\" via {}: {}",
+                    rule.name,
+                    failed.channel,
+                    failed.error.as_deref().unwrap_or("unknown error")
+                );
+            }
+            if results.iter().any(|r| r.ok) {
+                info!("Alert sent: {} ({} = {:.2})", rule.name, rule.metric, value);
+            } else {
+                // Nothing got out: let the next check try again.
+                self.release(&rule, now).await;
+            }
         }
+    }
 
-        // Check Slow Response Time
-        if metrics.p95_response_time_ms > response_time_threshold {
-            self.maybe_send_alert(
-                AlertType::SlowResponse,
-                &format!(
-                    "P95 response time is {:.0}ms (threshold: {:.0}ms)\n\n\
-                    Average: {:.0}ms\n\
-                    Min: {:.0}ms\n\
-                    Max: {:.0}ms\n\n\
-                    Consider optimizing database queries or scaling resources.",
-                    metrics.p95_response_time_ms,
-                    response_time_threshold,
-                    metrics.avg_response_time_ms,
-                    metrics.min_response_time_ms,
-                    metrics.max_response_time_ms
-                ),
-            )
-            .await;
-        }
+    async fn readings(&self) -> MetricReadings {
+        let metrics = self.metrics_service.get_metrics();
+        let current = MetricsSnapshot::from(&metrics);
+        let previous = self.previous_metrics.write().unwrap().replace(current);
+        let since_last = window(previous, current);
 
-        // Store current metrics snapshot
-        let snapshot = MetricsSnapshot {
-            total_requests: metrics.total_requests,
-            error_count: metrics.error_count,
-            rate_limited_count: metrics.rate_limited_count,
-            timestamp: Instant::now(),
-        };
-        *self.previous_metrics.write().unwrap() = Some(snapshot);
+        MetricReadings {
+            error_rate: error_rate(&since_last),
+            latency_p95: (metrics.total_requests > 0).then_some(metrics.p95_response_time_ms),
+            rate_limited: Some(since_last.rate_limited_count as f64),
+            db_pool_saturation: self
+                .metrics_service
+                .get_pool_metrics()
+                .map(|p| p.utilization_pct),
+            disk_usage: self.disk_usage(),
+            outbox_backlog: self.outbox_backlog().await,
+        }
     }
 
-    /// Send alert if cooldown period has passed
-    async fn maybe_send_alert(&self, alert_type: AlertType, body: &str) {
-        let cooldown_minutes: u64 = self
-            .get_setting("alerting_cooldown_minutes", "15")
-            .await
-            .parse()
-            .unwrap_or(15);
-
-        let cooldown = Duration::from_secs(cooldown_minutes * 60);
-        let key = alert_type.as_str().to_string();
+    fn disk_usage(&self) -> Option<f64> {
+        let dir = std::fs::canonicalize(&self.data_dir).unwrap_or_else(|_| self.data_dir.clone());
+        let disks = list_disks();
+        disk_usage_pct(&disks, &dir)
+    }
 
-        // Check cooldown
+    async fn outbox_backlog(&self) -> Option<f64> {
+        // The email outbox only exists on postgres.
+        #[cfg(feature = "postgres")]
         {
-            let times = self.last_alert_times.read().unwrap();
-            if let Some(last_time) = times.get(&key) {
-                if last_time.elapsed() < cooldown {
-                    return; // Still in cooldown
+            let count: Result<i64, sqlx::Error> = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM email_outbox WHERE status IN ('queued', 'sending') AND scheduled_at <= $1",
+            )
+            .bind(Utc::now())
+            .fetch_one(&self.pool)
+            .await;
+            match count {
+                Ok(count) => Some(count as f64),
+                Err(e) => {
+                    warn!("Failed to count the email outbox backlog: {}", e);
+                    None
                 }
             }
         }
 
-        // Send the alert
-        if let Err(e) = self.send_alert(alert_type.subject(), body).await {
-            warn!("Failed to send alert email: {}", e);
-        } else {
-            info!("Alert sent: {}", alert_type.subject());
-            // Update last alert time
-            let mut times = self.last_alert_times.write().unwrap();
-            times.insert(key, Instant::now());
+        #[cfg(not(feature = "postgres"))]
+        {
+            None
         }
     }
 
-    /// Send alert email to configured recipient
-    async fn send_alert(&self, subject: &str, body: &str) -> AppResult<()> {
-        let recipient = self.get_setting("alerting_email", "").await;
+    /// Take the rule's cooldown from `now`. False when another instance already has.
+    async fn claim(
+        &self,
+        rule: &SystemAlertRule,
+        value: f64,
+        now: DateTime<Utc>,
+    ) -> AppResult<bool> {
+        let res = sqlx::query(
+            r#"
+            UPDATE system_alert_rules
+            SET last_triggered_at = $1, last_value = $2
+            WHERE id = $3
+              AND (last_triggered_at IS NULL OR last_triggered_at <= $4)
+            "#,
+        )
+        .bind(now)
+        .bind(value)
+        .bind(&rule.id)
+        .bind(now - cooldown(rule.cooldown_minutes))
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() == 1)
+    }
 
-        if recipient.is_empty() {
-            warn!("Alerting email not configured, skipping alert");
-            return Ok(());
+    async fn release(&self, rule: &SystemAlertRule, claimed_at: DateTime<Utc>) {
+        let res = sqlx::query(
+            r#"
+            UPDATE system_alert_rules
+            SET last_triggered_at = $1, last_value = $2
+            WHERE id = $3 AND last_triggered_at = $4
+            "#,
+        )
+        .bind(rule.last_triggered_at)
+        .bind(rule.last_value)
+        .bind(&rule.id)
+        .bind(claimed_at)
+        .execute(&self.pool)
+        .await;
+        if let Err(e) = res {
+            warn!("Failed to release alert rule {}: {}", rule.id, e);
         }
+    }
 
+    /// Send on every channel of the rule. `value` is `None` for test alerts.
+    async fn deliver(
+        &self,
+        rule: &SystemAlertRule,
+        value: Option<f64>,
+        body: &str,
+    ) -> Vec<AlertDeliveryResult> {
         let app_name = self.get_setting("app_name", "SaaS App").await;
-
-        let full_subject = format!("[{}] {}", app_name, subject);
-        let full_body = format!(
+        let now = Utc::now();
+        let subject = match value {
+            Some(_) => format!("[{}] {}", app_name, rule.name),
+            None => format!("[{}] Test: {}", app_name, rule.name),
+        };
+        let text = format!(
             "{}\n\n---\nThis is an automated alert from {}.\nTimestamp: {}",
             body,
             app_name,
-            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+            now.format("%Y-%m-%d %H:%M:%S UTC")
         );
 
+        let mut results = Vec::new();
+        for name in &rule.channels {
+            let outcome = match AlertChannel::parse(name) {
+                Some(AlertChannel::Email) => self.send_email(&subject, &text).await,
+                Some(AlertChannel::Telegram) => {
+                    self.send_telegram(&format!("{}\n\n{}", subject, text))
+                        .await
+                }
+                Some(AlertChannel::Webhook) => {
+                    let payload = json!({
+                        "type": "system_alert",
+                        "test": value.is_none(),
+                        "rule_id": rule.id,
+                        "rule": rule.name,
+                        "metric": rule.metric,
+                        "value": value,
+                        "threshold": rule.threshold,
+                        "app_name": app_name,
+                        "message": body,
+                        "triggered_at": now,
+                    });
+                    self.send_webhook(&payload).await
+                }
+                None => Err(format!("Unknown channel {}", name)),
+            };
+            results.push(AlertDeliveryResult {
+                channel: name.clone(),
+                ok: outcome.is_ok(),
+                error: outcome.err(),
+            });
+        }
+        results
+    }
+
+    async fn send_email(&self, subject: &str, body: &str) -> Result<(), String> {
+        let recipient = self.get_setting("alerting_email", "").await;
+        if recipient.is_empty() {
+            return Err("No alert email address is configured".to_string());
+        }
+        // Straight to SMTP, not through the outbox: a backed-up outbox is
+        // one of the things being alerted on.
         self.email_service
-            .send_email(&recipient, &full_subject, &full_body)
+            .send_email(&recipient, subject, body)
             .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn send_telegram(&self, text: &str) -> Result<(), String> {
+        let chat_id = self.get_setting("alerting_telegram_chat_id", "").await;
+        if chat_id.is_empty() {
+            return Err("No Telegram chat is configured for alerts".to_string());
+        }
+        match &self.telegram {
+            Some(telegram) if telegram.is_active().await => {
+                telegram.send_message(&chat_id, text).await
+            }
+            _ => Err("The Telegram bot is not enabled".to_string()),
+        }
+    }
+
+    async fn send_webhook(&self, payload: &serde_json::Value) -> Result<(), String> {
+        let url = self.get_setting("alerting_webhook_url", "").await;
+        if url.is_empty() {
+            return Err("No alert webhook URL is configured".to_string());
+        }
+        let body = payload.to_string();
+        let mut request = self
+            .http
+            .post(&url)
+            .header("Content-Type", "application/json");
+        let secret = self.get_setting("alerting_webhook_secret", "").await;
+        if !secret.is_empty() {
+            let sig = hmac_sha256_hex(secret.as_bytes(), body.as_bytes());
+            request = request.header("X-Alert-Signature", format!("sha256={}", sig));
+        }
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("Webhook responded with {}", response.status()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(metric: &str, threshold: f64, last: Option<DateTime<Utc>>) -> SystemAlertRule {
+        let now = Utc::now();
+        SystemAlertRule {
+            id: "r1".to_string(),
+            name: "Rule".to_string(),
+            metric: metric.to_string(),
+            threshold,
+            channels: vec!["email".to_string()],
+            cooldown_minutes: 15,
+            enabled: true,
+            last_triggered_at: last,
+            last_value: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn request(metric: &str, threshold: f64, channels: &[&str]) -> UpsertSystemAlertRuleRequest {
+        UpsertSystemAlertRuleRequest {
+            name: " Disk ".to_string(),
+            metric: metric.to_string(),
+            threshold,
+            channels: channels.iter().map(|c| c.to_string()).collect(),
+            cooldown_minutes: 30,
+            enabled: true,
+        }
+    }
+
+    fn snapshot(total: u64, errors: u64, limited: u64) -> MetricsSnapshot {
+        MetricsSnapshot {
+            total_requests: total,
+            error_count: errors,
+            rate_limited_count: limited,
+        }
+    }
+
+    #[test]
+    fn request_metrics_are_taken_since_the_previous_check() {
+        let since = window(Some(snapshot(100, 5, 2)), snapshot(140, 25, 12));
+        assert_eq!(since.total_requests, 40);
+        assert_eq!(error_rate(&since), Some(50.0));
+        assert_eq!(since.rate_limited_count, 10);
+
+        // First check, and the first one after a restart, use the totals.
+        assert_eq!(window(None, snapshot(30, 3, 0)).total_requests, 30);
+        assert_eq!(
+            window(Some(snapshot(500, 5, 0)), snapshot(30, 3, 0)).total_requests,
+            30
+        );
+
+        assert_eq!(error_rate(&snapshot(9, 9, 0)), None);
+    }
+
+    #[test]
+    fn rules_fire_above_threshold_outside_cooldown() {
+        let now = Utc::now();
+        let readings = MetricReadings {
+            disk_usage: Some(93.5),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            firing_value(&rule("disk_usage", 90.0, None), &readings, now),
+            Some(93.5)
+        );
+        assert_eq!(
+            firing_value(&rule("disk_usage", 95.0, None), &readings, now),
+            None
+        );
+        // No reading, e.g. no outbox on this backend.
+        assert_eq!(
+            firing_value(&rule("outbox_backlog", 1.0, None), &readings, now),
+            None
+        );
+
+        let recent = Some(now - chrono::Duration::minutes(5));
+        assert_eq!(
+            firing_value(&rule("disk_usage", 90.0, recent), &readings, now),
+            None
+        );
+        let old = Some(now - chrono::Duration::minutes(15));
+        assert_eq!(
+            firing_value(&rule("disk_usage", 90.0, old), &readings, now),
+            Some(93.5)
+        );
+
+        let mut disabled = rule("disk_usage", 90.0, None);
+        disabled.enabled = false;
+        assert_eq!(firing_value(&disabled, &readings, now), None);
+    }
+
+    #[test]
+    fn disk_usage_uses_the_deepest_mount_holding_the_path() {
+        let disks = vec![
+            (PathBuf::from("/"), 100, 80),
+            (PathBuf::from("/var/lib"), 200, 20),
+            (PathBuf::from("/var/lib2"), 100, 0),
+        ];
+        assert_eq!(
+            disk_usage_pct(&disks, Path::new("/var/lib/app/data")),
+            Some(90.0)
+        );
+        assert_eq!(disk_usage_pct(&disks, Path::new("/home/app")), Some(20.0));
+        assert_eq!(disk_usage_pct(&[], Path::new("/home/app")), None);
+    }
+
+    #[test]
+    fn validates_rules() {
+        assert_eq!(
+            validate_rule(&request(
+                "disk_usage",
+                85.0,
+                &["webhook", "email", "webhook"]
+            ))
+            .unwrap(),
+            ["email", "webhook"]
+        );
+        assert!(validate_rule(&request("cpu", 85.0, &["email"])).is_err());
+        assert!(validate_rule(&request("disk_usage", 120.0, &["email"])).is_err());
+        assert!(validate_rule(&request("outbox_backlog", 1200.0, &["email"])).is_ok());
+        assert!(validate_rule(&request("disk_usage", 85.0, &[])).is_err());
+        assert!(validate_rule(&request("disk_usage", 85.0, &["sms"])).is_err());
+
+        let mut no_cooldown = request("disk_usage", 85.0, &["email"]);
+        no_cooldown.cooldown_minutes = 0;
+        assert!(validate_rule(&no_cooldown).is_err());
     }
 }
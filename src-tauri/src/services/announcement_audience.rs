@@ -0,0 +1,451 @@
+//! Segment audiences for announcements.
+//!
+//! `audience = "segment"` stores its selectors in `announcements.audience_targets`.
+//! Tenant announcements target roles, packages, customer tags and routers of
+//! that tenant; global ones target plans, tenants, global roles and customer
+//! tags across tenants. The matching users are resolved when the announcement
+//! is sent, not when it is saved, so a scheduled broadcast reaches whoever is in
+//! the segment at delivery time. The resolved list is kept in
+//! `announcement_recipients` and is what decides who can see the announcement
+//! in the app afterwards.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{Announcement, AudienceTargets};
+
+/// Upper bound on selectors per kind, to keep the resolver query reasonable.
+pub const MAX_TARGETS_PER_KIND: usize = 100;
+
+pub fn normalize_tag(tag: &str) -> String {
+    tag.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn normalize_list(values: Vec<String>, f: impl Fn(&str) -> String) -> Vec<String> {
+    let mut out: Vec<String> = Vec::with_capacity(values.len());
+    for v in values {
+        let v = f(&v);
+        if !v.is_empty() && !out.contains(&v) {
+            out.push(v);
+        }
+    }
+    out
+}
+
+/// Trims and de-duplicates every selector; tags are also lower-cased.
+pub fn normalize_targets(targets: AudienceTargets) -> AudienceTargets {
+    let trim = |v: &str| v.trim().to_string();
+    AudienceTargets {
+        role_ids: normalize_list(targets.role_ids, trim),
+        package_ids: normalize_list(targets.package_ids, trim),
+        customer_tags: normalize_list(targets.customer_tags, normalize_tag),
+        router_ids: normalize_list(targets.router_ids, trim),
+        plan_ids: normalize_list(targets.plan_ids, trim),
+        tenant_ids: normalize_list(targets.tenant_ids, trim),
+    }
+}
+
+/// Targets stored on an announcement; unreadable JSON is treated as empty.
+pub fn targets_of(announcement: &Announcement) -> AudienceTargets {
+    announcement
+        .audience_targets
+        .clone()
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn ensure_all_found(ids: &[String], found: &[String], what: &str) -> AppResult<()> {
+    if let Some(missing) = ids.iter().find(|id| !found.contains(id)) {
+        return Err(AppError::Validation(format!(
+            "Unknown {} '{}'",
+            what, missing
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "postgres")]
+async fn ensure_known(
+    pool: &DbPool,
+    sql: &str,
+    tenant_id: &str,
+    ids: &[String],
+    what: &str,
+) -> AppResult<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let found: Vec<String> = sqlx::query_scalar(sql)
+        .bind(tenant_id)
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+    ensure_all_found(ids, &found, what)
+}
+
+/// Like `ensure_known` for platform-wide rows; `sql` takes the ids as `$1`.
+#[cfg(feature = "postgres")]
+async fn ensure_known_global(
+    pool: &DbPool,
+    sql: &str,
+    ids: &[String],
+    what: &str,
+) -> AppResult<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let found: Vec<String> = sqlx::query_scalar(sql).bind(ids).fetch_all(pool).await?;
+    ensure_all_found(ids, &found, what)
+}
+
+/// Rejects selectors that do not apply to the announcement's scope.
+fn check_scope(tenant_id: Option<&str>, targets: &AudienceTargets) -> AppResult<()> {
+    if tenant_id.is_some() {
+        if !targets.plan_ids.is_empty() || !targets.tenant_ids.is_empty() {
+            return Err(AppError::Validation(
+                "Plan and tenant targets are only available for global announcements".to_string(),
+            ));
+        }
+    } else if !targets.package_ids.is_empty() || !targets.router_ids.is_empty() {
+        return Err(AppError::Validation(
+            "Package and router targets are only available for tenant announcements".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validates the targets for an announcement and returns what to store in
+/// `audience_targets`. Only segment audiences keep targets.
+pub async fn prepare_targets(
+    pool: &DbPool,
+    tenant_id: Option<&str>,
+    audience: &str,
+    targets: Option<AudienceTargets>,
+) -> AppResult<Option<serde_json::Value>> {
+    if audience != "segment" {
+        return Ok(None);
+    }
+    let targets = normalize_targets(targets.unwrap_or_default());
+    if targets.is_empty() {
+        return Err(AppError::Validation(match tenant_id {
+            Some(_) => "Pick at least one role, package, customer tag or router".to_string(),
+            None => "Pick at least one plan, tenant, role or customer tag".to_string(),
+        }));
+    }
+    for list in [
+        &targets.role_ids,
+        &targets.package_ids,
+        &targets.customer_tags,
+        &targets.router_ids,
+        &targets.plan_ids,
+        &targets.tenant_ids,
+    ] {
+        if list.len() > MAX_TARGETS_PER_KIND {
+            return Err(AppError::Validation(format!(
+                "At most {} entries per target type",
+                MAX_TARGETS_PER_KIND
+            )));
+        }
+    }
+    check_scope(tenant_id, &targets)?;
+
+    #[cfg(feature = "postgres")]
+    match tenant_id {
+        Some(tenant_id) => {
+            ensure_known(
+                pool,
+                "SELECT id FROM roles WHERE (tenant_id = $1 OR tenant_id IS NULL) AND id = ANY($2)",
+                tenant_id,
+                &targets.role_ids,
+                "role",
+            )
+            .await?;
+            ensure_known(
+                pool,
+                "SELECT id FROM isp_packages WHERE tenant_id = $1 AND id = ANY($2)",
+                tenant_id,
+                &targets.package_ids,
+                "package",
+            )
+            .await?;
+            ensure_known(
+                pool,
+                "SELECT id FROM mikrotik_routers WHERE tenant_id = $1 AND id = ANY($2)",
+                tenant_id,
+                &targets.router_ids,
+                "router",
+            )
+            .await?;
+        }
+        None => {
+            ensure_known_global(
+                pool,
+                "SELECT id FROM roles WHERE tenant_id IS NULL AND id = ANY($1)",
+                &targets.role_ids,
+                "role",
+            )
+            .await?;
+            ensure_known_global(
+                pool,
+                "SELECT id FROM plans WHERE id = ANY($1)",
+                &targets.plan_ids,
+                "plan",
+            )
+            .await?;
+            ensure_known_global(
+                pool,
+                "SELECT id FROM tenants WHERE id = ANY($1)",
+                &targets.tenant_ids,
+                "tenant",
+            )
+            .await?;
+        }
+    }
+    #[cfg(not(feature = "postgres"))]
+    let _ = pool;
+
+    serde_json::to_value(&targets)
+        .map(Some)
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+/// Active users matching any of the targets within the tenant: members holding
+/// one of the roles, plus portal users of non-deleted customers that have an
+/// active subscription on one of the packages, carry one of the tags, or are
+/// served from one of the routers (by subscription or PPPoE account).
+#[cfg(feature = "postgres")]
+async fn resolve_tenant_segment(
+    pool: &DbPool,
+    tenant_id: &str,
+    targets: &AudienceTargets,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        WITH portal AS (
+            SELECT cu.user_id, cu.customer_id
+            FROM customer_users cu
+            JOIN customers c ON c.id = cu.customer_id AND c.deleted_at IS NULL
+            WHERE cu.tenant_id = $1
+        ),
+        matched AS (
+            SELECT tm.user_id
+            FROM tenant_members tm
+            WHERE tm.tenant_id = $1 AND tm.role_id = ANY($2)
+            UNION
+            SELECT p.user_id
+            FROM portal p
+            JOIN customer_subscriptions cs
+              ON cs.customer_id = p.customer_id AND cs.status = 'active'
+            WHERE cs.package_id = ANY($3)
+            UNION
+            SELECT p.user_id
+            FROM portal p
+            JOIN customer_tags ct ON ct.customer_id = p.customer_id
+            WHERE ct.tag = ANY($4)
+            UNION
+            SELECT p.user_id
+            FROM portal p
+            JOIN customer_subscriptions cs
+              ON cs.customer_id = p.customer_id AND cs.status = 'active'
+            WHERE cs.router_id = ANY($5)
+            UNION
+            SELECT p.user_id
+            FROM portal p
+            JOIN pppoe_accounts pa ON pa.customer_id = p.customer_id
+            WHERE pa.router_id = ANY($5)
+        )
+        SELECT u.id
+        FROM users u
+        JOIN matched m ON m.user_id = u.id
+        WHERE u.is_active = true
+        ORDER BY u.id
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(&targets.role_ids)
+    .bind(&targets.package_ids)
+    .bind(&targets.customer_tags)
+    .bind(&targets.router_ids)
+    .fetch_all(pool)
+    .await
+}
+
+/// Active users of active tenants matching any of the global targets: every
+/// member of a tenant on one of the plans (active or trial subscription) or
+/// listed by id, members holding one of the global roles, and portal users of
+/// non-deleted customers carrying one of the tags.
+#[cfg(feature = "postgres")]
+async fn resolve_global_segment(
+    pool: &DbPool,
+    targets: &AudienceTargets,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        WITH picked_tenants AS (
+            SELECT ts.tenant_id
+            FROM tenant_subscriptions ts
+            WHERE ts.plan_id = ANY($1) AND ts.status IN ('active', 'trial')
+            UNION
+            SELECT t.id FROM tenants t WHERE t.id = ANY($2)
+        ),
+        matched AS (
+            SELECT tm.user_id, tm.tenant_id
+            FROM tenant_members tm
+            JOIN picked_tenants pt ON pt.tenant_id = tm.tenant_id
+            UNION
+            SELECT tm.user_id, tm.tenant_id
+            FROM tenant_members tm
+            WHERE tm.role_id = ANY($3)
+            UNION
+            SELECT cu.user_id, cu.tenant_id
+            FROM customer_users cu
+            JOIN customers c ON c.id = cu.customer_id AND c.deleted_at IS NULL
+            JOIN customer_tags ct ON ct.customer_id = c.id
+            WHERE ct.tag = ANY($4)
+        )
+        SELECT DISTINCT u.id
+        FROM users u
+        JOIN matched m ON m.user_id = u.id
+        JOIN tenants t ON t.id = m.tenant_id AND t.is_active = true
+        WHERE u.is_active = true
+        ORDER BY u.id
+        "#,
+    )
+    .bind(&targets.plan_ids)
+    .bind(&targets.tenant_ids)
+    .bind(&targets.role_ids)
+    .bind(&targets.customer_tags)
+    .fetch_all(pool)
+    .await
+}
+
+/// Users in the segment: within `tenant_id` for tenant announcements, across
+/// tenants for global ones (`None`).
+#[cfg(feature = "postgres")]
+pub async fn resolve_segment(
+    pool: &DbPool,
+    tenant_id: Option<&str>,
+    targets: &AudienceTargets,
+) -> Result<Vec<String>, sqlx::Error> {
+    if targets.is_empty() {
+        return Ok(Vec::new());
+    }
+    match tenant_id {
+        Some(tenant_id) => resolve_tenant_segment(pool, tenant_id, targets).await,
+        None => resolve_global_segment(pool, targets).await,
+    }
+}
+
+/// Resolves a segment announcement's recipients and records them so the
+/// announcement shows up for them in the app. Safe to call more than once per
+/// send; later calls only add users who joined the segment in between.
+#[cfg(feature = "postgres")]
+pub async fn resolve_and_record(
+    pool: &DbPool,
+    announcement: &Announcement,
+) -> Result<Vec<String>, sqlx::Error> {
+    let ids = resolve_segment(
+        pool,
+        announcement.tenant_id.as_deref(),
+        &targets_of(announcement),
+    )
+    .await?;
+    if !ids.is_empty() {
+        sqlx::query(
+            r#"
+            INSERT INTO announcement_recipients (announcement_id, user_id, created_at)
+            SELECT $1, uid, $3 FROM UNNEST($2::text[]) AS uid
+            ON CONFLICT (announcement_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(&announcement.id)
+        .bind(&ids)
+        .bind(chrono::Utc::now())
+        .execute(pool)
+        .await?;
+    }
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_trims_dedupes_and_lowercases_tags() {
+        let t = normalize_targets(AudienceTargets {
+            role_ids: vec![" r1 ".into(), "r1".into(), "".into()],
+            package_ids: vec![],
+            customer_tags: vec!["VIP".into(), " vip ".into(), "RT  05".into()],
+            router_ids: vec!["  ".into()],
+            plan_ids: vec![" pro ".into(), "pro".into()],
+            tenant_ids: vec![],
+        });
+        assert_eq!(t.role_ids, vec!["r1"]);
+        assert_eq!(t.customer_tags, vec!["vip", "rt 05"]);
+        assert!(t.router_ids.is_empty());
+        assert_eq!(t.plan_ids, vec!["pro"]);
+        assert!(!t.is_empty());
+    }
+
+    #[test]
+    fn selectors_must_fit_the_announcement_scope() {
+        let plans = AudienceTargets {
+            plan_ids: vec!["pro".into()],
+            ..Default::default()
+        };
+        let routers = AudienceTargets {
+            router_ids: vec!["r1".into()],
+            ..Default::default()
+        };
+        let tags = AudienceTargets {
+            customer_tags: vec!["vip".into()],
+            ..Default::default()
+        };
+
+        assert!(check_scope(None, &plans).is_ok());
+        assert!(check_scope(Some("t1"), &plans).is_err());
+        assert!(check_scope(Some("t1"), &routers).is_ok());
+        assert!(check_scope(None, &routers).is_err());
+        assert!(check_scope(None, &tags).is_ok());
+        assert!(check_scope(Some("t1"), &tags).is_ok());
+    }
+
+    #[test]
+    fn targets_default_when_missing_or_malformed() {
+        let now = chrono::Utc::now();
+        let mut ann = Announcement {
+            id: "a".into(),
+            tenant_id: Some("t".into()),
+            created_by: None,
+            cover_file_id: None,
+            title: "t".into(),
+            body: "b".into(),
+            severity: "info".into(),
+            audience: "segment".into(),
+            audience_targets: None,
+            translations: None,
+            mode: "post".into(),
+            format: "plain".into(),
+            deliver_in_app: true,
+            deliver_email: false,
+            deliver_email_force: true,
+            starts_at: now,
+            ends_at: None,
+            notified_at: None,
+            recurrence: None,
+            occurrence: 0,
+            next_occurrence_at: None,
+            occurrence_ends_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        assert!(targets_of(&ann).is_empty());
+        ann.audience_targets = Some(serde_json::json!("nope"));
+        assert!(targets_of(&ann).is_empty());
+        ann.audience_targets = Some(serde_json::json!({ "customer_tags": ["vip"] }));
+        assert_eq!(targets_of(&ann).customer_tags, vec!["vip"]);
+    }
+}
@@ -0,0 +1,301 @@
+//! Recurring announcements.
+//!
+//! A series is one `announcements` row with a `recurrence` rule. Whenever
+//! `next_occurrence_at` comes due the scheduler bumps `occurrence`, publishes
+//! the announcement again and moves `next_occurrence_at` to the following
+//! date. Dismissals are stored per occurrence, so closing last month's
+//! maintenance notice doesn't hide this month's. Occurrences missed while the
+//! scheduler was down are not replayed; the next run publishes once.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{Announcement, AnnouncementRecurrence};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Longest an occurrence may stay up (a month).
+pub const MAX_DURATION_MINUTES: u32 = 31 * 24 * 60;
+
+/// How far ahead `next_occurrence` searches (a year covers every rule).
+const MAX_LOOKAHEAD_DAYS: i64 = 400;
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// Checks a rule and returns it normalized (lower-case frequency, sorted
+/// weekdays, only the fields its frequency uses).
+pub fn validate_recurrence(rule: AnnouncementRecurrence) -> AppResult<AnnouncementRecurrence> {
+    let invalid = |msg: &str| Err(AppError::Validation(msg.to_string()));
+
+    let Some(time) = parse_time(&rule.time) else {
+        return invalid("Recurrence time must be HH:MM");
+    };
+    if let Some(minutes) = rule.duration_minutes {
+        if minutes == 0 || minutes > MAX_DURATION_MINUTES {
+            return Err(AppError::Validation(format!(
+                "Recurrence duration must be between 1 and {} minutes",
+                MAX_DURATION_MINUTES
+            )));
+        }
+    }
+
+    let mut out = AnnouncementRecurrence {
+        frequency: rule.frequency.trim().to_lowercase(),
+        time: time.format("%H:%M").to_string(),
+        weekdays: Vec::new(),
+        day_of_month: None,
+        week_of_month: None,
+        weekday: None,
+        duration_minutes: rule.duration_minutes,
+    };
+
+    match out.frequency.as_str() {
+        "daily" => {}
+        "weekly" => {
+            let mut weekdays = rule.weekdays;
+            weekdays.sort_unstable();
+            weekdays.dedup();
+            if weekdays.is_empty() || weekdays.iter().any(|d| *d > 6) {
+                return invalid("Weekly recurrence needs weekdays between 0 (Sunday) and 6");
+            }
+            out.weekdays = weekdays;
+        }
+        "monthly" => match (rule.day_of_month, rule.week_of_month, rule.weekday) {
+            (Some(day), None, None) if (1..=31).contains(&day) => out.day_of_month = Some(day),
+            (None, Some(week), Some(weekday))
+                if ((1..=5).contains(&week) || week == -1) && weekday <= 6 =>
+            {
+                out.week_of_month = Some(week);
+                out.weekday = Some(weekday);
+            }
+            _ => {
+                return invalid(
+                    "Monthly recurrence needs a day_of_month (1-31) or a week_of_month (1-5, -1 for last) with a weekday",
+                )
+            }
+        },
+        _ => return invalid("Recurrence frequency must be daily, weekly or monthly"),
+    }
+
+    Ok(out)
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (y, m) = (date.year(), date.month());
+    let (ny, nm) = if m == 12 { (y + 1, 1) } else { (y, m + 1) };
+    NaiveDate::from_ymd_opt(ny, nm, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
+
+fn occurs_on(rule: &AnnouncementRecurrence, date: NaiveDate) -> bool {
+    let weekday = date.weekday().num_days_from_sunday() as u8;
+    match rule.frequency.as_str() {
+        "daily" => true,
+        "weekly" => rule.weekdays.contains(&weekday),
+        "monthly" => match (rule.day_of_month, rule.week_of_month, rule.weekday) {
+            // Months without that day are skipped, like cron.
+            (Some(day), _, _) => date.day() == day,
+            (None, Some(-1), Some(wd)) => wd == weekday && date.day() + 7 > days_in_month(date),
+            (None, Some(week), Some(wd)) => {
+                wd == weekday && ((date.day() - 1) / 7 + 1) as i32 == week
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// First occurrence strictly after `after`. Local times skipped by a DST
+/// change are skipped; repeated ones fire at the earlier instant.
+pub fn next_occurrence(
+    rule: &AnnouncementRecurrence,
+    after: DateTime<Utc>,
+    tz: Tz,
+) -> Option<DateTime<Utc>> {
+    let time = parse_time(&rule.time)?;
+    let start = after.with_timezone(&tz).date_naive();
+    (0..=MAX_LOOKAHEAD_DAYS)
+        .filter_map(|offset| start.checked_add_signed(Duration::days(offset)))
+        .filter(|date| occurs_on(rule, *date))
+        .filter_map(|date| tz.from_local_datetime(&date.and_time(time)).earliest())
+        .map(|at| at.with_timezone(&Utc))
+        .find(|at| *at > after)
+}
+
+/// Stored rule; a malformed column reads as one-shot.
+pub fn recurrence_of(announcement: &Announcement) -> Option<AnnouncementRecurrence> {
+    announcement
+        .recurrence
+        .clone()
+        .and_then(|v| serde_json::from_value(v).ok())
+}
+
+/// Tenant `app_timezone`, else the global one, else UTC.
+pub async fn timezone_for(pool: &DbPool, tenant_id: Option<&str>) -> Tz {
+    let value: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT value FROM settings
+        WHERE key = 'app_timezone'
+          AND TRIM(value) <> ''
+          AND (tenant_id = $1 OR tenant_id IS NULL)
+        ORDER BY tenant_id NULLS LAST
+        LIMIT 1
+        "#,
+    )
+    .bind(tenant_id)
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten();
+    value
+        .and_then(|v| v.trim().parse::<Tz>().ok())
+        .unwrap_or(chrono_tz::UTC)
+}
+
+/// Validates a rule for saving: the stored JSON and the first occurrence at or
+/// after `starts_at`.
+pub async fn prepare_recurrence(
+    pool: &DbPool,
+    tenant_id: Option<&str>,
+    rule: Option<AnnouncementRecurrence>,
+    starts_at: DateTime<Utc>,
+) -> AppResult<(Option<serde_json::Value>, Option<DateTime<Utc>>)> {
+    let Some(rule) = rule else {
+        return Ok((None, None));
+    };
+    let rule = validate_recurrence(rule)?;
+    let tz = timezone_for(pool, tenant_id).await;
+    let Some(first) = next_occurrence(&rule, starts_at - Duration::seconds(1), tz) else {
+        return Err(AppError::Validation("Recurrence never occurs".to_string()));
+    };
+    let value = serde_json::to_value(&rule).map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok((Some(value), Some(first)))
+}
+
+/// When an occurrence published at `published_at` stops showing.
+pub fn occurrence_ends_at(
+    rule: &AnnouncementRecurrence,
+    published_at: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    rule.duration_minutes
+        .map(|m| published_at + Duration::minutes(i64::from(m)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(frequency: &str) -> AnnouncementRecurrence {
+        AnnouncementRecurrence {
+            frequency: frequency.to_string(),
+            time: "08:00".to_string(),
+            weekdays: Vec::new(),
+            day_of_month: None,
+            week_of_month: None,
+            weekday: None,
+            duration_minutes: None,
+        }
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn first_sunday_of_the_month() {
+        let first_sunday = AnnouncementRecurrence {
+            week_of_month: Some(1),
+            weekday: Some(0),
+            ..rule("monthly")
+        };
+        let tz: Tz = "Asia/Jakarta".parse().unwrap();
+
+        // 2026-11-01 is a Sunday; 08:00 WIB is 01:00 UTC.
+        let next = next_occurrence(&first_sunday, utc(2026, 10, 16, 0, 0), tz);
+        assert_eq!(next, Some(utc(2026, 11, 1, 1, 0)));
+        let after = next_occurrence(&first_sunday, next.unwrap(), tz);
+        assert_eq!(after, Some(utc(2026, 12, 6, 1, 0)));
+    }
+
+    #[test]
+    fn last_weekday_and_missing_days() {
+        let last_friday = AnnouncementRecurrence {
+            week_of_month: Some(-1),
+            weekday: Some(5),
+            ..rule("monthly")
+        };
+        assert_eq!(
+            next_occurrence(&last_friday, utc(2026, 10, 1, 0, 0), chrono_tz::UTC),
+            Some(utc(2026, 10, 30, 8, 0))
+        );
+
+        let on_31st = AnnouncementRecurrence {
+            day_of_month: Some(31),
+            ..rule("monthly")
+        };
+        assert_eq!(
+            next_occurrence(&on_31st, utc(2026, 10, 31, 9, 0), chrono_tz::UTC),
+            Some(utc(2026, 12, 31, 8, 0))
+        );
+    }
+
+    #[test]
+    fn weekly_and_daily_step_forward() {
+        let mon_thu = AnnouncementRecurrence {
+            weekdays: vec![1, 4],
+            ..rule("weekly")
+        };
+        // Friday 2026-10-16 -> Monday 19th, then Thursday 22nd.
+        let next = next_occurrence(&mon_thu, utc(2026, 10, 16, 12, 0), chrono_tz::UTC).unwrap();
+        assert_eq!(next, utc(2026, 10, 19, 8, 0));
+        assert_eq!(
+            next_occurrence(&mon_thu, next, chrono_tz::UTC),
+            Some(utc(2026, 10, 22, 8, 0))
+        );
+
+        assert_eq!(
+            next_occurrence(&rule("daily"), utc(2026, 10, 16, 7, 59), chrono_tz::UTC),
+            Some(utc(2026, 10, 16, 8, 0))
+        );
+    }
+
+    #[test]
+    fn rules_are_validated_and_normalized() {
+        let weekly = validate_recurrence(AnnouncementRecurrence {
+            frequency: " Weekly ".into(),
+            time: "8:05".into(),
+            weekdays: vec![4, 1, 4],
+            day_of_month: Some(3),
+            ..rule("weekly")
+        })
+        .unwrap();
+        assert_eq!(weekly.frequency, "weekly");
+        assert_eq!(weekly.time, "08:05");
+        assert_eq!(weekly.weekdays, vec![1, 4]);
+        assert_eq!(weekly.day_of_month, None);
+
+        assert!(validate_recurrence(rule("hourly")).is_err());
+        assert!(validate_recurrence(rule("weekly")).is_err());
+        assert!(validate_recurrence(rule("monthly")).is_err());
+        assert!(validate_recurrence(AnnouncementRecurrence {
+            time: "25:00".into(),
+            ..rule("daily")
+        })
+        .is_err());
+        assert!(validate_recurrence(AnnouncementRecurrence {
+            day_of_month: Some(1),
+            week_of_month: Some(1),
+            weekday: Some(0),
+            ..rule("monthly")
+        })
+        .is_err());
+        assert!(validate_recurrence(AnnouncementRecurrence {
+            duration_minutes: Some(0),
+            ..rule("daily")
+        })
+        .is_err());
+    }
+}
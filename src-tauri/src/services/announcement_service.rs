@@ -1,11 +1,21 @@
 use crate::db::DbPool;
-use crate::models::Announcement;
+use crate::error::{AppError, AppResult};
+use crate::models::{Announcement, BackgroundJob};
+#[cfg(feature = "postgres")]
+use crate::services::announcement_recurrence;
+#[cfg(feature = "postgres")]
+use crate::services::announcement_stats;
+use crate::services::announcement_translations::RecipientTexts;
 use crate::services::encode_unsubscribe_token;
 use crate::services::AuditService;
 use crate::services::NotificationService;
+use crate::services::{JobHandler, JobQueue};
+use async_trait::async_trait;
 use chrono::Utc;
 use std::collections::HashSet;
-use tracing::{error, info, warn};
+use std::sync::Arc;
+#[cfg(feature = "postgres")]
+use tracing::warn;
 
 fn strip_html_tags(input: &str) -> String {
     let mut out = String::with_capacity(input.len());
@@ -30,6 +40,7 @@ fn ann_snapshot_json(ann: &Announcement) -> serde_json::Value {
         "title": ann.title,
         "severity": ann.severity,
         "audience": ann.audience,
+        "audience_targets": ann.audience_targets,
         "mode": ann.mode,
         "format": ann.format,
         "deliver_in_app": ann.deliver_in_app,
@@ -38,6 +49,8 @@ fn ann_snapshot_json(ann: &Announcement) -> serde_json::Value {
         "starts_at": ann.starts_at.to_rfc3339(),
         "ends_at": ann.ends_at.map(|d| d.to_rfc3339()),
         "notified_at": ann.notified_at.map(|d| d.to_rfc3339()),
+        "recurrence": ann.recurrence,
+        "next_occurrence_at": ann.next_occurrence_at.map(|d| d.to_rfc3339()),
         "created_at": ann.created_at.to_rfc3339(),
         "updated_at": ann.updated_at.to_rfc3339(),
     })
@@ -63,86 +76,15 @@ impl AnnouncementScheduler {
         }
     }
 
-    pub async fn start(&self) {
-        let pool = self.pool.clone();
-        let notification_service = self.notification_service.clone();
-        let audit_service = self.audit_service.clone();
-
-        tokio::spawn(async move {
-            info!("Announcement Scheduler started.");
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
-            let mut warned_missing_schema = false;
-
-            loop {
-                interval.tick().await;
-
-                #[cfg(feature = "postgres")]
-                {
-                    // Prevent duplicate processing when running multiple instances.
-                    let mut advisory_conn = match pool.acquire().await {
-                        Ok(c) => c,
-                        Err(e) => {
-                            warn!("Announcement scheduler skipped: failed to acquire DB connection: {}", e);
-                            continue;
-                        }
-                    };
-
-                    let locked: bool =
-                        sqlx::query_scalar("SELECT pg_try_advisory_lock(hashtext($1))")
-                            .bind("announcement_scheduler")
-                            .fetch_one(&mut *advisory_conn)
-                            .await
-                            .unwrap_or(false);
-                    if !locked {
-                        continue;
-                    }
-
-                    if let Err(e) =
-                        Self::process_due(&pool, &notification_service, &audit_service).await
-                    {
-                        if e.contains("relation \"announcements\" does not exist")
-                            || e.contains("relation \"announcement_dismissals\" does not exist")
-                            || e.contains("relation \"users\" does not exist")
-                        {
-                            if !warned_missing_schema {
-                                warned_missing_schema = true;
-                                warn!(
-                                    "Announcement scheduler paused: database schema not migrated yet (missing announcements tables)."
-                                );
-                            }
-                        } else {
-                            error!("Announcement scheduler failed: {}", e);
-                        }
-                    }
-
-                    let _ =
-                        sqlx::query_scalar::<_, bool>("SELECT pg_advisory_unlock(hashtext($1))")
-                            .bind("announcement_scheduler")
-                            .fetch_one(&mut *advisory_conn)
-                            .await;
-                    continue;
-                }
-
-                #[cfg(not(feature = "postgres"))]
-                if let Err(e) =
-                    Self::process_due(&pool, &notification_service, &audit_service).await
-                {
-                    if e.contains("relation \"announcements\" does not exist")
-                        || e.contains("relation \"announcement_dismissals\" does not exist")
-                        || e.contains("relation \"users\" does not exist")
-                    {
-                        if !warned_missing_schema {
-                            warned_missing_schema = true;
-                            warn!(
-                                "Announcement scheduler paused: database schema not migrated yet (missing announcements tables)."
-                            );
-                        }
-                    } else {
-                        error!("Announcement scheduler failed: {}", e);
-                    }
-                }
-            }
-        });
+    /// Publishes due announcements every minute on the job queue.
+    pub async fn schedule(self, queue: &JobQueue) {
+        queue
+            .register_recurring(
+                "announcements.publish",
+                std::time::Duration::from_secs(60),
+                Arc::new(self),
+            )
+            .await;
     }
 
     #[cfg(feature = "postgres")]
@@ -190,7 +132,13 @@ impl AnnouncementScheduler {
 
         #[cfg(feature = "postgres")]
         {
-            if let Some(tid) = announcement.tenant_id.as_deref() {
+            if announcement.audience == "segment" {
+                recipients.extend(
+                    crate::services::announcement_audience::resolve_and_record(pool, announcement)
+                        .await
+                        .unwrap_or_default(),
+                );
+            } else if let Some(tid) = announcement.tenant_id.as_deref() {
                 if announcement.audience == "admins" {
                     recipients.extend(
                         Self::tenant_admin_user_ids(pool, tid)
@@ -210,26 +158,30 @@ impl AnnouncementScheduler {
             }
         }
 
-        let title = announcement.title.clone();
-        let plain = if announcement.format == "html" {
-            strip_html_tags(&announcement.body)
-        } else {
-            announcement.body.clone()
-        };
-        let msg = if plain.chars().count() > 180 {
-            let short: String = plain.chars().take(180).collect();
-            format!("{}…", short)
-        } else {
-            plain
-        };
+        let recipients: Vec<String> = recipients.into_iter().collect();
+        let texts = RecipientTexts::load(pool, announcement, &recipients).await;
+        #[cfg(feature = "postgres")]
+        announcement_stats::record_delivered(pool, announcement, &recipients).await;
 
         for uid in recipients {
+            let (title, body) = texts.for_user(&uid);
+            let plain = if announcement.format == "html" {
+                strip_html_tags(body)
+            } else {
+                body.to_string()
+            };
+            let msg = if plain.chars().count() > 180 {
+                let short: String = plain.chars().take(180).collect();
+                format!("{}…", short)
+            } else {
+                plain
+            };
             let _ = notification_service
                 .create_notification(
                     uid,
                     announcement.tenant_id.clone(),
-                    title.clone(),
-                    msg.clone(),
+                    title.to_string(),
+                    msg,
                     announcement.severity.clone(),
                     "announcement".to_string(),
                     Some(format!("/announcements/{}", announcement.id)),
@@ -250,7 +202,13 @@ impl AnnouncementScheduler {
 
         let mut recipients: HashSet<String> = HashSet::new();
 
-        if let Some(tid) = announcement.tenant_id.as_deref() {
+        if announcement.audience == "segment" {
+            recipients.extend(
+                crate::services::announcement_audience::resolve_and_record(pool, announcement)
+                    .await
+                    .unwrap_or_default(),
+            );
+        } else if let Some(tid) = announcement.tenant_id.as_deref() {
             if announcement.audience == "admins" {
                 recipients.extend(
                     Self::tenant_admin_user_ids(pool, tid)
@@ -298,8 +256,6 @@ impl AnnouncementScheduler {
             return;
         }
 
-        let subject = format!("[Announcement] {}", announcement.title);
-
         let main_domain: Option<String> = sqlx::query_scalar(
             "SELECT value FROM settings WHERE tenant_id IS NULL AND key = 'app_main_domain' LIMIT 1",
         )
@@ -324,7 +280,14 @@ impl AnnouncementScheduler {
                 .await
                 .unwrap_or_default();
 
+        let user_ids: Vec<String> = users.iter().map(|(id, _)| id.clone()).collect();
+        let texts = RecipientTexts::load(pool, announcement, &user_ids).await;
+        announcement_stats::record_delivered(pool, announcement, &user_ids).await;
+
         for (user_id, email) in users {
+            let (title, body) = texts.for_user(&user_id);
+            let subject = format!("[Announcement] {}", title);
+
             let open_url = match (main_domain.as_deref(), slug.as_deref()) {
                 (Some(domain), Some(sl)) => Some(format!(
                     "https://{}/{}/announcements/{}",
@@ -359,12 +322,12 @@ impl AnnouncementScheduler {
 
             let plain_body = {
                 let mut b = String::new();
-                b.push_str(&announcement.title);
+                b.push_str(title);
                 b.push_str("\n\n");
                 if announcement.format == "html" {
-                    b.push_str(&strip_html_tags(&announcement.body));
+                    b.push_str(&strip_html_tags(body));
                 } else {
-                    b.push_str(&announcement.body);
+                    b.push_str(body);
                 }
                 if let Some(url) = open_url.as_deref() {
                     b.push_str("\n\nOpen in app:\n");
@@ -381,10 +344,9 @@ impl AnnouncementScheduler {
 
             let html_body = {
                 let content = if announcement.format == "html" {
-                    announcement.body.clone()
+                    body.to_string()
                 } else {
-                    let esc = announcement
-                        .body
+                    let esc = body
                         .replace('&', "&amp;")
                         .replace('<', "&lt;")
                         .replace('>', "&gt;");
@@ -415,7 +377,7 @@ impl AnnouncementScheduler {
   </div>
 </body>
 </html>"#,
-                    announcement.title, content, open, unsub
+                    title, content, open, unsub
                 )
             };
 
@@ -445,6 +407,7 @@ impl AnnouncementScheduler {
             FROM announcements
             WHERE starts_at <= $1
               AND notified_at IS NULL
+              AND recurrence IS NULL
               AND (ends_at IS NULL OR ends_at > $1)
               AND (deliver_in_app = true OR deliver_email = true)
             ORDER BY starts_at ASC
@@ -498,6 +461,110 @@ impl AnnouncementScheduler {
                 .await;
         }
 
+        #[cfg(feature = "postgres")]
+        Self::process_due_occurrences(pool, notification_service, audit_service, now).await?;
+
+        Ok(())
+    }
+
+    /// Publishes the next occurrence of recurring announcements that are due.
+    #[cfg(feature = "postgres")]
+    async fn process_due_occurrences(
+        pool: &DbPool,
+        notification_service: &NotificationService,
+        audit_service: &AuditService,
+        now: chrono::DateTime<Utc>,
+    ) -> Result<(), String> {
+        let due: Vec<Announcement> = sqlx::query_as(
+            r#"
+            SELECT *
+            FROM announcements
+            WHERE recurrence IS NOT NULL
+              AND next_occurrence_at <= $1
+              AND (ends_at IS NULL OR ends_at > $1)
+              AND (deliver_in_app = true OR deliver_email = true)
+            ORDER BY next_occurrence_at ASC
+            LIMIT 50
+        "#,
+        )
+        .bind(now)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        for ann in due {
+            let Some(rule) = announcement_recurrence::recurrence_of(&ann) else {
+                warn!(
+                    "Announcement {} has an unreadable recurrence rule; stopping the series",
+                    ann.id
+                );
+                let _ =
+                    sqlx::query("UPDATE announcements SET next_occurrence_at = NULL WHERE id = $1")
+                        .bind(&ann.id)
+                        .execute(pool)
+                        .await;
+                continue;
+            };
+            let tz = announcement_recurrence::timezone_for(pool, ann.tenant_id.as_deref()).await;
+            let next = announcement_recurrence::next_occurrence(&rule, now, tz);
+
+            // Guarded on the old next_occurrence_at so an occurrence is only
+            // published once even if a run overlaps.
+            let published: Option<Announcement> = sqlx::query_as(
+                r#"
+                UPDATE announcements
+                SET occurrence = occurrence + 1,
+                    notified_at = $1,
+                    next_occurrence_at = $2,
+                    occurrence_ends_at = $3
+                WHERE id = $4 AND next_occurrence_at = $5
+                RETURNING *
+            "#,
+            )
+            .bind(now)
+            .bind(next)
+            .bind(announcement_recurrence::occurrence_ends_at(&rule, now))
+            .bind(&ann.id)
+            .bind(ann.next_occurrence_at)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            let Some(ann) = published else {
+                continue;
+            };
+
+            Self::send_announcement_notifications(pool, notification_service, &ann).await;
+            Self::send_announcement_emails(pool, notification_service, &ann).await;
+
+            let publish_details = serde_json::json!({
+                "cause": "recurrence",
+                "occurrence": ann.occurrence,
+                "scope": if ann.tenant_id.is_some() { "tenant" } else { "global" },
+                "announcement": ann_snapshot_json(&ann),
+            })
+            .to_string();
+            audit_service
+                .log(
+                    None,
+                    ann.tenant_id.as_deref(),
+                    "publish",
+                    "announcements",
+                    Some(&ann.id),
+                    Some(publish_details.as_str()),
+                    None,
+                )
+                .await;
+        }
+
         Ok(())
     }
 }
+
+#[async_trait]
+impl JobHandler for AnnouncementScheduler {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        Self::process_due(&self.pool, &self.notification_service, &self.audit_service)
+            .await
+            .map_err(AppError::Internal)
+    }
+}
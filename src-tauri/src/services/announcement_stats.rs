@@ -0,0 +1,204 @@
+//! Read-rate analytics for announcements.
+//!
+//! Deliveries and in-app views are recorded per reader and occurrence in
+//! `announcement_receipts`; dismissals come from `announcement_dismissals`.
+//! Tenant announcements break down by the readers' role, global ones by the
+//! tenants they belong to (a member of two tenants counts in both; the totals
+//! count each reader once).
+
+#[cfg(feature = "postgres")]
+use crate::db::DbPool;
+#[cfg(feature = "postgres")]
+use crate::error::AppResult;
+#[cfg(feature = "postgres")]
+use crate::models::{Announcement, AnnouncementSegmentStats, AnnouncementStats};
+
+/// Share of `delivered` readers that viewed it, rounded to 4 decimals.
+pub fn view_rate(viewed: i64, delivered: i64) -> Option<f64> {
+    if delivered <= 0 {
+        return None;
+    }
+    let rate = viewed.min(delivered) as f64 / delivered as f64;
+    Some((rate * 10_000.0).round() / 10_000.0)
+}
+
+/// Marks the current occurrence as delivered to `user_ids`. Best-effort:
+/// analytics never hold up a delivery.
+#[cfg(feature = "postgres")]
+pub async fn record_delivered(pool: &DbPool, announcement: &Announcement, user_ids: &[String]) {
+    if user_ids.is_empty() {
+        return;
+    }
+    let res = sqlx::query(
+        r#"
+        INSERT INTO announcement_receipts (announcement_id, occurrence, user_id, delivered_at)
+        SELECT $1, $2, uid, $4 FROM UNNEST($3::text[]) AS uid
+        ON CONFLICT (announcement_id, occurrence, user_id)
+        DO UPDATE SET delivered_at = COALESCE(announcement_receipts.delivered_at, EXCLUDED.delivered_at)
+        "#,
+    )
+    .bind(&announcement.id)
+    .bind(announcement.occurrence)
+    .bind(user_ids)
+    .bind(chrono::Utc::now())
+    .execute(pool)
+    .await;
+    if let Err(e) = res {
+        tracing::warn!(
+            "Failed to record deliveries of announcement {}: {}",
+            announcement.id,
+            e
+        );
+    }
+}
+
+/// Marks announcements served to a reader as viewed (first view wins).
+#[cfg(feature = "postgres")]
+pub async fn record_views(pool: &DbPool, user_id: &str, announcements: &[Announcement]) {
+    if announcements.is_empty() {
+        return;
+    }
+    let ids: Vec<&str> = announcements.iter().map(|a| a.id.as_str()).collect();
+    let occurrences: Vec<i32> = announcements.iter().map(|a| a.occurrence).collect();
+    let res = sqlx::query(
+        r#"
+        INSERT INTO announcement_receipts (announcement_id, occurrence, user_id, viewed_at)
+        SELECT aid, occ, $3, $4 FROM UNNEST($1::text[], $2::int[]) AS v(aid, occ)
+        ON CONFLICT (announcement_id, occurrence, user_id)
+        DO UPDATE SET viewed_at = COALESCE(announcement_receipts.viewed_at, EXCLUDED.viewed_at)
+        "#,
+    )
+    .bind(&ids)
+    .bind(&occurrences)
+    .bind(user_id)
+    .bind(chrono::Utc::now())
+    .execute(pool)
+    .await;
+    if let Err(e) = res {
+        tracing::warn!("Failed to record announcement views for {}: {}", user_id, e);
+    }
+}
+
+/// Readers of one occurrence with what happened to them.
+#[cfg(feature = "postgres")]
+const READERS_CTE: &str = r#"
+    WITH readers AS (
+        SELECT user_id,
+               bool_or(delivered) AS delivered,
+               bool_or(viewed) AS viewed,
+               bool_or(dismissed) AS dismissed
+        FROM (
+            SELECT user_id, delivered_at IS NOT NULL AS delivered,
+                   viewed_at IS NOT NULL AS viewed, false AS dismissed
+            FROM announcement_receipts
+            WHERE announcement_id = $1 AND occurrence = $2
+            UNION ALL
+            SELECT user_id, false, true, true
+            FROM announcement_dismissals
+            WHERE announcement_id = $1 AND occurrence = $2
+        ) events
+        GROUP BY user_id
+    )
+"#;
+
+/// Counts for `occurrence` (the current one when `None`).
+#[cfg(feature = "postgres")]
+pub async fn stats(
+    pool: &DbPool,
+    announcement: &Announcement,
+    occurrence: Option<i32>,
+) -> AppResult<AnnouncementStats> {
+    let occurrence = occurrence.unwrap_or(announcement.occurrence);
+
+    let (delivered, viewed, dismissed): (i64, i64, i64) = sqlx::query_as(&format!(
+        r#"{}
+        SELECT COUNT(*) FILTER (WHERE delivered),
+               COUNT(*) FILTER (WHERE viewed),
+               COUNT(*) FILTER (WHERE dismissed)
+        FROM readers
+        "#,
+        READERS_CTE
+    ))
+    .bind(&announcement.id)
+    .bind(occurrence)
+    .fetch_one(pool)
+    .await?;
+
+    let (segment_by, mut segments): (&str, Vec<AnnouncementSegmentStats>) =
+        match announcement.tenant_id.as_deref() {
+            Some(tid) => (
+                "role",
+                sqlx::query_as(&format!(
+                    r#"{}
+                    SELECT COALESCE(r.id, tm.role) AS key,
+                           COALESCE(r.name, tm.role) AS label,
+                           COUNT(*) FILTER (WHERE p.delivered) AS delivered,
+                           COUNT(*) FILTER (WHERE p.viewed) AS viewed,
+                           COUNT(*) FILTER (WHERE p.dismissed) AS dismissed
+                    FROM readers p
+                    JOIN tenant_members tm ON tm.user_id = p.user_id AND tm.tenant_id = $3
+                    LEFT JOIN roles r ON r.id = tm.role_id
+                    GROUP BY 1, 2
+                    ORDER BY delivered DESC, label
+                    "#,
+                    READERS_CTE
+                ))
+                .bind(&announcement.id)
+                .bind(occurrence)
+                .bind(tid)
+                .fetch_all(pool)
+                .await?,
+            ),
+            None => (
+                "tenant",
+                sqlx::query_as(&format!(
+                    r#"{}
+                    SELECT t.id AS key,
+                           t.name AS label,
+                           COUNT(*) FILTER (WHERE p.delivered) AS delivered,
+                           COUNT(*) FILTER (WHERE p.viewed) AS viewed,
+                           COUNT(*) FILTER (WHERE p.dismissed) AS dismissed
+                    FROM readers p
+                    JOIN tenant_members tm ON tm.user_id = p.user_id
+                    JOIN tenants t ON t.id = tm.tenant_id
+                    GROUP BY t.id, t.name
+                    ORDER BY delivered DESC, label
+                    "#,
+                    READERS_CTE
+                ))
+                .bind(&announcement.id)
+                .bind(occurrence)
+                .fetch_all(pool)
+                .await?,
+            ),
+        };
+    for segment in &mut segments {
+        segment.view_rate = view_rate(segment.viewed, segment.delivered);
+    }
+
+    Ok(AnnouncementStats {
+        announcement_id: announcement.id.clone(),
+        occurrence,
+        delivered,
+        viewed,
+        dismissed,
+        view_rate: view_rate(viewed, delivered),
+        segment_by: segment_by.to_string(),
+        segments,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_rate_needs_deliveries_and_is_capped() {
+        assert_eq!(view_rate(0, 0), None);
+        assert_eq!(view_rate(3, 0), None);
+        assert_eq!(view_rate(0, 4), Some(0.0));
+        assert_eq!(view_rate(1, 3), Some(0.3333));
+        // Readers who saw it in the app without a delivery (e.g. joined later).
+        assert_eq!(view_rate(5, 4), Some(1.0));
+    }
+}
@@ -0,0 +1,268 @@
+//! Per-locale variants of announcements.
+//!
+//! `announcements.translations` holds a title/body per locale next to the
+//! default-language `title`/`body`. Readers get the variant closest to their
+//! locale (see `locale::best_match`) and the default text when none matches.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{Announcement, AnnouncementTranslation};
+use crate::services::locale::{best_match, normalize_locale};
+use std::collections::{BTreeMap, HashMap};
+
+pub type Translations = BTreeMap<String, AnnouncementTranslation>;
+
+/// Upper bound on variants per announcement.
+pub const MAX_TRANSLATIONS: usize = 20;
+
+/// Validates submitted variants and returns what to store in `translations`.
+/// Locale keys are canonicalized; an empty map stores nothing.
+pub fn prepare_translations(
+    translations: Option<Translations>,
+) -> AppResult<Option<serde_json::Value>> {
+    let Some(translations) = translations else {
+        return Ok(None);
+    };
+    if translations.len() > MAX_TRANSLATIONS {
+        return Err(AppError::Validation(format!(
+            "At most {} translations per announcement",
+            MAX_TRANSLATIONS
+        )));
+    }
+
+    let mut out = Translations::new();
+    for (tag, variant) in translations {
+        let Some(locale) = normalize_locale(&tag) else {
+            return Err(AppError::Validation(format!("Invalid locale '{}'", tag)));
+        };
+        let title = variant.title.trim().to_string();
+        if title.is_empty() || variant.body.trim().is_empty() {
+            return Err(AppError::Validation(format!(
+                "Translation '{}' needs a title and a body",
+                locale
+            )));
+        }
+        if out.contains_key(&locale) {
+            return Err(AppError::Validation(format!(
+                "Duplicate translation for '{}'",
+                locale
+            )));
+        }
+        out.insert(
+            locale,
+            AnnouncementTranslation {
+                title,
+                body: variant.body,
+            },
+        );
+    }
+
+    if out.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_value(&out)
+        .map(Some)
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+/// Stored variants; a malformed column reads as none.
+pub fn translations_of(announcement: &Announcement) -> Translations {
+    announcement
+        .translations
+        .clone()
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Title and body for a reader in `locale`, falling back to the default text.
+pub fn text_for<'a>(
+    announcement: &'a Announcement,
+    translations: &'a Translations,
+    locale: Option<&str>,
+) -> (&'a str, &'a str) {
+    locale
+        .and_then(|l| best_match(translations.keys().map(String::as_str), l))
+        .and_then(|tag| translations.get(tag))
+        .map(|v| (v.title.as_str(), v.body.as_str()))
+        .unwrap_or((announcement.title.as_str(), announcement.body.as_str()))
+}
+
+/// Swaps in the reader's variant, as served by the in-app endpoints.
+pub fn localize(announcement: &mut Announcement, locale: Option<&str>) {
+    let translations = translations_of(announcement);
+    let (title, body) = text_for(announcement, &translations, locale);
+    let (title, body) = (title.to_string(), body.to_string());
+    announcement.title = title;
+    announcement.body = body;
+}
+
+/// Localizes announcements served to one reader. Lookup failures leave the
+/// default text in place.
+#[cfg(feature = "postgres")]
+pub async fn localize_for_reader(
+    pool: &DbPool,
+    user_id: &str,
+    tenant_id: Option<&str>,
+    announcements: &mut [Announcement],
+) {
+    if announcements.iter().all(|a| a.translations.is_none()) {
+        return;
+    }
+    let locale = crate::services::locale::reader_locale(pool, user_id, tenant_id)
+        .await
+        .unwrap_or_default();
+    for announcement in announcements {
+        localize(announcement, locale.as_deref());
+    }
+}
+
+/// Title and body per recipient of a delivery (in-app notification, email).
+pub struct RecipientTexts<'a> {
+    announcement: &'a Announcement,
+    translations: Translations,
+    locales: HashMap<String, String>,
+}
+
+impl<'a> RecipientTexts<'a> {
+    /// Looks up the recipients' locales, only when there are variants to pick
+    /// from. Lookup failures send everyone the default text.
+    pub async fn load(pool: &DbPool, announcement: &'a Announcement, user_ids: &[String]) -> Self {
+        let translations = translations_of(announcement);
+        #[cfg(feature = "postgres")]
+        let locales = if translations.is_empty() {
+            HashMap::new()
+        } else {
+            crate::services::locale::reader_locales(
+                pool,
+                user_ids,
+                announcement.tenant_id.as_deref(),
+            )
+            .await
+            .unwrap_or_default()
+        };
+        #[cfg(not(feature = "postgres"))]
+        let locales = {
+            let _ = (pool, user_ids);
+            HashMap::new()
+        };
+        Self {
+            announcement,
+            translations,
+            locales,
+        }
+    }
+
+    pub fn for_user(&self, user_id: &str) -> (&str, &str) {
+        text_for(
+            self.announcement,
+            &self.translations,
+            self.locales.get(user_id).map(String::as_str),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn variant(title: &str, body: &str) -> AnnouncementTranslation {
+        AnnouncementTranslation {
+            title: title.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    fn announcement(translations: Option<serde_json::Value>) -> Announcement {
+        let now = Utc::now();
+        Announcement {
+            id: "a1".into(),
+            tenant_id: None,
+            created_by: None,
+            cover_file_id: None,
+            title: "Maintenance tonight".into(),
+            body: "Service pauses at 23:00.".into(),
+            severity: "info".into(),
+            audience: "all".into(),
+            audience_targets: None,
+            translations,
+            mode: "post".into(),
+            format: "plain".into(),
+            deliver_in_app: true,
+            deliver_email: false,
+            deliver_email_force: false,
+            starts_at: now,
+            ends_at: None,
+            notified_at: None,
+            recurrence: None,
+            occurrence: 0,
+            next_occurrence_at: None,
+            occurrence_ends_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn prepare_canonicalizes_and_validates() {
+        let stored = prepare_translations(Some(Translations::from([(
+            "ID_id".to_string(),
+            variant("  Pemeliharaan malam ini ", "Layanan jeda pukul 23:00."),
+        )])))
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            stored,
+            serde_json::json!({
+                "id-ID": { "title": "Pemeliharaan malam ini", "body": "Layanan jeda pukul 23:00." }
+            })
+        );
+
+        assert_eq!(
+            prepare_translations(Some(Translations::new())).unwrap(),
+            None
+        );
+        assert!(prepare_translations(Some(Translations::from([(
+            "indonesian".to_string(),
+            variant("a", "b"),
+        )])))
+        .is_err());
+        assert!(prepare_translations(Some(Translations::from([(
+            "id".to_string(),
+            variant("a", "  "),
+        )])))
+        .is_err());
+        assert!(prepare_translations(Some(Translations::from([
+            ("id-id".to_string(), variant("a", "b")),
+            ("id-ID".to_string(), variant("c", "d")),
+        ])))
+        .is_err());
+    }
+
+    #[test]
+    fn readers_get_their_variant_or_the_default() {
+        let mut ann = announcement(Some(serde_json::json!({
+            "id": { "title": "Pemeliharaan malam ini", "body": "Layanan jeda pukul 23:00." }
+        })));
+        let translations = translations_of(&ann);
+
+        assert_eq!(
+            text_for(&ann, &translations, Some("id-ID")).0,
+            "Pemeliharaan malam ini"
+        );
+        assert_eq!(
+            text_for(&ann, &translations, Some("en-US")).0,
+            "Maintenance tonight"
+        );
+        assert_eq!(text_for(&ann, &translations, None).0, "Maintenance tonight");
+
+        localize(&mut ann, Some("id"));
+        assert_eq!(ann.body, "Layanan jeda pukul 23:00.");
+    }
+
+    #[test]
+    fn malformed_translations_read_as_none() {
+        let ann = announcement(Some(serde_json::json!(["not", "a", "map"])));
+        assert!(translations_of(&ann).is_empty());
+    }
+}
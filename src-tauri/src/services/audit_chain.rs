@@ -0,0 +1,337 @@
+//! Tamper-evident chaining of audit entries.
+//!
+//! Every entry in `audit_logs` gets the next sequence number of a single
+//! global chain, the hash of the entry before it (`prev_hash`) and its own
+//! hash (`entry_hash`): SHA-256 over the sequence number, `prev_hash` and the
+//! entry's content. Editing an entry breaks its hash; deleting one leaves a
+//! gap in the sequence; deleting the newest ones leaves `audit_chain_head`
+//! ahead of the table. `ChainVerifier` walks the chain and reports all three.
+//!
+//! Retention drops whole partitions from the old end, so the oldest surviving
+//! entry is taken as the start of the chain without checking its `prev_hash`.
+
+use crate::models::{AuditChainIssue, AuditChainIssueKind, AuditChainReport};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+/// `prev_hash` of the first entry in the chain.
+pub const CHAIN_GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+/// Issues kept in a report; past this the report only says there were more.
+pub const MAX_CHAIN_ISSUES: usize = 100;
+
+/// The columns of an audit entry its hash covers, as stored.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ChainEntry {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub tenant_id: Option<String>,
+    pub action: String,
+    pub resource: String,
+    pub resource_id: Option<String>,
+    pub details: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+}
+
+/// A chained entry read back for verification.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ChainRow {
+    pub chain_seq: i64,
+    pub prev_hash: String,
+    pub entry_hash: String,
+    #[sqlx(flatten)]
+    pub entry: ChainEntry,
+}
+
+/// `value` with object keys sorted, so the hash doesn't depend on the order
+/// the database hands them back in.
+fn canonical(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let mut out = Map::new();
+            for key in keys {
+                out.insert(key.clone(), canonical(&map[key]));
+            }
+            Value::Object(out)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonical).collect()),
+        other => other.clone(),
+    }
+}
+
+pub fn chain_hash(seq: i64, prev_hash: &str, entry: &ChainEntry) -> String {
+    // A JSON array keeps field boundaries unambiguous (`"ab", "c"` vs `"a", "bc"`).
+    let content = json!([
+        seq,
+        prev_hash,
+        entry.id,
+        entry
+            .created_at
+            .to_rfc3339_opts(SecondsFormat::Micros, true),
+        entry.user_id,
+        entry.tenant_id,
+        entry.action,
+        entry.resource,
+        entry.resource_id,
+        entry.details,
+        entry.ip_address,
+        entry.old_value.as_ref().map(canonical),
+        entry.new_value.as_ref().map(canonical),
+    ]);
+    let mut hasher = Sha256::new();
+    hasher.update(content.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Checks chained entries fed to it in sequence order.
+#[derive(Debug, Default)]
+pub struct ChainVerifier {
+    prev: Option<(i64, String)>,
+    first_seq: Option<i64>,
+    checked: i64,
+    issues: Vec<AuditChainIssue>,
+    issues_truncated: bool,
+}
+
+impl ChainVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sequence number of the last entry checked, to continue from.
+    pub fn last_seq(&self) -> Option<i64> {
+        self.prev.as_ref().map(|(seq, _)| *seq)
+    }
+
+    fn report(
+        &mut self,
+        kind: AuditChainIssueKind,
+        seq: i64,
+        log_id: Option<&str>,
+        detail: String,
+    ) {
+        if self.issues.len() >= MAX_CHAIN_ISSUES {
+            self.issues_truncated = true;
+            return;
+        }
+        self.issues.push(AuditChainIssue {
+            kind,
+            seq,
+            log_id: log_id.map(str::to_string),
+            detail,
+        });
+    }
+
+    pub fn check(&mut self, row: &ChainRow) {
+        let seq = row.chain_seq;
+        let id = Some(row.entry.id.as_str());
+        self.checked += 1;
+        self.first_seq.get_or_insert(seq);
+
+        if chain_hash(seq, &row.prev_hash, &row.entry) != row.entry_hash {
+            self.report(
+                AuditChainIssueKind::Modified,
+                seq,
+                id,
+                "Stored hash does not match the entry's content".to_string(),
+            );
+        }
+
+        match self.prev.take() {
+            Some((prev_seq, _)) if seq == prev_seq => self.report(
+                AuditChainIssueKind::Duplicate,
+                seq,
+                id,
+                format!("Sequence number {} is used more than once", seq),
+            ),
+            Some((prev_seq, _)) if seq > prev_seq + 1 => self.report(
+                AuditChainIssueKind::Gap,
+                seq,
+                id,
+                format!("Entries {} to {} are missing", prev_seq + 1, seq - 1),
+            ),
+            Some((_, prev_hash)) if row.prev_hash != prev_hash => self.report(
+                AuditChainIssueKind::BrokenLink,
+                seq,
+                id,
+                "Entry does not point at the hash of the entry before it".to_string(),
+            ),
+            None if seq == 1 && row.prev_hash != CHAIN_GENESIS => self.report(
+                AuditChainIssueKind::BrokenLink,
+                seq,
+                id,
+                "First entry does not start from the genesis hash".to_string(),
+            ),
+            _ => {}
+        }
+
+        self.prev = Some((seq, row.entry_hash.clone()));
+    }
+
+    /// Compares the newest entry with the chain head and builds the report.
+    pub fn finish(mut self, head_seq: i64, head_hash: &str, unchained: i64) -> AuditChainReport {
+        match self.prev.clone() {
+            Some((seq, _)) if head_seq > seq => self.report(
+                AuditChainIssueKind::Truncated,
+                head_seq,
+                None,
+                format!(
+                    "Entries {} to {} at the end of the chain are missing",
+                    seq + 1,
+                    head_seq
+                ),
+            ),
+            Some((seq, hash)) if head_seq == seq && head_hash != hash => self.report(
+                AuditChainIssueKind::Truncated,
+                seq,
+                None,
+                "Newest entry does not match the chain head".to_string(),
+            ),
+            None if head_seq > 0 => self.report(
+                AuditChainIssueKind::Truncated,
+                head_seq,
+                None,
+                format!(
+                    "No chained entries left, but the chain head is at {}",
+                    head_seq
+                ),
+            ),
+            _ => {}
+        }
+
+        AuditChainReport {
+            valid: self.issues.is_empty(),
+            checked: self.checked,
+            first_seq: self.first_seq,
+            last_seq: self.last_seq(),
+            head_seq,
+            unchained,
+            issues: self.issues,
+            issues_truncated: self.issues_truncated,
+            verified_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, details: &str) -> ChainEntry {
+        ChainEntry {
+            id: id.to_string(),
+            user_id: Some("u1".to_string()),
+            tenant_id: None,
+            action: "update".to_string(),
+            resource: "customers".to_string(),
+            resource_id: Some("c1".to_string()),
+            details: Some(details.to_string()),
+            ip_address: Some("10.0.0.5".to_string()),
+            created_at: DateTime::parse_from_rfc3339("2026-04-21T09:00:00.123456Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            old_value: Some(json!({"b": 1, "a": {"y": 2, "x": 1}})),
+            new_value: Some(json!({"b": 2, "a": {"y": 3, "x": 1}})),
+        }
+    }
+
+    fn chain(len: usize) -> Vec<ChainRow> {
+        let mut prev = CHAIN_GENESIS.to_string();
+        (1..=len as i64)
+            .map(|seq| {
+                let entry = entry(&format!("log-{}", seq), "changed plan");
+                let entry_hash = chain_hash(seq, &prev, &entry);
+                ChainRow {
+                    chain_seq: seq,
+                    prev_hash: std::mem::replace(&mut prev, entry_hash.clone()),
+                    entry_hash,
+                    entry,
+                }
+            })
+            .collect()
+    }
+
+    fn verify(rows: &[ChainRow], head_seq: i64, head_hash: &str) -> AuditChainReport {
+        let mut verifier = ChainVerifier::new();
+        rows.iter().for_each(|row| verifier.check(row));
+        verifier.finish(head_seq, head_hash, 0)
+    }
+
+    fn kinds(report: &AuditChainReport) -> Vec<AuditChainIssueKind> {
+        report.issues.iter().map(|i| i.kind).collect()
+    }
+
+    #[test]
+    fn hash_covers_content_not_key_order() {
+        let a = entry("log-1", "changed plan");
+        let mut b = a.clone();
+        b.old_value = Some(json!({"a": {"x": 1, "y": 2}, "b": 1}));
+        assert_eq!(
+            chain_hash(1, CHAIN_GENESIS, &a),
+            chain_hash(1, CHAIN_GENESIS, &b)
+        );
+
+        b.details = Some("changed plan!".to_string());
+        assert_ne!(
+            chain_hash(1, CHAIN_GENESIS, &a),
+            chain_hash(1, CHAIN_GENESIS, &b)
+        );
+        assert_ne!(
+            chain_hash(1, CHAIN_GENESIS, &a),
+            chain_hash(2, CHAIN_GENESIS, &a)
+        );
+    }
+
+    #[test]
+    fn intact_chain_verifies() {
+        let rows = chain(3);
+        let report = verify(&rows, 3, &rows[2].entry_hash);
+        assert!(report.valid, "{:?}", report.issues);
+        assert_eq!(report.checked, 3);
+        assert_eq!((report.first_seq, report.last_seq), (Some(1), Some(3)));
+
+        // Retention removed the oldest entries: the rest still verifies.
+        let report = verify(&rows[1..], 3, &rows[2].entry_hash);
+        assert!(report.valid, "{:?}", report.issues);
+    }
+
+    #[test]
+    fn detects_modified_missing_and_truncated_entries() {
+        let mut rows = chain(4);
+        let head = rows[3].entry_hash.clone();
+
+        rows[1].entry.details = Some("nothing to see".to_string());
+        assert_eq!(
+            kinds(&verify(&rows, 4, &head)),
+            [AuditChainIssueKind::Modified]
+        );
+
+        let mut rows = chain(4);
+        rows.remove(1);
+        let report = verify(&rows, 4, &head);
+        assert_eq!(kinds(&report), [AuditChainIssueKind::Gap]);
+        assert_eq!(report.issues[0].seq, 3);
+
+        let rows = chain(4);
+        assert_eq!(
+            kinds(&verify(&rows[..3], 4, &head)),
+            [AuditChainIssueKind::Truncated]
+        );
+    }
+
+    #[test]
+    fn detects_rehashed_entries_that_break_the_link() {
+        let mut rows = chain(3);
+        // Rewritten with a fresh hash, but the next entry still points at the old one.
+        rows[1].entry.details = Some("nothing to see".to_string());
+        rows[1].entry_hash = chain_hash(2, &rows[1].prev_hash, &rows[1].entry);
+        let report = verify(&rows, 3, &rows[2].entry_hash);
+        assert_eq!(kinds(&report), [AuditChainIssueKind::BrokenLink]);
+        assert_eq!(report.issues[0].seq, 3);
+    }
+}
@@ -1,17 +1,469 @@
 use crate::db::connection::DbPool;
+use crate::db::QueryRouter;
 use crate::error::{AppError, AppResult};
 // audit_service.rs implies these might be needed if not fully qualified
-use chrono::Utc;
+use chrono::{SubsecRound, Utc};
 // We need to import PlanService but it might cause circular deps if not careful.
 // Actually PlanService depends on DbPool, not AuditService.
 // But UserService depends on AuditService.
 // If PlanService depends on nothing complex, it is fine.
+use crate::models::{
+    AuditChainReport, AuditExportFormat, AuditFilterPreset, AuditLogExport, AuditLogFilter,
+    AuditLogResponse, SaveAuditFilterPresetDto,
+};
+use crate::services::audit_chain::{
+    chain_hash, ChainEntry, ChainRow, ChainVerifier, CHAIN_GENESIS,
+};
+use crate::services::audit_sink::{AuditEvent, AuditStream};
 use crate::services::plan_service::PlanService;
+use serde::Serialize;
+use serde_json::{Map, Value};
+#[cfg(feature = "postgres")]
+use sqlx::Postgres;
+use sqlx::QueryBuilder;
+#[cfg(feature = "sqlite")]
+use sqlx::Sqlite;
+use std::collections::BTreeSet;
+
+/// Chain entries read per query while verifying.
+const CHAIN_BATCH_SIZE: i64 = 1000;
+
+/// Stand-in for secret values in `old_value`/`new_value`.
+pub const REDACTED: &str = "[redacted]";
+
+/// Fields left out of diffs: they change on every write.
+const IGNORED_FIELDS: &[&str] = &["updated_at", "config_updated_at"];
+
+/// Whether a field name looks like it holds a credential.
+pub fn is_secret_field(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    [
+        "password",
+        "secret",
+        "token",
+        "api_key",
+        "apikey",
+        "private_key",
+        "access_key",
+        "server_key",
+        "credential",
+    ]
+    .iter()
+    .any(|needle| name.contains(needle))
+}
+
+/// Replaces non-null secret fields, at any depth, with `REDACTED`.
+fn redact(map: &mut Map<String, Value>) {
+    for (key, v) in map.iter_mut() {
+        if is_secret_field(key) {
+            if !v.is_null() {
+                *v = Value::String(REDACTED.to_string());
+            }
+        } else {
+            redact_nested(v);
+        }
+    }
+}
+
+fn redact_nested(value: &mut Value) {
+    match value {
+        Value::Object(map) => redact(map),
+        Value::Array(items) => items.iter_mut().for_each(redact_nested),
+        _ => {}
+    }
+}
+
+/// Before/after values of an update, kept in `audit_logs.old_value` and
+/// `new_value`. Only top-level fields that changed are kept.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditChange {
+    pub old_value: Map<String, Value>,
+    pub new_value: Map<String, Value>,
+}
+
+impl AuditChange {
+    /// Diffs the serialized forms of `before` and `after`. Secret fields are
+    /// redacted; a changed secret still shows up as changed.
+    pub fn between<T: Serialize>(before: &T, after: &T) -> Self {
+        let (Ok(Value::Object(before)), Ok(Value::Object(after))) =
+            (serde_json::to_value(before), serde_json::to_value(after))
+        else {
+            return Self::default();
+        };
+
+        let mut change = Self::default();
+        let fields: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+        for field in fields {
+            if IGNORED_FIELDS.contains(&field.as_str()) {
+                continue;
+            }
+            let old = before.get(field).cloned().unwrap_or(Value::Null);
+            let new = after.get(field).cloned().unwrap_or(Value::Null);
+            if old != new {
+                change.old_value.insert(field.clone(), old);
+                change.new_value.insert(field.clone(), new);
+            }
+        }
+        redact(&mut change.old_value);
+        redact(&mut change.new_value);
+        change
+    }
+
+    /// Records a change to a secret that is not serialized (or not compared),
+    /// such as a stored password hash.
+    pub fn with_secret(mut self, field: &str) -> Self {
+        self.old_value
+            .insert(field.to_string(), Value::String(REDACTED.to_string()));
+        self.new_value
+            .insert(field.to_string(), Value::String(REDACTED.to_string()));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.old_value.is_empty() && self.new_value.is_empty()
+    }
+}
+
+/// Pairs up the stored `old_value`/`new_value` per field for the audit API.
+pub fn field_changes(
+    old_value: Option<&Value>,
+    new_value: Option<&Value>,
+) -> Vec<crate::models::AuditFieldChange> {
+    let empty = Map::new();
+    let old = old_value.and_then(Value::as_object).unwrap_or(&empty);
+    let new = new_value.and_then(Value::as_object).unwrap_or(&empty);
+    let fields: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    fields
+        .into_iter()
+        .map(|field| crate::models::AuditFieldChange {
+            field: field.clone(),
+            old: old.get(field).cloned().unwrap_or(Value::Null),
+            new: new.get(field).cloned().unwrap_or(Value::Null),
+        })
+        .collect()
+}
+
+/// Most entries one export holds.
+pub const EXPORT_LIMIT: i64 = 50_000;
+
+/// Filter keys a saved preset may hold.
+const PRESET_FILTER_KEYS: &[&str] = &[
+    "search",
+    "actor",
+    "user_id",
+    "tenant_id",
+    "customer_id",
+    "resource",
+    "resource_id",
+    "action",
+    "ip_address",
+    "date_from",
+    "date_to",
+];
+
+/// Keeps the known, non-empty string filters of a preset.
+pub fn preset_filters(filters: &Value) -> AppResult<Value> {
+    let Some(filters) = filters.as_object() else {
+        return Err(AppError::Validation(
+            "Preset filters must be an object".to_string(),
+        ));
+    };
+    let kept: Map<String, Value> = filters
+        .iter()
+        .filter(|(key, _)| PRESET_FILTER_KEYS.contains(&key.as_str()))
+        .filter_map(|(key, value)| {
+            let value = value.as_str()?.trim();
+            (!value.is_empty()).then(|| (key.clone(), Value::String(value.to_string())))
+        })
+        .collect();
+    Ok(Value::Object(kept))
+}
+
+/// `LIKE` pattern for values starting with `prefix`.
+fn like_prefix(prefix: &str) -> String {
+    let escaped = prefix
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("{}%", escaped)
+}
+
+#[cfg(feature = "postgres")]
+fn push_filters(qb: &mut QueryBuilder<'_, Postgres>, filter: &AuditLogFilter) {
+    // Ids are compared as uuids so the (user_id|tenant_id, created_at) indexes apply.
+    if let Some(uid) = &filter.user_id {
+        match uuid::Uuid::parse_str(uid) {
+            Ok(uid) => {
+                qb.push(" AND l.user_id = ");
+                qb.push_bind(uid);
+            }
+            Err(_) => {
+                qb.push(" AND FALSE");
+            }
+        }
+    }
+
+    if let Some(tid) = &filter.tenant_id {
+        match uuid::Uuid::parse_str(tid) {
+            Ok(tid) => {
+                qb.push(" AND l.tenant_id = ");
+                qb.push_bind(tid);
+            }
+            Err(_) => {
+                qb.push(" AND FALSE");
+            }
+        }
+    }
+
+    if let Some(customer_id) = &filter.customer_id {
+        qb.push(" AND (");
+        qb.push(" (l.resource = 'customers' AND l.resource_id = ");
+        qb.push_bind(customer_id.clone());
+        qb.push(")");
+        qb.push(" OR (l.resource = 'customer_locations' AND EXISTS (SELECT 1 FROM customer_locations cl WHERE cl.id::text = l.resource_id AND cl.customer_id::text = ");
+        qb.push_bind(customer_id.clone());
+        qb.push("))");
+        qb.push(" OR (l.resource = 'customer_subscriptions' AND EXISTS (SELECT 1 FROM customer_subscriptions cs WHERE cs.id::text = l.resource_id AND cs.customer_id::text = ");
+        qb.push_bind(customer_id.clone());
+        qb.push("))");
+        qb.push(" OR (l.resource = 'customer_users' AND EXISTS (SELECT 1 FROM customer_users cu WHERE cu.id::text = l.resource_id AND cu.customer_id::text = ");
+        qb.push_bind(customer_id.clone());
+        qb.push("))");
+        qb.push(")");
+    }
+
+    if let Some(resource) = &filter.resource {
+        qb.push(" AND l.resource = ");
+        qb.push_bind(resource.clone());
+    }
+
+    if let Some(resource_id) = &filter.resource_id {
+        qb.push(" AND l.resource_id = ");
+        qb.push_bind(resource_id.clone());
+    }
+
+    if let Some(action) = &filter.action {
+        qb.push(" AND l.action = ");
+        qb.push_bind(action.clone());
+    }
+
+    if let Some(date_from) = filter.date_from {
+        qb.push(" AND l.created_at >= ");
+        qb.push_bind(date_from);
+    }
+
+    if let Some(date_to) = filter.date_to {
+        qb.push(" AND l.created_at <= ");
+        qb.push_bind(date_to);
+    }
+
+    if let Some(actor) = filter
+        .actor
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+    {
+        if let Ok(uid) = uuid::Uuid::parse_str(actor) {
+            qb.push(" AND l.user_id = ");
+            qb.push_bind(uid);
+        } else {
+            let pattern = format!("%{}%", actor);
+            qb.push(" AND (u.name ILIKE ");
+            qb.push_bind(pattern.clone());
+            qb.push(" OR u.email ILIKE ");
+            qb.push_bind(pattern);
+            qb.push(")");
+        }
+    }
+
+    if let Some(ip) = filter
+        .ip_address
+        .as_deref()
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+    {
+        match ip.strip_suffix('*') {
+            Some(prefix) => {
+                qb.push(" AND l.ip_address LIKE ");
+                qb.push_bind(like_prefix(prefix));
+            }
+            None => {
+                qb.push(" AND l.ip_address = ");
+                qb.push_bind(ip.to_string());
+            }
+        }
+    }
+
+    if let Some(search) = &filter.search {
+        let pattern = format!("%{}%", search);
+        qb.push(" AND (l.resource ILIKE ");
+        qb.push_bind(pattern.clone());
+        qb.push(" OR l.resource_id ILIKE ");
+        qb.push_bind(pattern.clone());
+        qb.push(" OR l.details ILIKE ");
+        qb.push_bind(pattern.clone());
+        qb.push(" OR u.name ILIKE ");
+        qb.push_bind(pattern);
+        qb.push(")");
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn push_filters(qb: &mut QueryBuilder<'_, Sqlite>, filter: &AuditLogFilter) {
+    if let Some(uid) = &filter.user_id {
+        qb.push(" AND l.user_id = ");
+        qb.push_bind(uid.clone());
+    }
+    if let Some(tid) = &filter.tenant_id {
+        qb.push(" AND l.tenant_id = ");
+        qb.push_bind(tid.clone());
+    }
+    if let Some(customer_id) = &filter.customer_id {
+        qb.push(" AND (");
+        qb.push(" (l.resource = 'customers' AND l.resource_id = ");
+        qb.push_bind(customer_id.clone());
+        qb.push(")");
+        qb.push(" OR (l.resource = 'customer_locations' AND EXISTS (SELECT 1 FROM customer_locations cl WHERE cl.id = l.resource_id AND cl.customer_id = ");
+        qb.push_bind(customer_id.clone());
+        qb.push("))");
+        qb.push(" OR (l.resource = 'customer_subscriptions' AND EXISTS (SELECT 1 FROM customer_subscriptions cs WHERE cs.id = l.resource_id AND cs.customer_id = ");
+        qb.push_bind(customer_id.clone());
+        qb.push("))");
+        qb.push(" OR (l.resource = 'customer_users' AND EXISTS (SELECT 1 FROM customer_users cu WHERE cu.id = l.resource_id AND cu.customer_id = ");
+        qb.push_bind(customer_id.clone());
+        qb.push("))");
+        qb.push(")");
+    }
+    if let Some(resource) = &filter.resource {
+        qb.push(" AND l.resource = ");
+        qb.push_bind(resource.clone());
+    }
+    if let Some(resource_id) = &filter.resource_id {
+        qb.push(" AND l.resource_id = ");
+        qb.push_bind(resource_id.clone());
+    }
+    if let Some(action) = &filter.action {
+        qb.push(" AND l.action = ");
+        qb.push_bind(action.clone());
+    }
+    if let Some(date_from) = filter.date_from {
+        qb.push(" AND l.created_at >= ");
+        qb.push_bind(date_from.to_rfc3339());
+    }
+    if let Some(date_to) = filter.date_to {
+        qb.push(" AND l.created_at <= ");
+        qb.push_bind(date_to.to_rfc3339());
+    }
+    if let Some(actor) = filter
+        .actor
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+    {
+        let pattern = format!("%{}%", actor);
+        qb.push(" AND (l.user_id = ");
+        qb.push_bind(actor.to_string());
+        qb.push(" OR u.name LIKE ");
+        qb.push_bind(pattern.clone());
+        qb.push(" OR u.email LIKE ");
+        qb.push_bind(pattern);
+        qb.push(")");
+    }
+    if let Some(ip) = filter
+        .ip_address
+        .as_deref()
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+    {
+        match ip.strip_suffix('*') {
+            Some(prefix) => {
+                qb.push(" AND l.ip_address LIKE ");
+                qb.push_bind(like_prefix(prefix));
+                qb.push(" ESCAPE '\\'");
+            }
+            None => {
+                qb.push(" AND l.ip_address = ");
+                qb.push_bind(ip.to_string());
+            }
+        }
+    }
+    if let Some(search) = &filter.search {
+        let pattern = format!("%{}%", search);
+        qb.push(" AND (l.resource LIKE ");
+        qb.push_bind(pattern.clone());
+        qb.push(" OR l.resource_id LIKE ");
+        qb.push_bind(pattern.clone());
+        qb.push(" OR l.details LIKE ");
+        qb.push_bind(pattern.clone());
+        qb.push(" OR u.name LIKE ");
+        qb.push_bind(pattern);
+        qb.push(")");
+    }
+}
+
+const CSV_HEADER: &str = "created_at,id,tenant_id,tenant_name,user_id,user_name,user_email,action,resource,resource_id,resource_name,ip_address,details,old_value,new_value,chain_seq,entry_hash";
+
+/// One CSV cell. Cells that a spreadsheet would run as a formula get a
+/// leading `'`.
+pub(crate) fn csv_cell(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+pub fn logs_to_csv(logs: &[AuditLogResponse]) -> String {
+    let json = |v: &Option<Value>| v.as_ref().map(Value::to_string).unwrap_or_default();
+    let mut out = String::from(CSV_HEADER);
+    out.push('\n');
+    for log in logs {
+        let cells = [
+            log.created_at.to_rfc3339(),
+            log.id.clone(),
+            log.tenant_id.clone().unwrap_or_default(),
+            log.tenant_name.clone().unwrap_or_default(),
+            log.user_id.clone().unwrap_or_default(),
+            log.user_name.clone().unwrap_or_default(),
+            log.user_email.clone().unwrap_or_default(),
+            log.action.clone(),
+            log.resource.clone(),
+            log.resource_id.clone().unwrap_or_default(),
+            log.resource_name.clone().unwrap_or_default(),
+            log.ip_address.clone().unwrap_or_default(),
+            log.details.clone().unwrap_or_default(),
+            json(&log.old_value),
+            json(&log.new_value),
+            log.chain_seq.map(|s| s.to_string()).unwrap_or_default(),
+            log.entry_hash.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = cells.iter().map(|c| csv_cell(c)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+pub fn logs_to_ndjson(logs: &[AuditLogResponse]) -> String {
+    let mut out = String::new();
+    for log in logs {
+        out.push_str(&serde_json::to_string(log).unwrap_or_default());
+        out.push('\n');
+    }
+    out
+}
 
 #[derive(Clone)]
 pub struct AuditService {
     pub pool: DbPool,
     pub plan_service: Option<PlanService>, // Option to avoid circular dep during initialization if needed, or just simple dep
+    router: QueryRouter,
+    stream: Option<AuditStream>,
 }
 
 impl AuditService {
@@ -25,7 +477,25 @@ impl AuditService {
     // This seems acyclic. Safe.
 
     pub fn new(pool: DbPool, plan_service: Option<PlanService>) -> Self {
-        Self { pool, plan_service }
+        let router = QueryRouter::new(pool.clone(), None);
+        Self {
+            pool,
+            plan_service,
+            router,
+            stream: None,
+        }
+    }
+
+    /// Serve `list` from `router.reader()` (read replica when configured).
+    pub fn with_query_router(mut self, router: QueryRouter) -> Self {
+        self.router = router;
+        self
+    }
+
+    /// Also forward every entry to the deployment's external sinks.
+    pub fn with_stream(mut self, stream: Option<AuditStream>) -> Self {
+        self.stream = stream;
+        self
     }
 
     #[allow(dead_code)]
@@ -44,55 +514,270 @@ impl AuditService {
         resource_id: Option<&str>,
         details: Option<&str>,
         ip_address: Option<&str>,
+    ) {
+        self.log_change(
+            user_id,
+            tenant_id,
+            action,
+            resource,
+            resource_id,
+            details,
+            ip_address,
+            &AuditChange::default(),
+        )
+        .await
+    }
+
+    /// Like `log`, also storing the before/after values of an update.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn log_change(
+        &self,
+        user_id: Option<&str>,
+        tenant_id: Option<&str>,
+        action: &str,
+        resource: &str,
+        resource_id: Option<&str>,
+        details: Option<&str>,
+        ip_address: Option<&str>,
+        change: &AuditChange,
     ) {
         // We spawn this to not block the main request flow, or just await it.
         // For safety/reliability in this context, we'll await it but ignore errors to not fail the main action.
         let id = uuid::Uuid::new_v4();
-        let now = Utc::now();
+        // Stored with microsecond precision; hashed the same way.
+        let now = Utc::now().trunc_subsecs(6);
+
+        let (old_value, new_value) = if change.is_empty() {
+            (None, None)
+        } else {
+            (
+                Some(Value::Object(change.old_value.clone())),
+                Some(Value::Object(change.new_value.clone())),
+            )
+        };
+
+        if let Some(stream) = &self.stream {
+            stream.publish(AuditEvent {
+                id: id.to_string(),
+                timestamp: now,
+                user_id: user_id.map(str::to_string),
+                tenant_id: tenant_id.map(str::to_string),
+                action: action.to_string(),
+                resource: resource.to_string(),
+                resource_id: resource_id.map(str::to_string),
+                details: details.map(str::to_string),
+                ip_address: ip_address.map(str::to_string),
+                old_value: old_value.clone(),
+                new_value: new_value.clone(),
+            });
+        }
+
+        // Ids as the uuid columns hand them back, so the chain hash verifies later.
+        #[cfg(feature = "postgres")]
+        let stored_id = |v: Option<&str>| {
+            v.and_then(|v| uuid::Uuid::parse_str(v).ok())
+                .map(|u| u.to_string())
+        };
+        #[cfg(feature = "sqlite")]
+        let stored_id = |v: Option<&str>| v.map(str::to_string);
+
+        let entry = ChainEntry {
+            id: id.to_string(),
+            user_id: stored_id(user_id),
+            tenant_id: stored_id(tenant_id),
+            action: action.to_string(),
+            resource: resource.to_string(),
+            resource_id: resource_id.map(str::to_string),
+            details: details.map(str::to_string),
+            ip_address: ip_address.map(str::to_string),
+            created_at: now,
+            old_value,
+            new_value,
+        };
+
+        if let Err(e) = self.append(&entry).await {
+            eprintln!("Failed to write audit log: {}", e);
+        }
+    }
+
+    /// Inserts `entry` as the next link of the audit chain. Appends are
+    /// serialized on the `audit_chain_head` row.
+    async fn append(&self, entry: &ChainEntry) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        #[cfg(feature = "postgres")]
+        let head_query = "SELECT last_seq, last_hash FROM audit_chain_head WHERE id = 1 FOR UPDATE";
+        #[cfg(feature = "sqlite")]
+        let head_query = "SELECT last_seq, last_hash FROM audit_chain_head WHERE id = 1";
+        let (last_seq, last_hash): (i64, String) =
+            sqlx::query_as(head_query).fetch_one(&mut *tx).await?;
+
+        let seq = last_seq + 1;
+        let hash = chain_hash(seq, &last_hash, entry);
 
         let query = r#"
-            INSERT INTO audit_logs (id, user_id, tenant_id, action, resource, resource_id, details, ip_address, created_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            INSERT INTO audit_logs (id, user_id, tenant_id, action, resource, resource_id, details, ip_address, created_at, old_value, new_value, chain_seq, prev_hash, entry_hash)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
         "#;
 
         #[cfg(feature = "postgres")]
-        let user_uuid = user_id.and_then(|v| uuid::Uuid::parse_str(v).ok());
-        #[cfg(feature = "postgres")]
-        let tenant_uuid = tenant_id.and_then(|v| uuid::Uuid::parse_str(v).ok());
+        {
+            let as_uuid = |v: Option<&str>| v.and_then(|v| uuid::Uuid::parse_str(v).ok());
+            sqlx::query(query)
+                .bind(as_uuid(Some(&entry.id)))
+                .bind(as_uuid(entry.user_id.as_deref()))
+                .bind(as_uuid(entry.tenant_id.as_deref()))
+                .bind(&entry.action)
+                .bind(&entry.resource)
+                .bind(&entry.resource_id)
+                .bind(&entry.details)
+                .bind(&entry.ip_address)
+                .bind(entry.created_at)
+                .bind(&entry.old_value)
+                .bind(&entry.new_value)
+                .bind(seq)
+                .bind(&last_hash)
+                .bind(&hash)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query(query)
+            .bind(&entry.id)
+            .bind(&entry.user_id)
+            .bind(&entry.tenant_id)
+            .bind(&entry.action)
+            .bind(&entry.resource)
+            .bind(&entry.resource_id)
+            .bind(&entry.details)
+            .bind(&entry.ip_address)
+            .bind(entry.created_at.to_rfc3339())
+            .bind(entry.old_value.as_ref().map(Value::to_string))
+            .bind(entry.new_value.as_ref().map(Value::to_string))
+            .bind(seq)
+            .bind(&last_hash)
+            .bind(&hash)
+            .execute(&mut *tx)
+            .await?;
+
         #[cfg(feature = "postgres")]
-        let res = sqlx::query(query)
-            .bind(id)
-            .bind(user_uuid)
-            .bind(tenant_uuid)
-            .bind(action)
-            .bind(resource)
-            .bind(resource_id)
-            .bind(details)
-            .bind(ip_address)
-            .bind(now);
+        let updated_at = Utc::now();
+        #[cfg(feature = "sqlite")]
+        let updated_at = Utc::now().to_rfc3339();
+        sqlx::query(
+            "UPDATE audit_chain_head SET last_seq = $1, last_hash = $2, updated_at = $3 WHERE id = 1",
+        )
+        .bind(seq)
+        .bind(&hash)
+        .bind(updated_at)
+        .execute(&mut *tx)
+        .await?;
 
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Walks the whole audit chain and reports entries that were changed,
+    /// removed or re-linked since they were written.
+    pub async fn verify_chain(&self) -> AppResult<AuditChainReport> {
+        // Read the head first: entries appended while we walk are left for the next run.
+        let (head_seq, head_hash): (i64, String) =
+            sqlx::query_as("SELECT last_seq, last_hash FROM audit_chain_head WHERE id = 1")
+                .fetch_optional(&self.pool)
+                .await?
+                .unwrap_or((0, CHAIN_GENESIS.to_string()));
+
+        #[cfg(feature = "postgres")]
+        let batch_query = r#"
+            SELECT chain_seq, prev_hash, entry_hash, id::text AS id, user_id::text AS user_id,
+                   tenant_id::text AS tenant_id, action, resource, resource_id, details,
+                   ip_address, created_at, old_value, new_value
+            FROM audit_logs
+            WHERE chain_seq IS NOT NULL AND chain_seq <= $1
+              AND (chain_seq > $2 OR (chain_seq = $2 AND id::text > $3))
+            ORDER BY chain_seq, id::text
+            LIMIT $4
+        "#;
         #[cfg(feature = "sqlite")]
-        let res = sqlx::query(query)
-            .bind(id.to_string())
-            .bind(user_id)
-            .bind(tenant_id)
-            .bind(action)
-            .bind(resource)
-            .bind(resource_id)
-            .bind(details)
-            .bind(ip_address)
-            .bind(now.to_rfc3339());
-
-        if let Err(e) = res.execute(&self.pool).await {
-            eprintln!("Failed to write audit log: {}", e);
+        let batch_query = r#"
+            SELECT chain_seq, prev_hash, entry_hash, id, user_id, tenant_id, action, resource,
+                   resource_id, details, ip_address, created_at, old_value, new_value
+            FROM audit_logs
+            WHERE chain_seq IS NOT NULL AND chain_seq <= $1
+              AND (chain_seq > $2 OR (chain_seq = $2 AND id > $3))
+            ORDER BY chain_seq, id
+            LIMIT $4
+        "#;
+
+        let mut verifier = ChainVerifier::new();
+        let (mut after_seq, mut after_id) = (0_i64, String::new());
+        loop {
+            let rows: Vec<ChainRow> = sqlx::query_as(batch_query)
+                .bind(head_seq)
+                .bind(after_seq)
+                .bind(&after_id)
+                .bind(CHAIN_BATCH_SIZE)
+                .fetch_all(&self.pool)
+                .await?;
+            for row in &rows {
+                verifier.check(row);
+            }
+            match rows.last() {
+                Some(last) if rows.len() as i64 == CHAIN_BATCH_SIZE => {
+                    after_seq = last.chain_seq;
+                    after_id = last.entry.id.clone();
+                }
+                _ => break,
+            }
         }
+
+        let (unchained,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM audit_logs WHERE chain_seq IS NULL")
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(verifier.finish(head_seq, &head_hash, unchained))
     }
 
     /// List logs with filters
-    pub async fn list(
+    pub async fn list(&self, filter: AuditLogFilter) -> AppResult<(Vec<AuditLogResponse>, i64)> {
+        self.ensure_plan_access(&filter).await?;
+
+        let page = filter.page.unwrap_or(1);
+        let per_page = filter.per_page.unwrap_or(20);
+        let offset = (page.saturating_sub(1)) * per_page;
+
+        let count = self.count(&filter).await?;
+        let logs = self.fetch(&filter, per_page as i64, offset as i64).await?;
+        Ok((logs, count))
+    }
+
+    /// The logs matching `filter` (newest first, at most `EXPORT_LIMIT`) as a file.
+    pub async fn export(
         &self,
-        filter: crate::models::AuditLogFilter,
-    ) -> AppResult<(Vec<crate::models::AuditLogResponse>, i64)> {
+        filter: AuditLogFilter,
+        format: AuditExportFormat,
+    ) -> AppResult<AuditLogExport> {
+        self.ensure_plan_access(&filter).await?;
+
+        let mut logs = self.fetch(&filter, EXPORT_LIMIT + 1, 0).await?;
+        let truncated = logs.len() as i64 > EXPORT_LIMIT;
+        logs.truncate(EXPORT_LIMIT as usize);
+
+        let content = match format {
+            AuditExportFormat::Csv => logs_to_csv(&logs),
+            AuditExportFormat::Ndjson => logs_to_ndjson(&logs),
+        };
+        Ok(AuditLogExport {
+            format: format.as_str().to_string(),
+            content,
+            rows: logs.len(),
+            truncated,
+        })
+    }
+
+    async fn ensure_plan_access(&self, filter: &AuditLogFilter) -> AppResult<()> {
         // Enforce Plan Limits
         if let Some(tenant_id) = &filter.tenant_id {
             if let Some(plan_service) = &self.plan_service {
@@ -109,345 +794,308 @@ impl AuditService {
                 }
             }
         }
+        Ok(())
+    }
 
-        let page = filter.page.unwrap_or(1);
-        let per_page = filter.per_page.unwrap_or(20);
-        let offset = (page.saturating_sub(1)) * per_page;
+    async fn count(&self, filter: &AuditLogFilter) -> AppResult<i64> {
+        use sqlx::Row;
 
-        // --- Postgres Implementation ---
         #[cfg(feature = "postgres")]
-        {
-            use sqlx::{Postgres, QueryBuilder, Row};
-
-            let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(
-                r#"SELECT 
-                    l.id::text, l.user_id::text, l.tenant_id::text, l.action, l.resource, l.resource_id, l.details, l.ip_address, l.created_at,
-                    u.name as user_name, u.email as user_email,
-                    t.name as tenant_name,
-                    CASE 
-                        WHEN l.resource = 'user' THEN ru.name
-                        WHEN l.resource = 'tenant' THEN rt.name
-                        WHEN l.resource = 'roles' THEN rr.name
-                        WHEN l.resource = 'settings' THEN l.resource_id
-                        ELSE l.resource_id
-                    END as resource_name
-                FROM audit_logs l
-                LEFT JOIN users u ON l.user_id::text = u.id::text
-                LEFT JOIN tenants t ON l.tenant_id::text = t.id::text
-                LEFT JOIN users ru ON l.resource = 'user' AND l.resource_id = ru.id::text
-                LEFT JOIN tenants rt ON l.resource = 'tenant' AND l.resource_id = rt.id::text
-                LEFT JOIN roles rr ON l.resource = 'roles' AND l.resource_id = rr.id::text
-                WHERE 1=1 "#,
-            );
-
-            if let Some(uid) = &filter.user_id {
-                qb.push(" AND l.user_id::text = ");
-                qb.push_bind(uid);
-            }
+        let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(
+            "SELECT COUNT(*) FROM audit_logs l LEFT JOIN users u ON l.user_id::text = u.id::text WHERE 1=1 ",
+        );
+        #[cfg(feature = "sqlite")]
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "SELECT COUNT(*) FROM audit_logs l LEFT JOIN users u ON l.user_id = u.id WHERE 1=1 ",
+        );
+        push_filters(&mut qb, filter);
 
-            if let Some(tid) = &filter.tenant_id {
-                qb.push(" AND l.tenant_id::text = ");
-                qb.push_bind(tid);
-            }
+        Ok(qb
+            .build()
+            .fetch_one(self.router.reader())
+            .await?
+            .try_get(0)?)
+    }
 
-            if let Some(customer_id) = &filter.customer_id {
-                qb.push(" AND (");
-                qb.push(" (l.resource = 'customers' AND l.resource_id = ");
-                qb.push_bind(customer_id);
-                qb.push(")");
-                qb.push(" OR (l.resource = 'customer_locations' AND EXISTS (SELECT 1 FROM customer_locations cl WHERE cl.id::text = l.resource_id AND cl.customer_id::text = ");
-                qb.push_bind(customer_id);
-                qb.push("))");
-                qb.push(" OR (l.resource = 'customer_subscriptions' AND EXISTS (SELECT 1 FROM customer_subscriptions cs WHERE cs.id::text = l.resource_id AND cs.customer_id::text = ");
-                qb.push_bind(customer_id);
-                qb.push("))");
-                qb.push(" OR (l.resource = 'customer_users' AND EXISTS (SELECT 1 FROM customer_users cu WHERE cu.id::text = l.resource_id AND cu.customer_id::text = ");
-                qb.push_bind(customer_id);
-                qb.push("))");
-                qb.push(")");
-            }
+    /// One page of matching logs, newest first.
+    async fn fetch(
+        &self,
+        filter: &AuditLogFilter,
+        limit: i64,
+        offset: i64,
+    ) -> AppResult<Vec<AuditLogResponse>> {
+        // --- Postgres Implementation ---
+        #[cfg(feature = "postgres")]
+        let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(
+            r#"SELECT 
+                l.id::text, l.user_id::text, l.tenant_id::text, l.action, l.resource, l.resource_id, l.details, l.ip_address, l.created_at, l.old_value, l.new_value,
+                l.chain_seq, l.entry_hash,
+                u.name as user_name, u.email as user_email,
+                t.name as tenant_name,
+                CASE 
+                    WHEN l.resource = 'user' THEN ru.name
+                    WHEN l.resource = 'tenant' THEN rt.name
+                    WHEN l.resource = 'roles' THEN rr.name
+                    WHEN l.resource = 'settings' THEN l.resource_id
+                    ELSE l.resource_id
+                END as resource_name
+            FROM audit_logs l
+            LEFT JOIN users u ON l.user_id::text = u.id::text
+            LEFT JOIN tenants t ON l.tenant_id::text = t.id::text
+            LEFT JOIN users ru ON l.resource = 'user' AND l.resource_id = ru.id::text
+            LEFT JOIN tenants rt ON l.resource = 'tenant' AND l.resource_id = rt.id::text
+            LEFT JOIN roles rr ON l.resource = 'roles' AND l.resource_id = rr.id::text
+            WHERE 1=1 "#,
+        );
 
-            if let Some(resource) = &filter.resource {
-                qb.push(" AND l.resource = ");
-                qb.push_bind(resource);
-            }
+        // --- SQLite Implementation ---
+        #[cfg(feature = "sqlite")]
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+            r#"SELECT 
+                l.id, l.user_id, l.tenant_id, l.action, l.resource, l.resource_id, l.details, l.ip_address, l.created_at, l.old_value, l.new_value,
+                l.chain_seq, l.entry_hash,
+                u.name as user_name, u.email as user_email,
+                t.name as tenant_name,
+                CASE 
+                    WHEN l.resource = 'user' THEN ru.name
+                    WHEN l.resource = 'tenant' THEN rt.name
+                    WHEN l.resource = 'roles' THEN rr.name
+                    WHEN l.resource = 'settings' THEN l.resource_id
+                    ELSE l.resource_id
+                END as resource_name
+            FROM audit_logs l
+            LEFT JOIN users u ON l.user_id = u.id
+            LEFT JOIN tenants t ON l.tenant_id = t.id
+            LEFT JOIN users ru ON l.resource = 'user' AND l.resource_id = ru.id
+            LEFT JOIN tenants rt ON l.resource = 'tenant' AND l.resource_id = rt.id
+            LEFT JOIN roles rr ON l.resource = 'roles' AND l.resource_id = rr.id
+            WHERE 1=1 "#,
+        );
 
-            if let Some(resource_id) = &filter.resource_id {
-                qb.push(" AND l.resource_id = ");
-                qb.push_bind(resource_id);
-            }
+        push_filters(&mut qb, filter);
 
-            if let Some(action) = &filter.action {
-                qb.push(" AND l.action = ");
-                qb.push_bind(action);
-            }
+        // Ordering and pagination
+        qb.push(" ORDER BY l.created_at DESC LIMIT ");
+        qb.push_bind(limit);
+        qb.push(" OFFSET ");
+        qb.push_bind(offset);
 
-            if let Some(date_from) = filter.date_from {
-                qb.push(" AND l.created_at >= ");
-                qb.push_bind(date_from);
-            }
+        let mut logs = qb
+            .build_query_as::<AuditLogResponse>()
+            .fetch_all(self.router.reader())
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to fetch audit logs: {}", e);
+                AppError::Internal(e.to_string())
+            })?;
+        for log in &mut logs {
+            log.changes = field_changes(log.old_value.as_ref(), log.new_value.as_ref());
+        }
+        Ok(logs)
+    }
 
-            if let Some(date_to) = filter.date_to {
-                qb.push(" AND l.created_at <= ");
-                qb.push_bind(date_to);
-            }
+    /// The user's saved filter sets for a tenant (`None`: the all-tenants view).
+    pub async fn list_presets(
+        &self,
+        user_id: &str,
+        tenant_id: Option<&str>,
+    ) -> AppResult<Vec<AuditFilterPreset>> {
+        let presets = sqlx::query_as(
+            r#"
+            SELECT * FROM audit_filter_presets
+            WHERE user_id = $1 AND COALESCE(tenant_id, '') = COALESCE($2, '')
+            ORDER BY lower(name)
+            "#,
+        )
+        .bind(user_id)
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(presets)
+    }
 
-            if let Some(search) = &filter.search {
-                let pattern = format!("%{}%", search);
-                qb.push(" AND (l.resource ILIKE ");
-                qb.push_bind(pattern.clone());
-                qb.push(" OR l.resource_id ILIKE ");
-                qb.push_bind(pattern.clone());
-                qb.push(" OR l.details ILIKE ");
-                qb.push_bind(pattern.clone());
-                qb.push(" OR u.name ILIKE ");
-                qb.push_bind(pattern);
-                qb.push(")");
-            }
+    /// Saves a preset; one with the same name (case-insensitive) is replaced.
+    pub async fn save_preset(
+        &self,
+        user_id: &str,
+        tenant_id: Option<&str>,
+        dto: SaveAuditFilterPresetDto,
+    ) -> AppResult<AuditFilterPreset> {
+        let name = dto.name.trim();
+        if name.is_empty() || name.chars().count() > 100 {
+            return Err(AppError::Validation(
+                "Preset name must be 1-100 characters".to_string(),
+            ));
+        }
+        let filters = preset_filters(&dto.filters)?;
+        let now = Utc::now();
 
-            // Count query clone before limit/offset
-            // Note: QueryBuilder doesn't easily clone, so we construct count separately or run window function.
-            // For simplicity, we'll run a separate count query with same WHERE clauses or just simple string construction for count if needed.
-            // Actually, let's just get the count first using a similar builder.
+        let existing: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM audit_filter_presets
+            WHERE user_id = $1 AND COALESCE(tenant_id, '') = COALESCE($2, '')
+              AND lower(name) = lower($3)
+            "#,
+        )
+        .bind(user_id)
+        .bind(tenant_id)
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?;
 
-            let mut count_qb: QueryBuilder<Postgres> = QueryBuilder::new("SELECT COUNT(*) FROM audit_logs l LEFT JOIN users u ON l.user_id::text = u.id::text WHERE 1=1 ");
-            if let Some(uid) = &filter.user_id {
-                count_qb.push(" AND l.user_id::text = ");
-                count_qb.push_bind(uid);
-            }
-            if let Some(tid) = &filter.tenant_id {
-                count_qb.push(" AND l.tenant_id::text = ");
-                count_qb.push_bind(tid);
-            }
-            if let Some(customer_id) = &filter.customer_id {
-                count_qb.push(" AND (");
-                count_qb.push(" (l.resource = 'customers' AND l.resource_id = ");
-                count_qb.push_bind(customer_id);
-                count_qb.push(")");
-                count_qb.push(" OR (l.resource = 'customer_locations' AND EXISTS (SELECT 1 FROM customer_locations cl WHERE cl.id::text = l.resource_id AND cl.customer_id::text = ");
-                count_qb.push_bind(customer_id);
-                count_qb.push("))");
-                count_qb.push(" OR (l.resource = 'customer_subscriptions' AND EXISTS (SELECT 1 FROM customer_subscriptions cs WHERE cs.id::text = l.resource_id AND cs.customer_id::text = ");
-                count_qb.push_bind(customer_id);
-                count_qb.push("))");
-                count_qb.push(" OR (l.resource = 'customer_users' AND EXISTS (SELECT 1 FROM customer_users cu WHERE cu.id::text = l.resource_id AND cu.customer_id::text = ");
-                count_qb.push_bind(customer_id);
-                count_qb.push("))");
-                count_qb.push(")");
-            }
-            if let Some(resource) = &filter.resource {
-                count_qb.push(" AND l.resource = ");
-                count_qb.push_bind(resource);
+        let preset = match existing {
+            Some(id) => {
+                sqlx::query_as(
+                    r#"
+                    UPDATE audit_filter_presets SET name = $1, filters = $2, updated_at = $3
+                    WHERE id = $4
+                    RETURNING *
+                    "#,
+                )
+                .bind(name)
+                .bind(&filters)
+                .bind(now)
+                .bind(id)
+                .fetch_one(&self.pool)
+                .await?
             }
-            if let Some(resource_id) = &filter.resource_id {
-                count_qb.push(" AND l.resource_id = ");
-                count_qb.push_bind(resource_id);
-            }
-            if let Some(action) = &filter.action {
-                count_qb.push(" AND l.action = ");
-                count_qb.push_bind(action);
-            }
-            if let Some(date_from) = filter.date_from {
-                count_qb.push(" AND l.created_at >= ");
-                count_qb.push_bind(date_from);
-            }
-            if let Some(date_to) = filter.date_to {
-                count_qb.push(" AND l.created_at <= ");
-                count_qb.push_bind(date_to);
-            }
-            if let Some(search) = &filter.search {
-                let pattern = format!("%{}%", search);
-                count_qb.push(" AND (l.resource ILIKE ");
-                count_qb.push_bind(pattern.clone());
-                count_qb.push(" OR l.resource_id ILIKE ");
-                count_qb.push_bind(pattern.clone());
-                count_qb.push(" OR l.details ILIKE ");
-                count_qb.push_bind(pattern.clone());
-                count_qb.push(" OR u.name ILIKE ");
-                count_qb.push_bind(pattern);
-                count_qb.push(")");
+            None => {
+                sqlx::query_as(
+                    r#"
+                    INSERT INTO audit_filter_presets
+                        (id, user_id, tenant_id, name, filters, created_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $6)
+                    RETURNING *
+                    "#,
+                )
+                .bind(uuid::Uuid::new_v4().to_string())
+                .bind(user_id)
+                .bind(tenant_id)
+                .bind(name)
+                .bind(&filters)
+                .bind(now)
+                .fetch_one(&self.pool)
+                .await?
             }
+        };
+        Ok(preset)
+    }
 
-            let count: i64 = count_qb.build().fetch_one(&self.pool).await?.try_get(0)?;
+    pub async fn delete_preset(
+        &self,
+        user_id: &str,
+        tenant_id: Option<&str>,
+        id: &str,
+    ) -> AppResult<()> {
+        let res = sqlx::query(
+            r#"
+            DELETE FROM audit_filter_presets
+            WHERE id = $1 AND user_id = $2 AND COALESCE(tenant_id, '') = COALESCE($3, '')
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound("Preset not found".to_string()));
+        }
+        Ok(())
+    }
+}
 
-            // Ordering and pagination
-            qb.push(" ORDER BY l.created_at DESC LIMIT ");
-            qb.push_bind(per_page as i64);
-            qb.push(" OFFSET ");
-            qb.push_bind(offset as i64);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
 
-            let logs = qb
-                .build_query_as::<crate::models::AuditLogResponse>()
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|e| {
-                    tracing::error!("Failed to fetch audit logs: {}", e);
-                    crate::error::AppError::Internal(e.to_string())
-                })?;
+    #[test]
+    fn change_keeps_only_changed_fields() {
+        let change = AuditChange::between(
+            &json!({ "name": "Core", "port": 8728, "enabled": true, "updated_at": "a" }),
+            &json!({ "name": "Core-1", "port": 8728, "enabled": false, "updated_at": "b" }),
+        );
+        assert_eq!(
+            Value::Object(change.old_value),
+            json!({ "name": "Core", "enabled": true })
+        );
+        assert_eq!(
+            Value::Object(change.new_value),
+            json!({ "name": "Core-1", "enabled": false })
+        );
+        assert!(AuditChange::between(&json!({ "a": 1 }), &json!({ "a": 1 })).is_empty());
+    }
 
-            Ok((logs, count))
-        }
+    #[test]
+    fn secrets_are_redacted_at_any_depth() {
+        let change = AuditChange::between(
+            &json!({ "smtp_password": "old", "config": { "api_key": "k1", "host": "a" } }),
+            &json!({ "smtp_password": "new", "config": { "api_key": "k2", "host": "b" } }),
+        );
+        assert_eq!(
+            Value::Object(change.old_value),
+            json!({ "smtp_password": REDACTED, "config": { "api_key": REDACTED, "host": "a" } })
+        );
+        assert_eq!(change.new_value["smtp_password"], json!(REDACTED));
 
-        // --- SQLite Implementation ---
-        #[cfg(feature = "sqlite")]
-        {
-            use sqlx::{QueryBuilder, Row, Sqlite};
-
-            let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
-                r#"SELECT 
-                    l.id, l.user_id, l.tenant_id, l.action, l.resource, l.resource_id, l.details, l.ip_address, l.created_at,
-                    u.name as user_name, u.email as user_email,
-                    t.name as tenant_name,
-                    CASE 
-                        WHEN l.resource = 'user' THEN ru.name
-                        WHEN l.resource = 'tenant' THEN rt.name
-                        WHEN l.resource = 'roles' THEN rr.name
-                        WHEN l.resource = 'settings' THEN l.resource_id
-                        ELSE l.resource_id
-                    END as resource_name
-                FROM audit_logs l
-                LEFT JOIN users u ON l.user_id = u.id
-                LEFT JOIN tenants t ON l.tenant_id = t.id
-                LEFT JOIN users ru ON l.resource = 'user' AND l.resource_id = ru.id
-                LEFT JOIN tenants rt ON l.resource = 'tenant' AND l.resource_id = rt.id
-                LEFT JOIN roles rr ON l.resource = 'roles' AND l.resource_id = rr.id
-                WHERE 1=1 "#,
-            );
-
-            if let Some(uid) = &filter.user_id {
-                qb.push(" AND l.user_id = ");
-                qb.push_bind(uid);
-            }
-            if let Some(tid) = &filter.tenant_id {
-                qb.push(" AND l.tenant_id = ");
-                qb.push_bind(tid);
-            }
-            if let Some(customer_id) = &filter.customer_id {
-                qb.push(" AND (");
-                qb.push(" (l.resource = 'customers' AND l.resource_id = ");
-                qb.push_bind(customer_id);
-                qb.push(")");
-                qb.push(" OR (l.resource = 'customer_locations' AND EXISTS (SELECT 1 FROM customer_locations cl WHERE cl.id = l.resource_id AND cl.customer_id = ");
-                qb.push_bind(customer_id);
-                qb.push("))");
-                qb.push(" OR (l.resource = 'customer_subscriptions' AND EXISTS (SELECT 1 FROM customer_subscriptions cs WHERE cs.id = l.resource_id AND cs.customer_id = ");
-                qb.push_bind(customer_id);
-                qb.push("))");
-                qb.push(" OR (l.resource = 'customer_users' AND EXISTS (SELECT 1 FROM customer_users cu WHERE cu.id = l.resource_id AND cu.customer_id = ");
-                qb.push_bind(customer_id);
-                qb.push("))");
-                qb.push(")");
-            }
-            if let Some(resource) = &filter.resource {
-                qb.push(" AND l.resource = ");
-                qb.push_bind(resource);
-            }
-            if let Some(resource_id) = &filter.resource_id {
-                qb.push(" AND l.resource_id = ");
-                qb.push_bind(resource_id);
-            }
-            if let Some(action) = &filter.action {
-                qb.push(" AND l.action = ");
-                qb.push_bind(action);
-            }
-            if let Some(date_from) = filter.date_from {
-                qb.push(" AND l.created_at >= ");
-                qb.push_bind(date_from.to_rfc3339());
-            }
-            if let Some(date_to) = filter.date_to {
-                qb.push(" AND l.created_at <= ");
-                qb.push_bind(date_to.to_rfc3339());
-            }
-            if let Some(search) = &filter.search {
-                let pattern = format!("%{}%", search);
-                qb.push(" AND (l.resource LIKE ");
-                qb.push_bind(pattern.clone());
-                qb.push(" OR l.resource_id LIKE ");
-                qb.push_bind(pattern.clone());
-                qb.push(" OR l.details LIKE ");
-                qb.push_bind(pattern.clone());
-                qb.push(" OR u.name LIKE ");
-                qb.push_bind(pattern);
-                qb.push(")");
-            }
+        // Setting a secret for the first time still shows that it was unset.
+        let change = AuditChange::between(&json!({ "token": null }), &json!({ "token": "t" }));
+        assert_eq!(change.old_value["token"], Value::Null);
+        assert_eq!(change.new_value["token"], json!(REDACTED));
+    }
 
-            // Count
-            let mut count_qb: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT COUNT(*) FROM audit_logs l LEFT JOIN users u ON l.user_id = u.id WHERE 1=1 ");
-            if let Some(uid) = &filter.user_id {
-                count_qb.push(" AND l.user_id = ");
-                count_qb.push_bind(uid);
-            }
-            if let Some(tid) = &filter.tenant_id {
-                count_qb.push(" AND l.tenant_id = ");
-                count_qb.push_bind(tid);
-            }
-            if let Some(customer_id) = &filter.customer_id {
-                count_qb.push(" AND (");
-                count_qb.push(" (l.resource = 'customers' AND l.resource_id = ");
-                count_qb.push_bind(customer_id);
-                count_qb.push(")");
-                count_qb.push(" OR (l.resource = 'customer_locations' AND EXISTS (SELECT 1 FROM customer_locations cl WHERE cl.id = l.resource_id AND cl.customer_id = ");
-                count_qb.push_bind(customer_id);
-                count_qb.push("))");
-                count_qb.push(" OR (l.resource = 'customer_subscriptions' AND EXISTS (SELECT 1 FROM customer_subscriptions cs WHERE cs.id = l.resource_id AND cs.customer_id = ");
-                count_qb.push_bind(customer_id);
-                count_qb.push("))");
-                count_qb.push(" OR (l.resource = 'customer_users' AND EXISTS (SELECT 1 FROM customer_users cu WHERE cu.id = l.resource_id AND cu.customer_id = ");
-                count_qb.push_bind(customer_id);
-                count_qb.push("))");
-                count_qb.push(")");
-            }
-            if let Some(resource) = &filter.resource {
-                count_qb.push(" AND l.resource = ");
-                count_qb.push_bind(resource);
-            }
-            if let Some(resource_id) = &filter.resource_id {
-                count_qb.push(" AND l.resource_id = ");
-                count_qb.push_bind(resource_id);
-            }
-            if let Some(action) = &filter.action {
-                count_qb.push(" AND l.action = ");
-                count_qb.push_bind(action);
-            }
-            if let Some(date_from) = filter.date_from {
-                count_qb.push(" AND l.created_at >= ");
-                count_qb.push_bind(date_from.to_rfc3339());
-            }
-            if let Some(date_to) = filter.date_to {
-                count_qb.push(" AND l.created_at <= ");
-                count_qb.push_bind(date_to.to_rfc3339());
-            }
-            if let Some(search) = &filter.search {
-                let pattern = format!("%{}%", search);
-                count_qb.push(" AND (l.resource LIKE ");
-                count_qb.push_bind(pattern.clone());
-                count_qb.push(" OR l.resource_id LIKE ");
-                count_qb.push_bind(pattern.clone());
-                count_qb.push(" OR l.details LIKE ");
-                count_qb.push_bind(pattern.clone());
-                count_qb.push(" OR u.name LIKE ");
-                count_qb.push_bind(pattern);
-                count_qb.push(")");
-            }
+    #[test]
+    fn field_changes_pair_up_old_and_new() {
+        let changes = field_changes(
+            Some(&json!({ "name": "a", "notes": null })),
+            Some(&json!({ "name": "b", "notes": "x" })),
+        );
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field, "name");
+        assert_eq!(changes[1].new, json!("x"));
+        assert!(field_changes(None, None).is_empty());
+    }
 
-            let count: i64 = count_qb.build().fetch_one(&self.pool).await?.try_get(0)?;
+    #[test]
+    fn presets_keep_known_filters_only() {
+        let filters = preset_filters(&json!({
+            "action": " update ",
+            "ip_address": "10.0.*",
+            "search": "",
+            "page": "3",
+            "actor": 42,
+        }))
+        .unwrap();
+        assert_eq!(
+            filters,
+            json!({ "action": "update", "ip_address": "10.0.*" })
+        );
+        assert!(preset_filters(&json!(["action"])).is_err());
+    }
 
-            // Order Limit Offset
-            qb.push(" ORDER BY l.created_at DESC LIMIT ");
-            qb.push_bind(per_page as i64);
-            qb.push(" OFFSET ");
-            qb.push_bind(offset as i64);
+    #[test]
+    fn csv_cells_are_quoted_and_defused() {
+        assert_eq!(csv_cell("login"), "login");
+        assert_eq!(csv_cell("a,b"), "\"a,b\"");
+        assert_eq!(csv_cell("say \"hi\"\nbye"), "\"say \"\"hi\"\"\nbye\"");
+        assert_eq!(csv_cell("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_cell("-5"), "'-5");
+    }
 
-            let logs = qb
-                .build_query_as::<crate::models::AuditLogResponse>()
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|e| {
-                    tracing::error!("Failed to fetch audit logs: {}", e);
-                    crate::error::AppError::Internal(e.to_string())
-                })?;
+    #[test]
+    fn export_formats() {
+        assert_eq!(AuditExportFormat::parse(None), Some(AuditExportFormat::Csv));
+        assert_eq!(
+            AuditExportFormat::parse(Some("NDJSON")),
+            Some(AuditExportFormat::Ndjson)
+        );
+        assert_eq!(AuditExportFormat::parse(Some("xlsx")), None);
+    }
 
-            Ok((logs, count))
-        }
+    #[test]
+    fn ip_prefixes_escape_like_wildcards() {
+        assert_eq!(like_prefix("10.0."), "10.0.%");
+        assert_eq!(like_prefix("a_b%"), "a\\_b\\%%");
     }
 }
@@ -0,0 +1,424 @@
+//! Real-time forwarding of audit entries to external collectors (SIEM).
+//!
+//! Sinks are configured per deployment through the environment:
+//!
+//! - `AUDIT_SYSLOG_ADDR`: syslog receiver, `udp://host:514` (the default
+//!   scheme) or `tcp://host:601`. Entries go out as RFC 5424 messages with
+//!   facility `log audit` and the entry as JSON in the message body; TCP uses
+//!   octet-counting framing (RFC 6587).
+//! - `AUDIT_HTTP_URL`: collector that receives a JSON array of entries per
+//!   POST, with `AUDIT_HTTP_TOKEN` sent as a bearer token when set.
+//! - `AUDIT_FILE_PATH`: file that entries are appended to as JSON lines.
+//!
+//! Forwarding never holds up the action being audited: entries are queued and
+//! sent from a background task, and dropped (with a warning) when the queue is
+//! full or a collector fails. `audit_logs` stays the system of record.
+
+use crate::error::{AppError, AppResult};
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, Mutex};
+
+/// Entries waiting to be forwarded before new ones are dropped.
+const QUEUE_CAPACITY: usize = 10_000;
+/// Entries handed to the sinks at once.
+const BATCH_SIZE: usize = 100;
+const HTTP_TIMEOUT_SECS: u64 = 10;
+const APP_NAME: &str = "isp-management";
+/// `log audit` facility, `notice` severity.
+const SYSLOG_PRI: u8 = 13 * 8 + 5;
+
+/// An audit entry as forwarded to collectors.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEvent {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub user_id: Option<String>,
+    pub tenant_id: Option<String>,
+    pub action: String,
+    pub resource: String,
+    pub resource_id: Option<String>,
+    pub details: Option<String>,
+    pub ip_address: Option<String>,
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: Option<serde_json::Value>,
+}
+
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    /// Shown in logs when forwarding fails.
+    fn name(&self) -> &str;
+
+    async fn send(&self, events: &[AuditEvent]) -> AppResult<()>;
+}
+
+/// Printable US-ASCII without spaces, as RFC 5424 header fields require;
+/// `-` (nil) when nothing is left.
+fn header_field(value: &str, max_len: usize) -> String {
+    let field: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_len)
+        .collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+/// One RFC 5424 message for `event`.
+pub fn format_syslog(event: &AuditEvent, hostname: &str) -> String {
+    let body = serde_json::to_string(event).unwrap_or_default();
+    format!(
+        "<{}>1 {} {} {} {} {} - \u{feff}{}",
+        SYSLOG_PRI,
+        event.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+        header_field(hostname, 255),
+        APP_NAME,
+        std::process::id(),
+        header_field(&event.action, 32),
+        body
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyslogTransport {
+    Udp,
+    Tcp,
+}
+
+/// `udp://host:port`, `tcp://host:port` or a bare `host:port` (UDP).
+pub fn parse_syslog_addr(raw: &str) -> AppResult<(SyslogTransport, String)> {
+    let raw = raw.trim();
+    let (transport, address) = if let Some(rest) = raw.strip_prefix("tcp://") {
+        (SyslogTransport::Tcp, rest)
+    } else if let Some(rest) = raw.strip_prefix("udp://") {
+        (SyslogTransport::Udp, rest)
+    } else if raw.contains("://") {
+        return Err(AppError::Configuration(format!(
+            "AUDIT_SYSLOG_ADDR must use udp:// or tcp://, got '{}'",
+            raw
+        )));
+    } else {
+        (SyslogTransport::Udp, raw)
+    };
+    let valid = address
+        .rsplit_once(':')
+        .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+    if !valid {
+        return Err(AppError::Configuration(format!(
+            "AUDIT_SYSLOG_ADDR needs host:port, got '{}'",
+            raw
+        )));
+    }
+    Ok((transport, address.to_string()))
+}
+
+fn local_hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .unwrap_or_default()
+}
+
+pub struct SyslogSink {
+    transport: SyslogTransport,
+    address: String,
+    hostname: String,
+    /// Kept open between batches; reconnected after a failed write.
+    tcp: Mutex<Option<tokio::net::TcpStream>>,
+}
+
+impl SyslogSink {
+    pub fn new(transport: SyslogTransport, address: String) -> Self {
+        Self {
+            transport,
+            address,
+            hostname: local_hostname(),
+            tcp: Mutex::new(None),
+        }
+    }
+
+    async fn send_udp(&self, messages: &[String]) -> AppResult<()> {
+        let io_err = |e: std::io::Error| AppError::Internal(format!("Syslog send failed: {}", e));
+        let target = tokio::net::lookup_host(&self.address)
+            .await
+            .map_err(io_err)?
+            .next()
+            .ok_or_else(|| {
+                AppError::Internal(format!("Syslog host '{}' did not resolve", self.address))
+            })?;
+        let bind = if target.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = tokio::net::UdpSocket::bind(bind).await.map_err(io_err)?;
+        for message in messages {
+            socket
+                .send_to(message.as_bytes(), target)
+                .await
+                .map_err(io_err)?;
+        }
+        Ok(())
+    }
+
+    async fn send_tcp(&self, messages: &[String]) -> AppResult<()> {
+        let io_err = |e: std::io::Error| AppError::Internal(format!("Syslog send failed: {}", e));
+        let mut framed = Vec::new();
+        for message in messages {
+            framed.extend_from_slice(format!("{} {}", message.len(), message).as_bytes());
+        }
+
+        let mut conn = self.tcp.lock().await;
+        let mut stream = match conn.take() {
+            Some(stream) => stream,
+            None => tokio::net::TcpStream::connect(&self.address)
+                .await
+                .map_err(io_err)?,
+        };
+        stream.write_all(&framed).await.map_err(io_err)?;
+        *conn = Some(stream);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditSink for SyslogSink {
+    fn name(&self) -> &str {
+        "syslog"
+    }
+
+    async fn send(&self, events: &[AuditEvent]) -> AppResult<()> {
+        let messages: Vec<String> = events
+            .iter()
+            .map(|e| format_syslog(e, &self.hostname))
+            .collect();
+        match self.transport {
+            SyslogTransport::Udp => self.send_udp(&messages).await,
+            SyslogTransport::Tcp => self.send_tcp(&messages).await,
+        }
+    }
+}
+
+pub struct HttpSink {
+    url: String,
+    token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl HttpSink {
+    pub fn new(url: String, token: Option<String>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default();
+        Self { url, token, http }
+    }
+}
+
+#[async_trait]
+impl AuditSink for HttpSink {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    async fn send(&self, events: &[AuditEvent]) -> AppResult<()> {
+        let mut req = self.http.post(&self.url).json(events);
+        if let Some(token) = &self.token {
+            req = req.bearer_auth(token);
+        }
+        let res = req
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Audit collector unreachable: {}", e)))?;
+        if !res.status().is_success() {
+            return Err(AppError::Internal(format!(
+                "Audit collector returned {}",
+                res.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+#[async_trait]
+impl AuditSink for FileSink {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn send(&self, events: &[AuditEvent]) -> AppResult<()> {
+        let mut lines = String::new();
+        for event in events {
+            lines.push_str(&serde_json::to_string(event).unwrap_or_default());
+            lines.push('\n');
+        }
+        // Reopened per batch so rotated files are picked up.
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to open audit file: {}", e)))?;
+        file.write_all(lines.as_bytes())
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to write audit file: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// Handle that queues entries for the configured sinks.
+#[derive(Clone)]
+pub struct AuditStream {
+    tx: mpsc::Sender<AuditEvent>,
+}
+
+impl AuditStream {
+    /// Builds the sinks named in the environment and starts forwarding;
+    /// `None` when no sink is configured.
+    pub fn from_env() -> Option<Self> {
+        let env = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let mut sinks: Vec<Box<dyn AuditSink>> = Vec::new();
+        if let Some(addr) = env("AUDIT_SYSLOG_ADDR") {
+            match parse_syslog_addr(&addr) {
+                Ok((transport, address)) => {
+                    sinks.push(Box::new(SyslogSink::new(transport, address)))
+                }
+                Err(e) => tracing::error!("Audit syslog sink disabled: {}", e),
+            }
+        }
+        if let Some(url) = env("AUDIT_HTTP_URL") {
+            sinks.push(Box::new(HttpSink::new(url, env("AUDIT_HTTP_TOKEN"))));
+        }
+        if let Some(path) = env("AUDIT_FILE_PATH") {
+            sinks.push(Box::new(FileSink::new(PathBuf::from(path))));
+        }
+
+        if sinks.is_empty() {
+            return None;
+        }
+        let names: Vec<&str> = sinks.iter().map(|s| s.name()).collect();
+        tracing::info!("Forwarding audit entries to: {}", names.join(", "));
+        Some(Self::start(sinks))
+    }
+
+    pub fn start(sinks: Vec<Box<dyn AuditSink>>) -> Self {
+        let (tx, mut rx) = mpsc::channel::<AuditEvent>(QUEUE_CAPACITY);
+        tokio::spawn(async move {
+            let mut batch = Vec::with_capacity(BATCH_SIZE);
+            while rx.recv_many(&mut batch, BATCH_SIZE).await > 0 {
+                for sink in &sinks {
+                    if let Err(e) = sink.send(&batch).await {
+                        tracing::warn!(
+                            "Failed to forward {} audit entries to {}: {}",
+                            batch.len(),
+                            sink.name(),
+                            e
+                        );
+                    }
+                }
+                batch.clear();
+            }
+        });
+        Self { tx }
+    }
+
+    pub fn publish(&self, event: AuditEvent) {
+        if let Err(e) = self.tx.try_send(event) {
+            tracing::warn!("Audit stream queue unavailable, entry not forwarded: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> AuditEvent {
+        AuditEvent {
+            id: "e1".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2026-04-19T09:00:00.5Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            user_id: Some("u1".to_string()),
+            tenant_id: Some("t1".to_string()),
+            action: "CUSTOMER UPDATE".to_string(),
+            resource: "customers".to_string(),
+            resource_id: Some("c1".to_string()),
+            details: None,
+            ip_address: None,
+            old_value: None,
+            new_value: None,
+        }
+    }
+
+    #[test]
+    fn syslog_messages_follow_rfc5424() {
+        let msg = format_syslog(&event(), "noc 1");
+        let prefix = format!(
+            "<109>1 2026-04-19T09:00:00.500000Z noc1 isp-management {} CUSTOMERUPDATE - \u{feff}{{",
+            std::process::id()
+        );
+        assert!(msg.starts_with(&prefix), "{}", msg);
+        let body = msg.split_once('\u{feff}').unwrap().1;
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(json["resource_id"], "c1");
+
+        assert!(format_syslog(&event(), "").contains(" - isp-management "));
+    }
+
+    #[test]
+    fn syslog_addresses() {
+        assert_eq!(
+            parse_syslog_addr("siem.local:514").unwrap(),
+            (SyslogTransport::Udp, "siem.local:514".to_string())
+        );
+        assert_eq!(
+            parse_syslog_addr("tcp://10.0.0.5:601").unwrap(),
+            (SyslogTransport::Tcp, "10.0.0.5:601".to_string())
+        );
+        assert!(parse_syslog_addr("https://siem.local").is_err());
+        assert!(parse_syslog_addr("udp://siem.local").is_err());
+        assert!(parse_syslog_addr(":514").is_err());
+    }
+
+    #[tokio::test]
+    async fn file_sink_appends_json_lines() {
+        let path = std::env::temp_dir().join(format!("audit-{}.jsonl", uuid::Uuid::new_v4()));
+        let sink = FileSink::new(path.clone());
+        sink.send(&[event(), event()]).await.unwrap();
+        sink.send(&[event()]).await.unwrap();
+
+        let written = tokio::fs::read_to_string(&path).await.unwrap();
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for line in lines {
+            let json: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(json["action"], "CUSTOMER UPDATE");
+        }
+        tokio::fs::remove_file(&path).await.ok();
+    }
+}
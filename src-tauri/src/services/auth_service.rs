@@ -3,7 +3,9 @@
 use crate::db::connection::DbPool;
 use crate::error::{AppError, AppResult};
 use crate::models::{LoginDto, RegisterDto, TrustedDevice, User, UserResponse};
-use crate::services::{AuditService, EmailService, SettingsService};
+use crate::services::{
+    AuditService, EmailOutboxService, EmailService, EmailTemplateService, SettingsService,
+};
 use argon2::{
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
@@ -12,12 +14,34 @@ use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use totp_rs::{Algorithm, Secret, TOTP};
 use tracing::{info, warn};
 use uuid::Uuid;
 
+/// Start of every tenant API key. Keys are accepted wherever a session token
+/// is and act as the user who created them.
+pub const API_KEY_PREFIX: &str = "ispk_";
+
+/// Stored form of an API key: SHA-256 as lowercase hex.
+pub fn api_key_hash(key: &str) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+#[derive(sqlx::FromRow)]
+struct ApiKeyIdentity {
+    key_id: String,
+    tenant_id: String,
+    user_id: String,
+    email: String,
+    role: String,
+    tenant_active: bool,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
 /// JWT Claims structure
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
@@ -93,7 +117,9 @@ impl Default for AuthSettings {
 pub struct AuthService {
     pub pool: DbPool,
     jwt_secret: Arc<RwLock<String>>,
-    email_service: EmailService,
+    email_templates: EmailTemplateService,
+    /// Priority lane for OTP, verification and reset mail.
+    email_outbox: EmailOutboxService,
     audit_service: AuditService,
     settings_service: SettingsService,
     /// Cached auth settings with TTL (60 seconds)
@@ -109,9 +135,18 @@ impl AuthService {
         settings_service: SettingsService,
     ) -> Self {
         Self {
+            email_templates: EmailTemplateService::new(
+                pool.clone(),
+                settings_service.clone(),
+                email_service.clone(),
+            ),
+            email_outbox: EmailOutboxService::new(
+                pool.clone(),
+                settings_service.clone(),
+                email_service,
+            ),
             pool,
             jwt_secret: Arc::new(RwLock::new(jwt_secret)),
-            email_service,
             audit_service,
             settings_service,
             // Initialize cache with 60 second TTL
@@ -347,6 +382,10 @@ impl AuthService {
 
     /// Validate JWT token and return claims
     pub async fn validate_token(&self, token: &str) -> AppResult<Claims> {
+        if token.starts_with(API_KEY_PREFIX) {
+            return self.validate_api_key(token).await;
+        }
+
         // Validate against active session first (sliding inactivity timeout).
         let now = Utc::now();
         let settings = self.get_auth_settings().await;
@@ -455,6 +494,58 @@ impl AuthService {
         Ok(claims)
     }
 
+    /// Claims of the user behind an API key. Keys have no session: they work
+    /// until revoked or expired, and only while their user is active and
+    /// still a member of the key's tenant.
+    async fn validate_api_key(&self, token: &str) -> AppResult<Claims> {
+        let now = Utc::now();
+        let key: ApiKeyIdentity = sqlx::query_as(
+            r#"
+            SELECT k.id AS key_id, k.tenant_id, u.id AS user_id, u.email, u.role,
+                   t.is_active AS tenant_active, k.last_used_at
+            FROM tenant_api_keys k
+            JOIN users u ON u.id = k.user_id
+            JOIN tenants t ON t.id = k.tenant_id
+            JOIN tenant_members m ON m.tenant_id = k.tenant_id AND m.user_id = k.user_id
+            WHERE k.key_hash = $1
+              AND k.revoked_at IS NULL
+              AND (k.expires_at IS NULL OR k.expires_at > $2)
+              AND u.is_active = true
+              AND u.deleted_at IS NULL
+            "#,
+        )
+        .bind(api_key_hash(token))
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AppError::InvalidToken)?;
+        if !key.tenant_active {
+            return Err(AppError::Forbidden("Tenant is suspended".to_string()));
+        }
+
+        // Minute precision is plenty and saves a write per request.
+        if key
+            .last_used_at
+            .is_none_or(|t| now - t > Duration::minutes(1))
+        {
+            let _ = sqlx::query("UPDATE tenant_api_keys SET last_used_at = $1 WHERE id = $2")
+                .bind(now)
+                .bind(&key.key_id)
+                .execute(&self.pool)
+                .await;
+        }
+
+        Ok(Claims {
+            sub: key.user_id,
+            email: key.email,
+            role: key.role,
+            tenant_id: Some(key.tenant_id),
+            is_super_admin: false,
+            exp: 0,
+            iat: now.timestamp() as usize,
+        })
+    }
+
     /// Validate 2FA temp token (does not check sessions table)
     /// This is used for temporary tokens during 2FA verification flow
     pub async fn validate_2fa_token(&self, token: &str) -> AppResult<Claims> {
@@ -776,16 +867,25 @@ impl AuthService {
             // ... existing email code ...
             // Send verification email
             if let Some(token) = &user.verification_token {
-                let link = format!("/auth/verify-email?token={}", token);
-
-                let body = format!(
-                    "Welcome, {}!\n\nPlease verify your email by clicking the link below:\n{}\n\nIf you cannot click the link, use this code: {}",
-                    user.name, link, token
-                );
+                let vars = HashMap::from([
+                    ("user_name", user.name.clone()),
+                    ("verify_url", format!("/auth/verify-email?token={}", token)),
+                    ("code", token.clone()),
+                ]);
+                let email = self
+                    .email_templates
+                    .render(None, "verification", &vars)
+                    .await;
 
                 if let Err(e) = self
-                    .email_service
-                    .send_email(&user.email, "Verify your email", &body)
+                    .email_outbox
+                    .send_priority(
+                        None,
+                        &user.email,
+                        &email.subject,
+                        &email.text,
+                        Some(email.html),
+                    )
                     .await
                 {
                     warn!("Failed to send verification email: {}", e);
@@ -942,15 +1042,27 @@ impl AuthService {
             query.bind(&user.id).execute(&self.pool).await?;
 
             // Send email
-            let link = format!("/forgot-password/reset?token={}", token);
-            let body = format!(
-                "Hello {},\n\nYou requested a password reset. Click the link below to reset your password:\n{}\n\nThis link expires in 1 hour.\n\nIf you did not request this, please ignore this email.",
-                user.name, link
-            );
+            let vars = HashMap::from([
+                ("user_name", user.name.clone()),
+                (
+                    "reset_url",
+                    format!("/forgot-password/reset?token={}", token),
+                ),
+            ]);
+            let email = self
+                .email_templates
+                .render(None, "password_reset", &vars)
+                .await;
 
             if let Err(e) = self
-                .email_service
-                .send_email(&user.email, "Reset your password", &body)
+                .email_outbox
+                .send_priority(
+                    None,
+                    &user.email,
+                    &email.subject,
+                    &email.text,
+                    Some(email.html),
+                )
                 .await
             {
                 warn!("Failed to send reset email: {}", e);
@@ -1353,7 +1465,7 @@ impl AuthService {
 
     /// Get user by ID
     pub async fn get_user_by_id(&self, user_id: &str) -> AppResult<User> {
-        sqlx::query_as("SELECT * FROM users WHERE id = $1")
+        sqlx::query_as("SELECT * FROM users WHERE id = $1 AND deleted_at IS NULL")
             .bind(user_id)
             .fetch_optional(&self.pool)
             .await?
@@ -2200,8 +2312,8 @@ impl AuthService {
             user.name, code_str, expiry_minutes
         );
 
-        self.email_service
-            .send_email(&user.email, subject, &body)
+        self.email_outbox
+            .send_priority(None, &user.email, subject, &body, None)
             .await?;
 
         info!("Email OTP sent to user {}", user_id);
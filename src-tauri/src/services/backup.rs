@@ -1,11 +1,20 @@
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
-use crate::models::UpsertSettingDto;
-use crate::services::SettingsService;
-use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use crate::models::{BackgroundJob, UpsertSettingDto};
+use crate::services::backup_remote::{RemoteBackupConfig, RemoteBackupStore};
+use crate::services::backup_validation::{
+    self, ArchiveContents, BackupManifest, RestoreValidationReport, ValidationTarget, MANIFEST_FILE,
+};
+use crate::services::cron_schedule::CronSchedule;
+use crate::services::{AuditService, JobHandler, JobQueue, SettingsService};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
 use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tokio::fs;
 use tracing::{error, info, warn};
 
@@ -23,6 +32,90 @@ pub struct BackupRecord {
     pub created_at: chrono::DateTime<Utc>,
     pub backup_type: String, // "global" or "tenant"
     pub tenant_id: Option<String>,
+    /// Pinned backups are never pruned by retention and cannot be deleted.
+    pub pinned: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ScratchTable {
+    pub name: String,
+    pub rows_in_archive: usize,
+    /// Lower than `rows_in_archive` when rows were skipped or filtered to the tenant.
+    pub rows_restored: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ScratchRestoreReport {
+    pub schema: String,
+    pub validation: RestoreValidationReport,
+    pub tables: Vec<ScratchTable>,
+}
+
+/// Tables a restore writes, in foreign key order.
+pub(crate) const RESTORE_ORDER: &[&str] = &[
+    "permissions",
+    "features",
+    "plans",
+    "bank_accounts",
+    "fx_rates",
+    "tenants",
+    "users",
+    "roles",
+    "settings",
+    "plan_features",
+    "tenant_subscriptions",
+    "file_records",
+    "invoices",
+    "invoice_reminder_logs",
+    "billing_collection_logs",
+    "customer_registration_invites",
+    "notifications",
+    "tenant_members",
+    "role_permissions",
+    "trusted_devices",
+    "notification_preferences",
+    "push_subscriptions",
+    // Announcements
+    "announcements",
+    "announcement_dismissals",
+    // Support
+    "support_tickets",
+    "support_ticket_messages",
+    "support_ticket_attachments",
+    // Outbox (global/admin tools)
+    "email_outbox",
+    "audit_logs",
+];
+
+/// Tables a tenant-scoped restore never writes (platform and billing data).
+const TENANT_RESTORE_SKIP: &[&str] = &[
+    "permissions",
+    "features",
+    "plans",
+    "plan_features",
+    "bank_accounts",
+    "fx_rates",
+    "tenants",
+    "users",
+    "tenant_subscriptions",
+    "invoices",
+    "invoice_reminder_logs",
+    "billing_collection_logs",
+    "customer_registration_invites",
+    "trusted_devices",
+    "email_outbox",
+];
+
+/// Scratch schemas are named `restore_scratch_<hex>`.
+const SCRATCH_SCHEMA_PREFIX: &str = "restore_scratch_";
+
+/// Suffix of the marker file that pins a backup (`<name>.zip.pinned`).
+const PIN_SUFFIX: &str = ".pinned";
+
+fn pin_marker_path(backup_path: &Path) -> PathBuf {
+    let mut name = backup_path.as_os_str().to_os_string();
+    name.push(PIN_SUFFIX);
+    PathBuf::from(name)
 }
 
 impl BackupService {
@@ -58,7 +151,7 @@ impl BackupService {
         )
     }
 
-    fn redact_settings_rows(
+    pub(crate) fn redact_settings_rows(
         mut rows: Vec<serde_json::Map<String, serde_json::Value>>,
     ) -> Vec<serde_json::Map<String, serde_json::Value>> {
         for row in &mut rows {
@@ -101,11 +194,12 @@ impl BackupService {
                     .map_err(|e| AppError::Internal(e.to_string()))?;
                 let name = entry.file_name().to_string_lossy().to_string();
 
-                if !name.starts_with("global_backup_") {
+                if !name.starts_with("global_backup_") || name.ends_with(PIN_SUFFIX) {
                     continue;
                 }
 
                 backups.push(BackupRecord {
+                    pinned: pin_marker_path(&path).exists(),
                     name,
                     path: path.to_string_lossy().to_string(),
                     size: metadata.len(),
@@ -149,11 +243,12 @@ impl BackupService {
                     let name = entry.file_name().to_string_lossy().to_string();
 
                     let expected_prefix = format!("tenant_{}_", tenant_id);
-                    if !name.starts_with(&expected_prefix) {
+                    if !name.starts_with(&expected_prefix) || name.ends_with(PIN_SUFFIX) {
                         continue;
                     }
 
                     backups.push(BackupRecord {
+                        pinned: pin_marker_path(&path).exists(),
                         name,
                         path: path.to_string_lossy().to_string(),
                         size: metadata.len(),
@@ -263,25 +358,15 @@ impl BackupService {
             }
         }
 
-        // --- ZIP CREATION ---
-        use std::io::Write;
-        use zip::write::FileOptions;
-
-        let file =
-            std::fs::File::create(&zip_path).map_err(|e| AppError::Internal(e.to_string()))?;
-        let mut zip = zip::ZipWriter::new(file);
-        let options = FileOptions::default().compression_method(zip::CompressionMethod::Stored);
-
-        for (filename, json_data) in data_map {
-            zip.start_file(filename, options)
-                .map_err(|e: zip::result::ZipError| AppError::Internal(e.to_string()))?;
-            let json_str = serde_json::to_string_pretty(&json_data).unwrap_or_default();
-            zip.write_all(json_str.as_bytes())
-                .map_err(|e: std::io::Error| AppError::Internal(e.to_string()))?;
-        }
-
-        zip.finish()
-            .map_err(|e: zip::result::ZipError| AppError::Internal(e.to_string()))?;
+        let schema_version = self.current_schema_version().await;
+        write_backup_zip(
+            &zip_path,
+            data_map,
+            "global",
+            None,
+            schema_version,
+            zip::CompressionMethod::Stored,
+        )?;
 
         info!("Global Backup successful: {:?}", zip_path);
         Ok(zip_path.to_string_lossy().to_string())
@@ -504,32 +589,22 @@ impl BackupService {
             serde_json::to_value(&role_permissions_rows).unwrap(),
         );
 
-        // --- ZIP CREATION ---
-        use std::io::Write;
-        use zip::write::FileOptions;
-
-        let file =
-            std::fs::File::create(&zip_path).map_err(|e| AppError::Internal(e.to_string()))?;
-        let mut zip = zip::ZipWriter::new(file);
-        let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
-
-        for (filename, json_data) in data_map {
-            zip.start_file(filename, options)
-                .map_err(|e: zip::result::ZipError| AppError::Internal(e.to_string()))?;
-            let json_str = serde_json::to_string_pretty(&json_data).unwrap_or_default();
-            zip.write_all(json_str.as_bytes())
-                .map_err(|e: std::io::Error| AppError::Internal(e.to_string()))?;
-        }
-
-        zip.finish()
-            .map_err(|e: zip::result::ZipError| AppError::Internal(e.to_string()))?;
+        let schema_version = self.current_schema_version().await;
+        write_backup_zip(
+            &zip_path,
+            data_map,
+            "tenant",
+            Some(tenant_id),
+            schema_version,
+            zip::CompressionMethod::Deflated,
+        )?;
 
         info!("Tenant Backup successful: {:?}", zip_path);
         Ok(zip_path.to_string_lossy().to_string())
     }
 
     // Helper to fetch generic rows as JSON
-    async fn fetch_rows(
+    pub(crate) async fn fetch_rows(
         &self,
         _sqlite_sql: &str,
         _pg_sql: &str,
@@ -681,37 +756,18 @@ impl BackupService {
         }
     }
 
+    /// Where `filename` lives (or would live) locally. Validates the name to prevent
+    /// directory traversal; does not check that the file exists.
+    pub fn local_backup_path(&self, filename: &str) -> AppResult<PathBuf> {
+        Ok(self
+            .get_backup_root_dir()
+            .join(backup_subdir(filename)?)
+            .join(filename))
+    }
+
     /// Get absolute path to a backup file, validating its name to prevent directory traversal
     pub fn get_backup_path(&self, filename: &str) -> AppResult<PathBuf> {
-        // Basic validation: allow alphanumeric, underscores, dashes, and dots
-        if !filename
-            .chars()
-            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
-        {
-            return Err(AppError::Validation(
-                "Invalid characters in filename".to_string(),
-            ));
-        }
-
-        // Prevent directory traversal
-        if filename.contains("..") || filename.contains('/') || filename.contains('\\') {
-            return Err(AppError::Validation("Invalid filename format".to_string()));
-        }
-
-        let file_path = if filename.starts_with("global_backup_") {
-            self.get_global_backup_dir().join(filename)
-        } else if filename.starts_with("tenant_") {
-            let parts: Vec<&str> = filename.split('_').collect();
-            if parts.len() < 3 {
-                return Err(AppError::Validation(
-                    "Invalid tenant backup filename".to_string(),
-                ));
-            }
-            let tenant_id = parts[1];
-            self.get_tenant_backup_dir(tenant_id).join(filename)
-        } else {
-            return Err(AppError::Validation("Unknown backup filename".to_string()));
-        };
+        let file_path = self.local_backup_path(filename)?;
 
         if !file_path.exists() {
             return Err(AppError::NotFound(format!(
@@ -725,13 +781,34 @@ impl BackupService {
 
     pub async fn delete_backup(&self, filename: String) -> AppResult<()> {
         let path = self.get_backup_path(&filename)?;
+        if pin_marker_path(&path).exists() {
+            return Err(AppError::Conflict(
+                "Backup is pinned; unpin it before deleting".to_string(),
+            ));
+        }
         fs::remove_file(path)
             .await
             .map_err(|e| AppError::Internal(e.to_string()))?;
         Ok(())
     }
 
-    /// Restore system or tenant data from a ZIP backup file
+    /// Pin or unpin a backup. The flag is a marker file next to the archive, so it
+    /// survives restarts and travels with the backup directory.
+    pub async fn set_pinned(&self, filename: &str, pinned: bool) -> AppResult<()> {
+        let marker = pin_marker_path(&self.get_backup_path(filename)?);
+        let res = if pinned {
+            fs::write(&marker, Utc::now().to_rfc3339()).await
+        } else {
+            match fs::remove_file(&marker).await {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                other => other,
+            }
+        };
+        res.map_err(|e| AppError::Internal(e.to_string()))
+    }
+
+    /// Restore system or tenant data from a ZIP backup file. The archive is
+    /// validated first; any validation error aborts before the database is touched.
     pub async fn restore_from_zip(
         &self,
         zip_path: PathBuf,
@@ -739,98 +816,183 @@ impl BackupService {
     ) -> AppResult<()> {
         info!("Starting restore from {:?}", zip_path);
 
-        // 1. Read everything into memory first
-        let mut table_data: std::collections::HashMap<String, String> =
-            std::collections::HashMap::new();
+        let contents = backup_validation::read_archive(&zip_path)?;
+        let report = self.check_contents(&contents, target_tenant_id).await?;
+        if !report.ok {
+            return Err(AppError::Validation(format!(
+                "Backup failed validation: {}",
+                report.error_summary()
+            )));
+        }
 
-        {
-            let file =
-                std::fs::File::open(&zip_path).map_err(|e| AppError::Internal(e.to_string()))?;
-            let mut archive =
-                zip::ZipArchive::new(file).map_err(|e| AppError::Internal(e.to_string()))?;
-
-            for i in 0..archive.len() {
-                let mut file = archive
-                    .by_index(i)
-                    .map_err(|e| AppError::Internal(e.to_string()))?;
-                let outpath = match file.enclosed_name() {
-                    Some(path) => path.to_owned(),
-                    None => continue,
-                };
+        self.restore_contents(contents.tables, target_tenant_id, None)
+            .await
+    }
 
-                if !outpath.to_string_lossy().ends_with(".json") {
-                    continue;
-                }
+    /// Inspect an archive without restoring it: manifest, checksums, row counts and
+    /// compatibility with the current schema.
+    pub async fn validate_backup(
+        &self,
+        zip_path: &Path,
+        target_tenant_id: Option<&str>,
+    ) -> AppResult<RestoreValidationReport> {
+        let contents = backup_validation::read_archive(zip_path)?;
+        self.check_contents(&contents, target_tenant_id).await
+    }
 
-                let table_name = outpath.file_stem().unwrap().to_string_lossy().to_string();
+    async fn check_contents(
+        &self,
+        contents: &ArchiveContents,
+        target_tenant_id: Option<&str>,
+    ) -> AppResult<RestoreValidationReport> {
+        let restorable: Vec<&str> = RESTORE_ORDER
+            .iter()
+            .copied()
+            .filter(|t| target_tenant_id.is_none() || !TENANT_RESTORE_SKIP.contains(t))
+            .collect();
+        let columns = self.live_columns(&restorable).await?;
+
+        Ok(backup_validation::check_archive(
+            contents,
+            &ValidationTarget {
+                current_schema_version: self.current_schema_version().await,
+                target_tenant_id,
+                restorable: &restorable,
+                columns: &columns,
+            },
+        ))
+    }
 
-                let mut contents = String::new();
-                use std::io::Read;
-                file.read_to_string(&mut contents)
-                    .map_err(|e| AppError::Internal(e.to_string()))?;
+    /// Latest successfully applied migration, recorded in new backup manifests.
+    pub(crate) async fn current_schema_version(&self) -> Option<i64> {
+        sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT MAX(version) FROM _sqlx_migrations WHERE success = true",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    /// Column names of each existing table in `tables`.
+    pub(crate) async fn live_columns(
+        &self,
+        tables: &[&str],
+    ) -> AppResult<HashMap<String, HashSet<String>>> {
+        let mut columns: HashMap<String, HashSet<String>> = HashMap::new();
+
+        #[cfg(feature = "postgres")]
+        {
+            let names: Vec<String> = tables.iter().map(|t| t.to_string()).collect();
+            let rows: Vec<(String, String)> = sqlx::query_as(
+                "SELECT table_name::text, column_name::text FROM information_schema.columns WHERE table_schema = 'public' AND table_name = ANY($1)",
+            )
+            .bind(&names)
+            .fetch_all(&self.pool)
+            .await?;
+            for (table, column) in rows {
+                columns.entry(table).or_default().insert(column);
+            }
+        }
 
-                table_data.insert(table_name, contents);
+        #[cfg(feature = "sqlite")]
+        for table in tables {
+            let cols: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+                .bind(*table)
+                .fetch_all(&self.pool)
+                .await?;
+            if !cols.is_empty() {
+                columns.insert(table.to_string(), cols.into_iter().collect());
             }
         }
 
-        // 2. Define Restoration Order (Foreign Key Hierarchy)
-        let restore_order = vec![
-            "permissions",
-            "features",
-            "plans",
-            "bank_accounts",
-            "fx_rates",
-            "tenants",
-            "users",
-            "roles",
-            "settings",
-            "plan_features",
-            "tenant_subscriptions",
-            "file_records",
-            "invoices",
-            "invoice_reminder_logs",
-            "billing_collection_logs",
-            "customer_registration_invites",
-            "notifications",
-            "tenant_members",
-            "role_permissions",
-            "trusted_devices",
-            "notification_preferences",
-            "push_subscriptions",
-            // Announcements
-            "announcements",
-            "announcement_dismissals",
-            // Support
-            "support_tickets",
-            "support_ticket_messages",
-            "support_ticket_attachments",
-            // Outbox (global/admin tools)
-            "email_outbox",
-            "audit_logs",
-        ];
+        Ok(columns)
+    }
 
-        let tenant_skip: std::collections::HashSet<&str> = if target_tenant_id.is_some() {
-            [
-                "permissions",
-                "features",
-                "plans",
-                "plan_features",
-                "bank_accounts",
-                "fx_rates",
-                "tenants",
-                "users",
-                "tenant_subscriptions",
-                "invoices",
-                "invoice_reminder_logs",
-                "billing_collection_logs",
-                "customer_registration_invites",
-                "trusted_devices",
-                "email_outbox",
-            ]
-            .into_iter()
-            .collect()
+    /// Restore an archive into a fresh scratch schema instead of the live tables, so
+    /// the result can be inspected before running the real restore. Postgres only.
+    ///
+    /// Scratch tables copy columns, defaults, constraints and indexes but not foreign
+    /// keys. The schema is left in place until [`drop_scratch_schema`](Self::drop_scratch_schema).
+    pub async fn restore_to_scratch(
+        &self,
+        zip_path: &Path,
+        target_tenant_id: Option<&str>,
+    ) -> AppResult<ScratchRestoreReport> {
+        if cfg!(not(feature = "postgres")) {
+            return Err(AppError::Validation(
+                "Scratch restore requires PostgreSQL".to_string(),
+            ));
+        }
+
+        let contents = backup_validation::read_archive(zip_path)?;
+        let validation = self.check_contents(&contents, target_tenant_id).await?;
+        if !validation.ok {
+            return Err(AppError::Validation(format!(
+                "Backup failed validation: {}",
+                validation.error_summary()
+            )));
+        }
+
+        let schema = format!(
+            "{}{}",
+            SCRATCH_SCHEMA_PREFIX,
+            &uuid::Uuid::new_v4().simple().to_string()[..12]
+        );
+        info!("Restoring {:?} into scratch schema {}", zip_path, schema);
+        self.restore_contents(contents.tables, target_tenant_id, Some(&schema))
+            .await?;
+
+        let mut tables = Vec::new();
+        for check in validation.tables.iter().filter(|t| t.will_restore) {
+            let restored: i64 = sqlx::query_scalar(&format!(
+                "SELECT COUNT(*) FROM \"{}\".\"{}\"",
+                schema, check.name
+            ))
+            .fetch_one(&self.pool)
+            .await?;
+            tables.push(ScratchTable {
+                name: check.name.clone(),
+                rows_in_archive: check.rows,
+                rows_restored: restored,
+            });
+        }
+
+        Ok(ScratchRestoreReport {
+            schema,
+            validation,
+            tables,
+        })
+    }
+
+    /// Drop a schema created by [`restore_to_scratch`](Self::restore_to_scratch).
+    pub async fn drop_scratch_schema(&self, schema: &str) -> AppResult<()> {
+        let suffix = schema
+            .strip_prefix(SCRATCH_SCHEMA_PREFIX)
+            .unwrap_or_default();
+        if suffix.is_empty() || !suffix.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(AppError::Validation(
+                "Not a restore scratch schema".to_string(),
+            ));
+        }
+        sqlx::query(&format!("DROP SCHEMA IF EXISTS \"{}\" CASCADE", schema))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Write already-validated table data. With `scratch_schema`, tables are created
+    /// in that schema and written there instead of the live ones.
+    async fn restore_contents(
+        &self,
+        table_data: HashMap<String, String>,
+        target_tenant_id: Option<&str>,
+        scratch_schema: Option<&str>,
+    ) -> AppResult<()> {
+        let tenant_skip: HashSet<&str> = if target_tenant_id.is_some() {
+            TENANT_RESTORE_SKIP.iter().copied().collect()
         } else {
-            std::collections::HashSet::new()
+            HashSet::new()
         };
 
         // If this is a tenant restore, pre-compute allowed role_ids and user_ids for validation
@@ -942,10 +1104,30 @@ impl BackupService {
             .await
             .map_err(|e| AppError::Internal(e.to_string()))?;
 
-        // 4. CLEANUP (Reverse Order)
-        if target_tenant_id.is_none() {
+        #[cfg(feature = "postgres")]
+        if let Some(schema) = scratch_schema {
+            sqlx::query(&format!("CREATE SCHEMA \"{}\"", schema))
+                .execute(&mut *tx)
+                .await?;
+            for table_name in RESTORE_ORDER {
+                sqlx::query(&format!(
+                    "CREATE TABLE \"{}\".\"{}\" (LIKE public.\"{}\" INCLUDING ALL)",
+                    schema, table_name, table_name
+                ))
+                .execute(&mut *tx)
+                .await?;
+            }
+            // Unqualified names below now resolve to the scratch tables.
+            sqlx::query("SELECT set_config('search_path', $1, true)")
+                .bind(format!("\"{}\"", schema))
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // 4. CLEANUP (Reverse Order). Scratch tables start empty.
+        if target_tenant_id.is_none() && scratch_schema.is_none() {
             // Global Cleanup
-            for table_name in restore_order.iter().rev() {
+            for table_name in RESTORE_ORDER.iter().rev() {
                 let trunc_query = format!("DELETE FROM {}", table_name);
                 #[cfg(feature = "postgres")]
                 sqlx::query(&trunc_query).execute(&mut *tx).await.ok();
@@ -961,7 +1143,7 @@ impl BackupService {
         }
 
         // 5. RESTORE (In Order)
-        for table_name in restore_order {
+        for &table_name in RESTORE_ORDER {
             if tenant_skip.contains(table_name) {
                 continue;
             }
@@ -1228,6 +1410,8 @@ impl BackupService {
                             || lc.contains("json")
                             || lc.contains("metadata")
                             || lc.contains("payload")
+                            || lc == "old_value"
+                            || lc == "new_value"
                     }
 
                     fn is_uuid_col(table: &str, col: &str) -> bool {
@@ -1463,6 +1647,99 @@ impl BackupService {
         let path = self.get_backup_path(&filename)?;
         self.restore_from_zip(path, target_tenant_id).await
     }
+
+    /// Download `filename` from off-site storage into the backups directory, then
+    /// restore it. The downloaded copy is kept and shows up in the local list.
+    pub async fn restore_remote_backup(
+        &self,
+        store: &RemoteBackupStore,
+        filename: String,
+        target_tenant_id: Option<&str>,
+    ) -> AppResult<()> {
+        let path = self.local_backup_path(&filename)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+        }
+        store.download(&filename, &path).await?;
+        self.restore_from_zip(path, target_tenant_id).await
+    }
+}
+
+/// Write table files (`<table>.json` → rows) and their manifest to `zip_path`.
+pub(crate) fn write_backup_zip(
+    zip_path: &Path,
+    data_map: HashMap<String, serde_json::Value>,
+    scope: &str,
+    tenant_id: Option<&str>,
+    schema_version: Option<i64>,
+    compression: zip::CompressionMethod,
+) -> AppResult<()> {
+    use std::io::Write;
+    use zip::write::FileOptions;
+
+    let mut files: Vec<(String, String)> = data_map
+        .into_iter()
+        .map(|(filename, json_data)| {
+            let json_str = serde_json::to_string_pretty(&json_data).unwrap_or_default();
+            (filename, json_str)
+        })
+        .collect();
+    let manifest = BackupManifest::build(scope, tenant_id, schema_version, &files);
+    files.push((
+        MANIFEST_FILE.to_string(),
+        serde_json::to_string_pretty(&manifest).unwrap_or_default(),
+    ));
+
+    let file = std::fs::File::create(zip_path).map_err(|e| AppError::Internal(e.to_string()))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = FileOptions::default().compression_method(compression);
+
+    for (filename, json_str) in files {
+        zip.start_file(filename, options)
+            .map_err(|e: zip::result::ZipError| AppError::Internal(e.to_string()))?;
+        zip.write_all(json_str.as_bytes())
+            .map_err(|e: std::io::Error| AppError::Internal(e.to_string()))?;
+    }
+
+    zip.finish()
+        .map_err(|e: zip::result::ZipError| AppError::Internal(e.to_string()))?;
+    Ok(())
+}
+
+/// Directory of a backup archive relative to the backup root, derived from its
+/// name: `global` for `global_backup_*`, `tenants/<id>` for `tenant_<id>_*`.
+/// Rejects names that could escape the backup root.
+pub(crate) fn backup_subdir(filename: &str) -> AppResult<String> {
+    // Basic validation: allow alphanumeric, underscores, dashes, and dots
+    if !filename
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+    {
+        return Err(AppError::Validation(
+            "Invalid characters in filename".to_string(),
+        ));
+    }
+
+    // Prevent directory traversal
+    if filename.contains("..") || filename.contains('/') || filename.contains('\\') {
+        return Err(AppError::Validation("Invalid filename format".to_string()));
+    }
+
+    if filename.starts_with("global_backup_") {
+        Ok("global".to_string())
+    } else if filename.starts_with("tenant_") {
+        let parts: Vec<&str> = filename.split('_').collect();
+        if parts.len() < 3 || parts[1].is_empty() {
+            return Err(AppError::Validation(
+                "Invalid tenant backup filename".to_string(),
+            ));
+        }
+        Ok(format!("tenants/{}", parts[1]))
+    } else {
+        Err(AppError::Validation("Unknown backup filename".to_string()))
+    }
 }
 
 // --- SCHEDULER ---
@@ -1470,6 +1747,7 @@ pub struct BackupScheduler {
     pool: DbPool,
     backup_service: BackupService,
     settings_service: SettingsService,
+    audit_service: AuditService,
 }
 
 impl BackupScheduler {
@@ -1477,141 +1755,72 @@ impl BackupScheduler {
         pool: DbPool,
         backup_service: BackupService,
         settings_service: SettingsService,
+        audit_service: AuditService,
     ) -> Self {
         Self {
             pool,
             backup_service,
             settings_service,
+            audit_service,
         }
     }
 
-    pub async fn start(&self) {
-        let pool = self.pool.clone();
-        let service = self.backup_service.clone();
-        let settings_service = self.settings_service.clone();
-
-        tokio::spawn(async move {
-            info!("Backup Scheduler started.");
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60)); // Check every minute
-            let mut warned_missing_schema = false;
-
-            loop {
-                interval.tick().await;
-
-                #[cfg(feature = "postgres")]
-                {
-                    // Prevent duplicate processing when running multiple instances.
-                    let mut advisory_conn = match pool.acquire().await {
-                        Ok(c) => c,
-                        Err(e) => {
-                            warn!(
-                                "Backup scheduler skipped: failed to acquire DB connection: {}",
-                                e
-                            );
-                            continue;
-                        }
-                    };
-
-                    let locked: bool =
-                        sqlx::query_scalar("SELECT pg_try_advisory_lock(hashtext($1))")
-                            .bind("backup_scheduler")
-                            .fetch_one(&mut *advisory_conn)
-                            .await
-                            .unwrap_or(false);
-                    if !locked {
-                        continue;
-                    }
+    /// Checks backup schedules every minute on the job queue.
+    pub async fn schedule(self, queue: &JobQueue) {
+        queue
+            .register_recurring(
+                "backups.run_scheduled",
+                std::time::Duration::from_secs(60),
+                Arc::new(self),
+            )
+            .await;
+    }
 
-                    // 1. Check Global Schedule
-                    if let Err(e) =
-                        Self::check_and_run_global(&pool, &service, &settings_service).await
-                    {
-                        if e.contains("relation \"settings\" does not exist")
-                            || e.contains("relation \"tenants\" does not exist")
-                        {
-                            if !warned_missing_schema {
-                                warned_missing_schema = true;
-                                warn!(
-                                    "Backup scheduler paused: database schema not migrated yet (missing settings/tenants tables)."
-                                );
-                            }
-                        } else {
-                            error!("Global backup schedule check failed: {}", e);
-                        }
-                    }
+    /// Runs whichever global, tenant and off-site backups are due. The three
+    /// checks are independent; a failing one doesn't hold back the others.
+    async fn run_due(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
 
-                    // 2. Check Tenant Schedules
-                    if let Err(e) =
-                        Self::check_and_run_tenants(&pool, &service, &settings_service).await
-                    {
-                        if e.contains("relation \"settings\" does not exist")
-                            || e.contains("relation \"tenants\" does not exist")
-                        {
-                            if !warned_missing_schema {
-                                warned_missing_schema = true;
-                                warn!(
-                                    "Backup scheduler paused: database schema not migrated yet (missing settings/tenants tables)."
-                                );
-                            }
-                        } else {
-                            error!("Tenant backup schedule check failed: {}", e);
-                        }
-                    }
+        if let Err(e) = Self::check_and_run_global(
+            &self.pool,
+            &self.backup_service,
+            &self.settings_service,
+            &self.audit_service,
+        )
+        .await
+        {
+            errors.push(format!("Global backup schedule check failed: {}", e));
+        }
 
-                    let _ =
-                        sqlx::query_scalar::<_, bool>("SELECT pg_advisory_unlock(hashtext($1))")
-                            .bind("backup_scheduler")
-                            .fetch_one(&mut *advisory_conn)
-                            .await;
-                    continue;
-                }
+        if let Err(e) = Self::check_and_run_tenants(
+            &self.pool,
+            &self.backup_service,
+            &self.settings_service,
+            &self.audit_service,
+        )
+        .await
+        {
+            errors.push(format!("Tenant backup schedule check failed: {}", e));
+        }
 
-                #[cfg(not(feature = "postgres"))]
-                {
-                    // 1. Check Global Schedule
-                    if let Err(e) =
-                        Self::check_and_run_global(&pool, &service, &settings_service).await
-                    {
-                        if e.contains("relation \"settings\" does not exist")
-                            || e.contains("relation \"tenants\" does not exist")
-                        {
-                            if !warned_missing_schema {
-                                warned_missing_schema = true;
-                                warn!(
-                                    "Backup scheduler paused: database schema not migrated yet (missing settings/tenants tables)."
-                                );
-                            }
-                        } else {
-                            error!("Global backup schedule check failed: {}", e);
-                        }
-                    }
+        if let Err(e) =
+            Self::check_and_run_offsite(&self.backup_service, &self.settings_service).await
+        {
+            errors.push(format!("Off-site backup schedule check failed: {}", e));
+        }
 
-                    // 2. Check Tenant Schedules
-                    if let Err(e) =
-                        Self::check_and_run_tenants(&pool, &service, &settings_service).await
-                    {
-                        if e.contains("relation \"settings\" does not exist")
-                            || e.contains("relation \"tenants\" does not exist")
-                        {
-                            if !warned_missing_schema {
-                                warned_missing_schema = true;
-                                warn!(
-                                    "Backup scheduler paused: database schema not migrated yet (missing settings/tenants tables)."
-                                );
-                            }
-                        } else {
-                            error!("Tenant backup schedule check failed: {}", e);
-                        }
-                    }
-                }
-            }
-        });
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
     }
 
     async fn check_and_run_global(
         _pool: &DbPool,
         service: &BackupService,
         settings_service: &SettingsService,
+        audit_service: &AuditService,
     ) -> Result<(), String> {
         let tz = get_app_timezone(settings_service).await;
         let trigger_now =
@@ -1623,51 +1832,22 @@ impl BackupScheduler {
         }
 
         let now = Utc::now();
-        let last_run =
-            get_datetime_setting(settings_service, None, "backup_global_last_run").await?;
         let should_run = if trigger_now {
             true
         } else {
-            let cfg = get_mode_settings(
-                settings_service,
-                None,
-                "backup_global",
-                "backup_global_schedule",
-                "02:00",
-            )
-            .await?;
-            let Some((mode, every, daily, weekday)) = cfg else {
+            let Some(schedule) = global_schedule(settings_service).await? else {
                 warn!("Invalid global backup schedule; skipping");
                 return Ok(());
             };
-
-            match mode {
-                ScheduleMode::Minute | ScheduleMode::Hour => {
-                    should_run_interval(now, last_run, every, mode)
-                }
-                ScheduleMode::Day => should_run_daily(now, last_run, daily, tz),
-                ScheduleMode::Week => should_run_weekly(now, last_run, weekday, daily, tz),
-            }
+            let last_run =
+                get_datetime_setting(settings_service, None, "backup_global_last_run").await?;
+            is_due(&schedule.cron, now, last_run, tz)
         };
 
         if should_run {
-            service
-                .create_global_backup()
-                .await
-                .map_err(|e| format!("Failed to create global backup: {}", e))?;
-            set_datetime_setting(
-                settings_service,
-                None,
-                "backup_global_last_run",
-                now,
-                "Last successful global backup run (UTC)",
-            )
-            .await?;
-
-            let retention_days =
-                get_i64_setting(settings_service, None, "backup_global_retention_days", 30).await?;
-            if retention_days > 0 {
-                cleanup_backups(service, retention_days, BackupScope::Global).await?;
+            let path = run_global_backup(service, settings_service, audit_service, tz).await?;
+            if offsite_follows_backups(settings_service).await? {
+                upload_offsite(settings_service, &[path]).await;
             }
 
             if trigger_now {
@@ -1689,32 +1869,26 @@ impl BackupScheduler {
         pool: &DbPool,
         service: &BackupService,
         settings_service: &SettingsService,
+        audit_service: &AuditService,
     ) -> Result<(), String> {
         let tz = get_app_timezone(settings_service).await;
         let trigger_now =
             get_bool_setting(settings_service, None, "backup_tenant_trigger", false).await?;
         let global_enabled =
             get_bool_setting(settings_service, None, "backup_tenant_enabled", false).await?;
-        let global_cfg = get_mode_settings(
-            settings_service,
-            None,
-            "backup_tenant",
-            "backup_tenant_schedule",
-            "02:30",
-        )
-        .await?;
-        let global_retention_days =
-            get_i64_setting(settings_service, None, "backup_tenant_retention_days", 14).await?;
-
         if !global_enabled && !trigger_now {
             return Ok(());
         }
 
+        let global_schedule = tenant_default_schedule(settings_service).await?;
+        let global_policy = tenant_default_policy(settings_service).await?;
+
         let now = Utc::now();
         let tenant_ids = list_active_tenants(pool)
             .await
             .map_err(|e| format!("Failed to list tenants: {}", e))?;
 
+        let mut created = Vec::new();
         for tenant_id in tenant_ids {
             let enabled =
                 get_bool_setting(settings_service, Some(&tenant_id), "backup_enabled", true)
@@ -1723,67 +1897,45 @@ impl BackupScheduler {
                 continue;
             }
 
-            let last_run =
-                get_datetime_setting(settings_service, Some(&tenant_id), "backup_last_run").await?;
             let should_run = if trigger_now {
                 true
             } else {
-                let tenant_cfg = get_mode_settings(
+                let tenant_schedule = effective_schedule(
                     settings_service,
                     Some(&tenant_id),
                     "backup",
                     "backup_schedule",
                     "02:30",
+                    None,
                 )
                 .await?;
-                let cfg = tenant_cfg.or(global_cfg);
-                let Some((mode, every, daily, weekday)) = cfg else {
+                let Some(schedule) = tenant_schedule.or_else(|| global_schedule.clone()) else {
                     warn!("Invalid backup schedule for tenant {}; skipping", tenant_id);
                     continue;
                 };
-
-                match mode {
-                    ScheduleMode::Minute | ScheduleMode::Hour => {
-                        should_run_interval(now, last_run, every, mode)
-                    }
-                    ScheduleMode::Day => should_run_daily(now, last_run, daily, tz),
-                    ScheduleMode::Week => should_run_weekly(now, last_run, weekday, daily, tz),
-                }
+                let last_run =
+                    get_datetime_setting(settings_service, Some(&tenant_id), "backup_last_run")
+                        .await?;
+                is_due(&schedule.cron, now, last_run, tz)
             };
             if should_run {
-                service
-                    .create_tenant_backup(&tenant_id)
-                    .await
-                    .map_err(|e| {
-                        format!("Failed to create tenant backup for {}: {}", tenant_id, e)
-                    })?;
-                set_datetime_setting(
-                    settings_service,
-                    Some(&tenant_id),
-                    "backup_last_run",
-                    now,
-                    "Last successful tenant backup run (UTC)",
-                )
-                .await?;
-
-                let retention_days = get_i64_setting(
+                let path = run_tenant_backup(
+                    service,
                     settings_service,
-                    Some(&tenant_id),
-                    "backup_retention_days",
-                    global_retention_days,
+                    audit_service,
+                    &tenant_id,
+                    global_policy,
+                    tz,
                 )
                 .await?;
-                if retention_days > 0 {
-                    cleanup_backups(
-                        service,
-                        retention_days,
-                        BackupScope::Tenant(tenant_id.clone()),
-                    )
-                    .await?;
-                }
+                created.push(path);
             }
         }
 
+        if offsite_follows_backups(settings_service).await? {
+            upload_offsite(settings_service, &created).await;
+        }
+
         if trigger_now {
             set_bool_setting(
                 settings_service,
@@ -1797,6 +1949,731 @@ impl BackupScheduler {
 
         Ok(())
     }
+
+    /// Off-site copies on their own schedule (`backup_remote_cron`) upload every
+    /// local archive created since the last upload.
+    async fn check_and_run_offsite(
+        service: &BackupService,
+        settings_service: &SettingsService,
+    ) -> Result<(), String> {
+        if !get_bool_setting(settings_service, None, "backup_remote_enabled", false).await? {
+            return Ok(());
+        }
+        let Some(schedule) = offsite_schedule(settings_service).await? else {
+            return Ok(());
+        };
+
+        let tz = get_app_timezone(settings_service).await;
+        let now = Utc::now();
+        let last_run =
+            get_datetime_setting(settings_service, None, "backup_remote_last_run").await?;
+        if !is_due(&schedule.cron, now, last_run, tz) {
+            return Ok(());
+        }
+
+        let uploaded = upload_pending_offsite(service, settings_service).await?;
+        info!("Scheduled off-site upload finished: {} backup(s)", uploaded);
+        set_datetime_setting(
+            settings_service,
+            None,
+            "backup_remote_last_run",
+            now,
+            "Last scheduled off-site upload run (UTC)",
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl JobHandler for BackupScheduler {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        self.run_due().await.map_err(AppError::Internal)
+    }
+}
+
+/// Create a global backup, record the run and apply global retention.
+async fn run_global_backup(
+    service: &BackupService,
+    settings_service: &SettingsService,
+    audit_service: &AuditService,
+    tz: Tz,
+) -> Result<String, String> {
+    let path = service
+        .create_global_backup()
+        .await
+        .map_err(|e| format!("Failed to create global backup: {}", e))?;
+    set_datetime_setting(
+        settings_service,
+        None,
+        "backup_global_last_run",
+        Utc::now(),
+        "Last successful global backup run (UTC)",
+    )
+    .await?;
+
+    let policy = RetentionPolicy {
+        max_age_days: get_i64_setting(settings_service, None, "backup_global_retention_days", 30)
+            .await?,
+        keep_daily: get_i64_setting(settings_service, None, "backup_global_keep_daily", 0).await?,
+        keep_weekly: get_i64_setting(settings_service, None, "backup_global_keep_weekly", 0)
+            .await?,
+        keep_monthly: get_i64_setting(settings_service, None, "backup_global_keep_monthly", 0)
+            .await?,
+    };
+    cleanup_backups(service, audit_service, policy, BackupScope::Global, tz).await?;
+    Ok(path)
+}
+
+/// Create one tenant's backup, record the run and apply that tenant's retention
+/// (falling back to `global_policy` for unset keys).
+async fn run_tenant_backup(
+    service: &BackupService,
+    settings_service: &SettingsService,
+    audit_service: &AuditService,
+    tenant_id: &str,
+    global_policy: RetentionPolicy,
+    tz: Tz,
+) -> Result<String, String> {
+    let path = service
+        .create_tenant_backup(tenant_id)
+        .await
+        .map_err(|e| format!("Failed to create tenant backup for {}: {}", tenant_id, e))?;
+    set_datetime_setting(
+        settings_service,
+        Some(tenant_id),
+        "backup_last_run",
+        Utc::now(),
+        "Last successful tenant backup run (UTC)",
+    )
+    .await?;
+
+    let tid = Some(tenant_id);
+    let policy = RetentionPolicy {
+        max_age_days: get_i64_setting(
+            settings_service,
+            tid,
+            "backup_retention_days",
+            global_policy.max_age_days,
+        )
+        .await?,
+        keep_daily: get_i64_setting(
+            settings_service,
+            tid,
+            "backup_keep_daily",
+            global_policy.keep_daily,
+        )
+        .await?,
+        keep_weekly: get_i64_setting(
+            settings_service,
+            tid,
+            "backup_keep_weekly",
+            global_policy.keep_weekly,
+        )
+        .await?,
+        keep_monthly: get_i64_setting(
+            settings_service,
+            tid,
+            "backup_keep_monthly",
+            global_policy.keep_monthly,
+        )
+        .await?,
+    };
+    cleanup_backups(
+        service,
+        audit_service,
+        policy,
+        BackupScope::Tenant(tenant_id.to_string()),
+        tz,
+    )
+    .await?;
+    Ok(path)
+}
+
+async fn tenant_default_policy(
+    settings_service: &SettingsService,
+) -> Result<RetentionPolicy, String> {
+    Ok(RetentionPolicy {
+        max_age_days: get_i64_setting(settings_service, None, "backup_tenant_retention_days", 14)
+            .await?,
+        keep_daily: get_i64_setting(settings_service, None, "backup_tenant_keep_daily", 0).await?,
+        keep_weekly: get_i64_setting(settings_service, None, "backup_tenant_keep_weekly", 0)
+            .await?,
+        keep_monthly: get_i64_setting(settings_service, None, "backup_tenant_keep_monthly", 0)
+            .await?,
+    })
+}
+
+/// Whether off-site copies are uploaded right after each backup, i.e. off-site
+/// has no schedule of its own.
+async fn offsite_follows_backups(settings_service: &SettingsService) -> Result<bool, String> {
+    Ok(offsite_schedule(settings_service).await?.is_none())
+}
+
+/// Upload local archives created since the last off-site upload. Before the
+/// first upload, only the newest archive of each scope is sent.
+async fn upload_pending_offsite(
+    service: &BackupService,
+    settings_service: &SettingsService,
+) -> Result<usize, String> {
+    let since = get_datetime_setting(settings_service, None, "backup_remote_last_upload").await?;
+    let mut backups = service.list_backups().await.map_err(|e| e.to_string())?;
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    let pending: Vec<String> = match since {
+        Some(since) => backups
+            .iter()
+            .filter(|b| b.created_at > since)
+            .map(|b| b.path.clone())
+            .collect(),
+        None => {
+            let mut seen = HashSet::new();
+            backups
+                .iter()
+                .filter(|b| seen.insert(b.tenant_id.clone()))
+                .map(|b| b.path.clone())
+                .collect()
+        }
+    };
+    Ok(upload_offsite(settings_service, &pending).await)
+}
+
+/// Copy freshly created archives to the off-site target, if one is configured,
+/// then apply its lifecycle rules. Failures are logged only: the local backup
+/// already succeeded and the next run retries with a new archive. Returns the
+/// number of archives uploaded.
+async fn upload_offsite(settings_service: &SettingsService, paths: &[String]) -> usize {
+    if paths.is_empty() {
+        return 0;
+    }
+    let config = match RemoteBackupConfig::from_settings(settings_service).await {
+        Ok(Some(c)) => c,
+        Ok(None) => return 0,
+        Err(e) => {
+            warn!("Off-site backup skipped: {}", e);
+            return 0;
+        }
+    };
+    let store = RemoteBackupStore::new(&config);
+
+    let mut uploaded = 0;
+    for path in paths {
+        match store.upload(Path::new(path)).await {
+            Ok(key) => {
+                info!("Uploaded backup off-site: {}", key);
+                uploaded += 1;
+            }
+            Err(e) => error!("Off-site upload of {} failed: {}", path, e),
+        }
+    }
+
+    if uploaded > 0 {
+        if let Err(e) = set_datetime_setting(
+            settings_service,
+            None,
+            "backup_remote_last_upload",
+            Utc::now(),
+            "Last successful off-site backup upload (UTC)",
+        )
+        .await
+        {
+            warn!("Failed to record off-site upload time: {}", e);
+        }
+    }
+
+    match store.apply_retention().await {
+        Ok(0) => {}
+        Ok(n) => info!("Removed {} expired off-site backup(s)", n),
+        Err(e) => warn!("Off-site backup retention failed: {}", e),
+    }
+    uploaded
+}
+
+/// One backup destination in the schedule preview.
+#[derive(Serialize, Debug)]
+pub struct DestinationSchedule {
+    /// "global", "tenant" or "offsite".
+    pub destination: String,
+    pub enabled: bool,
+    pub expression: Option<String>,
+    /// "cron", "legacy", "after_backup" (off-site copies follow each backup) or
+    /// "invalid".
+    pub source: String,
+    pub error: Option<String>,
+    pub last_run: Option<DateTime<Utc>>,
+    pub next_runs: Vec<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BackupSchedulePreview {
+    pub timezone: String,
+    pub destinations: Vec<DestinationSchedule>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CronPreview {
+    pub expression: String,
+    pub timezone: String,
+    pub next_runs: Vec<DateTime<Utc>>,
+}
+
+/// Current schedule of every backup destination with its next runs.
+pub async fn backup_schedule_preview(
+    settings_service: &SettingsService,
+) -> AppResult<BackupSchedulePreview> {
+    let tz = get_app_timezone(settings_service).await;
+    let now = Utc::now();
+
+    let global = destination_schedule(
+        "global",
+        get_bool_setting(settings_service, None, "backup_global_enabled", false)
+            .await
+            .map_err(AppError::Internal)?,
+        global_schedule(settings_service).await,
+        get_datetime_setting(settings_service, None, "backup_global_last_run")
+            .await
+            .map_err(AppError::Internal)?,
+        now,
+        tz,
+    );
+    // Tenants track their own last run; the preview shows the shared default.
+    let tenant = destination_schedule(
+        "tenant",
+        get_bool_setting(settings_service, None, "backup_tenant_enabled", false)
+            .await
+            .map_err(AppError::Internal)?,
+        tenant_default_schedule(settings_service).await,
+        None,
+        now,
+        tz,
+    );
+
+    let remote_enabled = get_bool_setting(settings_service, None, "backup_remote_enabled", false)
+        .await
+        .map_err(AppError::Internal)?;
+    let offsite = match offsite_schedule(settings_service).await {
+        Ok(None) => DestinationSchedule {
+            destination: "offsite".to_string(),
+            enabled: remote_enabled,
+            expression: None,
+            source: "after_backup".to_string(),
+            error: None,
+            last_run: get_datetime_setting(settings_service, None, "backup_remote_last_upload")
+                .await
+                .map_err(AppError::Internal)?,
+            next_runs: Vec::new(),
+        },
+        schedule => destination_schedule(
+            "offsite",
+            remote_enabled,
+            schedule,
+            get_datetime_setting(settings_service, None, "backup_remote_last_run")
+                .await
+                .map_err(AppError::Internal)?,
+            now,
+            tz,
+        ),
+    };
+
+    Ok(BackupSchedulePreview {
+        timezone: tz.name().to_string(),
+        destinations: vec![global, tenant, offsite],
+    })
+}
+
+fn destination_schedule(
+    destination: &str,
+    enabled: bool,
+    schedule: Result<Option<EffectiveSchedule>, String>,
+    last_run: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    tz: Tz,
+) -> DestinationSchedule {
+    let (expression, source, error, next_runs) = match schedule {
+        Ok(Some(s)) => (
+            Some(s.expression),
+            s.source,
+            None,
+            s.cron.upcoming(now, tz, PREVIEW_RUNS),
+        ),
+        Ok(None) => (
+            None,
+            "invalid",
+            Some("Schedule settings are not valid".to_string()),
+            Vec::new(),
+        ),
+        Err(e) => (None, "invalid", Some(e), Vec::new()),
+    };
+    DestinationSchedule {
+        destination: destination.to_string(),
+        enabled,
+        expression,
+        source: source.to_string(),
+        error,
+        last_run,
+        next_runs,
+    }
+}
+
+/// Validate a cron expression and list its next `count` runs in the app timezone.
+pub async fn preview_cron_expression(
+    settings_service: &SettingsService,
+    expression: &str,
+    count: usize,
+) -> AppResult<CronPreview> {
+    let cron = CronSchedule::parse(expression).map_err(AppError::Validation)?;
+    let tz = get_app_timezone(settings_service).await;
+    Ok(CronPreview {
+        expression: expression.trim().to_string(),
+        timezone: tz.name().to_string(),
+        next_runs: cron.upcoming(Utc::now(), tz, count.clamp(1, 50)),
+    })
+}
+
+/// What a manual run backs up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupRunTarget {
+    Global,
+    /// All active tenants, or one when a tenant id is given.
+    Tenants,
+    /// Upload local archives not yet copied off-site.
+    Offsite,
+}
+
+impl BackupRunTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Global => "global",
+            Self::Tenants => "tenants",
+            Self::Offsite => "offsite",
+        }
+    }
+}
+
+/// Progress of a manual run. Emitted when the run starts, before each step and
+/// once more with `done` set when it ends.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupRunProgress {
+    pub run_id: String,
+    pub target: BackupRunTarget,
+    pub message: String,
+    pub completed: usize,
+    pub total: usize,
+    pub done: bool,
+    pub error: Option<String>,
+}
+
+static MANUAL_RUN_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Clears `MANUAL_RUN_ACTIVE` even if the run panics.
+struct ManualRunGuard;
+
+impl Drop for ManualRunGuard {
+    fn drop(&mut self) {
+        MANUAL_RUN_ACTIVE.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Start a backup right away in the background and return its run id. Runs go
+/// through the same path as scheduled ones (last run, retention, off-site copy).
+/// Only one manual run may be active at a time.
+pub fn start_manual_run<F>(
+    pool: DbPool,
+    service: BackupService,
+    settings_service: SettingsService,
+    audit_service: AuditService,
+    target: BackupRunTarget,
+    tenant_id: Option<String>,
+    report: F,
+) -> AppResult<String>
+where
+    F: Fn(BackupRunProgress) + Send + Sync + 'static,
+{
+    if MANUAL_RUN_ACTIVE.swap(true, Ordering::SeqCst) {
+        return Err(AppError::Conflict(
+            "A backup run is already in progress".to_string(),
+        ));
+    }
+    let guard = ManualRunGuard;
+
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let id = run_id.clone();
+    tokio::spawn(async move {
+        let _guard = guard;
+        let event = |message: String, completed, total| BackupRunProgress {
+            run_id: id.clone(),
+            target,
+            message,
+            completed,
+            total,
+            done: false,
+            error: None,
+        };
+        let progress = |completed: usize, total: usize, message: String| {
+            report(event(message, completed, total))
+        };
+
+        progress(0, 0, format!("Starting {} backup run", target.as_str()));
+        let result = manual_run(
+            &pool,
+            &service,
+            &settings_service,
+            &audit_service,
+            target,
+            tenant_id.as_deref(),
+            &progress,
+        )
+        .await;
+
+        let last = match result {
+            Ok((summary, total)) => {
+                info!("Manual backup run {} finished: {}", id, summary);
+                BackupRunProgress {
+                    done: true,
+                    ..event(summary, total, total)
+                }
+            }
+            Err(e) => {
+                error!("Manual backup run {} failed: {}", id, e);
+                BackupRunProgress {
+                    done: true,
+                    error: Some(e),
+                    ..event("Backup run failed".to_string(), 0, 0)
+                }
+            }
+        };
+        report(last);
+    });
+
+    Ok(run_id)
+}
+
+/// Returns a summary and the number of steps taken.
+async fn manual_run(
+    pool: &DbPool,
+    service: &BackupService,
+    settings_service: &SettingsService,
+    audit_service: &AuditService,
+    target: BackupRunTarget,
+    tenant_id: Option<&str>,
+    progress: &(dyn Fn(usize, usize, String) + Send + Sync),
+) -> Result<(String, usize), String> {
+    let tz = get_app_timezone(settings_service).await;
+    match target {
+        BackupRunTarget::Global => {
+            progress(0, 1, "Creating global backup".to_string());
+            let path = run_global_backup(service, settings_service, audit_service, tz).await?;
+            if offsite_follows_backups(settings_service).await? {
+                progress(1, 1, "Uploading off-site".to_string());
+                upload_offsite(settings_service, &[path]).await;
+            }
+            Ok(("Global backup created".to_string(), 1))
+        }
+        BackupRunTarget::Tenants => {
+            let tenant_ids = match tenant_id {
+                Some(id) => vec![id.to_string()],
+                None => list_active_tenants(pool)
+                    .await
+                    .map_err(|e| format!("Failed to list tenants: {}", e))?,
+            };
+            let total = tenant_ids.len();
+            let global_policy = tenant_default_policy(settings_service).await?;
+
+            let mut created = Vec::new();
+            let mut failed = 0;
+            for (i, tenant_id) in tenant_ids.iter().enumerate() {
+                progress(
+                    i,
+                    total,
+                    format!("Backing up tenant {} of {}", i + 1, total),
+                );
+                match run_tenant_backup(
+                    service,
+                    settings_service,
+                    audit_service,
+                    tenant_id,
+                    global_policy,
+                    tz,
+                )
+                .await
+                {
+                    Ok(path) => created.push(path),
+                    Err(e) => {
+                        failed += 1;
+                        error!("{}", e);
+                    }
+                }
+            }
+
+            if !created.is_empty() && offsite_follows_backups(settings_service).await? {
+                progress(total, total, "Uploading off-site".to_string());
+                upload_offsite(settings_service, &created).await;
+            }
+
+            if failed > 0 && created.is_empty() {
+                return Err(format!("All {} tenant backup(s) failed", failed));
+            }
+            let mut summary = format!("{} tenant backup(s) created", created.len());
+            if failed > 0 {
+                summary.push_str(&format!(", {} failed", failed));
+            }
+            Ok((summary, total))
+        }
+        BackupRunTarget::Offsite => {
+            let configured = RemoteBackupConfig::from_settings(settings_service)
+                .await
+                .map_err(|e| e.to_string())?
+                .is_some();
+            if !configured {
+                return Err("Off-site backups are not enabled".to_string());
+            }
+            progress(0, 1, "Uploading pending backups off-site".to_string());
+            let uploaded = upload_pending_offsite(service, settings_service).await?;
+            Ok((format!("{} backup(s) uploaded off-site", uploaded), 1))
+        }
+    }
+}
+
+/// Upcoming runs listed per destination in the schedule preview.
+const PREVIEW_RUNS: usize = 5;
+
+/// The cron schedule a destination actually runs on.
+#[derive(Debug, Clone)]
+struct EffectiveSchedule {
+    expression: String,
+    /// "cron" when set through a `*_cron` setting, "legacy" when derived from the
+    /// older mode/every/at/weekday settings.
+    source: &'static str,
+    cron: CronSchedule,
+}
+
+impl EffectiveSchedule {
+    fn from_expression(expression: String, source: &'static str) -> Result<Self, String> {
+        let cron = CronSchedule::parse(&expression)?;
+        Ok(Self {
+            expression,
+            source,
+            cron,
+        })
+    }
+}
+
+/// A destination is due once a scheduled time has passed since its last run.
+/// Without a last run, a slot within the past day counts.
+fn is_due(
+    cron: &CronSchedule,
+    now: DateTime<Utc>,
+    last_run: Option<DateTime<Utc>>,
+    tz: Tz,
+) -> bool {
+    let anchor = last_run.unwrap_or(now - Duration::days(1));
+    cron.next_after(anchor, tz).is_some_and(|next| next <= now)
+}
+
+/// `<prefix>_cron` when set, otherwise the mode settings translated to cron.
+async fn effective_schedule(
+    settings_service: &SettingsService,
+    tenant_id: Option<&str>,
+    prefix: &str,
+    legacy_schedule_key: &str,
+    default_at: &str,
+    legacy_default: Option<&str>,
+) -> Result<Option<EffectiveSchedule>, String> {
+    let cron_key = format!("{}_cron", prefix);
+    let cron = settings_service
+        .get_value(tenant_id, &cron_key)
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    if let Some(expression) = cron {
+        return EffectiveSchedule::from_expression(expression, "cron")
+            .map(Some)
+            .map_err(|e| format!("Invalid {}: {}", cron_key, e));
+    }
+
+    let cfg = get_mode_settings(
+        settings_service,
+        tenant_id,
+        prefix,
+        legacy_schedule_key,
+        default_at,
+        legacy_default,
+    )
+    .await?;
+    Ok(cfg.and_then(|(mode, every, daily, weekday)| {
+        EffectiveSchedule::from_expression(
+            legacy_cron_expression(mode, every, daily, weekday),
+            "legacy",
+        )
+        .ok()
+    }))
+}
+
+async fn global_schedule(
+    settings_service: &SettingsService,
+) -> Result<Option<EffectiveSchedule>, String> {
+    effective_schedule(
+        settings_service,
+        None,
+        "backup_global",
+        "backup_global_schedule",
+        "02:00",
+        Some("0 2 * * *"),
+    )
+    .await
+}
+
+/// Schedule for tenants without their own `backup_cron` or `backup_mode`.
+async fn tenant_default_schedule(
+    settings_service: &SettingsService,
+) -> Result<Option<EffectiveSchedule>, String> {
+    effective_schedule(
+        settings_service,
+        None,
+        "backup_tenant",
+        "backup_tenant_schedule",
+        "02:30",
+        Some("30 2 * * *"),
+    )
+    .await
+}
+
+/// `None` when off-site copies follow each backup instead.
+async fn offsite_schedule(
+    settings_service: &SettingsService,
+) -> Result<Option<EffectiveSchedule>, String> {
+    let raw = settings_service
+        .get_value(None, "backup_remote_cron")
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    match raw {
+        Some(expression) => EffectiveSchedule::from_expression(expression, "cron")
+            .map(Some)
+            .map_err(|e| format!("Invalid backup_remote_cron: {}", e)),
+        None => Ok(None),
+    }
+}
+
+/// The cron expression equivalent to a mode-based schedule. Intervals are
+/// aligned to the clock (every 15 minutes fires at :00, :15, ...).
+fn legacy_cron_expression(
+    mode: ScheduleMode,
+    every: i64,
+    at: DailySchedule,
+    weekday: u32,
+) -> String {
+    let every = every.max(1);
+    match mode {
+        ScheduleMode::Minute if every < 60 => format!("*/{} * * * *", every),
+        ScheduleMode::Minute => format!("0 */{} * * *", (every / 60).min(23)),
+        ScheduleMode::Hour if every < 24 => format!("0 */{} * * *", every),
+        ScheduleMode::Hour => format!("0 0 */{} * *", (every / 24).min(31)),
+        ScheduleMode::Day => format!("{} {} * * *", at.minute, at.hour),
+        ScheduleMode::Week => format!("{} {} * * {}", at.minute, at.hour, weekday % 7),
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -1843,43 +2720,6 @@ fn parse_daily_schedule(input: &str) -> Option<DailySchedule> {
     None
 }
 
-fn should_run_daily(
-    now: DateTime<Utc>,
-    last_run: Option<DateTime<Utc>>,
-    schedule: DailySchedule,
-    tz: Tz,
-) -> bool {
-    // Interpret HH:MM in the app's timezone (app_timezone), then convert to UTC for comparison.
-    let local_day = now.with_timezone(&tz).date_naive();
-    let scheduled_today = scheduled_time_for_day(local_day, schedule, tz);
-    if now < scheduled_today {
-        return false;
-    }
-    match last_run {
-        None => true,
-        Some(last) => last < scheduled_today,
-    }
-}
-
-fn scheduled_time_for_day(day: NaiveDate, schedule: DailySchedule, tz: Tz) -> DateTime<Utc> {
-    let time = NaiveTime::from_hms_opt(schedule.hour, schedule.minute, 0)
-        .unwrap_or_else(|| NaiveTime::from_hms_opt(0, 0, 0).unwrap());
-
-    let naive = NaiveDateTime::new(day, time);
-    // DST can make local times ambiguous or invalid; pick a reasonable instant.
-    if let Some(dt) = tz
-        .from_local_datetime(&naive)
-        .single()
-        .or_else(|| tz.from_local_datetime(&naive).earliest())
-        .or_else(|| tz.from_local_datetime(&naive).latest())
-    {
-        return dt.with_timezone(&Utc);
-    }
-
-    // Fallback: treat it as UTC if timezone conversion fails (should be rare).
-    DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc)
-}
-
 #[derive(Debug, Clone, Copy)]
 enum ScheduleMode {
     Minute,
@@ -1926,75 +2766,17 @@ fn parse_weekday(input: &str) -> Option<u32> {
     }
 }
 
-fn weekday_of_date(day: NaiveDate) -> u32 {
-    day.weekday().number_from_monday()
-}
-
-fn should_run_interval(
-    now: DateTime<Utc>,
-    last_run: Option<DateTime<Utc>>,
-    every: i64,
-    unit: ScheduleMode,
-) -> bool {
-    let every = every.max(1);
-    let dur = match unit {
-        ScheduleMode::Minute => Duration::minutes(every),
-        ScheduleMode::Hour => Duration::hours(every),
-        _ => return false,
-    };
-    match last_run {
-        None => true,
-        Some(last) => now - last >= dur,
-    }
-}
-
-fn should_run_weekly(
-    now: DateTime<Utc>,
-    last_run: Option<DateTime<Utc>>,
-    weekday: u32,
-    schedule: DailySchedule,
-    tz: Tz,
-) -> bool {
-    // Run once per week at weekday+time in app_timezone
-    let today = now.with_timezone(&tz).date_naive();
-    let scheduled_this_week = scheduled_time_for_week_tz(today, weekday, schedule, tz);
-    if now < scheduled_this_week {
-        let last_week = today.checked_sub_signed(Duration::days(7)).unwrap_or(today);
-        let scheduled_last_week = scheduled_time_for_week_tz(last_week, weekday, schedule, tz);
-        return last_run.map(|lr| lr < scheduled_last_week).unwrap_or(false);
-    }
-    last_run.map(|lr| lr < scheduled_this_week).unwrap_or(true)
-}
-
-fn scheduled_time_for_week_tz(
-    day: NaiveDate,
-    weekday: u32,
-    schedule: DailySchedule,
-    tz: Tz,
-) -> DateTime<Utc> {
-    // weekday: 1=Mon .. 7=Sun
-    let current = weekday_of_date(day);
-    let delta_days = if current <= weekday {
-        weekday - current
-    } else {
-        7 - (current - weekday)
-    } as i64;
-
-    let target_day = day
-        .checked_add_signed(Duration::days(delta_days))
-        .unwrap_or(day);
-    scheduled_time_for_day(target_day, schedule, tz)
-}
-
 async fn get_mode_settings(
     settings_service: &SettingsService,
     tenant_id: Option<&str>,
     prefix: &str,
     legacy_schedule_key: &str,
     default_at: &str,
+    legacy_default: Option<&str>,
 ) -> Result<Option<(ScheduleMode, i64, DailySchedule, u32)>, String> {
     // Returns (mode, every, time, weekday)
-    // If mode not configured, falls back to legacy cron-string (treated as Day).
+    // If mode not configured, falls back to legacy cron-string (treated as Day),
+    // then to `legacy_default`. `None` when none of them is set.
     let mode_key = format!("{}_mode", prefix);
     let mode_raw = settings_service
         .get_value(tenant_id, &mode_key)
@@ -2037,7 +2819,9 @@ async fn get_mode_settings(
         .get_value(tenant_id, legacy_schedule_key)
         .await
         .map_err(|e| e.to_string())?;
-    let legacy = legacy.unwrap_or_else(|| "0 2 * * *".to_string());
+    let Some(legacy) = legacy.or_else(|| legacy_default.map(str::to_string)) else {
+        return Ok(None);
+    };
     if let Some(daily) = parse_daily_schedule(&legacy) {
         return Ok(Some((ScheduleMode::Day, 0, daily, 7)));
     }
@@ -2121,6 +2905,7 @@ async fn set_bool_setting(
             "false".to_string()
         },
         description: Some(description.to_string()),
+        expected_updated_at: None,
     };
     settings_service
         .upsert(tenant_id.map(|t| t.to_string()), dto, None, None)
@@ -2140,6 +2925,7 @@ async fn set_datetime_setting(
         key: key.to_string(),
         value: value.to_rfc3339(),
         description: Some(description.to_string()),
+        expected_updated_at: None,
     };
     settings_service
         .upsert(tenant_id.map(|t| t.to_string()), dto, None, None)
@@ -2171,23 +2957,274 @@ enum BackupScope {
     Tenant(String),
 }
 
+/// How many backups of one scope to keep.
+///
+/// Everything newer than `max_age_days` is kept. On top of that, the newest backup
+/// of each of the last `keep_daily` days, `keep_weekly` ISO weeks and
+/// `keep_monthly` months is kept (days are in `app_timezone`). Pinned backups are
+/// always kept. With every field at 0 nothing is pruned.
+#[derive(Debug, Clone, Copy, Default)]
+struct RetentionPolicy {
+    max_age_days: i64,
+    keep_daily: i64,
+    keep_weekly: i64,
+    keep_monthly: i64,
+}
+
+impl RetentionPolicy {
+    fn is_active(&self) -> bool {
+        self.max_age_days > 0
+            || self.keep_daily > 0
+            || self.keep_weekly > 0
+            || self.keep_monthly > 0
+    }
+}
+
+/// Indices into `backups` (newest first) that the policy does not keep.
+fn backups_to_prune(
+    backups: &[BackupRecord],
+    policy: RetentionPolicy,
+    now: DateTime<Utc>,
+    tz: Tz,
+) -> Vec<usize> {
+    if !policy.is_active() {
+        return Vec::new();
+    }
+
+    let mut keep = vec![false; backups.len()];
+    for (i, b) in backups.iter().enumerate() {
+        keep[i] = b.pinned
+            || (policy.max_age_days > 0
+                && b.created_at >= now - Duration::days(policy.max_age_days));
+    }
+
+    // Grandfather-father-son: walk newest to oldest and keep the first backup seen in
+    // each new period until the period count is reached.
+    let tiers: [(i64, fn(NaiveDate) -> (i32, u32)); 3] = [
+        (policy.keep_daily, |d: NaiveDate| (d.year(), d.ordinal())),
+        (policy.keep_weekly, |d: NaiveDate| {
+            (d.iso_week().year(), d.iso_week().week())
+        }),
+        (policy.keep_monthly, |d: NaiveDate| (d.year(), d.month())),
+    ];
+    for (count, period_of) in tiers {
+        let mut periods = Vec::new();
+        for (i, b) in backups.iter().enumerate() {
+            if periods.len() as i64 >= count {
+                break;
+            }
+            let period = period_of(b.created_at.with_timezone(&tz).date_naive());
+            if !periods.contains(&period) {
+                periods.push(period);
+                keep[i] = true;
+            }
+        }
+    }
+
+    keep.iter()
+        .enumerate()
+        .filter(|(_, k)| !**k)
+        .map(|(i, _)| i)
+        .collect()
+}
+
 async fn cleanup_backups(
     service: &BackupService,
-    retention_days: i64,
+    audit_service: &AuditService,
+    policy: RetentionPolicy,
     scope: BackupScope,
+    tz: Tz,
 ) -> Result<(), String> {
-    let cutoff = Utc::now() - Duration::days(retention_days);
-    let backups = service.list_backups().await.map_err(|e| e.to_string())?;
-    for backup in backups {
-        let should_delete = match &scope {
-            BackupScope::Global => backup.backup_type == "global",
+    if !policy.is_active() {
+        return Ok(());
+    }
+
+    let backups: Vec<BackupRecord> = service
+        .list_backups()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|b| match &scope {
+            BackupScope::Global => b.backup_type == "global",
             BackupScope::Tenant(tid) => {
-                backup.backup_type == "tenant" && backup.tenant_id.as_deref() == Some(tid.as_str())
+                b.backup_type == "tenant" && b.tenant_id.as_deref() == Some(tid.as_str())
             }
-        };
-        if should_delete && backup.created_at < cutoff {
-            let _ = service.delete_backup(backup.name).await;
+        })
+        .collect();
+
+    for i in backups_to_prune(&backups, policy, Utc::now(), tz) {
+        let backup = &backups[i];
+        if let Err(e) = service.delete_backup(backup.name.clone()).await {
+            warn!("Failed to prune backup {}: {}", backup.name, e);
+            continue;
         }
+        info!("Pruned backup {}", backup.name);
+
+        let details = serde_json::json!({
+            "filename": backup.name,
+            "created_at": backup.created_at.to_rfc3339(),
+            "retention_days": policy.max_age_days,
+            "keep_daily": policy.keep_daily,
+            "keep_weekly": policy.keep_weekly,
+            "keep_monthly": policy.keep_monthly,
+        })
+        .to_string();
+        audit_service
+            .log(
+                None,
+                backup.tenant_id.as_deref(),
+                "prune",
+                "backups",
+                None,
+                Some(details.as_str()),
+                None,
+            )
+            .await;
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn backup(name: &str, created_at: DateTime<Utc>, pinned: bool) -> BackupRecord {
+        BackupRecord {
+            name: name.to_string(),
+            path: String::new(),
+            size: 0,
+            created_at,
+            backup_type: "global".to_string(),
+            tenant_id: None,
+            pinned,
+        }
+    }
+
+    /// One backup per day at 02:00 UTC for `days` days, newest first.
+    fn daily_backups(now: DateTime<Utc>, days: i64) -> Vec<BackupRecord> {
+        (0..days)
+            .map(|d| backup(&format!("b{}", d), now - Duration::days(d), false))
+            .collect()
+    }
+
+    #[test]
+    fn test_prune_by_age_only() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 15, 2, 0, 0).unwrap();
+        let backups = daily_backups(now, 10);
+        let policy = RetentionPolicy {
+            max_age_days: 7,
+            ..Default::default()
+        };
+        // b0..b7 are within 7 days (b7 exactly at the cutoff).
+        assert_eq!(
+            backups_to_prune(&backups, policy, now, chrono_tz::UTC),
+            vec![8, 9]
+        );
+    }
+
+    #[test]
+    fn test_prune_inactive_policy_keeps_everything() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 15, 2, 0, 0).unwrap();
+        let backups = daily_backups(now, 5);
+        assert!(
+            backups_to_prune(&backups, RetentionPolicy::default(), now, chrono_tz::UTC).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_prune_grandfather_father_son() {
+        // Sunday 2026-03-15; 60 daily backups back to 2026-01-15.
+        let now = Utc.with_ymd_and_hms(2026, 3, 15, 2, 0, 0).unwrap();
+        let backups = daily_backups(now, 60);
+        let policy = RetentionPolicy {
+            max_age_days: 0,
+            keep_daily: 3,
+            keep_weekly: 2,
+            keep_monthly: 3,
+        };
+
+        let pruned = backups_to_prune(&backups, policy, now, chrono_tz::UTC);
+        let kept: Vec<&str> = (0..backups.len())
+            .filter(|i| !pruned.contains(i))
+            .map(|i| backups[i].name.as_str())
+            .collect();
+
+        // Daily: 15, 14, 13 Mar. Weekly: 15 Mar (Sun, ISO week 11) and 8 Mar
+        // (Sun, week 10). Monthly: 15 Mar, 28 Feb, 31 Jan.
+        assert_eq!(kept, vec!["b0", "b1", "b2", "b7", "b15", "b43"]);
+    }
+
+    #[test]
+    fn test_prune_never_touches_pinned() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 15, 2, 0, 0).unwrap();
+        let mut backups = daily_backups(now, 5);
+        backups[4].pinned = true;
+        let policy = RetentionPolicy {
+            keep_daily: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            backups_to_prune(&backups, policy, now, chrono_tz::UTC),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_prune_daily_uses_app_timezone() {
+        // 23:30 and 00:30 UTC the next day are the same evening in New York.
+        let now = Utc.with_ymd_and_hms(2026, 3, 16, 0, 30, 0).unwrap();
+        let backups = vec![
+            backup("late", now, false),
+            backup("early", now - Duration::hours(1), false),
+        ];
+        let policy = RetentionPolicy {
+            keep_daily: 2,
+            ..Default::default()
+        };
+        assert!(backups_to_prune(&backups, policy, now, chrono_tz::UTC).is_empty());
+        assert_eq!(
+            backups_to_prune(&backups, policy, now, chrono_tz::America::New_York),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn test_legacy_cron_expression() {
+        let at = DailySchedule {
+            hour: 2,
+            minute: 30,
+        };
+        let cases = [
+            (ScheduleMode::Minute, 15, 7, "*/15 * * * *"),
+            (ScheduleMode::Minute, 120, 7, "0 */2 * * *"),
+            (ScheduleMode::Hour, 6, 7, "0 */6 * * *"),
+            (ScheduleMode::Hour, 48, 7, "0 0 */2 * *"),
+            (ScheduleMode::Day, 0, 7, "30 2 * * *"),
+            (ScheduleMode::Week, 0, 7, "30 2 * * 0"),
+            (ScheduleMode::Week, 0, 3, "30 2 * * 3"),
+        ];
+        for (mode, every, weekday, expected) in cases {
+            let expr = legacy_cron_expression(mode, every, at, weekday);
+            assert_eq!(expr, expected);
+            assert!(CronSchedule::parse(&expr).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_is_due_after_missed_slot() {
+        let cron = CronSchedule::parse("0 2 * * *").unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 3, 15, 2, 0, 30).unwrap();
+        let yesterday = now - Duration::days(1);
+        assert!(is_due(&cron, now, Some(yesterday), chrono_tz::UTC));
+        assert!(is_due(&cron, now, None, chrono_tz::UTC));
+        // Already ran for today's slot.
+        assert!(!is_due(&cron, now, Some(now), chrono_tz::UTC));
+        assert!(!is_due(
+            &cron,
+            now - Duration::minutes(5),
+            Some(yesterday),
+            chrono_tz::UTC
+        ));
+    }
+}
@@ -0,0 +1,372 @@
+//! Off-site backup copies on S3-compatible storage (AWS S3, MinIO, Backblaze B2, R2)
+//!
+//! The target has its own endpoint and credentials (`backup_remote_*` settings),
+//! independent of the file storage bucket, so backups can live with a different
+//! provider. Archives keep the local layout under a prefix:
+//! `<prefix>/global/<name>` and `<prefix>/tenants/<id>/<name>`.
+//!
+//! Lifecycle: objects older than `backup_remote_retention_days` are deleted, but
+//! the newest `backup_remote_min_keep` archives of each scope are always kept, so
+//! a stalled scheduler never prunes the last good copy.
+
+use crate::error::{AppError, AppResult};
+use crate::services::backup::backup_subdir;
+use crate::services::SettingsService;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::{config::Region, Client};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::fs;
+
+#[derive(Debug, Clone)]
+pub struct RemoteBackupConfig {
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    pub prefix: String,
+    pub force_path_style: bool,
+    pub retention_days: i64,
+    pub min_keep: usize,
+}
+
+impl RemoteBackupConfig {
+    /// Read the off-site target from global settings. `Ok(None)` when disabled.
+    pub async fn from_settings(settings: &SettingsService) -> AppResult<Option<Self>> {
+        let get = |key: &'static str| async move {
+            settings
+                .get_value(None, key)
+                .await
+                .map(|v| v.map(|v| v.trim().to_string()).unwrap_or_default())
+        };
+
+        if get("backup_remote_enabled").await? != "true" {
+            return Ok(None);
+        }
+
+        let config = Self {
+            endpoint: get("backup_remote_endpoint").await?,
+            region: Some(get("backup_remote_region").await?)
+                .filter(|r| !r.is_empty())
+                .unwrap_or_else(|| "us-east-1".to_string()),
+            bucket: get("backup_remote_bucket").await?,
+            access_key: get("backup_remote_access_key").await?,
+            secret_key: get("backup_remote_secret_key").await?,
+            prefix: get("backup_remote_prefix")
+                .await?
+                .trim_matches('/')
+                .to_string(),
+            force_path_style: get("backup_remote_path_style").await? == "true",
+            retention_days: get("backup_remote_retention_days")
+                .await?
+                .parse()
+                .unwrap_or(90),
+            min_keep: get("backup_remote_min_keep").await?.parse().unwrap_or(3),
+        };
+
+        if config.bucket.is_empty() || config.access_key.is_empty() || config.secret_key.is_empty()
+        {
+            return Err(AppError::Validation(
+                "Off-site backup is enabled but bucket or credentials are missing".to_string(),
+            ));
+        }
+
+        Ok(Some(config))
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RemoteBackupRecord {
+    pub name: String,
+    pub key: String,
+    pub size: i64,
+    pub created_at: DateTime<Utc>,
+    pub backup_type: String, // "global" or "tenant"
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct RemoteBackupStore {
+    client: Client,
+    bucket: String,
+    prefix: String,
+    retention_days: i64,
+    min_keep: usize,
+}
+
+impl RemoteBackupStore {
+    pub fn new(config: &RemoteBackupConfig) -> Self {
+        let creds = aws_sdk_s3::config::Credentials::new(
+            &config.access_key,
+            &config.secret_key,
+            None,
+            None,
+            "backup_remote",
+        );
+
+        let mut builder = aws_sdk_s3::Config::builder()
+            .region(Region::new(config.region.clone()))
+            .credentials_provider(creds)
+            .force_path_style(config.force_path_style)
+            .behavior_version_latest();
+
+        if !config.endpoint.is_empty() {
+            builder = builder.endpoint_url(&config.endpoint);
+        }
+
+        Self {
+            client: Client::from_conf(builder.build()),
+            bucket: config.bucket.clone(),
+            prefix: config.prefix.clone(),
+            retention_days: config.retention_days,
+            min_keep: config.min_keep,
+        }
+    }
+
+    /// Build the store from settings, failing with a validation error when off-site
+    /// backup is disabled.
+    pub async fn from_settings(settings: &SettingsService) -> AppResult<Self> {
+        RemoteBackupConfig::from_settings(settings)
+            .await?
+            .map(|c| Self::new(&c))
+            .ok_or_else(|| AppError::Validation("Off-site backup is not enabled".to_string()))
+    }
+
+    fn key_for(&self, filename: &str) -> AppResult<String> {
+        object_key(&self.prefix, filename)
+    }
+
+    /// Upload a local archive. Returns the object key.
+    pub async fn upload(&self, local_path: &Path) -> AppResult<String> {
+        let filename = local_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| AppError::Validation("Invalid backup path".to_string()))?;
+        let key = self.key_for(filename)?;
+
+        let body = ByteStream::from_path(local_path)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to read backup for upload: {}", e)))?;
+
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .body(body)
+            .content_type("application/zip")
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Off-site backup upload failed: {}", e)))?;
+
+        Ok(key)
+    }
+
+    /// All archives under the prefix, newest first.
+    pub async fn list(&self) -> AppResult<Vec<RemoteBackupRecord>> {
+        let list_prefix = if self.prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", self.prefix)
+        };
+
+        let mut records = Vec::new();
+        let mut continuation: Option<String> = None;
+        loop {
+            let page = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(&list_prefix)
+                .set_continuation_token(continuation.take())
+                .send()
+                .await
+                .map_err(|e| {
+                    AppError::Internal(format!("Failed to list off-site backups: {}", e))
+                })?;
+
+            for obj in page.contents() {
+                let Some(key) = obj.key() else { continue };
+                let name = key.rsplit('/').next().unwrap_or(key);
+                // Only archives this app wrote, at the key it would have written them to.
+                if self.key_for(name).ok().as_deref() != Some(key) {
+                    continue;
+                }
+                let tenant_id = name
+                    .strip_prefix("tenant_")
+                    .and_then(|rest| rest.split('_').next())
+                    .map(str::to_string);
+                records.push(RemoteBackupRecord {
+                    name: name.to_string(),
+                    key: key.to_string(),
+                    size: obj.size().unwrap_or(0),
+                    created_at: obj
+                        .last_modified()
+                        .and_then(|t| DateTime::from_timestamp(t.secs(), t.subsec_nanos()))
+                        .unwrap_or_else(Utc::now),
+                    backup_type: if tenant_id.is_some() {
+                        "tenant".to_string()
+                    } else {
+                        "global".to_string()
+                    },
+                    tenant_id,
+                });
+            }
+
+            match page.next_continuation_token() {
+                Some(token) if page.is_truncated().unwrap_or(false) => {
+                    continuation = Some(token.to_string())
+                }
+                _ => break,
+            }
+        }
+
+        records.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(records)
+    }
+
+    /// Download an archive to `dest`, via a `.part` file so an interrupted
+    /// transfer never leaves a truncated zip behind.
+    pub async fn download(&self, filename: &str, dest: &Path) -> AppResult<()> {
+        let key = self.key_for(filename)?;
+        let output = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| {
+                AppError::NotFound(format!("Off-site backup {} not available: {}", filename, e))
+            })?;
+
+        let part = dest.with_extension("zip.part");
+        let mut file = fs::File::create(&part)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let mut reader = output.body.into_async_read();
+        let copied = tokio::io::copy(&mut reader, &mut file).await;
+        drop(file);
+        if let Err(e) = copied {
+            let _ = fs::remove_file(&part).await;
+            return Err(AppError::Internal(format!(
+                "Failed to download off-site backup: {}",
+                e
+            )));
+        }
+
+        fs::rename(&part, dest)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))
+    }
+
+    /// Apply the lifecycle rules. Returns how many archives were deleted.
+    pub async fn apply_retention(&self) -> AppResult<usize> {
+        if self.retention_days <= 0 {
+            return Ok(0);
+        }
+        let cutoff = Utc::now() - Duration::days(self.retention_days);
+        let records = self.list().await?;
+        let expired = expired_keys(&records, cutoff, self.min_keep);
+
+        for key in &expired {
+            self.client
+                .delete_object()
+                .bucket(&self.bucket)
+                .key(key)
+                .send()
+                .await
+                .map_err(|e| {
+                    AppError::Internal(format!("Failed to delete off-site backup {}: {}", key, e))
+                })?;
+        }
+        Ok(expired.len())
+    }
+}
+
+fn object_key(prefix: &str, filename: &str) -> AppResult<String> {
+    let subdir = backup_subdir(filename)?;
+    Ok(if prefix.is_empty() {
+        format!("{}/{}", subdir, filename)
+    } else {
+        format!("{}/{}/{}", prefix, subdir, filename)
+    })
+}
+
+/// Keys older than `cutoff`, skipping the newest `min_keep` of each scope
+/// (global, or one tenant). `records` must be sorted newest first.
+fn expired_keys(
+    records: &[RemoteBackupRecord],
+    cutoff: DateTime<Utc>,
+    min_keep: usize,
+) -> Vec<String> {
+    let mut seen: HashMap<Option<&str>, usize> = HashMap::new();
+    let mut expired = Vec::new();
+    for r in records {
+        let n = seen.entry(r.tenant_id.as_deref()).or_insert(0);
+        *n += 1;
+        if *n > min_keep && r.created_at < cutoff {
+            expired.push(r.key.clone());
+        }
+    }
+    expired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, tenant: Option<&str>, age_days: i64) -> RemoteBackupRecord {
+        RemoteBackupRecord {
+            name: name.to_string(),
+            key: name.to_string(),
+            size: 1,
+            created_at: Utc::now() - Duration::days(age_days),
+            backup_type: if tenant.is_some() { "tenant" } else { "global" }.to_string(),
+            tenant_id: tenant.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_object_key_layout() {
+        assert_eq!(
+            object_key("backups", "global_backup_20260101_020000.zip").unwrap(),
+            "backups/global/global_backup_20260101_020000.zip"
+        );
+        assert_eq!(
+            object_key("", "tenant_abc-1_20260101_020000.zip").unwrap(),
+            "tenants/abc-1/tenant_abc-1_20260101_020000.zip"
+        );
+        assert!(object_key("backups", "../etc/passwd").is_err());
+        assert!(object_key("backups", "random.zip").is_err());
+    }
+
+    #[test]
+    fn test_expired_keys_respects_min_keep_per_scope() {
+        let cutoff = Utc::now() - Duration::days(30);
+        let records = vec![
+            record("g1", None, 40),
+            record("t1", Some("a"), 35),
+            record("g2", None, 50),
+            record("g3", None, 60),
+            record("t2", Some("a"), 45),
+            record("t3", Some("b"), 90),
+        ];
+
+        // Everything is past the cutoff; keep the newest two of each scope.
+        assert_eq!(expired_keys(&records, cutoff, 2), vec!["g3".to_string()]);
+        assert_eq!(
+            expired_keys(&records, cutoff, 0),
+            vec!["g1", "t1", "g2", "g3", "t2", "t3"]
+        );
+    }
+
+    #[test]
+    fn test_expired_keys_keeps_recent() {
+        let cutoff = Utc::now() - Duration::days(30);
+        let records = vec![record("g1", None, 1), record("g2", None, 2)];
+        assert!(expired_keys(&records, cutoff, 0).is_empty());
+    }
+}
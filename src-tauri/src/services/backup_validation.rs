@@ -0,0 +1,590 @@
+//! Pre-restore validation of backup archives
+//!
+//! Every archive written by [`BackupService`](super::BackupService) carries a
+//! `manifest.json` with the schema version it was taken at and a row count and
+//! SHA-256 per table file. Before a restore touches the database the archive is
+//! checked against the manifest and against the live schema; any error blocks the
+//! restore, warnings are reported but do not.
+//!
+//! Archives from before the manifest existed still restore, with a warning that
+//! they could not be verified.
+
+use crate::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Bump when the archive layout changes in a way older builds cannot restore.
+pub const MANIFEST_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TableManifest {
+    pub rows: usize,
+    pub sha256: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub scope: String, // "global" or "tenant"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub database: String,
+    pub app_version: String,
+    /// Latest migration applied when the backup was taken.
+    #[serde(default)]
+    pub schema_version: Option<i64>,
+    /// Keyed by table name (the file stem inside the archive).
+    pub tables: BTreeMap<String, TableManifest>,
+}
+
+impl BackupManifest {
+    /// Describe table files as they are about to be written (`<table>.json` → contents).
+    pub fn build(
+        scope: &str,
+        tenant_id: Option<&str>,
+        schema_version: Option<i64>,
+        files: &[(String, String)],
+    ) -> Self {
+        let tables = files
+            .iter()
+            .filter_map(|(filename, contents)| {
+                let table = filename.strip_suffix(".json")?;
+                Some((
+                    table.to_string(),
+                    TableManifest {
+                        rows: count_rows(contents).unwrap_or(0),
+                        sha256: sha256_hex(contents.as_bytes()),
+                    },
+                ))
+            })
+            .collect();
+
+        Self {
+            format_version: MANIFEST_FORMAT_VERSION,
+            scope: scope.to_string(),
+            tenant_id: tenant_id.map(str::to_string),
+            created_at: Utc::now(),
+            database: crate::db::migrations::backend_name().to_string(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version,
+            tables,
+        }
+    }
+}
+
+/// Table files and manifest read out of an archive.
+pub struct ArchiveContents {
+    pub manifest: Option<BackupManifest>,
+    /// Table name → raw JSON.
+    pub tables: HashMap<String, String>,
+    /// Entries that could not be read (corrupt, bad CRC, not UTF-8) or an unparsable manifest.
+    pub read_errors: Vec<RestoreIssue>,
+}
+
+pub fn read_archive(zip_path: &Path) -> AppResult<ArchiveContents> {
+    use std::io::Read;
+
+    let file = std::fs::File::open(zip_path).map_err(|e| AppError::Internal(e.to_string()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| AppError::Validation(format!("Not a valid backup archive: {}", e)))?;
+
+    let mut contents = ArchiveContents {
+        manifest: None,
+        tables: HashMap::new(),
+        read_errors: Vec::new(),
+    };
+
+    for i in 0..archive.len() {
+        let mut file = match archive.by_index(i) {
+            Ok(f) => f,
+            Err(e) => {
+                contents.read_errors.push(RestoreIssue::error(
+                    None,
+                    format!("Unreadable entry #{}: {}", i, e),
+                ));
+                continue;
+            }
+        };
+        let Some(outpath) = file.enclosed_name().map(|p| p.to_owned()) else {
+            continue;
+        };
+        let name = outpath.to_string_lossy().to_string();
+        if !name.ends_with(".json") {
+            continue;
+        }
+
+        // Reading to the end verifies the entry's CRC32.
+        let mut data = String::new();
+        if let Err(e) = file.read_to_string(&mut data) {
+            contents.read_errors.push(RestoreIssue::error(
+                Some(name.as_str()),
+                format!("Failed to read {}: {}", name, e),
+            ));
+            continue;
+        }
+
+        if name == MANIFEST_FILE {
+            match serde_json::from_str::<BackupManifest>(&data) {
+                Ok(m) => contents.manifest = Some(m),
+                Err(e) => contents.read_errors.push(RestoreIssue::error(
+                    None,
+                    format!("Invalid {}: {}", MANIFEST_FILE, e),
+                )),
+            }
+            continue;
+        }
+
+        let table = outpath
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        contents.tables.insert(table, data);
+    }
+
+    Ok(contents)
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct RestoreIssue {
+    pub severity: String, // "error" or "warning"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    pub message: String,
+}
+
+impl RestoreIssue {
+    fn error(table: Option<&str>, message: String) -> Self {
+        Self {
+            severity: "error".to_string(),
+            table: table.map(str::to_string),
+            message,
+        }
+    }
+
+    fn warning(table: Option<&str>, message: String) -> Self {
+        Self {
+            severity: "warning".to_string(),
+            table: table.map(str::to_string),
+            message,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == "error"
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TableCheck {
+    pub name: String,
+    pub rows: usize,
+    /// `None` when the archive has no manifest entry to compare against.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum_ok: Option<bool>,
+    /// False for tables a restore skips (unknown, or out of scope for a tenant restore).
+    pub will_restore: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RestoreValidationReport {
+    /// No errors: a restore would be attempted.
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<BackupManifest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_schema_version: Option<i64>,
+    pub tables: Vec<TableCheck>,
+    pub issues: Vec<RestoreIssue>,
+}
+
+impl RestoreValidationReport {
+    /// Error messages joined for an `AppError::Validation`, capped at a few.
+    pub fn error_summary(&self) -> String {
+        let errors: Vec<&str> = self
+            .issues
+            .iter()
+            .filter(|i| i.is_error())
+            .map(|i| i.message.as_str())
+            .collect();
+        let mut summary = errors
+            .iter()
+            .take(5)
+            .copied()
+            .collect::<Vec<_>>()
+            .join("; ");
+        if errors.len() > 5 {
+            summary.push_str(&format!(" (and {} more)", errors.len() - 5));
+        }
+        summary
+    }
+}
+
+/// What the archive is checked against.
+pub struct ValidationTarget<'a> {
+    pub current_schema_version: Option<i64>,
+    pub target_tenant_id: Option<&'a str>,
+    /// Tables a restore with this target would write, in restore order.
+    pub restorable: &'a [&'a str],
+    /// Live columns per table; tables missing from the map do not exist.
+    pub columns: &'a HashMap<String, HashSet<String>>,
+}
+
+pub fn check_archive(
+    contents: &ArchiveContents,
+    target: &ValidationTarget<'_>,
+) -> RestoreValidationReport {
+    let mut issues = contents.read_errors.clone();
+    let manifest = contents.manifest.as_ref();
+
+    match manifest {
+        None => issues.push(RestoreIssue::warning(
+            None,
+            "Archive has no manifest (taken before validation was added); checksums and schema version cannot be verified".to_string(),
+        )),
+        Some(m) => {
+            if m.format_version > MANIFEST_FORMAT_VERSION {
+                issues.push(RestoreIssue::error(
+                    None,
+                    format!(
+                        "Archive format v{} is newer than this app supports (v{})",
+                        m.format_version, MANIFEST_FORMAT_VERSION
+                    ),
+                ));
+            }
+            if m.database != crate::db::migrations::backend_name() {
+                issues.push(RestoreIssue::warning(
+                    None,
+                    format!(
+                        "Backup was taken on {}; restoring into {}",
+                        m.database,
+                        crate::db::migrations::backend_name()
+                    ),
+                ));
+            }
+            match (m.schema_version, target.current_schema_version) {
+                (Some(archive), Some(current)) if archive > current => {
+                    issues.push(RestoreIssue::error(
+                        None,
+                        format!(
+                            "Backup schema version {} is newer than this database ({}); upgrade the app before restoring",
+                            archive, current
+                        ),
+                    ))
+                }
+                (Some(archive), Some(current)) if archive < current => {
+                    issues.push(RestoreIssue::warning(
+                        None,
+                        format!(
+                            "Backup schema version {} is older than this database ({}); columns added since will use their defaults",
+                            archive, current
+                        ),
+                    ))
+                }
+                _ => {}
+            }
+            match (m.scope.as_str(), target.target_tenant_id) {
+                ("tenant", None) => issues.push(RestoreIssue::error(
+                    None,
+                    "Tenant backup cannot be restored as a global restore".to_string(),
+                )),
+                ("global", Some(_)) => issues.push(RestoreIssue::error(
+                    None,
+                    "Global backup cannot be restored into a single tenant".to_string(),
+                )),
+                ("tenant", Some(tid)) if m.tenant_id.as_deref().is_some_and(|t| t != tid) => {
+                    issues.push(RestoreIssue::warning(
+                        None,
+                        format!(
+                            "Backup belongs to tenant {}; rows will be rewritten to tenant {}",
+                            m.tenant_id.as_deref().unwrap_or_default(),
+                            tid
+                        ),
+                    ))
+                }
+                _ => {}
+            }
+            for table in m.tables.keys() {
+                if !contents.tables.contains_key(table)
+                    && !contents
+                        .read_errors
+                        .iter()
+                        .any(|i| i.table.as_deref() == Some(format!("{}.json", table).as_str()))
+                {
+                    issues.push(RestoreIssue::error(
+                        Some(table.as_str()),
+                        format!("{}.json is listed in the manifest but missing", table),
+                    ));
+                }
+            }
+        }
+    }
+
+    let mut names: Vec<&String> = contents.tables.keys().collect();
+    names.sort_by_key(|n| {
+        target
+            .restorable
+            .iter()
+            .position(|t| *t == n.as_str())
+            .unwrap_or(usize::MAX)
+    });
+
+    let mut tables = Vec::new();
+    for name in names {
+        let data = &contents.tables[name];
+        let will_restore = target.restorable.contains(&name.as_str());
+
+        let rows: Vec<serde_json::Map<String, serde_json::Value>> = match serde_json::from_str(data)
+        {
+            Ok(r) => r,
+            Err(e) => {
+                issues.push(RestoreIssue::error(
+                    Some(name.as_str()),
+                    format!("{}.json is not a JSON array of rows: {}", name, e),
+                ));
+                Vec::new()
+            }
+        };
+
+        let checksum_ok = manifest.and_then(|m| m.tables.get(name)).map(|entry| {
+            let ok = entry.sha256 == sha256_hex(data.as_bytes());
+            if !ok {
+                issues.push(RestoreIssue::error(
+                    Some(name.as_str()),
+                    format!("{}.json checksum does not match the manifest", name),
+                ));
+            } else if entry.rows != rows.len() {
+                issues.push(RestoreIssue::error(
+                    Some(name.as_str()),
+                    format!(
+                        "{}.json has {} rows, manifest says {}",
+                        name,
+                        rows.len(),
+                        entry.rows
+                    ),
+                ));
+            }
+            ok
+        });
+        if manifest.is_some() && checksum_ok.is_none() {
+            issues.push(RestoreIssue::warning(
+                Some(name.as_str()),
+                format!("{}.json is not listed in the manifest", name),
+            ));
+        }
+
+        if !target.restorable.contains(&name.as_str())
+            && !crate::services::backup::RESTORE_ORDER.contains(&name.as_str())
+        {
+            issues.push(RestoreIssue::warning(
+                Some(name.as_str()),
+                format!("{} is not a restorable table and will be ignored", name),
+            ));
+        }
+
+        if will_restore && !rows.is_empty() {
+            match target.columns.get(name) {
+                None => issues.push(RestoreIssue::error(
+                    Some(name.as_str()),
+                    format!("Table {} does not exist in this database", name),
+                )),
+                Some(live) => {
+                    let mut unknown: Vec<&str> = rows
+                        .iter()
+                        .flat_map(|r| r.keys())
+                        .map(String::as_str)
+                        .filter(|c| !live.contains(*c))
+                        .collect::<HashSet<_>>()
+                        .into_iter()
+                        .collect();
+                    if !unknown.is_empty() {
+                        unknown.sort_unstable();
+                        issues.push(RestoreIssue::error(
+                            Some(name.as_str()),
+                            format!(
+                                "{} has columns this database does not: {}",
+                                name,
+                                unknown.join(", ")
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        tables.push(TableCheck {
+            name: name.clone(),
+            rows: rows.len(),
+            checksum_ok,
+            will_restore,
+        });
+    }
+
+    RestoreValidationReport {
+        ok: !issues.iter().any(RestoreIssue::is_error),
+        manifest: contents.manifest.clone(),
+        current_schema_version: target.current_schema_version,
+        tables,
+        issues,
+    }
+}
+
+fn count_rows(contents: &str) -> Option<usize> {
+    serde_json::from_str::<Vec<serde_json::Value>>(contents)
+        .ok()
+        .map(|v| v.len())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive(manifest: Option<BackupManifest>, files: &[(&str, &str)]) -> ArchiveContents {
+        ArchiveContents {
+            manifest,
+            tables: files
+                .iter()
+                .map(|(t, d)| (t.to_string(), d.to_string()))
+                .collect(),
+            read_errors: Vec::new(),
+        }
+    }
+
+    fn columns() -> HashMap<String, HashSet<String>> {
+        let mut m = HashMap::new();
+        m.insert(
+            "roles".to_string(),
+            ["id", "tenant_id", "name"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        );
+        m
+    }
+
+    fn target<'a>(
+        cols: &'a HashMap<String, HashSet<String>>,
+        tenant: Option<&'a str>,
+    ) -> ValidationTarget<'a> {
+        ValidationTarget {
+            current_schema_version: Some(20260301000000),
+            target_tenant_id: tenant,
+            restorable: &["roles"],
+            columns: cols,
+        }
+    }
+
+    const ROLES: &str = r#"[{"id":"r1","tenant_id":"t1","name":"Admin"}]"#;
+
+    fn manifest_for(scope: &str, tenant: Option<&str>, files: &[(&str, &str)]) -> BackupManifest {
+        let files: Vec<(String, String)> = files
+            .iter()
+            .map(|(t, d)| (format!("{}.json", t), d.to_string()))
+            .collect();
+        BackupManifest::build(scope, tenant, Some(20260301000000), &files)
+    }
+
+    #[test]
+    fn test_manifest_build_counts_and_hashes() {
+        let m = manifest_for("tenant", Some("t1"), &[("roles", ROLES)]);
+        assert_eq!(m.tables["roles"].rows, 1);
+        assert_eq!(m.tables["roles"].sha256, sha256_hex(ROLES.as_bytes()));
+        assert_eq!(m.tables["roles"].sha256.len(), 64);
+    }
+
+    #[test]
+    fn test_valid_archive_passes() {
+        let cols = columns();
+        let m = manifest_for("tenant", Some("t1"), &[("roles", ROLES)]);
+        let report = check_archive(
+            &archive(Some(m), &[("roles", ROLES)]),
+            &target(&cols, Some("t1")),
+        );
+        assert!(report.ok, "{:?}", report.issues);
+        assert!(report.issues.is_empty());
+        assert_eq!(report.tables[0].checksum_ok, Some(true));
+        assert!(report.tables[0].will_restore);
+    }
+
+    #[test]
+    fn test_tampered_table_fails_checksum() {
+        let cols = columns();
+        let m = manifest_for("tenant", Some("t1"), &[("roles", ROLES)]);
+        let tampered = ROLES.replace("Admin", "Owner");
+        let report = check_archive(
+            &archive(Some(m), &[("roles", tampered.as_str())]),
+            &target(&cols, Some("t1")),
+        );
+        assert!(!report.ok);
+        assert_eq!(report.tables[0].checksum_ok, Some(false));
+        assert!(report.error_summary().contains("checksum"));
+    }
+
+    #[test]
+    fn test_newer_schema_and_scope_mismatch_are_errors() {
+        let cols = columns();
+        let mut m = manifest_for("tenant", Some("t1"), &[("roles", ROLES)]);
+        m.schema_version = Some(20990101000000);
+        let report = check_archive(&archive(Some(m), &[("roles", ROLES)]), &target(&cols, None));
+        let errors: Vec<_> = report.issues.iter().filter(|i| i.is_error()).collect();
+        assert_eq!(errors.len(), 2, "{:?}", report.issues);
+        assert!(!report.ok);
+    }
+
+    #[test]
+    fn test_unknown_columns_and_missing_files() {
+        let cols = columns();
+        let m = manifest_for(
+            "tenant",
+            Some("t1"),
+            &[("roles", ROLES), ("settings", "[]")],
+        );
+        let extra = r#"[{"id":"r1","tenant_id":"t1","name":"Admin","legacy_flag":true}]"#;
+        let mut contents = archive(Some(m), &[("roles", extra)]);
+        // Only the checksum/row mismatch and unknown column matter here.
+        contents
+            .manifest
+            .as_mut()
+            .unwrap()
+            .tables
+            .get_mut("roles")
+            .unwrap()
+            .sha256 = sha256_hex(extra.as_bytes());
+        let report = check_archive(&contents, &target(&cols, Some("t1")));
+        let messages: Vec<&str> = report.issues.iter().map(|i| i.message.as_str()).collect();
+        assert!(
+            messages.iter().any(|m| m.contains("legacy_flag")),
+            "{:?}",
+            messages
+        );
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.contains("settings.json is listed")),
+            "{:?}",
+            messages
+        );
+    }
+
+    #[test]
+    fn test_legacy_archive_only_warns() {
+        let cols = columns();
+        let report = check_archive(
+            &archive(None, &[("roles", ROLES)]),
+            &target(&cols, Some("t1")),
+        );
+        assert!(report.ok);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].severity, "warning");
+        assert_eq!(report.tables[0].checksum_ok, None);
+    }
+}
@@ -0,0 +1,425 @@
+//! CGNAT port-block records for abuse and lawful requests.
+//!
+//! NAT logs are imported as assignment lines (public IP, port range, private
+//! IP, time). Each line is attributed to a PPPoE account when imported, by
+//! username when the log carries it, else by the account's remote address,
+//! and the username and customer name are copied so the record still
+//! answers a request after the account is gone. A lookup of public IP, port
+//! and time returns the subscriber(s) holding that port, and is audited.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CgnatImportResult, CgnatLogEntry, CgnatLookupQuery, CgnatPortBlock, ImportCgnatLogRequest,
+    SkippedCgnatLogEntry,
+};
+use crate::services::{AuditService, AuthService};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use uuid::Uuid;
+
+const MAX_IMPORT_ENTRIES: usize = 10_000;
+
+const BLOCK_SELECT: &str = r#"
+    SELECT b.id, b.router_id, r.name AS router_name, b.public_ip, b.port_start, b.port_end,
+           b.private_ip, b.pppoe_account_id, b.customer_id, b.username,
+           COALESCE(c.name, b.customer_name) AS customer_name, b.assigned_at, b.released_at
+    FROM cgnat_port_blocks b
+    LEFT JOIN mikrotik_routers r ON r.id = b.router_id
+    LEFT JOIN customers c ON c.id = b.customer_id
+"#;
+
+fn parse_ip(raw: &str, field: &str) -> Result<String, String> {
+    raw.trim()
+        .parse::<Ipv4Addr>()
+        .map(|ip| ip.to_string())
+        .map_err(|_| format!("{} '{}' is not an IPv4 address", field, raw.trim()))
+}
+
+#[derive(Debug, PartialEq)]
+struct ValidEntry {
+    public_ip: String,
+    port_start: i32,
+    port_end: i32,
+    private_ip: String,
+    assigned_at: DateTime<Utc>,
+    released_at: Option<DateTime<Utc>>,
+    username: Option<String>,
+}
+
+fn validate_entry(entry: CgnatLogEntry) -> Result<ValidEntry, String> {
+    let public_ip = parse_ip(&entry.public_ip, "Public IP")?;
+    let private_ip = parse_ip(&entry.private_ip, "Private IP")?;
+    if !(1..=65535).contains(&entry.port_start)
+        || !(entry.port_start..=65535).contains(&entry.port_end)
+    {
+        return Err(format!(
+            "Invalid port range {}-{}",
+            entry.port_start, entry.port_end
+        ));
+    }
+    if entry.released_at.is_some_and(|r| r <= entry.assigned_at) {
+        return Err("Released before it was assigned".to_string());
+    }
+    Ok(ValidEntry {
+        public_ip,
+        port_start: entry.port_start,
+        port_end: entry.port_end,
+        private_ip,
+        assigned_at: entry.assigned_at,
+        released_at: entry.released_at,
+        username: entry
+            .username
+            .map(|u| u.trim().to_string())
+            .filter(|u| !u.is_empty()),
+    })
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct AccountRef {
+    id: String,
+    username: String,
+    remote_address: Option<String>,
+    customer_id: String,
+    customer_name: String,
+}
+
+/// PPPoE accounts by username and by static remote address.
+struct AccountIndex {
+    by_username: HashMap<String, AccountRef>,
+    by_address: HashMap<String, AccountRef>,
+}
+
+impl AccountIndex {
+    fn new(accounts: Vec<AccountRef>) -> Self {
+        let mut by_username = HashMap::new();
+        let mut by_address = HashMap::new();
+        for account in accounts {
+            if let Some(ip) = account
+                .remote_address
+                .as_deref()
+                .and_then(|a| a.trim().trim_end_matches("/32").parse::<Ipv4Addr>().ok())
+            {
+                by_address.insert(ip.to_string(), account.clone());
+            }
+            by_username.insert(account.username.to_lowercase(), account);
+        }
+        Self {
+            by_username,
+            by_address,
+        }
+    }
+
+    /// A username in the log is trusted over the address: the address
+    /// is today's, the log line may be older.
+    fn find(&self, username: Option<&str>, private_ip: &str) -> Option<&AccountRef> {
+        match username {
+            Some(u) => self.by_username.get(&u.to_lowercase()),
+            None => self.by_address.get(private_ip),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CgnatService {
+    pool: DbPool,
+    auth_service: AuthService,
+    audit_service: AuditService,
+}
+
+impl CgnatService {
+    pub fn new(pool: DbPool, auth_service: AuthService, audit_service: AuditService) -> Self {
+        Self {
+            pool,
+            auth_service,
+            audit_service,
+        }
+    }
+
+    /// Stores the log entries, oldest first. A new assignment closes any
+    /// still-open earlier block on the same ports; importing an older log
+    /// later closes its blocks at the next known assignment.
+    pub async fn import_log(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: ImportCgnatLogRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<CgnatImportResult> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "cgnat", "manage")
+            .await?;
+        if dto.entries.is_empty() {
+            return Err(AppError::Validation("No log entries given".to_string()));
+        }
+        if dto.entries.len() > MAX_IMPORT_ENTRIES {
+            return Err(AppError::Validation(format!(
+                "At most {} log entries per import",
+                MAX_IMPORT_ENTRIES
+            )));
+        }
+        let router_id = dto
+            .router_id
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty());
+        if let Some(router_id) = router_id.as_deref() {
+            let exists: Option<i32> = sqlx::query_scalar(
+                "SELECT 1 FROM mikrotik_routers WHERE tenant_id = $1 AND id = $2",
+            )
+            .bind(tenant_id)
+            .bind(router_id)
+            .fetch_optional(&self.pool)
+            .await?;
+            if exists.is_none() {
+                return Err(AppError::Validation("Router not found".to_string()));
+            }
+        }
+
+        let mut skipped = Vec::new();
+        let mut entries = Vec::with_capacity(dto.entries.len());
+        for (index, entry) in dto.entries.into_iter().enumerate() {
+            match validate_entry(entry) {
+                Ok(entry) => entries.push(entry),
+                Err(reason) => skipped.push(SkippedCgnatLogEntry { index, reason }),
+            }
+        }
+        entries.sort_by_key(|e| e.assigned_at);
+
+        let accounts: Vec<AccountRef> = sqlx::query_as(
+            r#"
+            SELECT a.id, a.username, a.remote_address, a.customer_id, c.name AS customer_name
+            FROM pppoe_accounts a
+            JOIN customers c ON c.id = a.customer_id
+            WHERE a.tenant_id = $1 AND ($2::text IS NULL OR a.router_id = $2)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&router_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let index = AccountIndex::new(accounts);
+
+        let mut result = CgnatImportResult {
+            imported: 0,
+            updated: 0,
+            unmatched: 0,
+            skipped,
+        };
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        for entry in entries {
+            let account = index.find(entry.username.as_deref(), &entry.private_ip);
+
+            sqlx::query(
+                r#"
+                UPDATE cgnat_port_blocks
+                SET released_at = $5, updated_at = $6
+                WHERE tenant_id = $1 AND public_ip = $2 AND released_at IS NULL
+                  AND port_start <= $4 AND port_end >= $3 AND assigned_at < $5
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(&entry.public_ip)
+            .bind(entry.port_start)
+            .bind(entry.port_end)
+            .bind(entry.assigned_at)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+            let inserted: bool = sqlx::query_scalar(
+                r#"
+                INSERT INTO cgnat_port_blocks
+                    (id, tenant_id, router_id, public_ip, port_start, port_end, private_ip,
+                     pppoe_account_id, customer_id, username, customer_name,
+                     assigned_at, released_at, created_at, updated_at)
+                VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,
+                    COALESCE($13, (
+                        SELECT MIN(n.assigned_at) FROM cgnat_port_blocks n
+                        WHERE n.tenant_id = $2 AND n.public_ip = $4
+                          AND n.port_start <= $6 AND n.port_end >= $5 AND n.assigned_at > $12
+                    )),
+                    $14,$14)
+                ON CONFLICT (tenant_id, public_ip, port_start, assigned_at) DO UPDATE
+                SET released_at = COALESCE(cgnat_port_blocks.released_at, EXCLUDED.released_at),
+                    updated_at = EXCLUDED.updated_at
+                RETURNING (xmax = 0)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(tenant_id)
+            .bind(&router_id)
+            .bind(&entry.public_ip)
+            .bind(entry.port_start)
+            .bind(entry.port_end)
+            .bind(&entry.private_ip)
+            .bind(account.map(|a| a.id.as_str()))
+            .bind(account.map(|a| a.customer_id.as_str()))
+            .bind(
+                account
+                    .map(|a| a.username.as_str())
+                    .or(entry.username.as_deref()),
+            )
+            .bind(account.map(|a| a.customer_name.as_str()))
+            .bind(entry.assigned_at)
+            .bind(entry.released_at)
+            .bind(now)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if !inserted {
+                result.updated += 1;
+                continue;
+            }
+            result.imported += 1;
+            if account.is_none() {
+                result.unmatched += 1;
+            }
+        }
+        tx.commit().await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "CGNAT_LOG_IMPORT",
+                "cgnat_port_blocks",
+                router_id.as_deref(),
+                Some(&format!(
+                    "Imported {} CGNAT port blocks ({} updated, {} unmatched, {} skipped)",
+                    result.imported,
+                    result.updated,
+                    result.unmatched,
+                    result.skipped.len()
+                )),
+                ip_address,
+            )
+            .await;
+
+        Ok(result)
+    }
+
+    /// Who held `public_ip:port` at `at`, current holder first. Every lookup
+    /// is audited with its reference, whether or not anything matched.
+    pub async fn lookup(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        q: CgnatLookupQuery,
+        ip_address: Option<&str>,
+    ) -> AppResult<Vec<CgnatPortBlock>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "cgnat", "read")
+            .await?;
+        let public_ip = parse_ip(&q.public_ip, "Public IP").map_err(AppError::Validation)?;
+        if !(1..=65535).contains(&q.port) {
+            return Err(AppError::Validation("Port must be 1-65535".to_string()));
+        }
+        let reference = q
+            .reference
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty());
+
+        let rows: Vec<CgnatPortBlock> = sqlx::query_as(&format!(
+            r#"{BLOCK_SELECT}
+            WHERE b.tenant_id = $1 AND b.public_ip = $2
+              AND b.port_start <= $3 AND b.port_end >= $3
+              AND b.assigned_at <= $4 AND (b.released_at IS NULL OR b.released_at > $4)
+            ORDER BY b.assigned_at DESC"#
+        ))
+        .bind(tenant_id)
+        .bind(&public_ip)
+        .bind(q.port)
+        .bind(q.at)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut details = format!(
+            "Looked up {}:{} at {} ({} match{})",
+            public_ip,
+            q.port,
+            q.at.to_rfc3339(),
+            rows.len(),
+            if rows.len() == 1 { "" } else { "es" }
+        );
+        if let Some(reference) = reference.as_deref() {
+            details.push_str(&format!(", reference {}", reference));
+        }
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "CGNAT_LOOKUP",
+                "cgnat_port_blocks",
+                rows.first().map(|r| r.id.as_str()),
+                Some(&details),
+                ip_address,
+            )
+            .await;
+
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_entry, AccountIndex, AccountRef};
+    use crate::models::CgnatLogEntry;
+    use chrono::{Duration, Utc};
+
+    fn entry(port_start: i32, port_end: i32) -> CgnatLogEntry {
+        CgnatLogEntry {
+            public_ip: " 203.0.113.7 ".to_string(),
+            port_start,
+            port_end,
+            private_ip: "100.64.0.10".to_string(),
+            assigned_at: Utc::now(),
+            released_at: None,
+            username: Some("  ".to_string()),
+        }
+    }
+
+    #[test]
+    fn log_entries_are_checked() {
+        let ok = validate_entry(entry(1024, 2047)).unwrap();
+        assert_eq!(ok.public_ip, "203.0.113.7");
+        assert_eq!(ok.username, None);
+
+        assert!(validate_entry(entry(0, 100)).is_err());
+        assert!(validate_entry(entry(2048, 1024)).is_err());
+        assert!(validate_entry(entry(65000, 65536)).is_err());
+        assert!(validate_entry(CgnatLogEntry {
+            private_ip: "cust-10".to_string(),
+            ..entry(1024, 2047)
+        })
+        .is_err());
+        let e = entry(1024, 2047);
+        assert!(validate_entry(CgnatLogEntry {
+            released_at: Some(e.assigned_at - Duration::seconds(1)),
+            ..e
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn accounts_match_by_username_before_address() {
+        let account = |id: &str, username: &str, address: Option<&str>| AccountRef {
+            id: id.to_string(),
+            username: username.to_string(),
+            remote_address: address.map(str::to_string),
+            customer_id: format!("c-{}", id),
+            customer_name: id.to_string(),
+        };
+        let index = AccountIndex::new(vec![
+            account("a1", "Budi", Some("100.64.0.10/32")),
+            account("a2", "siti", Some("100.64.0.11")),
+            account("a3", "dynamic", None),
+        ]);
+        let find = |u: Option<&str>, ip: &str| index.find(u, ip).map(|a| a.id.as_str());
+        assert_eq!(find(None, "100.64.0.10"), Some("a1"));
+        assert_eq!(find(Some("SITI"), "100.64.0.10"), Some("a2"));
+        assert_eq!(find(Some("unknown"), "100.64.0.10"), None);
+        assert_eq!(find(None, "100.64.0.99"), None);
+    }
+}
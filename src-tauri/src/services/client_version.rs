@@ -0,0 +1,190 @@
+//! Desktop client versions: the release channel clients update from and the
+//! oldest client still allowed to use the API.
+
+use crate::error::{AppError, AppResult};
+use crate::models::ClientCompatibility;
+use crate::services::SettingsService;
+use std::cmp::Ordering;
+
+pub const UPDATE_CHANNELS: [&str; 2] = ["stable", "beta"];
+
+/// `major.minor.patch` with an optional pre-release tag, e.g. `1.4.0-beta.2`.
+/// Build metadata (`+...`) is ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientVersion {
+    numbers: [u64; 3],
+    pre: Option<String>,
+}
+
+impl ClientVersion {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let value = value.strip_prefix('v').unwrap_or(value);
+        let value = value.split('+').next()?;
+        let (core, pre) = match value.split_once('-') {
+            Some((core, pre)) if !pre.is_empty() => (core, Some(pre.to_string())),
+            Some(_) => return None,
+            None => (value, None),
+        };
+
+        let mut numbers = [0u64; 3];
+        let mut parts = core.split('.');
+        for (i, slot) in numbers.iter_mut().enumerate() {
+            match parts.next() {
+                Some(part) => *slot = part.parse().ok()?,
+                // `1.4` reads as `1.4.0`; a bare major is too vague.
+                None if i == 2 => break,
+                None => return None,
+            }
+        }
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self { numbers, pre })
+    }
+}
+
+impl Ord for ClientVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.numbers
+            .cmp(&other.numbers)
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                // A release sorts after its own pre-releases.
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_pre(a, b),
+            })
+    }
+}
+
+impl PartialOrd for ClientVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares dot-separated identifiers, numbers numerically, so `beta.10` > `beta.2`.
+fn compare_pre(a: &str, b: &str) -> Ordering {
+    let mut left = a.split('.');
+    let mut right = b.split('.');
+    loop {
+        let ord = match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => x.cmp(y),
+            },
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+}
+
+/// Rejects values the desktop clients could not act on.
+pub fn validate_setting(key: &str, value: &str) -> AppResult<()> {
+    let value = value.trim();
+    match key {
+        "update_channel" if !UPDATE_CHANNELS.contains(&value) => {
+            Err(AppError::Validation(format!(
+                "update_channel must be one of: {}",
+                UPDATE_CHANNELS.join(", ")
+            )))
+        }
+        "client_min_version" if !value.is_empty() && ClientVersion::parse(value).is_none() => Err(
+            AppError::Validation("client_min_version must look like 1.4.0".to_string()),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Whether a client may use the API. Once a minimum is set, clients that don't
+/// say which version they are (or send garbage) are turned away too.
+pub fn is_supported(client: Option<&str>, minimum: Option<&str>) -> bool {
+    let Some(minimum) = minimum.and_then(ClientVersion::parse) else {
+        return true;
+    };
+    client
+        .and_then(ClientVersion::parse)
+        .is_some_and(|client| client >= minimum)
+}
+
+pub async fn client_compatibility(
+    settings: &SettingsService,
+    client_version: Option<&str>,
+) -> AppResult<ClientCompatibility> {
+    let minimum_version = settings
+        .get_value(None, "client_min_version")
+        .await?
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    let update_channel = settings
+        .get_value(None, "update_channel")
+        .await?
+        .map(|v| v.trim().to_string())
+        .filter(|v| UPDATE_CHANNELS.contains(&v.as_str()))
+        .unwrap_or_else(|| "stable".to_string());
+    let client_version = client_version
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string);
+
+    Ok(ClientCompatibility {
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        supported: is_supported(client_version.as_deref(), minimum_version.as_deref()),
+        client_version,
+        minimum_version,
+        update_channel,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(value: &str) -> ClientVersion {
+        ClientVersion::parse(value).unwrap()
+    }
+
+    #[test]
+    fn parses_and_orders_versions() {
+        assert_eq!(v("v1.4"), v("1.4.0"));
+        assert_eq!(v("1.4.0+build.7"), v("1.4.0"));
+        assert!(ClientVersion::parse("1").is_none());
+        assert!(ClientVersion::parse("1.4.0.1").is_none());
+        assert!(ClientVersion::parse("1.4.0-").is_none());
+        assert!(ClientVersion::parse("latest").is_none());
+
+        assert!(v("1.10.0") > v("1.9.3"));
+        assert!(v("1.4.0") > v("1.4.0-beta.2"));
+        assert!(v("1.4.0-beta.10") > v("1.4.0-beta.2"));
+        assert!(v("1.4.0-beta") > v("1.4.0-alpha.5"));
+        assert!(v("1.4.0-beta.1") > v("1.4.0-beta"));
+    }
+
+    #[test]
+    fn supported_against_minimum() {
+        assert!(is_supported(None, None));
+        assert!(is_supported(Some("0.1.0"), None));
+        assert!(is_supported(Some("1.4.0"), Some("1.4.0")));
+        assert!(is_supported(Some("1.5.0-beta.1"), Some("1.4.0")));
+        assert!(!is_supported(Some("1.4.0-beta.3"), Some("1.4.0")));
+        assert!(!is_supported(None, Some("1.4.0")));
+        assert!(!is_supported(Some("dev"), Some("1.4.0")));
+    }
+
+    #[test]
+    fn validates_settings() {
+        assert!(validate_setting("update_channel", "beta").is_ok());
+        assert!(validate_setting("update_channel", "nightly").is_err());
+        assert!(validate_setting("client_min_version", "").is_ok());
+        assert!(validate_setting("client_min_version", "2.0.1").is_ok());
+        assert!(validate_setting("client_min_version", "two").is_err());
+        assert!(validate_setting("app_name", "anything").is_ok());
+    }
+}
@@ -0,0 +1,834 @@
+//! Completion reports (berita acara) for finished work orders.
+//!
+//! The customer signs on the technician's device when the work is done. The
+//! signature is stamped into a PDF together with the job details, checklist,
+//! materials and visit times; the PDF is kept in file storage under the
+//! customer and a copy is emailed to them.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CompleteWorkOrderRequest, InstallationWorkOrder, WorkOrderChecklistItem,
+    WorkOrderCompletionReport, WorkOrderSignatureInput, WorkOrderVisit,
+};
+use crate::services::report_pdf::{JpegImage, PdfDocument, SignatureMark};
+use crate::services::storage_service::StorageContent;
+use crate::services::{
+    AuditService, AuthService, CustomerService, EmailAttachment, EmailService, StorageService,
+    WorkOrderChecklistService,
+};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use tracing::warn;
+use uuid::Uuid;
+
+const MAX_SIGNER_NAME_LEN: usize = 120;
+const MAX_SIGNATURE_POINTS: usize = 5000;
+const MAX_SIGNATURE_IMAGE_BYTES: usize = 512 * 1024;
+
+const REPORT_SELECT: &str = r#"
+    SELECT r.id, r.work_order_id, r.customer_id, r.report_number, r.signer_name,
+           r.signature_kind, r.signature_file_id, r.file_id, r.signed_at,
+           r.emailed_to, r.emailed_at, r.email_error,
+           r.created_by, u.name AS created_by_name, r.created_at
+    FROM work_order_completion_reports r
+    LEFT JOIN users u ON u.id = r.created_by
+"#;
+
+/// A signature that passed validation.
+#[derive(Debug)]
+enum CapturedSignature {
+    Strokes(Vec<Vec<[f64; 2]>>),
+    Jpeg(Vec<u8>),
+}
+
+impl CapturedSignature {
+    fn kind(&self) -> &'static str {
+        match self {
+            CapturedSignature::Strokes(_) => "strokes",
+            CapturedSignature::Jpeg(_) => "image",
+        }
+    }
+}
+
+fn validate_signature(input: WorkOrderSignatureInput) -> AppResult<(String, CapturedSignature)> {
+    let signer_name = input.signer_name.trim().to_string();
+    if signer_name.is_empty() {
+        return Err(AppError::Validation(
+            "Name of the person signing is required".to_string(),
+        ));
+    }
+    if signer_name.chars().count() > MAX_SIGNER_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "Signer name is too long (max {} characters)",
+            MAX_SIGNER_NAME_LEN
+        )));
+    }
+
+    let strokes: Vec<Vec<[f64; 2]>> = input
+        .strokes
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect();
+    if !strokes.is_empty() {
+        let points = strokes.iter().map(Vec::len).sum::<usize>();
+        if points > MAX_SIGNATURE_POINTS {
+            return Err(AppError::Validation(format!(
+                "Signature has too many points (max {})",
+                MAX_SIGNATURE_POINTS
+            )));
+        }
+        if points < 2 {
+            return Err(AppError::Validation("Signature is empty".to_string()));
+        }
+        if strokes.iter().flatten().flatten().any(|v| !v.is_finite()) {
+            return Err(AppError::Validation(
+                "Signature contains invalid coordinates".to_string(),
+            ));
+        }
+        return Ok((signer_name, CapturedSignature::Strokes(strokes)));
+    }
+
+    let raw = input
+        .image_base64
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| {
+            AppError::Validation(
+                "Signature is required: send pen strokes or a JPEG image".to_string(),
+            )
+        })?;
+    // Accept `data:image/jpeg;base64,...` as produced by canvas.toDataURL.
+    let encoded = match raw.split_once(',') {
+        Some((prefix, data)) if prefix.starts_with("data:") => data,
+        _ => raw,
+    };
+    let data = general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|_| AppError::Validation("Signature image is not valid base64".to_string()))?;
+    if data.len() > MAX_SIGNATURE_IMAGE_BYTES {
+        return Err(AppError::Validation(format!(
+            "Signature image is too large (max {} KB)",
+            MAX_SIGNATURE_IMAGE_BYTES / 1024
+        )));
+    }
+    JpegImage::parse(data.clone())?;
+    Ok((signer_name, CapturedSignature::Jpeg(data)))
+}
+
+#[derive(Debug, Default, sqlx::FromRow)]
+struct ReportHeader {
+    tenant_name: String,
+    customer_name: Option<String>,
+    customer_email: Option<String>,
+    customer_phone: Option<String>,
+    location_label: Option<String>,
+    address_line1: Option<String>,
+    address_line2: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+    postal_code: Option<String>,
+    technician_name: Option<String>,
+    package_name: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct MaterialLine {
+    name: String,
+    unit: String,
+    quantity: f64,
+}
+
+/// Everything printed on the report.
+struct ReportContent<'a> {
+    report_number: &'a str,
+    work_order: &'a InstallationWorkOrder,
+    header: &'a ReportHeader,
+    checklist: &'a [WorkOrderChecklistItem],
+    materials: &'a [MaterialLine],
+    visits: &'a [WorkOrderVisit],
+    signer_name: &'a str,
+    signature: &'a CapturedSignature,
+    signed_at: DateTime<Utc>,
+    tz: Tz,
+}
+
+fn or_dash(value: Option<&str>) -> &str {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .unwrap_or("-")
+}
+
+fn format_quantity(quantity: f64) -> String {
+    let text = format!("{:.3}", quantity);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn render_report(c: &ReportContent<'_>) -> AppResult<Vec<u8>> {
+    let local = |at: DateTime<Utc>| at.with_timezone(&c.tz).format("%d %b %Y %H:%M").to_string();
+    let h = c.header;
+    let wo = c.work_order;
+
+    let mut doc = PdfDocument::new();
+    doc.title(&h.tenant_name);
+    doc.title("Work Completion Report (Berita Acara)");
+    doc.field("Report number", c.report_number);
+    doc.field("Work order", &wo.id);
+    doc.field("Type of work", &wo.work_type);
+    doc.field(
+        "Completed at",
+        &local(wo.completed_at.unwrap_or(c.signed_at)),
+    );
+    doc.field("Technician", or_dash(h.technician_name.as_deref()));
+
+    doc.heading("Customer");
+    doc.field("Name", or_dash(h.customer_name.as_deref()));
+    doc.field("Phone", or_dash(h.customer_phone.as_deref()));
+    doc.field("Email", or_dash(h.customer_email.as_deref()));
+    doc.field("Location", or_dash(h.location_label.as_deref()));
+    let address = [
+        &h.address_line1,
+        &h.address_line2,
+        &h.city,
+        &h.state,
+        &h.postal_code,
+    ]
+    .into_iter()
+    .filter_map(|v| v.as_deref().map(str::trim).filter(|v| !v.is_empty()))
+    .collect::<Vec<_>>()
+    .join(", ");
+    doc.field("Address", or_dash(Some(address.as_str())));
+    if let Some(package) = h.package_name.as_deref() {
+        doc.field("Package", package);
+    }
+
+    if !c.visits.is_empty() {
+        doc.heading("Site visits");
+        for visit in c.visits {
+            let out = visit
+                .check_out_at
+                .map(local)
+                .unwrap_or_else(|| "-".to_string());
+            doc.field(
+                or_dash(visit.technician_name.as_deref()),
+                &format!("{} until {}", local(visit.check_in_at), out),
+            );
+        }
+    }
+
+    if !c.checklist.is_empty() {
+        doc.heading("Checklist");
+        for item in c.checklist {
+            let mark = if item.is_done { "Done" } else { "Not done" };
+            let value = match (item.kind.as_str(), item.value.as_deref()) {
+                ("photo", _) => format!("{} (photo: {})", mark, or_dash(item.file_name.as_deref())),
+                (_, Some(v)) if !v.trim().is_empty() => format!("{} - {}", mark, v.trim()),
+                _ => mark.to_string(),
+            };
+            doc.field(&item.label, &value);
+        }
+    }
+
+    if !c.materials.is_empty() {
+        doc.heading("Materials used");
+        for line in c.materials {
+            doc.field(
+                &line.name,
+                &format!("{} {}", format_quantity(line.quantity), line.unit),
+            );
+        }
+    }
+
+    if let Some(notes) = wo.notes.as_deref().filter(|v| !v.trim().is_empty()) {
+        doc.heading("Notes");
+        doc.paragraph(notes.trim());
+    }
+
+    doc.heading("Customer acceptance");
+    doc.paragraph(
+        "The customer confirms that the work described above has been carried out and is accepted.",
+    );
+    let mark = match c.signature {
+        CapturedSignature::Strokes(strokes) => SignatureMark::Strokes(strokes),
+        CapturedSignature::Jpeg(data) => SignatureMark::Jpeg(JpegImage::parse(data.clone())?),
+    };
+    let signed = format!("Signed {}", local(c.signed_at));
+    doc.signature(mark, &[c.signer_name, signed.as_str()]);
+
+    Ok(doc.finish())
+}
+
+#[derive(Clone)]
+pub struct CompletionReportService {
+    pool: DbPool,
+    auth_service: AuthService,
+    audit_service: AuditService,
+    customers: CustomerService,
+    checklists: WorkOrderChecklistService,
+    storage: StorageService,
+    email: EmailService,
+}
+
+impl CompletionReportService {
+    pub fn new(
+        pool: DbPool,
+        auth_service: AuthService,
+        audit_service: AuditService,
+        customers: CustomerService,
+        storage: StorageService,
+        email: EmailService,
+    ) -> Self {
+        Self {
+            checklists: WorkOrderChecklistService::new(pool.clone()),
+            pool,
+            auth_service,
+            audit_service,
+            customers,
+            storage,
+            email,
+        }
+    }
+
+    /// Completes the work order and, when the customer signed, produces the
+    /// report. The signature is checked before anything changes; once the
+    /// order is completed a failure to build the report is only logged, and
+    /// the report can be made afterwards with [`Self::sign_completed`].
+    pub async fn complete_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        dto: CompleteWorkOrderRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<InstallationWorkOrder> {
+        let signature = dto.signature.map(validate_signature).transpose()?;
+        let row = self
+            .customers
+            .complete_installation_work_order(
+                actor_id,
+                tenant_id,
+                work_order_id,
+                dto.notes,
+                ip_address,
+            )
+            .await?;
+
+        if let Some((signer_name, signature)) = signature {
+            if let Err(err) = self
+                .create_report(
+                    actor_id,
+                    tenant_id,
+                    &row,
+                    signer_name,
+                    signature,
+                    ip_address,
+                )
+                .await
+            {
+                warn!(
+                    "Failed to create completion report for work order {}: {}",
+                    row.id, err
+                );
+            }
+        }
+        Ok(row)
+    }
+
+    /// Adds a signed report to a work order that is already completed, e.g.
+    /// when the customer was not around at completion or the first attempt
+    /// failed.
+    pub async fn sign_completed(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        dto: WorkOrderSignatureInput,
+        ip_address: Option<&str>,
+    ) -> AppResult<WorkOrderCompletionReport> {
+        self.customers
+            .authorize_work_order_access(actor_id, tenant_id, work_order_id, true)
+            .await?;
+        let (signer_name, signature) = validate_signature(dto)?;
+        let row = self.work_order(tenant_id, work_order_id).await?;
+        if row.status != "completed" {
+            return Err(AppError::Validation(
+                "Only completed work orders can be signed off".to_string(),
+            ));
+        }
+        self.create_report(
+            actor_id,
+            tenant_id,
+            &row,
+            signer_name,
+            signature,
+            ip_address,
+        )
+        .await
+    }
+
+    pub async fn list_for_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+    ) -> AppResult<Vec<WorkOrderCompletionReport>> {
+        self.customers
+            .authorize_work_order_access(actor_id, tenant_id, work_order_id, false)
+            .await?;
+        let rows: Vec<WorkOrderCompletionReport> = sqlx::query_as(&format!(
+            "{REPORT_SELECT} WHERE r.tenant_id = $1 AND r.work_order_id = $2 ORDER BY r.created_at DESC"
+        ))
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn list_for_customer(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        customer_id: &str,
+    ) -> AppResult<Vec<WorkOrderCompletionReport>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "customers", "read")
+            .await?;
+        let rows: Vec<WorkOrderCompletionReport> = sqlx::query_as(&format!(
+            "{REPORT_SELECT} WHERE r.tenant_id = $1 AND r.customer_id = $2 ORDER BY r.created_at DESC"
+        ))
+        .bind(tenant_id)
+        .bind(customer_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Emails the stored PDF to the customer again, e.g. after their address
+    /// was corrected.
+    pub async fn resend(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        report_id: &str,
+        ip_address: Option<&str>,
+    ) -> AppResult<WorkOrderCompletionReport> {
+        let report = self.get(tenant_id, report_id).await?;
+        self.customers
+            .authorize_work_order_access(actor_id, tenant_id, &report.work_order_id, true)
+            .await?;
+        let file_id = report.file_id.as_deref().ok_or_else(|| {
+            AppError::NotFound("The report file is no longer available".to_string())
+        })?;
+        let pdf = self.read_file(file_id).await?;
+        let header = self.header(tenant_id, &report.work_order_id).await?;
+        let email = header
+            .customer_email
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| AppError::Validation("Customer has no email address".to_string()))?;
+
+        if let Err(err) = self
+            .email_report(tenant_id, &report, &header, email, pdf)
+            .await
+        {
+            return Err(AppError::Validation(format!(
+                "Could not email the report: {}",
+                err
+            )));
+        }
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WORK_ORDER_REPORT_EMAIL",
+                "work_order_completion_reports",
+                Some(report_id),
+                Some(&format!(
+                    "Emailed report {} to {}",
+                    report.report_number, email
+                )),
+                ip_address,
+            )
+            .await;
+        self.get(tenant_id, report_id).await
+    }
+
+    async fn create_report(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        row: &InstallationWorkOrder,
+        signer_name: String,
+        signature: CapturedSignature,
+        ip_address: Option<&str>,
+    ) -> AppResult<WorkOrderCompletionReport> {
+        let id = Uuid::new_v4().to_string();
+        let signed_at = Utc::now();
+        let report_number = format!(
+            "BA-{}-{}",
+            signed_at.format("%Y%m%d"),
+            id[..8].to_uppercase()
+        );
+
+        let header = self.header(tenant_id, &row.id).await?;
+        let checklist = self.checklists.get_checklist(tenant_id, &row.id).await?;
+        let materials: Vec<MaterialLine> = sqlx::query_as(
+            r#"
+            SELECT i.name, i.unit, SUM(m.quantity)::float8 AS quantity
+            FROM inventory_movements m
+            JOIN inventory_items i ON i.id = m.item_id
+            WHERE m.tenant_id = $1 AND m.work_order_id = $2 AND m.kind = 'consume'
+            GROUP BY i.name, i.unit
+            ORDER BY i.name
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&row.id)
+        .fetch_all(&self.pool)
+        .await?;
+        let visits = self
+            .customers
+            .list_work_order_visits(actor_id, tenant_id, &row.id)
+            .await?;
+        let tz = sqlx::query_scalar::<_, String>(
+            "SELECT value FROM settings WHERE key = 'app_timezone' AND tenant_id IS NULL",
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .and_then(|v| v.trim().parse::<Tz>().ok())
+        .unwrap_or(chrono_tz::UTC);
+
+        let pdf = render_report(&ReportContent {
+            report_number: &report_number,
+            work_order: row,
+            header: &header,
+            checklist: &checklist.items,
+            materials: &materials,
+            visits: &visits,
+            signer_name: &signer_name,
+            signature: &signature,
+            signed_at,
+            tz,
+        })?;
+
+        let signature_file_id = match &signature {
+            CapturedSignature::Jpeg(data) => Some(
+                self.storage
+                    .upload(
+                        tenant_id,
+                        &format!("signature-{}.jpg", report_number),
+                        "image/jpeg",
+                        data,
+                        Some(actor_id),
+                    )
+                    .await?
+                    .id,
+            ),
+            CapturedSignature::Strokes(_) => None,
+        };
+        let file = self
+            .storage
+            .upload(
+                tenant_id,
+                &format!("berita-acara-{}.pdf", report_number),
+                "application/pdf",
+                &pdf,
+                Some(actor_id),
+            )
+            .await?;
+        let strokes = match &signature {
+            CapturedSignature::Strokes(strokes) => {
+                Some(serde_json::to_value(strokes).map_err(|e| AppError::Internal(e.to_string()))?)
+            }
+            CapturedSignature::Jpeg(_) => None,
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO work_order_completion_reports
+              (id, tenant_id, work_order_id, customer_id, report_number, signer_name,
+               signature_kind, signature_strokes, signature_file_id, file_id, signed_at,
+               created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&row.id)
+        .bind(&row.customer_id)
+        .bind(&report_number)
+        .bind(&signer_name)
+        .bind(signature.kind())
+        .bind(strokes)
+        .bind(signature_file_id)
+        .bind(&file.id)
+        .bind(signed_at)
+        .bind(actor_id)
+        .bind(signed_at)
+        .execute(&self.pool)
+        .await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WORK_ORDER_REPORT",
+                "work_order_completion_reports",
+                Some(&id),
+                Some(&format!(
+                    "Completion report {} for work order {} signed by {}",
+                    report_number, row.id, signer_name
+                )),
+                ip_address,
+            )
+            .await;
+
+        let report = self.get(tenant_id, &id).await?;
+        if let Some(email) = header
+            .customer_email
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        {
+            // Delivery problems are recorded on the report, not raised: the
+            // report exists and can be resent.
+            if let Err(err) = self
+                .email_report(tenant_id, &report, &header, email, pdf)
+                .await
+            {
+                warn!(
+                    "Failed to email completion report {}: {}",
+                    report_number, err
+                );
+            }
+            return self.get(tenant_id, &id).await;
+        }
+        Ok(report)
+    }
+
+    /// Sends the PDF and records the outcome on the report.
+    async fn email_report(
+        &self,
+        tenant_id: &str,
+        report: &WorkOrderCompletionReport,
+        header: &ReportHeader,
+        to: &str,
+        pdf: Vec<u8>,
+    ) -> AppResult<()> {
+        let subject = format!(
+            "Work completion report {} - {}",
+            report.report_number, header.tenant_name
+        );
+        let body = format!(
+            "Dear {},\n\nThank you for confirming the completion of our work at {}. A copy of the signed completion report (berita acara) {} is attached for your records.\n\nRegards,\n{}",
+            or_dash(header.customer_name.as_deref()),
+            or_dash(header.location_label.as_deref()),
+            report.report_number,
+            header.tenant_name
+        );
+        let attachment = EmailAttachment {
+            filename: format!("berita-acara-{}.pdf", report.report_number),
+            content_type: "application/pdf".to_string(),
+            data: pdf,
+        };
+        let result = self
+            .email
+            .send_email_with_attachments_for_tenant(
+                Some(tenant_id),
+                to,
+                &subject,
+                &body,
+                &[attachment],
+            )
+            .await;
+
+        let now = Utc::now();
+        let (emailed_at, error) = match &result {
+            Ok(()) => (Some(now), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        sqlx::query(
+            r#"
+            UPDATE work_order_completion_reports
+            SET emailed_to = $1, emailed_at = COALESCE($2, emailed_at), email_error = $3
+            WHERE tenant_id = $4 AND id = $5
+            "#,
+        )
+        .bind(to)
+        .bind(emailed_at)
+        .bind(error)
+        .bind(tenant_id)
+        .bind(&report.id)
+        .execute(&self.pool)
+        .await?;
+        result
+    }
+
+    async fn get(&self, tenant_id: &str, id: &str) -> AppResult<WorkOrderCompletionReport> {
+        sqlx::query_as(&format!(
+            "{REPORT_SELECT} WHERE r.tenant_id = $1 AND r.id = $2"
+        ))
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Completion report not found".to_string()))
+    }
+
+    async fn work_order(
+        &self,
+        tenant_id: &str,
+        work_order_id: &str,
+    ) -> AppResult<InstallationWorkOrder> {
+        sqlx::query_as(
+            r#"
+            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, work_type, details, status, assigned_to, scheduled_at, scheduled_end_at, completed_at, notes, created_at, updated_at
+            FROM installation_work_orders
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Work order not found".to_string()))
+    }
+
+    async fn header(&self, tenant_id: &str, work_order_id: &str) -> AppResult<ReportHeader> {
+        let header: Option<ReportHeader> = sqlx::query_as(
+            r#"
+            SELECT t.name AS tenant_name,
+                   c.name AS customer_name, c.email AS customer_email, c.phone AS customer_phone,
+                   l.label AS location_label, l.address_line1, l.address_line2, l.city, l.state, l.postal_code,
+                   u.name AS technician_name, p.name AS package_name
+            FROM installation_work_orders wo
+            JOIN tenants t ON t.id = wo.tenant_id
+            LEFT JOIN customers c ON c.tenant_id = wo.tenant_id AND c.id = wo.customer_id
+            LEFT JOIN customer_locations l ON l.tenant_id = wo.tenant_id AND l.id = wo.location_id
+            LEFT JOIN users u ON u.id = wo.assigned_to
+            LEFT JOIN customer_subscriptions cs ON cs.tenant_id = wo.tenant_id AND cs.id = wo.subscription_id
+            LEFT JOIN isp_packages p ON p.tenant_id = wo.tenant_id AND p.id = cs.package_id
+            WHERE wo.tenant_id = $1 AND wo.id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        header.ok_or_else(|| AppError::NotFound("Work order not found".to_string()))
+    }
+
+    async fn read_file(&self, file_id: &str) -> AppResult<Vec<u8>> {
+        let (_, content) = self.storage.get_file_content(file_id).await?;
+        match content {
+            StorageContent::Local(path) => tokio::fs::read(&path)
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to read report file: {}", e))),
+            StorageContent::S3(stream) => stream
+                .collect()
+                .await
+                .map(|data| data.into_bytes().to_vec())
+                .map_err(|e| AppError::Internal(format!("Failed to read report file: {}", e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(strokes: Vec<Vec<[f64; 2]>>, image: Option<&str>) -> WorkOrderSignatureInput {
+        WorkOrderSignatureInput {
+            signer_name: "  Budi Santoso ".to_string(),
+            strokes,
+            image_base64: image.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn signature_needs_a_name_and_a_mark() {
+        let (name, sig) =
+            validate_signature(input(vec![vec![], vec![[0.0, 0.0], [5.0, 3.0]]], None)).unwrap();
+        assert_eq!(name, "Budi Santoso");
+        assert!(matches!(sig, CapturedSignature::Strokes(ref s) if s.len() == 1));
+
+        let mut unnamed = input(vec![vec![[0.0, 0.0], [1.0, 1.0]]], None);
+        unnamed.signer_name = " ".to_string();
+        assert!(validate_signature(unnamed).is_err());
+
+        // A single tap is not a signature.
+        assert!(validate_signature(input(vec![vec![[1.0, 1.0]]], None)).is_err());
+        assert!(validate_signature(input(vec![vec![[0.0, f64::NAN], [1.0, 1.0]]], None)).is_err());
+        assert!(validate_signature(input(vec![], None)).is_err());
+
+        // Images must be JPEG, with or without a data URL prefix.
+        let jpeg = general_purpose::STANDARD.encode([
+            0xFF, 0xD8, 0xFF, 0xC0, 0x00, 0x08, 0x08, 0x00, 0x10, 0x00, 0x20, 0x03, 0xFF, 0xD9,
+        ]);
+        let url = format!("data:image/jpeg;base64,{}", jpeg);
+        let (_, sig) = validate_signature(input(vec![], Some(&url))).unwrap();
+        assert_eq!(sig.kind(), "image");
+        let png = general_purpose::STANDARD.encode(b"\x89PNG\r\n\x1a\n0000");
+        assert!(validate_signature(input(vec![], Some(&png))).is_err());
+    }
+
+    #[test]
+    fn report_lists_job_details_and_signer() {
+        let now = Utc::now();
+        let work_order = InstallationWorkOrder {
+            id: "wo-1".to_string(),
+            tenant_id: "t1".to_string(),
+            subscription_id: "sub-1".to_string(),
+            invoice_id: None,
+            customer_id: "c1".to_string(),
+            location_id: "loc-1".to_string(),
+            router_id: None,
+            work_type: "installation".to_string(),
+            details: serde_json::json!({}),
+            status: "completed".to_string(),
+            assigned_to: None,
+            scheduled_at: None,
+            scheduled_end_at: None,
+            completed_at: Some(now),
+            notes: Some("ONT mounted (indoor)".to_string()),
+            created_at: now,
+            updated_at: now,
+        };
+        let header = ReportHeader {
+            tenant_name: "NetCo".to_string(),
+            customer_name: Some("Budi".to_string()),
+            ..Default::default()
+        };
+        let materials = vec![MaterialLine {
+            name: "Drop cable".to_string(),
+            unit: "m".to_string(),
+            quantity: 45.5,
+        }];
+        let signature = CapturedSignature::Strokes(vec![vec![[0.0, 0.0], [20.0, 10.0]]]);
+        let pdf = render_report(&ReportContent {
+            report_number: "BA-20260408-ABCD1234",
+            work_order: &work_order,
+            header: &header,
+            checklist: &[],
+            materials: &materials,
+            visits: &[],
+            signer_name: "Budi",
+            signature: &signature,
+            signed_at: now,
+            tz: chrono_tz::Asia::Jakarta,
+        })
+        .unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(text.starts_with("%PDF-"));
+        assert!(text.contains("(BA-20260408-ABCD1234)"));
+        assert!(text.contains("(45.5 m)"));
+        assert!(text.contains("(ONT mounted \\(indoor\\))"));
+        assert!(text.contains("(Budi)"));
+    }
+}
@@ -0,0 +1,59 @@
+//! Optimistic concurrency for update endpoints
+//!
+//! Editable records expose `updated_at`. A client that wants protection against
+//! lost updates echoes the value it last read back as `expected_updated_at`; if the
+//! row has moved on since then the write is refused with `AppError::Conflict` (409)
+//! and the client must reload before saving again.
+
+use crate::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+
+/// Check the caller's precondition against the row's current `updated_at`.
+///
+/// Timestamps are compared at microsecond precision, which is what Postgres
+/// stores. Callers that omit the precondition keep last-write-wins behaviour.
+pub fn ensure_unmodified(
+    expected: Option<DateTime<Utc>>,
+    current: DateTime<Utc>,
+    entity: &str,
+) -> AppResult<()> {
+    match expected {
+        Some(expected) if expected.timestamp_micros() != current.timestamp_micros() => {
+            Err(modified_conflict(entity))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Error returned when a guarded UPDATE matched no row because another writer
+/// got there first.
+pub fn modified_conflict(entity: &str) -> AppError {
+    AppError::Conflict(format!(
+        "This {} was modified by someone else. Reload and try again.",
+        entity
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_ensure_unmodified() {
+        let now = Utc::now();
+
+        assert!(ensure_unmodified(None, now, "customer").is_ok());
+        assert!(ensure_unmodified(Some(now), now, "customer").is_ok());
+
+        // Sub-microsecond noise (e.g. a round trip through Postgres) is not a conflict.
+        let truncated = DateTime::from_timestamp_micros(now.timestamp_micros()).unwrap();
+        assert!(ensure_unmodified(Some(truncated), now, "customer").is_ok());
+
+        let stale = now - Duration::seconds(5);
+        match ensure_unmodified(Some(stale), now, "customer") {
+            Err(AppError::Conflict(msg)) => assert!(msg.contains("customer")),
+            other => panic!("expected conflict, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! Five-field cron expressions (`minute hour day-of-month month day-of-week`)
+//!
+//! Supports `*`, lists (`1,15`), ranges (`1-5`), steps (`*/15`, `8-18/2`), month
+//! and weekday names (`jan`, `mon`) and the `@hourly`, `@daily`, `@weekly`,
+//! `@monthly` and `@yearly` shorthands. As in Vixie cron, when both day fields
+//! are restricted a day matches if either does.
+//!
+//! Times are evaluated in a timezone (the app timezone for backups). Local times
+//! skipped by a DST change never fire; repeated local times fire once.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+
+/// How far ahead `next_after` looks before giving up (e.g. `0 0 30 2 *`).
+const MAX_LOOKAHEAD_DAYS: i64 = 366 * 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u32,
+    days_of_month: u32,
+    months: u16,
+    days_of_week: u8,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+const MONTH_NAMES: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expr = expression.trim();
+        let expanded = match expr.to_ascii_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            "@weekly" => "0 0 * * 0".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@hourly" => "0 * * * *".to_string(),
+            other if other.starts_with('@') => {
+                return Err(format!("Unknown shorthand '{}'", expr));
+            }
+            _ => expr.to_string(),
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Expected 5 fields (minute hour day month weekday), got {}",
+                fields.len()
+            ));
+        }
+
+        let minutes = parse_field(fields[0], 0, 59, &[], "minute")?;
+        let hours = parse_field(fields[1], 0, 23, &[], "hour")?;
+        let days_of_month = parse_field(fields[2], 1, 31, &[], "day of month")?;
+        let months = parse_field(fields[3], 1, 12, MONTH_NAMES, "month")?;
+        // 7 is Sunday too.
+        let mut days_of_week = parse_field(fields[4], 0, 7, WEEKDAY_NAMES, "weekday")?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes,
+            hours: hours as u32,
+            days_of_month: days_of_month as u32,
+            months: months as u16,
+            days_of_week: days_of_week as u8,
+            dom_restricted: !fields[2].starts_with('*'),
+            dow_restricted: !fields[4].starts_with('*'),
+        })
+    }
+
+    /// First run strictly after `after`, or `None` if the expression never fires.
+    pub fn next_after(&self, after: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        let local = after.with_timezone(&tz);
+        let start_day = local.date_naive();
+        let start_time = (local.hour(), local.minute());
+
+        for offset in 0..MAX_LOOKAHEAD_DAYS {
+            let day = start_day.checked_add_signed(Duration::days(offset))?;
+            if !self.matches_day(day) {
+                continue;
+            }
+            for hour in (0..24).filter(|h| self.hours & (1 << h) != 0) {
+                for minute in (0..60).filter(|m| self.minutes & (1u64 << m) != 0) {
+                    if offset == 0 && (hour, minute) <= start_time {
+                        continue;
+                    }
+                    let Some(time) = NaiveTime::from_hms_opt(hour, minute, 0) else {
+                        continue;
+                    };
+                    let Some(at) = tz.from_local_datetime(&day.and_time(time)).earliest() else {
+                        continue; // Skipped by a DST change.
+                    };
+                    let at = at.with_timezone(&Utc);
+                    if at > after {
+                        return Some(at);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// The next `count` runs after `after`.
+    pub fn upcoming(&self, after: DateTime<Utc>, tz: Tz, count: usize) -> Vec<DateTime<Utc>> {
+        let mut runs = Vec::with_capacity(count);
+        let mut cursor = after;
+        while runs.len() < count {
+            let Some(next) = self.next_after(cursor, tz) else {
+                break;
+            };
+            runs.push(next);
+            cursor = next;
+        }
+        runs
+    }
+
+    fn matches_day(&self, day: NaiveDate) -> bool {
+        if self.months & (1 << day.month()) == 0 {
+            return false;
+        }
+        let dom = self.days_of_month & (1 << day.day()) != 0;
+        let dow = self.days_of_week & (1 << day.weekday().num_days_from_sunday()) != 0;
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+}
+
+/// Bitmask of the values a field allows.
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    label: &str,
+) -> Result<u64, String> {
+    let value = |raw: &str| -> Result<u32, String> {
+        let lower = raw.to_ascii_lowercase();
+        if let Some(i) = names.iter().position(|n| *n == lower) {
+            // Month names are 1-based, weekday names 0-based.
+            return Ok(i as u32 + min);
+        }
+        let v: u32 = raw
+            .parse()
+            .map_err(|_| format!("Invalid {} value '{}'", label, raw))?;
+        if v < min || v > max {
+            return Err(format!(
+                "{} value {} is out of range {}-{}",
+                label, v, min, max
+            ));
+        }
+        Ok(v)
+    };
+
+    let mut mask = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((r, s)) => {
+                let step: u32 = s
+                    .parse()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("Invalid {} step '{}'", label, s))?;
+                (r, step)
+            }
+            None => (item, 1),
+        };
+
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            let (a, b) = (value(a)?, value(b)?);
+            if a > b {
+                return Err(format!("Invalid {} range '{}'", label, range));
+            }
+            (a, b)
+        } else {
+            let v = value(range)?;
+            // `5/15` means "from 5, every 15".
+            (v, if item.contains('/') { max } else { v })
+        };
+
+        let mut v = lo;
+        while v <= hi {
+            mask |= 1u64 << v;
+            v += step;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_expressions() {
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("0 5-2 * * *").is_err());
+        assert!(CronSchedule::parse("0 0 * foo *").is_err());
+        assert!(CronSchedule::parse("@fortnightly").is_err());
+        assert!(CronSchedule::parse("0 2 * * mon-fri").is_ok());
+        assert!(CronSchedule::parse("@daily").is_ok());
+    }
+
+    #[test]
+    fn test_next_after_steps_and_lists() {
+        let s = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            s.upcoming(utc("2026-01-01T10:07:30Z"), Tz::UTC, 3),
+            vec![
+                utc("2026-01-01T10:15:00Z"),
+                utc("2026-01-01T10:30:00Z"),
+                utc("2026-01-01T10:45:00Z"),
+            ]
+        );
+
+        let s = CronSchedule::parse("0 8,20 * * *").unwrap();
+        assert_eq!(
+            s.next_after(utc("2026-01-01T20:00:00Z"), Tz::UTC),
+            Some(utc("2026-01-02T08:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_next_after_uses_timezone() {
+        // 02:00 in Jakarta (UTC+7) is 19:00 UTC the day before.
+        let s = CronSchedule::parse("0 2 * * *").unwrap();
+        assert_eq!(
+            s.next_after(utc("2026-01-01T12:00:00Z"), Tz::Asia__Jakarta),
+            Some(utc("2026-01-01T19:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_day_fields_or_when_both_restricted() {
+        // 2026-03-01 is a Sunday.
+        let s = CronSchedule::parse("0 0 10 * sun").unwrap();
+        assert_eq!(
+            s.upcoming(utc("2026-02-28T12:00:00Z"), Tz::UTC, 3),
+            vec![
+                utc("2026-03-01T00:00:00Z"),
+                utc("2026-03-08T00:00:00Z"),
+                utc("2026-03-10T00:00:00Z"),
+            ]
+        );
+
+        let weekdays = CronSchedule::parse("30 9 * * mon-fri").unwrap();
+        assert_eq!(
+            weekdays.next_after(utc("2026-03-06T10:00:00Z"), Tz::UTC),
+            Some(utc("2026-03-09T09:30:00Z"))
+        );
+        let sunday_as_7 = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(
+            sunday_as_7.next_after(utc("2026-03-02T00:00:00Z"), Tz::UTC),
+            Some(utc("2026-03-08T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_dst_gap_is_skipped() {
+        // 02:30 does not exist in New York on 2026-03-08.
+        let s = CronSchedule::parse("30 2 * * *").unwrap();
+        assert_eq!(
+            s.next_after(utc("2026-03-07T12:00:00Z"), Tz::America__New_York),
+            Some(utc("2026-03-09T06:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_impossible_date_never_fires() {
+        let s = CronSchedule::parse("0 0 30 2 *").unwrap();
+        assert_eq!(s.next_after(utc("2026-01-01T00:00:00Z"), Tz::UTC), None);
+    }
+}
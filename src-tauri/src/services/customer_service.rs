@@ -1,21 +1,31 @@
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    AddCustomerPortalUserRequest, CreateCustomerLocationRequest, CreateCustomerPortalUserRequest,
+    AddCustomerPortalUserRequest, AssignInstallationWorkOrderRequest,
+    CreateCustomerLocationRequest, CreateCustomerPortalUserRequest,
     CreateCustomerRegistrationInviteRequest, CreateCustomerRequest,
     CreateCustomerSubscriptionRequest, CreateCustomerWithPortalRequest,
-    CreateMyCustomerLocationRequest, Customer, CustomerLocation, CustomerPortalSubscriptionStats,
-    CustomerPortalUser, CustomerRegistrationInviteCreateResponse, CustomerRegistrationInvitePolicy,
-    CustomerRegistrationInviteSummary, CustomerRegistrationInviteValidationView,
-    CustomerRegistrationInviteView, CustomerSubscription, CustomerSubscriptionView, CustomerUser,
+    CreateMyCustomerLocationRequest, CreateWorkOrderRequest, Customer, CustomerLocation,
+    CustomerPortalSubscriptionStats, CustomerPortalUser, CustomerRegistrationInviteCreateResponse,
+    CustomerRegistrationInvitePolicy, CustomerRegistrationInviteSummary,
+    CustomerRegistrationInviteValidationView, CustomerRegistrationInviteView, CustomerSubscription,
+    CustomerSubscriptionComponent, CustomerSubscriptionView, CustomerTagSummary, CustomerUser,
     InstallationWorkOrder, InstallationWorkOrderView, IspPackage, PaginatedResponse,
-    PortalCheckoutSubscriptionRequest, TeamMemberWithUser, UpdateCustomerLocationRequest,
-    UpdateCustomerRegistrationInvitePolicyRequest, UpdateCustomerRequest,
-    UpdateCustomerSubscriptionRequest, WorkOrderRescheduleDecisionRequest,
-    WorkOrderRescheduleRequestView,
+    PortalCheckoutSubscriptionRequest, SetCustomerTagsRequest, TeamMemberWithUser, TrashItem,
+    UpdateCustomerLocationRequest, UpdateCustomerRegistrationInvitePolicyRequest,
+    UpdateCustomerRequest, UpdateCustomerSubscriptionRequest, UpdateSubscriptionComponentRequest,
+    WorkOrderAgendaItem, WorkOrderCheckRequest, WorkOrderRescheduleDecisionRequest,
+    WorkOrderRescheduleRequestView, WorkOrderRoutePlan, WorkOrderRouteSkipped, WorkOrderRouteStop,
+    WorkOrderVisit,
 };
 use crate::security::secret::encrypt_secret_for;
-use crate::services::{AuditService, AuthService, NotificationService, PppoeService, UserService};
+use crate::services::event_outbox_service::{self, DomainEvent, EventOutboxService};
+use crate::services::leader_lock::LeaderLock;
+use crate::services::{
+    announcement_audience, concurrency, AuditChange, AuditService, AuthService,
+    NotificationService, PppoeService, UsageMetric, UsageService, UserService,
+    WorkOrderChecklistService,
+};
 use chrono::{DateTime, Duration, Utc};
 use sha2::{Digest, Sha256};
 use std::collections::HashSet;
@@ -34,6 +44,185 @@ const INSTALLATION_SLA_REMINDER_COOLDOWN_MINUTES_KEY: &str =
     "installation_sla_reminder_cooldown_minutes";
 const INSTALLATION_SLA_SCHEDULER_INTERVAL_MINUTES_KEY: &str =
     "installation_sla_scheduler_interval_minutes";
+const WORK_ORDER_SLOT_MINUTES_KEY: &str = "work_order_slot_minutes";
+const WORK_ORDER_AUTO_DISMANTLE_KEY: &str = "work_order_auto_dismantle_on_cancel";
+const MAX_WORK_ORDER_SLOT_MINUTES: i64 = 24 * 60;
+const MAX_AGENDA_RANGE_DAYS: i64 = 62;
+const MAX_ROUTE_STOPS: usize = 60;
+const MAX_ROUTE_IMPROVEMENT_PASSES: usize = 50;
+const MAX_OFFLINE_VISIT_AGE_HOURS: i64 = 72;
+const MAX_DEVICE_CLOCK_SKEW_SECS: i64 = 120;
+
+const MAX_CUSTOMER_TAGS: usize = 20;
+const MAX_CUSTOMER_TAG_LEN: usize = 40;
+
+fn normalize_customer_tags(tags: Vec<String>) -> AppResult<Vec<String>> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = announcement_audience::normalize_tag(&tag);
+        if tag.is_empty() || out.contains(&tag) {
+            continue;
+        }
+        if tag.chars().count() > MAX_CUSTOMER_TAG_LEN {
+            return Err(AppError::Validation(format!(
+                "Tags can be at most {} characters",
+                MAX_CUSTOMER_TAG_LEN
+            )));
+        }
+        out.push(tag);
+    }
+    if out.len() > MAX_CUSTOMER_TAGS {
+        return Err(AppError::Validation(format!(
+            "A customer can have at most {} tags",
+            MAX_CUSTOMER_TAGS
+        )));
+    }
+    out.sort();
+    Ok(out)
+}
+
+/// End of a work order's time slot; slots without a (valid) end use the default length.
+fn work_order_slot_end(
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    default_minutes: i64,
+) -> DateTime<Utc> {
+    end.filter(|e| *e > start)
+        .unwrap_or_else(|| start + Duration::minutes(default_minutes))
+}
+
+/// Where a slot ends after its start moves: an explicit end keeps the slot's length.
+fn moved_slot_end(
+    old_start: Option<DateTime<Utc>>,
+    old_end: Option<DateTime<Utc>>,
+    new_start: Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    match (old_start, old_end, new_start) {
+        (Some(s), Some(e), Some(ns)) if e > s => Some(ns + (e - s)),
+        _ => None,
+    }
+}
+
+fn slots_overlap(a: (DateTime<Utc>, DateTime<Utc>), b: (DateTime<Utc>, DateTime<Utc>)) -> bool {
+    a.0 < b.1 && b.0 < a.1
+}
+
+/// Flag agenda items whose technician is double-booked. Only pending and
+/// in-progress work orders count; finished jobs don't block the calendar.
+fn mark_agenda_conflicts(items: &mut [WorkOrderAgendaItem]) {
+    let active = |i: &WorkOrderAgendaItem| i.status == "pending" || i.status == "in_progress";
+    for a in 0..items.len() {
+        for b in (a + 1)..items.len() {
+            let (x, y) = (&items[a], &items[b]);
+            if x.assigned_to.is_some()
+                && x.assigned_to == y.assigned_to
+                && active(x)
+                && active(y)
+                && slots_overlap(
+                    (x.scheduled_at, x.scheduled_end_at),
+                    (y.scheduled_at, y.scheduled_end_at),
+                )
+            {
+                items[a].has_conflict = true;
+                items[b].has_conflict = true;
+            }
+        }
+    }
+}
+
+/// Status moves along the technician path for a work order of `work_type`.
+/// Cancel and reopen are admin actions shared by every type.
+fn work_order_transition_allowed(work_type: &str, from: &str, to: &str) -> bool {
+    match (from, to) {
+        ("pending", "in_progress") | ("in_progress", "completed") => true,
+        // Repairs can wait for parts or access; resuming goes through start.
+        ("in_progress", "on_hold") | ("on_hold", "in_progress") => work_type == "repair",
+        _ => false,
+    }
+}
+
+/// Cancelling a subscription that was installed leaves equipment on site.
+fn needs_dismantle(previous_status: &str, new_status: &str) -> bool {
+    new_status == "cancelled" && matches!(previous_status, "active" | "suspended")
+}
+
+/// A customer goes to the trash only once nothing is provisioned or billed for
+/// it any more: the trash purge deletes the row later without deprovisioning.
+fn ensure_trashable(live_subscriptions: i64, enabled_pppoe_accounts: i64) -> AppResult<()> {
+    if live_subscriptions > 0 {
+        return Err(AppError::Conflict(format!(
+            "Customer has {} active subscription(s); suspend or cancel them before deleting",
+            live_subscriptions
+        )));
+    }
+    if enabled_pppoe_accounts > 0 {
+        return Err(AppError::Conflict(format!(
+            "Customer has {} enabled PPPoE account(s); disable them before deleting",
+            enabled_pppoe_accounts
+        )));
+    }
+    Ok(())
+}
+
+/// Normalised type and `details` for a manually created work order.
+fn work_order_details(dto: &CreateWorkOrderRequest) -> AppResult<(String, serde_json::Value)> {
+    let text = |v: &Option<String>| {
+        v.as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let (problem, ticket_id) = (text(&dto.problem), text(&dto.support_ticket_id));
+    let (target_location_id, reason) = (text(&dto.target_location_id), text(&dto.reason));
+    let work_type = dto.work_type.trim().to_lowercase();
+    let misplaced = |extra: bool| {
+        if extra {
+            Err(AppError::Validation(format!(
+                "Field not used by {} work orders",
+                work_type
+            )))
+        } else {
+            Ok(())
+        }
+    };
+
+    let details = match work_type.as_str() {
+        "repair" => {
+            misplaced(target_location_id.is_some() || reason.is_some())?;
+            let problem = problem.ok_or_else(|| {
+                AppError::Validation("Describe the problem for a repair work order".to_string())
+            })?;
+            if problem.chars().count() > 2000 {
+                return Err(AppError::Validation(
+                    "Problem description is too long (max 2000 characters)".to_string(),
+                ));
+            }
+            serde_json::json!({ "problem": problem, "support_ticket_id": ticket_id })
+        }
+        "relocation" => {
+            misplaced(problem.is_some() || ticket_id.is_some() || reason.is_some())?;
+            let target = target_location_id.ok_or_else(|| {
+                AppError::Validation("Choose the new location for a relocation".to_string())
+            })?;
+            serde_json::json!({ "target_location_id": target })
+        }
+        "dismantle" => {
+            misplaced(problem.is_some() || ticket_id.is_some() || target_location_id.is_some())?;
+            serde_json::json!({ "reason": reason })
+        }
+        "installation" => {
+            return Err(AppError::Validation(
+                "Installation work orders are created from customer orders".to_string(),
+            ))
+        }
+        _ => {
+            return Err(AppError::Validation(
+                "work_type must be repair, relocation, or dismantle".to_string(),
+            ))
+        }
+    };
+    Ok((work_type, details))
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum InstallationSlaBreachType {
@@ -52,6 +241,143 @@ struct OverdueInstallationReminderRow {
     package_name: Option<String>,
 }
 
+/// Great-circle distance in metres between two `(latitude, longitude)` points.
+fn haversine_m(a: (f64, f64), b: (f64, f64)) -> f64 {
+    const EARTH_RADIUS_M: f64 = 6_371_000.0;
+    let (lat1, lat2) = (a.0.to_radians(), b.0.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lng = (b.1 - a.1).to_radians();
+    let h = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * h.sqrt().min(1.0).asin()
+}
+
+fn validate_gps_fix(dto: &WorkOrderCheckRequest) -> AppResult<()> {
+    if !(-90.0..=90.0).contains(&dto.latitude) || !(-180.0..=180.0).contains(&dto.longitude) {
+        return Err(AppError::Validation(
+            "Latitude must be within -90..90 and longitude within -180..180".to_string(),
+        ));
+    }
+    if dto.accuracy_m.is_some_and(|a| !a.is_finite() || a < 0.0) {
+        return Err(AppError::Validation(
+            "GPS accuracy must be a non-negative number of metres".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// When a visit fix was taken. Devices syncing after working offline send
+/// their own timestamp; it may be slightly ahead of the server clock but not
+/// older than the offline window.
+fn visit_fix_time(recorded_at: Option<&str>, now: DateTime<Utc>) -> AppResult<DateTime<Utc>> {
+    let Some(raw) = recorded_at.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(now);
+    };
+    let at = DateTime::parse_from_rfc3339(raw)
+        .map_err(|_| AppError::Validation("recorded_at must be an RFC3339 timestamp".to_string()))?
+        .with_timezone(&Utc);
+    if at > now + Duration::seconds(MAX_DEVICE_CLOCK_SKEW_SECS) {
+        return Err(AppError::Validation(
+            "recorded_at cannot be in the future".to_string(),
+        ));
+    }
+    if at < now - Duration::hours(MAX_OFFLINE_VISIT_AGE_HOURS) {
+        return Err(AppError::Validation(format!(
+            "recorded_at is more than {} hours old",
+            MAX_OFFLINE_VISIT_AGE_HOURS
+        )));
+    }
+    Ok(at.min(now))
+}
+
+/// Length of an open path visiting `points` in `order`, starting from `start`
+/// when given.
+fn route_length_m(start: Option<(f64, f64)>, points: &[(f64, f64)], order: &[usize]) -> f64 {
+    let mut prev = start;
+    let mut total = 0.0;
+    for &i in order {
+        if let Some(p) = prev {
+            total += haversine_m(p, points[i]);
+        }
+        prev = Some(points[i]);
+    }
+    total
+}
+
+/// Visit order (indices into `points`) for a day's route: nearest neighbour
+/// from `start` (or from the first point), then 2-opt until no segment
+/// reversal shortens the path.
+fn plan_visit_order(start: Option<(f64, f64)>, points: &[(f64, f64)]) -> Vec<usize> {
+    let n = points.len();
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut order = Vec::with_capacity(n);
+    let mut here = start;
+    while !remaining.is_empty() {
+        let pos = match here {
+            Some(h) => remaining
+                .iter()
+                .enumerate()
+                .min_by(|a, b| {
+                    haversine_m(h, points[*a.1]).total_cmp(&haversine_m(h, points[*b.1]))
+                })
+                .map(|(pos, _)| pos)
+                .unwrap_or(0),
+            None => 0,
+        };
+        let next = remaining.remove(pos);
+        here = Some(points[next]);
+        order.push(next);
+    }
+
+    let mut best = route_length_m(start, points, &order);
+    for _ in 0..MAX_ROUTE_IMPROVEMENT_PASSES {
+        let mut improved = false;
+        for i in 0..n.saturating_sub(1) {
+            for j in (i + 1)..n {
+                order[i..=j].reverse();
+                let len = route_length_m(start, points, &order);
+                if len + 1e-6 < best {
+                    best = len;
+                    improved = true;
+                } else {
+                    order[i..=j].reverse();
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    order
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct RouteRow {
+    work_order_id: String,
+    status: String,
+    scheduled_at: DateTime<Utc>,
+    customer_id: String,
+    customer_name: Option<String>,
+    location_label: Option<String>,
+    address_line1: Option<String>,
+    city: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct AgendaRow {
+    work_order_id: String,
+    status: String,
+    assigned_to: Option<String>,
+    assigned_to_name: Option<String>,
+    scheduled_at: DateTime<Utc>,
+    scheduled_end_at: Option<DateTime<Utc>>,
+    customer_id: String,
+    customer_name: Option<String>,
+    location_label: Option<String>,
+    package_name: Option<String>,
+}
+
 #[derive(sqlx::FromRow)]
 struct InviteSummaryRow {
     total: i64,
@@ -73,6 +399,8 @@ pub struct CustomerService {
     notification_service: NotificationService,
     pppoe_service: PppoeService,
     user_service: UserService,
+    checklists: WorkOrderChecklistService,
+    usage: UsageService,
 }
 
 impl CustomerService {
@@ -84,7 +412,7 @@ impl CustomerService {
         #[cfg(feature = "postgres")]
         let row: Option<InstallationWorkOrder> = sqlx::query_as(
             r#"
-            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, status, assigned_to, scheduled_at, completed_at, notes, created_at, updated_at
+            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, work_type, details, status, assigned_to, scheduled_at, scheduled_end_at, completed_at, notes, created_at, updated_at
             FROM installation_work_orders
             WHERE tenant_id = $1 AND id = $2
             LIMIT 1
@@ -98,7 +426,7 @@ impl CustomerService {
         #[cfg(feature = "sqlite")]
         let row: Option<InstallationWorkOrder> = sqlx::query_as(
             r#"
-            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, status, assigned_to, scheduled_at, completed_at, notes, created_at, updated_at
+            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, work_type, details, status, assigned_to, scheduled_at, scheduled_end_at, completed_at, notes, created_at, updated_at
             FROM installation_work_orders
             WHERE tenant_id = ? AND id = ?
             LIMIT 1
@@ -112,7 +440,11 @@ impl CustomerService {
         row.ok_or_else(|| AppError::NotFound("Work order not found".to_string()))
     }
 
-    async fn is_actor_admin_or_owner(&self, tenant_id: &str, actor_id: &str) -> AppResult<bool> {
+    pub(crate) async fn is_actor_admin_or_owner(
+        &self,
+        tenant_id: &str,
+        actor_id: &str,
+    ) -> AppResult<bool> {
         #[cfg(feature = "postgres")]
         let role_name: Option<String> = sqlx::query_scalar(
             r#"
@@ -356,6 +688,8 @@ impl CustomerService {
         user_service: UserService,
     ) -> Self {
         Self {
+            checklists: WorkOrderChecklistService::new(pool.clone()),
+            usage: UsageService::new(pool.clone()),
             pool,
             auth_service,
             audit_service,
@@ -367,11 +701,14 @@ impl CustomerService {
 
     pub fn start_installation_sla_scheduler(&self) {
         let svc = self.clone();
+        let leader = LeaderLock::new(self.pool.clone(), "installation_sla_reminders");
         tokio::spawn(async move {
             tracing::info!("Installation SLA reminder scheduler started.");
             loop {
-                if let Err(err) = svc.run_installation_sla_reminders_for_all_tenants().await {
-                    tracing::warn!("installation SLA reminder scheduler failed: {}", err);
+                if leader.acquire().await {
+                    if let Err(err) = svc.run_installation_sla_reminders_for_all_tenants().await {
+                        tracing::warn!("installation SLA reminder scheduler failed: {}", err);
+                    }
                 }
                 let interval_minutes = svc
                     .resolve_installation_sla_scheduler_interval_minutes()
@@ -527,9 +864,9 @@ impl CustomerService {
     fn normalize_work_order_status(v: &str) -> AppResult<String> {
         let x = v.trim().to_lowercase();
         match x.as_str() {
-            "pending" | "in_progress" | "completed" | "cancelled" => Ok(x),
+            "pending" | "in_progress" | "on_hold" | "completed" | "cancelled" => Ok(x),
             _ => Err(AppError::Validation(
-                "status must be pending, in_progress, completed, or cancelled".to_string(),
+                "status must be pending, in_progress, on_hold, completed, or cancelled".to_string(),
             )),
         }
     }
@@ -933,6 +1270,9 @@ impl CustomerService {
         )
     }
 
+    /// Provisions the PPPoE draft of a subscription. For a bundle the draft
+    /// uses the bundle's internet package and static IP, and the outcome is
+    /// recorded on the internet component.
     async fn auto_provision_pppoe_for_subscription(
         &self,
         actor_id: &str,
@@ -940,14 +1280,232 @@ impl CustomerService {
         sub: &CustomerSubscription,
         ip_address: Option<&str>,
     ) -> AppResult<()> {
+        let Some(internet_package_id) = self
+            .bundle_internet_package(tenant_id, &sub.package_id)
+            .await?
+        else {
+            return self
+                .provision_pppoe_draft(actor_id, tenant_id, sub, &sub.package_id, None, ip_address)
+                .await
+                .map(|_| ());
+        };
+
+        let static_ip = self.subscription_static_ip(tenant_id, &sub.id).await?;
+        let outcome = self
+            .provision_pppoe_draft(
+                actor_id,
+                tenant_id,
+                sub,
+                &internet_package_id,
+                static_ip.as_deref(),
+                ip_address,
+            )
+            .await;
+        let (status, error) = match &outcome {
+            Ok(false) => return Ok(()),
+            Ok(true) => ("provisioned", None),
+            Err(e) => ("failed", Some(e.to_string())),
+        };
+        self.set_subscription_component_status(
+            tenant_id,
+            &sub.id,
+            &internet_package_id,
+            status,
+            error.as_deref(),
+        )
+        .await?;
+        outcome.map(|_| ())
+    }
+
+    /// The internet package of a bundle; `None` for any other package.
+    async fn bundle_internet_package(
+        &self,
+        tenant_id: &str,
+        package_id: &str,
+    ) -> AppResult<Option<String>> {
+        #[cfg(feature = "postgres")]
+        let internet: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT c.component_id
+            FROM isp_package_components c
+            JOIN isp_packages p ON p.id = c.component_id
+            WHERE c.tenant_id = $1 AND c.bundle_id = $2 AND p.service_type = 'internet_pppoe'
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(package_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let internet: Option<String> = {
+            let _ = (tenant_id, package_id);
+            None
+        };
+
+        Ok(internet)
+    }
+
+    /// Address of a provisioned static IP component, if the subscription has one.
+    async fn subscription_static_ip(
+        &self,
+        tenant_id: &str,
+        subscription_id: &str,
+    ) -> AppResult<Option<String>> {
+        #[cfg(feature = "postgres")]
+        let address: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT detail FROM customer_subscription_components
+            WHERE tenant_id = $1
+              AND subscription_id = $2
+              AND service_type = 'static_ip'
+              AND status = 'provisioned'
+              AND detail IS NOT NULL
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(subscription_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let address: Option<String> = {
+            let _ = (tenant_id, subscription_id);
+            None
+        };
+
+        Ok(address)
+    }
+
+    async fn set_subscription_component_status(
+        &self,
+        tenant_id: &str,
+        subscription_id: &str,
+        component_package_id: &str,
+        status: &str,
+        error: Option<&str>,
+    ) -> AppResult<()> {
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            r#"
+            UPDATE customer_subscription_components
+            SET status = $1,
+                last_error = $2,
+                provisioned_at = CASE WHEN $1 = 'provisioned' THEN $3 ELSE provisioned_at END,
+                updated_at = $3
+            WHERE tenant_id = $4 AND subscription_id = $5 AND component_package_id = $6
+            "#,
+        )
+        .bind(status)
+        .bind(error)
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .bind(subscription_id)
+        .bind(component_package_id)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let _ = (
+            tenant_id,
+            subscription_id,
+            component_package_id,
+            status,
+            error,
+        );
+
+        Ok(())
+    }
+
+    /// Adds a pending row per component of the subscription's bundle and drops
+    /// rows of components it no longer has. Non-bundle subscriptions end up
+    /// with none.
+    async fn sync_subscription_components(
+        &self,
+        tenant_id: &str,
+        sub: &CustomerSubscription,
+    ) -> AppResult<()> {
+        #[cfg(feature = "postgres")]
+        {
+            sqlx::query(
+                r#"
+                DELETE FROM customer_subscription_components s
+                WHERE s.tenant_id = $1
+                  AND s.subscription_id = $2
+                  AND NOT EXISTS (
+                    SELECT 1 FROM isp_package_components c
+                    WHERE c.bundle_id = $3 AND c.component_id = s.component_package_id
+                  )
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(&sub.id)
+            .bind(&sub.package_id)
+            .execute(&self.pool)
+            .await?;
+
+            let components: Vec<(String, String)> = sqlx::query_as(
+                r#"
+                SELECT c.component_id, p.service_type
+                FROM isp_package_components c
+                JOIN isp_packages p ON p.id = c.component_id
+                WHERE c.tenant_id = $1 AND c.bundle_id = $2
+                ORDER BY c.sort_order
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(&sub.package_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+            let now = Utc::now();
+            for (component_id, service_type) in components {
+                sqlx::query(
+                    r#"
+                    INSERT INTO customer_subscription_components
+                      (id, tenant_id, subscription_id, component_package_id, service_type, status, created_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, 'pending', $6, $6)
+                    ON CONFLICT (subscription_id, component_package_id) DO NOTHING
+                    "#,
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(tenant_id)
+                .bind(&sub.id)
+                .bind(&component_id)
+                .bind(&service_type)
+                .bind(now)
+                .execute(&self.pool)
+                .await?;
+            }
+        }
+
+        #[cfg(feature = "sqlite")]
+        let _ = (tenant_id, sub);
+
+        Ok(())
+    }
+
+    /// Writes the PPPoE draft of an active subscription on `package_id`.
+    /// Returns `false` when there is nothing to provision yet.
+    async fn provision_pppoe_draft(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        sub: &CustomerSubscription,
+        package_id: &str,
+        remote_address: Option<&str>,
+        ip_address: Option<&str>,
+    ) -> AppResult<bool> {
         if sub.status != "active" {
-            return Ok(());
+            return Ok(false);
         }
         let Some(router_id) = sub.router_id.as_deref() else {
-            return Ok(());
+            return Ok(false);
         };
         if router_id.trim().is_empty() {
-            return Ok(());
+            return Ok(false);
         }
 
         #[derive(sqlx::FromRow)]
@@ -967,7 +1525,7 @@ impl CustomerService {
         )
         .bind(tenant_id)
         .bind(router_id)
-        .bind(&sub.package_id)
+        .bind(package_id)
         .fetch_optional(&self.pool)
         .await?;
 
@@ -982,7 +1540,7 @@ impl CustomerService {
         )
         .bind(tenant_id)
         .bind(router_id)
-        .bind(&sub.package_id)
+        .bind(package_id)
         .fetch_optional(&self.pool)
         .await?;
 
@@ -1116,17 +1674,18 @@ impl CustomerService {
                 SET username = $1,
                     package_id = $2,
                     router_profile_name = $3,
-                    remote_address = NULL,
-                    address_pool = $4,
+                    remote_address = $4,
+                    address_pool = $5,
                     disabled = true,
-                    comment = $5,
-                    updated_at = $6
-                WHERE tenant_id = $7 AND id = $8
+                    comment = $6,
+                    updated_at = $7
+                WHERE tenant_id = $8 AND id = $9
                 "#,
             )
             .bind(&username)
-            .bind(&sub.package_id)
+            .bind(package_id)
             .bind(&mapping.router_profile_name)
+            .bind(remote_address)
             .bind(&mapping.address_pool)
             .bind(&note)
             .bind(now)
@@ -1142,7 +1701,7 @@ impl CustomerService {
                 SET username = ?,
                     package_id = ?,
                     router_profile_name = ?,
-                    remote_address = NULL,
+                    remote_address = ?,
                     address_pool = ?,
                     disabled = 1,
                     comment = ?,
@@ -1151,8 +1710,9 @@ impl CustomerService {
                 "#,
             )
             .bind(&username)
-            .bind(&sub.package_id)
+            .bind(package_id)
             .bind(&mapping.router_profile_name)
+            .bind(remote_address)
             .bind(&mapping.address_pool)
             .bind(&note)
             .bind(now)
@@ -1173,7 +1733,7 @@ impl CustomerService {
                   (id, tenant_id, router_id, customer_id, location_id, username, password_enc, package_id, profile_id, router_profile_name,
                    remote_address, address_pool, disabled, comment, router_present, router_secret_id, last_sync_at, last_error, created_at, updated_at)
                 VALUES
-                  ($1,$2,$3,$4,$5,$6,$7,$8,NULL,$9,NULL,$10,true,$11,false,NULL,NULL,NULL,$12,$13)
+                  ($1,$2,$3,$4,$5,$6,$7,$8,NULL,$9,$10,$11,true,$12,false,NULL,NULL,NULL,$13,$14)
                 "#,
             )
             .bind(&id)
@@ -1183,8 +1743,9 @@ impl CustomerService {
             .bind(&sub.location_id)
             .bind(&username)
             .bind(&password_enc)
-            .bind(&sub.package_id)
+            .bind(package_id)
             .bind(&mapping.router_profile_name)
+            .bind(remote_address)
             .bind(&mapping.address_pool)
             .bind(&note)
             .bind(now)
@@ -1199,7 +1760,7 @@ impl CustomerService {
                   (id, tenant_id, router_id, customer_id, location_id, username, password_enc, package_id, profile_id, router_profile_name,
                    remote_address, address_pool, disabled, comment, router_present, router_secret_id, last_sync_at, last_error, created_at, updated_at)
                 VALUES
-                  (?,?,?,?,?,?,?,?,NULL,?,NULL,?,1,?,0,NULL,NULL,NULL,?,?)
+                  (?,?,?,?,?,?,?,?,NULL,?,?,?,1,?,0,NULL,NULL,NULL,?,?)
                 "#,
             )
             .bind(&id)
@@ -1209,8 +1770,9 @@ impl CustomerService {
             .bind(&sub.location_id)
             .bind(&username)
             .bind(&password_enc)
-            .bind(&sub.package_id)
+            .bind(package_id)
             .bind(&mapping.router_profile_name)
+            .bind(remote_address)
             .bind(&mapping.address_pool)
             .bind(&note)
             .bind(now)
@@ -1231,7 +1793,7 @@ impl CustomerService {
             )
             .await;
 
-        Ok(())
+        Ok(true)
     }
 
     // =========================
@@ -1260,6 +1822,7 @@ impl CustomerService {
                 COUNT(*) OVER() AS total_count
             FROM customers c
             WHERE c.tenant_id = $1
+              AND c.deleted_at IS NULL
               AND ($2 = '' OR c.name ILIKE '%' || $2 || '%' OR c.email ILIKE '%' || $2 || '%')
             ORDER BY c.created_at DESC
             LIMIT $3 OFFSET $4
@@ -1269,9 +1832,10 @@ impl CustomerService {
         let query = r#"
             SELECT
                 c.*,
-                (SELECT COUNT(*) FROM customers cc WHERE cc.tenant_id = ? AND (? = '' OR cc.name LIKE '%' || ? || '%' OR cc.email LIKE '%' || ? || '%')) AS total_count
+                (SELECT COUNT(*) FROM customers cc WHERE cc.tenant_id = ? AND cc.deleted_at IS NULL AND (? = '' OR cc.name LIKE '%' || ? || '%' OR cc.email LIKE '%' || ? || '%')) AS total_count
             FROM customers c
             WHERE c.tenant_id = ?
+              AND c.deleted_at IS NULL
               AND (? = '' OR c.name LIKE '%' || ? || '%' OR c.email LIKE '%' || ? || '%')
             ORDER BY c.created_at DESC
             LIMIT ? OFFSET ?
@@ -1328,20 +1892,22 @@ impl CustomerService {
             .await?;
 
         #[cfg(feature = "postgres")]
-        let customer: Option<Customer> =
-            sqlx::query_as("SELECT * FROM customers WHERE tenant_id = $1 AND id = $2")
-                .bind(tenant_id)
-                .bind(customer_id)
-                .fetch_optional(&self.pool)
-                .await?;
+        let customer: Option<Customer> = sqlx::query_as(
+            "SELECT * FROM customers WHERE tenant_id = $1 AND id = $2 AND deleted_at IS NULL",
+        )
+        .bind(tenant_id)
+        .bind(customer_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
         #[cfg(feature = "sqlite")]
-        let customer: Option<Customer> =
-            sqlx::query_as("SELECT * FROM customers WHERE tenant_id = ? AND id = ?")
-                .bind(tenant_id)
-                .bind(customer_id)
-                .fetch_optional(&self.pool)
-                .await?;
+        let customer: Option<Customer> = sqlx::query_as(
+            "SELECT * FROM customers WHERE tenant_id = ? AND id = ? AND deleted_at IS NULL",
+        )
+        .bind(tenant_id)
+        .bind(customer_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
         customer.ok_or_else(|| AppError::NotFound("Customer not found".to_string()))
     }
@@ -1356,6 +1922,9 @@ impl CustomerService {
         self.auth_service
             .check_permission(actor_id, tenant_id, "customers", "manage")
             .await?;
+        self.usage
+            .enforce(tenant_id, UsageMetric::Customers, 1)
+            .await?;
 
         let customer = Customer::new(
             tenant_id.to_string(),
@@ -1366,6 +1935,8 @@ impl CustomerService {
             dto.is_active,
         );
 
+        let mut tx = self.pool.begin().await?;
+
         #[cfg(feature = "postgres")]
         sqlx::query(
             r#"
@@ -1384,7 +1955,7 @@ impl CustomerService {
         .bind(customer.is_active)
         .bind(customer.created_at)
         .bind(customer.updated_at)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
         #[cfg(feature = "sqlite")]
@@ -1405,11 +1976,20 @@ impl CustomerService {
         .bind(customer.is_active)
         .bind(customer.created_at.to_rfc3339())
         .bind(customer.updated_at.to_rfc3339())
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        self.audit_service
-            .log(
+        EventOutboxService::stage(
+            &mut tx,
+            Some(tenant_id),
+            &DomainEvent::customer_created(&customer),
+        )
+        .await?;
+        tx.commit().await?;
+        event_outbox_service::wake();
+
+        self.audit_service
+            .log(
                 Some(actor_id),
                 Some(tenant_id),
                 "CUSTOMER_CREATE",
@@ -1434,6 +2014,10 @@ impl CustomerService {
             .check_permission(actor_id, tenant_id, "customers", "manage")
             .await?;
 
+        self.usage
+            .enforce(tenant_id, UsageMetric::Customers, 1)
+            .await?;
+
         let portal_email = dto.portal_email.trim().to_lowercase();
         if portal_email.is_empty() {
             return Err(AppError::Validation("portal_email is required".to_string()));
@@ -1621,7 +2205,14 @@ impl CustomerService {
             .await?;
         }
 
+        EventOutboxService::stage(
+            &mut tx,
+            Some(tenant_id),
+            &DomainEvent::customer_created(&customer),
+        )
+        .await?;
         tx.commit().await?;
+        event_outbox_service::wake();
 
         self.audit_service
             .log(
@@ -1703,6 +2294,9 @@ impl CustomerService {
         if let Some(existing) = existing_customer {
             return Ok(existing);
         }
+        self.usage
+            .enforce(tenant_id, UsageMetric::Customers, 1)
+            .await?;
 
         let customer = Customer::new(
             tenant_id.to_string(),
@@ -1834,7 +2428,14 @@ impl CustomerService {
             }
         }
 
+        EventOutboxService::stage(
+            &mut tx,
+            Some(tenant_id),
+            &DomainEvent::customer_created(&customer),
+        )
+        .await?;
         tx.commit().await?;
+        event_outbox_service::wake();
 
         self.audit_service
             .log(
@@ -2496,6 +3097,12 @@ impl CustomerService {
             .await?;
 
         let mut customer = self.get_customer(actor_id, tenant_id, customer_id).await?;
+        concurrency::ensure_unmodified(dto.expected_updated_at, customer.updated_at, "customer")?;
+        let before = customer.clone();
+        // SQLite is single-writer (desktop), so only Postgres needs the row guard below.
+        #[cfg(feature = "postgres")]
+        let previous_updated_at = customer.updated_at;
+
         if let Some(name) = dto.name {
             customer.name = name;
         }
@@ -2516,12 +3123,13 @@ impl CustomerService {
         }
         customer.updated_at = Utc::now();
 
+        // Guarding on the row version we just read closes the read-modify-write race.
         #[cfg(feature = "postgres")]
-        sqlx::query(
+        let result = sqlx::query(
             r#"
             UPDATE customers
             SET name=$1, email=$2, phone=$3, notes=$4, is_active=$5, updated_at=$6
-            WHERE tenant_id=$7 AND id=$8
+            WHERE tenant_id=$7 AND id=$8 AND updated_at=$9
             "#,
         )
         .bind(&customer.name)
@@ -2532,9 +3140,15 @@ impl CustomerService {
         .bind(customer.updated_at)
         .bind(tenant_id)
         .bind(customer_id)
+        .bind(previous_updated_at)
         .execute(&self.pool)
         .await?;
 
+        #[cfg(feature = "postgres")]
+        if result.rows_affected() == 0 {
+            return Err(concurrency::modified_conflict("customer"));
+        }
+
         #[cfg(feature = "sqlite")]
         sqlx::query(
             r#"
@@ -2555,7 +3169,7 @@ impl CustomerService {
         .await?;
 
         self.audit_service
-            .log(
+            .log_change(
                 Some(actor_id),
                 Some(tenant_id),
                 "CUSTOMER_UPDATE",
@@ -2563,12 +3177,14 @@ impl CustomerService {
                 Some(customer_id),
                 Some("Updated customer"),
                 ip_address,
+                &AuditChange::between(&before, &customer),
             )
             .await;
 
         Ok(customer)
     }
 
+    /// Move a customer to the trash. It is purged after the trash retention window.
     pub async fn delete_customer(
         &self,
         actor_id: &str,
@@ -2581,18 +3197,57 @@ impl CustomerService {
             .await?;
 
         #[cfg(feature = "postgres")]
-        let res = sqlx::query("DELETE FROM customers WHERE tenant_id = $1 AND id = $2")
-            .bind(tenant_id)
-            .bind(customer_id)
-            .execute(&self.pool)
-            .await?;
+        let (live_subscriptions, enabled_pppoe_accounts): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT
+              (SELECT COUNT(*) FROM customer_subscriptions
+               WHERE tenant_id = $1 AND customer_id = $2
+                 AND LOWER(status) IN ('active', 'pending_installation')),
+              (SELECT COUNT(*) FROM pppoe_accounts
+               WHERE tenant_id = $1 AND customer_id = $2 AND disabled = false)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(customer_id)
+        .fetch_one(&self.pool)
+        .await?;
 
         #[cfg(feature = "sqlite")]
-        let res = sqlx::query("DELETE FROM customers WHERE tenant_id = ? AND id = ?")
+        let (live_subscriptions, enabled_pppoe_accounts): (i64, i64) = (
+            sqlx::query_scalar(
+                "SELECT COUNT(*) FROM customer_subscriptions WHERE tenant_id = ? AND customer_id = ? AND LOWER(status) IN ('active', 'pending_installation')",
+            )
             .bind(tenant_id)
             .bind(customer_id)
-            .execute(&self.pool)
-            .await?;
+            .fetch_one(&self.pool)
+            .await?,
+            0,
+        );
+
+        ensure_trashable(live_subscriptions, enabled_pppoe_accounts)?;
+
+        let now = Utc::now();
+
+        #[cfg(feature = "postgres")]
+        let res = sqlx::query(
+            "UPDATE customers SET deleted_at = $3, updated_at = $3 WHERE tenant_id = $1 AND id = $2 AND deleted_at IS NULL",
+        )
+        .bind(tenant_id)
+        .bind(customer_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let res = sqlx::query(
+            "UPDATE customers SET deleted_at = ?, updated_at = ? WHERE tenant_id = ? AND id = ? AND deleted_at IS NULL",
+        )
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .bind(tenant_id)
+        .bind(customer_id)
+        .execute(&self.pool)
+        .await?;
 
         if res.rows_affected() == 0 {
             return Err(AppError::NotFound("Customer not found".to_string()));
@@ -2605,7 +3260,7 @@ impl CustomerService {
                 "CUSTOMER_DELETE",
                 "customers",
                 Some(customer_id),
-                Some("Deleted customer"),
+                Some("Moved customer to trash"),
                 ip_address,
             )
             .await;
@@ -2613,6 +3268,191 @@ impl CustomerService {
         Ok(())
     }
 
+    pub async fn list_deleted_customers(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+    ) -> AppResult<Vec<TrashItem>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "customers", "manage")
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        let query = r#"
+            SELECT id, name, email AS detail, deleted_at
+            FROM customers
+            WHERE tenant_id = $1 AND deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC
+        "#;
+
+        #[cfg(feature = "sqlite")]
+        let query = r#"
+            SELECT id, name, email AS detail, deleted_at
+            FROM customers
+            WHERE tenant_id = ? AND deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC
+        "#;
+
+        let rows: Vec<TrashItem> = sqlx::query_as(query)
+            .bind(tenant_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows)
+    }
+
+    pub async fn restore_customer(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        customer_id: &str,
+        ip_address: Option<&str>,
+    ) -> AppResult<Customer> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "customers", "manage")
+            .await?;
+
+        let now = Utc::now();
+
+        #[cfg(feature = "postgres")]
+        let res = sqlx::query(
+            "UPDATE customers SET deleted_at = NULL, updated_at = $3 WHERE tenant_id = $1 AND id = $2 AND deleted_at IS NOT NULL",
+        )
+        .bind(tenant_id)
+        .bind(customer_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let res = sqlx::query(
+            "UPDATE customers SET deleted_at = NULL, updated_at = ? WHERE tenant_id = ? AND id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(now.to_rfc3339())
+        .bind(tenant_id)
+        .bind(customer_id)
+        .execute(&self.pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound(
+                "Customer not found in trash".to_string(),
+            ));
+        }
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "CUSTOMER_RESTORE",
+                "customers",
+                Some(customer_id),
+                Some("Restored customer from trash"),
+                ip_address,
+            )
+            .await;
+
+        self.get_customer(actor_id, tenant_id, customer_id).await
+    }
+
+    // =========================
+    // Admin: Tags
+    // =========================
+
+    /// Tags in use across the tenant, for audience pickers and filters.
+    pub async fn list_customer_tags(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+    ) -> AppResult<Vec<CustomerTagSummary>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "customers", "read")
+            .await?;
+
+        let rows: Vec<CustomerTagSummary> = sqlx::query_as(
+            r#"
+            SELECT ct.tag, COUNT(*) AS customer_count
+            FROM customer_tags ct
+            JOIN customers c ON c.id = ct.customer_id AND c.deleted_at IS NULL
+            WHERE ct.tenant_id = $1
+            GROUP BY ct.tag
+            ORDER BY ct.tag
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn get_customer_tags(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        customer_id: &str,
+    ) -> AppResult<Vec<String>> {
+        let _ = self.get_customer(actor_id, tenant_id, customer_id).await?;
+
+        let tags: Vec<String> = sqlx::query_scalar(
+            "SELECT tag FROM customer_tags WHERE tenant_id = $1 AND customer_id = $2 ORDER BY tag",
+        )
+        .bind(tenant_id)
+        .bind(customer_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(tags)
+    }
+
+    /// Replaces the customer's tags. Tags are trimmed and lower-cased.
+    pub async fn set_customer_tags(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        customer_id: &str,
+        dto: SetCustomerTagsRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<Vec<String>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "customers", "manage")
+            .await?;
+        let _ = self.get_customer(actor_id, tenant_id, customer_id).await?;
+
+        let tags = normalize_customer_tags(dto.tags)?;
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM customer_tags WHERE tenant_id = $1 AND customer_id = $2")
+            .bind(tenant_id)
+            .bind(customer_id)
+            .execute(&mut *tx)
+            .await?;
+        for tag in &tags {
+            sqlx::query(
+                "INSERT INTO customer_tags (tenant_id, customer_id, tag) VALUES ($1, $2, $3)",
+            )
+            .bind(tenant_id)
+            .bind(customer_id)
+            .bind(tag)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        let details = format!("Set customer tags: {}", tags.join(", "));
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "CUSTOMER_TAGS_UPDATE",
+                "customers",
+                Some(customer_id),
+                Some(&details),
+                ip_address,
+            )
+            .await;
+
+        Ok(tags)
+    }
+
     // =========================
     // Admin: Locations
     // =========================
@@ -2819,6 +3659,7 @@ impl CustomerService {
                 .fetch_optional(&self.pool)
                 .await?
                 .ok_or_else(|| AppError::NotFound("Location not found".to_string()))?;
+        let before = loc.clone();
 
         if let Some(v) = dto.label {
             let vv = v.trim().to_string();
@@ -2913,7 +3754,7 @@ impl CustomerService {
         .await?;
 
         self.audit_service
-            .log(
+            .log_change(
                 Some(actor_id),
                 Some(tenant_id),
                 "CUSTOMER_LOCATION_UPDATE",
@@ -2921,6 +3762,7 @@ impl CustomerService {
                 Some(location_id),
                 Some("Updated customer location"),
                 ip_address,
+                &AuditChange::between(&before, &loc),
             )
             .await;
 
@@ -3305,6 +4147,7 @@ impl CustomerService {
                 FROM installation_work_orders iwo
                 WHERE iwo.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY iwo.created_at DESC
                 LIMIT 1
               ) AS latest_work_order_id,
@@ -3313,6 +4156,7 @@ impl CustomerService {
                 FROM installation_work_orders iwo
                 WHERE iwo.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY iwo.created_at DESC
                 LIMIT 1
               ) AS latest_work_order_status,
@@ -3323,6 +4167,7 @@ impl CustomerService {
                   FROM installation_work_orders iwo
                   WHERE iwo.tenant_id = cs.tenant_id
                     AND iwo.subscription_id = cs.id
+                    AND iwo.work_type = 'installation'
                   ORDER BY iwo.created_at DESC
                   LIMIT 1
                 ), '') = 'cancelled' THEN true
@@ -3334,6 +4179,7 @@ impl CustomerService {
                 JOIN installation_work_orders iwo ON iwo.id = worr.work_order_id
                 WHERE worr.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY worr.created_at DESC
                 LIMIT 1
               ) AS latest_reschedule_status,
@@ -3343,6 +4189,7 @@ impl CustomerService {
                 JOIN installation_work_orders iwo ON iwo.id = worr.work_order_id
                 WHERE worr.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY worr.created_at DESC
                 LIMIT 1
               ) AS latest_reschedule_requested_at
@@ -3389,6 +4236,7 @@ impl CustomerService {
                 FROM installation_work_orders iwo
                 WHERE iwo.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY iwo.created_at DESC
                 LIMIT 1
               ) AS latest_work_order_id,
@@ -3397,6 +4245,7 @@ impl CustomerService {
                 FROM installation_work_orders iwo
                 WHERE iwo.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY iwo.created_at DESC
                 LIMIT 1
               ) AS latest_work_order_status,
@@ -3407,6 +4256,7 @@ impl CustomerService {
                   FROM installation_work_orders iwo
                   WHERE iwo.tenant_id = cs.tenant_id
                     AND iwo.subscription_id = cs.id
+                    AND iwo.work_type = 'installation'
                   ORDER BY iwo.created_at DESC
                   LIMIT 1
                 ), '') = 'cancelled' THEN 1
@@ -3418,6 +4268,7 @@ impl CustomerService {
                 JOIN installation_work_orders iwo ON iwo.id = worr.work_order_id
                 WHERE worr.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY worr.created_at DESC
                 LIMIT 1
               ) AS latest_reschedule_status,
@@ -3427,6 +4278,7 @@ impl CustomerService {
                 JOIN installation_work_orders iwo ON iwo.id = worr.work_order_id
                 WHERE worr.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY worr.created_at DESC
                 LIMIT 1
               ) AS latest_reschedule_requested_at
@@ -3479,7 +4331,7 @@ impl CustomerService {
 
         #[cfg(feature = "postgres")]
         let exists_customer: bool = sqlx::query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM customers WHERE id = $1 AND tenant_id = $2)",
+            "SELECT EXISTS(SELECT 1 FROM customers WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL)",
         )
         .bind(&dto.customer_id)
         .bind(tenant_id)
@@ -3488,7 +4340,7 @@ impl CustomerService {
 
         #[cfg(feature = "sqlite")]
         let exists_customer: bool = sqlx::query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM customers WHERE id = ? AND tenant_id = ?)",
+            "SELECT EXISTS(SELECT 1 FROM customers WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL)",
         )
         .bind(&dto.customer_id)
         .bind(tenant_id)
@@ -3527,7 +4379,7 @@ impl CustomerService {
 
         #[cfg(feature = "postgres")]
         let exists_package: bool = sqlx::query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM isp_packages WHERE id = $1 AND tenant_id = $2)",
+            "SELECT EXISTS(SELECT 1 FROM isp_packages WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL)",
         )
         .bind(&dto.package_id)
         .bind(tenant_id)
@@ -3536,7 +4388,7 @@ impl CustomerService {
 
         #[cfg(feature = "sqlite")]
         let exists_package: bool = sqlx::query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM isp_packages WHERE id = ? AND tenant_id = ?)",
+            "SELECT EXISTS(SELECT 1 FROM isp_packages WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL)",
         )
         .bind(&dto.package_id)
         .bind(tenant_id)
@@ -3550,7 +4402,7 @@ impl CustomerService {
         if let Some(router_id) = dto.router_id.as_deref() {
             #[cfg(feature = "postgres")]
             let exists_router: bool = sqlx::query_scalar(
-                "SELECT EXISTS(SELECT 1 FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2)",
+                "SELECT EXISTS(SELECT 1 FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL)",
             )
             .bind(router_id)
             .bind(tenant_id)
@@ -3559,7 +4411,7 @@ impl CustomerService {
 
             #[cfg(feature = "sqlite")]
             let exists_router: bool = sqlx::query_scalar(
-                "SELECT EXISTS(SELECT 1 FROM mikrotik_routers WHERE id = ? AND tenant_id = ?)",
+                "SELECT EXISTS(SELECT 1 FROM mikrotik_routers WHERE id = ? AND tenant_id = ? AND deleted_at IS NULL)",
             )
             .bind(router_id)
             .bind(tenant_id)
@@ -3650,6 +4502,7 @@ impl CustomerService {
         .bind(tenant_id)
         .fetch_one(&self.pool)
         .await?;
+        self.sync_subscription_components(tenant_id, &row).await?;
 
         self.audit_service
             .log(
@@ -3700,6 +4553,8 @@ impl CustomerService {
         .fetch_optional(&self.pool)
         .await?
         .ok_or_else(|| AppError::NotFound("Subscription not found".to_string()))?;
+        let previous_status = row.status.clone();
+        let before = row.clone();
 
         if let Some(price) = dto.price {
             if price <= 0.0 {
@@ -3813,7 +4668,7 @@ impl CustomerService {
         .await?;
 
         self.audit_service
-            .log(
+            .log_change(
                 Some(actor_id),
                 Some(tenant_id),
                 "CUSTOMER_SUBSCRIPTION_UPDATE",
@@ -3821,9 +4676,36 @@ impl CustomerService {
                 Some(subscription_id),
                 Some("Updated customer subscription"),
                 ip_address,
+                &AuditChange::between(&before, &row),
             )
             .await;
 
+        if needs_dismantle(&previous_status, &row.status)
+            && self.auto_dismantle_enabled(tenant_id).await
+        {
+            match self
+                .insert_work_order(
+                    actor_id,
+                    tenant_id,
+                    &row,
+                    "dismantle",
+                    serde_json::json!({ "reason": "Subscription cancelled" }),
+                    None,
+                    ip_address,
+                )
+                .await
+            {
+                Ok(_) | Err(AppError::Conflict(_)) => {}
+                Err(err) => warn!(
+                    "failed to create dismantle work order: tenant_id={}, subscription_id={}, error={}",
+                    tenant_id, row.id, err
+                ),
+            }
+        }
+
+        if row.package_id != before.package_id {
+            self.sync_subscription_components(tenant_id, &row).await?;
+        }
         self.auto_provision_pppoe_for_subscription(actor_id, tenant_id, &row, ip_address)
             .await?;
 
@@ -3875,58 +4757,264 @@ impl CustomerService {
         Ok(())
     }
 
-    // =========================
-    // Portal: Self-service
-    // =========================
-
-    pub async fn get_portal_customer_id(
+    /// Components of a bundle subscription; empty for other packages.
+    pub async fn list_subscription_components(
         &self,
         actor_id: &str,
         tenant_id: &str,
-    ) -> AppResult<String> {
+        subscription_id: &str,
+    ) -> AppResult<Vec<CustomerSubscriptionComponent>> {
         self.auth_service
-            .check_permission(actor_id, tenant_id, "customers", "read_own")
+            .check_permission(actor_id, tenant_id, "customers", "read")
             .await?;
 
         #[cfg(feature = "postgres")]
-        let customer_id: Option<String> = sqlx::query_scalar(
-            "SELECT customer_id FROM customer_users WHERE tenant_id = $1 AND user_id = $2 LIMIT 1",
+        let rows: Vec<CustomerSubscriptionComponent> = sqlx::query_as(
+            r#"
+            SELECT
+              sc.id, sc.tenant_id, sc.subscription_id, sc.component_package_id,
+              p.name AS package_name, sc.service_type, sc.status, sc.detail, sc.last_error,
+              sc.provisioned_at, sc.created_at, sc.updated_at
+            FROM customer_subscription_components sc
+            LEFT JOIN isp_packages p ON p.id = sc.component_package_id
+            WHERE sc.tenant_id = $1 AND sc.subscription_id = $2
+            ORDER BY (sc.service_type = 'internet_pppoe') DESC, sc.created_at ASC
+            "#,
         )
         .bind(tenant_id)
-        .bind(actor_id)
-        .fetch_optional(&self.pool)
+        .bind(subscription_id)
+        .fetch_all(&self.pool)
         .await?;
 
         #[cfg(feature = "sqlite")]
-        let customer_id: Option<String> = sqlx::query_scalar(
-            "SELECT customer_id FROM customer_users WHERE tenant_id = ? AND user_id = ? LIMIT 1",
-        )
-        .bind(tenant_id)
-        .bind(actor_id)
-        .fetch_optional(&self.pool)
-        .await?;
+        let rows: Vec<CustomerSubscriptionComponent> = {
+            let _ = subscription_id;
+            Vec::new()
+        };
 
-        customer_id
-            .ok_or_else(|| AppError::Forbidden("You are not linked to any customer".to_string()))
+        Ok(rows)
     }
 
-    pub async fn list_my_locations(
+    /// Records the provisioning state of one bundle component. A provisioned
+    /// static IP is carried onto the subscription's PPPoE account.
+    pub async fn update_subscription_component(
         &self,
         actor_id: &str,
         tenant_id: &str,
-    ) -> AppResult<Vec<CustomerLocation>> {
-        let customer_id = self.get_portal_customer_id(actor_id, tenant_id).await?;
+        component_id: &str,
+        dto: UpdateSubscriptionComponentRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<CustomerSubscriptionComponent> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "customers", "manage")
+            .await?;
 
         #[cfg(feature = "postgres")]
-        let rows: Vec<CustomerLocation> = sqlx::query_as(
+        let row: Option<CustomerSubscriptionComponent> = sqlx::query_as(
             r#"
             SELECT
-                id,
-                tenant_id,
-                customer_id,
-                label,
-                address_line1,
-                address_line2,
+              sc.id, sc.tenant_id, sc.subscription_id, sc.component_package_id,
+              p.name AS package_name, sc.service_type, sc.status, sc.detail, sc.last_error,
+              sc.provisioned_at, sc.created_at, sc.updated_at
+            FROM customer_subscription_components sc
+            LEFT JOIN isp_packages p ON p.id = sc.component_package_id
+            WHERE sc.tenant_id = $1 AND sc.id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(component_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let row: Option<CustomerSubscriptionComponent> = {
+            let _ = component_id;
+            None
+        };
+
+        let mut row =
+            row.ok_or_else(|| AppError::NotFound("Subscription component not found".to_string()))?;
+        let before = row.clone();
+
+        if let Some(status) = dto.status {
+            let status = status.trim().to_lowercase();
+            if !matches!(
+                status.as_str(),
+                "pending" | "provisioned" | "failed" | "disabled"
+            ) {
+                return Err(AppError::Validation(
+                    "status must be one of: pending, provisioned, failed, disabled".to_string(),
+                ));
+            }
+            row.status = status;
+        }
+        if let Some(detail) = dto.detail {
+            let detail = detail.trim().to_string();
+            row.detail = if detail.is_empty() {
+                None
+            } else {
+                Some(detail)
+            };
+        }
+        if row.service_type == "static_ip" {
+            if let Some(address) = row.detail.as_deref() {
+                if address.parse::<std::net::Ipv4Addr>().is_err() {
+                    return Err(AppError::Validation(
+                        "Static IP must be an IPv4 address".to_string(),
+                    ));
+                }
+            }
+        }
+
+        let now = Utc::now();
+        if row.status == "provisioned" {
+            row.last_error = None;
+            if before.status != "provisioned" {
+                row.provisioned_at = Some(now);
+            }
+        }
+        row.updated_at = now;
+
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            r#"
+            UPDATE customer_subscription_components
+            SET status = $1, detail = $2, last_error = $3, provisioned_at = $4, updated_at = $5
+            WHERE tenant_id = $6 AND id = $7
+            "#,
+        )
+        .bind(&row.status)
+        .bind(&row.detail)
+        .bind(&row.last_error)
+        .bind(row.provisioned_at)
+        .bind(row.updated_at)
+        .bind(tenant_id)
+        .bind(component_id)
+        .execute(&self.pool)
+        .await?;
+
+        if row.service_type == "static_ip" {
+            let address = row
+                .detail
+                .as_deref()
+                .filter(|_| row.status == "provisioned");
+            self.apply_subscription_static_ip(tenant_id, &row.subscription_id, address)
+                .await?;
+        }
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "CUSTOMER_SUBSCRIPTION_COMPONENT_UPDATE",
+                "customer_subscriptions",
+                Some(&row.subscription_id),
+                Some(&format!(
+                    "{} component: {} -> {}{}",
+                    row.service_type,
+                    before.status,
+                    row.status,
+                    row.detail
+                        .as_deref()
+                        .map(|d| format!(" ({})", d))
+                        .unwrap_or_default()
+                )),
+                ip_address,
+            )
+            .await;
+
+        Ok(row)
+    }
+
+    /// Sets the remote address of the PPPoE accounts provisioned for a bundle
+    /// subscription's internet package.
+    async fn apply_subscription_static_ip(
+        &self,
+        tenant_id: &str,
+        subscription_id: &str,
+        address: Option<&str>,
+    ) -> AppResult<()> {
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            r#"
+            UPDATE pppoe_accounts a
+            SET remote_address = $1, updated_at = $2
+            FROM customer_subscriptions s
+            JOIN customer_subscription_components sc
+              ON sc.subscription_id = s.id AND sc.service_type = 'internet_pppoe'
+            WHERE s.tenant_id = $3
+              AND s.id = $4
+              AND a.tenant_id = s.tenant_id
+              AND a.customer_id = s.customer_id
+              AND a.location_id = s.location_id
+              AND a.package_id = sc.component_package_id
+            "#,
+        )
+        .bind(address)
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .bind(subscription_id)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let _ = (tenant_id, subscription_id, address);
+
+        Ok(())
+    }
+
+    // =========================
+    // Portal: Self-service
+    // =========================
+
+    pub async fn get_portal_customer_id(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+    ) -> AppResult<String> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "customers", "read_own")
+            .await?;
+
+        #[cfg(feature = "postgres")]
+        let customer_id: Option<String> = sqlx::query_scalar(
+            "SELECT customer_id FROM customer_users WHERE tenant_id = $1 AND user_id = $2 LIMIT 1",
+        )
+        .bind(tenant_id)
+        .bind(actor_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let customer_id: Option<String> = sqlx::query_scalar(
+            "SELECT customer_id FROM customer_users WHERE tenant_id = ? AND user_id = ? LIMIT 1",
+        )
+        .bind(tenant_id)
+        .bind(actor_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        customer_id
+            .ok_or_else(|| AppError::Forbidden("You are not linked to any customer".to_string()))
+    }
+
+    pub async fn list_my_locations(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+    ) -> AppResult<Vec<CustomerLocation>> {
+        let customer_id = self.get_portal_customer_id(actor_id, tenant_id).await?;
+
+        #[cfg(feature = "postgres")]
+        let rows: Vec<CustomerLocation> = sqlx::query_as(
+            r#"
+            SELECT
+                id,
+                tenant_id,
+                customer_id,
+                label,
+                address_line1,
+                address_line2,
                 city,
                 state,
                 postal_code,
@@ -4305,12 +5393,142 @@ impl CustomerService {
         Ok(())
     }
 
+    async fn ensure_my_location(
+        &self,
+        tenant_id: &str,
+        customer_id: &str,
+        location_id: &str,
+    ) -> AppResult<()> {
+        #[cfg(feature = "postgres")]
+        let location_ok: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM customer_locations WHERE tenant_id = $1 AND id = $2 AND customer_id = $3)",
+        )
+        .bind(tenant_id)
+        .bind(location_id)
+        .bind(customer_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let location_ok: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM customer_locations WHERE tenant_id = ? AND id = ? AND customer_id = ?)",
+        )
+        .bind(tenant_id)
+        .bind(location_id)
+        .bind(customer_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        if !location_ok {
+            return Err(AppError::Validation(
+                "Location does not belong to your customer account".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The service zone a location falls in, picked like the coverage check
+    /// does. `None` for locations without coordinates or outside all zones.
+    async fn location_zone_id(
+        &self,
+        tenant_id: &str,
+        location_id: &str,
+    ) -> AppResult<Option<String>> {
+        #[cfg(feature = "postgres")]
+        let zone_id: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT z.id::text
+            FROM customer_locations cl
+            JOIN service_zones z
+              ON z.tenant_id = $1::uuid
+             AND z.status = 'active'
+             AND ST_Contains(
+                   z.geom,
+                   ST_SetSRID(ST_MakePoint(cl.longitude::float8, cl.latitude::float8), 4326)
+                 )
+            WHERE cl.tenant_id = $1
+              AND cl.id = $2
+              AND cl.latitude IS NOT NULL
+              AND cl.longitude IS NOT NULL
+            ORDER BY z.priority ASC, z.updated_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(location_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        // Service zones need PostGIS.
+        #[cfg(feature = "sqlite")]
+        let zone_id: Option<String> = {
+            let _ = (tenant_id, location_id);
+            None
+        };
+
+        Ok(zone_id)
+    }
+
+    /// Whether a zone-restricted package is sold at the location: its zone
+    /// must have an active offer for the package.
+    async fn zone_package_available(
+        &self,
+        tenant_id: &str,
+        location_id: &str,
+        package_id: &str,
+    ) -> AppResult<bool> {
+        let Some(zone_id) = self.location_zone_id(tenant_id, location_id).await? else {
+            return Ok(false);
+        };
+
+        #[cfg(feature = "postgres")]
+        let offered: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+              SELECT 1 FROM zone_offers
+              WHERE tenant_id = $1::uuid
+                AND zone_id = $2::uuid
+                AND package_id = $3
+                AND is_active = true
+            )
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&zone_id)
+        .bind(package_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let offered: bool = {
+            let _ = (zone_id, package_id);
+            false
+        };
+
+        Ok(offered)
+    }
+
+    /// Active packages for the portal. With a location, zone-restricted
+    /// packages not offered there are left out.
     pub async fn list_my_packages(
         &self,
         actor_id: &str,
         tenant_id: &str,
+        location_id: Option<String>,
     ) -> AppResult<Vec<IspPackage>> {
-        let _customer_id = self.get_portal_customer_id(actor_id, tenant_id).await?;
+        let customer_id = self.get_portal_customer_id(actor_id, tenant_id).await?;
+
+        let location_id = location_id
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        let zone_id = match location_id.as_deref() {
+            Some(location_id) => {
+                self.ensure_my_location(tenant_id, &customer_id, location_id)
+                    .await?;
+                self.location_zone_id(tenant_id, location_id).await?
+            }
+            None => None,
+        };
 
         #[cfg(feature = "postgres")]
         let rows: Vec<IspPackage> = sqlx::query_as(
@@ -4323,6 +5541,7 @@ impl CustomerService {
               description,
               features,
               is_active,
+              zone_restricted,
               price_monthly::float8 AS price_monthly,
               price_yearly::float8 AS price_yearly,
               created_at,
@@ -4330,37 +5549,58 @@ impl CustomerService {
             FROM isp_packages
             WHERE tenant_id = $1
               AND is_active = true
+              AND deleted_at IS NULL
+              -- Add-ons are only sold inside a bundle.
+              AND service_type NOT IN ('static_ip', 'iptv', 'voip')
+              AND (
+                $2::text IS NULL
+                OR zone_restricted = false
+                OR id IN (
+                  SELECT package_id FROM zone_offers
+                  WHERE tenant_id = $1::uuid AND zone_id = $3::uuid AND is_active = true
+                )
+              )
             ORDER BY price_monthly ASC, name ASC
             "#,
         )
         .bind(tenant_id)
+        .bind(&location_id)
+        .bind(&zone_id)
         .fetch_all(&self.pool)
         .await?;
 
         #[cfg(feature = "sqlite")]
-        let rows: Vec<IspPackage> = sqlx::query_as(
-            r#"
-            SELECT
-              id,
-              tenant_id,
-              service_type,
-              name,
-              description,
-              features,
-              is_active,
-              price_monthly AS price_monthly,
-              price_yearly AS price_yearly,
-              created_at,
-              updated_at
-            FROM isp_packages
-            WHERE tenant_id = ?
-              AND is_active = 1
-            ORDER BY price_monthly ASC, name ASC
-            "#,
-        )
-        .bind(tenant_id)
-        .fetch_all(&self.pool)
-        .await?;
+        let rows: Vec<IspPackage> = {
+            let _ = zone_id;
+            sqlx::query_as(
+                r#"
+                SELECT
+                  id,
+                  tenant_id,
+                  service_type,
+                  name,
+                  description,
+                  features,
+                  is_active,
+                  zone_restricted,
+                  price_monthly AS price_monthly,
+                  price_yearly AS price_yearly,
+                  created_at,
+                  updated_at
+                FROM isp_packages
+                WHERE tenant_id = ?
+                  AND is_active = 1
+                  AND deleted_at IS NULL
+                  AND service_type NOT IN ('static_ip', 'iptv', 'voip')
+                  AND (? IS NULL OR zone_restricted = 0)
+                ORDER BY price_monthly ASC, name ASC
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(&location_id)
+            .fetch_all(&self.pool)
+            .await?
+        };
 
         Ok(rows)
     }
@@ -4422,6 +5662,7 @@ impl CustomerService {
                           FROM installation_work_orders iwo
                           WHERE iwo.tenant_id = cs.tenant_id
                             AND iwo.subscription_id = cs.id
+                            AND iwo.work_type = 'installation'
                           ORDER BY iwo.created_at DESC
                           LIMIT 1
                         ), '') = 'cancelled'
@@ -4455,6 +5696,7 @@ impl CustomerService {
                           FROM installation_work_orders iwo
                           WHERE iwo.tenant_id = cs.tenant_id
                             AND iwo.subscription_id = cs.id
+                            AND iwo.work_type = 'installation'
                           ORDER BY iwo.created_at DESC
                           LIMIT 1
                         ), '') = 'cancelled'
@@ -4498,6 +5740,7 @@ impl CustomerService {
                 FROM installation_work_orders iwo
                 WHERE iwo.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY iwo.created_at DESC
                 LIMIT 1
               ) AS latest_work_order_id,
@@ -4506,6 +5749,7 @@ impl CustomerService {
                 FROM installation_work_orders iwo
                 WHERE iwo.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY iwo.created_at DESC
                 LIMIT 1
               ) AS latest_work_order_status,
@@ -4516,6 +5760,7 @@ impl CustomerService {
                   FROM installation_work_orders iwo
                   WHERE iwo.tenant_id = cs.tenant_id
                     AND iwo.subscription_id = cs.id
+                    AND iwo.work_type = 'installation'
                   ORDER BY iwo.created_at DESC
                   LIMIT 1
                 ), '') = 'cancelled' THEN true
@@ -4527,6 +5772,7 @@ impl CustomerService {
                 JOIN installation_work_orders iwo ON iwo.id = worr.work_order_id
                 WHERE worr.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY worr.created_at DESC
                 LIMIT 1
               ) AS latest_reschedule_status,
@@ -4536,6 +5782,7 @@ impl CustomerService {
                 JOIN installation_work_orders iwo ON iwo.id = worr.work_order_id
                 WHERE worr.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY worr.created_at DESC
                 LIMIT 1
               ) AS latest_reschedule_requested_at
@@ -4557,6 +5804,7 @@ impl CustomerService {
                           FROM installation_work_orders iwo
                           WHERE iwo.tenant_id = cs.tenant_id
                             AND iwo.subscription_id = cs.id
+                            AND iwo.work_type = 'installation'
                           ORDER BY iwo.created_at DESC
                           LIMIT 1
                         ), '') = 'cancelled'
@@ -4602,6 +5850,7 @@ impl CustomerService {
                 FROM installation_work_orders iwo
                 WHERE iwo.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY iwo.created_at DESC
                 LIMIT 1
               ) AS latest_work_order_id,
@@ -4610,6 +5859,7 @@ impl CustomerService {
                 FROM installation_work_orders iwo
                 WHERE iwo.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY iwo.created_at DESC
                 LIMIT 1
               ) AS latest_work_order_status,
@@ -4620,6 +5870,7 @@ impl CustomerService {
                   FROM installation_work_orders iwo
                   WHERE iwo.tenant_id = cs.tenant_id
                     AND iwo.subscription_id = cs.id
+                    AND iwo.work_type = 'installation'
                   ORDER BY iwo.created_at DESC
                   LIMIT 1
                 ), '') = 'cancelled' THEN 1
@@ -4631,6 +5882,7 @@ impl CustomerService {
                 JOIN installation_work_orders iwo ON iwo.id = worr.work_order_id
                 WHERE worr.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY worr.created_at DESC
                 LIMIT 1
               ) AS latest_reschedule_status,
@@ -4640,6 +5892,7 @@ impl CustomerService {
                 JOIN installation_work_orders iwo ON iwo.id = worr.work_order_id
                 WHERE worr.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY worr.created_at DESC
                 LIMIT 1
               ) AS latest_reschedule_requested_at
@@ -4661,6 +5914,7 @@ impl CustomerService {
                           FROM installation_work_orders iwo
                           WHERE iwo.tenant_id = cs.tenant_id
                             AND iwo.subscription_id = cs.id
+                            AND iwo.work_type = 'installation'
                           ORDER BY iwo.created_at DESC
                           LIMIT 1
                         ), '') = 'cancelled'
@@ -4709,6 +5963,7 @@ impl CustomerService {
                     FROM installation_work_orders iwo
                     WHERE iwo.tenant_id = cs.tenant_id
                       AND iwo.subscription_id = cs.id
+                      AND iwo.work_type = 'installation'
                     ORDER BY iwo.created_at DESC
                     LIMIT 1
                   ), '') <> 'cancelled'
@@ -4720,6 +5975,7 @@ impl CustomerService {
                     FROM installation_work_orders iwo
                     WHERE iwo.tenant_id = cs.tenant_id
                       AND iwo.subscription_id = cs.id
+                      AND iwo.work_type = 'installation'
                     ORDER BY iwo.created_at DESC
                     LIMIT 1
                   ), '') = 'cancelled'
@@ -4748,6 +6004,7 @@ impl CustomerService {
                     FROM installation_work_orders iwo
                     WHERE iwo.tenant_id = cs.tenant_id
                       AND iwo.subscription_id = cs.id
+                      AND iwo.work_type = 'installation'
                     ORDER BY iwo.created_at DESC
                     LIMIT 1
                    ), '') <> 'cancelled'
@@ -4762,6 +6019,7 @@ impl CustomerService {
                       FROM installation_work_orders iwo
                       WHERE iwo.tenant_id = cs.tenant_id
                         AND iwo.subscription_id = cs.id
+                        AND iwo.work_type = 'installation'
                       ORDER BY iwo.created_at DESC
                       LIMIT 1
                     ), '') = 'cancelled'
@@ -4806,53 +6064,40 @@ impl CustomerService {
         let billing_cycle = Self::normalize_billing_cycle(&dto.billing_cycle)?;
         let now = Utc::now();
 
+        self.ensure_my_location(tenant_id, &customer_id, &location_id)
+            .await?;
+
         #[cfg(feature = "postgres")]
-        let location_ok: bool = sqlx::query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM customer_locations WHERE tenant_id = $1 AND id = $2 AND customer_id = $3)",
+        let pkg_row: Option<(f64, f64, bool)> = sqlx::query_as(
+            "SELECT price_monthly::float8, price_yearly::float8, zone_restricted FROM isp_packages WHERE tenant_id = $1 AND id = $2 AND is_active = true AND deleted_at IS NULL AND service_type NOT IN ('static_ip', 'iptv', 'voip') LIMIT 1",
         )
         .bind(tenant_id)
-        .bind(&location_id)
-        .bind(&customer_id)
-        .fetch_one(&self.pool)
+        .bind(&package_id)
+        .fetch_optional(&self.pool)
         .await?;
 
         #[cfg(feature = "sqlite")]
-        let location_ok: bool = sqlx::query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM customer_locations WHERE tenant_id = ? AND id = ? AND customer_id = ?)",
-        )
-        .bind(tenant_id)
-        .bind(&location_id)
-        .bind(&customer_id)
-        .fetch_one(&self.pool)
-        .await?;
-
-        if !location_ok {
-            return Err(AppError::Validation(
-                "Location does not belong to your customer account".to_string(),
-            ));
-        }
-
-        #[cfg(feature = "postgres")]
-        let pkg_row: Option<(f64, f64)> = sqlx::query_as(
-            "SELECT price_monthly::float8, price_yearly::float8 FROM isp_packages WHERE tenant_id = $1 AND id = $2 AND is_active = true LIMIT 1",
-        )
-        .bind(tenant_id)
-        .bind(&package_id)
-        .fetch_optional(&self.pool)
-        .await?;
-
-        #[cfg(feature = "sqlite")]
-        let pkg_row: Option<(f64, f64)> = sqlx::query_as(
-            "SELECT price_monthly AS price_monthly, price_yearly AS price_yearly FROM isp_packages WHERE tenant_id = ? AND id = ? AND is_active = 1 LIMIT 1",
+        let pkg_row: Option<(f64, f64, bool)> = sqlx::query_as(
+            "SELECT price_monthly AS price_monthly, price_yearly AS price_yearly, zone_restricted FROM isp_packages WHERE tenant_id = ? AND id = ? AND is_active = 1 AND deleted_at IS NULL AND service_type NOT IN ('static_ip', 'iptv', 'voip') LIMIT 1",
         )
         .bind(tenant_id)
         .bind(&package_id)
         .fetch_optional(&self.pool)
         .await?;
 
-        let (price_monthly, price_yearly) =
+        let (price_monthly, price_yearly, zone_restricted) =
             pkg_row.ok_or_else(|| AppError::Validation("Package not found".to_string()))?;
 
+        if zone_restricted
+            && !self
+                .zone_package_available(tenant_id, &location_id, &package_id)
+                .await?
+        {
+            return Err(AppError::Validation(
+                "This package is not available at the selected location".to_string(),
+            ));
+        }
+
         let price = if billing_cycle == "yearly" {
             if price_yearly <= 0.0 {
                 return Err(AppError::Validation(
@@ -4939,6 +6184,7 @@ impl CustomerService {
         .bind(tenant_id)
         .fetch_one(&self.pool)
         .await?;
+        self.sync_subscription_components(tenant_id, &row).await?;
 
         self.audit_service
             .log(
@@ -5026,6 +6272,7 @@ impl CustomerService {
             FROM installation_work_orders
             WHERE tenant_id = $1
               AND subscription_id = $2
+              AND work_type = 'installation'
             ORDER BY created_at DESC
             LIMIT 1
             "#,
@@ -5042,6 +6289,7 @@ impl CustomerService {
             FROM installation_work_orders
             WHERE tenant_id = ?
               AND subscription_id = ?
+              AND work_type = 'installation'
             ORDER BY created_at DESC
             LIMIT 1
             "#,
@@ -5153,6 +6401,7 @@ impl CustomerService {
                 FROM installation_work_orders iwo
                 WHERE iwo.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY iwo.created_at DESC
                 LIMIT 1
               ) AS latest_work_order_id,
@@ -5161,6 +6410,7 @@ impl CustomerService {
                 FROM installation_work_orders iwo
                 WHERE iwo.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY iwo.created_at DESC
                 LIMIT 1
               ) AS latest_work_order_status,
@@ -5172,6 +6422,7 @@ impl CustomerService {
                   FROM installation_work_orders iwo
                   WHERE iwo.tenant_id = cs.tenant_id
                     AND iwo.subscription_id = cs.id
+                    AND iwo.work_type = 'installation'
                     AND iwo.status = 'cancelled'
                 )
               ) AS can_request_reopen,
@@ -5181,6 +6432,7 @@ impl CustomerService {
                 JOIN installation_work_orders iwo ON iwo.id = worr.work_order_id
                 WHERE worr.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY worr.created_at DESC
                 LIMIT 1
               ) AS latest_reschedule_status,
@@ -5190,6 +6442,7 @@ impl CustomerService {
                 JOIN installation_work_orders iwo ON iwo.id = worr.work_order_id
                 WHERE worr.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY worr.created_at DESC
                 LIMIT 1
               ) AS latest_reschedule_requested_at
@@ -5231,6 +6484,7 @@ impl CustomerService {
                 FROM installation_work_orders iwo
                 WHERE iwo.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY iwo.created_at DESC
                 LIMIT 1
               ) AS latest_work_order_id,
@@ -5239,6 +6493,7 @@ impl CustomerService {
                 FROM installation_work_orders iwo
                 WHERE iwo.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY iwo.created_at DESC
                 LIMIT 1
               ) AS latest_work_order_status,
@@ -5250,6 +6505,7 @@ impl CustomerService {
                   FROM installation_work_orders iwo
                   WHERE iwo.tenant_id = cs.tenant_id
                     AND iwo.subscription_id = cs.id
+                    AND iwo.work_type = 'installation'
                     AND iwo.status = 'cancelled'
                 )
               ) AS can_request_reopen,
@@ -5259,6 +6515,7 @@ impl CustomerService {
                 JOIN installation_work_orders iwo ON iwo.id = worr.work_order_id
                 WHERE worr.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY worr.created_at DESC
                 LIMIT 1
               ) AS latest_reschedule_status,
@@ -5268,6 +6525,7 @@ impl CustomerService {
                 JOIN installation_work_orders iwo ON iwo.id = worr.work_order_id
                 WHERE worr.tenant_id = cs.tenant_id
                   AND iwo.subscription_id = cs.id
+                  AND iwo.work_type = 'installation'
                 ORDER BY worr.created_at DESC
                 LIMIT 1
               ) AS latest_reschedule_requested_at
@@ -5301,7 +6559,8 @@ impl CustomerService {
               wo.id, wo.tenant_id, wo.subscription_id, wo.invoice_id, wo.customer_id, wo.location_id,
               cs.package_id AS package_id,
               COALESCE(wo.router_id, cs.router_id) AS router_id,
-              wo.status, wo.assigned_to, wo.scheduled_at, wo.completed_at, wo.notes, wo.created_at, wo.updated_at,
+              wo.work_type, wo.details,
+              wo.status, wo.assigned_to, wo.scheduled_at, wo.scheduled_end_at, wo.completed_at, wo.notes, wo.created_at, wo.updated_at,
               c.name AS customer_name,
               l.label AS location_label,
               p.name AS package_name,
@@ -5343,6 +6602,7 @@ impl CustomerService {
             WHERE wo.tenant_id = $1
               AND wo.customer_id = $2
               AND wo.subscription_id = $3
+              AND wo.work_type = 'installation'
             ORDER BY wo.created_at DESC
             LIMIT 1
             "#,
@@ -5360,7 +6620,8 @@ impl CustomerService {
               wo.id, wo.tenant_id, wo.subscription_id, wo.invoice_id, wo.customer_id, wo.location_id,
               cs.package_id AS package_id,
               COALESCE(wo.router_id, cs.router_id) AS router_id,
-              wo.status, wo.assigned_to, wo.scheduled_at, wo.completed_at, wo.notes, wo.created_at, wo.updated_at,
+              wo.work_type, wo.details,
+              wo.status, wo.assigned_to, wo.scheduled_at, wo.scheduled_end_at, wo.completed_at, wo.notes, wo.created_at, wo.updated_at,
               c.name AS customer_name,
               l.label AS location_label,
               p.name AS package_name,
@@ -5402,6 +6663,7 @@ impl CustomerService {
             WHERE wo.tenant_id = ?
               AND wo.customer_id = ?
               AND wo.subscription_id = ?
+              AND wo.work_type = 'installation'
             ORDER BY wo.created_at DESC
             LIMIT 1
             "#,
@@ -5773,6 +7035,20 @@ impl CustomerService {
             .map(str::to_string)
             .unwrap_or_else(|| pending.requested_schedule_at.clone());
 
+        let target_start = Self::parse_optional_datetime(Some(target_schedule.clone()))?;
+        if let (Some(start), Some(technician_id)) = (
+            target_start,
+            current
+                .assigned_to
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty()),
+        ) {
+            let end = moved_slot_end(current.scheduled_at, current.scheduled_end_at, target_start);
+            self.ensure_technician_slot_free(tenant_id, technician_id, work_order_id, start, end)
+                .await?;
+        }
+
         let note = format!(
             "Reschedule approved. New schedule: {}{}",
             target_schedule,
@@ -5792,6 +7068,7 @@ impl CustomerService {
                 Some("pending"),
                 None,
                 Some(target_schedule),
+                None,
                 Some(note),
                 false,
                 ip_address,
@@ -5844,6 +7121,9 @@ impl CustomerService {
         .execute(&self.pool)
         .await?;
 
+        self.notify_customer_schedule_change(tenant_id, &current, &row)
+            .await;
+
         Ok(row)
     }
 
@@ -5894,6 +7174,7 @@ impl CustomerService {
                 None,
                 None,
                 None,
+                None,
                 Some(format!("Reschedule request rejected. Reason: {}", reason)),
                 false,
                 ip_address,
@@ -5957,10 +7238,11 @@ impl CustomerService {
         #[cfg(feature = "postgres")]
         let existing: Option<InstallationWorkOrder> = sqlx::query_as(
             r#"
-            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, status, assigned_to, scheduled_at, completed_at, notes, created_at, updated_at
+            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, work_type, details, status, assigned_to, scheduled_at, scheduled_end_at, completed_at, notes, created_at, updated_at
             FROM installation_work_orders
             WHERE tenant_id = $1
               AND subscription_id = $2
+              AND work_type = 'installation'
               AND status IN ('pending', 'in_progress')
             ORDER BY created_at DESC
             LIMIT 1
@@ -5974,10 +7256,11 @@ impl CustomerService {
         #[cfg(feature = "sqlite")]
         let existing: Option<InstallationWorkOrder> = sqlx::query_as(
             r#"
-            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, status, assigned_to, scheduled_at, completed_at, notes, created_at, updated_at
+            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, work_type, details, status, assigned_to, scheduled_at, scheduled_end_at, completed_at, notes, created_at, updated_at
             FROM installation_work_orders
             WHERE tenant_id = ?
               AND subscription_id = ?
+              AND work_type = 'installation'
               AND status IN ('pending', 'in_progress')
             ORDER BY created_at DESC
             LIMIT 1
@@ -6043,7 +7326,7 @@ impl CustomerService {
         #[cfg(feature = "postgres")]
         let row: InstallationWorkOrder = sqlx::query_as(
             r#"
-            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, status, assigned_to, scheduled_at, completed_at, notes, created_at, updated_at
+            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, work_type, details, status, assigned_to, scheduled_at, scheduled_end_at, completed_at, notes, created_at, updated_at
             FROM installation_work_orders
             WHERE tenant_id = $1 AND id = $2
             LIMIT 1
@@ -6057,7 +7340,7 @@ impl CustomerService {
         #[cfg(feature = "sqlite")]
         let row: InstallationWorkOrder = sqlx::query_as(
             r#"
-            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, status, assigned_to, scheduled_at, completed_at, notes, created_at, updated_at
+            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, work_type, details, status, assigned_to, scheduled_at, scheduled_end_at, completed_at, notes, created_at, updated_at
             FROM installation_work_orders
             WHERE tenant_id = ? AND id = ?
             LIMIT 1
@@ -6071,6 +7354,167 @@ impl CustomerService {
         Ok((row, true))
     }
 
+    async fn get_customer_subscription_row(
+        &self,
+        tenant_id: &str,
+        subscription_id: &str,
+    ) -> AppResult<CustomerSubscription> {
+        #[cfg(feature = "postgres")]
+        let sub: Option<CustomerSubscription> = sqlx::query_as(
+            "SELECT id, tenant_id, customer_id, location_id, package_id, router_id, billing_cycle, price::float8 as price, currency_code, status, starts_at, ends_at, notes, created_at, updated_at FROM customer_subscriptions WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(subscription_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let sub: Option<CustomerSubscription> = sqlx::query_as(
+            "SELECT id, tenant_id, customer_id, location_id, package_id, router_id, billing_cycle, price as price, currency_code, status, starts_at, ends_at, notes, created_at, updated_at FROM customer_subscriptions WHERE tenant_id = ? AND id = ?",
+        )
+        .bind(tenant_id)
+        .bind(subscription_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        sub.ok_or_else(|| AppError::NotFound("Customer subscription not found".to_string()))
+    }
+
+    /// Inserts a non-installation work order. Only one open order per type is
+    /// kept for a subscription.
+    #[allow(clippy::too_many_arguments)]
+    async fn insert_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        sub: &CustomerSubscription,
+        work_type: &str,
+        details: serde_json::Value,
+        notes: Option<String>,
+        ip_address: Option<&str>,
+    ) -> AppResult<InstallationWorkOrder> {
+        #[cfg(feature = "postgres")]
+        let open_exists: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+              SELECT 1
+              FROM installation_work_orders
+              WHERE tenant_id = $1
+                AND subscription_id = $2
+                AND work_type = $3
+                AND status IN ('pending', 'in_progress', 'on_hold')
+            )
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&sub.id)
+        .bind(work_type)
+        .fetch_one(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let open_exists: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+              SELECT 1
+              FROM installation_work_orders
+              WHERE tenant_id = ?
+                AND subscription_id = ?
+                AND work_type = ?
+                AND status IN ('pending', 'in_progress', 'on_hold')
+            )
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&sub.id)
+        .bind(work_type)
+        .fetch_one(&self.pool)
+        .await?;
+
+        if open_exists {
+            return Err(AppError::Conflict(format!(
+                "An open {} work order already exists for this subscription",
+                work_type
+            )));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        let notes = Self::merge_work_order_notes(None, actor_id, notes.as_deref());
+
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            r#"
+            INSERT INTO installation_work_orders
+              (id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, work_type, details, status, notes, created_at, updated_at)
+            VALUES
+              ($1,$2,$3,NULL,$4,$5,$6,$7,$8,'pending',$9,$10,$11)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&sub.id)
+        .bind(&sub.customer_id)
+        .bind(&sub.location_id)
+        .bind(&sub.router_id)
+        .bind(work_type)
+        .bind(&details)
+        .bind(&notes)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query(
+            r#"
+            INSERT INTO installation_work_orders
+              (id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, work_type, details, status, notes, created_at, updated_at)
+            VALUES
+              (?,?,?,NULL,?,?,?,?,?,'pending',?,?,?)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&sub.id)
+        .bind(&sub.customer_id)
+        .bind(&sub.location_id)
+        .bind(&sub.router_id)
+        .bind(work_type)
+        .bind(&details)
+        .bind(notes.clone())
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        let row = self.get_installation_work_order_row(tenant_id, &id).await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WORK_ORDER_CREATE",
+                "installation_work_orders",
+                Some(&row.id),
+                Some(&format!(
+                    "Created {} work order for subscription {}",
+                    work_type, sub.id
+                )),
+                ip_address,
+            )
+            .await;
+
+        if let Err(err) = self.notify_new_work_order(tenant_id, &row).await {
+            warn!(
+                "failed to send new work order notification: tenant_id={}, work_order_id={}, error={}",
+                tenant_id, row.id, err
+            );
+        }
+
+        Ok(row)
+    }
+
     async fn has_paid_customer_package_invoice_for_subscription(
         &self,
         tenant_id: &str,
@@ -6312,6 +7756,82 @@ impl CustomerService {
         Ok(())
     }
 
+    async fn notify_new_work_order(
+        &self,
+        tenant_id: &str,
+        work_order: &InstallationWorkOrder,
+    ) -> AppResult<()> {
+        let recipient_ids = self
+            .list_tenant_installation_alert_user_ids(tenant_id)
+            .await?;
+        if recipient_ids.is_empty() {
+            return Ok(());
+        }
+
+        #[cfg(feature = "postgres")]
+        let row: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT c.name, l.label
+            FROM installation_work_orders wo
+            LEFT JOIN customers c ON c.tenant_id = wo.tenant_id AND c.id = wo.customer_id
+            LEFT JOIN customer_locations l ON l.tenant_id = wo.tenant_id AND l.id = wo.location_id
+            WHERE wo.tenant_id = $1 AND wo.id = $2
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&work_order.id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let row: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT c.name, l.label
+            FROM installation_work_orders wo
+            LEFT JOIN customers c ON c.tenant_id = wo.tenant_id AND c.id = wo.customer_id
+            LEFT JOIN customer_locations l ON l.tenant_id = wo.tenant_id AND l.id = wo.location_id
+            WHERE wo.tenant_id = ? AND wo.id = ?
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&work_order.id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let (customer_name, location_label) = row.unwrap_or((None, None));
+        let mut kind = work_order.work_type.clone();
+        if let Some(first) = kind.get_mut(0..1) {
+            first.make_ascii_uppercase();
+        }
+
+        let title = format!("{} Work Order: New Request", kind);
+        let message = format!(
+            "New {} work order is ready for assignment and scheduling. Customer: {} • Location: {} • Work Order: {}",
+            work_order.work_type,
+            customer_name.unwrap_or_else(|| "-".to_string()),
+            location_label.unwrap_or_else(|| "-".to_string()),
+            work_order.id
+        );
+
+        for user_id in recipient_ids {
+            self.notification_service
+                .create_notification(
+                    user_id,
+                    Some(tenant_id.to_string()),
+                    title.clone(),
+                    message.clone(),
+                    "info".to_string(),
+                    "operations".to_string(),
+                    Some("/admin/network/installations".to_string()),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
     async fn notify_installation_rescheduled(
         &self,
         tenant_id: &str,
@@ -6523,6 +8043,7 @@ impl CustomerService {
               ON p.tenant_id = cs.tenant_id
              AND p.id = cs.package_id
             WHERE wo.tenant_id = $1
+              AND wo.work_type = 'installation'
               AND wo.status IN ('pending', 'in_progress')
               AND (
                 (wo.scheduled_at IS NOT NULL AND wo.scheduled_at <= $2)
@@ -6562,6 +8083,7 @@ impl CustomerService {
               ON p.tenant_id = cs.tenant_id
              AND p.id = cs.package_id
             WHERE wo.tenant_id = ?
+              AND wo.work_type = 'installation'
               AND wo.status IN ('pending', 'in_progress')
               AND (
                 (wo.scheduled_at IS NOT NULL AND wo.scheduled_at <= ?)
@@ -6633,12 +8155,14 @@ impl CustomerService {
         Ok(exists)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn list_installation_work_orders(
         &self,
         actor_id: &str,
         tenant_id: &str,
         status: Option<String>,
         assigned_to: Option<String>,
+        work_type: Option<String>,
         include_closed: bool,
         limit: u32,
     ) -> AppResult<Vec<InstallationWorkOrderView>> {
@@ -6658,6 +8182,11 @@ impl CustomerService {
             .map(str::trim)
             .filter(|s| !s.is_empty())
             .map(str::to_string);
+        let work_type_filter = work_type
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_lowercase);
         let is_admin_owner = self.is_actor_admin_or_owner(tenant_id, actor_id).await?;
 
         #[cfg(feature = "postgres")]
@@ -6667,7 +8196,8 @@ impl CustomerService {
               wo.id, wo.tenant_id, wo.subscription_id, wo.invoice_id, wo.customer_id, wo.location_id,
               cs.package_id AS package_id,
               COALESCE(wo.router_id, cs.router_id) AS router_id,
-              wo.status, wo.assigned_to, wo.scheduled_at, wo.completed_at, wo.notes, wo.created_at, wo.updated_at,
+              wo.work_type, wo.details,
+              wo.status, wo.assigned_to, wo.scheduled_at, wo.scheduled_end_at, wo.completed_at, wo.notes, wo.created_at, wo.updated_at,
               c.name AS customer_name,
               l.label AS location_label,
               p.name AS package_name,
@@ -6709,6 +8239,7 @@ impl CustomerService {
             WHERE wo.tenant_id = $1
               AND ($2::text IS NULL OR wo.status = $2)
               AND ($3::text IS NULL OR wo.assigned_to = $3)
+              AND ($8::text IS NULL OR wo.work_type = $8)
               AND (
                 $4::bool
                 OR wo.status NOT IN ('completed', 'cancelled')
@@ -6729,6 +8260,7 @@ impl CustomerService {
               CASE wo.status
                 WHEN 'pending' THEN 0
                 WHEN 'in_progress' THEN 1
+                WHEN 'on_hold' THEN 1
                 WHEN 'completed' THEN 2
                 WHEN 'cancelled' THEN 3
                 ELSE 4
@@ -6744,6 +8276,7 @@ impl CustomerService {
         .bind(is_admin_owner)
         .bind(actor_id)
         .bind(limit as i64)
+        .bind(&work_type_filter)
         .fetch_all(&self.pool)
         .await?;
 
@@ -6754,7 +8287,8 @@ impl CustomerService {
               wo.id, wo.tenant_id, wo.subscription_id, wo.invoice_id, wo.customer_id, wo.location_id,
               cs.package_id AS package_id,
               COALESCE(wo.router_id, cs.router_id) AS router_id,
-              wo.status, wo.assigned_to, wo.scheduled_at, wo.completed_at, wo.notes, wo.created_at, wo.updated_at,
+              wo.work_type, wo.details,
+              wo.status, wo.assigned_to, wo.scheduled_at, wo.scheduled_end_at, wo.completed_at, wo.notes, wo.created_at, wo.updated_at,
               c.name AS customer_name,
               l.label AS location_label,
               p.name AS package_name,
@@ -6796,6 +8330,7 @@ impl CustomerService {
             WHERE wo.tenant_id = ?
               AND (? IS NULL OR wo.status = ?)
               AND (? IS NULL OR wo.assigned_to = ?)
+              AND (? IS NULL OR wo.work_type = ?)
               AND (
                 ? = 1
                 OR wo.status NOT IN ('completed', 'cancelled')
@@ -6816,6 +8351,7 @@ impl CustomerService {
               CASE wo.status
                 WHEN 'pending' THEN 0
                 WHEN 'in_progress' THEN 1
+                WHEN 'on_hold' THEN 1
                 WHEN 'completed' THEN 2
                 WHEN 'cancelled' THEN 3
                 ELSE 4
@@ -6829,6 +8365,8 @@ impl CustomerService {
         .bind(&status_filter)
         .bind(&assigned_filter)
         .bind(&assigned_filter)
+        .bind(&work_type_filter)
+        .bind(&work_type_filter)
         .bind(if include_closed { 1 } else { 0 })
         .bind(if is_admin_owner { 1 } else { 0 })
         .bind(actor_id)
@@ -6844,11 +8382,10 @@ impl CustomerService {
         actor_id: &str,
         tenant_id: &str,
         work_order_id: &str,
-        assigned_to: &str,
-        scheduled_at: Option<String>,
-        notes: Option<String>,
+        dto: AssignInstallationWorkOrderRequest,
         ip_address: Option<&str>,
     ) -> AppResult<InstallationWorkOrder> {
+        let assigned_to = dto.assigned_to.as_str();
         self.auth_service
             .check_permission(actor_id, tenant_id, "work_orders", "manage")
             .await?;
@@ -6890,473 +8427,1738 @@ impl CustomerService {
             ));
         }
 
-        self.set_installation_work_order_status_internal(
-            actor_id,
-            tenant_id,
-            work_order_id,
-            if current.status == "pending" {
-                Some("pending")
-            } else {
-                None
-            },
-            Some(assigned_to),
-            scheduled_at,
-            notes,
-            false,
-            ip_address,
-            "WORK_ORDER_ASSIGN",
-            "Assigned installation work order",
-        )
-        .await
-    }
-
-    pub async fn claim_installation_work_order(
-        &self,
-        actor_id: &str,
-        tenant_id: &str,
-        work_order_id: &str,
-        notes: Option<String>,
-        ip_address: Option<&str>,
-    ) -> AppResult<InstallationWorkOrder> {
-        self.auth_service
-            .check_permission(actor_id, tenant_id, "work_orders", "manage")
-            .await?;
+        let slot_start = match dto.scheduled_at.as_ref() {
+            Some(raw) => Self::parse_optional_datetime(Some(raw.clone()))?,
+            None => current.scheduled_at,
+        };
+        let slot_end = Self::parse_optional_datetime(dto.scheduled_end_at.clone())?;
+        if let Some(end) = slot_end {
+            let Some(start) = slot_start else {
+                return Err(AppError::Validation(
+                    "Set the installation schedule before the slot end".to_string(),
+                ));
+            };
+            if end <= start || end - start > Duration::minutes(MAX_WORK_ORDER_SLOT_MINUTES) {
+                return Err(AppError::Validation(
+                    "Slot end must be after the start and within 24 hours".to_string(),
+                ));
+            }
+        }
 
-        let eligible = self
-            .is_installation_assignee_eligible(tenant_id, actor_id)
-            .await?;
-        if !eligible {
-            return Err(AppError::Forbidden(
-                "Only eligible installers can take installation work orders".to_string(),
-            ));
+        if let Some(start) = slot_start {
+            let end = slot_end.or_else(|| {
+                if slot_start == current.scheduled_at {
+                    current.scheduled_end_at
+                } else {
+                    moved_slot_end(current.scheduled_at, current.scheduled_end_at, slot_start)
+                }
+            });
+            let allow_overlap = is_admin_owner && dto.allow_overlap.unwrap_or(false);
+            if !allow_overlap {
+                self.ensure_technician_slot_free(
+                    tenant_id,
+                    assigned_to.trim(),
+                    work_order_id,
+                    start,
+                    end,
+                )
+                .await?;
+            }
         }
 
-        let current = self
-            .get_installation_work_order_row(tenant_id, work_order_id)
+        let row = self
+            .set_installation_work_order_status_internal(
+                actor_id,
+                tenant_id,
+                work_order_id,
+                if current.status == "pending" {
+                    Some("pending")
+                } else {
+                    None
+                },
+                Some(assigned_to),
+                dto.scheduled_at,
+                slot_end,
+                dto.notes,
+                false,
+                ip_address,
+                "WORK_ORDER_ASSIGN",
+                "Assigned installation work order",
+            )
             .await?;
-        if current.status != "pending" {
-            return Err(AppError::Validation(
-                "Only pending work order can be taken".to_string(),
-            ));
-        }
-        if let Some(assigned) = current
-            .assigned_to
+
+        self.notify_customer_schedule_change(tenant_id, &current, &row)
+            .await;
+        self.notify_work_order_assignee(actor_id, tenant_id, &current, &row)
+            .await;
+        Ok(row)
+    }
+
+    /// Slots on the dispatch calendar between `from` and `to` (default: the
+    /// next 7 days). Technicians only see their own calendar.
+    pub async fn list_work_order_agenda(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        from: Option<String>,
+        to: Option<String>,
+        technician_id: Option<String>,
+    ) -> AppResult<Vec<WorkOrderAgendaItem>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "work_orders", "read")
+            .await?;
+
+        let technician_id = technician_id
             .as_deref()
             .map(str::trim)
             .filter(|v| !v.is_empty())
-        {
-            if assigned != actor_id {
-                return Err(AppError::Conflict(
-                    "Work order already taken by another technician".to_string(),
+            .map(str::to_string);
+        let is_admin_owner = self.is_actor_admin_or_owner(tenant_id, actor_id).await?;
+        let technician_id = if is_admin_owner {
+            technician_id
+        } else {
+            if technician_id.as_deref().is_some_and(|t| t != actor_id) {
+                return Err(AppError::Forbidden(
+                    "Technician can only view own calendar".to_string(),
                 ));
             }
-            return Ok(current);
+            Some(actor_id.to_string())
+        };
+
+        let from = Self::parse_optional_datetime(from)?.unwrap_or_else(|| {
+            let today = Utc::now()
+                .date_naive()
+                .and_hms_opt(0, 0, 0)
+                .unwrap_or_default();
+            DateTime::<Utc>::from_naive_utc_and_offset(today, Utc)
+        });
+        let to = Self::parse_optional_datetime(to)?.unwrap_or(from + Duration::days(7));
+        if to <= from || to - from > Duration::days(MAX_AGENDA_RANGE_DAYS) {
+            return Err(AppError::Validation(format!(
+                "Calendar range must be positive and at most {} days",
+                MAX_AGENDA_RANGE_DAYS
+            )));
         }
 
-        let now = Utc::now();
+        let slot_minutes = self.resolve_work_order_slot_minutes(tenant_id).await;
+        let mut items = self
+            .scheduled_work_orders(tenant_id, technician_id.as_deref(), from, to, slot_minutes)
+            .await?;
+        mark_agenda_conflicts(&mut items);
+        Ok(items)
+    }
+
+    /// Order a technician's open work orders for one day (app timezone) by
+    /// travel distance. Work orders whose location has no coordinates are
+    /// returned separately.
+    pub async fn plan_technician_route(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        technician_id: Option<String>,
+        date: Option<String>,
+        start_latitude: Option<f64>,
+        start_longitude: Option<f64>,
+    ) -> AppResult<WorkOrderRoutePlan> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "work_orders", "read")
+            .await?;
+
+        let technician_id = technician_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string);
+        let technician_id = if self.is_actor_admin_or_owner(tenant_id, actor_id).await? {
+            technician_id
+                .ok_or_else(|| AppError::Validation("technician_id is required".to_string()))?
+        } else {
+            if technician_id.as_deref().is_some_and(|t| t != actor_id) {
+                return Err(AppError::Forbidden(
+                    "Technician can only plan own route".to_string(),
+                ));
+            }
+            actor_id.to_string()
+        };
+        let start = match (start_latitude, start_longitude) {
+            (None, None) => None,
+            (Some(lat), Some(lng))
+                if (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lng) =>
+            {
+                Some((lat, lng))
+            }
+            (Some(_), Some(_)) => {
+                return Err(AppError::Validation(
+                    "Latitude must be within -90..90 and longitude within -180..180".to_string(),
+                ))
+            }
+            _ => {
+                return Err(AppError::Validation(
+                    "Start point needs both latitude and longitude".to_string(),
+                ))
+            }
+        };
+
+        let tz = self
+            .read_global_setting_value("app_timezone")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| v.trim().parse::<chrono_tz::Tz>().ok())
+            .unwrap_or(chrono_tz::UTC);
+        let date = match date.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+            Some(raw) => chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d").map_err(|_| {
+                AppError::Validation("Invalid date format. Use YYYY-MM-DD".to_string())
+            })?,
+            None => Utc::now().with_timezone(&tz).date_naive(),
+        };
+        let day_start = |d: chrono::NaiveDate| {
+            d.and_hms_opt(0, 0, 0)
+                .and_then(|ndt| ndt.and_local_timezone(tz).earliest())
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok_or_else(|| AppError::Validation("Invalid date".to_string()))
+        };
+        let from = day_start(date)?;
+        let to = day_start(date + Duration::days(1))?;
 
         #[cfg(feature = "postgres")]
-        let affected = sqlx::query(
+        let technician_name: Option<Option<String>> = sqlx::query_scalar(
             r#"
-            UPDATE installation_work_orders
-            SET assigned_to = $1, updated_at = $2
-            WHERE tenant_id = $3
-              AND id = $4
-              AND status = 'pending'
-              AND (assigned_to IS NULL OR btrim(assigned_to) = '')
+            SELECT u.name FROM tenant_members tm JOIN users u ON u.id = tm.user_id
+            WHERE tm.tenant_id = $1 AND tm.user_id = $2
             "#,
         )
-        .bind(actor_id)
-        .bind(now)
         .bind(tenant_id)
-        .bind(work_order_id)
-        .execute(&self.pool)
-        .await?
-        .rows_affected();
+        .bind(&technician_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
         #[cfg(feature = "sqlite")]
-        let affected = sqlx::query(
+        let technician_name: Option<Option<String>> = sqlx::query_scalar(
             r#"
-            UPDATE installation_work_orders
-            SET assigned_to = ?, updated_at = ?
-            WHERE tenant_id = ?
-              AND id = ?
-              AND status = 'pending'
-              AND (assigned_to IS NULL OR trim(assigned_to) = '')
+            SELECT u.name FROM tenant_members tm JOIN users u ON u.id = tm.user_id
+            WHERE tm.tenant_id = ? AND tm.user_id = ?
             "#,
         )
-        .bind(actor_id)
-        .bind(now)
         .bind(tenant_id)
-        .bind(work_order_id)
-        .execute(&self.pool)
-        .await?
-        .rows_affected();
+        .bind(&technician_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
-        if affected == 0 {
-            return Err(AppError::Conflict(
-                "Work order already taken by another technician".to_string(),
-            ));
+        let technician_name = technician_name
+            .ok_or_else(|| AppError::NotFound("Technician not found".to_string()))?;
+
+        #[cfg(feature = "postgres")]
+        let rows: Vec<RouteRow> = sqlx::query_as(
+            r#"
+            SELECT
+              wo.id AS work_order_id, wo.status, wo.scheduled_at, wo.customer_id,
+              c.name AS customer_name, l.label AS location_label, l.address_line1, l.city,
+              l.latitude::float8 AS latitude, l.longitude::float8 AS longitude
+            FROM installation_work_orders wo
+            LEFT JOIN customers c ON c.tenant_id = wo.tenant_id AND c.id = wo.customer_id
+            LEFT JOIN customer_locations l ON l.tenant_id = wo.tenant_id AND l.id = wo.location_id
+            WHERE wo.tenant_id = $1
+              AND wo.assigned_to = $2
+              AND wo.status IN ('pending', 'in_progress')
+              AND wo.scheduled_at >= $3
+              AND wo.scheduled_at < $4
+            ORDER BY wo.scheduled_at ASC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&technician_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let rows: Vec<RouteRow> = sqlx::query_as(
+            r#"
+            SELECT
+              wo.id AS work_order_id, wo.status, wo.scheduled_at, wo.customer_id,
+              c.name AS customer_name, l.label AS location_label, l.address_line1, l.city,
+              l.latitude, l.longitude
+            FROM installation_work_orders wo
+            LEFT JOIN customers c ON c.tenant_id = wo.tenant_id AND c.id = wo.customer_id
+            LEFT JOIN customer_locations l ON l.tenant_id = wo.tenant_id AND l.id = wo.location_id
+            WHERE wo.tenant_id = ?
+              AND wo.assigned_to = ?
+              AND wo.status IN ('pending', 'in_progress')
+              AND wo.scheduled_at >= ?
+              AND wo.scheduled_at < ?
+            ORDER BY wo.scheduled_at ASC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&technician_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let (located, unlocated): (Vec<RouteRow>, Vec<RouteRow>) = rows
+            .into_iter()
+            .partition(|r| r.latitude.is_some() && r.longitude.is_some());
+        if located.len() > MAX_ROUTE_STOPS {
+            return Err(AppError::Validation(format!(
+                "Too many stops to plan ({}; max {})",
+                located.len(),
+                MAX_ROUTE_STOPS
+            )));
+        }
+        let points: Vec<(f64, f64)> = located
+            .iter()
+            .map(|r| {
+                (
+                    r.latitude.unwrap_or_default(),
+                    r.longitude.unwrap_or_default(),
+                )
+            })
+            .collect();
+        let order = plan_visit_order(start, &points);
+        let scheduled: Vec<usize> = (0..points.len()).collect();
+
+        let mut stops = Vec::with_capacity(order.len());
+        let mut prev = start;
+        let mut cumulative = 0.0;
+        for (seq, &i) in order.iter().enumerate() {
+            let r = &located[i];
+            let leg = prev.map(|p| haversine_m(p, points[i])).unwrap_or(0.0);
+            cumulative += leg;
+            prev = Some(points[i]);
+            let address = [r.address_line1.as_deref(), r.city.as_deref()]
+                .into_iter()
+                .flatten()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ");
+            stops.push(WorkOrderRouteStop {
+                sequence: seq as u32 + 1,
+                work_order_id: r.work_order_id.clone(),
+                status: r.status.clone(),
+                scheduled_at: r.scheduled_at,
+                customer_id: r.customer_id.clone(),
+                customer_name: r.customer_name.clone(),
+                location_label: r.location_label.clone(),
+                address: (!address.is_empty()).then_some(address),
+                latitude: points[i].0,
+                longitude: points[i].1,
+                leg_distance_m: leg,
+                cumulative_distance_m: cumulative,
+            });
         }
 
-        self.audit_service
-            .log(
-                Some(actor_id),
-                Some(tenant_id),
-                "WORK_ORDER_CLAIM",
-                "installation_work_orders",
-                Some(work_order_id),
-                Some("Technician took installation work order"),
-                ip_address,
-            )
-            .await;
+        // GeoJSON wants [longitude, latitude].
+        let coordinates: Vec<[f64; 2]> = start
+            .into_iter()
+            .chain(order.iter().map(|&i| points[i]))
+            .map(|(lat, lng)| [lng, lat])
+            .collect();
+        let path = (coordinates.len() >= 2)
+            .then(|| serde_json::json!({ "type": "LineString", "coordinates": coordinates }));
+
+        Ok(WorkOrderRoutePlan {
+            technician_id,
+            technician_name,
+            date,
+            start_latitude: start.map(|p| p.0),
+            start_longitude: start.map(|p| p.1),
+            total_distance_m: cumulative,
+            scheduled_order_distance_m: route_length_m(start, &points, &scheduled),
+            stops,
+            skipped: unlocated
+                .into_iter()
+                .map(|r| WorkOrderRouteSkipped {
+                    work_order_id: r.work_order_id,
+                    scheduled_at: r.scheduled_at,
+                    customer_name: r.customer_name,
+                    location_label: r.location_label,
+                })
+                .collect(),
+            path,
+        })
+    }
 
-        let mut row = self
-            .get_installation_work_order_row(tenant_id, work_order_id)
-            .await?;
-        if notes
-            .as_deref()
-            .map(str::trim)
-            .filter(|v| !v.is_empty())
-            .is_some()
+    async fn resolve_work_order_slot_minutes(&self, tenant_id: &str) -> i64 {
+        let raw = match self
+            .read_tenant_setting_value(tenant_id, WORK_ORDER_SLOT_MINUTES_KEY)
+            .await
         {
-            row = self
-                .set_installation_work_order_status_internal(
-                    actor_id,
-                    tenant_id,
-                    work_order_id,
-                    None,
-                    None,
-                    None,
-                    notes,
-                    false,
-                    ip_address,
-                    "WORK_ORDER_UPDATE_NOTE",
-                    "Updated work order notes while claiming",
-                )
-                .await?;
+            Ok(Some(v)) if !v.trim().is_empty() => Some(v),
+            _ => self
+                .read_global_setting_value(WORK_ORDER_SLOT_MINUTES_KEY)
+                .await
+                .ok()
+                .flatten(),
+        };
+        Self::parse_setting_i64(raw, 120, 15, MAX_WORK_ORDER_SLOT_MINUTES)
+    }
+
+    async fn auto_dismantle_enabled(&self, tenant_id: &str) -> bool {
+        let raw = match self
+            .read_tenant_setting_value(tenant_id, WORK_ORDER_AUTO_DISMANTLE_KEY)
+            .await
+        {
+            Ok(Some(v)) if !v.trim().is_empty() => Some(v),
+            _ => self
+                .read_global_setting_value(WORK_ORDER_AUTO_DISMANTLE_KEY)
+                .await
+                .ok()
+                .flatten(),
+        };
+        Self::parse_setting_bool(raw, true)
+    }
+
+    /// Non-cancelled work orders with a slot overlapping `[from, to)`, ordered by start.
+    async fn scheduled_work_orders(
+        &self,
+        tenant_id: &str,
+        technician_id: Option<&str>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        slot_minutes: i64,
+    ) -> AppResult<Vec<WorkOrderAgendaItem>> {
+        // Slots are at most a day long, so anything starting earlier can't reach `from`.
+        let earliest = from - Duration::minutes(MAX_WORK_ORDER_SLOT_MINUTES);
+
+        #[cfg(feature = "postgres")]
+        let rows: Vec<AgendaRow> = sqlx::query_as(
+            r#"
+            SELECT
+              wo.id AS work_order_id, wo.status, wo.assigned_to, u.name AS assigned_to_name,
+              wo.scheduled_at, wo.scheduled_end_at, wo.customer_id,
+              c.name AS customer_name, l.label AS location_label, p.name AS package_name
+            FROM installation_work_orders wo
+            LEFT JOIN users u ON u.id = wo.assigned_to
+            LEFT JOIN customers c ON c.tenant_id = wo.tenant_id AND c.id = wo.customer_id
+            LEFT JOIN customer_locations l ON l.tenant_id = wo.tenant_id AND l.id = wo.location_id
+            LEFT JOIN customer_subscriptions cs ON cs.tenant_id = wo.tenant_id AND cs.id = wo.subscription_id
+            LEFT JOIN isp_packages p ON p.tenant_id = wo.tenant_id AND p.id = cs.package_id
+            WHERE wo.tenant_id = $1
+              AND wo.status <> 'cancelled'
+              AND wo.scheduled_at IS NOT NULL
+              AND wo.scheduled_at >= $2
+              AND wo.scheduled_at < $3
+              AND ($4::text IS NULL OR wo.assigned_to = $4)
+            ORDER BY wo.scheduled_at ASC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(earliest)
+        .bind(to)
+        .bind(technician_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let rows: Vec<AgendaRow> = sqlx::query_as(
+            r#"
+            SELECT
+              wo.id AS work_order_id, wo.status, wo.assigned_to, u.name AS assigned_to_name,
+              wo.scheduled_at, wo.scheduled_end_at, wo.customer_id,
+              c.name AS customer_name, l.label AS location_label, p.name AS package_name
+            FROM installation_work_orders wo
+            LEFT JOIN users u ON u.id = wo.assigned_to
+            LEFT JOIN customers c ON c.tenant_id = wo.tenant_id AND c.id = wo.customer_id
+            LEFT JOIN customer_locations l ON l.tenant_id = wo.tenant_id AND l.id = wo.location_id
+            LEFT JOIN customer_subscriptions cs ON cs.tenant_id = wo.tenant_id AND cs.id = wo.subscription_id
+            LEFT JOIN isp_packages p ON p.tenant_id = wo.tenant_id AND p.id = cs.package_id
+            WHERE wo.tenant_id = ?
+              AND wo.status <> 'cancelled'
+              AND wo.scheduled_at IS NOT NULL
+              AND wo.scheduled_at >= ?
+              AND wo.scheduled_at < ?
+              AND (? IS NULL OR wo.assigned_to = ?)
+            ORDER BY wo.scheduled_at ASC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(earliest)
+        .bind(to)
+        .bind(technician_id)
+        .bind(technician_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| WorkOrderAgendaItem {
+                scheduled_end_at: work_order_slot_end(
+                    r.scheduled_at,
+                    r.scheduled_end_at,
+                    slot_minutes,
+                ),
+                work_order_id: r.work_order_id,
+                status: r.status,
+                assigned_to: r.assigned_to,
+                assigned_to_name: r.assigned_to_name,
+                scheduled_at: r.scheduled_at,
+                customer_id: r.customer_id,
+                customer_name: r.customer_name,
+                location_label: r.location_label,
+                package_name: r.package_name,
+                has_conflict: false,
+            })
+            .filter(|i| slots_overlap((i.scheduled_at, i.scheduled_end_at), (from, to)))
+            .collect())
+    }
+
+    /// Reject a booking that overlaps the technician's other pending or
+    /// in-progress work orders.
+    async fn ensure_technician_slot_free(
+        &self,
+        tenant_id: &str,
+        technician_id: &str,
+        work_order_id: &str,
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+    ) -> AppResult<()> {
+        if technician_id.is_empty() {
+            return Ok(());
+        }
+        let slot_minutes = self.resolve_work_order_slot_minutes(tenant_id).await;
+        let end = work_order_slot_end(start, end, slot_minutes);
+        let clashes: Vec<String> = self
+            .scheduled_work_orders(tenant_id, Some(technician_id), start, end, slot_minutes)
+            .await?
+            .into_iter()
+            .filter(|i| {
+                i.work_order_id != work_order_id
+                    && (i.status == "pending" || i.status == "in_progress")
+            })
+            .map(|i| {
+                format!(
+                    "{} ({} - {})",
+                    i.customer_name.unwrap_or(i.work_order_id),
+                    i.scheduled_at.format("%Y-%m-%d %H:%M"),
+                    i.scheduled_end_at.format("%H:%M UTC")
+                )
+            })
+            .collect();
+        if clashes.is_empty() {
+            return Ok(());
+        }
+        Err(AppError::Conflict(format!(
+            "Technician is already booked at that time: {}",
+            clashes.join(", ")
+        )))
+    }
+
+    /// Tell the customer their installation was scheduled or moved. Best effort.
+    async fn notify_customer_schedule_change(
+        &self,
+        tenant_id: &str,
+        before: &InstallationWorkOrder,
+        after: &InstallationWorkOrder,
+    ) {
+        let Some(start) = after.scheduled_at else {
+            return;
+        };
+        if before.scheduled_at == after.scheduled_at
+            && before.scheduled_end_at == after.scheduled_end_at
+        {
+            return;
+        }
+        let user_ids = match self
+            .list_customer_user_ids_for_subscription(tenant_id, &after.subscription_id)
+            .await
+        {
+            Ok(ids) => ids,
+            Err(err) => {
+                warn!(
+                    "failed to load customer users for schedule notification: tenant_id={}, work_order_id={}, error={}",
+                    tenant_id, after.id, err
+                );
+                return;
+            }
+        };
+
+        let slot_minutes = self.resolve_work_order_slot_minutes(tenant_id).await;
+        let end = work_order_slot_end(start, after.scheduled_end_at, slot_minutes);
+        let (title, verb) = if before.scheduled_at.is_none() {
+            ("Installation Scheduled", "scheduled")
+        } else {
+            ("Installation Rescheduled", "rescheduled")
+        };
+        let message = format!(
+            "Your installation has been {} for {} - {}.",
+            verb,
+            start.format("%Y-%m-%d %H:%M"),
+            end.format("%H:%M UTC")
+        );
+
+        for user_id in user_ids {
+            if let Err(err) = self
+                .notification_service
+                .create_notification(
+                    user_id,
+                    Some(tenant_id.to_string()),
+                    title.to_string(),
+                    message.clone(),
+                    "info".to_string(),
+                    "operations".to_string(),
+                    Some("/dashboard/services".to_string()),
+                )
+                .await
+            {
+                warn!(
+                    "failed to send installation schedule notification: tenant_id={}, work_order_id={}, error={}",
+                    tenant_id, after.id, err
+                );
+            }
+        }
+    }
+
+    /// Tell the new assignee a work order was handed to them. Best effort;
+    /// taking a work order yourself sends nothing.
+    async fn notify_work_order_assignee(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        before: &InstallationWorkOrder,
+        after: &InstallationWorkOrder,
+    ) {
+        let Some(assignee) = after.assigned_to.as_deref().map(str::trim) else {
+            return;
+        };
+        if assignee.is_empty()
+            || assignee == actor_id
+            || before.assigned_to.as_deref().map(str::trim) == Some(assignee)
+        {
+            return;
+        }
+
+        let message = match after.scheduled_at {
+            Some(start) => format!(
+                "A {} work order was assigned to you for {}.",
+                after.work_type,
+                start.format("%Y-%m-%d %H:%M UTC")
+            ),
+            None => format!("A {} work order was assigned to you.", after.work_type),
+        };
+        if let Err(err) = self
+            .notification_service
+            .create_notification(
+                assignee.to_string(),
+                Some(tenant_id.to_string()),
+                "Work Order Assigned".to_string(),
+                message,
+                "info".to_string(),
+                "operations".to_string(),
+                Some("/admin/network/installations".to_string()),
+            )
+            .await
+        {
+            warn!(
+                "failed to send work order assignment notification: tenant_id={}, work_order_id={}, error={}",
+                tenant_id, after.id, err
+            );
+        }
+    }
+
+    pub async fn claim_installation_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        notes: Option<String>,
+        ip_address: Option<&str>,
+    ) -> AppResult<InstallationWorkOrder> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "work_orders", "manage")
+            .await?;
+
+        let eligible = self
+            .is_installation_assignee_eligible(tenant_id, actor_id)
+            .await?;
+        if !eligible {
+            return Err(AppError::Forbidden(
+                "Only eligible installers can take installation work orders".to_string(),
+            ));
+        }
+
+        let current = self
+            .get_installation_work_order_row(tenant_id, work_order_id)
+            .await?;
+        if current.status != "pending" {
+            return Err(AppError::Validation(
+                "Only pending work order can be taken".to_string(),
+            ));
+        }
+        if let Some(assigned) = current
+            .assigned_to
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        {
+            if assigned != actor_id {
+                return Err(AppError::Conflict(
+                    "Work order already taken by another technician".to_string(),
+                ));
+            }
+            return Ok(current);
+        }
+
+        let now = Utc::now();
+
+        #[cfg(feature = "postgres")]
+        let affected = sqlx::query(
+            r#"
+            UPDATE installation_work_orders
+            SET assigned_to = $1, updated_at = $2
+            WHERE tenant_id = $3
+              AND id = $4
+              AND status = 'pending'
+              AND (assigned_to IS NULL OR btrim(assigned_to) = '')
+            "#,
+        )
+        .bind(actor_id)
+        .bind(now)
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(feature = "sqlite")]
+        let affected = sqlx::query(
+            r#"
+            UPDATE installation_work_orders
+            SET assigned_to = ?, updated_at = ?
+            WHERE tenant_id = ?
+              AND id = ?
+              AND status = 'pending'
+              AND (assigned_to IS NULL OR trim(assigned_to) = '')
+            "#,
+        )
+        .bind(actor_id)
+        .bind(now)
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if affected == 0 {
+            return Err(AppError::Conflict(
+                "Work order already taken by another technician".to_string(),
+            ));
+        }
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WORK_ORDER_CLAIM",
+                "installation_work_orders",
+                Some(work_order_id),
+                Some("Technician took installation work order"),
+                ip_address,
+            )
+            .await;
+
+        let mut row = self
+            .get_installation_work_order_row(tenant_id, work_order_id)
+            .await?;
+        if notes
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .is_some()
+        {
+            row = self
+                .set_installation_work_order_status_internal(
+                    actor_id,
+                    tenant_id,
+                    work_order_id,
+                    None,
+                    None,
+                    None,
+                    None,
+                    notes,
+                    false,
+                    ip_address,
+                    "WORK_ORDER_UPDATE_NOTE",
+                    "Updated work order notes while claiming",
+                )
+                .await?;
+        }
+
+        Ok(row)
+    }
+
+    pub async fn release_installation_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        notes: Option<String>,
+        ip_address: Option<&str>,
+    ) -> AppResult<InstallationWorkOrder> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "work_orders", "manage")
+            .await?;
+
+        if !self.is_actor_admin_or_owner(tenant_id, actor_id).await? {
+            return Err(AppError::Forbidden(
+                "Only admin/owner can release installation work orders".to_string(),
+            ));
+        }
+
+        let current = self
+            .get_installation_work_order_row(tenant_id, work_order_id)
+            .await?;
+        if current.status != "pending" {
+            return Err(AppError::Validation(
+                "Only pending work order can be released".to_string(),
+            ));
+        }
+
+        // Release means making assignment empty and clearing schedule so next assignee can re-plan.
+        self.set_installation_work_order_status_internal(
+            actor_id,
+            tenant_id,
+            work_order_id,
+            Some("pending"),
+            Some(""),
+            Some("".to_string()),
+            None,
+            notes,
+            false,
+            ip_address,
+            "WORK_ORDER_RELEASE",
+            "Released installation work order assignment",
+        )
+        .await
+    }
+
+    pub async fn start_installation_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        notes: Option<String>,
+        ip_address: Option<&str>,
+    ) -> AppResult<InstallationWorkOrder> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "work_orders", "manage")
+            .await?;
+
+        let is_admin = self.is_actor_admin_or_owner(tenant_id, actor_id).await?;
+        if !is_admin {
+            let current = self
+                .get_installation_work_order_row(tenant_id, work_order_id)
+                .await?;
+            let assigned = current.assigned_to.as_deref().map(str::trim).unwrap_or("");
+            if assigned != actor_id {
+                return Err(AppError::Forbidden(
+                    "Technician can only start own assigned work order".to_string(),
+                ));
+            }
+        }
+
+        self.set_installation_work_order_status_internal(
+            actor_id,
+            tenant_id,
+            work_order_id,
+            Some("in_progress"),
+            None,
+            None,
+            None,
+            notes,
+            false,
+            ip_address,
+            "WORK_ORDER_START",
+            "Started installation work order",
+        )
+        .await
+    }
+
+    pub async fn complete_installation_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        notes: Option<String>,
+        ip_address: Option<&str>,
+    ) -> AppResult<InstallationWorkOrder> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "work_orders", "manage")
+            .await?;
+
+        let is_admin = self.is_actor_admin_or_owner(tenant_id, actor_id).await?;
+        if !is_admin {
+            let current = self
+                .get_installation_work_order_row(tenant_id, work_order_id)
+                .await?;
+            let assigned = current.assigned_to.as_deref().map(str::trim).unwrap_or("");
+            if assigned != actor_id {
+                return Err(AppError::Forbidden(
+                    "Technician can only complete own assigned work order".to_string(),
+                ));
+            }
+        }
+        self.checklists
+            .ensure_ready_to_complete(tenant_id, work_order_id)
+            .await?;
+
+        let row = self
+            .set_installation_work_order_status_internal(
+                actor_id,
+                tenant_id,
+                work_order_id,
+                Some("completed"),
+                None,
+                None,
+                None,
+                notes,
+                false,
+                ip_address,
+                "WORK_ORDER_COMPLETE",
+                "Completed installation work order",
+            )
+            .await?;
+
+        if row.work_type != "installation" {
+            self.finish_field_work_order(actor_id, tenant_id, &row, ip_address)
+                .await?;
+            return Ok(row);
+        }
+
+        #[cfg(feature = "postgres")]
+        let sub: Option<CustomerSubscription> = sqlx::query_as(
+            "SELECT id, tenant_id, customer_id, location_id, package_id, router_id, billing_cycle, price::float8 as price, currency_code, status, starts_at, ends_at, notes, created_at, updated_at FROM customer_subscriptions WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(&row.subscription_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let sub: Option<CustomerSubscription> = sqlx::query_as(
+            "SELECT id, tenant_id, customer_id, location_id, package_id, router_id, billing_cycle, price as price, currency_code, status, starts_at, ends_at, notes, created_at, updated_at FROM customer_subscriptions WHERE tenant_id = ? AND id = ?",
+        )
+        .bind(tenant_id)
+        .bind(&row.subscription_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(mut s) = sub {
+            if s.status != "cancelled" {
+                let now = Utc::now();
+                let has_paid_invoice = self
+                    .has_paid_customer_package_invoice_for_subscription(tenant_id, &s.id)
+                    .await?;
+
+                if has_paid_invoice {
+                    s.status = "active".to_string();
+                    if s.starts_at.is_none() {
+                        s.starts_at = Some(now);
+                    }
+                    s.updated_at = now;
+
+                    #[cfg(feature = "postgres")]
+                    sqlx::query(
+                        r#"
+                        UPDATE customer_subscriptions
+                        SET status = 'active',
+                            starts_at = COALESCE(starts_at, $1),
+                            updated_at = $2
+                        WHERE tenant_id = $3 AND id = $4
+                        "#,
+                    )
+                    .bind(now)
+                    .bind(s.updated_at)
+                    .bind(tenant_id)
+                    .bind(&s.id)
+                    .execute(&self.pool)
+                    .await?;
+
+                    #[cfg(feature = "sqlite")]
+                    sqlx::query(
+                        r#"
+                        UPDATE customer_subscriptions
+                        SET status = 'active',
+                            starts_at = COALESCE(starts_at, ?),
+                            updated_at = ?
+                        WHERE tenant_id = ? AND id = ?
+                        "#,
+                    )
+                    .bind(now.to_rfc3339())
+                    .bind(s.updated_at)
+                    .bind(tenant_id)
+                    .bind(&s.id)
+                    .execute(&self.pool)
+                    .await?;
+
+                    let _ = self
+                        .set_location_pppoe_disabled_state(tenant_id, &s.location_id, false)
+                        .await;
+                } else {
+                    s.status = "pending_installation".to_string();
+                    s.updated_at = now;
+                    self.set_customer_subscription_status(tenant_id, &s.id, "pending_installation")
+                        .await?;
+                    let _ = self
+                        .set_location_pppoe_disabled_state(tenant_id, &s.location_id, true)
+                        .await;
+                }
+
+                let _ = self
+                    .auto_provision_pppoe_for_subscription(actor_id, tenant_id, &s, ip_address)
+                    .await;
+            }
+        }
+
+        Ok(row)
+    }
+
+    /// Applies what a completed repair, relocation or dismantle means for the
+    /// subscription. Repairs leave it untouched.
+    async fn finish_field_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        row: &InstallationWorkOrder,
+        ip_address: Option<&str>,
+    ) -> AppResult<()> {
+        match row.work_type.as_str() {
+            "relocation" => {
+                let Some(target) = row
+                    .details
+                    .get("target_location_id")
+                    .and_then(|v| v.as_str())
+                else {
+                    return Ok(());
+                };
+                let now = Utc::now();
+
+                #[cfg(feature = "postgres")]
+                sqlx::query(
+                    "UPDATE customer_subscriptions SET location_id = $1, updated_at = $2 WHERE tenant_id = $3 AND id = $4",
+                )
+                .bind(target)
+                .bind(now)
+                .bind(tenant_id)
+                .bind(&row.subscription_id)
+                .execute(&self.pool)
+                .await?;
+
+                #[cfg(feature = "sqlite")]
+                sqlx::query(
+                    "UPDATE customer_subscriptions SET location_id = ?, updated_at = ? WHERE tenant_id = ? AND id = ?",
+                )
+                .bind(target)
+                .bind(now.to_rfc3339())
+                .bind(tenant_id)
+                .bind(&row.subscription_id)
+                .execute(&self.pool)
+                .await?;
+
+                self.audit_service
+                    .log(
+                        Some(actor_id),
+                        Some(tenant_id),
+                        "SUBSCRIPTION_RELOCATE",
+                        "customer_subscriptions",
+                        Some(&row.subscription_id),
+                        Some(&format!(
+                            "Moved subscription from location {} to {} (work order {})",
+                            row.location_id, target, row.id
+                        )),
+                        ip_address,
+                    )
+                    .await;
+            }
+            "dismantle" => {
+                if let Err(err) = self
+                    .set_location_pppoe_disabled_state(tenant_id, &row.location_id, true)
+                    .await
+                {
+                    warn!(
+                        "failed to disable PPPoE after dismantle: tenant_id={}, work_order_id={}, error={}",
+                        tenant_id, row.id, err
+                    );
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Gate for work order sub-resources (checklist, materials): reading needs
+    /// `work_orders:read`; writing needs `work_orders:manage` and, for
+    /// technicians, being the assignee.
+    pub async fn authorize_work_order_access(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        write: bool,
+    ) -> AppResult<()> {
+        let action = if write { "manage" } else { "read" };
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "work_orders", action)
+            .await?;
+        let current = self
+            .get_installation_work_order_row(tenant_id, work_order_id)
+            .await?;
+        if write && !self.is_actor_admin_or_owner(tenant_id, actor_id).await? {
+            let assigned = current.assigned_to.as_deref().map(str::trim).unwrap_or("");
+            if assigned != actor_id {
+                return Err(AppError::Forbidden(
+                    "Technician can only update own assigned work order".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn cancel_installation_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        notes: Option<String>,
+        ip_address: Option<&str>,
+    ) -> AppResult<InstallationWorkOrder> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "work_orders", "manage")
+            .await?;
+
+        if !self.is_actor_admin_or_owner(tenant_id, actor_id).await? {
+            return Err(AppError::Forbidden(
+                "Only admin/owner can cancel installation work orders".to_string(),
+            ));
+        }
+
+        let reason = notes
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| {
+                AppError::Validation(
+                    "Cancellation reason is required (minimum 10 characters)".to_string(),
+                )
+            })?
+            .to_string();
+
+        if reason.chars().count() < 10 {
+            return Err(AppError::Validation(
+                "Cancellation reason is too short (minimum 10 characters)".to_string(),
+            ));
+        }
+
+        let row = self
+            .set_installation_work_order_status_internal(
+                actor_id,
+                tenant_id,
+                work_order_id,
+                Some("cancelled"),
+                None,
+                None,
+                None,
+                notes,
+                false,
+                ip_address,
+                "WORK_ORDER_CANCEL",
+                "Cancelled installation work order",
+            )
+            .await?;
+        if row.work_type != "installation" {
+            return Ok(row);
+        }
+
+        self.set_customer_subscription_status(tenant_id, &row.subscription_id, "cancelled")
+            .await?;
+
+        if let Err(err) = self
+            .notify_customer_installation_cancelled(tenant_id, &row.subscription_id, &reason)
+            .await
+        {
+            warn!(
+                "failed to send installation cancellation notification: tenant_id={}, work_order_id={}, error={}",
+                tenant_id, row.id, err
+            );
+        }
+
+        Ok(row)
+    }
+
+    pub async fn reopen_installation_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        notes: Option<String>,
+        ip_address: Option<&str>,
+    ) -> AppResult<InstallationWorkOrder> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "work_orders", "manage")
+            .await?;
+
+        let row = self
+            .set_installation_work_order_status_internal(
+                actor_id,
+                tenant_id,
+                work_order_id,
+                Some("pending"),
+                None,
+                None,
+                None,
+                notes,
+                true,
+                ip_address,
+                "WORK_ORDER_REOPEN",
+                "Reopened installation work order",
+            )
+            .await?;
+        if row.work_type != "installation" {
+            return Ok(row);
         }
 
+        self.set_customer_subscription_status(
+            tenant_id,
+            &row.subscription_id,
+            "pending_installation",
+        )
+        .await?;
+
         Ok(row)
     }
 
-    pub async fn release_installation_work_order(
+    /// Opens a repair, relocation or dismantle work order for a subscription.
+    pub async fn create_work_order(
         &self,
         actor_id: &str,
         tenant_id: &str,
-        work_order_id: &str,
-        notes: Option<String>,
+        dto: CreateWorkOrderRequest,
         ip_address: Option<&str>,
     ) -> AppResult<InstallationWorkOrder> {
         self.auth_service
             .check_permission(actor_id, tenant_id, "work_orders", "manage")
             .await?;
 
-        if !self.is_actor_admin_or_owner(tenant_id, actor_id).await? {
-            return Err(AppError::Forbidden(
-                "Only admin/owner can release installation work orders".to_string(),
+        let (work_type, details) = work_order_details(&dto)?;
+        let sub = self
+            .get_customer_subscription_row(tenant_id, dto.subscription_id.trim())
+            .await?;
+        if sub.status == "cancelled" && work_type != "dismantle" {
+            return Err(AppError::Validation(
+                "Subscription is cancelled; only a dismantle work order can be created".to_string(),
             ));
         }
 
-        let current = self
-            .get_installation_work_order_row(tenant_id, work_order_id)
+        if let Some(target) = details.get("target_location_id").and_then(|v| v.as_str()) {
+            if target == sub.location_id {
+                return Err(AppError::Validation(
+                    "New location must differ from the current one".to_string(),
+                ));
+            }
+
+            #[cfg(feature = "postgres")]
+            let owned: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM customer_locations WHERE tenant_id = $1 AND customer_id = $2 AND id = $3)",
+            )
+            .bind(tenant_id)
+            .bind(&sub.customer_id)
+            .bind(target)
+            .fetch_one(&self.pool)
             .await?;
-        if current.status != "pending" {
+
+            #[cfg(feature = "sqlite")]
+            let owned: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM customer_locations WHERE tenant_id = ? AND customer_id = ? AND id = ?)",
+            )
+            .bind(tenant_id)
+            .bind(&sub.customer_id)
+            .bind(target)
+            .fetch_one(&self.pool)
+            .await?;
+
+            if !owned {
+                return Err(AppError::Validation(
+                    "New location does not belong to this customer".to_string(),
+                ));
+            }
+        }
+
+        if let Some(ticket_id) = details.get("support_ticket_id").and_then(|v| v.as_str()) {
+            #[cfg(feature = "postgres")]
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM support_tickets WHERE tenant_id = $1 AND id = $2)",
+            )
+            .bind(tenant_id)
+            .bind(ticket_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+            #[cfg(feature = "sqlite")]
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM support_tickets WHERE tenant_id = ? AND id = ?)",
+            )
+            .bind(tenant_id)
+            .bind(ticket_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+            if !exists {
+                return Err(AppError::NotFound("Support ticket not found".to_string()));
+            }
+        }
+
+        self.insert_work_order(
+            actor_id, tenant_id, &sub, &work_type, details, dto.notes, ip_address,
+        )
+        .await
+    }
+
+    /// Pauses an in-progress repair. Resuming goes through
+    /// [`Self::start_installation_work_order`].
+    pub async fn hold_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        notes: Option<String>,
+        ip_address: Option<&str>,
+    ) -> AppResult<InstallationWorkOrder> {
+        if notes.as_deref().map(str::trim).unwrap_or("").is_empty() {
             return Err(AppError::Validation(
-                "Only pending work order can be released".to_string(),
+                "Explain why the work order is on hold".to_string(),
             ));
         }
+        self.authorize_work_order_access(actor_id, tenant_id, work_order_id, true)
+            .await?;
 
-        // Release means making assignment empty and clearing schedule so next assignee can re-plan.
         self.set_installation_work_order_status_internal(
             actor_id,
             tenant_id,
             work_order_id,
-            Some("pending"),
-            Some(""),
-            Some("".to_string()),
+            Some("on_hold"),
+            None,
+            None,
+            None,
             notes,
             false,
             ip_address,
-            "WORK_ORDER_RELEASE",
-            "Released installation work order assignment",
+            "WORK_ORDER_HOLD",
+            "Put repair work order on hold",
         )
         .await
     }
 
-    pub async fn start_installation_work_order(
+    /// Appends a note without changing status or assignment.
+    pub async fn add_work_order_note(
         &self,
         actor_id: &str,
         tenant_id: &str,
         work_order_id: &str,
-        notes: Option<String>,
+        note: &str,
         ip_address: Option<&str>,
     ) -> AppResult<InstallationWorkOrder> {
-        self.auth_service
-            .check_permission(actor_id, tenant_id, "work_orders", "manage")
-            .await?;
-
-        let is_admin = self.is_actor_admin_or_owner(tenant_id, actor_id).await?;
-        if !is_admin {
-            let current = self
-                .get_installation_work_order_row(tenant_id, work_order_id)
-                .await?;
-            let assigned = current.assigned_to.as_deref().map(str::trim).unwrap_or("");
-            if assigned != actor_id {
-                return Err(AppError::Forbidden(
-                    "Technician can only start own assigned work order".to_string(),
-                ));
-            }
+        if note.trim().is_empty() {
+            return Err(AppError::Validation("Note is empty".to_string()));
         }
+        self.authorize_work_order_access(actor_id, tenant_id, work_order_id, true)
+            .await?;
 
         self.set_installation_work_order_status_internal(
             actor_id,
             tenant_id,
             work_order_id,
-            Some("in_progress"),
             None,
             None,
-            notes,
+            None,
+            None,
+            Some(note.to_string()),
             false,
             ip_address,
-            "WORK_ORDER_START",
-            "Started installation work order",
+            "WORK_ORDER_NOTE",
+            "Added work order note",
         )
         .await
     }
 
-    pub async fn complete_installation_work_order(
+    /// Check-ins and check-outs recorded against a work order, newest first.
+    pub async fn list_work_order_visits(
         &self,
         actor_id: &str,
         tenant_id: &str,
         work_order_id: &str,
-        notes: Option<String>,
-        ip_address: Option<&str>,
-    ) -> AppResult<InstallationWorkOrder> {
+    ) -> AppResult<Vec<WorkOrderVisit>> {
         self.auth_service
-            .check_permission(actor_id, tenant_id, "work_orders", "manage")
+            .check_permission(actor_id, tenant_id, "work_orders", "read")
             .await?;
-
-        let is_admin = self.is_actor_admin_or_owner(tenant_id, actor_id).await?;
-        if !is_admin {
-            let current = self
-                .get_installation_work_order_row(tenant_id, work_order_id)
-                .await?;
-            let assigned = current.assigned_to.as_deref().map(str::trim).unwrap_or("");
-            if assigned != actor_id {
-                return Err(AppError::Forbidden(
-                    "Technician can only complete own assigned work order".to_string(),
-                ));
-            }
-        }
-
-        let row = self
-            .set_installation_work_order_status_internal(
-                actor_id,
-                tenant_id,
-                work_order_id,
-                Some("completed"),
-                None,
-                None,
-                notes,
-                false,
-                ip_address,
-                "WORK_ORDER_COMPLETE",
-                "Completed installation work order",
-            )
+        self.get_installation_work_order_row(tenant_id, work_order_id)
             .await?;
 
         #[cfg(feature = "postgres")]
-        let sub: Option<CustomerSubscription> = sqlx::query_as(
-            "SELECT id, tenant_id, customer_id, location_id, package_id, router_id, billing_cycle, price::float8 as price, currency_code, status, starts_at, ends_at, notes, created_at, updated_at FROM customer_subscriptions WHERE tenant_id = $1 AND id = $2",
+        let rows: Vec<WorkOrderVisit> = sqlx::query_as(
+            r#"
+            SELECT v.id, v.work_order_id, v.technician_id, u.name AS technician_name,
+                   v.check_in_at, v.check_in_latitude, v.check_in_longitude,
+                   v.check_in_accuracy_m, v.check_in_distance_m,
+                   v.check_out_at, v.check_out_latitude, v.check_out_longitude,
+                   v.check_out_accuracy_m, v.check_out_distance_m, v.notes
+            FROM installation_work_order_visits v
+            LEFT JOIN users u ON u.id = v.technician_id
+            WHERE v.tenant_id = $1 AND v.work_order_id = $2
+            ORDER BY v.check_in_at DESC
+            "#,
         )
         .bind(tenant_id)
-        .bind(&row.subscription_id)
-        .fetch_optional(&self.pool)
+        .bind(work_order_id)
+        .fetch_all(&self.pool)
         .await?;
 
         #[cfg(feature = "sqlite")]
-        let sub: Option<CustomerSubscription> = sqlx::query_as(
-            "SELECT id, tenant_id, customer_id, location_id, package_id, router_id, billing_cycle, price as price, currency_code, status, starts_at, ends_at, notes, created_at, updated_at FROM customer_subscriptions WHERE tenant_id = ? AND id = ?",
+        let rows: Vec<WorkOrderVisit> = sqlx::query_as(
+            r#"
+            SELECT v.id, v.work_order_id, v.technician_id, u.name AS technician_name,
+                   v.check_in_at, v.check_in_latitude, v.check_in_longitude,
+                   v.check_in_accuracy_m, v.check_in_distance_m,
+                   v.check_out_at, v.check_out_latitude, v.check_out_longitude,
+                   v.check_out_accuracy_m, v.check_out_distance_m, v.notes
+            FROM installation_work_order_visits v
+            LEFT JOIN users u ON u.id = v.technician_id
+            WHERE v.tenant_id = ? AND v.work_order_id = ?
+            ORDER BY v.check_in_at DESC
+            "#,
         )
         .bind(tenant_id)
-        .bind(&row.subscription_id)
-        .fetch_optional(&self.pool)
+        .bind(work_order_id)
+        .fetch_all(&self.pool)
         .await?;
 
-        if let Some(mut s) = sub {
-            if s.status != "cancelled" {
-                let now = Utc::now();
-                let has_paid_invoice = self
-                    .has_paid_customer_package_invoice_for_subscription(tenant_id, &s.id)
-                    .await?;
+        Ok(rows)
+    }
 
-                if has_paid_invoice {
-                    s.status = "active".to_string();
-                    if s.starts_at.is_none() {
-                        s.starts_at = Some(now);
-                    }
-                    s.updated_at = now;
+    /// Record that the technician arrived on site. Only the assignee (or an
+    /// admin/owner) can check in, and only one visit can be open at a time.
+    pub async fn check_in_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        dto: WorkOrderCheckRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<WorkOrderVisit> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "work_orders", "manage")
+            .await?;
+        validate_gps_fix(&dto)?;
+        let at = visit_fix_time(dto.recorded_at.as_deref(), Utc::now())?;
 
-                    #[cfg(feature = "postgres")]
-                    sqlx::query(
-                        r#"
-                        UPDATE customer_subscriptions
-                        SET status = 'active',
-                            starts_at = COALESCE(starts_at, $1),
-                            updated_at = $2
-                        WHERE tenant_id = $3 AND id = $4
-                        "#,
-                    )
-                    .bind(now)
-                    .bind(s.updated_at)
-                    .bind(tenant_id)
-                    .bind(&s.id)
-                    .execute(&self.pool)
-                    .await?;
+        let current = self
+            .get_installation_work_order_row(tenant_id, work_order_id)
+            .await?;
+        let is_admin = self.is_actor_admin_or_owner(tenant_id, actor_id).await?;
+        if !is_admin && current.assigned_to.as_deref().map(str::trim) != Some(actor_id) {
+            return Err(AppError::Forbidden(
+                "Technician can only check in to own assigned work order".to_string(),
+            ));
+        }
+        if current.status != "pending" && current.status != "in_progress" {
+            return Err(AppError::Validation(format!(
+                "Cannot check in to a {} work order",
+                current.status
+            )));
+        }
+        if self
+            .open_work_order_visit_id(tenant_id, work_order_id)
+            .await?
+            .is_some()
+        {
+            return Err(AppError::Conflict(
+                "Work order already has an open visit; check out first".to_string(),
+            ));
+        }
 
-                    #[cfg(feature = "sqlite")]
-                    sqlx::query(
-                        r#"
-                        UPDATE customer_subscriptions
-                        SET status = 'active',
-                            starts_at = COALESCE(starts_at, ?),
-                            updated_at = ?
-                        WHERE tenant_id = ? AND id = ?
-                        "#,
-                    )
-                    .bind(now.to_rfc3339())
-                    .bind(s.updated_at)
-                    .bind(tenant_id)
-                    .bind(&s.id)
-                    .execute(&self.pool)
-                    .await?;
+        let distance_m = self
+            .work_order_location_coords(tenant_id, &current.location_id)
+            .await?
+            .map(|site| haversine_m(site, (dto.latitude, dto.longitude)));
+        let notes = dto
+            .notes
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+        let id = Uuid::new_v4().to_string();
 
-                    let _ = self
-                        .set_location_pppoe_disabled_state(tenant_id, &s.location_id, false)
-                        .await;
-                } else {
-                    s.status = "pending_installation".to_string();
-                    s.updated_at = now;
-                    self.set_customer_subscription_status(tenant_id, &s.id, "pending_installation")
-                        .await?;
-                    let _ = self
-                        .set_location_pppoe_disabled_state(tenant_id, &s.location_id, true)
-                        .await;
-                }
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            r#"
+            INSERT INTO installation_work_order_visits
+              (id, tenant_id, work_order_id, technician_id, check_in_at, check_in_latitude,
+               check_in_longitude, check_in_accuracy_m, check_in_distance_m, notes)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .bind(actor_id)
+        .bind(at)
+        .bind(dto.latitude)
+        .bind(dto.longitude)
+        .bind(dto.accuracy_m)
+        .bind(distance_m)
+        .bind(&notes)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query(
+            r#"
+            INSERT INTO installation_work_order_visits
+              (id, tenant_id, work_order_id, technician_id, check_in_at, check_in_latitude,
+               check_in_longitude, check_in_accuracy_m, check_in_distance_m, notes)
+            VALUES (?,?,?,?,?,?,?,?,?,?)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .bind(actor_id)
+        .bind(at)
+        .bind(dto.latitude)
+        .bind(dto.longitude)
+        .bind(dto.accuracy_m)
+        .bind(distance_m)
+        .bind(&notes)
+        .execute(&self.pool)
+        .await?;
 
-                let _ = self
-                    .auto_provision_pppoe_for_subscription(actor_id, tenant_id, &s, ip_address)
-                    .await;
-            }
-        }
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WORK_ORDER_CHECK_IN",
+                "installation_work_orders",
+                Some(work_order_id),
+                Some(&Self::describe_visit_fix("Checked in", distance_m)),
+                ip_address,
+            )
+            .await;
 
-        Ok(row)
+        self.get_work_order_visit(tenant_id, &id).await
     }
 
-    pub async fn cancel_installation_work_order(
+    /// Close the open visit on a work order. The technician who checked in
+    /// (or an admin/owner) checks out.
+    pub async fn check_out_work_order(
         &self,
         actor_id: &str,
         tenant_id: &str,
         work_order_id: &str,
-        notes: Option<String>,
+        dto: WorkOrderCheckRequest,
         ip_address: Option<&str>,
-    ) -> AppResult<InstallationWorkOrder> {
+    ) -> AppResult<WorkOrderVisit> {
         self.auth_service
             .check_permission(actor_id, tenant_id, "work_orders", "manage")
             .await?;
+        validate_gps_fix(&dto)?;
+        let at = visit_fix_time(dto.recorded_at.as_deref(), Utc::now())?;
 
-        if !self.is_actor_admin_or_owner(tenant_id, actor_id).await? {
+        let current = self
+            .get_installation_work_order_row(tenant_id, work_order_id)
+            .await?;
+        let visit_id = self
+            .open_work_order_visit_id(tenant_id, work_order_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::Validation("Work order has no open visit to check out".to_string())
+            })?;
+        let visit = self.get_work_order_visit(tenant_id, &visit_id).await?;
+        if visit.technician_id.as_deref() != Some(actor_id)
+            && !self.is_actor_admin_or_owner(tenant_id, actor_id).await?
+        {
             return Err(AppError::Forbidden(
-                "Only admin/owner can cancel installation work orders".to_string(),
+                "Only the technician who checked in can check out".to_string(),
             ));
         }
-
-        let reason = notes
-            .as_deref()
-            .map(str::trim)
-            .filter(|v| !v.is_empty())
-            .ok_or_else(|| {
-                AppError::Validation(
-                    "Cancellation reason is required (minimum 10 characters)".to_string(),
-                )
-            })?
-            .to_string();
-
-        if reason.chars().count() < 10 {
+        if at < visit.check_in_at {
             return Err(AppError::Validation(
-                "Cancellation reason is too short (minimum 10 characters)".to_string(),
+                "Check-out cannot be earlier than check-in".to_string(),
             ));
         }
 
-        let row = self
-            .set_installation_work_order_status_internal(
-                actor_id,
-                tenant_id,
-                work_order_id,
-                Some("cancelled"),
-                None,
-                None,
-                notes,
-                false,
+        let distance_m = self
+            .work_order_location_coords(tenant_id, &current.location_id)
+            .await?
+            .map(|site| haversine_m(site, (dto.latitude, dto.longitude)));
+        let notes = dto
+            .notes
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .or(visit.notes);
+
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            r#"
+            UPDATE installation_work_order_visits
+            SET check_out_at = $1,
+                check_out_latitude = $2,
+                check_out_longitude = $3,
+                check_out_accuracy_m = $4,
+                check_out_distance_m = $5,
+                notes = $6
+            WHERE tenant_id = $7 AND id = $8
+            "#,
+        )
+        .bind(at)
+        .bind(dto.latitude)
+        .bind(dto.longitude)
+        .bind(dto.accuracy_m)
+        .bind(distance_m)
+        .bind(&notes)
+        .bind(tenant_id)
+        .bind(&visit_id)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query(
+            r#"
+            UPDATE installation_work_order_visits
+            SET check_out_at = ?,
+                check_out_latitude = ?,
+                check_out_longitude = ?,
+                check_out_accuracy_m = ?,
+                check_out_distance_m = ?,
+                notes = ?
+            WHERE tenant_id = ? AND id = ?
+            "#,
+        )
+        .bind(at)
+        .bind(dto.latitude)
+        .bind(dto.longitude)
+        .bind(dto.accuracy_m)
+        .bind(distance_m)
+        .bind(&notes)
+        .bind(tenant_id)
+        .bind(&visit_id)
+        .execute(&self.pool)
+        .await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WORK_ORDER_CHECK_OUT",
+                "installation_work_orders",
+                Some(work_order_id),
+                Some(&Self::describe_visit_fix("Checked out", distance_m)),
                 ip_address,
-                "WORK_ORDER_CANCEL",
-                "Cancelled installation work order",
             )
-            .await?;
+            .await;
 
-        self.set_customer_subscription_status(tenant_id, &row.subscription_id, "cancelled")
-            .await?;
+        self.get_work_order_visit(tenant_id, &visit_id).await
+    }
 
-        if let Err(err) = self
-            .notify_customer_installation_cancelled(tenant_id, &row.subscription_id, &reason)
-            .await
-        {
-            warn!(
-                "failed to send installation cancellation notification: tenant_id={}, work_order_id={}, error={}",
-                tenant_id, row.id, err
-            );
+    fn describe_visit_fix(action: &str, distance_m: Option<f64>) -> String {
+        match distance_m {
+            Some(d) => format!("{} {:.0} m from the customer location", action, d),
+            None => format!("{} (customer location has no coordinates)", action),
         }
-
-        Ok(row)
     }
 
-    pub async fn reopen_installation_work_order(
+    async fn open_work_order_visit_id(
         &self,
-        actor_id: &str,
         tenant_id: &str,
         work_order_id: &str,
-        notes: Option<String>,
-        ip_address: Option<&str>,
-    ) -> AppResult<InstallationWorkOrder> {
-        self.auth_service
-            .check_permission(actor_id, tenant_id, "work_orders", "manage")
-            .await?;
+    ) -> AppResult<Option<String>> {
+        #[cfg(feature = "postgres")]
+        let id: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM installation_work_order_visits WHERE tenant_id = $1 AND work_order_id = $2 AND check_out_at IS NULL",
+        )
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
-        let row = self
-            .set_installation_work_order_status_internal(
-                actor_id,
-                tenant_id,
-                work_order_id,
-                Some("pending"),
-                None,
-                None,
-                notes,
-                true,
-                ip_address,
-                "WORK_ORDER_REOPEN",
-                "Reopened installation work order",
-            )
-            .await?;
+        #[cfg(feature = "sqlite")]
+        let id: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM installation_work_order_visits WHERE tenant_id = ? AND work_order_id = ? AND check_out_at IS NULL",
+        )
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
-        self.set_customer_subscription_status(
-            tenant_id,
-            &row.subscription_id,
-            "pending_installation",
+        Ok(id)
+    }
+
+    async fn get_work_order_visit(&self, tenant_id: &str, id: &str) -> AppResult<WorkOrderVisit> {
+        #[cfg(feature = "postgres")]
+        let row: Option<WorkOrderVisit> = sqlx::query_as(
+            r#"
+            SELECT v.id, v.work_order_id, v.technician_id, u.name AS technician_name,
+                   v.check_in_at, v.check_in_latitude, v.check_in_longitude,
+                   v.check_in_accuracy_m, v.check_in_distance_m,
+                   v.check_out_at, v.check_out_latitude, v.check_out_longitude,
+                   v.check_out_accuracy_m, v.check_out_distance_m, v.notes
+            FROM installation_work_order_visits v
+            LEFT JOIN users u ON u.id = v.technician_id
+            WHERE v.tenant_id = $1 AND v.id = $2
+            "#,
         )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row)
+        #[cfg(feature = "sqlite")]
+        let row: Option<WorkOrderVisit> = sqlx::query_as(
+            r#"
+            SELECT v.id, v.work_order_id, v.technician_id, u.name AS technician_name,
+                   v.check_in_at, v.check_in_latitude, v.check_in_longitude,
+                   v.check_in_accuracy_m, v.check_in_distance_m,
+                   v.check_out_at, v.check_out_latitude, v.check_out_longitude,
+                   v.check_out_accuracy_m, v.check_out_distance_m, v.notes
+            FROM installation_work_order_visits v
+            LEFT JOIN users u ON u.id = v.technician_id
+            WHERE v.tenant_id = ? AND v.id = ?
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.ok_or_else(|| AppError::NotFound("Work order visit not found".to_string()))
+    }
+
+    async fn work_order_location_coords(
+        &self,
+        tenant_id: &str,
+        location_id: &str,
+    ) -> AppResult<Option<(f64, f64)>> {
+        #[cfg(feature = "postgres")]
+        let row: Option<(Option<f64>, Option<f64>)> = sqlx::query_as(
+            "SELECT latitude::float8, longitude::float8 FROM customer_locations WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(location_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let row: Option<(Option<f64>, Option<f64>)> = sqlx::query_as(
+            "SELECT latitude, longitude FROM customer_locations WHERE tenant_id = ? AND id = ?",
+        )
+        .bind(tenant_id)
+        .bind(location_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(match row {
+            Some((Some(lat), Some(lng))) => Some((lat, lng)),
+            _ => None,
+        })
     }
 
     async fn set_installation_work_order_status_internal(
@@ -7367,6 +10169,7 @@ impl CustomerService {
         new_status: Option<&str>,
         assigned_to: Option<&str>,
         scheduled_at: Option<String>,
+        scheduled_end_at: Option<DateTime<Utc>>,
         notes: Option<String>,
         allow_closed_update: bool,
         ip_address: Option<&str>,
@@ -7376,7 +10179,7 @@ impl CustomerService {
         #[cfg(feature = "postgres")]
         let mut row: InstallationWorkOrder = sqlx::query_as(
             r#"
-            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, status, assigned_to, scheduled_at, completed_at, notes, created_at, updated_at
+            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, work_type, details, status, assigned_to, scheduled_at, scheduled_end_at, completed_at, notes, created_at, updated_at
             FROM installation_work_orders
             WHERE tenant_id = $1 AND id = $2
             LIMIT 1
@@ -7391,7 +10194,7 @@ impl CustomerService {
         #[cfg(feature = "sqlite")]
         let mut row: InstallationWorkOrder = sqlx::query_as(
             r#"
-            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, status, assigned_to, scheduled_at, completed_at, notes, created_at, updated_at
+            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, work_type, details, status, assigned_to, scheduled_at, scheduled_end_at, completed_at, notes, created_at, updated_at
             FROM installation_work_orders
             WHERE tenant_id = ? AND id = ?
             LIMIT 1
@@ -7435,14 +10238,16 @@ impl CustomerService {
         if let Some(target_status) = normalized_new_status.as_deref() {
             match target_status {
                 "pending" => {
-                    if row.status == "in_progress" && !allow_closed_update {
+                    if matches!(row.status.as_str(), "in_progress" | "on_hold")
+                        && !allow_closed_update
+                    {
                         return Err(AppError::Validation(
                             "In-progress work order cannot be moved back to pending".to_string(),
                         ));
                     }
                 }
                 "in_progress" => {
-                    if row.status != "pending" {
+                    if !work_order_transition_allowed(&row.work_type, &row.status, "in_progress") {
                         return Err(AppError::Validation(
                             "Only pending work order can be started".to_string(),
                         ));
@@ -7471,6 +10276,13 @@ impl CustomerService {
                         ));
                     }
                 }
+                "on_hold" => {
+                    if !work_order_transition_allowed(&row.work_type, &row.status, "on_hold") {
+                        return Err(AppError::Validation(
+                            "Only in-progress repair work order can be put on hold".to_string(),
+                        ));
+                    }
+                }
                 _ => {}
             }
         }
@@ -7492,7 +10304,15 @@ impl CustomerService {
             };
         }
         if scheduled_at.is_some() {
+            let previous_start = row.scheduled_at;
             row.scheduled_at = Self::parse_optional_datetime(scheduled_at)?;
+            if row.scheduled_at != previous_start {
+                row.scheduled_end_at =
+                    moved_slot_end(previous_start, row.scheduled_end_at, row.scheduled_at);
+            }
+        }
+        if scheduled_end_at.is_some() && row.scheduled_at.is_some() {
+            row.scheduled_end_at = scheduled_end_at;
         }
         row.notes = Self::merge_work_order_notes(row.notes, actor_id, notes.as_deref());
         row.updated_at = Utc::now();
@@ -7504,15 +10324,17 @@ impl CustomerService {
             SET status = $1,
                 assigned_to = $2,
                 scheduled_at = $3,
-                completed_at = $4,
-                notes = $5,
-                updated_at = $6
-            WHERE tenant_id = $7 AND id = $8
+                scheduled_end_at = $4,
+                completed_at = $5,
+                notes = $6,
+                updated_at = $7
+            WHERE tenant_id = $8 AND id = $9
             "#,
         )
         .bind(&row.status)
         .bind(&row.assigned_to)
         .bind(row.scheduled_at)
+        .bind(row.scheduled_end_at)
         .bind(row.completed_at)
         .bind(&row.notes)
         .bind(row.updated_at)
@@ -7528,6 +10350,7 @@ impl CustomerService {
             SET status = ?,
                 assigned_to = ?,
                 scheduled_at = ?,
+                scheduled_end_at = ?,
                 completed_at = ?,
                 notes = ?,
                 updated_at = ?
@@ -7537,6 +10360,7 @@ impl CustomerService {
         .bind(&row.status)
         .bind(&row.assigned_to)
         .bind(row.scheduled_at)
+        .bind(row.scheduled_end_at)
         .bind(row.completed_at)
         .bind(&row.notes)
         .bind(row.updated_at)
@@ -7641,8 +10465,25 @@ impl CustomerService {
 
 #[cfg(test)]
 mod tests {
-    use super::{CustomerService, InstallationSlaBreachType};
-    use chrono::{Duration, Utc};
+    use super::{
+        ensure_trashable, haversine_m, mark_agenda_conflicts, moved_slot_end, needs_dismantle,
+        normalize_customer_tags, plan_visit_order, route_length_m, validate_gps_fix,
+        visit_fix_time, work_order_details, work_order_slot_end, work_order_transition_allowed,
+        CustomerService, InstallationSlaBreachType,
+    };
+    use crate::error::AppError;
+    use crate::models::{CreateWorkOrderRequest, WorkOrderAgendaItem, WorkOrderCheckRequest};
+    use chrono::{DateTime, Duration, Utc};
+
+    #[test]
+    fn customer_with_live_service_is_not_trashed() {
+        match ensure_trashable(1, 0) {
+            Err(AppError::Conflict(msg)) => assert!(msg.contains("1 active subscription")),
+            other => panic!("expected conflict, got {:?}", other),
+        }
+        assert!(matches!(ensure_trashable(0, 2), Err(AppError::Conflict(_))));
+        assert!(ensure_trashable(0, 0).is_ok());
+    }
 
     #[test]
     fn detect_installation_sla_breach_for_scheduled_work_order() {
@@ -7701,4 +10542,259 @@ mod tests {
         assert_eq!(CustomerService::format_elapsed_duration(145), "2h 25m");
         assert_eq!(CustomerService::format_elapsed_duration(26 * 60), "1d 2h");
     }
+
+    #[test]
+    fn customer_tags_are_normalized_and_bounded() {
+        let tags = normalize_customer_tags(vec![
+            " VIP ".to_string(),
+            "vip".to_string(),
+            "".to_string(),
+            "RT 05".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(tags, vec!["rt 05", "vip"]);
+
+        assert!(normalize_customer_tags(vec!["x".repeat(41)]).is_err());
+        assert!(normalize_customer_tags((0..21).map(|i| format!("t{}", i)).collect()).is_err());
+    }
+
+    #[test]
+    fn work_order_slots_fall_back_to_default_length() {
+        let start = Utc::now();
+        assert_eq!(
+            work_order_slot_end(start, None, 120),
+            start + Duration::minutes(120)
+        );
+        // An end at or before the start is ignored.
+        assert_eq!(
+            work_order_slot_end(start, Some(start), 60),
+            start + Duration::minutes(60)
+        );
+        let end = start + Duration::minutes(45);
+        assert_eq!(work_order_slot_end(start, Some(end), 120), end);
+    }
+
+    #[test]
+    fn moving_a_slot_keeps_its_length() {
+        let start = Utc::now();
+        let end = start + Duration::minutes(90);
+        let new_start = start + Duration::days(1);
+
+        assert_eq!(
+            moved_slot_end(Some(start), Some(end), Some(new_start)),
+            Some(new_start + Duration::minutes(90))
+        );
+        assert_eq!(moved_slot_end(Some(start), None, Some(new_start)), None);
+        assert_eq!(moved_slot_end(None, Some(end), Some(new_start)), None);
+    }
+
+    fn agenda_item(
+        id: &str,
+        tech: &str,
+        status: &str,
+        start: DateTime<Utc>,
+        minutes: i64,
+    ) -> WorkOrderAgendaItem {
+        WorkOrderAgendaItem {
+            work_order_id: id.to_string(),
+            status: status.to_string(),
+            assigned_to: Some(tech.to_string()),
+            assigned_to_name: None,
+            scheduled_at: start,
+            scheduled_end_at: start + Duration::minutes(minutes),
+            customer_id: "c".to_string(),
+            customer_name: None,
+            location_label: None,
+            package_name: None,
+            has_conflict: false,
+        }
+    }
+
+    #[test]
+    fn agenda_flags_double_booked_technicians_only() {
+        let t0 = Utc::now();
+        let mut items = vec![
+            agenda_item("a", "tech-1", "pending", t0, 120),
+            agenda_item("b", "tech-1", "in_progress", t0 + Duration::minutes(60), 60),
+            // Back-to-back slots touch but don't overlap.
+            agenda_item("c", "tech-1", "pending", t0 + Duration::minutes(120), 30),
+            agenda_item("d", "tech-2", "pending", t0, 120),
+            agenda_item("e", "tech-2", "completed", t0, 120),
+        ];
+        mark_agenda_conflicts(&mut items);
+
+        let flagged: Vec<&str> = items
+            .iter()
+            .filter(|i| i.has_conflict)
+            .map(|i| i.work_order_id.as_str())
+            .collect();
+        assert_eq!(flagged, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn haversine_distance_in_metres() {
+        let site = (-6.2000, 106.8166);
+        assert_eq!(haversine_m(site, site), 0.0);
+        // 0.001 degree of latitude is about 111 m.
+        let d = haversine_m(site, (-6.2010, 106.8166));
+        assert!((d - 111.2).abs() < 1.0, "got {d}");
+        // Jakarta - Bandung is roughly 119 km as the crow flies.
+        let d = haversine_m(site, (-6.9175, 107.6191));
+        assert!((118_000.0..121_000.0).contains(&d), "got {d}");
+    }
+
+    #[test]
+    fn gps_fix_must_be_in_range() {
+        let fix = |latitude: f64, longitude: f64, accuracy_m: Option<f64>| WorkOrderCheckRequest {
+            latitude,
+            longitude,
+            accuracy_m,
+            notes: None,
+            recorded_at: None,
+        };
+        assert!(validate_gps_fix(&fix(-6.2, 106.8, Some(12.0))).is_ok());
+        assert!(validate_gps_fix(&fix(91.0, 106.8, None)).is_err());
+        assert!(validate_gps_fix(&fix(-6.2, 181.0, None)).is_err());
+        assert!(validate_gps_fix(&fix(f64::NAN, 106.8, None)).is_err());
+        assert!(validate_gps_fix(&fix(-6.2, 106.8, Some(-1.0))).is_err());
+    }
+
+    #[test]
+    fn offline_visit_time_stays_within_window() {
+        let now: DateTime<Utc> = "2026-04-07T10:00:00Z".parse().unwrap();
+        assert_eq!(visit_fix_time(None, now).unwrap(), now);
+        assert_eq!(visit_fix_time(Some(" "), now).unwrap(), now);
+        assert_eq!(
+            visit_fix_time(Some("2026-04-07T15:30:00+07:00"), now).unwrap(),
+            now - Duration::minutes(30)
+        );
+        // A device clock running a minute fast is clamped to the server time.
+        assert_eq!(
+            visit_fix_time(Some("2026-04-07T10:01:00Z"), now).unwrap(),
+            now
+        );
+        assert!(visit_fix_time(Some("2026-04-07T10:30:00Z"), now).is_err());
+        assert!(visit_fix_time(Some("2026-04-01T10:00:00Z"), now).is_err());
+        assert!(visit_fix_time(Some("yesterday"), now).is_err());
+    }
+
+    #[test]
+    fn route_visits_stops_along_the_line() {
+        // Four stops on a west-east line, scheduled out of order.
+        let points = [
+            (-6.2, 106.83),
+            (-6.2, 106.80),
+            (-6.2, 106.82),
+            (-6.2, 106.81),
+        ];
+        let order = plan_visit_order(Some((-6.2, 106.79)), &points);
+        assert_eq!(order, vec![1, 3, 2, 0]);
+
+        let scheduled: Vec<usize> = (0..points.len()).collect();
+        assert!(
+            route_length_m(Some((-6.2, 106.79)), &points, &order)
+                < route_length_m(Some((-6.2, 106.79)), &points, &scheduled)
+        );
+    }
+
+    #[test]
+    fn route_without_start_point_is_still_shortest() {
+        let points = [
+            (-6.2, 106.81),
+            (-6.2, 106.83),
+            (-6.2, 106.80),
+            (-6.2, 106.82),
+        ];
+        let order = plan_visit_order(None, &points);
+        let len = route_length_m(None, &points, &order);
+        // Covering the 0.03 degree line end to end is about 3.3 km.
+        assert!(
+            (len - haversine_m(points[2], points[1])).abs() < 1.0,
+            "got {len}"
+        );
+        assert!(plan_visit_order(None, &[]).is_empty());
+        assert_eq!(plan_visit_order(Some((0.0, 0.0)), &[(1.0, 1.0)]), vec![0]);
+    }
+
+    fn create_request(work_type: &str) -> CreateWorkOrderRequest {
+        CreateWorkOrderRequest {
+            work_type: work_type.to_string(),
+            subscription_id: "sub-1".to_string(),
+            problem: None,
+            support_ticket_id: None,
+            target_location_id: None,
+            reason: None,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn only_repairs_can_be_put_on_hold() {
+        assert!(work_order_transition_allowed(
+            "repair",
+            "in_progress",
+            "on_hold"
+        ));
+        assert!(work_order_transition_allowed(
+            "repair",
+            "on_hold",
+            "in_progress"
+        ));
+        assert!(!work_order_transition_allowed(
+            "repair", "pending", "on_hold"
+        ));
+        assert!(!work_order_transition_allowed(
+            "installation",
+            "in_progress",
+            "on_hold"
+        ));
+        assert!(!work_order_transition_allowed(
+            "dismantle",
+            "on_hold",
+            "in_progress"
+        ));
+        assert!(work_order_transition_allowed(
+            "relocation",
+            "pending",
+            "in_progress"
+        ));
+        assert!(!work_order_transition_allowed(
+            "repair",
+            "on_hold",
+            "completed"
+        ));
+    }
+
+    #[test]
+    fn dismantle_follows_cancelling_an_installed_subscription() {
+        assert!(needs_dismantle("active", "cancelled"));
+        assert!(needs_dismantle("suspended", "cancelled"));
+        assert!(!needs_dismantle("pending_installation", "cancelled"));
+        assert!(!needs_dismantle("cancelled", "cancelled"));
+        assert!(!needs_dismantle("active", "suspended"));
+    }
+
+    #[test]
+    fn work_order_details_follow_the_type() {
+        let mut repair = create_request(" Repair ");
+        assert!(work_order_details(&repair).is_err());
+        repair.problem = Some("  No signal since the storm ".to_string());
+        let (work_type, details) = work_order_details(&repair).unwrap();
+        assert_eq!(work_type, "repair");
+        assert_eq!(details["problem"], "No signal since the storm");
+        assert!(details["support_ticket_id"].is_null());
+
+        repair.target_location_id = Some("loc-2".to_string());
+        assert!(work_order_details(&repair).is_err());
+
+        let mut relocation = create_request("relocation");
+        assert!(work_order_details(&relocation).is_err());
+        relocation.target_location_id = Some("loc-2".to_string());
+        let (_, details) = work_order_details(&relocation).unwrap();
+        assert_eq!(details["target_location_id"], "loc-2");
+
+        assert!(work_order_details(&create_request("dismantle")).is_ok());
+        assert!(work_order_details(&create_request("installation")).is_err());
+        assert!(work_order_details(&create_request("survey")).is_err());
+    }
 }
@@ -0,0 +1,385 @@
+//! Database maintenance
+//!
+//! Postgres: `ANALYZE` the whole database, then `VACUUM (ANALYZE)` tables whose
+//! dead tuples have piled up faster than autovacuum keeps up with.
+//! SQLite: `PRAGMA optimize` and a truncating WAL checkpoint.
+//!
+//! Runs on a schedule (`db_maintenance_enabled`, `db_maintenance_interval_hours`)
+//! checked hourly by a background job, and can be triggered by a super admin.
+//! Per-table size/bloat and the last run are reported in system diagnostics.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{BackgroundJob, UpsertSettingDto};
+use crate::services::{JobHandler, JobQueue, SettingsService};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+
+const DEFAULT_INTERVAL_HOURS: u64 = 24;
+const LAST_SCHEDULED_RUN_KEY: &str = "db_maintenance_last_scheduled_at";
+
+/// Vacuum a table once it has at least this many dead tuples...
+#[cfg(feature = "postgres")]
+const VACUUM_MIN_DEAD_TUPLES: i64 = 1_000;
+/// ...making up at least this share of its rows.
+#[cfg(feature = "postgres")]
+const VACUUM_MIN_DEAD_RATIO: f64 = 0.1;
+
+/// How many tables to list in diagnostics (largest first).
+const REPORTED_TABLES: i64 = 25;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TableStorage {
+    pub name: String,
+    pub total_bytes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub live_rows: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_rows: Option<i64>,
+    /// Dead rows as a share of live + dead (Postgres only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bloat_ratio: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_vacuum_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_analyze_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MaintenanceReport {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub trigger: String,
+    /// Statements that ran, e.g. `ANALYZE` or `VACUUM (ANALYZE) public.audit_logs`.
+    pub operations: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DatabaseMaintenanceSnapshot {
+    pub enabled: bool,
+    pub interval_hours: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<MaintenanceReport>,
+    /// Free pages waiting to be reclaimed (SQLite only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reclaimable_bytes: Option<i64>,
+    pub tables: Vec<TableStorage>,
+}
+
+#[derive(Clone)]
+pub struct DbMaintenanceService {
+    pool: DbPool,
+    settings_service: SettingsService,
+    last_run: Arc<RwLock<Option<MaintenanceReport>>>,
+    running: Arc<Mutex<()>>,
+}
+
+impl DbMaintenanceService {
+    pub fn new(pool: DbPool, settings_service: SettingsService) -> Self {
+        Self {
+            pool,
+            settings_service,
+            last_run: Arc::new(RwLock::new(None)),
+            running: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Checks hourly on the job queue whether a scheduled run is due.
+    pub async fn schedule(&self, queue: &JobQueue) {
+        queue
+            .register_recurring(
+                "db.maintenance",
+                std::time::Duration::from_secs(3600),
+                Arc::new(self.clone()),
+            )
+            .await;
+    }
+
+    async fn enabled(&self) -> bool {
+        self.settings_service
+            .get_value(None, "db_maintenance_enabled")
+            .await
+            .ok()
+            .flatten()
+            .map(|v| v.trim() == "true")
+            .unwrap_or(true)
+    }
+
+    async fn interval_hours(&self) -> u64 {
+        self.settings_service
+            .get_value(None, "db_maintenance_interval_hours")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_INTERVAL_HOURS)
+            .clamp(1, 24 * 30)
+    }
+
+    async fn mark_scheduled_run(&self, at: DateTime<Utc>) -> AppResult<()> {
+        let dto = UpsertSettingDto {
+            key: LAST_SCHEDULED_RUN_KEY.to_string(),
+            value: at.to_rfc3339(),
+            description: Some("Last scheduled database maintenance".to_string()),
+            expected_updated_at: None,
+        };
+        self.settings_service.upsert(None, dto, None, None).await?;
+        Ok(())
+    }
+
+    /// Run maintenance now. Fails with a conflict if a run is already in progress.
+    pub async fn run(&self, trigger: &str) -> AppResult<MaintenanceReport> {
+        let _guard = self.running.try_lock().map_err(|_| {
+            AppError::Conflict("Database maintenance is already running".to_string())
+        })?;
+
+        let started_at = Utc::now();
+        let timer = Instant::now();
+        let mut operations = Vec::new();
+        let mut errors = Vec::new();
+
+        #[cfg(feature = "postgres")]
+        {
+            let mut statements = vec!["ANALYZE".to_string()];
+            match self.bloated_tables().await {
+                Ok(tables) => statements.extend(
+                    tables
+                        .into_iter()
+                        .map(|t| format!("VACUUM (ANALYZE) public.\"{}\"", t)),
+                ),
+                Err(e) => errors.push(format!("bloat check: {}", e)),
+            }
+            for stmt in statements {
+                // VACUUM cannot run in a transaction block; raw_sql uses the simple protocol.
+                match sqlx::raw_sql(&stmt).execute(&self.pool).await {
+                    Ok(_) => operations.push(stmt),
+                    Err(e) => errors.push(format!("{}: {}", stmt, e)),
+                }
+            }
+        }
+
+        #[cfg(feature = "sqlite")]
+        {
+            for stmt in ["PRAGMA optimize", "PRAGMA wal_checkpoint(TRUNCATE)"] {
+                match sqlx::raw_sql(stmt).execute(&self.pool).await {
+                    Ok(_) => operations.push(stmt.to_string()),
+                    Err(e) => errors.push(format!("{}: {}", stmt, e)),
+                }
+            }
+        }
+
+        let report = MaintenanceReport {
+            started_at,
+            duration_ms: timer.elapsed().as_millis() as u64,
+            trigger: trigger.to_string(),
+            operations,
+            errors,
+        };
+        *self.last_run.write().await = Some(report.clone());
+        Ok(report)
+    }
+
+    /// Tables whose dead tuples cross both vacuum thresholds.
+    #[cfg(feature = "postgres")]
+    async fn bloated_tables(&self) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT relname::text
+            FROM pg_stat_user_tables
+            WHERE schemaname = 'public'
+              AND n_dead_tup >= $1
+              AND n_dead_tup::float8 / GREATEST(n_live_tup + n_dead_tup, 1) >= $2
+            ORDER BY n_dead_tup DESC
+            "#,
+        )
+        .bind(VACUUM_MIN_DEAD_TUPLES)
+        .bind(VACUUM_MIN_DEAD_RATIO)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Maintenance settings, last run and per-table storage for diagnostics.
+    pub async fn snapshot(&self) -> DatabaseMaintenanceSnapshot {
+        let tables = match self.table_storage().await {
+            Ok(t) => t,
+            Err(e) => {
+                warn!("Failed to collect table storage stats: {}", e);
+                Vec::new()
+            }
+        };
+
+        DatabaseMaintenanceSnapshot {
+            enabled: self.enabled().await,
+            interval_hours: self.interval_hours().await,
+            last_run: self.last_run.read().await.clone(),
+            reclaimable_bytes: self.reclaimable_bytes().await,
+            tables,
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    async fn table_storage(&self) -> Result<Vec<TableStorage>, sqlx::Error> {
+        #[derive(sqlx::FromRow)]
+        struct Row {
+            name: String,
+            total_bytes: i64,
+            live_rows: i64,
+            dead_rows: i64,
+            last_vacuum_at: Option<DateTime<Utc>>,
+            last_analyze_at: Option<DateTime<Utc>>,
+        }
+
+        // Partitions are reported individually; that is where the bloat lives.
+        let rows: Vec<Row> = sqlx::query_as(
+            r#"
+            SELECT
+                relname::text AS name,
+                pg_total_relation_size(relid) AS total_bytes,
+                n_live_tup AS live_rows,
+                n_dead_tup AS dead_rows,
+                GREATEST(last_vacuum, last_autovacuum) AS last_vacuum_at,
+                GREATEST(last_analyze, last_autoanalyze) AS last_analyze_at
+            FROM pg_stat_user_tables
+            WHERE schemaname = 'public'
+            ORDER BY pg_total_relation_size(relid) DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(REPORTED_TABLES)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| TableStorage {
+                bloat_ratio: Some(bloat_ratio(r.live_rows, r.dead_rows)),
+                name: r.name,
+                total_bytes: r.total_bytes,
+                live_rows: Some(r.live_rows),
+                dead_rows: Some(r.dead_rows),
+                last_vacuum_at: r.last_vacuum_at,
+                last_analyze_at: r.last_analyze_at,
+            })
+            .collect())
+    }
+
+    #[cfg(feature = "sqlite")]
+    async fn table_storage(&self) -> Result<Vec<TableStorage>, sqlx::Error> {
+        // dbstat is compiled into the bundled SQLite.
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT s.name, SUM(s.pgsize) AS total_bytes
+            FROM dbstat s
+            JOIN sqlite_master m ON m.name = s.name AND m.type = 'table'
+            GROUP BY s.name
+            ORDER BY total_bytes DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(REPORTED_TABLES)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(name, total_bytes)| TableStorage {
+                name,
+                total_bytes,
+                live_rows: None,
+                dead_rows: None,
+                bloat_ratio: None,
+                last_vacuum_at: None,
+                last_analyze_at: None,
+            })
+            .collect())
+    }
+
+    #[cfg(feature = "postgres")]
+    async fn reclaimable_bytes(&self) -> Option<i64> {
+        None
+    }
+
+    #[cfg(feature = "sqlite")]
+    async fn reclaimable_bytes(&self) -> Option<i64> {
+        let free: i64 = sqlx::query_scalar("PRAGMA freelist_count")
+            .fetch_one(&self.pool)
+            .await
+            .ok()?;
+        let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
+            .fetch_one(&self.pool)
+            .await
+            .ok()?;
+        Some(free * page_size)
+    }
+}
+
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+fn bloat_ratio(live: i64, dead: i64) -> f64 {
+    let total = live.max(0) + dead.max(0);
+    if total == 0 {
+        return 0.0;
+    }
+    dead.max(0) as f64 / total as f64
+}
+
+#[async_trait]
+impl JobHandler for DbMaintenanceService {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        if !self.enabled().await {
+            return Ok(());
+        }
+
+        // Kept in settings rather than memory: whichever instance claims the
+        // job has to know when any of them last ran maintenance.
+        let now = Utc::now();
+        let interval = chrono::Duration::hours(self.interval_hours().await as i64);
+        let last_scheduled = self
+            .settings_service
+            .get_value(None, LAST_SCHEDULED_RUN_KEY)
+            .await?
+            .and_then(|v| DateTime::parse_from_rfc3339(&v).ok())
+            .map(|at| at.with_timezone(&Utc));
+        // Some slack so the hourly checks don't slip an hour behind every run.
+        let due_after = interval - chrono::Duration::minutes(5);
+        if last_scheduled.is_some_and(|at| now - at < due_after) {
+            return Ok(());
+        }
+
+        self.mark_scheduled_run(now).await?;
+        let report = self.run("scheduled").await?;
+        if report.errors.is_empty() {
+            info!(
+                "Database maintenance finished in {}ms ({} operation(s))",
+                report.duration_ms,
+                report.operations.len()
+            );
+        } else {
+            warn!(
+                "Database maintenance finished with {} error(s)",
+                report.errors.len()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloat_ratio() {
+        assert_eq!(bloat_ratio(0, 0), 0.0);
+        assert_eq!(bloat_ratio(900, 100), 0.1);
+        assert_eq!(bloat_ratio(0, 50), 1.0);
+        // pg_stat estimates can go negative right after a reset.
+        assert_eq!(bloat_ratio(-5, 10), 1.0);
+    }
+}
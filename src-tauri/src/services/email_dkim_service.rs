@@ -0,0 +1,578 @@
+//! DKIM signing for outbound email.
+//!
+//! Each tenant can generate an RSA key for its sending domain. The private key
+//! is stored encrypted with the app secret; the public key is handed back as
+//! the TXT record to publish under `<selector>._domainkey.<domain>`.
+//!
+//! Only mail sent through SMTP is signed here (`rsa-sha256`, relaxed/relaxed
+//! canonicalization per RFC 6376). Resend and SendGrid sign with keys managed
+//! in their own dashboards.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    DkimDnsRecord, EmailDkimKey, EmailDkimStatus, GenerateDkimKeyRequest, UpdateDkimRequest,
+};
+use crate::security::secret::{decrypt_secret_for, encrypt_secret_for};
+use crate::services::SettingsService;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use rsa::pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey, LineEnding};
+use rsa::{Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey};
+use sha2::{Digest, Sha256};
+
+const SECRET_PURPOSE: &str = "email_dkim";
+const KEY_BITS: usize = 2048;
+const TXT_CHUNK_LEN: usize = 255;
+
+/// Headers covered by the signature, when present in the message.
+const SIGNED_HEADERS: &[&str] = &[
+    "From",
+    "To",
+    "Cc",
+    "Subject",
+    "Date",
+    "Message-ID",
+    "Reply-To",
+    "MIME-Version",
+    "Content-Type",
+    "Content-Transfer-Encoding",
+];
+
+fn is_wsp(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+/// Split a raw message into its header block and body at the first empty line.
+fn split_message(raw: &[u8]) -> (&[u8], &[u8]) {
+    match raw.windows(4).position(|w| w == b"\r\n\r\n") {
+        Some(i) => (&raw[..i + 2], &raw[i + 4..]),
+        None => (raw, &[]),
+    }
+}
+
+/// Header fields in order, each with its continuation lines still attached.
+fn parse_headers(block: &str) -> Vec<(String, String)> {
+    let mut out: Vec<(String, String)> = Vec::new();
+    for line in block.split("\r\n").filter(|l| !l.is_empty()) {
+        if line.starts_with(is_wsp) {
+            if let Some((_, value)) = out.last_mut() {
+                value.push_str("\r\n");
+                value.push_str(line);
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            out.push((name.to_string(), value.to_string()));
+        }
+    }
+    out
+}
+
+/// Collapse runs of whitespace (including folding) to one space and trim.
+fn collapse_wsp(value: &str) -> String {
+    value
+        .replace("\r\n", "")
+        .split(is_wsp)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// "relaxed" header canonicalization (RFC 6376 §3.4.2), without the CRLF.
+fn canonicalize_header(name: &str, value: &str) -> String {
+    format!("{}:{}", name.trim().to_lowercase(), collapse_wsp(value))
+}
+
+/// "relaxed" body canonicalization (RFC 6376 §3.4.4).
+fn canonicalize_body(body: &[u8]) -> Vec<u8> {
+    let text = String::from_utf8_lossy(body);
+    let mut lines: Vec<String> = text
+        .split("\r\n")
+        .map(|line| {
+            let mut out = String::with_capacity(line.len());
+            let mut in_wsp = false;
+            for c in line.chars() {
+                if is_wsp(c) {
+                    in_wsp = true;
+                } else {
+                    if in_wsp {
+                        out.push(' ');
+                    }
+                    in_wsp = false;
+                    out.push(c);
+                }
+            }
+            out
+        })
+        .collect();
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&line);
+        out.push_str("\r\n");
+    }
+    out.into_bytes()
+}
+
+/// Lower-cased domain part of an email address, `Name <a@b>` forms included.
+pub fn domain_of(address: &str) -> Option<String> {
+    let addr = address.trim();
+    let addr = match (addr.rfind('<'), addr.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &addr[start + 1..end],
+        _ => addr,
+    };
+    addr.rsplit_once('@')
+        .map(|(_, domain)| domain.trim().to_lowercase())
+        .filter(|d| !d.is_empty())
+}
+
+fn valid_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= 63
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn normalize_domain(domain: &str) -> AppResult<String> {
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    if domain.len() > 253 || !domain.contains('.') || !domain.split('.').all(valid_label) {
+        return Err(AppError::Validation(format!(
+            "'{}' is not a valid domain",
+            domain
+        )));
+    }
+    Ok(domain)
+}
+
+fn normalize_selector(selector: &str) -> AppResult<String> {
+    let selector = selector.trim().to_lowercase();
+    if selector.len() > 63 || !selector.split('.').all(valid_label) {
+        return Err(AppError::Validation(
+            "Selector may only contain letters, digits, hyphens and dots".to_string(),
+        ));
+    }
+    Ok(selector)
+}
+
+pub fn dns_record(domain: &str, selector: &str, public_key: &str) -> DkimDnsRecord {
+    let value = format!("v=DKIM1; k=rsa; p={}", public_key);
+    let chunks = value
+        .as_bytes()
+        .chunks(TXT_CHUNK_LEN)
+        .map(|c| String::from_utf8_lossy(c).into_owned())
+        .collect();
+    DkimDnsRecord {
+        record_type: "TXT".to_string(),
+        name: format!("{}._domainkey.{}", selector, domain),
+        value,
+        chunks,
+    }
+}
+
+/// Signs raw RFC 5322 messages for one domain/selector.
+pub struct DkimSigner {
+    domain: String,
+    selector: String,
+    key: RsaPrivateKey,
+}
+
+impl DkimSigner {
+    pub fn new(domain: &str, selector: &str, private_key_pem: &str) -> AppResult<Self> {
+        let key = RsaPrivateKey::from_pkcs8_pem(private_key_pem)
+            .map_err(|e| AppError::Internal(format!("Invalid DKIM private key: {}", e)))?;
+        Ok(Self {
+            domain: domain.to_string(),
+            selector: selector.to_string(),
+            key,
+        })
+    }
+
+    /// Returns `raw` with a `DKIM-Signature` header prepended. `raw` must use
+    /// CRLF line endings, as lettre's formatted messages do.
+    pub fn sign(&self, raw: &[u8], now: DateTime<Utc>) -> AppResult<Vec<u8>> {
+        let (header_block, body) = split_message(raw);
+        let headers = parse_headers(&String::from_utf8_lossy(header_block));
+
+        let body_hash = general_purpose::STANDARD.encode(Sha256::digest(canonicalize_body(body)));
+
+        let signed: Vec<&(String, String)> = SIGNED_HEADERS
+            .iter()
+            .filter_map(|wanted| {
+                headers
+                    .iter()
+                    .rev()
+                    .find(|(name, _)| name.trim().eq_ignore_ascii_case(wanted))
+            })
+            .collect();
+        let header_list = signed
+            .iter()
+            .map(|(name, _)| name.trim())
+            .collect::<Vec<_>>()
+            .join(":");
+
+        let unsigned_value = format!(
+            " v=1; a=rsa-sha256; c=relaxed/relaxed; d={}; s={}; t={}; h={}; bh={}; b=",
+            self.domain,
+            self.selector,
+            now.timestamp(),
+            header_list,
+            body_hash
+        );
+
+        let mut hasher = Sha256::new();
+        for (name, value) in &signed {
+            hasher.update(canonicalize_header(name, value).as_bytes());
+            hasher.update(b"\r\n");
+        }
+        hasher.update(canonicalize_header("DKIM-Signature", &unsigned_value).as_bytes());
+        let digest = hasher.finalize();
+
+        let signature = self
+            .key
+            .sign(Pkcs1v15Sign::new::<Sha256>(), &digest)
+            .map_err(|e| AppError::Internal(format!("DKIM signing failed: {}", e)))?;
+
+        let header = format!(
+            "DKIM-Signature:{}{}\r\n",
+            unsigned_value,
+            general_purpose::STANDARD.encode(signature)
+        );
+        let mut out = Vec::with_capacity(header.len() + raw.len());
+        out.extend_from_slice(header.as_bytes());
+        out.extend_from_slice(raw);
+        Ok(out)
+    }
+}
+
+#[derive(Clone)]
+pub struct EmailDkimService {
+    pool: DbPool,
+    settings_service: SettingsService,
+}
+
+impl EmailDkimService {
+    pub fn new(pool: DbPool, settings_service: SettingsService) -> Self {
+        Self {
+            pool,
+            settings_service,
+        }
+    }
+
+    async fn get_key(&self, tenant_id: &str) -> AppResult<Option<EmailDkimKey>> {
+        let row =
+            sqlx::query_as::<_, EmailDkimKey>("SELECT * FROM email_dkim_keys WHERE tenant_id = $1")
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row)
+    }
+
+    async fn setting(&self, tenant_id: &str, key: &str) -> Option<String> {
+        self.settings_service
+            .get_value_fallback(Some(tenant_id), key)
+            .await
+            .ok()
+            .flatten()
+            .filter(|v| !v.trim().is_empty())
+    }
+
+    pub async fn status(&self, tenant_id: &str) -> AppResult<EmailDkimStatus> {
+        let key = self.get_key(tenant_id).await?;
+        let provider = self
+            .setting(tenant_id, "email_provider")
+            .await
+            .unwrap_or_else(|| "resend".to_string());
+        let from_domain = self
+            .setting(tenant_id, "email_from_address")
+            .await
+            .and_then(|a| domain_of(&a));
+
+        let Some(key) = key else {
+            return Ok(EmailDkimStatus {
+                configured: false,
+                enabled: false,
+                domain: None,
+                selector: None,
+                algorithm: "rsa-sha256".to_string(),
+                dns_record: None,
+                from_domain,
+                domain_matches_from: false,
+                provider,
+                signing_active: false,
+                instructions: vec![
+                    "Generate a key to get the DNS record to publish for your sending domain."
+                        .to_string(),
+                ],
+                created_at: None,
+                updated_at: None,
+            });
+        };
+
+        let record = dns_record(&key.domain, &key.selector, &key.public_key);
+        let domain_matches_from = from_domain.as_deref() == Some(key.domain.as_str());
+        let mut instructions = vec![
+            format!(
+                "At your DNS provider, add a TXT record named {} with the value shown.",
+                record.name
+            ),
+            format!(
+                "If the DNS panel appends the domain itself, enter only {}._domainkey as the name.",
+                key.selector
+            ),
+            "If the panel limits TXT strings to 255 characters, enter the value as the listed chunks."
+                .to_string(),
+            "DNS changes can take up to 48 hours to propagate. Send a test email and check that the receiver reports dkim=pass."
+                .to_string(),
+        ];
+        if !domain_matches_from {
+            instructions.push(format!(
+                "The sender address is not on {}; DMARC only counts DKIM when the signing domain matches the From domain.",
+                key.domain
+            ));
+        }
+        if provider != "smtp" {
+            instructions.push(format!(
+                "Outgoing mail uses {} rather than SMTP, so it is not signed with this key. Set up DKIM in the provider's dashboard instead.",
+                provider
+            ));
+        }
+
+        Ok(EmailDkimStatus {
+            configured: true,
+            enabled: key.is_enabled,
+            domain: Some(key.domain),
+            selector: Some(key.selector),
+            algorithm: "rsa-sha256".to_string(),
+            dns_record: Some(record),
+            from_domain,
+            domain_matches_from,
+            signing_active: key.is_enabled && provider == "smtp",
+            provider,
+            instructions,
+            created_at: Some(key.created_at),
+            updated_at: Some(key.updated_at),
+        })
+    }
+
+    /// Create a new key pair, replacing any existing one. The new selector has
+    /// to be published before mail signed with it will verify.
+    pub async fn generate(
+        &self,
+        tenant_id: &str,
+        req: GenerateDkimKeyRequest,
+        actor_id: Option<&str>,
+    ) -> AppResult<EmailDkimStatus> {
+        let domain = match req.domain.as_deref().filter(|d| !d.trim().is_empty()) {
+            Some(d) => d.to_string(),
+            None => self
+                .setting(tenant_id, "email_from_address")
+                .await
+                .and_then(|a| domain_of(&a))
+                .ok_or_else(|| {
+                    AppError::Validation(
+                        "Set a sender address or give the domain to sign for".to_string(),
+                    )
+                })?,
+        };
+        let domain = normalize_domain(&domain)?;
+        let selector = match req.selector.as_deref().filter(|s| !s.trim().is_empty()) {
+            Some(s) => normalize_selector(s)?,
+            None => format!("isp{}", Utc::now().format("%Y%m")),
+        };
+
+        let (private_pem, public_key) = tokio::task::spawn_blocking(|| -> AppResult<_> {
+            let key = RsaPrivateKey::new(&mut rand::rngs::OsRng, KEY_BITS)
+                .map_err(|e| AppError::Internal(format!("Failed to generate DKIM key: {}", e)))?;
+            let pem = key
+                .to_pkcs8_pem(LineEnding::LF)
+                .map_err(|e| AppError::Internal(format!("Failed to encode DKIM key: {}", e)))?;
+            let public_der = RsaPublicKey::from(&key)
+                .to_public_key_der()
+                .map_err(|e| AppError::Internal(format!("Failed to encode DKIM key: {}", e)))?;
+            Ok((
+                pem.to_string(),
+                general_purpose::STANDARD.encode(public_der.as_bytes()),
+            ))
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("DKIM key generation failed: {}", e)))??;
+        let private_key_enc = encrypt_secret_for(SECRET_PURPOSE, &private_pem)?;
+
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO email_dkim_keys
+                (tenant_id, domain, selector, private_key_enc, public_key, is_enabled, created_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)
+            ON CONFLICT (tenant_id) DO UPDATE SET
+                domain = EXCLUDED.domain,
+                selector = EXCLUDED.selector,
+                private_key_enc = EXCLUDED.private_key_enc,
+                public_key = EXCLUDED.public_key,
+                is_enabled = EXCLUDED.is_enabled,
+                created_by = EXCLUDED.created_by,
+                created_at = EXCLUDED.created_at,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&domain)
+        .bind(&selector)
+        .bind(&private_key_enc)
+        .bind(&public_key)
+        .bind(true)
+        .bind(actor_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        self.status(tenant_id).await
+    }
+
+    pub async fn update(
+        &self,
+        tenant_id: &str,
+        req: UpdateDkimRequest,
+    ) -> AppResult<EmailDkimStatus> {
+        let result = sqlx::query(
+            "UPDATE email_dkim_keys SET is_enabled = $1, updated_at = $2 WHERE tenant_id = $3",
+        )
+        .bind(req.enabled)
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(
+                "No DKIM key has been generated".to_string(),
+            ));
+        }
+        self.status(tenant_id).await
+    }
+
+    pub async fn delete(&self, tenant_id: &str) -> AppResult<()> {
+        sqlx::query("DELETE FROM email_dkim_keys WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// The tenant's signer, if it has an enabled key.
+    pub async fn signer(&self, tenant_id: &str) -> AppResult<Option<DkimSigner>> {
+        let Some(key) = self.get_key(tenant_id).await? else {
+            return Ok(None);
+        };
+        if !key.is_enabled {
+            return Ok(None);
+        }
+        let pem = decrypt_secret_for(SECRET_PURPOSE, &key.private_key_enc)?;
+        DkimSigner::new(&key.domain, &key.selector, &pem).map(Some)
+    }
+
+    /// Sign `raw` for the tenant. Returns `None` when there is no enabled key or
+    /// signing fails, in which case the message goes out unsigned.
+    pub async fn sign_for_tenant(&self, tenant_id: &str, raw: &[u8]) -> Option<Vec<u8>> {
+        let signed = match self.signer(tenant_id).await {
+            Ok(Some(signer)) => signer.sign(raw, Utc::now()),
+            Ok(None) => return None,
+            Err(e) => Err(e),
+        };
+        match signed {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                tracing::warn!("Sending unsigned email for tenant {}: {}", tenant_id, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relaxed_canonicalization_matches_rfc_example() {
+        // RFC 6376 §3.4.5
+        let raw = b"A: X\r\nB : Y\t\r\n\tZ  \r\n\r\n C \r\nD \t E\r\n\r\n\r\n";
+        let (headers, body) = split_message(raw);
+        let headers = parse_headers(std::str::from_utf8(headers).unwrap());
+        let canon: Vec<String> = headers
+            .iter()
+            .map(|(n, v)| canonicalize_header(n, v))
+            .collect();
+        assert_eq!(canon, vec!["a:X", "b:Y Z"]);
+        assert_eq!(canonicalize_body(body), b" C\r\nD E\r\n".to_vec());
+        assert!(canonicalize_body(b"\r\n\r\n").is_empty());
+    }
+
+    #[test]
+    fn validates_domain_and_selector() {
+        assert_eq!(
+            normalize_domain(" Mail.Example.COM. ").unwrap(),
+            "mail.example.com"
+        );
+        assert!(normalize_domain("localhost").is_err());
+        assert!(normalize_domain("bad_domain.com").is_err());
+        assert_eq!(normalize_selector("ISP202603").unwrap(), "isp202603");
+        assert!(normalize_selector("a b").is_err());
+        assert_eq!(
+            domain_of("Net <Billing@Example.com>").as_deref(),
+            Some("example.com")
+        );
+    }
+
+    #[test]
+    fn dns_record_splits_long_values() {
+        let record = dns_record("example.com", "s1", &"A".repeat(400));
+        assert_eq!(record.name, "s1._domainkey.example.com");
+        assert_eq!(record.chunks.len(), 2);
+        assert_eq!(record.chunks.concat(), record.value);
+        assert!(record.chunks.iter().all(|c| c.len() <= TXT_CHUNK_LEN));
+    }
+
+    #[test]
+    fn signature_verifies_with_public_key() {
+        let key = RsaPrivateKey::new(&mut rand::rngs::OsRng, 1024).unwrap();
+        let pem = key.to_pkcs8_pem(LineEnding::LF).unwrap();
+        let signer = DkimSigner::new("example.com", "s1", &pem).unwrap();
+        let raw =
+            b"From: A <a@example.com>\r\nTo: b@example.org\r\nSubject: Hi\r\n\r\nHello  there\r\n";
+        let signed = signer.sign(raw, Utc::now()).unwrap();
+
+        let (header_block, body) = split_message(&signed);
+        let headers = parse_headers(std::str::from_utf8(header_block).unwrap());
+        let (_, dkim) = &headers[0];
+        let tags: Vec<(&str, &str)> = dkim
+            .split(';')
+            .filter_map(|t| t.trim().split_once('='))
+            .collect();
+        let tag = |name: &str| tags.iter().find(|(k, _)| *k == name).unwrap().1;
+        assert_eq!(tag("h"), "From:To:Subject");
+        assert_eq!(
+            tag("bh"),
+            general_purpose::STANDARD.encode(Sha256::digest(b"Hello there\r\n"))
+        );
+
+        let b_start = dkim.rfind("; b=").unwrap() + 4;
+        let mut hasher = Sha256::new();
+        for (name, value) in &headers[1..] {
+            hasher.update(canonicalize_header(name, value).as_bytes());
+            hasher.update(b"\r\n");
+        }
+        hasher.update(canonicalize_header("DKIM-Signature", &dkim[..b_start]).as_bytes());
+        let signature = general_purpose::STANDARD.decode(&dkim[b_start..]).unwrap();
+        RsaPublicKey::from(&key)
+            .verify(
+                Pkcs1v15Sign::new::<Sha256>(),
+                &hasher.finalize(),
+                &signature,
+            )
+            .unwrap();
+    }
+}
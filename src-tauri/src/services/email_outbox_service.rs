@@ -1,15 +1,77 @@
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
-use crate::services::{EmailService, SettingsService};
+use crate::models::BackgroundJob;
+use crate::services::email_suppression_service::{
+    classify_smtp_error, suppressed_error, BounceKind, MailEvent,
+};
+use crate::services::job_queue;
+use crate::services::{
+    EmailAttachment, EmailService, EmailSuppressionService, JobHandler, JobQueue, SettingsService,
+    UsageMetric, UsageService,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
 use chrono::{DateTime, Utc};
-use tracing::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
 use uuid::Uuid;
 
+const SENDER_JOB: &str = "email_outbox.send";
+
+/// Queue lane for outbox mail. Lower values are sent first; `High` is never
+/// held back by the hourly cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutboxPriority {
+    /// OTP, verification and password-reset mail.
+    High = 0,
+    Normal = 5,
+    /// Reminders and other mail fanned out to many users.
+    Bulk = 9,
+}
+
 #[derive(Clone)]
 pub struct EmailOutboxService {
     pool: DbPool,
     settings_service: SettingsService,
     email_service: EmailService,
+    suppressions: EmailSuppressionService,
+    usage: UsageService,
+}
+
+/// Attachment as stored in `email_outbox.attachments`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredAttachment {
+    filename: String,
+    content_type: String,
+    /// Base64.
+    data: String,
+}
+
+impl From<&EmailAttachment> for StoredAttachment {
+    fn from(a: &EmailAttachment) -> Self {
+        Self {
+            filename: a.filename.clone(),
+            content_type: a.content_type.clone(),
+            data: general_purpose::STANDARD.encode(&a.data),
+        }
+    }
+}
+
+impl TryFrom<StoredAttachment> for EmailAttachment {
+    type Error = AppError;
+
+    fn try_from(a: StoredAttachment) -> AppResult<Self> {
+        let data = general_purpose::STANDARD
+            .decode(a.data.as_bytes())
+            .map_err(|e| AppError::Internal(format!("Corrupt attachment {}: {}", a.filename, e)))?;
+        Ok(Self {
+            filename: a.filename,
+            content_type: a.content_type,
+            data,
+        })
+    }
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -20,7 +82,43 @@ struct EmailOutboxRow {
     pub subject: String,
     pub body: String,
     pub body_html: Option<String>,
+    pub attachments: Option<sqlx::types::Json<Vec<StoredAttachment>>>,
     pub max_attempts: i32,
+    pub priority: i32,
+}
+
+impl EmailOutboxRow {
+    fn attachments(&self) -> AppResult<Vec<EmailAttachment>> {
+        self.attachments
+            .as_ref()
+            .map(|a| a.0.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .map(EmailAttachment::try_from)
+            .collect()
+    }
+}
+
+/// Keep the rows that fit each scope's remaining hourly budget, in queue
+/// order. Scopes missing from `remaining` are uncapped; high-priority rows
+/// always pass but still use up budget.
+fn select_within_caps(
+    rows: Vec<EmailOutboxRow>,
+    remaining: &mut HashMap<String, i64>,
+) -> Vec<EmailOutboxRow> {
+    rows.into_iter()
+        .filter(|r| {
+            let scope = r.tenant_id.clone().unwrap_or_default();
+            match remaining.get_mut(&scope) {
+                None => true,
+                Some(left) if *left > 0 || r.priority <= OutboxPriority::High as i32 => {
+                    *left -= 1;
+                    true
+                }
+                Some(_) => false,
+            }
+        })
+        .collect()
 }
 
 impl EmailOutboxService {
@@ -30,12 +128,79 @@ impl EmailOutboxService {
         email_service: EmailService,
     ) -> Self {
         Self {
+            suppressions: EmailSuppressionService::new(pool.clone(), settings_service.clone()),
+            usage: UsageService::new(pool.clone()),
             pool,
             settings_service,
             email_service,
         }
     }
 
+    pub fn suppressions(&self) -> &EmailSuppressionService {
+        &self.suppressions
+    }
+
+    /// Refuse suppressed recipients. A failed lookup does not block sending.
+    async fn check_suppressed(&self, tenant_id: Option<&str>, to: &str) -> AppResult<()> {
+        match self.suppressions.active(tenant_id, to).await {
+            Ok(Some(s)) => Err(AppError::Validation(suppressed_error(&s))),
+            Ok(None) => Ok(()),
+            Err(e) => {
+                warn!("Suppression check failed for {}: {}", to, e);
+                Ok(())
+            }
+        }
+    }
+
+    /// After an SMTP failure, record the bounce if the reply was about the
+    /// recipient. Returns the kind so the caller can stop retrying hard bounces.
+    async fn record_smtp_bounce(
+        &self,
+        tenant_id: Option<&str>,
+        to: &str,
+        error: &str,
+    ) -> Option<BounceKind> {
+        if !error.contains("SMTP sending failed") {
+            return None;
+        }
+        let kind = classify_smtp_error(error)?;
+        let event = MailEvent {
+            email: to.trim().to_lowercase(),
+            kind,
+            detail: Some(error.chars().take(500).collect()),
+        };
+        if let Err(e) = self.suppressions.record(tenant_id, &event, "smtp").await {
+            warn!("Failed to record bounce for {}: {}", to, e);
+        }
+        Some(kind)
+    }
+
+    /// Send right away (outbox disabled), honouring suppressions.
+    async fn send_direct(
+        &self,
+        tenant_id: Option<&str>,
+        to: &str,
+        subject: &str,
+        body: &str,
+        body_html: Option<&str>,
+    ) -> AppResult<()> {
+        self.check_suppressed(tenant_id, to).await?;
+        match self
+            .email_service
+            .send_email_with_optional_html_for_tenant(tenant_id, to, subject, body, body_html)
+            .await
+        {
+            Ok(()) => {
+                self.suppressions.clear_soft_bounces(tenant_id, to).await;
+                Ok(())
+            }
+            Err(e) => {
+                self.record_smtp_bounce(tenant_id, to, &e.to_string()).await;
+                Err(e)
+            }
+        }
+    }
+
     async fn enabled(&self) -> bool {
         self.settings_service
             .get_value(None, "email_outbox_enabled")
@@ -68,6 +233,18 @@ impl EmailOutboxService {
             .clamp(5, 3600)
     }
 
+    /// Emails per hour for a tenant (global mail uses the global value). 0 = unlimited.
+    async fn hourly_limit(&self, tenant_id: Option<&str>) -> i64 {
+        self.settings_service
+            .get_value_fallback(tenant_id, "email_outbox_hourly_limit")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .unwrap_or(0)
+            .max(0)
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn enqueue(
         &self,
@@ -78,6 +255,34 @@ impl EmailOutboxService {
         body_html: Option<String>,
         max_attempts: Option<i32>,
         scheduled_at: Option<DateTime<Utc>>,
+        priority: OutboxPriority,
+    ) -> AppResult<String> {
+        self.insert_row(
+            tenant_id,
+            to_email,
+            subject,
+            body,
+            body_html,
+            &[],
+            max_attempts,
+            scheduled_at,
+            priority,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn insert_row(
+        &self,
+        tenant_id: Option<String>,
+        to_email: String,
+        subject: String,
+        body: String,
+        body_html: Option<String>,
+        attachments: &[EmailAttachment],
+        max_attempts: Option<i32>,
+        scheduled_at: Option<DateTime<Utc>>,
+        priority: OutboxPriority,
     ) -> AppResult<String> {
         let id = Uuid::new_v4().to_string();
         let now = Utc::now();
@@ -85,15 +290,23 @@ impl EmailOutboxService {
             .unwrap_or(self.max_attempts_default().await)
             .clamp(1, 25);
         let scheduled_at = scheduled_at.unwrap_or(now);
+        let attachments = (!attachments.is_empty()).then(|| {
+            sqlx::types::Json(
+                attachments
+                    .iter()
+                    .map(StoredAttachment::from)
+                    .collect::<Vec<_>>(),
+            )
+        });
 
         #[cfg(feature = "postgres")]
         {
             sqlx::query(
                 r#"
                 INSERT INTO email_outbox
-                  (id, tenant_id, to_email, subject, body, body_html, status, attempts, max_attempts, scheduled_at, last_error, sent_at, created_at, updated_at)
+                  (id, tenant_id, to_email, subject, body, body_html, attachments, status, attempts, max_attempts, priority, scheduled_at, last_error, sent_at, created_at, updated_at)
                 VALUES
-                  ($1,$2,$3,$4,$5,$6,'queued',0,$7,$8,NULL,NULL,$9,$10)
+                  ($1,$2,$3,$4,$5,$6,$7,'queued',0,$8,$9,$10,NULL,NULL,$11,$12)
             "#,
             )
             .bind(&id)
@@ -102,7 +315,9 @@ impl EmailOutboxService {
             .bind(&subject)
             .bind(&body)
             .bind(body_html.as_deref())
+            .bind(attachments)
             .bind(max_attempts)
+            .bind(priority as i32)
             .bind(scheduled_at)
             .bind(now)
             .bind(now)
@@ -111,6 +326,9 @@ impl EmailOutboxService {
             .map_err(AppError::Database)?;
         }
 
+        #[cfg(not(feature = "postgres"))]
+        let _ = attachments;
+
         Ok(id)
     }
 
@@ -122,28 +340,127 @@ impl EmailOutboxService {
         subject: &str,
         body: &str,
     ) -> AppResult<()> {
-        if self.enabled().await {
-            let _ = self
-                .enqueue(
-                    tenant_id,
-                    to.to_string(),
-                    subject.to_string(),
-                    body.to_string(),
-                    None,
-                    None,
-                    None,
-                )
+        self.send_or_enqueue_tracked(tenant_id, to, subject, body, None)
+            .await
+            .map(|_| ())
+    }
+
+    /// Like `send_or_enqueue_with_html`, but returns the outbox id when the
+    /// email was queued rather than sent directly.
+    pub async fn send_or_enqueue_tracked(
+        &self,
+        tenant_id: Option<String>,
+        to: &str,
+        subject: &str,
+        body: &str,
+        body_html: Option<String>,
+    ) -> AppResult<Option<String>> {
+        self.send_or_enqueue_in_lane(
+            tenant_id,
+            to,
+            subject,
+            body,
+            body_html,
+            OutboxPriority::Normal,
+        )
+        .await
+    }
+
+    /// Send email with optional HTML body using outbox when enabled.
+    pub async fn send_or_enqueue_with_html(
+        &self,
+        tenant_id: Option<String>,
+        to: &str,
+        subject: &str,
+        body_text: &str,
+        body_html: Option<String>,
+    ) -> AppResult<()> {
+        self.send_or_enqueue_in_lane(
+            tenant_id,
+            to,
+            subject,
+            body_text,
+            body_html,
+            OutboxPriority::Normal,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Plain-text email with files attached, queued in the normal lane where
+    /// the outbox is available and sent directly otherwise.
+    pub async fn send_or_enqueue_with_attachments(
+        &self,
+        tenant_id: Option<String>,
+        to: &str,
+        subject: &str,
+        body: &str,
+        attachments: &[EmailAttachment],
+    ) -> AppResult<()> {
+        if let Some(tid) = tenant_id.as_deref() {
+            self.usage
+                .enforce(tid, UsageMetric::EmailsMonthly, 1)
                 .await?;
-            Ok(())
+        }
+
+        if cfg!(feature = "postgres") && self.enabled().await {
+            self.insert_row(
+                tenant_id.clone(),
+                to.to_string(),
+                subject.to_string(),
+                body.to_string(),
+                None,
+                attachments,
+                None,
+                None,
+                OutboxPriority::Normal,
+            )
+            .await?;
         } else {
-            self.email_service
-                .send_email_for_tenant(tenant_id.as_deref(), to, subject, body)
+            self.check_suppressed(tenant_id.as_deref(), to).await?;
+            if let Err(e) = self
+                .email_service
+                .send_email_with_attachments_for_tenant(
+                    tenant_id.as_deref(),
+                    to,
+                    subject,
+                    body,
+                    attachments,
+                )
                 .await
+            {
+                self.record_smtp_bounce(tenant_id.as_deref(), to, &e.to_string())
+                    .await;
+                return Err(e);
+            }
+            self.suppressions
+                .clear_soft_bounces(tenant_id.as_deref(), to)
+                .await;
+        }
+
+        self.record_email_usage(tenant_id.as_deref()).await;
+        Ok(())
+    }
+
+    /// Count one email against the tenant's monthly quota. Metering problems
+    /// never stop mail that was already accepted.
+    async fn record_email_usage(&self, tenant_id: Option<&str>) {
+        let Some(tenant_id) = tenant_id else {
+            return;
+        };
+        if let Err(e) = self
+            .usage
+            .record(tenant_id, UsageMetric::EmailsMonthly, 1)
+            .await
+        {
+            warn!("Failed to record email usage for {}: {}", tenant_id, e);
         }
     }
 
-    /// Send email with optional HTML body using outbox when enabled.
-    pub async fn send_or_enqueue_with_html(
+    /// Send time-critical mail (OTP, password reset) ahead of everything else
+    /// in the queue. Falls back to a direct send where the outbox is not
+    /// available. Counted against the monthly quota but never blocked by it.
+    pub async fn send_priority(
         &self,
         tenant_id: Option<String>,
         to: &str,
@@ -151,28 +468,113 @@ impl EmailOutboxService {
         body_text: &str,
         body_html: Option<String>,
     ) -> AppResult<()> {
-        if self.enabled().await {
-            let _ = self
+        self.record_email_usage(tenant_id.as_deref()).await;
+        if cfg!(feature = "postgres") && self.enabled().await {
+            self.enqueue(
+                tenant_id,
+                to.to_string(),
+                subject.to_string(),
+                body_text.to_string(),
+                body_html,
+                None,
+                None,
+                OutboxPriority::High,
+            )
+            .await?;
+            job_queue::run_now(&self.pool, SENDER_JOB).await;
+            Ok(())
+        } else {
+            self.send_direct(
+                tenant_id.as_deref(),
+                to,
+                subject,
+                body_text,
+                body_html.as_deref(),
+            )
+            .await
+        }
+    }
+
+    async fn send_or_enqueue_in_lane(
+        &self,
+        tenant_id: Option<String>,
+        to: &str,
+        subject: &str,
+        body: &str,
+        body_html: Option<String>,
+        priority: OutboxPriority,
+    ) -> AppResult<Option<String>> {
+        if let Some(tid) = tenant_id.as_deref() {
+            self.usage
+                .enforce(tid, UsageMetric::EmailsMonthly, 1)
+                .await?;
+        }
+
+        let tracked = if self.enabled().await {
+            let id = self
                 .enqueue(
-                    tenant_id,
+                    tenant_id.clone(),
                     to.to_string(),
                     subject.to_string(),
-                    body_text.to_string(),
+                    body.to_string(),
                     body_html,
                     None,
                     None,
+                    priority,
                 )
                 .await?;
-            Ok(())
-        } else if let Some(html) = body_html.as_deref() {
-            self.email_service
-                .send_email_with_html_for_tenant(tenant_id.as_deref(), to, subject, body_text, html)
-                .await
+            Some(id)
         } else {
-            self.email_service
-                .send_email_for_tenant(tenant_id.as_deref(), to, subject, body_text)
+            self.send_direct(
+                tenant_id.as_deref(),
+                to,
+                subject,
+                body,
+                body_html.as_deref(),
+            )
+            .await?;
+            None
+        };
+
+        self.record_email_usage(tenant_id.as_deref()).await;
+        Ok(tracked)
+    }
+
+    /// Move a queued email to a new send time.
+    pub async fn reschedule(
+        &self,
+        tenant_id: Option<&str>,
+        id: &str,
+        scheduled_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        #[cfg(feature = "postgres")]
+        {
+            let mut qb: sqlx::QueryBuilder<sqlx::Postgres> =
+                sqlx::QueryBuilder::new("UPDATE email_outbox SET scheduled_at = ");
+            qb.push_bind(scheduled_at);
+            qb.push(", updated_at = ");
+            qb.push_bind(Utc::now());
+            qb.push(" WHERE id = ");
+            qb.push_bind(id);
+            qb.push(" AND status = 'queued'");
+            if let Some(tid) = tenant_id {
+                qb.push(" AND tenant_id = ");
+                qb.push_bind(tid);
+            }
+            let res = qb
+                .build()
+                .execute(&self.pool)
                 .await
+                .map_err(AppError::Database)?;
+            if res.rows_affected() == 0 {
+                return Err(AppError::NotFound("Queued email not found".to_string()));
+            }
         }
+
+        #[cfg(not(feature = "postgres"))]
+        let _ = (tenant_id, id, scheduled_at);
+
+        Ok(())
     }
 
     /// Send email to users (by user id). Uses outbox when enabled.
@@ -198,7 +600,14 @@ impl EmailOutboxService {
         for email in emails {
             // Keep it simple: enqueue each recipient separately.
             let _ = self
-                .send_or_enqueue(tenant_id.clone(), &email, subject, body)
+                .send_or_enqueue_in_lane(
+                    tenant_id.clone(),
+                    &email,
+                    subject,
+                    body,
+                    None,
+                    OutboxPriority::Bulk,
+                )
                 .await;
         }
 
@@ -228,92 +637,29 @@ impl EmailOutboxService {
 
         for email in emails {
             let _ = self
-                .send_or_enqueue_with_html(
+                .send_or_enqueue_in_lane(
                     tenant_id.clone(),
                     &email,
                     subject,
                     body_text,
                     body_html.clone(),
+                    OutboxPriority::Bulk,
                 )
                 .await;
         }
         Ok(())
     }
 
-    pub async fn start_sender(&self) {
-        let svc = self.clone();
-        tokio::spawn(async move {
-            info!("Email outbox sender started.");
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(10));
-            let lock_key = "email_outbox_sender";
-            let mut warned_missing_schema = false;
-
-            loop {
-                interval.tick().await;
-
-                if !svc.enabled().await {
-                    continue;
-                }
-
-                #[cfg(feature = "postgres")]
-                {
-                    let mut advisory_conn = match svc.pool.acquire().await {
-                        Ok(c) => c,
-                        Err(e) => {
-                            warn!(
-                                "Email outbox skipped: failed to acquire DB connection: {}",
-                                e
-                            );
-                            continue;
-                        }
-                    };
-
-                    let locked: bool =
-                        sqlx::query_scalar("SELECT pg_try_advisory_lock(hashtext($1))")
-                            .bind(lock_key)
-                            .fetch_one(&mut *advisory_conn)
-                            .await
-                            .unwrap_or(false);
-                    if !locked {
-                        continue;
-                    }
-
-                    let res = svc.process_batch().await;
-
-                    let _ =
-                        sqlx::query_scalar::<_, bool>("SELECT pg_advisory_unlock(hashtext($1))")
-                            .bind(lock_key)
-                            .fetch_one(&mut *advisory_conn)
-                            .await;
-
-                    if let Err(e) = res {
-                        let msg = e.to_string();
-                        if msg.contains("relation \"email_outbox\" does not exist") {
-                            if !warned_missing_schema {
-                                warned_missing_schema = true;
-                                warn!("Email outbox paused: database schema not migrated yet (missing email_outbox table).");
-                            }
-                        } else {
-                            error!("Email outbox sender failed: {}", msg);
-                        }
-                    }
-                    continue;
-                }
-
-                #[cfg(not(feature = "postgres"))]
-                if let Err(e) = svc.process_batch().await {
-                    let msg = e.to_string();
-                    if msg.contains("relation \"email_outbox\" does not exist") {
-                        if !warned_missing_schema {
-                            warned_missing_schema = true;
-                            warn!("Email outbox paused: database schema not migrated yet (missing email_outbox table).");
-                        }
-                    } else {
-                        error!("Email outbox sender failed: {}", msg);
-                    }
-                }
-            }
-        });
+    /// Sends queued mail every 10 seconds on the job queue; priority mail
+    /// brings the run forward.
+    pub async fn schedule_sender(&self, queue: &JobQueue) {
+        queue
+            .register_recurring(
+                SENDER_JOB,
+                std::time::Duration::from_secs(10),
+                Arc::new(self.clone()),
+            )
+            .await;
     }
 
     async fn process_batch(&self) -> AppResult<()> {
@@ -327,24 +673,77 @@ impl EmailOutboxService {
             let now = Utc::now();
             let base_delay = self.base_delay_seconds().await;
 
+            let high = OutboxPriority::High as i32;
+
+            // Remaining hourly budget per capped scope ('' = global mail).
+            // Mail being sent right now counts against the budget too.
+            let scopes: Vec<String> = sqlx::query_scalar(
+                r#"
+                SELECT DISTINCT COALESCE(tenant_id, '')
+                FROM email_outbox
+                WHERE status = 'queued'
+                  AND scheduled_at <= $1
+                  AND priority > $2
+            "#,
+            )
+            .bind(now)
+            .bind(high)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::Database)?;
+
+            let mut remaining: HashMap<String, i64> = HashMap::new();
+            for scope in scopes {
+                let tenant = Some(scope.as_str()).filter(|s| !s.is_empty());
+                let limit = self.hourly_limit(tenant).await;
+                if limit == 0 {
+                    continue;
+                }
+                let used: i64 = sqlx::query_scalar(
+                    r#"
+                    SELECT COUNT(*)::bigint
+                    FROM email_outbox
+                    WHERE COALESCE(tenant_id, '') = $1
+                      AND (status = 'sending' OR (status = 'sent' AND sent_at >= $2))
+                "#,
+                )
+                .bind(&scope)
+                .bind(now - chrono::Duration::hours(1))
+                .fetch_one(&self.pool)
+                .await
+                .map_err(AppError::Database)?;
+                remaining.insert(scope, limit - used);
+            }
+            let exhausted: Vec<String> = remaining
+                .iter()
+                .filter(|(_, left)| **left <= 0)
+                .map(|(scope, _)| scope.clone())
+                .collect();
+
             let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
 
             let rows: Vec<EmailOutboxRow> = sqlx::query_as(
                 r#"
-                SELECT id::text, tenant_id::text as tenant_id, to_email, subject, body, body_html, max_attempts
+                SELECT id::text, tenant_id::text as tenant_id, to_email, subject, body, body_html, attachments, max_attempts, priority
                 FROM email_outbox
                 WHERE status = 'queued'
                   AND scheduled_at <= $1
-                ORDER BY scheduled_at ASC, created_at ASC
+                  AND (priority <= $2 OR COALESCE(tenant_id, '') <> ALL($3))
+                ORDER BY priority ASC, scheduled_at ASC, created_at ASC
                 LIMIT 50
                 FOR UPDATE SKIP LOCKED
             "#,
             )
             .bind(now)
+            .bind(high)
+            .bind(&exhausted)
             .fetch_all(&mut *tx)
             .await
             .map_err(AppError::Database)?;
 
+            // Rows over budget stay queued for a later tick.
+            let rows = select_within_caps(rows, &mut remaining);
+
             if rows.is_empty() {
                 tx.commit().await.map_err(AppError::Database)?;
                 return Ok(());
@@ -379,18 +778,41 @@ impl EmailOutboxService {
                         .await
                         .unwrap_or(r.max_attempts);
 
-                match self
-                    .email_service
-                    .send_email_with_optional_html_for_tenant(
-                        r.tenant_id.as_deref(),
-                        &r.to_email,
-                        &r.subject,
-                        &r.body,
-                        r.body_html.as_deref(),
-                    )
-                    .await
-                {
+                let check = self
+                    .check_suppressed(r.tenant_id.as_deref(), &r.to_email)
+                    .await;
+                let suppressed = check.is_err();
+                let sent = match check.and_then(|()| r.attachments()) {
+                    Ok(attachments) if !attachments.is_empty() => {
+                        self.email_service
+                            .send_email_with_attachments_for_tenant(
+                                r.tenant_id.as_deref(),
+                                &r.to_email,
+                                &r.subject,
+                                &r.body,
+                                &attachments,
+                            )
+                            .await
+                    }
                     Ok(_) => {
+                        self.email_service
+                            .send_email_with_optional_html_for_tenant(
+                                r.tenant_id.as_deref(),
+                                &r.to_email,
+                                &r.subject,
+                                &r.body,
+                                r.body_html.as_deref(),
+                            )
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+
+                match sent {
+                    Ok(_) => {
+                        self.suppressions
+                            .clear_soft_bounces(r.tenant_id.as_deref(), &r.to_email)
+                            .await;
                         let _ = sqlx::query(
                             "UPDATE email_outbox SET status = 'sent', sent_at = $1, updated_at = $1, last_error = NULL WHERE id = $2",
                         )
@@ -398,10 +820,20 @@ impl EmailOutboxService {
                         .bind(&r.id)
                         .execute(&self.pool)
                         .await;
+                        crate::services::NotificationDeliveryService::sync_email_outbox(
+                            &self.pool, &r.id, "sent", None,
+                        )
+                        .await;
                     }
                     Err(e) => {
                         let err_msg = format!("{}", e);
-                        let is_final = attempts >= max_attempts;
+                        let bounce = self
+                            .record_smtp_bounce(r.tenant_id.as_deref(), &r.to_email, &err_msg)
+                            .await;
+                        // Suppressed or hard-bounced addresses will not start accepting mail on retry.
+                        let is_final = attempts >= max_attempts
+                            || suppressed
+                            || bounce == Some(BounceKind::Hard);
                         let next_delay = (base_delay
                             * (2_i64.saturating_pow((attempts - 1).max(0) as u32)))
                         .min(60 * 60);
@@ -416,6 +848,13 @@ impl EmailOutboxService {
                             .bind(&r.id)
                             .execute(&self.pool)
                             .await;
+                            crate::services::NotificationDeliveryService::sync_email_outbox(
+                                &self.pool,
+                                &r.id,
+                                "failed",
+                                Some(&err_msg),
+                            )
+                            .await;
                         } else {
                             let _ = sqlx::query(
                                 "UPDATE email_outbox SET status = 'queued', scheduled_at = $1, last_error = $2, updated_at = $3 WHERE id = $4",
@@ -426,6 +865,13 @@ impl EmailOutboxService {
                             .bind(&r.id)
                             .execute(&self.pool)
                             .await;
+                            crate::services::NotificationDeliveryService::sync_email_outbox(
+                                &self.pool,
+                                &r.id,
+                                "queued",
+                                Some(&err_msg),
+                            )
+                            .await;
                         }
                     }
                 }
@@ -435,3 +881,64 @@ impl EmailOutboxService {
         }
     }
 }
+
+#[async_trait]
+impl JobHandler for EmailOutboxService {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        if !self.enabled().await {
+            return Ok(());
+        }
+        self.process_batch().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: &str, tenant: Option<&str>, priority: OutboxPriority) -> EmailOutboxRow {
+        EmailOutboxRow {
+            id: id.to_string(),
+            tenant_id: tenant.map(str::to_string),
+            to_email: "user@example.com".to_string(),
+            subject: "s".to_string(),
+            body: "b".to_string(),
+            body_html: None,
+            attachments: None,
+            max_attempts: 5,
+            priority: priority as i32,
+        }
+    }
+
+    fn ids(rows: &[EmailOutboxRow]) -> Vec<&str> {
+        rows.iter().map(|r| r.id.as_str()).collect()
+    }
+
+    #[test]
+    fn caps_hold_bulk_mail_but_not_priority_mail() {
+        let rows = vec![
+            row("otp", Some("t1"), OutboxPriority::High),
+            row("n1", Some("t1"), OutboxPriority::Normal),
+            row("b1", Some("t1"), OutboxPriority::Bulk),
+            row("g1", None, OutboxPriority::Bulk),
+            row("t2", Some("t2"), OutboxPriority::Bulk),
+        ];
+        let mut remaining = HashMap::from([("t1".to_string(), 2), ("".to_string(), 0)]);
+
+        let picked = select_within_caps(rows, &mut remaining);
+        assert_eq!(ids(&picked), vec!["otp", "n1", "t2"]);
+        assert_eq!(remaining["t1"], 0);
+    }
+
+    #[test]
+    fn priority_mail_goes_out_when_budget_is_spent() {
+        let rows = vec![
+            row("otp1", Some("t1"), OutboxPriority::High),
+            row("otp2", Some("t1"), OutboxPriority::High),
+        ];
+        let mut remaining = HashMap::from([("t1".to_string(), -3)]);
+
+        let picked = select_within_caps(rows, &mut remaining);
+        assert_eq!(ids(&picked), vec!["otp1", "otp2"]);
+    }
+}
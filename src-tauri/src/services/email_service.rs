@@ -4,8 +4,9 @@
 //! Note: SMTP support requires uncommenting lettre in Cargo.toml after Windows restart
 
 use crate::error::{AppError, AppResult};
-use crate::services::SettingsService;
-use lettre::message::{header::ContentType, MultiPart, SinglePart};
+use crate::services::{EmailDkimService, SettingsService};
+use base64::{engine::general_purpose, Engine as _};
+use lettre::message::{header::ContentType, Attachment, MultiPart, SinglePart};
 use lettre::transport::smtp::authentication::Credentials;
 use lettre::transport::smtp::client::Tls;
 use lettre::transport::smtp::client::TlsParameters;
@@ -14,6 +15,14 @@ use serde::Serialize;
 use std::time::Instant;
 use tracing::info;
 
+/// File attached to an outgoing email.
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SmtpConnectionTestResult {
     pub ok: bool,
@@ -29,6 +38,7 @@ pub struct SmtpConnectionTestResult {
 #[derive(Clone)]
 pub struct EmailService {
     settings_service: SettingsService,
+    dkim: Option<EmailDkimService>,
 }
 
 /// Email configuration from settings  
@@ -56,6 +66,15 @@ struct ResendRequest {
     text: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     html: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<ResendAttachment>,
+}
+
+#[derive(Debug, Serialize)]
+struct ResendAttachment {
+    filename: String,
+    /// Base64 encoded.
+    content: String,
 }
 
 /// Email request for SendGrid API
@@ -65,6 +84,18 @@ struct SendGridRequest {
     from: SendGridEmail,
     subject: String,
     content: Vec<SendGridContent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<SendGridAttachment>,
+}
+
+#[derive(Debug, Serialize)]
+struct SendGridAttachment {
+    /// Base64 encoded.
+    content: String,
+    filename: String,
+    #[serde(rename = "type")]
+    content_type: String,
+    disposition: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -96,11 +127,33 @@ struct WebhookRequest {
     body: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     body_html: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<WebhookAttachment>,
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookAttachment {
+    filename: String,
+    content_type: String,
+    content_base64: String,
 }
 
 impl EmailService {
     pub fn new(settings_service: SettingsService) -> Self {
-        Self { settings_service }
+        Self {
+            settings_service,
+            dkim: None,
+        }
+    }
+
+    /// Sign SMTP mail with the tenant's DKIM key when it has one enabled.
+    pub fn with_dkim(mut self, dkim: EmailDkimService) -> Self {
+        self.dkim = Some(dkim);
+        self
+    }
+
+    pub fn dkim(&self) -> Option<&EmailDkimService> {
+        self.dkim.as_ref()
     }
 
     async fn get_value_fallback(&self, tenant_id: Option<&str>, key: &str) -> Option<String> {
@@ -196,14 +249,20 @@ impl EmailService {
         info!("Sending email to {} via {}", to, config.provider);
 
         match config.provider.as_str() {
-            "resend" => self.send_via_resend(&config, to, subject, body, None).await,
-            "smtp" => self.send_via_smtp(&config, to, subject, body).await,
+            "resend" => {
+                self.send_via_resend(&config, to, subject, body, None, &[])
+                    .await
+            }
+            "smtp" => {
+                self.send_via_smtp(&config, tenant_id, to, subject, body)
+                    .await
+            }
             "sendgrid" => {
-                self.send_via_sendgrid(&config, to, subject, body, None)
+                self.send_via_sendgrid(&config, to, subject, body, None, &[])
                     .await
             }
             "webhook" => {
-                self.send_via_webhook(&config, to, subject, body, None)
+                self.send_via_webhook(&config, to, subject, body, None, &[])
                     .await
             }
             _ => Err(AppError::Validation(format!(
@@ -243,19 +302,89 @@ impl EmailService {
 
         match config.provider.as_str() {
             "resend" => {
-                self.send_via_resend(&config, to, subject, body_text, Some(body_html.to_string()))
-                    .await
+                self.send_via_resend(
+                    &config,
+                    to,
+                    subject,
+                    body_text,
+                    Some(body_html.to_string()),
+                    &[],
+                )
+                .await
             }
             "smtp" => {
-                self.send_via_smtp_html(&config, to, subject, body_text, body_html)
+                self.send_via_smtp_html(&config, tenant_id, to, subject, body_text, body_html)
+                    .await
+            }
+            "sendgrid" => {
+                self.send_via_sendgrid(
+                    &config,
+                    to,
+                    subject,
+                    body_text,
+                    Some(body_html.to_string()),
+                    &[],
+                )
+                .await
+            }
+            "webhook" => {
+                self.send_via_webhook(
+                    &config,
+                    to,
+                    subject,
+                    body_text,
+                    Some(body_html.to_string()),
+                    &[],
+                )
+                .await
+            }
+            _ => Err(AppError::Validation(format!(
+                "Unknown email provider: {}",
+                config.provider
+            ))),
+        }
+    }
+
+    /// Plain-text email with files attached. Use
+    /// `EmailOutboxService::send_or_enqueue_with_attachments` to queue it.
+    pub async fn send_email_with_attachments_for_tenant(
+        &self,
+        tenant_id: Option<&str>,
+        to: &str,
+        subject: &str,
+        body_text: &str,
+        attachments: &[EmailAttachment],
+    ) -> AppResult<()> {
+        let config = self.get_config_for(tenant_id).await?;
+        info!(
+            "Sending email with {} attachment(s) to {} via {}",
+            attachments.len(),
+            to,
+            config.provider
+        );
+
+        match config.provider.as_str() {
+            "resend" => {
+                self.send_via_resend(&config, to, subject, body_text, None, attachments)
                     .await
             }
+            "smtp" => {
+                self.send_via_smtp_attachments(
+                    &config,
+                    tenant_id,
+                    to,
+                    subject,
+                    body_text,
+                    attachments,
+                )
+                .await
+            }
             "sendgrid" => {
-                self.send_via_sendgrid(&config, to, subject, body_text, Some(body_html.to_string()))
+                self.send_via_sendgrid(&config, to, subject, body_text, None, attachments)
                     .await
             }
             "webhook" => {
-                self.send_via_webhook(&config, to, subject, body_text, Some(body_html.to_string()))
+                self.send_via_webhook(&config, to, subject, body_text, None, attachments)
                     .await
             }
             _ => Err(AppError::Validation(format!(
@@ -324,10 +453,32 @@ impl EmailService {
         Ok(mailer)
     }
 
+    /// Hand a built message to the SMTP relay, DKIM-signed when the tenant has
+    /// a key. A signing problem is logged and the message goes out unsigned.
+    async fn deliver_smtp(
+        &self,
+        config: &EmailConfig,
+        tenant_id: Option<&str>,
+        email: Message,
+    ) -> AppResult<()> {
+        let mailer = self.build_smtp_transport(config)?;
+        let signed = match (&self.dkim, tenant_id) {
+            (Some(dkim), Some(tid)) => dkim.sign_for_tenant(tid, &email.formatted()).await,
+            _ => None,
+        };
+        let result = match signed {
+            Some(raw) => mailer.send_raw(email.envelope(), &raw).await,
+            None => mailer.send(email).await,
+        };
+        result.map_err(|e| AppError::Internal(format!("SMTP sending failed: {}", e)))?;
+        Ok(())
+    }
+
     /// Send via SMTP
     async fn send_via_smtp(
         &self,
         config: &EmailConfig,
+        tenant_id: Option<&str>,
         to: &str,
         subject: &str,
         body: &str,
@@ -345,12 +496,7 @@ impl EmailService {
             .body(body.to_string())
             .map_err(|e| AppError::Internal(format!("Failed to build email: {}", e)))?;
 
-        let mailer = self.build_smtp_transport(config)?;
-
-        mailer
-            .send(email)
-            .await
-            .map_err(|e| AppError::Internal(format!("SMTP sending failed: {}", e)))?;
+        self.deliver_smtp(config, tenant_id, email).await?;
 
         info!("Email sent via SMTP");
         Ok(())
@@ -359,6 +505,7 @@ impl EmailService {
     async fn send_via_smtp_html(
         &self,
         config: &EmailConfig,
+        tenant_id: Option<&str>,
         to: &str,
         subject: &str,
         body_text: &str,
@@ -391,11 +538,52 @@ impl EmailService {
             .multipart(multipart)
             .map_err(|e| AppError::Internal(format!("Failed to build email: {}", e)))?;
 
-        let mailer = self.build_smtp_transport(config)?;
-        mailer
-            .send(email)
-            .await
-            .map_err(|e| AppError::Internal(format!("SMTP sending failed: {}", e)))?;
+        self.deliver_smtp(config, tenant_id, email).await?;
+
+        info!("Email sent via SMTP");
+        Ok(())
+    }
+
+    async fn send_via_smtp_attachments(
+        &self,
+        config: &EmailConfig,
+        tenant_id: Option<&str>,
+        to: &str,
+        subject: &str,
+        body_text: &str,
+        attachments: &[EmailAttachment],
+    ) -> AppResult<()> {
+        let builder = Message::builder()
+            .from(
+                format!("{} <{}>", config.from_name, config.from_email)
+                    .parse()
+                    .map_err(|e| AppError::Validation(format!("Invalid from address: {}", e)))?,
+            )
+            .to(to
+                .parse()
+                .map_err(|e| AppError::Validation(format!("Invalid to address: {}", e)))?)
+            .subject(subject);
+
+        let mut multipart = MultiPart::mixed().singlepart(
+            SinglePart::builder()
+                .header(ContentType::TEXT_PLAIN)
+                .body(body_text.to_string()),
+        );
+        for attachment in attachments {
+            let content_type = ContentType::parse(&attachment.content_type).map_err(|e| {
+                AppError::Internal(format!("Invalid attachment content type: {}", e))
+            })?;
+            multipart = multipart.singlepart(
+                Attachment::new(attachment.filename.clone())
+                    .body(attachment.data.clone(), content_type),
+            );
+        }
+
+        let email = builder
+            .multipart(multipart)
+            .map_err(|e| AppError::Internal(format!("Failed to build email: {}", e)))?;
+
+        self.deliver_smtp(config, tenant_id, email).await?;
 
         info!("Email sent via SMTP");
         Ok(())
@@ -409,6 +597,7 @@ impl EmailService {
         subject: &str,
         body_text: &str,
         body_html: Option<String>,
+        attachments: &[EmailAttachment],
     ) -> AppResult<()> {
         if config.api_key.is_empty() {
             return Err(AppError::Validation(
@@ -423,6 +612,13 @@ impl EmailService {
             subject: subject.to_string(),
             text: body_text.to_string(),
             html: body_html,
+            attachments: attachments
+                .iter()
+                .map(|a| ResendAttachment {
+                    filename: a.filename.clone(),
+                    content: general_purpose::STANDARD.encode(&a.data),
+                })
+                .collect(),
         };
 
         let response = client
@@ -450,6 +646,7 @@ impl EmailService {
         subject: &str,
         body_text: &str,
         body_html: Option<String>,
+        attachments: &[EmailAttachment],
     ) -> AppResult<()> {
         if config.api_key.is_empty() {
             return Err(AppError::Validation(
@@ -482,6 +679,15 @@ impl EmailService {
             },
             subject: subject.to_string(),
             content,
+            attachments: attachments
+                .iter()
+                .map(|a| SendGridAttachment {
+                    content: general_purpose::STANDARD.encode(&a.data),
+                    filename: a.filename.clone(),
+                    content_type: a.content_type.clone(),
+                    disposition: "attachment".to_string(),
+                })
+                .collect(),
         };
 
         let response = client
@@ -509,6 +715,7 @@ impl EmailService {
         subject: &str,
         body_text: &str,
         body_html: Option<String>,
+        attachments: &[EmailAttachment],
     ) -> AppResult<()> {
         if config.webhook_url.is_empty() {
             return Err(AppError::Validation(
@@ -524,6 +731,14 @@ impl EmailService {
             subject: subject.to_string(),
             body: body_text.to_string(),
             body_html,
+            attachments: attachments
+                .iter()
+                .map(|a| WebhookAttachment {
+                    filename: a.filename.clone(),
+                    content_type: a.content_type.clone(),
+                    content_base64: general_purpose::STANDARD.encode(&a.data),
+                })
+                .collect(),
         };
 
         let response = client
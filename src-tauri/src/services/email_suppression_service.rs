@@ -0,0 +1,716 @@
+//! Bounce and complaint handling.
+//!
+//! Bounces reach us two ways: the SMTP relay rejecting a recipient while the
+//! outbox sends (classified from the reply code), and provider webhooks (SES
+//! via SNS, SendGrid event webhook, Mailgun webhooks). Both end up as a row in
+//! `email_suppressions`, and the outbox refuses to send to suppressed addresses.
+//!
+//! Hard bounces, complaints and manual entries suppress immediately. Soft
+//! bounces (mailbox full and the like) are counted and suppress once they
+//! reach `SOFT_BOUNCE_LIMIT`; a successful send clears the count.
+//!
+//! Webhooks are addressed as `/api/email-suppressions/webhooks/{provider}/{token}`
+//! where `token` is the tenant's (or the global) `email_events_webhook_token`
+//! setting, which also tells us whose list the event belongs to.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AddEmailSuppressionRequest, EmailEventsResult, EmailSuppression, PaginatedResponse,
+};
+use crate::services::event_outbox_service::hmac_sha256_hex;
+use crate::services::SettingsService;
+use chrono::Utc;
+use serde_json::Value;
+use sqlx::QueryBuilder;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+#[cfg(feature = "postgres")]
+type Db = sqlx::Postgres;
+#[cfg(feature = "sqlite")]
+type Db = sqlx::Sqlite;
+
+/// Soft bounces in a row before an address is suppressed.
+pub const SOFT_BOUNCE_LIMIT: i32 = 3;
+pub const REASONS: &[&str] = &["hard_bounce", "soft_bounce", "complaint", "manual"];
+
+const MAX_PER_PAGE: u32 = 100;
+const MAX_DETAIL_LEN: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BounceKind {
+    Hard,
+    Soft,
+    Complaint,
+    Manual,
+}
+
+impl BounceKind {
+    pub fn reason(self) -> &'static str {
+        match self {
+            BounceKind::Hard => "hard_bounce",
+            BounceKind::Soft => "soft_bounce",
+            BounceKind::Complaint => "complaint",
+            BounceKind::Manual => "manual",
+        }
+    }
+}
+
+/// One bounce or complaint for one recipient.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MailEvent {
+    pub email: String,
+    pub kind: BounceKind,
+    pub detail: Option<String>,
+}
+
+impl MailEvent {
+    fn new(email: &str, kind: BounceKind, detail: Option<&str>) -> Option<Self> {
+        let email = normalize_email(email)?;
+        Some(Self {
+            email,
+            kind,
+            detail: detail
+                .map(str::trim)
+                .filter(|d| !d.is_empty())
+                .map(|d| d.chars().take(MAX_DETAIL_LEN).collect()),
+        })
+    }
+}
+
+fn normalize_email(email: &str) -> Option<String> {
+    let email = email
+        .trim()
+        .trim_matches(|c| c == '<' || c == '>')
+        .to_lowercase();
+    match email.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && domain.contains('.') => Some(email),
+        _ => None,
+    }
+}
+
+/// Whether an SMTP rejection says something about the recipient address.
+///
+/// Looks at the enhanced status code (RFC 3463) first and falls back to the
+/// basic reply code plus wording. Errors about our own setup (auth, relay
+/// policy, connection) return `None`; they say nothing about the address.
+pub fn classify_smtp_error(message: &str) -> Option<BounceKind> {
+    let lower = message.to_lowercase();
+
+    let enhanced = lower
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map(|token| token.trim_matches('.'))
+        .find(|token| {
+            let parts: Vec<&str> = token.split('.').collect();
+            parts.len() == 3
+                && matches!(parts[0], "4" | "5")
+                && parts[1..]
+                    .iter()
+                    .all(|p| (1..=3).contains(&p.len()) && p.chars().all(|c| c.is_ascii_digit()))
+        });
+    if let Some(code) = enhanced {
+        let mut parts = code.split('.');
+        let (class, subject, detail) = (parts.next()?, parts.next()?, parts.next()?);
+        return match (class, subject, detail) {
+            (_, "2", "2") => Some(BounceKind::Soft),
+            ("5", "1", _) | ("5", "2", "1") => Some(BounceKind::Hard),
+            _ => None,
+        };
+    }
+
+    let reply = lower
+        .split(|c: char| !c.is_ascii_digit())
+        .find(|t| t.len() == 3 && (t.starts_with('4') || t.starts_with('5')));
+    let mentions = |words: &[&str]| words.iter().any(|w| lower.contains(w));
+    match reply {
+        Some("552") | Some("452")
+            if mentions(&["mailbox full", "quota", "insufficient storage"]) =>
+        {
+            Some(BounceKind::Soft)
+        }
+        Some("550") | Some("551") | Some("553")
+            if mentions(&[
+                "user unknown",
+                "unknown user",
+                "no such user",
+                "does not exist",
+                "mailbox unavailable",
+                "mailbox not found",
+                "invalid recipient",
+                "recipient rejected",
+                "address rejected",
+            ]) =>
+        {
+            Some(BounceKind::Hard)
+        }
+        _ => None,
+    }
+}
+
+/// Amazon SES notifications, either raw or wrapped in an SNS envelope.
+pub fn parse_ses(payload: &Value) -> Vec<MailEvent> {
+    let unwrapped;
+    let msg = match payload["Message"].as_str() {
+        Some(inner) => {
+            unwrapped = serde_json::from_str::<Value>(inner).unwrap_or(Value::Null);
+            &unwrapped
+        }
+        None => payload,
+    };
+    let kind = msg["notificationType"]
+        .as_str()
+        .or_else(|| msg["eventType"].as_str())
+        .unwrap_or_default();
+
+    let recipients = |list: &Value, kind: BounceKind, detail: &dyn Fn(&Value) -> Option<String>| {
+        list.as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|r| MailEvent::new(r["emailAddress"].as_str()?, kind, detail(r).as_deref()))
+            .collect::<Vec<_>>()
+    };
+
+    match kind {
+        "Bounce" => {
+            let bounce = &msg["bounce"];
+            let kind = if bounce["bounceType"].as_str() == Some("Permanent") {
+                BounceKind::Hard
+            } else {
+                BounceKind::Soft
+            };
+            let sub_type = bounce["bounceSubType"].as_str().map(str::to_string);
+            recipients(&bounce["bouncedRecipients"], kind, &|r| {
+                r["diagnosticCode"]
+                    .as_str()
+                    .map(str::to_string)
+                    .or_else(|| sub_type.clone())
+            })
+        }
+        "Complaint" => {
+            let complaint = &msg["complaint"];
+            let feedback = complaint["complaintFeedbackType"]
+                .as_str()
+                .map(str::to_string);
+            recipients(
+                &complaint["complainedRecipients"],
+                BounceKind::Complaint,
+                &|_| feedback.clone(),
+            )
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// SendGrid event webhook: an array of events.
+pub fn parse_sendgrid(payload: &Value) -> Vec<MailEvent> {
+    payload
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|ev| {
+            let email = ev["email"].as_str()?;
+            let reason = ev["reason"].as_str();
+            let kind = match ev["event"].as_str()? {
+                "bounce" if ev["type"].as_str() == Some("blocked") => BounceKind::Soft,
+                "bounce" => BounceKind::Hard,
+                "spamreport" => BounceKind::Complaint,
+                "dropped" => match reason.unwrap_or_default() {
+                    "Bounced Address" | "Invalid" => BounceKind::Hard,
+                    "Spam Reporting Address" => BounceKind::Complaint,
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            MailEvent::new(email, kind, reason)
+        })
+        .collect()
+}
+
+/// Mailgun webhooks (`event-data` JSON format).
+pub fn parse_mailgun(payload: &Value) -> Vec<MailEvent> {
+    let ev = &payload["event-data"];
+    let Some(email) = ev["recipient"].as_str() else {
+        return Vec::new();
+    };
+    let status = &ev["delivery-status"];
+    let detail = status["description"]
+        .as_str()
+        .filter(|d| !d.is_empty())
+        .or_else(|| status["message"].as_str());
+    let kind = match ev["event"].as_str().unwrap_or_default() {
+        "failed" if ev["severity"].as_str() == Some("permanent") => BounceKind::Hard,
+        "failed" => BounceKind::Soft,
+        "complained" => BounceKind::Complaint,
+        _ => return Vec::new(),
+    };
+    MailEvent::new(email, kind, detail).into_iter().collect()
+}
+
+/// Mailgun signs `timestamp + token` with the webhook signing key.
+fn mailgun_signature_ok(signing_key: &str, payload: &Value) -> bool {
+    let sig = &payload["signature"];
+    let (Some(timestamp), Some(token), Some(signature)) = (
+        sig["timestamp"].as_str(),
+        sig["token"].as_str(),
+        sig["signature"].as_str(),
+    ) else {
+        return false;
+    };
+    let expected = hmac_sha256_hex(
+        signing_key.as_bytes(),
+        format!("{}{}", timestamp, token).as_bytes(),
+    );
+    let signature = signature.to_ascii_lowercase();
+    expected.len() == signature.len()
+        && expected
+            .bytes()
+            .zip(signature.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// `tenant_id = ..`, or `tenant_id IS NULL` for the global list.
+fn push_scope(qb: &mut QueryBuilder<'_, Db>, tenant_id: Option<&str>) {
+    match tenant_id {
+        Some(tid) => {
+            qb.push(" tenant_id = ");
+            qb.push_bind(tid.to_string());
+        }
+        None => {
+            qb.push(" tenant_id IS NULL");
+        }
+    }
+}
+
+fn push_list_filters(
+    qb: &mut QueryBuilder<'_, Db>,
+    tenant_id: Option<&str>,
+    reason: Option<&str>,
+    search: Option<&str>,
+) {
+    push_scope(qb, tenant_id);
+    if let Some(r) = reason {
+        qb.push(" AND reason = ");
+        qb.push_bind(r.to_string());
+    }
+    if let Some(s) = search {
+        qb.push(" AND email LIKE ");
+        qb.push_bind(format!("%{}%", s));
+    }
+}
+
+/// Error text for a send refused because of a suppression.
+pub fn suppressed_error(s: &EmailSuppression) -> String {
+    format!(
+        "Recipient {} is suppressed ({})",
+        s.email,
+        s.reason.replace('_', " ")
+    )
+}
+
+#[derive(Clone)]
+pub struct EmailSuppressionService {
+    pool: DbPool,
+    settings_service: SettingsService,
+}
+
+impl EmailSuppressionService {
+    pub fn new(pool: DbPool, settings_service: SettingsService) -> Self {
+        Self {
+            pool,
+            settings_service,
+        }
+    }
+
+    async fn find(
+        &self,
+        tenant_id: Option<&str>,
+        email: &str,
+    ) -> AppResult<Option<EmailSuppression>> {
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new("SELECT * FROM email_suppressions WHERE");
+        push_scope(&mut qb, tenant_id);
+        qb.push(" AND email = ");
+        qb.push_bind(email.to_string());
+        Ok(qb
+            .build_query_as::<EmailSuppression>()
+            .fetch_optional(&self.pool)
+            .await?)
+    }
+
+    /// The suppression stopping mail to `email`, if there is one.
+    pub async fn active(
+        &self,
+        tenant_id: Option<&str>,
+        email: &str,
+    ) -> AppResult<Option<EmailSuppression>> {
+        let Some(email) = normalize_email(email) else {
+            return Ok(None);
+        };
+        Ok(self
+            .find(tenant_id, &email)
+            .await?
+            .filter(|s| s.is_suppressed))
+    }
+
+    /// Record a bounce or complaint and return the resulting entry. A soft
+    /// bounce never downgrades an existing hard bounce or complaint.
+    pub async fn record(
+        &self,
+        tenant_id: Option<&str>,
+        event: &MailEvent,
+        source: &str,
+    ) -> AppResult<EmailSuppression> {
+        let now = Utc::now();
+        let existing = self.find(tenant_id, &event.email).await?;
+        let count = existing.as_ref().map_or(1, |e| e.bounce_count + 1);
+        let (reason, suppressed) = match (event.kind, &existing) {
+            (BounceKind::Soft, Some(e)) if e.reason != "soft_bounce" => {
+                (e.reason.clone(), e.is_suppressed)
+            }
+            (BounceKind::Soft, _) => ("soft_bounce".to_string(), count >= SOFT_BOUNCE_LIMIT),
+            (kind, _) => (kind.reason().to_string(), true),
+        };
+
+        match existing {
+            Some(e) => {
+                sqlx::query(
+                    r#"
+                    UPDATE email_suppressions
+                    SET reason = $1, source = $2, detail = COALESCE($3, detail), bounce_count = $4,
+                        is_suppressed = $5, last_event_at = $6, updated_at = $6
+                    WHERE id = $7
+                    "#,
+                )
+                .bind(&reason)
+                .bind(source)
+                .bind(&event.detail)
+                .bind(count)
+                .bind(suppressed)
+                .bind(now)
+                .bind(&e.id)
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO email_suppressions
+                        (id, tenant_id, email, reason, source, detail, bounce_count, is_suppressed,
+                         last_event_at, created_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, 1, $7, $8, $8, $8)
+                    "#,
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(tenant_id)
+                .bind(&event.email)
+                .bind(&reason)
+                .bind(source)
+                .bind(&event.detail)
+                .bind(suppressed)
+                .bind(now)
+                .execute(&self.pool)
+                .await?;
+            }
+        }
+        if suppressed {
+            info!(
+                "Suppressed {} for tenant {:?} ({}, via {})",
+                event.email, tenant_id, reason, source
+            );
+        }
+
+        self.find(tenant_id, &event.email)
+            .await?
+            .ok_or_else(|| AppError::Internal("Suppression was not saved".to_string()))
+    }
+
+    /// Forget soft bounces for an address that just accepted mail.
+    pub async fn clear_soft_bounces(&self, tenant_id: Option<&str>, email: &str) {
+        let Some(email) = normalize_email(email) else {
+            return;
+        };
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new(
+            "DELETE FROM email_suppressions WHERE reason = 'soft_bounce' AND is_suppressed = ",
+        );
+        qb.push_bind(false);
+        qb.push(" AND");
+        push_scope(&mut qb, tenant_id);
+        qb.push(" AND email = ");
+        qb.push_bind(email);
+        if let Err(e) = qb.build().execute(&self.pool).await {
+            warn!("Failed to clear soft bounces: {}", e);
+        }
+    }
+
+    pub async fn list(
+        &self,
+        tenant_id: Option<&str>,
+        search: Option<&str>,
+        reason: Option<&str>,
+        page: u32,
+        per_page: u32,
+    ) -> AppResult<PaginatedResponse<EmailSuppression>> {
+        let reason = reason
+            .map(str::trim)
+            .filter(|r| !r.is_empty() && *r != "all");
+        if let Some(r) = reason {
+            if !REASONS.contains(&r) {
+                return Err(AppError::Validation(format!("Unknown reason '{}'", r)));
+            }
+        }
+        let search = search
+            .map(|s| s.trim().to_lowercase())
+            .filter(|s| !s.is_empty());
+        let page = page.max(1);
+        let per_page = per_page.clamp(1, MAX_PER_PAGE);
+
+        let mut count_qb: QueryBuilder<Db> =
+            QueryBuilder::new("SELECT COUNT(*) FROM email_suppressions WHERE");
+        push_list_filters(&mut count_qb, tenant_id, reason, search.as_deref());
+        let total: i64 = count_qb.build_query_scalar().fetch_one(&self.pool).await?;
+
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new("SELECT * FROM email_suppressions WHERE");
+        push_list_filters(&mut qb, tenant_id, reason, search.as_deref());
+        qb.push(" ORDER BY last_event_at DESC LIMIT ");
+        qb.push_bind(per_page as i64);
+        qb.push(" OFFSET ");
+        qb.push_bind(((page - 1) * per_page) as i64);
+        let data = qb
+            .build_query_as::<EmailSuppression>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(PaginatedResponse {
+            data,
+            total,
+            page,
+            per_page,
+        })
+    }
+
+    /// Suppress an address by hand, e.g. on a customer's request.
+    pub async fn add(
+        &self,
+        tenant_id: Option<&str>,
+        req: AddEmailSuppressionRequest,
+    ) -> AppResult<EmailSuppression> {
+        let event = MailEvent::new(&req.email, BounceKind::Manual, req.detail.as_deref())
+            .ok_or_else(|| AppError::Validation("Invalid email address".to_string()))?;
+        self.record(tenant_id, &event, "manual").await
+    }
+
+    /// Lift a suppression so the address receives mail again.
+    pub async fn remove(&self, tenant_id: Option<&str>, id: &str) -> AppResult<()> {
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new("DELETE FROM email_suppressions WHERE");
+        push_scope(&mut qb, tenant_id);
+        qb.push(" AND id = ");
+        qb.push_bind(id.to_string());
+        if qb.build().execute(&self.pool).await?.rows_affected() == 0 {
+            return Err(AppError::NotFound("Suppression not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Apply a provider webhook call. `token` picks the tenant (or the global
+    /// list) through its `email_events_webhook_token` setting.
+    pub async fn handle_webhook(
+        &self,
+        provider: &str,
+        token: &str,
+        body: &[u8],
+    ) -> AppResult<EmailEventsResult> {
+        if token.trim().is_empty() {
+            return Err(AppError::Forbidden("Invalid webhook token".to_string()));
+        }
+        let scope: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT tenant_id FROM settings WHERE key = 'email_events_webhook_token' AND value = $1 LIMIT 1",
+        )
+        .bind(token.trim())
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(tenant_id) = scope else {
+            return Err(AppError::Forbidden("Invalid webhook token".to_string()));
+        };
+
+        let payload: Value = serde_json::from_slice(body)
+            .map_err(|e| AppError::Validation(format!("Invalid payload: {}", e)))?;
+
+        let events = match provider {
+            "ses" => {
+                if payload["Type"].as_str() == Some("SubscriptionConfirmation") {
+                    self.confirm_sns_subscription(&payload).await;
+                    return Ok(EmailEventsResult::default());
+                }
+                parse_ses(&payload)
+            }
+            "sendgrid" => parse_sendgrid(&payload),
+            "mailgun" => {
+                let key = self
+                    .settings_service
+                    .get_value_fallback(tenant_id.as_deref(), "email_mailgun_webhook_secret")
+                    .await?
+                    .filter(|k| !k.trim().is_empty());
+                if let Some(key) = key {
+                    if !mailgun_signature_ok(key.trim(), &payload) {
+                        return Err(AppError::Forbidden("Invalid Mailgun signature".to_string()));
+                    }
+                }
+                parse_mailgun(&payload)
+            }
+            _ => {
+                return Err(AppError::Validation(format!(
+                    "Unknown email provider '{}'",
+                    provider
+                )))
+            }
+        };
+
+        let mut result = EmailEventsResult {
+            received: events.len(),
+            ..Default::default()
+        };
+        for event in &events {
+            if self
+                .record(tenant_id.as_deref(), event, provider)
+                .await?
+                .is_suppressed
+            {
+                result.suppressed += 1;
+            } else {
+                result.ignored += 1;
+            }
+        }
+        Ok(result)
+    }
+
+    /// SNS only delivers after the subscription URL has been visited once.
+    async fn confirm_sns_subscription(&self, payload: &Value) {
+        let Some(url) = payload["SubscribeURL"].as_str() else {
+            return;
+        };
+        let trusted = reqwest::Url::parse(url).ok().is_some_and(|u| {
+            u.scheme() == "https"
+                && u.host_str()
+                    .is_some_and(|h| h.starts_with("sns.") && h.ends_with(".amazonaws.com"))
+        });
+        if !trusted {
+            warn!(
+                "Ignored SNS subscription confirmation for untrusted URL {}",
+                url
+            );
+            return;
+        }
+        match reqwest::get(url).await {
+            Ok(resp) if resp.status().is_success() => info!("Confirmed SNS subscription"),
+            Ok(resp) => warn!("SNS subscription confirmation failed: {}", resp.status()),
+            Err(e) => warn!("SNS subscription confirmation failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn classifies_smtp_rejections() {
+        let hard = "SMTP sending failed: permanent error (550): 5.1.1 <x@example.com>: Recipient address rejected: User unknown";
+        assert_eq!(classify_smtp_error(hard), Some(BounceKind::Hard));
+        let full = "SMTP sending failed: transient error (452): 4.2.2 Mailbox full";
+        assert_eq!(classify_smtp_error(full), Some(BounceKind::Soft));
+        let no_enhanced = "permanent error (550): No such user here";
+        assert_eq!(classify_smtp_error(no_enhanced), Some(BounceKind::Hard));
+        // Our own problems say nothing about the recipient.
+        let auth = "permanent error (535): 5.7.8 Authentication credentials invalid";
+        assert_eq!(classify_smtp_error(auth), None);
+        assert_eq!(
+            classify_smtp_error("Connection refused (os error 111)"),
+            None
+        );
+        let policy = "permanent error (550): 5.7.1 Relaying denied";
+        assert_eq!(classify_smtp_error(policy), None);
+    }
+
+    #[test]
+    fn parses_ses_bounce_inside_sns_envelope() {
+        let message = json!({
+            "notificationType": "Bounce",
+            "bounce": {
+                "bounceType": "Permanent",
+                "bounceSubType": "General",
+                "bouncedRecipients": [
+                    { "emailAddress": "Gone@Example.com", "diagnosticCode": "smtp; 550 5.1.1 user unknown" }
+                ]
+            }
+        });
+        let payload = json!({ "Type": "Notification", "Message": message.to_string() });
+        let events = parse_ses(&payload);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].email, "gone@example.com");
+        assert_eq!(events[0].kind, BounceKind::Hard);
+        assert_eq!(
+            events[0].detail.as_deref(),
+            Some("smtp; 550 5.1.1 user unknown")
+        );
+
+        let complaint = json!({
+            "notificationType": "Complaint",
+            "complaint": {
+                "complaintFeedbackType": "abuse",
+                "complainedRecipients": [{ "emailAddress": "a@example.com" }]
+            }
+        });
+        assert_eq!(parse_ses(&complaint)[0].kind, BounceKind::Complaint);
+    }
+
+    #[test]
+    fn parses_sendgrid_events() {
+        let payload = json!([
+            { "email": "a@example.com", "event": "bounce", "type": "bounce", "reason": "550 unknown" },
+            { "email": "b@example.com", "event": "bounce", "type": "blocked" },
+            { "email": "c@example.com", "event": "spamreport" },
+            { "email": "d@example.com", "event": "delivered" },
+            { "email": "e@example.com", "event": "dropped", "reason": "Bounced Address" }
+        ]);
+        let kinds: Vec<(String, BounceKind)> = parse_sendgrid(&payload)
+            .into_iter()
+            .map(|e| (e.email, e.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("a@example.com".to_string(), BounceKind::Hard),
+                ("b@example.com".to_string(), BounceKind::Soft),
+                ("c@example.com".to_string(), BounceKind::Complaint),
+                ("e@example.com".to_string(), BounceKind::Hard),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_and_verifies_mailgun() {
+        let signature = hmac_sha256_hex(b"key", b"1700000000abc");
+        let payload = json!({
+            "signature": { "timestamp": "1700000000", "token": "abc", "signature": signature },
+            "event-data": {
+                "event": "failed",
+                "severity": "temporary",
+                "recipient": "x@example.com",
+                "delivery-status": { "message": "Mailbox full", "description": "" }
+            }
+        });
+        assert!(mailgun_signature_ok("key", &payload));
+        assert!(!mailgun_signature_ok("other", &payload));
+        let events = parse_mailgun(&payload);
+        assert_eq!(events[0].kind, BounceKind::Soft);
+        assert_eq!(events[0].detail.as_deref(), Some("Mailbox full"));
+    }
+
+    #[test]
+    fn rejects_malformed_addresses() {
+        assert!(MailEvent::new("not-an-address", BounceKind::Hard, None).is_none());
+        assert_eq!(normalize_email(" <A@B.co> ").as_deref(), Some("a@b.co"));
+    }
+}
@@ -0,0 +1,1125 @@
+//! HTML email templates.
+//!
+//! Each email type has a built-in subject, HTML body and plain-text body
+//! (`DEFAULT_EMAILS`); tenants can override them from settings. Templates use a
+//! small Handlebars subset:
+//!
+//! - `{{name}}` inserts a variable, HTML-escaped in the HTML body;
+//! - `{{{name}}}` inserts it as is;
+//! - `{{#if name}}…{{else}}…{{/if}}` keeps a section when the variable is set;
+//! - `{{! comment }}` is dropped.
+//!
+//! Overrides are checked when saved. Sending never fails on a template: a
+//! missing or unreadable override falls back to the default, written in the
+//! tenant's `default_locale` when `TRANSLATED_EMAILS` has it.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    EmailTemplate, EmailTemplateOverride, EmailTemplateVariable, Notification,
+    PreviewEmailTemplateRequest, RenderedEmail, TestSendEmailTemplateRequest,
+    UpdateEmailTemplateRequest,
+};
+use crate::services::i18n;
+use crate::services::locale::tenant_locale;
+use crate::services::{EmailService, SettingsService};
+use chrono::Utc;
+use std::collections::HashMap;
+use tracing::warn;
+use uuid::Uuid;
+
+const MAX_SUBJECT_LEN: usize = 200;
+const MAX_HTML_LEN: usize = 100_000;
+const MAX_TEXT_LEN: usize = 20_000;
+
+/// A built-in email.
+pub struct DefaultEmail {
+    pub code: &'static str,
+    pub description: &'static str,
+    pub subject: &'static str,
+    pub body_html: &'static str,
+    pub body_text: &'static str,
+    pub variables: &'static [&'static str],
+}
+
+/// Wraps `$content` in the card layout shared by the default emails.
+macro_rules! email_layout {
+    ($label:literal, $content:literal) => {
+        concat!(
+            "<!doctype html>\n<html>\n",
+            "<body style=\"font-family:ui-sans-serif,system-ui,-apple-system,Segoe UI,Roboto,Arial;line-height:1.5;color:#111827\">\n",
+            "  <div style=\"max-width:640px;margin:0 auto;padding:20px\">\n",
+            "    <div style=\"border:1px solid #e5e7eb;border-radius:14px;padding:18px\">\n",
+            "      <div style=\"font-size:12px;letter-spacing:.12em;text-transform:uppercase;color:#6b7280\">",
+            $label,
+            "</div>\n",
+            $content,
+            "    </div>\n",
+            "    <p style=\"color:#6b7280;font-size:12px\">{{app_name}}</p>\n",
+            "  </div>\n",
+            "</body>\n</html>\n"
+        )
+    };
+}
+
+const NOTIFICATION_VARIABLES: &[&str] = &[
+    "app_name",
+    "app_url",
+    "user_name",
+    "title",
+    "message",
+    "action_url",
+    "severity",
+];
+
+pub const DEFAULT_EMAILS: &[DefaultEmail] = &[
+    DefaultEmail {
+        code: "verification",
+        description: "Sent after sign-up to confirm the email address",
+        subject: "Verify your email",
+        body_html: email_layout!(
+            "Verify your email",
+            r#"      <h1 style="margin:10px 0 0;font-size:20px">Welcome, {{user_name}}!</h1>
+      <p>Please verify your email address to finish setting up your account.</p>
+      <p><a href="{{verify_url}}" style="display:inline-block;background:#111827;color:#ffffff;padding:10px 16px;border-radius:8px;text-decoration:none">Verify email</a></p>
+      <p style="color:#6b7280;font-size:13px">If you cannot click the button, use this code: {{code}}</p>
+"#
+        ),
+        body_text: "Welcome, {{user_name}}!\n\nPlease verify your email by clicking the link below:\n{{verify_url}}\n\nIf you cannot click the link, use this code: {{code}}",
+        variables: &["app_name", "app_url", "user_name", "verify_url", "code"],
+    },
+    DefaultEmail {
+        code: "password_reset",
+        description: "Sent when a user asks to reset their password",
+        subject: "Reset your password",
+        body_html: email_layout!(
+            "Password reset",
+            r#"      <h1 style="margin:10px 0 0;font-size:20px">Hello {{user_name}},</h1>
+      <p>You requested a password reset. The link below expires in 1 hour.</p>
+      <p><a href="{{reset_url}}" style="display:inline-block;background:#111827;color:#ffffff;padding:10px 16px;border-radius:8px;text-decoration:none">Reset password</a></p>
+      <p style="color:#6b7280;font-size:13px">If you did not request this, please ignore this email.</p>
+"#
+        ),
+        body_text: "Hello {{user_name}},\n\nYou requested a password reset. Click the link below to reset your password:\n{{reset_url}}\n\nThis link expires in 1 hour.\n\nIf you did not request this, please ignore this email.",
+        variables: &["app_name", "app_url", "user_name", "reset_url"],
+    },
+    DefaultEmail {
+        code: "invoice",
+        description: "Email copy of billing notifications (new invoice, payment received)",
+        subject: "{{title}}",
+        body_html: email_layout!(
+            "Billing",
+            r#"      <h1 style="margin:10px 0 0;font-size:20px">{{title}}</h1>
+      <p style="white-space:pre-wrap">{{message}}</p>
+      {{#if action_url}}<p><a href="{{action_url}}">View invoice</a></p>{{/if}}
+"#
+        ),
+        body_text: "{{message}}{{#if action_url}}\n\nOpen: {{action_url}}{{/if}}",
+        variables: NOTIFICATION_VARIABLES,
+    },
+    DefaultEmail {
+        code: "reminder",
+        description: "Email copy of billing reminders (due soon, due today, overdue)",
+        subject: "[Reminder] {{title}}",
+        body_html: email_layout!(
+            "Payment reminder",
+            r#"      <h1 style="margin:10px 0 0;font-size:20px">{{title}}</h1>
+      <p style="white-space:pre-wrap">{{message}}</p>
+      {{#if action_url}}<p><a href="{{action_url}}" style="display:inline-block;background:#111827;color:#ffffff;padding:10px 16px;border-radius:8px;text-decoration:none">Pay now</a></p>{{/if}}
+"#
+        ),
+        body_text: "{{message}}{{#if action_url}}\n\nPay: {{action_url}}{{/if}}",
+        variables: NOTIFICATION_VARIABLES,
+    },
+    DefaultEmail {
+        code: "alert",
+        description: "Email copy of warning and error notifications outside billing",
+        subject: "[Alert] {{title}}",
+        body_html: email_layout!(
+            "Alert",
+            r#"      <h1 style="margin:10px 0 0;font-size:20px">{{title}}</h1>
+      <p style="white-space:pre-wrap">{{message}}</p>
+      {{#if action_url}}<p><a href="{{action_url}}">Open in app</a></p>{{/if}}
+"#
+        ),
+        body_text: "{{message}}{{#if action_url}}\n\nOpen: {{action_url}}{{/if}}",
+        variables: NOTIFICATION_VARIABLES,
+    },
+];
+
+/// A built-in email in another language. Variables and description are the
+/// English default's.
+pub struct TranslatedEmail {
+    pub language: &'static str,
+    pub code: &'static str,
+    pub subject: &'static str,
+    pub body_html: &'static str,
+    pub body_text: &'static str,
+}
+
+pub const TRANSLATED_EMAILS: &[TranslatedEmail] = &[
+    TranslatedEmail {
+        language: "id",
+        code: "verification",
+        subject: "Verifikasi email Anda",
+        body_html: email_layout!(
+            "Verifikasi email",
+            r#"      <h1 style="margin:10px 0 0;font-size:20px">Selamat datang, {{user_name}}!</h1>
+      <p>Silakan verifikasi alamat email Anda untuk menyelesaikan pembuatan akun.</p>
+      <p><a href="{{verify_url}}" style="display:inline-block;background:#111827;color:#ffffff;padding:10px 16px;border-radius:8px;text-decoration:none">Verifikasi email</a></p>
+      <p style="color:#6b7280;font-size:13px">Jika tombol tidak bisa diklik, gunakan kode ini: {{code}}</p>
+"#
+        ),
+        body_text: "Selamat datang, {{user_name}}!\n\nSilakan verifikasi email Anda melalui tautan berikut:\n{{verify_url}}\n\nJika tautan tidak bisa dibuka, gunakan kode ini: {{code}}",
+    },
+    TranslatedEmail {
+        language: "id",
+        code: "password_reset",
+        subject: "Atur ulang kata sandi Anda",
+        body_html: email_layout!(
+            "Atur ulang kata sandi",
+            r#"      <h1 style="margin:10px 0 0;font-size:20px">Halo {{user_name}},</h1>
+      <p>Anda meminta pengaturan ulang kata sandi. Tautan di bawah berlaku selama 1 jam.</p>
+      <p><a href="{{reset_url}}" style="display:inline-block;background:#111827;color:#ffffff;padding:10px 16px;border-radius:8px;text-decoration:none">Atur ulang kata sandi</a></p>
+      <p style="color:#6b7280;font-size:13px">Jika Anda tidak memintanya, abaikan email ini.</p>
+"#
+        ),
+        body_text: "Halo {{user_name}},\n\nAnda meminta pengaturan ulang kata sandi. Klik tautan berikut untuk mengatur ulang kata sandi Anda:\n{{reset_url}}\n\nTautan ini berlaku selama 1 jam.\n\nJika Anda tidak memintanya, abaikan email ini.",
+    },
+    TranslatedEmail {
+        language: "id",
+        code: "invoice",
+        subject: "{{title}}",
+        body_html: email_layout!(
+            "Tagihan",
+            r#"      <h1 style="margin:10px 0 0;font-size:20px">{{title}}</h1>
+      <p style="white-space:pre-wrap">{{message}}</p>
+      {{#if action_url}}<p><a href="{{action_url}}">Lihat tagihan</a></p>{{/if}}
+"#
+        ),
+        body_text: "{{message}}{{#if action_url}}\n\nBuka: {{action_url}}{{/if}}",
+    },
+    TranslatedEmail {
+        language: "id",
+        code: "reminder",
+        subject: "[Pengingat] {{title}}",
+        body_html: email_layout!(
+            "Pengingat pembayaran",
+            r#"      <h1 style="margin:10px 0 0;font-size:20px">{{title}}</h1>
+      <p style="white-space:pre-wrap">{{message}}</p>
+      {{#if action_url}}<p><a href="{{action_url}}" style="display:inline-block;background:#111827;color:#ffffff;padding:10px 16px;border-radius:8px;text-decoration:none">Bayar sekarang</a></p>{{/if}}
+"#
+        ),
+        body_text: "{{message}}{{#if action_url}}\n\nBayar: {{action_url}}{{/if}}",
+    },
+    TranslatedEmail {
+        language: "id",
+        code: "alert",
+        subject: "[Peringatan] {{title}}",
+        body_html: email_layout!(
+            "Peringatan",
+            r#"      <h1 style="margin:10px 0 0;font-size:20px">{{title}}</h1>
+      <p style="white-space:pre-wrap">{{message}}</p>
+      {{#if action_url}}<p><a href="{{action_url}}">Buka di aplikasi</a></p>{{/if}}
+"#
+        ),
+        body_text: "{{message}}{{#if action_url}}\n\nBuka: {{action_url}}{{/if}}",
+    },
+];
+
+/// Subject, HTML and text of the built-in email in the language closest to
+/// `locale`, English when there is no translation.
+fn localized_default(
+    default: &'static DefaultEmail,
+    locale: Option<&str>,
+) -> (&'static str, &'static str, &'static str) {
+    let language = i18n::language_for(locale);
+    TRANSLATED_EMAILS
+        .iter()
+        .find(|t| t.language == language && t.code == default.code)
+        .map_or(
+            (default.subject, default.body_html, default.body_text),
+            |t| (t.subject, t.body_html, t.body_text),
+        )
+}
+
+/// `(name, description, sample)` for every variable a default declares.
+const VARIABLE_DOCS: &[(&str, &str, &str)] = &[
+    ("app_name", "Application name from settings", "ISP Management"),
+    (
+        "app_url",
+        "Public URL of the application",
+        "https://app.example.com",
+    ),
+    ("user_name", "Name of the recipient", "Budi Santoso"),
+    (
+        "verify_url",
+        "Link that confirms the email address",
+        "https://app.example.com/auth/verify-email?token=sample",
+    ),
+    (
+        "code",
+        "Verification code for when the link can't be opened",
+        "sample-token",
+    ),
+    (
+        "reset_url",
+        "Link to choose a new password",
+        "https://app.example.com/forgot-password/reset?token=sample",
+    ),
+    ("title", "Notification title", "Invoice due in 3 day(s)"),
+    (
+        "message",
+        "Notification text",
+        "Invoice INV-20260301-0001 is due on 2026-03-10 00:00 UTC. Please complete payment to keep service active.",
+    ),
+    (
+        "action_url",
+        "Link to the related page; may be empty",
+        "https://app.example.com/dashboard/invoices",
+    ),
+    (
+        "severity",
+        "Notification type: info, success, warning or error",
+        "warning",
+    ),
+];
+
+pub fn default_email(code: &str) -> Option<&'static DefaultEmail> {
+    DEFAULT_EMAILS.iter().find(|t| t.code == code)
+}
+
+/// The email type a notification's email copy uses, if any. Other
+/// notifications keep the plain-text email.
+pub fn email_code_for(notif: &Notification) -> Option<&'static str> {
+    let urgent = matches!(notif.notification_type.as_str(), "warning" | "error");
+    match notif.category.as_str() {
+        "billing" if urgent => Some("reminder"),
+        "billing" => Some("invoice"),
+        _ if urgent => Some("alert"),
+        _ => None,
+    }
+}
+
+fn variable_docs(default: &DefaultEmail) -> Vec<EmailTemplateVariable> {
+    default
+        .variables
+        .iter()
+        .map(|name| {
+            let (description, sample) = VARIABLE_DOCS
+                .iter()
+                .find(|(n, _, _)| n == name)
+                .map_or(("", ""), |(_, d, s)| (*d, *s));
+            EmailTemplateVariable {
+                name: name.to_string(),
+                description: description.to_string(),
+                sample: sample.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn sample_vars() -> HashMap<&'static str, String> {
+    VARIABLE_DOCS
+        .iter()
+        .map(|(name, _, sample)| (*name, sample.to_string()))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Text(String),
+    Var {
+        name: String,
+        raw: bool,
+    },
+    If {
+        name: String,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+struct OpenIf {
+    name: String,
+    then: Vec<Node>,
+    otherwise: Vec<Node>,
+    in_else: bool,
+}
+
+fn is_name(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn current<'a>(root: &'a mut Vec<Node>, open: &'a mut [OpenIf]) -> &'a mut Vec<Node> {
+    match open.last_mut() {
+        Some(block) if block.in_else => &mut block.otherwise,
+        Some(block) => &mut block.then,
+        None => root,
+    }
+}
+
+fn parse(src: &str) -> Result<Vec<Node>, String> {
+    let mut root = Vec::new();
+    let mut open: Vec<OpenIf> = Vec::new();
+    let mut rest = src;
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            current(&mut root, &mut open).push(Node::Text(rest[..start].to_string()));
+        }
+        let after = &rest[start..];
+        let (tag, len, raw) = if let Some(inner) = after.strip_prefix("{{{") {
+            let end = inner
+                .find("}}}")
+                .ok_or_else(|| "Unclosed {{{ placeholder".to_string())?;
+            (inner[..end].trim(), end + 6, true)
+        } else {
+            let inner = &after[2..];
+            let end = inner
+                .find("}}")
+                .ok_or_else(|| "Unclosed {{ placeholder".to_string())?;
+            (inner[..end].trim(), end + 4, false)
+        };
+        rest = &after[len..];
+
+        if tag.starts_with('!') {
+            continue;
+        }
+        if raw {
+            if !is_name(tag) {
+                return Err(format!("Invalid placeholder '{}'", tag));
+            }
+            current(&mut root, &mut open).push(Node::Var {
+                name: tag.to_string(),
+                raw: true,
+            });
+        } else if let Some(name) = tag.strip_prefix("#if ") {
+            let name = name.trim();
+            if !is_name(name) {
+                return Err(format!("Invalid condition '#if {}'", name));
+            }
+            open.push(OpenIf {
+                name: name.to_string(),
+                then: Vec::new(),
+                otherwise: Vec::new(),
+                in_else: false,
+            });
+        } else if tag == "else" {
+            match open.last_mut() {
+                Some(block) if !block.in_else => block.in_else = true,
+                _ => return Err("{{else}} outside an {{#if}} block".to_string()),
+            }
+        } else if tag == "/if" {
+            let block = open
+                .pop()
+                .ok_or_else(|| "{{/if}} without a matching {{#if}}".to_string())?;
+            current(&mut root, &mut open).push(Node::If {
+                name: block.name,
+                then: block.then,
+                otherwise: block.otherwise,
+            });
+        } else if is_name(tag) {
+            current(&mut root, &mut open).push(Node::Var {
+                name: tag.to_string(),
+                raw: false,
+            });
+        } else {
+            return Err(format!("Unsupported placeholder '{}'", tag));
+        }
+    }
+    if !rest.is_empty() {
+        current(&mut root, &mut open).push(Node::Text(rest.to_string()));
+    }
+    if let Some(block) = open.last() {
+        return Err(format!("Missing {{{{/if}}}} for '#if {}'", block.name));
+    }
+    Ok(root)
+}
+
+fn collect_names(nodes: &[Node], out: &mut Vec<String>) {
+    for node in nodes {
+        let (name, children) = match node {
+            Node::Text(_) => continue,
+            Node::Var { name, .. } => (name, None),
+            Node::If {
+                name,
+                then,
+                otherwise,
+            } => (name, Some((then, otherwise))),
+        };
+        if !out.contains(name) {
+            out.push(name.clone());
+        }
+        if let Some((then, otherwise)) = children {
+            collect_names(then, out);
+            collect_names(otherwise, out);
+        }
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn render_nodes(nodes: &[Node], vars: &HashMap<&str, String>, escape: bool, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { name, raw } => {
+                if let Some(value) = vars.get(name.as_str()) {
+                    if escape && !raw {
+                        out.push_str(&escape_html(value));
+                    } else {
+                        out.push_str(value);
+                    }
+                }
+            }
+            Node::If {
+                name,
+                then,
+                otherwise,
+            } => {
+                let set = vars
+                    .get(name.as_str())
+                    .is_some_and(|v| !v.trim().is_empty());
+                render_nodes(if set { then } else { otherwise }, vars, escape, out);
+            }
+        }
+    }
+}
+
+pub(crate) fn render_str(
+    src: &str,
+    vars: &HashMap<&str, String>,
+    escape: bool,
+) -> Result<String, String> {
+    let nodes = parse(src)?;
+    let mut out = String::with_capacity(src.len());
+    render_nodes(&nodes, vars, escape, &mut out);
+    Ok(out)
+}
+
+/// Plain-text version of an HTML body, used when a template has no text part.
+/// Block elements become line breaks and links keep their target.
+fn html_to_text(html: &str) -> String {
+    const BREAKS: &[&str] = &[
+        "br", "p", "div", "li", "tr", "table", "h1", "h2", "h3", "h4", "h5", "h6",
+    ];
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    let mut skipping: Option<String> = None;
+    let mut href: Option<String> = None;
+    while let Some(start) = rest.find('<') {
+        if skipping.is_none() {
+            out.push_str(&rest[..start]);
+        }
+        let Some(end) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        if let Some(skip) = &skipping {
+            if closing && &name == skip {
+                skipping = None;
+            }
+            continue;
+        }
+        match name.as_str() {
+            "style" | "script" | "head" | "title" if !closing => skipping = Some(name),
+            "a" if !closing => {
+                href = tag
+                    .split("href=\"")
+                    .nth(1)
+                    .and_then(|v| v.split('"').next())
+                    .map(str::to_string);
+            }
+            "a" => {
+                if let Some(url) = href.take().filter(|u| !u.is_empty()) {
+                    out.push_str(&format!(" ({})", url));
+                }
+            }
+            n if BREAKS.contains(&n) => out.push('\n'),
+            _ => {}
+        }
+    }
+    if skipping.is_none() {
+        out.push_str(rest);
+    }
+
+    let decoded = out
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    let mut text = String::with_capacity(decoded.len());
+    let mut blank = true;
+    for line in decoded.lines().map(str::trim) {
+        if line.is_empty() {
+            if !blank {
+                text.push('\n');
+            }
+            blank = true;
+        } else {
+            text.push_str(line);
+            text.push('\n');
+            blank = false;
+        }
+    }
+    text.trim().to_string()
+}
+
+fn render_parts(
+    subject: &str,
+    body_html: &str,
+    body_text: Option<&str>,
+    vars: &HashMap<&str, String>,
+) -> Result<RenderedEmail, String> {
+    // Collapse whitespace so a variable can't break the subject header.
+    let subject = render_str(subject, vars, false)?
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let html = render_str(body_html, vars, true)?;
+    let text = match body_text.filter(|t| !t.trim().is_empty()) {
+        Some(t) => render_str(t, vars, false)?,
+        None => html_to_text(&html),
+    };
+    Ok(RenderedEmail {
+        subject,
+        html,
+        text,
+    })
+}
+
+/// Checks that `src` parses and only uses the type's variables.
+fn validate_part(default: &DefaultEmail, label: &str, src: &str) -> AppResult<()> {
+    let nodes = parse(src).map_err(|e| AppError::Validation(format!("{}: {}", label, e)))?;
+    let mut names = Vec::new();
+    collect_names(&nodes, &mut names);
+    let unknown: Vec<String> = names
+        .into_iter()
+        .filter(|n| !default.variables.contains(&n.as_str()))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(AppError::Validation(format!(
+            "{}: unknown variable(s): {}. Available: {}",
+            label,
+            unknown.join(", "),
+            default.variables.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn to_view(default: &DefaultEmail, custom: Option<&EmailTemplateOverride>) -> EmailTemplate {
+    EmailTemplate {
+        code: default.code.to_string(),
+        description: default.description.to_string(),
+        subject: custom
+            .map_or(default.subject, |c| c.subject.as_str())
+            .to_string(),
+        body_html: custom
+            .map_or(default.body_html, |c| c.body_html.as_str())
+            .to_string(),
+        body_text: match custom {
+            Some(c) => c.body_text.clone(),
+            None => Some(default.body_text.to_string()),
+        },
+        default_subject: default.subject.to_string(),
+        default_body_html: default.body_html.to_string(),
+        default_body_text: default.body_text.to_string(),
+        variables: variable_docs(default),
+        is_customized: custom.is_some(),
+        updated_at: custom.map(|c| c.updated_at),
+    }
+}
+
+/// Turns a path such as `/dashboard/invoices` into a link usable from an email.
+fn absolute_url(app_url: &str, value: &str) -> String {
+    if value.starts_with('/') && !app_url.is_empty() {
+        format!("{}{}", app_url.trim_end_matches('/'), value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[derive(Clone)]
+pub struct EmailTemplateService {
+    pool: DbPool,
+    settings_service: SettingsService,
+    email_service: EmailService,
+}
+
+impl EmailTemplateService {
+    pub fn new(
+        pool: DbPool,
+        settings_service: SettingsService,
+        email_service: EmailService,
+    ) -> Self {
+        Self {
+            pool,
+            settings_service,
+            email_service,
+        }
+    }
+
+    fn known(code: &str) -> AppResult<&'static DefaultEmail> {
+        default_email(code)
+            .ok_or_else(|| AppError::NotFound(format!("Unknown email template: {}", code)))
+    }
+
+    async fn get_override(
+        &self,
+        tenant_id: &str,
+        code: &str,
+    ) -> AppResult<Option<EmailTemplateOverride>> {
+        let row = sqlx::query_as::<_, EmailTemplateOverride>(
+            "SELECT * FROM email_templates WHERE tenant_id = $1 AND code = $2",
+        )
+        .bind(tenant_id)
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// `app_name` and `app_url`, which every email type can use.
+    async fn base_vars(&self, tenant_id: Option<&str>) -> HashMap<&'static str, String> {
+        let app_name = self
+            .settings_service
+            .get_value_fallback(tenant_id, "app_name")
+            .await
+            .ok()
+            .flatten()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| "SaaS App".to_string());
+        let app_url = self
+            .settings_service
+            .get_value(None, "app_public_url")
+            .await
+            .ok()
+            .flatten()
+            .map(|v| v.trim().trim_end_matches('/').to_string())
+            .unwrap_or_default();
+        HashMap::from([("app_name", app_name), ("app_url", app_url)])
+    }
+
+    pub async fn list(&self, tenant_id: &str) -> AppResult<Vec<EmailTemplate>> {
+        let overrides = sqlx::query_as::<_, EmailTemplateOverride>(
+            "SELECT * FROM email_templates WHERE tenant_id = $1",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(DEFAULT_EMAILS
+            .iter()
+            .map(|d| to_view(d, overrides.iter().find(|o| o.code == d.code)))
+            .collect())
+    }
+
+    pub async fn get(&self, tenant_id: &str, code: &str) -> AppResult<EmailTemplate> {
+        let default = Self::known(code)?;
+        let custom = self.get_override(tenant_id, code).await?;
+        Ok(to_view(default, custom.as_ref()))
+    }
+
+    /// What the editor can insert into an email type.
+    pub fn variables(&self, code: &str) -> AppResult<Vec<EmailTemplateVariable>> {
+        Ok(variable_docs(Self::known(code)?))
+    }
+
+    pub async fn update(
+        &self,
+        tenant_id: &str,
+        code: &str,
+        req: UpdateEmailTemplateRequest,
+        actor_id: Option<&str>,
+    ) -> AppResult<EmailTemplate> {
+        let default = Self::known(code)?;
+        let subject = req.subject.trim();
+        let body_html = req.body_html.trim();
+        let body_text = req
+            .body_text
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty());
+        if subject.is_empty() || body_html.is_empty() {
+            return Err(AppError::Validation(
+                "Email subject and HTML body are required".to_string(),
+            ));
+        }
+        if subject.chars().count() > MAX_SUBJECT_LEN
+            || body_html.len() > MAX_HTML_LEN
+            || body_text.is_some_and(|t| t.len() > MAX_TEXT_LEN)
+        {
+            return Err(AppError::Validation(format!(
+                "Email subject is limited to {} characters, HTML body to {} bytes and text body to {} bytes",
+                MAX_SUBJECT_LEN, MAX_HTML_LEN, MAX_TEXT_LEN
+            )));
+        }
+        validate_part(default, "Subject", subject)?;
+        validate_part(default, "HTML body", body_html)?;
+        if let Some(text) = body_text {
+            validate_part(default, "Text body", text)?;
+        }
+
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO email_templates
+                (id, tenant_id, code, subject, body_html, body_text, updated_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)
+            ON CONFLICT (tenant_id, code) DO UPDATE SET
+                subject = EXCLUDED.subject,
+                body_html = EXCLUDED.body_html,
+                body_text = EXCLUDED.body_text,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(code)
+        .bind(subject)
+        .bind(body_html)
+        .bind(body_text)
+        .bind(actor_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        self.get(tenant_id, code).await
+    }
+
+    /// Drop the tenant's override so the default applies again.
+    pub async fn reset(&self, tenant_id: &str, code: &str) -> AppResult<EmailTemplate> {
+        let default = Self::known(code)?;
+        sqlx::query("DELETE FROM email_templates WHERE tenant_id = $1 AND code = $2")
+            .bind(tenant_id)
+            .bind(code)
+            .execute(&self.pool)
+            .await?;
+        Ok(to_view(default, None))
+    }
+
+    /// Render the current (or the given unsaved) email with sample values.
+    pub async fn preview(
+        &self,
+        tenant_id: &str,
+        code: &str,
+        req: PreviewEmailTemplateRequest,
+    ) -> AppResult<RenderedEmail> {
+        let mut current = self.get(tenant_id, code).await?;
+        if !current.is_customized {
+            let locale = tenant_locale(&self.pool, Some(tenant_id)).await;
+            let (subject, html, text) = localized_default(Self::known(code)?, locale.as_deref());
+            current.subject = subject.to_string();
+            current.body_html = html.to_string();
+            current.body_text = Some(text.to_string());
+        }
+        let subject = req.subject.unwrap_or(current.subject);
+        let body_html = req.body_html.unwrap_or(current.body_html);
+        let body_text = req.body_text.or(current.body_text);
+
+        let mut vars = sample_vars();
+        vars.extend(self.base_vars(Some(tenant_id)).await);
+        render_parts(&subject, &body_html, body_text.as_deref(), &vars)
+            .map_err(AppError::Validation)
+    }
+
+    /// Send a preview to `req.to`, or to the user's own address.
+    pub async fn test_send(
+        &self,
+        tenant_id: &str,
+        code: &str,
+        user_id: &str,
+        req: TestSendEmailTemplateRequest,
+    ) -> AppResult<RenderedEmail> {
+        let to = match req.to.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            Some(to) => to.to_string(),
+            None => sqlx::query_scalar::<_, String>("SELECT email FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound("User not found".to_string()))?,
+        };
+        if !to.contains('@') {
+            return Err(AppError::Validation(format!(
+                "Invalid recipient address: {}",
+                to
+            )));
+        }
+
+        let mut rendered = self
+            .preview(
+                tenant_id,
+                code,
+                PreviewEmailTemplateRequest {
+                    subject: req.subject,
+                    body_html: req.body_html,
+                    body_text: req.body_text,
+                },
+            )
+            .await?;
+        rendered.subject = format!("[Test] {}", rendered.subject);
+        self.email_service
+            .send_email_with_html_for_tenant(
+                Some(tenant_id),
+                &to,
+                &rendered.subject,
+                &rendered.text,
+                &rendered.html,
+            )
+            .await?;
+        Ok(rendered)
+    }
+
+    /// Email for `code` about to be sent. `app_name` and `app_url` are filled
+    /// in, and `*_url` values given as paths are made absolute. Never fails:
+    /// lookup or render errors fall back to the default.
+    pub async fn render(
+        &self,
+        tenant_id: Option<&str>,
+        code: &str,
+        vars: &HashMap<&str, String>,
+    ) -> RenderedEmail {
+        let base = self.base_vars(tenant_id).await;
+        let app_url = base.get("app_url").cloned().unwrap_or_default();
+        let mut all: HashMap<&str, String> = base.into_iter().collect();
+        for (name, value) in vars {
+            let value = if name.ends_with("_url") {
+                absolute_url(&app_url, value)
+            } else {
+                value.clone()
+            };
+            all.insert(*name, value);
+        }
+
+        let Some(default) = default_email(code) else {
+            warn!("Unknown email template code: {}", code);
+            return RenderedEmail {
+                subject: code.to_string(),
+                html: String::new(),
+                text: String::new(),
+            };
+        };
+
+        let custom = match tenant_id {
+            Some(tenant_id) => match self.get_override(tenant_id, code).await {
+                Ok(custom) => custom,
+                Err(e) => {
+                    warn!(
+                        "Failed to load email template {} for tenant {}: {}",
+                        code, tenant_id, e
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        if let Some(c) = custom {
+            match render_parts(&c.subject, &c.body_html, c.body_text.as_deref(), &all) {
+                Ok(rendered) => return rendered,
+                Err(e) => warn!(
+                    "Email template {} for tenant {} no longer renders: {}",
+                    code, c.tenant_id, e
+                ),
+            }
+        }
+        let locale = tenant_locale(&self.pool, tenant_id).await;
+        let (subject, html, text) = localized_default(default, locale.as_deref());
+        render_parts(subject, html, Some(text), &all).unwrap_or_else(|_| RenderedEmail {
+            subject: subject.to_string(),
+            html: html.to_string(),
+            text: text.to_string(),
+        })
+    }
+
+    /// HTML email for a notification's email copy, when its kind has one.
+    pub async fn render_notification(
+        &self,
+        notif: &Notification,
+        user_name: &str,
+    ) -> Option<RenderedEmail> {
+        let code = email_code_for(notif)?;
+        let vars = HashMap::from([
+            ("user_name", user_name.to_string()),
+            ("title", notif.title.clone()),
+            ("message", notif.message.clone()),
+            ("action_url", notif.action_url.clone().unwrap_or_default()),
+            ("severity", notif.notification_type.clone()),
+        ]);
+        Some(self.render(notif.tenant_id.as_deref(), code, &vars).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&'static str, &str)]) -> HashMap<&'static str, String> {
+        pairs.iter().map(|(k, v)| (*k, v.to_string())).collect()
+    }
+
+    #[test]
+    fn defaults_parse_and_only_use_documented_variables() {
+        for (i, d) in DEFAULT_EMAILS.iter().enumerate() {
+            assert!(
+                DEFAULT_EMAILS[i + 1..].iter().all(|o| o.code != d.code),
+                "duplicate email code {}",
+                d.code
+            );
+            for (label, src) in [
+                ("subject", d.subject),
+                ("html", d.body_html),
+                ("text", d.body_text),
+            ] {
+                assert!(
+                    validate_part(d, label, src).is_ok(),
+                    "{} {} is invalid",
+                    d.code,
+                    label
+                );
+            }
+            for doc in variable_docs(d) {
+                assert!(!doc.sample.is_empty(), "{} has no sample", doc.name);
+            }
+        }
+    }
+
+    #[test]
+    fn translations_cover_every_default_and_parse() {
+        for d in DEFAULT_EMAILS {
+            let (subject, html, text) = localized_default(d, Some("id-ID"));
+            assert_ne!(html, d.body_html, "{} has no Indonesian email", d.code);
+            for (label, src) in [("subject", subject), ("html", html), ("text", text)] {
+                assert!(
+                    validate_part(d, label, src).is_ok(),
+                    "Indonesian {} {} is invalid",
+                    d.code,
+                    label
+                );
+            }
+        }
+        for t in TRANSLATED_EMAILS {
+            assert!(default_email(t.code).is_some(), "unknown code {}", t.code);
+        }
+        let d = default_email("verification").unwrap();
+        assert_eq!(localized_default(d, Some("fr")).0, d.subject);
+    }
+
+    #[test]
+    fn escapes_html_but_not_triple_braces_or_text() {
+        let v = vars(&[("name", "<b>Tom & Jerry</b>")]);
+        assert_eq!(
+            render_str("Hi {{ name }} / {{{name}}}", &v, true).unwrap(),
+            "Hi &lt;b&gt;Tom &amp; Jerry&lt;/b&gt; / <b>Tom & Jerry</b>"
+        );
+        assert_eq!(
+            render_str("Hi {{name}}", &v, false).unwrap(),
+            "Hi <b>Tom & Jerry</b>"
+        );
+    }
+
+    #[test]
+    fn if_else_blocks_nest_and_treat_blank_as_unset() {
+        let src = "{{#if a}}A{{#if b}}B{{else}}-{{/if}}{{else}}none{{/if}}{{! note }}";
+        assert_eq!(
+            render_str(src, &vars(&[("a", "1"), ("b", "1")]), true).unwrap(),
+            "AB"
+        );
+        assert_eq!(
+            render_str(src, &vars(&[("a", "1"), ("b", " ")]), true).unwrap(),
+            "A-"
+        );
+        assert_eq!(render_str(src, &vars(&[]), true).unwrap(), "none");
+    }
+
+    #[test]
+    fn rejects_malformed_templates() {
+        for bad in [
+            "{{#if a}}open",
+            "{{/if}}",
+            "{{else}}",
+            "{{#if a}}{{else}}{{else}}{{/if}}",
+            "{{name",
+            "{{#each items}}{{/each}}",
+            "{{user name}}",
+        ] {
+            assert!(parse(bad).is_err(), "{} should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn flags_unknown_variables() {
+        let d = default_email("verification").unwrap();
+        assert!(validate_part(d, "HTML body", "{{user_name}} {{verify_url}}").is_ok());
+        let err = validate_part(d, "HTML body", "{{#if invoice}}{{user_name}}{{/if}}")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("invoice"));
+    }
+
+    #[test]
+    fn derives_text_from_html() {
+        let html = "<html><head><style>p{color:red}</style></head><body><h1>Hello &amp; welcome</h1>\n<p>Click <a href=\"https://x.test/v\">here</a>.</p><p>Bye</p></body></html>";
+        assert_eq!(
+            html_to_text(html),
+            "Hello & welcome\n\nClick here (https://x.test/v).\n\nBye"
+        );
+    }
+
+    #[test]
+    fn subject_is_kept_on_one_line() {
+        let r = render_parts(
+            "{{title}}",
+            "<p>x</p>",
+            None,
+            &vars(&[("title", "a\r\nBcc: x")]),
+        )
+        .unwrap();
+        assert_eq!(r.subject, "a Bcc: x");
+        assert_eq!(r.text, "x");
+    }
+
+    #[test]
+    fn picks_email_type_by_category_and_severity() {
+        let mut n = Notification::new(
+            "u".to_string(),
+            None,
+            "t".to_string(),
+            "m".to_string(),
+            "info".to_string(),
+            "billing".to_string(),
+            None,
+        );
+        assert_eq!(email_code_for(&n), Some("invoice"));
+        n.notification_type = "warning".to_string();
+        assert_eq!(email_code_for(&n), Some("reminder"));
+        n.category = "network".to_string();
+        assert_eq!(email_code_for(&n), Some("alert"));
+        n.notification_type = "info".to_string();
+        assert_eq!(email_code_for(&n), None);
+        assert_eq!(
+            absolute_url("https://app.test", "/dashboard"),
+            "https://app.test/dashboard"
+        );
+    }
+}
@@ -0,0 +1,891 @@
+//! Transactional outbox for real-time events
+//!
+//! Domain changes that clients must hear about (roles, team, maintenance mode,
+//! support replies) record a `WsEvent` in `event_outbox` inside the same
+//! transaction as the change itself, via [`EventOutboxService::stage`]. The
+//! dispatcher then broadcasts it on the `WsHub` and, when `event_webhook_url` is
+//! set, POSTs it to that URL signed with `event_webhook_secret`
+//! (`X-Event-Signature: sha256=<hex hmac of the body>`).
+//!
+//! Delivery is at-least-once: a crash between delivering and marking the row
+//! replays the event on restart, so consumers should treat events as idempotent
+//! refresh signals (they already are). The WebSocket leg is marked separately, so
+//! webhook retries never re-broadcast.
+//!
+//! Events that belong to a tenant are also queued for the tenant's own
+//! webhooks (`tenant_webhook_deliveries`). Each of those deliveries is retried
+//! on its own schedule and signed with that webhook's secret, so one slow or
+//! broken endpoint holds up nobody else.
+//!
+//! Business events (`invoice.paid`, `router.offline`, ...) are [`DomainEvent`]s
+//! staged the same way. They carry customer data, so they only go to webhooks
+//! and are never broadcast on the WebSocket.
+//!
+//! Per-user notification pushes stay direct; the notification row is the durable
+//! record there and clients reload it on reconnect.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::http::{WsEvent, WsHub};
+use crate::models::{Customer, SupportTicket};
+#[cfg(feature = "postgres")]
+use crate::security::secret::decrypt_secret_opt_for;
+#[cfg(feature = "postgres")]
+use crate::services::integration_service::PURPOSE_WEBHOOK;
+use crate::services::leader_lock::LeaderLock;
+use crate::services::SettingsService;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Dispatcher wake-up shared by everything that stages events. Staging happens
+/// inside a caller's transaction, so callers nudge the dispatcher only after
+/// committing; the poll interval covers anyone who forgets.
+static DISPATCH: Notify = Notify::const_new();
+
+const POLL_INTERVAL_SECS: u64 = 2;
+const BATCH_SIZE: i64 = 100;
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+const BASE_RETRY_DELAY_SECS: i64 = 5;
+const MAX_RETRY_DELAY_SECS: i64 = 3600;
+
+#[derive(Clone)]
+pub struct EventOutboxService {
+    pool: DbPool,
+    ws_hub: Arc<WsHub>,
+    settings_service: SettingsService,
+    http: reqwest::Client,
+}
+
+#[cfg(feature = "postgres")]
+#[derive(Debug, sqlx::FromRow)]
+struct TenantDeliveryRow {
+    id: String,
+    event_id: String,
+    event_type: String,
+    payload: String,
+    attempts: i32,
+    url: String,
+    secret_enc: String,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct EventOutboxRow {
+    id: String,
+    #[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+    tenant_id: Option<String>,
+    event_type: String,
+    payload: String,
+    attempts: i32,
+    ws_delivered_at: Option<DateTime<Utc>>,
+}
+
+/// Business events for webhooks, typed `<resource>.<what happened>`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum DomainEvent {
+    #[serde(rename = "customer.created")]
+    CustomerCreated {
+        customer_id: String,
+        name: String,
+        email: Option<String>,
+        created_at: DateTime<Utc>,
+    },
+    /// A customer's package invoice was paid.
+    #[serde(rename = "invoice.paid")]
+    InvoicePaid {
+        invoice_id: String,
+        invoice_number: String,
+        subscription_id: Option<String>,
+        amount: f64,
+        currency_code: String,
+        paid_at: DateTime<Utc>,
+    },
+    /// A router that answered the previous poll stopped answering.
+    #[serde(rename = "router.offline")]
+    RouterOffline {
+        router_id: String,
+        name: String,
+        host: String,
+        error: String,
+        at: DateTime<Utc>,
+    },
+    #[serde(rename = "ticket.created")]
+    TicketCreated {
+        ticket_id: String,
+        subject: String,
+        priority: String,
+        category: Option<String>,
+        created_by: Option<String>,
+        created_at: DateTime<Utc>,
+    },
+}
+
+impl DomainEvent {
+    pub fn customer_created(customer: &Customer) -> Self {
+        DomainEvent::CustomerCreated {
+            customer_id: customer.id.clone(),
+            name: customer.name.clone(),
+            email: customer.email.clone(),
+            created_at: customer.created_at,
+        }
+    }
+
+    pub fn ticket_created(ticket: &SupportTicket) -> Self {
+        DomainEvent::TicketCreated {
+            ticket_id: ticket.id.clone(),
+            subject: ticket.subject.clone(),
+            priority: ticket.priority.clone(),
+            category: ticket.category.clone(),
+            created_by: ticket.created_by.clone(),
+            created_at: ticket.created_at,
+        }
+    }
+}
+
+/// `WsEvent` types are snake_case; only domain events have a dot.
+fn is_domain_event(event_type: &str) -> bool {
+    event_type.contains('.')
+}
+
+fn event_type(event: &impl Serialize) -> String {
+    serde_json::to_value(event)
+        .ok()
+        .and_then(|v| v.get("type").and_then(|t| t.as_str()).map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Wake the dispatcher. Call after committing a transaction that staged events.
+pub fn wake() {
+    DISPATCH.notify_one();
+}
+
+impl EventOutboxService {
+    pub fn new(pool: DbPool, ws_hub: Arc<WsHub>, settings_service: SettingsService) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default();
+        Self {
+            pool,
+            ws_hub,
+            settings_service,
+            http,
+        }
+    }
+
+    /// Record `event` (a `WsEvent` or a [`DomainEvent`]) as part of `tx`. It
+    /// is delivered once `tx` commits.
+    #[cfg(feature = "postgres")]
+    pub async fn stage(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        tenant_id: Option<&str>,
+        event: &impl Serialize,
+    ) -> Result<(), sqlx::Error> {
+        let payload = serde_json::to_string(event)
+            .map_err(|e| sqlx::Error::Protocol(format!("Failed to encode event: {}", e)))?;
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO event_outbox
+              (id, tenant_id, event_type, payload, status, attempts, next_attempt_at, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, 'pending', 0, $5, $5, $5)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(event_type(event))
+        .bind(payload)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Record `event` (a `WsEvent` or a [`DomainEvent`]) as part of `tx`. It
+    /// is delivered once `tx` commits.
+    #[cfg(feature = "sqlite")]
+    pub async fn stage(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        tenant_id: Option<&str>,
+        event: &impl Serialize,
+    ) -> Result<(), sqlx::Error> {
+        let payload = serde_json::to_string(event)
+            .map_err(|e| sqlx::Error::Protocol(format!("Failed to encode event: {}", e)))?;
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO event_outbox
+              (id, tenant_id, event_type, payload, status, attempts, next_attempt_at, created_at, updated_at)
+            VALUES (?, ?, ?, ?, 'pending', 0, ?, ?, ?)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(event_type(event))
+        .bind(payload)
+        .bind(&now)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Record and dispatch events for a change that was already committed by a
+    /// service without a transaction of its own. If the outbox write fails the
+    /// events are broadcast directly so live clients still hear about them.
+    pub async fn publish(&self, tenant_id: Option<&str>, events: Vec<WsEvent>) {
+        let staged = async {
+            let mut tx = self.pool.begin().await?;
+            for event in &events {
+                Self::stage(&mut tx, tenant_id, event).await?;
+            }
+            tx.commit().await
+        }
+        .await;
+
+        match staged {
+            Ok(()) => wake(),
+            Err(e) => {
+                warn!("Event outbox write failed, broadcasting directly: {}", e);
+                for event in events {
+                    self.ws_hub.broadcast(event);
+                }
+            }
+        }
+    }
+
+    async fn webhook_url(&self) -> Option<String> {
+        self.settings_service
+            .get_value(None, "event_webhook_url")
+            .await
+            .ok()
+            .flatten()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    }
+
+    async fn webhook_secret(&self) -> Option<String> {
+        self.settings_service
+            .get_value(None, "event_webhook_secret")
+            .await
+            .ok()
+            .flatten()
+            .filter(|v| !v.is_empty())
+    }
+
+    async fn max_attempts(&self) -> i32 {
+        self.settings_service
+            .get_value(None, "event_webhook_max_attempts")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| v.trim().parse::<i32>().ok())
+            .unwrap_or(10)
+            .clamp(1, 50)
+    }
+
+    async fn retention_days(&self) -> i64 {
+        self.settings_service
+            .get_value(None, "event_outbox_retention_days")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .unwrap_or(7)
+            .clamp(1, 365)
+    }
+
+    pub async fn start_dispatcher(&self) {
+        let svc = self.clone();
+        let leader = LeaderLock::new(self.pool.clone(), "event_outbox_dispatcher");
+        tokio::spawn(async move {
+            info!("Event outbox dispatcher started.");
+            let mut warned_missing_schema = false;
+            let mut last_purge: Option<DateTime<Utc>> = None;
+
+            loop {
+                // Whichever comes first: a commit nudge or the poll interval.
+                let _ = tokio::time::timeout(
+                    tokio::time::Duration::from_secs(POLL_INTERVAL_SECS),
+                    DISPATCH.notified(),
+                )
+                .await;
+
+                if !leader.acquire().await {
+                    continue;
+                }
+
+                // Drain: keep going while full batches come back.
+                let res = loop {
+                    match svc.process_batch().await {
+                        Ok(n) if n as i64 >= BATCH_SIZE => continue,
+                        other => break other,
+                    }
+                };
+
+                #[cfg(feature = "postgres")]
+                if res.is_ok() {
+                    loop {
+                        match svc.deliver_tenant_webhooks().await {
+                            Ok(n) if n as i64 >= BATCH_SIZE => continue,
+                            Ok(_) => break,
+                            Err(e) => {
+                                warn!("Tenant webhook delivery failed: {}", e);
+                                break;
+                            }
+                        }
+                    }
+                }
+
+                let due_purge = last_purge
+                    .map(|t| Utc::now() - t > chrono::Duration::hours(1))
+                    .unwrap_or(true);
+                if due_purge {
+                    last_purge = Some(Utc::now());
+                    if let Err(e) = svc.purge_delivered().await {
+                        warn!("Event outbox purge failed: {}", e);
+                    }
+                }
+
+                if let Err(e) = res {
+                    let msg = e.to_string();
+                    if (msg.contains("event_outbox") && msg.contains("no such table"))
+                        || msg.contains("relation \"event_outbox\" does not exist")
+                    {
+                        if !warned_missing_schema {
+                            warned_missing_schema = true;
+                            warn!("Event outbox paused: database schema not migrated yet (missing event_outbox table).");
+                        }
+                    } else {
+                        error!("Event outbox dispatcher failed: {}", msg);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Deliver one batch of due events. Returns how many were picked up.
+    async fn process_batch(&self) -> AppResult<usize> {
+        let now = Utc::now();
+        let rows = self.claim_due(now).await?;
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        let webhook_url = self.webhook_url().await;
+        let webhook_secret = self.webhook_secret().await;
+        let max_attempts = self.max_attempts().await;
+
+        for row in &rows {
+            if row.ws_delivered_at.is_none() {
+                if !is_domain_event(&row.event_type) {
+                    match serde_json::from_str::<WsEvent>(&row.payload) {
+                        Ok(event) => self.ws_hub.broadcast(event),
+                        Err(e) => warn!(
+                            "Event outbox row {} ({}) has an unreadable payload: {}",
+                            row.id, row.event_type, e
+                        ),
+                    }
+                }
+                // Before marking, so a crash in between queues it again;
+                // queueing twice is harmless.
+                #[cfg(feature = "postgres")]
+                if let Some(tenant_id) = row.tenant_id.as_deref() {
+                    self.queue_tenant_webhooks(tenant_id, row, now).await?;
+                }
+                self.mark_ws_delivered(&row.id, now).await?;
+            }
+
+            let outcome = match webhook_url.as_deref() {
+                Some(url) => {
+                    let secret = webhook_secret.as_deref();
+                    self.post_webhook(url, secret, &row.id, &row.event_type, &row.payload)
+                        .await
+                        .1
+                }
+                None => Ok(()),
+            };
+
+            match outcome {
+                Ok(()) => self.mark_delivered(&row.id, now).await?,
+                Err(err) => {
+                    // attempts was already incremented by the claim.
+                    let attempts = row.attempts + 1;
+                    if attempts >= max_attempts {
+                        warn!(
+                            "Event {} ({}) webhook failed permanently: {}",
+                            row.id, row.event_type, err
+                        );
+                        self.mark_failed(&row.id, &err, None, now).await?;
+                    } else {
+                        let next_at = now + chrono::Duration::seconds(retry_delay_secs(attempts));
+                        self.mark_failed(&row.id, &err, Some(next_at), now).await?;
+                    }
+                }
+            }
+        }
+
+        Ok(rows.len())
+    }
+
+    /// POST one event. Returns the response status, if a response came
+    /// back, and whether the event counts as delivered.
+    async fn post_webhook(
+        &self,
+        url: &str,
+        secret: Option<&str>,
+        event_id: &str,
+        event_type: &str,
+        payload: &str,
+    ) -> (Option<u16>, Result<(), String>) {
+        let mut req = self
+            .http
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("X-Event-Id", event_id)
+            .header("X-Event-Type", event_type);
+        if let Some(secret) = secret {
+            let sig = hmac_sha256_hex(secret.as_bytes(), payload.as_bytes());
+            req = req.header("X-Event-Signature", format!("sha256={}", sig));
+        }
+
+        match req.body(payload.to_string()).send().await {
+            Ok(res) if res.status().is_success() => (Some(res.status().as_u16()), Ok(())),
+            Ok(res) => (
+                Some(res.status().as_u16()),
+                Err(format!("webhook returned HTTP {}", res.status())),
+            ),
+            Err(e) => (None, Err(e.to_string())),
+        }
+    }
+
+    /// Queue `row` for each active webhook of `tenant_id` that takes its
+    /// event type. A webhook gets each event once, however often this runs.
+    #[cfg(feature = "postgres")]
+    async fn queue_tenant_webhooks(
+        &self,
+        tenant_id: &str,
+        row: &EventOutboxRow,
+        now: DateTime<Utc>,
+    ) -> AppResult<()> {
+        let webhooks: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM tenant_webhooks
+            WHERE tenant_id = $1
+              AND is_active
+              AND (cardinality(event_types) = 0 OR $2 = ANY(event_types))
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&row.event_type)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for webhook_id in webhooks {
+            sqlx::query(
+                r#"
+                INSERT INTO tenant_webhook_deliveries
+                  (id, tenant_id, webhook_id, event_id, event_type, payload, status, attempts,
+                   next_attempt_at, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, 'pending', 0, $7, $7, $7)
+                ON CONFLICT (webhook_id, event_id) DO NOTHING
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(tenant_id)
+            .bind(&webhook_id)
+            .bind(&row.id)
+            .bind(&row.event_type)
+            .bind(&row.payload)
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Deliver one batch of due tenant webhook events, all at once so a slow
+    /// endpoint costs at most one request timeout. Returns how many were
+    /// picked up.
+    #[cfg(feature = "postgres")]
+    async fn deliver_tenant_webhooks(&self) -> AppResult<usize> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        let rows: Vec<TenantDeliveryRow> = sqlx::query_as(
+            r#"
+            SELECT d.id, d.event_id, d.event_type, d.payload, d.attempts, w.url, w.secret_enc
+            FROM tenant_webhook_deliveries d
+            JOIN tenant_webhooks w ON w.id = d.webhook_id
+            WHERE d.status = 'pending'
+              AND d.next_attempt_at <= $1
+              AND w.is_active
+            ORDER BY d.next_attempt_at ASC
+            LIMIT $2
+            FOR UPDATE OF d SKIP LOCKED
+            "#,
+        )
+        .bind(now)
+        .bind(BATCH_SIZE)
+        .fetch_all(&mut *tx)
+        .await?;
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        // Lease them like outbox rows: a crash mid-delivery retries later.
+        let ids: Vec<String> = rows.iter().map(|r| r.id.clone()).collect();
+        sqlx::query(
+            "UPDATE tenant_webhook_deliveries SET attempts = attempts + 1, next_attempt_at = $1, updated_at = $2 WHERE id = ANY($3)",
+        )
+        .bind(now + chrono::Duration::seconds(60))
+        .bind(now)
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let max_attempts = self.max_attempts().await;
+        let attempts = futures::future::join_all(rows.iter().map(|row| async move {
+            let started = std::time::Instant::now();
+            let (status, outcome) = match decrypt_secret_opt_for(PURPOSE_WEBHOOK, &row.secret_enc) {
+                Ok(Some(secret)) => {
+                    self.post_webhook(
+                        &row.url,
+                        Some(&secret),
+                        &row.event_id,
+                        &row.event_type,
+                        &row.payload,
+                    )
+                    .await
+                }
+                _ => (None, Err("Signing secret could not be read".to_string())),
+            };
+            (status, outcome, started.elapsed().as_millis() as i32)
+        }))
+        .await;
+
+        for (row, (status, outcome, duration_ms)) in rows.iter().zip(attempts) {
+            let done = Utc::now();
+            // attempts was already incremented by the claim.
+            let attempts = row.attempts + 1;
+            let (state, next_at, error) = match outcome {
+                Ok(()) => ("delivered", done, None),
+                Err(e) if attempts >= max_attempts => ("failed", done, Some(e)),
+                Err(e) => (
+                    "pending",
+                    done + chrono::Duration::seconds(retry_delay_secs(attempts)),
+                    Some(e),
+                ),
+            };
+            sqlx::query(
+                r#"
+                UPDATE tenant_webhook_deliveries
+                SET status = $1, next_attempt_at = $2, response_status = $3, duration_ms = $4,
+                    last_error = $5,
+                    delivered_at = CASE WHEN $1 = 'delivered' THEN $6 ELSE delivered_at END,
+                    updated_at = $6
+                WHERE id = $7
+                "#,
+            )
+            .bind(state)
+            .bind(next_at)
+            .bind(status.map(i32::from))
+            .bind(duration_ms)
+            .bind(error)
+            .bind(done)
+            .bind(&row.id)
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(rows.len())
+    }
+
+    /// Claim due rows by bumping their attempt counter and pushing
+    /// `next_attempt_at` out, so a crash mid-delivery retries them later.
+    #[cfg(feature = "postgres")]
+    async fn claim_due(&self, now: DateTime<Utc>) -> AppResult<Vec<EventOutboxRow>> {
+        let lease_until = now + chrono::Duration::seconds(60);
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        let rows: Vec<EventOutboxRow> = sqlx::query_as(
+            r#"
+            SELECT id, tenant_id, event_type, payload, attempts, ws_delivered_at
+            FROM event_outbox
+            WHERE status = 'pending'
+              AND next_attempt_at <= $1
+            ORDER BY created_at ASC
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(now)
+        .bind(BATCH_SIZE)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        if !rows.is_empty() {
+            let ids: Vec<String> = rows.iter().map(|r| r.id.clone()).collect();
+            sqlx::query(
+                "UPDATE event_outbox SET attempts = attempts + 1, next_attempt_at = $1, updated_at = $2 WHERE id = ANY($3)",
+            )
+            .bind(lease_until)
+            .bind(now)
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        }
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(rows)
+    }
+
+    /// Claim due rows. SQLite has a single writer, so no row locking is needed.
+    #[cfg(feature = "sqlite")]
+    async fn claim_due(&self, now: DateTime<Utc>) -> AppResult<Vec<EventOutboxRow>> {
+        let lease_until = (now + chrono::Duration::seconds(60)).to_rfc3339();
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        let rows: Vec<EventOutboxRow> = sqlx::query_as(
+            r#"
+            SELECT id, tenant_id, event_type, payload, attempts, ws_delivered_at
+            FROM event_outbox
+            WHERE status = 'pending'
+              AND next_attempt_at <= ?
+            ORDER BY created_at ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(now.to_rfc3339())
+        .bind(BATCH_SIZE)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        for r in &rows {
+            sqlx::query(
+                "UPDATE event_outbox SET attempts = attempts + 1, next_attempt_at = ?, updated_at = ? WHERE id = ?",
+            )
+            .bind(&lease_until)
+            .bind(now.to_rfc3339())
+            .bind(&r.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        }
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(rows)
+    }
+
+    async fn mark_ws_delivered(&self, id: &str, now: DateTime<Utc>) -> AppResult<()> {
+        #[cfg(feature = "postgres")]
+        sqlx::query("UPDATE event_outbox SET ws_delivered_at = $1, updated_at = $1 WHERE id = $2")
+            .bind(now)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query("UPDATE event_outbox SET ws_delivered_at = ?, updated_at = ? WHERE id = ?")
+            .bind(now.to_rfc3339())
+            .bind(now.to_rfc3339())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn mark_delivered(&self, id: &str, now: DateTime<Utc>) -> AppResult<()> {
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            "UPDATE event_outbox SET status = 'delivered', delivered_at = $1, last_error = NULL, updated_at = $1 WHERE id = $2",
+        )
+        .bind(now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query(
+            "UPDATE event_outbox SET status = 'delivered', delivered_at = ?, last_error = NULL, updated_at = ? WHERE id = ?",
+        )
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a webhook failure: reschedule at `retry_at`, or give up when `None`.
+    async fn mark_failed(
+        &self,
+        id: &str,
+        err: &str,
+        retry_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> AppResult<()> {
+        let status = if retry_at.is_some() {
+            "pending"
+        } else {
+            "failed"
+        };
+        let next_at = retry_at.unwrap_or(now);
+
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            "UPDATE event_outbox SET status = $1, next_attempt_at = $2, last_error = $3, updated_at = $4 WHERE id = $5",
+        )
+        .bind(status)
+        .bind(next_at)
+        .bind(err)
+        .bind(now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query(
+            "UPDATE event_outbox SET status = ?, next_attempt_at = ?, last_error = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(status)
+        .bind(next_at.to_rfc3339())
+        .bind(err)
+        .bind(now.to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Drop delivered events past the retention window. Failed rows are kept
+    /// for inspection, except tenant webhook deliveries: tenants see those in
+    /// their delivery log until the window passes.
+    async fn purge_delivered(&self) -> AppResult<u64> {
+        let cutoff = Utc::now() - chrono::Duration::days(self.retention_days().await);
+
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            "DELETE FROM tenant_webhook_deliveries WHERE status <> 'pending' AND updated_at < $1",
+        )
+        .bind(cutoff)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "postgres")]
+        let res = sqlx::query(
+            "DELETE FROM event_outbox WHERE status = 'delivered' AND delivered_at < $1",
+        )
+        .bind(cutoff)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let res =
+            sqlx::query("DELETE FROM event_outbox WHERE status = 'delivered' AND delivered_at < ?")
+                .bind(cutoff.to_rfc3339())
+                .execute(&self.pool)
+                .await?;
+
+        Ok(res.rows_affected())
+    }
+}
+
+/// Exponential backoff after the `attempts`-th failed delivery.
+fn retry_delay_secs(attempts: i32) -> i64 {
+    let exp = (attempts - 1).clamp(0, 20) as u32;
+    BASE_RETRY_DELAY_SECS
+        .saturating_mul(2_i64.saturating_pow(exp))
+        .min(MAX_RETRY_DELAY_SECS)
+}
+
+/// HMAC-SHA256 (RFC 2104) as lowercase hex.
+pub(crate) fn hmac_sha256_hex(key: &[u8], message: &[u8]) -> String {
+    const BLOCK: usize = 64;
+
+    let mut block_key = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block_key[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block_key.map(|b| b ^ 0x36));
+    inner.update(message);
+    let inner_hash = inner.finalize();
+
+    let mut outer = Sha256::new();
+    outer.update(block_key.map(|b| b ^ 0x5c));
+    outer.update(inner_hash);
+    format!("{:x}", outer.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        // Test case 2
+        assert_eq!(
+            hmac_sha256_hex(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Test case 6: key longer than the block size is hashed first
+        assert_eq!(
+            hmac_sha256_hex(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            ),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        assert_eq!(retry_delay_secs(1), 5);
+        assert_eq!(retry_delay_secs(2), 10);
+        assert_eq!(retry_delay_secs(4), 40);
+        assert_eq!(retry_delay_secs(30), MAX_RETRY_DELAY_SECS);
+    }
+
+    #[test]
+    fn test_event_type_matches_serde_tag() {
+        assert_eq!(
+            event_type(&WsEvent::RoleCreated {
+                role_id: "r1".to_string()
+            }),
+            "role_created"
+        );
+        assert_eq!(
+            event_type(&WsEvent::PermissionsChanged),
+            "permissions_changed"
+        );
+    }
+
+    #[test]
+    fn test_domain_events_are_dotted_and_not_broadcast() {
+        let event = DomainEvent::CustomerCreated {
+            customer_id: "c1".to_string(),
+            name: "Budi".to_string(),
+            email: None,
+            created_at: Utc::now(),
+        };
+        assert_eq!(event_type(&event), "customer.created");
+        assert!(is_domain_event(&event_type(&event)));
+        assert!(!is_domain_event(&event_type(&WsEvent::PermissionsChanged)));
+
+        let payload = serde_json::to_value(&event).unwrap();
+        assert_eq!(payload["type"], "customer.created");
+        assert_eq!(payload["customer_id"], "c1");
+    }
+}
@@ -0,0 +1,340 @@
+//! Runtime feature flags.
+//!
+//! Operational switches, separate from the plan features that billing sells.
+//! A flag is evaluated per tenant: `enabled = false` kills it everywhere;
+//! otherwise a tenant override wins, and without one the tenant is bucketed
+//! into the rollout percentage by a stable hash of flag key and tenant id.
+//! Flags and overrides are read as one snapshot cached in memory for a short
+//! TTL and dropped on every write, so guards on hot paths stay cheap.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    FeatureFlag, FeatureFlagOverride, FeatureFlagWithOverrides, UpsertFeatureFlagRequest,
+};
+use crate::services::cache::SingleValueCache;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Gates `checkout_plan_upgrade` (self-serve plan upgrades).
+pub const FLAG_PLAN_SELF_SERVE_UPGRADE: &str = "plan_self_serve_upgrade";
+
+const CACHE_TTL_SECS: u64 = 30;
+const MAX_KEY_LEN: usize = 64;
+
+#[derive(Default)]
+struct FlagSnapshot {
+    flags: HashMap<String, FeatureFlag>,
+    /// (flag_key, tenant_id) -> enabled
+    overrides: HashMap<(String, String), bool>,
+}
+
+/// Stable 0..100 bucket for a tenant on a flag (FNV-1a), so a tenant keeps
+/// its answer as the percentage grows and flags roll out independently.
+fn rollout_bucket(flag_key: &str, tenant_id: &str) -> u32 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in flag_key
+        .bytes()
+        .chain(std::iter::once(b':'))
+        .chain(tenant_id.bytes())
+    {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    (hash % 100) as u32
+}
+
+/// Outside a tenant (super admin, platform jobs) only a full rollout counts.
+fn evaluate(flag: &FeatureFlag, tenant_override: Option<bool>, tenant_id: Option<&str>) -> bool {
+    if !flag.enabled {
+        return false;
+    }
+    if let Some(enabled) = tenant_override {
+        return enabled;
+    }
+    let percentage = flag.rollout_percentage.clamp(0, 100) as u32;
+    match tenant_id {
+        _ if percentage >= 100 => true,
+        Some(tenant_id) => rollout_bucket(&flag.key, tenant_id) < percentage,
+        None => false,
+    }
+}
+
+fn validate_key(key: &str) -> AppResult<()> {
+    let valid = !key.is_empty()
+        && key.len() <= MAX_KEY_LEN
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.');
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!(
+            "Flag key must be 1-{} characters of a-z, 0-9, '_' or '.'",
+            MAX_KEY_LEN
+        )))
+    }
+}
+
+#[derive(Clone)]
+pub struct FeatureFlagService {
+    pool: DbPool,
+    cache: Arc<SingleValueCache<Arc<FlagSnapshot>>>,
+}
+
+impl FeatureFlagService {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            cache: Arc::new(SingleValueCache::new(CACHE_TTL_SECS)),
+        }
+    }
+
+    async fn snapshot(&self) -> AppResult<Arc<FlagSnapshot>> {
+        if let Some(snapshot) = self.cache.get() {
+            return Ok(snapshot);
+        }
+
+        let flags: Vec<FeatureFlag> = sqlx::query_as("SELECT * FROM feature_flags")
+            .fetch_all(&self.pool)
+            .await?;
+        let overrides: Vec<FeatureFlagOverride> =
+            sqlx::query_as("SELECT * FROM feature_flag_overrides")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let snapshot = Arc::new(FlagSnapshot {
+            flags: flags.into_iter().map(|f| (f.key.clone(), f)).collect(),
+            overrides: overrides
+                .into_iter()
+                .map(|o| ((o.flag_key, o.tenant_id), o.enabled))
+                .collect(),
+        });
+        self.cache.set(snapshot.clone());
+        Ok(snapshot)
+    }
+
+    /// Whether `key` is on for the tenant. Unknown flags are off.
+    pub async fn is_enabled(&self, key: &str, tenant_id: Option<&str>) -> AppResult<bool> {
+        let snapshot = self.snapshot().await?;
+        let Some(flag) = snapshot.flags.get(key) else {
+            return Ok(false);
+        };
+        let tenant_override = tenant_id.and_then(|tid| {
+            snapshot
+                .overrides
+                .get(&(key.to_string(), tid.to_string()))
+                .copied()
+        });
+        Ok(evaluate(flag, tenant_override, tenant_id))
+    }
+
+    /// Backend guard: `Forbidden` while the flag is off for the tenant.
+    pub async fn require(&self, key: &str, tenant_id: Option<&str>) -> AppResult<()> {
+        if self.is_enabled(key, tenant_id).await? {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(
+                "This feature is currently unavailable".to_string(),
+            ))
+        }
+    }
+
+    /// Every flag evaluated for the tenant (the frontend's view).
+    pub async fn evaluate_all(&self, tenant_id: Option<&str>) -> AppResult<HashMap<String, bool>> {
+        let snapshot = self.snapshot().await?;
+        Ok(snapshot
+            .flags
+            .values()
+            .map(|flag| {
+                let tenant_override = tenant_id.and_then(|tid| {
+                    snapshot
+                        .overrides
+                        .get(&(flag.key.clone(), tid.to_string()))
+                        .copied()
+                });
+                (flag.key.clone(), evaluate(flag, tenant_override, tenant_id))
+            })
+            .collect())
+    }
+
+    pub async fn list(&self) -> AppResult<Vec<FeatureFlagWithOverrides>> {
+        let flags: Vec<FeatureFlag> = sqlx::query_as("SELECT * FROM feature_flags ORDER BY key")
+            .fetch_all(&self.pool)
+            .await?;
+        let overrides: Vec<FeatureFlagOverride> =
+            sqlx::query_as("SELECT * FROM feature_flag_overrides ORDER BY flag_key, tenant_id")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut by_flag: HashMap<String, Vec<FeatureFlagOverride>> = HashMap::new();
+        for o in overrides {
+            by_flag.entry(o.flag_key.clone()).or_default().push(o);
+        }
+        Ok(flags
+            .into_iter()
+            .map(|flag| FeatureFlagWithOverrides {
+                overrides: by_flag.remove(&flag.key).unwrap_or_default(),
+                flag,
+            })
+            .collect())
+    }
+
+    pub async fn upsert(&self, key: &str, req: UpsertFeatureFlagRequest) -> AppResult<FeatureFlag> {
+        validate_key(key)?;
+        if !(0..=100).contains(&req.rollout_percentage) {
+            return Err(AppError::Validation(
+                "rollout_percentage must be between 0 and 100".to_string(),
+            ));
+        }
+        let description = req
+            .description
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty());
+
+        let now = Utc::now();
+        let flag: FeatureFlag = sqlx::query_as(
+            r#"
+            INSERT INTO feature_flags (key, description, enabled, rollout_percentage, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $5)
+            ON CONFLICT (key) DO UPDATE SET
+                description = EXCLUDED.description,
+                enabled = EXCLUDED.enabled,
+                rollout_percentage = EXCLUDED.rollout_percentage,
+                updated_at = EXCLUDED.updated_at
+            RETURNING *
+            "#,
+        )
+        .bind(key)
+        .bind(description)
+        .bind(req.enabled)
+        .bind(req.rollout_percentage)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        self.cache.invalidate();
+        Ok(flag)
+    }
+
+    pub async fn delete(&self, key: &str) -> AppResult<()> {
+        let res = sqlx::query("DELETE FROM feature_flags WHERE key = $1")
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!(
+                "Feature flag {} not found",
+                key
+            )));
+        }
+        self.cache.invalidate();
+        Ok(())
+    }
+
+    pub async fn set_override(
+        &self,
+        key: &str,
+        tenant_id: &str,
+        enabled: bool,
+    ) -> AppResult<FeatureFlagOverride> {
+        let exists: Option<String> =
+            sqlx::query_scalar("SELECT key FROM feature_flags WHERE key = $1")
+                .bind(key)
+                .fetch_optional(&self.pool)
+                .await?;
+        if exists.is_none() {
+            return Err(AppError::NotFound(format!(
+                "Feature flag {} not found",
+                key
+            )));
+        }
+
+        let row: FeatureFlagOverride = sqlx::query_as(
+            r#"
+            INSERT INTO feature_flag_overrides (flag_key, tenant_id, enabled, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (flag_key, tenant_id) DO UPDATE SET enabled = EXCLUDED.enabled
+            RETURNING *
+            "#,
+        )
+        .bind(key)
+        .bind(tenant_id)
+        .bind(enabled)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        self.cache.invalidate();
+        Ok(row)
+    }
+
+    pub async fn remove_override(&self, key: &str, tenant_id: &str) -> AppResult<()> {
+        sqlx::query("DELETE FROM feature_flag_overrides WHERE flag_key = $1 AND tenant_id = $2")
+            .bind(key)
+            .bind(tenant_id)
+            .execute(&self.pool)
+            .await?;
+        self.cache.invalidate();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flag(enabled: bool, rollout_percentage: i32) -> FeatureFlag {
+        FeatureFlag {
+            key: "new_dashboard".to_string(),
+            description: None,
+            enabled,
+            rollout_percentage,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn kill_switch_beats_overrides() {
+        assert!(!evaluate(&flag(false, 100), Some(true), Some("t1")));
+        assert!(!evaluate(&flag(true, 100), Some(false), Some("t1")));
+        assert!(evaluate(&flag(true, 0), Some(true), Some("t1")));
+    }
+
+    #[test]
+    fn rollout_buckets_are_stable_and_cumulative() {
+        let tenants: Vec<String> = (0..500).map(|i| format!("tenant-{}", i)).collect();
+        let on_at = |pct: i32| -> Vec<bool> {
+            tenants
+                .iter()
+                .map(|t| evaluate(&flag(true, pct), None, Some(t)))
+                .collect()
+        };
+
+        assert!(on_at(0).iter().all(|on| !on));
+        assert!(on_at(100).iter().all(|on| *on));
+
+        let quarter = on_at(25);
+        let half = on_at(50);
+        let share = quarter.iter().filter(|on| **on).count();
+        assert!((75..=175).contains(&share), "25% rollout hit {}", share);
+        assert!(quarter.iter().zip(&half).all(|(q, h)| !q || *h));
+        assert_eq!(quarter, on_at(25));
+    }
+
+    #[test]
+    fn no_tenant_needs_a_full_rollout() {
+        assert!(evaluate(&flag(true, 100), None, None));
+        assert!(!evaluate(&flag(true, 99), None, None));
+    }
+
+    #[test]
+    fn flag_keys_are_validated() {
+        assert!(validate_key("billing.new_checkout").is_ok());
+        assert!(validate_key("").is_err());
+        assert!(validate_key("New Checkout").is_err());
+        assert!(validate_key(&"a".repeat(MAX_KEY_LEN + 1)).is_err());
+    }
+}
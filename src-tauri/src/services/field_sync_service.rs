@@ -0,0 +1,772 @@
+//! Delta sync for technician devices that work without signal.
+//!
+//! A pull walks the work orders the technician can see by `(updated_at, id)`
+//! and brings along the customers, locations and checklists needed to work on
+//! them offline. A push replays the device's queued mutations through the
+//! regular work order operations, so permissions and status rules are the same
+//! as online; each result is stored under the device's mutation id so a
+//! retried push never applies anything twice.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Customer, CustomerLocation, FieldSyncChanges, FieldSyncMutation, FieldSyncMutationResult,
+    FieldSyncPullRequest, FieldSyncPushRequest, FieldSyncPushResponse, InstallationWorkOrder,
+    InstallationWorkOrderView, UpdateCustomerLocationRequest, UpdateWorkOrderChecklistItemRequest,
+    WorkOrderCheckRequest, WorkOrderChecklist,
+};
+use crate::services::{AuthService, CustomerService, WorkOrderChecklistService};
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+const DEFAULT_PULL_LIMIT: u32 = 100;
+const MAX_PULL_LIMIT: u32 = 200;
+const MAX_KNOWN_WORK_ORDERS: usize = 1000;
+const MAX_PUSH_MUTATIONS: usize = 100;
+const MAX_CLIENT_MUTATION_ID_LEN: usize = 100;
+const MUTATION_RETENTION_DAYS: i64 = 30;
+
+/// Work orders the actor sees: everything for admins/owners (`$2`), otherwise
+/// their own plus unassigned pending ones they could claim (`$3` = actor).
+const VISIBLE: &str = "($2 OR wo.assigned_to = $3 OR (wo.status = 'pending' AND COALESCE(btrim(wo.assigned_to), '') = ''))";
+
+const OPEN: &str = "wo.status IN ('pending', 'in_progress', 'on_hold')";
+
+const WORK_ORDER_VIEW_SELECT: &str = r#"
+    SELECT
+      wo.id, wo.tenant_id, wo.subscription_id, wo.invoice_id, wo.customer_id, wo.location_id,
+      cs.package_id AS package_id,
+      COALESCE(wo.router_id, cs.router_id) AS router_id,
+      wo.work_type, wo.details,
+      wo.status, wo.assigned_to, wo.scheduled_at, wo.scheduled_end_at, wo.completed_at, wo.notes, wo.created_at, wo.updated_at,
+      c.name AS customer_name,
+      l.label AS location_label,
+      p.name AS package_name,
+      r.name AS router_name,
+      u.name AS assigned_to_name,
+      u.email AS assigned_to_email,
+      csa.id AS assignment_id,
+      csa.status AS assignment_status,
+      cs.status AS subscription_status,
+      cs.starts_at AS subscription_starts_at,
+      EXISTS(
+        SELECT 1
+        FROM invoices i
+        WHERE i.tenant_id = wo.tenant_id
+          AND (
+            i.external_id = 'pkgsub:' || wo.subscription_id
+            OR i.external_id LIKE 'pkgsub:' || wo.subscription_id || ':%'
+          )
+      ) AS has_customer_package_invoice,
+      csa.selected_zone_id AS selected_zone_id,
+      sz.name AS selected_zone_name,
+      csa.selected_node_id AS selected_node_id,
+      nn.name AS selected_node_name,
+      csa.selected_node_score::float8 AS selected_node_score,
+      csa.path_node_ids AS path_node_ids,
+      csa.path_link_ids AS path_link_ids
+    FROM installation_work_orders wo
+    LEFT JOIN customers c ON c.tenant_id = wo.tenant_id AND c.id = wo.customer_id
+    LEFT JOIN customer_locations l ON l.tenant_id = wo.tenant_id AND l.id = wo.location_id
+    LEFT JOIN customer_subscriptions cs ON cs.tenant_id = wo.tenant_id AND cs.id = wo.subscription_id
+    LEFT JOIN isp_packages p ON p.tenant_id = wo.tenant_id AND p.id = cs.package_id
+    LEFT JOIN mikrotik_routers r
+      ON r.tenant_id = wo.tenant_id
+     AND r.id = COALESCE(wo.router_id, cs.router_id)
+    LEFT JOIN users u ON u.id = wo.assigned_to
+    LEFT JOIN customer_service_assignments csa ON csa.tenant_id = wo.tenant_id AND csa.work_order_id = wo.id
+    LEFT JOIN service_zones sz ON sz.tenant_id = wo.tenant_id::uuid AND sz.id::text = csa.selected_zone_id
+    LEFT JOIN network_nodes nn ON nn.tenant_id = wo.tenant_id::uuid AND nn.id::text = csa.selected_node_id
+"#;
+
+/// Position of a device in the change stream.
+#[derive(Debug, Clone, PartialEq)]
+struct SyncCursor {
+    /// Last work order delivered.
+    updated_at: DateTime<Utc>,
+    id: String,
+    /// Customers, locations and checklists changed after this are resent.
+    /// Stays at the epoch until the first full download is finished.
+    data_since: DateTime<Utc>,
+}
+
+impl SyncCursor {
+    fn start() -> Self {
+        Self {
+            updated_at: DateTime::UNIX_EPOCH,
+            id: String::new(),
+            data_since: DateTime::UNIX_EPOCH,
+        }
+    }
+
+    /// Still on the first download, which skips closed work orders.
+    fn is_initial(&self) -> bool {
+        self.data_since == DateTime::UNIX_EPOCH
+    }
+
+    fn encode(&self) -> String {
+        format!(
+            "{}.{}.{}",
+            self.updated_at.timestamp_micros(),
+            self.data_since.timestamp_micros(),
+            self.id
+        )
+    }
+
+    fn parse(raw: &str) -> AppResult<Self> {
+        let invalid = || AppError::Validation("Invalid sync cursor".to_string());
+        let mut parts = raw.trim().splitn(3, '.');
+        let mut micros = || {
+            parts
+                .next()
+                .and_then(|v| v.parse::<i64>().ok())
+                .and_then(DateTime::from_timestamp_micros)
+                .ok_or_else(invalid)
+        };
+        let updated_at = micros()?;
+        let data_since = micros()?;
+        let id = parts.next().ok_or_else(invalid)?.to_string();
+        Ok(Self {
+            updated_at,
+            id,
+            data_since,
+        })
+    }
+}
+
+/// How a failed mutation is reported. Conflicts are changes made on a stale
+/// copy: the device should refresh the record and let the technician decide.
+/// Rejections will fail however often they are retried.
+fn failure_status(err: &AppError, changed_since_base: bool) -> &'static str {
+    match err {
+        AppError::Conflict(_) => "conflict",
+        AppError::Validation(_) if changed_since_base => "conflict",
+        _ => "rejected",
+    }
+}
+
+/// Infrastructure errors are not the mutation's fault; the push is aborted so
+/// the device keeps it queued.
+fn is_transient(err: &AppError) -> bool {
+    matches!(err, AppError::Database(_) | AppError::Internal(_))
+}
+
+fn payload<T: DeserializeOwned>(m: &FieldSyncMutation) -> AppResult<T> {
+    let value = if m.payload.is_null() {
+        serde_json::json!({})
+    } else {
+        m.payload.clone()
+    };
+    serde_json::from_value(value)
+        .map_err(|e| AppError::Validation(format!("Invalid payload for {}: {}", m.kind, e)))
+}
+
+fn to_data<T: serde::Serialize>(value: T) -> AppResult<serde_json::Value> {
+    serde_json::to_value(value).map_err(|e| AppError::Internal(e.to_string()))
+}
+
+#[derive(sqlx::FromRow)]
+struct StoredMutation {
+    status: String,
+    error: Option<String>,
+    data: Option<serde_json::Value>,
+}
+
+#[derive(Clone)]
+pub struct FieldSyncService {
+    pool: DbPool,
+    auth_service: AuthService,
+    customers: CustomerService,
+    checklists: WorkOrderChecklistService,
+}
+
+impl FieldSyncService {
+    pub fn new(pool: DbPool, auth_service: AuthService, customers: CustomerService) -> Self {
+        Self {
+            checklists: WorkOrderChecklistService::new(pool.clone()),
+            pool,
+            auth_service,
+            customers,
+        }
+    }
+
+    // ---- Pull ----
+
+    pub async fn pull(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: FieldSyncPullRequest,
+    ) -> AppResult<FieldSyncChanges> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "work_orders", "read")
+            .await?;
+        if dto.known_work_order_ids.len() > MAX_KNOWN_WORK_ORDERS {
+            return Err(AppError::Validation(format!(
+                "At most {} known work orders can be sent",
+                MAX_KNOWN_WORK_ORDERS
+            )));
+        }
+
+        let server_time = Utc::now();
+        let cursor = match dto.cursor.as_deref().map(str::trim) {
+            Some(raw) if !raw.is_empty() => SyncCursor::parse(raw)?,
+            _ => SyncCursor::start(),
+        };
+        let limit = dto
+            .limit
+            .unwrap_or(DEFAULT_PULL_LIMIT)
+            .clamp(1, MAX_PULL_LIMIT);
+        let is_admin = self
+            .customers
+            .is_actor_admin_or_owner(tenant_id, actor_id)
+            .await?;
+
+        let mut work_orders: Vec<InstallationWorkOrderView> = sqlx::query_as(&format!(
+            r#"
+            {WORK_ORDER_VIEW_SELECT}
+            WHERE wo.tenant_id = $1
+              AND {VISIBLE}
+              AND (wo.updated_at, wo.id) > ($4, $5)
+              AND (NOT $6 OR {OPEN})
+            ORDER BY wo.updated_at ASC, wo.id ASC
+            LIMIT $7
+            "#
+        ))
+        .bind(tenant_id)
+        .bind(is_admin)
+        .bind(actor_id)
+        .bind(cursor.updated_at)
+        .bind(&cursor.id)
+        .bind(cursor.is_initial())
+        .bind(limit as i64 + 1)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let has_more = work_orders.len() > limit as usize;
+        work_orders.truncate(limit as usize);
+        let (end_at, end_id) = work_orders
+            .last()
+            .map(|w| (w.updated_at, w.id.clone()))
+            .unwrap_or((cursor.updated_at, cursor.id.clone()));
+
+        // Customer data for open work orders on this page, plus whatever
+        // changed for the other open ones since the last pull.
+        let customers: Vec<Customer> = sqlx::query_as(&format!(
+            r#"
+            SELECT DISTINCT c.id, c.tenant_id, c.name, c.email, c.phone, c.notes, c.is_active, c.created_at, c.updated_at
+            FROM customers c
+            JOIN installation_work_orders wo ON wo.tenant_id = c.tenant_id AND wo.customer_id = c.id
+            WHERE c.tenant_id = $1
+              AND c.deleted_at IS NULL
+              AND {VISIBLE}
+              AND {OPEN}
+              AND (
+                ((wo.updated_at, wo.id) > ($4, $5) AND (wo.updated_at, wo.id) <= ($6, $7))
+                OR c.updated_at > $8
+              )
+            "#
+        ))
+        .bind(tenant_id)
+        .bind(is_admin)
+        .bind(actor_id)
+        .bind(cursor.updated_at)
+        .bind(&cursor.id)
+        .bind(end_at)
+        .bind(&end_id)
+        .bind(cursor.data_since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let locations: Vec<CustomerLocation> = sqlx::query_as(&format!(
+            r#"
+            SELECT DISTINCT
+                l.id, l.tenant_id, l.customer_id, l.label, l.address_line1, l.address_line2,
+                l.city, l.state, l.postal_code, l.country,
+                l.latitude::float8 AS latitude, l.longitude::float8 AS longitude,
+                l.notes, l.created_at, l.updated_at
+            FROM customer_locations l
+            JOIN installation_work_orders wo ON wo.tenant_id = l.tenant_id AND wo.location_id = l.id
+            WHERE l.tenant_id = $1
+              AND {VISIBLE}
+              AND {OPEN}
+              AND (
+                ((wo.updated_at, wo.id) > ($4, $5) AND (wo.updated_at, wo.id) <= ($6, $7))
+                OR l.updated_at > $8
+              )
+            "#
+        ))
+        .bind(tenant_id)
+        .bind(is_admin)
+        .bind(actor_id)
+        .bind(cursor.updated_at)
+        .bind(&cursor.id)
+        .bind(end_at)
+        .bind(&end_id)
+        .bind(cursor.data_since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let ticked_elsewhere: Vec<String> = sqlx::query_scalar(&format!(
+            r#"
+            SELECT DISTINCT wo.id
+            FROM installation_work_order_checklist_items i
+            JOIN installation_work_orders wo ON wo.tenant_id = i.tenant_id AND wo.id = i.work_order_id
+            WHERE i.tenant_id = $1
+              AND {VISIBLE}
+              AND {OPEN}
+              AND i.completed_at > $4
+            "#
+        ))
+        .bind(tenant_id)
+        .bind(is_admin)
+        .bind(actor_id)
+        .bind(cursor.data_since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut checklist_ids: Vec<String> = work_orders
+            .iter()
+            .filter(|w| matches!(w.status.as_str(), "pending" | "in_progress" | "on_hold"))
+            .map(|w| w.id.clone())
+            .collect();
+        for id in ticked_elsewhere {
+            if !checklist_ids.contains(&id) {
+                checklist_ids.push(id);
+            }
+        }
+        let mut checklists: Vec<WorkOrderChecklist> = Vec::with_capacity(checklist_ids.len());
+        for id in &checklist_ids {
+            checklists.push(self.checklists.get_checklist(tenant_id, id).await?);
+        }
+
+        let revoked_work_order_ids = if has_more || dto.known_work_order_ids.is_empty() {
+            Vec::new()
+        } else {
+            let open: HashSet<String> = sqlx::query_scalar::<_, String>(&format!(
+                "SELECT wo.id FROM installation_work_orders wo WHERE wo.tenant_id = $1 AND {VISIBLE} AND {OPEN}"
+            ))
+            .bind(tenant_id)
+            .bind(is_admin)
+            .bind(actor_id)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .collect();
+            dto.known_work_order_ids
+                .into_iter()
+                .filter(|id| !open.contains(id))
+                .collect()
+        };
+
+        let next_cursor = SyncCursor {
+            updated_at: end_at,
+            id: end_id,
+            data_since: if has_more {
+                cursor.data_since
+            } else {
+                server_time
+            },
+        };
+
+        Ok(FieldSyncChanges {
+            work_orders,
+            checklists,
+            customers,
+            locations,
+            revoked_work_order_ids,
+            next_cursor: next_cursor.encode(),
+            has_more,
+            server_time,
+        })
+    }
+
+    // ---- Push ----
+
+    pub async fn push(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: FieldSyncPushRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<FieldSyncPushResponse> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "work_orders", "read")
+            .await?;
+        if dto.mutations.len() > MAX_PUSH_MUTATIONS {
+            return Err(AppError::Validation(format!(
+                "At most {} mutations can be pushed at once",
+                MAX_PUSH_MUTATIONS
+            )));
+        }
+
+        sqlx::query(
+            "DELETE FROM field_sync_mutations WHERE tenant_id = $1 AND user_id = $2 AND created_at < $3",
+        )
+        .bind(tenant_id)
+        .bind(actor_id)
+        .bind(Utc::now() - Duration::days(MUTATION_RETENTION_DAYS))
+        .execute(&self.pool)
+        .await?;
+
+        let mut results = Vec::with_capacity(dto.mutations.len());
+        for m in &dto.mutations {
+            results.push(self.push_one(actor_id, tenant_id, m, ip_address).await?);
+        }
+
+        Ok(FieldSyncPushResponse {
+            results,
+            server_time: Utc::now(),
+        })
+    }
+
+    async fn push_one(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        m: &FieldSyncMutation,
+        ip_address: Option<&str>,
+    ) -> AppResult<FieldSyncMutationResult> {
+        let client_id = m.client_mutation_id.trim();
+        if client_id.is_empty() || client_id.chars().count() > MAX_CLIENT_MUTATION_ID_LEN {
+            return Ok(FieldSyncMutationResult {
+                client_mutation_id: m.client_mutation_id.clone(),
+                status: "rejected".to_string(),
+                error: Some(format!(
+                    "client_mutation_id is required (max {} characters)",
+                    MAX_CLIENT_MUTATION_ID_LEN
+                )),
+                data: None,
+                replayed: false,
+            });
+        }
+
+        let stored: Option<StoredMutation> = sqlx::query_as(
+            "SELECT status, error, data FROM field_sync_mutations WHERE tenant_id = $1 AND user_id = $2 AND client_mutation_id = $3",
+        )
+        .bind(tenant_id)
+        .bind(actor_id)
+        .bind(client_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        if let Some(s) = stored {
+            return Ok(FieldSyncMutationResult {
+                client_mutation_id: client_id.to_string(),
+                status: s.status,
+                error: s.error,
+                data: s.data,
+                replayed: true,
+            });
+        }
+
+        let (status, error, data) = match self.apply(actor_id, tenant_id, m, ip_address).await {
+            Ok(data) => ("applied", None, Some(data)),
+            Err(err) if is_transient(&err) => return Err(err),
+            Err(err) => {
+                let changed = self.changed_since_base(tenant_id, m).await?;
+                let status = failure_status(&err, changed);
+                let data = if status == "conflict" {
+                    self.server_copy(tenant_id, m).await?
+                } else {
+                    None
+                };
+                (status, Some(err.to_string()), data)
+            }
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO field_sync_mutations
+                (id, tenant_id, user_id, client_mutation_id, kind, work_order_id, status, error, data, created_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10)
+            ON CONFLICT (tenant_id, user_id, client_mutation_id) DO NOTHING
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(actor_id)
+        .bind(client_id)
+        .bind(&m.kind)
+        .bind(&m.work_order_id)
+        .bind(status)
+        .bind(&error)
+        .bind(&data)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(FieldSyncMutationResult {
+            client_mutation_id: client_id.to_string(),
+            status: status.to_string(),
+            error,
+            data,
+            replayed: false,
+        })
+    }
+
+    async fn apply(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        m: &FieldSyncMutation,
+        ip_address: Option<&str>,
+    ) -> AppResult<serde_json::Value> {
+        let work_order_id = || {
+            m.work_order_id
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| AppError::Validation(format!("{} needs work_order_id", m.kind)))
+        };
+        let notes = || {
+            m.payload
+                .get("notes")
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+        let c = &self.customers;
+
+        match m.kind.as_str() {
+            "note" => to_data(
+                c.add_work_order_note(
+                    actor_id,
+                    tenant_id,
+                    work_order_id()?,
+                    &notes().unwrap_or_default(),
+                    ip_address,
+                )
+                .await?,
+            ),
+            "start" => to_data(
+                c.start_installation_work_order(
+                    actor_id,
+                    tenant_id,
+                    work_order_id()?,
+                    notes(),
+                    ip_address,
+                )
+                .await?,
+            ),
+            "hold" => to_data(
+                c.hold_work_order(actor_id, tenant_id, work_order_id()?, notes(), ip_address)
+                    .await?,
+            ),
+            "complete" => to_data(
+                c.complete_installation_work_order(
+                    actor_id,
+                    tenant_id,
+                    work_order_id()?,
+                    notes(),
+                    ip_address,
+                )
+                .await?,
+            ),
+            "check_in" => {
+                let dto: WorkOrderCheckRequest = payload(m)?;
+                to_data(
+                    c.check_in_work_order(actor_id, tenant_id, work_order_id()?, dto, ip_address)
+                        .await?,
+                )
+            }
+            "check_out" => {
+                let dto: WorkOrderCheckRequest = payload(m)?;
+                to_data(
+                    c.check_out_work_order(actor_id, tenant_id, work_order_id()?, dto, ip_address)
+                        .await?,
+                )
+            }
+            "checklist_item" => {
+                let work_order_id = work_order_id()?;
+                let item_id = m
+                    .item_id
+                    .as_deref()
+                    .filter(|v| !v.trim().is_empty())
+                    .ok_or_else(|| {
+                        AppError::Validation("checklist_item needs item_id".to_string())
+                    })?;
+                let dto: UpdateWorkOrderChecklistItemRequest = payload(m)?;
+                c.authorize_work_order_access(actor_id, tenant_id, work_order_id, true)
+                    .await?;
+                if let Some(base) = m.base_updated_at {
+                    let current = self
+                        .checklists
+                        .get_checklist(tenant_id, work_order_id)
+                        .await?;
+                    let taken = current.items.iter().any(|i| {
+                        i.id == item_id
+                            && i.completed_at.is_some_and(|at| at > base)
+                            && i.completed_by.as_deref() != Some(actor_id)
+                    });
+                    if taken {
+                        return Err(AppError::Conflict(
+                            "Checklist item was filled in by someone else".to_string(),
+                        ));
+                    }
+                }
+                to_data(
+                    self.checklists
+                        .update_item(tenant_id, work_order_id, item_id, actor_id, dto)
+                        .await?,
+                )
+            }
+            "location" => {
+                let location_id = m
+                    .location_id
+                    .as_deref()
+                    .filter(|v| !v.trim().is_empty())
+                    .ok_or_else(|| {
+                        AppError::Validation("location needs location_id".to_string())
+                    })?;
+                let dto: UpdateCustomerLocationRequest = payload(m)?;
+                if let Some(base) = m.base_updated_at {
+                    if self
+                        .location_updated_at(tenant_id, location_id)
+                        .await?
+                        .is_some_and(|at| at > base)
+                    {
+                        return Err(AppError::Conflict(
+                            "Location was changed on the server".to_string(),
+                        ));
+                    }
+                }
+                to_data(
+                    c.update_location(actor_id, tenant_id, location_id, dto, ip_address)
+                        .await?,
+                )
+            }
+            other => Err(AppError::Validation(format!(
+                "Unknown mutation kind '{}'",
+                other
+            ))),
+        }
+    }
+
+    async fn work_order_row(
+        &self,
+        tenant_id: &str,
+        work_order_id: &str,
+    ) -> AppResult<Option<InstallationWorkOrder>> {
+        let row = sqlx::query_as(
+            r#"
+            SELECT id, tenant_id, subscription_id, invoice_id, customer_id, location_id, router_id, work_type, details, status, assigned_to, scheduled_at, scheduled_end_at, completed_at, notes, created_at, updated_at
+            FROM installation_work_orders
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    async fn location_updated_at(
+        &self,
+        tenant_id: &str,
+        location_id: &str,
+    ) -> AppResult<Option<DateTime<Utc>>> {
+        let at = sqlx::query_scalar(
+            "SELECT updated_at FROM customer_locations WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(location_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(at)
+    }
+
+    /// Whether the work order moved on after the device last saw it.
+    async fn changed_since_base(&self, tenant_id: &str, m: &FieldSyncMutation) -> AppResult<bool> {
+        let (Some(base), Some(id)) = (m.base_updated_at, m.work_order_id.as_deref()) else {
+            return Ok(false);
+        };
+        Ok(self
+            .work_order_row(tenant_id, id)
+            .await?
+            .is_some_and(|wo| wo.updated_at > base))
+    }
+
+    /// The server's version of what a conflicting mutation touched.
+    async fn server_copy(
+        &self,
+        tenant_id: &str,
+        m: &FieldSyncMutation,
+    ) -> AppResult<Option<serde_json::Value>> {
+        match (m.kind.as_str(), m.work_order_id.as_deref()) {
+            ("location", _) => {
+                let Some(location_id) = m.location_id.as_deref() else {
+                    return Ok(None);
+                };
+                let row: Option<CustomerLocation> = sqlx::query_as(
+                    r#"
+                    SELECT id, tenant_id, customer_id, label, address_line1, address_line2,
+                           city, state, postal_code, country,
+                           latitude::float8 AS latitude, longitude::float8 AS longitude,
+                           notes, created_at, updated_at
+                    FROM customer_locations
+                    WHERE tenant_id = $1 AND id = $2
+                    "#,
+                )
+                .bind(tenant_id)
+                .bind(location_id)
+                .fetch_optional(&self.pool)
+                .await?;
+                row.map(to_data).transpose()
+            }
+            ("checklist_item", Some(id)) => {
+                to_data(self.checklists.get_checklist(tenant_id, id).await?).map(Some)
+            }
+            (_, Some(id)) => self
+                .work_order_row(tenant_id, id)
+                .await?
+                .map(to_data)
+                .transpose(),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{failure_status, SyncCursor};
+    use crate::error::AppError;
+    use chrono::{DateTime, Utc};
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursor = SyncCursor {
+            updated_at: "2026-04-07T08:15:30.123456Z"
+                .parse::<DateTime<Utc>>()
+                .unwrap(),
+            id: "6f1c2a1e-8d4b-4b55-9d0e-0c5c8f7a9b12".to_string(),
+            data_since: "2026-04-07T08:20:00Z".parse::<DateTime<Utc>>().unwrap(),
+        };
+        assert_eq!(SyncCursor::parse(&cursor.encode()).unwrap(), cursor);
+        assert!(!cursor.is_initial());
+
+        let start = SyncCursor::start();
+        assert_eq!(SyncCursor::parse(&start.encode()).unwrap(), start);
+        assert!(start.is_initial());
+
+        assert!(SyncCursor::parse("garbage").is_err());
+        assert!(SyncCursor::parse("1.2").is_err());
+        assert!(SyncCursor::parse("x.2.id").is_err());
+    }
+
+    #[test]
+    fn stale_failures_are_conflicts() {
+        let invalid = || AppError::Validation("Only pending work order can be started".into());
+        assert_eq!(failure_status(&invalid(), true), "conflict");
+        assert_eq!(failure_status(&invalid(), false), "rejected");
+        assert_eq!(
+            failure_status(&AppError::Conflict("taken".into()), false),
+            "conflict"
+        );
+        assert_eq!(
+            failure_status(&AppError::Forbidden("not yours".into()), true),
+            "rejected"
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! Translations of text the backend writes: error messages, built-in
+//! notification texts and receipt labels.
+//!
+//! English is the source language and stays inline in the code, gettext
+//! style: the catalog is keyed by the English text, so existing `AppError`
+//! messages translate without call-site changes. Entries containing `{}` are
+//! patterns whose holes carry over into the translation in order. Text
+//! without an entry stays English. Bahasa Indonesia (`id`) is the first
+//! translation.
+
+use crate::services::locale::{best_match, normalize_locale};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+pub const SOURCE_LANGUAGE: &str = "en";
+pub const LANGUAGES: &[&str] = &["en", "id"];
+
+const ID: &[(&str, &str)] = &[
+    // Errors
+    ("Invalid credentials", "Email atau kata sandi salah"),
+    ("User not found", "Pengguna tidak ditemukan"),
+    ("User already exists", "Pengguna sudah terdaftar"),
+    ("Unauthorized", "Tidak diizinkan"),
+    ("Invalid token", "Token tidak valid"),
+    ("Token expired", "Token sudah kedaluwarsa"),
+    ("Forbidden", "Akses ditolak"),
+    ("Not a tenant user", "Bukan pengguna tenant"),
+    ("No tenant ID in token", "Token tidak memuat ID tenant"),
+    ("Tenant not found", "Tenant tidak ditemukan"),
+    ("Tenant is suspended", "Tenant sedang ditangguhkan"),
+    ("No OTP code pending", "Tidak ada kode OTP yang menunggu"),
+    ("Invalid OTP code", "Kode OTP tidak valid"),
+    ("OTP code has expired", "Kode OTP sudah kedaluwarsa"),
+    ("Router not found", "Router tidak ditemukan"),
+    ("No access to router", "Tidak ada akses ke router"),
+    ("Location not found", "Lokasi tidak ditemukan"),
+    ("Customer not found", "Pelanggan tidak ditemukan"),
+    ("Subscription not found", "Langganan tidak ditemukan"),
+    ("Customer subscription not found", "Langganan pelanggan tidak ditemukan"),
+    ("You are not linked to any customer", "Akun Anda tidak terhubung ke pelanggan mana pun"),
+    ("Package not found", "Paket tidak ditemukan"),
+    ("Package name already exists", "Nama paket sudah digunakan"),
+    ("PPPoE account not found", "Akun PPPoE tidak ditemukan"),
+    ("Invoice not found", "Tagihan tidak ditemukan"),
+    ("Only paid invoices have a payment receipt", "Bukti pembayaran hanya tersedia untuk tagihan yang sudah lunas"),
+    ("Work order not found", "Perintah kerja tidak ditemukan"),
+    ("Incident not found", "Insiden tidak ditemukan"),
+    ("Support ticket not found", "Tiket dukungan tidak ditemukan"),
+    ("Ticket is closed", "Tiket sudah ditutup"),
+    ("Invalid download link", "Tautan unduhan tidak valid"),
+    ("Download link has expired", "Tautan unduhan sudah kedaluwarsa"),
+    ("Database error: {}", "Kesalahan basis data: {}"),
+    ("Permission denied: {}", "Akses ditolak: {}"),
+    ("Invoice {} not found", "Tagihan {} tidak ditemukan"),
+    ("Invalid locale '{}'", "Locale '{}' tidak valid"),
+    ("{} is required", "{} wajib diisi"),
+    ("{} not found", "{} tidak ditemukan"),
+    // Notification defaults (placeholders are kept as is)
+    ("Incident assigned: {{incident_title}}", "Insiden ditugaskan: {{incident_title}}"),
+    ("You were assigned to incident on {{target}}. Current status: {{status}}.", "Anda ditugaskan menangani insiden pada {{target}}. Status saat ini: {{status}}."),
+    ("Incident Assigned: {{incident_title}}", "Insiden Ditugaskan: {{incident_title}}"),
+    ("You were assigned to incident:\n{{incident_message}}\n\nTarget: {{target}}\nStatus: {{status}}\nOpen: {{action_url}}", "Anda ditugaskan menangani insiden:\n{{incident_message}}\n\nTarget: {{target}}\nStatus: {{status}}\nBuka: {{action_url}}"),
+    ("Incident escalated", "Insiden dieskalasi"),
+    ("{{incident_title}} has exceeded {{threshold_minutes}} minutes without acknowledgement.", "{{incident_title}} sudah lewat {{threshold_minutes}} menit tanpa ditanggapi."),
+    ("Router down", "Router mati"),
+    ("{{router_name}} became unreachable: {{error}}", "{{router_name}} tidak dapat dijangkau: {{error}}"),
+    ("Router online", "Router kembali online"),
+    ("{{router_name}} is back online.", "{{router_name}} kembali online."),
+    ("Router recovered", "Router pulih"),
+    ("{{router_name}} recovered after {{offline_seconds}}s offline.", "{{router_name}} pulih setelah {{offline_seconds}} detik offline."),
+    ("High CPU", "CPU tinggi"),
+    ("{{router_name}} CPU is {{cpu}}%.", "CPU {{router_name}} mencapai {{cpu}}%."),
+    ("High latency", "Latensi tinggi"),
+    ("{{router_name}} latency is {{latency_ms}}ms.", "Latensi {{router_name}} mencapai {{latency_ms}} ms."),
+    ("Invoice created", "Tagihan terbit"),
+    ("New invoice {{invoice_number}} is ready ({{amount}}). Please complete payment to activate/keep service.", "Tagihan baru {{invoice_number}} sudah terbit ({{amount}}). Silakan selesaikan pembayaran untuk mengaktifkan/mempertahankan layanan."),
+    ("Invoice due in {{days}} day(s)", "Tagihan jatuh tempo dalam {{days}} hari"),
+    ("Invoice due today", "Tagihan jatuh tempo hari ini"),
+    ("Invoice overdue by {{days}} day(s)", "Tagihan terlambat {{days}} hari"),
+    ("Invoice {{invoice_number}} is due on {{due_date}}. Please complete payment to keep service active.", "Tagihan {{invoice_number}} jatuh tempo pada {{due_date}}. Silakan selesaikan pembayaran agar layanan tetap aktif."),
+    ("Payment Successful", "Pembayaran Berhasil"),
+    ("Invoice {{invoice_number}} has been successfully paid. Thank you!", "Tagihan {{invoice_number}} berhasil dibayar. Terima kasih!"),
+    ("Payment Failed", "Pembayaran Gagal"),
+    ("Payment for invoice {{invoice_number}} failed. Please check your payment method.", "Pembayaran tagihan {{invoice_number}} gagal. Silakan periksa metode pembayaran Anda."),
+    ("Payment proof for invoice {{invoice_number}} was rejected. Please review the reason and upload a new proof.", "Bukti pembayaran tagihan {{invoice_number}} ditolak. Silakan periksa alasannya dan unggah bukti baru."),
+    ("New Payment Proof Uploaded", "Bukti Pembayaran Baru Diunggah"),
+    ("A payment proof has been uploaded for customer invoice {{invoice_number}}", "Bukti pembayaran telah diunggah untuk tagihan pelanggan {{invoice_number}}"),
+    ("Customer Payment Received", "Pembayaran Pelanggan Diterima"),
+    ("Customer invoice {{invoice_number}} has been paid. Amount: {{amount}}", "Tagihan pelanggan {{invoice_number}} telah dibayar. Jumlah: {{amount}}"),
+    ("Subscription suspended", "Langganan ditangguhkan"),
+    ("Your subscription has been suspended (invoice {{invoice_number}} overdue {{overdue_days}} day(s)).", "Langganan Anda ditangguhkan (tagihan {{invoice_number}} terlambat {{overdue_days}} hari)."),
+    ("Subscription resumed", "Langganan aktif kembali"),
+    ("Payment received for invoice {{invoice_number}}. Your subscription is active again.", "Pembayaran tagihan {{invoice_number}} sudah diterima. Langganan Anda aktif kembali."),
+    ("Order Queued for Installation", "Pesanan Menunggu Pemasangan"),
+    ("Payment for invoice {{invoice_number}} is confirmed. Your order is now Pending Installation and waiting assignment/schedule from admin or technician.", "Pembayaran tagihan {{invoice_number}} sudah dikonfirmasi. Pesanan Anda kini Menunggu Pemasangan dan menunggu penugasan/jadwal dari admin atau teknisi."),
+    ("Installation Work Order: New Request", "Perintah Kerja Pemasangan: Permintaan Baru"),
+    ("Invoice {{invoice_number}} is paid. A new installation work order is ready for assignment and scheduling (WO {{work_order_id}}).", "Tagihan {{invoice_number}} sudah dibayar. Perintah kerja pemasangan baru siap ditugaskan dan dijadwalkan (WO {{work_order_id}})."),
+    // Receipt and invoice labels (printers only take ASCII)
+    ("PAYMENT RECEIPT", "BUKTI PEMBAYARAN"),
+    ("INVOICE", "TAGIHAN"),
+    ("Paid", "Dibayar"),
+    ("Date", "Tanggal"),
+    ("Due", "Jatuh tempo"),
+    ("Customer", "Pelanggan"),
+    ("Payment", "Pembayaran"),
+    ("Method", "Metode"),
+    ("Cashier", "Kasir"),
+    ("** PAID **", "** LUNAS **"),
+    ("Printed {}", "Dicetak {}"),
+    ("pending", "menunggu"),
+    ("paid", "lunas"),
+    ("overdue", "terlambat"),
+    ("failed", "gagal"),
+    ("cancelled", "dibatalkan"),
+    ("rejected", "ditolak"),
+];
+
+struct Catalog {
+    exact: HashMap<&'static str, &'static str>,
+    /// Entries with `{}` holes, most specific first.
+    patterns: Vec<(&'static str, &'static str)>,
+}
+
+impl Catalog {
+    fn new(entries: &'static [(&'static str, &'static str)]) -> Self {
+        let mut exact = HashMap::new();
+        let mut patterns = Vec::new();
+        for &(source, target) in entries {
+            if source.contains("{}") {
+                patterns.push((source, target));
+            } else {
+                exact.insert(source, target);
+            }
+        }
+        // More literal text means a more specific pattern.
+        patterns.sort_by_key(|(source, _)| std::cmp::Reverse(source.replace("{}", "").len()));
+        Self { exact, patterns }
+    }
+
+    fn translate(&self, text: &str) -> Option<String> {
+        if let Some(target) = self.exact.get(text) {
+            return Some(target.to_string());
+        }
+        self.patterns.iter().find_map(|(source, target)| {
+            let holes = match_pattern(source, text)?;
+            Some(fill_pattern(target, &holes))
+        })
+    }
+}
+
+static CATALOGS: Lazy<HashMap<&'static str, Catalog>> =
+    Lazy::new(|| HashMap::from([("id", Catalog::new(ID))]));
+
+/// Values of the `{}` holes when `text` fits `pattern`. Holes are non-empty
+/// and take the shortest text that lets the rest match.
+fn match_pattern<'a>(pattern: &str, text: &'a str) -> Option<Vec<&'a str>> {
+    let mut pieces = pattern.split("{}");
+    let first = pieces.next()?;
+    let mut rest = text.strip_prefix(first)?;
+    let pieces: Vec<&str> = pieces.collect();
+    let mut holes = Vec::with_capacity(pieces.len());
+    for (i, piece) in pieces.iter().enumerate() {
+        let last = i == pieces.len() - 1;
+        let end = if last {
+            rest.strip_suffix(piece).map(str::len)?
+        } else {
+            rest.get(1..)?.find(piece)? + 1
+        };
+        if end == 0 {
+            return None;
+        }
+        holes.push(&rest[..end]);
+        rest = &rest[end + piece.len()..];
+    }
+    Some(holes)
+}
+
+fn fill_pattern(pattern: &str, holes: &[&str]) -> String {
+    let mut out = String::with_capacity(pattern.len());
+    let mut holes = holes.iter();
+    let mut pieces = pattern.split("{}").peekable();
+    while let Some(piece) = pieces.next() {
+        out.push_str(piece);
+        if pieces.peek().is_some() {
+            out.push_str(holes.next().copied().unwrap_or_default());
+        }
+    }
+    out
+}
+
+/// The supported language closest to `locale`, English when there is none.
+pub fn language_for(locale: Option<&str>) -> &'static str {
+    locale
+        .and_then(|l| best_match(LANGUAGES.iter().copied(), l))
+        .unwrap_or(SOURCE_LANGUAGE)
+}
+
+/// First supported language in an `Accept-Language` header, by preference.
+pub fn language_from_accept(header: &str) -> Option<&'static str> {
+    let mut ranges: Vec<(f32, String)> = header
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = normalize_locale(parts.next()?)?;
+            let q = parts
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (q > 0.0).then_some((q, tag))
+        })
+        .collect();
+    // Stable, so equal weights keep the header order.
+    ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranges
+        .iter()
+        .find_map(|(_, tag)| best_match(LANGUAGES.iter().copied(), tag))
+}
+
+/// `text` in the language closest to `locale`, or `text` itself when the
+/// catalog has no entry for it.
+pub fn translate(locale: Option<&str>, text: &str) -> String {
+    CATALOGS
+        .get(language_for(locale))
+        .and_then(|catalog| catalog.translate(text))
+        .unwrap_or_else(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_exact_text_and_patterns() {
+        assert_eq!(
+            translate(Some("id-ID"), "Invoice not found"),
+            "Tagihan tidak ditemukan"
+        );
+        assert_eq!(
+            translate(Some("id"), "Permission denied: customers:read"),
+            "Akses ditolak: customers:read"
+        );
+        assert_eq!(
+            translate(Some("id"), "Invoice INV-1 not found"),
+            "Tagihan INV-1 tidak ditemukan"
+        );
+        assert_eq!(
+            translate(Some("id"), "Zone not found"),
+            "Zone tidak ditemukan"
+        );
+        assert_eq!(translate(Some("id"), " not found"), " not found");
+        assert_eq!(translate(Some("id"), "Something new"), "Something new");
+        assert_eq!(
+            translate(Some("en-US"), "Invoice not found"),
+            "Invoice not found"
+        );
+        assert_eq!(translate(None, "Forbidden"), "Forbidden");
+    }
+
+    #[test]
+    fn patterns_need_every_literal_piece() {
+        assert_eq!(
+            match_pattern("{} is required", "name is required"),
+            Some(vec!["name"])
+        );
+        assert_eq!(
+            match_pattern("a {} b {} c", "a 1 b 2 b 3 c"),
+            Some(vec!["1", "2 b 3"])
+        );
+        assert_eq!(match_pattern("{} is required", "is required"), None);
+        assert_eq!(
+            match_pattern("Invoice {} not found", "Invoice not found"),
+            None
+        );
+        assert_eq!(
+            fill_pattern("{} wajib diisi", &["name"]),
+            "name wajib diisi"
+        );
+    }
+
+    #[test]
+    fn picks_language_from_locale_and_accept_language() {
+        assert_eq!(language_for(Some("id-ID")), "id");
+        assert_eq!(language_for(Some("fr")), "en");
+        assert_eq!(language_for(Some("not a tag")), "en");
+        assert_eq!(
+            language_from_accept("id-ID,id;q=0.9,en-US;q=0.8"),
+            Some("id")
+        );
+        assert_eq!(
+            language_from_accept("fr-FR, en;q=0.5, id;q=0.7"),
+            Some("id")
+        );
+        assert_eq!(language_from_accept("id;q=0, en"), Some("en"));
+        assert_eq!(language_from_accept("fr, de"), None);
+        assert_eq!(language_from_accept(""), None);
+    }
+}
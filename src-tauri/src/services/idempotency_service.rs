@@ -0,0 +1,249 @@
+//! Idempotency Service - replay-safe handling of retried mutating requests
+//!
+//! Clients send an `Idempotency-Key` header on selected POST endpoints. The first
+//! request reserves the key, the final response is persisted, and retries within
+//! the window receive the stored response instead of executing the handler again.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// In-progress reservations older than this are treated as abandoned
+/// (e.g. the process crashed mid-request) and can be taken over.
+const STALE_RESERVATION_SECS: i64 = 300;
+
+#[derive(Clone)]
+pub struct IdempotencyService {
+    pool: DbPool,
+}
+
+/// Response persisted for a completed idempotent request.
+#[derive(Debug, Clone)]
+pub struct StoredIdempotentResponse {
+    pub status_code: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// Outcome of trying to reserve an idempotency key.
+#[derive(Debug)]
+pub enum IdempotencyBegin {
+    /// Key reserved; the request should be executed and then completed/released.
+    Started(String),
+    /// Key already completed with the same payload; replay the stored response.
+    Replay(StoredIdempotentResponse),
+    /// Another request with the same key is still executing.
+    InProgress,
+    /// Key was already used for a different request payload.
+    Mismatch,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct IdempotencyRow {
+    id: String,
+    request_hash: String,
+    status: String,
+    response_status: Option<i32>,
+    response_content_type: Option<String>,
+    response_body: Option<Vec<u8>>,
+    created_at: DateTime<Utc>,
+}
+
+impl IdempotencyService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Fingerprint of the request so a key can't be reused for a different payload.
+    pub fn fingerprint(method: &str, path: &str, body: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(method.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(path.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(body);
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Reserve `key` for `scope` (user or client IP), or report why it can't be executed.
+    pub async fn begin(
+        &self,
+        scope: &str,
+        key: &str,
+        method: &str,
+        path: &str,
+        request_hash: &str,
+        window_hours: i64,
+    ) -> AppResult<IdempotencyBegin> {
+        let now = Utc::now();
+        let expires_at = now + Duration::hours(window_hours.max(1));
+
+        // Expired keys are free to be reused.
+        sqlx::query(
+            "DELETE FROM idempotency_keys WHERE scope = $1 AND idempotency_key = $2 AND expires_at < $3",
+        )
+        .bind(scope)
+        .bind(key)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let id = Uuid::new_v4().to_string();
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO idempotency_keys
+              (id, scope, idempotency_key, request_method, request_path, request_hash, status, created_at, expires_at)
+            VALUES
+              ($1, $2, $3, $4, $5, $6, 'in_progress', $7, $8)
+            ON CONFLICT (scope, idempotency_key) DO NOTHING
+        "#,
+        )
+        .bind(&id)
+        .bind(scope)
+        .bind(key)
+        .bind(method)
+        .bind(path)
+        .bind(request_hash)
+        .bind(now)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?
+        .rows_affected();
+
+        if inserted == 1 {
+            return Ok(IdempotencyBegin::Started(id));
+        }
+
+        let row = sqlx::query_as::<_, IdempotencyRow>(
+            r#"
+            SELECT id, request_hash, status, response_status, response_content_type, response_body, created_at
+            FROM idempotency_keys
+            WHERE scope = $1 AND idempotency_key = $2
+        "#,
+        )
+        .bind(scope)
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let Some(row) = row else {
+            // Released between our insert and select; let the client retry.
+            return Ok(IdempotencyBegin::InProgress);
+        };
+
+        if row.request_hash != request_hash {
+            return Ok(IdempotencyBegin::Mismatch);
+        }
+
+        if row.status == "completed" {
+            return Ok(IdempotencyBegin::Replay(StoredIdempotentResponse {
+                status_code: row
+                    .response_status
+                    .and_then(|s| u16::try_from(s).ok())
+                    .unwrap_or(200),
+                content_type: row.response_content_type,
+                body: row.response_body.unwrap_or_default(),
+            }));
+        }
+
+        if now - row.created_at > Duration::seconds(STALE_RESERVATION_SECS) {
+            let taken = sqlx::query(
+                r#"
+                UPDATE idempotency_keys
+                SET created_at = $1, expires_at = $2
+                WHERE id = $3 AND status = 'in_progress' AND created_at = $4
+            "#,
+            )
+            .bind(now)
+            .bind(expires_at)
+            .bind(&row.id)
+            .bind(row.created_at)
+            .execute(&self.pool)
+            .await
+            .map_err(AppError::Database)?
+            .rows_affected();
+
+            if taken == 1 {
+                return Ok(IdempotencyBegin::Started(row.id));
+            }
+        }
+
+        Ok(IdempotencyBegin::InProgress)
+    }
+
+    /// Persist the final response for a reserved key.
+    pub async fn complete(
+        &self,
+        id: &str,
+        status_code: u16,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE idempotency_keys
+            SET status = 'completed',
+                response_status = $1,
+                response_content_type = $2,
+                response_body = $3,
+                completed_at = $4
+            WHERE id = $5
+        "#,
+        )
+        .bind(status_code as i32)
+        .bind(content_type)
+        .bind(body)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// Drop a reservation so the client can retry (used when the handler failed server-side).
+    pub async fn release(&self, id: &str) -> AppResult<()> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE id = $1 AND status = 'in_progress'")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// Remove keys past their replay window.
+    pub async fn purge_expired(&self) -> AppResult<u64> {
+        let res = sqlx::query("DELETE FROM idempotency_keys WHERE expires_at < $1")
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(res.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_depends_on_payload() {
+        let a = IdempotencyService::fingerprint("POST", "/api/payment/invoices/plan", b"{\"a\":1}");
+        let b = IdempotencyService::fingerprint("POST", "/api/payment/invoices/plan", b"{\"a\":2}");
+        let c = IdempotencyService::fingerprint("POST", "/api/payment/invoices/plan", b"{\"a\":1}");
+        assert_ne!(a, b);
+        assert_eq!(a, c);
+    }
+
+    #[test]
+    fn test_fingerprint_depends_on_path() {
+        let a = IdempotencyService::fingerprint("POST", "/api/auth/register", b"{}");
+        let b = IdempotencyService::fingerprint("POST", "/api/public/customer-register", b"{}");
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,825 @@
+//! Self-service integration data for tenants building on the public API:
+//! API keys, webhook subscriptions, the log of webhook deliveries and how
+//! much of its rate limit each key is using.
+//!
+//! Keys and signing secrets are random and shown once. Keys are kept as a
+//! SHA-256 hash (see `AuthService::validate_token` for how they sign in);
+//! webhook secrets are kept encrypted because deliveries need them back.
+//! Deliveries themselves are made by the event outbox dispatcher; a test
+//! event can be queued for a single webhook to check the endpoint.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::http::middleware::api_key_rate_limit_key;
+use crate::models::{
+    ApiKeyRateLimit, CreateApiKeyRequest, CreateWebhookRequest, CreatedApiKey, CreatedWebhook,
+    IntegrationsOverview, TenantApiKey, TenantWebhook, UpdateWebhookRequest, WebhookDelivery,
+    WebhookDeliveryQuery,
+};
+use crate::security::secret::encrypt_secret_for;
+use crate::services::auth_service::{api_key_hash, API_KEY_PREFIX};
+use crate::services::event_outbox_service;
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::{AuditService, AuthService};
+use chrono::Utc;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use std::net::IpAddr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub(crate) const PURPOSE_WEBHOOK: &str = "tenant_webhook_secrets";
+const WEBHOOK_SECRET_PREFIX: &str = "whsec_";
+/// Event type of the ping sent by [`IntegrationService::test_webhook`].
+const WEBHOOK_TEST_EVENT: &str = "webhook.test";
+
+const MAX_ACTIVE_KEYS: i64 = 25;
+const MAX_WEBHOOKS: i64 = 10;
+const MAX_EVENT_TYPES: usize = 50;
+const MAX_NAME_LEN: usize = 100;
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+const MAX_DELIVERY_LIMIT: i64 = 200;
+const DELIVERY_STATUSES: &[&str] = &["pending", "delivered", "failed"];
+/// Characters of a key kept in the clear to tell keys apart.
+const KEY_PREFIX_LEN: usize = 12;
+
+const API_KEY_SELECT: &str = r#"
+    SELECT k.id, k.name, k.key_prefix, k.user_id, u.name AS user_name,
+           k.expires_at, k.last_used_at, k.revoked_at, k.created_at
+    FROM tenant_api_keys k
+    LEFT JOIN users u ON u.id = k.user_id
+"#;
+
+const WEBHOOK_SELECT: &str = r#"
+    SELECT id, url, description, event_types, is_active, created_at, updated_at
+    FROM tenant_webhooks
+"#;
+
+const DELIVERY_SELECT: &str = r#"
+    SELECT id, webhook_id, event_id, event_type, payload, status, attempts, response_status,
+           duration_ms, last_error, next_attempt_at, delivered_at, created_at
+    FROM tenant_webhook_deliveries
+"#;
+
+fn random_token(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+fn validate_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "Name must be 1 to {} characters",
+            MAX_NAME_LEN
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// `http(s)` URL of a host outside this server. Private networks are
+/// allowed, since an ISP's own systems usually live on one, but loopback and
+/// link-local addresses (cloud metadata) are not.
+fn validate_url(raw: &str) -> AppResult<String> {
+    let invalid = || AppError::Validation("Webhook URL must be an http(s) URL".to_string());
+    let url = reqwest::Url::parse(raw.trim()).map_err(|_| invalid())?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid());
+    }
+    let host = url.host_str().ok_or_else(invalid)?.to_ascii_lowercase();
+    let internal = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(IpAddr::V4(ip)) => ip.is_loopback() || ip.is_link_local() || ip.is_unspecified(),
+        Ok(IpAddr::V6(ip)) => {
+            ip.is_loopback() || ip.is_unspecified() || (ip.segments()[0] & 0xffc0) == 0xfe80
+        }
+        Err(_) => host == "localhost" || host.ends_with(".localhost"),
+    };
+    if internal {
+        return Err(AppError::Validation(
+            "Webhook URL must not point at this server".to_string(),
+        ));
+    }
+    Ok(url.to_string())
+}
+
+/// Trimmed, deduplicated event types, e.g. `support_ticket_updated`.
+fn validate_event_types(types: Vec<String>) -> AppResult<Vec<String>> {
+    let mut out: Vec<String> = Vec::new();
+    for t in types {
+        let t = t.trim().to_string();
+        let valid = !t.is_empty()
+            && t.len() <= MAX_NAME_LEN
+            && t.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':' | '-'));
+        if !valid {
+            return Err(AppError::Validation(format!("Invalid event type '{}'", t)));
+        }
+        if !out.contains(&t) {
+            out.push(t);
+        }
+    }
+    if out.len() > MAX_EVENT_TYPES {
+        return Err(AppError::Validation(format!(
+            "At most {} event types per webhook",
+            MAX_EVENT_TYPES
+        )));
+    }
+    Ok(out)
+}
+
+fn clean_description(description: Option<String>) -> Option<String> {
+    description
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+}
+
+#[derive(Clone)]
+pub struct IntegrationService {
+    pool: DbPool,
+    auth_service: AuthService,
+    audit_service: AuditService,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl IntegrationService {
+    pub fn new(
+        pool: DbPool,
+        auth_service: AuthService,
+        audit_service: AuditService,
+        rate_limiter: Arc<RateLimiter>,
+    ) -> Self {
+        Self {
+            pool,
+            auth_service,
+            audit_service,
+            rate_limiter,
+        }
+    }
+
+    /// Keys, webhooks, the latest deliveries and rate-limit use together.
+    /// `api_limit_per_minute` is the configured API rate limit.
+    pub async fn overview(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        api_limit_per_minute: u32,
+    ) -> AppResult<IntegrationsOverview> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "integrations", "read")
+            .await?;
+        Ok(IntegrationsOverview {
+            api_keys: self.fetch_api_keys(tenant_id).await?,
+            webhooks: self.fetch_webhooks(tenant_id).await?,
+            recent_deliveries: self
+                .fetch_deliveries(tenant_id, None, None, DEFAULT_DELIVERY_LIMIT)
+                .await?,
+            rate_limits: self
+                .fetch_rate_limits(tenant_id, api_limit_per_minute)
+                .await?,
+        })
+    }
+
+    // ---- API keys ----
+
+    pub async fn list_api_keys(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+    ) -> AppResult<Vec<TenantApiKey>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "integrations", "read")
+            .await?;
+        self.fetch_api_keys(tenant_id).await
+    }
+
+    async fn fetch_api_keys(&self, tenant_id: &str) -> AppResult<Vec<TenantApiKey>> {
+        let rows: Vec<TenantApiKey> = sqlx::query_as(&format!(
+            "{API_KEY_SELECT} WHERE k.tenant_id = $1 ORDER BY k.revoked_at IS NOT NULL, k.created_at DESC"
+        ))
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn get_api_key(&self, tenant_id: &str, id: &str) -> AppResult<TenantApiKey> {
+        let row: Option<TenantApiKey> = sqlx::query_as(&format!(
+            "{API_KEY_SELECT} WHERE k.tenant_id = $1 AND k.id = $2"
+        ))
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.ok_or_else(|| AppError::NotFound("API key not found".to_string()))
+    }
+
+    /// New key acting as `actor_id`. The token in the result is the only
+    /// time the key is readable.
+    pub async fn create_api_key(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: CreateApiKeyRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<CreatedApiKey> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "integrations", "manage")
+            .await?;
+        let name = validate_name(&dto.name)?;
+        let now = Utc::now();
+        if dto.expires_at.is_some_and(|at| at <= now) {
+            return Err(AppError::Validation(
+                "Expiry must be in the future".to_string(),
+            ));
+        }
+        let active: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM tenant_api_keys WHERE tenant_id = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > $2)",
+        )
+        .bind(tenant_id)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+        if active >= MAX_ACTIVE_KEYS {
+            return Err(AppError::QuotaExceeded(format!(
+                "At most {} active API keys; revoke one first",
+                MAX_ACTIVE_KEYS
+            )));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let token = format!("{}{}", API_KEY_PREFIX, random_token(40));
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_api_keys
+                (id, tenant_id, user_id, name, key_prefix, key_hash, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(actor_id)
+        .bind(&name)
+        .bind(&token[..KEY_PREFIX_LEN])
+        .bind(api_key_hash(&token))
+        .bind(dto.expires_at)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "API_KEY_CREATE",
+                "tenant_api_keys",
+                Some(&id),
+                Some(&format!(
+                    "Created API key {} ({})",
+                    name,
+                    &token[..KEY_PREFIX_LEN]
+                )),
+                ip_address,
+            )
+            .await;
+        Ok(CreatedApiKey {
+            key: self.get_api_key(tenant_id, &id).await?,
+            token,
+        })
+    }
+
+    pub async fn revoke_api_key(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        ip_address: Option<&str>,
+    ) -> AppResult<TenantApiKey> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "integrations", "manage")
+            .await?;
+        let key = self.get_api_key(tenant_id, id).await?;
+        if key.revoked_at.is_some() {
+            return Ok(key);
+        }
+        sqlx::query("UPDATE tenant_api_keys SET revoked_at = $1 WHERE tenant_id = $2 AND id = $3")
+            .bind(Utc::now())
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "API_KEY_REVOKE",
+                "tenant_api_keys",
+                Some(id),
+                Some(&format!(
+                    "Revoked API key {} ({})",
+                    key.name, key.key_prefix
+                )),
+                ip_address,
+            )
+            .await;
+        self.get_api_key(tenant_id, id).await
+    }
+
+    // ---- Webhooks ----
+
+    pub async fn list_webhooks(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+    ) -> AppResult<Vec<TenantWebhook>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "integrations", "read")
+            .await?;
+        self.fetch_webhooks(tenant_id).await
+    }
+
+    async fn fetch_webhooks(&self, tenant_id: &str) -> AppResult<Vec<TenantWebhook>> {
+        let rows: Vec<TenantWebhook> = sqlx::query_as(&format!(
+            "{WEBHOOK_SELECT} WHERE tenant_id = $1 ORDER BY created_at"
+        ))
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn get_webhook(&self, tenant_id: &str, id: &str) -> AppResult<TenantWebhook> {
+        let row: Option<TenantWebhook> = sqlx::query_as(&format!(
+            "{WEBHOOK_SELECT} WHERE tenant_id = $1 AND id = $2"
+        ))
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))
+    }
+
+    /// New subscription with a fresh signing secret, returned this once.
+    pub async fn create_webhook(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: CreateWebhookRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<CreatedWebhook> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "integrations", "manage")
+            .await?;
+        let url = validate_url(&dto.url)?;
+        let event_types = validate_event_types(dto.event_types)?;
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM tenant_webhooks WHERE tenant_id = $1")
+                .bind(tenant_id)
+                .fetch_one(&self.pool)
+                .await?;
+        if count >= MAX_WEBHOOKS {
+            return Err(AppError::QuotaExceeded(format!(
+                "At most {} webhooks",
+                MAX_WEBHOOKS
+            )));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let secret = format!("{}{}", WEBHOOK_SECRET_PREFIX, random_token(32));
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_webhooks
+                (id, tenant_id, url, description, event_types, secret_enc, is_active,
+                 created_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, true, $7, $8, $8)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&url)
+        .bind(clean_description(dto.description))
+        .bind(&event_types)
+        .bind(encrypt_secret_for(PURPOSE_WEBHOOK, &secret)?)
+        .bind(actor_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WEBHOOK_CREATE",
+                "tenant_webhooks",
+                Some(&id),
+                Some(&format!("Added webhook {}", url)),
+                ip_address,
+            )
+            .await;
+        Ok(CreatedWebhook {
+            webhook: self.get_webhook(tenant_id, &id).await?,
+            secret,
+        })
+    }
+
+    /// Changes a subscription. Turning it off gives up on its pending
+    /// deliveries rather than flooding the endpoint when it comes back.
+    pub async fn update_webhook(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        dto: UpdateWebhookRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<TenantWebhook> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "integrations", "manage")
+            .await?;
+        let current = self.get_webhook(tenant_id, id).await?;
+        let url = match dto.url {
+            Some(url) => validate_url(&url)?,
+            None => current.url.clone(),
+        };
+        let event_types = match dto.event_types {
+            Some(types) => validate_event_types(types)?,
+            None => current.event_types.clone(),
+        };
+        let description = match dto.description {
+            Some(d) => clean_description(Some(d)),
+            None => current.description.clone(),
+        };
+        let is_active = dto.is_active.unwrap_or(current.is_active);
+        let now = Utc::now();
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            UPDATE tenant_webhooks
+            SET url = $1, description = $2, event_types = $3, is_active = $4, updated_at = $5
+            WHERE tenant_id = $6 AND id = $7
+            "#,
+        )
+        .bind(&url)
+        .bind(&description)
+        .bind(&event_types)
+        .bind(is_active)
+        .bind(now)
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        if current.is_active && !is_active {
+            sqlx::query(
+                r#"
+                UPDATE tenant_webhook_deliveries
+                SET status = 'failed', last_error = 'Webhook turned off', updated_at = $1
+                WHERE webhook_id = $2 AND status = 'pending'
+                "#,
+            )
+            .bind(now)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        let details = match (current.is_active, is_active) {
+            (true, false) => format!("Turned off webhook {}", url),
+            (false, true) => format!("Turned on webhook {}", url),
+            _ => format!("Updated webhook {}", url),
+        };
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WEBHOOK_UPDATE",
+                "tenant_webhooks",
+                Some(id),
+                Some(&details),
+                ip_address,
+            )
+            .await;
+        self.get_webhook(tenant_id, id).await
+    }
+
+    pub async fn delete_webhook(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        ip_address: Option<&str>,
+    ) -> AppResult<()> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "integrations", "manage")
+            .await?;
+        let current = self.get_webhook(tenant_id, id).await?;
+        sqlx::query("DELETE FROM tenant_webhooks WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WEBHOOK_DELETE",
+                "tenant_webhooks",
+                Some(id),
+                Some(&format!("Removed webhook {}", current.url)),
+                ip_address,
+            )
+            .await;
+        Ok(())
+    }
+
+    /// Queues a `webhook.test` event for this webhook alone. It is signed
+    /// and retried like any other event and shows up in the delivery log.
+    pub async fn test_webhook(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        ip_address: Option<&str>,
+    ) -> AppResult<WebhookDelivery> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "integrations", "manage")
+            .await?;
+        let webhook = self.get_webhook(tenant_id, id).await?;
+        if !webhook.is_active {
+            return Err(AppError::Validation(
+                "Turn the webhook on before testing it".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        let delivery_id = Uuid::new_v4().to_string();
+        let event_id = Uuid::new_v4().to_string();
+        let payload = serde_json::json!({
+            "type": WEBHOOK_TEST_EVENT,
+            "webhook_id": id,
+            "sent_at": now,
+        })
+        .to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_webhook_deliveries
+              (id, tenant_id, webhook_id, event_id, event_type, payload, status, attempts,
+               next_attempt_at, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, 'pending', 0, $7, $7, $7)
+            "#,
+        )
+        .bind(&delivery_id)
+        .bind(tenant_id)
+        .bind(id)
+        .bind(&event_id)
+        .bind(WEBHOOK_TEST_EVENT)
+        .bind(&payload)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        event_outbox_service::wake();
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WEBHOOK_TEST",
+                "tenant_webhooks",
+                Some(id),
+                Some(&format!("Sent a test event to {}", webhook.url)),
+                ip_address,
+            )
+            .await;
+
+        let delivery: Option<WebhookDelivery> = sqlx::query_as(&format!(
+            "{DELIVERY_SELECT} WHERE tenant_id = $1 AND id = $2"
+        ))
+        .bind(tenant_id)
+        .bind(&delivery_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        delivery.ok_or_else(|| AppError::NotFound("Delivery not found".to_string()))
+    }
+
+    // ---- Deliveries ----
+
+    /// Newest first. Deliveries are kept for the event outbox retention.
+    pub async fn list_deliveries(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        q: WebhookDeliveryQuery,
+    ) -> AppResult<Vec<WebhookDelivery>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "integrations", "read")
+            .await?;
+        if let Some(status) = q.status.as_deref() {
+            if !DELIVERY_STATUSES.contains(&status) {
+                return Err(AppError::Validation(format!(
+                    "status must be one of: {}",
+                    DELIVERY_STATUSES.join(", ")
+                )));
+            }
+        }
+        let limit = q
+            .limit
+            .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+            .clamp(1, MAX_DELIVERY_LIMIT);
+        self.fetch_deliveries(
+            tenant_id,
+            q.webhook_id.as_deref(),
+            q.status.as_deref(),
+            limit,
+        )
+        .await
+    }
+
+    async fn fetch_deliveries(
+        &self,
+        tenant_id: &str,
+        webhook_id: Option<&str>,
+        status: Option<&str>,
+        limit: i64,
+    ) -> AppResult<Vec<WebhookDelivery>> {
+        let rows: Vec<WebhookDelivery> = sqlx::query_as(&format!(
+            r#"{DELIVERY_SELECT}
+            WHERE tenant_id = $1
+              AND ($2::text IS NULL OR webhook_id = $2)
+              AND ($3::text IS NULL OR status = $3)
+            ORDER BY created_at DESC
+            LIMIT $4"#
+        ))
+        .bind(tenant_id)
+        .bind(webhook_id)
+        .bind(status)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Sends a delivery again from the first attempt, whatever its status.
+    pub async fn redeliver(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        ip_address: Option<&str>,
+    ) -> AppResult<WebhookDelivery> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "integrations", "manage")
+            .await?;
+        let now = Utc::now();
+        let queued = sqlx::query(
+            r#"
+            UPDATE tenant_webhook_deliveries d
+            SET status = 'pending', attempts = 0, next_attempt_at = $1, last_error = NULL,
+                updated_at = $1
+            FROM tenant_webhooks w
+            WHERE w.id = d.webhook_id AND w.is_active AND d.tenant_id = $2 AND d.id = $3
+            "#,
+        )
+        .bind(now)
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        let delivery: Option<WebhookDelivery> = sqlx::query_as(&format!(
+            "{DELIVERY_SELECT} WHERE tenant_id = $1 AND id = $2"
+        ))
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let delivery =
+            delivery.ok_or_else(|| AppError::NotFound("Delivery not found".to_string()))?;
+        if queued.rows_affected() == 0 {
+            return Err(AppError::Validation(
+                "Turn the webhook on before redelivering".to_string(),
+            ));
+        }
+        event_outbox_service::wake();
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WEBHOOK_REDELIVER",
+                "tenant_webhook_deliveries",
+                Some(id),
+                Some(&format!(
+                    "Queued {} event {} again",
+                    delivery.event_type, delivery.event_id
+                )),
+                ip_address,
+            )
+            .await;
+        Ok(delivery)
+    }
+
+    // ---- Rate limits ----
+
+    /// Use of the current window by each key that can still sign in.
+    pub async fn rate_limits(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        api_limit_per_minute: u32,
+    ) -> AppResult<Vec<ApiKeyRateLimit>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "integrations", "read")
+            .await?;
+        self.fetch_rate_limits(tenant_id, api_limit_per_minute)
+            .await
+    }
+
+    async fn fetch_rate_limits(
+        &self,
+        tenant_id: &str,
+        api_limit_per_minute: u32,
+    ) -> AppResult<Vec<ApiKeyRateLimit>> {
+        let keys: Vec<(String, String, String)> = sqlx::query_as(
+            r#"
+            SELECT id, name, key_hash FROM tenant_api_keys
+            WHERE tenant_id = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > $2)
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(Utc::now())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let (limit, window_secs) = crate::http::middleware::api_rate_limit(api_limit_per_minute);
+        let mut out = Vec::with_capacity(keys.len());
+        for (id, name, hash) in keys {
+            let info = self
+                .rate_limiter
+                .peek(&api_key_rate_limit_key(&hash), limit, window_secs)
+                .await;
+            out.push(ApiKeyRateLimit {
+                api_key_id: id,
+                name,
+                limit,
+                used: limit - info.remaining,
+                remaining: info.remaining,
+                window_secs,
+                reset_in_secs: info.reset_in_secs,
+            });
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webhook_urls_must_leave_the_server() {
+        assert_eq!(
+            validate_url(" https://erp.example.com/hooks?x=1 ").unwrap(),
+            "https://erp.example.com/hooks?x=1"
+        );
+        assert!(validate_url("http://10.0.0.5:8080/in").is_ok());
+        for bad in [
+            "ftp://example.com/",
+            "not a url",
+            "http://localhost:3000/",
+            "http://127.0.0.1/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]/",
+            "http://0.0.0.0/",
+        ] {
+            assert!(validate_url(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn event_types_are_trimmed_and_deduplicated() {
+        assert_eq!(
+            validate_event_types(vec![
+                " support_ticket_updated ".to_string(),
+                "support_ticket_updated".to_string(),
+                "role.updated".to_string(),
+            ])
+            .unwrap(),
+            vec!["support_ticket_updated", "role.updated"]
+        );
+        assert!(validate_event_types(vec!["".to_string()]).is_err());
+        assert!(validate_event_types(vec!["has space".to_string()]).is_err());
+    }
+
+    #[test]
+    fn generated_keys_are_prefixed_and_hashed() {
+        let token = format!("{}{}", API_KEY_PREFIX, random_token(40));
+        assert_eq!(token.len(), API_KEY_PREFIX.len() + 40);
+        assert_ne!(random_token(40), random_token(40));
+        assert_eq!(api_key_hash(&token).len(), 64);
+        assert_ne!(api_key_hash(&token), token);
+    }
+}
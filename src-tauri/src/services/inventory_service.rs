@@ -0,0 +1,1511 @@
+//! Materials inventory: items, stock per warehouse/technician location and
+//! the movement ledger. Work orders consume materials from a technician's (or
+//! warehouse) stock; dropping below an item's threshold alerts the tenant's
+//! inventory managers. Devices such as ONUs and routers can also be tracked
+//! per unit by the serial number scanned from their label.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    ConsumeWorkOrderMaterialsRequest, CreateInventoryMovementRequest,
+    InstallInventorySerialRequest, InventoryItem, InventoryLocation, InventoryMovement,
+    InventoryScanResult, InventorySerial, InventorySerialBatchResult, InventoryStockLevel,
+    ReceiveInventorySerialsRequest, SkippedInventorySerial, TransferInventorySerialsRequest,
+    UpsertInventoryItemRequest, UpsertInventoryLocationRequest,
+};
+use crate::services::{AuditService, AuthService, NotificationService};
+use chrono::Utc;
+use std::collections::HashMap;
+use tracing::warn;
+use uuid::Uuid;
+
+#[cfg(feature = "postgres")]
+type Db = sqlx::Postgres;
+
+#[cfg(feature = "sqlite")]
+type Db = sqlx::Sqlite;
+
+const MAX_QUANTITY: f64 = 1_000_000_000.0;
+const MAX_SERIAL_LEN: usize = 64;
+const MAX_SERIAL_BATCH: usize = 500;
+
+const ITEM_SELECT: &str = r#"
+    SELECT
+        i.id, i.tenant_id, i.sku, i.name, i.unit,
+        i.low_stock_threshold::float8 AS low_stock_threshold, i.is_active,
+        COALESCE(s.total, 0)::float8 AS total_quantity,
+        (i.low_stock_threshold IS NOT NULL AND COALESCE(s.total, 0) < i.low_stock_threshold) AS is_low_stock,
+        i.created_at, i.updated_at
+    FROM inventory_items i
+    LEFT JOIN (
+        SELECT item_id, SUM(quantity) AS total FROM inventory_stock GROUP BY item_id
+    ) s ON s.item_id = i.id
+"#;
+
+const LOCATION_SELECT: &str = r#"
+    SELECT l.id, l.tenant_id, l.name, l.kind, l.technician_id, u.name AS technician_name,
+           l.is_active, l.created_at, l.updated_at
+    FROM inventory_locations l
+    LEFT JOIN users u ON u.id = l.technician_id
+"#;
+
+const MOVEMENT_SELECT: &str = r#"
+    SELECT
+        m.id, m.item_id, i.sku, i.name AS item_name, i.unit, m.kind,
+        m.from_location_id, lf.name AS from_location_name,
+        m.to_location_id, lt.name AS to_location_name,
+        m.quantity::float8 AS quantity, m.work_order_id, m.note,
+        m.created_by, u.name AS created_by_name, m.created_at
+    FROM inventory_movements m
+    LEFT JOIN inventory_items i ON i.id = m.item_id
+    LEFT JOIN inventory_locations lf ON lf.id = m.from_location_id
+    LEFT JOIN inventory_locations lt ON lt.id = m.to_location_id
+    LEFT JOIN users u ON u.id = m.created_by
+"#;
+
+const SERIAL_SELECT: &str = r#"
+    SELECT s.id, s.item_id, i.sku, i.name AS item_name, s.serial_number, s.status,
+           s.location_id, l.name AS location_name, s.work_order_id, s.created_at, s.updated_at
+    FROM inventory_serials s
+    JOIN inventory_items i ON i.id = s.item_id
+    LEFT JOIN inventory_locations l ON l.id = s.location_id
+"#;
+
+/// Positive quantity rounded to the column's three decimals.
+fn normalize_quantity(quantity: f64) -> AppResult<f64> {
+    let q = (quantity * 1000.0).round() / 1000.0;
+    if !q.is_finite() || q <= 0.0 || q > MAX_QUANTITY {
+        return Err(AppError::Validation(
+            "Quantity must be a positive number".to_string(),
+        ));
+    }
+    Ok(q)
+}
+
+/// Locations a manual movement of `kind` takes stock from and puts it into.
+fn movement_sides(
+    kind: &str,
+    from: Option<String>,
+    to: Option<String>,
+) -> AppResult<(Option<String>, Option<String>)> {
+    let from = from.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let to = to.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let ok = match kind {
+        "receive" => from.is_none() && to.is_some(),
+        "transfer" => from.is_some() && to.is_some() && from != to,
+        "adjust" => from.is_some() != to.is_some(),
+        _ => {
+            return Err(AppError::Validation(format!(
+                "Invalid movement kind '{}' (expected one of: receive, transfer, adjust)",
+                kind
+            )))
+        }
+    };
+    if !ok {
+        return Err(AppError::Validation(match kind {
+            "receive" => "A receipt needs a destination location only".to_string(),
+            "transfer" => "A transfer needs two different locations".to_string(),
+            _ => "An adjustment needs exactly one location".to_string(),
+        }));
+    }
+    Ok((from, to))
+}
+
+/// A scanned serial as stored. Scanners may add a trailing CR or tab, and
+/// GS1 labels carry group separators, so whitespace and control characters
+/// are dropped before the length and charset checks.
+fn normalize_serial(code: &str) -> AppResult<String> {
+    let serial: String = code
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_uppercase();
+    if serial.is_empty()
+        || serial.chars().count() > MAX_SERIAL_LEN
+        || !serial.chars().all(|c| c.is_ascii_graphic())
+    {
+        return Err(AppError::Validation(format!(
+            "Serial number must be 1-{} printable characters",
+            MAX_SERIAL_LEN
+        )));
+    }
+    Ok(serial)
+}
+
+/// Normalized serials of a scan batch, in scan order and without repeats;
+/// unreadable and repeated scans are reported as skipped.
+fn normalize_serial_batch(
+    codes: Vec<String>,
+) -> AppResult<(Vec<String>, Vec<SkippedInventorySerial>)> {
+    if codes.is_empty() {
+        return Err(AppError::Validation("No serial numbers given".to_string()));
+    }
+    if codes.len() > MAX_SERIAL_BATCH {
+        return Err(AppError::Validation(format!(
+            "At most {} serial numbers per batch",
+            MAX_SERIAL_BATCH
+        )));
+    }
+    let mut serials: Vec<String> = Vec::with_capacity(codes.len());
+    let mut skipped = Vec::new();
+    for code in codes {
+        match normalize_serial(&code) {
+            Ok(serial) if serials.contains(&serial) => skipped.push(SkippedInventorySerial {
+                serial_number: serial,
+                reason: "Scanned twice".to_string(),
+            }),
+            Ok(serial) => serials.push(serial),
+            Err(_) => skipped.push(SkippedInventorySerial {
+                serial_number: code.trim().to_string(),
+                reason: "Not a valid serial number".to_string(),
+            }),
+        }
+    }
+    Ok((serials, skipped))
+}
+
+/// True when stock went from at/above the threshold to below it.
+fn crossed_low_stock(before: f64, after: f64, threshold: Option<f64>) -> bool {
+    threshold.is_some_and(|t| before >= t && after < t)
+}
+
+#[derive(Clone)]
+pub struct InventoryService {
+    pool: DbPool,
+    auth_service: AuthService,
+    audit_service: AuditService,
+    notification_service: NotificationService,
+}
+
+impl InventoryService {
+    pub fn new(
+        pool: DbPool,
+        auth_service: AuthService,
+        audit_service: AuditService,
+        notification_service: NotificationService,
+    ) -> Self {
+        Self {
+            pool,
+            auth_service,
+            audit_service,
+            notification_service,
+        }
+    }
+
+    // ---- Items ----
+
+    pub async fn list_items(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        include_inactive: bool,
+    ) -> AppResult<Vec<InventoryItem>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "inventory", "read")
+            .await?;
+        let rows: Vec<InventoryItem> = sqlx::query_as(&format!(
+            "{ITEM_SELECT} WHERE i.tenant_id = $1 AND ($2 OR i.is_active) ORDER BY lower(i.name)"
+        ))
+        .bind(tenant_id)
+        .bind(include_inactive)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn get_item(&self, tenant_id: &str, id: &str) -> AppResult<InventoryItem> {
+        let row: Option<InventoryItem> = sqlx::query_as(&format!(
+            "{ITEM_SELECT} WHERE i.tenant_id = $1 AND i.id = $2"
+        ))
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.ok_or_else(|| AppError::NotFound("Inventory item not found".to_string()))
+    }
+
+    fn validate_item(
+        dto: UpsertInventoryItemRequest,
+    ) -> AppResult<(String, String, String, Option<f64>, bool)> {
+        let sku = dto.sku.trim().to_uppercase();
+        if sku.is_empty() || sku.chars().count() > 64 {
+            return Err(AppError::Validation(
+                "SKU is required (max 64 characters)".to_string(),
+            ));
+        }
+        let name = dto.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > 120 {
+            return Err(AppError::Validation(
+                "Item name is required (max 120 characters)".to_string(),
+            ));
+        }
+        let unit = dto
+            .unit
+            .map(|u| u.trim().to_lowercase())
+            .filter(|u| !u.is_empty())
+            .unwrap_or_else(|| "pcs".to_string());
+        if unit.chars().count() > 16 {
+            return Err(AppError::Validation(
+                "Unit is too long (max 16 characters)".to_string(),
+            ));
+        }
+        let threshold = match dto.low_stock_threshold {
+            Some(t) if !t.is_finite() || t < 0.0 => {
+                return Err(AppError::Validation(
+                    "Low-stock threshold must be zero or more".to_string(),
+                ))
+            }
+            other => other,
+        };
+        Ok((sku, name, unit, threshold, dto.is_active.unwrap_or(true)))
+    }
+
+    fn map_unique_violation(e: sqlx::Error, message: &str) -> AppError {
+        if e.as_database_error()
+            .and_then(|d| d.code().map(|c| c == "23505"))
+            .unwrap_or(false)
+        {
+            AppError::Conflict(message.to_string())
+        } else {
+            AppError::Database(e)
+        }
+    }
+
+    pub async fn create_item(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: UpsertInventoryItemRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<InventoryItem> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "inventory", "manage")
+            .await?;
+        let (sku, name, unit, threshold, is_active) = Self::validate_item(dto)?;
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        sqlx::query(
+            r#"
+            INSERT INTO inventory_items
+                (id, tenant_id, sku, name, unit, low_stock_threshold, is_active, created_at, updated_at)
+            VALUES ($1,$2,$3,$4,$5,$6::numeric,$7,$8,$8)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&sku)
+        .bind(&name)
+        .bind(&unit)
+        .bind(threshold)
+        .bind(is_active)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::map_unique_violation(e, "An item with this SKU already exists"))?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "INVENTORY_ITEM_CREATE",
+                "inventory_items",
+                Some(&id),
+                Some(&format!("Created inventory item {} ({})", name, sku)),
+                ip_address,
+            )
+            .await;
+
+        self.get_item(tenant_id, &id).await
+    }
+
+    pub async fn update_item(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        dto: UpsertInventoryItemRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<InventoryItem> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "inventory", "manage")
+            .await?;
+        self.get_item(tenant_id, id).await?;
+        let (sku, name, unit, threshold, is_active) = Self::validate_item(dto)?;
+
+        sqlx::query(
+            r#"
+            UPDATE inventory_items
+            SET sku = $1, name = $2, unit = $3, low_stock_threshold = $4::numeric,
+                is_active = $5, updated_at = $6
+            WHERE tenant_id = $7 AND id = $8
+            "#,
+        )
+        .bind(&sku)
+        .bind(&name)
+        .bind(&unit)
+        .bind(threshold)
+        .bind(is_active)
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::map_unique_violation(e, "An item with this SKU already exists"))?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "INVENTORY_ITEM_UPDATE",
+                "inventory_items",
+                Some(id),
+                Some(&format!("Updated inventory item {} ({})", name, sku)),
+                ip_address,
+            )
+            .await;
+
+        self.get_item(tenant_id, id).await
+    }
+
+    // ---- Locations ----
+
+    pub async fn list_locations(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+    ) -> AppResult<Vec<InventoryLocation>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "inventory", "read")
+            .await?;
+        let rows: Vec<InventoryLocation> = sqlx::query_as(&format!(
+            "{LOCATION_SELECT} WHERE l.tenant_id = $1 ORDER BY l.kind DESC, lower(l.name)"
+        ))
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn get_location(&self, tenant_id: &str, id: &str) -> AppResult<InventoryLocation> {
+        let row: Option<InventoryLocation> = sqlx::query_as(&format!(
+            "{LOCATION_SELECT} WHERE l.tenant_id = $1 AND l.id = $2"
+        ))
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.ok_or_else(|| AppError::NotFound("Inventory location not found".to_string()))
+    }
+
+    pub async fn create_location(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: UpsertInventoryLocationRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<InventoryLocation> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "inventory", "manage")
+            .await?;
+        let name = dto.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > 100 {
+            return Err(AppError::Validation(
+                "Location name is required (max 100 characters)".to_string(),
+            ));
+        }
+        let kind = dto
+            .kind
+            .map(|k| k.trim().to_lowercase())
+            .filter(|k| !k.is_empty())
+            .unwrap_or_else(|| "warehouse".to_string());
+        let technician_id = dto
+            .technician_id
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty());
+        match (kind.as_str(), technician_id.as_deref()) {
+            ("warehouse", None) => {}
+            ("technician", Some(user_id)) => {
+                let is_member: bool = sqlx::query_scalar(
+                    "SELECT EXISTS(SELECT 1 FROM tenant_members WHERE tenant_id = $1 AND user_id = $2)",
+                )
+                .bind(tenant_id)
+                .bind(user_id)
+                .fetch_one(&self.pool)
+                .await?;
+                if !is_member {
+                    return Err(AppError::Validation(
+                        "Technician is not a member of this tenant".to_string(),
+                    ));
+                }
+            }
+            ("technician", None) => {
+                return Err(AppError::Validation(
+                    "A technician location needs a technician".to_string(),
+                ))
+            }
+            ("warehouse", Some(_)) => {
+                return Err(AppError::Validation(
+                    "A warehouse can't have a technician".to_string(),
+                ))
+            }
+            _ => {
+                return Err(AppError::Validation(format!(
+                    "Invalid location kind '{}' (expected warehouse or technician)",
+                    kind
+                )))
+            }
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO inventory_locations
+                (id, tenant_id, name, kind, technician_id, is_active, created_at, updated_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$7)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&name)
+        .bind(&kind)
+        .bind(&technician_id)
+        .bind(dto.is_active.unwrap_or(true))
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            Self::map_unique_violation(
+                e,
+                "Location name is taken or the technician already has a location",
+            )
+        })?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "INVENTORY_LOCATION_CREATE",
+                "inventory_locations",
+                Some(&id),
+                Some(&format!("Created {} location {}", kind, name)),
+                ip_address,
+            )
+            .await;
+
+        self.get_location(tenant_id, &id).await
+    }
+
+    /// Rename or (de)activate a location. Kind and technician are fixed.
+    pub async fn update_location(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        dto: UpsertInventoryLocationRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<InventoryLocation> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "inventory", "manage")
+            .await?;
+        let current = self.get_location(tenant_id, id).await?;
+        let name = dto.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > 100 {
+            return Err(AppError::Validation(
+                "Location name is required (max 100 characters)".to_string(),
+            ));
+        }
+
+        sqlx::query(
+            "UPDATE inventory_locations SET name = $1, is_active = $2, updated_at = $3 WHERE tenant_id = $4 AND id = $5",
+        )
+        .bind(&name)
+        .bind(dto.is_active.unwrap_or(current.is_active))
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::map_unique_violation(e, "Location name is taken"))?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "INVENTORY_LOCATION_UPDATE",
+                "inventory_locations",
+                Some(id),
+                Some(&format!("Updated location {}", name)),
+                ip_address,
+            )
+            .await;
+
+        self.get_location(tenant_id, id).await
+    }
+
+    // ---- Stock & movements ----
+
+    /// Stock levels; technicians without `inventory:read` only see their own
+    /// location.
+    pub async fn list_stock(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        location_id: Option<String>,
+        item_id: Option<String>,
+    ) -> AppResult<Vec<InventoryStockLevel>> {
+        let location_id = if self
+            .auth_service
+            .has_permission(actor_id, tenant_id, "inventory", "read")
+            .await?
+        {
+            location_id
+        } else {
+            self.auth_service
+                .check_permission(actor_id, tenant_id, "work_orders", "manage")
+                .await?;
+            let own = self.technician_location_id(tenant_id, actor_id).await?;
+            if location_id.is_some() && location_id != own {
+                return Err(AppError::Forbidden(
+                    "Technician can only view own stock".to_string(),
+                ));
+            }
+            Some(own.ok_or_else(|| {
+                AppError::NotFound("No stock location is assigned to you".to_string())
+            })?)
+        };
+
+        let rows: Vec<InventoryStockLevel> = sqlx::query_as(
+            r#"
+            SELECT s.item_id, i.sku, i.name AS item_name, i.unit,
+                   s.location_id, l.name AS location_name, l.kind AS location_kind,
+                   s.quantity::float8 AS quantity, s.updated_at
+            FROM inventory_stock s
+            JOIN inventory_items i ON i.id = s.item_id
+            JOIN inventory_locations l ON l.id = s.location_id
+            WHERE s.tenant_id = $1
+              AND ($2::text IS NULL OR s.location_id = $2)
+              AND ($3::text IS NULL OR s.item_id = $3)
+              AND s.quantity > 0
+            ORDER BY lower(l.name), lower(i.name)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(location_id)
+        .bind(item_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn list_movements(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        item_id: Option<String>,
+        location_id: Option<String>,
+        limit: u32,
+    ) -> AppResult<Vec<InventoryMovement>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "inventory", "read")
+            .await?;
+        let rows: Vec<InventoryMovement> = sqlx::query_as(&format!(
+            r#"{MOVEMENT_SELECT}
+            WHERE m.tenant_id = $1
+              AND ($2::text IS NULL OR m.item_id = $2)
+              AND ($3::text IS NULL OR m.from_location_id = $3 OR m.to_location_id = $3)
+            ORDER BY m.created_at DESC
+            LIMIT $4"#
+        ))
+        .bind(tenant_id)
+        .bind(item_id)
+        .bind(location_id)
+        .bind(limit.clamp(1, 500) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Materials consumed by a work order. Access to the work order itself is
+    /// checked by the caller.
+    pub async fn list_work_order_materials(
+        &self,
+        tenant_id: &str,
+        work_order_id: &str,
+    ) -> AppResult<Vec<InventoryMovement>> {
+        let rows: Vec<InventoryMovement> = sqlx::query_as(&format!(
+            "{MOVEMENT_SELECT} WHERE m.tenant_id = $1 AND m.work_order_id = $2 ORDER BY m.created_at"
+        ))
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn create_movement(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: CreateInventoryMovementRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<InventoryMovement> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "inventory", "manage")
+            .await?;
+        let kind = dto.kind.trim().to_lowercase();
+        let (from, to) = movement_sides(&kind, dto.from_location_id, dto.to_location_id)?;
+        let quantity = normalize_quantity(dto.quantity)?;
+        let item = self.get_item(tenant_id, dto.item_id.trim()).await?;
+        for location_id in from.iter().chain(to.iter()) {
+            self.get_location(tenant_id, location_id).await?;
+        }
+        let note = dto
+            .note
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+
+        let mut tx = self.pool.begin().await?;
+        let id = self
+            .apply_movement(
+                &mut tx,
+                tenant_id,
+                actor_id,
+                &item,
+                &kind,
+                from.as_deref(),
+                to.as_deref(),
+                quantity,
+                None,
+                note.as_deref(),
+            )
+            .await?;
+        tx.commit().await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "INVENTORY_MOVEMENT",
+                "inventory_items",
+                Some(&item.id),
+                Some(&format!(
+                    "{} {} {} of {}",
+                    kind, quantity, item.unit, item.sku
+                )),
+                ip_address,
+            )
+            .await;
+
+        if from.is_some() && to.is_none() {
+            self.alert_low_stock(tenant_id, &HashMap::from([(item.id.clone(), quantity)]))
+                .await;
+        }
+
+        let row: InventoryMovement = sqlx::query_as(&format!(
+            "{MOVEMENT_SELECT} WHERE m.tenant_id = $1 AND m.id = $2"
+        ))
+        .bind(tenant_id)
+        .bind(&id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Take materials out of stock for a work order in one transaction; if any
+    /// line is short, nothing is consumed.
+    pub async fn consume_for_work_order(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        dto: ConsumeWorkOrderMaterialsRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<Vec<InventoryMovement>> {
+        if dto.items.is_empty() {
+            return Err(AppError::Validation("No materials given".to_string()));
+        }
+        self.ensure_work_order_open(tenant_id, work_order_id)
+            .await?;
+
+        let location_id = match dto
+            .location_id
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+        {
+            Some(id) => id,
+            None => self
+                .technician_location_id(tenant_id, actor_id)
+                .await?
+                .ok_or_else(|| {
+                    AppError::Validation(
+                        "No stock location is assigned to you; choose a location".to_string(),
+                    )
+                })?,
+        };
+        let location = self.get_location(tenant_id, &location_id).await?;
+        self.check_draw_from(actor_id, tenant_id, &location).await?;
+        let note = dto
+            .note
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+
+        let mut consumed: HashMap<String, f64> = HashMap::new();
+        let mut ids = Vec::with_capacity(dto.items.len());
+        let mut tx = self.pool.begin().await?;
+        for line in dto.items {
+            let quantity = normalize_quantity(line.quantity)?;
+            let item = self.get_item(tenant_id, line.item_id.trim()).await?;
+            if !item.is_active {
+                return Err(AppError::Validation(format!(
+                    "{} is no longer stocked",
+                    item.name
+                )));
+            }
+            ids.push(
+                self.apply_movement(
+                    &mut tx,
+                    tenant_id,
+                    actor_id,
+                    &item,
+                    "consume",
+                    Some(&location.id),
+                    None,
+                    quantity,
+                    Some(work_order_id),
+                    note.as_deref(),
+                )
+                .await?,
+            );
+            *consumed.entry(item.id.clone()).or_default() += quantity;
+        }
+        tx.commit().await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WORK_ORDER_MATERIALS",
+                "installation_work_orders",
+                Some(work_order_id),
+                Some(&format!(
+                    "Consumed {} material line(s) from {}",
+                    ids.len(),
+                    location.name
+                )),
+                ip_address,
+            )
+            .await;
+
+        self.alert_low_stock(tenant_id, &consumed).await;
+
+        let mut rows = self
+            .list_work_order_materials(tenant_id, work_order_id)
+            .await?;
+        rows.retain(|m| ids.contains(&m.id));
+        Ok(rows)
+    }
+
+    async fn ensure_work_order_open(&self, tenant_id: &str, work_order_id: &str) -> AppResult<()> {
+        let status: Option<String> = sqlx::query_scalar(
+            "SELECT status FROM installation_work_orders WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        match status.as_deref() {
+            None => Err(AppError::NotFound("Work order not found".to_string())),
+            Some("cancelled") => Err(AppError::Validation(
+                "Cannot record materials on a cancelled work order".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Technicians draw from their own stock; other locations need
+    /// inventory:manage.
+    async fn check_draw_from(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        location: &InventoryLocation,
+    ) -> AppResult<()> {
+        if location.technician_id.as_deref() != Some(actor_id) {
+            self.auth_service
+                .check_permission(actor_id, tenant_id, "inventory", "manage")
+                .await?;
+        }
+        Ok(())
+    }
+
+    // ---- Serial numbers ----
+
+    async fn find_serial(
+        &self,
+        tenant_id: &str,
+        serial_number: &str,
+    ) -> AppResult<Option<InventorySerial>> {
+        let row: Option<InventorySerial> = sqlx::query_as(&format!(
+            "{SERIAL_SELECT} WHERE s.tenant_id = $1 AND s.serial_number = $2"
+        ))
+        .bind(tenant_id)
+        .bind(serial_number)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    async fn serials_by_id(
+        &self,
+        tenant_id: &str,
+        ids: &[String],
+    ) -> AppResult<Vec<InventorySerial>> {
+        let mut rows = Vec::with_capacity(ids.len());
+        for id in ids {
+            let row: InventorySerial = sqlx::query_as(&format!(
+                "{SERIAL_SELECT} WHERE s.tenant_id = $1 AND s.id = $2"
+            ))
+            .bind(tenant_id)
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+
+    /// Look up a scanned barcode: first as a registered serial number, then as
+    /// an item SKU. Technicians without `inventory:read` may scan too.
+    pub async fn resolve_scan(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        code: &str,
+    ) -> AppResult<InventoryScanResult> {
+        if !self
+            .auth_service
+            .has_permission(actor_id, tenant_id, "inventory", "read")
+            .await?
+        {
+            self.auth_service
+                .check_permission(actor_id, tenant_id, "work_orders", "manage")
+                .await?;
+        }
+        let code = normalize_serial(code)?;
+
+        if let Some(serial) = self.find_serial(tenant_id, &code).await? {
+            return Ok(InventoryScanResult {
+                code,
+                matched: "serial".to_string(),
+                item: None,
+                serial: Some(serial),
+            });
+        }
+        let item: Option<InventoryItem> = sqlx::query_as(&format!(
+            "{ITEM_SELECT} WHERE i.tenant_id = $1 AND lower(i.sku) = lower($2)"
+        ))
+        .bind(tenant_id)
+        .bind(&code)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(InventoryScanResult {
+            code,
+            matched: if item.is_some() { "item" } else { "unknown" }.to_string(),
+            serial: None,
+            item,
+        })
+    }
+
+    pub async fn list_serials(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        item_id: Option<String>,
+        location_id: Option<String>,
+        status: Option<String>,
+    ) -> AppResult<Vec<InventorySerial>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "inventory", "read")
+            .await?;
+        let rows: Vec<InventorySerial> = sqlx::query_as(&format!(
+            r#"{SERIAL_SELECT}
+            WHERE s.tenant_id = $1
+              AND ($2::text IS NULL OR s.item_id = $2)
+              AND ($3::text IS NULL OR s.location_id = $3)
+              AND ($4::text IS NULL OR s.status = $4)
+            ORDER BY s.updated_at DESC
+            LIMIT 500"#
+        ))
+        .bind(tenant_id)
+        .bind(item_id)
+        .bind(location_id)
+        .bind(status)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Warehouse intake: register the scanned serials and receive that many
+    /// units into the location. Serials already on file are skipped.
+    pub async fn receive_serials(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: ReceiveInventorySerialsRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<InventorySerialBatchResult> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "inventory", "manage")
+            .await?;
+        let item = self.get_item(tenant_id, dto.item_id.trim()).await?;
+        if !item.is_active {
+            return Err(AppError::Validation(format!(
+                "{} is no longer stocked",
+                item.name
+            )));
+        }
+        let location = self.get_location(tenant_id, dto.location_id.trim()).await?;
+        let (serials, mut skipped) = normalize_serial_batch(dto.serials)?;
+        let note = dto
+            .note
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+
+        let now = Utc::now();
+        let mut ids = Vec::with_capacity(serials.len());
+        let mut tx = self.pool.begin().await?;
+        for serial in serials {
+            let id = Uuid::new_v4().to_string();
+            let res = sqlx::query(
+                r#"
+                INSERT INTO inventory_serials
+                    (id, tenant_id, item_id, serial_number, status, location_id, created_by,
+                     created_at, updated_at)
+                VALUES ($1,$2,$3,$4,'in_stock',$5,$6,$7,$7)
+                ON CONFLICT (tenant_id, serial_number) DO NOTHING
+                "#,
+            )
+            .bind(&id)
+            .bind(tenant_id)
+            .bind(&item.id)
+            .bind(&serial)
+            .bind(&location.id)
+            .bind(actor_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            if res.rows_affected() == 0 {
+                skipped.push(SkippedInventorySerial {
+                    serial_number: serial,
+                    reason: "Already registered".to_string(),
+                });
+            } else {
+                ids.push(id);
+            }
+        }
+        if ids.is_empty() {
+            return Ok(InventorySerialBatchResult {
+                serials: Vec::new(),
+                skipped,
+            });
+        }
+        self.apply_movement(
+            &mut tx,
+            tenant_id,
+            actor_id,
+            &item,
+            "receive",
+            None,
+            Some(&location.id),
+            ids.len() as f64,
+            None,
+            note.as_deref(),
+        )
+        .await?;
+        tx.commit().await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "INVENTORY_SERIALS_RECEIVE",
+                "inventory_items",
+                Some(&item.id),
+                Some(&format!(
+                    "Received {} serial-numbered {} into {}",
+                    ids.len(),
+                    item.sku,
+                    location.name
+                )),
+                ip_address,
+            )
+            .await;
+
+        Ok(InventorySerialBatchResult {
+            serials: self.serials_by_id(tenant_id, &ids).await?,
+            skipped,
+        })
+    }
+
+    /// Move scanned in-stock units to another location, with one transfer
+    /// movement per item and source location.
+    pub async fn transfer_serials(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: TransferInventorySerialsRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<InventorySerialBatchResult> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "inventory", "manage")
+            .await?;
+        let to = self
+            .get_location(tenant_id, dto.to_location_id.trim())
+            .await?;
+        let (serials, mut skipped) = normalize_serial_batch(dto.serials)?;
+        let note = dto
+            .note
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+
+        let mut groups: HashMap<(String, String), Vec<InventorySerial>> = HashMap::new();
+        for serial_number in serials {
+            let reason = match self.find_serial(tenant_id, &serial_number).await? {
+                None => "Not registered",
+                Some(s) if s.status != "in_stock" => "Already installed",
+                Some(s) if s.location_id.as_deref() == Some(to.id.as_str()) => {
+                    "Already at this location"
+                }
+                Some(InventorySerial {
+                    location_id: None, ..
+                }) => "No current location",
+                Some(s) => {
+                    let key = (s.item_id.clone(), s.location_id.clone().unwrap_or_default());
+                    groups.entry(key).or_default().push(s);
+                    continue;
+                }
+            };
+            skipped.push(SkippedInventorySerial {
+                serial_number,
+                reason: reason.to_string(),
+            });
+        }
+        if groups.is_empty() {
+            return Ok(InventorySerialBatchResult {
+                serials: Vec::new(),
+                skipped,
+            });
+        }
+
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        let mut tx = self.pool.begin().await?;
+        for ((item_id, from), units) in &groups {
+            let item = self.get_item(tenant_id, item_id).await?;
+            for unit in units {
+                let res = sqlx::query(
+                    r#"
+                    UPDATE inventory_serials SET location_id = $1, updated_at = $2
+                    WHERE tenant_id = $3 AND id = $4 AND status = 'in_stock' AND location_id = $5
+                    "#,
+                )
+                .bind(&to.id)
+                .bind(now)
+                .bind(tenant_id)
+                .bind(&unit.id)
+                .bind(from)
+                .execute(&mut *tx)
+                .await?;
+                if res.rows_affected() == 0 {
+                    return Err(AppError::Conflict(format!(
+                        "{} was moved or installed meanwhile; scan again",
+                        unit.serial_number
+                    )));
+                }
+                ids.push(unit.id.clone());
+            }
+            self.apply_movement(
+                &mut tx,
+                tenant_id,
+                actor_id,
+                &item,
+                "transfer",
+                Some(from),
+                Some(&to.id),
+                units.len() as f64,
+                None,
+                note.as_deref(),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "INVENTORY_SERIALS_TRANSFER",
+                "inventory_locations",
+                Some(&to.id),
+                Some(&format!(
+                    "Moved {} serial-numbered unit(s) to {}",
+                    ids.len(),
+                    to.name
+                )),
+                ip_address,
+            )
+            .await;
+
+        Ok(InventorySerialBatchResult {
+            serials: self.serials_by_id(tenant_id, &ids).await?,
+            skipped,
+        })
+    }
+
+    /// Install a scanned unit on a work order: consume one unit of its item
+    /// from the location it is kept at and mark it installed. Access to the
+    /// work order itself is checked by the caller.
+    pub async fn install_serial(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        work_order_id: &str,
+        dto: InstallInventorySerialRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<InventorySerial> {
+        self.ensure_work_order_open(tenant_id, work_order_id)
+            .await?;
+        let serial_number = normalize_serial(&dto.serial_number)?;
+        let serial = self
+            .find_serial(tenant_id, &serial_number)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Serial number {} is not registered", serial_number))
+            })?;
+        if serial.status != "in_stock" {
+            return Err(AppError::Conflict(format!(
+                "{} is already installed",
+                serial_number
+            )));
+        }
+        let location_id = serial.location_id.clone().ok_or_else(|| {
+            AppError::Validation(format!("{} has no stock location", serial_number))
+        })?;
+        let location = self.get_location(tenant_id, &location_id).await?;
+        self.check_draw_from(actor_id, tenant_id, &location).await?;
+        let item = self.get_item(tenant_id, &serial.item_id).await?;
+        let note = match dto.note.map(|n| n.trim().to_string()) {
+            Some(n) if !n.is_empty() => format!("S/N {}: {}", serial_number, n),
+            _ => format!("S/N {}", serial_number),
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query(
+            r#"
+            UPDATE inventory_serials
+            SET status = 'installed', location_id = NULL, work_order_id = $1, updated_at = $2
+            WHERE tenant_id = $3 AND id = $4 AND status = 'in_stock'
+            "#,
+        )
+        .bind(work_order_id)
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .bind(&serial.id)
+        .execute(&mut *tx)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::Conflict(format!(
+                "{} is already installed",
+                serial_number
+            )));
+        }
+        self.apply_movement(
+            &mut tx,
+            tenant_id,
+            actor_id,
+            &item,
+            "consume",
+            Some(&location.id),
+            None,
+            1.0,
+            Some(work_order_id),
+            Some(&note),
+        )
+        .await?;
+        tx.commit().await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "WORK_ORDER_SERIAL_INSTALL",
+                "installation_work_orders",
+                Some(work_order_id),
+                Some(&format!(
+                    "Installed {} S/N {} from {}",
+                    item.sku, serial_number, location.name
+                )),
+                ip_address,
+            )
+            .await;
+
+        self.alert_low_stock(tenant_id, &HashMap::from([(item.id.clone(), 1.0)]))
+            .await;
+
+        self.serials_by_id(tenant_id, &[serial.id])
+            .await?
+            .pop()
+            .ok_or_else(|| AppError::NotFound("Serial number not found".to_string()))
+    }
+
+    async fn technician_location_id(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+    ) -> AppResult<Option<String>> {
+        let id: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM inventory_locations WHERE tenant_id = $1 AND technician_id = $2 AND is_active",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// Move stock and write the ledger row. Taking more than the location
+    /// holds is rejected.
+    #[allow(clippy::too_many_arguments)]
+    async fn apply_movement(
+        &self,
+        tx: &mut sqlx::Transaction<'_, Db>,
+        tenant_id: &str,
+        actor_id: &str,
+        item: &InventoryItem,
+        kind: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        quantity: f64,
+        work_order_id: Option<&str>,
+        note: Option<&str>,
+    ) -> AppResult<String> {
+        let now = Utc::now();
+        if let Some(from) = from {
+            let res = sqlx::query(
+                r#"
+                UPDATE inventory_stock
+                SET quantity = quantity - $1::numeric, updated_at = $2
+                WHERE item_id = $3 AND location_id = $4 AND quantity >= $1::numeric
+                "#,
+            )
+            .bind(quantity)
+            .bind(now)
+            .bind(&item.id)
+            .bind(from)
+            .execute(&mut **tx)
+            .await?;
+            if res.rows_affected() == 0 {
+                return Err(AppError::Validation(format!(
+                    "Not enough {} in stock at the selected location",
+                    item.name
+                )));
+            }
+        }
+        if let Some(to) = to {
+            sqlx::query(
+                r#"
+                INSERT INTO inventory_stock (tenant_id, item_id, location_id, quantity, updated_at)
+                VALUES ($1, $2, $3, $4::numeric, $5)
+                ON CONFLICT (item_id, location_id)
+                DO UPDATE SET quantity = inventory_stock.quantity + EXCLUDED.quantity,
+                              updated_at = EXCLUDED.updated_at
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(&item.id)
+            .bind(to)
+            .bind(quantity)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        let id = Uuid::new_v4().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO inventory_movements
+                (id, tenant_id, item_id, kind, from_location_id, to_location_id, quantity,
+                 work_order_id, note, created_by, created_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7::numeric,$8,$9,$10,$11)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&item.id)
+        .bind(kind)
+        .bind(from)
+        .bind(to)
+        .bind(quantity)
+        .bind(work_order_id)
+        .bind(note)
+        .bind(actor_id)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+        Ok(id)
+    }
+
+    /// Notify inventory managers about items whose total stock just fell below
+    /// the threshold. `taken` is how much of each item left stock. Best effort.
+    async fn alert_low_stock(&self, tenant_id: &str, taken: &HashMap<String, f64>) {
+        let mut crossed = Vec::new();
+        for (item_id, qty) in taken {
+            match self.get_item(tenant_id, item_id).await {
+                Ok(item)
+                    if crossed_low_stock(
+                        item.total_quantity + qty,
+                        item.total_quantity,
+                        item.low_stock_threshold,
+                    ) =>
+                {
+                    crossed.push(item)
+                }
+                Ok(_) => {}
+                Err(err) => warn!(
+                    "low-stock check failed: tenant_id={}, item_id={}, error={}",
+                    tenant_id, item_id, err
+                ),
+            }
+        }
+        if crossed.is_empty() {
+            return;
+        }
+
+        let recipients: Vec<String> = match sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT tm.user_id
+            FROM tenant_members tm
+            JOIN roles r ON r.id = tm.role_id
+            LEFT JOIN role_permissions rp ON rp.role_id = r.id
+            WHERE tm.tenant_id = $1
+              AND (r.name = 'Owner' OR rp.permission_id = 'inventory:manage')
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(ids) => ids,
+            Err(err) => {
+                warn!(
+                    "failed to load low-stock recipients: tenant_id={}, error={}",
+                    tenant_id, err
+                );
+                return;
+            }
+        };
+
+        for item in crossed {
+            let message = format!(
+                "{} ({}) is down to {} {} (threshold {}).",
+                item.name,
+                item.sku,
+                item.total_quantity,
+                item.unit,
+                item.low_stock_threshold.unwrap_or_default()
+            );
+            for user_id in &recipients {
+                if let Err(err) = self
+                    .notification_service
+                    .create_notification(
+                        user_id.clone(),
+                        Some(tenant_id.to_string()),
+                        "Low Stock".to_string(),
+                        message.clone(),
+                        "warning".to_string(),
+                        "operations".to_string(),
+                        Some("/admin/inventory".to_string()),
+                    )
+                    .await
+                {
+                    warn!(
+                        "failed to send low-stock notification: tenant_id={}, user_id={}, error={}",
+                        tenant_id, user_id, err
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        crossed_low_stock, movement_sides, normalize_quantity, normalize_serial,
+        normalize_serial_batch,
+    };
+
+    #[test]
+    fn quantities_are_positive_and_rounded() {
+        assert_eq!(normalize_quantity(12.34567).unwrap(), 12.346);
+        assert!(normalize_quantity(0.0).is_err());
+        assert!(normalize_quantity(-1.0).is_err());
+        assert!(normalize_quantity(0.0001).is_err());
+        assert!(normalize_quantity(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn movement_kinds_need_matching_locations() {
+        let s = |v: &str| Some(v.to_string());
+        assert_eq!(
+            movement_sides("receive", None, s("wh")).unwrap(),
+            (None, s("wh"))
+        );
+        assert!(movement_sides("receive", s("wh"), s("van")).is_err());
+        assert!(movement_sides("transfer", s("wh"), s("van")).is_ok());
+        assert!(movement_sides("transfer", s("wh"), s("wh")).is_err());
+        assert!(movement_sides("adjust", s("wh"), None).is_ok());
+        assert!(movement_sides("adjust", s(" "), None).is_err());
+        assert!(movement_sides("consume", s("wh"), None).is_err());
+    }
+
+    #[test]
+    fn low_stock_alert_fires_once_on_crossing() {
+        assert!(crossed_low_stock(12.0, 8.0, Some(10.0)));
+        assert!(crossed_low_stock(10.0, 9.5, Some(10.0)));
+        assert!(!crossed_low_stock(9.0, 7.0, Some(10.0)));
+        assert!(!crossed_low_stock(20.0, 15.0, Some(10.0)));
+        assert!(!crossed_low_stock(5.0, 0.0, None));
+    }
+    #[test]
+    fn scanned_serials_are_normalized() {
+        assert_eq!(
+            normalize_serial(" zteg1234abcd\r\n").unwrap(),
+            "ZTEG1234ABCD"
+        );
+        assert_eq!(
+            normalize_serial("48575443\u{1d}ABCD").unwrap(),
+            "48575443ABCD"
+        );
+        assert_eq!(normalize_serial("AA:BB:CC 00").unwrap(), "AA:BB:CC00");
+        assert!(normalize_serial(" \t").is_err());
+        assert!(normalize_serial("ÜNICODE").is_err());
+        assert!(normalize_serial(&"X".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn serial_batches_skip_repeats_and_bad_scans() {
+        let codes = ["sn1", "SN2", " sn1 ", "", "SN3"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let (serials, skipped) = normalize_serial_batch(codes).unwrap();
+        assert_eq!(serials, vec!["SN1", "SN2", "SN3"]);
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(skipped[0].serial_number, "SN1");
+        assert_eq!(skipped[0].reason, "Scanned twice");
+        assert!(normalize_serial_batch(Vec::new()).is_err());
+    }
+}
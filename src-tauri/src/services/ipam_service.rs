@@ -0,0 +1,792 @@
+//! IP address plan: IPv4 subnets tagged with VLAN, purpose and router, and
+//! a planner view of how full each one is. The hierarchy comes from CIDR
+//! containment, so adding a supernet later regroups the existing subnets
+//! under it. Assigned addresses are the PPPoE accounts' remote addresses and
+//! provisioned static IPs; router IP pools count as pooled space.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{IpamHeatCell, IpamPlan, IpamSubnet, IpamSubnetNode, UpsertIpamSubnetRequest};
+use crate::services::{AuditService, AuthService};
+use chrono::Utc;
+use std::fmt;
+use std::net::Ipv4Addr;
+use uuid::Uuid;
+
+const PURPOSES: &[&str] = &[
+    "customer",
+    "pppoe",
+    "static",
+    "infrastructure",
+    "management",
+    "transit",
+    "reserved",
+];
+const MAX_TAGS: usize = 16;
+const MAX_TAG_LEN: usize = 32;
+/// A heatmap splits a subnet this many prefix bits deeper (256 cells).
+const HEATMAP_BITS: u8 = 8;
+const MAX_FREE_BLOCKS: usize = 16;
+
+const SUBNET_SELECT: &str = r#"
+    SELECT s.id, s.tenant_id, s.cidr, s.name, s.vlan_id, s.purpose, s.tags,
+           s.router_id, r.name AS router_name, s.notes, s.created_at, s.updated_at
+    FROM ipam_subnets s
+    LEFT JOIN mikrotik_routers r ON r.id = s.router_id
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Cidr {
+    network: u32,
+    prefix: u8,
+}
+
+impl Cidr {
+    fn mask(prefix: u8) -> u32 {
+        if prefix == 0 {
+            0
+        } else {
+            u32::MAX << (32 - prefix)
+        }
+    }
+
+    fn size(&self) -> u64 {
+        1u64 << (32 - self.prefix)
+    }
+
+    fn last(&self) -> u32 {
+        self.network | !Self::mask(self.prefix)
+    }
+
+    fn usable(&self) -> u64 {
+        match self.prefix {
+            32 => 1,
+            31 => 2,
+            _ => self.size() - 2,
+        }
+    }
+
+    fn contains_addr(&self, addr: u32) -> bool {
+        addr & Self::mask(self.prefix) == self.network
+    }
+
+    fn contains(&self, other: &Cidr) -> bool {
+        other.prefix >= self.prefix && self.contains_addr(other.network)
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", Ipv4Addr::from(self.network), self.prefix)
+    }
+}
+
+/// Parses `a.b.c.d/len`; the address must be the network address.
+fn parse_cidr(raw: &str) -> AppResult<Cidr> {
+    let raw = raw.trim();
+    let Some((addr, prefix)) = raw.split_once('/') else {
+        return Err(AppError::Validation(
+            "Subnet must be in CIDR notation, e.g. 10.20.0.0/22".to_string(),
+        ));
+    };
+    let addr: Ipv4Addr = addr
+        .trim()
+        .parse()
+        .map_err(|_| AppError::Validation(format!("'{}' is not an IPv4 subnet", raw)))?;
+    let prefix: u8 = match prefix.trim().parse() {
+        Ok(p) if p <= 32 => p,
+        _ => {
+            return Err(AppError::Validation(
+                "Prefix length must be 0-32".to_string(),
+            ))
+        }
+    };
+    let addr = u32::from(addr);
+    let cidr = Cidr {
+        network: addr & Cidr::mask(prefix),
+        prefix,
+    };
+    if cidr.network != addr {
+        return Err(AppError::Validation(format!(
+            "{} has host bits set; did you mean {}?",
+            raw, cidr
+        )));
+    }
+    Ok(cidr)
+}
+
+/// A single address as stored on accounts and static IP components, which
+/// may carry a `/32` suffix.
+fn parse_address(raw: &str) -> Option<u32> {
+    let raw = raw.trim();
+    let raw = raw.strip_suffix("/32").unwrap_or(raw);
+    raw.parse::<Ipv4Addr>().ok().map(u32::from)
+}
+
+/// Address ranges of a RouterOS pool: `a-b`, CIDR and single addresses,
+/// comma separated. Unreadable parts are skipped.
+fn parse_pool_ranges(raw: &str) -> Vec<(u32, u32)> {
+    raw.split(',')
+        .filter_map(|part| {
+            let part = part.trim();
+            if let Some((from, to)) = part.split_once('-') {
+                let (from, to) = (parse_address(from)?, parse_address(to)?);
+                (from <= to).then_some((from, to))
+            } else if part.contains('/') {
+                let (addr, prefix) = part.split_once('/')?;
+                let prefix: u8 = prefix.trim().parse().ok().filter(|p| *p <= 32)?;
+                let addr = u32::from(addr.trim().parse::<Ipv4Addr>().ok()?);
+                let cidr = Cidr {
+                    network: addr & Cidr::mask(prefix),
+                    prefix,
+                };
+                Some((cidr.network, cidr.last()))
+            } else {
+                parse_address(part).map(|a| (a, a))
+            }
+        })
+        .collect()
+}
+
+/// Sorted, non-overlapping ranges.
+fn merge_ranges(mut ranges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+    for (from, to) in ranges {
+        match merged.last_mut() {
+            Some(last) if u64::from(from) <= u64::from(last.1) + 1 => last.1 = last.1.max(to),
+            _ => merged.push((from, to)),
+        }
+    }
+    merged
+}
+
+/// Addresses of `cidr` inside the merged `ranges`.
+fn overlap(cidr: &Cidr, ranges: &[(u32, u32)]) -> u64 {
+    let (first, last) = (cidr.network, cidr.last());
+    ranges
+        .iter()
+        .filter(|(from, to)| *from <= last && *to >= first)
+        .map(|(from, to)| u64::from((*to).min(last)) - u64::from((*from).max(first)) + 1)
+        .sum()
+}
+
+/// Entries of the sorted `addrs` inside `cidr`.
+fn count_in(cidr: &Cidr, addrs: &[u32]) -> u64 {
+    let start = addrs.partition_point(|a| *a < cidr.network);
+    let end = addrs.partition_point(|a| *a <= cidr.last());
+    (end - start) as u64
+}
+
+/// The fewest CIDR blocks exactly covering `first..=last`.
+fn range_blocks(first: u64, last: u64) -> Vec<Cidr> {
+    let mut blocks = Vec::new();
+    let mut start = first;
+    while start <= last {
+        let mut prefix = 32 - start.trailing_zeros().min(32) as u8;
+        while (1u64 << (32 - prefix)) > last - start + 1 {
+            prefix += 1;
+        }
+        blocks.push(Cidr {
+            network: start as u32,
+            prefix,
+        });
+        start += 1u64 << (32 - prefix);
+    }
+    blocks
+}
+
+fn pct(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    (part as f64 * 1000.0 / whole as f64).round() / 10.0
+}
+
+/// Builds the subnet tree. Blocks of a CIDR plan are either disjoint or
+/// nested, so sorting by network and then prefix puts every subnet right
+/// after its ancestors.
+struct Planner<'a> {
+    cidrs: Vec<Cidr>,
+    subnets: Vec<Option<IpamSubnet>>,
+    children: Vec<Vec<usize>>,
+    assigned: &'a [u32],
+    pools: &'a [(u32, u32)],
+}
+
+impl<'a> Planner<'a> {
+    fn new(subnets: Vec<IpamSubnet>, assigned: &'a [u32], pools: &'a [(u32, u32)]) -> Self {
+        let mut parsed: Vec<(Cidr, IpamSubnet)> = subnets
+            .into_iter()
+            .filter_map(|s| parse_cidr(&s.cidr).ok().map(|c| (c, s)))
+            .collect();
+        parsed.sort_by_key(|(c, _)| *c);
+        let (cidrs, subnets): (Vec<Cidr>, Vec<IpamSubnet>) = parsed.into_iter().unzip();
+        Self {
+            children: vec![Vec::new(); cidrs.len()],
+            cidrs,
+            subnets: subnets.into_iter().map(Some).collect(),
+            assigned,
+            pools,
+        }
+    }
+
+    fn build(mut self) -> IpamPlan {
+        let mut roots = Vec::new();
+        let mut stack: Vec<usize> = Vec::new();
+        for i in 0..self.cidrs.len() {
+            while stack
+                .last()
+                .is_some_and(|top| !self.cidrs[*top].contains(&self.cidrs[i]))
+            {
+                stack.pop();
+            }
+            match stack.last() {
+                Some(parent) => self.children[*parent].push(i),
+                None => roots.push(i),
+            }
+            stack.push(i);
+        }
+
+        let planned: u64 = roots
+            .iter()
+            .map(|i| count_in(&self.cidrs[*i], self.assigned))
+            .sum();
+        let subnets = roots.into_iter().map(|i| self.node(i, None, 0)).collect();
+        IpamPlan {
+            subnets,
+            unplanned_assigned: self.assigned.len() as u64 - planned,
+            generated_at: Utc::now(),
+        }
+    }
+
+    fn node(&mut self, i: usize, parent_id: Option<String>, depth: u32) -> IpamSubnetNode {
+        let cidr = self.cidrs[i];
+        let subnet = self.subnets[i].take().expect("each subnet is visited once");
+        let children: Vec<(Cidr, String)> = self.children[i]
+            .iter()
+            .filter_map(|c| Some((self.cidrs[*c], self.subnets[*c].as_ref()?.id.clone())))
+            .collect();
+
+        let assigned = count_in(&cidr, self.assigned);
+        let allocated: u64 = children.iter().map(|(c, _)| c.size()).sum();
+
+        let mut free_blocks = Vec::new();
+        let mut next = u64::from(cidr.network);
+        for (child, _) in &children {
+            if u64::from(child.network) > next {
+                free_blocks.extend(range_blocks(next, u64::from(child.network) - 1));
+            }
+            next = u64::from(child.last()) + 1;
+        }
+        if next <= u64::from(cidr.last()) {
+            free_blocks.extend(range_blocks(next, u64::from(cidr.last())));
+        }
+        free_blocks.sort_by_key(|b| (b.prefix, b.network));
+        free_blocks.truncate(MAX_FREE_BLOCKS);
+
+        let cell_prefix = (cidr.prefix + HEATMAP_BITS).min(32);
+        let cells = (0..1u64 << (cell_prefix - cidr.prefix))
+            .map(|n| {
+                let cell = Cidr {
+                    network: (u64::from(cidr.network) + (n << (32 - cell_prefix))) as u32,
+                    prefix: cell_prefix,
+                };
+                let used = count_in(&cell, self.assigned);
+                IpamHeatCell {
+                    cidr: cell.to_string(),
+                    size: cell.size(),
+                    assigned: used,
+                    utilization_pct: pct(used, cell.size()),
+                    subnet_id: children
+                        .iter()
+                        .find(|(c, _)| c.contains(&cell))
+                        .map(|(_, id)| id.clone()),
+                }
+            })
+            .collect();
+
+        let child_nodes = self.children[i]
+            .clone()
+            .into_iter()
+            .map(|c| self.node(c, Some(subnet.id.clone()), depth + 1))
+            .collect();
+
+        IpamSubnetNode {
+            parent_id,
+            depth,
+            first_address: Ipv4Addr::from(cidr.network).to_string(),
+            last_address: Ipv4Addr::from(cidr.last()).to_string(),
+            size: cidr.size(),
+            usable: cidr.usable(),
+            assigned,
+            pooled: overlap(&cidr, self.pools),
+            allocated,
+            utilization_pct: pct(assigned, cidr.usable()),
+            allocation_pct: pct(allocated, cidr.size()),
+            free_blocks: free_blocks.iter().map(|b| b.to_string()).collect(),
+            cells,
+            children: child_nodes,
+            subnet,
+        }
+    }
+}
+
+struct ValidSubnet {
+    cidr: String,
+    name: String,
+    vlan_id: Option<i32>,
+    purpose: Option<String>,
+    tags: Vec<String>,
+    router_id: Option<String>,
+    notes: Option<String>,
+}
+
+fn validate_subnet(dto: UpsertIpamSubnetRequest) -> AppResult<ValidSubnet> {
+    let cidr = parse_cidr(&dto.cidr)?.to_string();
+    let name = dto.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > 120 {
+        return Err(AppError::Validation(
+            "Subnet name is required (max 120 characters)".to_string(),
+        ));
+    }
+    if dto.vlan_id.is_some_and(|v| !(1..=4094).contains(&v)) {
+        return Err(AppError::Validation("VLAN ID must be 1-4094".to_string()));
+    }
+    let purpose = dto
+        .purpose
+        .map(|p| p.trim().to_lowercase())
+        .filter(|p| !p.is_empty());
+    if let Some(p) = purpose.as_deref() {
+        if !PURPOSES.contains(&p) {
+            return Err(AppError::Validation(format!(
+                "Invalid purpose '{}' (expected one of: {})",
+                p,
+                PURPOSES.join(", ")
+            )));
+        }
+    }
+    let mut tags: Vec<String> = Vec::new();
+    for tag in dto.tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || tags.contains(&tag) {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_LEN {
+            return Err(AppError::Validation(format!(
+                "Tags are at most {} characters",
+                MAX_TAG_LEN
+            )));
+        }
+        tags.push(tag);
+    }
+    if tags.len() > MAX_TAGS {
+        return Err(AppError::Validation(format!(
+            "At most {} tags per subnet",
+            MAX_TAGS
+        )));
+    }
+    Ok(ValidSubnet {
+        cidr,
+        name,
+        vlan_id: dto.vlan_id,
+        purpose,
+        tags,
+        router_id: dto
+            .router_id
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty()),
+        notes: dto
+            .notes
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty()),
+    })
+}
+
+#[derive(Clone)]
+pub struct IpamService {
+    pool: DbPool,
+    auth_service: AuthService,
+    audit_service: AuditService,
+}
+
+impl IpamService {
+    pub fn new(pool: DbPool, auth_service: AuthService, audit_service: AuditService) -> Self {
+        Self {
+            pool,
+            auth_service,
+            audit_service,
+        }
+    }
+
+    pub async fn list_subnets(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+    ) -> AppResult<Vec<IpamSubnet>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "ipam", "read")
+            .await?;
+        self.fetch_subnets(tenant_id).await
+    }
+
+    async fn fetch_subnets(&self, tenant_id: &str) -> AppResult<Vec<IpamSubnet>> {
+        let rows: Vec<IpamSubnet> = sqlx::query_as(&format!(
+            "{SUBNET_SELECT} WHERE s.tenant_id = $1 ORDER BY s.cidr::inet"
+        ))
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn get_subnet(&self, tenant_id: &str, id: &str) -> AppResult<IpamSubnet> {
+        let row: Option<IpamSubnet> = sqlx::query_as(&format!(
+            "{SUBNET_SELECT} WHERE s.tenant_id = $1 AND s.id = $2"
+        ))
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.ok_or_else(|| AppError::NotFound("Subnet not found".to_string()))
+    }
+
+    async fn ensure_router(&self, tenant_id: &str, router_id: Option<&str>) -> AppResult<()> {
+        let Some(router_id) = router_id else {
+            return Ok(());
+        };
+        let exists: Option<i32> =
+            sqlx::query_scalar("SELECT 1 FROM mikrotik_routers WHERE tenant_id = $1 AND id = $2")
+                .bind(tenant_id)
+                .bind(router_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        if exists.is_none() {
+            return Err(AppError::Validation("Router not found".to_string()));
+        }
+        Ok(())
+    }
+
+    fn map_unique_violation(e: sqlx::Error, cidr: &str) -> AppError {
+        if e.as_database_error()
+            .and_then(|d| d.code().map(|c| c == "23505"))
+            .unwrap_or(false)
+        {
+            AppError::Conflict(format!("{} is already in the plan", cidr))
+        } else {
+            AppError::Database(e)
+        }
+    }
+
+    pub async fn create_subnet(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: UpsertIpamSubnetRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<IpamSubnet> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "ipam", "manage")
+            .await?;
+        let v = validate_subnet(dto)?;
+        self.ensure_router(tenant_id, v.router_id.as_deref())
+            .await?;
+        let id = Uuid::new_v4().to_string();
+
+        sqlx::query(
+            r#"
+            INSERT INTO ipam_subnets
+                (id, tenant_id, cidr, name, vlan_id, purpose, tags, router_id, notes,
+                 created_at, updated_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$10)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&v.cidr)
+        .bind(&v.name)
+        .bind(v.vlan_id)
+        .bind(&v.purpose)
+        .bind(&v.tags)
+        .bind(&v.router_id)
+        .bind(&v.notes)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::map_unique_violation(e, &v.cidr))?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "IPAM_SUBNET_CREATE",
+                "ipam_subnets",
+                Some(&id),
+                Some(&format!("Added subnet {} ({})", v.cidr, v.name)),
+                ip_address,
+            )
+            .await;
+
+        self.get_subnet(tenant_id, &id).await
+    }
+
+    pub async fn update_subnet(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        dto: UpsertIpamSubnetRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<IpamSubnet> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "ipam", "manage")
+            .await?;
+        let current = self.get_subnet(tenant_id, id).await?;
+        let v = validate_subnet(dto)?;
+        self.ensure_router(tenant_id, v.router_id.as_deref())
+            .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE ipam_subnets
+            SET cidr = $1, name = $2, vlan_id = $3, purpose = $4, tags = $5,
+                router_id = $6, notes = $7, updated_at = $8
+            WHERE tenant_id = $9 AND id = $10
+            "#,
+        )
+        .bind(&v.cidr)
+        .bind(&v.name)
+        .bind(v.vlan_id)
+        .bind(&v.purpose)
+        .bind(&v.tags)
+        .bind(&v.router_id)
+        .bind(&v.notes)
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::map_unique_violation(e, &v.cidr))?;
+
+        let details = if current.cidr == v.cidr {
+            format!("Updated subnet {} ({})", v.cidr, v.name)
+        } else {
+            format!("Updated subnet {} -> {} ({})", current.cidr, v.cidr, v.name)
+        };
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "IPAM_SUBNET_UPDATE",
+                "ipam_subnets",
+                Some(id),
+                Some(&details),
+                ip_address,
+            )
+            .await;
+
+        self.get_subnet(tenant_id, id).await
+    }
+
+    /// Child subnets stay in the plan and move up to the next containing subnet.
+    pub async fn delete_subnet(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        ip_address: Option<&str>,
+    ) -> AppResult<()> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "ipam", "manage")
+            .await?;
+        let current = self.get_subnet(tenant_id, id).await?;
+        sqlx::query("DELETE FROM ipam_subnets WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "IPAM_SUBNET_DELETE",
+                "ipam_subnets",
+                Some(id),
+                Some(&format!(
+                    "Removed subnet {} ({})",
+                    current.cidr, current.name
+                )),
+                ip_address,
+            )
+            .await;
+        Ok(())
+    }
+
+    /// The subnet tree with utilization and heatmap cells for the planner.
+    pub async fn plan(&self, actor_id: &str, tenant_id: &str) -> AppResult<IpamPlan> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "ipam", "read")
+            .await?;
+        let subnets = self.fetch_subnets(tenant_id).await?;
+
+        let addresses: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT remote_address FROM pppoe_accounts
+            WHERE tenant_id = $1 AND remote_address IS NOT NULL
+            UNION ALL
+            SELECT detail FROM customer_subscription_components
+            WHERE tenant_id = $1
+              AND service_type = 'static_ip'
+              AND status = 'provisioned'
+              AND detail IS NOT NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut assigned: Vec<u32> = addresses.iter().filter_map(|a| parse_address(a)).collect();
+        assigned.sort_unstable();
+        assigned.dedup();
+
+        let pool_ranges: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT ranges FROM mikrotik_ip_pools
+            WHERE tenant_id = $1 AND router_present AND ranges IS NOT NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let pools = merge_ranges(
+            pool_ranges
+                .iter()
+                .flat_map(|r| parse_pool_ranges(r))
+                .collect(),
+        );
+
+        Ok(Planner::new(subnets, &assigned, &pools).build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        merge_ranges, parse_address, parse_cidr, parse_pool_ranges, range_blocks, Planner,
+    };
+    use crate::models::IpamSubnet;
+    use chrono::Utc;
+
+    fn subnet(id: &str, cidr: &str) -> IpamSubnet {
+        IpamSubnet {
+            id: id.to_string(),
+            tenant_id: "t1".to_string(),
+            cidr: cidr.to_string(),
+            name: id.to_string(),
+            vlan_id: None,
+            purpose: None,
+            tags: Vec::new(),
+            router_id: None,
+            router_name: None,
+            notes: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn addr(raw: &str) -> u32 {
+        parse_address(raw).unwrap()
+    }
+
+    #[test]
+    fn subnets_must_be_network_addresses() {
+        assert_eq!(
+            parse_cidr(" 10.20.0.0/22 ").unwrap().to_string(),
+            "10.20.0.0/22"
+        );
+        assert_eq!(parse_cidr("0.0.0.0/0").unwrap().to_string(), "0.0.0.0/0");
+        assert_eq!(
+            parse_cidr("10.0.0.7/32").unwrap().to_string(),
+            "10.0.0.7/32"
+        );
+        assert!(parse_cidr("10.20.1.0/22").is_err());
+        assert!(parse_cidr("10.20.0.0").is_err());
+        assert!(parse_cidr("10.20.0.0/33").is_err());
+        assert!(parse_cidr("2001:db8::/32").is_err());
+    }
+
+    #[test]
+    fn pool_ranges_accept_routeros_notation() {
+        let ranges = parse_pool_ranges("10.0.0.10-10.0.0.19, 10.0.1.0/30,10.0.2.5,junk");
+        assert_eq!(
+            ranges,
+            vec![
+                (addr("10.0.0.10"), addr("10.0.0.19")),
+                (addr("10.0.1.0"), addr("10.0.1.3")),
+                (addr("10.0.2.5"), addr("10.0.2.5")),
+            ]
+        );
+        assert_eq!(
+            merge_ranges(vec![(5, 9), (1, 3), (4, 4), (8, 12)]),
+            vec![(1, 12)]
+        );
+    }
+
+    #[test]
+    fn gaps_split_into_aligned_blocks() {
+        let blocks: Vec<String> =
+            range_blocks(u64::from(addr("10.0.0.1")), u64::from(addr("10.0.0.8")))
+                .iter()
+                .map(|b| b.to_string())
+                .collect();
+        assert_eq!(
+            blocks,
+            vec!["10.0.0.1/32", "10.0.0.2/31", "10.0.0.4/30", "10.0.0.8/32"]
+        );
+    }
+
+    #[test]
+    fn plan_nests_subnets_and_counts_usage() {
+        let subnets = vec![
+            subnet("access", "10.0.1.0/24"),
+            subnet("mgmt", "192.168.88.0/24"),
+            subnet("core", "10.0.0.0/16"),
+            subnet("p2p", "10.0.1.0/30"),
+        ];
+        let mut assigned = vec![
+            addr("10.0.1.1"),
+            addr("10.0.1.20"),
+            addr("10.0.2.9"),
+            addr("172.16.0.1"),
+        ];
+        assigned.sort_unstable();
+        let pools = vec![(addr("10.0.1.100"), addr("10.0.1.199"))];
+        let plan = Planner::new(subnets, &assigned, &pools).build();
+
+        assert_eq!(plan.unplanned_assigned, 1);
+        let roots: Vec<&str> = plan.subnets.iter().map(|n| n.subnet.id.as_str()).collect();
+        assert_eq!(roots, vec!["core", "mgmt"]);
+
+        let core = &plan.subnets[0];
+        assert_eq!((core.size, core.usable, core.assigned), (65536, 65534, 3));
+        assert_eq!(core.allocated, 256);
+        assert_eq!(core.free_blocks[0], "10.0.128.0/17");
+        assert_eq!(core.cells.len(), 256);
+        assert_eq!(core.cells[1].cidr, "10.0.1.0/24");
+        assert_eq!(core.cells[1].assigned, 2);
+        assert_eq!(core.cells[1].subnet_id.as_deref(), Some("access"));
+        assert_eq!(core.cells[2].subnet_id, None);
+
+        let access = &core.children[0];
+        assert_eq!(access.parent_id.as_deref(), Some("core"));
+        assert_eq!(access.depth, 1);
+        assert_eq!((access.assigned, access.pooled), (2, 100));
+        assert_eq!(access.utilization_pct, 0.8);
+        assert_eq!(access.last_address, "10.0.1.255");
+
+        let p2p = &access.children[0];
+        assert_eq!((p2p.depth, p2p.usable, p2p.cells.len()), (2, 2, 4));
+        assert_eq!(p2p.utilization_pct, 50.0);
+        assert!(plan.subnets[1].children.is_empty());
+    }
+}
@@ -1,14 +1,36 @@
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    CreateIspPackageRequest, IspPackage, IspPackageRouterMapping, IspPackageRouterMappingView,
-    PaginatedResponse, UpdateIspPackageRequest, UpsertIspPackageRouterMappingRequest,
+    BandwidthBurst, CreateIspPackageRequest, FairUsagePolicy, IspPackage, IspPackageBandwidth,
+    IspPackagePriceHistory, IspPackageRouterMapping, IspPackageRouterMappingView,
+    PaginatedResponse, TrashItem, UpdateIspPackageRequest, UpsertIspPackageRouterMappingRequest,
 };
 use crate::services::{AuditService, AuthService};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use std::collections::HashSet;
 use uuid::Uuid;
 
+#[cfg(feature = "postgres")]
+type Db = sqlx::Postgres;
+
+#[cfg(feature = "sqlite")]
+type Db = sqlx::Sqlite;
+
+/// Longest burst averaging window accepted, in seconds.
+const MAX_BURST_TIME_SECS: i32 = 3600;
+
+/// Services that are sold inside a bundle next to an internet package.
+const ADDON_SERVICE_TYPES: [&str; 3] = ["static_ip", "iptv", "voip"];
+
+/// RouterOS speed notation: whole megabits as `M`, anything else in `k`.
+fn format_kbps(kbps: i32) -> String {
+    if kbps % 1000 == 0 {
+        format!("{}M", kbps / 1000)
+    } else {
+        format!("{}k", kbps)
+    }
+}
+
 #[derive(Clone)]
 pub struct IspPackageService {
     pool: DbPool,
@@ -27,7 +49,9 @@ impl IspPackageService {
 
     async fn ensure_router_access(&self, tenant_id: &str, router_id: &str) -> AppResult<()> {
         let exists: Option<String> =
-            sqlx::query_scalar("SELECT id FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2")
+            sqlx::query_scalar(
+                "SELECT id FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL",
+            )
                 .bind(router_id)
                 .bind(tenant_id)
                 .fetch_optional(&self.pool)
@@ -41,13 +65,14 @@ impl IspPackageService {
     }
 
     async fn ensure_package_access(&self, tenant_id: &str, package_id: &str) -> AppResult<()> {
-        let exists: Option<String> =
-            sqlx::query_scalar("SELECT id FROM isp_packages WHERE id = $1 AND tenant_id = $2")
-                .bind(package_id)
-                .bind(tenant_id)
-                .fetch_optional(&self.pool)
-                .await
-                .map_err(AppError::Database)?;
+        let exists: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM isp_packages WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL",
+        )
+        .bind(package_id)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
 
         if exists.is_none() {
             return Err(AppError::Validation("Package not found".into()));
@@ -77,10 +102,379 @@ impl IspPackageService {
             .trim()
             .to_lowercase();
         match value.as_str() {
-            "internet_pppoe" | "hotspot" | "vpn" => Ok(value),
+            "internet_pppoe" | "hotspot" | "vpn" | "static_ip" | "iptv" | "voip" | "bundle" => {
+                Ok(value)
+            }
             _ => Err(AppError::Validation(
-                "service_type must be one of: internet_pppoe, hotspot, vpn".into(),
+                "service_type must be one of: internet_pppoe, hotspot, vpn, static_ip, iptv, voip, bundle"
+                    .into(),
+            )),
+        }
+    }
+
+    /// A bundle holds exactly one internet package plus add-ons, each at most
+    /// once. `components` are `(id, service_type)` pairs.
+    fn validate_bundle_components(components: &[(String, String)]) -> AppResult<()> {
+        let internet = components
+            .iter()
+            .filter(|(_, t)| t == "internet_pppoe")
+            .count();
+        if internet != 1 {
+            return Err(AppError::Validation(
+                "A bundle needs exactly one internet_pppoe package".into(),
+            ));
+        }
+        if let Some((_, t)) = components
+            .iter()
+            .find(|(_, t)| t != "internet_pppoe" && !ADDON_SERVICE_TYPES.contains(&t.as_str()))
+        {
+            return Err(AppError::Validation(format!(
+                "A {} package cannot be part of a bundle",
+                t
+            )));
+        }
+        let mut seen = HashSet::new();
+        if components.iter().any(|(id, _)| !seen.insert(id)) {
+            return Err(AppError::Validation(
+                "A bundle cannot contain the same package twice".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks the requested components and returns their ids, internet package first.
+    async fn resolve_components(
+        &self,
+        tenant_id: &str,
+        bundle_id: &str,
+        ids: Vec<String>,
+    ) -> AppResult<Vec<String>> {
+        let ids: Vec<String> = ids
+            .into_iter()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
+        if ids.iter().any(|id| id == bundle_id) {
+            return Err(AppError::Validation(
+                "A bundle cannot contain itself".into(),
+            ));
+        }
+
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT id, service_type FROM isp_packages
+            WHERE tenant_id = $1 AND id = ANY($2) AND deleted_at IS NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let mut components = Vec::with_capacity(ids.len());
+        for id in ids {
+            let Some((_, service_type)) = rows.iter().find(|(row_id, _)| *row_id == id) else {
+                return Err(AppError::Validation(format!(
+                    "Bundle component {} not found",
+                    id
+                )));
+            };
+            components.push((id, service_type.clone()));
+        }
+        Self::validate_bundle_components(&components)?;
+
+        components.sort_by_key(|(_, t)| t != "internet_pppoe");
+        Ok(components.into_iter().map(|(id, _)| id).collect())
+    }
+
+    async fn save_components(
+        tx: &mut sqlx::Transaction<'_, Db>,
+        pkg: &IspPackage,
+    ) -> AppResult<()> {
+        sqlx::query("DELETE FROM isp_package_components WHERE tenant_id = $1 AND bundle_id = $2")
+            .bind(&pkg.tenant_id)
+            .bind(&pkg.id)
+            .execute(&mut **tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        let now = Utc::now();
+        for (i, component_id) in pkg.component_ids.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO isp_package_components (
+                  id, tenant_id, bundle_id, component_id, sort_order, created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&pkg.tenant_id)
+            .bind(&pkg.id)
+            .bind(component_id)
+            .bind(i as i32)
+            .bind(now)
+            .execute(&mut **tx)
+            .await
+            .map_err(AppError::Database)?;
+        }
+        Ok(())
+    }
+
+    /// Fills `component_ids` of the bundles in `packages`.
+    async fn load_component_ids(
+        &self,
+        tenant_id: &str,
+        packages: &mut [IspPackage],
+    ) -> AppResult<()> {
+        let bundle_ids: Vec<String> = packages
+            .iter()
+            .filter(|p| p.service_type == "bundle")
+            .map(|p| p.id.clone())
+            .collect();
+        if bundle_ids.is_empty() {
+            return Ok(());
+        }
+
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT bundle_id, component_id FROM isp_package_components
+            WHERE tenant_id = $1 AND bundle_id = ANY($2)
+            ORDER BY sort_order, created_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&bundle_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        for pkg in packages.iter_mut() {
+            pkg.component_ids = rows
+                .iter()
+                .filter(|(bundle_id, _)| *bundle_id == pkg.id)
+                .map(|(_, component_id)| component_id.clone())
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// Names of the live bundles that contain `package_id`.
+    async fn bundles_containing(
+        &self,
+        tenant_id: &str,
+        package_id: &str,
+    ) -> AppResult<Vec<String>> {
+        sqlx::query_scalar(
+            r#"
+            SELECT b.name
+            FROM isp_package_components c
+            JOIN isp_packages b ON b.id = c.bundle_id
+            WHERE c.tenant_id = $1 AND c.component_id = $2 AND b.deleted_at IS NULL
+            ORDER BY b.name
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(package_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Appends the package's current prices to its price history.
+    async fn record_price(
+        tx: &mut sqlx::Transaction<'_, Db>,
+        pkg: &IspPackage,
+        actor_id: &str,
+        effective_from: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO isp_package_price_history (
+              id, tenant_id, package_id, price_monthly, price_yearly, effective_from, changed_by,
+              created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&pkg.tenant_id)
+        .bind(&pkg.id)
+        .bind(pkg.price_monthly)
+        .bind(pkg.price_yearly)
+        .bind(effective_from)
+        .bind(actor_id)
+        .bind(Utc::now())
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    fn validate_burst(
+        burst: &BandwidthBurst,
+        rate_up: Option<i32>,
+        rate_down: Option<i32>,
+    ) -> AppResult<()> {
+        let shaped = [
+            burst.burst_limit_up_kbps,
+            burst.burst_limit_down_kbps,
+            burst.burst_threshold_up_kbps,
+            burst.burst_threshold_down_kbps,
+            burst.burst_time_secs,
+        ];
+        if shaped.iter().any(|v| v.is_some_and(|v| v <= 0)) {
+            return Err(AppError::Validation(
+                "Burst limit, threshold and time must be greater than 0".into(),
+            ));
+        }
+        if let Some(p) = burst.burst_priority {
+            if !(1..=8).contains(&p) {
+                return Err(AppError::Validation(
+                    "burst_priority must be between 1 and 8".into(),
+                ));
+            }
+        }
+        if burst.is_empty() {
+            return Ok(());
+        }
+        let (Some(rate_up), Some(rate_down)) = (rate_up, rate_down) else {
+            return Err(AppError::Validation(
+                "Burst settings require the package upload and download rate".into(),
+            ));
+        };
+        if shaped.iter().all(|v| v.is_none()) {
+            return Ok(());
+        }
+        let [Some(limit_up), Some(limit_down), Some(thr_up), Some(thr_down), Some(time)] = shaped
+        else {
+            return Err(AppError::Validation(
+                "Burst limit, threshold and time must be set together".into(),
+            ));
+        };
+        if limit_up <= rate_up || limit_down <= rate_down {
+            return Err(AppError::Validation(
+                "Burst limit must be higher than the rate limit".into(),
+            ));
+        }
+        if thr_up > rate_up || thr_down > rate_down {
+            return Err(AppError::Validation(
+                "Burst threshold must not exceed the rate limit".into(),
+            ));
+        }
+        if time > MAX_BURST_TIME_SECS {
+            return Err(AppError::Validation(format!(
+                "burst_time_secs must be at most {}",
+                MAX_BURST_TIME_SECS
+            )));
+        }
+        Ok(())
+    }
+
+    fn validate_bandwidth(bandwidth: &IspPackageBandwidth) -> AppResult<()> {
+        let rates = [bandwidth.rate_limit_up_kbps, bandwidth.rate_limit_down_kbps];
+        if rates.iter().any(|v| v.is_some_and(|v| v <= 0)) {
+            return Err(AppError::Validation(
+                "Rate limits must be greater than 0".into(),
+            ));
+        }
+        if rates[0].is_some() != rates[1].is_some() {
+            return Err(AppError::Validation(
+                "Set both the upload and download rate, or neither".into(),
+            ));
+        }
+        Self::validate_burst(&bandwidth.burst, rates[0], rates[1])?;
+        Self::validate_fup(&bandwidth.fup, rates[0], rates[1])
+    }
+
+    fn validate_fup(
+        fup: &FairUsagePolicy,
+        rate_up: Option<i32>,
+        rate_down: Option<i32>,
+    ) -> AppResult<()> {
+        if fup.is_empty() {
+            return Ok(());
+        }
+        let (Some(quota), Some(up), Some(down)) = (
+            fup.fup_quota_gb,
+            fup.fup_rate_up_kbps,
+            fup.fup_rate_down_kbps,
+        ) else {
+            return Err(AppError::Validation(
+                "Fair-usage quota and throttled upload/download speed must be set together".into(),
+            ));
+        };
+        if quota <= 0 || up <= 0 || down <= 0 {
+            return Err(AppError::Validation(
+                "Fair-usage quota and throttled speeds must be greater than 0".into(),
+            ));
+        }
+        if rate_up.is_some_and(|r| up >= r) || rate_down.is_some_and(|r| down >= r) {
+            return Err(AppError::Validation(
+                "Throttled speeds must be lower than the package rate".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The `rate-limit` of the throttled profile, or `None` without a fair-usage policy.
+    pub fn fup_rate_limit(fup: &FairUsagePolicy) -> Option<String> {
+        fup.fup_quota_gb?;
+        Some(format!(
+            "{}/{}",
+            format_kbps(fup.fup_rate_up_kbps?),
+            format_kbps(fup.fup_rate_down_kbps?)
+        ))
+    }
+
+    /// The MikroTik `rate-limit` value for these speeds, or `None` when the
+    /// package has no rate. RouterOS reads it from the router's side, so the
+    /// client's upload comes first:
+    /// `rx/tx [burst-rx/burst-tx threshold-rx/threshold-tx time/time [priority]]`.
+    pub fn mikrotik_rate_limit(bandwidth: &IspPackageBandwidth) -> Option<String> {
+        let up = bandwidth.rate_limit_up_kbps?;
+        let down = bandwidth.rate_limit_down_kbps?;
+        let mut out = format!("{}/{}", format_kbps(up), format_kbps(down));
+
+        let b = &bandwidth.burst;
+        let burst = match (
+            b.burst_limit_up_kbps,
+            b.burst_limit_down_kbps,
+            b.burst_threshold_up_kbps,
+            b.burst_threshold_down_kbps,
+            b.burst_time_secs,
+        ) {
+            (Some(lu), Some(ld), Some(tu), Some(td), Some(t)) => Some(format!(
+                " {}/{} {}/{} {}/{}",
+                format_kbps(lu),
+                format_kbps(ld),
+                format_kbps(tu),
+                format_kbps(td),
+                t,
+                t
             )),
+            _ => None,
+        };
+        match (burst, b.burst_priority) {
+            (Some(burst), priority) => {
+                out.push_str(&burst);
+                if let Some(priority) = priority {
+                    out.push_str(&format!(" {}", priority));
+                }
+            }
+            // Priority is positional, so the burst fields in front of it are zeroed.
+            (None, Some(priority)) => out.push_str(&format!(" 0/0 0/0 0/0 {}", priority)),
+            (None, None) => {}
+        }
+        Some(out)
+    }
+
+    /// `<quota>GB -> <rate-limit>` for audit messages, empty without a policy.
+    fn fup_summary(fup: &FairUsagePolicy) -> String {
+        match (fup.fup_quota_gb, Self::fup_rate_limit(fup)) {
+            (Some(quota), Some(rate)) => format!("{}GB -> {}", quota, rate),
+            _ => String::new(),
         }
     }
 
@@ -131,6 +525,7 @@ impl IspPackageService {
             r#"
             SELECT COUNT(*) FROM isp_packages
             WHERE tenant_id = $1
+              AND deleted_at IS NULL
               AND ($2 = '' OR name ILIKE '%' || $2 || '%')
             "#,
         )
@@ -150,19 +545,32 @@ impl IspPackageService {
               description,
               features,
               is_active,
+              zone_restricted,
               price_monthly::float8 AS price_monthly,
               price_yearly::float8 AS price_yearly,
+              rate_limit_up_kbps,
+              rate_limit_down_kbps,
+              burst_limit_up_kbps,
+              burst_limit_down_kbps,
+              burst_threshold_up_kbps,
+              burst_threshold_down_kbps,
+              burst_time_secs,
+              burst_priority,
+              fup_quota_gb,
+              fup_rate_up_kbps,
+              fup_rate_down_kbps,
               created_at,
               updated_at
             FROM isp_packages
             WHERE tenant_id = $1
+              AND deleted_at IS NULL
               AND ($2 = '' OR name ILIKE '%' || $2 || '%')
             ORDER BY {sort_column} {sort_direction}
             LIMIT $3 OFFSET $4
             "#
         );
 
-        let rows: Vec<IspPackage> = sqlx::query_as(&list_sql)
+        let mut rows: Vec<IspPackage> = sqlx::query_as(&list_sql)
             .bind(tenant_id)
             .bind(&q)
             .bind(per_page as i64)
@@ -170,6 +578,7 @@ impl IspPackageService {
             .fetch_all(&self.pool)
             .await
             .map_err(AppError::Database)?;
+        self.load_component_ids(tenant_id, &mut rows).await?;
 
         Ok(PaginatedResponse {
             data: rows,
@@ -209,8 +618,16 @@ impl IspPackageService {
 
         let normalized_features = Self::normalize_features(dto.features);
         let service_type = Self::normalize_service_type(dto.service_type)?;
+        let bandwidth = dto.bandwidth.unwrap_or_default();
+        Self::validate_bandwidth(&bandwidth)?;
+        let components = dto.components.unwrap_or_default();
+        if service_type != "bundle" && !components.is_empty() {
+            return Err(AppError::Validation(
+                "components are only allowed for bundle packages".into(),
+            ));
+        }
 
-        let pkg = IspPackage::new(
+        let mut pkg = IspPackage::new(
             tenant_id.to_string(),
             Some(service_type),
             name,
@@ -227,11 +644,26 @@ impl IspPackageService {
             Some(monthly),
             Some(yearly),
         );
+        pkg.zone_restricted = dto.zone_restricted.unwrap_or(false);
+        // A bundle's speeds come from its internet package.
+        if pkg.service_type == "bundle" {
+            pkg.component_ids = self
+                .resolve_components(tenant_id, &pkg.id, components)
+                .await?;
+        } else {
+            pkg.bandwidth = bandwidth;
+        }
 
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
         sqlx::query(
             r#"
-            INSERT INTO isp_packages (id, tenant_id, service_type, name, description, features, is_active, price_monthly, price_yearly, created_at, updated_at)
-            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)
+            INSERT INTO isp_packages (
+              id, tenant_id, service_type, name, description, features, is_active, zone_restricted,
+              price_monthly, price_yearly, rate_limit_up_kbps, rate_limit_down_kbps, burst_limit_up_kbps, burst_limit_down_kbps,
+              burst_threshold_up_kbps, burst_threshold_down_kbps, burst_time_secs, burst_priority,
+              fup_quota_gb, fup_rate_up_kbps, fup_rate_down_kbps, created_at, updated_at
+            )
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,$17,$18,$19,$20,$21,$22,$23)
             "#,
         )
         .bind(&pkg.id)
@@ -241,11 +673,23 @@ impl IspPackageService {
         .bind(&pkg.description)
         .bind(&pkg.features)
         .bind(pkg.is_active)
+        .bind(pkg.zone_restricted)
         .bind(pkg.price_monthly)
         .bind(pkg.price_yearly)
+        .bind(pkg.bandwidth.rate_limit_up_kbps)
+        .bind(pkg.bandwidth.rate_limit_down_kbps)
+        .bind(pkg.bandwidth.burst.burst_limit_up_kbps)
+        .bind(pkg.bandwidth.burst.burst_limit_down_kbps)
+        .bind(pkg.bandwidth.burst.burst_threshold_up_kbps)
+        .bind(pkg.bandwidth.burst.burst_threshold_down_kbps)
+        .bind(pkg.bandwidth.burst.burst_time_secs)
+        .bind(pkg.bandwidth.burst.burst_priority)
+        .bind(pkg.bandwidth.fup.fup_quota_gb)
+        .bind(pkg.bandwidth.fup.fup_rate_up_kbps)
+        .bind(pkg.bandwidth.fup.fup_rate_down_kbps)
         .bind(pkg.created_at)
         .bind(pkg.updated_at)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| {
             if e.as_database_error()
@@ -257,6 +701,9 @@ impl IspPackageService {
                 AppError::Database(e)
             }
         })?;
+        Self::record_price(&mut tx, &pkg, actor_id, pkg.created_at).await?;
+        Self::save_components(&mut tx, &pkg).await?;
+        tx.commit().await.map_err(AppError::Database)?;
 
         self.audit_service
             .log(
@@ -266,12 +713,16 @@ impl IspPackageService {
                 "isp_packages",
                 Some(&pkg.id),
                 Some(&format!(
-                    "Created ISP package {} (type={}, monthly={}, yearly={}, features={})",
+                    "Created ISP package {} (type={}, monthly={}, yearly={}, features={}, rate-limit={}, fup={}, zone_restricted={}, components=[{}])",
                     pkg.name,
                     pkg.service_type,
                     pkg.price_monthly,
                     pkg.price_yearly,
-                    pkg.features.join(" | ")
+                    pkg.features.join(" | "),
+                    Self::mikrotik_rate_limit(&pkg.bandwidth).unwrap_or_default(),
+                    Self::fup_summary(&pkg.bandwidth.fup),
+                    pkg.zone_restricted,
+                    pkg.component_ids.join(", ")
                 )),
                 ip_address,
             )
@@ -302,12 +753,24 @@ impl IspPackageService {
               description,
               features,
               is_active,
+              zone_restricted,
               price_monthly::float8 AS price_monthly,
               price_yearly::float8 AS price_yearly,
+              rate_limit_up_kbps,
+              rate_limit_down_kbps,
+              burst_limit_up_kbps,
+              burst_limit_down_kbps,
+              burst_threshold_up_kbps,
+              burst_threshold_down_kbps,
+              burst_time_secs,
+              burst_priority,
+              fup_quota_gb,
+              fup_rate_up_kbps,
+              fup_rate_down_kbps,
               created_at,
               updated_at
             FROM isp_packages
-            WHERE tenant_id = $1 AND id = $2
+            WHERE tenant_id = $1 AND id = $2 AND deleted_at IS NULL
             "#,
         )
         .bind(tenant_id)
@@ -316,6 +779,8 @@ impl IspPackageService {
         .await
         .map_err(AppError::Database)?
         .ok_or_else(|| AppError::NotFound("Package not found".into()))?;
+        self.load_component_ids(tenant_id, std::slice::from_mut(&mut pkg))
+            .await?;
 
         let old_monthly = pkg.price_monthly;
         let old_yearly = pkg.price_yearly;
@@ -323,7 +788,10 @@ impl IspPackageService {
         let old_name = pkg.name.clone();
         let old_description = pkg.description.clone();
         let old_active = pkg.is_active;
+        let old_zone_restricted = pkg.zone_restricted;
         let old_service_type = pkg.service_type.clone();
+        let old_bandwidth = pkg.bandwidth.clone();
+        let old_components = pkg.component_ids.clone();
 
         if let Some(v) = dto.name {
             let vv = v.trim().to_string();
@@ -344,6 +812,9 @@ impl IspPackageService {
         if let Some(v) = dto.is_active {
             pkg.is_active = v;
         }
+        if let Some(v) = dto.zone_restricted {
+            pkg.zone_restricted = v;
+        }
         if let Some(v) = dto.price_monthly {
             if v <= 0.0 {
                 return Err(AppError::Validation(
@@ -360,14 +831,47 @@ impl IspPackageService {
             }
             pkg.price_yearly = v;
         }
+        if let Some(bandwidth) = dto.bandwidth {
+            Self::validate_bandwidth(&bandwidth)?;
+            pkg.bandwidth = bandwidth;
+        }
         if pkg.price_monthly <= 0.0 {
             return Err(AppError::Validation(
                 "price_monthly is required and must be greater than 0".into(),
             ));
         }
+        if pkg.service_type != old_service_type {
+            let bundles = self.bundles_containing(tenant_id, id).await?;
+            if !bundles.is_empty() {
+                return Err(AppError::Conflict(format!(
+                    "The service type cannot change while the package is part of a bundle: {}",
+                    bundles.join(", ")
+                )));
+            }
+        }
+        if pkg.service_type == "bundle" {
+            if let Some(ids) = dto.components {
+                pkg.component_ids = self.resolve_components(tenant_id, id, ids).await?;
+            } else if pkg.component_ids.is_empty() {
+                return Err(AppError::Validation(
+                    "components are required for bundle packages".into(),
+                ));
+            }
+            pkg.bandwidth = IspPackageBandwidth::default();
+        } else {
+            if dto.components.is_some_and(|ids| !ids.is_empty()) {
+                return Err(AppError::Validation(
+                    "components are only allowed for bundle packages".into(),
+                ));
+            }
+            pkg.component_ids.clear();
+        }
 
         pkg.updated_at = Utc::now();
+        let price_changed = (old_monthly - pkg.price_monthly).abs() > f64::EPSILON
+            || (old_yearly - pkg.price_yearly).abs() > f64::EPSILON;
 
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
         sqlx::query(
             r#"
             UPDATE isp_packages SET
@@ -376,10 +880,22 @@ impl IspPackageService {
               description = $3,
               features = $4,
               is_active = $5,
-              price_monthly = $6,
-              price_yearly = $7,
-              updated_at = $8
-            WHERE tenant_id = $9 AND id = $10
+              zone_restricted = $6,
+              price_monthly = $7,
+              price_yearly = $8,
+              rate_limit_up_kbps = $9,
+              rate_limit_down_kbps = $10,
+              burst_limit_up_kbps = $11,
+              burst_limit_down_kbps = $12,
+              burst_threshold_up_kbps = $13,
+              burst_threshold_down_kbps = $14,
+              burst_time_secs = $15,
+              burst_priority = $16,
+              fup_quota_gb = $17,
+              fup_rate_up_kbps = $18,
+              fup_rate_down_kbps = $19,
+              updated_at = $20
+            WHERE tenant_id = $21 AND id = $22
             "#,
         )
         .bind(&pkg.service_type)
@@ -387,12 +903,24 @@ impl IspPackageService {
         .bind(&pkg.description)
         .bind(&pkg.features)
         .bind(pkg.is_active)
+        .bind(pkg.zone_restricted)
         .bind(pkg.price_monthly)
         .bind(pkg.price_yearly)
+        .bind(pkg.bandwidth.rate_limit_up_kbps)
+        .bind(pkg.bandwidth.rate_limit_down_kbps)
+        .bind(pkg.bandwidth.burst.burst_limit_up_kbps)
+        .bind(pkg.bandwidth.burst.burst_limit_down_kbps)
+        .bind(pkg.bandwidth.burst.burst_threshold_up_kbps)
+        .bind(pkg.bandwidth.burst.burst_threshold_down_kbps)
+        .bind(pkg.bandwidth.burst.burst_time_secs)
+        .bind(pkg.bandwidth.burst.burst_priority)
+        .bind(pkg.bandwidth.fup.fup_quota_gb)
+        .bind(pkg.bandwidth.fup.fup_rate_up_kbps)
+        .bind(pkg.bandwidth.fup.fup_rate_down_kbps)
         .bind(pkg.updated_at)
         .bind(tenant_id)
         .bind(id)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| {
             if e.as_database_error()
@@ -404,6 +932,13 @@ impl IspPackageService {
                 AppError::Database(e)
             }
         })?;
+        if price_changed {
+            Self::record_price(&mut tx, &pkg, actor_id, pkg.updated_at).await?;
+        }
+        if old_components != pkg.component_ids {
+            Self::save_components(&mut tx, &pkg).await?;
+        }
+        tx.commit().await.map_err(AppError::Database)?;
 
         let audit_message = {
             let mut changes = Vec::new();
@@ -432,6 +967,29 @@ impl IspPackageService {
             if old_active != pkg.is_active {
                 changes.push(format!("active: {} -> {}", old_active, pkg.is_active));
             }
+            if old_zone_restricted != pkg.zone_restricted {
+                changes.push(format!(
+                    "zone_restricted: {} -> {}",
+                    old_zone_restricted, pkg.zone_restricted
+                ));
+            }
+            if old_bandwidth.rate_limit_up_kbps != pkg.bandwidth.rate_limit_up_kbps
+                || old_bandwidth.rate_limit_down_kbps != pkg.bandwidth.rate_limit_down_kbps
+                || old_bandwidth.burst != pkg.bandwidth.burst
+            {
+                changes.push(format!(
+                    "rate-limit: '{}' -> '{}'",
+                    Self::mikrotik_rate_limit(&old_bandwidth).unwrap_or_default(),
+                    Self::mikrotik_rate_limit(&pkg.bandwidth).unwrap_or_default()
+                ));
+            }
+            if old_bandwidth.fup != pkg.bandwidth.fup {
+                changes.push(format!(
+                    "fup: '{}' -> '{}'",
+                    Self::fup_summary(&old_bandwidth.fup),
+                    Self::fup_summary(&pkg.bandwidth.fup)
+                ));
+            }
             if old_features != pkg.features {
                 changes.push(format!(
                     "features: [{}] -> [{}]",
@@ -439,6 +997,13 @@ impl IspPackageService {
                     pkg.features.join(" | ")
                 ));
             }
+            if old_components != pkg.component_ids {
+                changes.push(format!(
+                    "components: [{}] -> [{}]",
+                    old_components.join(", "),
+                    pkg.component_ids.join(", ")
+                ));
+            }
 
             if changes.is_empty() {
                 "Updated ISP package (no field changes)".to_string()
@@ -462,6 +1027,57 @@ impl IspPackageService {
         Ok(pkg)
     }
 
+    /// Price changes of a package, newest first.
+    pub async fn list_price_history(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        package_id: &str,
+    ) -> AppResult<Vec<IspPackagePriceHistory>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "isp_packages", "read")
+            .await?;
+
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM isp_packages WHERE tenant_id = $1 AND id = $2)",
+        )
+        .bind(tenant_id)
+        .bind(package_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+        if !exists {
+            return Err(AppError::NotFound("Package not found".into()));
+        }
+
+        let rows: Vec<IspPackagePriceHistory> = sqlx::query_as(
+            r#"
+            SELECT
+              h.id,
+              h.tenant_id,
+              h.package_id,
+              h.price_monthly::float8 AS price_monthly,
+              h.price_yearly::float8 AS price_yearly,
+              h.effective_from,
+              h.changed_by,
+              u.name AS changed_by_name,
+              h.created_at
+            FROM isp_package_price_history h
+            LEFT JOIN users u ON u.id = h.changed_by
+            WHERE h.tenant_id = $1 AND h.package_id = $2
+            ORDER BY h.effective_from DESC, h.created_at DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(package_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows)
+    }
+
+    /// Move a package to the trash. Existing subscriptions keep their package reference.
     pub async fn delete_package(
         &self,
         actor_id: &str,
@@ -473,20 +1089,40 @@ impl IspPackageService {
             .check_permission(actor_id, tenant_id, "isp_packages", "manage")
             .await?;
 
-        let name: Option<String> =
-            sqlx::query_scalar("SELECT name FROM isp_packages WHERE tenant_id = $1 AND id = $2")
-                .bind(tenant_id)
-                .bind(id)
-                .fetch_optional(&self.pool)
-                .await
-                .map_err(AppError::Database)?;
+        let name: Option<String> = sqlx::query_scalar(
+            "SELECT name FROM isp_packages WHERE tenant_id = $1 AND id = $2 AND deleted_at IS NULL",
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
 
-        sqlx::query("DELETE FROM isp_packages WHERE tenant_id = $1 AND id = $2")
-            .bind(tenant_id)
-            .bind(id)
-            .execute(&self.pool)
-            .await
-            .map_err(AppError::Database)?;
+        let Some(name) = name else {
+            return Err(AppError::NotFound("Package not found".into()));
+        };
+        let bundles = self.bundles_containing(tenant_id, id).await?;
+        if !bundles.is_empty() {
+            return Err(AppError::Conflict(format!(
+                "Package is part of a bundle: {}",
+                bundles.join(", ")
+            )));
+        }
+
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            UPDATE isp_packages
+            SET deleted_at = $3, updated_at = $3
+            WHERE tenant_id = $1 AND id = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
 
         self.audit_service
             .log(
@@ -495,10 +1131,101 @@ impl IspPackageService {
                 "ISP_PACKAGE_DELETE",
                 "isp_packages",
                 Some(id),
-                Some(&format!(
-                    "Deleted ISP package {}",
-                    name.unwrap_or_else(|| id.to_string())
-                )),
+                Some(&format!("Moved ISP package {} to trash", name)),
+                ip_address,
+            )
+            .await;
+
+        Ok(())
+    }
+
+    pub async fn list_deleted_packages(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+    ) -> AppResult<Vec<TrashItem>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "isp_packages", "manage")
+            .await?;
+
+        let rows: Vec<TrashItem> = sqlx::query_as(
+            r#"
+            SELECT id, name, description AS detail, deleted_at
+            FROM isp_packages
+            WHERE tenant_id = $1 AND deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows)
+    }
+
+    pub async fn restore_package(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        ip_address: Option<&str>,
+    ) -> AppResult<()> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "isp_packages", "manage")
+            .await?;
+
+        let name: Option<String> = sqlx::query_scalar(
+            "SELECT name FROM isp_packages WHERE tenant_id = $1 AND id = $2 AND deleted_at IS NOT NULL",
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let Some(name) = name else {
+            return Err(AppError::NotFound("Package not found in trash".into()));
+        };
+
+        let name_taken: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM isp_packages WHERE tenant_id = $1 AND name = $2 AND deleted_at IS NULL)",
+        )
+        .bind(tenant_id)
+        .bind(&name)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        if name_taken {
+            return Err(AppError::Conflict(format!(
+                "A package named '{}' already exists",
+                name
+            )));
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE isp_packages
+            SET deleted_at = NULL, updated_at = $3
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "ISP_PACKAGE_RESTORE",
+                "isp_packages",
+                Some(id),
+                Some(&format!("Restored ISP package {} from trash", name)),
                 ip_address,
             )
             .await;
@@ -538,10 +1265,16 @@ impl IspPackageService {
               r.name AS router_name,
               m.router_profile_name,
               m.address_pool,
+              m.burst_limit_up_kbps,
+              m.burst_limit_down_kbps,
+              m.burst_threshold_up_kbps,
+              m.burst_threshold_down_kbps,
+              m.burst_time_secs,
+              m.burst_priority,
               m.created_at,
               m.updated_at
             FROM isp_package_router_mappings m
-            JOIN isp_packages p ON p.id = m.package_id
+            JOIN isp_packages p ON p.id = m.package_id AND p.deleted_at IS NULL
             LEFT JOIN mikrotik_routers r ON r.tenant_id = m.tenant_id AND r.id = m.router_id
             WHERE m.tenant_id = $1
               AND ($2 = '' OR m.router_id = $2)
@@ -572,19 +1305,46 @@ impl IspPackageService {
         self.ensure_package_access(tenant_id, &dto.package_id)
             .await?;
 
-        let package_type: Option<String> = sqlx::query_scalar(
-            "SELECT service_type FROM isp_packages WHERE tenant_id = $1 AND id = $2",
+        #[derive(sqlx::FromRow)]
+        struct PackageRow {
+            service_type: String,
+            #[sqlx(flatten)]
+            bandwidth: IspPackageBandwidth,
+        }
+
+        let package: Option<PackageRow> = sqlx::query_as(
+            r#"
+            SELECT
+              service_type,
+              rate_limit_up_kbps,
+              rate_limit_down_kbps,
+              burst_limit_up_kbps,
+              burst_limit_down_kbps,
+              burst_threshold_up_kbps,
+              burst_threshold_down_kbps,
+              burst_time_secs,
+              burst_priority
+            FROM isp_packages
+            WHERE tenant_id = $1 AND id = $2
+            "#,
         )
         .bind(tenant_id)
         .bind(&dto.package_id)
         .fetch_optional(&self.pool)
         .await
         .map_err(AppError::Database)?;
-        if package_type.as_deref() != Some("internet_pppoe") {
+        let Some(package) = package.filter(|p| p.service_type == "internet_pppoe") else {
             return Err(AppError::Validation(
                 "Router mapping is only available for service type internet_pppoe".into(),
             ));
-        }
+        };
+
+        // The overrides are checked as they end up on the router: merged over the package burst.
+        Self::validate_burst(
+            &dto.burst.or(&package.bandwidth.burst),
+            package.bandwidth.rate_limit_up_kbps,
+            package.bandwidth.rate_limit_down_kbps,
+        )?;
 
         let profile = dto.router_profile_name.trim().to_string();
         if profile.is_empty() {
@@ -609,12 +1369,20 @@ impl IspPackageService {
         sqlx::query(
             r#"
             INSERT INTO isp_package_router_mappings
-              (id, tenant_id, router_id, package_id, router_profile_name, address_pool, created_at, updated_at)
+              (id, tenant_id, router_id, package_id, router_profile_name, address_pool,
+               burst_limit_up_kbps, burst_limit_down_kbps, burst_threshold_up_kbps,
+               burst_threshold_down_kbps, burst_time_secs, burst_priority, created_at, updated_at)
             VALUES
-              ($1,$2,$3,$4,$5,$6,$7,$8)
+              ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14)
             ON CONFLICT (tenant_id, router_id, package_id) DO UPDATE SET
               router_profile_name = EXCLUDED.router_profile_name,
               address_pool = EXCLUDED.address_pool,
+              burst_limit_up_kbps = EXCLUDED.burst_limit_up_kbps,
+              burst_limit_down_kbps = EXCLUDED.burst_limit_down_kbps,
+              burst_threshold_up_kbps = EXCLUDED.burst_threshold_up_kbps,
+              burst_threshold_down_kbps = EXCLUDED.burst_threshold_down_kbps,
+              burst_time_secs = EXCLUDED.burst_time_secs,
+              burst_priority = EXCLUDED.burst_priority,
               updated_at = EXCLUDED.updated_at
             "#,
         )
@@ -624,6 +1392,12 @@ impl IspPackageService {
         .bind(&dto.package_id)
         .bind(&profile)
         .bind(&addr_pool)
+        .bind(dto.burst.burst_limit_up_kbps)
+        .bind(dto.burst.burst_limit_down_kbps)
+        .bind(dto.burst.burst_threshold_up_kbps)
+        .bind(dto.burst.burst_threshold_down_kbps)
+        .bind(dto.burst.burst_time_secs)
+        .bind(dto.burst.burst_priority)
         .bind(now)
         .bind(now)
         .execute(&self.pool)
@@ -658,3 +1432,152 @@ impl IspPackageService {
         Ok(mapping)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::IspPackageService;
+    use crate::models::{BandwidthBurst, FairUsagePolicy, IspPackageBandwidth};
+
+    fn bandwidth(up: i32, down: i32, burst: BandwidthBurst) -> IspPackageBandwidth {
+        IspPackageBandwidth {
+            rate_limit_up_kbps: Some(up),
+            rate_limit_down_kbps: Some(down),
+            burst,
+            fup: FairUsagePolicy::default(),
+        }
+    }
+
+    fn burst() -> BandwidthBurst {
+        BandwidthBurst {
+            burst_limit_up_kbps: Some(10_000),
+            burst_limit_down_kbps: Some(20_000),
+            burst_threshold_up_kbps: Some(3_000),
+            burst_threshold_down_kbps: Some(7_500),
+            burst_time_secs: Some(16),
+            burst_priority: None,
+        }
+    }
+
+    #[test]
+    fn rate_limit_renders_upload_first() {
+        let render = IspPackageService::mikrotik_rate_limit;
+        assert_eq!(render(&IspPackageBandwidth::default()), None);
+        assert_eq!(
+            render(&bandwidth(5_000, 10_000, BandwidthBurst::default())).as_deref(),
+            Some("5M/10M")
+        );
+        assert_eq!(
+            render(&bandwidth(5_000, 10_000, burst())).as_deref(),
+            Some("5M/10M 10M/20M 3M/7500k 16/16")
+        );
+
+        let prioritized = BandwidthBurst {
+            burst_priority: Some(2),
+            ..burst()
+        };
+        assert_eq!(
+            render(&bandwidth(5_000, 10_000, prioritized)).as_deref(),
+            Some("5M/10M 10M/20M 3M/7500k 16/16 2")
+        );
+
+        let priority_only = BandwidthBurst {
+            burst_priority: Some(8),
+            ..BandwidthBurst::default()
+        };
+        assert_eq!(
+            render(&bandwidth(1_500, 10_000, priority_only)).as_deref(),
+            Some("1500k/10M 0/0 0/0 0/0 8")
+        );
+    }
+
+    #[test]
+    fn burst_is_validated_against_the_rate() {
+        assert!(IspPackageService::validate_bandwidth(&bandwidth(5_000, 10_000, burst())).is_ok());
+
+        let partial = BandwidthBurst {
+            burst_time_secs: None,
+            ..burst()
+        };
+        assert!(IspPackageService::validate_bandwidth(&bandwidth(5_000, 10_000, partial)).is_err());
+        // The burst has to exceed the rate and the threshold stay below it.
+        assert!(
+            IspPackageService::validate_bandwidth(&bandwidth(10_000, 20_000, burst())).is_err()
+        );
+        assert!(IspPackageService::validate_bandwidth(&bandwidth(2_000, 10_000, burst())).is_err());
+
+        let bad_priority = BandwidthBurst {
+            burst_priority: Some(9),
+            ..burst()
+        };
+        assert!(
+            IspPackageService::validate_bandwidth(&bandwidth(5_000, 10_000, bad_priority)).is_err()
+        );
+        assert!(IspPackageService::validate_burst(&burst(), None, None).is_err());
+
+        // A mapping override only needs to be valid once merged over the package burst.
+        let override_time = BandwidthBurst {
+            burst_time_secs: Some(30),
+            ..BandwidthBurst::default()
+        };
+        let merged = override_time.or(&burst());
+        assert_eq!(merged.burst_time_secs, Some(30));
+        assert!(IspPackageService::validate_burst(&merged, Some(5_000), Some(10_000)).is_ok());
+    }
+
+    #[test]
+    fn fup_throttles_below_the_package_rate() {
+        let fup = |quota, up, down| FairUsagePolicy {
+            fup_quota_gb: quota,
+            fup_rate_up_kbps: up,
+            fup_rate_down_kbps: down,
+        };
+        let with_fup = |policy| IspPackageBandwidth {
+            fup: policy,
+            ..bandwidth(5_000, 10_000, BandwidthBurst::default())
+        };
+        let validate = IspPackageService::validate_bandwidth;
+
+        let policy = fup(Some(300), Some(1_000), Some(2_500));
+        assert!(validate(&with_fup(policy.clone())).is_ok());
+        assert_eq!(
+            IspPackageService::fup_rate_limit(&policy).as_deref(),
+            Some("1M/2500k")
+        );
+        assert_eq!(
+            IspPackageService::fup_rate_limit(&FairUsagePolicy::default()),
+            None
+        );
+
+        assert!(validate(&with_fup(fup(Some(300), None, None))).is_err());
+        assert!(validate(&with_fup(fup(None, Some(1_000), Some(2_500)))).is_err());
+        assert!(validate(&with_fup(fup(Some(0), Some(1_000), Some(2_500)))).is_err());
+        assert!(validate(&with_fup(fup(Some(300), Some(5_000), Some(2_500)))).is_err());
+        // Without a package rate any throttled speed is accepted.
+        let unshaped = IspPackageBandwidth {
+            fup: fup(Some(300), Some(50_000), Some(50_000)),
+            ..IspPackageBandwidth::default()
+        };
+        assert!(validate(&unshaped).is_ok());
+    }
+
+    #[test]
+    fn bundles_need_one_internet_package_plus_addons() {
+        let c = |id: &str, t: &str| (id.to_string(), t.to_string());
+        let validate = IspPackageService::validate_bundle_components;
+
+        assert!(validate(&[c("net", "internet_pppoe")]).is_ok());
+        assert!(validate(&[
+            c("ip", "static_ip"),
+            c("net", "internet_pppoe"),
+            c("tv", "iptv")
+        ])
+        .is_ok());
+
+        assert!(validate(&[]).is_err());
+        assert!(validate(&[c("tv", "iptv"), c("voip", "voip")]).is_err());
+        assert!(validate(&[c("a", "internet_pppoe"), c("b", "internet_pppoe")]).is_err());
+        assert!(validate(&[c("net", "internet_pppoe"), c("hs", "hotspot")]).is_err());
+        assert!(validate(&[c("net", "internet_pppoe"), c("b", "bundle")]).is_err());
+        assert!(validate(&[c("net", "internet_pppoe"), c("tv", "iptv"), c("tv", "iptv")]).is_err());
+    }
+}
@@ -0,0 +1,593 @@
+//! Database-backed background job queue.
+//!
+//! Jobs are rows in `background_jobs`. Every instance runs a worker that
+//! claims due rows, runs the handler registered for their `job_type` and
+//! writes the outcome back, so a job runs on one instance at a time and
+//! operators can see what is waiting, what ran and what failed.
+//!
+//! A failed run is retried with exponential backoff. One-off jobs that fail
+//! `max_attempts` times are dead-lettered (`dead`) until someone retries
+//! them. Recurring jobs keep a single row that goes back in the queue after
+//! every run; they are never dead-lettered, and a failure is retried no later
+//! than the next regular run.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{BackgroundJob, BackgroundJobStats, PaginatedResponse};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use sqlx::QueryBuilder;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::{Notify, Semaphore};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+#[cfg(feature = "postgres")]
+type Db = sqlx::Postgres;
+#[cfg(feature = "sqlite")]
+type Db = sqlx::Sqlite;
+
+/// Wakes the worker when a job is made due before its next poll.
+static WORKER_WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+
+const POLL_INTERVAL_SECS: u64 = 5;
+/// Jobs one instance runs at the same time.
+const MAX_CONCURRENT_JOBS: usize = 4;
+pub const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+const BASE_BACKOFF_SECS: i64 = 30;
+const MAX_BACKOFF_SECS: i64 = 3600;
+/// A job still `running` this long after it was claimed belongs to a worker
+/// that went away, and is claimed again.
+const STALE_LOCK_SECS: i64 = 2 * 3600;
+/// How long finished one-off jobs are kept for inspection.
+const FINISHED_RETENTION_DAYS: i64 = 14;
+const MAX_PER_PAGE: u32 = 100;
+
+const PURGE_JOB: &str = "jobs.purge";
+
+const JOB_COLUMNS: &str = "id, job_type, payload, status, recurring_secs, attempts, max_attempts, \
+     run_at, locked_at, locked_by, last_error, last_run_at, finished_at, created_at, updated_at";
+
+#[async_trait]
+pub trait JobHandler: Send + Sync {
+    async fn run(&self, job: &BackgroundJob) -> AppResult<()>;
+}
+
+/// Delay before retrying a job that has failed `attempts` times in a row.
+fn backoff(attempts: i32) -> Duration {
+    let exp = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    Duration::seconds((BASE_BACKOFF_SECS << exp).min(MAX_BACKOFF_SECS))
+}
+
+/// State a job is written back with after a run.
+#[derive(Debug, PartialEq)]
+struct Settled {
+    status: &'static str,
+    attempts: i32,
+    run_at: DateTime<Utc>,
+    last_error: Option<String>,
+}
+
+/// Where `job` (with the finished run already counted in `attempts`) goes
+/// after a run that failed with `error`, or succeeded if there is none.
+fn settle(job: &BackgroundJob, error: Option<String>, now: DateTime<Utc>) -> Settled {
+    let interval = job
+        .recurring_secs
+        .map(|secs| Duration::seconds(i64::from(secs.max(1))));
+
+    let Some(error) = error else {
+        return match interval {
+            Some(interval) => Settled {
+                status: "queued",
+                attempts: 0,
+                run_at: now + interval,
+                last_error: None,
+            },
+            None => Settled {
+                status: "succeeded",
+                attempts: job.attempts,
+                run_at: job.run_at,
+                last_error: None,
+            },
+        };
+    };
+
+    let (status, run_at) = match interval {
+        Some(interval) => ("failed", now + backoff(job.attempts).min(interval)),
+        None if job.attempts >= job.max_attempts => ("dead", job.run_at),
+        None => ("failed", now + backoff(job.attempts)),
+    };
+    Settled {
+        status,
+        attempts: job.attempts,
+        run_at,
+        last_error: Some(error),
+    }
+}
+
+fn push_job_filters(qb: &mut QueryBuilder<'_, Db>, status: Option<&str>, job_type: Option<&str>) {
+    qb.push(" WHERE 1=1");
+    if let Some(status) = status {
+        qb.push(" AND status = ");
+        qb.push_bind(status.to_string());
+    }
+    if let Some(job_type) = job_type {
+        qb.push(" AND job_type = ");
+        qb.push_bind(job_type.to_string());
+    }
+}
+
+/// Makes the recurring job `job_type` due now, for work that should not wait
+/// for its next regular run.
+pub async fn run_now(pool: &DbPool, job_type: &str) {
+    let now = Utc::now();
+    let res = sqlx::query(
+        r#"
+        UPDATE background_jobs
+        SET run_at = $2, updated_at = $2
+        WHERE job_type = $1
+          AND recurring_secs IS NOT NULL
+          AND status IN ('queued', 'failed')
+          AND run_at > $2
+    "#,
+    )
+    .bind(job_type)
+    .bind(now)
+    .execute(pool)
+    .await;
+    if let Err(e) = res {
+        warn!("Failed to bring job {} forward: {}", job_type, e);
+    }
+    WORKER_WAKE.notify_one();
+}
+
+/// Deletes one-off jobs that finished more than `FINISHED_RETENTION_DAYS` ago.
+struct PurgeFinishedJobs {
+    pool: DbPool,
+}
+
+#[async_trait]
+impl JobHandler for PurgeFinishedJobs {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        let cutoff = Utc::now() - Duration::days(FINISHED_RETENTION_DAYS);
+        let purged = sqlx::query(
+            r#"
+            DELETE FROM background_jobs
+            WHERE recurring_secs IS NULL
+              AND status IN ('succeeded', 'dead')
+              AND finished_at < $1
+        "#,
+        )
+        .bind(cutoff)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        if purged > 0 {
+            info!("Purged {} finished background job(s)", purged);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct JobQueue {
+    pool: DbPool,
+    worker_id: String,
+    handlers: Arc<RwLock<HashMap<String, Arc<dyn JobHandler>>>>,
+}
+
+impl JobQueue {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            worker_id: format!("worker-{}", &Uuid::new_v4().simple().to_string()[..12]),
+            handlers: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Runs jobs of `job_type` on this instance with `handler`.
+    pub fn register(&self, job_type: &str, handler: Arc<dyn JobHandler>) {
+        if let Ok(mut handlers) = self.handlers.write() {
+            handlers.insert(job_type.to_string(), handler);
+        }
+    }
+
+    /// Registers `handler` and makes sure `job_type` is queued to run every
+    /// `every`. A job that already exists keeps its next run time.
+    pub async fn register_recurring(
+        &self,
+        job_type: &str,
+        every: std::time::Duration,
+        handler: Arc<dyn JobHandler>,
+    ) {
+        self.register(job_type, handler);
+
+        let now = Utc::now();
+        let secs = every.as_secs().clamp(1, i32::MAX as u64) as i32;
+        let res = sqlx::query(
+            r#"
+            INSERT INTO background_jobs
+                (id, job_type, payload, status, recurring_secs, attempts, max_attempts,
+                 run_at, created_at, updated_at)
+            VALUES ($1, $2, $3, 'queued', $4, 0, $5, $6, $6, $6)
+            ON CONFLICT (job_type) WHERE recurring_secs IS NOT NULL
+            DO UPDATE SET recurring_secs = excluded.recurring_secs,
+                          updated_at = excluded.updated_at
+        "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(job_type)
+        .bind(serde_json::json!({}))
+        .bind(secs)
+        .bind(DEFAULT_MAX_ATTEMPTS)
+        .bind(now)
+        .execute(&self.pool)
+        .await;
+        if let Err(e) = res {
+            warn!("Failed to schedule recurring job {}: {}", job_type, e);
+        }
+    }
+
+    /// Queues a one-off job, to run at `run_at` or as soon as possible.
+    pub async fn enqueue(
+        &self,
+        job_type: &str,
+        payload: serde_json::Value,
+        run_at: Option<DateTime<Utc>>,
+    ) -> AppResult<String> {
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO background_jobs
+                (id, job_type, payload, status, attempts, max_attempts,
+                 run_at, created_at, updated_at)
+            VALUES ($1, $2, $3, 'queued', 0, $4, $5, $6, $6)
+        "#,
+        )
+        .bind(&id)
+        .bind(job_type)
+        .bind(payload)
+        .bind(DEFAULT_MAX_ATTEMPTS)
+        .bind(run_at.unwrap_or(now))
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        if run_at.is_none_or(|at| at <= now) {
+            WORKER_WAKE.notify_one();
+        }
+        Ok(id)
+    }
+
+    /// Starts this instance's worker. Register handlers first: the worker
+    /// only claims job types it has a handler for.
+    pub async fn start(&self) {
+        self.register_recurring(
+            PURGE_JOB,
+            std::time::Duration::from_secs(24 * 3600),
+            Arc::new(PurgeFinishedJobs {
+                pool: self.pool.clone(),
+            }),
+        )
+        .await;
+
+        let this = self.clone();
+        tokio::spawn(async move {
+            info!("Background job worker {} started.", this.worker_id);
+            let slots = Arc::new(Semaphore::new(MAX_CONCURRENT_JOBS));
+            let mut interval =
+                tokio::time::interval(tokio::time::Duration::from_secs(POLL_INTERVAL_SECS));
+            let mut warned_claim_failure = false;
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = WORKER_WAKE.notified() => {}
+                }
+
+                let free = slots.available_permits();
+                if free == 0 {
+                    continue;
+                }
+
+                let jobs = match this.claim(free as i64).await {
+                    Ok(jobs) => {
+                        warned_claim_failure = false;
+                        jobs
+                    }
+                    Err(e) => {
+                        if !warned_claim_failure {
+                            warned_claim_failure = true;
+                            warn!("Background jobs not claimed: {}", e);
+                        }
+                        continue;
+                    }
+                };
+
+                for job in jobs {
+                    let Ok(permit) = slots.clone().acquire_owned().await else {
+                        break;
+                    };
+                    let this = this.clone();
+                    tokio::spawn(async move {
+                        this.execute(job).await;
+                        drop(permit);
+                    });
+                }
+            }
+        });
+    }
+
+    fn job_types(&self) -> Vec<String> {
+        self.handlers
+            .read()
+            .map(|handlers| handlers.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Marks up to `limit` due jobs as running on this worker and returns them.
+    async fn claim(&self, limit: i64) -> AppResult<Vec<BackgroundJob>> {
+        let job_types = self.job_types();
+        if job_types.is_empty() {
+            return Ok(Vec::new());
+        }
+        let now = Utc::now();
+
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new(
+            "UPDATE background_jobs SET status = 'running', attempts = attempts + 1, locked_at = ",
+        );
+        qb.push_bind(now);
+        qb.push(", locked_by = ");
+        qb.push_bind(self.worker_id.clone());
+        qb.push(", last_run_at = ");
+        qb.push_bind(now);
+        qb.push(", updated_at = ");
+        qb.push_bind(now);
+        qb.push(
+            " WHERE id IN (SELECT id FROM background_jobs \
+             WHERE ((status IN ('queued', 'failed') AND run_at <= ",
+        );
+        qb.push_bind(now);
+        qb.push(") OR (status = 'running' AND locked_at < ");
+        qb.push_bind(now - Duration::seconds(STALE_LOCK_SECS));
+        qb.push(")) AND job_type IN (");
+        let mut types = qb.separated(", ");
+        for job_type in job_types {
+            types.push_bind(job_type);
+        }
+        qb.push(") ORDER BY run_at LIMIT ");
+        qb.push_bind(limit);
+        // Other instances skip the rows this one is claiming instead of
+        // waiting for them.
+        #[cfg(feature = "postgres")]
+        qb.push(" FOR UPDATE SKIP LOCKED");
+        qb.push(") RETURNING ");
+        qb.push(JOB_COLUMNS);
+
+        Ok(qb.build_query_as().fetch_all(&self.pool).await?)
+    }
+
+    async fn execute(&self, job: BackgroundJob) {
+        let handler = self
+            .handlers
+            .read()
+            .ok()
+            .and_then(|handlers| handlers.get(&job.job_type).cloned());
+
+        let result = match handler {
+            Some(handler) => {
+                let claimed = job.clone();
+                // A panicking handler fails its job instead of leaving it running.
+                match tokio::spawn(async move { handler.run(&claimed).await }).await {
+                    Ok(res) => res.map_err(|e| e.to_string()),
+                    Err(e) => Err(format!("Job panicked: {}", e)),
+                }
+            }
+            None => Err(format!("No handler registered for {}", job.job_type)),
+        };
+
+        if let Err(e) = &result {
+            warn!(
+                "Job {} ({}) failed on attempt {}: {}",
+                job.job_type, job.id, job.attempts, e
+            );
+        }
+
+        let now = Utc::now();
+        let settled = settle(&job, result.err(), now);
+        if settled.status == "dead" {
+            error!(
+                "Job {} ({}) moved to the dead-letter queue after {} attempt(s)",
+                job.job_type, job.id, job.attempts
+            );
+        }
+
+        let res = sqlx::query(
+            r#"
+            UPDATE background_jobs
+            SET status = $2, attempts = $3, run_at = $4, last_error = $5,
+                locked_at = NULL, locked_by = NULL, finished_at = $6, updated_at = $6
+            WHERE id = $1 AND locked_by = $7
+        "#,
+        )
+        .bind(&job.id)
+        .bind(settled.status)
+        .bind(settled.attempts)
+        .bind(settled.run_at)
+        .bind(settled.last_error)
+        .bind(now)
+        .bind(&self.worker_id)
+        .execute(&self.pool)
+        .await;
+        if let Err(e) = res {
+            error!("Failed to record outcome of job {}: {}", job.id, e);
+        }
+    }
+
+    pub async fn list(
+        &self,
+        status: Option<&str>,
+        job_type: Option<&str>,
+        page: u32,
+        per_page: u32,
+    ) -> AppResult<PaginatedResponse<BackgroundJob>> {
+        let page = page.max(1);
+        let per_page = per_page.clamp(1, MAX_PER_PAGE);
+
+        let mut count_qb: QueryBuilder<Db> =
+            QueryBuilder::new("SELECT COUNT(*) FROM background_jobs");
+        push_job_filters(&mut count_qb, status, job_type);
+        let total: i64 = count_qb.build_query_scalar().fetch_one(&self.pool).await?;
+
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new("SELECT ");
+        qb.push(JOB_COLUMNS);
+        qb.push(" FROM background_jobs");
+        push_job_filters(&mut qb, status, job_type);
+        qb.push(" ORDER BY updated_at DESC LIMIT ");
+        qb.push_bind(per_page as i64);
+        qb.push(" OFFSET ");
+        qb.push_bind(((page - 1) * per_page) as i64);
+        let data: Vec<BackgroundJob> = qb.build_query_as().fetch_all(&self.pool).await?;
+
+        Ok(PaginatedResponse {
+            data,
+            total,
+            page,
+            per_page,
+        })
+    }
+
+    pub async fn stats(&self) -> AppResult<BackgroundJobStats> {
+        let rows: Vec<(String, i64)> =
+            sqlx::query_as("SELECT status, COUNT(*) FROM background_jobs GROUP BY status")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut stats = BackgroundJobStats::default();
+        for (status, count) in rows {
+            match status.as_str() {
+                "queued" => stats.queued = count,
+                "running" => stats.running = count,
+                "succeeded" => stats.succeeded = count,
+                "failed" => stats.failed = count,
+                "dead" => stats.dead = count,
+                _ => {}
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Every recurring job, i.e. the state of each scheduler.
+    pub async fn recurring(&self) -> AppResult<Vec<BackgroundJob>> {
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new("SELECT ");
+        qb.push(JOB_COLUMNS);
+        qb.push(" FROM background_jobs WHERE recurring_secs IS NOT NULL ORDER BY job_type");
+        Ok(qb.build_query_as().fetch_all(&self.pool).await?)
+    }
+
+    /// Queues a failed, dead or waiting job to run now with a fresh set of
+    /// attempts.
+    pub async fn retry(&self, id: &str) -> AppResult<BackgroundJob> {
+        let now = Utc::now();
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new(
+            "UPDATE background_jobs SET status = 'queued', attempts = 0, run_at = ",
+        );
+        qb.push_bind(now);
+        qb.push(", updated_at = ");
+        qb.push_bind(now);
+        qb.push(" WHERE id = ");
+        qb.push_bind(id.to_string());
+        qb.push(" AND status IN ('queued', 'failed', 'dead') RETURNING ");
+        qb.push(JOB_COLUMNS);
+
+        let job: Option<BackgroundJob> = qb.build_query_as().fetch_optional(&self.pool).await?;
+        if let Some(job) = job {
+            WORKER_WAKE.notify_one();
+            return Ok(job);
+        }
+
+        let exists: Option<String> =
+            sqlx::query_scalar("SELECT status FROM background_jobs WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+        match exists {
+            Some(status) => Err(AppError::Conflict(format!(
+                "A {} job cannot be retried",
+                status
+            ))),
+            None => Err(AppError::NotFound("Job not found".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(recurring_secs: Option<i32>, attempts: i32) -> BackgroundJob {
+        let at = DateTime::parse_from_rfc3339("2026-04-22T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        BackgroundJob {
+            id: "job-1".to_string(),
+            job_type: "test.job".to_string(),
+            payload: serde_json::json!({}),
+            status: "running".to_string(),
+            recurring_secs,
+            attempts,
+            max_attempts: 3,
+            run_at: at,
+            locked_at: Some(at),
+            locked_by: Some("worker-1".to_string()),
+            last_error: None,
+            last_run_at: Some(at),
+            finished_at: None,
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(1), Duration::seconds(30));
+        assert_eq!(backoff(2), Duration::seconds(60));
+        assert_eq!(backoff(4), Duration::seconds(240));
+        assert_eq!(backoff(20), Duration::seconds(MAX_BACKOFF_SECS));
+    }
+
+    #[test]
+    fn one_off_jobs_retry_then_go_dead() {
+        let now = Utc::now();
+
+        let done = settle(&job(None, 1), None, now);
+        assert_eq!(done.status, "succeeded");
+
+        let retry = settle(&job(None, 2), Some("timeout".to_string()), now);
+        assert_eq!(retry.status, "failed");
+        assert_eq!(retry.run_at, now + Duration::seconds(60));
+        assert_eq!(retry.last_error.as_deref(), Some("timeout"));
+
+        let dead = settle(&job(None, 3), Some("timeout".to_string()), now);
+        assert_eq!(dead.status, "dead");
+        assert_eq!(dead.attempts, 3);
+    }
+
+    #[test]
+    fn recurring_jobs_requeue_and_never_go_dead() {
+        let now = Utc::now();
+
+        let done = settle(&job(Some(600), 2), None, now);
+        assert_eq!(done.status, "queued");
+        assert_eq!(done.attempts, 0);
+        assert_eq!(done.run_at, now + Duration::seconds(600));
+
+        // Retried no later than the next regular run.
+        let failed = settle(&job(Some(60), 9), Some("db down".to_string()), now);
+        assert_eq!(failed.status, "failed");
+        assert_eq!(failed.run_at, now + Duration::seconds(60));
+    }
+}
@@ -0,0 +1,182 @@
+//! Leader election for background loops that must run on one instance.
+//!
+//! Work queued in the background job queue needs none of this: each run is
+//! claimed by a single worker. Loops that run on their own timer (billing
+//! runs, SLA reminders, the event dispatcher, the Telegram poller, ...) would
+//! otherwise run once per instance, so each asks its `LeaderLock` before
+//! every pass and skips the pass when another instance leads.
+//!
+//! Leading means holding a Postgres session advisory lock named after the
+//! loop. All locks of an instance are held on one connection of its own,
+//! outside the pool, so leading many loops costs one connection. When that
+//! connection drops (database restart, network) Postgres releases the locks
+//! and the loops are up for election again; when the leading instance stops,
+//! another one takes over on its next pass.
+//!
+//! Leadership is never handed over otherwise: an instance whose loop hangs
+//! mid-pass keeps the lead, and the other instances keep skipping that loop,
+//! until the process exits or its lock connection drops. Every round trip on
+//! the lock connection is capped at a few seconds so that a slow or
+//! unreachable database does not hold up the loops queued behind it.
+//!
+//! SQLite deployments are a single instance, which always leads.
+
+use crate::db::DbPool;
+#[cfg(feature = "postgres")]
+use once_cell::sync::Lazy;
+#[cfg(feature = "postgres")]
+use sqlx::{Connection, PgConnection};
+#[cfg(feature = "postgres")]
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::Duration;
+#[cfg(feature = "postgres")]
+use tokio::sync::Mutex;
+#[cfg(feature = "postgres")]
+use tracing::{info, warn};
+
+/// The connection holding this instance's locks, and the loops it leads.
+#[cfg(feature = "postgres")]
+#[derive(Default)]
+struct Session {
+    conn: Option<PgConnection>,
+    leading: HashSet<&'static str>,
+}
+
+#[cfg(feature = "postgres")]
+static SESSION: Lazy<Mutex<Session>> = Lazy::new(|| Mutex::new(Session::default()));
+
+/// Limit on each connect, ping or lock query of the session. All loops share
+/// the session, so this is also the longest one loop waits for another.
+#[cfg(feature = "postgres")]
+const SESSION_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// `fut`'s result, or an error once `limit` has passed.
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+async fn bounded<T, E: std::fmt::Display>(
+    limit: Duration,
+    fut: impl Future<Output = Result<T, E>>,
+) -> Result<T, String> {
+    match tokio::time::timeout(limit, fut).await {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(_) => Err(format!("timed out after {}s", limit.as_secs_f32())),
+    }
+}
+
+#[derive(Clone)]
+pub struct LeaderLock {
+    #[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+    pool: DbPool,
+    name: &'static str,
+}
+
+impl LeaderLock {
+    /// `name` identifies the loop; every instance must use the same one.
+    pub fn new(pool: DbPool, name: &'static str) -> Self {
+        Self { pool, name }
+    }
+
+    /// Whether this instance leads the loop, taking the lead if nobody has
+    /// it. Call before every pass; leadership can be lost in between.
+    pub async fn acquire(&self) -> bool {
+        #[cfg(feature = "postgres")]
+        {
+            let mut session = SESSION.lock().await;
+
+            if let Some(conn) = session.conn.as_mut() {
+                if let Err(e) = bounded(SESSION_TIMEOUT, conn.ping()).await {
+                    // The locks go with the connection.
+                    let lost: Vec<&str> = session.leading.drain().collect();
+                    session.conn = None;
+                    if !lost.is_empty() {
+                        warn!(
+                            "Lost the leader lock session ({}); no longer leading {:?}",
+                            e, lost
+                        );
+                    }
+                }
+            }
+            if session.leading.contains(self.name) {
+                return true;
+            }
+
+            if session.conn.is_none() {
+                let connect = PgConnection::connect_with(&self.pool.connect_options());
+                match bounded(SESSION_TIMEOUT, connect).await {
+                    Ok(conn) => session.conn = Some(conn),
+                    Err(e) => {
+                        warn!(
+                            "{} skipped: leader lock connection failed: {}",
+                            self.name, e
+                        );
+                        return false;
+                    }
+                }
+            }
+            let Some(conn) = session.conn.as_mut() else {
+                return false;
+            };
+            let query = sqlx::query_scalar::<_, bool>("SELECT pg_try_advisory_lock(hashtext($1))")
+                .bind(self.name)
+                .fetch_one(conn);
+            let locked = match bounded(SESSION_TIMEOUT, query).await {
+                Ok(locked) => locked,
+                Err(e) => {
+                    // The lock may or may not have been taken; dropping the
+                    // connection settles it, for every loop it held.
+                    let lost: Vec<&str> = session.leading.drain().collect();
+                    session.conn = None;
+                    warn!(
+                        "{} skipped: leader lock query failed ({}); no longer leading {:?}",
+                        self.name, e, lost
+                    );
+                    return false;
+                }
+            };
+            if locked {
+                info!("This instance now leads {}", self.name);
+                session.leading.insert(self.name);
+            }
+            locked
+        }
+
+        #[cfg(not(feature = "postgres"))]
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bounded() {
+        let fast = bounded(Duration::from_secs(1), async { Ok::<_, String>(7) }).await;
+        assert_eq!(fast, Ok(7));
+
+        let failed = bounded(Duration::from_secs(1), async {
+            Err::<(), _>("connection refused")
+        })
+        .await;
+        assert_eq!(failed, Err("connection refused".to_string()));
+
+        let slow = bounded(Duration::from_millis(10), async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok::<_, String>(())
+        })
+        .await;
+        assert!(slow.unwrap_err().contains("timed out"));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_sqlite_always_leads() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .connect_lazy("sqlite::memory:")
+            .unwrap();
+        let a = LeaderLock::new(pool.clone(), "test_loop");
+        let b = LeaderLock::new(pool, "test_loop");
+        assert!(a.acquire().await);
+        assert!(b.acquire().await);
+    }
+}
@@ -0,0 +1,1476 @@
+//! Migration from other ISP billing systems.
+//!
+//! An adapter per source names the CSV exports it takes and the columns read
+//! from each: PHPNuxBill (phpMyAdmin exports of its tables), Mikbill (`users`
+//! and `packets`) and a generic layout for everything else. Rows are parsed
+//! into one intermediate shape and checked against each other and against
+//! the tenant's data. The result is a report; nothing is written until a
+//! request asks to commit and its report has no errors, and then everything
+//! goes in one transaction.
+//!
+//! Records that already exist are reused, so running the same import twice
+//! only adds what is missing: packages match by name, customers by email or
+//! phone, secrets by username on the router and unpaid balances by their
+//! `MIG-` invoice number.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    LegacyImportCounts, LegacyImportFile, LegacyImportIssue, LegacyImportReport,
+    LegacyImportRequest, LegacyImportSource,
+};
+use crate::security::secret::encrypt_secret_for;
+use crate::services::money;
+use crate::services::pppoe_service::PURPOSE_PPPOE;
+use crate::services::{AuditService, AuthService};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+const MAX_ROWS: usize = 50_000;
+
+/// `(field, columns)`: the first listed column present in the export wins.
+type Fields = &'static [(&'static str, &'static [&'static str])];
+
+struct Adapter {
+    code: &'static str,
+    name: &'static str,
+    /// `(file, source table, fields)` for each export the source takes.
+    files: &'static [(&'static str, &'static str, Fields)],
+    /// Balance `status` values that mean unpaid; empty when every row is.
+    unpaid_statuses: &'static [&'static str],
+}
+
+impl Adapter {
+    fn fields(&self, file: &str) -> Option<Fields> {
+        self.files
+            .iter()
+            .find(|(f, _, _)| *f == file)
+            .map(|(_, _, fields)| *fields)
+    }
+}
+
+const ADAPTERS: &[Adapter] = &[
+    Adapter {
+        code: "phpnuxbill",
+        name: "PHPNuxBill",
+        files: &[
+            (
+                "customers",
+                "tbl_customers",
+                &[
+                    ("key", &["id"]),
+                    ("name", &["fullname"]),
+                    ("email", &["email"]),
+                    ("phone", &["phonenumber"]),
+                    ("address", &["address"]),
+                    ("password", &["pppoe_password", "password"]),
+                ],
+            ),
+            (
+                "packages",
+                "tbl_plans",
+                &[
+                    ("key", &["id"]),
+                    ("name", &["name_plan"]),
+                    ("price", &["price"]),
+                    ("validity", &["validity"]),
+                    ("cycle", &["validity_unit"]),
+                ],
+            ),
+            (
+                "secrets",
+                "tbl_user_recharges",
+                &[
+                    ("customer", &["customer_id"]),
+                    ("username", &["username"]),
+                    ("package", &["plan_id", "namebp"]),
+                    ("disabled", &["status"]),
+                ],
+            ),
+            (
+                "balances",
+                "tbl_payment_gateway",
+                &[
+                    ("customer", &["username"]),
+                    ("amount", &["price"]),
+                    ("due", &["created_date"]),
+                    ("reference", &["id"]),
+                    ("status", &["status"]),
+                ],
+            ),
+        ],
+        unpaid_statuses: &["1"],
+    },
+    Adapter {
+        code: "mikbill",
+        name: "Mikbill",
+        files: &[
+            (
+                "customers",
+                "users",
+                &[
+                    ("key", &["uid"]),
+                    ("name", &["fio"]),
+                    ("email", &["email"]),
+                    ("phone", &["mob_tel", "sms_tel", "phone"]),
+                    ("address", &["address"]),
+                    ("username", &["user"]),
+                    ("password", &["password"]),
+                    ("package", &["gid"]),
+                    ("remote_address", &["framed_ip", "local_ip"]),
+                    ("disabled", &["blocked"]),
+                    ("deposit", &["deposit"]),
+                ],
+            ),
+            (
+                "packages",
+                "packets",
+                &[
+                    ("key", &["gid"]),
+                    ("name", &["packet"]),
+                    ("price", &["fixed_cost"]),
+                ],
+            ),
+        ],
+        unpaid_statuses: &[],
+    },
+    Adapter {
+        code: "csv",
+        name: "Generic CSV",
+        files: &[
+            (
+                "customers",
+                "customers.csv",
+                &[
+                    ("key", &["customer_id", "id"]),
+                    ("name", &["name"]),
+                    ("email", &["email"]),
+                    ("phone", &["phone"]),
+                    ("address", &["address"]),
+                    ("username", &["username"]),
+                    ("password", &["password"]),
+                    ("package", &["package_id", "package"]),
+                    ("remote_address", &["remote_address"]),
+                    ("disabled", &["disabled"]),
+                    ("balance", &["balance", "unpaid"]),
+                ],
+            ),
+            (
+                "packages",
+                "packages.csv",
+                &[
+                    ("key", &["package_id", "id"]),
+                    ("name", &["name"]),
+                    ("price", &["price"]),
+                    ("cycle", &["billing_cycle"]),
+                ],
+            ),
+            (
+                "secrets",
+                "secrets.csv",
+                &[
+                    ("customer", &["customer_id"]),
+                    ("username", &["username"]),
+                    ("password", &["password"]),
+                    ("package", &["package_id", "package"]),
+                    ("remote_address", &["remote_address"]),
+                    ("disabled", &["disabled"]),
+                ],
+            ),
+            (
+                "balances",
+                "balances.csv",
+                &[
+                    ("customer", &["customer_id"]),
+                    ("amount", &["amount"]),
+                    ("due", &["due_date"]),
+                    ("reference", &["reference", "invoice_number"]),
+                ],
+            ),
+        ],
+        unpaid_statuses: &[],
+    },
+];
+
+/// Fields a file is unusable without.
+const REQUIRED: &[(&str, &[&str])] = &[
+    ("customers", &["key", "name"]),
+    ("packages", &["key", "name", "price"]),
+    ("secrets", &["customer", "username"]),
+    ("balances", &["customer", "amount"]),
+];
+
+fn adapter(code: &str) -> AppResult<&'static Adapter> {
+    let code = code.trim().to_ascii_lowercase();
+    ADAPTERS.iter().find(|a| a.code == code).ok_or_else(|| {
+        AppError::Validation(format!(
+            "Import source must be one of: {}",
+            ADAPTERS
+                .iter()
+                .map(|a| a.code)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    })
+}
+
+// ---------------------------------------------------------------------------
+// CSV
+
+struct CsvTable {
+    columns: HashMap<String, usize>,
+    /// Line of the row's first character, and its cells.
+    rows: Vec<(usize, Vec<String>)>,
+}
+
+/// Reads CSV as written by phpMyAdmin, spreadsheets and most billing
+/// exports: `,`, `;` or tab separated (guessed from the header), quoted
+/// cells with `""` escapes and line breaks, an optional BOM.
+fn read_csv(text: &str) -> Result<CsvTable, String> {
+    let text = text.trim_start_matches('\u{feff}');
+    let header_line = text.lines().next().unwrap_or_default();
+    let delimiter = [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|d| header_line.matches(*d).count())
+        .unwrap_or(',');
+
+    let mut records: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut cells: Vec<String> = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if cell.is_empty() => quoted = true,
+            '\n' if quoted => {
+                line += 1;
+                cell.push(c);
+            }
+            '\r' if !quoted => {}
+            '\n' => {
+                cells.push(std::mem::take(&mut cell));
+                records.push((start, std::mem::take(&mut cells)));
+                line += 1;
+                start = line;
+            }
+            c if c == delimiter && !quoted => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    if quoted {
+        return Err(format!("Unclosed quote in the row at line {}", start));
+    }
+    if !cell.is_empty() || !cells.is_empty() {
+        cells.push(cell);
+        records.push((start, cells));
+    }
+    records.retain(|(_, cells)| cells.iter().any(|c| !c.trim().is_empty()));
+
+    let mut records = records.into_iter();
+    let (_, header) = records.next().ok_or("The file is empty")?;
+    let columns = header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.trim().trim_matches('`').to_ascii_lowercase(), i))
+        .collect();
+    Ok(CsvTable {
+        columns,
+        rows: records.collect(),
+    })
+}
+
+impl CsvTable {
+    fn column(&self, fields: Fields, field: &str) -> Option<usize> {
+        let (_, names) = fields.iter().find(|(f, _)| *f == field)?;
+        names.iter().find_map(|n| self.columns.get(*n).copied())
+    }
+
+    /// Trimmed value, `None` when empty or SQL `NULL`.
+    fn get<'a>(&self, cells: &'a [String], fields: Fields, field: &str) -> Option<&'a str> {
+        let value = cells.get(self.column(fields, field)?)?.trim();
+        (!value.is_empty() && value != "NULL").then_some(value)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Values
+
+/// `150000`, `150.000`, `Rp 150.000,00`, `1,234.50`. A lone separator
+/// followed by exactly three digits groups thousands; otherwise it is the
+/// decimal point.
+fn parse_amount(raw: &str) -> Option<f64> {
+    let cleaned: String = raw
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | '-'))
+        .collect();
+    if !cleaned.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let normalized = match (cleaned.rfind('.'), cleaned.rfind(',')) {
+        (Some(dot), Some(comma)) => {
+            let (thousands, point) = if dot > comma { (',', '.') } else { ('.', ',') };
+            cleaned.replace(thousands, "").replace(point, ".")
+        }
+        (Some(i), None) | (None, Some(i)) => {
+            let sep = cleaned[i..].chars().next().unwrap_or('.');
+            let grouped = cleaned.matches(sep).count() > 1 || cleaned.len() - i - 1 == 3;
+            if grouped {
+                cleaned.replace(sep, "")
+            } else {
+                cleaned.replace(sep, ".")
+            }
+        }
+        (None, None) => cleaned,
+    };
+    normalized.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+fn parse_flag(raw: Option<&str>) -> bool {
+    raw.is_some_and(|v| {
+        matches!(
+            v.to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "y" | "off" | "disabled" | "blocked"
+        )
+    })
+}
+
+fn parse_date(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|d| d.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|d| d.and_utc())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|d| d.and_utc())
+        })
+}
+
+/// Billing cycle from a cycle or validity unit column and an optional
+/// count: `monthly`, `1 Months`, `12 Months`, `1 Year`...
+fn parse_cycle(unit: Option<&str>, validity: Option<&str>) -> Option<&'static str> {
+    let count = match validity {
+        Some(v) => v.trim().parse::<u32>().ok()?,
+        None => 1,
+    };
+    let unit = unit.map(|u| u.trim().to_ascii_lowercase());
+    match (unit.as_deref().unwrap_or("monthly"), count) {
+        ("monthly" | "month" | "months" | "bulan", 1) => Some("monthly"),
+        ("monthly" | "month" | "months" | "bulan", 12) => Some("yearly"),
+        ("yearly" | "year" | "years" | "annual" | "tahun", 1) => Some("yearly"),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+
+#[derive(Debug)]
+struct Package {
+    key: String,
+    name: String,
+    price: f64,
+    cycle: &'static str,
+}
+
+#[derive(Debug)]
+struct Customer {
+    key: String,
+    name: String,
+    email: Option<String>,
+    phone: Option<String>,
+    address: Option<String>,
+    password: Option<String>,
+}
+
+/// A customer's service: a package to subscribe to, a PPPoE login, or both.
+#[derive(Debug)]
+struct Login {
+    customer: usize,
+    username: Option<String>,
+    password: Option<String>,
+    package: Option<usize>,
+    remote_address: Option<String>,
+    disabled: bool,
+}
+
+#[derive(Debug)]
+struct Balance {
+    customer: usize,
+    amount: f64,
+    due: Option<DateTime<Utc>>,
+    reference: String,
+}
+
+#[derive(Debug, Default)]
+struct Parsed {
+    packages: Vec<Package>,
+    customers: Vec<Customer>,
+    logins: Vec<Login>,
+    balances: Vec<Balance>,
+    issues: Vec<LegacyImportIssue>,
+}
+
+impl Parsed {
+    fn issue(&mut self, severity: &str, file: &str, line: usize, message: String) {
+        self.issues.push(LegacyImportIssue {
+            file: file.to_string(),
+            line,
+            severity: severity.to_string(),
+            message,
+        });
+    }
+
+    fn error(&mut self, file: &str, line: usize, message: String) {
+        self.issue("error", file, line, message);
+    }
+
+    fn warning(&mut self, file: &str, line: usize, message: String) {
+        self.issue("warning", file, line, message);
+    }
+
+    fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == "error")
+    }
+
+    fn package_ref(&self, reference: &str) -> Option<usize> {
+        let lower = reference.to_lowercase();
+        self.packages
+            .iter()
+            .position(|p| p.key == reference)
+            .or_else(|| {
+                self.packages
+                    .iter()
+                    .position(|p| p.name.to_lowercase() == lower)
+            })
+    }
+
+    /// By the source's customer key, else by a login username.
+    fn customer_ref(&self, reference: &str) -> Option<usize> {
+        self.customers
+            .iter()
+            .position(|c| c.key == reference)
+            .or_else(|| {
+                self.logins
+                    .iter()
+                    .find(|l| l.username.as_deref() == Some(reference))
+                    .map(|l| l.customer)
+            })
+    }
+}
+
+/// Opens one export, checking it is within limits and has the columns
+/// `file` cannot do without.
+fn open(
+    parsed: &mut Parsed,
+    adapter: &Adapter,
+    file: &'static str,
+    text: Option<&str>,
+) -> Option<(CsvTable, Fields)> {
+    let fields = adapter.fields(file)?;
+    let text = text.filter(|t| !t.trim().is_empty())?;
+    let table = match read_csv(text) {
+        Ok(table) => table,
+        Err(e) => {
+            parsed.error(file, 0, e);
+            return None;
+        }
+    };
+    if table.rows.len() > MAX_ROWS {
+        parsed.error(file, 0, format!("At most {} rows per file", MAX_ROWS));
+        return None;
+    }
+    let required = REQUIRED
+        .iter()
+        .find(|(f, _)| *f == file)
+        .map_or(&[][..], |(_, r)| *r);
+    let missing: Vec<String> = required
+        .iter()
+        .filter(|field| table.column(fields, field).is_none())
+        .map(|field| {
+            let (_, names) = fields.iter().find(|(f, _)| f == field).unwrap();
+            names.join(" or ")
+        })
+        .collect();
+    if !missing.is_empty() {
+        parsed.error(file, 1, format!("Missing column {}", missing.join(", ")));
+        return None;
+    }
+    Some((table, fields))
+}
+
+/// Turns the exports into customers, packages, logins and balances, with
+/// every problem found on the way. Touches no database.
+fn parse(adapter: &Adapter, req: &LegacyImportRequest) -> Parsed {
+    let mut parsed = Parsed::default();
+
+    if let Some((table, fields)) = open(
+        &mut parsed,
+        adapter,
+        "packages",
+        req.packages_csv.as_deref(),
+    ) {
+        for (line, cells) in &table.rows {
+            let (line, get) = (*line, |field: &str| table.get(cells, fields, field));
+            let (Some(key), Some(name)) = (get("key"), get("name")) else {
+                parsed.error(
+                    "packages",
+                    line,
+                    "Package without an id or name".to_string(),
+                );
+                continue;
+            };
+            if parsed.packages.iter().any(|p| p.key == key) {
+                parsed.error("packages", line, format!("Duplicate package id {}", key));
+                continue;
+            }
+            // Package names are unique per tenant here, unlike in most sources.
+            if parsed
+                .packages
+                .iter()
+                .any(|p| p.name.to_lowercase() == name.to_lowercase())
+            {
+                parsed.error("packages", line, format!("Duplicate package name {}", name));
+                continue;
+            }
+            let Some(price) = get("price").and_then(parse_amount).filter(|p| *p >= 0.0) else {
+                parsed.error(
+                    "packages",
+                    line,
+                    format!("Package {} has no valid price", name),
+                );
+                continue;
+            };
+            let cycle = parse_cycle(get("cycle"), get("validity")).unwrap_or_else(|| {
+                parsed.warning(
+                    "packages",
+                    line,
+                    format!(
+                        "Package {} is billed every {} {}; imported as monthly",
+                        name,
+                        get("validity").unwrap_or("1"),
+                        get("cycle").unwrap_or_default()
+                    ),
+                );
+                "monthly"
+            });
+            parsed.packages.push(Package {
+                key: key.to_string(),
+                name: name.to_string(),
+                price,
+                cycle,
+            });
+        }
+    }
+
+    let Some((table, fields)) = open(
+        &mut parsed,
+        adapter,
+        "customers",
+        req.customers_csv.as_deref(),
+    ) else {
+        if !parsed.issues.iter().any(|i| i.file == "customers") {
+            parsed.error(
+                "customers",
+                0,
+                "The customers export is required".to_string(),
+            );
+        }
+        return parsed;
+    };
+    for (line, cells) in &table.rows {
+        let (line, get) = (*line, |field: &str| table.get(cells, fields, field));
+        let (Some(key), Some(name)) = (get("key"), get("name")) else {
+            parsed.error(
+                "customers",
+                line,
+                "Customer without an id or name".to_string(),
+            );
+            continue;
+        };
+        if parsed.customers.iter().any(|c| c.key == key) {
+            parsed.error("customers", line, format!("Duplicate customer id {}", key));
+            continue;
+        }
+        let email = get("email").map(str::to_lowercase);
+        if let Some(bad) = email.as_deref().filter(|e| !e.contains('@')) {
+            parsed.warning(
+                "customers",
+                line,
+                format!("'{}' is not an email address; left empty", bad),
+            );
+        }
+        let customer = parsed.customers.len();
+        parsed.customers.push(Customer {
+            key: key.to_string(),
+            name: name.to_string(),
+            email: email.filter(|e| e.contains('@')),
+            phone: get("phone").map(str::to_string),
+            address: get("address").map(str::to_string),
+            password: get("password").map(str::to_string),
+        });
+
+        if get("username").is_some() || get("package").is_some() {
+            let login = login(
+                &mut parsed,
+                "customers",
+                line,
+                customer,
+                get("package"),
+                &get,
+            );
+            parsed.logins.push(login);
+        }
+        let owed = match (get("balance"), get("deposit")) {
+            (Some(raw), _) => parse_amount(raw).ok_or(raw),
+            (None, Some(raw)) => parse_amount(raw).map(|d| -d).ok_or(raw),
+            (None, None) => Ok(0.0),
+        };
+        match owed {
+            Ok(amount) if amount > 0.0 => parsed.balances.push(Balance {
+                customer,
+                amount,
+                due: None,
+                reference: key.to_string(),
+            }),
+            Ok(_) => {}
+            Err(raw) => parsed.error("customers", line, format!("'{}' is not an amount", raw)),
+        }
+    }
+
+    if let Some((table, fields)) = open(&mut parsed, adapter, "secrets", req.secrets_csv.as_deref())
+    {
+        for (line, cells) in &table.rows {
+            let (line, get) = (*line, |field: &str| table.get(cells, fields, field));
+            let reference = get("customer").unwrap_or_default();
+            let Some(customer) = parsed.customer_ref(reference) else {
+                parsed.error("secrets", line, format!("Customer {} not found", reference));
+                continue;
+            };
+            let login = login(&mut parsed, "secrets", line, customer, get("package"), &get);
+            parsed.logins.push(login);
+        }
+    }
+
+    if let Some((table, fields)) = open(
+        &mut parsed,
+        adapter,
+        "balances",
+        req.balances_csv.as_deref(),
+    ) {
+        for (line, cells) in &table.rows {
+            let (line, get) = (*line, |field: &str| table.get(cells, fields, field));
+            if !adapter.unpaid_statuses.is_empty()
+                && !get("status").is_some_and(|s| adapter.unpaid_statuses.contains(&s))
+            {
+                continue;
+            }
+            let reference = get("customer").unwrap_or_default();
+            let Some(customer) = parsed.customer_ref(reference) else {
+                parsed.error(
+                    "balances",
+                    line,
+                    format!("Customer {} not found", reference),
+                );
+                continue;
+            };
+            let raw = get("amount").unwrap_or_default();
+            let Some(amount) = parse_amount(raw) else {
+                parsed.error("balances", line, format!("'{}' is not an amount", raw));
+                continue;
+            };
+            if amount <= 0.0 {
+                continue;
+            }
+            let due = get("due").and_then(|raw| {
+                parse_date(raw).or_else(|| {
+                    parsed.warning(
+                        "balances",
+                        line,
+                        format!("'{}' is not a date; due on import", raw),
+                    );
+                    None
+                })
+            });
+            parsed.balances.push(Balance {
+                customer,
+                amount,
+                due,
+                reference: get("reference")
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("B{}", line)),
+            });
+        }
+    }
+
+    parsed
+}
+
+/// A login row of `file`, checked for a unique username, a password and a
+/// known package.
+fn login<'a>(
+    parsed: &mut Parsed,
+    file: &str,
+    line: usize,
+    customer: usize,
+    package: Option<&str>,
+    get: &impl Fn(&str) -> Option<&'a str>,
+) -> Login {
+    let mut username = get("username").map(str::to_string);
+    if let Some(name) = username.as_deref() {
+        if parsed
+            .logins
+            .iter()
+            .any(|l| l.username.as_deref() == Some(name))
+        {
+            parsed.error(file, line, format!("Duplicate PPPoE username {}", name));
+            username = None;
+        }
+    }
+    let password = get("password")
+        .map(str::to_string)
+        .or_else(|| parsed.customers[customer].password.clone());
+    if let (Some(name), None) = (username.as_deref(), password.as_deref()) {
+        parsed.warning(
+            file,
+            line,
+            format!("PPPoE user {} has no password; the secret is skipped", name),
+        );
+        username = None;
+    }
+    let package = package.and_then(|reference| {
+        parsed.package_ref(reference).or_else(|| {
+            parsed.warning(
+                file,
+                line,
+                format!(
+                    "Package {} not found; no subscription is created",
+                    reference
+                ),
+            );
+            None
+        })
+    });
+    Login {
+        customer,
+        username,
+        password,
+        package,
+        remote_address: get("remote_address").map(str::to_string),
+        disabled: parse_flag(get("disabled")),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Planning and writing
+
+/// Id of each parsed record in the tenant, and whether it exists already.
+struct Plan {
+    packages: Vec<(String, bool)>,
+    customers: Vec<(String, bool)>,
+    /// `(customer, package, subscription id, exists)`.
+    subscriptions: Vec<(usize, usize, String, bool)>,
+    /// Logins to create a PPPoE secret for.
+    secrets: Vec<usize>,
+    /// `(balance, invoice number, subscription)` of invoices to create.
+    invoices: Vec<(usize, String, usize)>,
+    existing: LegacyImportCounts,
+    currency_code: String,
+}
+
+fn digits(phone: &str) -> String {
+    phone.chars().filter(char::is_ascii_digit).collect()
+}
+
+#[derive(Clone)]
+pub struct LegacyImportService {
+    pool: DbPool,
+    auth_service: AuthService,
+    audit_service: AuditService,
+}
+
+impl LegacyImportService {
+    pub fn new(pool: DbPool, auth_service: AuthService, audit_service: AuditService) -> Self {
+        Self {
+            pool,
+            auth_service,
+            audit_service,
+        }
+    }
+
+    /// The systems the importer reads and the exports each one takes.
+    pub fn sources(&self) -> Vec<LegacyImportSource> {
+        ADAPTERS
+            .iter()
+            .map(|a| LegacyImportSource {
+                code: a.code.to_string(),
+                name: a.name.to_string(),
+                files: a
+                    .files
+                    .iter()
+                    .map(|(file, table, fields)| LegacyImportFile {
+                        file: file.to_string(),
+                        table: table.to_string(),
+                        columns: fields.iter().map(|(_, names)| names.join(" / ")).collect(),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Validates the exports and reports what an import would create. With
+    /// `commit` and no errors in the report, also writes it.
+    pub async fn import(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        req: LegacyImportRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<LegacyImportReport> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "legacy_import", "manage")
+            .await?;
+        let adapter = adapter(&req.source)?;
+        let router_id = req
+            .router_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|r| !r.is_empty());
+        if let Some(router_id) = router_id {
+            let exists: Option<i32> = sqlx::query_scalar(
+                "SELECT 1 FROM mikrotik_routers WHERE tenant_id = $1 AND id = $2 AND deleted_at IS NULL",
+            )
+            .bind(tenant_id)
+            .bind(router_id)
+            .fetch_optional(&self.pool)
+            .await?;
+            if exists.is_none() {
+                return Err(AppError::Validation("Router not found".to_string()));
+            }
+        }
+
+        let mut parsed = parse(adapter, &req);
+        let plan = self.plan(tenant_id, &mut parsed, router_id).await?;
+        let mut report = LegacyImportReport {
+            source: adapter.code.to_string(),
+            committed: false,
+            created: LegacyImportCounts {
+                customers: plan.customers.iter().filter(|(_, e)| !e).count() as u32,
+                packages: plan.packages.iter().filter(|(_, e)| !e).count() as u32,
+                subscriptions: plan.subscriptions.iter().filter(|s| !s.3).count() as u32,
+                secrets: plan.secrets.len() as u32,
+                invoices: plan.invoices.len() as u32,
+            },
+            existing: plan.existing.clone(),
+            unpaid_total: money::sum(
+                plan.invoices
+                    .iter()
+                    .map(|(b, _, _)| parsed.balances[*b].amount),
+                &plan.currency_code,
+            ),
+            currency_code: plan.currency_code.clone(),
+            issues: Vec::new(),
+        };
+
+        if req.commit && !parsed.has_errors() {
+            self.write(tenant_id, adapter, &parsed, &plan, router_id)
+                .await?;
+            report.committed = true;
+            self.audit_service
+                .log(
+                    Some(actor_id),
+                    Some(tenant_id),
+                    "LEGACY_IMPORT",
+                    "customers",
+                    None,
+                    Some(&format!(
+                        "Imported from {}: {} customers, {} packages, {} subscriptions, {} PPPoE secrets, {} unpaid invoices",
+                        adapter.name,
+                        report.created.customers,
+                        report.created.packages,
+                        report.created.subscriptions,
+                        report.created.secrets,
+                        report.created.invoices
+                    )),
+                    ip_address,
+                )
+                .await;
+        }
+        report.issues = parsed.issues;
+        Ok(report)
+    }
+
+    /// Matches the parsed records against the tenant's data.
+    async fn plan(
+        &self,
+        tenant_id: &str,
+        parsed: &mut Parsed,
+        router_id: Option<&str>,
+    ) -> AppResult<Plan> {
+        let mut existing = LegacyImportCounts::default();
+
+        let currency_code: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT value FROM settings
+            WHERE (tenant_id = $1 AND key = 'currency_code')
+               OR (tenant_id IS NULL AND key = 'base_currency_code')
+            ORDER BY tenant_id NULLS LAST
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let currency_code = currency_code
+            .map(|c| c.trim().to_uppercase())
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| "IDR".to_string());
+
+        let current: Vec<(String, String)> = sqlx::query_as(
+            "SELECT id, name FROM isp_packages WHERE tenant_id = $1 AND deleted_at IS NULL",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let by_name: HashMap<String, String> = current
+            .into_iter()
+            .map(|(id, name)| (name.to_lowercase(), id))
+            .collect();
+        let packages: Vec<(String, bool)> = parsed
+            .packages
+            .iter()
+            .map(|p| match by_name.get(&p.name.to_lowercase()) {
+                Some(id) => (id.clone(), true),
+                None => (Uuid::new_v4().to_string(), false),
+            })
+            .collect();
+        existing.packages = packages.iter().filter(|(_, e)| *e).count() as u32;
+
+        let current: Vec<(String, Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT id, email, phone FROM customers WHERE tenant_id = $1 AND deleted_at IS NULL",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut by_email = HashMap::new();
+        let mut by_phone = HashMap::new();
+        for (id, email, phone) in current {
+            if let Some(email) = email.filter(|e| !e.trim().is_empty()) {
+                by_email.insert(email.trim().to_lowercase(), id.clone());
+            }
+            if let Some(phone) = phone.map(|p| digits(&p)).filter(|p| p.len() >= 6) {
+                by_phone.insert(phone, id);
+            }
+        }
+        let customers: Vec<(String, bool)> = parsed
+            .customers
+            .iter()
+            .map(|c| {
+                let email = c.email.as_ref().and_then(|e| by_email.get(e));
+                let phone = c.phone.as_ref().and_then(|p| by_phone.get(&digits(p)));
+                match email.or(phone) {
+                    Some(id) => (id.clone(), true),
+                    None => (Uuid::new_v4().to_string(), false),
+                }
+            })
+            .collect();
+        existing.customers = customers.iter().filter(|(_, e)| *e).count() as u32;
+
+        let current: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT id, customer_id, package_id FROM customer_subscriptions WHERE tenant_id = $1 AND status <> 'cancelled'",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut subscriptions: Vec<(usize, usize, String, bool)> = Vec::new();
+        for login in &parsed.logins {
+            let Some(package) = login.package else {
+                continue;
+            };
+            if subscriptions
+                .iter()
+                .any(|s| s.0 == login.customer && s.1 == package)
+            {
+                continue;
+            }
+            let (customer_id, package_id) = (&customers[login.customer].0, &packages[package].0);
+            let found = current
+                .iter()
+                .find(|(_, c, p)| c == customer_id && p == package_id);
+            subscriptions.push(match found {
+                Some((id, _, _)) => (login.customer, package, id.clone(), true),
+                None => (login.customer, package, Uuid::new_v4().to_string(), false),
+            });
+        }
+        existing.subscriptions = subscriptions.iter().filter(|s| s.3).count() as u32;
+
+        let mut secrets = Vec::new();
+        let with_secret = parsed
+            .logins
+            .iter()
+            .filter(|l| l.username.is_some())
+            .count();
+        match router_id {
+            Some(router_id) => {
+                let current: HashSet<String> = sqlx::query_scalar(
+                    "SELECT username FROM pppoe_accounts WHERE tenant_id = $1 AND router_id = $2",
+                )
+                .bind(tenant_id)
+                .bind(router_id)
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .collect();
+                for (i, login) in parsed.logins.iter().enumerate() {
+                    match login.username.as_deref() {
+                        Some(name) if current.contains(name) => existing.secrets += 1,
+                        Some(_) => secrets.push(i),
+                        None => {}
+                    }
+                }
+            }
+            None if with_secret > 0 => parsed.warning(
+                "secrets",
+                0,
+                format!(
+                    "{} PPPoE secrets are skipped; choose the router they belong to",
+                    with_secret
+                ),
+            ),
+            None => {}
+        }
+
+        // Invoice numbers are unique across tenants, hence the tenant prefix.
+        let prefix = format!(
+            "MIG-{}-",
+            tenant_id.chars().take(8).collect::<String>().to_uppercase()
+        );
+        let current: HashSet<String> = sqlx::query_scalar(
+            "SELECT invoice_number FROM invoices WHERE tenant_id = $1 AND invoice_number LIKE $2",
+        )
+        .bind(tenant_id)
+        .bind(format!("{}%", prefix))
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .collect();
+        let mut invoices = Vec::new();
+        let mut unbilled = Vec::new();
+        for (i, balance) in parsed.balances.iter().enumerate() {
+            let number = format!("{}{}", prefix, balance.reference.to_uppercase());
+            if current.contains(&number) || invoices.iter().any(|(_, n, _)| *n == number) {
+                existing.invoices += 1;
+                continue;
+            }
+            match subscriptions.iter().position(|s| s.0 == balance.customer) {
+                Some(subscription) => invoices.push((i, number, subscription)),
+                None => unbilled.push(format!(
+                    "{} owes {} but has no package to bill it on; skipped",
+                    parsed.customers[balance.customer].name,
+                    money::format_money(balance.amount, &currency_code, None)
+                )),
+            }
+        }
+        for message in unbilled {
+            parsed.warning("balances", 0, message);
+        }
+
+        Ok(Plan {
+            packages,
+            customers,
+            subscriptions,
+            secrets,
+            invoices,
+            existing,
+            currency_code,
+        })
+    }
+
+    async fn write(
+        &self,
+        tenant_id: &str,
+        adapter: &Adapter,
+        parsed: &Parsed,
+        plan: &Plan,
+        router_id: Option<&str>,
+    ) -> AppResult<()> {
+        let now = Utc::now();
+        let note = |key: &str| format!("Imported from {} (id {})", adapter.name, key);
+        let mut tx = self.pool.begin().await?;
+
+        for (package, (id, exists)) in parsed.packages.iter().zip(&plan.packages) {
+            if *exists {
+                continue;
+            }
+            let price = money::round(package.price, &plan.currency_code);
+            let (monthly, yearly) = match package.cycle {
+                "yearly" => (0.0, price),
+                _ => (price, 0.0),
+            };
+            sqlx::query(
+                r#"
+                INSERT INTO isp_packages (
+                  id, tenant_id, service_type, name, description, features, is_active,
+                  price_monthly, price_yearly, created_at, updated_at
+                )
+                VALUES ($1, $2, 'internet_pppoe', $3, $4, '{}', true, $5, $6, $7, $7)
+                "#,
+            )
+            .bind(id)
+            .bind(tenant_id)
+            .bind(&package.name)
+            .bind(note(&package.key))
+            .bind(monthly)
+            .bind(yearly)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                r#"
+                INSERT INTO isp_package_price_history (
+                  id, tenant_id, package_id, price_monthly, price_yearly, effective_from, changed_by,
+                  created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, NULL, $6)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(tenant_id)
+            .bind(id)
+            .bind(monthly)
+            .bind(yearly)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let mut locations = Vec::with_capacity(parsed.customers.len());
+        for (customer, (id, exists)) in parsed.customers.iter().zip(&plan.customers) {
+            if *exists {
+                let location: Option<String> = sqlx::query_scalar(
+                    "SELECT id FROM customer_locations WHERE tenant_id = $1 AND customer_id = $2 ORDER BY created_at LIMIT 1",
+                )
+                .bind(tenant_id)
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+                if let Some(location) = location {
+                    locations.push(location);
+                    continue;
+                }
+            } else {
+                sqlx::query(
+                    r#"
+                    INSERT INTO customers
+                        (id, tenant_id, name, email, phone, notes, is_active, created_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, true, $7, $7)
+                    "#,
+                )
+                .bind(id)
+                .bind(tenant_id)
+                .bind(&customer.name)
+                .bind(&customer.email)
+                .bind(&customer.phone)
+                .bind(note(&customer.key))
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+            }
+            let location = Uuid::new_v4().to_string();
+            sqlx::query(
+                r#"
+                INSERT INTO customer_locations
+                    (id, tenant_id, customer_id, label, address_line1, created_at, updated_at)
+                VALUES ($1, $2, $3, 'Main', $4, $5, $5)
+                "#,
+            )
+            .bind(&location)
+            .bind(tenant_id)
+            .bind(id)
+            .bind(&customer.address)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            locations.push(location);
+        }
+
+        for (customer, package, id, exists) in &plan.subscriptions {
+            if *exists {
+                continue;
+            }
+            let source = &parsed.packages[*package];
+            sqlx::query(
+                r#"
+                INSERT INTO customer_subscriptions (
+                  id, tenant_id, customer_id, location_id, package_id, router_id, billing_cycle,
+                  price, currency_code, status, starts_at, notes, created_at, updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, 'active', $10, $11, $10, $10)
+                "#,
+            )
+            .bind(id)
+            .bind(tenant_id)
+            .bind(&plan.customers[*customer].0)
+            .bind(&locations[*customer])
+            .bind(&plan.packages[*package].0)
+            .bind(router_id)
+            .bind(source.cycle)
+            .bind(money::round(source.price, &plan.currency_code))
+            .bind(&plan.currency_code)
+            .bind(now)
+            .bind(format!("Imported from {}", adapter.name))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if let Some(router_id) = router_id {
+            for login in plan.secrets.iter().map(|i| &parsed.logins[*i]) {
+                let password_enc = encrypt_secret_for(
+                    PURPOSE_PPPOE,
+                    login.password.as_deref().unwrap_or_default(),
+                )?;
+                sqlx::query(
+                    r#"
+                    INSERT INTO pppoe_accounts
+                      (id, tenant_id, router_id, customer_id, location_id, username, password_enc, package_id,
+                       remote_address, disabled, comment, router_present, created_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, false, $12, $12)
+                    "#,
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(tenant_id)
+                .bind(router_id)
+                .bind(&plan.customers[login.customer].0)
+                .bind(&locations[login.customer])
+                .bind(login.username.as_deref().unwrap_or_default())
+                .bind(password_enc)
+                .bind(login.package.map(|p| plan.packages[p].0.as_str()))
+                .bind(&login.remote_address)
+                .bind(login.disabled)
+                .bind(format!("Imported from {}", adapter.name))
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        for (balance, number, subscription) in &plan.invoices {
+            let balance = &parsed.balances[*balance];
+            let (_, _, subscription_id, _) = &plan.subscriptions[*subscription];
+            sqlx::query(
+                r#"
+                INSERT INTO invoices (
+                    id, tenant_id, invoice_number, amount, currency_code, base_currency_code,
+                    status, description, due_date, external_id, created_at, updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $5, 'pending', $6, $7, $8, $9, $9)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(tenant_id)
+            .bind(number)
+            .bind(money::round(balance.amount, &plan.currency_code))
+            .bind(&plan.currency_code)
+            .bind(format!("Unpaid balance carried over from {}", adapter.name))
+            .bind(balance.due.unwrap_or(now))
+            .bind(format!(
+                "pkgsub:{}:migrated-{}",
+                subscription_id,
+                balance.reference.to_lowercase()
+            ))
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(source: &str) -> LegacyImportRequest {
+        LegacyImportRequest {
+            source: source.to_string(),
+            customers_csv: None,
+            packages_csv: None,
+            secrets_csv: None,
+            balances_csv: None,
+            router_id: None,
+            commit: false,
+        }
+    }
+
+    fn errors(parsed: &Parsed) -> Vec<String> {
+        parsed
+            .issues
+            .iter()
+            .filter(|i| i.severity == "error")
+            .map(|i| format!("{}:{} {}", i.file, i.line, i.message))
+            .collect()
+    }
+
+    #[test]
+    fn reads_quoted_and_semicolon_csv() {
+        let table =
+            read_csv("\u{feff}id;name\r\n1;\"Budi; \"\"B\"\"\"\n\n2;\"Multi\nline\"\n3;Sari")
+                .unwrap();
+        assert_eq!(table.columns["name"], 1);
+        let rows: Vec<_> = table
+            .rows
+            .iter()
+            .map(|(l, c)| (*l, c[1].as_str()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![(2, "Budi; \"B\""), (4, "Multi\nline"), (6, "Sari")]
+        );
+        assert!(read_csv("id,name\n1,\"open").is_err());
+        assert!(read_csv("").is_err());
+    }
+
+    #[test]
+    fn parses_amounts_cycles_and_flags() {
+        assert_eq!(parse_amount("150000"), Some(150_000.0));
+        assert_eq!(parse_amount("Rp 150.000"), Some(150_000.0));
+        assert_eq!(parse_amount("150.000,50"), Some(150_000.5));
+        assert_eq!(parse_amount("1,234.50"), Some(1234.5));
+        assert_eq!(parse_amount("-2500.5"), Some(-2500.5));
+        assert_eq!(parse_amount("1.000.000"), Some(1_000_000.0));
+        assert_eq!(parse_amount("abc"), None);
+
+        assert_eq!(parse_cycle(None, None), Some("monthly"));
+        assert_eq!(parse_cycle(Some("Months"), Some("1")), Some("monthly"));
+        assert_eq!(parse_cycle(Some("Months"), Some("12")), Some("yearly"));
+        assert_eq!(parse_cycle(Some("yearly"), None), Some("yearly"));
+        assert_eq!(parse_cycle(Some("Days"), Some("30")), None);
+
+        assert!(parse_flag(Some("off")));
+        assert!(parse_flag(Some("1")));
+        assert!(!parse_flag(Some("on")));
+        assert!(!parse_flag(None));
+    }
+
+    #[test]
+    fn phpnuxbill_exports_map_to_customers_logins_and_balances() {
+        let mut req = request("phpnuxbill");
+        req.packages_csv = Some(
+            "id,name_plan,price,validity,validity_unit\n\
+             1,Home 10M,150000,1,Months\n\
+             2,Voucher 1D,5000,1,Days\n"
+                .to_string(),
+        );
+        req.customers_csv = Some(
+            "id,username,password,fullname,address,phonenumber,email\n\
+             7,budi,rahasia,Budi Santoso,Jl. Merdeka 1,0812345678,BUDI@example.com\n\
+             8,sari,,Sari,,0813,not-an-email\n"
+                .to_string(),
+        );
+        req.secrets_csv = Some(
+            "id,customer_id,username,plan_id,namebp,status\n\
+             1,7,budi,1,Home 10M,on\n\
+             2,8,sari,9,Gone,off\n"
+                .to_string(),
+        );
+        req.balances_csv = Some(
+            "id,username,price,created_date,status\n\
+             31,budi,150000,2026-03-01 00:00:00,1\n\
+             32,budi,150000,2026-02-01 00:00:00,2\n"
+                .to_string(),
+        );
+        let parsed = parse(adapter("phpnuxbill").unwrap(), &req);
+        assert!(errors(&parsed).is_empty(), "{:?}", errors(&parsed));
+
+        assert_eq!(parsed.packages[0].cycle, "monthly");
+        assert_eq!(parsed.packages[1].cycle, "monthly");
+        assert_eq!(
+            parsed.customers[0].email.as_deref(),
+            Some("budi@example.com")
+        );
+        assert_eq!(parsed.customers[1].email, None);
+
+        let budi = &parsed.logins[0];
+        assert_eq!(budi.username.as_deref(), Some("budi"));
+        assert_eq!(budi.password.as_deref(), Some("rahasia"));
+        assert_eq!(budi.package, Some(0));
+        assert!(!budi.disabled);
+        // No password for sari and an unknown plan: no secret, no subscription.
+        assert_eq!(parsed.logins[1].username, None);
+        assert_eq!(parsed.logins[1].package, None);
+        assert!(parsed.logins[1].disabled);
+
+        assert_eq!(parsed.balances.len(), 1);
+        assert_eq!(parsed.balances[0].customer, 0);
+        assert_eq!(parsed.balances[0].reference, "31");
+        let warnings = parsed
+            .issues
+            .iter()
+            .filter(|i| i.severity == "warning")
+            .count();
+        assert_eq!(warnings, 4, "{:?}", parsed.issues);
+    }
+
+    #[test]
+    fn mikbill_debt_comes_from_a_negative_deposit() {
+        let mut req = request("mikbill");
+        req.packages_csv = Some("gid,packet,fixed_cost\n3,Basic,100000\n".to_string());
+        req.customers_csv = Some(
+            "uid;user;password;fio;mob_tel;gid;framed_ip;blocked;deposit\n\
+             1;u1;p1;Andi;0811;3;10.0.0.2;0;-200000\n\
+             2;u2;p2;Dewi;0812;3;;1;50000\n"
+                .to_string(),
+        );
+        let parsed = parse(adapter("mikbill").unwrap(), &req);
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        assert_eq!(parsed.logins.len(), 2);
+        assert_eq!(parsed.logins[0].remote_address.as_deref(), Some("10.0.0.2"));
+        assert!(parsed.logins[1].disabled);
+        assert_eq!(parsed.balances.len(), 1);
+        assert_eq!(parsed.balances[0].amount, 200_000.0);
+    }
+
+    #[test]
+    fn reports_blocking_problems() {
+        let mut req = request("csv");
+        assert_eq!(
+            errors(&parse(adapter("csv").unwrap(), &req)),
+            vec!["customers:0 The customers export is required"]
+        );
+
+        req.customers_csv = Some("customer_id,email\n1,a@b.c\n".to_string());
+        assert_eq!(
+            errors(&parse(adapter("csv").unwrap(), &req)),
+            vec!["customers:1 Missing column name"]
+        );
+
+        req.customers_csv =
+            Some("customer_id,name,username,password\n1,A,same,x\n1,B,,\n2,C,same,y\n".to_string());
+        req.balances_csv = Some("customer_id,amount\n9,1000\n2,lots\n".to_string());
+        assert_eq!(
+            errors(&parse(adapter("csv").unwrap(), &req)),
+            vec![
+                "customers:3 Duplicate customer id 1",
+                "customers:4 Duplicate PPPoE username same",
+                "balances:2 Customer 9 not found",
+                "balances:3 'lots' is not an amount",
+            ]
+        );
+        assert!(adapter("splynx").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! Locale tags and readers' locale preferences.
+//!
+//! Tags are kept as `ll` or `ll-RR` ("id", "en-US"). A user's preference is
+//! `users.locale`; without one the tenant's `default_locale` setting applies,
+//! then the global one.
+
+use crate::db::DbPool;
+#[cfg(feature = "postgres")]
+use std::collections::HashMap;
+
+/// Canonical form of a locale tag (`id_id` -> `id-ID`), or `None` when the
+/// tag isn't a 2-3 letter language with an optional 2 letter region.
+pub fn normalize_locale(tag: &str) -> Option<String> {
+    let tag = tag.trim().replace('_', "-");
+    let mut parts = tag.split('-');
+    let language = parts.next()?;
+    let region = parts.next();
+    if parts.next().is_some()
+        || !(2..=3).contains(&language.len())
+        || !language.chars().all(|c| c.is_ascii_alphabetic())
+    {
+        return None;
+    }
+    let language = language.to_ascii_lowercase();
+    match region {
+        None => Some(language),
+        Some(r) if r.len() == 2 && r.chars().all(|c| c.is_ascii_alphabetic()) => {
+            Some(format!("{}-{}", language, r.to_ascii_uppercase()))
+        }
+        Some(_) => None,
+    }
+}
+
+fn language_of(tag: &str) -> &str {
+    tag.split('-').next().unwrap_or(tag)
+}
+
+/// Closest of `available` (canonical tags) for a reader wanting `wanted`: the
+/// exact tag, then the bare language, then any region of the same language.
+pub fn best_match<'a, I>(available: I, wanted: &str) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let wanted = normalize_locale(wanted)?;
+    let language = language_of(&wanted);
+    let mut bare = None;
+    let mut same_language = None;
+    for tag in available {
+        if tag == wanted {
+            return Some(tag);
+        }
+        if tag == language {
+            bare = Some(tag);
+        } else if same_language.is_none() && language_of(tag) == language {
+            same_language = Some(tag);
+        }
+    }
+    bare.or(same_language)
+}
+
+/// The `default_locale` setting of `tenant_id`, else the platform's. This is
+/// the language of text written for a tenant rather than for one reader.
+pub async fn tenant_locale(pool: &DbPool, tenant_id: Option<&str>) -> Option<String> {
+    let value: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT value FROM settings
+        WHERE key = 'default_locale'
+          AND TRIM(value) <> ''
+          AND (tenant_id = $1 OR tenant_id IS NULL)
+        ORDER BY tenant_id NULLS LAST
+        LIMIT 1
+        "#,
+    )
+    .bind(tenant_id)
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten();
+    value.and_then(|v| normalize_locale(&v))
+}
+
+/// Effective locale per user: their preference, else the `default_locale`
+/// setting of `tenant_id` or the platform. Users with neither are left out.
+#[cfg(feature = "postgres")]
+pub async fn reader_locales(
+    pool: &DbPool,
+    user_ids: &[String],
+    tenant_id: Option<&str>,
+) -> Result<HashMap<String, String>, sqlx::Error> {
+    if user_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows: Vec<(String, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT u.id, COALESCE(NULLIF(TRIM(u.locale), ''), d.value)
+        FROM users u
+        LEFT JOIN LATERAL (
+            SELECT s.value FROM settings s
+            WHERE s.key = 'default_locale'
+              AND TRIM(s.value) <> ''
+              AND (s.tenant_id = $2 OR s.tenant_id IS NULL)
+            ORDER BY s.tenant_id NULLS LAST
+            LIMIT 1
+        ) d ON true
+        WHERE u.id = ANY($1)
+        "#,
+    )
+    .bind(user_ids)
+    .bind(tenant_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, locale)| Some((id, locale?)))
+        .collect())
+}
+
+#[cfg(feature = "postgres")]
+pub async fn reader_locale(
+    pool: &DbPool,
+    user_id: &str,
+    tenant_id: Option<&str>,
+) -> Result<Option<String>, sqlx::Error> {
+    let mut locales = reader_locales(pool, &[user_id.to_string()], tenant_id).await?;
+    Ok(locales.remove(user_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_are_canonicalized() {
+        assert_eq!(normalize_locale(" EN_us ").as_deref(), Some("en-US"));
+        assert_eq!(normalize_locale("id").as_deref(), Some("id"));
+        assert_eq!(normalize_locale("fil").as_deref(), Some("fil"));
+        assert_eq!(normalize_locale(""), None);
+        assert_eq!(normalize_locale("english"), None);
+        assert_eq!(normalize_locale("en-US-x"), None);
+        assert_eq!(normalize_locale("en-1"), None);
+    }
+
+    #[test]
+    fn closest_variant_prefers_exact_then_language() {
+        let tags = ["en-GB", "id", "id-ID", "pt-BR"];
+        let pick = |wanted| best_match(tags.iter().copied(), wanted);
+        assert_eq!(pick("id-ID"), Some("id-ID"));
+        assert_eq!(pick("id"), Some("id"));
+        assert_eq!(pick("en-US"), Some("en-GB"));
+        assert_eq!(pick("pt"), Some("pt-BR"));
+        assert_eq!(pick("fr"), None);
+        assert_eq!(pick("not a tag"), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! Malware scanning of uploads through ClamAV's daemon.
+//!
+//! Files are streamed to clamd with the `zINSTREAM` command, over its unix
+//! socket (`/run/clamav/clamd.ctl`, `unix:/...`) or TCP (`host:3310`).
+//! Scanning is off unless the `storage_scan_enabled` setting is on. An
+//! infected upload is still stored, but quarantined: its record is marked
+//! `infected` and the file can no longer be downloaded.
+
+use crate::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Bytes sent per INSTREAM chunk.
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Upper bound for connecting, streaming and waiting for the verdict.
+const SCAN_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    /// Name of the signature that matched.
+    Infected(String),
+}
+
+/// What was uploaded: a received file or bytes in memory.
+pub enum ScanTarget<'a> {
+    File(&'a Path),
+    Bytes(&'a [u8]),
+}
+
+/// Scan outcome recorded on the file (`file_records.scan_status` and friends).
+#[derive(Debug, Clone)]
+pub struct FileScan {
+    /// `unscanned`, `clean`, `infected` or `error`.
+    pub status: &'static str,
+    pub signature: Option<String>,
+    pub scanned_at: Option<DateTime<Utc>>,
+}
+
+impl FileScan {
+    pub fn unscanned() -> Self {
+        Self {
+            status: "unscanned",
+            signature: None,
+            scanned_at: None,
+        }
+    }
+
+    /// The scanner could not be reached or gave no verdict.
+    pub fn error() -> Self {
+        Self {
+            status: "error",
+            signature: None,
+            scanned_at: Some(Utc::now()),
+        }
+    }
+
+    pub fn from_verdict(verdict: ScanVerdict) -> Self {
+        let (status, signature) = match verdict {
+            ScanVerdict::Clean => ("clean", None),
+            ScanVerdict::Infected(signature) => ("infected", Some(signature)),
+        };
+        Self {
+            status,
+            signature,
+            scanned_at: Some(Utc::now()),
+        }
+    }
+
+    pub fn is_infected(&self) -> bool {
+        self.status == "infected"
+    }
+}
+
+/// Verdict from a clamd reply such as `stream: OK` or
+/// `stream: Eicar-Signature FOUND`.
+pub fn parse_clamd_reply(reply: &str) -> AppResult<ScanVerdict> {
+    let reply = reply.trim_end_matches('\0').trim();
+    let result = reply.strip_prefix("stream:").unwrap_or(reply).trim();
+    if result == "OK" {
+        return Ok(ScanVerdict::Clean);
+    }
+    if let Some(signature) = result.strip_suffix(" FOUND") {
+        return Ok(ScanVerdict::Infected(signature.trim().to_string()));
+    }
+    Err(AppError::Internal(format!("ClamAV scan failed: {}", reply)))
+}
+
+/// Streams `data` to clamd over an open connection and reads its verdict.
+async fn instream<S, R>(mut conn: S, mut data: R) -> AppResult<ScanVerdict>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    R: AsyncRead + Unpin,
+{
+    let io_err = |e: std::io::Error| AppError::Internal(format!("ClamAV connection error: {}", e));
+
+    conn.write_all(b"zINSTREAM\0").await.map_err(io_err)?;
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        let n = data
+            .read(&mut buf)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to read upload for scan: {}", e)))?;
+        if n == 0 {
+            break;
+        }
+        conn.write_all(&(n as u32).to_be_bytes())
+            .await
+            .map_err(io_err)?;
+        conn.write_all(&buf[..n]).await.map_err(io_err)?;
+    }
+    conn.write_all(&0u32.to_be_bytes()).await.map_err(io_err)?;
+    conn.flush().await.map_err(io_err)?;
+
+    let mut reply = Vec::new();
+    conn.read_to_end(&mut reply).await.map_err(io_err)?;
+    parse_clamd_reply(&String::from_utf8_lossy(&reply))
+}
+
+#[derive(Debug, Clone)]
+pub struct ClamdScanner {
+    address: String,
+}
+
+impl ClamdScanner {
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.trim().to_string(),
+        }
+    }
+
+    pub async fn scan(&self, target: ScanTarget<'_>) -> AppResult<ScanVerdict> {
+        tokio::time::timeout(SCAN_TIMEOUT, async {
+            match target {
+                ScanTarget::File(path) => {
+                    let file = tokio::fs::File::open(path).await.map_err(|e| {
+                        AppError::Internal(format!("Failed to open upload for scan: {}", e))
+                    })?;
+                    self.scan_reader(file).await
+                }
+                ScanTarget::Bytes(data) => self.scan_reader(data).await,
+            }
+        })
+        .await
+        .map_err(|_| AppError::Internal("ClamAV scan timed out".to_string()))?
+    }
+
+    async fn scan_reader<R: AsyncRead + Unpin>(&self, data: R) -> AppResult<ScanVerdict> {
+        let connect_err =
+            |e: std::io::Error| AppError::Internal(format!("Failed to connect to ClamAV: {}", e));
+
+        let socket_path = self.address.strip_prefix("unix:").or_else(|| {
+            self.address
+                .starts_with('/')
+                .then_some(self.address.as_str())
+        });
+        if let Some(path) = socket_path {
+            #[cfg(unix)]
+            {
+                let conn = tokio::net::UnixStream::connect(path)
+                    .await
+                    .map_err(connect_err)?;
+                return instream(conn, data).await;
+            }
+            #[cfg(not(unix))]
+            {
+                let _ = path;
+                return Err(AppError::Configuration(
+                    "ClamAV unix sockets are not supported on this platform".to_string(),
+                ));
+            }
+        }
+
+        let address = self.address.strip_prefix("tcp://").unwrap_or(&self.address);
+        let conn = tokio::net::TcpStream::connect(address)
+            .await
+            .map_err(connect_err)?;
+        instream(conn, data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamd_replies() {
+        assert_eq!(
+            parse_clamd_reply("stream: OK\0").unwrap(),
+            ScanVerdict::Clean
+        );
+        assert_eq!(
+            parse_clamd_reply("stream: Win.Test.EICAR_HDB-1 FOUND\0").unwrap(),
+            ScanVerdict::Infected("Win.Test.EICAR_HDB-1".to_string())
+        );
+        assert!(parse_clamd_reply("INSTREAM size limit exceeded. ERROR\0").is_err());
+        assert!(parse_clamd_reply("").is_err());
+    }
+
+    #[tokio::test]
+    async fn instream_frames_chunks_and_reads_the_verdict() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        let fake_clamd = tokio::spawn(async move {
+            let mut command = [0u8; 10];
+            server.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"zINSTREAM\0");
+
+            let mut received = Vec::new();
+            loop {
+                let mut len = [0u8; 4];
+                server.read_exact(&mut len).await.unwrap();
+                let len = u32::from_be_bytes(len) as usize;
+                if len == 0 {
+                    break;
+                }
+                let mut chunk = vec![0u8; len];
+                server.read_exact(&mut chunk).await.unwrap();
+                received.extend_from_slice(&chunk);
+            }
+            server
+                .write_all(b"stream: Eicar-Signature FOUND\0")
+                .await
+                .unwrap();
+            received
+        });
+
+        let data = vec![7u8; CHUNK_SIZE + 10];
+        let verdict = instream(client, data.as_slice()).await.unwrap();
+        assert_eq!(
+            verdict,
+            ScanVerdict::Infected("Eicar-Signature".to_string())
+        );
+        assert_eq!(fake_clamd.await.unwrap(), data);
+    }
+}
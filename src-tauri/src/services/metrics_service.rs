@@ -1,16 +1,24 @@
 //! Metrics Service - Request Performance Tracking
 //!
-//! Tracks request counts, response times, and error rates for monitoring.
+//! Tracks request counts, response times, and error rates for monitoring,
+//! plus database pool utilization sampled in the background.
 
+use crate::db::DbPool;
 use serde::Serialize;
 use std::collections::VecDeque;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
 /// Maximum number of response times to track (circular buffer)
 const MAX_RESPONSE_TIMES: usize = 1000;
 
+/// Maximum number of pool acquire-wait samples to keep
+const MAX_POOL_WAIT_SAMPLES: usize = 120;
+
+/// How often the pool sampler probes the database pool
+const POOL_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
 /// Request metrics for monitoring dashboard
 #[derive(Debug, Clone, Serialize)]
 pub struct RequestMetrics {
@@ -47,6 +55,27 @@ impl Default for RequestMetrics {
     }
 }
 
+/// Database connection pool utilization
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PoolMetrics {
+    /// Configured maximum pool size
+    pub max_connections: u32,
+    /// Open connections (idle + active)
+    pub size: u32,
+    /// Idle connections
+    pub idle: u32,
+    /// Connections currently checked out
+    pub active: u32,
+    /// Active connections as a percentage of the maximum
+    pub utilization_pct: f64,
+    /// Average time to acquire a connection over recent samples
+    pub avg_acquire_wait_ms: f64,
+    /// Slowest acquire over recent samples
+    pub max_acquire_wait_ms: f64,
+    /// Acquire attempts that timed out since startup
+    pub acquire_timeouts: u64,
+}
+
 /// Internal struct to track request timing
 struct TimedRequest {
     duration: Duration,
@@ -63,6 +92,12 @@ pub struct MetricsService {
     rate_limited_count: AtomicU64,
     /// Response times with timestamps (circular buffer)
     response_times: RwLock<VecDeque<TimedRequest>>,
+    /// Latest pool size snapshot: (max, size, idle)
+    pool_state: RwLock<Option<(u32, u32, u32)>>,
+    /// Recent pool acquire waits (circular buffer)
+    pool_waits: RwLock<VecDeque<Duration>>,
+    /// Pool acquire timeouts
+    pool_acquire_timeouts: AtomicU64,
     /// Service start time
     start_time: Instant,
 }
@@ -75,6 +110,9 @@ impl MetricsService {
             error_count: AtomicU64::new(0),
             rate_limited_count: AtomicU64::new(0),
             response_times: RwLock::new(VecDeque::with_capacity(MAX_RESPONSE_TIMES)),
+            pool_state: RwLock::new(None),
+            pool_waits: RwLock::new(VecDeque::with_capacity(MAX_POOL_WAIT_SAMPLES)),
+            pool_acquire_timeouts: AtomicU64::new(0),
             start_time: Instant::now(),
         }
     }
@@ -164,6 +202,90 @@ impl MetricsService {
         }
     }
 
+    /// Record a pool sample. `acquire_wait` is `None` when the acquire timed out.
+    pub fn record_pool_sample(
+        &self,
+        max_connections: u32,
+        size: u32,
+        idle: u32,
+        acquire_wait: Option<Duration>,
+    ) {
+        *self.pool_state.write().unwrap() = Some((max_connections, size, idle));
+
+        match acquire_wait {
+            Some(wait) => {
+                let mut waits = self.pool_waits.write().unwrap();
+                if waits.len() >= MAX_POOL_WAIT_SAMPLES {
+                    waits.pop_front();
+                }
+                waits.push_back(wait);
+            }
+            None => {
+                self.pool_acquire_timeouts.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Get current pool metrics, if the pool has been sampled yet
+    pub fn get_pool_metrics(&self) -> Option<PoolMetrics> {
+        let (max_connections, size, idle) = (*self.pool_state.read().unwrap())?;
+        let active = size.saturating_sub(idle);
+
+        let waits = self.pool_waits.read().unwrap();
+        let wait_ms: Vec<f64> = waits.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        let (avg, max) = if wait_ms.is_empty() {
+            (0.0, 0.0)
+        } else {
+            (
+                wait_ms.iter().sum::<f64>() / wait_ms.len() as f64,
+                wait_ms.iter().cloned().fold(0.0, f64::max),
+            )
+        };
+        let utilization = if max_connections == 0 {
+            0.0
+        } else {
+            active as f64 / max_connections as f64 * 100.0
+        };
+
+        Some(PoolMetrics {
+            max_connections,
+            size,
+            idle,
+            active,
+            utilization_pct: (utilization * 100.0).round() / 100.0,
+            avg_acquire_wait_ms: (avg * 100.0).round() / 100.0,
+            max_acquire_wait_ms: (max * 100.0).round() / 100.0,
+            acquire_timeouts: self.pool_acquire_timeouts.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Periodically sample pool size and acquire latency in the background.
+    pub fn start_pool_sampler(self: Arc<Self>, pool: DbPool) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(POOL_SAMPLE_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                // Snapshot before acquiring so the probe itself isn't counted as active.
+                let size = pool.size();
+                let idle = pool.num_idle() as u32;
+                let max_connections = pool.options().get_max_connections();
+
+                let started = Instant::now();
+                let wait = match pool.acquire().await {
+                    Ok(_conn) => Some(started.elapsed()),
+                    Err(sqlx::Error::PoolTimedOut) => None,
+                    Err(e) => {
+                        tracing::warn!("Pool sampler failed to acquire a connection: {}", e);
+                        continue;
+                    }
+                };
+
+                self.record_pool_sample(max_connections, size, idle, wait);
+            }
+        });
+    }
+
     /// Get uptime in seconds
     #[allow(dead_code)]
     pub fn uptime_secs(&self) -> u64 {
@@ -206,4 +328,22 @@ mod tests {
         assert_eq!(metrics.rate_limited_count, 2);
         assert_eq!(metrics.total_requests, 2);
     }
+
+    #[test]
+    fn test_pool_metrics() {
+        let service = MetricsService::new();
+        assert!(service.get_pool_metrics().is_none());
+
+        service.record_pool_sample(10, 4, 1, Some(Duration::from_millis(2)));
+        service.record_pool_sample(10, 5, 0, Some(Duration::from_millis(6)));
+        service.record_pool_sample(10, 5, 0, None);
+
+        let pool = service.get_pool_metrics().unwrap();
+        assert_eq!(pool.size, 5);
+        assert_eq!(pool.active, 5);
+        assert_eq!(pool.utilization_pct, 50.0);
+        assert_eq!(pool.acquire_timeouts, 1);
+        assert!(pool.avg_acquire_wait_ms >= 3.9 && pool.avg_acquire_wait_ms <= 4.1);
+        assert!(pool.max_acquire_wait_ms >= 5.9);
+    }
 }
@@ -4,22 +4,41 @@
 //! - CRUD routers (host/port/username/password)
 //! - Test connection (identity/version)
 //! - Background poller to update online/offline + store snapshots
+//! - Monitored devices: OLTs, switches and other non-MikroTik gear polled
+//!   over SNMP v2c/v3, feeding the same alerts and incidents as the routers
 //!
 //! Notes:
 //! - Passwords are stored encrypted-at-rest in DB (never returned via API).
 //!   Encryption uses `MIKROTIK_CRED_KEY` (see `crate::security::secret`).
 
-use crate::db::DbPool;
+#[cfg(feature = "postgres")]
+use crate::db::partitions;
+use crate::db::{DbPool, QueryRouter};
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    CreateMikrotikRouterRequest, MikrotikAlert, MikrotikHealthSnapshot, MikrotikIncident,
+    BackgroundJob, CreateMikrotikRouterRequest, CreateMonitoredDeviceRequest, MikrotikAlert,
+    MikrotikHealthSnapshot, MikrotikIncident, MikrotikIncidentArea, MikrotikIncidentImpact,
     MikrotikInterfaceCounter, MikrotikInterfaceMetric, MikrotikInterfaceSnapshot,
     MikrotikIpAddressSnapshot, MikrotikLogEntry, MikrotikLogSyncResult, MikrotikRouter,
     MikrotikRouterMetric, MikrotikRouterNocRow, MikrotikRouterSnapshot, MikrotikTestResult,
-    PaginatedResponse, UpdateMikrotikRouterRequest,
+    MonitoredDevice, MonitoredDeviceTestResult, PaginatedResponse, TrashItem,
+    UpdateMikrotikRouterRequest, UpdateMonitoredDeviceRequest,
 };
-use crate::security::secret::{decrypt_secret_opt, encrypt_secret};
-use crate::services::{AuditService, NotificationService, SettingsService};
+use crate::security::secret::{
+    decrypt_secret_for, decrypt_secret_opt, encrypt_secret, encrypt_secret_for,
+};
+use crate::services::event_outbox_service::{self, DomainEvent, EventOutboxService};
+use crate::services::notification_service::TenantAlert;
+use crate::services::snmp_client::{
+    parse_oid, AuthProtocol, PrivProtocol, SnmpClient, SnmpCredentials, SnmpError, SnmpValue,
+};
+use crate::services::telegram_service::TelegramAlert;
+use crate::services::whatsapp_service::WhatsappEvent;
+use crate::services::{
+    concurrency, AuditChange, AuditService, JobHandler, JobQueue, NotificationService,
+    SettingsService, UsageMetric, UsageService,
+};
+use async_trait::async_trait;
 use chrono::DateTime;
 use chrono::{Duration as ChronoDuration, Utc};
 use mikrotik_rs::{protocol::command::CommandBuilder, protocol::CommandResponse, MikrotikDevice};
@@ -38,6 +57,65 @@ const LATENCY_HOT_MS: i32 = 400;
 const OFFLINE_AFTER_SECS: i64 = 60;
 const WALLBOARD_SLOTS_SETTING_KEY: &str = "mikrotik_wallboard_slots_json";
 const WALLBOARD_TRACK_CACHE_TTL_SECS: u64 = 10;
+/// Router fields written by the poller, left out of edit diffs.
+const ROUTER_STATUS_FIELDS: &[&str] = &[
+    "identity",
+    "ros_version",
+    "is_online",
+    "last_seen_at",
+    "latency_ms",
+    "last_error",
+];
+
+pub(crate) const PURPOSE_SNMP: &str = "snmp_credentials";
+const DEVICE_TYPES: &[&str] = &["olt", "switch", "router", "other"];
+const SNMP_TIMEOUT_SECS: u64 = 3;
+const OID_SYS_DESCR: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 1, 0];
+const OID_SYS_UPTIME: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 3, 0];
+const OID_SYS_NAME: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 5, 0];
+
+/// Device fields written by the poller, left out of edit diffs.
+const DEVICE_STATUS_FIELDS: &[&str] = &[
+    "is_online",
+    "last_seen_at",
+    "latency_ms",
+    "last_error",
+    "sys_name",
+    "sys_descr",
+    "uptime_seconds",
+    "cpu_load",
+];
+/// A `routeros` device reads its status from the router the poller keeps up
+/// to date; only `snmp` devices carry their own.
+const DEVICE_SELECT: &str = r#"
+    SELECT d.id, d.tenant_id, d.name,
+           COALESCE(r.host, d.host) AS host,
+           d.device_type, d.protocol, d.router_id, d.snmp_version, d.snmp_port,
+           d.snmp_community, d.snmp_username, d.snmp_auth_protocol, d.snmp_auth_password,
+           d.snmp_priv_protocol, d.snmp_priv_password, d.cpu_oid,
+           CASE WHEN r.id IS NULL THEN d.enabled ELSE r.enabled END AS enabled,
+           CASE WHEN r.id IS NULL THEN d.maintenance_until ELSE r.maintenance_until END
+             AS maintenance_until,
+           CASE WHEN r.id IS NULL THEN d.is_online ELSE r.is_online END AS is_online,
+           CASE WHEN r.id IS NULL THEN d.last_seen_at ELSE r.last_seen_at END AS last_seen_at,
+           CASE WHEN r.id IS NULL THEN d.latency_ms ELSE r.latency_ms END AS latency_ms,
+           CASE WHEN r.id IS NULL THEN d.last_error ELSE r.last_error END AS last_error,
+           CASE WHEN r.id IS NULL THEN d.sys_name ELSE r.identity END AS sys_name,
+           CASE WHEN r.id IS NULL THEN d.sys_descr ELSE r.ros_version END AS sys_descr,
+           CASE WHEN r.id IS NULL THEN d.uptime_seconds ELSE m.uptime_seconds END
+             AS uptime_seconds,
+           CASE WHEN r.id IS NULL THEN d.cpu_load ELSE m.cpu_load END AS cpu_load,
+           d.created_at, d.updated_at
+    FROM monitored_devices d
+    LEFT JOIN mikrotik_routers r ON r.id = d.router_id
+    LEFT JOIN LATERAL (
+      SELECT rm.uptime_seconds, rm.cpu_load
+      FROM mikrotik_router_metrics rm
+      WHERE rm.router_id = d.router_id
+      ORDER BY rm.ts DESC
+      LIMIT 1
+    ) m ON TRUE
+"#;
 
 #[derive(Clone, Copy)]
 struct Thresholds {
@@ -49,12 +127,56 @@ struct Thresholds {
     offline_after_secs: i64,
 }
 
+/// What one SNMP poll of a device read.
+struct SnmpReading {
+    sys_name: Option<String>,
+    sys_descr: Option<String>,
+    uptime_seconds: Option<i64>,
+    cpu_load: Option<i32>,
+}
+
+/// What an alert or incident is about: a router, or a device polled over
+/// SNMP. Either way its id goes in `router_id`; `source` tells them apart.
+#[derive(Clone, Copy)]
+struct AlertSubject<'a> {
+    id: &'a str,
+    name: &'a str,
+    source: &'static str,
+}
+
+impl<'a> AlertSubject<'a> {
+    fn router(router: &'a MikrotikRouter) -> Self {
+        Self {
+            id: &router.id,
+            name: &router.name,
+            source: "routeros",
+        }
+    }
+
+    fn device(device: &'a MonitoredDevice) -> Self {
+        Self {
+            id: &device.id,
+            name: &device.name,
+            source: "snmp",
+        }
+    }
+
+    fn link(&self) -> String {
+        match self.source {
+            "routeros" => format!("/admin/network/routers/{}", self.id),
+            _ => "/admin/network/incidents".to_string(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct MikrotikService {
     pool: DbPool,
+    router: QueryRouter,
     notification_service: NotificationService,
     audit_service: AuditService,
     settings_service: SettingsService,
+    usage: UsageService,
     wallboard_track_cache:
         Arc<std::sync::RwLock<HashMap<String, (Instant, HashMap<String, HashSet<String>>)>>>,
 }
@@ -138,8 +260,11 @@ impl MikrotikService {
         audit_service: AuditService,
         settings_service: SettingsService,
     ) -> Self {
+        let router = QueryRouter::new(pool.clone(), None);
         Self {
+            usage: UsageService::new(pool.clone()),
             pool,
+            router,
             notification_service,
             audit_service,
             settings_service,
@@ -147,11 +272,17 @@ impl MikrotikService {
         }
     }
 
+    /// NOC list and metrics history queries use `router.reader()`.
+    pub fn with_query_router(mut self, router: QueryRouter) -> Self {
+        self.router = router;
+        self
+    }
+
     pub async fn list_routers(&self, tenant_id: &str) -> AppResult<Vec<MikrotikRouter>> {
         let routers = sqlx::query_as::<_, MikrotikRouter>(
             r#"
             SELECT * FROM mikrotik_routers
-            WHERE tenant_id = $1
+            WHERE tenant_id = $1 AND deleted_at IS NULL
             ORDER BY updated_at DESC
             "#,
         )
@@ -182,12 +313,12 @@ impl MikrotikService {
               (SELECT m.rx_bps FROM mikrotik_router_metrics m WHERE m.router_id = r.id ORDER BY m.ts DESC LIMIT 1) AS rx_bps,
               (SELECT m.tx_bps FROM mikrotik_router_metrics m WHERE m.router_id = r.id ORDER BY m.ts DESC LIMIT 1) AS tx_bps
             FROM mikrotik_routers r
-            WHERE r.tenant_id = $1
+            WHERE r.tenant_id = $1 AND r.deleted_at IS NULL
             ORDER BY r.updated_at DESC
             "#,
         )
         .bind(tenant_id)
-        .fetch_all(&self.pool)
+        .fetch_all(self.router.reader())
         .await
         .map_err(AppError::Database)?;
 
@@ -237,10 +368,17 @@ impl MikrotikService {
         &self,
         tenant_id: &str,
         active_only: bool,
+        by_impact: bool,
         limit: u32,
     ) -> AppResult<Vec<MikrotikIncident>> {
+        // Triage view: the widest blast radius first.
+        let order = if by_impact {
+            "COALESCE(i.affected_subscriptions, 0) DESC, i.updated_at DESC"
+        } else {
+            "i.updated_at DESC"
+        };
         let rows = if active_only {
-            sqlx::query_as::<_, MikrotikIncident>(
+            sqlx::query_as::<_, MikrotikIncident>(&format!(
                 r#"
                 SELECT
                   i.*,
@@ -260,17 +398,17 @@ impl MikrotikService {
                   ) AS escalated_at
                 FROM mikrotik_incidents i
                 WHERE i.tenant_id = $1 AND i.resolved_at IS NULL
-                ORDER BY i.updated_at DESC
+                ORDER BY {order}
                 LIMIT $2
-                "#,
-            )
+                "#
+            ))
             .bind(tenant_id)
             .bind(limit as i64)
             .fetch_all(&self.pool)
             .await
             .map_err(AppError::Database)?
         } else {
-            sqlx::query_as::<_, MikrotikIncident>(
+            sqlx::query_as::<_, MikrotikIncident>(&format!(
                 r#"
                 SELECT
                   i.*,
@@ -290,10 +428,10 @@ impl MikrotikService {
                   ) AS escalated_at
                 FROM mikrotik_incidents i
                 WHERE i.tenant_id = $1
-                ORDER BY i.updated_at DESC
+                ORDER BY {order}
                 LIMIT $2
-                "#,
-            )
+                "#
+            ))
             .bind(tenant_id)
             .bind(limit as i64)
             .fetch_all(&self.pool)
@@ -439,6 +577,10 @@ impl MikrotikService {
                         r#"
                         SELECT name FROM mikrotik_routers
                         WHERE id = $1 AND tenant_id = $2
+                        UNION ALL
+                        SELECT name FROM monitored_devices
+                        WHERE id = $1 AND tenant_id = $2
+                        LIMIT 1
                         "#,
                     )
                     .bind(&incident.router_id)
@@ -457,19 +599,28 @@ impl MikrotikService {
                         router_name.unwrap_or(incident.router_id.clone())
                     };
 
+                    let action_url = format!("/admin/network/incidents?incident={}", incident.id);
+                    let vars = HashMap::from([
+                        ("incident_title", incident.title.clone()),
+                        ("incident_message", incident.message.clone()),
+                        ("target", incident_target),
+                        ("status", incident.status.clone()),
+                        ("action_url", action_url.clone()),
+                    ]);
+                    let rendered = self
+                        .notification_service
+                        .render_template(Some(tenant_id), "incident_assigned", &vars)
+                        .await;
                     let _ = self
                         .notification_service
                         .create_notification(
                             assignee_user_id.clone(),
                             Some(tenant_id.to_string()),
-                            format!("Incident assigned: {}", incident.title),
-                            format!(
-                                "You were assigned to incident on {}. Current status: {}.",
-                                incident_target, incident.status
-                            ),
+                            rendered.title,
+                            rendered.body,
                             "warning".to_string(),
                             "network".to_string(),
-                            Some(format!("/admin/network/incidents?incident={}", incident.id)),
+                            Some(action_url),
                         )
                         .await;
 
@@ -506,18 +657,17 @@ impl MikrotikService {
                         .flatten();
 
                         if let Some(email) = assignee_email {
-                            let subject = format!("Incident Assigned: {}", incident.title);
-                            let body = format!(
-                                "You were assigned to incident:\n{}\n\nTarget: {}\nStatus: {}\nOpen: /admin/network/incidents?incident={}",
-                                incident.message, incident_target, incident.status, incident.id
-                            );
+                            let rendered = self
+                                .notification_service
+                                .render_template(Some(tenant_id), "incident_assigned_email", &vars)
+                                .await;
                             let _ = self
                                 .notification_service
                                 .force_send_email(
                                     Some(tenant_id.to_string()),
                                     &email,
-                                    &subject,
-                                    &body,
+                                    &rendered.title,
+                                    &rendered.body,
                                 )
                                 .await;
                         }
@@ -597,7 +747,7 @@ impl MikrotikService {
 
         self.upsert_incident(
             tenant_id,
-            router_id,
+            AlertSubject::router(&router),
             normalized_interface.as_deref(),
             &normalized_type,
             severity_value,
@@ -768,39 +918,70 @@ impl MikrotikService {
         let now = Utc::now();
         let mut upserted = 0u32;
 
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        // Serialize syncs of the same router: two of them could otherwise both
+        // miss a row below and insert it twice.
+        #[cfg(feature = "postgres")]
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(format!("mikrotik_logs:{}", router_id))
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
         for (router_log_id, router_time, topics, message) in raw_rows.iter() {
             let level = Self::log_level_from_topics(topics.as_deref());
             if let Some(rid) = router_log_id.as_ref() {
-                sqlx::query(
+                // mikrotik_logs is partitioned, so (router_id, router_log_id) can't carry
+                // a unique index for ON CONFLICT; update in place and insert on a miss.
+                // `logged_at` is the partition key and stays put, or re-synced rows would
+                // move into the current partition and outlive retention.
+                let updated = sqlx::query(
                     r#"
-                    INSERT INTO mikrotik_logs
-                      (id, tenant_id, router_id, router_log_id, logged_at, router_time, topics, level, message, created_at, updated_at)
-                    VALUES
-                      ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-                    ON CONFLICT (router_id, router_log_id) WHERE router_log_id IS NOT NULL
-                    DO UPDATE SET
-                      router_time = EXCLUDED.router_time,
-                      topics = EXCLUDED.topics,
-                      level = EXCLUDED.level,
-                      message = EXCLUDED.message,
-                      logged_at = EXCLUDED.logged_at,
-                      updated_at = EXCLUDED.updated_at
+                    UPDATE mikrotik_logs SET
+                      router_time = $3,
+                      topics = $4,
+                      level = $5,
+                      message = $6,
+                      updated_at = $7
+                    WHERE router_id = $1 AND router_log_id = $2
                     "#,
                 )
-                .bind(uuid::Uuid::new_v4().to_string())
-                .bind(tenant_id)
                 .bind(router_id)
                 .bind(rid)
-                .bind(now)
                 .bind(router_time)
                 .bind(topics)
                 .bind(level)
                 .bind(message)
                 .bind(now)
-                .bind(now)
-                .execute(&self.pool)
+                .execute(&mut *tx)
                 .await
-                .map_err(AppError::Database)?;
+                .map_err(AppError::Database)?
+                .rows_affected();
+
+                if updated == 0 {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO mikrotik_logs
+                          (id, tenant_id, router_id, router_log_id, logged_at, router_time, topics, level, message, created_at, updated_at)
+                        VALUES
+                          ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                        "#,
+                    )
+                    .bind(uuid::Uuid::new_v4().to_string())
+                    .bind(tenant_id)
+                    .bind(router_id)
+                    .bind(rid)
+                    .bind(now)
+                    .bind(router_time)
+                    .bind(topics)
+                    .bind(level)
+                    .bind(message)
+                    .bind(now)
+                    .bind(now)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(AppError::Database)?;
+                }
             } else {
                 sqlx::query(
                     r#"
@@ -820,12 +1001,13 @@ impl MikrotikService {
                 .bind(message)
                 .bind(now)
                 .bind(now)
-                .execute(&self.pool)
+                .execute(&mut *tx)
                 .await
                 .map_err(AppError::Database)?;
             }
             upserted += 1;
         }
+        tx.commit().await.map_err(AppError::Database)?;
 
         // Keep log table bounded per-router to avoid unbounded growth.
         sqlx::query(
@@ -1114,7 +1296,7 @@ impl MikrotikService {
         let router = sqlx::query_as::<_, MikrotikRouter>(
             r#"
             SELECT * FROM mikrotik_routers
-            WHERE id = $1 AND tenant_id = $2
+            WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL
             "#,
         )
         .bind(id)
@@ -1132,6 +1314,9 @@ impl MikrotikService {
         req: CreateMikrotikRouterRequest,
     ) -> AppResult<MikrotikRouter> {
         Self::validate_router_coordinates(req.latitude, req.longitude)?;
+        self.usage
+            .enforce(tenant_id, UsageMetric::Routers, 1)
+            .await?;
         let encrypted_password = encrypt_secret(req.password.as_str())?;
         let router = MikrotikRouter::new(
             tenant_id.to_string(),
@@ -1152,12 +1337,12 @@ impl MikrotikService {
             (id, tenant_id, name, host, port, username, password, use_tls, enabled,
              identity, ros_version, is_online, last_seen_at, latency_ms, last_error,
              maintenance_until, maintenance_reason, latitude, longitude,
-             created_at, updated_at)
+             created_at, updated_at, config_updated_at)
             VALUES
             ($1,$2,$3,$4,$5,$6,$7,$8,$9,
              $10,$11,$12,$13,$14,$15,
              $16,$17,$18,$19,
-             $20,$21)
+             $20,$21,$22)
             "#,
         )
         .bind(&router.id)
@@ -1181,6 +1366,7 @@ impl MikrotikService {
         .bind(router.longitude)
         .bind(router.created_at)
         .bind(router.updated_at)
+        .bind(router.config_updated_at)
         .execute(&self.pool)
         .await
         .map_err(AppError::Database)?;
@@ -1188,18 +1374,27 @@ impl MikrotikService {
         Ok(router)
     }
 
+    /// Updates a router; also returns the edit's diff for the audit log.
     pub async fn update_router(
         &self,
         tenant_id: &str,
         id: &str,
         req: UpdateMikrotikRouterRequest,
-    ) -> AppResult<MikrotikRouter> {
+    ) -> AppResult<(MikrotikRouter, AuditChange)> {
         let existing = self
             .get_router(tenant_id, id)
             .await?
             .ok_or_else(|| AppError::NotFound("Router not found".to_string()))?;
+        // Polling bumps `updated_at` all the time; only edits move `config_updated_at`.
+        concurrency::ensure_unmodified(
+            req.expected_updated_at,
+            existing.config_updated_at,
+            "router",
+        )?;
+        let before = existing.clone();
 
         let now = Utc::now();
+        let previous_config_updated_at = existing.config_updated_at;
         let name = req.name.unwrap_or(existing.name);
         let host = req.host.unwrap_or(existing.host);
         let port = req.port.unwrap_or(existing.port);
@@ -1218,7 +1413,7 @@ impl MikrotikService {
         let maintenance_until = req.maintenance_until;
         let maintenance_reason = req.maintenance_reason;
 
-        sqlx::query(
+        let result = sqlx::query(
             r#"
             UPDATE mikrotik_routers SET
               name = $1,
@@ -1232,8 +1427,9 @@ impl MikrotikService {
               maintenance_reason = $9,
               latitude = $10,
               longitude = $11,
-              updated_at = $12
-            WHERE id = $13 AND tenant_id = $14
+              updated_at = $12,
+              config_updated_at = $12
+            WHERE id = $13 AND tenant_id = $14 AND config_updated_at = $15
             "#,
         )
         .bind(&name)
@@ -1250,28 +1446,613 @@ impl MikrotikService {
         .bind(now)
         .bind(id)
         .bind(tenant_id)
+        .bind(previous_config_updated_at)
         .execute(&self.pool)
         .await
         .map_err(AppError::Database)?;
 
+        if result.rows_affected() == 0 {
+            return Err(concurrency::modified_conflict("router"));
+        }
+
         let updated = self
             .get_router(tenant_id, id)
             .await?
             .ok_or_else(|| AppError::NotFound("Router not found".to_string()))?;
 
-        Ok(updated)
+        let mut change = AuditChange::between(&before, &updated);
+        for field in ROUTER_STATUS_FIELDS {
+            change.old_value.remove(*field);
+            change.new_value.remove(*field);
+        }
+        // The password is never serialized; it is re-encrypted only when a new one is set.
+        if before.password != updated.password {
+            change = change.with_secret("password");
+        }
+
+        Ok((updated, change))
     }
 
+    /// Move a router to the trash; the poller stops monitoring it immediately.
     pub async fn delete_router(&self, tenant_id: &str, id: &str) -> AppResult<()> {
-        sqlx::query("DELETE FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2")
+        sqlx::query(
+            r#"
+            UPDATE mikrotik_routers
+            SET deleted_at = $3, updated_at = $3
+            WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(tenant_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    pub async fn list_deleted_routers(&self, tenant_id: &str) -> AppResult<Vec<TrashItem>> {
+        let rows = sqlx::query_as::<_, TrashItem>(
+            r#"
+            SELECT id, name, host AS detail, deleted_at
+            FROM mikrotik_routers
+            WHERE tenant_id = $1 AND deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows)
+    }
+
+    pub async fn restore_router(&self, tenant_id: &str, id: &str) -> AppResult<MikrotikRouter> {
+        let res = sqlx::query(
+            r#"
+            UPDATE mikrotik_routers
+            SET deleted_at = NULL, updated_at = $3
+            WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(id)
+        .bind(tenant_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound("Router not found in trash".into()));
+        }
+
+        self.get_router(tenant_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Router not found".into()))
+    }
+
+    pub async fn list_devices(&self, tenant_id: &str) -> AppResult<Vec<MonitoredDevice>> {
+        sqlx::query_as::<_, MonitoredDevice>(&format!(
+            "{DEVICE_SELECT} WHERE d.tenant_id = $1 AND r.deleted_at IS NULL ORDER BY d.name"
+        ))
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// A device; `routeros` devices are hidden while their router is in the trash.
+    pub async fn get_device(
+        &self,
+        tenant_id: &str,
+        id: &str,
+    ) -> AppResult<Option<MonitoredDevice>> {
+        sqlx::query_as::<_, MonitoredDevice>(&format!(
+            "{DEVICE_SELECT} WHERE d.tenant_id = $1 AND d.id = $2 AND r.deleted_at IS NULL"
+        ))
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    pub async fn create_device(
+        &self,
+        tenant_id: &str,
+        req: CreateMonitoredDeviceRequest,
+    ) -> AppResult<MonitoredDevice> {
+        let protocol = req.protocol.trim().to_ascii_lowercase();
+        let mut device = match protocol.as_str() {
+            "snmp" => {
+                let host = req.host.unwrap_or_default();
+                let mut device =
+                    MonitoredDevice::new(tenant_id.to_string(), req.name, host, "snmp".into());
+                device.snmp_version = req.snmp_version;
+                device.snmp_port = req.snmp_port.unwrap_or(161);
+                device.snmp_community = req.snmp_community.unwrap_or_default();
+                device.snmp_username = req.snmp_username;
+                device.snmp_auth_protocol = req.snmp_auth_protocol;
+                device.snmp_auth_password = req.snmp_auth_password.unwrap_or_default();
+                device.snmp_priv_protocol = req.snmp_priv_protocol;
+                device.snmp_priv_password = req.snmp_priv_password.unwrap_or_default();
+                device.cpu_oid = req.cpu_oid;
+                device.enabled = req.enabled.unwrap_or(true);
+                device.maintenance_until = req.maintenance_until;
+                device
+            }
+            "routeros" => {
+                let router_id = req.router_id.as_deref().ok_or_else(|| {
+                    AppError::Validation("router_id is required for a routeros device".to_string())
+                })?;
+                let router = self
+                    .get_router(tenant_id, router_id)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound("Router not found".to_string()))?;
+                let taken: bool = sqlx::query_scalar(
+                    "SELECT EXISTS(SELECT 1 FROM monitored_devices WHERE router_id = $1)",
+                )
+                .bind(&router.id)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(AppError::Database)?;
+                if taken {
+                    return Err(AppError::Conflict(
+                        "The router is already a monitored device".to_string(),
+                    ));
+                }
+                let mut device = MonitoredDevice::new(
+                    tenant_id.to_string(),
+                    req.name,
+                    router.host,
+                    "routeros".into(),
+                );
+                device.router_id = Some(router.id);
+                device
+            }
+            _ => {
+                return Err(AppError::Validation(
+                    "protocol must be snmp or routeros".to_string(),
+                ))
+            }
+        };
+        if let Some(device_type) = req.device_type {
+            device.device_type = device_type;
+        }
+        Self::validate_device(&mut device)?;
+        Self::seal_device_secrets(&mut device)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO monitored_devices
+            (id, tenant_id, name, host, device_type, protocol, router_id,
+             snmp_version, snmp_port, snmp_community, snmp_username,
+             snmp_auth_protocol, snmp_auth_password, snmp_priv_protocol, snmp_priv_password,
+             cpu_oid, enabled, maintenance_until, is_online, created_at, updated_at)
+            VALUES
+            ($1,$2,$3,$4,$5,$6,$7,
+             $8,$9,$10,$11,
+             $12,$13,$14,$15,
+             $16,$17,$18,$19,$20,$21)
+            "#,
+        )
+        .bind(&device.id)
+        .bind(&device.tenant_id)
+        .bind(&device.name)
+        .bind(&device.host)
+        .bind(&device.device_type)
+        .bind(&device.protocol)
+        .bind(&device.router_id)
+        .bind(&device.snmp_version)
+        .bind(device.snmp_port)
+        .bind(&device.snmp_community)
+        .bind(&device.snmp_username)
+        .bind(&device.snmp_auth_protocol)
+        .bind(&device.snmp_auth_password)
+        .bind(&device.snmp_priv_protocol)
+        .bind(&device.snmp_priv_password)
+        .bind(&device.cpu_oid)
+        .bind(device.enabled)
+        .bind(device.maintenance_until)
+        .bind(device.is_online)
+        .bind(device.created_at)
+        .bind(device.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        self.get_device(tenant_id, &device.id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Device not found".to_string()))
+    }
+
+    /// Updates a device; also returns the edit's diff for the audit log.
+    /// Enabling and maintenance of a `routeros` device stay with its router.
+    pub async fn update_device(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        req: UpdateMonitoredDeviceRequest,
+    ) -> AppResult<(MonitoredDevice, AuditChange)> {
+        let before = self
+            .get_device(tenant_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Device not found".to_string()))?;
+
+        let mut device = before.clone();
+        Self::open_device_secrets(&mut device)?;
+        if let Some(name) = req.name {
+            device.name = name;
+        }
+        if let Some(device_type) = req.device_type {
+            device.device_type = device_type;
+        }
+        let mut new_secrets = Vec::new();
+        if device.protocol == "snmp" {
+            if let Some(host) = req.host {
+                device.host = host;
+            }
+            if req.snmp_version.is_some() {
+                device.snmp_version = req.snmp_version;
+            }
+            if let Some(port) = req.snmp_port {
+                device.snmp_port = port;
+            }
+            if req.snmp_username.is_some() {
+                device.snmp_username = req.snmp_username;
+            }
+            if req.snmp_auth_protocol.is_some() {
+                device.snmp_auth_protocol = req.snmp_auth_protocol;
+            }
+            if req.snmp_priv_protocol.is_some() {
+                device.snmp_priv_protocol = req.snmp_priv_protocol;
+            }
+            for (field, value, slot) in [
+                (
+                    "snmp_community",
+                    req.snmp_community,
+                    &mut device.snmp_community,
+                ),
+                (
+                    "snmp_auth_password",
+                    req.snmp_auth_password,
+                    &mut device.snmp_auth_password,
+                ),
+                (
+                    "snmp_priv_password",
+                    req.snmp_priv_password,
+                    &mut device.snmp_priv_password,
+                ),
+            ] {
+                if let Some(value) = value.filter(|v| !v.is_empty()) {
+                    *slot = value;
+                    new_secrets.push(field);
+                }
+            }
+            if req.cpu_oid.is_some() {
+                device.cpu_oid = req.cpu_oid;
+            }
+            if let Some(enabled) = req.enabled {
+                device.enabled = enabled;
+            }
+            device.maintenance_until = req.maintenance_until;
+        }
+        Self::validate_device(&mut device)?;
+        Self::seal_device_secrets(&mut device)?;
+
+        sqlx::query(
+            r#"
+            UPDATE monitored_devices SET
+              name = $1,
+              host = $2,
+              device_type = $3,
+              snmp_version = $4,
+              snmp_port = $5,
+              snmp_community = $6,
+              snmp_username = $7,
+              snmp_auth_protocol = $8,
+              snmp_auth_password = $9,
+              snmp_priv_protocol = $10,
+              snmp_priv_password = $11,
+              cpu_oid = $12,
+              enabled = $13,
+              maintenance_until = $14,
+              updated_at = $15
+            WHERE id = $16 AND tenant_id = $17
+            "#,
+        )
+        .bind(&device.name)
+        .bind(&device.host)
+        .bind(&device.device_type)
+        .bind(&device.snmp_version)
+        .bind(device.snmp_port)
+        .bind(&device.snmp_community)
+        .bind(&device.snmp_username)
+        .bind(&device.snmp_auth_protocol)
+        .bind(&device.snmp_auth_password)
+        .bind(&device.snmp_priv_protocol)
+        .bind(&device.snmp_priv_password)
+        .bind(&device.cpu_oid)
+        .bind(device.enabled)
+        .bind(device.maintenance_until)
+        .bind(Utc::now())
+        .bind(id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let updated = self
+            .get_device(tenant_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Device not found".to_string()))?;
+
+        let mut change = AuditChange::between(&before, &updated);
+        for field in DEVICE_STATUS_FIELDS {
+            change.old_value.remove(*field);
+            change.new_value.remove(*field);
+        }
+        for field in new_secrets {
+            change = change.with_secret(field);
+        }
+
+        Ok((updated, change))
+    }
+
+    /// Deletes a device along with the alerts and incidents it raised. A
+    /// `routeros` device only leaves the list; its router is still polled.
+    pub async fn delete_device(&self, tenant_id: &str, id: &str) -> AppResult<MonitoredDevice> {
+        let device = self
+            .get_device(tenant_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Device not found".to_string()))?;
+
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        for table in ["mikrotik_alerts", "mikrotik_incidents"] {
+            sqlx::query(&format!(
+                "DELETE FROM {table} WHERE tenant_id = $1 AND router_id = $2 AND source = 'snmp'"
+            ))
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        }
+        sqlx::query("DELETE FROM monitored_devices WHERE id = $1 AND tenant_id = $2")
             .bind(id)
             .bind(tenant_id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await
             .map_err(AppError::Database)?;
+        tx.commit().await.map_err(AppError::Database)?;
+
+        Ok(device)
+    }
+
+    /// Probes a device once without recording anything.
+    pub async fn test_device(
+        &self,
+        tenant_id: &str,
+        id: &str,
+    ) -> AppResult<MonitoredDeviceTestResult> {
+        let device = self
+            .get_device(tenant_id, id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Device not found".to_string()))?;
+
+        if let Some(router_id) = device.router_id.as_deref() {
+            let result = self.test_connection(tenant_id, router_id).await?;
+            return Ok(MonitoredDeviceTestResult {
+                ok: result.ok,
+                sys_name: result.identity,
+                sys_descr: result.ros_version,
+                latency_ms: result.latency_ms,
+                error: result.error,
+            });
+        }
+
+        let started = Instant::now();
+        let probe = Self::snmp_probe(&device).await;
+        let latency_ms = Some(started.elapsed().as_millis().min(i32::MAX as u128) as i32);
+        Ok(match probe {
+            Ok(reading) => MonitoredDeviceTestResult {
+                ok: true,
+                sys_name: reading.sys_name,
+                sys_descr: reading.sys_descr,
+                latency_ms,
+                error: None,
+            },
+            Err(e) => MonitoredDeviceTestResult {
+                ok: false,
+                sys_name: None,
+                sys_descr: None,
+                latency_ms,
+                error: Some(e),
+            },
+        })
+    }
+
+    /// Normalizes a device's settings and checks them, with its secrets in
+    /// plain text.
+    fn validate_device(device: &mut MonitoredDevice) -> AppResult<()> {
+        fn blank_to_none(v: &mut Option<String>) {
+            if v.as_deref().is_some_and(|s| s.trim().is_empty()) {
+                *v = None;
+            }
+        }
+
+        device.name = device.name.trim().to_string();
+        if device.name.is_empty() {
+            return Err(AppError::Validation("Name is required".to_string()));
+        }
+        device.device_type = device.device_type.trim().to_ascii_lowercase();
+        if !DEVICE_TYPES.contains(&device.device_type.as_str()) {
+            return Err(AppError::Validation(format!(
+                "device_type must be one of {}",
+                DEVICE_TYPES.join(", ")
+            )));
+        }
+        if device.protocol != "snmp" {
+            return Ok(());
+        }
+
+        device.host = device.host.trim().to_string();
+        if device.host.is_empty() {
+            return Err(AppError::Validation("Host is required".to_string()));
+        }
+        if !(1..=65535).contains(&device.snmp_port) {
+            return Err(AppError::Validation(
+                "snmp_port must be between 1 and 65535".to_string(),
+            ));
+        }
+        for field in [
+            &mut device.snmp_version,
+            &mut device.snmp_username,
+            &mut device.snmp_auth_protocol,
+            &mut device.snmp_priv_protocol,
+            &mut device.cpu_oid,
+        ] {
+            blank_to_none(field);
+        }
+        device.snmp_version = device
+            .snmp_version
+            .as_deref()
+            .map(|v| v.trim().to_ascii_lowercase());
+        if let Some(oid) = device.cpu_oid.as_deref() {
+            parse_oid(oid).map_err(|e| AppError::Validation(format!("cpu_oid: {}", e)))?;
+            device.cpu_oid = Some(oid.trim().to_string());
+        }
+
+        // Keep only what the chosen version uses, in canonical form.
+        match Self::snmp_credentials(device)? {
+            SnmpCredentials::V2c { .. } => {
+                device.snmp_username = None;
+                device.snmp_auth_protocol = None;
+                device.snmp_auth_password.clear();
+                device.snmp_priv_protocol = None;
+                device.snmp_priv_password.clear();
+            }
+            SnmpCredentials::V3(user) => {
+                device.snmp_community.clear();
+                device.snmp_username = Some(user.username);
+                device.snmp_auth_protocol = user.auth.as_ref().map(|(p, _)| p.as_str().into());
+                device.snmp_priv_protocol = user.privacy.as_ref().map(|(p, _)| p.as_str().into());
+                if user.auth.is_none() {
+                    device.snmp_auth_password.clear();
+                }
+                if user.privacy.is_none() {
+                    device.snmp_priv_password.clear();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Credentials of a device whose secrets are in plain text.
+    fn snmp_credentials(device: &MonitoredDevice) -> AppResult<SnmpCredentials> {
+        let invalid = |e: SnmpError| AppError::Validation(e.to_string());
+        match device.snmp_version.as_deref() {
+            Some("v2c") => SnmpCredentials::v2c(&device.snmp_community).map_err(invalid),
+            Some("v3") => {
+                let auth = match device.snmp_auth_protocol.as_deref() {
+                    Some(p) => Some((
+                        AuthProtocol::parse(p).ok_or_else(|| {
+                            AppError::Validation(format!("Unknown SNMP auth protocol '{}'", p))
+                        })?,
+                        device.snmp_auth_password.clone(),
+                    )),
+                    None => None,
+                };
+                let privacy = match device.snmp_priv_protocol.as_deref() {
+                    Some(p) => Some((
+                        PrivProtocol::parse(p).ok_or_else(|| {
+                            AppError::Validation(format!("Unknown SNMP privacy protocol '{}'", p))
+                        })?,
+                        device.snmp_priv_password.clone(),
+                    )),
+                    None => None,
+                };
+                let username = device.snmp_username.as_deref().unwrap_or_default();
+                SnmpCredentials::v3(username, auth, privacy).map_err(invalid)
+            }
+            _ => Err(AppError::Validation(
+                "snmp_version must be v2c or v3".to_string(),
+            )),
+        }
+    }
+
+    fn seal_device_secrets(device: &mut MonitoredDevice) -> AppResult<()> {
+        for secret in [
+            &mut device.snmp_community,
+            &mut device.snmp_auth_password,
+            &mut device.snmp_priv_password,
+        ] {
+            if !secret.is_empty() {
+                *secret = encrypt_secret_for(PURPOSE_SNMP, secret)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn open_device_secrets(device: &mut MonitoredDevice) -> AppResult<()> {
+        for secret in [
+            &mut device.snmp_community,
+            &mut device.snmp_auth_password,
+            &mut device.snmp_priv_password,
+        ] {
+            *secret = decrypt_secret_for(PURPOSE_SNMP, secret)?;
+        }
         Ok(())
     }
 
+    /// Reads the system group, and the CPU load when the device has a CPU
+    /// OID. Errors come back as the message to show.
+    async fn snmp_probe(device: &MonitoredDevice) -> Result<SnmpReading, String> {
+        let mut plain = device.clone();
+        Self::open_device_secrets(&mut plain).map_err(|e| e.to_string())?;
+        let credentials = Self::snmp_credentials(&plain).map_err(|e| e.to_string())?;
+        let cpu_oid = match device.cpu_oid.as_deref() {
+            Some(oid) => Some(parse_oid(oid).map_err(|e| e.to_string())?),
+            None => None,
+        };
+
+        let mut oids = vec![
+            OID_SYS_DESCR.to_vec(),
+            OID_SYS_UPTIME.to_vec(),
+            OID_SYS_NAME.to_vec(),
+        ];
+        oids.extend(cpu_oid.clone());
+        let port = u16::try_from(device.snmp_port).map_err(|_| "Invalid SNMP port".to_string())?;
+        let client = SnmpClient::new(
+            &device.host,
+            port,
+            credentials,
+            Duration::from_secs(SNMP_TIMEOUT_SECS),
+        );
+        let values = client.get(&oids).await.map_err(|e| e.to_string())?;
+
+        let value = |oid: &[u32]| {
+            values
+                .iter()
+                .find(|(o, _)| o.as_slice() == oid)
+                .map(|(_, v)| v)
+        };
+        Ok(SnmpReading {
+            sys_name: value(OID_SYS_NAME).and_then(SnmpValue::as_text),
+            sys_descr: value(OID_SYS_DESCR).and_then(SnmpValue::as_text),
+            // sysUpTime counts hundredths of a second.
+            uptime_seconds: value(OID_SYS_UPTIME)
+                .and_then(SnmpValue::as_i64)
+                .map(|t| t / 100),
+            cpu_load: cpu_oid
+                .as_deref()
+                .and_then(value)
+                .and_then(SnmpValue::as_i64)
+                .map(|v| v.clamp(0, 100) as i32),
+        })
+    }
+
     pub async fn list_metrics(
         &self,
         tenant_id: &str,
@@ -1280,10 +2061,12 @@ impl MikrotikService {
     ) -> AppResult<Vec<MikrotikRouterMetric>> {
         // Ensure router belongs to tenant
         let exists: Option<String> =
-            sqlx::query_scalar("SELECT id FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2")
+            sqlx::query_scalar(
+                "SELECT id FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL",
+            )
                 .bind(router_id)
                 .bind(tenant_id)
-                .fetch_optional(&self.pool)
+                .fetch_optional(self.router.reader())
                 .await
                 .map_err(AppError::Database)?;
 
@@ -1301,7 +2084,7 @@ impl MikrotikService {
         )
         .bind(router_id)
         .bind(limit as i64)
-        .fetch_all(&self.pool)
+        .fetch_all(self.router.reader())
         .await
         .map_err(AppError::Database)?;
 
@@ -1317,10 +2100,12 @@ impl MikrotikService {
     ) -> AppResult<Vec<MikrotikInterfaceMetric>> {
         // Ensure router belongs to tenant
         let exists: Option<String> =
-            sqlx::query_scalar("SELECT id FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2")
+            sqlx::query_scalar(
+                "SELECT id FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL",
+            )
                 .bind(router_id)
                 .bind(tenant_id)
-                .fetch_optional(&self.pool)
+                .fetch_optional(self.router.reader())
                 .await
                 .map_err(AppError::Database)?;
 
@@ -1341,7 +2126,7 @@ impl MikrotikService {
             .bind(router_id)
             .bind(ifname)
             .bind(limit as i64)
-            .fetch_all(&self.pool)
+            .fetch_all(self.router.reader())
             .await
             .map_err(AppError::Database)?
         } else {
@@ -1355,7 +2140,7 @@ impl MikrotikService {
             )
             .bind(router_id)
             .bind(limit as i64)
-            .fetch_all(&self.pool)
+            .fetch_all(self.router.reader())
             .await
             .map_err(AppError::Database)?
         };
@@ -1370,7 +2155,9 @@ impl MikrotikService {
     ) -> AppResult<Vec<MikrotikInterfaceMetric>> {
         // Ensure router belongs to tenant
         let exists: Option<String> =
-            sqlx::query_scalar("SELECT id FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2")
+            sqlx::query_scalar(
+                "SELECT id FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL",
+            )
                 .bind(router_id)
                 .bind(tenant_id)
                 .fetch_optional(&self.pool)
@@ -1822,40 +2609,38 @@ impl MikrotikService {
         Ok((identity, version))
     }
 
-    /// Background poller (best-effort).
+    /// Polls routers and SNMP devices and expires old metrics on the job queue.
     ///
-    /// Default interval: 300s. Can be overridden by `MIKROTIK_POLL_INTERVAL_SECS`.
-    pub fn start_poller(self: Arc<Self>) {
-        tokio::spawn(async move {
-            let interval_secs = std::env::var("MIKROTIK_POLL_INTERVAL_SECS")
-                .ok()
-                .and_then(|v| v.parse::<u64>().ok())
-                .filter(|v| *v >= 30 && *v <= 3600)
-                .unwrap_or(300);
-
-            let cleanup_interval_secs = std::env::var("MIKROTIK_METRICS_CLEANUP_INTERVAL_SECS")
-                .ok()
-                .and_then(|v| v.parse::<u64>().ok())
-                .filter(|v| *v >= 60 && *v <= 86400)
-                .unwrap_or(3600);
-
-            let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
-            let mut last_cleanup = std::time::Instant::now()
-                .checked_sub(Duration::from_secs(cleanup_interval_secs))
-                .unwrap_or_else(std::time::Instant::now);
-            loop {
-                interval.tick().await;
-                if let Err(e) = self.poll_once().await {
-                    warn!("[MikrotikPoller] Poll failed: {}", e);
-                }
-                if last_cleanup.elapsed().as_secs() >= cleanup_interval_secs {
-                    if let Err(e) = self.cleanup_old_metrics().await {
-                        warn!("[MikrotikPoller] Metrics cleanup failed: {}", e);
-                    }
-                    last_cleanup = std::time::Instant::now();
-                }
-            }
-        });
+    /// Poll interval: 300s, overridable with `MIKROTIK_POLL_INTERVAL_SECS`.
+    /// Metrics cleanup: hourly, overridable with
+    /// `MIKROTIK_METRICS_CLEANUP_INTERVAL_SECS`.
+    pub async fn schedule_poller(&self, queue: &JobQueue) {
+        let interval_secs = std::env::var("MIKROTIK_POLL_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v >= 30 && *v <= 3600)
+            .unwrap_or(300);
+
+        let cleanup_interval_secs = std::env::var("MIKROTIK_METRICS_CLEANUP_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v >= 60 && *v <= 86400)
+            .unwrap_or(3600);
+
+        queue
+            .register_recurring(
+                "mikrotik.poll",
+                Duration::from_secs(interval_secs),
+                Arc::new(MikrotikPollJob(self.clone())),
+            )
+            .await;
+        queue
+            .register_recurring(
+                "mikrotik.metrics_cleanup",
+                Duration::from_secs(cleanup_interval_secs),
+                Arc::new(MikrotikMetricsCleanupJob(self.clone())),
+            )
+            .await;
     }
 
     async fn metrics_retention_days(&self) -> i64 {
@@ -1880,90 +2665,79 @@ impl MikrotikService {
 
         let cutoff = Utc::now() - ChronoDuration::days(retention_days);
 
+        // Metric tables are partitioned by month on Postgres: expired months are
+        // dropped whole, and only the month the cutoff falls in is deleted by row.
         #[cfg(feature = "postgres")]
-        async fn prune_table(
-            pool: &DbPool,
-            table: &str,
-            cutoff: DateTime<Utc>,
-            batch_size: i64,
-        ) -> Result<u64, sqlx::Error> {
-            let mut total = 0u64;
-            loop {
-                let sql = format!(
-                    r#"
-                    DELETE FROM {table}
-                    WHERE ctid IN (
-                        SELECT ctid FROM {table}
-                        WHERE ts < $1
-                        LIMIT $2
-                    )
-                    "#
-                );
-
-                let affected = sqlx::query(&sql)
-                    .bind(cutoff)
-                    .bind(batch_size)
-                    .execute(pool)
-                    .await?
-                    .rows_affected();
+        {
+            let dropped_iface =
+                partitions::drop_expired(&self.pool, "mikrotik_interface_metrics", cutoff)
+                    .await
+                    .map_err(AppError::Database)?;
+            let dropped_router =
+                partitions::drop_expired(&self.pool, "mikrotik_router_metrics", cutoff)
+                    .await
+                    .map_err(AppError::Database)?;
 
-                total = total.saturating_add(affected);
-                if affected == 0 {
-                    break;
-                }
+            if dropped_iface > 0 || dropped_router > 0 {
+                info!(
+                    "[MikrotikPoller] Metrics cleanup done: dropped partitions interface={} router={} (retention={}d)",
+                    dropped_iface, dropped_router, retention_days
+                );
             }
-            Ok(total)
         }
 
         #[cfg(feature = "sqlite")]
-        async fn prune_table(
-            pool: &DbPool,
-            table: &str,
-            cutoff: DateTime<Utc>,
-            batch_size: i64,
-        ) -> Result<u64, sqlx::Error> {
-            let mut total = 0u64;
-            loop {
-                let sql = format!(
-                    r#"
-                    DELETE FROM {table}
-                    WHERE rowid IN (
-                        SELECT rowid FROM {table}
-                        WHERE ts < $1
-                        LIMIT $2
-                    )
-                    "#
-                );
-
-                let affected = sqlx::query(&sql)
-                    .bind(cutoff)
-                    .bind(batch_size)
-                    .execute(pool)
-                    .await?
-                    .rows_affected();
-
-                total = total.saturating_add(affected);
-                if affected == 0 {
-                    break;
+        {
+            async fn prune_table(
+                pool: &DbPool,
+                table: &str,
+                cutoff: DateTime<Utc>,
+                batch_size: i64,
+            ) -> Result<u64, sqlx::Error> {
+                let mut total = 0u64;
+                loop {
+                    let sql = format!(
+                        r#"
+                        DELETE FROM {table}
+                        WHERE rowid IN (
+                            SELECT rowid FROM {table}
+                            WHERE ts < $1
+                            LIMIT $2
+                        )
+                        "#
+                    );
+
+                    let affected = sqlx::query(&sql)
+                        .bind(cutoff)
+                        .bind(batch_size)
+                        .execute(pool)
+                        .await?
+                        .rows_affected();
+
+                    total = total.saturating_add(affected);
+                    if affected == 0 {
+                        break;
+                    }
                 }
+                Ok(total)
             }
-            Ok(total)
-        }
 
-        let batch_size = 5_000i64;
-        let deleted_iface =
-            prune_table(&self.pool, "mikrotik_interface_metrics", cutoff, batch_size)
-                .await
-                .map_err(AppError::Database)?;
-        let deleted_router = prune_table(&self.pool, "mikrotik_router_metrics", cutoff, batch_size)
-            .await
-            .map_err(AppError::Database)?;
+            let batch_size = 5_000i64;
+            let deleted_iface =
+                prune_table(&self.pool, "mikrotik_interface_metrics", cutoff, batch_size)
+                    .await
+                    .map_err(AppError::Database)?;
+            let deleted_router =
+                prune_table(&self.pool, "mikrotik_router_metrics", cutoff, batch_size)
+                    .await
+                    .map_err(AppError::Database)?;
 
-        if deleted_iface > 0 || deleted_router > 0 {
-            info!(
-                "[MikrotikPoller] Metrics cleanup done: deleted interface={} router={} (retention={}d)",
-                deleted_iface, deleted_router, retention_days
-            );
+            if deleted_iface > 0 || deleted_router > 0 {
+                info!(
+                    "[MikrotikPoller] Metrics cleanup done: deleted interface={} router={} (retention={}d)",
+                    deleted_iface, deleted_router, retention_days
+                );
+            }
         }
 
         Ok(())
@@ -1973,7 +2747,7 @@ impl MikrotikService {
         let routers = sqlx::query_as::<_, MikrotikRouter>(
             r#"
             SELECT * FROM mikrotik_routers
-            WHERE enabled = true
+            WHERE enabled = true AND deleted_at IS NULL
             ORDER BY updated_at DESC
             "#,
         )
@@ -2000,7 +2774,24 @@ impl MikrotikService {
             let _ = self.poll_router(router, tracked_for_router).await;
         }
 
-        for tenant_id in tracked_by_tenant.keys() {
+        let devices = sqlx::query_as::<_, MonitoredDevice>(
+            r#"
+            SELECT * FROM monitored_devices
+            WHERE protocol = 'snmp' AND enabled = true
+            ORDER BY updated_at DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let mut tenant_ids: HashSet<String> = tracked_by_tenant.into_keys().collect();
+        for device in devices {
+            tenant_ids.insert(device.tenant_id.clone());
+            let _ = self.poll_device(device).await;
+        }
+
+        for tenant_id in &tenant_ids {
             let _ = self.auto_escalate_incidents(tenant_id).await;
         }
         Ok(())
@@ -2081,13 +2872,14 @@ impl MikrotikService {
             }
             escalated_count += affected as i64;
 
+            let vars = HashMap::from([
+                ("incident_title", incident.title.clone()),
+                ("threshold_minutes", threshold_minutes.to_string()),
+            ]);
             self.notify_tenant(
                 tenant_id,
-                "Incident escalated",
-                format!(
-                    "{} has exceeded {} minutes without acknowledgement.",
-                    incident.title, threshold_minutes
-                ),
+                "incident_escalated",
+                &vars,
                 Some(format!("/admin/network/incidents?incident={}", incident.id)),
                 "error",
             )
@@ -2126,6 +2918,7 @@ impl MikrotikService {
         let latency_ms = Some(started.elapsed().as_millis().min(i32::MAX as u128) as i32);
 
         let in_maintenance = router.maintenance_until.map(|u| u > now).unwrap_or(false);
+        let subject = AlertSubject::router(&router);
 
         match probe {
             Ok((identity, version)) => {
@@ -2233,45 +3026,243 @@ impl MikrotikService {
                     }
                 }
 
-                // Resolve "offline" incident and evaluate CPU/latency incidents.
+                // Resolve "offline" incident and evaluate CPU/latency incidents.
+                if in_maintenance {
+                    let _ = self.resolve_all_router_alerts(&tenant_id, &router.id).await;
+                } else {
+                    let _ = self.resolve_alert(&tenant_id, &router.id, "offline").await;
+                    let _ = self
+                        .eval_cpu_alert(&tenant_id, subject, metric.cpu_load, now)
+                        .await;
+                    let _ = self
+                        .eval_latency_alert(&tenant_id, subject, latency_ms, now)
+                        .await;
+                }
+
+                if !prev_online {
+                    let offline_for_secs = {
+                        let base = router.last_seen_at.unwrap_or(router.created_at);
+                        (now - base).num_seconds().max(0)
+                    };
+                    let recovered_after_secs = std::env::var("MIKROTIK_RECOVERED_AFTER_SECS")
+                        .ok()
+                        .and_then(|v| v.parse::<i64>().ok())
+                        .unwrap_or(300)
+                        .clamp(30, 24 * 3600);
+                    let code = if offline_for_secs >= recovered_after_secs {
+                        "router_recovered"
+                    } else {
+                        "router_online"
+                    };
+                    let vars = HashMap::from([
+                        ("router_name", router.name.clone()),
+                        ("offline_seconds", offline_for_secs.to_string()),
+                    ]);
+                    self.notify_router_status_change(
+                        &tenant_id,
+                        code,
+                        &vars,
+                        Some(format!("/admin/network/routers/{}", router.id)),
+                        "success",
+                    )
+                    .await;
+
+                    self.audit_service
+                        .log(
+                            None,
+                            Some(&tenant_id),
+                            "status_online",
+                            "mikrotik_router",
+                            Some(&router.id),
+                            Some(&format!(
+                                "{} is back online (offline {}s)",
+                                router.name, offline_for_secs
+                            )),
+                            None,
+                        )
+                        .await;
+                }
+            }
+            Err(e) => {
+                let msg = e.to_string();
+                let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+                sqlx::query(
+                    r#"
+                    UPDATE mikrotik_routers SET
+                      is_online = false,
+                      latency_ms = $1,
+                      last_error = $2,
+                      updated_at = $3
+                    WHERE id = $4
+                    "#,
+                )
+                .bind(latency_ms)
+                .bind(&msg)
+                .bind(now)
+                .bind(&router.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::Database)?;
+                // Staged with the status change, so the webhook can't get lost
+                // between the two.
+                if prev_online {
+                    let event = DomainEvent::RouterOffline {
+                        router_id: router.id.clone(),
+                        name: router.name.clone(),
+                        host: router.host.clone(),
+                        error: msg.clone(),
+                        at: now,
+                    };
+                    EventOutboxService::stage(&mut tx, Some(tenant_id.as_str()), &event)
+                        .await
+                        .map_err(AppError::Database)?;
+                }
+                tx.commit().await.map_err(AppError::Database)?;
+                if prev_online {
+                    event_outbox_service::wake();
+                }
+
+                if in_maintenance {
+                    let _ = self.resolve_all_router_alerts(&tenant_id, &router.id).await;
+                } else {
+                    let th = self.get_thresholds(&tenant_id).await;
+                    if !th.enabled {
+                        let _ = self.resolve_all_router_alerts(&tenant_id, &router.id).await;
+                    } else {
+                        // Only open an incident after the router has been unreachable for a while (anti-flap).
+                        let base = router.last_seen_at.unwrap_or(router.created_at);
+                        let offline_for_secs = (now - base).num_seconds().max(0);
+                        if offline_for_secs >= th.offline_after_secs {
+                            // Create/refresh "offline" incident. CPU/latency becomes unknown when offline, so resolve them.
+                            let created = self
+                                .upsert_alert(
+                                    &tenant_id,
+                                    subject,
+                                    "offline",
+                                    "critical",
+                                    "Router offline",
+                                    format!(
+                                        "{} is unreachable ({}s).",
+                                        router.name, offline_for_secs
+                                    ),
+                                    Some(offline_for_secs as f64),
+                                    Some(th.offline_after_secs.max(0) as f64),
+                                    now,
+                                )
+                                .await
+                                .unwrap_or(false);
+                            if created {
+                                self.notify_customers_outage(&tenant_id, &router);
+                            }
+                        }
+                    }
+                    let _ = self.resolve_alert(&tenant_id, &router.id, "cpu").await;
+                    let _ = self.resolve_alert(&tenant_id, &router.id, "latency").await;
+                }
+
+                if prev_online {
+                    let vars = HashMap::from([
+                        ("router_name", router.name.clone()),
+                        ("error", msg.to_string()),
+                    ]);
+                    self.notify_router_status_change(
+                        &tenant_id,
+                        "router_down",
+                        &vars,
+                        Some(format!("/admin/network/routers/{}", router.id)),
+                        "error",
+                    )
+                    .await;
+
+                    self.audit_service
+                        .log(
+                            None,
+                            Some(&tenant_id),
+                            "status_offline",
+                            "mikrotik_router",
+                            Some(&router.id),
+                            Some(&format!("{} became unreachable: {}", router.name, msg)),
+                            None,
+                        )
+                        .await;
+                }
+            }
+        }
+
+        info!(
+            "[MikrotikPoller] {} ({}) polled in {}ms",
+            router.name,
+            router.host,
+            started.elapsed().as_millis()
+        );
+
+        Ok(())
+    }
+
+    /// Polls one SNMP device. Status changes, CPU and latency go through the
+    /// same alerts and notifications as a router's.
+    async fn poll_device(&self, device: MonitoredDevice) -> AppResult<()> {
+        let started = Instant::now();
+        let probe = Self::snmp_probe(&device).await;
+        let now = Utc::now();
+        let latency_ms = Some(started.elapsed().as_millis().min(i32::MAX as u128) as i32);
+
+        let tenant_id = device.tenant_id.as_str();
+        let in_maintenance = device.maintenance_until.map(|u| u > now).unwrap_or(false);
+        let subject = AlertSubject::device(&device);
+
+        match probe {
+            Ok(reading) => {
+                sqlx::query(
+                    r#"
+                    UPDATE monitored_devices SET
+                      is_online = true,
+                      last_seen_at = $1,
+                      latency_ms = $2,
+                      last_error = NULL,
+                      sys_name = $3,
+                      sys_descr = $4,
+                      uptime_seconds = $5,
+                      cpu_load = $6,
+                      updated_at = $1
+                    WHERE id = $7
+                    "#,
+                )
+                .bind(now)
+                .bind(latency_ms)
+                .bind(&reading.sys_name)
+                .bind(&reading.sys_descr)
+                .bind(reading.uptime_seconds)
+                .bind(reading.cpu_load)
+                .bind(&device.id)
+                .execute(&self.pool)
+                .await
+                .map_err(AppError::Database)?;
+
                 if in_maintenance {
-                    let _ = self.resolve_all_router_alerts(&tenant_id, &router.id).await;
+                    let _ = self.resolve_all_router_alerts(tenant_id, &device.id).await;
                 } else {
-                    let _ = self.resolve_alert(&tenant_id, &router.id, "offline").await;
+                    let _ = self.resolve_alert(tenant_id, &device.id, "offline").await;
                     let _ = self
-                        .eval_cpu_alert(&tenant_id, &router, metric.cpu_load, now)
+                        .eval_cpu_alert(tenant_id, subject, reading.cpu_load, now)
                         .await;
                     let _ = self
-                        .eval_latency_alert(&tenant_id, &router, latency_ms, now)
+                        .eval_latency_alert(tenant_id, subject, latency_ms, now)
                         .await;
                 }
 
-                if !prev_online {
-                    let offline_for_secs = {
-                        let base = router.last_seen_at.unwrap_or(router.created_at);
-                        (now - base).num_seconds().max(0)
-                    };
-                    let recovered_after_secs = std::env::var("MIKROTIK_RECOVERED_AFTER_SECS")
-                        .ok()
-                        .and_then(|v| v.parse::<i64>().ok())
-                        .unwrap_or(300)
-                        .clamp(30, 24 * 3600);
-                    let (title, message) = if offline_for_secs >= recovered_after_secs {
-                        (
-                            "Router recovered",
-                            format!(
-                                "{} recovered after {}s offline.",
-                                router.name, offline_for_secs
-                            ),
-                        )
-                    } else {
-                        ("Router online", format!("{} is back online.", router.name))
-                    };
+                if !device.is_online {
+                    let base = device.last_seen_at.unwrap_or(device.created_at);
+                    let offline_for_secs = (now - base).num_seconds().max(0);
+                    let vars = HashMap::from([
+                        ("router_name", device.name.clone()),
+                        ("offline_seconds", offline_for_secs.to_string()),
+                    ]);
                     self.notify_router_status_change(
-                        &tenant_id,
-                        title,
-                        message,
-                        Some(format!("/admin/network/routers/{}", router.id)),
+                        tenant_id,
+                        "router_online",
+                        &vars,
+                        Some(subject.link()),
                         "success",
                     )
                     .await;
@@ -2279,24 +3270,23 @@ impl MikrotikService {
                     self.audit_service
                         .log(
                             None,
-                            Some(&tenant_id),
+                            Some(tenant_id),
                             "status_online",
-                            "mikrotik_router",
-                            Some(&router.id),
+                            "monitored_device",
+                            Some(&device.id),
                             Some(&format!(
                                 "{} is back online (offline {}s)",
-                                router.name, offline_for_secs
+                                device.name, offline_for_secs
                             )),
                             None,
                         )
                         .await;
                 }
             }
-            Err(e) => {
-                let msg = e.to_string();
+            Err(msg) => {
                 sqlx::query(
                     r#"
-                    UPDATE mikrotik_routers SET
+                    UPDATE monitored_devices SET
                       is_online = false,
                       latency_ms = $1,
                       last_error = $2,
@@ -2307,53 +3297,46 @@ impl MikrotikService {
                 .bind(latency_ms)
                 .bind(&msg)
                 .bind(now)
-                .bind(&router.id)
+                .bind(&device.id)
                 .execute(&self.pool)
                 .await
                 .map_err(AppError::Database)?;
 
-                if in_maintenance {
-                    let _ = self.resolve_all_router_alerts(&tenant_id, &router.id).await;
+                let th = self.get_thresholds(tenant_id).await;
+                if in_maintenance || !th.enabled {
+                    let _ = self.resolve_all_router_alerts(tenant_id, &device.id).await;
                 } else {
-                    let th = self.get_thresholds(&tenant_id).await;
-                    if !th.enabled {
-                        let _ = self.resolve_all_router_alerts(&tenant_id, &router.id).await;
-                    } else {
-                        // Only open an incident after the router has been unreachable for a while (anti-flap).
-                        let base = router.last_seen_at.unwrap_or(router.created_at);
-                        let offline_for_secs = (now - base).num_seconds().max(0);
-                        if offline_for_secs >= th.offline_after_secs {
-                            // Create/refresh "offline" incident. CPU/latency becomes unknown when offline, so resolve them.
-                            let created = self
-                                .upsert_alert(
-                                    &tenant_id,
-                                    &router,
-                                    "offline",
-                                    "critical",
-                                    "Router offline",
-                                    format!(
-                                        "{} is unreachable ({}s).",
-                                        router.name, offline_for_secs
-                                    ),
-                                    Some(offline_for_secs as f64),
-                                    Some(th.offline_after_secs.max(0) as f64),
-                                    now,
-                                )
-                                .await
-                                .unwrap_or(false);
-                            let _ = created;
-                        }
+                    let base = device.last_seen_at.unwrap_or(device.created_at);
+                    let offline_for_secs = (now - base).num_seconds().max(0);
+                    if offline_for_secs >= th.offline_after_secs {
+                        let _ = self
+                            .upsert_alert(
+                                tenant_id,
+                                subject,
+                                "offline",
+                                "critical",
+                                "Device offline",
+                                format!("{} is unreachable ({}s).", device.name, offline_for_secs),
+                                Some(offline_for_secs as f64),
+                                Some(th.offline_after_secs.max(0) as f64),
+                                now,
+                            )
+                            .await;
                     }
-                    let _ = self.resolve_alert(&tenant_id, &router.id, "cpu").await;
-                    let _ = self.resolve_alert(&tenant_id, &router.id, "latency").await;
+                    let _ = self.resolve_alert(tenant_id, &device.id, "cpu").await;
+                    let _ = self.resolve_alert(tenant_id, &device.id, "latency").await;
                 }
 
-                if prev_online {
+                if device.is_online {
+                    let vars = HashMap::from([
+                        ("router_name", device.name.clone()),
+                        ("error", msg.clone()),
+                    ]);
                     self.notify_router_status_change(
-                        &tenant_id,
-                        "Router down",
-                        format!("{} became unreachable: {}", router.name, msg),
-                        Some(format!("/admin/network/routers/{}", router.id)),
+                        tenant_id,
+                        "router_down",
+                        &vars,
+                        Some(subject.link()),
                         "error",
                     )
                     .await;
@@ -2361,11 +3344,11 @@ impl MikrotikService {
                     self.audit_service
                         .log(
                             None,
-                            Some(&tenant_id),
+                            Some(tenant_id),
                             "status_offline",
-                            "mikrotik_router",
-                            Some(&router.id),
-                            Some(&format!("{} became unreachable: {}", router.name, msg)),
+                            "monitored_device",
+                            Some(&device.id),
+                            Some(&format!("{} became unreachable: {}", device.name, msg)),
                             None,
                         )
                         .await;
@@ -2374,9 +3357,9 @@ impl MikrotikService {
         }
 
         info!(
-            "[MikrotikPoller] {} ({}) polled in {}ms",
-            router.name,
-            router.host,
+            "[MikrotikPoller] {} ({}) polled over SNMP in {}ms",
+            device.name,
+            device.host,
             started.elapsed().as_millis()
         );
 
@@ -2386,13 +3369,13 @@ impl MikrotikService {
     async fn eval_cpu_alert(
         &self,
         tenant_id: &str,
-        router: &MikrotikRouter,
+        subject: AlertSubject<'_>,
         cpu_load: Option<i32>,
         now: DateTime<Utc>,
     ) -> AppResult<()> {
         let th = self.get_thresholds(tenant_id).await;
         if !th.enabled {
-            let _ = self.resolve_all_router_alerts(tenant_id, &router.id).await;
+            let _ = self.resolve_all_router_alerts(tenant_id, subject.id).await;
             return Ok(());
         }
 
@@ -2401,7 +3384,7 @@ impl MikrotikService {
                 let created = self
                     .upsert_alert(
                         tenant_id,
-                        router,
+                        subject,
                         "cpu",
                         if cpu >= th.cpu_hot {
                             "critical"
@@ -2411,7 +3394,7 @@ impl MikrotikService {
                         "High CPU",
                         format!(
                             "{} CPU is {}% (threshold: {}%).",
-                            router.name, cpu, th.cpu_risk
+                            subject.name, cpu, th.cpu_risk
                         ),
                         Some(cpu as f64),
                         Some(th.cpu_risk as f64),
@@ -2420,11 +3403,15 @@ impl MikrotikService {
                     .await?;
 
                 if created {
+                    let vars = HashMap::from([
+                        ("router_name", subject.name.to_string()),
+                        ("cpu", cpu.to_string()),
+                    ]);
                     self.notify_tenant(
                         tenant_id,
-                        "High CPU",
-                        format!("{} CPU is {}%.", router.name, cpu),
-                        Some(format!("/admin/network/routers/{}", router.id)),
+                        "router_high_cpu",
+                        &vars,
+                        Some(subject.link()),
                         "warning",
                     )
                     .await;
@@ -2435,8 +3422,8 @@ impl MikrotikService {
                             Some(tenant_id),
                             "alert_cpu",
                             "mikrotik_alert",
-                            Some(&router.id),
-                            Some(&format!("CPU alert: {}% on {}", cpu, router.name)),
+                            Some(subject.id),
+                            Some(&format!("CPU alert: {}% on {}", cpu, subject.name)),
                             None,
                         )
                         .await;
@@ -2446,20 +3433,20 @@ impl MikrotikService {
             }
         }
 
-        let _ = self.resolve_alert(tenant_id, &router.id, "cpu").await;
+        let _ = self.resolve_alert(tenant_id, subject.id, "cpu").await;
         Ok(())
     }
 
     async fn eval_latency_alert(
         &self,
         tenant_id: &str,
-        router: &MikrotikRouter,
+        subject: AlertSubject<'_>,
         latency_ms: Option<i32>,
         now: DateTime<Utc>,
     ) -> AppResult<()> {
         let th = self.get_thresholds(tenant_id).await;
         if !th.enabled {
-            let _ = self.resolve_all_router_alerts(tenant_id, &router.id).await;
+            let _ = self.resolve_all_router_alerts(tenant_id, subject.id).await;
             return Ok(());
         }
 
@@ -2468,7 +3455,7 @@ impl MikrotikService {
                 let created = self
                     .upsert_alert(
                         tenant_id,
-                        router,
+                        subject,
                         "latency",
                         if lat >= th.latency_hot_ms {
                             "critical"
@@ -2478,7 +3465,7 @@ impl MikrotikService {
                         "High latency",
                         format!(
                             "{} latency is {}ms (threshold: {}ms).",
-                            router.name, lat, th.latency_risk_ms
+                            subject.name, lat, th.latency_risk_ms
                         ),
                         Some(lat as f64),
                         Some(th.latency_risk_ms as f64),
@@ -2487,11 +3474,15 @@ impl MikrotikService {
                     .await?;
 
                 if created {
+                    let vars = HashMap::from([
+                        ("router_name", subject.name.to_string()),
+                        ("latency_ms", lat.to_string()),
+                    ]);
                     self.notify_tenant(
                         tenant_id,
-                        "High latency",
-                        format!("{} latency is {}ms.", router.name, lat),
-                        Some(format!("/admin/network/routers/{}", router.id)),
+                        "router_high_latency",
+                        &vars,
+                        Some(subject.link()),
                         "warning",
                     )
                     .await;
@@ -2502,8 +3493,8 @@ impl MikrotikService {
                             Some(tenant_id),
                             "alert_latency",
                             "mikrotik_alert",
-                            Some(&router.id),
-                            Some(&format!("Latency alert: {}ms on {}", lat, router.name)),
+                            Some(subject.id),
+                            Some(&format!("Latency alert: {}ms on {}", lat, subject.name)),
                             None,
                         )
                         .await;
@@ -2513,14 +3504,14 @@ impl MikrotikService {
             }
         }
 
-        let _ = self.resolve_alert(tenant_id, &router.id, "latency").await;
+        let _ = self.resolve_alert(tenant_id, subject.id, "latency").await;
         Ok(())
     }
 
     async fn upsert_alert(
         &self,
         tenant_id: &str,
-        router: &MikrotikRouter,
+        subject: AlertSubject<'_>,
         alert_type: &str,
         severity: &str,
         title: &str,
@@ -2530,7 +3521,7 @@ impl MikrotikService {
         now: DateTime<Utc>,
     ) -> AppResult<bool> {
         if self
-            .should_suppress_correlated_incident(tenant_id, &router.id, alert_type)
+            .should_suppress_correlated_incident(tenant_id, subject.id, alert_type)
             .await?
         {
             return Ok(false);
@@ -2544,7 +3535,7 @@ impl MikrotikService {
             "#,
         )
         .bind(tenant_id)
-        .bind(&router.id)
+        .bind(subject.id)
         .bind(alert_type)
         .fetch_optional(&self.pool)
         .await
@@ -2577,7 +3568,7 @@ impl MikrotikService {
             .map_err(AppError::Database)?;
             self.upsert_incident(
                 tenant_id,
-                &router.id,
+                subject,
                 None,
                 alert_type,
                 severity,
@@ -2593,7 +3584,7 @@ impl MikrotikService {
 
         let mut alert = MikrotikAlert::new(
             tenant_id.to_string(),
-            router.id.clone(),
+            subject.id.to_string(),
             alert_type.to_string(),
             severity.to_string(),
             title.to_string(),
@@ -2601,6 +3592,7 @@ impl MikrotikService {
             value_num,
             threshold_num,
         );
+        alert.source = subject.source.to_string();
         alert.triggered_at = now;
         alert.last_seen_at = now;
         alert.created_at = now;
@@ -2611,11 +3603,11 @@ impl MikrotikService {
             INSERT INTO mikrotik_alerts
             (id, tenant_id, router_id, alert_type, severity, status, title, message,
              value_num, threshold_num, triggered_at, last_seen_at, resolved_at,
-             acked_at, acked_by, created_at, updated_at)
+             acked_at, acked_by, created_at, updated_at, source)
             VALUES
             ($1,$2,$3,$4,$5,$6,$7,$8,
              $9,$10,$11,$12,$13,
-             $14,$15,$16,$17)
+             $14,$15,$16,$17,$18)
             "#,
         )
         .bind(&alert.id)
@@ -2635,13 +3627,14 @@ impl MikrotikService {
         .bind(&alert.acked_by)
         .bind(alert.created_at)
         .bind(alert.updated_at)
+        .bind(&alert.source)
         .execute(&self.pool)
         .await
         .map_err(AppError::Database)?;
 
         self.upsert_incident(
             tenant_id,
-            &router.id,
+            subject,
             None,
             alert_type,
             severity,
@@ -2746,7 +3739,7 @@ impl MikrotikService {
     async fn upsert_incident(
         &self,
         tenant_id: &str,
-        router_id: &str,
+        subject: AlertSubject<'_>,
         interface_name: Option<&str>,
         incident_type: &str,
         severity: &str,
@@ -2756,7 +3749,7 @@ impl MikrotikService {
         threshold_num: Option<f64>,
         now: DateTime<Utc>,
     ) -> AppResult<()> {
-        let dedup_key = MikrotikIncident::dedup_key(router_id, interface_name, incident_type);
+        let dedup_key = MikrotikIncident::dedup_key(subject.id, interface_name, incident_type);
         let existing: Option<String> = sqlx::query_scalar(
             r#"
             SELECT id FROM mikrotik_incidents
@@ -2799,7 +3792,7 @@ impl MikrotikService {
 
         let mut incident = MikrotikIncident::new(
             tenant_id.to_string(),
-            router_id.to_string(),
+            subject.id.to_string(),
             interface_name.map(|s| s.to_string()),
             incident_type.to_string(),
             severity.to_string(),
@@ -2808,6 +3801,7 @@ impl MikrotikService {
             value_num,
             threshold_num,
         );
+        incident.source = subject.source.to_string();
         incident.first_seen_at = now;
         incident.last_seen_at = now;
         incident.created_at = now;
@@ -2818,11 +3812,11 @@ impl MikrotikService {
             INSERT INTO mikrotik_incidents
             (id, tenant_id, router_id, interface_name, incident_type, dedup_key, severity, status,
              title, message, value_num, threshold_num, first_seen_at, last_seen_at, resolved_at,
-             acked_at, acked_by, owner_user_id, notes, created_at, updated_at)
+             acked_at, acked_by, owner_user_id, notes, created_at, updated_at, source)
             VALUES
             ($1,$2,$3,$4,$5,$6,$7,$8,
              $9,$10,$11,$12,$13,$14,$15,
-             $16,$17,$18,$19,$20,$21)
+             $16,$17,$18,$19,$20,$21,$22)
             "#,
         )
         .bind(&incident.id)
@@ -2846,13 +3840,245 @@ impl MikrotikService {
         .bind(&incident.notes)
         .bind(incident.created_at)
         .bind(incident.updated_at)
+        .bind(&incident.source)
         .execute(&self.pool)
         .await
         .map_err(AppError::Database)?;
 
+        // Subscriptions hang off routers; a switch or OLT has none recorded.
+        if subject.source != "routeros" {
+            return Ok(());
+        }
+        if let Err(e) = self
+            .compute_incident_impact(tenant_id, &incident.id, subject.id)
+            .await
+        {
+            warn!(
+                "[MikrotikPoller] Impact resolution failed for incident {}: {}",
+                incident.id, e
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Snapshots the active subscriptions behind the incident's router and
+    /// stores their counts, grouped by area, on the incident. A subscription
+    /// is behind the router when it is billed on it, has a PPPoE account on
+    /// it, or its planned service path runs through the router's node.
+    async fn compute_incident_impact(
+        &self,
+        tenant_id: &str,
+        incident_id: &str,
+        router_id: &str,
+    ) -> AppResult<()> {
+        let rows: Vec<MikrotikIncidentImpact> = sqlx::query_as(
+            r#"
+            WITH router_nodes AS (
+              SELECT id::text AS id
+              FROM network_nodes
+              WHERE tenant_id::text = $2
+                AND (
+                  (metadata->>'asset_type' = 'mikrotik_router' AND metadata->>'asset_id' = $3)
+                  OR metadata->>'router_id' = $3
+                  OR metadata->>'mikrotik_router_id' = $3
+                )
+            ),
+            hits AS (
+              SELECT cs.id AS subscription_id, 0 AS rank, 'router' AS via
+              FROM customer_subscriptions cs
+              WHERE cs.tenant_id = $2 AND cs.router_id = $3
+              UNION ALL
+              SELECT cs.id, 1, 'pppoe'
+              FROM pppoe_accounts pa
+              JOIN customer_subscriptions cs
+                ON cs.tenant_id = pa.tenant_id AND cs.location_id = pa.location_id
+              WHERE pa.tenant_id = $2 AND pa.router_id = $3
+              UNION ALL
+              SELECT csa.subscription_id, 2, 'topology'
+              FROM customer_service_assignments csa
+              WHERE csa.tenant_id = $2
+                AND (
+                  csa.selected_node_id IN (SELECT id FROM router_nodes)
+                  OR EXISTS (
+                    SELECT 1
+                    FROM jsonb_array_elements_text(csa.path_node_ids) n(node_id)
+                    WHERE n.node_id IN (SELECT id FROM router_nodes)
+                  )
+                )
+            ),
+            firsts AS (
+              SELECT DISTINCT ON (subscription_id) subscription_id, via
+              FROM hits
+              ORDER BY subscription_id, rank
+            )
+            SELECT
+              $1::text AS incident_id,
+              cs.id AS subscription_id,
+              c.id AS customer_id,
+              c.name AS customer_name,
+              cl.id AS location_id,
+              cl.label AS location_label,
+              COALESCE(z.name, NULLIF(BTRIM(cl.city), ''), 'Unknown') AS area,
+              f.via
+            FROM firsts f
+            JOIN customer_subscriptions cs ON cs.id = f.subscription_id
+            JOIN customers c ON c.tenant_id = cs.tenant_id AND c.id = cs.customer_id
+            JOIN customer_locations cl ON cl.tenant_id = cs.tenant_id AND cl.id = cs.location_id
+            LEFT JOIN LATERAL (
+              SELECT sz.name
+              FROM service_zones sz
+              WHERE sz.tenant_id::text = cs.tenant_id
+                AND sz.status = 'active'
+                AND cl.latitude IS NOT NULL
+                AND cl.longitude IS NOT NULL
+                AND ST_Contains(
+                  sz.geom,
+                  ST_SetSRID(ST_MakePoint(cl.longitude::float8, cl.latitude::float8), 4326)
+                )
+              ORDER BY sz.priority ASC, sz.updated_at DESC
+              LIMIT 1
+            ) z ON TRUE
+            WHERE cs.status = 'active' AND c.deleted_at IS NULL
+            ORDER BY area, c.name
+            "#,
+        )
+        .bind(incident_id)
+        .bind(tenant_id)
+        .bind(router_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let customers: HashSet<&str> = rows.iter().map(|r| r.customer_id.as_str()).collect();
+        let mut by_area: HashMap<&str, (i32, HashSet<&str>)> = HashMap::new();
+        for r in &rows {
+            let entry = by_area.entry(r.area.as_str()).or_default();
+            entry.0 += 1;
+            entry.1.insert(r.customer_id.as_str());
+        }
+        let mut areas: Vec<MikrotikIncidentArea> = by_area
+            .into_iter()
+            .map(|(area, (subscriptions, customers))| MikrotikIncidentArea {
+                area: area.to_string(),
+                subscriptions,
+                customers: customers.len() as i32,
+            })
+            .collect();
+        areas.sort_by(|a, b| {
+            b.subscriptions
+                .cmp(&a.subscriptions)
+                .then_with(|| a.area.cmp(&b.area))
+        });
+
+        let mut tx = self.pool.begin().await.map_err(AppError::Database)?;
+        sqlx::query("DELETE FROM mikrotik_incident_impacts WHERE incident_id = $1")
+            .bind(incident_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        for r in &rows {
+            sqlx::query(
+                r#"
+                INSERT INTO mikrotik_incident_impacts
+                  (incident_id, tenant_id, subscription_id, customer_id, customer_name,
+                   location_id, location_label, area, via)
+                VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9)
+                "#,
+            )
+            .bind(incident_id)
+            .bind(tenant_id)
+            .bind(&r.subscription_id)
+            .bind(&r.customer_id)
+            .bind(&r.customer_name)
+            .bind(&r.location_id)
+            .bind(&r.location_label)
+            .bind(&r.area)
+            .bind(&r.via)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        }
+        sqlx::query(
+            r#"
+            UPDATE mikrotik_incidents
+            SET affected_subscriptions = $1,
+                affected_customers = $2,
+                affected_areas = $3,
+                impact_computed_at = $4
+            WHERE tenant_id = $5 AND id = $6
+            "#,
+        )
+        .bind(rows.len() as i32)
+        .bind(customers.len() as i32)
+        .bind(serde_json::to_value(&areas).unwrap_or_default())
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .bind(incident_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        tx.commit().await.map_err(AppError::Database)?;
         Ok(())
     }
 
+    /// Subscriptions recorded as affected by an incident, by area.
+    pub async fn list_incident_impact(
+        &self,
+        tenant_id: &str,
+        incident_id: &str,
+    ) -> AppResult<Vec<MikrotikIncidentImpact>> {
+        sqlx::query_as(
+            r#"
+            SELECT incident_id, subscription_id, customer_id, customer_name,
+                   location_id, location_label, area, via
+            FROM mikrotik_incident_impacts
+            WHERE tenant_id = $1 AND incident_id = $2
+            ORDER BY area, customer_name
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(incident_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Re-resolves the affected subscriptions of an open incident, e.g. after
+    /// customers were moved onto or off the router.
+    pub async fn refresh_incident_impact(
+        &self,
+        tenant_id: &str,
+        incident_id: &str,
+    ) -> AppResult<MikrotikIncident> {
+        let incident = sqlx::query_as::<_, MikrotikIncident>(
+            "SELECT * FROM mikrotik_incidents WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(incident_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?
+        .ok_or_else(|| AppError::NotFound("Incident not found".to_string()))?;
+        if incident.resolved_at.is_some() {
+            return Err(AppError::Validation(
+                "Resolved incidents keep the impact recorded when they were open".to_string(),
+            ));
+        }
+        if incident.source != "routeros" {
+            return Err(AppError::Validation(
+                "Impact is only resolved for router incidents".to_string(),
+            ));
+        }
+        self.compute_incident_impact(tenant_id, &incident.id, &incident.router_id)
+            .await?;
+        sqlx::query_as::<_, MikrotikIncident>("SELECT * FROM mikrotik_incidents WHERE id = $1")
+            .bind(&incident.id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(AppError::Database)
+    }
+
     async fn resolve_alert(
         &self,
         tenant_id: &str,
@@ -3037,6 +4263,18 @@ impl MikrotikService {
             .filter(|v| *v >= 1 && *v <= 256)
             .unwrap_or(16);
 
+        // Uplinks are always persisted so capacity reports have data.
+        let uplinks: std::collections::HashSet<String> = sqlx::query_scalar::<_, String>(
+            "SELECT interface_name FROM mikrotik_uplinks WHERE router_id = $1",
+        )
+        .bind(&router.id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|name| Self::normalize_interface_name(name))
+        .collect();
+
         let interfaces: Vec<MikrotikInterfaceSnapshot> = match tracked_ifaces {
             // Persist only interfaces selected on wallboard when a tracked list exists.
             Some(allowed) if !allowed.is_empty() => {
@@ -3044,6 +4282,7 @@ impl MikrotikService {
                     .iter()
                     .map(|name| Self::normalize_interface_name(name))
                     .filter(|name| !name.is_empty())
+                    .chain(uplinks.iter().cloned())
                     .collect();
                 let mut selected: Vec<MikrotikInterfaceSnapshot> = Vec::new();
                 let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
@@ -3075,11 +4314,17 @@ impl MikrotikService {
             }
             // Fallback: if no tracked list is configured, still persist a bounded set so
             // historical charts are available instead of staying empty forever.
-            _ => snapshot_interfaces
-                .into_iter()
-                .filter(Self::is_active_interface)
-                .take(untracked_max)
-                .collect(),
+            _ => {
+                let (mut selected, rest): (Vec<_>, Vec<_>) = snapshot_interfaces
+                    .into_iter()
+                    .partition(|i| uplinks.contains(&Self::normalize_interface_name(&i.name)));
+                selected.extend(
+                    rest.into_iter()
+                        .filter(Self::is_active_interface)
+                        .take(untracked_max),
+                );
+                selected
+            }
         };
 
         if interfaces.is_empty() {
@@ -3973,7 +5218,72 @@ impl MikrotikService {
         self.list_ip_pools(tenant_id, router_id).await
     }
 
+    /// Tell active customers behind a router about an outage on WhatsApp (when
+    /// the tenant routes `outage_notice`). Runs in the background so a large
+    /// customer base doesn't stall polling.
+    fn notify_customers_outage(&self, tenant_id: &str, router: &MikrotikRouter) {
+        if self.notification_service.whatsapp().is_none() {
+            return;
+        }
+        let notification_service = self.notification_service.clone();
+        let tenant_id = tenant_id.to_string();
+        let router_id = router.id.clone();
+        let area = router.name.clone();
+        tokio::spawn(async move {
+            let Some(whatsapp) = notification_service.whatsapp() else {
+                return;
+            };
+            let recipients = match whatsapp.router_recipients(&tenant_id, &router_id).await {
+                Ok(r) => r,
+                Err(e) => {
+                    warn!(
+                        "Failed to load outage recipients for router {}: {}",
+                        router_id, e
+                    );
+                    return;
+                }
+            };
+            for recipient in recipients {
+                let vars = HashMap::from([
+                    ("customer_name", recipient.name.clone()),
+                    ("area", area.clone()),
+                    (
+                        "message",
+                        "We are experiencing a network outage in your area and are working to restore service."
+                            .to_string(),
+                    ),
+                ]);
+                notification_service
+                    .send_whatsapp(&tenant_id, WhatsappEvent::OutageNotice, &recipient, &vars)
+                    .await;
+            }
+        });
+    }
+
+    /// Render the `code` template and alert the tenant's router staff with it.
     async fn notify_tenant(
+        &self,
+        tenant_id: &str,
+        code: &str,
+        vars: &HashMap<&str, String>,
+        action_url: Option<String>,
+        notification_type: &str,
+    ) {
+        let rendered = self
+            .notification_service
+            .render_template(Some(tenant_id), code, vars)
+            .await;
+        self.send_tenant_alert(
+            tenant_id,
+            &rendered.title,
+            rendered.body,
+            action_url,
+            notification_type,
+        )
+        .await;
+    }
+
+    async fn send_tenant_alert(
         &self,
         tenant_id: &str,
         title: &str,
@@ -4002,6 +5312,22 @@ impl MikrotikService {
             Err(_) => return,
         };
 
+        let alert = TenantAlert {
+            category: "network",
+            notification_type,
+            title,
+            message: &message,
+            action_url: action_url.as_deref(),
+            telegram: TelegramAlert::Incident,
+        };
+        if self
+            .notification_service
+            .route_alert(tenant_id, &user_ids, &alert)
+            .await
+        {
+            return;
+        }
+
         for uid in &user_ids {
             let _ = self
                 .notification_service
@@ -4017,6 +5343,15 @@ impl MikrotikService {
                 .await;
         }
 
+        self.notification_service
+            .send_telegram_alert(
+                tenant_id,
+                TelegramAlert::Incident,
+                &user_ids,
+                &format!("{}\n{}", title, message),
+            )
+            .await;
+
         // Optional: email notify to the same audience (tenant-scoped SMTP settings).
         let email_enabled = match self
             .settings_service
@@ -4050,8 +5385,8 @@ impl MikrotikService {
     async fn notify_router_status_change(
         &self,
         tenant_id: &str,
-        title: &str,
-        message: String,
+        code: &str,
+        vars: &HashMap<&str, String>,
         action_url: Option<String>,
         notification_type: &str,
     ) {
@@ -4081,6 +5416,12 @@ impl MikrotikService {
         }
         .clamp(0, 3600);
 
+        let rendered = self
+            .notification_service
+            .render_template(Some(tenant_id), code, vars)
+            .await;
+        let title = rendered.title.as_str();
+
         if cooldown_secs > 0 {
             let latest: Result<Option<DateTime<Utc>>, sqlx::Error> = sqlx::query_scalar(
                 r#"
@@ -4107,8 +5448,14 @@ impl MikrotikService {
             }
         }
 
-        self.notify_tenant(tenant_id, title, message, action_url, notification_type)
-            .await;
+        self.send_tenant_alert(
+            tenant_id,
+            title,
+            rendered.body,
+            action_url,
+            notification_type,
+        )
+        .await;
     }
 }
 
@@ -4134,3 +5481,96 @@ fn parse_uptime_to_secs(s: &str) -> i64 {
     }
     total
 }
+
+struct MikrotikPollJob(MikrotikService);
+
+#[async_trait]
+impl JobHandler for MikrotikPollJob {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        self.0.poll_once().await
+    }
+}
+
+struct MikrotikMetricsCleanupJob(MikrotikService);
+
+#[async_trait]
+impl JobHandler for MikrotikMetricsCleanupJob {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        self.0.cleanup_old_metrics().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snmp_device(version: &str) -> MonitoredDevice {
+        let mut device = MonitoredDevice::new(
+            "t1".to_string(),
+            " OLT-1 ".to_string(),
+            " 10.0.0.2 ".to_string(),
+            "snmp".to_string(),
+        );
+        device.device_type = "OLT".to_string();
+        device.snmp_version = Some(version.to_string());
+        device
+    }
+
+    #[test]
+    fn test_validate_device_keeps_only_the_version_settings() {
+        let mut device = snmp_device("V3");
+        device.snmp_community = "public".to_string();
+        device.snmp_username = Some("noc".to_string());
+        device.snmp_auth_protocol = Some("SHA".to_string());
+        device.snmp_auth_password = "authpass1".to_string();
+        device.snmp_priv_protocol = Some(String::new());
+        device.snmp_priv_password = "ignored".to_string();
+        device.cpu_oid = Some(" .1.3.6.1.4.1.2011.6.3.4.1.2 ".to_string());
+        MikrotikService::validate_device(&mut device).unwrap();
+
+        assert_eq!(device.name, "OLT-1");
+        assert_eq!(device.host, "10.0.0.2");
+        assert_eq!(device.device_type, "olt");
+        assert_eq!(device.snmp_version.as_deref(), Some("v3"));
+        assert_eq!(device.snmp_auth_protocol.as_deref(), Some("sha1"));
+        assert!(device.snmp_community.is_empty());
+        assert!(device.snmp_priv_protocol.is_none());
+        assert!(device.snmp_priv_password.is_empty());
+        assert_eq!(
+            device.cpu_oid.as_deref(),
+            Some(".1.3.6.1.4.1.2011.6.3.4.1.2")
+        );
+
+        let mut device = snmp_device("v2c");
+        device.snmp_community = "public".to_string();
+        device.snmp_username = Some("noc".to_string());
+        MikrotikService::validate_device(&mut device).unwrap();
+        assert!(device.snmp_username.is_none());
+    }
+
+    #[test]
+    fn test_validate_device_rejects_bad_settings() {
+        let mut device = snmp_device("v2c");
+        assert!(MikrotikService::validate_device(&mut device).is_err());
+
+        let mut device = snmp_device("v1");
+        device.snmp_community = "public".to_string();
+        assert!(MikrotikService::validate_device(&mut device).is_err());
+
+        let mut device = snmp_device("v3");
+        device.snmp_username = Some("noc".to_string());
+        device.snmp_priv_protocol = Some("aes".to_string());
+        device.snmp_priv_password = "privpass1".to_string();
+        assert!(MikrotikService::validate_device(&mut device).is_err());
+
+        let mut device = snmp_device("v2c");
+        device.snmp_community = "public".to_string();
+        device.cpu_oid = Some("cpu".to_string());
+        assert!(MikrotikService::validate_device(&mut device).is_err());
+
+        let mut device = snmp_device("v2c");
+        device.snmp_community = "public".to_string();
+        device.device_type = "toaster".to_string();
+        assert!(MikrotikService::validate_device(&mut device).is_err());
+    }
+}
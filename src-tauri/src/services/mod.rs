@@ -3,50 +3,152 @@
 pub mod alert_service;
 pub mod auth_service;
 pub mod cache;
+pub mod client_version;
+pub mod concurrency;
+pub mod cron_schedule;
+pub mod email_dkim_service;
 pub mod email_outbox_service;
 pub mod email_service;
+pub mod email_suppression_service;
+pub mod email_template_service;
+pub mod event_outbox_service;
+pub mod feature_flag_service;
+pub mod idempotency_service;
 pub mod metrics_service;
 pub mod network_mapping_service;
+pub mod rate_limit_store;
 pub mod rate_limiter;
+pub mod receipt_printer;
 pub mod role_service;
 pub mod settings_service;
+pub mod snmp_client;
 pub mod team_service;
+pub mod telegram_service;
+pub mod tenant_analytics_service;
+pub mod tenant_transfer;
 pub mod unsubscribe_token;
+pub mod usage_service;
 pub mod user_service;
+pub mod web_push_service;
+pub mod whatsapp_service;
 
 pub use auth_service::*;
+pub mod announcement_audience;
+pub mod announcement_recurrence;
 pub mod announcement_service;
+pub mod announcement_stats;
+pub mod announcement_translations;
+pub mod audit_chain;
 pub mod audit_service;
+pub mod audit_sink;
 pub mod backup;
+pub mod backup_remote;
+pub mod backup_validation;
+pub mod cgnat_service;
+pub mod completion_report_service;
 pub mod customer_service;
+pub mod db_maintenance_service;
+pub mod field_sync_service;
+pub mod i18n;
+pub mod integration_service;
+pub mod inventory_service;
+pub mod ipam_service;
 pub mod isp_package_service;
+pub mod job_queue;
+pub mod leader_lock;
+pub mod legacy_import_service;
+pub mod locale;
+pub mod malware_scanner;
 pub mod mikrotik_service;
+pub mod money;
+pub mod notification_delivery_service;
+pub mod notification_routing_service;
 pub mod notification_service;
+pub mod notification_template_service;
+pub mod onboarding_service;
+pub mod partition_service;
 pub mod payment_service;
 pub mod plan_service;
 pub mod pppoe_service;
+pub mod quiet_hours_service;
+pub mod report_pdf;
+pub mod report_schedule_service;
+pub mod status_page_service;
+pub mod storage_backend;
+pub mod storage_policy_service;
 pub mod storage_service;
+pub mod support_escalation_service;
+pub mod support_inbound_service;
+pub mod support_link_service;
+pub mod support_macro_service;
+pub mod support_routing_service;
 pub mod system_service;
+pub mod trash_service;
+pub mod uplink_capacity_service;
+pub mod work_order_checklist_service;
 
 pub use alert_service::AlertService;
 pub use announcement_service::AnnouncementScheduler;
-pub use audit_service::AuditService;
+pub use audit_service::{AuditChange, AuditService};
+pub use audit_sink::AuditStream;
 pub use auth_service::AuthService;
 pub use backup::BackupService;
+pub use cgnat_service::CgnatService;
+pub use completion_report_service::CompletionReportService;
 pub use customer_service::CustomerService;
+pub use db_maintenance_service::DbMaintenanceService;
+pub use email_dkim_service::EmailDkimService;
 pub use email_outbox_service::EmailOutboxService;
-pub use email_service::EmailService;
+pub use email_service::{EmailAttachment, EmailService};
+pub use email_suppression_service::EmailSuppressionService;
+pub use email_template_service::EmailTemplateService;
+pub use event_outbox_service::EventOutboxService;
+pub use feature_flag_service::FeatureFlagService;
+pub use field_sync_service::FieldSyncService;
+pub use idempotency_service::IdempotencyService;
+pub use integration_service::IntegrationService;
+pub use inventory_service::InventoryService;
+pub use ipam_service::IpamService;
 pub use isp_package_service::IspPackageService;
+pub use job_queue::{JobHandler, JobQueue};
+pub use legacy_import_service::LegacyImportService;
 pub use mikrotik_service::MikrotikService;
 pub use network_mapping_service::NetworkMappingService;
+pub use notification_delivery_service::NotificationDeliveryService;
+pub use notification_routing_service::NotificationRoutingService;
 pub use notification_service::NotificationService;
-pub use payment_service::{BillingCollectionRunResult, BulkGenerateInvoicesResult, PaymentService};
-pub use plan_service::PlanService;
+pub use notification_template_service::NotificationTemplateService;
+pub use onboarding_service::OnboardingService;
+pub use partition_service::PartitionMaintenanceScheduler;
+pub use payment_service::{
+    BillingCollectionRunResult, BulkGenerateInvoicesResult, PaymentService, PlanUpgradeCheckout,
+    PlanUpgradeQuote,
+};
+pub use plan_service::{PlanService, PlanTrialScheduler};
 pub use pppoe_service::PppoeService;
+pub use quiet_hours_service::QuietHoursService;
+pub use receipt_printer::ReceiptPrinterService;
+pub use report_schedule_service::ReportScheduleService;
 pub use role_service::RoleService;
 pub use settings_service::SettingsService;
+pub use status_page_service::StatusPageService;
+pub use storage_policy_service::StoragePolicyService;
 pub use storage_service::StorageService;
+pub use support_escalation_service::SupportEscalationService;
+pub use support_inbound_service::SupportInboundService;
+pub use support_link_service::SupportLinkService;
+pub use support_macro_service::SupportMacroService;
+pub use support_routing_service::SupportRoutingService;
 pub use system_service::SystemService;
 pub use team_service::TeamService;
+pub use telegram_service::{TelegramBot, TelegramService};
+pub use tenant_analytics_service::TenantAnalyticsService;
+pub use tenant_transfer::TenantTransferService;
+pub use trash_service::TrashPurgeScheduler;
 pub use unsubscribe_token::*;
+pub use uplink_capacity_service::UplinkCapacityService;
+pub use usage_service::{UsageMetric, UsageService};
 pub use user_service::UserService;
+pub use web_push_service::WebPushService;
+pub use whatsapp_service::WhatsappService;
+pub use work_order_checklist_service::WorkOrderChecklistService;
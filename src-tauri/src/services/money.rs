@@ -0,0 +1,159 @@
+//! Money arithmetic, rounding and formatting.
+//!
+//! Amounts are stored as `f64` in major units (150000.0 IDR, 12.5 USD), so
+//! anything that rounds, adds up or sends an amount to a gateway goes through
+//! minor units here: whole rupiah for IDR and other currencies without
+//! decimals, cents for most others. Display follows the reader's locale when
+//! it is known and the currency's home convention otherwise (`Rp 150.000`,
+//! `USD 1,234.50`).
+
+use crate::services::i18n;
+
+/// How a fraction of a minor unit is resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Nearest, halves away from zero. Invoices and conversions.
+    HalfUp,
+    /// Toward zero, e.g. a prorated credit never exceeds what was paid.
+    Down,
+    /// Away from zero.
+    Up,
+}
+
+/// Digits after the decimal point (ISO 4217 minor units) of `currency`.
+pub fn minor_digits(currency: &str) -> u32 {
+    match currency.trim().to_ascii_uppercase().as_str() {
+        "IDR" | "JPY" | "KRW" | "VND" | "CLP" | "ISK" | "UGX" | "XAF" | "XOF" => 0,
+        "BHD" | "JOD" | "KWD" | "OMR" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+fn factor(currency: &str) -> f64 {
+    10_f64.powi(minor_digits(currency) as i32)
+}
+
+/// `amount` in minor units of `currency`.
+pub fn to_minor(amount: f64, currency: &str, rounding: Rounding) -> i64 {
+    if !amount.is_finite() {
+        return 0;
+    }
+    // Decimal amounts rarely have an exact binary form (1.005 is stored as
+    // 1.00499...), so settle the scaled value to 6 places before rounding.
+    let scaled = ((amount * factor(currency)) * 1e6).round() / 1e6;
+    let minor = match rounding {
+        Rounding::HalfUp => scaled.round(),
+        Rounding::Down => scaled.trunc(),
+        Rounding::Up => {
+            if scaled < 0.0 {
+                scaled.floor()
+            } else {
+                scaled.ceil()
+            }
+        }
+    };
+    minor as i64
+}
+
+/// Minor units of `currency` back in major units.
+pub fn from_minor(minor: i64, currency: &str) -> f64 {
+    minor as f64 / factor(currency)
+}
+
+/// `amount` rounded to what `currency` can express, halves away from zero.
+pub fn round(amount: f64, currency: &str) -> f64 {
+    round_with(amount, currency, Rounding::HalfUp)
+}
+
+/// `amount` rounded to what `currency` can express, the given way.
+pub fn round_with(amount: f64, currency: &str, rounding: Rounding) -> f64 {
+    from_minor(to_minor(amount, currency, rounding), currency)
+}
+
+/// Total of amounts in the same currency, each rounded first so the sum
+/// matches the invoices it came from.
+pub fn sum(amounts: impl IntoIterator<Item = f64>, currency: &str) -> f64 {
+    let minor: i64 = amounts
+        .into_iter()
+        .map(|a| to_minor(a, currency, Rounding::HalfUp))
+        .sum();
+    from_minor(minor, currency)
+}
+
+/// `amount` with the currency's decimals and no grouping (`150000`,
+/// `12.50`), for CSV columns and report cells.
+pub fn format_plain(amount: f64, currency: &str) -> String {
+    let digits = minor_digits(currency) as usize;
+    format!("{:.*}", digits, round(amount, currency))
+}
+
+/// `amount` for people: symbol, grouped digits and the currency's decimals.
+/// Indonesian readers get `.` groups and `,` decimals, English readers the
+/// reverse. Without a locale the currency's home convention applies.
+pub fn format_money(amount: f64, currency: &str, locale: Option<&str>) -> String {
+    let currency = currency.trim().to_ascii_uppercase();
+    let rupiah = currency == "IDR";
+    let indonesian = match locale {
+        Some(_) => i18n::language_for(locale) == "id",
+        None => rupiah,
+    };
+    let (thousands, point) = if indonesian { ('.', ',') } else { (',', '.') };
+
+    let minor = to_minor(amount, &currency, Rounding::HalfUp);
+    let digits = minor_digits(&currency) as usize;
+    let fixed = format!("{:.*}", digits, from_minor(minor.abs(), &currency));
+    let (whole, fraction) = fixed.split_once('.').unwrap_or((&fixed, ""));
+
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(thousands);
+        }
+        grouped.push(digit);
+    }
+    if !fraction.is_empty() {
+        grouped.push(point);
+        grouped.push_str(fraction);
+    }
+    let sign = if minor < 0 { "-" } else { "" };
+    let symbol = if rupiah { "Rp" } else { &currency };
+    format!("{}{} {}", sign, symbol, grouped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_in_minor_units() {
+        assert_eq!(to_minor(1.005, "USD", Rounding::HalfUp), 101);
+        assert_eq!(to_minor(149_999.5, "IDR", Rounding::HalfUp), 150_000);
+        assert_eq!(to_minor(-2.5, "IDR", Rounding::HalfUp), -3);
+        assert_eq!(to_minor(0.1 + 0.2, "USD", Rounding::HalfUp), 30);
+        assert_eq!(to_minor(99_999.99, "IDR", Rounding::Down), 99_999);
+        assert_eq!(to_minor(10.001, "USD", Rounding::Up), 1001);
+        assert_eq!(to_minor(f64::NAN, "USD", Rounding::HalfUp), 0);
+        assert_eq!(round(1.2345, "KWD"), 1.235);
+        assert_eq!(round(33_333.333, "idr"), 33_333.0);
+    }
+
+    #[test]
+    fn sums_without_drift() {
+        assert_eq!(sum([0.1; 10], "USD"), 1.0);
+        assert_eq!(sum([150_000.4, 150_000.4], "IDR"), 300_000.0);
+    }
+
+    #[test]
+    fn formats_plain_and_for_people() {
+        assert_eq!(format_plain(150_000.0, "IDR"), "150000");
+        assert_eq!(format_plain(12.5, "USD"), "12.50");
+
+        assert_eq!(format_money(150000.0, "IDR", None), "Rp 150.000");
+        assert_eq!(format_money(999.0, "idr", None), "Rp 999");
+        assert_eq!(format_money(1234.5, "USD", None), "USD 1,234.50");
+        assert_eq!(format_money(-1000000.0, "IDR", None), "-Rp 1.000.000");
+        assert_eq!(format_money(1234.5, "USD", Some("id-ID")), "USD 1.234,50");
+        assert_eq!(format_money(150000.0, "IDR", Some("en")), "Rp 150,000");
+        assert_eq!(format_money(-0.001, "USD", None), "USD 0.00");
+    }
+}
@@ -3,18 +3,56 @@ use crate::error::{AppError, AppResult};
 use crate::models::{
     ComputePathRequest, ComputePathResponse, ComputedPathHop, ConnectNodeToLinkRequest,
     ConnectNodeToLinkResponse, CoverageCheckRequest, CoverageCheckResponse,
-    CreateNetworkLinkRequest, CreateNetworkNodeRequest, CreateServiceZoneRequest,
-    CreateZoneNodeBindingRequest, CreateZoneOfferRequest, NetworkImpactCustomer,
-    NetworkImpactResponse, NetworkLink, NetworkNode, PaginatedResponse, RankCandidateNodesRequest,
-    RankCandidateNodesResponse, RankedCandidateNode, ResolveZoneRequest, ResolvedZone,
-    ResolvedZoneResponse, ServiceZone, SyncTopologyAssetsResponse, UpdateNetworkLinkRequest,
-    UpdateNetworkNodeRequest, UpdateServiceZoneRequest, UpdateZoneOfferRequest, ZoneNodeBinding,
-    ZoneOffer,
+    CreateFiberPointRequest, CreateNetworkLinkRequest, CreateNetworkNodeRequest,
+    CreateServiceZoneRequest, CreateZoneNodeBindingRequest, CreateZoneOfferRequest, FiberPoint,
+    NetworkImpactCustomer, NetworkImpactResponse, NetworkLink, NetworkNode, PaginatedResponse,
+    RankCandidateNodesRequest, RankCandidateNodesResponse, RankedCandidateNode, ResolveZoneRequest,
+    ResolvedZone, ResolvedZoneResponse, ServiceZone, SyncTopologyAssetsResponse,
+    UpdateFiberPointRequest, UpdateNetworkLinkRequest, UpdateNetworkNodeRequest,
+    UpdateServiceZoneRequest, UpdateZoneOfferRequest, ZoneNodeBinding, ZoneOffer,
 };
 use crate::services::AuthService;
 use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+const FIBER_POINT_SELECT: &str = r#"
+    SELECT
+      f.id::text AS id,
+      f.tenant_id::text AS tenant_id,
+      f.kind,
+      f.code,
+      f.name,
+      f.status,
+      ST_Y(f.geom)::float8 AS lat,
+      ST_X(f.geom)::float8 AS lng,
+      f.parent_id::text AS parent_id,
+      f.router_id,
+      f.port_capacity,
+      COALESCE(u.ports_used, 0) AS ports_used,
+      n.id::text AS node_id,
+      f.notes,
+      f.created_at,
+      f.updated_at
+    FROM fiber_points f
+    LEFT JOIN LATERAL (
+      SELECT nn.id
+      FROM network_nodes nn
+      WHERE nn.tenant_id = f.tenant_id
+        AND nn.metadata->>'asset_type' = 'fiber_point'
+        AND nn.metadata->>'asset_id' = f.id::text
+      LIMIT 1
+    ) n ON TRUE
+    LEFT JOIN LATERAL (
+      SELECT COUNT(*) AS ports_used
+      FROM network_links l
+      JOIN network_nodes o
+        ON o.id = CASE WHEN l.from_node_id = n.id THEN l.to_node_id ELSE l.from_node_id END
+      WHERE l.tenant_id = f.tenant_id
+        AND (l.from_node_id = n.id OR l.to_node_id = n.id)
+        AND o.node_type IN ('customer_premise', 'customer_endpoint')
+    ) u ON TRUE
+"#;
+
 #[derive(Clone)]
 pub struct NetworkMappingService {
     pool: DbPool,
@@ -102,6 +140,48 @@ struct SyncCustomerLocationRow {
     longitude: f64,
 }
 
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct MapRouterRow {
+    id: String,
+    name: String,
+    enabled: bool,
+    is_online: bool,
+    latitude: f64,
+    longitude: f64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct MapLocationRow {
+    location_id: String,
+    customer_id: String,
+    customer_name: String,
+    label: String,
+    subscription_status: Option<String>,
+    latitude: f64,
+    longitude: f64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct MapLinkRow {
+    id: String,
+    name: String,
+    link_type: String,
+    status: String,
+    geometry: serde_json::Value,
+    from_asset_type: Option<String>,
+    from_asset_id: Option<String>,
+    to_asset_type: Option<String>,
+    to_asset_id: Option<String>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct MapFeederRow {
+    from_id: String,
+    to_layer: String,
+    to_id: String,
+    geometry: serde_json::Value,
+}
+
 #[derive(Debug, Clone)]
 struct SnappedPolylinePoint {
     lng: f64,
@@ -809,6 +889,7 @@ impl NetworkMappingService {
               longitude::float8 AS longitude
             FROM mikrotik_routers
             WHERE tenant_id = $1::text
+              AND deleted_at IS NULL
               AND latitude IS NOT NULL
               AND longitude IS NOT NULL
             "#,
@@ -2572,21 +2653,26 @@ impl NetworkMappingService {
             sqlx::query_as(
                 r#"
                 SELECT
-                  id::text AS id,
-                  tenant_id::text AS tenant_id,
-                  zone_id::text AS zone_id,
-                  package_id,
-                  price_monthly::float8 AS price_monthly,
-                  price_yearly::float8 AS price_yearly,
-                  is_active,
-                  metadata,
-                  created_at,
-                  updated_at
-                FROM zone_offers
-                WHERE tenant_id = $1::uuid
-                  AND zone_id = $2::uuid
-                  AND is_active = true
-                ORDER BY updated_at DESC
+                  o.id::text AS id,
+                  o.tenant_id::text AS tenant_id,
+                  o.zone_id::text AS zone_id,
+                  o.package_id,
+                  o.price_monthly::float8 AS price_monthly,
+                  o.price_yearly::float8 AS price_yearly,
+                  o.is_active,
+                  o.metadata,
+                  o.created_at,
+                  o.updated_at
+                FROM zone_offers o
+                JOIN isp_packages p
+                  ON p.tenant_id = o.tenant_id::text
+                 AND p.id = o.package_id
+                 AND p.is_active = true
+                 AND p.deleted_at IS NULL
+                WHERE o.tenant_id = $1::uuid
+                  AND o.zone_id = $2::uuid
+                  AND o.is_active = true
+                ORDER BY o.updated_at DESC
                 "#,
             )
             .bind(tenant_id)
@@ -2768,6 +2854,645 @@ impl NetworkMappingService {
         })
     }
 
+    // ---- Fiber points (ODC/ODP) and the asset map ----
+
+    fn validate_fiber_point_status(status: &str) -> AppResult<()> {
+        match status {
+            "active" | "inactive" | "maintenance" | "planning" => Ok(()),
+            _ => Err(AppError::Validation(
+                "fiber point status must be one of: active, inactive, maintenance, planning".into(),
+            )),
+        }
+    }
+
+    fn fiber_point_code(code: &str) -> AppResult<String> {
+        let code = code.trim().to_uppercase();
+        if code.is_empty() || code.chars().count() > 64 {
+            return Err(AppError::Validation(
+                "code is required (max 64 characters)".into(),
+            ));
+        }
+        Ok(code)
+    }
+
+    fn fiber_point_name(name: &str) -> AppResult<String> {
+        let name = name.trim().to_string();
+        if name.is_empty() || name.chars().count() > 120 {
+            return Err(AppError::Validation(
+                "name is required (max 120 characters)".into(),
+            ));
+        }
+        Ok(name)
+    }
+
+    fn fiber_point_ports(ports: Option<i32>) -> AppResult<Option<i32>> {
+        match ports {
+            Some(p) if !(1..=1024).contains(&p) => Err(AppError::Validation(
+                "port_capacity must be between 1 and 1024".into(),
+            )),
+            other => Ok(other),
+        }
+    }
+
+    fn trimmed(value: Option<String>) -> Option<String> {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    }
+
+    fn map_fiber_point_db_error(err: sqlx::Error, code: &str) -> AppError {
+        if err
+            .as_database_error()
+            .and_then(|d| d.code().map(|c| c == "23505"))
+            .unwrap_or(false)
+        {
+            return AppError::Conflict(format!("Fiber point code {code} is already used"));
+        }
+        AppError::Database(err)
+    }
+
+    /// Only an ODP has a parent, and it must be an ODC.
+    async fn check_fiber_point_links(
+        &self,
+        tenant_id: &str,
+        kind: &str,
+        parent_id: Option<&str>,
+        router_id: Option<&str>,
+    ) -> AppResult<()> {
+        if let Some(parent_id) = parent_id {
+            if kind != "odp" {
+                return Err(AppError::Validation(
+                    "only an ODP can be fed from an ODC".into(),
+                ));
+            }
+            let parent = self
+                .get_fiber_point_by_id(tenant_id, parent_id)
+                .await
+                .map_err(|_| AppError::Validation("parent ODC not found".into()))?;
+            if parent.kind != "odc" {
+                return Err(AppError::Validation("parent must be an ODC".into()));
+            }
+        }
+        if let Some(router_id) = router_id {
+            let exists: Option<i32> = sqlx::query_scalar(
+                r#"
+                SELECT 1 FROM mikrotik_routers
+                WHERE tenant_id = $1::text AND id = $2 AND deleted_at IS NULL
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(router_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(AppError::Database)?;
+            if exists.is_none() {
+                return Err(AppError::Validation("router not found".into()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Mirrors the point as a system-managed `odc`/`odp` topology node.
+    async fn sync_fiber_point_node(&self, tenant_id: &str, point: &FiberPoint) -> AppResult<()> {
+        let status = match point.status.as_str() {
+            "planning" => "inactive",
+            other => other,
+        };
+        self.upsert_system_managed_node(
+            tenant_id,
+            "fiber_point",
+            &point.id,
+            &format!("{} - {}", point.code, point.name),
+            &point.kind,
+            status,
+            point.lat,
+            point.lng,
+            serde_json::json!({
+                "system_managed": true,
+                "asset_source": "fiber_point",
+                "asset_type": "fiber_point",
+                "asset_id": point.id,
+                "fiber_point_id": point.id,
+                "code": point.code,
+                "port_capacity": point.port_capacity,
+            }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn fetch_fiber_points(
+        &self,
+        tenant_id: &str,
+        kind: Option<&str>,
+        bbox: Option<(f64, f64, f64, f64)>,
+    ) -> AppResult<Vec<FiberPoint>> {
+        let (min_lng, min_lat, max_lng, max_lat) = bbox.unwrap_or((0.0, 0.0, 0.0, 0.0));
+        sqlx::query_as(&format!(
+            r#"{FIBER_POINT_SELECT}
+            WHERE f.tenant_id = $1::uuid
+              AND ($2::text IS NULL OR f.kind = $2)
+              AND (
+                $3::bool = false
+                OR ST_Intersects(f.geom, ST_MakeEnvelope($4, $5, $6, $7, 4326))
+              )
+            ORDER BY f.kind, f.code
+            "#
+        ))
+        .bind(tenant_id)
+        .bind(kind)
+        .bind(bbox.is_some())
+        .bind(min_lng)
+        .bind(min_lat)
+        .bind(max_lng)
+        .bind(max_lat)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    pub async fn list_fiber_points(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        kind: Option<String>,
+        bbox: Option<(f64, f64, f64, f64)>,
+    ) -> AppResult<Vec<FiberPoint>> {
+        self.require_installation_read(actor_id, tenant_id).await?;
+        let kind = Self::trimmed(kind).map(|k| k.to_lowercase());
+        self.fetch_fiber_points(tenant_id, kind.as_deref(), bbox)
+            .await
+    }
+
+    pub async fn create_fiber_point(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: CreateFiberPointRequest,
+    ) -> AppResult<FiberPoint> {
+        self.require_manage(actor_id, tenant_id).await?;
+        let kind = dto.kind.trim().to_lowercase();
+        if kind != "odc" && kind != "odp" {
+            return Err(AppError::Validation("kind must be one of: odc, odp".into()));
+        }
+        let code = Self::fiber_point_code(&dto.code)?;
+        let name = Self::fiber_point_name(&dto.name)?;
+        let status = dto
+            .status
+            .map(|s| s.trim().to_lowercase())
+            .unwrap_or_else(|| "active".to_string());
+        Self::validate_fiber_point_status(&status)?;
+        Self::validate_lat_lng(dto.lat, dto.lng, "fiber_point")?;
+        let parent_id = Self::trimmed(dto.parent_id);
+        let router_id = Self::trimmed(dto.router_id);
+        let port_capacity = Self::fiber_point_ports(dto.port_capacity)?;
+        self.check_fiber_point_links(tenant_id, &kind, parent_id.as_deref(), router_id.as_deref())
+            .await?;
+
+        let id = Uuid::new_v4().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO fiber_points
+              (id, tenant_id, kind, code, name, status, geom, parent_id, router_id, port_capacity, notes, created_at, updated_at)
+            VALUES
+              ($1::uuid, $2::uuid, $3, $4, $5, $6, ST_SetSRID(ST_MakePoint($7, $8), 4326), $9::uuid, $10, $11, $12, now(), now())
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&kind)
+        .bind(&code)
+        .bind(&name)
+        .bind(&status)
+        .bind(dto.lng)
+        .bind(dto.lat)
+        .bind(&parent_id)
+        .bind(&router_id)
+        .bind(port_capacity)
+        .bind(Self::trimmed(dto.notes))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::map_fiber_point_db_error(e, &code))?;
+
+        let point = self.get_fiber_point_by_id(tenant_id, &id).await?;
+        self.sync_fiber_point_node(tenant_id, &point).await?;
+        self.get_fiber_point_by_id(tenant_id, &id).await
+    }
+
+    pub async fn update_fiber_point(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        dto: UpdateFiberPointRequest,
+    ) -> AppResult<FiberPoint> {
+        self.require_manage(actor_id, tenant_id).await?;
+        let current = self.get_fiber_point_by_id(tenant_id, id).await?;
+        let code = match dto.code {
+            Some(code) => Self::fiber_point_code(&code)?,
+            None => current.code,
+        };
+        let name = match dto.name {
+            Some(name) => Self::fiber_point_name(&name)?,
+            None => current.name,
+        };
+        let status = dto
+            .status
+            .map(|s| s.trim().to_lowercase())
+            .unwrap_or(current.status);
+        Self::validate_fiber_point_status(&status)?;
+        let lat = dto.lat.unwrap_or(current.lat);
+        let lng = dto.lng.unwrap_or(current.lng);
+        Self::validate_lat_lng(lat, lng, "fiber_point")?;
+        let parent_id = match dto.parent_id {
+            Some(v) => Self::trimmed(v),
+            None => current.parent_id,
+        };
+        if parent_id.as_deref() == Some(id) {
+            return Err(AppError::Validation(
+                "a fiber point cannot feed itself".into(),
+            ));
+        }
+        let router_id = match dto.router_id {
+            Some(v) => Self::trimmed(v),
+            None => current.router_id,
+        };
+        let port_capacity = match dto.port_capacity {
+            Some(v) => Self::fiber_point_ports(v)?,
+            None => current.port_capacity,
+        };
+        let notes = match dto.notes {
+            Some(v) => Self::trimmed(v),
+            None => current.notes,
+        };
+        self.check_fiber_point_links(
+            tenant_id,
+            &current.kind,
+            parent_id.as_deref(),
+            router_id.as_deref(),
+        )
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE fiber_points
+            SET code = $1,
+                name = $2,
+                status = $3,
+                geom = ST_SetSRID(ST_MakePoint($4, $5), 4326),
+                parent_id = $6::uuid,
+                router_id = $7,
+                port_capacity = $8,
+                notes = $9,
+                updated_at = now()
+            WHERE tenant_id = $10::uuid AND id = $11::uuid
+            "#,
+        )
+        .bind(&code)
+        .bind(&name)
+        .bind(&status)
+        .bind(lng)
+        .bind(lat)
+        .bind(&parent_id)
+        .bind(&router_id)
+        .bind(port_capacity)
+        .bind(&notes)
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::map_fiber_point_db_error(e, &code))?;
+
+        let point = self.get_fiber_point_by_id(tenant_id, id).await?;
+        self.sync_fiber_point_node(tenant_id, &point).await?;
+        Ok(point)
+    }
+
+    /// Removes the point with its topology node and the links drawn to it.
+    /// ODPs fed from a removed ODC are kept without a parent.
+    pub async fn delete_fiber_point(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+    ) -> AppResult<()> {
+        self.require_manage(actor_id, tenant_id).await?;
+        let current = self.get_fiber_point_by_id(tenant_id, id).await?;
+        sqlx::query(
+            r#"
+            DELETE FROM network_nodes
+            WHERE tenant_id = $1::uuid
+              AND metadata->>'asset_type' = 'fiber_point'
+              AND metadata->>'asset_id' = $2::text
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&current.id)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+        sqlx::query("DELETE FROM fiber_points WHERE tenant_id = $1::uuid AND id = $2::uuid")
+            .bind(tenant_id)
+            .bind(&current.id)
+            .execute(&self.pool)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    async fn get_fiber_point_by_id(&self, tenant_id: &str, id: &str) -> AppResult<FiberPoint> {
+        sqlx::query_as(&format!(
+            "{FIBER_POINT_SELECT} WHERE f.tenant_id = $1::uuid AND f.id = $2::uuid"
+        ))
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?
+        .ok_or_else(|| AppError::NotFound("Fiber point not found".into()))
+    }
+
+    /// Routers, customer locations, ODC/ODP points, topology links and the
+    /// ODP→ODC→router feeders as one GeoJSON FeatureCollection. Every feature
+    /// carries `properties.layer`; link features name the features at their
+    /// ends (`router:<id>`, `fiber_point:<id>`, ...) where the nodes are assets.
+    pub async fn asset_map(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        bbox: Option<(f64, f64, f64, f64)>,
+    ) -> AppResult<serde_json::Value> {
+        self.require_installation_read(actor_id, tenant_id).await?;
+        let has_bbox = bbox.is_some();
+        let (min_lng, min_lat, max_lng, max_lat) = bbox.unwrap_or((0.0, 0.0, 0.0, 0.0));
+
+        let routers: Vec<MapRouterRow> = sqlx::query_as(
+            r#"
+            SELECT
+              id,
+              name,
+              enabled,
+              is_online,
+              latitude::float8 AS latitude,
+              longitude::float8 AS longitude
+            FROM mikrotik_routers
+            WHERE tenant_id = $1::text
+              AND deleted_at IS NULL
+              AND latitude IS NOT NULL
+              AND longitude IS NOT NULL
+              AND (
+                $2::bool = false
+                OR (longitude BETWEEN $3 AND $5 AND latitude BETWEEN $4 AND $6)
+              )
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(has_bbox)
+        .bind(min_lng)
+        .bind(min_lat)
+        .bind(max_lng)
+        .bind(max_lat)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let locations: Vec<MapLocationRow> = sqlx::query_as(
+            r#"
+            SELECT
+              cl.id AS location_id,
+              cl.customer_id,
+              c.name AS customer_name,
+              COALESCE(NULLIF(BTRIM(cl.label), ''), c.name) AS label,
+              svc.status AS subscription_status,
+              cl.latitude::float8 AS latitude,
+              cl.longitude::float8 AS longitude
+            FROM customer_locations cl
+            JOIN customers c
+              ON c.tenant_id = cl.tenant_id AND c.id = cl.customer_id
+            LEFT JOIN LATERAL (
+              SELECT cs.status
+              FROM customer_subscriptions cs
+              WHERE cs.tenant_id = cl.tenant_id
+                AND cs.location_id = cl.id
+              ORDER BY
+                CASE cs.status
+                  WHEN 'active' THEN 0
+                  WHEN 'pending_installation' THEN 1
+                  WHEN 'suspended' THEN 2
+                  ELSE 3
+                END,
+                cs.updated_at DESC
+              LIMIT 1
+            ) svc ON TRUE
+            WHERE cl.tenant_id = $1::text
+              AND c.deleted_at IS NULL
+              AND cl.latitude IS NOT NULL
+              AND cl.longitude IS NOT NULL
+              AND (
+                $2::bool = false
+                OR (cl.longitude BETWEEN $3 AND $5 AND cl.latitude BETWEEN $4 AND $6)
+              )
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(has_bbox)
+        .bind(min_lng)
+        .bind(min_lat)
+        .bind(max_lng)
+        .bind(max_lat)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let points = self.fetch_fiber_points(tenant_id, None, bbox).await?;
+
+        let links: Vec<MapLinkRow> = sqlx::query_as(
+            r#"
+            SELECT
+              l.id::text AS id,
+              l.name,
+              l.link_type,
+              l.status,
+              ST_AsGeoJSON(l.geom)::jsonb AS geometry,
+              fn.metadata->>'asset_type' AS from_asset_type,
+              fn.metadata->>'asset_id' AS from_asset_id,
+              tn.metadata->>'asset_type' AS to_asset_type,
+              tn.metadata->>'asset_id' AS to_asset_id
+            FROM network_links l
+            JOIN network_nodes fn ON fn.id = l.from_node_id
+            JOIN network_nodes tn ON tn.id = l.to_node_id
+            WHERE l.tenant_id = $1::uuid
+              AND (
+                $2::bool = false
+                OR ST_Intersects(l.geom, ST_MakeEnvelope($3, $4, $5, $6, 4326))
+              )
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(has_bbox)
+        .bind(min_lng)
+        .bind(min_lat)
+        .bind(max_lng)
+        .bind(max_lat)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let feeders: Vec<MapFeederRow> = sqlx::query_as(
+            r#"
+            SELECT x.from_id, x.to_layer, x.to_id, ST_AsGeoJSON(x.geom)::jsonb AS geometry
+            FROM (
+              SELECT
+                f.id::text AS from_id,
+                'fiber_point' AS to_layer,
+                p.id::text AS to_id,
+                ST_MakeLine(f.geom, p.geom) AS geom
+              FROM fiber_points f
+              JOIN fiber_points p ON p.id = f.parent_id
+              WHERE f.tenant_id = $1::uuid
+              UNION ALL
+              SELECT
+                f.id::text,
+                'router',
+                r.id,
+                ST_MakeLine(f.geom, ST_SetSRID(ST_MakePoint(r.longitude, r.latitude), 4326))
+              FROM fiber_points f
+              JOIN mikrotik_routers r
+                ON r.tenant_id = f.tenant_id::text AND r.id = f.router_id
+              WHERE f.tenant_id = $1::uuid
+                AND r.deleted_at IS NULL
+                AND r.latitude IS NOT NULL
+                AND r.longitude IS NOT NULL
+            ) x
+            WHERE $2::bool = false
+               OR ST_Intersects(x.geom, ST_MakeEnvelope($3, $4, $5, $6, 4326))
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(has_bbox)
+        .bind(min_lng)
+        .bind(min_lat)
+        .bind(max_lng)
+        .bind(max_lat)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let mut features = Vec::with_capacity(
+            routers.len() + locations.len() + points.len() + links.len() + feeders.len(),
+        );
+        for r in routers {
+            let status = if !r.enabled {
+                "disabled"
+            } else if r.is_online {
+                "online"
+            } else {
+                "offline"
+            };
+            features.push(Self::map_feature(
+                "router",
+                &r.id,
+                Self::point_geometry(r.longitude, r.latitude),
+                serde_json::json!({ "name": r.name, "status": status }),
+            ));
+        }
+        for l in locations {
+            features.push(Self::map_feature(
+                "customer_location",
+                &l.location_id,
+                Self::point_geometry(l.longitude, l.latitude),
+                serde_json::json!({
+                    "customer_id": l.customer_id,
+                    "customer_name": l.customer_name,
+                    "label": l.label,
+                    "subscription_status": l.subscription_status,
+                }),
+            ));
+        }
+        for p in points {
+            features.push(Self::map_feature(
+                "fiber_point",
+                &p.id,
+                Self::point_geometry(p.lng, p.lat),
+                serde_json::json!({
+                    "kind": p.kind,
+                    "code": p.code,
+                    "name": p.name,
+                    "status": p.status,
+                    "parent_id": p.parent_id,
+                    "router_id": p.router_id,
+                    "port_capacity": p.port_capacity,
+                    "ports_used": p.ports_used,
+                    "node_id": p.node_id,
+                }),
+            ));
+        }
+        for l in links {
+            features.push(Self::map_feature(
+                "link",
+                &l.id,
+                l.geometry,
+                serde_json::json!({
+                    "name": l.name,
+                    "link_type": l.link_type,
+                    "status": l.status,
+                    "from": Self::asset_feature_id(l.from_asset_type.as_deref(), l.from_asset_id),
+                    "to": Self::asset_feature_id(l.to_asset_type.as_deref(), l.to_asset_id),
+                }),
+            ));
+        }
+        for f in feeders {
+            let id = format!("{}-{}", f.from_id, f.to_id);
+            features.push(Self::map_feature(
+                "feeder",
+                &id,
+                f.geometry,
+                serde_json::json!({
+                    "from": format!("fiber_point:{}", f.from_id),
+                    "to": format!("{}:{}", f.to_layer, f.to_id),
+                }),
+            ));
+        }
+
+        Ok(serde_json::json!({
+            "type": "FeatureCollection",
+            "features": features,
+        }))
+    }
+
+    fn point_geometry(lng: f64, lat: f64) -> serde_json::Value {
+        serde_json::json!({ "type": "Point", "coordinates": [lng, lat] })
+    }
+
+    fn map_feature(
+        layer: &str,
+        id: &str,
+        geometry: serde_json::Value,
+        mut properties: serde_json::Value,
+    ) -> serde_json::Value {
+        if let Some(props) = properties.as_object_mut() {
+            props.insert("layer".into(), layer.into());
+            props.insert("id".into(), id.into());
+        }
+        serde_json::json!({
+            "type": "Feature",
+            "id": format!("{layer}:{id}"),
+            "geometry": geometry,
+            "properties": properties,
+        })
+    }
+
+    /// Feature id of the asset behind a topology node, if it is one.
+    fn asset_feature_id(asset_type: Option<&str>, asset_id: Option<String>) -> Option<String> {
+        let layer = match asset_type? {
+            "mikrotik_router" => "router",
+            "customer_location" => "customer_location",
+            "fiber_point" => "fiber_point",
+            _ => return None,
+        };
+        Some(format!("{layer}:{}", asset_id?))
+    }
+
     async fn get_node_by_id(&self, tenant_id: &str, id: &str) -> AppResult<NetworkNode> {
         sqlx::query_as(
             r#"
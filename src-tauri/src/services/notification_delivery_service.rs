@@ -0,0 +1,308 @@
+//! Per-channel delivery tracking for notifications.
+//!
+//! Every channel a notification goes out on gets one row in
+//! `notification_deliveries`, moved along as the delivery progresses:
+//!
+//! - `in_app`: `delivered` once it is in the inbox, `read` when the user reads it.
+//! - `email`: `queued` while deferred or waiting in the email outbox, then
+//!   `sent` (accepted by SMTP) or `failed`.
+//! - `push`: `queued` while deferred, then `delivered` (accepted by at least one
+//!   push service) or `failed`.
+//!
+//! Recording is best-effort: a tracking failure is logged and never blocks the
+//! notification itself.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Notification, NotificationDelivery, NotificationDeliveryFilter, NotificationDeliveryReport,
+    NotificationDeliveryStat,
+};
+use chrono::{DateTime, Utc};
+use sqlx::QueryBuilder;
+use uuid::Uuid;
+
+#[cfg(feature = "postgres")]
+type Db = sqlx::Postgres;
+#[cfg(feature = "sqlite")]
+type Db = sqlx::Sqlite;
+
+pub const CHANNELS: &[&str] = &["in_app", "email", "push"];
+pub const STATUSES: &[&str] = &["queued", "sent", "delivered", "failed", "read"];
+
+const MAX_PER_PAGE: u32 = 100;
+const MAX_ERROR_LEN: usize = 500;
+
+/// Appends the `WHERE` clause for a tenant's delivery report.
+fn push_delivery_filters(
+    qb: &mut QueryBuilder<'_, Db>,
+    tenant_id: &str,
+    filter: &NotificationDeliveryFilter,
+) {
+    qb.push(" WHERE d.tenant_id = ");
+    qb.push_bind(tenant_id.to_string());
+    let exact = [
+        ("d.channel", &filter.channel),
+        ("d.status", &filter.status),
+        ("d.category", &filter.category),
+        ("d.user_id", &filter.user_id),
+        ("d.notification_id", &filter.notification_id),
+    ];
+    for (column, value) in exact {
+        if let Some(value) = value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+            qb.push(format!(" AND {} = ", column));
+            qb.push_bind(value.to_string());
+        }
+    }
+    if let Some(from) = filter.from {
+        qb.push(" AND d.created_at >= ");
+        qb.push_bind(from);
+    }
+    if let Some(to) = filter.to {
+        qb.push(" AND d.created_at <= ");
+        qb.push_bind(to);
+    }
+}
+
+fn validate_filter(filter: &NotificationDeliveryFilter) -> AppResult<()> {
+    let allowed = |value: &Option<String>, list: &[&str], what: &str| -> AppResult<()> {
+        match value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+            Some(v) if !list.contains(&v) => {
+                Err(AppError::Validation(format!("Unknown {} '{}'", what, v)))
+            }
+            _ => Ok(()),
+        }
+    };
+    allowed(&filter.channel, CHANNELS, "channel")?;
+    allowed(&filter.status, STATUSES, "status")?;
+    if let (Some(from), Some(to)) = (filter.from, filter.to) {
+        if from > to {
+            return Err(AppError::Validation(
+                "'from' must be before 'to'".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn truncate_error(error: &str) -> String {
+    error.chars().take(MAX_ERROR_LEN).collect()
+}
+
+#[derive(Clone)]
+pub struct NotificationDeliveryService {
+    pool: DbPool,
+}
+
+impl NotificationDeliveryService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Set the status of `notif` on `channel`, creating the row on first use.
+    pub async fn record(
+        &self,
+        notif: &Notification,
+        channel: &str,
+        status: &str,
+        error: Option<&str>,
+        email_outbox_id: Option<&str>,
+    ) {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO notification_deliveries
+                (id, notification_id, tenant_id, user_id, category, notification_type, title,
+                 channel, status, error, email_outbox_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $12)
+            ON CONFLICT (notification_id, channel) DO UPDATE SET
+                status = excluded.status,
+                error = excluded.error,
+                email_outbox_id = excluded.email_outbox_id,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&notif.id)
+        .bind(&notif.tenant_id)
+        .bind(&notif.user_id)
+        .bind(&notif.category)
+        .bind(&notif.notification_type)
+        .bind(&notif.title)
+        .bind(channel)
+        .bind(status)
+        .bind(error.map(truncate_error))
+        .bind(email_outbox_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await;
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to record {} delivery for notification {}: {}",
+                channel,
+                notif.id,
+                e
+            );
+        }
+    }
+
+    /// Mark in-app deliveries read: one notification, or all of the user's.
+    pub async fn mark_read(&self, user_id: &str, notification_id: Option<&str>) {
+        let mut qb: QueryBuilder<Db> =
+            QueryBuilder::new("UPDATE notification_deliveries SET status = 'read', updated_at = ");
+        qb.push_bind(Utc::now());
+        qb.push(" WHERE channel = 'in_app' AND status <> 'read' AND user_id = ");
+        qb.push_bind(user_id.to_string());
+        if let Some(id) = notification_id {
+            qb.push(" AND notification_id = ");
+            qb.push_bind(id.to_string());
+        }
+        if let Err(e) = qb.build().execute(&self.pool).await {
+            tracing::warn!("Failed to mark deliveries read for {}: {}", user_id, e);
+        }
+    }
+
+    /// Carry an email outbox result over to the delivery it belongs to, if any.
+    /// `status` is the delivery status (`queued` while retries remain).
+    pub async fn sync_email_outbox(
+        pool: &DbPool,
+        email_outbox_id: &str,
+        status: &str,
+        error: Option<&str>,
+    ) {
+        let result = sqlx::query(
+            "UPDATE notification_deliveries SET status = $1, error = $2, updated_at = $3 WHERE email_outbox_id = $4",
+        )
+        .bind(status)
+        .bind(error.map(truncate_error))
+        .bind(Utc::now())
+        .bind(email_outbox_id)
+        .execute(pool)
+        .await;
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to sync delivery for email outbox {}: {}",
+                email_outbox_id,
+                e
+            );
+        }
+    }
+
+    /// Delivery report for a tenant, newest first, with per-channel/status totals.
+    pub async fn report(
+        &self,
+        tenant_id: &str,
+        filter: &NotificationDeliveryFilter,
+        page: u32,
+        per_page: u32,
+    ) -> AppResult<NotificationDeliveryReport> {
+        validate_filter(filter)?;
+        let page = page.max(1);
+        let per_page = per_page.clamp(1, MAX_PER_PAGE);
+        let offset = (page - 1) * per_page;
+
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new(
+            r#"
+            SELECT d.id, d.notification_id, d.tenant_id, d.user_id, u.email AS user_email,
+                   d.category, d.notification_type, d.title, d.channel, d.status, d.error,
+                   d.created_at, d.updated_at
+            FROM notification_deliveries d
+            LEFT JOIN users u ON u.id = d.user_id
+            "#,
+        );
+        push_delivery_filters(&mut qb, tenant_id, filter);
+        qb.push(" ORDER BY d.created_at DESC, d.channel LIMIT ");
+        qb.push_bind(per_page as i64);
+        qb.push(" OFFSET ");
+        qb.push_bind(offset as i64);
+        let data = qb
+            .build_query_as::<NotificationDelivery>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut summary_qb: QueryBuilder<Db> = QueryBuilder::new(
+            "SELECT d.channel, d.status, COUNT(*) AS count FROM notification_deliveries d",
+        );
+        push_delivery_filters(&mut summary_qb, tenant_id, filter);
+        summary_qb.push(" GROUP BY d.channel, d.status ORDER BY d.channel, d.status");
+        let summary = summary_qb
+            .build_query_as::<NotificationDeliveryStat>()
+            .fetch_all(&self.pool)
+            .await?;
+        let total = summary.iter().map(|s| s.count).sum();
+
+        Ok(NotificationDeliveryReport {
+            summary,
+            data,
+            total,
+            page,
+            per_page,
+        })
+    }
+
+    /// Delete delivery records created before `cutoff`.
+    pub async fn purge_before(&self, cutoff: DateTime<Utc>) -> AppResult<u64> {
+        Ok(
+            sqlx::query("DELETE FROM notification_deliveries WHERE created_at < $1")
+                .bind(cutoff)
+                .execute(&self.pool)
+                .await?
+                .rows_affected(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn where_clause(filter: &NotificationDeliveryFilter) -> String {
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new("SELECT * FROM notification_deliveries d");
+        push_delivery_filters(&mut qb, "t1", filter);
+        qb.sql().to_string()
+    }
+
+    #[test]
+    fn report_is_always_tenant_scoped() {
+        let sql = where_clause(&NotificationDeliveryFilter::default());
+        assert!(sql.contains("d.tenant_id = "));
+        assert!(!sql.contains("d.channel"));
+        assert!(!sql.contains("d.created_at"));
+    }
+
+    #[test]
+    fn filters_add_conditions_and_skip_blanks() {
+        let sql = where_clause(&NotificationDeliveryFilter {
+            channel: Some("email".to_string()),
+            status: Some("  ".to_string()),
+            category: Some("billing".to_string()),
+            from: Some(Utc::now()),
+            ..Default::default()
+        });
+        assert!(sql.contains("d.channel = "));
+        assert!(!sql.contains("d.status"));
+        assert!(sql.contains("d.category = "));
+        assert!(sql.contains("d.created_at >= "));
+        assert!(!sql.contains("d.created_at <= "));
+    }
+
+    #[test]
+    fn rejects_unknown_channel_or_status() {
+        let bad_channel = NotificationDeliveryFilter {
+            channel: Some("fax".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_filter(&bad_channel).is_err());
+        let bad_status = NotificationDeliveryFilter {
+            status: Some("bounced".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_filter(&bad_status).is_err());
+        let ok = NotificationDeliveryFilter {
+            channel: Some("push".to_string()),
+            status: Some("failed".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_filter(&ok).is_ok());
+    }
+}
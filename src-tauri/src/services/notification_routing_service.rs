@@ -0,0 +1,597 @@
+//! Per-tenant routing rules for admin alerts.
+//!
+//! A rule matches on category, minimum severity and time of day (business hours
+//! or after hours, in the tenant's `app_timezone`) and names the channels and
+//! recipients to use instead of the producer's defaults. For example, critical
+//! network alerts can page an on-call user on Telegram after hours while the same
+//! alert during the day only goes out by email. Rules are tried by ascending
+//! priority; when none matches, producers keep their built-in behavior.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    EvaluateNotificationRoutingRequest, NotificationRoutingEvaluation, NotificationRoutingRule,
+    NotificationRoutingRuleRow, UpsertNotificationRoutingRuleRequest,
+};
+use crate::services::SettingsService;
+use chrono::{DateTime, Datelike, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use tracing::warn;
+use uuid::Uuid;
+
+pub const CHANNELS: &[&str] = &["in_app", "email", "telegram"];
+pub const SEVERITIES: &[&str] = &["info", "warning", "critical"];
+pub const TIME_WINDOWS: &[&str] = &["any", "business_hours", "after_hours"];
+/// Categories producers route through rules.
+pub const CATEGORIES: &[&str] = &["network", "billing", "operations"];
+const DEFAULT_PRIORITY: i32 = 100;
+
+/// Severity of a notification type: `error` alerts are critical.
+pub fn severity_for_type(notification_type: &str) -> &'static str {
+    match notification_type {
+        "error" => "critical",
+        "warning" => "warning",
+        _ => "info",
+    }
+}
+
+fn severity_rank(severity: &str) -> usize {
+    SEVERITIES.iter().position(|s| *s == severity).unwrap_or(0)
+}
+
+/// The tenant's working week, e.g. Mon-Fri 08:00-17:00. `end` before `start`
+/// means the window runs past midnight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BusinessHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub days: Vec<Weekday>,
+}
+
+impl Default for BusinessHours {
+    fn default() -> Self {
+        Self {
+            start: NaiveTime::from_hms_opt(8, 0, 0).unwrap_or_default(),
+            end: NaiveTime::from_hms_opt(17, 0, 0).unwrap_or_default(),
+            days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+        }
+    }
+}
+
+impl BusinessHours {
+    /// Unparseable parts keep their defaults.
+    pub fn parse(start: Option<&str>, end: Option<&str>, days: Option<&str>) -> Self {
+        let defaults = Self::default();
+        let time =
+            |v: Option<&str>| v.and_then(|v| NaiveTime::parse_from_str(v.trim(), "%H:%M").ok());
+        let days = days
+            .map(|v| {
+                v.split(',')
+                    .filter_map(|d| d.trim().parse::<Weekday>().ok())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|d| !d.is_empty());
+        Self {
+            start: time(start).unwrap_or(defaults.start),
+            end: time(end).unwrap_or(defaults.end),
+            days: days.unwrap_or(defaults.days),
+        }
+    }
+
+    pub fn contains<T: TimeZone>(&self, at: &DateTime<T>) -> bool {
+        let time = at.time();
+        let weekday = at.weekday();
+        if self.start <= self.end {
+            self.days.contains(&weekday) && time >= self.start && time < self.end
+        } else if time >= self.start {
+            self.days.contains(&weekday)
+        } else if time < self.end {
+            // Early-morning tail of a window that opened the previous day.
+            self.days.contains(&weekday.pred())
+        } else {
+            false
+        }
+    }
+}
+
+fn rule_matches(
+    rule: &NotificationRoutingRule,
+    category: &str,
+    severity: &str,
+    is_business_hours: bool,
+) -> bool {
+    rule.is_enabled
+        && rule.category.as_deref().is_none_or(|c| c == category)
+        && severity_rank(severity) >= severity_rank(&rule.min_severity)
+        && match rule.time_window.as_str() {
+            "business_hours" => is_business_hours,
+            "after_hours" => !is_business_hours,
+            _ => true,
+        }
+}
+
+/// First matching rule; `rules` must be sorted by priority.
+fn select_rule<'a>(
+    rules: &'a [NotificationRoutingRule],
+    category: &str,
+    severity: &str,
+    is_business_hours: bool,
+) -> Option<&'a NotificationRoutingRule> {
+    rules
+        .iter()
+        .find(|r| rule_matches(r, category, severity, is_business_hours))
+}
+
+fn normalize_list(values: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for v in values.iter().map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        if !out.iter().any(|o| o == v) {
+            out.push(v.to_string());
+        }
+    }
+    out
+}
+
+#[derive(Clone)]
+pub struct NotificationRoutingService {
+    pool: DbPool,
+    settings_service: SettingsService,
+}
+
+impl NotificationRoutingService {
+    pub fn new(pool: DbPool, settings_service: SettingsService) -> Self {
+        Self {
+            pool,
+            settings_service,
+        }
+    }
+
+    async fn setting(&self, tenant_id: &str, key: &str) -> Option<String> {
+        self.settings_service
+            .get_value_fallback(Some(tenant_id), key)
+            .await
+            .ok()
+            .flatten()
+            .filter(|v| !v.trim().is_empty())
+    }
+
+    async fn timezone(&self, tenant_id: &str) -> Tz {
+        let raw = self
+            .setting(tenant_id, "app_timezone")
+            .await
+            .unwrap_or_else(|| "UTC".to_string());
+        raw.trim().parse::<Tz>().unwrap_or(chrono_tz::UTC)
+    }
+
+    pub async fn business_hours(&self, tenant_id: &str) -> BusinessHours {
+        let start = self
+            .setting(tenant_id, "notification_business_hours_start")
+            .await;
+        let end = self
+            .setting(tenant_id, "notification_business_hours_end")
+            .await;
+        let days = self.setting(tenant_id, "notification_business_days").await;
+        BusinessHours::parse(start.as_deref(), end.as_deref(), days.as_deref())
+    }
+
+    async fn is_business_hours(&self, tenant_id: &str, at: DateTime<Utc>) -> bool {
+        let tz = self.timezone(tenant_id).await;
+        self.business_hours(tenant_id)
+            .await
+            .contains(&at.with_timezone(&tz))
+    }
+
+    pub async fn list(&self, tenant_id: &str) -> AppResult<Vec<NotificationRoutingRule>> {
+        let rows = sqlx::query_as::<_, NotificationRoutingRuleRow>(
+            "SELECT * FROM notification_routing_rules WHERE tenant_id = $1 ORDER BY priority ASC, created_at ASC",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn get(&self, tenant_id: &str, id: &str) -> AppResult<NotificationRoutingRule> {
+        sqlx::query_as::<_, NotificationRoutingRuleRow>(
+            "SELECT * FROM notification_routing_rules WHERE id = $1 AND tenant_id = $2",
+        )
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(Into::into)
+        .ok_or_else(|| AppError::NotFound("Routing rule not found".to_string()))
+    }
+
+    /// Checks the request and returns `(channels, recipients)` in stored form.
+    async fn validate(
+        &self,
+        tenant_id: &str,
+        req: &UpsertNotificationRoutingRuleRequest,
+    ) -> AppResult<(String, String)> {
+        if req.name.trim().is_empty() {
+            return Err(AppError::Validation("Rule name is required".to_string()));
+        }
+        if let Some(category) = req.category.as_deref().map(str::trim) {
+            if !category.is_empty() && !CATEGORIES.contains(&category) {
+                return Err(AppError::Validation(format!(
+                    "Unknown category '{}'. Use one of: {}",
+                    category,
+                    CATEGORIES.join(", ")
+                )));
+            }
+        }
+        if let Some(severity) = req.min_severity.as_deref() {
+            if !SEVERITIES.contains(&severity) {
+                return Err(AppError::Validation(format!(
+                    "Severity must be one of: {}",
+                    SEVERITIES.join(", ")
+                )));
+            }
+        }
+        if let Some(window) = req.time_window.as_deref() {
+            if !TIME_WINDOWS.contains(&window) {
+                return Err(AppError::Validation(format!(
+                    "Time window must be one of: {}",
+                    TIME_WINDOWS.join(", ")
+                )));
+            }
+        }
+
+        let channels = normalize_list(&req.channels);
+        if channels.is_empty() {
+            return Err(AppError::Validation(
+                "Select at least one channel".to_string(),
+            ));
+        }
+        if let Some(bad) = channels.iter().find(|c| !CHANNELS.contains(&c.as_str())) {
+            return Err(AppError::Validation(format!(
+                "Unknown channel '{}'. Use one of: {}",
+                bad,
+                CHANNELS.join(", ")
+            )));
+        }
+
+        let mut recipients = normalize_list(req.recipients.as_deref().unwrap_or_default());
+        if recipients.is_empty() {
+            recipients.push("default".to_string());
+        }
+        for recipient in &recipients {
+            let known = if recipient == "default" {
+                true
+            } else if let Some(user_id) = recipient.strip_prefix("user:") {
+                sqlx::query_scalar::<_, bool>(
+                    "SELECT EXISTS(SELECT 1 FROM tenant_members WHERE tenant_id = $1 AND user_id = $2)",
+                )
+                .bind(tenant_id)
+                .bind(user_id)
+                .fetch_one(&self.pool)
+                .await?
+            } else if let Some(role_id) = recipient.strip_prefix("role:") {
+                sqlx::query_scalar::<_, bool>(
+                    "SELECT EXISTS(SELECT 1 FROM roles WHERE id = $1 AND (tenant_id = $2 OR tenant_id IS NULL))",
+                )
+                .bind(role_id)
+                .bind(tenant_id)
+                .fetch_one(&self.pool)
+                .await?
+            } else {
+                false
+            };
+            if !known {
+                return Err(AppError::Validation(format!(
+                    "Unknown recipient '{}'. Use default, user:<id> of a tenant member, or role:<id>",
+                    recipient
+                )));
+            }
+        }
+
+        Ok((channels.join(","), recipients.join(",")))
+    }
+
+    pub async fn create(
+        &self,
+        tenant_id: &str,
+        req: UpsertNotificationRoutingRuleRequest,
+        actor_id: Option<&str>,
+    ) -> AppResult<NotificationRoutingRule> {
+        let (channels, recipients) = self.validate(tenant_id, &req).await?;
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO notification_routing_rules
+                (id, tenant_id, name, priority, is_enabled, category, min_severity,
+                 time_window, channels, recipients, created_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $12)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(req.name.trim())
+        .bind(req.priority.unwrap_or(DEFAULT_PRIORITY))
+        .bind(req.is_enabled.unwrap_or(true))
+        .bind(
+            req.category
+                .as_deref()
+                .map(str::trim)
+                .filter(|c| !c.is_empty()),
+        )
+        .bind(req.min_severity.as_deref().unwrap_or("info"))
+        .bind(req.time_window.as_deref().unwrap_or("any"))
+        .bind(channels)
+        .bind(recipients)
+        .bind(actor_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        self.get(tenant_id, &id).await
+    }
+
+    pub async fn update(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        req: UpsertNotificationRoutingRuleRequest,
+    ) -> AppResult<NotificationRoutingRule> {
+        let existing = self.get(tenant_id, id).await?;
+        let (channels, recipients) = self.validate(tenant_id, &req).await?;
+        sqlx::query(
+            r#"
+            UPDATE notification_routing_rules
+            SET name = $1, priority = $2, is_enabled = $3, category = $4, min_severity = $5,
+                time_window = $6, channels = $7, recipients = $8, updated_at = $9
+            WHERE id = $10 AND tenant_id = $11
+            "#,
+        )
+        .bind(req.name.trim())
+        .bind(req.priority.unwrap_or(existing.priority))
+        .bind(req.is_enabled.unwrap_or(existing.is_enabled))
+        .bind(
+            req.category
+                .as_deref()
+                .map(str::trim)
+                .filter(|c| !c.is_empty()),
+        )
+        .bind(
+            req.min_severity
+                .as_deref()
+                .unwrap_or(&existing.min_severity),
+        )
+        .bind(req.time_window.as_deref().unwrap_or(&existing.time_window))
+        .bind(channels)
+        .bind(recipients)
+        .bind(Utc::now())
+        .bind(id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        self.get(tenant_id, id).await
+    }
+
+    pub async fn delete(&self, tenant_id: &str, id: &str) -> AppResult<()> {
+        let result =
+            sqlx::query("DELETE FROM notification_routing_rules WHERE id = $1 AND tenant_id = $2")
+                .bind(id)
+                .bind(tenant_id)
+                .execute(&self.pool)
+                .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Routing rule not found".to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn evaluate(
+        &self,
+        tenant_id: &str,
+        req: EvaluateNotificationRoutingRequest,
+    ) -> AppResult<NotificationRoutingEvaluation> {
+        let at = req.at.unwrap_or_else(Utc::now);
+        let is_business_hours = self.is_business_hours(tenant_id, at).await;
+        let rules = self.list(tenant_id).await?;
+        let rule = select_rule(&rules, &req.category, &req.severity, is_business_hours).cloned();
+        let channels = match &rule {
+            Some(r) => r.channels.clone(),
+            None => vec!["default".to_string()],
+        };
+        Ok(NotificationRoutingEvaluation {
+            rule,
+            is_business_hours,
+            channels,
+        })
+    }
+
+    /// The rule for an alert being sent now, if any. Lookup errors count as no
+    /// match so alerts still go out with the producer's defaults.
+    pub async fn resolve(
+        &self,
+        tenant_id: &str,
+        category: &str,
+        notification_type: &str,
+    ) -> Option<NotificationRoutingRule> {
+        let rules = match self.list(tenant_id).await {
+            Ok(rules) => rules,
+            Err(e) => {
+                warn!(
+                    "Failed to load routing rules for tenant {}: {}",
+                    tenant_id, e
+                );
+                return None;
+            }
+        };
+        if !rules.iter().any(|r| r.is_enabled) {
+            return None;
+        }
+        let is_business_hours = self.is_business_hours(tenant_id, Utc::now()).await;
+        select_rule(
+            &rules,
+            category,
+            severity_for_type(notification_type),
+            is_business_hours,
+        )
+        .cloned()
+    }
+
+    /// User ids a rule sends to. `default` expands to the producer's audience.
+    pub async fn resolve_recipients(
+        &self,
+        tenant_id: &str,
+        rule: &NotificationRoutingRule,
+        defaults: &[String],
+    ) -> Vec<String> {
+        let mut user_ids: Vec<String> = Vec::new();
+        for recipient in &rule.recipients {
+            let found: Vec<String> = if recipient == "default" {
+                defaults.to_vec()
+            } else if let Some(user_id) = recipient.strip_prefix("user:") {
+                sqlx::query_scalar(
+                    "SELECT user_id FROM tenant_members WHERE tenant_id = $1 AND user_id = $2",
+                )
+                .bind(tenant_id)
+                .bind(user_id)
+                .fetch_all(&self.pool)
+                .await
+                .unwrap_or_default()
+            } else if let Some(role_id) = recipient.strip_prefix("role:") {
+                sqlx::query_scalar(
+                    "SELECT user_id FROM tenant_members WHERE tenant_id = $1 AND role_id = $2",
+                )
+                .bind(tenant_id)
+                .bind(role_id)
+                .fetch_all(&self.pool)
+                .await
+                .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+            for id in found {
+                if !user_ids.contains(&id) {
+                    user_ids.push(id);
+                }
+            }
+        }
+        user_ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(
+        category: Option<&str>,
+        min_severity: &str,
+        time_window: &str,
+        channels: &[&str],
+    ) -> NotificationRoutingRule {
+        NotificationRoutingRule {
+            id: Uuid::new_v4().to_string(),
+            name: "rule".to_string(),
+            priority: 100,
+            is_enabled: true,
+            category: category.map(str::to_string),
+            min_severity: min_severity.to_string(),
+            time_window: time_window.to_string(),
+            channels: channels.iter().map(|c| c.to_string()).collect(),
+            recipients: vec!["default".to_string()],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn at(tz: Tz, y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Tz> {
+        tz.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn maps_notification_types_to_severity() {
+        assert_eq!(severity_for_type("error"), "critical");
+        assert_eq!(severity_for_type("warning"), "warning");
+        assert_eq!(severity_for_type("success"), "info");
+        assert_eq!(severity_for_type("info"), "info");
+    }
+
+    #[test]
+    fn business_hours_parse_and_contain() {
+        let tz: Tz = "Asia/Jakarta".parse().unwrap();
+        let hours = BusinessHours::parse(Some("08:00"), Some("17:00"), Some("mon,tue,wed,thu,fri"));
+        // 2026-03-02 is a Monday.
+        assert!(hours.contains(&at(tz, 2026, 3, 2, 8, 0)));
+        assert!(hours.contains(&at(tz, 2026, 3, 2, 16, 59)));
+        assert!(!hours.contains(&at(tz, 2026, 3, 2, 17, 0)));
+        assert!(!hours.contains(&at(tz, 2026, 3, 7, 10, 0))); // Saturday
+
+        let fallback = BusinessHours::parse(Some("8am"), None, Some("someday"));
+        assert_eq!(fallback, BusinessHours::default());
+    }
+
+    #[test]
+    fn overnight_business_hours_wrap_midnight() {
+        let tz = chrono_tz::UTC;
+        let night_shift = BusinessHours::parse(Some("22:00"), Some("06:00"), Some("fri"));
+        assert!(night_shift.contains(&at(tz, 2026, 3, 6, 23, 0))); // Friday night
+        assert!(night_shift.contains(&at(tz, 2026, 3, 7, 5, 0))); // into Saturday
+        assert!(!night_shift.contains(&at(tz, 2026, 3, 7, 23, 0))); // Saturday night
+        assert!(!night_shift.contains(&at(tz, 2026, 3, 6, 12, 0)));
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = vec![
+            rule(Some("network"), "critical", "after_hours", &["telegram"]),
+            rule(Some("network"), "critical", "any", &["email"]),
+            rule(None, "warning", "any", &["in_app"]),
+        ];
+
+        let night = select_rule(&rules, "network", "critical", false).unwrap();
+        assert_eq!(night.channels, vec!["telegram"]);
+        let day = select_rule(&rules, "network", "critical", true).unwrap();
+        assert_eq!(day.channels, vec!["email"]);
+        let billing = select_rule(&rules, "billing", "warning", true).unwrap();
+        assert_eq!(billing.channels, vec!["in_app"]);
+        assert!(select_rule(&rules, "network", "info", false).is_none());
+    }
+
+    #[test]
+    fn disabled_rules_are_skipped() {
+        let mut r = rule(None, "info", "any", &["email"]);
+        assert!(rule_matches(&r, "billing", "info", true));
+        r.is_enabled = false;
+        assert!(!rule_matches(&r, "billing", "info", true));
+    }
+
+    #[test]
+    fn splits_stored_lists() {
+        let row = NotificationRoutingRuleRow {
+            id: "r1".to_string(),
+            tenant_id: "t1".to_string(),
+            name: "Night on-call".to_string(),
+            priority: 10,
+            is_enabled: true,
+            category: Some("network".to_string()),
+            min_severity: "critical".to_string(),
+            time_window: "after_hours".to_string(),
+            channels: "telegram, email".to_string(),
+            recipients: "user:u1,,role:r2".to_string(),
+            created_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let parsed: NotificationRoutingRule = row.into();
+        assert_eq!(parsed.channels, vec!["telegram", "email"]);
+        assert_eq!(parsed.recipients, vec!["user:u1", "role:r2"]);
+        assert_eq!(
+            normalize_list(&[" email".to_string(), "email".to_string(), String::new()]),
+            vec!["email"]
+        );
+    }
+}
@@ -2,34 +2,329 @@ use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
 use crate::http::WsHub;
 use crate::models::{
-    CreatePushSubscriptionRequest, Notification, NotificationPreference, PaginatedResponse,
-    PushSubscription, UpdatePreferenceRequest,
+    CreatePushSubscriptionRequest, Notification, NotificationFilter, NotificationPreference,
+    PaginatedResponse, RenderedNotification, UpdatePreferenceRequest, WhatsappMessage,
 };
-use crate::services::EmailOutboxService;
-use axum::http::Uri;
-use chrono::Utc;
+use crate::services::leader_lock::LeaderLock;
+use crate::services::quiet_hours_service::bypasses_quiet_hours;
+use crate::services::telegram_service::TelegramAlert;
+use crate::services::whatsapp_service::{WhatsappEvent, WhatsappRecipient};
+use crate::services::{
+    EmailOutboxService, EmailTemplateService, NotificationDeliveryService,
+    NotificationRoutingService, NotificationTemplateService, QuietHoursService, SettingsService,
+    TelegramService, WebPushService, WhatsappService,
+};
+use chrono::{DateTime, Utc};
+use sqlx::QueryBuilder;
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
-// Web Push (pure Rust implementation)
-use base64ct::{Base64UrlUnpadded, Encoding};
-use web_push_native::{
-    jwt_simple::algorithms::ES256KeyPair, p256::PublicKey, Auth, WebPushBuilder,
-};
+#[cfg(feature = "postgres")]
+type Db = sqlx::Postgres;
+#[cfg(feature = "sqlite")]
+type Db = sqlx::Sqlite;
+
+const MAX_PER_PAGE: u32 = 100;
+const MAX_BULK_IDS: usize = 500;
+const PURGE_BATCH_SIZE: i64 = 5_000;
+const RETENTION_INTERVAL_SECS: u64 = 6 * 3600;
+const DEFERRED_BATCH_SIZE: i64 = 200;
+
+/// Appends the `WHERE` clause for a user's notification list.
+fn push_notification_filters(
+    qb: &mut QueryBuilder<'_, Db>,
+    user_id: &str,
+    filter: &NotificationFilter,
+) {
+    qb.push(" WHERE user_id = ");
+    qb.push_bind(user_id.to_string());
+    if filter.archived.unwrap_or(false) {
+        qb.push(" AND archived_at IS NOT NULL");
+    } else {
+        qb.push(" AND archived_at IS NULL");
+    }
+    if let Some(category) = filter.category.as_deref().filter(|v| !v.is_empty()) {
+        qb.push(" AND category = ");
+        qb.push_bind(category.to_string());
+    }
+    if let Some(kind) = filter
+        .notification_type
+        .as_deref()
+        .filter(|v| !v.is_empty())
+    {
+        qb.push(" AND notification_type = ");
+        qb.push_bind(kind.to_string());
+    }
+    if let Some(is_read) = filter.is_read {
+        qb.push(" AND is_read = ");
+        qb.push_bind(is_read);
+    }
+    if let Some(from) = filter.from {
+        qb.push(" AND created_at >= ");
+        qb.push_bind(from);
+    }
+    if let Some(to) = filter.to {
+        qb.push(" AND created_at <= ");
+        qb.push_bind(to);
+    }
+    if let Some(search) = filter
+        .search
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+    {
+        let pattern = format!("%{}%", search.to_lowercase());
+        qb.push(" AND (LOWER(title) LIKE ");
+        qb.push_bind(pattern.clone());
+        qb.push(" OR LOWER(message) LIKE ");
+        qb.push_bind(pattern);
+        qb.push(")");
+    }
+}
 
 #[derive(Clone)]
 pub struct NotificationService {
     pool: DbPool,
     ws_hub: Arc<WsHub>,
     email_outbox: EmailOutboxService,
+    templates: NotificationTemplateService,
+    deliveries: NotificationDeliveryService,
+    whatsapp: Option<WhatsappService>,
+    telegram: Option<TelegramService>,
+    web_push: Option<WebPushService>,
+    routing: Option<NotificationRoutingService>,
+    quiet_hours: Option<QuietHoursService>,
+    email_templates: Option<EmailTemplateService>,
+}
+
+/// An admin alert that tenant routing rules may redirect.
+pub struct TenantAlert<'a> {
+    pub category: &'a str,
+    pub notification_type: &'a str,
+    pub title: &'a str,
+    pub message: &'a str,
+    pub action_url: Option<&'a str>,
+    pub telegram: TelegramAlert,
 }
 
 impl NotificationService {
     pub fn new(pool: DbPool, ws_hub: Arc<WsHub>, email_outbox: EmailOutboxService) -> Self {
         Self {
+            templates: NotificationTemplateService::new(pool.clone()),
+            deliveries: NotificationDeliveryService::new(pool.clone()),
             pool,
             ws_hub,
             email_outbox,
+            whatsapp: None,
+            telegram: None,
+            web_push: None,
+            routing: None,
+            quiet_hours: None,
+            email_templates: None,
+        }
+    }
+
+    pub fn templates(&self) -> &NotificationTemplateService {
+        &self.templates
+    }
+
+    pub fn deliveries(&self) -> &NotificationDeliveryService {
+        &self.deliveries
+    }
+
+    pub fn email_outbox(&self) -> &EmailOutboxService {
+        &self.email_outbox
+    }
+
+    /// Title and body for a notification, using the tenant's template override if any.
+    pub async fn render_template(
+        &self,
+        tenant_id: Option<&str>,
+        code: &str,
+        vars: &HashMap<&str, String>,
+    ) -> RenderedNotification {
+        self.templates.render(tenant_id, code, vars).await
+    }
+
+    pub fn with_whatsapp(mut self, whatsapp: WhatsappService) -> Self {
+        self.whatsapp = Some(whatsapp);
+        self
+    }
+
+    pub fn whatsapp(&self) -> Option<&WhatsappService> {
+        self.whatsapp.as_ref()
+    }
+
+    pub fn with_web_push(mut self, web_push: WebPushService) -> Self {
+        self.web_push = Some(web_push);
+        self
+    }
+
+    pub fn web_push(&self) -> Option<&WebPushService> {
+        self.web_push.as_ref()
+    }
+
+    pub fn with_telegram(mut self, telegram: TelegramService) -> Self {
+        self.telegram = Some(telegram);
+        self
+    }
+
+    pub fn telegram(&self) -> Option<&TelegramService> {
+        self.telegram.as_ref()
+    }
+
+    pub fn with_routing(mut self, routing: NotificationRoutingService) -> Self {
+        self.routing = Some(routing);
+        self
+    }
+
+    pub fn routing(&self) -> Option<&NotificationRoutingService> {
+        self.routing.as_ref()
+    }
+
+    pub fn with_quiet_hours(mut self, quiet_hours: QuietHoursService) -> Self {
+        self.quiet_hours = Some(quiet_hours);
+        self
+    }
+
+    pub fn quiet_hours(&self) -> Option<&QuietHoursService> {
+        self.quiet_hours.as_ref()
+    }
+
+    pub fn with_email_templates(mut self, email_templates: EmailTemplateService) -> Self {
+        self.email_templates = Some(email_templates);
+        self
+    }
+
+    pub fn email_templates(&self) -> Option<&EmailTemplateService> {
+        self.email_templates.as_ref()
+    }
+
+    /// Deliver `alert` as the tenant's first matching routing rule says.
+    ///
+    /// Returns `false` when no rule matches; the caller then sends the alert to
+    /// `default_recipients` the usual way. Channels are sent regardless of user
+    /// preferences since the rule is an explicit admin choice.
+    pub async fn route_alert(
+        &self,
+        tenant_id: &str,
+        default_recipients: &[String],
+        alert: &TenantAlert<'_>,
+    ) -> bool {
+        let Some(routing) = &self.routing else {
+            return false;
+        };
+        let Some(rule) = routing
+            .resolve(tenant_id, alert.category, alert.notification_type)
+            .await
+        else {
+            return false;
+        };
+        let user_ids = routing
+            .resolve_recipients(tenant_id, &rule, default_recipients)
+            .await;
+        if user_ids.is_empty() {
+            tracing::warn!(
+                "Routing rule '{}' (tenant={}) resolved no recipients",
+                rule.name,
+                tenant_id
+            );
+            return true;
+        }
+
+        for channel in &rule.channels {
+            match channel.as_str() {
+                "in_app" => {
+                    for user_id in &user_ids {
+                        let _ = self
+                            .create_notification(
+                                user_id.clone(),
+                                Some(tenant_id.to_string()),
+                                alert.title.to_string(),
+                                alert.message.to_string(),
+                                alert.notification_type.to_string(),
+                                alert.category.to_string(),
+                                alert.action_url.map(str::to_string),
+                            )
+                            .await;
+                    }
+                }
+                "email" => {
+                    let mut body = alert.message.to_string();
+                    if let Some(url) = alert.action_url {
+                        body.push_str("\n\nOpen: ");
+                        body.push_str(url);
+                    }
+                    for user_id in &user_ids {
+                        let email: Option<String> =
+                            sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
+                                .bind(user_id)
+                                .fetch_optional(&self.pool)
+                                .await
+                                .ok()
+                                .flatten();
+                        let Some(email) = email.filter(|e| !e.trim().is_empty()) else {
+                            continue;
+                        };
+                        if let Err(e) = self
+                            .force_send_email(
+                                Some(tenant_id.to_string()),
+                                &email,
+                                alert.title,
+                                &body,
+                            )
+                            .await
+                        {
+                            tracing::warn!("Routed email to {} failed: {}", email, e);
+                        }
+                    }
+                }
+                "telegram" => {
+                    let text = format!("{}\n{}", alert.title, alert.message);
+                    self.send_telegram_alert(tenant_id, alert.telegram, &user_ids, &text)
+                        .await;
+                }
+                other => tracing::warn!("Unknown routing channel '{}'", other),
+            }
+        }
+        true
+    }
+
+    /// Mirror an admin alert to the Telegram chats linked by `user_ids`.
+    pub async fn send_telegram_alert(
+        &self,
+        tenant_id: &str,
+        kind: TelegramAlert,
+        user_ids: &[String],
+        text: &str,
+    ) {
+        if let Some(telegram) = &self.telegram {
+            telegram.send_alert(tenant_id, kind, user_ids, text).await;
+        }
+    }
+
+    /// Send a customer-facing event over WhatsApp when the tenant has routed it.
+    /// Best effort: returns the logged message, or `None` if nothing was sent.
+    pub async fn send_whatsapp(
+        &self,
+        tenant_id: &str,
+        event: WhatsappEvent,
+        recipient: &WhatsappRecipient,
+        vars: &HashMap<&str, String>,
+    ) -> Option<WhatsappMessage> {
+        let whatsapp = self.whatsapp.as_ref()?;
+        match whatsapp.send_event(tenant_id, event, recipient, vars).await {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!(
+                    "WhatsApp {} failed (tenant={}): {}",
+                    event.as_str(),
+                    tenant_id,
+                    e
+                );
+                None
+            }
         }
     }
 
@@ -159,36 +454,45 @@ impl NotificationService {
         Ok(notification)
     }
 
-    /// List notifications for a user
+    /// List notifications for a user, newest first, narrowed by `filter`.
     pub async fn list_notifications(
         &self,
         user_id: &str,
         page: u32,
         per_page: u32,
+        filter: &NotificationFilter,
     ) -> AppResult<PaginatedResponse<Notification>> {
+        if let (Some(from), Some(to)) = (filter.from, filter.to) {
+            if from > to {
+                return Err(AppError::Validation(
+                    "'from' must be before 'to'".to_string(),
+                ));
+            }
+        }
+        let page = page.max(1);
+        let per_page = per_page.clamp(1, MAX_PER_PAGE);
         let offset = (page - 1) * per_page;
 
-        let notifications = sqlx::query_as::<_, Notification>(
-            r#"
-            SELECT * FROM notifications 
-            WHERE user_id = $1 
-            ORDER BY created_at DESC 
-            LIMIT $2 OFFSET $3
-        "#,
-        )
-        .bind(user_id)
-        .bind(per_page as i64)
-        .bind(offset as i64)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(AppError::Database)?;
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new("SELECT * FROM notifications");
+        push_notification_filters(&mut qb, user_id, filter);
+        qb.push(" ORDER BY created_at DESC LIMIT ");
+        qb.push_bind(per_page as i64);
+        qb.push(" OFFSET ");
+        qb.push_bind(offset as i64);
+        let notifications = qb
+            .build_query_as::<Notification>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::Database)?;
 
-        let total: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM notifications WHERE user_id = $1")
-                .bind(user_id)
-                .fetch_one(&self.pool)
-                .await
-                .map_err(AppError::Database)?;
+        let mut count_qb: QueryBuilder<Db> =
+            QueryBuilder::new("SELECT COUNT(*) FROM notifications");
+        push_notification_filters(&mut count_qb, user_id, filter);
+        let total: i64 = count_qb
+            .build_query_scalar()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(AppError::Database)?;
 
         Ok(PaginatedResponse {
             data: notifications,
@@ -198,11 +502,138 @@ impl NotificationService {
         })
     }
 
+    /// Archive (or with `archived = false`, restore) the user's notifications by id.
+    /// Returns how many rows changed.
+    pub async fn archive_notifications(
+        &self,
+        user_id: &str,
+        ids: &[String],
+        archived: bool,
+    ) -> AppResult<u64> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        if ids.len() > MAX_BULK_IDS {
+            return Err(AppError::Validation(format!(
+                "At most {} notifications can be archived at once",
+                MAX_BULK_IDS
+            )));
+        }
+
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new("UPDATE notifications SET archived_at = ");
+        qb.push_bind(archived.then(Utc::now));
+        qb.push(" WHERE user_id = ");
+        qb.push_bind(user_id.to_string());
+        qb.push(if archived {
+            " AND archived_at IS NULL"
+        } else {
+            " AND archived_at IS NOT NULL"
+        });
+        qb.push(" AND id IN (");
+        let mut ids_sql = qb.separated(", ");
+        for id in ids {
+            ids_sql.push_bind(id.clone());
+        }
+        ids_sql.push_unseparated(")");
+
+        let result = qb
+            .build()
+            .execute(&self.pool)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(result.rows_affected())
+    }
+
+    /// Delete notifications past retention: archived ones after
+    /// `archived_retention_days`, everything after `retention_days` (0 keeps
+    /// forever). Deletes in batches so a large backlog doesn't hold long locks.
+    pub async fn purge_expired(
+        &self,
+        retention_days: i64,
+        archived_retention_days: i64,
+    ) -> AppResult<u64> {
+        let now = Utc::now();
+        let mut purged = 0;
+        if archived_retention_days > 0 {
+            purged += self
+                .purge_batched(
+                    "archived_at IS NOT NULL AND archived_at < $1",
+                    now - chrono::Duration::days(archived_retention_days),
+                )
+                .await?;
+        }
+        if retention_days > 0 {
+            let cutoff = now - chrono::Duration::days(retention_days);
+            purged += self.purge_batched("created_at < $1", cutoff).await?;
+            self.deliveries.purge_before(cutoff).await?;
+        }
+        Ok(purged)
+    }
+
+    async fn purge_batched(&self, condition: &str, cutoff: DateTime<Utc>) -> AppResult<u64> {
+        let sql = format!(
+            "DELETE FROM notifications WHERE id IN (SELECT id FROM notifications WHERE {} LIMIT {})",
+            condition, PURGE_BATCH_SIZE
+        );
+        let mut total = 0;
+        loop {
+            let deleted = sqlx::query(&sql)
+                .bind(cutoff)
+                .execute(&self.pool)
+                .await
+                .map_err(AppError::Database)?
+                .rows_affected();
+            total += deleted;
+            if deleted < PURGE_BATCH_SIZE as u64 {
+                return Ok(total);
+            }
+        }
+    }
+
+    /// Run retention cleanup every few hours (`notification_retention_days`,
+    /// `notification_archived_retention_days`).
+    pub fn start_retention_cleanup(&self, settings_service: SettingsService) {
+        let this = self.clone();
+        let leader = LeaderLock::new(self.pool.clone(), "notification_retention");
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(tokio::time::Duration::from_secs(RETENTION_INTERVAL_SECS));
+            // Skip the immediate first tick; there is no rush at boot.
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if !leader.acquire().await {
+                    continue;
+                }
+                let days = |key: &'static str, default: i64| {
+                    let settings_service = settings_service.clone();
+                    async move {
+                        settings_service
+                            .get_value(None, key)
+                            .await
+                            .ok()
+                            .flatten()
+                            .and_then(|v| v.trim().parse::<i64>().ok())
+                            .unwrap_or(default)
+                            .max(0)
+                    }
+                };
+                let retention = days("notification_retention_days", 180).await;
+                let archived_retention = days("notification_archived_retention_days", 30).await;
+                match this.purge_expired(retention, archived_retention).await {
+                    Ok(0) => {}
+                    Ok(n) => tracing::info!("Purged {} expired notification(s)", n),
+                    Err(e) => tracing::warn!("Notification retention cleanup failed: {}", e),
+                }
+            }
+        });
+    }
+
     /// Get unread count
     pub async fn get_unread_count(&self, user_id: &str) -> AppResult<i64> {
         #[cfg(feature = "postgres")]
         let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND is_read = false",
+            "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND is_read = false AND archived_at IS NULL",
         )
         .bind(user_id)
         .fetch_one(&self.pool)
@@ -211,7 +642,7 @@ impl NotificationService {
 
         #[cfg(feature = "sqlite")]
         let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND is_read = 0",
+            "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND is_read = 0 AND archived_at IS NULL",
         )
         .bind(user_id)
         .fetch_one(&self.pool)
@@ -239,6 +670,7 @@ impl NotificationService {
             .await
             .map_err(AppError::Database)?;
 
+        self.deliveries.mark_read(user_id, Some(id)).await;
         Ok(())
     }
 
@@ -258,6 +690,7 @@ impl NotificationService {
             .await
             .map_err(AppError::Database)?;
 
+        self.deliveries.mark_read(user_id, None).await;
         Ok(())
     }
 
@@ -340,38 +773,10 @@ impl NotificationService {
         user_id: &str,
         req: CreatePushSubscriptionRequest,
     ) -> AppResult<()> {
-        let id = Uuid::new_v4().to_string();
-        let now = Utc::now();
-
-        let exists: bool = sqlx::query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM push_subscriptions WHERE endpoint = $1)",
-        )
-        .bind(&req.endpoint)
-        .fetch_one(&self.pool)
-        .await
-        .unwrap_or(false);
-
-        if exists {
-            return Ok(());
-        }
-
-        sqlx::query(
-            r#"
-            INSERT INTO push_subscriptions (id, user_id, endpoint, p256dh, auth, created_at)
-            VALUES ($1, $2, $3, $4, $5, $6)
-        "#,
-        )
-        .bind(id)
-        .bind(user_id)
-        .bind(req.endpoint)
-        .bind(req.p256dh)
-        .bind(req.auth)
-        .bind(now)
-        .execute(&self.pool)
-        .await
-        .map_err(AppError::Database)?;
-
-        Ok(())
+        self.web_push()
+            .ok_or_else(|| AppError::Internal("Web Push is not initialized".to_string()))?
+            .subscribe(user_id, req)
+            .await
     }
 
     pub async fn unsubscribe_push_for_user(&self, endpoint: &str, user_id: &str) -> AppResult<()> {
@@ -384,155 +789,6 @@ impl NotificationService {
         Ok(())
     }
 
-    /// Send Push Notification using web-push-native
-    pub async fn send_push_notification(
-        &self,
-        notif: &Notification,
-        user_id: &str,
-    ) -> AppResult<()> {
-        let subscriptions = sqlx::query_as::<_, PushSubscription>(
-            "SELECT * FROM push_subscriptions WHERE user_id = $1",
-        )
-        .bind(user_id)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(AppError::Database)?;
-
-        if subscriptions.is_empty() {
-            return Ok(());
-        }
-
-        // Get VAPID private key from env
-        let vapid_private_key = std::env::var("VAPID_PRIVATE_KEY").unwrap_or_default();
-        if vapid_private_key.is_empty() {
-            tracing::warn!("VAPID_PRIVATE_KEY not set. Skipping push notification.");
-            return Ok(());
-        }
-
-        // Parse keypair
-        let key_bytes = match Base64UrlUnpadded::decode_vec(&vapid_private_key) {
-            Ok(b) => b,
-            Err(e) => {
-                tracing::error!("Invalid VAPID key encoding: {}", e);
-                return Ok(());
-            }
-        };
-        let key_pair = match ES256KeyPair::from_bytes(&key_bytes) {
-            Ok(kp) => kp,
-            Err(e) => {
-                tracing::error!("Invalid VAPID key: {}", e);
-                return Ok(());
-            }
-        };
-
-        // Create payload
-        let payload = serde_json::json!({
-            "title": notif.title,
-            "message": notif.message,
-            "action_url": notif.action_url
-        });
-        let payload_bytes = payload.to_string().into_bytes();
-
-        // Create HTTP client
-        let client = reqwest::Client::new();
-
-        for sub in subscriptions {
-            // Decode subscription keys (Handle both URL-safe and Standard Base64 for backward compatibility)
-            let p256dh_safe = sub.p256dh.replace('+', "-").replace('/', "_");
-            let p256dh_safe = p256dh_safe.trim_end_matches('=');
-            let p256dh_bytes = match Base64UrlUnpadded::decode_vec(p256dh_safe) {
-                Ok(b) => b,
-                Err(e) => {
-                    tracing::error!("Invalid p256dh for {}: {}", sub.endpoint, e);
-                    continue;
-                }
-            };
-
-            let auth_safe = sub.auth.replace('+', "-").replace('/', "_");
-            let auth_safe = auth_safe.trim_end_matches('=');
-            let auth_bytes = match Base64UrlUnpadded::decode_vec(auth_safe) {
-                Ok(b) => b,
-                Err(e) => {
-                    tracing::error!("Invalid auth for {}: {}", sub.endpoint, e);
-                    continue;
-                }
-            };
-
-            let public_key = match PublicKey::from_sec1_bytes(&p256dh_bytes) {
-                Ok(pk) => pk,
-                Err(e) => {
-                    tracing::error!("Invalid public key for {}: {}", sub.endpoint, e);
-                    continue;
-                }
-            };
-
-            // Auth must be exactly 16 bytes
-            if auth_bytes.len() != 16 {
-                tracing::error!(
-                    "Invalid auth length for {}: got {} bytes, expected 16",
-                    sub.endpoint,
-                    auth_bytes.len()
-                );
-                continue;
-            }
-            let auth = Auth::clone_from_slice(&auth_bytes);
-
-            // Build push request
-            let endpoint: Uri = match sub.endpoint.parse() {
-                Ok(u) => u,
-                Err(e) => {
-                    tracing::error!("Invalid endpoint URL {}: {}", sub.endpoint, e);
-                    continue;
-                }
-            };
-
-            let builder = WebPushBuilder::new(endpoint, public_key, auth)
-                .with_vapid(&key_pair, "mailto:admin@example.com");
-
-            let request = match builder.build(payload_bytes.clone()) {
-                Ok(r) => r,
-                Err(e) => {
-                    tracing::error!("Failed to build push request: {:?}", e);
-                    continue;
-                }
-            };
-
-            // Convert http::Request to reqwest and send
-            let (parts, body) = request.into_parts();
-            let url = format!("{}", parts.uri);
-
-            let mut req_builder = client.post(&url);
-            for (name, value) in parts.headers.iter() {
-                if let Ok(v) = value.to_str() {
-                    req_builder = req_builder.header(name.as_str(), v);
-                }
-            }
-            req_builder = req_builder.body(body);
-
-            match req_builder.send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        tracing::info!("Push sent to {}", sub.endpoint);
-                    } else if response.status() == 410 {
-                        let _ = self.unsubscribe_push_for_user(&sub.endpoint, user_id).await;
-                        tracing::info!("Removed expired subscription: {}", sub.endpoint);
-                    } else {
-                        tracing::warn!(
-                            "Push failed with status {}: {}",
-                            response.status(),
-                            sub.endpoint
-                        );
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Push request failed: {}", e);
-                }
-            }
-        }
-
-        Ok(())
-    }
-
     // ================= Delivery Logic =================
 
     async fn deliver_notification(&self, notif: &Notification) -> AppResult<()> {
@@ -559,20 +815,38 @@ impl NotificationService {
                 .any(|p| p.channel == channel && p.category == category && p.enabled)
         };
 
+        // Quiet hours hold back email/push (and the live popup) for anything
+        // that isn't critical; the inbox row already exists.
+        let defer_until = match &self.quiet_hours {
+            Some(quiet_hours)
+                if !bypasses_quiet_hours(&notif.notification_type, &notif.category) =>
+            {
+                quiet_hours
+                    .deferral_until(&notif.user_id, notif.tenant_id.as_deref())
+                    .await
+            }
+            _ => None,
+        };
+
         // 1. In-App: Send WS Event
         if should_send("in_app", &notif.category) {
-            let event = crate::http::WsEvent::NotificationReceived {
-                user_id: notif.user_id.clone(),
-                tenant_id: notif.tenant_id.clone(),
-                id: notif.id.clone(),
-                title: notif.title.clone(),
-                message: notif.message.clone(),
-                notification_type: notif.notification_type.clone(),
-                category: notif.category.clone(),
-                action_url: notif.action_url.clone(),
-                created_at: notif.created_at.to_rfc3339(),
-            };
-            self.ws_hub.broadcast(event);
+            self.deliveries
+                .record(notif, "in_app", "delivered", None, None)
+                .await;
+            if defer_until.is_none() {
+                let event = crate::http::WsEvent::NotificationReceived {
+                    user_id: notif.user_id.clone(),
+                    tenant_id: notif.tenant_id.clone(),
+                    id: notif.id.clone(),
+                    title: notif.title.clone(),
+                    message: notif.message.clone(),
+                    notification_type: notif.notification_type.clone(),
+                    category: notif.category.clone(),
+                    action_url: notif.action_url.clone(),
+                    created_at: notif.created_at.to_rfc3339(),
+                };
+                self.ws_hub.broadcast(event);
+            }
 
             if let Ok(count) = self.get_unread_count(&notif.user_id).await {
                 self.ws_hub
@@ -583,37 +857,233 @@ impl NotificationService {
             }
         }
 
+        let mut deferred: Vec<&str> = Vec::new();
+
         // 2. Email
         if should_send("email", &notif.category) {
-            let user_email: Option<String> =
-                sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
-                    .bind(&notif.user_id)
-                    .fetch_optional(&self.pool)
-                    .await
-                    .unwrap_or(None);
+            if defer_until.is_some() {
+                deferred.push("email");
+                self.deliveries
+                    .record(notif, "email", "queued", None, None)
+                    .await;
+            } else {
+                self.send_email_channel(notif).await;
+            }
+        }
 
-            if let Some(email) = user_email {
+        // 3. Push
+        if should_send("push", &notif.category) {
+            if defer_until.is_some() {
+                deferred.push("push");
+                self.deliveries
+                    .record(notif, "push", "queued", None, None)
+                    .await;
+            } else {
+                self.send_push_channel(notif);
+            }
+        }
+
+        if let (Some(until), Some(quiet_hours)) = (defer_until, &self.quiet_hours) {
+            if !deferred.is_empty() {
+                quiet_hours
+                    .defer(&notif.id, &notif.user_id, &deferred, until)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn send_email_channel(&self, notif: &Notification) {
+        let user: Option<(String, String)> =
+            sqlx::query_as("SELECT email, name FROM users WHERE id = $1")
+                .bind(&notif.user_id)
+                .fetch_optional(&self.pool)
+                .await
+                .unwrap_or(None);
+
+        let Some((email, name)) = user else {
+            self.deliveries
+                .record(
+                    notif,
+                    "email",
+                    "failed",
+                    Some("User has no email address"),
+                    None,
+                )
+                .await;
+            return;
+        };
+        let html = match &self.email_templates {
+            Some(templates) => templates.render_notification(notif, &name).await,
+            None => None,
+        };
+        let (subject, body, body_html) = match html {
+            Some(email) => (email.subject, email.text, Some(email.html)),
+            None => {
                 let prefix = match notif.notification_type.as_str() {
                     "error" => "[Error] ",
                     "warning" => "[Alert] ",
                     "success" => "[Success] ",
                     _ => "",
                 };
-                let subject = format!("{}{}", prefix, notif.title);
+                (
+                    format!("{}{}", prefix, notif.title),
+                    notif.message.clone(),
+                    None,
+                )
+            }
+        };
 
-                // Use outbox to ensure reliable delivery with retries.
-                let _ = self
-                    .email_outbox
-                    .send_or_enqueue(notif.tenant_id.clone(), &email, &subject, &notif.message)
-                    .await;
+        // Use outbox to ensure reliable delivery with retries; the outbox
+        // reports the final result back to the delivery record.
+        match self
+            .email_outbox
+            .send_or_enqueue_tracked(notif.tenant_id.clone(), &email, &subject, &body, body_html)
+            .await
+        {
+            Ok(Some(outbox_id)) => {
+                self.deliveries
+                    .record(notif, "email", "queued", None, Some(&outbox_id))
+                    .await
+            }
+            Ok(None) => {
+                self.deliveries
+                    .record(notif, "email", "sent", None, None)
+                    .await
+            }
+            Err(e) => {
+                self.deliveries
+                    .record(notif, "email", "failed", Some(&e.to_string()), None)
+                    .await
             }
         }
+    }
 
-        // 3. Push
-        if should_send("push", &notif.category) {
-            let _ = self.send_push_notification(notif, &notif.user_id).await;
-        }
+    /// Push services can be slow to answer; don't hold up the caller.
+    fn send_push_channel(&self, notif: &Notification) {
+        let notif = notif.clone();
+        let deliveries = self.deliveries.clone();
+        let Some(web_push) = self.web_push.clone() else {
+            tokio::spawn(async move {
+                deliveries
+                    .record(
+                        &notif,
+                        "push",
+                        "failed",
+                        Some("Web Push is not configured"),
+                        None,
+                    )
+                    .await;
+            });
+            return;
+        };
+        tokio::spawn(async move {
+            match web_push.send_notification(&notif).await {
+                Ok(0) => {
+                    deliveries
+                        .record(
+                            &notif,
+                            "push",
+                            "failed",
+                            Some("No push service accepted it"),
+                            None,
+                        )
+                        .await
+                }
+                Ok(_) => {
+                    deliveries
+                        .record(&notif, "push", "delivered", None, None)
+                        .await
+                }
+                Err(e) => {
+                    tracing::warn!("Web Push delivery failed for {}: {}", notif.id, e);
+                    deliveries
+                        .record(&notif, "push", "failed", Some(&e.to_string()), None)
+                        .await
+                }
+            }
+        });
+    }
 
-        Ok(())
+    /// Send channels held back by quiet hours once their window has ended.
+    pub fn start_deferred_delivery(&self) {
+        let Some(quiet_hours) = self.quiet_hours.clone() else {
+            return;
+        };
+        let this = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                let due = match quiet_hours.take_due(DEFERRED_BATCH_SIZE).await {
+                    Ok(due) => due,
+                    Err(e) => {
+                        tracing::warn!("Deferred notification lookup failed: {}", e);
+                        continue;
+                    }
+                };
+                for (notification_id, channels) in due {
+                    let notif = sqlx::query_as::<_, Notification>(
+                        "SELECT * FROM notifications WHERE id = $1",
+                    )
+                    .bind(&notification_id)
+                    .fetch_optional(&this.pool)
+                    .await;
+                    // Deleted or archived meanwhile: nothing left to announce.
+                    let Ok(Some(notif)) = notif else { continue };
+                    if notif.archived_at.is_some() {
+                        continue;
+                    }
+                    for channel in &channels {
+                        match channel.as_str() {
+                            "email" => this.send_email_channel(&notif).await,
+                            "push" => this.send_push_channel(&notif),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn where_clause(filter: &NotificationFilter) -> String {
+        let mut qb: QueryBuilder<Db> = QueryBuilder::new("SELECT * FROM notifications");
+        push_notification_filters(&mut qb, "u1", filter);
+        qb.sql().to_string()
+    }
+
+    #[test]
+    fn default_filter_hides_archived() {
+        let sql = where_clause(&NotificationFilter::default());
+        assert!(sql.contains("user_id = "));
+        assert!(sql.contains("archived_at IS NULL"));
+        assert!(!sql.contains("category"));
+        assert!(!sql.contains("LIKE"));
+    }
+
+    #[test]
+    fn filters_add_conditions() {
+        let sql = where_clause(&NotificationFilter {
+            category: Some("billing".to_string()),
+            notification_type: Some(String::new()),
+            is_read: Some(false),
+            archived: Some(true),
+            from: Some(Utc::now()),
+            to: None,
+            search: Some("  invoice ".to_string()),
+        });
+        assert!(sql.contains("archived_at IS NOT NULL"));
+        assert!(sql.contains("category = "));
+        assert!(!sql.contains("notification_type"));
+        assert!(sql.contains("is_read = "));
+        assert!(sql.contains("created_at >= "));
+        assert!(!sql.contains("created_at <= "));
+        assert!(sql.contains("LOWER(title) LIKE "));
     }
 }
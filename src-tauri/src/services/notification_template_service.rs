@@ -0,0 +1,568 @@
+//! Notification texts with `{{variable}}` placeholders.
+//!
+//! Every notification the services send has a code and a built-in default title
+//! and body (`DEFAULT_TEMPLATES`). Tenants can override either from settings;
+//! rendering falls back to the default when there is no override or it can't be
+//! loaded, so a broken template table never stops a notification. Defaults are
+//! written in the tenant's `default_locale` when the catalog has them.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    NotificationTemplate, NotificationTemplateOverride, PreviewNotificationTemplateRequest,
+    RenderedNotification, UpdateNotificationTemplateRequest,
+};
+use crate::services::i18n;
+use crate::services::locale::tenant_locale;
+use crate::services::whatsapp_service::{render_template, template_variables};
+use chrono::Utc;
+use std::collections::HashMap;
+use tracing::warn;
+use uuid::Uuid;
+
+const MAX_TITLE_LEN: usize = 200;
+const MAX_BODY_LEN: usize = 4000;
+
+/// A built-in notification text.
+pub struct DefaultTemplate {
+    pub code: &'static str,
+    pub category: &'static str,
+    pub description: &'static str,
+    pub title: &'static str,
+    pub body: &'static str,
+    pub variables: &'static [&'static str],
+}
+
+const INVOICE_DUE_BODY: &str =
+    "Invoice {{invoice_number}} is due on {{due_date}}. Please complete payment to keep service active.";
+
+pub const DEFAULT_TEMPLATES: &[DefaultTemplate] = &[
+    // Network
+    DefaultTemplate {
+        code: "incident_assigned",
+        category: "network",
+        description: "In-app notice to the member an incident is assigned to",
+        title: "Incident assigned: {{incident_title}}",
+        body: "You were assigned to incident on {{target}}. Current status: {{status}}.",
+        variables: &["incident_title", "target", "status"],
+    },
+    DefaultTemplate {
+        code: "incident_assigned_email",
+        category: "network",
+        description: "Email to the assignee (when incident assignment emails are enabled)",
+        title: "Incident Assigned: {{incident_title}}",
+        body: "You were assigned to incident:\n{{incident_message}}\n\nTarget: {{target}}\nStatus: {{status}}\nOpen: {{action_url}}",
+        variables: &[
+            "incident_title",
+            "incident_message",
+            "target",
+            "status",
+            "action_url",
+        ],
+    },
+    DefaultTemplate {
+        code: "incident_escalated",
+        category: "network",
+        description: "An incident went unacknowledged past the escalation threshold",
+        title: "Incident escalated",
+        body: "{{incident_title}} has exceeded {{threshold_minutes}} minutes without acknowledgement.",
+        variables: &["incident_title", "threshold_minutes"],
+    },
+    DefaultTemplate {
+        code: "router_down",
+        category: "network",
+        description: "A router stopped answering",
+        title: "Router down",
+        body: "{{router_name}} became unreachable: {{error}}",
+        variables: &["router_name", "error"],
+    },
+    DefaultTemplate {
+        code: "router_online",
+        category: "network",
+        description: "A router answered again after a short outage",
+        title: "Router online",
+        body: "{{router_name}} is back online.",
+        variables: &["router_name"],
+    },
+    DefaultTemplate {
+        code: "router_recovered",
+        category: "network",
+        description: "A router answered again after a long outage",
+        title: "Router recovered",
+        body: "{{router_name}} recovered after {{offline_seconds}}s offline.",
+        variables: &["router_name", "offline_seconds"],
+    },
+    DefaultTemplate {
+        code: "router_high_cpu",
+        category: "network",
+        description: "Router CPU load crossed the alert threshold",
+        title: "High CPU",
+        body: "{{router_name}} CPU is {{cpu}}%.",
+        variables: &["router_name", "cpu"],
+    },
+    DefaultTemplate {
+        code: "router_high_latency",
+        category: "network",
+        description: "Router latency crossed the alert threshold",
+        title: "High latency",
+        body: "{{router_name}} latency is {{latency_ms}}ms.",
+        variables: &["router_name", "latency_ms"],
+    },
+    // Billing
+    DefaultTemplate {
+        code: "invoice_created",
+        category: "billing",
+        description: "A new invoice was issued for a customer subscription",
+        title: "Invoice created",
+        body: "New invoice {{invoice_number}} is ready ({{amount}}). Please complete payment to activate/keep service.",
+        variables: &["invoice_number", "amount"],
+    },
+    DefaultTemplate {
+        code: "invoice_due_soon",
+        category: "billing",
+        description: "Reminder before the due date",
+        title: "Invoice due in {{days}} day(s)",
+        body: INVOICE_DUE_BODY,
+        variables: &["invoice_number", "due_date", "days"],
+    },
+    DefaultTemplate {
+        code: "invoice_due_today",
+        category: "billing",
+        description: "Reminder on the due date",
+        title: "Invoice due today",
+        body: INVOICE_DUE_BODY,
+        variables: &["invoice_number", "due_date"],
+    },
+    DefaultTemplate {
+        code: "invoice_overdue",
+        category: "billing",
+        description: "Reminder after the due date",
+        title: "Invoice overdue by {{days}} day(s)",
+        body: INVOICE_DUE_BODY,
+        variables: &["invoice_number", "due_date", "days"],
+    },
+    DefaultTemplate {
+        code: "payment_successful",
+        category: "billing",
+        description: "An invoice was paid",
+        title: "Payment Successful",
+        body: "Invoice {{invoice_number}} has been successfully paid. Thank you!",
+        variables: &["invoice_number"],
+    },
+    DefaultTemplate {
+        code: "payment_failed",
+        category: "billing",
+        description: "An online payment failed",
+        title: "Payment Failed",
+        body: "Payment for invoice {{invoice_number}} failed. Please check your payment method.",
+        variables: &["invoice_number"],
+    },
+    DefaultTemplate {
+        code: "payment_proof_rejected",
+        category: "billing",
+        description: "A manual payment proof was rejected",
+        title: "Payment Failed",
+        body: "Payment proof for invoice {{invoice_number}} was rejected. Please review the reason and upload a new proof.",
+        variables: &["invoice_number"],
+    },
+    DefaultTemplate {
+        code: "payment_proof_uploaded",
+        category: "billing",
+        description: "A customer uploaded a payment proof (to owners and admins)",
+        title: "New Payment Proof Uploaded",
+        body: "A payment proof has been uploaded for customer invoice {{invoice_number}}",
+        variables: &["invoice_number"],
+    },
+    DefaultTemplate {
+        code: "customer_payment_received",
+        category: "billing",
+        description: "A customer invoice was paid (to owners and admins)",
+        title: "Customer Payment Received",
+        body: "Customer invoice {{invoice_number}} has been paid. Amount: {{amount}}",
+        variables: &["invoice_number", "amount"],
+    },
+    DefaultTemplate {
+        code: "subscription_suspended",
+        category: "billing",
+        description: "A subscription was suspended for an overdue invoice",
+        title: "Subscription suspended",
+        body: "Your subscription has been suspended (invoice {{invoice_number}} overdue {{overdue_days}} day(s)).",
+        variables: &["invoice_number", "overdue_days"],
+    },
+    DefaultTemplate {
+        code: "subscription_resumed",
+        category: "billing",
+        description: "A suspended subscription was resumed after payment",
+        title: "Subscription resumed",
+        body: "Payment received for invoice {{invoice_number}}. Your subscription is active again.",
+        variables: &["invoice_number"],
+    },
+    // Operations
+    DefaultTemplate {
+        code: "installation_pending",
+        category: "operations",
+        description: "A paid order is waiting for installation (to the customer)",
+        title: "Order Queued for Installation",
+        body: "Payment for invoice {{invoice_number}} is confirmed. Your order is now Pending Installation and waiting assignment/schedule from admin or technician.",
+        variables: &["invoice_number"],
+    },
+    DefaultTemplate {
+        code: "installation_requested",
+        category: "operations",
+        description: "A paid order created an installation work order (to installation staff)",
+        title: "Installation Work Order: New Request",
+        body: "Invoice {{invoice_number}} is paid. A new installation work order is ready for assignment and scheduling (WO {{work_order_id}}).",
+        variables: &["invoice_number", "work_order_id"],
+    },
+];
+
+/// Values used by previews, one per variable any default template declares.
+const SAMPLE_VALUES: &[(&str, &str)] = &[
+    ("incident_title", "Router offline: Core-01"),
+    (
+        "incident_message",
+        "Core-01 stopped answering API requests.",
+    ),
+    ("target", "Core-01 (ether1)"),
+    ("status", "open"),
+    ("action_url", "/admin/network/incidents?incident=sample"),
+    ("threshold_minutes", "30"),
+    ("router_name", "Core-01"),
+    ("error", "connection timed out"),
+    ("offline_seconds", "420"),
+    ("cpu", "93"),
+    ("latency_ms", "250"),
+    ("invoice_number", "INV-20260301-0001"),
+    ("amount", "Rp 150.000"),
+    ("due_date", "2026-03-10 00:00 UTC"),
+    ("days", "3"),
+    ("overdue_days", "5"),
+    ("work_order_id", "wo-sample"),
+];
+
+pub fn default_template(code: &str) -> Option<&'static DefaultTemplate> {
+    DEFAULT_TEMPLATES.iter().find(|t| t.code == code)
+}
+
+fn sample_vars() -> HashMap<&'static str, String> {
+    SAMPLE_VALUES
+        .iter()
+        .map(|(k, v)| (*k, v.to_string()))
+        .collect()
+}
+
+/// Placeholders in `title`/`body` that the event doesn't provide.
+fn unknown_variables(template: &DefaultTemplate, title: &str, body: &str) -> Vec<String> {
+    let mut unknown: Vec<String> = Vec::new();
+    for name in template_variables(title)
+        .into_iter()
+        .chain(template_variables(body))
+    {
+        if !template.variables.contains(&name.as_str()) && !unknown.contains(&name) {
+            unknown.push(name);
+        }
+    }
+    unknown
+}
+
+fn render(title: &str, body: &str, vars: &HashMap<&str, String>) -> RenderedNotification {
+    RenderedNotification {
+        title: render_template(title, vars),
+        body: render_template(body, vars),
+    }
+}
+
+/// The built-in title and body in `locale`.
+fn localized_default(default: &DefaultTemplate, locale: Option<&str>) -> (String, String) {
+    (
+        i18n::translate(locale, default.title),
+        i18n::translate(locale, default.body),
+    )
+}
+
+fn to_view(
+    default: &DefaultTemplate,
+    custom: Option<&NotificationTemplateOverride>,
+) -> NotificationTemplate {
+    NotificationTemplate {
+        code: default.code.to_string(),
+        category: default.category.to_string(),
+        description: default.description.to_string(),
+        title: custom
+            .map_or(default.title, |c| c.title.as_str())
+            .to_string(),
+        body: custom.map_or(default.body, |c| c.body.as_str()).to_string(),
+        default_title: default.title.to_string(),
+        default_body: default.body.to_string(),
+        variables: default.variables.iter().map(|v| v.to_string()).collect(),
+        is_customized: custom.is_some(),
+        updated_at: custom.map(|c| c.updated_at),
+    }
+}
+
+#[derive(Clone)]
+pub struct NotificationTemplateService {
+    pool: DbPool,
+}
+
+impl NotificationTemplateService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn known(code: &str) -> AppResult<&'static DefaultTemplate> {
+        default_template(code)
+            .ok_or_else(|| AppError::NotFound(format!("Unknown notification template: {}", code)))
+    }
+
+    async fn get_override(
+        &self,
+        tenant_id: &str,
+        code: &str,
+    ) -> AppResult<Option<NotificationTemplateOverride>> {
+        let row = sqlx::query_as::<_, NotificationTemplateOverride>(
+            "SELECT * FROM notification_templates WHERE tenant_id = $1 AND code = $2",
+        )
+        .bind(tenant_id)
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    pub async fn list(&self, tenant_id: &str) -> AppResult<Vec<NotificationTemplate>> {
+        let overrides = sqlx::query_as::<_, NotificationTemplateOverride>(
+            "SELECT * FROM notification_templates WHERE tenant_id = $1",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(DEFAULT_TEMPLATES
+            .iter()
+            .map(|d| to_view(d, overrides.iter().find(|o| o.code == d.code)))
+            .collect())
+    }
+
+    pub async fn get(&self, tenant_id: &str, code: &str) -> AppResult<NotificationTemplate> {
+        let default = Self::known(code)?;
+        let custom = self.get_override(tenant_id, code).await?;
+        Ok(to_view(default, custom.as_ref()))
+    }
+
+    pub async fn update(
+        &self,
+        tenant_id: &str,
+        code: &str,
+        req: UpdateNotificationTemplateRequest,
+        actor_id: Option<&str>,
+    ) -> AppResult<NotificationTemplate> {
+        let default = Self::known(code)?;
+        let title = req.title.trim();
+        let body = req.body.trim();
+        if title.is_empty() || body.is_empty() {
+            return Err(AppError::Validation(
+                "Template title and body are required".to_string(),
+            ));
+        }
+        if title.chars().count() > MAX_TITLE_LEN || body.chars().count() > MAX_BODY_LEN {
+            return Err(AppError::Validation(format!(
+                "Template title is limited to {} and body to {} characters",
+                MAX_TITLE_LEN, MAX_BODY_LEN
+            )));
+        }
+        let unknown = unknown_variables(default, title, body);
+        if !unknown.is_empty() {
+            return Err(AppError::Validation(format!(
+                "Unknown variable(s): {}. Available: {}",
+                unknown.join(", "),
+                default.variables.join(", ")
+            )));
+        }
+
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO notification_templates
+                (id, tenant_id, code, title, body, updated_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+            ON CONFLICT (tenant_id, code) DO UPDATE SET
+                title = EXCLUDED.title,
+                body = EXCLUDED.body,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(code)
+        .bind(title)
+        .bind(body)
+        .bind(actor_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        self.get(tenant_id, code).await
+    }
+
+    /// Drop the tenant's override so the default applies again.
+    pub async fn reset(&self, tenant_id: &str, code: &str) -> AppResult<NotificationTemplate> {
+        let default = Self::known(code)?;
+        sqlx::query("DELETE FROM notification_templates WHERE tenant_id = $1 AND code = $2")
+            .bind(tenant_id)
+            .bind(code)
+            .execute(&self.pool)
+            .await?;
+        Ok(to_view(default, None))
+    }
+
+    /// Render the current (or the given unsaved) text with sample values.
+    pub async fn preview(
+        &self,
+        tenant_id: &str,
+        code: &str,
+        req: PreviewNotificationTemplateRequest,
+    ) -> AppResult<RenderedNotification> {
+        let current = self.get(tenant_id, code).await?;
+        let (title, body) = if current.is_customized {
+            (current.title, current.body)
+        } else {
+            let locale = tenant_locale(&self.pool, Some(tenant_id)).await;
+            localized_default(Self::known(code)?, locale.as_deref())
+        };
+        let title = req.title.unwrap_or(title);
+        let body = req.body.unwrap_or(body);
+        Ok(render(&title, &body, &sample_vars()))
+    }
+
+    /// Text for a notification about to be sent. Never fails: unknown codes and
+    /// lookup errors fall back to the default (or the bare code).
+    pub async fn render(
+        &self,
+        tenant_id: Option<&str>,
+        code: &str,
+        vars: &HashMap<&str, String>,
+    ) -> RenderedNotification {
+        let Some(default) = default_template(code) else {
+            warn!("Unknown notification template code: {}", code);
+            return RenderedNotification {
+                title: code.to_string(),
+                body: String::new(),
+            };
+        };
+
+        let custom = match tenant_id {
+            Some(tenant_id) => match self.get_override(tenant_id, code).await {
+                Ok(custom) => custom,
+                Err(e) => {
+                    warn!(
+                        "Failed to load notification template {} for tenant {}: {}",
+                        code, tenant_id, e
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+
+        match custom {
+            Some(c) => render(&c.title, &c.body, vars),
+            None => {
+                let locale = tenant_locale(&self.pool, tenant_id).await;
+                let (title, body) = localized_default(default, locale.as_deref());
+                render(&title, &body, vars)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_codes_are_unique() {
+        for (i, t) in DEFAULT_TEMPLATES.iter().enumerate() {
+            assert!(
+                DEFAULT_TEMPLATES[i + 1..].iter().all(|o| o.code != t.code),
+                "duplicate template code {}",
+                t.code
+            );
+        }
+    }
+
+    #[test]
+    fn defaults_only_use_declared_variables_with_samples() {
+        let samples = sample_vars();
+        for t in DEFAULT_TEMPLATES {
+            assert!(
+                unknown_variables(t, t.title, t.body).is_empty(),
+                "{} uses an undeclared variable",
+                t.code
+            );
+            for v in t.variables {
+                assert!(samples.contains_key(v), "{} has no sample value", v);
+            }
+        }
+    }
+
+    #[test]
+    fn flags_unknown_variables_once() {
+        let t = default_template("router_down").unwrap();
+        assert_eq!(
+            unknown_variables(t, "{{router}} down", "{{router}}: {{error}} {{ uptime }}"),
+            vec!["router".to_string(), "uptime".to_string()]
+        );
+        assert!(unknown_variables(t, "{{ router_name }}", "{{error}}").is_empty());
+    }
+
+    #[test]
+    fn renders_default_and_override_views() {
+        let vars = HashMap::from([("router_name", "Core-01".to_string())]);
+        let t = default_template("router_online").unwrap();
+        assert_eq!(
+            render(t.title, t.body, &vars),
+            RenderedNotification {
+                title: "Router online".to_string(),
+                body: "Core-01 is back online.".to_string(),
+            }
+        );
+
+        let custom = NotificationTemplateOverride {
+            id: "1".to_string(),
+            tenant_id: "t1".to_string(),
+            code: t.code.to_string(),
+            title: "{{router_name}} kembali online".to_string(),
+            body: "Router {{router_name}} sudah terhubung.".to_string(),
+            updated_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let view = to_view(t, Some(&custom));
+        assert!(view.is_customized);
+        assert_eq!(view.default_title, "Router online");
+        assert_eq!(
+            render(&view.title, &view.body, &vars).title,
+            "Core-01 kembali online"
+        );
+        assert!(!to_view(t, None).is_customized);
+    }
+
+    #[test]
+    fn defaults_are_translated_to_indonesian_with_the_same_variables() {
+        for t in DEFAULT_TEMPLATES {
+            let (title, body) = localized_default(t, Some("id-ID"));
+            for (english, translated) in [(t.title, &title), (t.body, &body)] {
+                assert_ne!(english, translated, "{} has no Indonesian text", t.code);
+                let mut want = template_variables(english);
+                let mut got = template_variables(translated);
+                want.sort();
+                got.sort();
+                assert_eq!(want, got, "{} translation changes the variables", t.code);
+            }
+        }
+        let t = default_template("router_online").unwrap();
+        assert_eq!(localized_default(t, Some("en")).0, "Router online");
+    }
+}
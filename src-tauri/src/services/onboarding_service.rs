@@ -0,0 +1,290 @@
+//! Onboarding checklist for new ISP tenants.
+//!
+//! The steps a tenant goes through before it can bill its first customer, in
+//! order. Steps are detected from the tenant's own data whenever progress is
+//! read, and a step found done is recorded and stays done: deleting the only
+//! router later does not send the tenant back to step two. A step the tenant
+//! has no use for (say, it takes no online payments) can be skipped, and
+//! resumed again while it is not done.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{OnboardingProgress, OnboardingStep};
+use crate::services::{AuditService, AuthService};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Checklist steps, in the order they are meant to be done.
+pub const ONBOARDING_STEPS: &[&str] =
+    &["smtp", "router", "packages", "payment_gateway", "customers"];
+
+/// Tenant settings that make outgoing email work; any one will do.
+const EMAIL_SETTING_KEYS: &[&str] = &["email_smtp_host", "email_api_key", "email_webhook_url"];
+
+/// Tenant settings holding a payment gateway's credentials.
+const GATEWAY_SETTING_KEYS: &[&str] = &[
+    "payment_midtrans_server_key",
+    "payment_xendit_secret_key",
+    "payment_stripe_secret_key",
+    "payment_paypal_client_secret",
+];
+
+fn validate_step(step: &str) -> AppResult<&'static str> {
+    ONBOARDING_STEPS
+        .iter()
+        .copied()
+        .find(|s| *s == step)
+        .ok_or_else(|| AppError::Validation(format!("Unknown onboarding step '{}'", step)))
+}
+
+/// Progress from the recorded `(status, updated_at)` of each step.
+fn build_progress(recorded: &HashMap<String, (String, DateTime<Utc>)>) -> OnboardingProgress {
+    let steps: Vec<OnboardingStep> = ONBOARDING_STEPS
+        .iter()
+        .map(|step| match recorded.get(*step) {
+            Some((status, at)) => OnboardingStep {
+                step: step.to_string(),
+                status: status.clone(),
+                completed_at: Some(*at),
+            },
+            None => OnboardingStep {
+                step: step.to_string(),
+                status: "pending".to_string(),
+                completed_at: None,
+            },
+        })
+        .collect();
+
+    let total = steps.len() as u32;
+    let completed = steps.iter().filter(|s| s.status != "pending").count() as u32;
+    OnboardingProgress {
+        next_step: steps
+            .iter()
+            .find(|s| s.status == "pending")
+            .map(|s| s.step.clone()),
+        steps,
+        completed,
+        total,
+        percent: (completed * 100).checked_div(total).unwrap_or(100),
+    }
+}
+
+#[derive(Clone)]
+pub struct OnboardingService {
+    pool: DbPool,
+    auth_service: AuthService,
+    audit_service: AuditService,
+}
+
+impl OnboardingService {
+    pub fn new(pool: DbPool, auth_service: AuthService, audit_service: AuditService) -> Self {
+        Self {
+            pool,
+            auth_service,
+            audit_service,
+        }
+    }
+
+    /// The checklist, with steps done since the last look marked done.
+    pub async fn progress(&self, actor_id: &str, tenant_id: &str) -> AppResult<OnboardingProgress> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "settings", "read")
+            .await?;
+        self.detect(tenant_id).await?;
+        self.load(tenant_id).await
+    }
+
+    /// Leaves `step` out of the way. A step already done stays done.
+    pub async fn skip_step(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        step: &str,
+        ip_address: Option<&str>,
+    ) -> AppResult<OnboardingProgress> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "settings", "update")
+            .await?;
+        let step = validate_step(step)?;
+
+        let now = Utc::now();
+        let skipped = sqlx::query(
+            r#"
+            INSERT INTO tenant_onboarding_steps
+              (tenant_id, step, status, updated_by, created_at, updated_at)
+            VALUES ($1, $2, 'skipped', $3, $4, $4)
+            ON CONFLICT (tenant_id, step) DO NOTHING
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(step)
+        .bind(actor_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if skipped > 0 {
+            self.audit_service
+                .log(
+                    Some(actor_id),
+                    Some(tenant_id),
+                    "ONBOARDING_STEP_SKIP",
+                    "tenant_onboarding_steps",
+                    Some(step),
+                    Some(&format!("Skipped onboarding step {}", step)),
+                    ip_address,
+                )
+                .await;
+        }
+        self.detect(tenant_id).await?;
+        self.load(tenant_id).await
+    }
+
+    /// Puts a skipped step back on the checklist.
+    pub async fn resume_step(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        step: &str,
+        ip_address: Option<&str>,
+    ) -> AppResult<OnboardingProgress> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "settings", "update")
+            .await?;
+        let step = validate_step(step)?;
+
+        let resumed = sqlx::query(
+            "DELETE FROM tenant_onboarding_steps WHERE tenant_id = $1 AND step = $2 AND status = 'skipped'",
+        )
+        .bind(tenant_id)
+        .bind(step)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if resumed > 0 {
+            self.audit_service
+                .log(
+                    Some(actor_id),
+                    Some(tenant_id),
+                    "ONBOARDING_STEP_RESUME",
+                    "tenant_onboarding_steps",
+                    Some(step),
+                    Some(&format!("Resumed onboarding step {}", step)),
+                    ip_address,
+                )
+                .await;
+        }
+        self.detect(tenant_id).await?;
+        self.load(tenant_id).await
+    }
+
+    /// Records the steps the tenant's data shows are done. Skipped steps
+    /// that got done after all become done.
+    async fn detect(&self, tenant_id: &str) -> AppResult<()> {
+        let (smtp, router, packages, gateway, customers): (bool, bool, bool, bool, bool) =
+            sqlx::query_as(
+                r#"
+                SELECT
+                  EXISTS (SELECT 1 FROM settings
+                          WHERE tenant_id = $1 AND key = ANY($2) AND btrim(value) <> ''),
+                  EXISTS (SELECT 1 FROM mikrotik_routers WHERE tenant_id = $1 AND deleted_at IS NULL),
+                  EXISTS (SELECT 1 FROM isp_packages WHERE tenant_id = $1 AND deleted_at IS NULL),
+                  EXISTS (SELECT 1 FROM settings
+                          WHERE tenant_id = $1 AND key = ANY($3) AND btrim(value) <> ''),
+                  EXISTS (SELECT 1 FROM customers WHERE tenant_id = $1 AND deleted_at IS NULL)
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(EMAIL_SETTING_KEYS)
+            .bind(GATEWAY_SETTING_KEYS)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let done: Vec<&str> = ONBOARDING_STEPS
+            .iter()
+            .copied()
+            .zip([smtp, router, packages, gateway, customers])
+            .filter(|(_, found)| *found)
+            .map(|(step, _)| step)
+            .collect();
+        if done.is_empty() {
+            return Ok(());
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_onboarding_steps
+              (tenant_id, step, status, updated_by, created_at, updated_at)
+            SELECT $1, step, 'done', NULL, $3, $3 FROM UNNEST($2::text[]) AS step
+            ON CONFLICT (tenant_id, step) DO UPDATE
+              SET status = 'done', updated_by = NULL, updated_at = excluded.updated_at
+              WHERE tenant_onboarding_steps.status <> 'done'
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&done)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn load(&self, tenant_id: &str) -> AppResult<OnboardingProgress> {
+        let rows: Vec<(String, String, DateTime<Utc>)> = sqlx::query_as(
+            "SELECT step, status, updated_at FROM tenant_onboarding_steps WHERE tenant_id = $1",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let recorded = rows
+            .into_iter()
+            .map(|(step, status, at)| (step, (status, at)))
+            .collect();
+        Ok(build_progress(&recorded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_points_at_first_pending_step() {
+        let now = Utc::now();
+        let recorded = HashMap::from([
+            ("smtp".to_string(), ("done".to_string(), now)),
+            ("packages".to_string(), ("done".to_string(), now)),
+            ("payment_gateway".to_string(), ("skipped".to_string(), now)),
+        ]);
+        let progress = build_progress(&recorded);
+
+        assert_eq!(progress.total, 5);
+        assert_eq!(progress.completed, 3);
+        assert_eq!(progress.percent, 60);
+        assert_eq!(progress.next_step.as_deref(), Some("router"));
+        assert_eq!(progress.steps[1].status, "pending");
+        assert!(progress.steps[1].completed_at.is_none());
+        assert_eq!(progress.steps[3].status, "skipped");
+    }
+
+    #[test]
+    fn test_progress_finished() {
+        let now = Utc::now();
+        let recorded = ONBOARDING_STEPS
+            .iter()
+            .map(|s| (s.to_string(), ("done".to_string(), now)))
+            .collect();
+        let progress = build_progress(&recorded);
+        assert_eq!(progress.percent, 100);
+        assert!(progress.next_step.is_none());
+    }
+
+    #[test]
+    fn test_validate_step() {
+        assert_eq!(validate_step("router").unwrap(), "router");
+        assert!(validate_step("Router").is_err());
+        assert!(validate_step("").is_err());
+    }
+}
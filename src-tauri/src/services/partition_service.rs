@@ -0,0 +1,132 @@
+//! Partition maintenance for time-series tables
+//!
+//! On Postgres, `audit_logs`, `mikrotik_logs`, `usage_records` and the MikroTik
+//! metric tables are partitioned by month (see `db::partitions`). This job keeps
+//! partitions created a couple of months ahead and expires log, audit and usage
+//! rows past their retention window. Audit and usage retention are off unless
+//! configured: billing and reports read usage, and the audit hash chain needs
+//! its oldest entries. Metrics expire through the same
+//! `partitions::drop_expired`, run by the MikroTik poller's cleanup job with
+//! `mikrotik_metrics_retention_days`.
+//!
+//! SQLite builds have no partitioned tables and never schedule it.
+
+use crate::db::DbPool;
+use crate::services::{JobQueue, SettingsService};
+
+#[cfg(feature = "postgres")]
+use crate::db::partitions;
+#[cfg(feature = "postgres")]
+use crate::error::{AppError, AppResult};
+#[cfg(feature = "postgres")]
+use crate::models::BackgroundJob;
+#[cfg(feature = "postgres")]
+use crate::services::JobHandler;
+#[cfg(feature = "postgres")]
+use async_trait::async_trait;
+#[cfg(feature = "postgres")]
+use chrono::{Duration, Utc};
+#[cfg(feature = "postgres")]
+use std::sync::Arc;
+#[cfg(feature = "postgres")]
+use tracing::info;
+
+/// How many months past the current one to keep pre-created.
+#[cfg(feature = "postgres")]
+const MONTHS_AHEAD: i32 = 2;
+
+/// Tables with a retention setting: (table, setting key, default days; 0
+/// keeps everything).
+#[cfg(feature = "postgres")]
+const RETAINED_TABLES: &[(&str, &str, i64)] = &[
+    ("mikrotik_logs", "mikrotik_logs_retention_days", 30),
+    ("audit_logs", "audit_logs_retention_days", 0),
+    ("usage_records", "usage_records_retention_days", 0),
+];
+
+#[derive(Clone)]
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+pub struct PartitionMaintenanceScheduler {
+    pool: DbPool,
+    settings_service: SettingsService,
+}
+
+impl PartitionMaintenanceScheduler {
+    pub fn new(pool: DbPool, settings_service: SettingsService) -> Self {
+        Self {
+            pool,
+            settings_service,
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub async fn schedule(self, _queue: &JobQueue) {}
+
+    /// Runs partition maintenance every six hours on the job queue. A new
+    /// job runs right away so next month's partitions exist right after boot.
+    #[cfg(feature = "postgres")]
+    pub async fn schedule(self, queue: &JobQueue) {
+        queue
+            .register_recurring(
+                "partitions.maintain",
+                std::time::Duration::from_secs(6 * 3600),
+                Arc::new(self),
+            )
+            .await;
+    }
+
+    #[cfg(feature = "postgres")]
+    async fn run_once(&self) -> AppResult<()> {
+        let mut errors = Vec::new();
+
+        match partitions::ensure_upcoming(&self.pool, MONTHS_AHEAD).await {
+            Ok(created) if created > 0 => info!("Created {} upcoming partition(s)", created),
+            Ok(_) => {}
+            Err(e) => errors.push(format!("Failed to create upcoming partitions: {}", e)),
+        }
+
+        for (table, key, default_days) in RETAINED_TABLES {
+            let days = self
+                .settings_service
+                .get_value(None, key)
+                .await
+                .ok()
+                .flatten()
+                .and_then(|v| v.trim().parse::<i64>().ok())
+                .unwrap_or(*default_days)
+                .clamp(0, 3650);
+
+            // 0 keeps everything.
+            if days == 0 {
+                continue;
+            }
+
+            let cutoff = Utc::now() - Duration::days(days);
+            match partitions::drop_expired(&self.pool, table, cutoff).await {
+                Ok(dropped) if dropped > 0 => info!(
+                    "Dropped {} expired {} partition(s) (retention={}d)",
+                    dropped, table, days
+                ),
+                Ok(_) => {}
+                Err(e) => errors.push(format!(
+                    "Failed to drop expired {} partitions: {}",
+                    table, e
+                )),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Internal(errors.join("; ")))
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl JobHandler for PartitionMaintenanceScheduler {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        self.run_once().await
+    }
+}
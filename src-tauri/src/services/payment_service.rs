@@ -1,10 +1,10 @@
 //! Payment Service - Manages invoices and bank accounts
 
-use crate::db::DbPool;
+use crate::db::{DbPool, QueryRouter};
 use crate::error::{AppError, AppResult};
 use crate::models::{
     BankAccount, BillingCollectionLogView, CreateBankAccountRequest, Invoice,
-    InvoiceReminderLogView,
+    InvoiceReminderLogView, WhatsappMessage,
 };
 use base64::{engine::general_purpose, Engine as _};
 use chrono::{Datelike, Duration, Months, Utc};
@@ -12,12 +12,26 @@ use reqwest::Client;
 use serde::Serialize;
 use serde_json::json;
 use sha2::{Digest, Sha512};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
-use crate::services::{NotificationService, PppoeService};
+use crate::services::event_outbox_service::{self, DomainEvent, EventOutboxService};
+use crate::services::leader_lock::LeaderLock;
+use crate::services::money::{self, Rounding};
+use crate::services::notification_service::TenantAlert;
+use crate::services::telegram_service::TelegramAlert;
+use crate::services::whatsapp_service::WhatsappEvent;
+use crate::services::{NotificationService, PlanService, PppoeService};
+
+#[cfg(feature = "postgres")]
+type Db = sqlx::Postgres;
+
+#[cfg(feature = "sqlite")]
+type Db = sqlx::Sqlite;
 
 const CUSTOMER_PACKAGE_INVOICE_PREFIX: &str = "pkgsub:";
+/// Self-serve upgrade invoices: "upgrade:{plan_id}:{billing_cycle}".
+const PLAN_UPGRADE_INVOICE_PREFIX: &str = "upgrade:";
 const BILLING_AUTO_SUSPEND_ENABLED_KEY: &str = "billing_auto_suspend_enabled";
 const BILLING_AUTO_SUSPEND_GRACE_DAYS_KEY: &str = "billing_auto_suspend_grace_days";
 const BILLING_AUTO_RESUME_ON_PAYMENT_KEY: &str = "billing_auto_resume_on_payment";
@@ -30,6 +44,48 @@ fn is_customer_package_invoice_external_id(external_id: Option<&str>) -> bool {
         .unwrap_or(false)
 }
 
+fn parse_plan_upgrade_external_id(external_id: Option<&str>) -> Option<(&str, &str)> {
+    let rest = external_id?.strip_prefix(PLAN_UPGRADE_INVOICE_PREFIX)?;
+    let (plan_id, cycle) = rest.split_once(':')?;
+    if plan_id.is_empty() || !matches!(cycle, "monthly" | "yearly") {
+        return None;
+    }
+    Some((plan_id, cycle))
+}
+
+/// Unused share of what was paid for the current period, credited against
+/// an upgrade.
+fn prorated_credit(paid_price: f64, remaining_days: i64, period_days: i64) -> f64 {
+    if paid_price <= 0.0 || remaining_days <= 0 || period_days <= 0 {
+        return 0.0;
+    }
+    paid_price * remaining_days.min(period_days) as f64 / period_days as f64
+}
+
+/// "pkgsub:{subscription_id}:{period_key}" -> (subscription_id, period_key).
+fn parse_customer_package_external_id(external_id: Option<&str>) -> Option<(&str, &str)> {
+    let rest = external_id?.strip_prefix(CUSTOMER_PACKAGE_INVOICE_PREFIX)?;
+    let (subscription_id, period_key) = rest.rsplit_once(':')?;
+    if subscription_id.is_empty() || period_key.is_empty() {
+        return None;
+    }
+    Some((subscription_id, period_key))
+}
+
+/// What a subscription pays for a billing period. A subscription on the
+/// package's current list price pays the list price that was in effect when
+/// the period started; custom prices (discounts, zone offers) are kept.
+fn subscription_period_price(
+    price: f64,
+    list_price_now: Option<f64>,
+    list_price_then: Option<f64>,
+) -> f64 {
+    match (list_price_now, list_price_then) {
+        (Some(now), Some(then)) if (price - now).abs() < 0.005 && then > 0.0 => then,
+        _ => price,
+    }
+}
+
 fn is_manual_payment_invoice(invoice: &Invoice) -> bool {
     let method = invoice
         .payment_method
@@ -130,6 +186,33 @@ pub struct BillingCollectionSettings {
     pub reminder_schedule: Vec<String>,
 }
 
+/// Prorated price of moving the tenant to another plan. Amounts are in the
+/// base currency; the invoice converts them like any other plan invoice.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanUpgradeQuote {
+    pub current_plan_id: Option<String>,
+    pub current_plan_name: Option<String>,
+    pub target_plan_id: String,
+    pub target_plan_name: String,
+    pub billing_cycle: String,
+    pub currency_code: String,
+    pub plan_price: f64,
+    pub credit: f64,
+    pub amount_due: f64,
+    pub remaining_days: i64,
+    pub period_days: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanUpgradeCheckout {
+    pub quote: PlanUpgradeQuote,
+    pub invoice: Invoice,
+    /// "midtrans" when the gateway is enabled, otherwise "manual" (bank
+    /// transfer with proof upload).
+    pub gateway: String,
+    pub snap_token: Option<String>,
+}
+
 impl Default for BillingCollectionSettings {
     fn default() -> Self {
         Self {
@@ -173,6 +256,7 @@ struct AssignmentCandidateNodeRow {
 #[derive(Clone)]
 pub struct PaymentService {
     pool: DbPool,
+    router: QueryRouter,
     http_client: Client,
     notification_service: NotificationService,
     pppoe_service: PppoeService,
@@ -184,26 +268,37 @@ impl PaymentService {
         notification_service: NotificationService,
         pppoe_service: PppoeService,
     ) -> Self {
+        let router = QueryRouter::new(pool.clone(), None);
         Self {
             pool,
+            router,
             http_client: Client::new(),
             notification_service,
             pppoe_service,
         }
     }
 
+    /// Billing collection and reminder log reports read from `router.reader()`.
+    pub fn with_query_router(mut self, router: QueryRouter) -> Self {
+        self.router = router;
+        self
+    }
+
     pub fn start_customer_invoice_scheduler(&self) {
         let svc = self.clone();
+        let leader = LeaderLock::new(self.pool.clone(), "customer_invoice_scheduler");
         tokio::spawn(async move {
             loop {
-                if let Err(e) = svc
-                    .generate_due_customer_package_invoices_for_all_tenants()
-                    .await
-                {
-                    tracing::warn!("customer invoice scheduler failed: {}", e);
-                }
-                if let Err(e) = svc.run_billing_collection_for_all_tenants().await {
-                    tracing::warn!("billing collection scheduler failed: {}", e);
+                if leader.acquire().await {
+                    if let Err(e) = svc
+                        .generate_due_customer_package_invoices_for_all_tenants()
+                        .await
+                    {
+                        tracing::warn!("customer invoice scheduler failed: {}", e);
+                    }
+                    if let Err(e) = svc.run_billing_collection_for_all_tenants().await {
+                        tracing::warn!("billing collection scheduler failed: {}", e);
+                    }
                 }
                 let interval_minutes = svc.resolve_scheduler_interval_minutes().await;
                 let sleep_secs = (interval_minutes.max(5) as u64) * 60;
@@ -247,13 +342,13 @@ impl PaymentService {
                     .await?;
                 let converted = amount * rate;
                 (
-                    self.round_amount(converted, &currency_code),
+                    money::round(converted, &currency_code),
                     Some(rate),
                     Some(source),
                     Some(fetched_at),
                 )
             } else {
-                (self.round_amount(amount, &currency_code), None, None, None)
+                (money::round(amount, &currency_code), None, None, None)
             };
 
         #[cfg(feature = "postgres")]
@@ -607,7 +702,7 @@ impl PaymentService {
             r#"
             SELECT subscription_id
             FROM installation_work_orders
-            WHERE tenant_id = $1 AND id = $2
+            WHERE tenant_id = $1 AND id = $2 AND work_type = 'installation'
             LIMIT 1
             "#,
         )
@@ -622,7 +717,7 @@ impl PaymentService {
             r#"
             SELECT subscription_id
             FROM installation_work_orders
-            WHERE tenant_id = ? AND id = ?
+            WHERE tenant_id = ? AND id = ? AND work_type = 'installation'
             LIMIT 1
             "#,
         )
@@ -653,6 +748,8 @@ impl PaymentService {
             f64,
             Option<chrono::DateTime<chrono::Utc>>,
             Option<chrono::DateTime<chrono::Utc>>,
+            String,
+            Option<f64>,
         )> = sqlx::query_as(
             r#"
             SELECT
@@ -661,9 +758,12 @@ impl PaymentService {
                 cs.billing_cycle,
                 cs.price::FLOAT8 AS price,
                 cs.starts_at,
-                cs.ends_at
+                cs.ends_at,
+                cs.package_id,
+                (CASE WHEN cs.billing_cycle = 'yearly' THEN p.price_yearly ELSE p.price_monthly END)::FLOAT8 AS list_price
             FROM customer_subscriptions cs
-            INNER JOIN customers c ON c.id = cs.customer_id AND c.tenant_id = cs.tenant_id
+            INNER JOIN customers c
+              ON c.id = cs.customer_id AND c.tenant_id = cs.tenant_id AND c.deleted_at IS NULL
             LEFT JOIN isp_packages p ON p.id = cs.package_id AND p.tenant_id = cs.tenant_id
             WHERE cs.id = $1 AND cs.tenant_id = $2
             LIMIT 1
@@ -683,6 +783,8 @@ impl PaymentService {
             f64,
             Option<chrono::DateTime<chrono::Utc>>,
             Option<chrono::DateTime<chrono::Utc>>,
+            String,
+            Option<f64>,
         )> = sqlx::query_as(
             r#"
             SELECT
@@ -691,9 +793,12 @@ impl PaymentService {
                 cs.billing_cycle,
                 cs.price AS price,
                 cs.starts_at,
-                cs.ends_at
+                cs.ends_at,
+                cs.package_id,
+                CASE WHEN cs.billing_cycle = 'yearly' THEN p.price_yearly ELSE p.price_monthly END AS list_price
             FROM customer_subscriptions cs
-            INNER JOIN customers c ON c.id = cs.customer_id AND c.tenant_id = cs.tenant_id
+            INNER JOIN customers c
+              ON c.id = cs.customer_id AND c.tenant_id = cs.tenant_id AND c.deleted_at IS NULL
             LEFT JOIN isp_packages p ON p.id = cs.package_id AND p.tenant_id = cs.tenant_id
             WHERE cs.id = ? AND cs.tenant_id = ?
             LIMIT 1
@@ -705,8 +810,16 @@ impl PaymentService {
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-        let (customer_name, package_name, billing_cycle, price, starts_at, ends_at) =
-            row.ok_or_else(|| AppError::NotFound("Customer subscription not found".to_string()))?;
+        let (
+            customer_name,
+            package_name,
+            billing_cycle,
+            price,
+            starts_at,
+            ends_at,
+            package_id,
+            list_price,
+        ) = row.ok_or_else(|| AppError::NotFound("Customer subscription not found".to_string()))?;
         if let Some(ends) = ends_at {
             if period_ref > ends {
                 return Err(AppError::Validation(
@@ -769,6 +882,17 @@ impl PaymentService {
             "Customer {} - {} ({} billing, period {})",
             customer_name, package_name, billing_cycle, period_key
         );
+        let price = self
+            .price_for_period(
+                tenant_id,
+                &package_id,
+                &billing_cycle,
+                starts_at.as_ref(),
+                &period_key,
+                price,
+                list_price,
+            )
+            .await?;
 
         let invoice = self
             .create_invoice(tenant_id, price, Some(description), Some(external_id))
@@ -782,6 +906,7 @@ impl PaymentService {
                 &invoice.invoice_number,
                 invoice.amount,
                 &invoice.currency_code,
+                invoice.due_date,
             )
             .await
         {
@@ -797,6 +922,197 @@ impl PaymentService {
         Ok(invoice)
     }
 
+    /// The package's list price for `billing_cycle` as recorded in its price
+    /// history at `at`.
+    async fn package_list_price_at(
+        &self,
+        tenant_id: &str,
+        package_id: &str,
+        billing_cycle: &str,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<Option<f64>> {
+        #[cfg(feature = "postgres")]
+        let price: Option<f64> = sqlx::query_scalar(
+            r#"
+            SELECT (CASE WHEN $3 = 'yearly' THEN price_yearly ELSE price_monthly END)::FLOAT8
+            FROM isp_package_price_history
+            WHERE tenant_id = $1 AND package_id = $2 AND effective_from <= $4
+            ORDER BY effective_from DESC, created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(package_id)
+        .bind(billing_cycle)
+        .bind(at)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        // Packages and their price history only exist on Postgres.
+        #[cfg(feature = "sqlite")]
+        let price: Option<f64> = {
+            let _ = (tenant_id, package_id, billing_cycle, at);
+            None
+        };
+
+        Ok(price)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn price_for_period(
+        &self,
+        tenant_id: &str,
+        package_id: &str,
+        billing_cycle: &str,
+        starts_at: Option<&chrono::DateTime<chrono::Utc>>,
+        period_key: &str,
+        price: f64,
+        list_price: Option<f64>,
+    ) -> AppResult<f64> {
+        let period_start = Self::billing_period_start(billing_cycle, starts_at, period_key)?;
+        let list_price_then = self
+            .package_list_price_at(tenant_id, package_id, billing_cycle, period_start)
+            .await?;
+        Ok(subscription_period_price(
+            price,
+            list_price,
+            list_price_then,
+        ))
+    }
+
+    /// Recomputes the amount of a pending customer package invoice from the
+    /// price in effect for its billing period, e.g. after a wrong package
+    /// price was corrected.
+    pub async fn reprice_customer_package_invoice(
+        &self,
+        tenant_id: &str,
+        invoice_id: &str,
+    ) -> AppResult<Invoice> {
+        let invoice = self.get_invoice(invoice_id).await?;
+        if invoice.tenant_id != tenant_id {
+            return Err(AppError::NotFound("Invoice not found".to_string()));
+        }
+        let Some((subscription_id, period_key)) =
+            parse_customer_package_external_id(invoice.external_id.as_deref())
+        else {
+            return Err(AppError::Validation(
+                "Only customer package invoices can be repriced".to_string(),
+            ));
+        };
+        if invoice.status != "pending" {
+            return Err(AppError::Validation(
+                "Only pending invoices can be repriced".to_string(),
+            ));
+        }
+
+        #[cfg(feature = "postgres")]
+        let row: Option<(
+            String,
+            String,
+            f64,
+            Option<chrono::DateTime<chrono::Utc>>,
+            Option<f64>,
+        )> = sqlx::query_as(
+            r#"
+            SELECT
+                cs.package_id,
+                cs.billing_cycle,
+                cs.price::FLOAT8 AS price,
+                cs.starts_at,
+                (CASE WHEN cs.billing_cycle = 'yearly' THEN p.price_yearly ELSE p.price_monthly END)::FLOAT8 AS list_price
+            FROM customer_subscriptions cs
+            LEFT JOIN isp_packages p ON p.id = cs.package_id AND p.tenant_id = cs.tenant_id
+            WHERE cs.id = $1 AND cs.tenant_id = $2
+            LIMIT 1
+            "#,
+        )
+        .bind(subscription_id)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        #[cfg(feature = "sqlite")]
+        let row: Option<(
+            String,
+            String,
+            f64,
+            Option<chrono::DateTime<chrono::Utc>>,
+            Option<f64>,
+        )> = sqlx::query_as(
+            r#"
+            SELECT
+                cs.package_id,
+                cs.billing_cycle,
+                cs.price AS price,
+                cs.starts_at,
+                CASE WHEN cs.billing_cycle = 'yearly' THEN p.price_yearly ELSE p.price_monthly END AS list_price
+            FROM customer_subscriptions cs
+            LEFT JOIN isp_packages p ON p.id = cs.package_id AND p.tenant_id = cs.tenant_id
+            WHERE cs.id = ? AND cs.tenant_id = ?
+            LIMIT 1
+            "#,
+        )
+        .bind(subscription_id)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let (package_id, billing_cycle, price, starts_at, list_price) =
+            row.ok_or_else(|| AppError::NotFound("Customer subscription not found".to_string()))?;
+        let price = self
+            .price_for_period(
+                tenant_id,
+                &package_id,
+                &billing_cycle,
+                starts_at.as_ref(),
+                period_key,
+                price,
+                list_price,
+            )
+            .await?;
+
+        // Keep the exchange rate the invoice was issued with.
+        let amount = match invoice.fx_rate {
+            Some(rate) if invoice.currency_code != invoice.base_currency_code => {
+                money::round(price * rate, &invoice.currency_code)
+            }
+            _ => money::round(price, &invoice.currency_code),
+        };
+        if (amount - invoice.amount).abs() < f64::EPSILON {
+            return Ok(invoice);
+        }
+
+        let now = Utc::now();
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            "UPDATE invoices SET amount = $1, updated_at = $2 WHERE tenant_id = $3 AND id = $4 AND status = 'pending'",
+        )
+        .bind(amount)
+        .bind(now)
+        .bind(tenant_id)
+        .bind(invoice_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query(
+            "UPDATE invoices SET amount = ?, updated_at = ? WHERE tenant_id = ? AND id = ? AND status = 'pending'",
+        )
+        .bind(amount)
+        .bind(now.to_rfc3339())
+        .bind(tenant_id)
+        .bind(invoice_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        self.get_invoice(invoice_id).await
+    }
+
     pub async fn generate_due_customer_package_invoices(
         &self,
         tenant_id: &str,
@@ -828,8 +1144,10 @@ impl PaymentService {
             r#"
             SELECT cs.id, cs.billing_cycle, cs.starts_at, cs.ends_at
             FROM customer_subscriptions cs
+            INNER JOIN customers c ON c.id = cs.customer_id AND c.tenant_id = cs.tenant_id
             WHERE cs.tenant_id = $1
               AND cs.status = 'active'
+              AND c.deleted_at IS NULL
               AND (cs.starts_at IS NULL OR cs.starts_at <= NOW())
               AND (cs.ends_at IS NULL OR cs.ends_at >= NOW())
             ORDER BY cs.created_at ASC
@@ -850,8 +1168,10 @@ impl PaymentService {
             r#"
             SELECT cs.id, cs.billing_cycle, cs.starts_at, cs.ends_at
             FROM customer_subscriptions cs
+            INNER JOIN customers c ON c.id = cs.customer_id AND c.tenant_id = cs.tenant_id
             WHERE cs.tenant_id = ?
               AND cs.status = 'active'
+              AND c.deleted_at IS NULL
               AND (cs.starts_at IS NULL OR cs.starts_at <= ?)
               AND (cs.ends_at IS NULL OR cs.ends_at >= ?)
             ORDER BY cs.created_at ASC
@@ -1129,7 +1449,9 @@ impl PaymentService {
                         .send_invoice_reminder(
                             tenant_id,
                             &subscription_id,
+                            &invoice_id,
                             &invoice_number,
+                            &reminder_code,
                             due_date,
                             day_offset,
                         )
@@ -1355,7 +1677,7 @@ impl PaymentService {
         .bind(to)
         .bind(search)
         .bind(limit)
-        .fetch_all(&self.pool)
+        .fetch_all(self.router.reader())
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
@@ -1420,7 +1742,7 @@ impl PaymentService {
         .bind(search)
         .bind(search)
         .bind(limit)
-        .fetch_all(&self.pool)
+        .fetch_all(self.router.reader())
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
@@ -1482,7 +1804,7 @@ impl PaymentService {
         .bind(to)
         .bind(search)
         .bind(limit)
-        .fetch_all(&self.pool)
+        .fetch_all(self.router.reader())
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
@@ -1536,7 +1858,7 @@ impl PaymentService {
         .bind(search)
         .bind(search)
         .bind(limit)
-        .fetch_all(&self.pool)
+        .fetch_all(self.router.reader())
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
@@ -1736,15 +2058,16 @@ impl PaymentService {
             app_url.trim_end_matches('/')
         );
 
-        // 3. Prepare Payload
+        // 3. Prepare Payload (Midtrans takes whole rupiah)
+        let gross_amount = money::to_minor(invoice.amount, "IDR", Rounding::HalfUp);
         let payload = json!({
             "transaction_details": {
                 "order_id": invoice.invoice_number,
-                "gross_amount": invoice.amount as i64 // IDR usually no decimals
+                "gross_amount": gross_amount
             },
             "item_details": [{
                 "id": invoice.id,
-                "price": invoice.amount as i64,
+                "price": gross_amount,
                 "quantity": 1,
                 "name": invoice.description.clone().unwrap_or("Payment".to_string())
             }],
@@ -2054,31 +2377,71 @@ impl PaymentService {
         }
 
         // 2. Update Status
-        let now = Utc::now();
-        let paid_at = if status == "paid" { Some(now) } else { None };
-
-        #[cfg(feature = "postgres")]
-        sqlx::query("UPDATE invoices SET status = $1, paid_at = $2, rejection_reason = CASE WHEN $1 = 'paid' THEN NULL ELSE rejection_reason END, updated_at = $3 WHERE id = $4")
-            .bind(status)
-            .bind(paid_at)
-            .bind(now)
-            .bind(&invoice.id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| AppError::Internal(e.to_string()))?;
+        // Upgrade invoices are marked paid together with the plan switch.
+        let upgrade = if status == "paid" {
+            parse_plan_upgrade_external_id(invoice.external_id.as_deref())
+        } else {
+            None
+        };
+        if let Some((plan_id, cycle)) = upgrade {
+            self.apply_plan_upgrade(&invoice, plan_id, cycle).await?;
+        } else {
+            let now = Utc::now();
+            let paid_at = if status == "paid" { Some(now) } else { None };
+            let mut tx = self
+                .pool
+                .begin()
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
 
-        #[cfg(feature = "sqlite")]
-        {
-            let paid_str = paid_at.map(|t| t.to_rfc3339());
-            sqlx::query("UPDATE invoices SET status = ?, paid_at = ?, rejection_reason = CASE WHEN ? = 'paid' THEN NULL ELSE rejection_reason END, updated_at = ? WHERE id = ?")
-                .bind(status)
-                .bind(paid_str)
+            #[cfg(feature = "postgres")]
+            sqlx::query("UPDATE invoices SET status = $1, paid_at = $2, rejection_reason = CASE WHEN $1 = 'paid' THEN NULL ELSE rejection_reason END, updated_at = $3 WHERE id = $4")
                 .bind(status)
-                .bind(now.to_rfc3339())
+                .bind(paid_at)
+                .bind(now)
                 .bind(&invoice.id)
-                .execute(&self.pool)
+                .execute(&mut *tx)
                 .await
                 .map_err(|e| AppError::Internal(e.to_string()))?;
+
+            #[cfg(feature = "sqlite")]
+            {
+                let paid_str = paid_at.map(|t| t.to_rfc3339());
+                sqlx::query("UPDATE invoices SET status = ?, paid_at = ?, rejection_reason = CASE WHEN ? = 'paid' THEN NULL ELSE rejection_reason END, updated_at = ? WHERE id = ?")
+                    .bind(status)
+                    .bind(paid_str)
+                    .bind(status)
+                    .bind(now.to_rfc3339())
+                    .bind(&invoice.id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))?;
+            }
+
+            // The tenant's webhooks hear about a paid customer invoice in the
+            // same commit that marks it paid.
+            let paid_event = (status == "paid"
+                && is_customer_package_invoice_external_id(invoice.external_id.as_deref()))
+            .then(|| DomainEvent::InvoicePaid {
+                invoice_id: invoice.id.clone(),
+                invoice_number: invoice.invoice_number.clone(),
+                subscription_id: parse_customer_package_external_id(invoice.external_id.as_deref())
+                    .map(|(id, _)| id.to_string()),
+                amount: invoice.amount,
+                currency_code: invoice.currency_code.clone(),
+                paid_at: now,
+            });
+            if let Some(event) = &paid_event {
+                EventOutboxService::stage(&mut tx, Some(invoice.tenant_id.as_str()), event)
+                    .await
+                    .map_err(|e| AppError::Internal(e.to_string()))?;
+            }
+            tx.commit()
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+            if paid_event.is_some() {
+                event_outbox_service::wake();
+            }
         }
 
         // 3. Activate Subscription if Paid
@@ -2101,6 +2464,7 @@ impl PaymentService {
             // external_id stores either:
             // - "pkgsub:{subscription_id}" for customer package invoices
             // - "plan:{plan_id}:{billing_cycle}" for SaaS plan invoices
+            // - "upgrade:{plan_id}:{billing_cycle}" for self-serve upgrades (switched above)
             // - legacy "{plan_id}:{billing_cycle}" for old SaaS plan invoices
             if let Some(ext_id) = &invoice.external_id {
                 if ext_id.starts_with(CUSTOMER_PACKAGE_INVOICE_PREFIX) {
@@ -2108,6 +2472,11 @@ impl PaymentService {
                         "DEBUG: Customer package invoice handled by customer-flow; tenant SaaS activation skipped for {}",
                         invoice.invoice_number
                     );
+                } else if ext_id.starts_with(PLAN_UPGRADE_INVOICE_PREFIX) {
+                    println!(
+                        "DEBUG: Plan upgrade for Tenant {} applied with invoice {}",
+                        invoice.tenant_id, invoice.invoice_number
+                    );
                 } else if let Some(rest) = ext_id.strip_prefix("plan:") {
                     let parts: Vec<&str> = rest.split(':').collect();
                     if parts.len() == 2 {
@@ -2158,28 +2527,20 @@ impl PaymentService {
         //   - owner/admin role => admin invoice page (/admin/invoices)
         // - SaaS plan invoice: notify Owner/Admin tenant members only (/admin/subscription)
         if status == "paid" || status == "failed" {
-            let title = if status == "paid" {
-                "Payment Successful".to_string()
-            } else {
-                "Payment Failed".to_string()
-            };
             let manual_failure = status == "failed" && is_manual_payment_invoice(&invoice);
-            let message = if status == "paid" {
-                format!(
-                    "Invoice {} has been successfully paid. Thank you!",
-                    invoice.invoice_number
-                )
+            let code = if status == "paid" {
+                "payment_successful"
             } else if manual_failure {
-                format!(
-                    "Payment proof for invoice {} was rejected. Please review the reason and upload a new proof.",
-                    invoice.invoice_number
-                )
+                "payment_proof_rejected"
             } else {
-                format!(
-                    "Payment for invoice {} failed. Please check your payment method.",
-                    invoice.invoice_number
-                )
+                "payment_failed"
             };
+            let vars = HashMap::from([("invoice_number", invoice.invoice_number.clone())]);
+            let rendered = self
+                .notification_service
+                .render_template(Some(&invoice.tenant_id), code, &vars)
+                .await;
+            let (title, message) = (rendered.title, rendered.body);
 
             if is_customer_package {
                 if manual_failure {
@@ -2297,20 +2658,50 @@ impl PaymentService {
                     .await
                     .unwrap_or_default();
 
-                for user_id in tenant_admins {
-                    let _ = self
-                        .notification_service
-                        .create_notification(
-                            user_id,
-                            Some(invoice.tenant_id.clone()),
-                            "Customer Payment Received".to_string(),
-                            format!(
-                                "Customer invoice {} has been paid. Amount: {}",
-                                invoice.invoice_number, invoice.amount
-                            ),
-                            "success".to_string(),
-                            "billing".to_string(),
-                            Some("/admin/invoices".to_string()),
+                let vars = HashMap::from([
+                    ("invoice_number", invoice.invoice_number.clone()),
+                    (
+                        "amount",
+                        money::format_money(invoice.amount, &invoice.currency_code, None),
+                    ),
+                ]);
+                let rendered = self
+                    .notification_service
+                    .render_template(Some(&invoice.tenant_id), "customer_payment_received", &vars)
+                    .await;
+                let alert = TenantAlert {
+                    category: "billing",
+                    notification_type: "success",
+                    title: &rendered.title,
+                    message: &rendered.body,
+                    action_url: Some("/admin/invoices"),
+                    telegram: TelegramAlert::Payment,
+                };
+                if !self
+                    .notification_service
+                    .route_alert(&invoice.tenant_id, &tenant_admins, &alert)
+                    .await
+                {
+                    for user_id in &tenant_admins {
+                        let _ = self
+                            .notification_service
+                            .create_notification(
+                                user_id.clone(),
+                                Some(invoice.tenant_id.clone()),
+                                rendered.title.clone(),
+                                rendered.body.clone(),
+                                "success".to_string(),
+                                "billing".to_string(),
+                                Some("/admin/invoices".to_string()),
+                            )
+                            .await;
+                    }
+                    self.notification_service
+                        .send_telegram_alert(
+                            &invoice.tenant_id,
+                            TelegramAlert::Payment,
+                            &tenant_admins,
+                            &format!("{}\n{}", rendered.title, rendered.body),
                         )
                         .await;
                 }
@@ -2338,7 +2729,8 @@ impl PaymentService {
                             "New Subscription Sale!".to_string(),
                             format!(
                                 "Invoice {} has been paid. Amount: {}",
-                                invoice.invoice_number, invoice.amount
+                                invoice.invoice_number,
+                                money::format_money(invoice.amount, &invoice.currency_code, None)
                             ),
                             "success".to_string(),
                             "billing".to_string(),
@@ -2521,6 +2913,239 @@ impl PaymentService {
         Ok(())
     }
 
+    // ==================== PLAN UPGRADES ====================
+
+    /// Price an upgrade to `plan_id`. The new period starts when the invoice
+    /// is paid, so the unused days of an active paid period are credited
+    /// against the new plan's price.
+    pub async fn quote_plan_upgrade(
+        &self,
+        tenant_id: &str,
+        plan_id: &str,
+        billing_cycle: &str,
+    ) -> AppResult<PlanUpgradeQuote> {
+        let billing_cycle = billing_cycle.trim().to_ascii_lowercase();
+        if billing_cycle != "monthly" && billing_cycle != "yearly" {
+            return Err(AppError::Validation(
+                "billing_cycle must be monthly or yearly".to_string(),
+            ));
+        }
+
+        let plan_service = PlanService::new(self.pool.clone());
+        let target = plan_service.get_plan(plan_id).await?;
+        if !target.is_active {
+            return Err(AppError::Validation(format!(
+                "Plan {} is not available",
+                target.name
+            )));
+        }
+        let plan_price = if billing_cycle == "yearly" {
+            target.price_yearly
+        } else {
+            target.price_monthly
+        };
+        if plan_price <= 0.0 {
+            return Err(AppError::Validation(
+                "Free plans do not need a checkout".to_string(),
+            ));
+        }
+
+        let mut current_plan_id = None;
+        let mut current_plan_name = None;
+        let mut credit = 0.0;
+        let mut remaining_days = 0;
+        let mut period_days = 0;
+
+        if let Some(sub) = plan_service.get_tenant_subscription(tenant_id).await? {
+            let current = plan_service.get_plan(&sub.plan_id).await.ok();
+            current_plan_id = Some(sub.plan_id.clone());
+            current_plan_name = current.as_ref().map(|p| p.name.clone());
+
+            // Trials and lapsed subscriptions have nothing paid to credit
+            // and may pick any plan.
+            if sub.status == "active" {
+                if sub.plan_id == target.id {
+                    return Err(AppError::Conflict(format!(
+                        "Tenant is already on the {} plan",
+                        target.name
+                    )));
+                }
+                if let Some(current) = &current {
+                    if target.price_monthly <= current.price_monthly {
+                        return Err(AppError::Validation(
+                            "Only upgrades to a higher plan can be checked out".to_string(),
+                        ));
+                    }
+                    if let (Some(start), Some(end)) =
+                        (sub.current_period_start, sub.current_period_end)
+                    {
+                        period_days = (end - start).num_days().max(0);
+                        remaining_days = (end - Utc::now()).num_days().clamp(0, period_days);
+                        let paid_price = if period_days > 31 {
+                            current.price_yearly
+                        } else {
+                            current.price_monthly
+                        };
+                        credit = prorated_credit(paid_price, remaining_days, period_days);
+                    }
+                }
+            }
+        }
+
+        let currency_code = self
+            .get_setting_value(None, "base_currency_code")
+            .await
+            .unwrap_or_else(|| "IDR".to_string())
+            .to_uppercase();
+        // Round the credit down so it never exceeds the unused share paid.
+        let credit = money::round_with(credit.min(plan_price), &currency_code, Rounding::Down);
+        let amount_due = money::round(plan_price - credit, &currency_code);
+
+        Ok(PlanUpgradeQuote {
+            current_plan_id,
+            current_plan_name,
+            target_plan_id: target.id,
+            target_plan_name: target.name,
+            billing_cycle,
+            currency_code,
+            plan_price,
+            credit,
+            amount_due,
+            remaining_days,
+            period_days,
+        })
+    }
+
+    /// Invoice the prorated upgrade and start payment on the configured
+    /// gateway. The plan switches when the invoice is reported paid.
+    pub async fn checkout_plan_upgrade(
+        &self,
+        tenant_id: &str,
+        plan_id: &str,
+        billing_cycle: &str,
+    ) -> AppResult<PlanUpgradeCheckout> {
+        let quote = self
+            .quote_plan_upgrade(tenant_id, plan_id, billing_cycle)
+            .await?;
+        if quote.amount_due <= 0.0 {
+            return Err(AppError::Validation(
+                "The remaining credit covers this plan; choose the yearly cycle instead"
+                    .to_string(),
+            ));
+        }
+
+        let description = format!(
+            "Upgrade to {} Plan ({} billing, prorated)",
+            quote.target_plan_name, quote.billing_cycle
+        );
+        let external_id = format!(
+            "{}{}:{}",
+            PLAN_UPGRADE_INVOICE_PREFIX, quote.target_plan_id, quote.billing_cycle
+        );
+        let invoice = self
+            .create_invoice(
+                tenant_id,
+                quote.amount_due,
+                Some(description),
+                Some(external_id),
+            )
+            .await?;
+
+        let midtrans_enabled = self
+            .get_setting_value(None, "payment_midtrans_enabled")
+            .await
+            .as_deref()
+            == Some("true");
+        let (gateway, snap_token) = if midtrans_enabled {
+            let token = self.initiate_midtrans(&invoice.id).await?;
+            ("midtrans", Some(token))
+        } else {
+            ("manual", None)
+        };
+
+        Ok(PlanUpgradeCheckout {
+            quote,
+            invoice,
+            gateway: gateway.to_string(),
+            snap_token,
+        })
+    }
+
+    /// Mark an upgrade invoice paid and move the subscription to the new plan
+    /// in one transaction. Features resolve from `plan_id`, so they switch
+    /// with it; a failure leaves both the invoice and the old plan untouched.
+    async fn apply_plan_upgrade(
+        &self,
+        invoice: &Invoice,
+        plan_id: &str,
+        billing_cycle: &str,
+    ) -> AppResult<()> {
+        let now = Utc::now();
+        let end_date = if billing_cycle == "yearly" {
+            now + Duration::days(365)
+        } else {
+            now + Duration::days(30)
+        };
+
+        let mut tx: sqlx::Transaction<'_, Db> = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE invoices
+            SET status = 'paid', paid_at = $1, rejection_reason = NULL, updated_at = $1
+            WHERE id = $2
+            "#,
+        )
+        .bind(now)
+        .bind(&invoice.id)
+        .execute(&mut *tx)
+        .await?;
+
+        let rows = sqlx::query(
+            r#"
+            UPDATE tenant_subscriptions
+            SET plan_id = $1, status = 'active', trial_ends_at = NULL, trial_notice_days = NULL,
+                current_period_start = $2, current_period_end = $3, updated_at = $2
+            WHERE tenant_id = $4
+            "#,
+        )
+        .bind(plan_id)
+        .bind(now)
+        .bind(end_date)
+        .bind(&invoice.tenant_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if rows == 0 {
+            sqlx::query(
+                r#"
+                INSERT INTO tenant_subscriptions
+                    (id, tenant_id, plan_id, status, current_period_start, current_period_end, created_at, updated_at)
+                VALUES ($1, $2, $3, 'active', $4, $5, $4, $4)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&invoice.tenant_id)
+            .bind(plan_id)
+            .bind(now)
+            .bind(end_date)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        tracing::info!(
+            "Tenant {} upgraded to plan {} ({}) by invoice {}",
+            invoice.tenant_id,
+            plan_id,
+            billing_cycle,
+            invoice.invoice_number
+        );
+        Ok(())
+    }
+
     /// Submit Payment Proof (Manual Transfer)
     pub async fn submit_payment_proof(&self, invoice_id: &str, file_path: &str) -> AppResult<()> {
         let invoice = self.get_invoice(invoice_id).await?;
@@ -2553,17 +3178,19 @@ impl PaymentService {
                 .await
                 .unwrap_or_default();
 
+            let vars = HashMap::from([("invoice_number", invoice.invoice_number.clone())]);
+            let rendered = self
+                .notification_service
+                .render_template(Some(&invoice.tenant_id), "payment_proof_uploaded", &vars)
+                .await;
             for user_id in tenant_admins {
                 let _ = self
                     .notification_service
                     .create_notification(
                         user_id,
                         Some(invoice.tenant_id.clone()),
-                        "New Payment Proof Uploaded".to_string(),
-                        format!(
-                            "A payment proof has been uploaded for customer invoice {}",
-                            invoice.invoice_number
-                        ),
+                        rendered.title.clone(),
+                        rendered.body.clone(),
                         "info".to_string(),
                         "billing".to_string(),
                         Some("/admin/invoices".to_string()),
@@ -3024,34 +3651,84 @@ impl PaymentService {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn send_invoice_reminder(
         &self,
         tenant_id: &str,
         subscription_id: &str,
+        invoice_id: &str,
         invoice_number: &str,
+        reminder_code: &str,
         due_date: chrono::DateTime<chrono::Utc>,
         day_offset: i64,
     ) -> AppResult<usize> {
+        let code = if day_offset < 0 {
+            "invoice_due_soon"
+        } else if day_offset == 0 {
+            "invoice_due_today"
+        } else {
+            "invoice_overdue"
+        };
+        let template_vars = HashMap::from([
+            ("invoice_number", invoice_number.to_string()),
+            (
+                "due_date",
+                due_date.format("%Y-%m-%d %H:%M UTC").to_string(),
+            ),
+            ("days", day_offset.abs().to_string()),
+        ]);
+        let rendered = self
+            .notification_service
+            .render_template(Some(tenant_id), code, &template_vars)
+            .await;
+        let title = rendered.title;
+
+        // The customer hears about it on WhatsApp even without a portal account.
+        let mut whatsapp_sent = 0usize;
+        let vars = HashMap::from([
+            ("invoice_number", invoice_number.to_string()),
+            ("due_date", due_date.format("%Y-%m-%d").to_string()),
+            ("reminder", title.clone()),
+        ]);
+        if let Some(message) = self
+            .send_invoice_whatsapp(
+                tenant_id,
+                subscription_id,
+                invoice_id,
+                WhatsappEvent::InvoiceReminder,
+                vars,
+            )
+            .await
+        {
+            let status = if message.status == "failed" {
+                "failed"
+            } else {
+                "sent"
+            };
+            if status == "sent" {
+                whatsapp_sent = 1;
+            }
+            let _ = self
+                .insert_invoice_reminder_log(
+                    tenant_id,
+                    invoice_id,
+                    reminder_code,
+                    "whatsapp",
+                    Some(&message.to_phone),
+                    status,
+                    message.error.as_deref(),
+                )
+                .await;
+        }
+
         let user_ids = self
             .list_notification_user_ids_for_subscription(tenant_id, subscription_id)
             .await?;
         if user_ids.is_empty() {
-            return Ok(0);
+            return Ok(whatsapp_sent);
         }
 
-        let title = if day_offset < 0 {
-            format!("Invoice due in {} day(s)", day_offset.abs())
-        } else if day_offset == 0 {
-            "Invoice due today".to_string()
-        } else {
-            format!("Invoice overdue by {} day(s)", day_offset)
-        };
-
-        let message = format!(
-            "Invoice {} is due on {}. Please complete payment to keep service active.",
-            invoice_number,
-            due_date.format("%Y-%m-%d %H:%M UTC")
-        );
+        let message = rendered.body;
 
         let mut sent = 0usize;
         for user_id in user_ids {
@@ -3073,9 +3750,10 @@ impl PaymentService {
             }
         }
 
-        Ok(sent)
+        Ok(sent + whatsapp_sent)
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn notify_subscription_invoice_created(
         &self,
         tenant_id: &str,
@@ -3084,7 +3762,22 @@ impl PaymentService {
         invoice_number: &str,
         amount: f64,
         currency_code: &str,
+        due_date: chrono::DateTime<chrono::Utc>,
     ) -> AppResult<usize> {
+        let vars = HashMap::from([
+            ("invoice_number", invoice_number.to_string()),
+            ("amount", money::format_money(amount, currency_code, None)),
+            ("due_date", due_date.format("%Y-%m-%d").to_string()),
+        ]);
+        self.send_invoice_whatsapp(
+            tenant_id,
+            subscription_id,
+            invoice_id,
+            WhatsappEvent::InvoiceCreated,
+            vars.clone(),
+        )
+        .await;
+
         let user_ids = self
             .list_notification_user_ids_for_subscription(tenant_id, subscription_id)
             .await?;
@@ -3092,11 +3785,11 @@ impl PaymentService {
             return Ok(0);
         }
 
-        let title = "Invoice created".to_string();
-        let message = format!(
-            "New invoice {} is ready ({} {:.2}). Please complete payment to activate/keep service.",
-            invoice_number, currency_code, amount
-        );
+        let rendered = self
+            .notification_service
+            .render_template(Some(tenant_id), "invoice_created", &vars)
+            .await;
+        let (title, message) = (rendered.title, rendered.body);
 
         let mut sent = 0usize;
         for user_id in user_ids {
@@ -3121,6 +3814,42 @@ impl PaymentService {
         Ok(sent)
     }
 
+    /// Send an invoice event to the subscription's customer on WhatsApp, if the
+    /// tenant routes it and the customer has a phone number.
+    async fn send_invoice_whatsapp(
+        &self,
+        tenant_id: &str,
+        subscription_id: &str,
+        invoice_id: &str,
+        event: WhatsappEvent,
+        mut vars: HashMap<&'static str, String>,
+    ) -> Option<WhatsappMessage> {
+        let whatsapp = self.notification_service.whatsapp()?;
+        let recipient = match whatsapp
+            .subscription_recipient(tenant_id, subscription_id)
+            .await
+        {
+            Ok(recipient) => recipient?,
+            Err(e) => {
+                tracing::warn!(
+                    "failed to load WhatsApp recipient: tenant={}, subscription={}, error={}",
+                    tenant_id,
+                    subscription_id,
+                    e
+                );
+                return None;
+            }
+        };
+        vars.insert("customer_name", recipient.name.clone());
+        vars.insert(
+            "pay_url",
+            whatsapp.public_url(&format!("/pay/{}", invoice_id)).await,
+        );
+        self.notification_service
+            .send_whatsapp(tenant_id, event, &recipient, &vars)
+            .await
+    }
+
     async fn notify_subscription_suspension(
         &self,
         tenant_id: &str,
@@ -3136,11 +3865,15 @@ impl PaymentService {
             return Ok(0);
         }
 
-        let title = "Subscription suspended".to_string();
-        let message = format!(
-            "Your subscription has been suspended (invoice {} overdue {} day(s)).",
-            invoice_number, overdue_days
-        );
+        let vars = HashMap::from([
+            ("invoice_number", invoice_number.to_string()),
+            ("overdue_days", overdue_days.to_string()),
+        ]);
+        let rendered = self
+            .notification_service
+            .render_template(Some(tenant_id), "subscription_suspended", &vars)
+            .await;
+        let (title, message) = (rendered.title, rendered.body);
 
         let mut sent = 0usize;
         for user_id in user_ids {
@@ -3179,11 +3912,12 @@ impl PaymentService {
             return Ok(0);
         }
 
-        let title = "Subscription resumed".to_string();
-        let message = format!(
-            "Payment received for invoice {}. Your subscription is active again.",
-            invoice_number
-        );
+        let vars = HashMap::from([("invoice_number", invoice_number.to_string())]);
+        let rendered = self
+            .notification_service
+            .render_template(Some(tenant_id), "subscription_resumed", &vars)
+            .await;
+        let (title, message) = (rendered.title, rendered.body);
 
         let mut sent = 0usize;
         for user_id in user_ids {
@@ -3221,11 +3955,12 @@ impl PaymentService {
             return Ok(0);
         }
 
-        let title = "Order Queued for Installation".to_string();
-        let message = format!(
-            "Payment for invoice {} is confirmed. Your order is now Pending Installation and waiting assignment/schedule from admin or technician.",
-            invoice_number
-        );
+        let vars = HashMap::from([("invoice_number", invoice_number.to_string())]);
+        let rendered = self
+            .notification_service
+            .render_template(Some(tenant_id), "installation_pending", &vars)
+            .await;
+        let (title, message) = (rendered.title, rendered.body);
 
         let mut sent = 0usize;
         for user_id in user_ids {
@@ -3263,11 +3998,15 @@ impl PaymentService {
             return Ok(0);
         }
 
-        let title = "Installation Work Order: New Request".to_string();
-        let message = format!(
-            "Invoice {} is paid. A new installation work order is ready for assignment and scheduling (WO {}).",
-            invoice_number, work_order_id
-        );
+        let vars = HashMap::from([
+            ("invoice_number", invoice_number.to_string()),
+            ("work_order_id", work_order_id.to_string()),
+        ]);
+        let rendered = self
+            .notification_service
+            .render_template(Some(tenant_id), "installation_requested", &vars)
+            .await;
+        let (title, message) = (rendered.title, rendered.body);
 
         let mut sent = 0usize;
         for user_id in user_ids {
@@ -3613,6 +4352,7 @@ impl PaymentService {
               FROM installation_work_orders
               WHERE tenant_id = $1
                 AND subscription_id = $2
+                AND work_type = 'installation'
                 AND status = 'completed'
             )
             "#,
@@ -3631,6 +4371,7 @@ impl PaymentService {
               FROM installation_work_orders
               WHERE tenant_id = ?
                 AND subscription_id = ?
+                AND work_type = 'installation'
                 AND status = 'completed'
             )
             "#,
@@ -4130,6 +4871,7 @@ impl PaymentService {
             FROM installation_work_orders
             WHERE tenant_id = $1
               AND subscription_id = $2
+              AND work_type = 'installation'
               AND status IN ('pending', 'in_progress')
             ORDER BY created_at DESC
             LIMIT 1
@@ -4148,6 +4890,7 @@ impl PaymentService {
             FROM installation_work_orders
             WHERE tenant_id = ?
               AND subscription_id = ?
+              AND work_type = 'installation'
               AND status IN ('pending', 'in_progress')
             ORDER BY created_at DESC
             LIMIT 1
@@ -4464,19 +5207,6 @@ impl PaymentService {
         parsed
     }
 
-    fn currency_decimals(&self, currency: &str) -> i32 {
-        match currency.to_uppercase().as_str() {
-            "IDR" | "JPY" | "KRW" => 0,
-            _ => 2,
-        }
-    }
-
-    fn round_amount(&self, amount: f64, currency: &str) -> f64 {
-        let d = self.currency_decimals(currency);
-        let factor = 10_f64.powi(d);
-        (amount * factor).round() / factor
-    }
-
     fn billing_period_key(
         billing_cycle: &str,
         starts_at: Option<&chrono::DateTime<chrono::Utc>>,
@@ -4513,6 +5243,45 @@ impl PaymentService {
         ))
     }
 
+    /// Start of the billing period named by `period_key`, the inverse of
+    /// [`Self::billing_period_key`]. Anniversary days past the end of a short
+    /// month fall on its last day.
+    fn billing_period_start(
+        billing_cycle: &str,
+        starts_at: Option<&chrono::DateTime<chrono::Utc>>,
+        period_key: &str,
+    ) -> AppResult<chrono::DateTime<chrono::Utc>> {
+        let start_day = starts_at.map(|d| d.day()).unwrap_or(1);
+        let start_month = starts_at.map(|d| d.month()).unwrap_or(1);
+        let invalid = || AppError::Validation(format!("Invalid billing period '{}'", period_key));
+
+        let (year, month) = match billing_cycle.trim().to_ascii_lowercase().as_str() {
+            "monthly" => {
+                let (year, month) = period_key.split_once('-').ok_or_else(invalid)?;
+                (
+                    year.parse::<i32>().map_err(|_| invalid())?,
+                    month.parse::<u32>().map_err(|_| invalid())?,
+                )
+            }
+            "yearly" => (
+                period_key.parse::<i32>().map_err(|_| invalid())?,
+                start_month,
+            ),
+            _ => {
+                return Err(AppError::Validation(
+                    "billing_cycle must be monthly or yearly".to_string(),
+                ))
+            }
+        };
+
+        (1..=start_day)
+            .rev()
+            .find_map(|day| chrono::NaiveDate::from_ymd_opt(year, month, day))
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|dt| dt.and_utc())
+            .ok_or_else(invalid)
+    }
+
     fn next_renewal_at(
         billing_cycle: &str,
         starts_at: Option<&chrono::DateTime<chrono::Utc>>,
@@ -4679,8 +5448,11 @@ mod tests {
     use super::{
         filter_installation_request_user_ids, filter_owner_admin_user_ids,
         is_customer_package_invoice_external_id, is_owner_admin_or_technician_role,
-        is_owner_or_admin_role, resolve_post_paid_subscription_action, PostPaidSubscriptionAction,
+        is_owner_or_admin_role, parse_customer_package_external_id, parse_plan_upgrade_external_id,
+        prorated_credit, resolve_post_paid_subscription_action, subscription_period_price,
+        PaymentService, PostPaidSubscriptionAction,
     };
+    use chrono::{TimeZone, Utc};
 
     #[test]
     fn owner_admin_role_detection_is_case_insensitive() {
@@ -4792,4 +5564,96 @@ mod tests {
         let action = resolve_post_paid_subscription_action(false, "suspended", true);
         assert_eq!(action, PostPaidSubscriptionAction::ResumeIfSuspended);
     }
+
+    #[test]
+    fn upgrade_external_id_carries_plan_and_cycle() {
+        assert_eq!(
+            parse_plan_upgrade_external_id(Some("upgrade:pro:yearly")),
+            Some(("pro", "yearly"))
+        );
+        assert_eq!(
+            parse_plan_upgrade_external_id(Some("upgrade:pro:weekly")),
+            None
+        );
+        assert_eq!(
+            parse_plan_upgrade_external_id(Some("upgrade::monthly")),
+            None
+        );
+        assert_eq!(
+            parse_plan_upgrade_external_id(Some("plan:pro:monthly")),
+            None
+        );
+        assert!(!is_customer_package_invoice_external_id(Some(
+            "upgrade:pro:monthly"
+        )));
+    }
+
+    #[test]
+    fn upgrade_credit_covers_the_unused_days() {
+        assert_eq!(prorated_credit(300_000.0, 15, 30), 150_000.0);
+        assert_eq!(prorated_credit(300_000.0, 45, 30), 300_000.0);
+        assert_eq!(prorated_credit(300_000.0, 0, 30), 0.0);
+        assert_eq!(prorated_credit(0.0, 10, 30), 0.0);
+        assert_eq!(prorated_credit(300_000.0, 10, 0), 0.0);
+    }
+
+    #[test]
+    fn package_invoice_external_id_carries_subscription_and_period() {
+        assert_eq!(
+            parse_customer_package_external_id(Some("pkgsub:sub-1:2026-03")),
+            Some(("sub-1", "2026-03"))
+        );
+        assert_eq!(
+            parse_customer_package_external_id(Some("pkgsub:sub-1")),
+            None
+        );
+        assert_eq!(
+            parse_customer_package_external_id(Some("upgrade:pro:monthly")),
+            None
+        );
+    }
+
+    #[test]
+    fn list_priced_subscriptions_pay_the_list_price_of_the_period() {
+        // On the current list price: the period is billed at the price of its time.
+        assert_eq!(
+            subscription_period_price(150_000.0, Some(150_000.0), Some(120_000.0)),
+            120_000.0
+        );
+        // Custom prices are kept.
+        assert_eq!(
+            subscription_period_price(100_000.0, Some(150_000.0), Some(120_000.0)),
+            100_000.0
+        );
+        // No history for the period.
+        assert_eq!(
+            subscription_period_price(150_000.0, Some(150_000.0), None),
+            150_000.0
+        );
+    }
+
+    #[test]
+    fn billing_period_start_inverts_the_period_key() {
+        let starts_at = Utc.with_ymd_and_hms(2025, 1, 31, 8, 0, 0).unwrap();
+        for now in [
+            Utc.with_ymd_and_hms(2026, 3, 31, 9, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2026, 4, 15, 9, 0, 0).unwrap(),
+        ] {
+            for cycle in ["monthly", "yearly"] {
+                let key = PaymentService::billing_period_key(cycle, Some(&starts_at), now).unwrap();
+                let start =
+                    PaymentService::billing_period_start(cycle, Some(&starts_at), &key).unwrap();
+                assert!(start <= now, "{cycle} {key}: {start} > {now}");
+                assert_eq!(
+                    PaymentService::billing_period_key(cycle, Some(&starts_at), start).unwrap(),
+                    key
+                );
+            }
+        }
+        // A 31st anniversary starts February's period on its last day.
+        assert_eq!(
+            PaymentService::billing_period_start("monthly", Some(&starts_at), "2026-02").unwrap(),
+            Utc.with_ymd_and_hms(2026, 2, 28, 0, 0, 0).unwrap()
+        );
+    }
 }
@@ -1,16 +1,84 @@
 //! Plan Service - Manages subscription plans and features
 
 use crate::db::DbPool;
+use crate::error::AppResult;
 use crate::models::{
-    CreateFeatureRequest, CreatePlanRequest, FeatureAccess, FeatureDefinition, Plan, PlanFeature,
-    PlanFeatureValue, PlanWithFeatures, TenantSubscription, UpdatePlanRequest,
+    BackgroundJob, CreateFeatureRequest, CreatePlanRequest, FeatureAccess, FeatureDefinition, Plan,
+    PlanFeature, PlanFeatureValue, PlanWithFeatures, TenantSubscription, UpdatePlanRequest,
 };
-use chrono::Utc;
+use crate::services::{EmailOutboxService, JobHandler, JobQueue};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use tracing::{info, warn};
 use uuid::Uuid;
 
 #[cfg(feature = "postgres")]
 use sqlx::Postgres;
 
+/// Trial ends by moving the tenant to the free plan.
+pub const TRIAL_EXPIRY_DOWNGRADE: &str = "downgrade";
+/// Trial ends by keeping the plan but switching every feature off.
+pub const TRIAL_EXPIRY_LOCK: &str = "lock";
+
+/// Reminder emails go out when this many days (or fewer) of a trial are left.
+const TRIAL_NOTICE_DAYS: &[i32] = &[7, 3, 1];
+
+/// Whether a subscription has run past its paid period or its trial.
+fn has_lapsed(sub: &TenantSubscription, now: DateTime<Utc>) -> bool {
+    match sub.status.as_str() {
+        "active" => sub.current_period_end.is_some_and(|end| end < now),
+        "trial" => sub.trial_ends_at.is_some_and(|end| end <= now),
+        _ => false,
+    }
+}
+
+/// The reminder due for a trial with `days_left` to go, given the last one
+/// already sent. Each threshold in `TRIAL_NOTICE_DAYS` is sent at most once.
+fn due_trial_notice(days_left: i64, last_notice: Option<i32>) -> Option<i32> {
+    let due = TRIAL_NOTICE_DAYS
+        .iter()
+        .copied()
+        .filter(|d| days_left <= *d as i64)
+        .min()?;
+    match last_notice {
+        Some(last) if last <= due => None,
+        _ => Some(due),
+    }
+}
+
+/// Whole days left until `ends_at`, rounded up so "ends in 20 hours" reads as 1.
+fn days_until(ends_at: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    let secs = (ends_at - now).num_seconds().max(0);
+    (secs + 86_399) / 86_400
+}
+
+/// Feature access while a subscription is locked after its trial: switches
+/// read as off and limits as zero; text features keep their default value.
+fn locked_feature_access(feature_code: &str, feature: FeatureDefinition) -> FeatureAccess {
+    let value = match feature.value_type.as_str() {
+        "boolean" => "false".to_string(),
+        "number" => "0".to_string(),
+        _ => feature.default_value,
+    };
+    FeatureAccess {
+        code: feature_code.to_string(),
+        has_access: false,
+        value,
+        value_type: feature.value_type,
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct TrialSubscriptionRow {
+    tenant_id: String,
+    tenant_name: String,
+    plan_name: String,
+    trial_expiry_action: String,
+    trial_ends_at: DateTime<Utc>,
+    trial_notice_days: Option<i32>,
+}
+
 #[derive(Clone)]
 pub struct PlanService {
     pool: DbPool,
@@ -32,7 +100,8 @@ impl PlanService {
                 id, name, slug, description, 
                 price_monthly::FLOAT8 as price_monthly, 
                 price_yearly::FLOAT8 as price_yearly, 
-                is_active, is_default, sort_order, created_at, updated_at
+                is_active, is_default, sort_order, trial_days, trial_expiry_action,
+                created_at, updated_at
             FROM plans 
             ORDER BY sort_order ASC, created_at ASC
             "#,
@@ -58,7 +127,8 @@ impl PlanService {
                 id, name, slug, description, 
                 price_monthly::FLOAT8 as price_monthly, 
                 price_yearly::FLOAT8 as price_yearly, 
-                is_active, is_default, sort_order, created_at, updated_at
+                is_active, is_default, sort_order, trial_days, trial_expiry_action,
+                created_at, updated_at
             FROM plans 
             WHERE is_active = true
             ORDER BY sort_order ASC, created_at ASC
@@ -89,7 +159,8 @@ impl PlanService {
                 id, name, slug, description, 
                 price_monthly::FLOAT8 as price_monthly, 
                 price_yearly::FLOAT8 as price_yearly, 
-                is_active, is_default, sort_order, created_at, updated_at
+                is_active, is_default, sort_order, trial_days, trial_expiry_action,
+                created_at, updated_at
             FROM plans 
             WHERE id = $1
             "#,
@@ -168,8 +239,8 @@ impl PlanService {
         #[cfg(feature = "postgres")]
         sqlx::query(
             r#"
-            INSERT INTO plans (id, name, slug, description, price_monthly, price_yearly, is_active, is_default, sort_order, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            INSERT INTO plans (id, name, slug, description, price_monthly, price_yearly, is_active, is_default, sort_order, trial_days, trial_expiry_action, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
             "#
         )
         .bind(&id)
@@ -181,6 +252,8 @@ impl PlanService {
         .bind(req.is_active.unwrap_or(true))
         .bind(req.is_default.unwrap_or(false))
         .bind(req.sort_order.unwrap_or(0))
+        .bind(req.trial_days.unwrap_or(0))
+        .bind(req.trial_expiry_action.as_deref().unwrap_or(TRIAL_EXPIRY_DOWNGRADE))
         .bind(now)
         .bind(now)
         .execute(&self.pool)
@@ -189,8 +262,8 @@ impl PlanService {
         #[cfg(feature = "sqlite")]
         sqlx::query(
             r#"
-            INSERT INTO plans (id, name, slug, description, price_monthly, price_yearly, is_active, is_default, sort_order, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO plans (id, name, slug, description, price_monthly, price_yearly, is_active, is_default, sort_order, trial_days, trial_expiry_action, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#
         )
         .bind(&id)
@@ -202,6 +275,8 @@ impl PlanService {
         .bind(req.is_active.unwrap_or(true))
         .bind(req.is_default.unwrap_or(false))
         .bind(req.sort_order.unwrap_or(0))
+        .bind(req.trial_days.unwrap_or(0))
+        .bind(req.trial_expiry_action.as_deref().unwrap_or(TRIAL_EXPIRY_DOWNGRADE))
         .bind(now.to_rfc3339())
         .bind(now.to_rfc3339())
         .execute(&self.pool)
@@ -220,7 +295,8 @@ impl PlanService {
                 id, name, slug, description, 
                 price_monthly::FLOAT8 as price_monthly, 
                 price_yearly::FLOAT8 as price_yearly, 
-                is_active, is_default, sort_order, created_at, updated_at
+                is_active, is_default, sort_order, trial_days, trial_expiry_action,
+                created_at, updated_at
             FROM plans 
             WHERE id = $1
             "#,
@@ -258,7 +334,9 @@ impl PlanService {
                 is_active = COALESCE($7, is_active),
                 is_default = COALESCE($8, is_default),
                 sort_order = COALESCE($9, sort_order),
-                updated_at = $10
+                trial_days = COALESCE($10, trial_days),
+                trial_expiry_action = COALESCE($11, trial_expiry_action),
+                updated_at = $12
             WHERE id = $1
             "#,
         )
@@ -271,6 +349,8 @@ impl PlanService {
         .bind(req.is_active)
         .bind(req.is_default)
         .bind(req.sort_order)
+        .bind(req.trial_days)
+        .bind(&req.trial_expiry_action)
         .bind(now)
         .execute(&self.pool)
         .await?;
@@ -283,7 +363,8 @@ impl PlanService {
                 r#"
                 UPDATE plans SET
                     name = ?, slug = ?, description = ?, price_monthly = ?, price_yearly = ?,
-                    is_active = ?, is_default = ?, sort_order = ?, updated_at = ?
+                    is_active = ?, is_default = ?, sort_order = ?, trial_days = ?,
+                    trial_expiry_action = ?, updated_at = ?
                 WHERE id = ?
                 "#,
             )
@@ -295,6 +376,12 @@ impl PlanService {
             .bind(req.is_active.unwrap_or(existing.is_active))
             .bind(req.is_default.unwrap_or(existing.is_default))
             .bind(req.sort_order.unwrap_or(existing.sort_order))
+            .bind(req.trial_days.unwrap_or(existing.trial_days))
+            .bind(
+                req.trial_expiry_action
+                    .as_ref()
+                    .unwrap_or(&existing.trial_expiry_action),
+            )
             .bind(now.to_rfc3339())
             .bind(plan_id)
             .execute(&self.pool)
@@ -531,22 +618,52 @@ impl PlanService {
         let sub = self.get_tenant_subscription_raw(tenant_id).await?;
 
         if let Some(ref s) = sub {
-            // Check for expiration
-            if let Some(end_date) = s.current_period_end {
-                if end_date < Utc::now() && s.status == "active" {
-                    // Expired! Downgrade to Free.
-                    self.downgrade_to_free(tenant_id).await?;
-                    // Return the new state
-                    return self.get_tenant_subscription_raw(tenant_id).await;
-                }
+            if has_lapsed(s, Utc::now()) {
+                self.expire_subscription(s).await?;
+                // Return the new state
+                return self.get_tenant_subscription_raw(tenant_id).await;
             }
         }
 
         Ok(sub)
     }
 
-    /// Downgrade tenant to Free plan
-    async fn downgrade_to_free(&self, tenant_id: &str) -> Result<(), sqlx::Error> {
+    /// Apply the expiry rule to a lapsed subscription. A paid period that ran
+    /// out always falls back to the free plan; an ended trial follows the
+    /// plan's `trial_expiry_action` (and locks when there is no free plan).
+    async fn expire_subscription(&self, sub: &TenantSubscription) -> Result<(), sqlx::Error> {
+        if sub.status != "trial" {
+            self.downgrade_to_free(&sub.tenant_id).await?;
+            return Ok(());
+        }
+
+        let action = self.trial_expiry_action(&sub.plan_id).await?;
+        if action == TRIAL_EXPIRY_LOCK || !self.downgrade_to_free(&sub.tenant_id).await? {
+            self.lock_subscription(&sub.tenant_id).await?;
+        }
+        Ok(())
+    }
+
+    async fn trial_expiry_action(&self, plan_id: &str) -> Result<String, sqlx::Error> {
+        #[cfg(feature = "postgres")]
+        let action: Option<String> =
+            sqlx::query_scalar("SELECT trial_expiry_action FROM plans WHERE id = $1")
+                .bind(plan_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        #[cfg(feature = "sqlite")]
+        let action: Option<String> =
+            sqlx::query_scalar("SELECT trial_expiry_action FROM plans WHERE id = ?")
+                .bind(plan_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(action.unwrap_or_else(|| TRIAL_EXPIRY_DOWNGRADE.to_string()))
+    }
+
+    /// Downgrade tenant to Free plan. Returns false when no free plan exists.
+    async fn downgrade_to_free(&self, tenant_id: &str) -> Result<bool, sqlx::Error> {
         // 1. Get Free Plan ID
         #[cfg(feature = "postgres")]
         let free_plan_id: Option<String> =
@@ -560,24 +677,52 @@ impl PlanService {
                 .fetch_optional(&self.pool)
                 .await?;
 
-        if let Some(free_id) = free_plan_id {
-            let now = Utc::now();
+        let Some(free_id) = free_plan_id else {
+            return Ok(false);
+        };
+        let now = Utc::now();
 
-            // 2. Update Subscription
-            #[cfg(feature = "postgres")]
-            sqlx::query(
-                "UPDATE tenant_subscriptions SET plan_id = $1, status = 'active', current_period_end = NULL, updated_at = $2 WHERE tenant_id = $3"
-            )
-            .bind(&free_id).bind(now).bind(tenant_id)
-            .execute(&self.pool).await?;
+        // 2. Update Subscription
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            "UPDATE tenant_subscriptions SET plan_id = $1, status = 'active', current_period_end = NULL, updated_at = $2 WHERE tenant_id = $3"
+        )
+        .bind(&free_id).bind(now).bind(tenant_id)
+        .execute(&self.pool).await?;
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query(
+            "UPDATE tenant_subscriptions SET plan_id = ?, status = 'active', current_period_end = NULL, updated_at = ? WHERE tenant_id = ?"
+        )
+        .bind(&free_id).bind(now.to_rfc3339()).bind(tenant_id)
+        .execute(&self.pool).await?;
+
+        Ok(true)
+    }
+
+    /// Keep the tenant on its plan but mark the subscription expired, which
+    /// switches every feature off until a plan is assigned again.
+    async fn lock_subscription(&self, tenant_id: &str) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            "UPDATE tenant_subscriptions SET status = 'expired', updated_at = $1 WHERE tenant_id = $2",
+        )
+        .bind(now)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query(
+            "UPDATE tenant_subscriptions SET status = 'expired', updated_at = ? WHERE tenant_id = ?",
+        )
+        .bind(now.to_rfc3339())
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
 
-            #[cfg(feature = "sqlite")]
-            sqlx::query(
-                "UPDATE tenant_subscriptions SET plan_id = ?, status = 'active', current_period_end = NULL, updated_at = ? WHERE tenant_id = ?"
-            )
-            .bind(&free_id).bind(now.to_rfc3339()).bind(tenant_id)
-            .execute(&self.pool).await?;
-        }
         Ok(())
     }
 
@@ -604,43 +749,240 @@ impl PlanService {
         .await?;
 
         // 2. Check Expiration
-        if let Some(ref s) = sub {
-            if let Some(end_date) = s.current_period_end {
-                if end_date < Utc::now() && s.status == "active" {
-                    // Downgrade Logic (Inline for transaction)
-                    let free_plan_id: Option<String> =
-                        sqlx::query_scalar("SELECT id FROM plans WHERE slug = 'free'")
-                            .fetch_optional(&mut **tx)
-                            .await?;
-
-                    if let Some(free_id) = free_plan_id {
-                        let now = Utc::now();
-                        sqlx::query(
-                            "UPDATE tenant_subscriptions SET plan_id = $1, status = 'active', current_period_end = NULL, updated_at = $2 WHERE tenant_id = $3"
-                        )
-                        .bind(free_id).bind(now).bind(tenant_id)
-                        .execute(&mut **tx).await?;
-
-                        // Refetch
-                        return sqlx::query_as(
-                            r#"
-                            SELECT 
-                                id, tenant_id, plan_id, status, trial_ends_at, 
-                                current_period_start, current_period_end, 
-                                feature_overrides::TEXT as feature_overrides, 
-                                created_at, updated_at 
-                            FROM tenant_subscriptions WHERE tenant_id = $1
-                            "#,
-                        )
-                        .bind(tenant_id)
-                        .fetch_optional(&mut **tx)
-                        .await;
-                    }
-                }
-            }
+        let Some(ref s) = sub else {
+            return Ok(sub);
+        };
+        if !has_lapsed(s, Utc::now()) {
+            return Ok(sub);
         }
 
-        Ok(sub)
+        // Expiry logic (inline for transaction, mirrors expire_subscription)
+        let is_trial = s.status == "trial";
+        let action: String = if is_trial {
+            sqlx::query_scalar("SELECT trial_expiry_action FROM plans WHERE id = $1")
+                .bind(&s.plan_id)
+                .fetch_optional(&mut **tx)
+                .await?
+                .unwrap_or_else(|| TRIAL_EXPIRY_DOWNGRADE.to_string())
+        } else {
+            TRIAL_EXPIRY_DOWNGRADE.to_string()
+        };
+
+        let free_plan_id: Option<String> = if action == TRIAL_EXPIRY_DOWNGRADE {
+            sqlx::query_scalar("SELECT id FROM plans WHERE slug = 'free'")
+                .fetch_optional(&mut **tx)
+                .await?
+        } else {
+            None
+        };
+
+        let now = Utc::now();
+        if let Some(free_id) = free_plan_id {
+            sqlx::query(
+                "UPDATE tenant_subscriptions SET plan_id = $1, status = 'active', current_period_end = NULL, updated_at = $2 WHERE tenant_id = $3"
+            )
+            .bind(free_id).bind(now).bind(tenant_id)
+            .execute(&mut **tx).await?;
+        } else if is_trial {
+            sqlx::query(
+                "UPDATE tenant_subscriptions SET status = 'expired', updated_at = $1 WHERE tenant_id = $2",
+            )
+            .bind(now)
+            .bind(tenant_id)
+            .execute(&mut **tx)
+            .await?;
+        } else {
+            return Ok(sub);
+        }
+
+        // Refetch
+        sqlx::query_as(
+            r#"
+            SELECT 
+                id, tenant_id, plan_id, status, trial_ends_at, 
+                current_period_start, current_period_end, 
+                feature_overrides::TEXT as feature_overrides, 
+                created_at, updated_at 
+            FROM tenant_subscriptions WHERE tenant_id = $1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+    }
+
+    /// Put a tenant on a plan in trial for the plan's `trial_days`. Plans
+    /// without a trial period are assigned as a regular active subscription.
+    pub async fn start_trial(
+        &self,
+        tenant_id: &str,
+        plan_id: &str,
+    ) -> Result<TenantSubscription, sqlx::Error> {
+        let plan = self.get_plan(plan_id).await?;
+        if plan.trial_days <= 0 {
+            return self.assign_plan_to_tenant(tenant_id, plan_id).await;
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        let trial_ends_at = now + Duration::days(plan.trial_days as i64);
+        // Reminders at or above the trial length would fire right away.
+        let notice_days = due_trial_notice(plan.trial_days as i64, None);
+
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_subscriptions (id, tenant_id, plan_id, status, trial_ends_at, trial_notice_days, current_period_start, current_period_end, created_at, updated_at)
+            VALUES ($1, $2, $3, 'trial', $4, $5, $6, NULL, $7, $8)
+            ON CONFLICT (tenant_id) DO UPDATE SET 
+                plan_id = $3, 
+                status = 'trial',
+                trial_ends_at = $4,
+                trial_notice_days = $5,
+                current_period_start = $6,
+                current_period_end = NULL,
+                updated_at = $8
+            "#
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(plan_id)
+        .bind(trial_ends_at)
+        .bind(notice_days)
+        .bind(now)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_subscriptions (id, tenant_id, plan_id, status, trial_ends_at, trial_notice_days, current_period_start, current_period_end, created_at, updated_at)
+            VALUES (?, ?, ?, 'trial', ?, ?, ?, NULL, ?, ?)
+            ON CONFLICT (tenant_id) DO UPDATE SET 
+                plan_id = excluded.plan_id, 
+                status = 'trial',
+                trial_ends_at = excluded.trial_ends_at,
+                trial_notice_days = excluded.trial_notice_days,
+                current_period_start = excluded.current_period_start,
+                current_period_end = NULL,
+                updated_at = excluded.updated_at
+            "#
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(plan_id)
+        .bind(trial_ends_at.to_rfc3339())
+        .bind(notice_days)
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        self.get_tenant_subscription(tenant_id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    /// Running trials ending before `cutoff`, with what the reminder emails need.
+    async fn list_trials_ending_before(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Vec<TrialSubscriptionRow>, sqlx::Error> {
+        #[cfg(feature = "postgres")]
+        let rows: Vec<TrialSubscriptionRow> = sqlx::query_as(
+            r#"
+            SELECT ts.tenant_id, t.name AS tenant_name, p.name AS plan_name,
+                   p.trial_expiry_action, ts.trial_ends_at, ts.trial_notice_days
+            FROM tenant_subscriptions ts
+            JOIN tenants t ON t.id = ts.tenant_id
+            JOIN plans p ON p.id = ts.plan_id
+            WHERE ts.status = 'trial' AND ts.trial_ends_at IS NOT NULL AND ts.trial_ends_at <= $1
+            ORDER BY ts.trial_ends_at ASC
+            "#,
+        )
+        .bind(cutoff)
+        .fetch_all(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let rows: Vec<TrialSubscriptionRow> = sqlx::query_as(
+            r#"
+            SELECT ts.tenant_id, t.name AS tenant_name, p.name AS plan_name,
+                   p.trial_expiry_action, ts.trial_ends_at, ts.trial_notice_days
+            FROM tenant_subscriptions ts
+            JOIN tenants t ON t.id = ts.tenant_id
+            JOIN plans p ON p.id = ts.plan_id
+            WHERE ts.status = 'trial' AND ts.trial_ends_at IS NOT NULL AND ts.trial_ends_at <= ?
+            ORDER BY ts.trial_ends_at ASC
+            "#,
+        )
+        .bind(cutoff.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn mark_trial_notice(&self, tenant_id: &str, days: i32) -> Result<(), sqlx::Error> {
+        #[cfg(feature = "postgres")]
+        sqlx::query(
+            "UPDATE tenant_subscriptions SET trial_notice_days = $1 WHERE tenant_id = $2 AND status = 'trial'",
+        )
+        .bind(days)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        sqlx::query(
+            "UPDATE tenant_subscriptions SET trial_notice_days = ? WHERE tenant_id = ? AND status = 'trial'",
+        )
+        .bind(days)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Emails of the tenant's owners and admins, who get the trial reminders.
+    async fn tenant_admin_emails(&self, tenant_id: &str) -> Result<Vec<String>, sqlx::Error> {
+        #[cfg(feature = "postgres")]
+        let emails: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT u.email
+            FROM tenant_members tm
+            JOIN users u ON u.id = tm.user_id
+            WHERE tm.tenant_id = $1
+              AND LOWER(COALESCE(tm.role, '')) IN ('owner', 'admin')
+              AND u.is_active = true
+              AND u.deleted_at IS NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let emails: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT u.email
+            FROM tenant_members tm
+            JOIN users u ON u.id = tm.user_id
+            WHERE tm.tenant_id = ?
+              AND LOWER(COALESCE(tm.role, '')) IN ('owner', 'admin')
+              AND u.is_active = 1
+              AND u.deleted_at IS NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(emails)
     }
 
     /// Assign a plan to a tenant
@@ -751,6 +1093,10 @@ impl PlanService {
             }
         };
 
+        if subscription.status == "expired" {
+            return Ok(locked_feature_access(feature_code, feature));
+        }
+
         // Check for feature override in subscription
         if let Some(ref overrides_json) = subscription.feature_overrides {
             if let Ok(overrides) = serde_json::from_str::<serde_json::Value>(overrides_json) {
@@ -825,6 +1171,15 @@ impl PlanService {
         Ok(access.value.parse::<i64>().ok())
     }
 
+    /// Whether the tenant's plan only flags quota overages instead of
+    /// blocking them (`quota_enforcement` = "soft").
+    pub async fn soft_quota(&self, tenant_id: &str) -> Result<bool, sqlx::Error> {
+        let access = self
+            .check_feature_access(tenant_id, "quota_enforcement")
+            .await?;
+        Ok(access.value.trim().eq_ignore_ascii_case("soft"))
+    }
+
     /// Get detailed subscription info for dashboard (Usage vs Limits)
     pub async fn get_tenant_subscription_details(
         &self,
@@ -833,15 +1188,22 @@ impl PlanService {
         // 1. Get Subscription & Plan
         let sub = self.get_tenant_subscription(tenant_id).await?;
 
-        let (plan_name, plan_slug, status, period_end) = if let Some(s) = sub {
+        let (plan_name, plan_slug, status, trial_ends_at, period_end) = if let Some(s) = sub {
             let plan = self.get_plan(&s.plan_id).await?;
-            (plan.name, plan.slug, s.status, s.current_period_end)
+            (
+                plan.name,
+                plan.slug,
+                s.status,
+                s.trial_ends_at,
+                s.current_period_end,
+            )
         } else {
             (
                 "Free".to_string(),
                 "free".to_string(),
                 "active".to_string(),
                 None,
+                None,
             )
         };
 
@@ -886,6 +1248,7 @@ impl PlanService {
             plan_name,
             plan_slug,
             status,
+            trial_ends_at,
             current_period_end: period_end,
             storage_usage,
             storage_limit,
@@ -959,6 +1322,10 @@ impl PlanService {
             }
         };
 
+        if subscription.status == "expired" {
+            return Ok(locked_feature_access(feature_code, feature));
+        }
+
         // Check for feature override in subscription
         if let Some(ref overrides_json) = subscription.feature_overrides {
             if let Ok(overrides) = serde_json::from_str::<serde_json::Value>(overrides_json) {
@@ -1064,6 +1431,38 @@ impl PlanService {
                 "support",
                 "Standard",
             ),
+            (
+                "max_customers",
+                "Maximum Customers",
+                "Maximum number of ISP customers",
+                "number",
+                "limits",
+                "unlimited",
+            ),
+            (
+                "max_routers",
+                "Maximum Routers",
+                "Maximum number of MikroTik routers",
+                "number",
+                "limits",
+                "unlimited",
+            ),
+            (
+                "max_emails_per_month",
+                "Emails per Month",
+                "Outgoing emails per calendar month",
+                "number",
+                "limits",
+                "unlimited",
+            ),
+            (
+                "quota_enforcement",
+                "Quota Enforcement",
+                "hard: block actions over a limit; soft: allow them and flag the overage",
+                "text",
+                "limits",
+                "hard",
+            ),
         ];
 
         for (i, (code, name, description, value_type, category, default_value)) in
@@ -1115,3 +1514,232 @@ impl PlanService {
         Ok(())
     }
 }
+
+/// Hourly job for plan trials: sends the pre-expiry reminders to tenant owners
+/// and admins, and applies the expiry rule to trials that have ended so the
+/// tenant does not keep full access until someone happens to load its plan.
+#[derive(Clone)]
+pub struct PlanTrialScheduler {
+    plan_service: PlanService,
+    email_outbox: EmailOutboxService,
+}
+
+impl PlanTrialScheduler {
+    pub fn new(plan_service: PlanService, email_outbox: EmailOutboxService) -> Self {
+        Self {
+            plan_service,
+            email_outbox,
+        }
+    }
+
+    pub async fn schedule(self, queue: &JobQueue) {
+        queue
+            .register_recurring(
+                "plans.check_trials",
+                std::time::Duration::from_secs(3600),
+                Arc::new(self),
+            )
+            .await;
+    }
+
+    async fn run_once(&self, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        let longest = TRIAL_NOTICE_DAYS.iter().copied().max().unwrap_or(0) as i64;
+        let trials = self
+            .plan_service
+            .list_trials_ending_before(now + Duration::days(longest))
+            .await?;
+
+        let mut expired = 0;
+        for trial in trials {
+            if trial.trial_ends_at <= now {
+                // Reading the subscription applies the expiry rule.
+                let sub = self
+                    .plan_service
+                    .get_tenant_subscription(&trial.tenant_id)
+                    .await?;
+                let locked = sub.is_some_and(|s| s.status == "expired");
+                self.notify_expired(&trial, locked).await;
+                expired += 1;
+                continue;
+            }
+
+            let days_left = days_until(trial.trial_ends_at, now);
+            let Some(notice) = due_trial_notice(days_left, trial.trial_notice_days) else {
+                continue;
+            };
+            self.notify_ending(&trial, days_left).await;
+            self.plan_service
+                .mark_trial_notice(&trial.tenant_id, notice)
+                .await?;
+        }
+
+        if expired > 0 {
+            info!("Plan trial check expired {} trial(s)", expired);
+        }
+        Ok(())
+    }
+
+    async fn notify_ending(&self, trial: &TrialSubscriptionRow, days_left: i64) {
+        let subject = if days_left <= 1 {
+            format!("Your {} trial ends tomorrow", trial.plan_name)
+        } else {
+            format!("Your {} trial ends in {} days", trial.plan_name, days_left)
+        };
+        let after = if trial.trial_expiry_action == TRIAL_EXPIRY_LOCK {
+            "its features will be locked until a plan is chosen"
+        } else {
+            "it will move to the Free plan"
+        };
+        let body = format!(
+            "The {} trial for {} ends on {}. Afterwards {}.\n\nChoose a plan from the subscription page to keep your current features.",
+            trial.plan_name,
+            trial.tenant_name,
+            trial.trial_ends_at.format("%Y-%m-%d %H:%M UTC"),
+            after
+        );
+        self.send_to_admins(&trial.tenant_id, &subject, &body).await;
+    }
+
+    async fn notify_expired(&self, trial: &TrialSubscriptionRow, locked: bool) {
+        let subject = format!("Your {} trial has ended", trial.plan_name);
+        let outcome = if locked {
+            "Its features are locked until a plan is chosen."
+        } else {
+            "It has been moved to the Free plan."
+        };
+        let body = format!(
+            "The {} trial for {} ended on {}. {}\n\nChoose a plan from the subscription page to continue.",
+            trial.plan_name,
+            trial.tenant_name,
+            trial.trial_ends_at.format("%Y-%m-%d %H:%M UTC"),
+            outcome
+        );
+        self.send_to_admins(&trial.tenant_id, &subject, &body).await;
+    }
+
+    async fn send_to_admins(&self, tenant_id: &str, subject: &str, body: &str) {
+        let emails = match self.plan_service.tenant_admin_emails(tenant_id).await {
+            Ok(emails) => emails,
+            Err(e) => {
+                warn!(
+                    "Trial notice: failed to load admins of {}: {}",
+                    tenant_id, e
+                );
+                return;
+            }
+        };
+
+        // Platform mail (global email settings), so it is neither sent through
+        // the tenant's own SMTP nor counted against its email quota.
+        for email in emails {
+            if let Err(e) = self
+                .email_outbox
+                .send_or_enqueue(None, &email, subject, body)
+                .await
+            {
+                warn!("Trial notice to {} failed: {}", email, e);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl JobHandler for PlanTrialScheduler {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        Ok(self.run_once(Utc::now()).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(status: &str) -> TenantSubscription {
+        let now = Utc::now();
+        TenantSubscription {
+            id: "sub-1".to_string(),
+            tenant_id: "tenant-1".to_string(),
+            plan_id: "plan-1".to_string(),
+            status: status.to_string(),
+            trial_ends_at: None,
+            current_period_start: Some(now),
+            current_period_end: None,
+            feature_overrides: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn lapsed_checks_the_date_matching_the_status() {
+        let now = Utc::now();
+        let past = now - Duration::hours(1);
+
+        let mut trial = subscription("trial");
+        assert!(!has_lapsed(&trial, now));
+        trial.current_period_end = Some(past);
+        assert!(!has_lapsed(&trial, now));
+        trial.trial_ends_at = Some(past);
+        assert!(has_lapsed(&trial, now));
+
+        let mut active = subscription("active");
+        active.trial_ends_at = Some(past);
+        assert!(!has_lapsed(&active, now));
+        active.current_period_end = Some(past);
+        assert!(has_lapsed(&active, now));
+
+        let mut expired = subscription("expired");
+        expired.trial_ends_at = Some(past);
+        assert!(!has_lapsed(&expired, now));
+    }
+
+    #[test]
+    fn trial_notices_are_sent_once_per_threshold() {
+        assert_eq!(due_trial_notice(14, None), None);
+        assert_eq!(due_trial_notice(7, None), Some(7));
+        assert_eq!(due_trial_notice(5, Some(7)), None);
+        assert_eq!(due_trial_notice(3, Some(7)), Some(3));
+        assert_eq!(due_trial_notice(1, Some(3)), Some(1));
+        assert_eq!(due_trial_notice(1, Some(1)), None);
+        // A job that missed the 3-day window jumps straight to the latest one.
+        assert_eq!(due_trial_notice(1, Some(7)), Some(1));
+    }
+
+    #[test]
+    fn days_until_rounds_up() {
+        let now = Utc::now();
+        assert_eq!(days_until(now + Duration::hours(20), now), 1);
+        assert_eq!(days_until(now + Duration::days(3), now), 3);
+        assert_eq!(
+            days_until(now + Duration::days(3) + Duration::minutes(1), now),
+            4
+        );
+        assert_eq!(days_until(now - Duration::hours(1), now), 0);
+    }
+
+    #[test]
+    fn locked_features_read_as_off() {
+        let feature = |value_type: &str, default_value: &str| FeatureDefinition {
+            id: "f-1".to_string(),
+            code: "x".to_string(),
+            name: "X".to_string(),
+            description: None,
+            value_type: value_type.to_string(),
+            category: "limits".to_string(),
+            default_value: default_value.to_string(),
+            sort_order: 0,
+            created_at: Utc::now(),
+        };
+
+        let access = locked_feature_access("max_users", feature("number", "5"));
+        assert!(!access.has_access);
+        assert_eq!(access.value, "0");
+
+        let access = locked_feature_access("api_access", feature("boolean", "true"));
+        assert_eq!(access.value, "false");
+
+        let access = locked_feature_access("support_level", feature("text", "Standard"));
+        assert!(!access.has_access);
+        assert_eq!(access.value, "Standard");
+    }
+}
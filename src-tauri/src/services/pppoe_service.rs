@@ -1,22 +1,90 @@
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    CreatePppoeAccountRequest, PaginatedResponse, PppoeAccount, PppoeAccountPublic,
-    PppoeImportAction, PppoeImportCandidate, PppoeImportError, PppoeImportFromRouterRequest,
-    PppoeImportResult, UpdatePppoeAccountRequest,
+    BackgroundJob, CreatePppoeAccountRequest, CustomerUsage, FairUsagePolicy, IspPackageBandwidth,
+    PaginatedResponse, PppoeAccount, PppoeAccountPublic, PppoeAccountUsage, PppoeImportAction,
+    PppoeImportCandidate, PppoeImportError, PppoeImportFromRouterRequest, PppoeImportResult,
+    UpdatePppoeAccountRequest, UsagePoint,
 };
 use crate::security::secret::{decrypt_secret_opt, decrypt_secret_opt_for, encrypt_secret_for};
-use crate::services::{AuditService, AuthService, SettingsService};
-use chrono::Utc;
+use crate::services::{
+    AuditService, AuthService, IspPackageService, JobHandler, JobQueue, SettingsService,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc};
 use mikrotik_rs::{protocol::command::CommandBuilder, protocol::CommandResponse, MikrotikDevice};
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Instant;
 use tokio::time::{timeout, Duration};
 use uuid::Uuid;
 
-const PURPOSE_PPPOE: &str = "pppoe_secrets";
+pub(crate) const PURPOSE_PPPOE: &str = "pppoe_secrets";
 const IMPORT_PLACEHOLDER_CUSTOMER_NAME: &str = "Imported (Unassigned)";
 const IMPORT_PLACEHOLDER_LOCATION_LABEL: &str = "Unassigned";
 
+/// Appended to the account's profile name for the fair-usage profile.
+const FUP_PROFILE_SUFFIX: &str = "-fup";
+const BYTES_PER_GB: i64 = 1024 * 1024 * 1024;
+
+/// Growth of a session's byte counters since the previous poll. A new session
+/// (or counters that went backwards after a router reboot) counts from zero.
+fn counter_growth(previous: Option<(&str, i64, i64)>, key: &str, up: i64, down: i64) -> (i64, i64) {
+    match previous {
+        Some((prev_key, prev_up, prev_down))
+            if prev_key == key && up >= prev_up && down >= prev_down =>
+        {
+            (up - prev_up, down - prev_down)
+        }
+        _ => (up.max(0), down.max(0)),
+    }
+}
+
+/// `day` of the given month, or its last day when the month is shorter.
+fn clamped_date(year: i32, month: u32, day: u32) -> NaiveDate {
+    let mut day = day.clamp(1, 31);
+    loop {
+        if let Some(date) = NaiveDate::from_ymd_opt(year, month, day) {
+            return date;
+        }
+        day -= 1;
+    }
+}
+
+/// Start of the monthly usage period containing `now`. Periods begin on the
+/// day of month the subscription started, or on the 1st without one.
+fn usage_period_start(anchor: Option<DateTime<Utc>>, now: DateTime<Utc>) -> DateTime<Utc> {
+    let day = anchor.map(|a| a.day()).unwrap_or(1);
+    let mut start = clamped_date(now.year(), now.month(), day);
+    if start > now.date_naive() {
+        let (year, month) = if now.month() == 1 {
+            (now.year() - 1, 12)
+        } else {
+            (now.year(), now.month() - 1)
+        };
+        start = clamped_date(year, month, day);
+    }
+    Utc.from_utc_datetime(&start.and_hms_opt(0, 0, 0).unwrap_or_default())
+}
+
+/// Chart bucket for a usage window of `days`: hours up to two days, days
+/// beyond that.
+fn usage_bucket(days: u32) -> &'static str {
+    if days <= 2 {
+        "hour"
+    } else {
+        "day"
+    }
+}
+
+/// The throttle state an account should move to, or `None` to leave it.
+/// Accounts are released when the period resets (usage starts at zero) or
+/// the package no longer has a quota.
+fn fup_transition(quota_gb: Option<i32>, used_bytes: i64, throttled: bool) -> Option<bool> {
+    let over = quota_gb.is_some_and(|q| used_bytes >= i64::from(q) * BYTES_PER_GB);
+    (over != throttled).then_some(over)
+}
+
 #[derive(Debug, Clone)]
 struct RouterSecretRow {
     username: String,
@@ -104,7 +172,9 @@ impl PppoeService {
 
     async fn ensure_router_access(&self, tenant_id: &str, router_id: &str) -> AppResult<()> {
         let exists: Option<String> =
-            sqlx::query_scalar("SELECT id FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2")
+            sqlx::query_scalar(
+                "SELECT id FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL",
+            )
                 .bind(router_id)
                 .bind(tenant_id)
                 .fetch_optional(&self.pool)
@@ -144,7 +214,7 @@ impl PppoeService {
 
     async fn connect_router(&self, tenant_id: &str, router_id: &str) -> AppResult<MikrotikDevice> {
         let row = sqlx::query_as::<_, crate::models::MikrotikRouter>(
-            "SELECT * FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2",
+            "SELECT * FROM mikrotik_routers WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL",
         )
         .bind(router_id)
         .bind(tenant_id)
@@ -275,13 +345,14 @@ impl PppoeService {
     async fn ensure_import_placeholder(&self, tenant_id: &str) -> AppResult<(String, String)> {
         let now = Utc::now();
 
-        let existing_customer: Option<String> =
-            sqlx::query_scalar("SELECT id FROM customers WHERE tenant_id = $1 AND name = $2")
-                .bind(tenant_id)
-                .bind(IMPORT_PLACEHOLDER_CUSTOMER_NAME)
-                .fetch_optional(&self.pool)
-                .await
-                .map_err(AppError::Database)?;
+        let existing_customer: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM customers WHERE tenant_id = $1 AND name = $2 AND deleted_at IS NULL",
+        )
+        .bind(tenant_id)
+        .bind(IMPORT_PLACEHOLDER_CUSTOMER_NAME)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
 
         let customer_id = if let Some(id) = existing_customer {
             id
@@ -349,6 +420,117 @@ impl PppoeService {
         Ok((customer_id, location_id))
     }
 
+    /// The package `rate-limit` for its mapped profile on this router, with the
+    /// mapping's burst overrides applied. Other profiles are left as they are.
+    async fn package_rate_limit(
+        &self,
+        tenant_id: &str,
+        router_id: &str,
+        package_id: &str,
+        profile_name: &str,
+    ) -> AppResult<Option<String>> {
+        let bandwidth: Option<IspPackageBandwidth> = sqlx::query_as(
+            r#"
+            SELECT
+              p.rate_limit_up_kbps,
+              p.rate_limit_down_kbps,
+              COALESCE(m.burst_limit_up_kbps, p.burst_limit_up_kbps) AS burst_limit_up_kbps,
+              COALESCE(m.burst_limit_down_kbps, p.burst_limit_down_kbps) AS burst_limit_down_kbps,
+              COALESCE(m.burst_threshold_up_kbps, p.burst_threshold_up_kbps) AS burst_threshold_up_kbps,
+              COALESCE(m.burst_threshold_down_kbps, p.burst_threshold_down_kbps) AS burst_threshold_down_kbps,
+              COALESCE(m.burst_time_secs, p.burst_time_secs) AS burst_time_secs,
+              COALESCE(m.burst_priority, p.burst_priority) AS burst_priority
+            FROM isp_packages p
+            JOIN isp_package_router_mappings m
+              ON m.tenant_id = p.tenant_id AND m.package_id = p.id AND m.router_id = $3
+            WHERE p.tenant_id = $1 AND p.id = $2 AND m.router_profile_name = $4
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(package_id)
+        .bind(router_id)
+        .bind(profile_name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(bandwidth
+            .as_ref()
+            .and_then(IspPackageService::mikrotik_rate_limit))
+    }
+
+    /// The throttled `rate-limit` of a package with a fair-usage policy.
+    async fn package_fup_rate_limit(
+        &self,
+        tenant_id: &str,
+        package_id: &str,
+    ) -> AppResult<Option<String>> {
+        let fup: Option<FairUsagePolicy> = sqlx::query_as(
+            r#"
+            SELECT fup_quota_gb, fup_rate_up_kbps, fup_rate_down_kbps
+            FROM isp_packages
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(package_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(fup.as_ref().and_then(IspPackageService::fup_rate_limit))
+    }
+
+    /// Sets `rate-limit` on a PPP profile, adding the profile if the router lacks it.
+    async fn router_set_profile_rate_limit(
+        &self,
+        dev: &MikrotikDevice,
+        profile_name: &str,
+        rate_limit: &str,
+    ) -> Result<(), anyhow::Error> {
+        let cmd = CommandBuilder::new().command("/ppp/profile/print").build();
+        let mut rx = dev.send_command(cmd).await?;
+        let mut existing: Option<String> = None;
+        while let Some(res) = rx.recv().await {
+            match res? {
+                CommandResponse::Reply(reply) => {
+                    let name = reply.attributes.get("name").and_then(|v| v.clone());
+                    if name.as_deref() == Some(profile_name) {
+                        existing = reply.attributes.get(".id").and_then(|v| v.clone());
+                    }
+                }
+                CommandResponse::Done(_) => break,
+                _ => {}
+            }
+        }
+
+        let b = match existing.as_deref() {
+            Some(id) => CommandBuilder::new()
+                .command("/ppp/profile/set")
+                .attribute("numbers", Some(id)),
+            None => CommandBuilder::new()
+                .command("/ppp/profile/add")
+                .attribute("name", Some(profile_name)),
+        };
+        let cmd = b.attribute("rate-limit", Some(rate_limit)).build();
+        let mut rx = dev.send_command(cmd).await?;
+        while let Some(res) = rx.recv().await {
+            match res? {
+                CommandResponse::Done(_) => break,
+                CommandResponse::Trap(trap) => {
+                    return Err(anyhow::anyhow!(
+                        "profile {} rejected rate-limit {}: {:?}",
+                        profile_name,
+                        rate_limit,
+                        trap
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
     async fn router_add_or_set_secret(
         &self,
         dev: &MikrotikDevice,
@@ -1305,8 +1487,42 @@ impl PppoeService {
             None
         };
 
-        let res = self
-            .router_add_or_set_secret(
+        // A throttled account moves to its own profile with the fair-usage speeds.
+        let fup_rate_limit = match (account.fup_throttled_at, account.package_id.as_deref()) {
+            (Some(_), Some(package_id)) => {
+                self.package_fup_rate_limit(tenant_id, package_id).await?
+            }
+            _ => None,
+        };
+
+        // Package speeds go onto the profile before the secret points at it.
+        let (profile_name, rate_limit) = match fup_rate_limit {
+            Some(fup_rate_limit) => (
+                Some(format!(
+                    "{}{}",
+                    profile_name.as_deref().unwrap_or("default"),
+                    FUP_PROFILE_SUFFIX
+                )),
+                Some(fup_rate_limit),
+            ),
+            None => {
+                let rate_limit = match (profile_name.as_deref(), account.package_id.as_deref()) {
+                    (Some(profile), Some(package_id)) => {
+                        self.package_rate_limit(tenant_id, &account.router_id, package_id, profile)
+                            .await?
+                    }
+                    _ => None,
+                };
+                (profile_name, rate_limit)
+            }
+        };
+
+        let res = async {
+            if let (Some(profile), Some(rate_limit)) = (profile_name.as_deref(), rate_limit) {
+                self.router_set_profile_rate_limit(&dev, profile, &rate_limit)
+                    .await?;
+            }
+            self.router_add_or_set_secret(
                 &dev,
                 account.username.as_str(),
                 password.as_str(),
@@ -1316,7 +1532,9 @@ impl PppoeService {
                 account.disabled,
                 account.comment.as_deref(),
             )
-            .await;
+            .await
+        }
+        .await;
 
         let now = Utc::now();
         match res {
@@ -1479,4 +1697,640 @@ impl PppoeService {
             "router_total": router_usernames.len() as i64
         }))
     }
+
+    // ========================
+    // Usage accounting & fair-usage policy
+    // ========================
+
+    /// Reads PPPoE session counters and checks fair-usage quotas every 15
+    /// minutes (`PPPOE_USAGE_POLL_INTERVAL_SECS`), so an account is throttled
+    /// within one poll of going over its quota.
+    pub async fn schedule_usage_jobs(&self, queue: &JobQueue) {
+        let interval_secs = std::env::var("PPPOE_USAGE_POLL_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v >= 60 && *v <= 3600)
+            .unwrap_or(900);
+
+        queue
+            .register_recurring(
+                "pppoe.usage_collect",
+                Duration::from_secs(interval_secs),
+                Arc::new(PppoeUsageJob(self.clone())),
+            )
+            .await;
+    }
+
+    /// Byte counters of the active sessions by username, from the dynamic
+    /// `<pppoe-username>` interfaces: `(.id, upload, download)`.
+    async fn router_session_counters(
+        &self,
+        dev: &MikrotikDevice,
+    ) -> Result<HashMap<String, (String, i64, i64)>, anyhow::Error> {
+        let cmd = CommandBuilder::new().command("/interface/print").build();
+        let mut rx = dev.send_command(cmd).await?;
+        let mut out = HashMap::new();
+        while let Some(res) = rx.recv().await {
+            match res? {
+                CommandResponse::Reply(reply) => {
+                    let attr = |k: &str| reply.attributes.get(k).and_then(|v| v.clone());
+                    if attr("type").as_deref() != Some("pppoe-in") {
+                        continue;
+                    }
+                    let Some(username) = attr("name").and_then(|n| {
+                        n.strip_prefix("<pppoe-")
+                            .and_then(|n| n.strip_suffix('>'))
+                            .map(str::to_string)
+                    }) else {
+                        continue;
+                    };
+                    let bytes = |k: &str| attr(k).and_then(|v| v.parse::<i64>().ok()).unwrap_or(0);
+                    // The router receives what the subscriber uploads.
+                    let key = attr(".id").unwrap_or_default();
+                    out.insert(username, (key, bytes("rx-byte"), bytes("tx-byte")));
+                }
+                CommandResponse::Done(_) => break,
+                _ => {}
+            }
+        }
+        Ok(out)
+    }
+
+    /// Adds the traffic of active sessions since the previous poll to each
+    /// account's current usage period, and records it in `usage_records` for
+    /// the usage charts. Traffic of a session that ended between two polls
+    /// after the last read is not counted.
+    pub async fn collect_usage(&self) -> AppResult<()> {
+        #[derive(sqlx::FromRow)]
+        struct AccountRow {
+            id: String,
+            tenant_id: String,
+            router_id: String,
+            customer_id: String,
+            username: String,
+            period_anchor: Option<DateTime<Utc>>,
+        }
+
+        #[derive(sqlx::FromRow)]
+        struct SessionRow {
+            account_id: String,
+            session_key: Option<String>,
+            session_upload_bytes: Option<i64>,
+            session_download_bytes: Option<i64>,
+        }
+
+        let accounts: Vec<AccountRow> = sqlx::query_as(
+            r#"
+            SELECT
+              a.id,
+              a.tenant_id,
+              a.router_id,
+              a.customer_id,
+              a.username,
+              (
+                SELECT s.starts_at FROM customer_subscriptions s
+                WHERE s.tenant_id = a.tenant_id AND s.location_id = a.location_id AND s.status = 'active'
+                ORDER BY COALESCE(s.package_id = a.package_id, false) DESC, s.updated_at DESC
+                LIMIT 1
+              ) AS period_anchor
+            FROM pppoe_accounts a
+            JOIN mikrotik_routers r ON r.id = a.router_id
+            WHERE r.enabled = true AND r.deleted_at IS NULL AND a.disabled = false
+            ORDER BY a.router_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let mut by_router: HashMap<(String, String), Vec<AccountRow>> = HashMap::new();
+        for account in accounts {
+            by_router
+                .entry((account.tenant_id.clone(), account.router_id.clone()))
+                .or_default()
+                .push(account);
+        }
+
+        let now = Utc::now();
+        for ((tenant_id, router_id), accounts) in by_router {
+            let counters = match self.connect_router(&tenant_id, &router_id).await {
+                Ok(dev) => self.router_session_counters(&dev).await,
+                Err(e) => Err(anyhow::anyhow!(e.to_string())),
+            };
+            let counters = match counters {
+                Ok(c) => c,
+                Err(e) => {
+                    tracing::warn!("[PppoeUsage] router {} skipped: {}", router_id, e);
+                    continue;
+                }
+            };
+
+            let ids: Vec<String> = accounts.iter().map(|a| a.id.clone()).collect();
+            let previous: HashMap<String, SessionRow> = sqlx::query_as::<_, SessionRow>(
+                r#"
+                SELECT DISTINCT ON (account_id)
+                  account_id, session_key, session_upload_bytes, session_download_bytes
+                FROM pppoe_usage_periods
+                WHERE account_id = ANY($1)
+                ORDER BY account_id, period_start DESC
+                "#,
+            )
+            .bind(&ids)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::Database)?
+            .into_iter()
+            .map(|r| (r.account_id.clone(), r))
+            .collect();
+
+            for account in accounts {
+                let Some((key, up, down)) = counters.get(&account.username) else {
+                    continue;
+                };
+                let prev = previous.get(&account.id).and_then(|r| {
+                    Some((
+                        r.session_key.as_deref()?,
+                        r.session_upload_bytes?,
+                        r.session_download_bytes?,
+                    ))
+                });
+                let (grown_up, grown_down) = counter_growth(prev, key, *up, *down);
+                let period_start = usage_period_start(account.period_anchor, now);
+
+                sqlx::query(
+                    r#"
+                    INSERT INTO pppoe_usage_periods
+                      (id, tenant_id, account_id, period_start, upload_bytes, download_bytes,
+                       session_key, session_upload_bytes, session_download_bytes, created_at, updated_at)
+                    VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$10)
+                    ON CONFLICT (account_id, period_start) DO UPDATE SET
+                      upload_bytes = pppoe_usage_periods.upload_bytes + EXCLUDED.upload_bytes,
+                      download_bytes = pppoe_usage_periods.download_bytes + EXCLUDED.download_bytes,
+                      session_key = EXCLUDED.session_key,
+                      session_upload_bytes = EXCLUDED.session_upload_bytes,
+                      session_download_bytes = EXCLUDED.session_download_bytes,
+                      updated_at = EXCLUDED.updated_at
+                    "#,
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(&account.tenant_id)
+                .bind(&account.id)
+                .bind(period_start)
+                .bind(grown_up)
+                .bind(grown_down)
+                .bind(key)
+                .bind(up)
+                .bind(down)
+                .bind(now)
+                .execute(&self.pool)
+                .await
+                .map_err(AppError::Database)?;
+
+                if grown_up == 0 && grown_down == 0 {
+                    continue;
+                }
+                sqlx::query(
+                    r#"
+                    INSERT INTO usage_records
+                      (id, tenant_id, account_id, customer_id, ts, upload_bytes, download_bytes)
+                    VALUES ($1,$2,$3,$4,$5,$6,$7)
+                    "#,
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(&account.tenant_id)
+                .bind(&account.id)
+                .bind(&account.customer_id)
+                .bind(now)
+                .bind(grown_up)
+                .bind(grown_down)
+                .execute(&self.pool)
+                .await
+                .map_err(AppError::Database)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Throttles accounts that went over their package's fair-usage quota in
+    /// the current period and releases the ones whose period has reset. The
+    /// active session is dropped so the client reconnects on the new profile.
+    pub async fn enforce_fair_usage(&self) -> AppResult<()> {
+        #[derive(sqlx::FromRow)]
+        struct FupRow {
+            id: String,
+            tenant_id: String,
+            router_id: String,
+            username: String,
+            fup_throttled_at: Option<DateTime<Utc>>,
+            fup_quota_gb: Option<i32>,
+            period_anchor: Option<DateTime<Utc>>,
+        }
+
+        self.collect_usage().await?;
+
+        let rows: Vec<FupRow> = sqlx::query_as(
+            r#"
+            SELECT
+              a.id,
+              a.tenant_id,
+              a.router_id,
+              a.username,
+              a.fup_throttled_at,
+              p.fup_quota_gb,
+              (
+                SELECT s.starts_at FROM customer_subscriptions s
+                WHERE s.tenant_id = a.tenant_id AND s.location_id = a.location_id AND s.status = 'active'
+                ORDER BY COALESCE(s.package_id = a.package_id, false) DESC, s.updated_at DESC
+                LIMIT 1
+              ) AS period_anchor
+            FROM pppoe_accounts a
+            LEFT JOIN isp_packages p ON p.tenant_id = a.tenant_id AND p.id = a.package_id
+            WHERE a.fup_throttled_at IS NOT NULL
+               OR (p.fup_quota_gb IS NOT NULL AND p.fup_rate_up_kbps IS NOT NULL)
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        let ids: Vec<String> = rows.iter().map(|r| r.id.clone()).collect();
+        let usage: HashMap<(String, DateTime<Utc>), i64> =
+            sqlx::query_as::<_, (String, DateTime<Utc>, i64)>(
+                r#"
+            SELECT account_id, period_start, upload_bytes + download_bytes
+            FROM pppoe_usage_periods
+            WHERE account_id = ANY($1) AND period_start >= $2
+            "#,
+            )
+            .bind(&ids)
+            .bind(now - chrono::Duration::days(62))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::Database)?
+            .into_iter()
+            .map(|(id, start, used)| ((id, start), used))
+            .collect();
+
+        for row in rows {
+            let period_start = usage_period_start(row.period_anchor, now);
+            let used = usage
+                .get(&(row.id.clone(), period_start))
+                .copied()
+                .unwrap_or(0);
+            let Some(throttle) =
+                fup_transition(row.fup_quota_gb, used, row.fup_throttled_at.is_some())
+            else {
+                continue;
+            };
+
+            sqlx::query(
+                "UPDATE pppoe_accounts SET fup_throttled_at = $1, updated_at = $2 WHERE tenant_id = $3 AND id = $4",
+            )
+            .bind(throttle.then_some(now))
+            .bind(now)
+            .bind(&row.tenant_id)
+            .bind(&row.id)
+            .execute(&self.pool)
+            .await
+            .map_err(AppError::Database)?;
+
+            let applied = self.apply_account_internal(&row.tenant_id, &row.id).await;
+            if applied.as_ref().is_ok_and(|a| a.last_error.is_none()) {
+                if let Err(e) = self
+                    .drop_active_session(&row.tenant_id, &row.router_id, &row.username)
+                    .await
+                {
+                    tracing::warn!("[PppoeFup] {} keeps its session: {}", row.username, e);
+                }
+            }
+
+            let (action, details) = if throttle {
+                (
+                    "PPPOE_FUP_THROTTLE",
+                    format!(
+                        "Throttled {} after {:.1} GB of {} GB fair-usage quota",
+                        row.username,
+                        used as f64 / BYTES_PER_GB as f64,
+                        row.fup_quota_gb.unwrap_or_default()
+                    ),
+                )
+            } else {
+                (
+                    "PPPOE_FUP_RELEASE",
+                    format!("Released {} from fair-usage speeds", row.username),
+                )
+            };
+            self.audit_service
+                .log(
+                    None,
+                    Some(&row.tenant_id),
+                    action,
+                    "pppoe",
+                    Some(&row.id),
+                    Some(&details),
+                    None,
+                )
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Staff with `customers:read`, or a portal user linked to the customer.
+    async fn require_customer_usage_access(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        customer_id: &str,
+    ) -> AppResult<()> {
+        if self
+            .auth_service
+            .has_permission(actor_id, tenant_id, "customers", "read")
+            .await?
+        {
+            return Ok(());
+        }
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "customers", "read_own")
+            .await?;
+
+        let linked: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM customer_users WHERE tenant_id = $1 AND user_id = $2 AND customer_id = $3)",
+        )
+        .bind(tenant_id)
+        .bind(actor_id)
+        .bind(customer_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+        if linked {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(
+                "You can only view your own usage".to_string(),
+            ))
+        }
+    }
+
+    /// Traffic of a customer's PPPoE accounts over the last `days` (default
+    /// 30, at most 365), and where each account stands against its quota.
+    pub async fn customer_usage(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        customer_id: &str,
+        days: Option<u32>,
+    ) -> AppResult<CustomerUsage> {
+        #[derive(sqlx::FromRow)]
+        struct AccountRow {
+            id: String,
+            username: String,
+            package_name: Option<String>,
+            fup_quota_gb: Option<i32>,
+            fup_throttled_at: Option<DateTime<Utc>>,
+            period_anchor: Option<DateTime<Utc>>,
+        }
+
+        self.require_customer_usage_access(actor_id, tenant_id, customer_id)
+            .await?;
+
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM customers WHERE tenant_id = $1 AND id = $2 AND deleted_at IS NULL)",
+        )
+        .bind(tenant_id)
+        .bind(customer_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+        if !exists {
+            return Err(AppError::NotFound("Customer not found".to_string()));
+        }
+
+        let days = days.unwrap_or(30).clamp(1, 365);
+        let bucket = usage_bucket(days);
+        let now = Utc::now();
+
+        let points: Vec<UsagePoint> = sqlx::query_as(
+            r#"
+            SELECT
+              date_trunc($4, ts) AS ts,
+              SUM(upload_bytes)::bigint AS upload_bytes,
+              SUM(download_bytes)::bigint AS download_bytes
+            FROM usage_records
+            WHERE tenant_id = $1 AND customer_id = $2 AND ts >= $3
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(customer_id)
+        .bind(now - chrono::Duration::days(days as i64))
+        .bind(bucket)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let rows: Vec<AccountRow> = sqlx::query_as(
+            r#"
+            SELECT
+              a.id,
+              a.username,
+              p.name AS package_name,
+              p.fup_quota_gb,
+              a.fup_throttled_at,
+              (
+                SELECT s.starts_at FROM customer_subscriptions s
+                WHERE s.tenant_id = a.tenant_id AND s.location_id = a.location_id AND s.status = 'active'
+                ORDER BY COALESCE(s.package_id = a.package_id, false) DESC, s.updated_at DESC
+                LIMIT 1
+              ) AS period_anchor
+            FROM pppoe_accounts a
+            LEFT JOIN isp_packages p ON p.tenant_id = a.tenant_id AND p.id = a.package_id
+            WHERE a.tenant_id = $1 AND a.customer_id = $2
+            ORDER BY a.username
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(customer_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let ids: Vec<String> = rows.iter().map(|r| r.id.clone()).collect();
+        let periods: HashMap<(String, DateTime<Utc>), (i64, i64)> =
+            sqlx::query_as::<_, (String, DateTime<Utc>, i64, i64)>(
+                r#"
+            SELECT account_id, period_start, upload_bytes, download_bytes
+            FROM pppoe_usage_periods
+            WHERE account_id = ANY($1) AND period_start >= $2
+            "#,
+            )
+            .bind(&ids)
+            .bind(now - chrono::Duration::days(62))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::Database)?
+            .into_iter()
+            .map(|(id, start, up, down)| ((id, start), (up, down)))
+            .collect();
+
+        let accounts = rows
+            .into_iter()
+            .map(|row| {
+                let period_start = usage_period_start(row.period_anchor, now);
+                let (upload_bytes, download_bytes) = periods
+                    .get(&(row.id.clone(), period_start))
+                    .copied()
+                    .unwrap_or((0, 0));
+                PppoeAccountUsage {
+                    account_id: row.id,
+                    username: row.username,
+                    package_name: row.package_name,
+                    period_start,
+                    upload_bytes,
+                    download_bytes,
+                    quota_gb: row.fup_quota_gb,
+                    throttled: row.fup_throttled_at.is_some(),
+                }
+            })
+            .collect();
+
+        Ok(CustomerUsage {
+            customer_id: customer_id.to_string(),
+            days,
+            bucket: bucket.to_string(),
+            points,
+            accounts,
+        })
+    }
+
+    /// Disconnects the user's active session so it comes back on its current profile.
+    async fn drop_active_session(
+        &self,
+        tenant_id: &str,
+        router_id: &str,
+        username: &str,
+    ) -> Result<(), anyhow::Error> {
+        let dev = self.connect_router(tenant_id, router_id).await?;
+        let cmd = CommandBuilder::new().command("/ppp/active/print").build();
+        let mut rx = dev.send_command(cmd).await?;
+        let mut ids = Vec::new();
+        while let Some(res) = rx.recv().await {
+            match res? {
+                CommandResponse::Reply(reply) => {
+                    let name = reply.attributes.get("name").and_then(|v| v.clone());
+                    if name.as_deref() != Some(username) {
+                        continue;
+                    }
+                    if let Some(id) = reply.attributes.get(".id").and_then(|v| v.clone()) {
+                        ids.push(id);
+                    }
+                }
+                CommandResponse::Done(_) => break,
+                _ => {}
+            }
+        }
+        for id in ids {
+            let cmd = CommandBuilder::new()
+                .command("/ppp/active/remove")
+                .attribute("numbers", Some(id.as_str()))
+                .build();
+            let mut rx = dev.send_command(cmd).await?;
+            while let Some(res) = rx.recv().await {
+                if let CommandResponse::Done(_) = res? {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct PppoeUsageJob(PppoeService);
+
+#[async_trait]
+impl JobHandler for PppoeUsageJob {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        self.0.enforce_fair_usage().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{counter_growth, fup_transition, usage_bucket, usage_period_start, BYTES_PER_GB};
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn session_counters_only_add_their_growth() {
+        assert_eq!(counter_growth(None, "*1", 100, 500), (100, 500));
+        assert_eq!(
+            counter_growth(Some(("*1", 40, 200)), "*1", 100, 500),
+            (60, 300)
+        );
+        // A reconnect gets a new interface, a router reboot resets the counters.
+        assert_eq!(
+            counter_growth(Some(("*1", 40, 200)), "*2", 10, 20),
+            (10, 20)
+        );
+        assert_eq!(
+            counter_growth(Some(("*1", 40, 200)), "*1", 10, 20),
+            (10, 20)
+        );
+    }
+
+    #[test]
+    fn usage_periods_follow_the_subscription_start_day() {
+        let at = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap();
+        let midnight = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap();
+
+        assert_eq!(
+            usage_period_start(None, at(2026, 5, 17)),
+            midnight(2026, 5, 1)
+        );
+        let anchor = Some(at(2025, 11, 20));
+        assert_eq!(
+            usage_period_start(anchor, at(2026, 5, 20)),
+            midnight(2026, 5, 20)
+        );
+        assert_eq!(
+            usage_period_start(anchor, at(2026, 5, 19)),
+            midnight(2026, 4, 20)
+        );
+        assert_eq!(
+            usage_period_start(anchor, at(2026, 1, 3)),
+            midnight(2025, 12, 20)
+        );
+        // Started on the 31st: short months reset on their last day.
+        let anchor = Some(at(2026, 1, 31));
+        assert_eq!(
+            usage_period_start(anchor, at(2026, 3, 2)),
+            midnight(2026, 2, 28)
+        );
+        assert_eq!(
+            usage_period_start(anchor, at(2026, 3, 31)),
+            midnight(2026, 3, 31)
+        );
+    }
+
+    #[test]
+    fn accounts_are_throttled_over_quota_and_released_on_reset() {
+        let gb = |n: i64| n * BYTES_PER_GB;
+        assert_eq!(fup_transition(Some(100), gb(99), false), None);
+        assert_eq!(fup_transition(Some(100), gb(100), false), Some(true));
+        assert_eq!(fup_transition(Some(100), gb(150), true), None);
+        assert_eq!(fup_transition(Some(100), 0, true), Some(false));
+        assert_eq!(fup_transition(None, gb(500), true), Some(false));
+        assert_eq!(fup_transition(None, gb(500), false), None);
+    }
+
+    #[test]
+    fn usage_charts_switch_to_days_past_two_days() {
+        assert_eq!(usage_bucket(1), "hour");
+        assert_eq!(usage_bucket(2), "hour");
+        assert_eq!(usage_bucket(3), "day");
+        assert_eq!(usage_bucket(365), "day");
+    }
 }
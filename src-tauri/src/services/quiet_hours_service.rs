@@ -0,0 +1,336 @@
+//! Per-user quiet hours.
+//!
+//! While a user's window is in effect, email and push for non-critical
+//! notifications are queued in `notification_deferred_deliveries` and sent when
+//! the window ends. The in-app row is still written so the inbox stays complete.
+//! Windows are evaluated in the user's timezone, or the tenant's `app_timezone`
+//! when the user hasn't picked one.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{NotificationQuietHours, NotificationQuietHoursRow, UpdateQuietHoursRequest};
+use crate::services::notification_routing_service::severity_for_type;
+use crate::services::SettingsService;
+use chrono::{DateTime, Duration, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use uuid::Uuid;
+
+const DEFAULT_START: &str = "22:00";
+const DEFAULT_END: &str = "07:00";
+
+/// Whether a notification may interrupt quiet hours.
+pub fn bypasses_quiet_hours(notification_type: &str, category: &str) -> bool {
+    severity_for_type(notification_type) == "critical" || category == "security"
+}
+
+fn parse_hhmm(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+fn to_utc(tz: Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    // A local time skipped by a DST jump resolves to the first valid instant after it.
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|| local.and_utc())
+}
+
+/// End of the quiet window containing `now`, or `None` outside it. `end` before
+/// `start` means the window runs past midnight; equal times disable it.
+fn window_end(
+    start: NaiveTime,
+    end: NaiveTime,
+    tz: Tz,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let local = now.with_timezone(&tz).naive_local();
+    let (date, time) = (local.date(), local.time());
+    let end_date = if start < end {
+        (time >= start && time < end).then_some(date)?
+    } else if start > end {
+        if time >= start {
+            date.succ_opt()?
+        } else if time < end {
+            date
+        } else {
+            return None;
+        }
+    } else {
+        return None;
+    };
+    Some(to_utc(tz, end_date.and_time(end)))
+}
+
+#[derive(Clone)]
+pub struct QuietHoursService {
+    pool: DbPool,
+    settings_service: SettingsService,
+}
+
+impl QuietHoursService {
+    pub fn new(pool: DbPool, settings_service: SettingsService) -> Self {
+        Self {
+            pool,
+            settings_service,
+        }
+    }
+
+    async fn row(&self, user_id: &str) -> AppResult<Option<NotificationQuietHoursRow>> {
+        Ok(sqlx::query_as::<_, NotificationQuietHoursRow>(
+            "SELECT * FROM notification_quiet_hours WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    async fn timezone(
+        &self,
+        row: Option<&NotificationQuietHoursRow>,
+        tenant_id: Option<&str>,
+    ) -> Tz {
+        if let Some(tz) = row
+            .and_then(|r| r.timezone.as_deref())
+            .and_then(|v| v.trim().parse::<Tz>().ok())
+        {
+            return tz;
+        }
+        self.settings_service
+            .get_value_fallback(tenant_id, "app_timezone")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| v.trim().parse::<Tz>().ok())
+            .unwrap_or(chrono_tz::UTC)
+    }
+
+    async fn view(
+        &self,
+        row: Option<NotificationQuietHoursRow>,
+        tenant_id: Option<&str>,
+    ) -> NotificationQuietHours {
+        let tz = self.timezone(row.as_ref(), tenant_id).await;
+        let resumes_at = row.as_ref().filter(|r| r.is_enabled).and_then(|r| {
+            window_end(
+                parse_hhmm(&r.start_time)?,
+                parse_hhmm(&r.end_time)?,
+                tz,
+                Utc::now(),
+            )
+        });
+        match row {
+            Some(r) => NotificationQuietHours {
+                enabled: r.is_enabled,
+                start: r.start_time,
+                end: r.end_time,
+                timezone: r.timezone,
+                effective_timezone: tz.name().to_string(),
+                resumes_at,
+            },
+            None => NotificationQuietHours {
+                enabled: false,
+                start: DEFAULT_START.to_string(),
+                end: DEFAULT_END.to_string(),
+                timezone: None,
+                effective_timezone: tz.name().to_string(),
+                resumes_at: None,
+            },
+        }
+    }
+
+    pub async fn get(
+        &self,
+        user_id: &str,
+        tenant_id: Option<&str>,
+    ) -> AppResult<NotificationQuietHours> {
+        let row = self.row(user_id).await?;
+        Ok(self.view(row, tenant_id).await)
+    }
+
+    pub async fn update(
+        &self,
+        user_id: &str,
+        tenant_id: Option<&str>,
+        req: UpdateQuietHoursRequest,
+    ) -> AppResult<NotificationQuietHours> {
+        let (Some(start), Some(end)) = (parse_hhmm(&req.start), parse_hhmm(&req.end)) else {
+            return Err(AppError::Validation(
+                "Quiet hours must be given as HH:MM".to_string(),
+            ));
+        };
+        if req.enabled && start == end {
+            return Err(AppError::Validation(
+                "Quiet hours start and end must differ".to_string(),
+            ));
+        }
+        let timezone = req
+            .timezone
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty());
+        if let Some(tz) = timezone {
+            if tz.parse::<Tz>().is_err() {
+                return Err(AppError::Validation(format!("Unknown timezone '{}'", tz)));
+            }
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO notification_quiet_hours (user_id, is_enabled, start_time, end_time, timezone, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id)
+            DO UPDATE SET is_enabled = $2, start_time = $3, end_time = $4, timezone = $5, updated_at = $6
+            "#,
+        )
+        .bind(user_id)
+        .bind(req.enabled)
+        .bind(start.format("%H:%M").to_string())
+        .bind(end.format("%H:%M").to_string())
+        .bind(timezone)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        self.get(user_id, tenant_id).await
+    }
+
+    /// When the user's quiet hours end, if they are in effect now.
+    pub async fn deferral_until(
+        &self,
+        user_id: &str,
+        tenant_id: Option<&str>,
+    ) -> Option<DateTime<Utc>> {
+        let row = self.row(user_id).await.ok().flatten()?;
+        if !row.is_enabled {
+            return None;
+        }
+        let (start, end) = (parse_hhmm(&row.start_time)?, parse_hhmm(&row.end_time)?);
+        let tz = self.timezone(Some(&row), tenant_id).await;
+        window_end(start, end, tz, Utc::now())
+    }
+
+    pub async fn defer(
+        &self,
+        notification_id: &str,
+        user_id: &str,
+        channels: &[&str],
+        deliver_after: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO notification_deferred_deliveries
+                (id, notification_id, user_id, channels, deliver_after, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(notification_id)
+        .bind(user_id)
+        .bind(channels.join(","))
+        .bind(deliver_after)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Claims up to `limit` due deliveries as `(notification_id, channels)`.
+    /// Rows are removed when claimed, so a failed send is not retried here;
+    /// email has its own outbox retries.
+    pub async fn take_due(&self, limit: i64) -> AppResult<Vec<(String, Vec<String>)>> {
+        let rows: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT id, notification_id, channels FROM notification_deferred_deliveries WHERE deliver_after <= $1 ORDER BY deliver_after LIMIT $2",
+        )
+        .bind(Utc::now())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut due = Vec::with_capacity(rows.len());
+        for (id, notification_id, channels) in rows {
+            let claimed = sqlx::query("DELETE FROM notification_deferred_deliveries WHERE id = $1")
+                .bind(&id)
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+            // Another instance got there first.
+            if claimed == 0 {
+                continue;
+            }
+            let channels = channels
+                .split(',')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string)
+                .collect();
+            due.push((notification_id, channels));
+        }
+        Ok(due)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hm(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn overnight_window_ends_next_morning() {
+        let tz: Tz = "Asia/Jakarta".parse().unwrap(); // UTC+7
+                                                      // 23:30 local on Mar 2 -> quiet until 07:00 local on Mar 3 (00:00 UTC).
+        let end = window_end(hm(22, 0), hm(7, 0), tz, utc(2026, 3, 2, 16, 30));
+        assert_eq!(end, Some(utc(2026, 3, 3, 0, 0)));
+        // 05:00 local -> same day 07:00 local.
+        let end = window_end(hm(22, 0), hm(7, 0), tz, utc(2026, 3, 2, 22, 0));
+        assert_eq!(end, Some(utc(2026, 3, 3, 0, 0)));
+        // Midday is outside the window.
+        assert_eq!(
+            window_end(hm(22, 0), hm(7, 0), tz, utc(2026, 3, 2, 5, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn daytime_window_and_disabled_window() {
+        let tz = chrono_tz::UTC;
+        assert_eq!(
+            window_end(hm(12, 0), hm(13, 0), tz, utc(2026, 3, 2, 12, 15)),
+            Some(utc(2026, 3, 2, 13, 0))
+        );
+        assert_eq!(
+            window_end(hm(12, 0), hm(13, 0), tz, utc(2026, 3, 2, 13, 0)),
+            None
+        );
+        assert_eq!(
+            window_end(hm(9, 0), hm(9, 0), tz, utc(2026, 3, 2, 9, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn window_end_in_dst_gap_moves_forward() {
+        let tz: Tz = "Europe/Berlin".parse().unwrap();
+        // 2026-03-29 02:30 local does not exist (clocks jump 02:00 -> 03:00).
+        let end = window_end(hm(22, 0), hm(2, 30), tz, utc(2026, 3, 28, 23, 0)).unwrap();
+        assert_eq!(end, utc(2026, 3, 29, 1, 30));
+    }
+
+    #[test]
+    fn critical_and_security_bypass() {
+        assert!(bypasses_quiet_hours("error", "network"));
+        assert!(bypasses_quiet_hours("info", "security"));
+        assert!(!bypasses_quiet_hours("warning", "billing"));
+        assert!(!bypasses_quiet_hours("info", "system"));
+    }
+}
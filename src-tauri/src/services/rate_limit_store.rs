@@ -0,0 +1,444 @@
+//! Where rate-limit windows, abuse strikes and IP blocks are kept.
+//!
+//! The memory store keeps them in the process, which is all a single node (and
+//! the desktop app) needs. With more than one API instance behind a load
+//! balancer each would count only its own share of a client's requests, so
+//! `REDIS_URL` switches to the Redis store, shared by every instance:
+//!
+//! - rate-limit windows are sorted sets of request times, trimmed and counted
+//!   in one script so concurrent instances cannot overshoot the limit;
+//! - blocks and strikes are plain keys that Redis expires itself.
+//!
+//! Times in Redis come from the Redis server, so clock skew between instances
+//! does not stretch or shrink windows. Keys are prefixed with
+//! `REDIS_KEY_PREFIX` (default `isp:`) when several deployments share a server.
+
+use crate::error::{AppError, AppResult};
+use crate::services::rate_limiter::RateLimitInfo;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+/// Redis calls slower than this count as failures, so a struggling Redis
+/// does not hold up every request.
+const REDIS_TIMEOUT: Duration = Duration::from_millis(500);
+const REDIS_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Outcome of counting a request against a window.
+#[derive(Debug, Clone)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub info: RateLimitInfo,
+}
+
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Shown in logs.
+    fn name(&self) -> &str;
+
+    /// Records a request for `key` unless `limit` requests already fall in
+    /// the last `window_secs`.
+    async fn hit(&self, key: &str, limit: u32, window_secs: u64) -> AppResult<RateLimitDecision>;
+
+    /// The window of `key` without recording a request.
+    async fn peek(&self, key: &str, limit: u32, window_secs: u64) -> AppResult<RateLimitInfo>;
+
+    async fn blocked_until(&self, ip: &str) -> AppResult<Option<DateTime<Utc>>>;
+
+    async fn block(&self, ip: &str, until: DateTime<Utc>) -> AppResult<()>;
+
+    /// Adds a strike against `ip` and returns its strikes so far; the count
+    /// starts over `window_secs` after the first one.
+    async fn strike(&self, ip: &str, window_secs: u64) -> AppResult<u32>;
+
+    /// Drops expired entries. Stores that expire entries themselves have
+    /// nothing to do.
+    async fn cleanup(&self) {}
+}
+
+/// In-process store: sliding windows of request instants per key.
+pub struct MemoryStore {
+    requests: RwLock<HashMap<String, Vec<Instant>>>,
+    blocks: RwLock<HashMap<String, DateTime<Utc>>>,
+    /// Strikes and when they start over.
+    strikes: RwLock<HashMap<String, (u32, Instant)>>,
+    /// Windows idle for longer than this are dropped by `cleanup`.
+    cleanup_threshold: Duration,
+}
+
+impl MemoryStore {
+    pub fn new(cleanup_threshold_secs: u64) -> Self {
+        Self {
+            requests: RwLock::new(HashMap::new()),
+            blocks: RwLock::new(HashMap::new()),
+            strikes: RwLock::new(HashMap::new()),
+            cleanup_threshold: Duration::from_secs(cleanup_threshold_secs),
+        }
+    }
+
+    fn hit_sync(&self, key: &str, limit: u32, window_secs: u64) -> RateLimitDecision {
+        let now = Instant::now();
+        let window = Duration::from_secs(window_secs);
+        let cutoff = now - window;
+
+        let mut requests = self.requests.write().unwrap();
+        let timestamps = requests.entry(key.to_string()).or_default();
+        timestamps.retain(|&ts| ts > cutoff);
+
+        let current_count = timestamps.len() as u32;
+
+        // When the oldest request in the window expires
+        let reset_in_secs = timestamps
+            .first()
+            .map(|&oldest| (window - now.duration_since(oldest)).as_secs())
+            .unwrap_or(window_secs);
+
+        if current_count >= limit {
+            return RateLimitDecision {
+                allowed: false,
+                info: RateLimitInfo {
+                    limit,
+                    remaining: 0,
+                    reset_in_secs,
+                },
+            };
+        }
+        timestamps.push(now);
+        RateLimitDecision {
+            allowed: true,
+            info: RateLimitInfo {
+                limit,
+                remaining: limit - current_count - 1,
+                reset_in_secs,
+            },
+        }
+    }
+
+    fn peek_sync(&self, key: &str, limit: u32, window_secs: u64) -> RateLimitInfo {
+        let now = Instant::now();
+        let window = Duration::from_secs(window_secs);
+        let cutoff = now - window;
+
+        let requests = self.requests.read().unwrap();
+        let in_window: Vec<Instant> = requests
+            .get(key)
+            .map(|ts| ts.iter().copied().filter(|&t| t > cutoff).collect())
+            .unwrap_or_default();
+
+        let reset_in_secs = in_window
+            .first()
+            .map(|&oldest| (window - now.duration_since(oldest)).as_secs())
+            .unwrap_or(window_secs);
+
+        RateLimitInfo {
+            limit,
+            remaining: limit.saturating_sub(in_window.len() as u32),
+            reset_in_secs,
+        }
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new(300) // 5 minutes default cleanup threshold
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    async fn hit(&self, key: &str, limit: u32, window_secs: u64) -> AppResult<RateLimitDecision> {
+        Ok(self.hit_sync(key, limit, window_secs))
+    }
+
+    async fn peek(&self, key: &str, limit: u32, window_secs: u64) -> AppResult<RateLimitInfo> {
+        Ok(self.peek_sync(key, limit, window_secs))
+    }
+
+    async fn blocked_until(&self, ip: &str) -> AppResult<Option<DateTime<Utc>>> {
+        let until = self.blocks.read().unwrap().get(ip).copied();
+        Ok(until.filter(|until| *until > Utc::now()))
+    }
+
+    async fn block(&self, ip: &str, until: DateTime<Utc>) -> AppResult<()> {
+        self.blocks.write().unwrap().insert(ip.to_string(), until);
+        Ok(())
+    }
+
+    async fn strike(&self, ip: &str, window_secs: u64) -> AppResult<u32> {
+        let now = Instant::now();
+        let window = Duration::from_secs(window_secs);
+        let mut strikes = self.strikes.write().unwrap();
+        let entry = strikes.entry(ip.to_string()).or_insert((0, now + window));
+        if now >= entry.1 {
+            *entry = (0, now + window);
+        }
+        entry.0 = entry.0.saturating_add(1);
+        Ok(entry.0)
+    }
+
+    async fn cleanup(&self) {
+        let now = Instant::now();
+        let cutoff = now - self.cleanup_threshold;
+        self.requests.write().unwrap().retain(|_, timestamps| {
+            timestamps.retain(|&ts| ts > cutoff);
+            !timestamps.is_empty()
+        });
+        self.strikes
+            .write()
+            .unwrap()
+            .retain(|_, (_, resets_at)| *resets_at > now);
+        let utc_now = Utc::now();
+        self.blocks
+            .write()
+            .unwrap()
+            .retain(|_, until| *until > utc_now);
+    }
+}
+
+/// Server time in milliseconds, from inside a script.
+const LUA_NOW_MS: &str = r#"
+local t = redis.call('TIME')
+local now = tonumber(t[1]) * 1000 + math.floor(tonumber(t[2]) / 1000)
+"#;
+
+/// KEYS[1] window; ARGV limit, window ms, member.
+/// Returns {allowed, count after this request, oldest ms, now ms}.
+const LUA_HIT: &str = r#"
+local limit = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now - window)
+local count = redis.call('ZCARD', KEYS[1])
+local first = redis.call('ZRANGE', KEYS[1], 0, 0, 'WITHSCORES')
+local oldest = now
+if first[2] then oldest = tonumber(first[2]) end
+local allowed = 0
+if count < limit then
+  redis.call('ZADD', KEYS[1], now, ARGV[3])
+  count = count + 1
+  allowed = 1
+end
+redis.call('PEXPIRE', KEYS[1], window)
+return {allowed, count, oldest, now}
+"#;
+
+/// KEYS[1] window; ARGV window ms. Returns {count, oldest ms, now ms}.
+const LUA_PEEK: &str = r#"
+local window = tonumber(ARGV[1])
+local cutoff = string.format('(%d', now - window)
+local count = redis.call('ZCOUNT', KEYS[1], cutoff, '+inf')
+local first = redis.call('ZRANGEBYSCORE', KEYS[1], cutoff, '+inf', 'WITHSCORES', 'LIMIT', 0, 1)
+local oldest = now
+if first[2] then oldest = tonumber(first[2]) end
+return {count, oldest, now}
+"#;
+
+/// KEYS[1] strikes; ARGV window secs. Returns the strikes so far.
+const LUA_STRIKE: &str = r#"
+local n = redis.call('INCR', KEYS[1])
+if n == 1 then redis.call('EXPIRE', KEYS[1], ARGV[1]) end
+return n
+"#;
+
+/// Store shared by all instances through Redis.
+pub struct RedisStore {
+    client: redis::Client,
+    /// Connected on first use and reconnected by the manager after that, so
+    /// Redis being down at startup is not fatal.
+    conn: OnceCell<ConnectionManager>,
+    prefix: String,
+    hit_script: redis::Script,
+    peek_script: redis::Script,
+    strike_script: redis::Script,
+}
+
+impl RedisStore {
+    pub fn new(url: &str, prefix: &str) -> AppResult<Self> {
+        let client = redis::Client::open(url)
+            .map_err(|e| AppError::Configuration(format!("Invalid REDIS_URL: {}", e)))?;
+        Ok(Self {
+            client,
+            conn: OnceCell::new(),
+            prefix: prefix.to_string(),
+            hit_script: redis::Script::new(&format!("{}{}", LUA_NOW_MS, LUA_HIT)),
+            peek_script: redis::Script::new(&format!("{}{}", LUA_NOW_MS, LUA_PEEK)),
+            strike_script: redis::Script::new(LUA_STRIKE),
+        })
+    }
+
+    fn key(&self, kind: &str, id: &str) -> String {
+        format!("{}{}:{}", self.prefix, kind, id)
+    }
+
+    async fn conn(&self) -> AppResult<ConnectionManager> {
+        let conn = self
+            .conn
+            .get_or_try_init(|| async {
+                tokio::time::timeout(
+                    REDIS_CONNECT_TIMEOUT,
+                    ConnectionManager::new(self.client.clone()),
+                )
+                .await
+                .map_err(|_| AppError::Internal("Redis connect timed out".to_string()))?
+                .map_err(redis_err)
+            })
+            .await?;
+        Ok(conn.clone())
+    }
+
+    async fn run<T, F>(&self, fut: F) -> AppResult<T>
+    where
+        F: std::future::Future<Output = redis::RedisResult<T>>,
+    {
+        tokio::time::timeout(REDIS_TIMEOUT, fut)
+            .await
+            .map_err(|_| AppError::Internal("Redis timed out".to_string()))?
+            .map_err(redis_err)
+    }
+}
+
+fn redis_err(e: redis::RedisError) -> AppError {
+    AppError::Internal(format!("Redis error: {}", e))
+}
+
+/// Seconds until the window frees a slot: `oldest + window - now`.
+fn reset_in_secs(oldest_ms: i64, now_ms: i64, window_secs: u64) -> u64 {
+    let window_ms = window_secs as i64 * 1000;
+    ((oldest_ms + window_ms - now_ms).clamp(0, window_ms) / 1000) as u64
+}
+
+#[async_trait]
+impl RateLimitStore for RedisStore {
+    fn name(&self) -> &str {
+        "redis"
+    }
+
+    async fn hit(&self, key: &str, limit: u32, window_secs: u64) -> AppResult<RateLimitDecision> {
+        let mut conn = self.conn().await?;
+        let mut invocation = self.hit_script.key(self.key("rl", key));
+        invocation
+            .arg(limit)
+            .arg(window_secs * 1000)
+            .arg(Uuid::new_v4().to_string());
+        let (allowed, count, oldest, now): (i64, i64, i64, i64) =
+            self.run(invocation.invoke_async(&mut conn)).await?;
+
+        let allowed = allowed == 1;
+        Ok(RateLimitDecision {
+            allowed,
+            info: RateLimitInfo {
+                limit,
+                remaining: if allowed {
+                    limit.saturating_sub(count as u32)
+                } else {
+                    0
+                },
+                reset_in_secs: reset_in_secs(oldest, now, window_secs),
+            },
+        })
+    }
+
+    async fn peek(&self, key: &str, limit: u32, window_secs: u64) -> AppResult<RateLimitInfo> {
+        let mut conn = self.conn().await?;
+        let mut invocation = self.peek_script.key(self.key("rl", key));
+        invocation.arg(window_secs * 1000);
+        let (count, oldest, now): (i64, i64, i64) =
+            self.run(invocation.invoke_async(&mut conn)).await?;
+        Ok(RateLimitInfo {
+            limit,
+            remaining: limit.saturating_sub(count as u32),
+            reset_in_secs: reset_in_secs(oldest, now, window_secs),
+        })
+    }
+
+    async fn blocked_until(&self, ip: &str) -> AppResult<Option<DateTime<Utc>>> {
+        let mut conn = self.conn().await?;
+        let mut cmd = redis::cmd("GET");
+        cmd.arg(self.key("block", ip));
+        let raw: Option<String> = self.run(cmd.query_async(&mut conn)).await?;
+        Ok(raw
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|until| until.with_timezone(&Utc))
+            .filter(|until| *until > Utc::now()))
+    }
+
+    async fn block(&self, ip: &str, until: DateTime<Utc>) -> AppResult<()> {
+        let secs = (until - Utc::now()).num_seconds();
+        if secs <= 0 {
+            return Ok(());
+        }
+        let mut conn = self.conn().await?;
+        let mut cmd = redis::cmd("SET");
+        cmd.arg(self.key("block", ip))
+            .arg(until.to_rfc3339())
+            .arg("EX")
+            .arg(secs);
+        let _: () = self.run(cmd.query_async(&mut conn)).await?;
+        Ok(())
+    }
+
+    async fn strike(&self, ip: &str, window_secs: u64) -> AppResult<u32> {
+        let mut conn = self.conn().await?;
+        let mut invocation = self.strike_script.key(self.key("strikes", ip));
+        invocation.arg(window_secs);
+        let n: i64 = self.run(invocation.invoke_async(&mut conn)).await?;
+        Ok(n.clamp(0, u32::MAX as i64) as u32)
+    }
+}
+
+/// Redis store when `REDIS_URL` is set, else `None`.
+pub fn redis_from_env() -> AppResult<Option<RedisStore>> {
+    let env = |key: &str| {
+        std::env::var(key)
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let Some(url) = env("REDIS_URL") else {
+        return Ok(None);
+    };
+    let prefix = env("REDIS_KEY_PREFIX").unwrap_or_else(|| "isp:".to_string());
+    RedisStore::new(&url, &prefix).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_strikes_count_per_ip() {
+        let store = MemoryStore::default();
+        assert_eq!(store.strike("1.2.3.4", 600).await.unwrap(), 1);
+        assert_eq!(store.strike("1.2.3.4", 600).await.unwrap(), 2);
+        assert_eq!(store.strike("5.6.7.8", 600).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_memory_blocks_expire() {
+        let store = MemoryStore::default();
+        let until = Utc::now() + chrono::Duration::minutes(5);
+        store.block("1.2.3.4", until).await.unwrap();
+        assert_eq!(store.blocked_until("1.2.3.4").await.unwrap(), Some(until));
+
+        store
+            .block("5.6.7.8", Utc::now() - chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(store.blocked_until("5.6.7.8").await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_reset_in_secs() {
+        assert_eq!(reset_in_secs(1_000, 1_000, 60), 60);
+        assert_eq!(reset_in_secs(1_000, 31_000, 60), 30);
+        assert_eq!(reset_in_secs(1_000, 100_000, 60), 0);
+    }
+}
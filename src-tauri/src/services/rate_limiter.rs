@@ -1,11 +1,17 @@
 //! Rate Limiter Service - IP-based request rate limiting
 //!
 //! Implements a sliding window rate limiter to protect against
-//! brute force and DDoS attacks.
-
-use std::collections::HashMap;
-use std::sync::RwLock;
-use std::time::{Duration, Instant};
+//! brute force and DDoS attacks. Counts, abuse strikes and IP blocks live in a
+//! `RateLimitStore` (see `rate_limit_store`): in process by default, in Redis
+//! when `REDIS_URL` is set so every API instance shares them.
+
+use crate::services::rate_limit_store::{
+    redis_from_env, MemoryStore, RateLimitDecision, RateLimitStore,
+};
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::{error, info, warn};
 
 /// Rate limit error when limit is exceeded
 #[derive(Debug, Clone)]
@@ -17,21 +23,58 @@ pub struct RateLimitInfo {
 
 /// Thread-safe rate limiter using sliding window algorithm
 pub struct RateLimiter {
-    /// Map of key -> list of request timestamps
-    requests: RwLock<HashMap<String, Vec<Instant>>>,
-    /// Cleanup interval - entries older than this are removed
-    cleanup_threshold: Duration,
+    store: Arc<dyn RateLimitStore>,
+    /// Counts of this instance alone, used while a shared store is
+    /// unreachable so limits degrade to per-instance instead of off.
+    fallback: Option<MemoryStore>,
+    degraded: AtomicBool,
 }
 
 impl RateLimiter {
-    /// Create a new rate limiter
-    ///
-    /// # Arguments
-    /// * `cleanup_threshold` - How long to keep old entries before cleanup (default: 5 minutes)
-    pub fn new(cleanup_threshold_secs: u64) -> Self {
+    /// Create a rate limiter over `store`
+    pub fn new(store: Arc<dyn RateLimitStore>) -> Self {
         Self {
-            requests: RwLock::new(HashMap::new()),
-            cleanup_threshold: Duration::from_secs(cleanup_threshold_secs),
+            store,
+            fallback: None,
+            degraded: AtomicBool::new(false),
+        }
+    }
+
+    /// Redis-backed when `REDIS_URL` is set, in-memory otherwise. An invalid
+    /// URL is logged and falls back to memory rather than stopping the server.
+    pub fn from_env() -> Self {
+        match redis_from_env() {
+            Ok(Some(redis)) => {
+                info!("Rate limits, strikes and IP blocks are kept in Redis");
+                Self {
+                    store: Arc::new(redis),
+                    fallback: Some(MemoryStore::default()),
+                    degraded: AtomicBool::new(false),
+                }
+            }
+            Ok(None) => Self::default(),
+            Err(e) => {
+                error!("{}; rate limits are kept in memory", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// The store to use after `store` failed with `err`, if any.
+    fn fall_back(&self, err: &crate::error::AppError) -> Option<&MemoryStore> {
+        if !self.degraded.swap(true, Ordering::Relaxed) {
+            warn!(
+                "Rate limit store {} unavailable ({}); limiting per instance until it is back",
+                self.store.name(),
+                err
+            );
+        }
+        self.fallback.as_ref()
+    }
+
+    fn recovered(&self) {
+        if self.degraded.swap(false, Ordering::Relaxed) {
+            info!("Rate limit store {} is back", self.store.name());
         }
     }
 
@@ -45,104 +88,103 @@ impl RateLimiter {
     /// # Returns
     /// * `Ok(RateLimitInfo)` - Request allowed, with remaining quota
     /// * `Err(RateLimitInfo)` - Rate limit exceeded
-    pub fn check(
+    pub async fn check(
         &self,
         key: &str,
         limit: u32,
         window_secs: u64,
     ) -> Result<RateLimitInfo, RateLimitInfo> {
-        let now = Instant::now();
-        let window = Duration::from_secs(window_secs);
-        let cutoff = now - window;
-
-        let mut requests = self.requests.write().unwrap();
-
-        // Get or create entry for this key
-        let timestamps = requests.entry(key.to_string()).or_default();
-
-        // Remove expired timestamps (sliding window)
-        timestamps.retain(|&ts| ts > cutoff);
-
-        let current_count = timestamps.len() as u32;
-
-        // Calculate reset time (when the oldest request in window expires)
-        let reset_in_secs = if let Some(&oldest) = timestamps.first() {
-            let elapsed = now.duration_since(oldest);
-            if elapsed < window {
-                (window - elapsed).as_secs()
-            } else {
-                window_secs
+        let decision = match self.store.hit(key, limit, window_secs).await {
+            Ok(d) => {
+                self.recovered();
+                d
             }
-        } else {
-            window_secs
+            Err(e) => match self.fall_back(&e) {
+                Some(memory) => memory
+                    .hit(key, limit, window_secs)
+                    .await
+                    .unwrap_or_else(|_| allow(limit, window_secs)),
+                None => allow(limit, window_secs),
+            },
         };
-
-        if current_count >= limit {
-            // Rate limit exceeded
-            Err(RateLimitInfo {
-                limit,
-                remaining: 0,
-                reset_in_secs,
-            })
+        if decision.allowed {
+            Ok(decision.info)
         } else {
-            // Allow request and record it
-            timestamps.push(now);
-
-            Ok(RateLimitInfo {
-                limit,
-                remaining: limit - current_count - 1,
-                reset_in_secs,
-            })
+            Err(decision.info)
         }
     }
 
     /// Check rate limit without recording the request
     /// Useful for checking status without incrementing counter
-    #[allow(dead_code)]
-    pub fn peek(&self, key: &str, limit: u32, window_secs: u64) -> RateLimitInfo {
-        let now = Instant::now();
-        let window = Duration::from_secs(window_secs);
-        let cutoff = now - window;
-
-        let requests = self.requests.read().unwrap();
+    pub async fn peek(&self, key: &str, limit: u32, window_secs: u64) -> RateLimitInfo {
+        match self.store.peek(key, limit, window_secs).await {
+            Ok(info) => info,
+            Err(e) => match self.fall_back(&e) {
+                Some(memory) => memory
+                    .peek(key, limit, window_secs)
+                    .await
+                    .unwrap_or_else(|_| allow(limit, window_secs).info),
+                None => allow(limit, window_secs).info,
+            },
+        }
+    }
 
-        let current_count = requests
-            .get(key)
-            .map(|ts| ts.iter().filter(|&&t| t > cutoff).count() as u32)
-            .unwrap_or(0);
+    /// Until when `ip` is blocked, if it is. Unknown while the store is
+    /// unreachable, which counts as not blocked.
+    pub async fn blocked_until(&self, ip: &str) -> Option<DateTime<Utc>> {
+        match self.store.blocked_until(ip).await {
+            Ok(until) => until,
+            Err(e) => match self.fall_back(&e) {
+                Some(memory) => memory.blocked_until(ip).await.ok().flatten(),
+                None => None,
+            },
+        }
+    }
 
-        RateLimitInfo {
-            limit,
-            remaining: limit.saturating_sub(current_count),
-            reset_in_secs: window_secs,
+    /// Adds a strike against `ip` and blocks it until `until` once it has
+    /// `threshold` strikes within `window_secs`.
+    pub async fn strike(&self, ip: &str, window_secs: u64, threshold: u32, until: DateTime<Utc>) {
+        let store: &dyn RateLimitStore = match self.store.strike(ip, window_secs).await {
+            Ok(n) if n < threshold => return,
+            Ok(_) => self.store.as_ref(),
+            Err(e) => match self.fall_back(&e) {
+                Some(memory) => match memory.strike(ip, window_secs).await {
+                    Ok(n) if n >= threshold => memory,
+                    _ => return,
+                },
+                None => return,
+            },
+        };
+        if let Err(e) = store.block(ip, until).await {
+            warn!("Failed to block {}: {}", ip, e);
         }
     }
 
     /// Cleanup old entries to prevent memory bloat
     /// Should be called periodically (e.g., every minute)
-    pub fn cleanup(&self) {
-        let now = Instant::now();
-        let cutoff = now - self.cleanup_threshold;
-
-        let mut requests = self.requests.write().unwrap();
-
-        // Remove entries where all timestamps are expired
-        requests.retain(|_, timestamps| {
-            timestamps.retain(|&ts| ts > cutoff);
-            !timestamps.is_empty()
-        });
+    pub async fn cleanup(&self) {
+        self.store.cleanup().await;
+        if let Some(memory) = &self.fallback {
+            memory.cleanup().await;
+        }
     }
+}
 
-    /// Get the number of tracked keys (for monitoring)
-    #[allow(dead_code)]
-    pub fn key_count(&self) -> usize {
-        self.requests.read().unwrap().len()
+/// What a request gets when no store can count it: through, as if first.
+fn allow(limit: u32, window_secs: u64) -> RateLimitDecision {
+    RateLimitDecision {
+        allowed: true,
+        info: RateLimitInfo {
+            limit,
+            remaining: limit.saturating_sub(1),
+            reset_in_secs: window_secs,
+        },
     }
 }
 
 impl Default for RateLimiter {
     fn default() -> Self {
-        Self::new(300) // 5 minutes default cleanup threshold
+        Self::new(Arc::new(MemoryStore::default()))
     }
 }
 
@@ -150,42 +192,57 @@ impl Default for RateLimiter {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_rate_limit_allows_under_limit() {
+    #[tokio::test]
+    async fn test_rate_limit_allows_under_limit() {
         let limiter = RateLimiter::default();
 
         // Should allow 5 requests when limit is 5
         for i in 0..5 {
-            let result = limiter.check("test_ip", 5, 60);
+            let result = limiter.check("test_ip", 5, 60).await;
             assert!(result.is_ok(), "Request {} should be allowed", i);
         }
     }
 
-    #[test]
-    fn test_rate_limit_blocks_over_limit() {
+    #[tokio::test]
+    async fn test_rate_limit_blocks_over_limit() {
         let limiter = RateLimiter::default();
 
         // Use up the limit
         for _ in 0..5 {
-            let _ = limiter.check("test_ip", 5, 60);
+            let _ = limiter.check("test_ip", 5, 60).await;
         }
 
         // 6th request should be blocked
-        let result = limiter.check("test_ip", 5, 60);
+        let result = limiter.check("test_ip", 5, 60).await;
         assert!(result.is_err(), "Request over limit should be blocked");
     }
 
-    #[test]
-    fn test_different_keys_independent() {
+    #[tokio::test]
+    async fn test_peek_does_not_count() {
+        let limiter = RateLimiter::default();
+
+        for _ in 0..3 {
+            let _ = limiter.check("test_ip", 5, 60).await;
+        }
+
+        let info = limiter.peek("test_ip", 5, 60).await;
+        assert_eq!(info.remaining, 2);
+        assert!(info.reset_in_secs <= 60);
+        assert_eq!(limiter.peek("test_ip", 5, 60).await.remaining, 2);
+        assert_eq!(limiter.peek("other_ip", 5, 60).await.remaining, 5);
+    }
+
+    #[tokio::test]
+    async fn test_different_keys_independent() {
         let limiter = RateLimiter::default();
 
         // Use up limit for ip1
         for _ in 0..5 {
-            let _ = limiter.check("ip1", 5, 60);
+            let _ = limiter.check("ip1", 5, 60).await;
         }
 
         // ip2 should still be allowed
-        let result = limiter.check("ip2", 5, 60);
+        let result = limiter.check("ip2", 5, 60).await;
         assert!(result.is_ok(), "Different IP should have independent limit");
     }
 }
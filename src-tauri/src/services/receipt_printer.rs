@@ -0,0 +1,729 @@
+//! Thermal receipt printing for the cashier desk.
+//!
+//! Payment receipts and short invoices are laid out as lines of text for a 58
+//! or 80 mm roll and sent as ESC/POS, either to a network printer over raw TCP
+//! (port 9100 unless given) or to a USB printer through its device file, e.g.
+//! `/dev/usb/lp0`, or a shared printer such as `\\localhost\Receipt` on
+//! Windows. The header and footer are tenant settings written in the email
+//! template syntax. Labels follow the tenant's `default_locale`. Printers get
+//! plain ASCII; other characters print as `?`.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::Invoice;
+use crate::services::announcement_recurrence::timezone_for;
+use crate::services::email_template_service::render_str;
+use crate::services::i18n;
+use crate::services::locale::tenant_locale;
+use crate::services::money;
+use crate::services::SettingsService;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+pub const PRINTER_CONNECTIONS: [&str; 2] = ["network", "usb"];
+pub const PAPER_WIDTHS: [&str; 2] = ["58", "80"];
+pub const PRINT_KINDS: [&str; 2] = ["receipt", "invoice"];
+
+const DEFAULT_HEADER: &str = "{{tenant_name}}";
+const DEFAULT_FOOTER: &str = "Terima kasih / Thank you";
+const DEFAULT_NETWORK_PORT: u16 = 9100;
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_TEMPLATE_LEN: usize = 1000;
+
+/// Characters per line in the printer's default font.
+fn columns(paper_width: &str) -> usize {
+    if paper_width == "80" {
+        48
+    } else {
+        32
+    }
+}
+
+/// Rejects printer settings the cashier desk could not use.
+pub fn validate_setting(key: &str, value: &str) -> AppResult<()> {
+    let value = value.trim();
+    match key {
+        "receipt_printer_connection" if !PRINTER_CONNECTIONS.contains(&value) => {
+            Err(AppError::Validation(format!(
+                "receipt_printer_connection must be one of: {}",
+                PRINTER_CONNECTIONS.join(", ")
+            )))
+        }
+        "receipt_paper_width" if !PAPER_WIDTHS.contains(&value) => {
+            Err(AppError::Validation(format!(
+                "receipt_paper_width must be one of: {}",
+                PAPER_WIDTHS.join(", ")
+            )))
+        }
+        "receipt_header_template" | "receipt_footer_template" => {
+            if value.len() > MAX_TEMPLATE_LEN {
+                return Err(AppError::Validation(format!(
+                    "{} must be at most {} characters",
+                    key, MAX_TEMPLATE_LEN
+                )));
+            }
+            render_str(value, &HashMap::new(), false)
+                .map(|_| ())
+                .map_err(|e| AppError::Validation(format!("Invalid {}: {}", key, e)))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrinterTarget {
+    Network(String),
+    Device(String),
+}
+
+impl PrinterTarget {
+    /// `host` or `host:port` for network printers, a device path for USB ones.
+    pub fn parse(connection: &str, address: &str) -> AppResult<Self> {
+        let address = address.trim();
+        if address.is_empty() {
+            return Err(AppError::Validation(
+                "No receipt printer configured. Set receipt_printer_address in settings."
+                    .to_string(),
+            ));
+        }
+        match connection {
+            "usb" => Ok(Self::Device(address.to_string())),
+            _ => {
+                let port = match address.rsplit_once(':') {
+                    // A bare IPv6 address has colons but no port.
+                    Some((host, port)) if !host.contains(':') || host.ends_with(']') => Some(port),
+                    _ => None,
+                };
+                match port {
+                    Some(port) => {
+                        port.parse::<u16>().map_err(|_| {
+                            AppError::Validation(format!("Invalid printer port in {}", address))
+                        })?;
+                        Ok(Self::Network(address.to_string()))
+                    }
+                    None if address.contains(':') => Ok(Self::Network(format!(
+                        "[{}]:{}",
+                        address, DEFAULT_NETWORK_PORT
+                    ))),
+                    None => Ok(Self::Network(format!(
+                        "{}:{}",
+                        address, DEFAULT_NETWORK_PORT
+                    ))),
+                }
+            }
+        }
+    }
+
+    async fn send(&self, bytes: &[u8]) -> std::io::Result<()> {
+        match self {
+            Self::Network(addr) => {
+                let mut stream = tokio::net::TcpStream::connect(addr).await?;
+                stream.write_all(bytes).await?;
+                stream.shutdown().await
+            }
+            Self::Device(path) => {
+                let mut file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+                file.write_all(bytes).await?;
+                file.flush().await
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for PrinterTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Network(addr) => write!(f, "tcp://{}", addr),
+            Self::Device(path) => f.write_str(path),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Normal,
+    Bold,
+    /// Double width and height; fits half as many characters.
+    Large,
+}
+
+enum Line {
+    Text {
+        text: String,
+        centered: bool,
+        style: Style,
+    },
+    Rule,
+}
+
+/// A receipt as lines of text, printable as ESC/POS or shown as a preview.
+pub struct ReceiptLayout {
+    columns: usize,
+    lines: Vec<Line>,
+}
+
+impl ReceiptLayout {
+    fn new(columns: usize) -> Self {
+        Self {
+            columns,
+            lines: Vec::new(),
+        }
+    }
+
+    fn width(&self, style: Style) -> usize {
+        if style == Style::Large {
+            self.columns / 2
+        } else {
+            self.columns
+        }
+    }
+
+    fn push(&mut self, text: &str, centered: bool, style: Style) {
+        let width = self.width(style);
+        for raw in text.lines() {
+            for line in wrap(&ascii(raw), width) {
+                self.lines.push(Line::Text {
+                    text: line,
+                    centered,
+                    style,
+                });
+            }
+        }
+    }
+
+    fn center(&mut self, text: &str, style: Style) {
+        self.push(text, true, style);
+    }
+
+    fn text(&mut self, text: &str) {
+        self.push(text, false, Style::Normal);
+    }
+
+    /// `label` on the left and `value` flush right, on one line when they fit.
+    fn pair(&mut self, label: &str, value: &str, style: Style) {
+        let (label, value) = (ascii(label), ascii(value));
+        let width = self.width(style);
+        let used = label.chars().count() + value.chars().count();
+        if used < width {
+            let gap = " ".repeat(width - used);
+            self.lines.push(Line::Text {
+                text: format!("{}{}{}", label, gap, value),
+                centered: false,
+                style,
+            });
+        } else {
+            self.push(&label, false, style);
+            for line in wrap(&value, width) {
+                self.lines.push(Line::Text {
+                    text: format!("{:>width$}", line, width = width),
+                    centered: false,
+                    style,
+                });
+            }
+        }
+    }
+
+    fn rule(&mut self) {
+        self.lines.push(Line::Rule);
+    }
+
+    /// How the receipt will look, for an on-screen preview.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for line in &self.lines {
+            match line {
+                Line::Rule => out.push_str(&"-".repeat(self.columns)),
+                Line::Text {
+                    text,
+                    centered,
+                    style,
+                } => {
+                    let text = if *style == Style::Large {
+                        text.chars().flat_map(|c| [c, ' ']).collect::<String>()
+                    } else {
+                        text.clone()
+                    };
+                    let text = text.trim_end();
+                    if *centered {
+                        let pad = self.columns.saturating_sub(text.chars().count()) / 2;
+                        out.push_str(&" ".repeat(pad));
+                    }
+                    out.push_str(text);
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// ESC/POS bytes: initialise, the lines, then feed and cut.
+    pub fn to_escpos(&self) -> Vec<u8> {
+        let mut out = vec![0x1B, b'@'];
+        for line in &self.lines {
+            match line {
+                Line::Rule => {
+                    out.extend_from_slice(&[0x1B, b'a', 0]);
+                    out.extend_from_slice("-".repeat(self.columns).as_bytes());
+                }
+                Line::Text {
+                    text,
+                    centered,
+                    style,
+                } => {
+                    out.extend_from_slice(&[0x1B, b'a', u8::from(*centered)]);
+                    out.extend_from_slice(&[0x1B, b'E', u8::from(*style != Style::Normal)]);
+                    out.extend_from_slice(&[
+                        0x1D,
+                        b'!',
+                        if *style == Style::Large { 0x11 } else { 0 },
+                    ]);
+                    out.extend_from_slice(text.as_bytes());
+                    out.extend_from_slice(&[0x1B, b'E', 0, 0x1D, b'!', 0]);
+                }
+            }
+            out.push(b'\n');
+        }
+        // Feed past the cutter, then a partial cut.
+        out.extend_from_slice(&[0x1B, b'd', 4, 0x1D, b'V', 66, 0]);
+        out
+    }
+}
+
+fn ascii(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\t' => ' ',
+            c if c.is_ascii() && !c.is_ascii_control() => c,
+            _ => '?',
+        })
+        .collect()
+}
+
+/// Word-wraps to `width` columns, breaking words that are longer than a line.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split(' ').filter(|w| !w.is_empty()) {
+        let mut word = word.to_string();
+        loop {
+            let needed = if current.is_empty() {
+                word.len()
+            } else {
+                current.len() + 1 + word.len()
+            };
+            if needed <= width {
+                if !current.is_empty() {
+                    current.push(' ');
+                }
+                current.push_str(&word);
+                break;
+            }
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+                continue;
+            }
+            let rest = word.split_off(width);
+            lines.push(word);
+            word = rest;
+        }
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+pub struct ReceiptData<'a> {
+    pub tenant_name: &'a str,
+    pub invoice: &'a Invoice,
+    pub customer_name: Option<&'a str>,
+    pub cashier: Option<&'a str>,
+    pub printed_at: DateTime<Utc>,
+    pub tz: Tz,
+    /// Language of the printed labels; English when `None`.
+    pub locale: Option<&'a str>,
+}
+
+/// Lays out a payment receipt (`receipt`) or a short invoice (`invoice`).
+pub fn build_layout(
+    kind: &str,
+    data: &ReceiptData<'_>,
+    header: &str,
+    footer: &str,
+    paper_width: &str,
+) -> AppResult<ReceiptLayout> {
+    let invoice = data.invoice;
+    let receipt = match kind {
+        "receipt" => true,
+        "invoice" => false,
+        _ => {
+            return Err(AppError::Validation(format!(
+                "Print kind must be one of: {}",
+                PRINT_KINDS.join(", ")
+            )))
+        }
+    };
+    if receipt && invoice.status != "paid" {
+        return Err(AppError::Validation(
+            "Only paid invoices have a payment receipt".to_string(),
+        ));
+    }
+
+    let local = |at: DateTime<Utc>| {
+        at.with_timezone(&data.tz)
+            .format("%d/%m/%Y %H:%M")
+            .to_string()
+    };
+    let vars: HashMap<&str, String> = HashMap::from([
+        ("tenant_name", data.tenant_name.to_string()),
+        ("invoice_number", invoice.invoice_number.clone()),
+        (
+            "customer_name",
+            data.customer_name.unwrap_or_default().to_string(),
+        ),
+        ("cashier", data.cashier.unwrap_or_default().to_string()),
+        ("date", local(data.printed_at)),
+    ]);
+    let render = |template: &str| render_str(template, &vars, false).map_err(AppError::Validation);
+    let tr = |text: &str| i18n::translate(data.locale, text);
+
+    let mut layout = ReceiptLayout::new(columns(paper_width));
+    let header = render(header)?;
+    let mut header_lines = header.lines().filter(|l| !l.trim().is_empty());
+    if let Some(first) = header_lines.next() {
+        layout.center(first.trim(), Style::Large);
+    }
+    for line in header_lines {
+        layout.center(line.trim(), Style::Normal);
+    }
+    layout.rule();
+    layout.center(
+        &tr(if receipt {
+            "PAYMENT RECEIPT"
+        } else {
+            "INVOICE"
+        }),
+        Style::Bold,
+    );
+    layout.pair("No", &invoice.invoice_number, Style::Normal);
+    if receipt {
+        let paid_at = invoice.paid_at.unwrap_or(data.printed_at);
+        layout.pair(&tr("Paid"), &local(paid_at), Style::Normal);
+    } else {
+        layout.pair(&tr("Date"), &local(invoice.created_at), Style::Normal);
+        layout.pair(&tr("Due"), &local(invoice.due_date), Style::Normal);
+    }
+    if let Some(customer) = data.customer_name.filter(|c| !c.trim().is_empty()) {
+        layout.pair(&tr("Customer"), customer, Style::Normal);
+    }
+    layout.rule();
+    match invoice.description.as_deref() {
+        Some(description) => layout.text(description),
+        None => layout.text(&tr("Payment")),
+    }
+    layout.rule();
+    layout.pair(
+        "TOTAL",
+        &money::format_money(invoice.amount, &invoice.currency_code, data.locale),
+        Style::Bold,
+    );
+    if receipt {
+        let method = invoice.payment_method.as_deref().unwrap_or("-");
+        layout.pair(&tr("Method"), method, Style::Normal);
+        if let Some(cashier) = data.cashier.filter(|c| !c.trim().is_empty()) {
+            layout.pair(&tr("Cashier"), cashier, Style::Normal);
+        }
+        layout.center(&tr("** PAID **"), Style::Bold);
+    } else {
+        layout.pair(
+            &tr("Status"),
+            &tr(&invoice.status).to_uppercase(),
+            Style::Normal,
+        );
+    }
+
+    let footer = render(footer)?;
+    let footer_lines: Vec<&str> = footer
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    if !footer_lines.is_empty() {
+        layout.rule();
+        for line in footer_lines {
+            layout.center(line, Style::Normal);
+        }
+    }
+    layout.center(
+        &tr(&format!("Printed {}", local(data.printed_at))),
+        Style::Normal,
+    );
+    Ok(layout)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrintResult {
+    pub printer: String,
+    pub bytes: usize,
+}
+
+#[derive(Clone)]
+pub struct ReceiptPrinterService {
+    pool: DbPool,
+    settings: SettingsService,
+}
+
+impl ReceiptPrinterService {
+    pub fn new(pool: DbPool, settings: SettingsService) -> Self {
+        Self { pool, settings }
+    }
+
+    async fn setting(&self, tenant_id: &str, key: &str) -> AppResult<Option<String>> {
+        Ok(self
+            .settings
+            .get_value(Some(tenant_id), key)
+            .await?
+            .filter(|v| !v.trim().is_empty()))
+    }
+
+    /// Renders `kind` for an invoice of the tenant without printing it.
+    pub async fn layout(
+        &self,
+        tenant_id: &str,
+        invoice: &Invoice,
+        kind: &str,
+        cashier_id: Option<&str>,
+    ) -> AppResult<ReceiptLayout> {
+        let tenant_name: String = sqlx::query_scalar("SELECT name FROM tenants WHERE id = $1")
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .unwrap_or_default();
+        let customer_name: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT c.name
+            FROM customer_subscriptions cs
+            JOIN customers c ON c.id = cs.customer_id
+            WHERE cs.tenant_id = $1
+              AND ($2 = 'pkgsub:' || cs.id OR $2 LIKE 'pkgsub:' || cs.id || ':%')
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(invoice.external_id.as_deref().unwrap_or_default())
+        .fetch_optional(&self.pool)
+        .await?;
+        let cashier: Option<String> = match cashier_id {
+            Some(id) => {
+                sqlx::query_scalar("SELECT name FROM users WHERE id = $1")
+                    .bind(id)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            None => None,
+        };
+
+        let header = self.setting(tenant_id, "receipt_header_template").await?;
+        let footer = self.setting(tenant_id, "receipt_footer_template").await?;
+        let paper_width = self.setting(tenant_id, "receipt_paper_width").await?;
+        let locale = tenant_locale(&self.pool, Some(tenant_id)).await;
+        let data = ReceiptData {
+            tenant_name: &tenant_name,
+            invoice,
+            customer_name: customer_name.as_deref(),
+            cashier: cashier.as_deref(),
+            printed_at: Utc::now(),
+            tz: timezone_for(&self.pool, Some(tenant_id)).await,
+            locale: locale.as_deref(),
+        };
+        build_layout(
+            kind,
+            &data,
+            header.as_deref().unwrap_or(DEFAULT_HEADER),
+            footer.as_deref().unwrap_or(DEFAULT_FOOTER),
+            paper_width.as_deref().unwrap_or("58"),
+        )
+    }
+
+    pub async fn print(&self, tenant_id: &str, layout: &ReceiptLayout) -> AppResult<PrintResult> {
+        let connection = self
+            .setting(tenant_id, "receipt_printer_connection")
+            .await?
+            .unwrap_or_else(|| "network".to_string());
+        let address = self
+            .setting(tenant_id, "receipt_printer_address")
+            .await?
+            .unwrap_or_default();
+        let target = PrinterTarget::parse(&connection, &address)?;
+
+        let bytes = layout.to_escpos();
+        tokio::time::timeout(SEND_TIMEOUT, target.send(&bytes))
+            .await
+            .map_err(|_| AppError::Internal(format!("Printer {} did not respond", target)))?
+            .map_err(|e| AppError::Internal(format!("Failed to print on {}: {}", target, e)))?;
+        Ok(PrintResult {
+            printer: target.to_string(),
+            bytes: bytes.len(),
+        })
+    }
+
+    /// A sample receipt with the tenant's header and footer, to check the setup.
+    pub async fn print_test(&self, tenant_id: &str) -> AppResult<PrintResult> {
+        let paper_width = self
+            .setting(tenant_id, "receipt_paper_width")
+            .await?
+            .unwrap_or_else(|| "58".to_string());
+        let mut layout = ReceiptLayout::new(columns(&paper_width));
+        layout.center("TEST PRINT", Style::Large);
+        layout.rule();
+        layout.pair("Paper", &format!("{} mm", paper_width), Style::Normal);
+        layout.pair("Columns", &layout.columns.to_string(), Style::Normal);
+        layout.pair("Bold", "OK", Style::Bold);
+        layout.rule();
+        layout.text("The quick brown fox jumps over the lazy dog 0123456789");
+        self.print(tenant_id, &layout).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invoice(status: &str) -> Invoice {
+        let at = DateTime::parse_from_rfc3339("2026-03-01T03:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        Invoice {
+            id: "inv-1".to_string(),
+            tenant_id: "t1".to_string(),
+            invoice_number: "INV-2026-0042".to_string(),
+            amount: 150000.0,
+            currency_code: "IDR".to_string(),
+            base_currency_code: "IDR".to_string(),
+            fx_rate: None,
+            fx_source: None,
+            fx_fetched_at: None,
+            status: status.to_string(),
+            description: Some("Internet 20 Mbps - Maret 2026".to_string()),
+            due_date: at,
+            paid_at: Some(at),
+            payment_method: Some("cash".to_string()),
+            external_id: Some("pkgsub:s1".to_string()),
+            merchant_id: Some("t1".to_string()),
+            proof_attachment: None,
+            rejection_reason: None,
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    fn data(invoice: &Invoice) -> ReceiptData<'_> {
+        ReceiptData {
+            tenant_name: "Net Jaya",
+            invoice,
+            customer_name: Some("Budi Santoso"),
+            cashier: Some("Sari"),
+            printed_at: invoice.created_at,
+            tz: chrono_tz::Asia::Jakarta,
+            locale: None,
+        }
+    }
+
+    #[test]
+    fn wraps_long_words_and_lines() {
+        assert_eq!(wrap("a bb ccc", 4), vec!["a bb", "ccc"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 4), vec![""]);
+    }
+
+    #[test]
+    fn parses_printer_targets() {
+        assert_eq!(
+            PrinterTarget::parse("network", "192.168.1.50").unwrap(),
+            PrinterTarget::Network("192.168.1.50:9100".to_string())
+        );
+        assert_eq!(
+            PrinterTarget::parse("network", "printer.local:9101").unwrap(),
+            PrinterTarget::Network("printer.local:9101".to_string())
+        );
+        assert_eq!(
+            PrinterTarget::parse("network", "fe80::1").unwrap(),
+            PrinterTarget::Network("[fe80::1]:9100".to_string())
+        );
+        assert_eq!(
+            PrinterTarget::parse("usb", "/dev/usb/lp0").unwrap(),
+            PrinterTarget::Device("/dev/usb/lp0".to_string())
+        );
+        assert!(PrinterTarget::parse("network", "host:abc").is_err());
+        assert!(PrinterTarget::parse("usb", " ").is_err());
+    }
+
+    #[test]
+    fn lays_out_receipt_with_templates() {
+        let paid = invoice("paid");
+        let layout = build_layout(
+            "receipt",
+            &data(&paid),
+            "{{tenant_name}}\nJl. Merdeka 1",
+            "Terima kasih, {{customer_name}}!{{#if cashier}}\nKasir: {{cashier}}{{/if}}",
+            "58",
+        )
+        .unwrap();
+        let text = layout.to_text();
+        assert!(text.contains("N e t   J a y a"), "{}", text);
+        assert!(text.contains("Jl. Merdeka 1"));
+        assert!(text.contains("PAYMENT RECEIPT"));
+        let line = |label: &str| text.lines().find(|l| l.starts_with(label)).unwrap();
+        assert!(line("Paid").ends_with(" 01/03/2026 10:00"));
+        assert!(line("TOTAL").ends_with(" Rp 150.000"));
+        assert_eq!(line("TOTAL").len(), 32);
+        assert!(text.contains("Terima kasih, Budi Santoso!"));
+        assert!(text.contains("Kasir: Sari"));
+        assert!(text.lines().all(|l| l.chars().count() <= 32), "{}", text);
+
+        let bytes = layout.to_escpos();
+        assert_eq!(&bytes[..2], &[0x1B, b'@']);
+        assert_eq!(&bytes[bytes.len() - 4..], &[0x1D, b'V', 66, 0]);
+    }
+
+    #[test]
+    fn receipt_needs_a_paid_invoice() {
+        let pending = invoice("pending");
+        assert!(build_layout("receipt", &data(&pending), "", "", "80").is_err());
+        let text = build_layout("invoice", &data(&pending), "", "", "80")
+            .unwrap()
+            .to_text();
+        assert!(text.contains("Status"));
+        assert!(text.contains("PENDING"));
+        assert!(build_layout("bill", &data(&pending), "", "", "80").is_err());
+    }
+
+    #[test]
+    fn labels_follow_the_locale() {
+        let pending = invoice("pending");
+        let mut indonesian = data(&pending);
+        indonesian.locale = Some("id-ID");
+        let text = build_layout("invoice", &indonesian, "", "", "58")
+            .unwrap()
+            .to_text();
+        assert!(text.contains("TAGIHAN"), "{}", text);
+        assert!(text.contains("MENUNGGU"));
+        assert!(text.lines().any(|l| l.starts_with("Jatuh tempo")));
+        assert!(text.contains("Dicetak 01/03/2026 10:00"));
+        assert!(!text.contains("Due"));
+    }
+
+    #[test]
+    fn validates_settings() {
+        assert!(validate_setting("receipt_printer_connection", "usb").is_ok());
+        assert!(validate_setting("receipt_printer_connection", "bluetooth").is_err());
+        assert!(validate_setting("receipt_paper_width", "80").is_ok());
+        assert!(validate_setting("receipt_paper_width", "72").is_err());
+        assert!(validate_setting("receipt_header_template", "{{tenant_name}}").is_ok());
+        assert!(validate_setting("receipt_footer_template", "{{#if cashier}}x").is_err());
+    }
+}
@@ -0,0 +1,645 @@
+//! Small PDF writer for generated documents such as work order completion
+//! reports (berita acara) and scheduled reports.
+//!
+//! Produces A4 pages of Helvetica text with simple rules, tables and a
+//! signature box, drawn from pen strokes or embedded as a JPEG. Only the standard PDF fonts
+//! are used, so characters outside Latin-1 are printed as `?`.
+
+use crate::error::{AppError, AppResult};
+use std::fmt::Write as _;
+
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const MARGIN: f64 = 50.0;
+const LABEL_WIDTH: f64 = 140.0;
+const SIGNATURE_BOX: (f64, f64) = (220.0, 90.0);
+
+#[derive(Clone, Copy)]
+enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// A baseline JPEG kept as-is; PDF viewers decode it themselves.
+#[derive(Debug, Clone)]
+pub struct JpegImage {
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+    components: u8,
+}
+
+impl JpegImage {
+    /// Reads size and colour components from the frame header.
+    pub fn parse(data: Vec<u8>) -> AppResult<Self> {
+        let invalid = || AppError::Validation("Signature image must be a JPEG".to_string());
+        if data.len() < 4 || data[0] != 0xFF || data[1] != 0xD8 {
+            return Err(invalid());
+        }
+        let mut i = 2;
+        while i + 4 <= data.len() {
+            if data[i] != 0xFF {
+                return Err(invalid());
+            }
+            let marker = data[i + 1];
+            // Fill bytes and markers without a length field.
+            if marker == 0xFF {
+                i += 1;
+                continue;
+            }
+            if marker == 0x01 || (0xD0..=0xD8).contains(&marker) {
+                i += 2;
+                continue;
+            }
+            let len = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+            if len < 2 {
+                return Err(invalid());
+            }
+            let is_frame = (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+            if is_frame {
+                let seg = data.get(i + 4..i + 2 + len).ok_or_else(invalid)?;
+                if seg.len() < 6 {
+                    return Err(invalid());
+                }
+                let height = u16::from_be_bytes([seg[1], seg[2]]) as u32;
+                let width = u16::from_be_bytes([seg[3], seg[4]]) as u32;
+                let components = seg[5];
+                if width == 0 || height == 0 || !matches!(components, 1 | 3) {
+                    return Err(AppError::Validation(
+                        "Signature image must be a greyscale or RGB JPEG".to_string(),
+                    ));
+                }
+                return Ok(Self {
+                    data,
+                    width,
+                    height,
+                    components,
+                });
+            }
+            // Start of scan before any frame header.
+            if marker == 0xDA {
+                break;
+            }
+            i += 2 + len;
+        }
+        Err(invalid())
+    }
+}
+
+/// What goes into the signature box.
+pub enum SignatureMark<'a> {
+    /// Pen strokes in the pad's coordinates, y growing downwards.
+    Strokes(&'a [Vec<[f64; 2]>]),
+    Jpeg(JpegImage),
+}
+
+/// Appends `text` as the body of a PDF literal string.
+fn push_text(out: &mut Vec<u8>, text: &str) {
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                out.push(c as u8);
+            }
+            '\t' | '\r' | '\n' => out.push(b' '),
+            c if (c as u32) < 0x20 => {}
+            c if (c as u32) <= 0xFF => out.push(c as u32 as u8),
+            _ => out.push(b'?'),
+        }
+    }
+}
+
+/// Greedy word wrap to `max_chars` per line; words longer than a line are cut.
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            while word.len() > max_chars {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                lines.push(word.drain(..max_chars).collect());
+            }
+            let word: String = word.into_iter().collect();
+            let needed =
+                line.chars().count() + usize::from(!line.is_empty()) + word.chars().count();
+            if needed > max_chars && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Characters of Helvetica at `size` that fit in `width`, erring on the short
+/// side since glyph widths are not measured.
+fn chars_per_line(width: f64, size: f64) -> usize {
+    (width / (size * 0.55)).floor() as usize
+}
+
+pub struct PdfDocument {
+    pages: Vec<Vec<u8>>,
+    page: Vec<u8>,
+    y: f64,
+    images: Vec<JpegImage>,
+}
+
+impl Default for PdfDocument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfDocument {
+    pub fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            page: Vec::new(),
+            y: PAGE_HEIGHT - MARGIN,
+            images: Vec::new(),
+        }
+    }
+
+    fn ensure_space(&mut self, height: f64) {
+        if self.y - height < MARGIN {
+            self.pages.push(std::mem::take(&mut self.page));
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn text_at(&mut self, x: f64, y: f64, font: Font, size: f64, text: &str) {
+        let _ = write!(
+            Ops(&mut self.page),
+            "BT /{} {} Tf {:.2} {:.2} Td (",
+            font.resource(),
+            size,
+            x,
+            y
+        );
+        push_text(&mut self.page, text);
+        self.page.extend_from_slice(b") Tj ET\n");
+    }
+
+    fn lines(&mut self, x: f64, width: f64, font: Font, size: f64, text: &str) {
+        let leading = size * 1.4;
+        for line in wrap(text, chars_per_line(width, size)) {
+            self.ensure_space(leading);
+            self.y -= leading;
+            self.text_at(x, self.y, font, size, &line);
+        }
+    }
+
+    pub fn title(&mut self, text: &str) {
+        self.lines(MARGIN, PAGE_WIDTH - 2.0 * MARGIN, Font::Bold, 16.0, text);
+        self.y -= 6.0;
+    }
+
+    /// Section heading followed by a thin rule.
+    pub fn heading(&mut self, text: &str) {
+        self.ensure_space(40.0);
+        self.y -= 10.0;
+        self.lines(MARGIN, PAGE_WIDTH - 2.0 * MARGIN, Font::Bold, 12.0, text);
+        self.y -= 4.0;
+        let _ = writeln!(
+            Ops(&mut self.page),
+            "0.5 w {:.2} {:.2} m {:.2} {:.2} l S",
+            MARGIN,
+            self.y,
+            PAGE_WIDTH - MARGIN,
+            self.y
+        );
+    }
+
+    pub fn paragraph(&mut self, text: &str) {
+        self.lines(MARGIN, PAGE_WIDTH - 2.0 * MARGIN, Font::Regular, 10.0, text);
+    }
+
+    /// `label` in bold on the left, `value` wrapped in the column beside it.
+    pub fn field(&mut self, label: &str, value: &str) {
+        let size = 10.0;
+        let leading = size * 1.4;
+        let value_width = PAGE_WIDTH - 2.0 * MARGIN - LABEL_WIDTH;
+        let value_lines = wrap(value, chars_per_line(value_width, size));
+        for (i, line) in value_lines.iter().enumerate() {
+            self.ensure_space(leading);
+            self.y -= leading;
+            if i == 0 {
+                self.text_at(MARGIN, self.y, Font::Bold, size, label);
+            }
+            self.text_at(MARGIN + LABEL_WIDTH, self.y, Font::Regular, size, line);
+        }
+    }
+
+    /// Rows under a bold header in equal-width columns. Cells that don't fit
+    /// are clipped, and the header is repeated at the top of each new page.
+    pub fn table(&mut self, columns: &[&str], rows: &[Vec<String>]) {
+        if columns.is_empty() {
+            return;
+        }
+        let size = 8.0;
+        let leading = size * 1.5;
+        let width = (PAGE_WIDTH - 2.0 * MARGIN) / columns.len() as f64;
+        let max_chars = chars_per_line(width - 4.0, size);
+        let header: Vec<String> = columns.iter().map(|c| c.to_string()).collect();
+
+        self.ensure_space(2.0 * leading);
+        self.table_row(&header, Font::Bold, size, width, max_chars);
+        for row in rows {
+            if self.y - leading < MARGIN {
+                self.ensure_space(leading);
+                self.table_row(&header, Font::Bold, size, width, max_chars);
+            }
+            self.table_row(row, Font::Regular, size, width, max_chars);
+        }
+    }
+
+    fn table_row(&mut self, cells: &[String], font: Font, size: f64, width: f64, max_chars: usize) {
+        self.y -= size * 1.5;
+        for (i, cell) in cells.iter().enumerate() {
+            let x = MARGIN + width * i as f64;
+            self.text_at(x, self.y, font, size, &clip(cell, max_chars));
+        }
+        if matches!(font, Font::Bold) {
+            let _ = writeln!(
+                Ops(&mut self.page),
+                "0.5 w {:.2} {:.2} m {:.2} {:.2} l S",
+                MARGIN,
+                self.y - 3.0,
+                PAGE_WIDTH - MARGIN,
+                self.y - 3.0
+            );
+        }
+    }
+
+    pub fn gap(&mut self, height: f64) {
+        self.y -= height;
+    }
+
+    /// Framed signature with `caption` lines (name, date) underneath.
+    pub fn signature(&mut self, mark: SignatureMark<'_>, caption: &[&str]) {
+        let (w, h) = SIGNATURE_BOX;
+        self.ensure_space(h + 20.0 + 14.0 * caption.len() as f64);
+        self.y -= 10.0;
+        let (x, top) = (MARGIN, self.y);
+        let bottom = top - h;
+        let _ = writeln!(
+            Ops(&mut self.page),
+            "q 0.6 G 0.5 w {:.2} {:.2} {:.2} {:.2} re S Q",
+            x,
+            bottom,
+            w,
+            h
+        );
+        let pad = 6.0;
+        match mark {
+            SignatureMark::Strokes(strokes) => draw_strokes(
+                &mut self.page,
+                strokes,
+                x + pad,
+                top - pad,
+                w - 2.0 * pad,
+                h - 2.0 * pad,
+            ),
+            SignatureMark::Jpeg(image) => {
+                let (iw, ih) = (image.width as f64, image.height as f64);
+                let scale = ((w - 2.0 * pad) / iw).min((h - 2.0 * pad) / ih);
+                let (dw, dh) = (iw * scale, ih * scale);
+                let _ = writeln!(
+                    Ops(&mut self.page),
+                    "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im{} Do Q",
+                    dw,
+                    dh,
+                    x + (w - dw) / 2.0,
+                    bottom + (h - dh) / 2.0,
+                    self.images.len() + 1
+                );
+                self.images.push(image);
+            }
+        }
+        self.y = bottom;
+        for line in caption {
+            self.y -= 14.0;
+            self.text_at(x, self.y, Font::Regular, 10.0, line);
+        }
+    }
+
+    /// Serialises the document, numbering the pages in the footer.
+    pub fn finish(mut self) -> Vec<u8> {
+        if !self.page.is_empty() || self.pages.is_empty() {
+            self.pages.push(std::mem::take(&mut self.page));
+        }
+        let total = self.pages.len();
+        for (i, page) in self.pages.iter_mut().enumerate() {
+            let _ = writeln!(
+                Ops(page),
+                "BT /F1 8 Tf {:.2} {:.2} Td (Page {} of {}) Tj ET",
+                PAGE_WIDTH - MARGIN - 50.0,
+                MARGIN / 2.0,
+                i + 1,
+                total
+            );
+        }
+
+        // 1 catalog, 2 page tree, 3-4 fonts, then images, then page/content pairs.
+        let first_image = 5;
+        let first_page = first_image + self.images.len();
+        let object_count = first_page + 2 * total - 1;
+        let mut w = ObjectWriter::new(object_count);
+
+        let kids: Vec<String> = (0..total)
+            .map(|i| format!("{} 0 R", first_page + 2 * i))
+            .collect();
+        w.object(1, b"<< /Type /Catalog /Pages 2 0 R >>");
+        w.object(
+            2,
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                total
+            )
+            .as_bytes(),
+        );
+        w.object(
+            3,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+        );
+        w.object(
+            4,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>",
+        );
+
+        let mut xobjects = String::new();
+        for (i, image) in self.images.iter().enumerate() {
+            let n = first_image + i;
+            let _ = write!(xobjects, " /Im{} {} 0 R", i + 1, n);
+            let color_space = if image.components == 1 {
+                "/DeviceGray"
+            } else {
+                "/DeviceRGB"
+            };
+            w.stream(
+                n,
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace {} /BitsPerComponent 8 /Filter /DCTDecode",
+                    image.width, image.height, color_space
+                ),
+                &image.data,
+            );
+        }
+
+        for (i, content) in self.pages.iter().enumerate() {
+            let n = first_page + 2 * i;
+            w.object(
+                n,
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> /XObject <<{} >> >> /Contents {} 0 R >>",
+                    PAGE_WIDTH, PAGE_HEIGHT, xobjects, n + 1
+                )
+                .as_bytes(),
+            );
+            w.stream(n + 1, "", content);
+        }
+        w.finish()
+    }
+}
+
+/// Fits the strokes' bounding box into the given area, keeping proportions.
+fn draw_strokes(
+    out: &mut Vec<u8>,
+    strokes: &[Vec<[f64; 2]>],
+    left: f64,
+    top: f64,
+    width: f64,
+    height: f64,
+) {
+    let points = strokes.iter().flatten();
+    let (mut min_x, mut min_y) = (f64::MAX, f64::MAX);
+    let (mut max_x, mut max_y) = (f64::MIN, f64::MIN);
+    for [x, y] in points {
+        min_x = min_x.min(*x);
+        min_y = min_y.min(*y);
+        max_x = max_x.max(*x);
+        max_y = max_y.max(*y);
+    }
+    if min_x > max_x {
+        return;
+    }
+    let (span_x, span_y) = ((max_x - min_x).max(1.0), (max_y - min_y).max(1.0));
+    let scale = (width / span_x).min(height / span_y);
+    let off_x = left + (width - span_x * scale) / 2.0;
+    let off_y = top - (height - span_y * scale) / 2.0;
+
+    let mut ops = Ops(out);
+    let _ = writeln!(ops, "q 0 0 0.4 RG 1.4 w 1 J 1 j");
+    for stroke in strokes {
+        for (i, [x, y]) in stroke.iter().enumerate() {
+            let px = off_x + (x - min_x) * scale;
+            let py = off_y - (y - min_y) * scale;
+            let _ = write!(
+                ops,
+                "{:.2} {:.2} {} ",
+                px,
+                py,
+                if i == 0 { "m" } else { "l" }
+            );
+            // A tap leaves a single point; draw it as a dot.
+            if stroke.len() == 1 {
+                let _ = write!(ops, "{:.2} {:.2} l ", px + 0.1, py);
+            }
+        }
+        if !stroke.is_empty() {
+            let _ = writeln!(ops, "S");
+        }
+    }
+    let _ = writeln!(ops, "Q");
+}
+
+/// `fmt::Write` over a byte buffer for content stream operators.
+struct Ops<'a>(&'a mut Vec<u8>);
+
+impl std::fmt::Write for Ops<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Numbered indirect objects followed by the cross-reference table.
+struct ObjectWriter {
+    out: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl ObjectWriter {
+    fn new(object_count: usize) -> Self {
+        let mut out = Vec::new();
+        out.extend_from_slice(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n");
+        Self {
+            out,
+            offsets: vec![0; object_count + 1],
+        }
+    }
+
+    fn object(&mut self, n: usize, body: &[u8]) {
+        self.offsets[n] = self.out.len();
+        self.out
+            .extend_from_slice(format!("{} 0 obj\n", n).as_bytes());
+        self.out.extend_from_slice(body);
+        self.out.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn stream(&mut self, n: usize, dict: &str, data: &[u8]) {
+        let mut body = format!("<< {} /Length {} >>\nstream\n", dict, data.len()).into_bytes();
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\nendstream");
+        self.object(n, &body);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let xref = self.out.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len());
+        for offset in &self.offsets[1..] {
+            let _ = writeln!(table, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len(),
+            xref
+        );
+        self.out.extend_from_slice(table.as_bytes());
+        self.out
+    }
+}
+
+/// `text` cut to `max_chars`, ending in `...` when something was dropped.
+fn clip(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+    format!("{}...", kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiny_jpeg(width: u16, height: u16, components: u8) -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8];
+        // APP0 segment before the frame header.
+        data.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00]);
+        data.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x08, 0x08]);
+        data.extend_from_slice(&height.to_be_bytes());
+        data.extend_from_slice(&width.to_be_bytes());
+        data.push(components);
+        data.extend_from_slice(&[0xFF, 0xD9]);
+        data
+    }
+
+    #[test]
+    fn jpeg_frame_header_is_read() {
+        let image = JpegImage::parse(tiny_jpeg(320, 120, 3)).unwrap();
+        assert_eq!((image.width, image.height, image.components), (320, 120, 3));
+
+        assert!(JpegImage::parse(b"\x89PNG\r\n\x1a\n".to_vec()).is_err());
+        // CMYK is not supported.
+        assert!(JpegImage::parse(tiny_jpeg(10, 10, 4)).is_err());
+    }
+
+    #[test]
+    fn text_is_escaped_and_wrapped() {
+        let mut out = Vec::new();
+        push_text(&mut out, "a(b)\\c\nç✓");
+        assert_eq!(out, b"a\\(b\\)\\\\c \xE7?".to_vec());
+
+        assert_eq!(
+            wrap("one two three four", 9),
+            vec!["one two", "three", "four"]
+        );
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("a\n\nb", 10), vec!["a", "", "b"]);
+
+        assert_eq!(clip("short", 8), "short");
+        assert_eq!(clip("much too long", 8), "much ...");
+    }
+
+    #[test]
+    fn long_tables_repeat_their_header() {
+        let mut doc = PdfDocument::new();
+        let rows: Vec<Vec<String>> = (0..120)
+            .map(|i| vec![format!("INV-{}", i), "100.00".to_string()])
+            .collect();
+        doc.table(&["Invoice", "Amount"], &rows);
+        let text = String::from_utf8_lossy(&doc.finish()).to_string();
+
+        assert!(text.contains("(INV-119)"));
+        let pages = text.matches("(Page ").count();
+        assert!(pages > 1);
+        assert_eq!(text.matches("(Invoice)").count(), pages);
+    }
+
+    #[test]
+    fn document_has_valid_cross_references() {
+        let mut doc = PdfDocument::new();
+        doc.title("Work Completion Report");
+        for i in 0..80 {
+            doc.field("Item", &format!("line {}", i));
+        }
+        let strokes = vec![vec![[0.0, 0.0], [40.0, 20.0]], vec![[10.0, 5.0]]];
+        doc.signature(SignatureMark::Strokes(&strokes), &["Signer"]);
+        doc.signature(
+            SignatureMark::Jpeg(JpegImage::parse(tiny_jpeg(8, 4, 1)).unwrap()),
+            &[],
+        );
+        let pdf = doc.finish();
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("(Page 2 of 2)"));
+        assert!(text.contains("/Im1 Do"));
+
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|v| v.lines().next())
+            .and_then(|v| v.parse().ok())
+            .unwrap();
+        assert!(pdf[startxref..].starts_with(b"xref"));
+
+        // Every entry points at the start of its object.
+        let xref = String::from_utf8_lossy(&pdf[startxref..]);
+        for (n, line) in xref
+            .lines()
+            .skip(3)
+            .take_while(|l| l.ends_with(" n "))
+            .enumerate()
+        {
+            let offset: usize = line[..10].parse().unwrap();
+            let header = format!("{} 0 obj", n + 1);
+            assert!(
+                pdf[offset..].starts_with(header.as_bytes()),
+                "object {}",
+                n + 1
+            );
+        }
+    }
+}
@@ -0,0 +1,1061 @@
+//! Scheduled report delivery.
+//!
+//! A schedule names a report (revenue, aging, SLA or incidents), a format
+//! (CSV or PDF) and a cadence. When it comes due the report is rendered for
+//! the period that just ended (yesterday, last week or last month, in the
+//! tenant's timezone) and emailed to the recipients through the outbox. Due
+//! schedules are picked up by a recurring job; each run claims its schedule
+//! by moving `next_run_at`, so a report goes out once per period.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{BackgroundJob, ReportSchedule, ReportSendResult, UpsertReportScheduleRequest};
+use crate::services::announcement_recurrence::timezone_for;
+use crate::services::audit_service::csv_cell;
+use crate::services::cron_schedule::CronSchedule;
+use crate::services::money::{self, Rounding};
+use crate::services::report_pdf::PdfDocument;
+use crate::services::{EmailAttachment, EmailOutboxService, JobHandler, JobQueue};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::warn;
+use uuid::Uuid;
+
+const JOB_TYPE: &str = "reports.deliver";
+const RUN_INTERVAL_SECS: u64 = 300;
+/// Schedules started per run; the rest wait for the next run.
+const BATCH_LIMIT: i64 = 50;
+/// Rows a single report lists.
+const MAX_ROWS: i64 = 5000;
+const MAX_RECIPIENTS: usize = 20;
+const MAX_NAME_LEN: usize = 100;
+
+pub const REPORT_KINDS: &[&str] = &["revenue", "aging", "sla", "incidents"];
+pub const REPORT_FORMATS: &[&str] = &["csv", "pdf"];
+pub const REPORT_CADENCES: &[&str] = &["daily", "weekly", "monthly"];
+/// Ages of unpaid invoices, by days past due.
+const AGING_BUCKETS: &[&str] = &["current", "1-30", "31-60", "61-90", "90+"];
+
+#[cfg(feature = "postgres")]
+const AMOUNT: &str = "i.amount::float8";
+#[cfg(feature = "sqlite")]
+const AMOUNT: &str = "CAST(i.amount AS REAL)";
+
+/// Permission the person scheduling a report needs to read its data, on top
+/// of the settings permission for managing schedules.
+pub fn report_permission(report: &str) -> (&'static str, &'static str) {
+    match report {
+        "revenue" | "aging" => ("billing", "read"),
+        "sla" => ("support", "read_all"),
+        _ => ("network_routers", "read"),
+    }
+}
+
+fn report_title(report: &str) -> &'static str {
+    match report {
+        "revenue" => "Revenue report",
+        "aging" => "Receivables aging report",
+        "sla" => "Support SLA report",
+        _ => "Network incident report",
+    }
+}
+
+fn cron_for(cadence: &str, send_hour: i32) -> Option<CronSchedule> {
+    let expr = match cadence {
+        "daily" => format!("0 {} * * *", send_hour),
+        "weekly" => format!("0 {} * * 1", send_hour),
+        "monthly" => format!("0 {} 1 * *", send_hour),
+        _ => return None,
+    };
+    CronSchedule::parse(&expr).ok()
+}
+
+/// First send after `after`: every day, every Monday or on the 1st, at
+/// `send_hour` local time.
+fn next_run(cadence: &str, send_hour: i32, after: DateTime<Utc>, tz: Tz) -> DateTime<Utc> {
+    cron_for(cadence, send_hour)
+        .and_then(|cron| cron.next_after(after, tz))
+        .unwrap_or_else(|| after + Duration::days(1))
+}
+
+fn local_midnight(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+/// The whole day, week (Monday to Sunday) or month before the one `at` falls
+/// in, as `[start, end)`.
+fn report_period(cadence: &str, at: DateTime<Utc>, tz: Tz) -> (DateTime<Utc>, DateTime<Utc>) {
+    let today = at.with_timezone(&tz).date_naive();
+    let (start, end) = match cadence {
+        "weekly" => {
+            let monday = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+            (monday - Duration::days(7), monday)
+        }
+        "monthly" => {
+            let first = today.with_day(1).unwrap_or(today);
+            let previous = (first - Duration::days(1)).with_day(1).unwrap_or(first);
+            (previous, first)
+        }
+        _ => (today - Duration::days(1), today),
+    };
+    (local_midnight(start, tz), local_midnight(end, tz))
+}
+
+fn aging_bucket(days_overdue: i64) -> &'static str {
+    match days_overdue {
+        i64::MIN..=0 => AGING_BUCKETS[0],
+        1..=30 => AGING_BUCKETS[1],
+        31..=60 => AGING_BUCKETS[2],
+        61..=90 => AGING_BUCKETS[3],
+        _ => AGING_BUCKETS[4],
+    }
+}
+
+/// First-response and resolution targets by ticket priority.
+fn sla_targets(priority: &str) -> (Duration, Duration) {
+    match priority {
+        "urgent" => (Duration::hours(1), Duration::hours(8)),
+        "high" => (Duration::hours(4), Duration::hours(24)),
+        "low" => (Duration::hours(24), Duration::hours(120)),
+        _ => (Duration::hours(8), Duration::hours(72)),
+    }
+}
+
+/// `met`, `missed`, or `pending` while there is still time.
+fn sla_outcome(
+    start: DateTime<Utc>,
+    done: Option<DateTime<Utc>>,
+    target: Duration,
+    now: DateTime<Utc>,
+) -> &'static str {
+    match done {
+        Some(done) if done - start <= target => "met",
+        Some(_) => "missed",
+        None if now - start > target => "missed",
+        None => "pending",
+    }
+}
+
+fn local_time(at: DateTime<Utc>, tz: Tz) -> String {
+    at.with_timezone(&tz).format("%Y-%m-%d %H:%M").to_string()
+}
+
+fn minutes(d: Duration) -> String {
+    d.num_minutes().to_string()
+}
+
+fn percent(part: usize, whole: usize) -> String {
+    if whole == 0 {
+        return "-".to_string();
+    }
+    format!("{:.1}%", part as f64 * 100.0 / whole as f64)
+}
+
+/// Sum of minor-unit amounts and count per key.
+fn totals_by<K: Ord>(items: impl Iterator<Item = (K, i64)>) -> BTreeMap<K, (i64, usize)> {
+    let mut totals = BTreeMap::new();
+    for (key, amount) in items {
+        let entry = totals.entry(key).or_insert((0, 0));
+        entry.0 += amount;
+        entry.1 += 1;
+    }
+    totals
+}
+
+fn normalize_recipients(recipients: &[String]) -> AppResult<Vec<String>> {
+    let mut out: Vec<String> = Vec::new();
+    for r in recipients.iter().map(|r| r.trim().to_lowercase()) {
+        if r.is_empty() {
+            continue;
+        }
+        let valid = r.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty() && domain.contains('.') && !r.contains(char::is_whitespace)
+        });
+        if !valid {
+            return Err(AppError::Validation(format!(
+                "'{}' is not an email address",
+                r
+            )));
+        }
+        if !out.contains(&r) {
+            out.push(r);
+        }
+    }
+    if out.is_empty() || out.len() > MAX_RECIPIENTS {
+        return Err(AppError::Validation(format!(
+            "Add between 1 and {} recipients",
+            MAX_RECIPIENTS
+        )));
+    }
+    Ok(out)
+}
+
+fn validate(req: &UpsertReportScheduleRequest) -> AppResult<(String, i32, Vec<String>)> {
+    let name = req.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "Name must be 1-{} characters",
+            MAX_NAME_LEN
+        )));
+    }
+    for (field, value, allowed) in [
+        ("report", &req.report, REPORT_KINDS),
+        ("format", &req.format, REPORT_FORMATS),
+        ("cadence", &req.cadence, REPORT_CADENCES),
+    ] {
+        if !allowed.contains(&value.as_str()) {
+            return Err(AppError::Validation(format!(
+                "{} must be one of: {}",
+                field,
+                allowed.join(", ")
+            )));
+        }
+    }
+    let send_hour = req.send_hour.unwrap_or(7);
+    if !(0..=23).contains(&send_hour) {
+        return Err(AppError::Validation(
+            "send_hour must be between 0 and 23".to_string(),
+        ));
+    }
+    Ok((
+        name.to_string(),
+        send_hour,
+        normalize_recipients(&req.recipients)?,
+    ))
+}
+
+/// A rendered report before it is turned into a file.
+struct ReportData {
+    summary: Vec<(String, String)>,
+    columns: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+impl ReportData {
+    fn to_csv(&self) -> Vec<u8> {
+        let header: Vec<String> = self.columns.iter().map(|c| c.to_string()).collect();
+        let mut out = String::new();
+        for row in std::iter::once(&header).chain(&self.rows) {
+            let cells: Vec<String> = row.iter().map(|c| csv_cell(c)).collect();
+            out.push_str(&cells.join(","));
+            out.push('\n');
+        }
+        out.into_bytes()
+    }
+
+    fn to_pdf(&self, title: &str, tenant_name: &str, period: &str) -> Vec<u8> {
+        let mut doc = PdfDocument::new();
+        doc.title(title);
+        doc.field("Organization", tenant_name);
+        doc.field("Period", period);
+        doc.heading("Summary");
+        for (label, value) in &self.summary {
+            doc.field(label, value);
+        }
+        doc.heading("Details");
+        if self.rows.is_empty() {
+            doc.paragraph("Nothing to report for this period.");
+        } else {
+            doc.table(self.columns, &self.rows);
+        }
+        doc.finish()
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct InvoiceLine {
+    invoice_number: String,
+    customer_name: Option<String>,
+    amount: f64,
+    currency_code: String,
+    status: String,
+    due_date: DateTime<Utc>,
+    paid_at: Option<DateTime<Utc>>,
+    payment_method: Option<String>,
+}
+
+impl InvoiceLine {
+    fn minor(&self) -> i64 {
+        money::to_minor(self.amount, &self.currency_code, Rounding::HalfUp)
+    }
+}
+
+/// A minor-unit total as a report cell, `150000` or `12.50`.
+fn total_cell(minor: i64, currency: &str) -> String {
+    money::format_plain(money::from_minor(minor, currency), currency)
+}
+
+#[derive(sqlx::FromRow)]
+struct TicketLine {
+    id: String,
+    subject: String,
+    priority: String,
+    status: String,
+    created_at: DateTime<Utc>,
+    closed_at: Option<DateTime<Utc>>,
+    first_response_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct IncidentLine {
+    router_name: Option<String>,
+    incident_type: String,
+    severity: String,
+    status: String,
+    title: String,
+    first_seen_at: DateTime<Utc>,
+    resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct ReportScheduleService {
+    pool: DbPool,
+    outbox: EmailOutboxService,
+}
+
+impl ReportScheduleService {
+    pub fn new(pool: DbPool, outbox: EmailOutboxService) -> Self {
+        Self { pool, outbox }
+    }
+
+    /// Sends due reports every five minutes on the job queue.
+    pub async fn schedule(self, queue: &JobQueue) {
+        queue
+            .register_recurring(
+                JOB_TYPE,
+                std::time::Duration::from_secs(RUN_INTERVAL_SECS),
+                Arc::new(self),
+            )
+            .await;
+    }
+
+    pub async fn list(&self, tenant_id: &str) -> AppResult<Vec<ReportSchedule>> {
+        Ok(sqlx::query_as(
+            "SELECT * FROM report_schedules WHERE tenant_id = $1 ORDER BY created_at ASC",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn get(&self, tenant_id: &str, id: &str) -> AppResult<ReportSchedule> {
+        sqlx::query_as("SELECT * FROM report_schedules WHERE id = $1 AND tenant_id = $2")
+            .bind(id)
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Report schedule not found".to_string()))
+    }
+
+    pub async fn create(
+        &self,
+        tenant_id: &str,
+        req: UpsertReportScheduleRequest,
+        created_by: Option<&str>,
+    ) -> AppResult<ReportSchedule> {
+        let (name, send_hour, recipients) = validate(&req)?;
+        let now = Utc::now();
+        let tz = timezone_for(&self.pool, Some(tenant_id)).await;
+        let id = Uuid::new_v4().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO report_schedules
+                (id, tenant_id, name, report, format, cadence, send_hour, recipients, enabled,
+                 next_run_at, created_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $12)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&name)
+        .bind(&req.report)
+        .bind(&req.format)
+        .bind(&req.cadence)
+        .bind(send_hour)
+        .bind(sqlx::types::Json(&recipients))
+        .bind(req.enabled.unwrap_or(true))
+        .bind(next_run(&req.cadence, send_hour, now, tz))
+        .bind(created_by)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        self.get(tenant_id, &id).await
+    }
+
+    pub async fn update(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        req: UpsertReportScheduleRequest,
+    ) -> AppResult<ReportSchedule> {
+        let existing = self.get(tenant_id, id).await?;
+        let (name, send_hour, recipients) = validate(&req)?;
+        let now = Utc::now();
+        let tz = timezone_for(&self.pool, Some(tenant_id)).await;
+        let next_run_at = if existing.cadence == req.cadence && existing.send_hour == send_hour {
+            existing.next_run_at
+        } else {
+            next_run(&req.cadence, send_hour, now, tz)
+        };
+        sqlx::query(
+            r#"
+            UPDATE report_schedules
+            SET name = $1, report = $2, format = $3, cadence = $4, send_hour = $5,
+                recipients = $6, enabled = $7, next_run_at = $8, updated_at = $9
+            WHERE id = $10 AND tenant_id = $11
+            "#,
+        )
+        .bind(&name)
+        .bind(&req.report)
+        .bind(&req.format)
+        .bind(&req.cadence)
+        .bind(send_hour)
+        .bind(sqlx::types::Json(&recipients))
+        .bind(req.enabled.unwrap_or(existing.enabled))
+        .bind(next_run_at)
+        .bind(now)
+        .bind(id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+        self.get(tenant_id, id).await
+    }
+
+    pub async fn delete(&self, tenant_id: &str, id: &str) -> AppResult<()> {
+        let res = sqlx::query("DELETE FROM report_schedules WHERE id = $1 AND tenant_id = $2")
+            .bind(id)
+            .bind(tenant_id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound("Report schedule not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Sends the report for the last complete period now, without moving
+    /// the schedule's next run.
+    pub async fn send_now(&self, tenant_id: &str, id: &str) -> AppResult<ReportSendResult> {
+        let schedule = self.get(tenant_id, id).await?;
+        let now = Utc::now();
+        let result = self.deliver(&schedule, now).await;
+        self.record_run(&schedule.id, now, result.as_ref().err())
+            .await;
+        result
+    }
+
+    async fn run_due(&self, now: DateTime<Utc>) -> AppResult<()> {
+        let due: Vec<ReportSchedule> = sqlx::query_as(
+            r#"
+            SELECT * FROM report_schedules
+            WHERE enabled = true AND next_run_at <= $1
+            ORDER BY next_run_at ASC
+            LIMIT $2
+            "#,
+        )
+        .bind(now)
+        .bind(BATCH_LIMIT)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for schedule in due {
+            let tz = timezone_for(&self.pool, Some(&schedule.tenant_id)).await;
+            let next = next_run(&schedule.cadence, schedule.send_hour, now, tz);
+            let claimed = sqlx::query(
+                "UPDATE report_schedules SET next_run_at = $1 WHERE id = $2 AND next_run_at <= $3",
+            )
+            .bind(next)
+            .bind(&schedule.id)
+            .bind(now)
+            .execute(&self.pool)
+            .await?
+            .rows_affected()
+                == 1;
+            if !claimed {
+                continue;
+            }
+
+            let result = self.deliver(&schedule, now).await;
+            if let Err(e) = &result {
+                warn!("Scheduled report {} failed: {}", schedule.id, e);
+            }
+            self.record_run(&schedule.id, now, result.as_ref().err())
+                .await;
+        }
+        Ok(())
+    }
+
+    async fn record_run(&self, id: &str, at: DateTime<Utc>, error: Option<&AppError>) {
+        let res = sqlx::query(
+            "UPDATE report_schedules SET last_run_at = $1, last_status = $2, last_error = $3 WHERE id = $4",
+        )
+        .bind(at)
+        .bind(if error.is_some() { "failed" } else { "sent" })
+        .bind(error.map(|e| e.to_string()))
+        .bind(id)
+        .execute(&self.pool)
+        .await;
+        if let Err(e) = res {
+            warn!("Failed to record report run for {}: {}", id, e);
+        }
+    }
+
+    /// Renders the schedule's report and emails it to every recipient.
+    /// Fails only if no recipient could be reached.
+    async fn deliver(
+        &self,
+        schedule: &ReportSchedule,
+        now: DateTime<Utc>,
+    ) -> AppResult<ReportSendResult> {
+        let tenant_id = schedule.tenant_id.as_str();
+        let tz = timezone_for(&self.pool, Some(tenant_id)).await;
+        let (start, end) = report_period(&schedule.cadence, now, tz);
+        let tenant_name: String = sqlx::query_scalar("SELECT name FROM tenants WHERE id = $1")
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .unwrap_or_default();
+
+        let data = match schedule.report.as_str() {
+            "revenue" => self.revenue(tenant_id, start, end, tz).await?,
+            "aging" => self.aging(tenant_id, now, tz).await?,
+            "sla" => self.sla(tenant_id, start, end, tz, now).await?,
+            _ => self.incidents(tenant_id, start, end, tz).await?,
+        };
+
+        let title = report_title(&schedule.report);
+        let last_day = (end - Duration::seconds(1)).with_timezone(&tz).date_naive();
+        let first_day = start.with_timezone(&tz).date_naive();
+        let period = if schedule.report == "aging" {
+            format!("As of {}", local_time(now, tz))
+        } else if first_day == last_day {
+            first_day.to_string()
+        } else {
+            format!("{} to {}", first_day, last_day)
+        };
+
+        let (bytes, content_type) = match schedule.format.as_str() {
+            "csv" => (data.to_csv(), "text/csv"),
+            _ => (data.to_pdf(title, &tenant_name, &period), "application/pdf"),
+        };
+        let attachment = EmailAttachment {
+            filename: format!("{}-{}.{}", schedule.report, first_day, schedule.format),
+            content_type: content_type.to_string(),
+            data: bytes,
+        };
+
+        let subject = format!("{}: {} ({})", tenant_name, schedule.name, period);
+        let summary: Vec<String> = data
+            .summary
+            .iter()
+            .map(|(label, value)| format!("{}: {}", label, value))
+            .collect();
+        let body = format!(
+            "{} for {}, {}.\n\n{}\n\nThe full report is attached. You receive it because you are on the \"{}\" report schedule.",
+            title,
+            tenant_name,
+            period,
+            summary.join("\n"),
+            schedule.name
+        );
+
+        let mut errors = Vec::new();
+        for to in &schedule.recipients {
+            if let Err(e) = self
+                .outbox
+                .send_or_enqueue_with_attachments(
+                    Some(tenant_id.to_string()),
+                    to,
+                    &subject,
+                    &body,
+                    std::slice::from_ref(&attachment),
+                )
+                .await
+            {
+                errors.push(format!("{}: {}", to, e));
+            }
+        }
+        if !errors.is_empty() && errors.len() == schedule.recipients.len() {
+            return Err(AppError::Internal(errors.join("; ")));
+        }
+        if !errors.is_empty() {
+            warn!(
+                "Report {} not sent to every recipient: {}",
+                schedule.id,
+                errors.join("; ")
+            );
+        }
+
+        Ok(ReportSendResult {
+            filename: attachment.filename,
+            period_start: start,
+            period_end: end,
+            rows: data.rows.len(),
+            recipients: schedule.recipients.len() - errors.len(),
+        })
+    }
+
+    async fn revenue(
+        &self,
+        tenant_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tz: Tz,
+    ) -> AppResult<ReportData> {
+        let lines: Vec<InvoiceLine> = sqlx::query_as(&format!(
+            r#"
+            SELECT i.invoice_number, c.name AS customer_name, {AMOUNT} AS amount, i.currency_code,
+                   i.status, i.due_date, i.paid_at, i.payment_method
+            FROM invoices i
+            LEFT JOIN customer_subscriptions cs
+              ON i.external_id = 'pkgsub:' || cs.id OR i.external_id LIKE 'pkgsub:' || cs.id || ':%'
+            LEFT JOIN customers c ON c.id = cs.customer_id
+            WHERE i.tenant_id = $1
+              AND i.external_id LIKE 'pkgsub:%'
+              AND i.status = 'paid'
+              AND i.paid_at >= $2 AND i.paid_at < $3
+            ORDER BY i.paid_at ASC
+            LIMIT $4
+            "#
+        ))
+        .bind(tenant_id)
+        .bind(start)
+        .bind(end)
+        .bind(MAX_ROWS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut summary = vec![("Paid invoices".to_string(), lines.len().to_string())];
+        for (currency, (total, count)) in
+            totals_by(lines.iter().map(|l| (l.currency_code.clone(), l.minor())))
+        {
+            summary.push((
+                format!("Revenue ({})", currency),
+                format!("{} from {} invoices", total_cell(total, &currency), count),
+            ));
+        }
+        for ((method, currency), (total, count)) in totals_by(lines.iter().map(|l| {
+            let method = l.payment_method.clone().unwrap_or_else(|| "-".to_string());
+            ((method, l.currency_code.clone()), l.minor())
+        })) {
+            summary.push((
+                format!("Via {} ({})", method, currency),
+                format!("{} ({})", total_cell(total, &currency), count),
+            ));
+        }
+
+        let rows = lines
+            .iter()
+            .map(|l| {
+                vec![
+                    l.paid_at.map(|at| local_time(at, tz)).unwrap_or_default(),
+                    l.invoice_number.clone(),
+                    l.customer_name.clone().unwrap_or_default(),
+                    money::format_plain(l.amount, &l.currency_code),
+                    l.currency_code.clone(),
+                    l.payment_method.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        Ok(ReportData {
+            summary,
+            columns: &[
+                "Paid at", "Invoice", "Customer", "Amount", "Currency", "Method",
+            ],
+            rows,
+        })
+    }
+
+    /// Unpaid invoices as they stand at `now`, whatever the cadence.
+    async fn aging(&self, tenant_id: &str, now: DateTime<Utc>, tz: Tz) -> AppResult<ReportData> {
+        let lines: Vec<InvoiceLine> = sqlx::query_as(&format!(
+            r#"
+            SELECT i.invoice_number, c.name AS customer_name, {AMOUNT} AS amount, i.currency_code,
+                   i.status, i.due_date, i.paid_at, i.payment_method
+            FROM invoices i
+            LEFT JOIN customer_subscriptions cs
+              ON i.external_id = 'pkgsub:' || cs.id OR i.external_id LIKE 'pkgsub:' || cs.id || ':%'
+            LEFT JOIN customers c ON c.id = cs.customer_id
+            WHERE i.tenant_id = $1
+              AND i.external_id LIKE 'pkgsub:%'
+              AND i.status IN ('pending', 'verification_pending', 'failed')
+            ORDER BY i.due_date ASC
+            LIMIT $2
+            "#
+        ))
+        .bind(tenant_id)
+        .bind(MAX_ROWS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let days_overdue = |l: &InvoiceLine| (now - l.due_date).num_days();
+        let mut summary = vec![("Unpaid invoices".to_string(), lines.len().to_string())];
+        let totals = totals_by(lines.iter().map(|l| {
+            let bucket = AGING_BUCKETS
+                .iter()
+                .position(|b| *b == aging_bucket(days_overdue(l)))
+                .unwrap_or(0);
+            ((bucket, l.currency_code.clone()), l.minor())
+        }));
+        for ((bucket, currency), (total, count)) in totals {
+            let label = match AGING_BUCKETS[bucket] {
+                "current" => "Not yet due".to_string(),
+                days => format!("{} days overdue", days),
+            };
+            summary.push((
+                format!("{} ({})", label, currency),
+                format!("{} from {} invoices", total_cell(total, &currency), count),
+            ));
+        }
+
+        let rows = lines
+            .iter()
+            .map(|l| {
+                let days = days_overdue(l);
+                vec![
+                    l.invoice_number.clone(),
+                    l.customer_name.clone().unwrap_or_default(),
+                    local_time(l.due_date, tz),
+                    days.max(0).to_string(),
+                    aging_bucket(days).to_string(),
+                    money::format_plain(l.amount, &l.currency_code),
+                    l.currency_code.clone(),
+                    l.status.clone(),
+                ]
+            })
+            .collect();
+        Ok(ReportData {
+            summary,
+            columns: &[
+                "Invoice",
+                "Customer",
+                "Due",
+                "Days late",
+                "Bucket",
+                "Amount",
+                "Currency",
+                "Status",
+            ],
+            rows,
+        })
+    }
+
+    /// Tickets opened in the period against the first-response and
+    /// resolution targets for their priority.
+    async fn sla(
+        &self,
+        tenant_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tz: Tz,
+        now: DateTime<Utc>,
+    ) -> AppResult<ReportData> {
+        let tickets: Vec<TicketLine> = sqlx::query_as(
+            r#"
+            SELECT t.id, t.subject, t.priority, t.status, t.created_at, t.closed_at,
+                   (SELECT MIN(m.created_at) FROM support_ticket_messages m
+                     WHERE m.ticket_id = t.id
+                       AND m.is_internal = false
+                       AND m.author_id IS NOT NULL
+                       AND m.author_id <> COALESCE(t.created_by, '')) AS first_response_at
+            FROM support_tickets t
+            WHERE t.tenant_id = $1 AND t.created_at >= $2 AND t.created_at < $3
+            ORDER BY t.created_at ASC
+            LIMIT $4
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(start)
+        .bind(end)
+        .bind(MAX_ROWS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut response_met = 0;
+        let mut resolution_met = 0;
+        let mut response_times = Vec::new();
+        let rows: Vec<Vec<String>> = tickets
+            .iter()
+            .map(|t| {
+                let (response_target, resolution_target) = sla_targets(&t.priority);
+                let response = sla_outcome(t.created_at, t.first_response_at, response_target, now);
+                let resolution = sla_outcome(t.created_at, t.closed_at, resolution_target, now);
+                response_met += usize::from(response == "met");
+                resolution_met += usize::from(resolution == "met");
+                if let Some(at) = t.first_response_at {
+                    response_times.push(at - t.created_at);
+                }
+                vec![
+                    t.id.chars().take(8).collect(),
+                    t.subject.clone(),
+                    t.priority.clone(),
+                    t.status.clone(),
+                    local_time(t.created_at, tz),
+                    t.first_response_at
+                        .map(|at| minutes(at - t.created_at))
+                        .unwrap_or_default(),
+                    response.to_string(),
+                    t.closed_at
+                        .map(|at| minutes(at - t.created_at))
+                        .unwrap_or_default(),
+                    resolution.to_string(),
+                ]
+            })
+            .collect();
+
+        let average_response = if response_times.is_empty() {
+            "-".to_string()
+        } else {
+            let total: Duration = response_times.iter().copied().sum();
+            format!(
+                "{} min",
+                (total / response_times.len() as i32).num_minutes()
+            )
+        };
+        let summary = vec![
+            ("Tickets opened".to_string(), tickets.len().to_string()),
+            (
+                "First response on time".to_string(),
+                percent(response_met, tickets.len()),
+            ),
+            (
+                "Resolved on time".to_string(),
+                percent(resolution_met, tickets.len()),
+            ),
+            ("Average first response".to_string(), average_response),
+            (
+                "Targets (response / resolution)".to_string(),
+                "urgent 1h/8h, high 4h/24h, normal 8h/72h, low 24h/120h".to_string(),
+            ),
+        ];
+        Ok(ReportData {
+            summary,
+            columns: &[
+                "Ticket",
+                "Subject",
+                "Priority",
+                "Status",
+                "Opened",
+                "Response (min)",
+                "Response SLA",
+                "Resolution (min)",
+                "Resolution SLA",
+            ],
+            rows,
+        })
+    }
+
+    async fn incidents(
+        &self,
+        tenant_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tz: Tz,
+    ) -> AppResult<ReportData> {
+        let incidents: Vec<IncidentLine> = sqlx::query_as(
+            r#"
+            SELECT COALESCE(r.name, d.name) AS router_name, i.incident_type, i.severity,
+                   i.status, i.title, i.first_seen_at, i.resolved_at
+            FROM mikrotik_incidents i
+            LEFT JOIN mikrotik_routers r ON i.source = 'routeros' AND r.id = i.router_id
+            LEFT JOIN monitored_devices d ON i.source = 'snmp' AND d.id = i.router_id
+            WHERE i.tenant_id = $1 AND i.first_seen_at >= $2 AND i.first_seen_at < $3
+            ORDER BY i.first_seen_at ASC
+            LIMIT $4
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(start)
+        .bind(end)
+        .bind(MAX_ROWS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let durations: Vec<Duration> = incidents
+            .iter()
+            .filter_map(|i| i.resolved_at.map(|at| at - i.first_seen_at))
+            .collect();
+        let mttr = if durations.is_empty() {
+            "-".to_string()
+        } else {
+            let total: Duration = durations.iter().copied().sum();
+            format!("{} min", (total / durations.len() as i32).num_minutes())
+        };
+        let mut summary = vec![
+            ("Incidents".to_string(), incidents.len().to_string()),
+            (
+                "Critical".to_string(),
+                incidents
+                    .iter()
+                    .filter(|i| i.severity == "critical")
+                    .count()
+                    .to_string(),
+            ),
+            ("Resolved".to_string(), durations.len().to_string()),
+            ("Mean time to resolve".to_string(), mttr),
+        ];
+        for (kind, (_, count)) in totals_by(incidents.iter().map(|i| (i.incident_type.clone(), 0)))
+        {
+            summary.push((format!("Type {}", kind), count.to_string()));
+        }
+
+        let rows = incidents
+            .iter()
+            .map(|i| {
+                vec![
+                    local_time(i.first_seen_at, tz),
+                    i.router_name.clone().unwrap_or_default(),
+                    i.incident_type.clone(),
+                    i.severity.clone(),
+                    i.title.clone(),
+                    i.status.clone(),
+                    i.resolved_at
+                        .map(|at| minutes(at - i.first_seen_at))
+                        .unwrap_or_default(),
+                ]
+            })
+            .collect();
+        Ok(ReportData {
+            summary,
+            columns: &[
+                "First seen",
+                "Router",
+                "Type",
+                "Severity",
+                "Title",
+                "Status",
+                "Duration (min)",
+            ],
+            rows,
+        })
+    }
+}
+
+#[async_trait]
+impl JobHandler for ReportScheduleService {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        self.run_due(Utc::now()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn periods_cover_the_day_week_or_month_before() {
+        let jakarta: Tz = "Asia/Jakarta".parse().unwrap();
+        // 2026-03-04 07:00 in Jakarta, a Wednesday.
+        let now = at(2026, 3, 4, 0);
+
+        assert_eq!(
+            report_period("daily", now, jakarta),
+            (at(2026, 3, 2, 17), at(2026, 3, 3, 17))
+        );
+        assert_eq!(
+            report_period("weekly", now, jakarta),
+            (at(2026, 2, 22, 17), at(2026, 3, 1, 17))
+        );
+        assert_eq!(
+            report_period("monthly", now, jakarta),
+            (at(2026, 1, 31, 17), at(2026, 2, 28, 17))
+        );
+        // January reaches back into the previous year.
+        assert_eq!(
+            report_period("monthly", at(2026, 1, 1, 9), chrono_tz::UTC),
+            (at(2025, 12, 1, 0), at(2026, 1, 1, 0))
+        );
+    }
+
+    #[test]
+    fn next_run_lands_on_the_send_hour() {
+        let now = at(2026, 3, 4, 10); // Wednesday
+        assert_eq!(next_run("daily", 7, now, chrono_tz::UTC), at(2026, 3, 5, 7));
+        assert_eq!(
+            next_run("daily", 12, now, chrono_tz::UTC),
+            at(2026, 3, 4, 12)
+        );
+        assert_eq!(
+            next_run("weekly", 7, now, chrono_tz::UTC),
+            at(2026, 3, 9, 7)
+        );
+        assert_eq!(
+            next_run("monthly", 7, now, chrono_tz::UTC),
+            at(2026, 4, 1, 7)
+        );
+    }
+
+    #[test]
+    fn aging_buckets_and_sla_outcomes() {
+        assert_eq!(aging_bucket(-3), "current");
+        assert_eq!(aging_bucket(0), "current");
+        assert_eq!(aging_bucket(30), "1-30");
+        assert_eq!(aging_bucket(31), "31-60");
+        assert_eq!(aging_bucket(91), "90+");
+
+        let opened = at(2026, 3, 4, 8);
+        let target = Duration::hours(4);
+        let now = at(2026, 3, 4, 10);
+        assert_eq!(
+            sla_outcome(opened, Some(at(2026, 3, 4, 12)), target, now),
+            "met"
+        );
+        assert_eq!(
+            sla_outcome(opened, Some(at(2026, 3, 4, 13)), target, now),
+            "missed"
+        );
+        assert_eq!(sla_outcome(opened, None, target, now), "pending");
+        assert_eq!(
+            sla_outcome(opened, None, target, at(2026, 3, 5, 0)),
+            "missed"
+        );
+    }
+
+    #[test]
+    fn validates_schedules() {
+        let req = |recipients: &[&str]| UpsertReportScheduleRequest {
+            name: " Monthly numbers ".to_string(),
+            report: "revenue".to_string(),
+            format: "pdf".to_string(),
+            cadence: "monthly".to_string(),
+            send_hour: None,
+            recipients: recipients.iter().map(|r| r.to_string()).collect(),
+            enabled: None,
+        };
+
+        let (name, hour, recipients) =
+            validate(&req(&["Boss@Example.com", "boss@example.com", " "])).unwrap();
+        assert_eq!((name.as_str(), hour), ("Monthly numbers", 7));
+        assert_eq!(recipients, vec!["boss@example.com"]);
+
+        assert!(validate(&req(&[])).is_err());
+        assert!(validate(&req(&["not-an-address"])).is_err());
+        let mut bad = req(&["a@example.com"]);
+        bad.cadence = "hourly".to_string();
+        assert!(validate(&bad).is_err());
+    }
+
+    #[test]
+    fn csv_has_a_header_and_escaped_cells() {
+        let data = ReportData {
+            summary: Vec::new(),
+            columns: &["Invoice", "Customer"],
+            rows: vec![vec!["INV-1".to_string(), "Doe, Jane".to_string()]],
+        };
+        assert_eq!(
+            String::from_utf8(data.to_csv()).unwrap(),
+            "Invoice,Customer\nINV-1,\"Doe, Jane\"\n"
+        );
+    }
+}
@@ -1,8 +1,12 @@
 //! Role and Permission service for RBAC
 
 use crate::db::DbPool;
+use crate::error::AppResult;
+use crate::http::WsEvent;
 use crate::models::{CreateRoleDto, Permission, Role, RoleWithPermissions, UpdateRoleDto};
-use crate::services::audit_service::AuditService;
+use crate::services::audit_service::{AuditChange, AuditService};
+use crate::services::concurrency;
+use crate::services::event_outbox_service::{self, EventOutboxService};
 use chrono::Utc;
 use std::collections::HashSet;
 use uuid::Uuid;
@@ -91,6 +95,12 @@ impl RoleService {
             ("email_outbox", "read", "View email outbox"),
             ("email_outbox", "retry", "Retry outbox items"),
             ("email_outbox", "delete", "Delete outbox items"),
+            // Notification delivery reports (which channel reached whom)
+            (
+                "notification_reports",
+                "read",
+                "View notification delivery reports",
+            ),
         ]
     }
 
@@ -151,6 +161,7 @@ impl RoleService {
                     "email_outbox:read",
                     "email_outbox:retry",
                     "email_outbox:delete",
+                    "notification_reports:read",
                 ],
             ),
             (
@@ -203,6 +214,7 @@ impl RoleService {
                     "email_outbox:read",
                     "email_outbox:retry",
                     "email_outbox:delete",
+                    "notification_reports:read",
                 ],
             ),
             (
@@ -276,6 +288,7 @@ impl RoleService {
                     "support:assign",
                     "support:internal",
                     "announcements:read",
+                    "notification_reports:read",
                 ],
             ),
             (
@@ -576,6 +589,7 @@ impl RoleService {
             FROM permissions p
             JOIN role_permissions rp ON p.id = rp.permission_id
             WHERE rp.role_id = $1
+            ORDER BY p.resource, p.action
         "#,
         )
         .bind(role_id)
@@ -589,6 +603,7 @@ impl RoleService {
             FROM permissions p
             JOIN role_permissions rp ON p.id = rp.permission_id
             WHERE rp.role_id = ?
+            ORDER BY p.resource, p.action
         "#,
         )
         .bind(role_id)
@@ -620,6 +635,7 @@ impl RoleService {
     ) -> Result<RoleWithPermissions, sqlx::Error> {
         let now = Utc::now();
         let role_id = Uuid::new_v4().to_string();
+        let mut tx = self.pool.begin().await?;
 
         #[cfg(feature = "postgres")]
         {
@@ -634,7 +650,7 @@ impl RoleService {
             .bind(dto.level.unwrap_or(0))
             .bind(now)
             .bind(now)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
         }
 
@@ -652,7 +668,7 @@ impl RoleService {
             .bind(dto.level.unwrap_or(0))
             .bind(&now_str)
             .bind(&now_str)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
         }
 
@@ -675,7 +691,7 @@ impl RoleService {
                 .bind(&role_id)
                 .bind(resource)
                 .bind(action)
-                .execute(&self.pool)
+                .execute(&mut *tx)
                 .await?;
             }
 
@@ -690,11 +706,22 @@ impl RoleService {
                 .bind(&role_id)
                 .bind(resource)
                 .bind(action)
-                .execute(&self.pool)
+                .execute(&mut *tx)
                 .await?;
             }
         }
 
+        EventOutboxService::stage(
+            &mut tx,
+            tenant_id,
+            &WsEvent::RoleCreated {
+                role_id: role_id.clone(),
+            },
+        )
+        .await?;
+        tx.commit().await?;
+        event_outbox_service::wake();
+
         // Audit
         self.audit_service
             .log(
@@ -751,7 +778,7 @@ impl RoleService {
         is_super_admin: bool,
         actor_id: Option<&str>,
         ip_address: Option<&str>,
-    ) -> Result<RoleWithPermissions, sqlx::Error> {
+    ) -> AppResult<RoleWithPermissions> {
         let now = Utc::now();
 
         // Check if role is system role
@@ -772,18 +799,36 @@ impl RoleService {
         let role_description_before = role.description.clone();
         let role_level_before = role.level;
 
-        // Capture existing permissions for diffing (only if caller is changing permissions).
-        let existing_permissions: Vec<String> = if dto.permissions.is_some() {
-            self.get_role_permissions(role_id).await?
-        } else {
-            vec![]
-        };
+        // Capture existing permissions for the audit diff.
+        let existing_permissions: Vec<String> = self.get_role_permissions(role_id).await?;
+        let before = RoleWithPermissions::from_role(role.clone(), existing_permissions.clone());
 
         // Only Superadmins can modify system roles
         if role.is_system && !is_super_admin {
             return Err(sqlx::Error::Protocol(
                 "System roles can only be modified by Super Admin".to_string(),
-            ));
+            )
+            .into());
+        }
+
+        concurrency::ensure_unmodified(dto.expected_updated_at, role.updated_at, "role")?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // Name, level, description and permissions are written separately, so claim the
+        // row version up front; a concurrent editor then fails instead of interleaving.
+        #[cfg(feature = "postgres")]
+        {
+            let claimed =
+                sqlx::query("UPDATE roles SET updated_at = $1 WHERE id = $2 AND updated_at = $3")
+                    .bind(now)
+                    .bind(role_id)
+                    .bind(role.updated_at)
+                    .execute(&mut *tx)
+                    .await?;
+            if claimed.rows_affected() == 0 {
+                return Err(concurrency::modified_conflict("role"));
+            }
         }
 
         if role.is_system && !is_super_admin {
@@ -795,7 +840,7 @@ impl RoleService {
                     .bind(name)
                     .bind(now)
                     .bind(role_id)
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await?;
 
                 #[cfg(feature = "sqlite")]
@@ -803,7 +848,7 @@ impl RoleService {
                     .bind(name)
                     .bind(now.to_rfc3339())
                     .bind(role_id)
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await?;
             }
 
@@ -813,7 +858,7 @@ impl RoleService {
                     .bind(level)
                     .bind(now)
                     .bind(role_id)
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await?;
 
                 #[cfg(feature = "sqlite")]
@@ -821,7 +866,7 @@ impl RoleService {
                     .bind(level)
                     .bind(now.to_rfc3339())
                     .bind(role_id)
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await?;
             }
 
@@ -831,7 +876,7 @@ impl RoleService {
                     .bind(description)
                     .bind(now)
                     .bind(role_id)
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await?;
 
                 #[cfg(feature = "sqlite")]
@@ -839,7 +884,7 @@ impl RoleService {
                     .bind(description)
                     .bind(now.to_rfc3339())
                     .bind(role_id)
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await?;
             }
         }
@@ -879,13 +924,13 @@ impl RoleService {
             #[cfg(feature = "postgres")]
             sqlx::query("DELETE FROM role_permissions WHERE role_id = $1")
                 .bind(role_id)
-                .execute(&self.pool)
+                .execute(&mut *tx)
                 .await?;
 
             #[cfg(feature = "sqlite")]
             sqlx::query("DELETE FROM role_permissions WHERE role_id = ?")
                 .bind(role_id)
-                .execute(&self.pool)
+                .execute(&mut *tx)
                 .await?;
 
             // Add new permissions
@@ -907,7 +952,7 @@ impl RoleService {
                     .bind(role_id)
                     .bind(resource)
                     .bind(action)
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await?;
                 }
 
@@ -922,12 +967,23 @@ impl RoleService {
                     .bind(role_id)
                     .bind(resource)
                     .bind(action)
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await?;
                 }
             }
         }
 
+        EventOutboxService::stage(
+            &mut tx,
+            role.tenant_id.as_deref(),
+            &WsEvent::RoleUpdated {
+                role_id: role_id.to_string(),
+            },
+        )
+        .await?;
+        tx.commit().await?;
+        event_outbox_service::wake();
+
         let role_name_after = dto.name.clone().unwrap_or(role_name_before.clone());
         let role_description_after = dto
             .description
@@ -948,9 +1004,14 @@ impl RoleService {
         })
         .to_string();
 
+        let updated = self
+            .get_role_by_id(role_id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
         // Audit
         self.audit_service
-            .log(
+            .log_change(
                 actor_id,
                 role.tenant_id.as_deref(),
                 "ROLE_UPDATE",
@@ -958,12 +1019,11 @@ impl RoleService {
                 Some(role_id),
                 Some(details.as_str()),
                 ip_address,
+                &AuditChange::between(&before, &updated),
             )
             .await;
 
-        self.get_role_by_id(role_id)
-            .await?
-            .ok_or_else(|| sqlx::Error::RowNotFound)
+        Ok(updated)
     }
 
     /// Delete a role (system roles can only be deleted by Superadmins)
@@ -997,18 +1057,31 @@ impl RoleService {
 
             let tenant_id_str = tid.map(|t| t.to_string());
 
+            let mut tx = self.pool.begin().await?;
+
             #[cfg(feature = "postgres")]
             sqlx::query("DELETE FROM roles WHERE id = $1")
                 .bind(role_id)
-                .execute(&self.pool)
+                .execute(&mut *tx)
                 .await?;
 
             #[cfg(feature = "sqlite")]
             sqlx::query("DELETE FROM roles WHERE id = ?")
                 .bind(role_id)
-                .execute(&self.pool)
+                .execute(&mut *tx)
                 .await?;
 
+            EventOutboxService::stage(
+                &mut tx,
+                tenant_id_str.as_deref(),
+                &WsEvent::RoleDeleted {
+                    role_id: role_id.to_string(),
+                },
+            )
+            .await?;
+            tx.commit().await?;
+            event_outbox_service::wake();
+
             // Audit
             self.audit_service
                 .log(
@@ -0,0 +1,1092 @@
+//! Minimal SNMP manager: GET requests over UDP, v2c and v3.
+//!
+//! Just enough to poll a handful of scalars (system group, a vendor CPU OID)
+//! from devices that do not speak the RouterOS API, such as OLTs and switches.
+//! v3 uses the User-based Security Model (RFC 3414): noAuthNoPriv, authNoPriv
+//! and authPriv, with HMAC-MD5-96 or HMAC-SHA-96 authentication and CBC-DES or
+//! CFB AES-128 (RFC 3826) privacy.
+
+use aes::Aes128;
+use cbc::cipher::block_padding::NoPadding;
+use cbc::cipher::{AsyncStreamCipher, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use des::Des;
+use hmac::{Hmac, Mac};
+use md5::Md5;
+use sha1::{Digest, Sha1};
+use tokio::net::{lookup_host, UdpSocket};
+use tokio::time::{timeout_at, Duration, Instant};
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_NULL: u8 = 0x05;
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_IP_ADDRESS: u8 = 0x40;
+const TAG_COUNTER32: u8 = 0x41;
+const TAG_GAUGE32: u8 = 0x42;
+const TAG_TIMETICKS: u8 = 0x43;
+const TAG_OPAQUE: u8 = 0x44;
+const TAG_COUNTER64: u8 = 0x46;
+const TAG_NO_SUCH_OBJECT: u8 = 0x80;
+const TAG_NO_SUCH_INSTANCE: u8 = 0x81;
+const TAG_END_OF_MIB_VIEW: u8 = 0x82;
+
+const PDU_GET: u8 = 0xa0;
+const PDU_RESPONSE: u8 = 0xa2;
+const PDU_REPORT: u8 = 0xa8;
+
+const VERSION_2C: i64 = 1;
+const VERSION_3: i64 = 3;
+const USM_SECURITY_MODEL: i64 = 3;
+const MAX_MESSAGE_SIZE: i64 = 65_507;
+
+const FLAG_AUTH: u8 = 0x01;
+const FLAG_PRIV: u8 = 0x02;
+const FLAG_REPORTABLE: u8 = 0x04;
+
+/// usmStats counters an agent reports a refused v3 request with.
+const USM_STATS: [u32; 9] = [1, 3, 6, 1, 6, 3, 15, 1, 1];
+const USM_NOT_IN_TIME_WINDOW: u32 = 2;
+
+/// Resends after a timeout before giving up.
+const RETRIES: usize = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SnmpError {
+    #[error("no response from agent")]
+    Timeout,
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("malformed SNMP message: {0}")]
+    Malformed(&'static str),
+    #[error("agent returned {0}")]
+    Agent(String),
+    #[error("{0}")]
+    Security(String),
+    #[error("invalid SNMP settings: {0}")]
+    Config(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthProtocol {
+    Md5,
+    Sha1,
+}
+
+impl AuthProtocol {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "md5" => Some(Self::Md5),
+            "sha" | "sha1" => Some(Self::Sha1),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Md5 => "md5",
+            Self::Sha1 => "sha1",
+        }
+    }
+
+    /// Localized key (RFC 3414 A.2): the password stretched to 1MB and
+    /// hashed, then hashed again around the agent's engine ID.
+    fn localize(self, password: &[u8], engine_id: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Md5 => localize_key::<Md5>(password, engine_id),
+            Self::Sha1 => localize_key::<Sha1>(password, engine_id),
+        }
+    }
+
+    /// The first 96 bits of the message's HMAC.
+    fn hmac96(self, key: &[u8], message: &[u8]) -> [u8; 12] {
+        let full = match self {
+            Self::Md5 => {
+                let mut mac =
+                    Hmac::<Md5>::new_from_slice(key).expect("HMAC takes keys of any length");
+                mac.update(message);
+                mac.finalize().into_bytes().to_vec()
+            }
+            Self::Sha1 => {
+                let mut mac =
+                    Hmac::<Sha1>::new_from_slice(key).expect("HMAC takes keys of any length");
+                mac.update(message);
+                mac.finalize().into_bytes().to_vec()
+            }
+        };
+        let mut out = [0u8; 12];
+        out.copy_from_slice(&full[..12]);
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivProtocol {
+    Des,
+    Aes128,
+}
+
+impl PrivProtocol {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "des" => Some(Self::Des),
+            "aes" | "aes128" => Some(Self::Aes128),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Des => "des",
+            Self::Aes128 => "aes128",
+        }
+    }
+
+    /// Encrypts a scoped PDU; returns the ciphertext and the salt that goes
+    /// out as msgPrivacyParameters.
+    fn encrypt(
+        self,
+        key: &[u8],
+        boots: i64,
+        time: i64,
+        plain: &[u8],
+    ) -> Result<(Vec<u8>, [u8; 8]), SnmpError> {
+        let salt: [u8; 8] = match self {
+            // Engine boots and a local counter; random will do for the latter.
+            Self::Des => {
+                let mut salt = [0u8; 8];
+                salt[..4].copy_from_slice(&(boots as u32).to_be_bytes());
+                salt[4..].copy_from_slice(&rand::random::<u32>().to_be_bytes());
+                salt
+            }
+            Self::Aes128 => rand::random(),
+        };
+        let iv = self.iv(key, boots, time, &salt)?;
+        let mut buf = plain.to_vec();
+        match self {
+            Self::Des => {
+                // The receiver ignores the padding: the BER length says where
+                // the PDU ends.
+                buf.resize(plain.len().div_ceil(8) * 8, 0);
+                let len = buf.len();
+                cbc::Encryptor::<Des>::new_from_slices(&key[..8], &iv)
+                    .map_err(|_| SnmpError::Security("bad DES key".to_string()))?
+                    .encrypt_padded_mut::<NoPadding>(&mut buf, len)
+                    .map_err(|_| SnmpError::Security("DES encryption failed".to_string()))?;
+            }
+            Self::Aes128 => {
+                cfb_mode::Encryptor::<Aes128>::new_from_slices(&key[..16], &iv)
+                    .map_err(|_| SnmpError::Security("bad AES key".to_string()))?
+                    .encrypt(&mut buf);
+            }
+        }
+        Ok((buf, salt))
+    }
+
+    fn decrypt(
+        self,
+        key: &[u8],
+        boots: i64,
+        time: i64,
+        salt: &[u8],
+        cipher: &[u8],
+    ) -> Result<Vec<u8>, SnmpError> {
+        let salt: [u8; 8] = salt
+            .try_into()
+            .map_err(|_| SnmpError::Malformed("privacy parameters"))?;
+        let iv = self.iv(key, boots, time, &salt)?;
+        let mut buf = cipher.to_vec();
+        match self {
+            Self::Des => {
+                if buf.len() % 8 != 0 {
+                    return Err(SnmpError::Malformed("DES ciphertext length"));
+                }
+                cbc::Decryptor::<Des>::new_from_slices(&key[..8], &iv)
+                    .map_err(|_| SnmpError::Security("bad DES key".to_string()))?
+                    .decrypt_padded_mut::<NoPadding>(&mut buf)
+                    .map_err(|_| SnmpError::Security("DES decryption failed".to_string()))?;
+            }
+            Self::Aes128 => {
+                cfb_mode::Decryptor::<Aes128>::new_from_slices(&key[..16], &iv)
+                    .map_err(|_| SnmpError::Security("bad AES key".to_string()))?
+                    .decrypt(&mut buf);
+            }
+        }
+        Ok(buf)
+    }
+
+    fn iv(self, key: &[u8], boots: i64, time: i64, salt: &[u8; 8]) -> Result<Vec<u8>, SnmpError> {
+        if key.len() < 16 {
+            return Err(SnmpError::Security("privacy key too short".to_string()));
+        }
+        Ok(match self {
+            // Pre-IV (second half of the key) XOR salt.
+            Self::Des => key[8..16].iter().zip(salt).map(|(k, s)| k ^ s).collect(),
+            Self::Aes128 => {
+                let mut iv = Vec::with_capacity(16);
+                iv.extend_from_slice(&(boots as u32).to_be_bytes());
+                iv.extend_from_slice(&(time as u32).to_be_bytes());
+                iv.extend_from_slice(salt);
+                iv
+            }
+        })
+    }
+}
+
+/// A v3 user. Privacy needs authentication.
+#[derive(Debug, Clone)]
+pub struct UsmUser {
+    pub username: String,
+    pub auth: Option<(AuthProtocol, String)>,
+    pub privacy: Option<(PrivProtocol, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SnmpCredentials {
+    V2c { community: String },
+    V3(UsmUser),
+}
+
+impl SnmpCredentials {
+    pub fn v2c(community: &str) -> Result<Self, SnmpError> {
+        if community.is_empty() {
+            return Err(SnmpError::Config("community is required".to_string()));
+        }
+        Ok(Self::V2c {
+            community: community.to_string(),
+        })
+    }
+
+    pub fn v3(
+        username: &str,
+        auth: Option<(AuthProtocol, String)>,
+        privacy: Option<(PrivProtocol, String)>,
+    ) -> Result<Self, SnmpError> {
+        if username.trim().is_empty() {
+            return Err(SnmpError::Config("username is required".to_string()));
+        }
+        if privacy.is_some() && auth.is_none() {
+            return Err(SnmpError::Config(
+                "privacy requires an authentication protocol".to_string(),
+            ));
+        }
+        // RFC 3414 asks for at least 8 characters; agents refuse shorter ones.
+        let short = |p: &str| p.chars().count() < 8;
+        if auth.as_ref().is_some_and(|(_, p)| short(p))
+            || privacy.as_ref().is_some_and(|(_, p)| short(p))
+        {
+            return Err(SnmpError::Config(
+                "passwords must be at least 8 characters".to_string(),
+            ));
+        }
+        Ok(Self::V3(UsmUser {
+            username: username.trim().to_string(),
+            auth,
+            privacy,
+        }))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnmpValue {
+    Integer(i64),
+    OctetString(Vec<u8>),
+    Null,
+    Oid(Vec<u32>),
+    IpAddress([u8; 4]),
+    Counter32(u32),
+    Gauge32(u32),
+    TimeTicks(u32),
+    Opaque(Vec<u8>),
+    Counter64(u64),
+    NoSuchObject,
+    NoSuchInstance,
+    EndOfMibView,
+}
+
+impl SnmpValue {
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Integer(v) => Some(*v),
+            Self::Counter32(v) | Self::Gauge32(v) | Self::TimeTicks(v) => Some(*v as i64),
+            Self::Counter64(v) => i64::try_from(*v).ok(),
+            // Some vendors answer numbers as strings.
+            Self::OctetString(_) => self.as_text()?.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<String> {
+        match self {
+            Self::OctetString(bytes) => {
+                let text = String::from_utf8_lossy(bytes);
+                Some(
+                    text.trim_matches(|c: char| c == '\0' || c.is_whitespace())
+                        .to_string(),
+                )
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parses a dotted OID such as `1.3.6.1.2.1.1.3.0` (a leading dot is fine).
+pub fn parse_oid(s: &str) -> Result<Vec<u32>, SnmpError> {
+    let arcs = s
+        .trim()
+        .trim_start_matches('.')
+        .split('.')
+        .map(|arc| arc.parse::<u32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| SnmpError::Config(format!("'{}' is not an OID", s.trim())))?;
+    if arcs.len() < 2 || arcs[0] > 2 || (arcs[0] < 2 && arcs[1] >= 40) {
+        return Err(SnmpError::Config(format!("'{}' is not an OID", s.trim())));
+    }
+    Ok(arcs)
+}
+
+pub struct SnmpClient {
+    host: String,
+    port: u16,
+    credentials: SnmpCredentials,
+    timeout: Duration,
+}
+
+impl SnmpClient {
+    pub fn new(host: &str, port: u16, credentials: SnmpCredentials, timeout: Duration) -> Self {
+        Self {
+            host: host.trim().to_string(),
+            port,
+            credentials,
+            timeout,
+        }
+    }
+
+    /// GETs `oids`, in order. Missing objects come back as `NoSuchObject` /
+    /// `NoSuchInstance` rather than as an error.
+    pub async fn get(&self, oids: &[Vec<u32>]) -> Result<Vec<(Vec<u32>, SnmpValue)>, SnmpError> {
+        let addr = lookup_host((self.host.as_str(), self.port))
+            .await?
+            .next()
+            .ok_or_else(|| SnmpError::Config(format!("cannot resolve {}", self.host)))?;
+        let socket = UdpSocket::bind(if addr.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        })
+        .await?;
+        socket.connect(addr).await?;
+
+        let pdu = match &self.credentials {
+            SnmpCredentials::V2c { community } => {
+                let request_id = next_id();
+                let request = encode_v2c(community.as_bytes(), encode_get_pdu(request_id, oids));
+                let reply = self
+                    .round_trip(&socket, &request, |d| {
+                        decode_v2c(d).is_ok_and(|p| p.request_id == request_id as i64)
+                    })
+                    .await?;
+                decode_v2c(&reply)?
+            }
+            SnmpCredentials::V3(user) => self.get_v3(&socket, user, oids).await?,
+        };
+
+        if pdu.error_status != 0 {
+            return Err(SnmpError::Agent(format!(
+                "{} (index {})",
+                error_status_name(pdu.error_status),
+                pdu.error_index
+            )));
+        }
+        Ok(pdu.varbinds)
+    }
+
+    async fn get_v3(
+        &self,
+        socket: &UdpSocket,
+        user: &UsmUser,
+        oids: &[Vec<u32>],
+    ) -> Result<Pdu, SnmpError> {
+        // Discovery: an empty unauthenticated request, answered with a report
+        // carrying the agent's engine ID, boots and time.
+        let msg_id = next_id();
+        let probe = encode_v3(
+            msg_id,
+            FLAG_REPORTABLE,
+            &UsmParams::default(),
+            encode_scoped_pdu(&[], encode_get_pdu(next_id(), &[])),
+        );
+        let reply = self
+            .round_trip(socket, &probe, |d| {
+                decode_v3(d).is_ok_and(|m| m.msg_id == msg_id as i64)
+            })
+            .await?;
+        let engine = decode_v3(&reply)?;
+        if engine.engine_id.is_empty() {
+            return Err(SnmpError::Malformed("agent sent no engine ID"));
+        }
+        let (mut boots, mut time) = (engine.boots, engine.time);
+
+        let auth_key = user
+            .auth
+            .as_ref()
+            .map(|(proto, password)| proto.localize(password.as_bytes(), &engine.engine_id));
+        let priv_key = match (&user.auth, &user.privacy) {
+            (Some((proto, _)), Some((_, password))) => {
+                Some(proto.localize(password.as_bytes(), &engine.engine_id))
+            }
+            _ => None,
+        };
+
+        for attempt in 0..2 {
+            let msg_id = next_id();
+            let request_id = next_id();
+            let scoped = encode_scoped_pdu(&engine.engine_id, encode_get_pdu(request_id, oids));
+
+            let mut flags = FLAG_REPORTABLE;
+            let (data, privacy) = match (&user.privacy, &priv_key) {
+                (Some((proto, _)), Some(key)) => {
+                    flags |= FLAG_PRIV;
+                    let (cipher, salt) = proto.encrypt(key, boots, time, &scoped)?;
+                    (tlv(TAG_OCTET_STRING, &cipher), salt.to_vec())
+                }
+                _ => (scoped, Vec::new()),
+            };
+            let auth_placeholder = [0u8; 12];
+            let digest;
+            let mut usm = UsmParams {
+                engine_id: &engine.engine_id,
+                boots,
+                time,
+                user: user.username.as_bytes(),
+                auth: &[],
+                privacy: &privacy,
+            };
+            if user.auth.is_some() {
+                flags |= FLAG_AUTH;
+                usm.auth = &auth_placeholder;
+            }
+            let mut request = encode_v3(msg_id, flags, &usm, data.clone());
+            // The digest covers the whole message with zeros in its place;
+            // filling it in leaves every length unchanged.
+            if let (Some((proto, _)), Some(key)) = (&user.auth, &auth_key) {
+                digest = proto.hmac96(key, &request);
+                usm.auth = &digest;
+                request = encode_v3(msg_id, flags, &usm, data);
+            }
+
+            let raw = self
+                .round_trip(socket, &request, |d| {
+                    decode_v3(d).is_ok_and(|m| m.msg_id == msg_id as i64)
+                })
+                .await?;
+            let reply = decode_v3(&raw)?;
+
+            let scoped = if reply.flags & FLAG_PRIV != 0 {
+                let (Some((proto, _)), Some(key)) = (&user.privacy, &priv_key) else {
+                    return Err(SnmpError::Malformed("encrypted reply to a plain request"));
+                };
+                proto.decrypt(key, reply.boots, reply.time, &reply.privacy, &reply.data)?
+            } else {
+                reply.data.clone()
+            };
+            let pdu = decode_scoped_pdu(&scoped)?;
+
+            if pdu.tag == PDU_REPORT {
+                // Reports about a refused request come unauthenticated.
+                let counter = pdu
+                    .varbinds
+                    .first()
+                    .and_then(|(oid, _)| oid.strip_prefix(&USM_STATS[..]))
+                    .and_then(|rest| rest.first().copied());
+                if counter == Some(USM_NOT_IN_TIME_WINDOW) && attempt == 0 {
+                    boots = reply.boots;
+                    time = reply.time;
+                    continue;
+                }
+                return Err(SnmpError::Security(usm_report_reason(counter)));
+            }
+
+            if let (Some((proto, _)), Some(key)) = (&user.auth, &auth_key) {
+                if reply.flags & FLAG_AUTH == 0 {
+                    return Err(SnmpError::Security(
+                        "reply was not authenticated".to_string(),
+                    ));
+                }
+                let mut zeroed = raw.clone();
+                zeroed[reply.auth_offset..reply.auth_offset + reply.auth.len()].fill(0);
+                if reply.auth.len() != 12 || proto.hmac96(key, &zeroed)[..] != reply.auth[..] {
+                    return Err(SnmpError::Security(
+                        "reply failed authentication".to_string(),
+                    ));
+                }
+            }
+            if pdu.request_id != request_id as i64 {
+                return Err(SnmpError::Malformed("request ID mismatch"));
+            }
+            return Ok(pdu);
+        }
+        Err(SnmpError::Security(
+            "agent clock out of the time window".to_string(),
+        ))
+    }
+
+    /// Sends `request` until a datagram `accept` takes comes back, resending
+    /// on timeout. Stray datagrams (late replies to earlier tries) are skipped.
+    async fn round_trip(
+        &self,
+        socket: &UdpSocket,
+        request: &[u8],
+        accept: impl Fn(&[u8]) -> bool,
+    ) -> Result<Vec<u8>, SnmpError> {
+        let mut buf = vec![0u8; 65_535];
+        for _ in 0..=RETRIES {
+            socket.send(request).await?;
+            let deadline = Instant::now() + self.timeout;
+            loop {
+                match timeout_at(deadline, socket.recv(&mut buf)).await {
+                    Err(_) => break,
+                    Ok(Err(e)) => return Err(e.into()),
+                    Ok(Ok(n)) if accept(&buf[..n]) => return Ok(buf[..n].to_vec()),
+                    Ok(Ok(_)) => {}
+                }
+            }
+        }
+        Err(SnmpError::Timeout)
+    }
+}
+
+fn next_id() -> i32 {
+    rand::random::<i32>() & i32::MAX
+}
+
+fn localize_key<D: Digest>(password: &[u8], engine_id: &[u8]) -> Vec<u8> {
+    let mut hasher = D::new();
+    if !password.is_empty() {
+        let mut chunk = [0u8; 64];
+        let mut index = 0;
+        for _ in 0..(1_048_576 / 64) {
+            for b in chunk.iter_mut() {
+                *b = password[index % password.len()];
+                index += 1;
+            }
+            hasher.update(chunk);
+        }
+    }
+    let ku = hasher.finalize();
+
+    let mut hasher = D::new();
+    hasher.update(&ku);
+    hasher.update(engine_id);
+    hasher.update(&ku);
+    hasher.finalize().to_vec()
+}
+
+fn error_status_name(status: i64) -> String {
+    let name = match status {
+        1 => "tooBig",
+        2 => "noSuchName",
+        3 => "badValue",
+        4 => "readOnly",
+        5 => "genErr",
+        6 => "noAccess",
+        16 => "authorizationError",
+        _ => return format!("error-status {}", status),
+    };
+    name.to_string()
+}
+
+fn usm_report_reason(counter: Option<u32>) -> String {
+    match counter {
+        Some(1) => "unsupported security level",
+        Some(2) => "agent clock out of the time window",
+        Some(3) => "unknown user name",
+        Some(4) => "unknown engine ID",
+        Some(5) => "wrong digest (check the authentication password)",
+        Some(6) => "decryption error (check the privacy password)",
+        _ => "request refused by the agent",
+    }
+    .to_string()
+}
+
+// --- BER encoding -----------------------------------------------------------
+
+fn push_len(out: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        out.push(len as u8);
+        return;
+    }
+    let bytes = len.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count();
+    out.push(0x80 | (bytes.len() - skip) as u8);
+    out.extend_from_slice(&bytes[skip..]);
+}
+
+fn tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(value.len() + 6);
+    out.push(tag);
+    push_len(&mut out, value.len());
+    out.extend_from_slice(value);
+    out
+}
+
+fn seq(parts: &[Vec<u8>]) -> Vec<u8> {
+    tlv(TAG_SEQUENCE, &parts.concat())
+}
+
+fn int(v: i64) -> Vec<u8> {
+    let bytes = v.to_be_bytes();
+    // Shortest two's complement form.
+    let mut start = 0;
+    while start < 7
+        && ((bytes[start] == 0x00 && bytes[start + 1] & 0x80 == 0)
+            || (bytes[start] == 0xff && bytes[start + 1] & 0x80 != 0))
+    {
+        start += 1;
+    }
+    tlv(TAG_INTEGER, &bytes[start..])
+}
+
+fn octets(v: &[u8]) -> Vec<u8> {
+    tlv(TAG_OCTET_STRING, v)
+}
+
+fn push_base128(out: &mut Vec<u8>, mut v: u32) {
+    let mut tmp = [0u8; 5];
+    let mut i = tmp.len();
+    loop {
+        i -= 1;
+        tmp[i] = (v & 0x7f) as u8;
+        v >>= 7;
+        if v == 0 {
+            break;
+        }
+    }
+    let last = tmp.len() - 1;
+    for b in &mut tmp[i..last] {
+        *b |= 0x80;
+    }
+    out.extend_from_slice(&tmp[i..]);
+}
+
+fn oid(arcs: &[u32]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(arcs.len() + 4);
+    push_base128(&mut body, arcs[0] * 40 + arcs[1]);
+    for arc in &arcs[2..] {
+        push_base128(&mut body, *arc);
+    }
+    tlv(TAG_OID, &body)
+}
+
+fn encode_get_pdu(request_id: i32, oids: &[Vec<u32>]) -> Vec<u8> {
+    let varbinds: Vec<Vec<u8>> = oids
+        .iter()
+        .map(|o| seq(&[oid(o), tlv(TAG_NULL, &[])]))
+        .collect();
+    tlv(
+        PDU_GET,
+        &[int(request_id as i64), int(0), int(0), seq(&varbinds)].concat(),
+    )
+}
+
+fn encode_v2c(community: &[u8], pdu: Vec<u8>) -> Vec<u8> {
+    seq(&[int(VERSION_2C), octets(community), pdu])
+}
+
+fn encode_scoped_pdu(context_engine_id: &[u8], pdu: Vec<u8>) -> Vec<u8> {
+    seq(&[octets(context_engine_id), octets(&[]), pdu])
+}
+
+/// msgSecurityParameters of a v3 message.
+#[derive(Default, Clone, Copy)]
+struct UsmParams<'a> {
+    engine_id: &'a [u8],
+    boots: i64,
+    time: i64,
+    user: &'a [u8],
+    auth: &'a [u8],
+    privacy: &'a [u8],
+}
+
+fn encode_v3(msg_id: i32, flags: u8, usm: &UsmParams, data: Vec<u8>) -> Vec<u8> {
+    let global = seq(&[
+        int(msg_id as i64),
+        int(MAX_MESSAGE_SIZE),
+        octets(&[flags]),
+        int(USM_SECURITY_MODEL),
+    ]);
+    let security = seq(&[
+        octets(usm.engine_id),
+        int(usm.boots),
+        int(usm.time),
+        octets(usm.user),
+        octets(usm.auth),
+        octets(usm.privacy),
+    ]);
+    seq(&[int(VERSION_3), global, octets(&security), data])
+}
+
+// --- BER decoding -----------------------------------------------------------
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    /// Offset of `buf` in the whole message.
+    base: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self::at(buf, 0)
+    }
+
+    fn at(buf: &'a [u8], base: usize) -> Self {
+        Self { buf, pos: 0, base }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+
+    /// The next TLV: its tag, the offset of its value in the whole message,
+    /// and the value.
+    fn read_tlv(&mut self) -> Result<(u8, usize, &'a [u8]), SnmpError> {
+        const TRUNCATED: SnmpError = SnmpError::Malformed("truncated");
+        let tag = *self.buf.get(self.pos).ok_or(TRUNCATED)?;
+        let first = *self.buf.get(self.pos + 1).ok_or(TRUNCATED)?;
+        self.pos += 2;
+        let len = if first < 0x80 {
+            first as usize
+        } else {
+            let n = (first & 0x7f) as usize;
+            if n == 0 || n > 4 {
+                return Err(SnmpError::Malformed("length"));
+            }
+            let bytes = self.buf.get(self.pos..self.pos + n).ok_or(TRUNCATED)?;
+            self.pos += n;
+            bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize)
+        };
+        let start = self.pos;
+        let end = start.checked_add(len).ok_or(TRUNCATED)?;
+        let value = self.buf.get(start..end).ok_or(TRUNCATED)?;
+        self.pos = end;
+        Ok((tag, self.base + start, value))
+    }
+
+    fn expect(&mut self, tag: u8) -> Result<(usize, &'a [u8]), SnmpError> {
+        let (got, offset, value) = self.read_tlv()?;
+        if got != tag {
+            return Err(SnmpError::Malformed("unexpected tag"));
+        }
+        Ok((offset, value))
+    }
+
+    fn int(&mut self) -> Result<i64, SnmpError> {
+        let (_, value) = self.expect(TAG_INTEGER)?;
+        decode_int(value)
+    }
+
+    fn octets(&mut self) -> Result<&'a [u8], SnmpError> {
+        Ok(self.expect(TAG_OCTET_STRING)?.1)
+    }
+}
+
+fn decode_int(bytes: &[u8]) -> Result<i64, SnmpError> {
+    if bytes.is_empty() || bytes.len() > 8 {
+        return Err(SnmpError::Malformed("integer"));
+    }
+    let init: i64 = if bytes[0] & 0x80 != 0 { -1 } else { 0 };
+    Ok(bytes.iter().fold(init, |acc, b| (acc << 8) | *b as i64))
+}
+
+fn decode_uint(bytes: &[u8]) -> Result<u64, SnmpError> {
+    let bytes = match bytes {
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        _ => bytes,
+    };
+    if bytes.is_empty() || bytes.len() > 8 {
+        return Err(SnmpError::Malformed("unsigned integer"));
+    }
+    Ok(bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+}
+
+fn decode_u32(bytes: &[u8]) -> Result<u32, SnmpError> {
+    u32::try_from(decode_uint(bytes)?).map_err(|_| SnmpError::Malformed("32-bit value"))
+}
+
+fn decode_oid(bytes: &[u8]) -> Result<Vec<u32>, SnmpError> {
+    if bytes.last().is_none_or(|b| b & 0x80 != 0) {
+        return Err(SnmpError::Malformed("OID"));
+    }
+    let mut arcs = Vec::with_capacity(bytes.len() + 1);
+    let mut v: u32 = 0;
+    for b in bytes {
+        if v > u32::MAX >> 7 {
+            return Err(SnmpError::Malformed("OID arc"));
+        }
+        v = (v << 7) | (b & 0x7f) as u32;
+        if b & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (v / 40).min(2);
+                arcs.push(first);
+                arcs.push(v - first * 40);
+            } else {
+                arcs.push(v);
+            }
+            v = 0;
+        }
+    }
+    Ok(arcs)
+}
+
+fn decode_value(tag: u8, bytes: &[u8]) -> Result<SnmpValue, SnmpError> {
+    Ok(match tag {
+        TAG_INTEGER => SnmpValue::Integer(decode_int(bytes)?),
+        TAG_OCTET_STRING => SnmpValue::OctetString(bytes.to_vec()),
+        TAG_NULL => SnmpValue::Null,
+        TAG_OID => SnmpValue::Oid(decode_oid(bytes)?),
+        TAG_IP_ADDRESS => SnmpValue::IpAddress(
+            bytes
+                .try_into()
+                .map_err(|_| SnmpError::Malformed("IP address"))?,
+        ),
+        TAG_COUNTER32 => SnmpValue::Counter32(decode_u32(bytes)?),
+        TAG_GAUGE32 => SnmpValue::Gauge32(decode_u32(bytes)?),
+        TAG_TIMETICKS => SnmpValue::TimeTicks(decode_u32(bytes)?),
+        TAG_OPAQUE => SnmpValue::Opaque(bytes.to_vec()),
+        TAG_COUNTER64 => SnmpValue::Counter64(decode_uint(bytes)?),
+        TAG_NO_SUCH_OBJECT => SnmpValue::NoSuchObject,
+        TAG_NO_SUCH_INSTANCE => SnmpValue::NoSuchInstance,
+        TAG_END_OF_MIB_VIEW => SnmpValue::EndOfMibView,
+        _ => return Err(SnmpError::Malformed("unknown value type")),
+    })
+}
+
+#[derive(Debug)]
+struct Pdu {
+    tag: u8,
+    request_id: i64,
+    error_status: i64,
+    error_index: i64,
+    varbinds: Vec<(Vec<u32>, SnmpValue)>,
+}
+
+fn decode_pdu(reader: &mut Reader) -> Result<Pdu, SnmpError> {
+    let (tag, offset, body) = reader.read_tlv()?;
+    if tag != PDU_RESPONSE && tag != PDU_REPORT {
+        return Err(SnmpError::Malformed("unexpected PDU type"));
+    }
+    let mut r = Reader::at(body, offset);
+    let request_id = r.int()?;
+    let error_status = r.int()?;
+    let error_index = r.int()?;
+    let (_, list) = r.expect(TAG_SEQUENCE)?;
+
+    let mut list = Reader::new(list);
+    let mut varbinds = Vec::new();
+    while !list.is_empty() {
+        let (_, varbind) = list.expect(TAG_SEQUENCE)?;
+        let mut varbind = Reader::new(varbind);
+        let (_, name) = varbind.expect(TAG_OID)?;
+        let (value_tag, _, value) = varbind.read_tlv()?;
+        varbinds.push((decode_oid(name)?, decode_value(value_tag, value)?));
+    }
+    Ok(Pdu {
+        tag,
+        request_id,
+        error_status,
+        error_index,
+        varbinds,
+    })
+}
+
+fn decode_v2c(message: &[u8]) -> Result<Pdu, SnmpError> {
+    let (offset, body) = Reader::new(message).expect(TAG_SEQUENCE)?;
+    let mut r = Reader::at(body, offset);
+    if r.int()? != VERSION_2C {
+        return Err(SnmpError::Malformed("not a v2c message"));
+    }
+    r.octets()?;
+    decode_pdu(&mut r)
+}
+
+/// Decodes a ScopedPDU (with any DES padding after it).
+fn decode_scoped_pdu(bytes: &[u8]) -> Result<Pdu, SnmpError> {
+    let (offset, body) = Reader::new(bytes).expect(TAG_SEQUENCE)?;
+    let mut r = Reader::at(body, offset);
+    r.octets()?;
+    r.octets()?;
+    decode_pdu(&mut r)
+}
+
+struct V3Message {
+    msg_id: i64,
+    flags: u8,
+    engine_id: Vec<u8>,
+    boots: i64,
+    time: i64,
+    auth: Vec<u8>,
+    /// Where msgAuthenticationParameters sit in the message.
+    auth_offset: usize,
+    privacy: Vec<u8>,
+    /// The encrypted PDU, or the whole plaintext ScopedPDU.
+    data: Vec<u8>,
+}
+
+fn decode_v3(message: &[u8]) -> Result<V3Message, SnmpError> {
+    let (offset, body) = Reader::new(message).expect(TAG_SEQUENCE)?;
+    let mut r = Reader::at(body, offset);
+    if r.int()? != VERSION_3 {
+        return Err(SnmpError::Malformed("not a v3 message"));
+    }
+
+    let (offset, global) = r.expect(TAG_SEQUENCE)?;
+    let mut g = Reader::at(global, offset);
+    let msg_id = g.int()?;
+    g.int()?;
+    let flags = *g
+        .octets()?
+        .first()
+        .ok_or(SnmpError::Malformed("message flags"))?;
+    if g.int()? != USM_SECURITY_MODEL {
+        return Err(SnmpError::Malformed("not a USM message"));
+    }
+
+    let (offset, security) = r.expect(TAG_OCTET_STRING)?;
+    let (offset, usm) = Reader::at(security, offset).expect(TAG_SEQUENCE)?;
+    let mut u = Reader::at(usm, offset);
+    let engine_id = u.octets()?.to_vec();
+    let boots = u.int()?;
+    let time = u.int()?;
+    u.octets()?;
+    let (auth_offset, auth) = u.expect(TAG_OCTET_STRING)?;
+    let privacy = u.octets()?.to_vec();
+
+    let tlv_start = r.pos;
+    let (tag, _, data) = r.read_tlv()?;
+    let data = match tag {
+        TAG_OCTET_STRING => data.to_vec(),
+        TAG_SEQUENCE => r.buf[tlv_start..r.pos].to_vec(),
+        _ => return Err(SnmpError::Malformed("message data")),
+    };
+
+    Ok(V3Message {
+        msg_id,
+        flags,
+        engine_id,
+        boots,
+        time,
+        auth: auth.to_vec(),
+        auth_offset,
+        privacy,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_localized_keys_rfc3414() {
+        // RFC 3414 A.3.1 and A.3.2.
+        let engine_id = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2];
+        assert_eq!(
+            hex(&AuthProtocol::Md5.localize(b"maplesyrup", &engine_id)),
+            "526f5eed9fcce26f8964c2930787d82b"
+        );
+        assert_eq!(
+            hex(&AuthProtocol::Sha1.localize(b"maplesyrup", &engine_id)),
+            "6695febc9288e36282235fc7151f128497b38f3f"
+        );
+    }
+
+    #[test]
+    fn test_ber_integers_and_oids() {
+        assert_eq!(int(0), vec![0x02, 0x01, 0x00]);
+        assert_eq!(int(128), vec![0x02, 0x02, 0x00, 0x80]);
+        assert_eq!(int(-1), vec![0x02, 0x01, 0xff]);
+        assert_eq!(int(-129), vec![0x02, 0x02, 0xff, 0x7f]);
+        for v in [0, 1, 127, 128, 65_535, -1, -128, -129, i64::MAX, i64::MIN] {
+            assert_eq!(decode_int(&int(v)[2..]).unwrap(), v);
+        }
+
+        let uptime = parse_oid(".1.3.6.1.2.1.1.3.0").unwrap();
+        assert_eq!(
+            oid(&uptime),
+            vec![0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x03, 0x00]
+        );
+        let enterprise = parse_oid("1.3.6.1.4.1.2011.6.3.4.1.2").unwrap();
+        assert_eq!(decode_oid(&oid(&enterprise)[2..]).unwrap(), enterprise);
+        assert!(parse_oid("1.3.six").is_err());
+        assert!(parse_oid("1").is_err());
+        assert!(parse_oid("1.40").is_err());
+    }
+
+    #[test]
+    fn test_decode_v2c_response() {
+        let varbinds = seq(&[
+            seq(&[oid(&[1, 3, 6, 1, 2, 1, 1, 5, 0]), octets(b"olt-01")]),
+            seq(&[
+                oid(&[1, 3, 6, 1, 2, 1, 1, 3, 0]),
+                tlv(TAG_TIMETICKS, &[0x00, 0x9c, 0x40, 0x00, 0x00]),
+            ]),
+            seq(&[oid(&[1, 3, 6, 1, 4, 1, 9, 9]), tlv(TAG_NO_SUCH_OBJECT, &[])]),
+        ]);
+        let pdu = tlv(PDU_RESPONSE, &[int(42), int(0), int(0), varbinds].concat());
+        let message = encode_v2c(b"public", pdu);
+
+        let pdu = decode_v2c(&message).unwrap();
+        assert_eq!(pdu.request_id, 42);
+        assert_eq!(pdu.varbinds.len(), 3);
+        assert_eq!(pdu.varbinds[0].1.as_text().as_deref(), Some("olt-01"));
+        assert_eq!(pdu.varbinds[1].1, SnmpValue::TimeTicks(0x9c40_0000));
+        assert_eq!(pdu.varbinds[2].1, SnmpValue::NoSuchObject);
+        assert!(decode_v2c(&message[..message.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_v3_message_round_trip() {
+        let scoped = encode_scoped_pdu(
+            b"engine",
+            tlv(PDU_REPORT, &[int(7), int(0), int(0), seq(&[])].concat()),
+        );
+        let usm = UsmParams {
+            engine_id: b"engine",
+            boots: 3,
+            time: 1234,
+            user: b"noc",
+            auth: &[0xaa; 12],
+            privacy: &[],
+        };
+        let message = encode_v3(99, FLAG_AUTH | FLAG_REPORTABLE, &usm, scoped.clone());
+
+        let decoded = decode_v3(&message).unwrap();
+        assert_eq!(decoded.msg_id, 99);
+        assert_eq!(decoded.flags, FLAG_AUTH | FLAG_REPORTABLE);
+        assert_eq!(decoded.engine_id, b"engine");
+        assert_eq!((decoded.boots, decoded.time), (3, 1234));
+        assert_eq!(
+            &message[decoded.auth_offset..decoded.auth_offset + 12],
+            &[0xaa; 12]
+        );
+        assert_eq!(decoded.data, scoped);
+        assert_eq!(decode_scoped_pdu(&decoded.data).unwrap().request_id, 7);
+    }
+
+    #[test]
+    fn test_privacy_round_trip() {
+        let key = AuthProtocol::Sha1.localize(b"privpassword", b"engine");
+        let plain = encode_scoped_pdu(b"engine", encode_get_pdu(5, &[vec![1, 3, 6, 1]]));
+        for proto in [PrivProtocol::Des, PrivProtocol::Aes128] {
+            let (cipher, salt) = proto.encrypt(&key, 4, 500, &plain).unwrap();
+            assert_ne!(cipher[..plain.len()], plain[..]);
+            let back = proto.decrypt(&key, 4, 500, &salt, &cipher).unwrap();
+            assert_eq!(back[..plain.len()], plain[..]);
+        }
+    }
+}
@@ -0,0 +1,869 @@
+//! Per-tenant public status page.
+//!
+//! A tenant lists components (API, billing, network regions, anything else)
+//! and posts incidents and scheduled maintenance against them. A component's
+//! status is the worst of its own (the admin override, or for a network
+//! component the state of its routers) and that of the active posts naming
+//! it. The public page is off until the tenant turns on `status_page_enabled`
+//! and is cached per tenant for `CACHE_TTL_SECS`.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AddStatusPostUpdateRequest, CreateStatusPostRequest, PublicStatusComponent, PublicStatusPage,
+    StatusComponent, StatusComponentRow, StatusPost, StatusPostRow, StatusPostUpdate,
+    UpdateStatusPostRequest, UpsertStatusComponentRequest,
+};
+use crate::services::cache::MemoryCache;
+use crate::services::SettingsService;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const CACHE_TTL_SECS: u64 = 30;
+const MAX_NAME_LEN: usize = 100;
+const MAX_TITLE_LEN: usize = 200;
+const MAX_MESSAGE_LEN: usize = 5000;
+
+pub const COMPONENT_KINDS: &[&str] = &["api", "billing", "network", "custom"];
+/// Component statuses, from best to worst.
+pub const COMPONENT_STATUSES: &[&str] = &[
+    "operational",
+    "maintenance",
+    "degraded",
+    "partial_outage",
+    "major_outage",
+];
+pub const POST_IMPACTS: &[&str] = &["none", "minor", "major", "critical"];
+pub const INCIDENT_STATUSES: &[&str] = &["investigating", "identified", "monitoring", "resolved"];
+pub const MAINTENANCE_STATUSES: &[&str] = &["scheduled", "in_progress", "completed"];
+
+fn status_rank(status: &str) -> usize {
+    COMPONENT_STATUSES
+        .iter()
+        .position(|s| *s == status)
+        .unwrap_or(0)
+}
+
+fn worst(a: &str, b: &str) -> &'static str {
+    COMPONENT_STATUSES[status_rank(a).max(status_rank(b))]
+}
+
+/// What an active incident of this impact does to the components it names.
+fn impact_status(impact: &str) -> &'static str {
+    match impact {
+        "critical" => "major_outage",
+        "major" => "partial_outage",
+        "minor" => "degraded",
+        _ => "operational",
+    }
+}
+
+fn post_statuses(kind: &str) -> &'static [&'static str] {
+    if kind == "maintenance" {
+        MAINTENANCE_STATUSES
+    } else {
+        INCIDENT_STATUSES
+    }
+}
+
+fn is_closing_status(status: &str) -> bool {
+    matches!(status, "resolved" | "completed")
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct RouterHealth {
+    id: String,
+    is_online: bool,
+    maintenance_until: Option<DateTime<Utc>>,
+}
+
+/// Status of a group of routers. Routers in a maintenance window don't count
+/// as down; a group that is entirely in maintenance shows as such.
+fn router_status(routers: &[&RouterHealth], now: DateTime<Utc>) -> &'static str {
+    let in_service: Vec<&&RouterHealth> = routers
+        .iter()
+        .filter(|r| r.maintenance_until.is_none_or(|until| until <= now))
+        .collect();
+    if in_service.is_empty() {
+        return if routers.is_empty() {
+            "operational"
+        } else {
+            "maintenance"
+        };
+    }
+    let offline = in_service.iter().filter(|r| !r.is_online).count();
+    if offline == 0 {
+        "operational"
+    } else if offline == in_service.len() {
+        "major_outage"
+    } else if offline * 2 >= in_service.len() {
+        "partial_outage"
+    } else {
+        "degraded"
+    }
+}
+
+/// What the post currently does to its components, if anything.
+fn post_effect(post: &StatusPost, now: DateTime<Utc>) -> Option<&'static str> {
+    match post.kind.as_str() {
+        "incident" if post.status != "resolved" => Some(impact_status(&post.impact)),
+        "maintenance" => {
+            let in_window = post.scheduled_start.is_some_and(|start| start <= now)
+                && post.scheduled_end.is_none_or(|end| end > now);
+            let underway =
+                post.status == "in_progress" || (post.status == "scheduled" && in_window);
+            underway.then_some("maintenance")
+        }
+        _ => None,
+    }
+}
+
+fn component_status(
+    component: &StatusComponent,
+    routers: &[RouterHealth],
+    posts: &[StatusPost],
+    now: DateTime<Utc>,
+) -> String {
+    let own = match (&component.status_override, component.kind.as_str()) {
+        (Some(status), _) => worst(status, "operational"),
+        (None, "network") => {
+            let group: Vec<&RouterHealth> = if component.router_ids.is_empty() {
+                routers.iter().collect()
+            } else {
+                routers
+                    .iter()
+                    .filter(|r| component.router_ids.contains(&r.id))
+                    .collect()
+            };
+            router_status(&group, now)
+        }
+        _ => "operational",
+    };
+
+    posts
+        .iter()
+        .filter(|p| p.component_ids.contains(&component.id))
+        .filter_map(|p| post_effect(p, now))
+        .fold(own, worst)
+        .to_string()
+}
+
+/// Shown when a tenant hasn't set up any components yet.
+fn default_components(now: DateTime<Utc>) -> Vec<StatusComponent> {
+    [
+        ("api", "API"),
+        ("billing", "Billing"),
+        ("network", "Network"),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, (kind, name))| StatusComponent {
+        id: kind.to_string(),
+        name: name.to_string(),
+        description: None,
+        kind: kind.to_string(),
+        router_ids: Vec::new(),
+        status_override: None,
+        position: i as i32,
+        is_visible: true,
+        created_at: now,
+        updated_at: now,
+    })
+    .collect()
+}
+
+fn normalize_ids(values: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for v in values.iter().map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        if !out.iter().any(|o| o == v) {
+            out.push(v.to_string());
+        }
+    }
+    out
+}
+
+fn require_one_of(field: &str, value: &str, allowed: &[&str]) -> AppResult<()> {
+    if allowed.contains(&value) {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!(
+            "{} must be one of: {}",
+            field,
+            allowed.join(", ")
+        )))
+    }
+}
+
+fn require_text(field: &str, value: &str, max: usize) -> AppResult<String> {
+    let value = value.trim();
+    if value.is_empty() || value.chars().count() > max {
+        return Err(AppError::Validation(format!(
+            "{} must be 1-{} characters",
+            field, max
+        )));
+    }
+    Ok(value.to_string())
+}
+
+fn validate_schedule(
+    kind: &str,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> AppResult<()> {
+    match (kind, start, end) {
+        ("maintenance", Some(start), Some(end)) if end > start => Ok(()),
+        ("maintenance", Some(_), Some(_)) => Err(AppError::Validation(
+            "Maintenance must end after it starts".to_string(),
+        )),
+        ("maintenance", _, _) => Err(AppError::Validation(
+            "Maintenance needs scheduled_start and scheduled_end".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Clone)]
+pub struct StatusPageService {
+    pool: DbPool,
+    settings_service: SettingsService,
+    cache: Arc<MemoryCache<PublicStatusPage>>,
+}
+
+impl StatusPageService {
+    pub fn new(pool: DbPool, settings_service: SettingsService) -> Self {
+        Self {
+            pool,
+            settings_service,
+            cache: Arc::new(MemoryCache::new(CACHE_TTL_SECS)),
+        }
+    }
+
+    async fn is_enabled(&self, tenant_id: &str) -> bool {
+        self.settings_service
+            .get_value(Some(tenant_id), "status_page_enabled")
+            .await
+            .ok()
+            .flatten()
+            .is_some_and(|v| v.trim() == "true")
+    }
+
+    /// The page for `slug`, or NotFound when there is no such active tenant or
+    /// it hasn't enabled its status page.
+    pub async fn get_public(&self, slug: &str) -> AppResult<PublicStatusPage> {
+        let tenant: Option<(String, String, String)> = sqlx::query_as(
+            "SELECT id, name, slug FROM tenants WHERE slug = $1 AND is_active = true",
+        )
+        .bind(slug)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((tenant_id, name, slug)) = tenant else {
+            return Err(AppError::NotFound("Status page not found".to_string()));
+        };
+        if !self.is_enabled(&tenant_id).await {
+            return Err(AppError::NotFound("Status page not found".to_string()));
+        }
+
+        if let Some(page) = self.cache.get(&tenant_id) {
+            return Ok(page);
+        }
+        let page = self.build(&tenant_id, name, slug).await?;
+        self.cache.set(tenant_id, page.clone());
+        Ok(page)
+    }
+
+    /// The page as the public would see it, whether or not it is enabled.
+    pub async fn preview(&self, tenant_id: &str) -> AppResult<PublicStatusPage> {
+        let (name, slug): (String, String) =
+            sqlx::query_as("SELECT name, slug FROM tenants WHERE id = $1")
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Tenant not found".to_string()))?;
+        self.build(tenant_id, name, slug).await
+    }
+
+    async fn build(
+        &self,
+        tenant_id: &str,
+        tenant_name: String,
+        slug: String,
+    ) -> AppResult<PublicStatusPage> {
+        let now = Utc::now();
+        let mut components = self.list_components(tenant_id).await?;
+        if components.is_empty() {
+            components = default_components(now);
+        }
+        let routers: Vec<RouterHealth> = sqlx::query_as(
+            r#"
+            SELECT id, is_online, maintenance_until
+            FROM mikrotik_routers
+            WHERE tenant_id = $1 AND enabled = true AND deleted_at IS NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let posts = self.list_posts(tenant_id, false).await?;
+
+        let components: Vec<PublicStatusComponent> = components
+            .into_iter()
+            .filter(|c| c.is_visible)
+            .map(|c| PublicStatusComponent {
+                status: component_status(&c, &routers, &posts, now),
+                id: c.id,
+                name: c.name,
+                description: c.description,
+                kind: c.kind,
+            })
+            .collect();
+        let status = components
+            .iter()
+            .map(|c| c.status.as_str())
+            .fold("operational", worst)
+            .to_string();
+
+        let (incidents, maintenance): (Vec<StatusPost>, Vec<StatusPost>) =
+            posts.into_iter().partition(|p| p.kind == "incident");
+        let maintenance = maintenance
+            .into_iter()
+            .filter(|p| p.scheduled_end.is_none_or(|end| end > now))
+            .collect();
+
+        Ok(PublicStatusPage {
+            tenant_name,
+            slug,
+            status,
+            components,
+            incidents,
+            maintenance,
+            generated_at: now,
+        })
+    }
+
+    pub async fn list_components(&self, tenant_id: &str) -> AppResult<Vec<StatusComponent>> {
+        let rows = sqlx::query_as::<_, StatusComponentRow>(
+            "SELECT * FROM status_components WHERE tenant_id = $1 ORDER BY position ASC, created_at ASC",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn get_component(&self, tenant_id: &str, id: &str) -> AppResult<StatusComponent> {
+        sqlx::query_as::<_, StatusComponentRow>(
+            "SELECT * FROM status_components WHERE id = $1 AND tenant_id = $2",
+        )
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(Into::into)
+        .ok_or_else(|| AppError::NotFound("Status component not found".to_string()))
+    }
+
+    /// Checks the request and returns `(name, router_ids)` in stored form.
+    async fn validate_component(
+        &self,
+        tenant_id: &str,
+        req: &UpsertStatusComponentRequest,
+    ) -> AppResult<(String, String)> {
+        let name = require_text("Name", &req.name, MAX_NAME_LEN)?;
+        require_one_of("kind", &req.kind, COMPONENT_KINDS)?;
+        if let Some(status) = req.status_override.as_deref() {
+            require_one_of("status_override", status, COMPONENT_STATUSES)?;
+        }
+
+        let router_ids = normalize_ids(req.router_ids.as_deref().unwrap_or_default());
+        if !router_ids.is_empty() {
+            if req.kind != "network" {
+                return Err(AppError::Validation(
+                    "Only network components can have routers".to_string(),
+                ));
+            }
+            let known: Vec<String> = sqlx::query_scalar(
+                "SELECT id FROM mikrotik_routers WHERE tenant_id = $1 AND deleted_at IS NULL",
+            )
+            .bind(tenant_id)
+            .fetch_all(&self.pool)
+            .await?;
+            if let Some(bad) = router_ids.iter().find(|id| !known.contains(id)) {
+                return Err(AppError::Validation(format!("Unknown router '{}'", bad)));
+            }
+        }
+        Ok((name, router_ids.join(",")))
+    }
+
+    pub async fn create_component(
+        &self,
+        tenant_id: &str,
+        req: UpsertStatusComponentRequest,
+    ) -> AppResult<StatusComponent> {
+        let (name, router_ids) = self.validate_component(tenant_id, &req).await?;
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO status_components
+                (id, tenant_id, name, description, kind, router_ids, status_override, position, is_visible, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&name)
+        .bind(req.description.as_deref().map(str::trim).filter(|d| !d.is_empty()))
+        .bind(&req.kind)
+        .bind(&router_ids)
+        .bind(req.status_override.as_deref())
+        .bind(req.position.unwrap_or(0))
+        .bind(req.is_visible.unwrap_or(true))
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        self.cache.invalidate(tenant_id);
+        self.get_component(tenant_id, &id).await
+    }
+
+    pub async fn update_component(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        req: UpsertStatusComponentRequest,
+    ) -> AppResult<StatusComponent> {
+        let existing = self.get_component(tenant_id, id).await?;
+        let (name, router_ids) = self.validate_component(tenant_id, &req).await?;
+        sqlx::query(
+            r#"
+            UPDATE status_components
+            SET name = $1, description = $2, kind = $3, router_ids = $4, status_override = $5,
+                position = $6, is_visible = $7, updated_at = $8
+            WHERE id = $9 AND tenant_id = $10
+            "#,
+        )
+        .bind(&name)
+        .bind(
+            req.description
+                .as_deref()
+                .map(str::trim)
+                .filter(|d| !d.is_empty()),
+        )
+        .bind(&req.kind)
+        .bind(&router_ids)
+        .bind(req.status_override.as_deref())
+        .bind(req.position.unwrap_or(existing.position))
+        .bind(req.is_visible.unwrap_or(existing.is_visible))
+        .bind(Utc::now())
+        .bind(id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        self.cache.invalidate(tenant_id);
+        self.get_component(tenant_id, id).await
+    }
+
+    pub async fn delete_component(&self, tenant_id: &str, id: &str) -> AppResult<()> {
+        let res = sqlx::query("DELETE FROM status_components WHERE id = $1 AND tenant_id = $2")
+            .bind(id)
+            .bind(tenant_id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound("Status component not found".to_string()));
+        }
+        self.cache.invalidate(tenant_id);
+        Ok(())
+    }
+
+    /// Incidents and maintenance, newest first. Closed ones only with `include_closed`.
+    pub async fn list_posts(
+        &self,
+        tenant_id: &str,
+        include_closed: bool,
+    ) -> AppResult<Vec<StatusPost>> {
+        let sql = if include_closed {
+            "SELECT * FROM status_posts WHERE tenant_id = $1 ORDER BY created_at DESC LIMIT 200"
+        } else {
+            "SELECT * FROM status_posts WHERE tenant_id = $1 AND resolved_at IS NULL ORDER BY created_at DESC"
+        };
+        let rows: Vec<StatusPostRow> = sqlx::query_as(sql)
+            .bind(tenant_id)
+            .fetch_all(&self.pool)
+            .await?;
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let updates: Vec<StatusPostUpdate> = sqlx::query_as(
+            r#"
+            SELECT u.id, u.post_id, u.status, u.message, u.created_at
+            FROM status_post_updates u
+            JOIN status_posts p ON p.id = u.post_id
+            WHERE p.tenant_id = $1
+            ORDER BY u.created_at ASC
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut by_post: HashMap<String, Vec<StatusPostUpdate>> = HashMap::new();
+        for update in updates {
+            by_post
+                .entry(update.post_id.clone())
+                .or_default()
+                .push(update);
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let updates = by_post.remove(&row.id).unwrap_or_default();
+                StatusPost::from_row(row, updates)
+            })
+            .collect())
+    }
+
+    async fn get_post(&self, tenant_id: &str, id: &str) -> AppResult<StatusPost> {
+        let row: StatusPostRow =
+            sqlx::query_as("SELECT * FROM status_posts WHERE id = $1 AND tenant_id = $2")
+                .bind(id)
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Status post not found".to_string()))?;
+        let updates: Vec<StatusPostUpdate> = sqlx::query_as(
+            "SELECT id, post_id, status, message, created_at FROM status_post_updates WHERE post_id = $1 ORDER BY created_at ASC",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(StatusPost::from_row(row, updates))
+    }
+
+    async fn validate_component_ids(&self, tenant_id: &str, ids: &[String]) -> AppResult<String> {
+        let ids = normalize_ids(ids);
+        if !ids.is_empty() {
+            let known: Vec<String> =
+                sqlx::query_scalar("SELECT id FROM status_components WHERE tenant_id = $1")
+                    .bind(tenant_id)
+                    .fetch_all(&self.pool)
+                    .await?;
+            if let Some(bad) = ids.iter().find(|id| !known.contains(id)) {
+                return Err(AppError::Validation(format!(
+                    "Unknown status component '{}'",
+                    bad
+                )));
+            }
+        }
+        Ok(ids.join(","))
+    }
+
+    pub async fn create_post(
+        &self,
+        tenant_id: &str,
+        req: CreateStatusPostRequest,
+        created_by: Option<&str>,
+    ) -> AppResult<StatusPost> {
+        require_one_of("kind", &req.kind, &["incident", "maintenance"])?;
+        let title = require_text("Title", &req.title, MAX_TITLE_LEN)?;
+        let message = require_text("Message", &req.message, MAX_MESSAGE_LEN)?;
+        let default_impact = if req.kind == "maintenance" {
+            "none"
+        } else {
+            "minor"
+        };
+        let impact = req.impact.as_deref().unwrap_or(default_impact);
+        require_one_of("impact", impact, POST_IMPACTS)?;
+        let statuses = post_statuses(&req.kind);
+        let status = req.status.as_deref().unwrap_or(statuses[0]);
+        require_one_of("status", status, statuses)?;
+        validate_schedule(&req.kind, req.scheduled_start, req.scheduled_end)?;
+        let component_ids = self
+            .validate_component_ids(tenant_id, req.component_ids.as_deref().unwrap_or_default())
+            .await?;
+
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO status_posts
+                (id, tenant_id, kind, title, impact, status, component_ids, scheduled_start, scheduled_end, resolved_at, created_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $12)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&req.kind)
+        .bind(&title)
+        .bind(impact)
+        .bind(status)
+        .bind(&component_ids)
+        .bind(req.scheduled_start)
+        .bind(req.scheduled_end)
+        .bind(is_closing_status(status).then_some(now))
+        .bind(created_by)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO status_post_updates (id, post_id, status, message, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&id)
+        .bind(status)
+        .bind(&message)
+        .bind(created_by)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.cache.invalidate(tenant_id);
+        self.get_post(tenant_id, &id).await
+    }
+
+    pub async fn update_post(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        req: UpdateStatusPostRequest,
+    ) -> AppResult<StatusPost> {
+        let existing = self.get_post(tenant_id, id).await?;
+        let title = match req.title.as_deref() {
+            Some(title) => require_text("Title", title, MAX_TITLE_LEN)?,
+            None => existing.title.clone(),
+        };
+        let impact = req.impact.unwrap_or(existing.impact);
+        require_one_of("impact", &impact, POST_IMPACTS)?;
+        let scheduled_start = req.scheduled_start.or(existing.scheduled_start);
+        let scheduled_end = req.scheduled_end.or(existing.scheduled_end);
+        validate_schedule(&existing.kind, scheduled_start, scheduled_end)?;
+        let component_ids = match req.component_ids.as_deref() {
+            Some(ids) => self.validate_component_ids(tenant_id, ids).await?,
+            None => existing.component_ids.join(","),
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE status_posts
+            SET title = $1, impact = $2, component_ids = $3, scheduled_start = $4,
+                scheduled_end = $5, updated_at = $6
+            WHERE id = $7 AND tenant_id = $8
+            "#,
+        )
+        .bind(&title)
+        .bind(&impact)
+        .bind(&component_ids)
+        .bind(scheduled_start)
+        .bind(scheduled_end)
+        .bind(Utc::now())
+        .bind(id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        self.cache.invalidate(tenant_id);
+        self.get_post(tenant_id, id).await
+    }
+
+    /// Adds an entry to the post's timeline and moves the post to its status.
+    /// Resolving (or completing) closes the post; any other status reopens it.
+    pub async fn add_update(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        req: AddStatusPostUpdateRequest,
+        created_by: Option<&str>,
+    ) -> AppResult<StatusPost> {
+        let post = self.get_post(tenant_id, id).await?;
+        require_one_of("status", &req.status, post_statuses(&post.kind))?;
+        let message = require_text("Message", &req.message, MAX_MESSAGE_LEN)?;
+
+        let now = Utc::now();
+        let resolved_at = if is_closing_status(&req.status) {
+            Some(post.resolved_at.unwrap_or(now))
+        } else {
+            None
+        };
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "UPDATE status_posts SET status = $1, resolved_at = $2, updated_at = $3 WHERE id = $4 AND tenant_id = $5",
+        )
+        .bind(&req.status)
+        .bind(resolved_at)
+        .bind(now)
+        .bind(id)
+        .bind(tenant_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO status_post_updates (id, post_id, status, message, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(id)
+        .bind(&req.status)
+        .bind(&message)
+        .bind(created_by)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.cache.invalidate(tenant_id);
+        self.get_post(tenant_id, id).await
+    }
+
+    pub async fn delete_post(&self, tenant_id: &str, id: &str) -> AppResult<()> {
+        let res = sqlx::query("DELETE FROM status_posts WHERE id = $1 AND tenant_id = $2")
+            .bind(id)
+            .bind(tenant_id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound("Status post not found".to_string()));
+        }
+        self.cache.invalidate(tenant_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn router(id: &str, online: bool, maintenance_until: Option<DateTime<Utc>>) -> RouterHealth {
+        RouterHealth {
+            id: id.to_string(),
+            is_online: online,
+            maintenance_until,
+        }
+    }
+
+    fn component(id: &str, kind: &str, router_ids: &[&str]) -> StatusComponent {
+        let mut c = default_components(Utc::now()).remove(0);
+        c.id = id.to_string();
+        c.kind = kind.to_string();
+        c.router_ids = router_ids.iter().map(|r| r.to_string()).collect();
+        c
+    }
+
+    fn post(kind: &str, status: &str, impact: &str, component_ids: &[&str]) -> StatusPost {
+        let now = Utc::now();
+        StatusPost {
+            id: "p1".to_string(),
+            kind: kind.to_string(),
+            title: "Post".to_string(),
+            impact: impact.to_string(),
+            status: status.to_string(),
+            component_ids: component_ids.iter().map(|c| c.to_string()).collect(),
+            scheduled_start: None,
+            scheduled_end: None,
+            resolved_at: None,
+            updates: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn router_groups_degrade_with_the_share_offline() {
+        let now = Utc::now();
+        let later = Some(now + Duration::hours(1));
+        let up = router("a", true, None);
+        let down = router("b", false, None);
+        let down2 = router("c", false, None);
+        let maint = router("d", false, later);
+
+        assert_eq!(router_status(&[], now), "operational");
+        assert_eq!(router_status(&[&up, &up, &up], now), "operational");
+        assert_eq!(router_status(&[&up, &up, &down], now), "degraded");
+        assert_eq!(router_status(&[&up, &down], now), "partial_outage");
+        assert_eq!(router_status(&[&down, &down2], now), "major_outage");
+        // Routers in a maintenance window are left out.
+        assert_eq!(router_status(&[&up, &maint], now), "operational");
+        assert_eq!(router_status(&[&maint], now), "maintenance");
+    }
+
+    #[test]
+    fn components_take_the_worst_of_routers_override_and_posts() {
+        let now = Utc::now();
+        let routers = vec![router("r1", true, None), router("r2", false, None)];
+
+        let north = component("north", "network", &["r1"]);
+        let south = component("south", "network", &["r2"]);
+        let all = component("all", "network", &[]);
+        assert_eq!(component_status(&north, &routers, &[], now), "operational");
+        assert_eq!(component_status(&south, &routers, &[], now), "major_outage");
+        assert_eq!(component_status(&all, &routers, &[], now), "partial_outage");
+
+        let mut overridden = south.clone();
+        overridden.status_override = Some("degraded".to_string());
+        assert_eq!(
+            component_status(&overridden, &routers, &[], now),
+            "degraded"
+        );
+
+        let api = component("api", "api", &[]);
+        let incident = post("incident", "investigating", "major", &["api"]);
+        assert_eq!(
+            component_status(&api, &routers, &[incident.clone()], now),
+            "partial_outage"
+        );
+        let resolved = post("incident", "resolved", "critical", &["api"]);
+        assert_eq!(
+            component_status(&api, &routers, &[resolved], now),
+            "operational"
+        );
+        // A post about another component doesn't touch this one.
+        assert_eq!(
+            component_status(&north, &routers, &[incident], now),
+            "operational"
+        );
+    }
+
+    #[test]
+    fn maintenance_counts_inside_its_window_or_once_started() {
+        let now = Utc::now();
+        let mut upcoming = post("maintenance", "scheduled", "none", &["api"]);
+        upcoming.scheduled_start = Some(now + Duration::hours(2));
+        upcoming.scheduled_end = Some(now + Duration::hours(3));
+        assert_eq!(post_effect(&upcoming, now), None);
+
+        let mut due = upcoming.clone();
+        due.scheduled_start = Some(now - Duration::minutes(5));
+        assert_eq!(post_effect(&due, now), Some("maintenance"));
+
+        upcoming.status = "in_progress".to_string();
+        assert_eq!(post_effect(&upcoming, now), Some("maintenance"));
+
+        due.status = "completed".to_string();
+        assert_eq!(post_effect(&due, now), None);
+    }
+
+    #[test]
+    fn validates_statuses_and_schedules() {
+        assert!(require_one_of("status", "monitoring", post_statuses("incident")).is_ok());
+        assert!(require_one_of("status", "in_progress", post_statuses("incident")).is_err());
+        assert!(require_one_of("status", "in_progress", post_statuses("maintenance")).is_ok());
+
+        let now = Utc::now();
+        assert!(validate_schedule("incident", None, None).is_ok());
+        assert!(validate_schedule("maintenance", None, None).is_err());
+        assert!(validate_schedule("maintenance", Some(now), Some(now)).is_err());
+        assert!(
+            validate_schedule("maintenance", Some(now), Some(now + Duration::hours(1))).is_ok()
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Where uploaded file bytes live.
+//!
+//! A backend stores objects under keys laid out as `tenant_id/YYYY/MM/<name>`.
+//! The local backend keeps them under the app data dir; the S3 backend talks to
+//! any S3-compatible store (AWS S3, MinIO, Cloudflare R2). Every file record
+//! keeps the provider and bucket it was written to, so reads and deletes go to
+//! where the bytes are even after a tenant's storage settings change.
+
+use crate::error::{AppError, AppResult};
+use crate::services::storage_service::{StorageConfig, StorageContent};
+use async_trait::async_trait;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::{config::Region, Client};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Bytes handed to `StorageBackend::put`.
+pub enum StorageBody {
+    Bytes(Vec<u8>),
+    /// A temporary file, moved into the backend (gone after `put`).
+    TempFile(PathBuf),
+    /// An existing file, copied and left in place.
+    CopyOf(PathBuf),
+}
+
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Value kept in `file_records.storage_provider`.
+    fn provider(&self) -> &str;
+
+    /// Value kept in `file_records.storage_bucket`; `None` on local disk.
+    fn bucket(&self) -> Option<&str>;
+
+    /// Stores `body` under `key` and returns the path to record for it.
+    async fn put(&self, key: &str, body: StorageBody, content_type: &str) -> AppResult<String>;
+
+    async fn get(&self, path: &str) -> AppResult<StorageContent>;
+
+    /// Removes the object; a missing one is not an error.
+    async fn delete(&self, path: &str) -> AppResult<()>;
+}
+
+pub fn is_s3_provider(provider: &str) -> bool {
+    matches!(provider, "s3" | "r2")
+}
+
+/// `prefix/key`, with stray slashes trimmed from the prefix.
+pub fn prefixed_key(prefix: &str, key: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}/{}", prefix, key)
+    }
+}
+
+pub struct LocalBackend {
+    root: PathBuf,
+}
+
+impl LocalBackend {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+}
+
+#[async_trait]
+impl StorageBackend for LocalBackend {
+    fn provider(&self) -> &str {
+        "local"
+    }
+
+    fn bucket(&self) -> Option<&str> {
+        None
+    }
+
+    async fn put(&self, key: &str, body: StorageBody, _content_type: &str) -> AppResult<String> {
+        let path = self.root.join(key);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to create directory: {}", e)))?;
+        }
+
+        match body {
+            StorageBody::Bytes(data) => fs::write(&path, data).await,
+            // Temp files usually share the filesystem; copy when they don't.
+            StorageBody::TempFile(src) => match fs::rename(&src, &path).await {
+                Ok(()) => Ok(()),
+                Err(_) => {
+                    let copied = fs::copy(&src, &path).await.map(|_| ());
+                    fs::remove_file(&src).await.ok();
+                    copied
+                }
+            },
+            StorageBody::CopyOf(src) => fs::copy(&src, &path).await.map(|_| ()),
+        }
+        .map_err(|e| AppError::Internal(format!("Failed to write file to disk: {}", e)))?;
+
+        Ok(path.to_string_lossy().to_string())
+    }
+
+    async fn get(&self, path: &str) -> AppResult<StorageContent> {
+        let path = PathBuf::from(path);
+        if !path.exists() {
+            return Err(AppError::NotFound("File not found on disk".to_string()));
+        }
+        Ok(StorageContent::Local(path))
+    }
+
+    async fn delete(&self, path: &str) -> AppResult<()> {
+        let path = Path::new(path);
+        if path.exists() {
+            fs::remove_file(path)
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to delete file: {}", e)))?;
+        }
+        Ok(())
+    }
+}
+
+pub struct S3Backend {
+    client: Client,
+    provider: String,
+    bucket: String,
+    prefix: String,
+}
+
+impl S3Backend {
+    pub fn new(config: &StorageConfig) -> Self {
+        let creds = aws_sdk_s3::config::Credentials::new(
+            &config.access_key,
+            &config.secret_key,
+            None,
+            None,
+            "static",
+        );
+
+        let mut builder = aws_sdk_s3::Config::builder()
+            .region(Region::new(config.region.clone()))
+            .credentials_provider(creds)
+            .force_path_style(config.force_path_style)
+            .behavior_version_latest();
+
+        if !config.endpoint.is_empty() {
+            builder = builder.endpoint_url(&config.endpoint);
+        }
+
+        Self {
+            client: Client::from_conf(builder.build()),
+            provider: config.driver.clone(),
+            bucket: config.bucket.clone(),
+            prefix: config.prefix.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl StorageBackend for S3Backend {
+    fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    fn bucket(&self) -> Option<&str> {
+        Some(&self.bucket)
+    }
+
+    async fn put(&self, key: &str, body: StorageBody, content_type: &str) -> AppResult<String> {
+        let object_key = prefixed_key(&self.prefix, key);
+        let (stream, temp) = match body {
+            StorageBody::Bytes(data) => (ByteStream::from(data), None),
+            StorageBody::TempFile(src) => (ByteStream::from_path(&src).await, Some(src)),
+            StorageBody::CopyOf(src) => (ByteStream::from_path(&src).await, None),
+        }
+        .map_err(|e| AppError::Internal(format!("Failed to read file for S3 upload: {}", e)))?;
+
+        let res = self
+            .client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(&object_key)
+            .body(stream)
+            .content_type(content_type)
+            .send()
+            .await;
+        if let Some(src) = temp {
+            fs::remove_file(&src).await.ok();
+        }
+        res.map_err(|e| AppError::Internal(format!("S3 Upload Failed: {}", e)))?;
+
+        Ok(object_key)
+    }
+
+    async fn get(&self, path: &str) -> AppResult<StorageContent> {
+        let output = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(path)
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to get S3 object: {}", e)))?;
+        Ok(StorageContent::S3(output.body))
+    }
+
+    async fn delete(&self, path: &str) -> AppResult<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(path)
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to delete S3 object: {}", e)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_prefixed_once() {
+        assert_eq!(prefixed_key("", "t1/2026/10/a.pdf"), "t1/2026/10/a.pdf");
+        assert_eq!(
+            prefixed_key("/isp/uploads/", "t1/2026/10/a.pdf"),
+            "isp/uploads/t1/2026/10/a.pdf"
+        );
+    }
+
+    #[tokio::test]
+    async fn local_backend_moves_temp_files_and_copies_others() {
+        let root = std::env::temp_dir().join(format!("storage-backend-{}", uuid::Uuid::new_v4()));
+        let backend = LocalBackend::new(root.join("uploads"));
+        fs::create_dir_all(&root).await.unwrap();
+
+        let temp = root.join("upload.tmp");
+        fs::write(&temp, b"temp").await.unwrap();
+        let moved = backend
+            .put(
+                "t1/2026/10/a.txt",
+                StorageBody::TempFile(temp.clone()),
+                "text/plain",
+            )
+            .await
+            .unwrap();
+        assert!(!temp.exists());
+        assert_eq!(fs::read(&moved).await.unwrap(), b"temp");
+
+        let copied = backend
+            .put(
+                "t1/2026/10/b.txt",
+                StorageBody::CopyOf(PathBuf::from(&moved)),
+                "text/plain",
+            )
+            .await
+            .unwrap();
+        assert!(Path::new(&moved).exists());
+        assert_eq!(fs::read(&copied).await.unwrap(), b"temp");
+
+        backend.delete(&copied).await.unwrap();
+        backend.delete(&copied).await.unwrap();
+        assert!(backend.get(&copied).await.is_err());
+
+        fs::remove_dir_all(&root).await.ok();
+    }
+}
@@ -0,0 +1,571 @@
+//! Storage quota alerts and cleanup rules.
+//!
+//! An hourly job compares each tenant's `storage_usage` with its plan's
+//! storage quota and tells the owners/admins when usage first reaches 80% and
+//! 95%; `tenants.storage_alert_threshold` remembers the last level announced
+//! and follows usage back down, so a level is announced again only after
+//! usage dropped below it. The same job runs the tenants' cleanup rules once a
+//! day, deleting old files that match a name pattern and/or content type
+//! (e.g. `*.csv` after 30 days).
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    StorageCleanupResult, StorageCleanupRule, StorageUsageCategory, StorageUsageReport,
+    UpsertStorageCleanupRuleDto,
+};
+use crate::services::leader_lock::LeaderLock;
+use crate::services::{NotificationService, StorageService, UsageMetric, UsageService};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+/// Usage levels (percent of the quota) that notify the tenant's admins.
+pub const ALERT_THRESHOLDS: [i32; 2] = [80, 95];
+
+const POLICY_INTERVAL_SECS: u64 = 3600;
+/// Cleanup rules run at most this often.
+const RULE_RUN_INTERVAL_HOURS: i64 = 24;
+/// Files deleted per rule and run; the rest waits for the next run.
+const CLEANUP_BATCH: i64 = 500;
+const MAX_OLDER_THAN_DAYS: i32 = 3650;
+const MAX_RULE_NAME_LEN: usize = 100;
+const MAX_PATTERN_LEN: usize = 255;
+
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Highest alert threshold `used` has reached, 0 below all of them (and for
+/// an empty quota, which blocks uploads outright).
+pub fn alert_threshold(used: i64, limit: i64) -> i32 {
+    if limit <= 0 {
+        return 0;
+    }
+    ALERT_THRESHOLDS
+        .iter()
+        .rev()
+        .copied()
+        .find(|t| i128::from(used) * 100 >= i128::from(*t) * i128::from(limit))
+        .unwrap_or(0)
+}
+
+/// `used` as a percentage of `limit`, to one decimal.
+pub fn usage_percent(used: i64, limit: i64) -> Option<f64> {
+    if limit <= 0 {
+        return None;
+    }
+    Some((used as f64 * 1000.0 / limit as f64).round() / 10.0)
+}
+
+/// SQL `LIKE` pattern (escaped with `\`) for a lower-cased wildcard pattern.
+pub fn glob_to_like(pattern: &str) -> String {
+    let mut out = String::with_capacity(pattern.len());
+    for c in pattern.trim().to_lowercase().chars() {
+        match c {
+            '*' => out.push('%'),
+            '?' => out.push('_'),
+            '%' | '_' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Trimmed pattern, or None when blank.
+fn normalize_pattern(value: Option<String>, label: &str) -> AppResult<Option<String>> {
+    let Some(value) = value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+    else {
+        return Ok(None);
+    };
+    if value.len() > MAX_PATTERN_LEN {
+        return Err(AppError::Validation(format!(
+            "{} is longer than {} characters",
+            label, MAX_PATTERN_LEN
+        )));
+    }
+    Ok(Some(value))
+}
+
+/// Validated rule fields: (name, name_pattern, content_type, older_than_days).
+fn validate_rule(
+    dto: UpsertStorageCleanupRuleDto,
+) -> AppResult<(String, Option<String>, Option<String>, i32)> {
+    let name = dto.name.trim().to_string();
+    if name.is_empty() || name.len() > MAX_RULE_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "Rule name must be 1 to {} characters",
+            MAX_RULE_NAME_LEN
+        )));
+    }
+    let name_pattern = normalize_pattern(dto.name_pattern, "Name pattern")?;
+    let content_type =
+        normalize_pattern(dto.content_type, "Content type")?.map(|v| v.to_lowercase());
+    if name_pattern.is_none() && content_type.is_none() {
+        return Err(AppError::Validation(
+            "A cleanup rule needs a name pattern or a content type".to_string(),
+        ));
+    }
+    if !(1..=MAX_OLDER_THAN_DAYS).contains(&dto.older_than_days) {
+        return Err(AppError::Validation(format!(
+            "Age must be between 1 and {} days",
+            MAX_OLDER_THAN_DAYS
+        )));
+    }
+    Ok((name, name_pattern, content_type, dto.older_than_days))
+}
+
+fn format_gb(bytes: i64) -> String {
+    format!("{:.2} GB", bytes as f64 / BYTES_PER_GB)
+}
+
+#[derive(Clone)]
+pub struct StoragePolicyService {
+    pool: DbPool,
+    storage: StorageService,
+    usage: UsageService,
+    notification_service: NotificationService,
+}
+
+impl StoragePolicyService {
+    pub fn new(
+        pool: DbPool,
+        storage: StorageService,
+        notification_service: NotificationService,
+    ) -> Self {
+        Self {
+            usage: UsageService::new(pool.clone()),
+            pool,
+            storage,
+            notification_service,
+        }
+    }
+
+    /// Usage against the plan quota, with a breakdown of what takes the space.
+    /// Files kept in a tenant's own bucket are not counted.
+    pub async fn usage_report(&self, tenant_id: &str) -> AppResult<StorageUsageReport> {
+        let used = self.usage.usage(tenant_id, UsageMetric::Storage).await?;
+        let limit = self.usage.limit(tenant_id, UsageMetric::Storage).await?;
+
+        let categories: Vec<StorageUsageCategory> = sqlx::query_as(
+            r#"
+            SELECT category,
+                   COUNT(*) AS files,
+                   CAST(COALESCE(SUM(size), 0) AS BIGINT) AS bytes
+            FROM (
+                SELECT size,
+                       CASE
+                           WHEN content_type LIKE 'image/%' THEN 'image'
+                           WHEN content_type LIKE 'video/%' THEN 'video'
+                           WHEN content_type LIKE 'audio/%' THEN 'audio'
+                           WHEN content_type LIKE 'text/%'
+                             OR content_type = 'application/pdf'
+                             OR content_type LIKE 'application/vnd.%'
+                             OR content_type LIKE 'application/msword%' THEN 'document'
+                           ELSE 'other'
+                       END AS category
+                FROM file_records
+                WHERE tenant_id = $1 AND quota_counted
+            ) f
+            GROUP BY category
+            ORDER BY bytes DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let (version_count, version_bytes): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*), CAST(COALESCE(SUM(size), 0) AS BIGINT)
+            FROM file_versions
+            WHERE tenant_id = $1 AND quota_counted
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let alert_threshold: i32 =
+            sqlx::query_scalar("SELECT storage_alert_threshold FROM tenants WHERE id = $1")
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .unwrap_or(0);
+
+        Ok(StorageUsageReport {
+            tenant_id: tenant_id.to_string(),
+            used_bytes: used,
+            limit_bytes: limit,
+            percent: limit.and_then(|l| usage_percent(used, l)),
+            alert_threshold,
+            file_count: categories.iter().map(|c| c.files).sum(),
+            file_bytes: categories.iter().map(|c| c.bytes).sum(),
+            version_count,
+            version_bytes,
+            categories,
+        })
+    }
+
+    /// Moves the tenant's alert level to where usage is now, notifying the
+    /// owners/admins when it went up.
+    pub async fn check_quota_alert(&self, tenant_id: &str) -> AppResult<()> {
+        let used = self.usage.usage(tenant_id, UsageMetric::Storage).await?;
+        let limit = self.usage.limit(tenant_id, UsageMetric::Storage).await?;
+        let level = limit.map(|l| alert_threshold(used, l)).unwrap_or(0);
+        let previous: i32 =
+            sqlx::query_scalar("SELECT storage_alert_threshold FROM tenants WHERE id = $1")
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .unwrap_or(0);
+        if level == previous {
+            return Ok(());
+        }
+
+        // Compare-and-set, so two instances don't both announce a level.
+        let res = sqlx::query(
+            "UPDATE tenants SET storage_alert_threshold = $1 WHERE id = $2 AND storage_alert_threshold = $3",
+        )
+        .bind(level)
+        .bind(tenant_id)
+        .bind(previous)
+        .execute(&self.pool)
+        .await?;
+        if res.rows_affected() == 0 || level < previous {
+            return Ok(());
+        }
+        if let Some(limit) = limit {
+            self.notify_quota(tenant_id, level, used, limit).await;
+        }
+        Ok(())
+    }
+
+    async fn notify_quota(&self, tenant_id: &str, level: i32, used: i64, limit: i64) {
+        let admins: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT user_id FROM tenant_members WHERE tenant_id = $1 AND LOWER(TRIM(role)) IN ('owner', 'admin')",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        let title = format!("Storage is {}% full", level);
+        let message = format!(
+            "{} of {} used. Delete files you no longer need, set up cleanup rules or upgrade the plan before uploads are blocked.",
+            format_gb(used),
+            format_gb(limit)
+        );
+        let notification_type = if level >= 95 { "error" } else { "warning" };
+        for user_id in admins {
+            let _ = self
+                .notification_service
+                .create_notification(
+                    user_id,
+                    Some(tenant_id.to_string()),
+                    title.clone(),
+                    message.clone(),
+                    notification_type.to_string(),
+                    "billing".to_string(),
+                    Some("/admin/storage".to_string()),
+                )
+                .await;
+        }
+    }
+
+    pub async fn list_rules(&self, tenant_id: &str) -> AppResult<Vec<StorageCleanupRule>> {
+        let rows: Vec<StorageCleanupRule> = sqlx::query_as(
+            "SELECT * FROM storage_cleanup_rules WHERE tenant_id = $1 ORDER BY lower(name)",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn get_rule(&self, tenant_id: &str, id: &str) -> AppResult<StorageCleanupRule> {
+        let row: Option<StorageCleanupRule> =
+            sqlx::query_as("SELECT * FROM storage_cleanup_rules WHERE tenant_id = $1 AND id = $2")
+                .bind(tenant_id)
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+        row.ok_or_else(|| AppError::NotFound("Cleanup rule not found".to_string()))
+    }
+
+    pub async fn create_rule(
+        &self,
+        tenant_id: &str,
+        created_by: &str,
+        dto: UpsertStorageCleanupRuleDto,
+    ) -> AppResult<StorageCleanupRule> {
+        let enabled = dto.enabled.unwrap_or(true);
+        let (name, name_pattern, content_type, older_than_days) = validate_rule(dto)?;
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO storage_cleanup_rules
+                (id, tenant_id, name, name_pattern, content_type, older_than_days, enabled,
+                 created_by, created_at, updated_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$9)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&name)
+        .bind(&name_pattern)
+        .bind(&content_type)
+        .bind(older_than_days)
+        .bind(enabled)
+        .bind(created_by)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        self.get_rule(tenant_id, &id).await
+    }
+
+    pub async fn update_rule(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        dto: UpsertStorageCleanupRuleDto,
+    ) -> AppResult<StorageCleanupRule> {
+        let current = self.get_rule(tenant_id, id).await?;
+        let enabled = dto.enabled.unwrap_or(current.enabled);
+        let (name, name_pattern, content_type, older_than_days) = validate_rule(dto)?;
+        sqlx::query(
+            r#"
+            UPDATE storage_cleanup_rules
+            SET name = $1,
+                name_pattern = $2,
+                content_type = $3,
+                older_than_days = $4,
+                enabled = $5,
+                updated_at = $6
+            WHERE tenant_id = $7 AND id = $8
+            "#,
+        )
+        .bind(&name)
+        .bind(&name_pattern)
+        .bind(&content_type)
+        .bind(older_than_days)
+        .bind(enabled)
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        self.get_rule(tenant_id, id).await
+    }
+
+    pub async fn delete_rule(&self, tenant_id: &str, id: &str) -> AppResult<()> {
+        let res = sqlx::query("DELETE FROM storage_cleanup_rules WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound("Cleanup rule not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Deletes the files the rule matches (with their versions) and records
+    /// the outcome on the rule.
+    pub async fn run_rule(&self, rule: &StorageCleanupRule) -> AppResult<StorageCleanupResult> {
+        let cutoff = Utc::now() - Duration::days(i64::from(rule.older_than_days));
+        let matches: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT id, size FROM file_records
+            WHERE tenant_id = $1
+              AND updated_at < $2
+              AND ($3 IS NULL OR LOWER(original_name) LIKE $3 ESCAPE '\')
+              AND ($4 IS NULL OR LOWER(content_type) LIKE $4 ESCAPE '\')
+            ORDER BY updated_at
+            LIMIT $5
+            "#,
+        )
+        .bind(&rule.tenant_id)
+        .bind(cutoff)
+        .bind(rule.name_pattern.as_deref().map(glob_to_like))
+        .bind(rule.content_type.as_deref().map(glob_to_like))
+        .bind(CLEANUP_BATCH)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut result = StorageCleanupResult {
+            rule_id: rule.id.clone(),
+            deleted_count: 0,
+            deleted_bytes: 0,
+            failed: 0,
+        };
+        for (file_id, size) in matches {
+            match self
+                .storage
+                .delete_tenant_file(&file_id, &rule.tenant_id)
+                .await
+            {
+                Ok(()) => {
+                    result.deleted_count += 1;
+                    result.deleted_bytes += size;
+                }
+                // Deleted meanwhile.
+                Err(AppError::NotFound(_)) => {}
+                Err(e) => {
+                    result.failed += 1;
+                    tracing::warn!(
+                        "Cleanup rule {} failed to delete file {}: {}",
+                        rule.id,
+                        file_id,
+                        e
+                    );
+                }
+            }
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE storage_cleanup_rules
+            SET last_run_at = $1, last_deleted_count = $2, last_deleted_bytes = $3
+            WHERE id = $4
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(result.deleted_count as i32)
+        .bind(result.deleted_bytes)
+        .bind(&rule.id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.deleted_count > 0 {
+            tracing::info!(
+                "Cleanup rule {} of tenant {} deleted {} file(s) ({} bytes)",
+                rule.id,
+                rule.tenant_id,
+                result.deleted_count,
+                result.deleted_bytes
+            );
+            self.check_quota_alert(&rule.tenant_id).await.ok();
+        }
+        Ok(result)
+    }
+
+    /// Background job: quota alerts every hour, cleanup rules once a day.
+    pub fn start_worker(&self) {
+        let this = self.clone();
+        // One instance at a time, so files are deleted and alerts sent once.
+        let leader = LeaderLock::new(self.pool.clone(), "storage_policies");
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(tokio::time::Duration::from_secs(POLICY_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                if leader.acquire().await {
+                    this.run_due_rules().await;
+                    this.check_all_quota_alerts().await;
+                }
+            }
+        });
+    }
+
+    async fn run_due_rules(&self) {
+        let due_before = Utc::now() - Duration::hours(RULE_RUN_INTERVAL_HOURS);
+        let rules: Vec<StorageCleanupRule> = match sqlx::query_as(
+            "SELECT * FROM storage_cleanup_rules WHERE enabled AND (last_run_at IS NULL OR last_run_at < $1)",
+        )
+        .bind(due_before)
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(rules) => rules,
+            Err(e) => {
+                tracing::warn!("Failed to list due storage cleanup rules: {}", e);
+                return;
+            }
+        };
+
+        for rule in rules {
+            if let Err(e) = self.run_rule(&rule).await {
+                tracing::warn!("Storage cleanup rule {} failed: {}", rule.id, e);
+            }
+        }
+    }
+
+    async fn check_all_quota_alerts(&self) {
+        let tenant_ids: Vec<String> = match sqlx::query_scalar(
+            "SELECT id FROM tenants WHERE storage_usage > 0 OR storage_alert_threshold > 0",
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(ids) => ids,
+            Err(e) => {
+                tracing::warn!("Storage quota alerts failed to list tenants: {}", e);
+                return;
+            }
+        };
+
+        for tenant_id in tenant_ids {
+            if let Err(e) = self.check_quota_alert(&tenant_id).await {
+                tracing::warn!("Storage quota alert for tenant {} failed: {}", tenant_id, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dto(name_pattern: Option<&str>, content_type: Option<&str>) -> UpsertStorageCleanupRuleDto {
+        UpsertStorageCleanupRuleDto {
+            name: " Old exports ".to_string(),
+            name_pattern: name_pattern.map(str::to_string),
+            content_type: content_type.map(str::to_string),
+            older_than_days: 30,
+            enabled: None,
+        }
+    }
+
+    #[test]
+    fn alert_levels_follow_usage() {
+        assert_eq!(alert_threshold(79, 100), 0);
+        assert_eq!(alert_threshold(80, 100), 80);
+        assert_eq!(alert_threshold(94, 100), 80);
+        assert_eq!(alert_threshold(95, 100), 95);
+        assert_eq!(alert_threshold(150, 100), 95);
+        assert_eq!(alert_threshold(10, 0), 0);
+        assert_eq!(alert_threshold(i64::MAX, i64::MAX), 95);
+        assert_eq!(usage_percent(1, 3), Some(33.3));
+        assert_eq!(usage_percent(1, 0), None);
+    }
+
+    #[test]
+    fn wildcards_become_escaped_like_patterns() {
+        assert_eq!(glob_to_like("*.CSV"), "%.csv");
+        assert_eq!(glob_to_like("export_??.csv"), "export\\___.csv");
+        assert_eq!(glob_to_like("100%"), "100\\%");
+        assert_eq!(glob_to_like("image/*"), "image/%");
+    }
+
+    #[test]
+    fn rules_need_a_filter_and_a_sane_age() {
+        let (name, pattern, content_type, days) =
+            validate_rule(dto(Some(" *.csv "), Some(" "))).unwrap();
+        assert_eq!(name, "Old exports");
+        assert_eq!(pattern.as_deref(), Some("*.csv"));
+        assert_eq!(content_type, None);
+        assert_eq!(days, 30);
+
+        assert!(validate_rule(dto(None, Some("TEXT/CSV"))).is_ok());
+        assert!(validate_rule(dto(None, None)).is_err());
+        assert!(validate_rule(UpsertStorageCleanupRuleDto {
+            older_than_days: 0,
+            ..dto(Some("*.csv"), None)
+        })
+        .is_err());
+    }
+}
@@ -1,15 +1,36 @@
 //! Storage Service for handling file uploads
+//!
+//! File bytes go through a `StorageBackend` (local disk or S3-compatible, see
+//! `storage_backend`); this service owns the `file_records` bookkeeping, plan
+//! quotas and the choice of backend per tenant.
 use crate::db::DbPool;
 use crate::error::{AppError, AppResult};
-use crate::services::PlanService;
+use crate::services::event_outbox_service::hmac_sha256_hex;
+use crate::services::leader_lock::LeaderLock;
+use crate::services::malware_scanner::{ClamdScanner, FileScan, ScanTarget};
+use crate::services::storage_backend::{
+    is_s3_provider, LocalBackend, S3Backend, StorageBackend, StorageBody,
+};
+use crate::services::{NotificationService, PlanService};
 use aws_sdk_s3::primitives::ByteStream;
-use aws_sdk_s3::{config::Region, Client};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
+/// Files moved per tenant on each run of the migration worker.
+const MIGRATION_BATCH: i64 = 25;
+const MIGRATION_INTERVAL_SECS: u64 = 300;
+/// Earlier versions kept per file when `storage_max_versions` is not set.
+const DEFAULT_MAX_VERSIONS: i64 = 10;
+const MAX_LOGICAL_PATH_LEN: usize = 512;
+/// Validity of a signed download link when none is asked for (7 days), and
+/// the longest allowed (30 days).
+pub const DEFAULT_LINK_TTL_MINUTES: i64 = 7 * 24 * 60;
+pub const MAX_LINK_TTL_MINUTES: i64 = 30 * 24 * 60;
+
 #[derive(Debug)]
 pub enum StorageContent {
     Local(PathBuf),
@@ -25,6 +46,139 @@ pub struct StorageConfig {
     pub access_key: String,
     pub secret_key: String,
     pub public_url: String,
+    /// Key prefix inside the bucket.
+    pub prefix: String,
+    /// Path-style URLs (`endpoint/bucket/key`), needed by MinIO.
+    pub force_path_style: bool,
+    /// The tenant's own bucket and credentials; kept out of the plan quota.
+    pub tenant_owned: bool,
+}
+
+/// `storage_*` settings of one scope as a config.
+fn scope_config(settings: &HashMap<String, String>, tenant_owned: bool) -> StorageConfig {
+    let get = |key: &str| {
+        settings
+            .get(key)
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    StorageConfig {
+        driver: get("storage_driver").unwrap_or_else(|| "local".to_string()),
+        bucket: get("storage_s3_bucket").unwrap_or_default(),
+        region: get("storage_s3_region").unwrap_or_else(|| "us-east-1".to_string()),
+        endpoint: get("storage_s3_endpoint").unwrap_or_default(),
+        access_key: get("storage_s3_access_key").unwrap_or_default(),
+        secret_key: get("storage_s3_secret_key").unwrap_or_default(),
+        public_url: get("storage_s3_public_url").unwrap_or_default(),
+        prefix: get("storage_s3_prefix").unwrap_or_default(),
+        force_path_style: get("storage_s3_path_style").as_deref() == Some("true"),
+        tenant_owned,
+    }
+}
+
+/// Where a tenant's new files go. A tenant whose own `storage_driver` is s3/r2
+/// uses its own bucket and credentials entirely. Otherwise the platform driver
+/// applies; on platform S3 the tenant may still pick its own
+/// `storage_s3_bucket` and `storage_s3_prefix`.
+pub fn resolve_storage_config(
+    global: &HashMap<String, String>,
+    tenant: &HashMap<String, String>,
+) -> StorageConfig {
+    let tenant_config = scope_config(tenant, true);
+    if tenant.contains_key("storage_driver") && is_s3_provider(&tenant_config.driver) {
+        return tenant_config;
+    }
+
+    let mut config = scope_config(global, false);
+    if is_s3_provider(&config.driver) {
+        if !tenant_config.bucket.is_empty() {
+            config.bucket = tenant_config.bucket;
+        }
+        if !tenant_config.prefix.is_empty() {
+            config.prefix = tenant_config.prefix;
+        }
+    }
+    config
+}
+
+/// Object key for a file: `tenant_id/YYYY/MM/<name>`.
+fn object_key(tenant_id: &str, at: DateTime<Utc>, name: &str) -> String {
+    format!("{}/{}/{}", tenant_id, at.format("%Y/%m"), name)
+}
+
+/// A second upload racing to create the same logical path is a conflict.
+fn insert_error(e: sqlx::Error) -> AppError {
+    match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            AppError::Conflict("A file already exists at this path".to_string())
+        }
+        e => AppError::Internal(format!("Insert failed: {}", e)),
+    }
+}
+
+/// Canonical form of an uploader-chosen file path: forward slashes, no
+/// leading slash, no `.`/`..` or empty segments.
+pub fn normalize_logical_path(path: &str) -> AppResult<String> {
+    let path = path.trim().replace('\\', "/");
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    if segments.is_empty() {
+        return Err(AppError::Validation("File path is empty".to_string()));
+    }
+    if segments.iter().any(|s| *s == "." || *s == "..") {
+        return Err(AppError::Validation(
+            "File path must not contain '.' or '..'".to_string(),
+        ));
+    }
+    let normalized = segments.join("/");
+    if normalized.len() > MAX_LOGICAL_PATH_LEN {
+        return Err(AppError::Validation(format!(
+            "File path is longer than {} characters",
+            MAX_LOGICAL_PATH_LEN
+        )));
+    }
+    Ok(normalized)
+}
+
+/// Signature of a download link for `file_id` valid until `expires` (unix
+/// seconds).
+pub fn download_signature(secret: &str, file_id: &str, expires: i64) -> String {
+    hmac_sha256_hex(
+        secret.as_bytes(),
+        format!("file-download:{}:{}", file_id, expires).as_bytes(),
+    )
+}
+
+/// Checks a link's signature (in constant time) and that it has not expired.
+pub fn verify_download_signature(
+    secret: &str,
+    file_id: &str,
+    expires: i64,
+    signature: &str,
+    now: DateTime<Utc>,
+) -> AppResult<()> {
+    let expected = download_signature(secret, file_id, expires);
+    let signature = signature.trim().to_ascii_lowercase();
+    let valid = expected.len() == signature.len()
+        && expected
+            .bytes()
+            .zip(signature.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0;
+    if !valid {
+        return Err(AppError::Forbidden("Invalid download link".to_string()));
+    }
+    if expires <= now.timestamp() {
+        return Err(AppError::Forbidden("Download link has expired".to_string()));
+    }
+    Ok(())
+}
+
+/// Outcome of one migration run.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct StorageMigrationReport {
+    pub migrated: u64,
+    pub failed: u64,
 }
 
 #[derive(Clone)]
@@ -32,6 +186,7 @@ pub struct StorageService {
     pool: DbPool,
     plan_service: PlanService,
     base_storage_path: PathBuf,
+    notification_service: Option<NotificationService>,
 }
 
 impl StorageService {
@@ -76,198 +231,312 @@ impl StorageService {
             pool,
             plan_service,
             base_storage_path: storage_path,
+            notification_service: None,
         }
     }
 
+    /// Enables admin notifications about quarantined uploads.
+    pub fn with_notifications(mut self, notification_service: NotificationService) -> Self {
+        self.notification_service = Some(notification_service);
+        self
+    }
+
     /// Get file content stream (Local path or S3 Stream)
     pub async fn get_file_content(
         &self,
         file_id: &str,
     ) -> AppResult<(crate::models::FileRecord, StorageContent)> {
         let file = self.get_file(file_id).await?;
+        if file.scan_status == "infected" {
+            return Err(AppError::Forbidden(
+                "File is quarantined: malware detected".to_string(),
+            ));
+        }
+        let content = self.backend_of(&file).await?.get(&file.path).await?;
+        Ok((file, content))
+    }
 
-        if file.storage_provider == "local" {
-            let path = PathBuf::from(&file.path);
-            if !path.exists() {
-                return Err(AppError::NotFound("File not found on disk".to_string()));
-            }
-            Ok((file, StorageContent::Local(path)))
-        } else if file.storage_provider == "s3" || file.storage_provider == "r2" {
-            let config = self.get_storage_config(&file.tenant_id).await?;
-            let client = self.get_s3_client(&config).await;
-
-            let output = client
-                .get_object()
-                .bucket(&config.bucket)
-                .key(&file.path)
-                .send()
-                .await
-                .map_err(|e| AppError::Internal(format!("Failed to get S3 object: {}", e)))?;
-
-            Ok((file, StorageContent::S3(output.body)))
-        } else {
-            Err(AppError::Internal("Unknown storage provider".to_string()))
+    /// Signs a download link for `file_id`, valid for `ttl_minutes` (7 days
+    /// by default, at most 30).
+    pub async fn create_signed_link(
+        &self,
+        file_id: &str,
+        ttl_minutes: Option<i64>,
+    ) -> AppResult<crate::models::SignedFileLink> {
+        let ttl = ttl_minutes.unwrap_or(DEFAULT_LINK_TTL_MINUTES);
+        if !(1..=MAX_LINK_TTL_MINUTES).contains(&ttl) {
+            return Err(AppError::Validation(format!(
+                "Link expiry must be between 1 and {} minutes",
+                MAX_LINK_TTL_MINUTES
+            )));
         }
+        let file = self.get_file(file_id).await?;
+        if file.scan_status == "infected" {
+            return Err(AppError::Forbidden(
+                "File is quarantined: malware detected".to_string(),
+            ));
+        }
+
+        let expires_at = Utc::now() + chrono::Duration::minutes(ttl);
+        let expires = expires_at.timestamp();
+        let signature = download_signature(&self.link_secret().await?, &file.id, expires);
+        let app_url: Option<String> = sqlx::query_scalar(
+            "SELECT value FROM settings WHERE key = 'app_public_url' AND tenant_id IS NULL",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        let app_url = app_url.unwrap_or_else(|| "http://localhost:3000".to_string());
+
+        Ok(crate::models::SignedFileLink {
+            url: format!(
+                "{}/api/storage/shared/{}?expires={}&signature={}",
+                app_url.trim().trim_end_matches('/'),
+                file.id,
+                expires,
+                signature
+            ),
+            file_id: file.id,
+            expires_at,
+        })
     }
 
-    /// Get storage configuration for a tenant (prioritizing tenant settings over global)
-    async fn get_storage_config(&self, tenant_id: &str) -> AppResult<StorageConfig> {
-        #[cfg(feature = "postgres")]
-        let keys = vec![
-            "storage_driver",
-            "storage_s3_bucket",
-            "storage_s3_region",
-            "storage_s3_endpoint",
-            "storage_s3_access_key",
-            "storage_s3_secret_key",
-            "storage_s3_public_url",
-        ];
-
-        let mut config = StorageConfig {
-            driver: "local".to_string(),
-            bucket: "".to_string(),
-            region: "us-east-1".to_string(),
-            endpoint: "".to_string(),
-            access_key: "".to_string(),
-            secret_key: "".to_string(),
-            public_url: "".to_string(),
-        };
+    /// Content behind a signed download link.
+    pub async fn get_signed_file_content(
+        &self,
+        file_id: &str,
+        expires: i64,
+        signature: &str,
+    ) -> AppResult<(crate::models::FileRecord, StorageContent)> {
+        verify_download_signature(
+            &self.link_secret().await?,
+            file_id,
+            expires,
+            signature,
+            Utc::now(),
+        )?;
+        self.get_file_content(file_id).await
+    }
 
-        // Fetch Tenant Driver preference first
+    /// Download links are signed with the platform's `jwt_secret`.
+    async fn link_secret(&self) -> AppResult<String> {
+        let secret: Option<String> = sqlx::query_scalar(
+            "SELECT value FROM settings WHERE key = 'jwt_secret' AND tenant_id IS NULL",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        secret
+            .filter(|s| !s.trim().is_empty())
+            .ok_or_else(|| AppError::Configuration("jwt_secret is not configured".to_string()))
+    }
+
+    /// Global and tenant `storage_*` settings.
+    async fn storage_settings(
+        &self,
+        tenant_id: &str,
+    ) -> AppResult<(HashMap<String, String>, HashMap<String, String>)> {
         #[cfg(feature = "postgres")]
-        let tenant_driver: Option<String> = sqlx::query_scalar(
-            "SELECT value FROM settings WHERE tenant_id = $1 AND key = 'storage_driver'",
+        let rows: Vec<(Option<String>, String, String)> = sqlx::query_as(
+            "SELECT tenant_id, key, value FROM settings WHERE (tenant_id IS NULL OR tenant_id = $1) AND key LIKE 'storage_%'",
         )
         .bind(tenant_id)
-        .fetch_optional(&self.pool)
-        .await
-        .unwrap_or(None);
+        .fetch_all(&self.pool)
+        .await?;
 
         #[cfg(feature = "sqlite")]
-        let tenant_driver: Option<String> = sqlx::query_scalar(
-            "SELECT value FROM settings WHERE tenant_id = ? AND key = 'storage_driver'",
+        let rows: Vec<(Option<String>, String, String)> = sqlx::query_as(
+            "SELECT tenant_id, key, value FROM settings WHERE (tenant_id IS NULL OR tenant_id = ?) AND key LIKE 'storage_%'",
         )
         .bind(tenant_id)
-        .fetch_optional(&self.pool)
-        .await
-        .unwrap_or(None);
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut global = HashMap::new();
+        let mut tenant = HashMap::new();
+        for (scope, key, value) in rows {
+            match scope {
+                Some(_) => tenant.insert(key, value),
+                None => global.insert(key, value),
+            };
+        }
+        Ok((global, tenant))
+    }
 
-        let use_tenant_config = matches!(tenant_driver.as_deref(), Some("s3") | Some("r2"));
+    /// Get storage configuration for a tenant (see `resolve_storage_config`)
+    async fn get_storage_config(&self, tenant_id: &str) -> AppResult<StorageConfig> {
+        let (global, tenant) = self.storage_settings(tenant_id).await?;
+        Ok(resolve_storage_config(&global, &tenant))
+    }
 
-        #[cfg(feature = "postgres")]
-        let rows: Vec<(String, String)> = if use_tenant_config {
-            if let Some(td) = tenant_driver {
-                config.driver = td;
+    /// Scans an upload when `storage_scan_enabled` is on. A scanner failure
+    /// rejects the upload when `storage_scan_fail_closed` is on and is
+    /// recorded as `error` otherwise.
+    async fn scan_upload(&self, target: ScanTarget<'_>) -> AppResult<FileScan> {
+        let settings: HashMap<String, String> = sqlx::query_as::<_, (String, String)>(
+            "SELECT key, value FROM settings WHERE tenant_id IS NULL AND key IN ('storage_scan_enabled', 'storage_scan_fail_closed', 'storage_clamd_address')",
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .collect();
+        let enabled = |key: &str| settings.get(key).map(|v| v.trim()) == Some("true");
+        if !enabled("storage_scan_enabled") {
+            return Ok(FileScan::unscanned());
+        }
+        let address = settings
+            .get("storage_clamd_address")
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .unwrap_or("127.0.0.1:3310");
+
+        match ClamdScanner::new(address).scan(target).await {
+            Ok(verdict) => Ok(FileScan::from_verdict(verdict)),
+            Err(e) if enabled("storage_scan_fail_closed") => {
+                tracing::error!("[Storage] Malware scan failed, rejecting upload: {}", e);
+                Err(AppError::Internal(
+                    "Upload could not be scanned for malware".to_string(),
+                ))
             }
-            sqlx::query_as("SELECT key, value FROM settings WHERE tenant_id = $1 AND key = ANY($2)")
-                .bind(tenant_id)
-                .bind(&keys)
+            Err(e) => {
+                tracing::warn!("[Storage] Malware scan failed, storing unscanned: {}", e);
+                Ok(FileScan::error())
+            }
+        }
+    }
+
+    /// Tells the tenant's owners/admins and the superadmins about a
+    /// quarantined upload.
+    async fn notify_quarantined(&self, file: &crate::models::FileRecord) {
+        let Some(notification_service) = &self.notification_service else {
+            return;
+        };
+
+        #[cfg(feature = "postgres")]
+        let super_admins: Vec<String> =
+            sqlx::query_scalar("SELECT id FROM users WHERE is_super_admin = true")
                 .fetch_all(&self.pool)
                 .await
-                .unwrap_or_default()
-        } else {
-            sqlx::query_as(
-                "SELECT key, value FROM settings WHERE tenant_id IS NULL AND key = ANY($1)",
-            )
-            .bind(&keys)
-            .fetch_all(&self.pool)
-            .await
-            .unwrap_or_default()
-        };
+                .unwrap_or_default();
 
         #[cfg(feature = "sqlite")]
-        let rows: Vec<(String, String)> = if use_tenant_config {
-            config.driver = tenant_driver.unwrap();
-            // SQLite doesn't support ANY, so we use IN with individual bindings
-            sqlx::query_as(
-                "SELECT key, value FROM settings WHERE tenant_id = ? AND key IN ('storage_driver', 'storage_s3_bucket', 'storage_s3_region', 'storage_s3_endpoint', 'storage_s3_access_key', 'storage_s3_secret_key', 'storage_s3_public_url')"
-            )
-                .bind(tenant_id)
-                .fetch_all(&self.pool).await.unwrap_or_default()
-        } else {
-            sqlx::query_as(
-                "SELECT key, value FROM settings WHERE tenant_id IS NULL AND key IN ('storage_driver', 'storage_s3_bucket', 'storage_s3_region', 'storage_s3_endpoint', 'storage_s3_access_key', 'storage_s3_secret_key', 'storage_s3_public_url')"
-            )
-                .fetch_all(&self.pool).await.unwrap_or_default()
-        };
+        let super_admins: Vec<String> =
+            sqlx::query_scalar("SELECT id FROM users WHERE is_super_admin = 1")
+                .fetch_all(&self.pool)
+                .await
+                .unwrap_or_default();
 
-        for (k, v) in rows {
-            match k.as_str() {
-                "storage_driver" => {
-                    if !use_tenant_config {
-                        config.driver = v
-                    }
-                }
-                "storage_s3_bucket" => config.bucket = v,
-                "storage_s3_region" => config.region = v,
-                "storage_s3_endpoint" => config.endpoint = v,
-                "storage_s3_access_key" => config.access_key = v,
-                "storage_s3_secret_key" => config.secret_key = v,
-                "storage_s3_public_url" => config.public_url = v,
-                _ => {}
+        let tenant_admins: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT user_id FROM tenant_members WHERE tenant_id = $1 AND LOWER(TRIM(role)) IN ('owner', 'admin')",
+        )
+        .bind(&file.tenant_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        let title = "Malware detected in an upload".to_string();
+        let message = format!(
+            "\"{}\" was flagged as {} and has been quarantined; it can no longer be downloaded.",
+            file.original_name,
+            file.scan_signature.as_deref().unwrap_or("malware")
+        );
+        let recipients = tenant_admins
+            .into_iter()
+            .map(|id| (id, Some(file.tenant_id.clone()), "/admin/storage"))
+            .chain(
+                super_admins
+                    .into_iter()
+                    .map(|id| (id, None, "/superadmin/storage")),
+            );
+        let mut notified = std::collections::HashSet::new();
+        for (user_id, tenant_id, action_url) in recipients {
+            if !notified.insert(user_id.clone()) {
+                continue;
             }
+            let _ = notification_service
+                .create_notification(
+                    user_id,
+                    tenant_id,
+                    title.clone(),
+                    message.clone(),
+                    "error".to_string(),
+                    "security".to_string(),
+                    Some(action_url.to_string()),
+                )
+                .await;
         }
-
-        Ok(config)
     }
 
-    /// Create S3 Client from config
-    async fn get_s3_client(&self, config: &StorageConfig) -> Client {
-        let region = Region::new(config.region.clone());
-
-        let creds = aws_sdk_s3::config::Credentials::new(
-            &config.access_key,
-            &config.secret_key,
-            None,
-            None,
-            "static",
-        );
-
-        let mut builder = aws_sdk_s3::Config::builder()
-            .region(region)
-            .credentials_provider(creds)
-            .behavior_version_latest();
-
-        if !config.endpoint.is_empty() {
-            builder = builder.endpoint_url(&config.endpoint);
+    fn backend_for(&self, config: &StorageConfig) -> AppResult<Box<dyn StorageBackend>> {
+        if !is_s3_provider(&config.driver) {
+            return Ok(Box::new(LocalBackend::new(self.base_storage_path.clone())));
+        }
+        if config.bucket.is_empty() {
+            return Err(AppError::Configuration(
+                "S3 storage is selected but no bucket is configured".to_string(),
+            ));
         }
+        Ok(Box::new(S3Backend::new(config)))
+    }
 
-        let s3_config = builder.build();
-        Client::from_conf(s3_config)
+    /// Backend holding an existing file.
+    async fn backend_of(
+        &self,
+        file: &crate::models::FileRecord,
+    ) -> AppResult<Box<dyn StorageBackend>> {
+        self.backend_at(
+            &file.tenant_id,
+            &file.storage_provider,
+            file.storage_bucket.as_deref(),
+            file.quota_counted,
+        )
+        .await
     }
 
-    /// Prepare upload path (mkdir) and return (Absolute Path, Safe Filename, File ID)
-    pub async fn prepare_upload_path(
+    /// Backend for an object at a recorded provider and bucket, with the
+    /// tenant's credentials for tenant-owned objects and the platform's
+    /// otherwise.
+    async fn backend_at(
         &self,
         tenant_id: &str,
-        file_name: &str,
-    ) -> AppResult<(PathBuf, String, String)> {
-        let file_id = Uuid::new_v4().to_string();
-        let ext = std::path::Path::new(file_name)
-            .extension()
-            .and_then(|s| s.to_str())
-            .unwrap_or("bin");
-
-        let now = Utc::now();
-        let year = now.format("%Y").to_string();
-        let month = now.format("%m").to_string();
+        provider: &str,
+        bucket: Option<&str>,
+        quota_counted: bool,
+    ) -> AppResult<Box<dyn StorageBackend>> {
+        if provider == "local" {
+            return Ok(Box::new(LocalBackend::new(self.base_storage_path.clone())));
+        }
+        if !is_s3_provider(provider) {
+            return Err(AppError::Internal("Unknown storage provider".to_string()));
+        }
 
-        let relative_path = std::path::Path::new(tenant_id).join(&year).join(&month);
-        let target_dir = self.base_storage_path.join(&relative_path);
+        let (global, tenant) = self.storage_settings(tenant_id).await?;
+        let mut config = match bucket {
+            Some(bucket) => StorageConfig {
+                bucket: bucket.to_string(),
+                ..if quota_counted {
+                    scope_config(&global, false)
+                } else {
+                    scope_config(&tenant, true)
+                }
+            },
+            // Stored before buckets were recorded: the tenant's current settings.
+            None => resolve_storage_config(&global, &tenant),
+        };
+        config.driver = provider.to_string();
+        self.backend_for(&config)
+    }
 
-        if !target_dir.exists() {
-            fs::create_dir_all(&target_dir)
+    /// Fresh path under the temp dir for an upload being received.
+    pub async fn temp_upload_path(&self) -> AppResult<PathBuf> {
+        let temp_dir = self.base_storage_path.join("temp");
+        if !temp_dir.exists() {
+            fs::create_dir_all(&temp_dir)
                 .await
-                .map_err(|e| AppError::Internal(format!("Failed to create directory: {}", e)))?;
+                .map_err(|e| AppError::Internal(format!("Failed to create temp dir: {}", e)))?;
         }
-
-        let safe_filename = format!("{}.{}", file_id, ext);
-        let file_path = target_dir.join(&safe_filename);
-
-        Ok((file_path, safe_filename, file_id))
+        Ok(temp_dir.join(Uuid::new_v4().to_string()))
     }
 
-    /// Register a file that has been written to disk into the database
+    /// Register a file that has been written to its backend into the database
     #[allow(clippy::too_many_arguments)]
     pub async fn register_upload(
         &self,
@@ -279,8 +548,11 @@ impl StorageService {
         content_type: &str,
         size: i64,
         storage_provider: &str,
+        storage_bucket: Option<&str>,
+        logical_path: Option<&str>,
         user_id: Option<&str>,
         bypass_quota: bool,
+        scan: &FileScan,
     ) -> AppResult<crate::models::FileRecord> {
         #[cfg(feature = "postgres")]
         tracing::info!("[Storage] Mode: POSTGRES");
@@ -288,17 +560,18 @@ impl StorageService {
         tracing::info!("[Storage] Mode: SQLITE");
 
         let now = Utc::now();
+        let quota_counted = !bypass_quota;
 
         #[cfg(feature = "postgres")]
         let query = r#"
-            INSERT INTO file_records (id, tenant_id, name, original_name, path, size, content_type, storage_provider, uploaded_by, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            INSERT INTO file_records (id, tenant_id, name, original_name, path, size, content_type, storage_provider, uploaded_by, created_at, updated_at, storage_bucket, quota_counted, scan_status, scan_signature, scanned_at, logical_path)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
         "#;
 
         #[cfg(feature = "sqlite")]
         let query = r#"
-            INSERT INTO file_records (id, tenant_id, name, original_name, path, size, content_type, storage_provider, uploaded_by, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO file_records (id, tenant_id, name, original_name, path, size, content_type, storage_provider, uploaded_by, created_at, updated_at, storage_bucket, quota_counted, scan_status, scan_signature, scanned_at, logical_path)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#;
 
         tracing::info!("[Storage] Executing INSERT for {}", file_id);
@@ -316,9 +589,15 @@ impl StorageService {
             .bind(user_id)
             .bind(now)
             .bind(now)
+            .bind(storage_bucket)
+            .bind(quota_counted)
+            .bind(scan.status)
+            .bind(&scan.signature)
+            .bind(scan.scanned_at)
+            .bind(logical_path)
             .execute(&self.pool)
             .await
-            .map_err(|e| AppError::Internal(format!("Insert failed: {}", e)))?;
+            .map_err(insert_error)?;
 
         #[cfg(feature = "sqlite")]
         sqlx::query(query)
@@ -333,13 +612,19 @@ impl StorageService {
             .bind(user_id)
             .bind(now.to_rfc3339())
             .bind(now.to_rfc3339())
+            .bind(storage_bucket)
+            .bind(quota_counted)
+            .bind(scan.status)
+            .bind(&scan.signature)
+            .bind(scan.scanned_at.map(|t| t.to_rfc3339()))
+            .bind(logical_path)
             .execute(&self.pool)
             .await
-            .map_err(|e| AppError::Internal(format!("Insert failed: {}", e)))?;
+            .map_err(insert_error)?;
 
         tracing::info!("[Storage] INSERT Success.");
 
-        if !bypass_quota {
+        if quota_counted {
             sqlx::query("UPDATE tenants SET storage_usage = storage_usage + $1 WHERE id = $2")
                 .bind(size)
                 .bind(tenant_id)
@@ -357,44 +642,146 @@ impl StorageService {
             size,
             content_type: content_type.to_string(),
             storage_provider: storage_provider.to_string(),
+            storage_bucket: storage_bucket.map(|s| s.to_string()),
+            quota_counted,
+            scan_status: scan.status.to_string(),
+            scan_signature: scan.signature.clone(),
+            scanned_at: scan.scanned_at,
+            logical_path: logical_path.map(|s| s.to_string()),
+            version: 1,
             uploaded_by: user_id.map(|s| s.to_string()),
             created_at: now,
             updated_at: now,
         })
     }
 
-    pub async fn upload(
+    /// Store a received file (moved out of the temp dir) on the tenant's
+    /// backend and register it. With a `logical_path` the tenant already has
+    /// a file at, the upload becomes that file's next version.
+    pub async fn store_file(
         &self,
         tenant_id: &str,
         file_name: &str,
         content_type: &str,
-        data: &[u8],
+        temp_path: PathBuf,
+        logical_path: Option<&str>,
         user_id: Option<&str>,
     ) -> AppResult<crate::models::FileRecord> {
+        let size = fs::metadata(&temp_path)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to read temp file: {}", e)))?
+            .len();
+
+        let prepared = async {
+            let logical_path = logical_path.map(normalize_logical_path).transpose()?;
+            let config = self.get_storage_config(tenant_id).await?;
+            let backend = self.backend_for(&config)?;
+            let scan = self.scan_upload(ScanTarget::File(&temp_path)).await?;
+            Ok::<_, AppError>((logical_path, config, backend, scan))
+        }
+        .await;
+        let (logical_path, config, backend, scan) = match prepared {
+            Ok(v) => v,
+            Err(e) => {
+                fs::remove_file(&temp_path).await.ok();
+                return Err(e);
+            }
+        };
+
         let file_id = Uuid::new_v4().to_string();
         let ext = std::path::Path::new(file_name)
             .extension()
             .and_then(|s| s.to_str())
             .unwrap_or("bin");
+        let safe_name = format!("{}.{}", file_id, ext);
+        let key = object_key(tenant_id, Utc::now(), &safe_name);
 
-        // --- Structured Storage Path: tenant_id/YYYY/MM/ ---
-        let now = Utc::now();
-        let year = now.format("%Y").to_string();
-        let month = now.format("%m").to_string();
+        let path = backend
+            .put(&key, StorageBody::TempFile(temp_path), content_type)
+            .await?;
 
-        // Construct relative path: tenant_id/2024/01/
-        let relative_path = std::path::Path::new(tenant_id).join(&year).join(&month);
-        let target_dir = self.base_storage_path.join(&relative_path);
+        let current = match logical_path.as_deref() {
+            Some(logical_path) => self.find_by_logical_path(tenant_id, logical_path).await,
+            None => Ok(None),
+        };
+        let res = match current {
+            Err(e) => Err(e),
+            Ok(Some(current)) => {
+                let next = crate::models::FileRecord {
+                    name: safe_name,
+                    original_name: file_name.to_string(),
+                    path: path.clone(),
+                    size: size as i64,
+                    content_type: content_type.to_string(),
+                    storage_provider: backend.provider().to_string(),
+                    storage_bucket: backend.bucket().map(|b| b.to_string()),
+                    quota_counted: !config.tenant_owned,
+                    scan_status: scan.status.to_string(),
+                    scan_signature: scan.signature.clone(),
+                    scanned_at: scan.scanned_at,
+                    uploaded_by: user_id.map(|s| s.to_string()),
+                    ..current.clone()
+                };
+                self.push_version(&current, next, None).await
+            }
+            Ok(None) => {
+                self.register_upload(
+                    tenant_id,
+                    &file_id,
+                    file_name,
+                    &safe_name,
+                    &path,
+                    content_type,
+                    size as i64,
+                    backend.provider(),
+                    backend.bucket(),
+                    logical_path.as_deref(),
+                    user_id,
+                    config.tenant_owned, // the tenant's own bucket is not quota-counted
+                    &scan,
+                )
+                .await
+            }
+        };
 
-        // Ensure directory exists
-        if !target_dir.exists() {
-            fs::create_dir_all(&target_dir).await.map_err(|e| {
-                AppError::Internal(format!("Failed to create directory structure: {}", e))
-            })?;
+        match &res {
+            Ok(record) => {
+                tracing::info!("[Storage] DB Registration Success");
+                if scan.is_infected() {
+                    self.notify_quarantined(record).await;
+                }
+            }
+            Err(e) => {
+                tracing::error!("[Storage] DB Registration Failed: {}", e);
+                backend.delete(&path).await.ok();
+            }
         }
+        res
+    }
 
+    pub async fn upload(
+        &self,
+        tenant_id: &str,
+        file_name: &str,
+        content_type: &str,
+        data: &[u8],
+        user_id: Option<&str>,
+    ) -> AppResult<crate::models::FileRecord> {
+        let file_id = Uuid::new_v4().to_string();
+        let ext = std::path::Path::new(file_name)
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("bin");
+
+        // --- Structured Storage Path: tenant_id/YYYY/MM/ ---
+        let now = Utc::now();
         let safe_filename = format!("{}.{}", file_id, ext);
-        let file_path = target_dir.join(&safe_filename);
+        let key = object_key(tenant_id, now, &safe_filename);
+
+        let config = self.get_storage_config(tenant_id).await?;
+        let backend = self.backend_for(&config)?;
+        let quota_counted = !config.tenant_owned;
+        let scan = self.scan_upload(ScanTarget::Bytes(data)).await?;
 
         // --- Perform DB operations in a transaction ---
         let mut tx = self
@@ -409,13 +796,21 @@ impl StorageService {
         let db_result = async {
             // Check Plan Storage Limit
             #[cfg(feature = "postgres")]
-            let limit_gb = self
-                .plan_service
-                .get_feature_limit_with_conn(tenant_id, "max_storage_gb", &mut tx)
-                .await?;
+            let limit_gb = if quota_counted {
+                self
+                    .plan_service
+                    .get_feature_limit_with_conn(tenant_id, "max_storage_gb", &mut tx)
+                    .await?
+            } else {
+                None
+            };
 
             #[cfg(feature = "sqlite")]
-            let limit_gb = self.plan_service.get_feature_limit(tenant_id, "max_storage_gb").await?;
+            let limit_gb = if quota_counted {
+                self.plan_service.get_feature_limit(tenant_id, "max_storage_gb").await?
+            } else {
+                None
+            };
 
             if let Some(max_gb) = limit_gb {
                 let max_bytes = (max_gb as u64) * 1024 * 1024 * 1024;
@@ -437,96 +832,130 @@ impl StorageService {
                 .await?;
 
                 if (current_usage as u64) + (size as u64) > max_bytes {
-                    return Err(AppError::Validation(format!(
-                        "Plan storage limit reached. Max {} GB allowed.",
+                    if !self.plan_service.soft_quota(tenant_id).await? {
+                        return Err(AppError::QuotaExceeded(format!(
+                            "Plan storage limit reached. Max {} GB allowed.",
+                            max_gb
+                        )));
+                    }
+                    tracing::warn!(
+                        "Tenant {} is over its storage quota ({} GB); soft enforcement, allowing",
+                        tenant_id,
                         max_gb
-                    )));
+                    );
                 }
             }
 
-            // Save metadata to DB
-            // 'now' is captured from outer scope (used for path generation)
+            // Write to the backend before committing, so a failed write leaves no record
+            let path = backend
+                .put(&key, StorageBody::Bytes(data.to_vec()), content_type)
+                .await?;
 
             #[cfg(feature="postgres")]
             let query = r#"
-                INSERT INTO file_records (id, tenant_id, name, original_name, path, size, content_type, uploaded_by, created_at, updated_at)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                INSERT INTO file_records (id, tenant_id, name, original_name, path, size, content_type, uploaded_by, created_at, updated_at, storage_provider, storage_bucket, quota_counted, scan_status, scan_signature, scanned_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
             "#;
 
             #[cfg(feature="sqlite")]
             let query = r#"
-                INSERT INTO file_records (id, tenant_id, name, original_name, path, size, content_type, uploaded_by, created_at, updated_at)
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                INSERT INTO file_records (id, tenant_id, name, original_name, path, size, content_type, uploaded_by, created_at, updated_at, storage_provider, storage_bucket, quota_counted, scan_status, scan_signature, scanned_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#;
 
             #[cfg(feature="postgres")]
-            sqlx::query(query)
+            let inserted = sqlx::query(query)
                 .bind(&file_id)
                 .bind(tenant_id)
                 .bind(&safe_filename)
                 .bind(file_name)
-                .bind(file_path.to_string_lossy().to_string())
+                .bind(&path)
                 .bind(size)
                 .bind(content_type)
                 .bind(user_id)
                 .bind(now)
                 .bind(now)
+                .bind(backend.provider())
+                .bind(backend.bucket())
+                .bind(quota_counted)
+                .bind(scan.status)
+                .bind(&scan.signature)
+                .bind(scan.scanned_at)
                 .execute(&mut *tx)
-                .await?;
+                .await;
 
             #[cfg(feature="sqlite")]
-            sqlx::query(query)
+            let inserted = sqlx::query(query)
                 .bind(&file_id)
                 .bind(tenant_id)
                 .bind(&safe_filename)
                 .bind(file_name)
-                .bind(file_path.to_string_lossy().to_string())
+                .bind(&path)
                 .bind(size)
                 .bind(content_type)
                 .bind(user_id)
                 .bind(now.to_rfc3339())
                 .bind(now.to_rfc3339())
+                .bind(backend.provider())
+                .bind(backend.bucket())
+                .bind(quota_counted)
+                .bind(scan.status)
+                .bind(&scan.signature)
+                .bind(scan.scanned_at.map(|t| t.to_rfc3339()))
                 .execute(&mut *tx)
-                .await?;
+                .await;
+
+            if let Err(e) = inserted {
+                backend.delete(&path).await.ok();
+                return Err(e.into());
+            }
 
             // Update tenant's storage usage
-            sqlx::query("UPDATE tenants SET storage_usage = storage_usage + $1 WHERE id = $2")
-                .bind(size)
-                .bind(tenant_id)
-                .execute(&mut *tx)
-                .await?;
+            if quota_counted {
+                sqlx::query("UPDATE tenants SET storage_usage = storage_usage + $1 WHERE id = $2")
+                    .bind(size)
+                    .bind(tenant_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
 
-            Ok(now)
+            Ok(path)
         }.await;
 
         match db_result {
-            Ok(now) => {
-                // After DB is confirmed, write file to disk
-                let mut file = fs::File::create(&file_path).await.map_err(|e| {
-                    AppError::Internal(format!("Failed to create file on disk: {}", e))
-                })?;
-
-                file.write_all(data).await.map_err(|e| {
-                    AppError::Internal(format!("Failed to write data to disk: {}", e))
-                })?;
-
-                tx.commit().await.map_err(|e| {
-                    AppError::Internal(format!("Failed to commit transaction: {}", e))
-                })?;
+            Ok(path) => {
+                if let Err(e) = tx.commit().await {
+                    backend.delete(&path).await.ok();
+                    return Err(AppError::Internal(format!(
+                        "Failed to commit transaction: {}",
+                        e
+                    )));
+                }
 
-                Ok(crate::models::FileRecord {
+                let record = crate::models::FileRecord {
                     id: file_id,
                     tenant_id: tenant_id.to_string(),
                     name: safe_filename,
                     original_name: file_name.to_string(),
-                    path: file_path.to_string_lossy().to_string(),
+                    path,
                     size,
                     content_type: content_type.to_string(),
-                    storage_provider: "local".to_string(), // Default for direct upload
+                    storage_provider: backend.provider().to_string(),
+                    storage_bucket: backend.bucket().map(|b| b.to_string()),
+                    quota_counted,
+                    scan_status: scan.status.to_string(),
+                    scan_signature: scan.signature.clone(),
+                    scanned_at: scan.scanned_at,
+                    logical_path: None,
+                    version: 1,
                     uploaded_by: user_id.map(|s| s.to_string()),
                     created_at: now,
                     updated_at: now,
-                })
+                };
+                if scan.is_infected() {
+                    self.notify_quarantined(&record).await;
+                }
+                Ok(record)
             }
             Err(e) => {
                 tx.rollback()
@@ -709,6 +1138,18 @@ impl StorageService {
                     let storage_provider: String = row
                         .try_get("storage_provider")
                         .unwrap_or("local".to_string());
+                    let storage_bucket: Option<String> =
+                        row.try_get("storage_bucket").ok().flatten();
+                    let quota_counted: bool = row.try_get("quota_counted").unwrap_or(true);
+                    let scan_status: String = row
+                        .try_get("scan_status")
+                        .unwrap_or("unscanned".to_string());
+                    let scan_signature: Option<String> =
+                        row.try_get("scan_signature").ok().flatten();
+                    let scanned_at: Option<DateTime<Utc>> =
+                        row.try_get("scanned_at").ok().flatten();
+                    let logical_path: Option<String> = row.try_get("logical_path").ok().flatten();
+                    let version: i32 = row.try_get("version").unwrap_or(1);
                     let uploaded_by: Option<String> = row.try_get("uploaded_by").ok();
                     let created_at: DateTime<Utc> = row.try_get("created_at").ok()?;
                     let updated_at: DateTime<Utc> = row.try_get("updated_at").ok()?;
@@ -722,6 +1163,13 @@ impl StorageService {
                         size,
                         content_type,
                         storage_provider,
+                        storage_bucket,
+                        quota_counted,
+                        scan_status,
+                        scan_signature,
+                        scanned_at,
+                        logical_path,
+                        version,
                         uploaded_by,
                         created_at,
                         updated_at,
@@ -783,6 +1231,231 @@ impl StorageService {
         }
     }
 
+    async fn find_by_logical_path(
+        &self,
+        tenant_id: &str,
+        logical_path: &str,
+    ) -> AppResult<Option<crate::models::FileRecord>> {
+        Ok(
+            sqlx::query_as("SELECT * FROM file_records WHERE tenant_id = $1 AND logical_path = $2")
+                .bind(tenant_id)
+                .bind(logical_path)
+                .fetch_optional(&self.pool)
+                .await?,
+        )
+    }
+
+    /// The `storage_max_versions` setting; 0 keeps every version.
+    async fn max_versions(&self) -> AppResult<i64> {
+        let value: Option<String> = sqlx::query_scalar(
+            "SELECT value FROM settings WHERE tenant_id IS NULL AND key = 'storage_max_versions'",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(value
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_MAX_VERSIONS)
+            .max(0))
+    }
+
+    /// Makes `next` (the content fields of a record) the current version of
+    /// `current`, archiving what it replaces. `next` is either a new upload,
+    /// counted against the quota here, or the restored `restoring` version,
+    /// whose size is already counted.
+    async fn push_version(
+        &self,
+        current: &crate::models::FileRecord,
+        next: crate::models::FileRecord,
+        restoring: Option<&crate::models::FileVersion>,
+    ) -> AppResult<crate::models::FileRecord> {
+        let now = Utc::now();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to begin transaction: {}", e)))?;
+        Self::apply_rls_context_tx_values(&mut tx, Some(&current.tenant_id), true).await?;
+        let changed = || AppError::Conflict("File was changed meanwhile, try again".to_string());
+
+        let archived = sqlx::query(
+            r#"
+            INSERT INTO file_versions (id, file_id, tenant_id, version, name, original_name, path, size, content_type, storage_provider, storage_bucket, quota_counted, scan_status, scan_signature, scanned_at, uploaded_by, created_at, replaced_at)
+            SELECT $1, id, tenant_id, version, name, original_name, path, size, content_type, storage_provider, storage_bucket, quota_counted, scan_status, scan_signature, scanned_at, uploaded_by, updated_at, $2
+            FROM file_records WHERE id = $3 AND version = $4
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(now)
+        .bind(&current.id)
+        .bind(current.version)
+        .execute(&mut *tx)
+        .await;
+        match archived {
+            Ok(r) if r.rows_affected() == 1 => {}
+            Ok(_) => return Err(changed()),
+            Err(sqlx::Error::Database(db)) if db.is_unique_violation() => return Err(changed()),
+            Err(e) => return Err(e.into()),
+        }
+
+        let updated = sqlx::query(
+            "UPDATE file_records SET name = $1, original_name = $2, path = $3, size = $4, content_type = $5, storage_provider = $6, storage_bucket = $7, quota_counted = $8, scan_status = $9, scan_signature = $10, scanned_at = $11, uploaded_by = $12, updated_at = $13, version = version + 1 WHERE id = $14 AND version = $15",
+        )
+        .bind(&next.name)
+        .bind(&next.original_name)
+        .bind(&next.path)
+        .bind(next.size)
+        .bind(&next.content_type)
+        .bind(&next.storage_provider)
+        .bind(&next.storage_bucket)
+        .bind(next.quota_counted)
+        .bind(&next.scan_status)
+        .bind(&next.scan_signature)
+        .bind(next.scanned_at)
+        .bind(&next.uploaded_by)
+        .bind(now)
+        .bind(&current.id)
+        .bind(current.version)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() != 1 {
+            return Err(changed());
+        }
+
+        match restoring {
+            // The restored object is the current one now.
+            Some(version) => {
+                let deleted = sqlx::query("DELETE FROM file_versions WHERE id = $1")
+                    .bind(&version.id)
+                    .execute(&mut *tx)
+                    .await?;
+                if deleted.rows_affected() != 1 {
+                    return Err(changed());
+                }
+            }
+            None if next.quota_counted => {
+                sqlx::query("UPDATE tenants SET storage_usage = storage_usage + $1 WHERE id = $2")
+                    .bind(next.size)
+                    .bind(&current.tenant_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            None => {}
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to commit transaction: {}", e)))?;
+
+        if let Err(e) = self.prune_versions(&current.id).await {
+            tracing::warn!(
+                "[Storage] Failed to prune versions of {}: {}",
+                current.id,
+                e
+            );
+        }
+
+        Ok(crate::models::FileRecord {
+            version: current.version + 1,
+            updated_at: now,
+            ..next
+        })
+    }
+
+    /// Drops the oldest versions of a file beyond `storage_max_versions`.
+    async fn prune_versions(&self, file_id: &str) -> AppResult<()> {
+        let keep = self.max_versions().await?;
+        if keep == 0 {
+            return Ok(());
+        }
+        let versions = self.list_versions(file_id).await?;
+        for version in versions.into_iter().skip(keep as usize) {
+            let deleted = sqlx::query("DELETE FROM file_versions WHERE id = $1")
+                .bind(&version.id)
+                .execute(&self.pool)
+                .await?;
+            if deleted.rows_affected() != 1 {
+                continue;
+            }
+            if version.quota_counted {
+                sqlx::query("UPDATE tenants SET storage_usage = storage_usage - $1 WHERE id = $2")
+                    .bind(version.size)
+                    .bind(&version.tenant_id)
+                    .execute(&self.pool)
+                    .await?;
+            }
+            self.delete_version_objects(std::slice::from_ref(&version))
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Best-effort removal of the objects of archived versions.
+    async fn delete_version_objects(&self, versions: &[crate::models::FileVersion]) {
+        for version in versions {
+            let backend = self
+                .backend_at(
+                    &version.tenant_id,
+                    &version.storage_provider,
+                    version.storage_bucket.as_deref(),
+                    version.quota_counted,
+                )
+                .await;
+            if let Ok(backend) = backend {
+                backend.delete(&version.path).await.ok();
+            }
+        }
+    }
+
+    /// Earlier versions of a file, newest first.
+    pub async fn list_versions(&self, file_id: &str) -> AppResult<Vec<crate::models::FileVersion>> {
+        Ok(
+            sqlx::query_as("SELECT * FROM file_versions WHERE file_id = $1 ORDER BY version DESC")
+                .bind(file_id)
+                .fetch_all(&self.pool)
+                .await?,
+        )
+    }
+
+    /// Makes an earlier version current again. The restored content becomes
+    /// a new version (numbered after the current one), which is archived.
+    pub async fn restore_version(
+        &self,
+        file_id: &str,
+        version_id: &str,
+        user_id: Option<&str>,
+    ) -> AppResult<crate::models::FileRecord> {
+        let current = self.get_file(file_id).await?;
+        let version: crate::models::FileVersion =
+            sqlx::query_as("SELECT * FROM file_versions WHERE id = $1 AND file_id = $2")
+                .bind(version_id)
+                .bind(file_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound("File version not found".to_string()))?;
+        if version.scan_status == "infected" {
+            return Err(AppError::Forbidden(
+                "File version is quarantined: malware detected".to_string(),
+            ));
+        }
+
+        let next = crate::models::FileRecord {
+            name: version.name.clone(),
+            original_name: version.original_name.clone(),
+            path: version.path.clone(),
+            size: version.size,
+            content_type: version.content_type.clone(),
+            storage_provider: version.storage_provider.clone(),
+            storage_bucket: version.storage_bucket.clone(),
+            quota_counted: version.quota_counted,
+            scan_status: version.scan_status.clone(),
+            scan_signature: version.scan_signature.clone(),
+            scanned_at: version.scanned_at,
+            uploaded_by: user_id.map(|s| s.to_string()),
+            ..current.clone()
+        };
+        self.push_version(&current, next, Some(&version)).await
+    }
+
     /// Get file record by ID
     pub async fn get_file(&self, file_id: &str) -> AppResult<crate::models::FileRecord> {
         #[cfg(feature = "postgres")]
@@ -833,23 +1506,27 @@ impl StorageService {
 
         if let Some(file) = record {
             // 1. Delete from Storage Provider
-            if file.storage_provider == "local" {
-                let path = PathBuf::from(&file.path);
-                if path.exists() {
-                    fs::remove_file(path).await.ok();
-                }
-            } else if file.storage_provider == "s3" || file.storage_provider == "r2" {
-                if let Ok(config) = self.get_storage_config(&file.tenant_id).await {
-                    let client = self.get_s3_client(&config).await;
-                    let _ = client
-                        .delete_object()
-                        .bucket(&config.bucket)
-                        .key(&file.path)
-                        .send()
-                        .await;
-                }
+            if let Ok(backend) = self.backend_of(&file).await {
+                backend.delete(&file.path).await.ok();
             }
 
+            let versions: Vec<crate::models::FileVersion> =
+                sqlx::query_as("SELECT * FROM file_versions WHERE file_id = $1")
+                    .bind(file_id)
+                    .fetch_all(&mut *tx)
+                    .await?;
+            self.delete_version_objects(&versions).await;
+            sqlx::query("DELETE FROM file_versions WHERE file_id = $1")
+                .bind(file_id)
+                .execute(&mut *tx)
+                .await?;
+            let counted_size = versions
+                .iter()
+                .filter(|v| v.quota_counted)
+                .map(|v| v.size)
+                .sum::<i64>()
+                + if file.quota_counted { file.size } else { 0 };
+
             // 2. Remove from DB
             #[cfg(feature = "postgres")]
             sqlx::query("DELETE FROM file_records WHERE id = $1")
@@ -865,10 +1542,10 @@ impl StorageService {
                 .await
                 .map_err(|e| AppError::Internal(e.to_string()))?;
 
-            // 3. Update usage (Only if local)
-            if file.storage_provider == "local" {
+            // 3. Update usage (only files counted against the quota)
+            if counted_size != 0 {
                 sqlx::query("UPDATE tenants SET storage_usage = storage_usage - $1 WHERE id = $2")
-                    .bind(file.size)
+                    .bind(counted_size)
                     .bind(&file.tenant_id)
                     .execute(&mut *tx)
                     .await
@@ -917,23 +1594,27 @@ impl StorageService {
                 .map_err(|e| AppError::Internal(e.to_string()))?;
 
         if let Some(file) = record {
-            if file.storage_provider == "local" {
-                let path = PathBuf::from(&file.path);
-                if path.exists() {
-                    fs::remove_file(path).await.ok();
-                }
-            } else if file.storage_provider == "s3" || file.storage_provider == "r2" {
-                if let Ok(config) = self.get_storage_config(&file.tenant_id).await {
-                    let client = self.get_s3_client(&config).await;
-                    let _ = client
-                        .delete_object()
-                        .bucket(&config.bucket)
-                        .key(&file.path)
-                        .send()
-                        .await;
-                }
+            if let Ok(backend) = self.backend_of(&file).await {
+                backend.delete(&file.path).await.ok();
             }
 
+            let versions: Vec<crate::models::FileVersion> =
+                sqlx::query_as("SELECT * FROM file_versions WHERE file_id = $1")
+                    .bind(file_id)
+                    .fetch_all(&mut *tx)
+                    .await?;
+            self.delete_version_objects(&versions).await;
+            sqlx::query("DELETE FROM file_versions WHERE file_id = $1")
+                .bind(file_id)
+                .execute(&mut *tx)
+                .await?;
+            let counted_size = versions
+                .iter()
+                .filter(|v| v.quota_counted)
+                .map(|v| v.size)
+                .sum::<i64>()
+                + if file.quota_counted { file.size } else { 0 };
+
             #[cfg(feature = "postgres")]
             sqlx::query("DELETE FROM file_records WHERE id = $1")
                 .bind(file_id)
@@ -948,9 +1629,9 @@ impl StorageService {
                 .await
                 .map_err(|e| AppError::Internal(e.to_string()))?;
 
-            if file.storage_provider == "local" {
+            if counted_size != 0 {
                 sqlx::query("UPDATE tenants SET storage_usage = storage_usage - $1 WHERE id = $2")
-                    .bind(file.size)
+                    .bind(counted_size)
                     .bind(tenant_id)
                     .execute(&mut *tx)
                     .await
@@ -1034,6 +1715,7 @@ impl StorageService {
         upload_id: &str,
         file_name: &str,
         content_type: &str,
+        logical_path: Option<&str>,
         user_id: Option<&str>,
     ) -> AppResult<crate::models::FileRecord> {
         tracing::info!("[Storage] Completing chunk session: {}", upload_id);
@@ -1044,94 +1726,302 @@ impl StorageService {
             return Err(AppError::NotFound("Upload session not found".to_string()));
         }
 
-        let metadata = fs::metadata(&temp_path)
-            .await
-            .map_err(|e| AppError::Internal(format!("Failed to read temp file: {}", e)))?;
-        let size = metadata.len();
+        self.store_file(
+            tenant_id,
+            file_name,
+            content_type,
+            temp_path,
+            logical_path,
+            user_id,
+        )
+        .await
+    }
 
-        // 1. Get Configuration
+    /// Moves up to `limit` of a tenant's files that are not on its current
+    /// storage backend onto it. Each file is copied, its record repointed and
+    /// only then the old copy removed, so a failure leaves it readable where
+    /// it was.
+    pub async fn migrate_files(
+        &self,
+        tenant_id: &str,
+        limit: i64,
+    ) -> AppResult<StorageMigrationReport> {
         let config = self.get_storage_config(tenant_id).await?;
-        let is_custom_storage = config.driver == "s3" || config.driver == "r2";
-
-        let final_path: String;
-        let safe_name: String;
-        let file_id: String;
-
-        // 2. Process File based on Driver
-        if is_custom_storage {
-            // --- S3 / R2 Upload ---
-            let client = self.get_s3_client(&config).await;
-
-            let file_uuid = Uuid::new_v4().to_string();
-            let ext = std::path::Path::new(file_name)
-                .extension()
-                .and_then(|s| s.to_str())
-                .unwrap_or("bin");
-            let now = Utc::now();
-            // Structure: tenant_id/YYYY/MM/uuid.ext
-            let key = format!(
-                "{}/{}/{}/{}.{}",
-                tenant_id,
-                now.format("%Y"),
-                now.format("%m"),
-                file_uuid,
-                ext
-            );
-
-            safe_name = format!("{}.{}", file_uuid, ext);
-            file_id = file_uuid;
-            final_path = key.clone();
+        let target = self.backend_for(&config)?;
+        let quota_counted = !config.tenant_owned;
 
-            let body = ByteStream::from_path(&temp_path).await.map_err(|e| {
-                AppError::Internal(format!("Failed to read file for S3 upload: {}", e))
-            })?;
+        let files: Vec<crate::models::FileRecord> = match target.bucket() {
+            None => sqlx::query_as(
+                "SELECT * FROM file_records WHERE tenant_id = $1 AND storage_provider <> 'local' ORDER BY created_at LIMIT $2",
+            )
+            .bind(tenant_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?,
+            Some(bucket) => sqlx::query_as(
+                "SELECT * FROM file_records WHERE tenant_id = $1 AND (storage_provider NOT IN ('s3', 'r2') OR COALESCE(storage_bucket, '') <> $2) ORDER BY created_at LIMIT $3",
+            )
+            .bind(tenant_id)
+            .bind(bucket)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?,
+        };
 
-            client
-                .put_object()
-                .bucket(&config.bucket)
-                .key(&key)
-                .body(body)
-                .content_type(content_type)
-                .send()
+        let mut report = StorageMigrationReport::default();
+        for file in files {
+            match self
+                .migrate_file(&file, target.as_ref(), quota_counted)
                 .await
-                .map_err(|e| AppError::Internal(format!("S3 Upload Failed: {}", e)))?;
+            {
+                Ok(()) => report.migrated += 1,
+                Err(e) => {
+                    report.failed += 1;
+                    tracing::warn!("[Storage] Failed to migrate file {}: {}", file.id, e);
+                }
+            }
+        }
+        Ok(report)
+    }
 
-            fs::remove_file(&temp_path).await.ok();
+    async fn migrate_file(
+        &self,
+        file: &crate::models::FileRecord,
+        target: &dyn StorageBackend,
+        quota_counted: bool,
+    ) -> AppResult<()> {
+        let source = self.backend_of(file).await?;
+
+        // Legacy S3 records that already live in the target bucket only
+        // need their location recorded.
+        let moved = source.provider() != target.provider() || source.bucket() != target.bucket();
+        let new_path = if !moved {
+            file.path.clone()
         } else {
-            // --- Local Storage ---
-            let (dest_path, s_name, f_id) = self.prepare_upload_path(tenant_id, file_name).await?;
+            let body = match source.get(&file.path).await? {
+                StorageContent::Local(path) => StorageBody::CopyOf(path),
+                StorageContent::S3(mut stream) => {
+                    let temp_path = self.temp_upload_path().await?;
+                    let mut temp = fs::File::create(&temp_path).await.map_err(|e| {
+                        AppError::Internal(format!("Failed to create temp file: {}", e))
+                    })?;
+                    let copied: AppResult<()> = async {
+                        while let Some(chunk) = stream.try_next().await.map_err(|e| {
+                            AppError::Internal(format!("Failed to read S3 object: {}", e))
+                        })? {
+                            temp.write_all(&chunk).await.map_err(|e| {
+                                AppError::Internal(format!("Failed to write temp file: {}", e))
+                            })?;
+                        }
+                        temp.flush()
+                            .await
+                            .map_err(|e| AppError::Internal(e.to_string()))
+                    }
+                    .await;
+                    if let Err(e) = copied {
+                        fs::remove_file(&temp_path).await.ok();
+                        return Err(e);
+                    }
+                    StorageBody::TempFile(temp_path)
+                }
+            };
+            let key = object_key(&file.tenant_id, file.created_at, &file.name);
+            target.put(&key, body, &file.content_type).await?
+        };
 
-            fs::rename(&temp_path, &dest_path)
-                .await
-                .map_err(|e| AppError::Internal(format!("Failed to move final file: {}", e)))?;
+        let updated = sqlx::query(
+            "UPDATE file_records SET path = $1, storage_provider = $2, storage_bucket = $3, quota_counted = $4 WHERE id = $5 AND path = $6",
+        )
+        .bind(&new_path)
+        .bind(target.provider())
+        .bind(target.bucket())
+        .bind(quota_counted)
+        .bind(&file.id)
+        .bind(&file.path)
+        .execute(&self.pool)
+        .await;
+
+        match updated {
+            Ok(r) if r.rows_affected() == 1 => {}
+            other => {
+                // Deleted or moved meanwhile; drop the copy we just made.
+                if moved {
+                    target.delete(&new_path).await.ok();
+                }
+                return match other {
+                    Err(e) => Err(e.into()),
+                    Ok(_) => Err(AppError::Conflict(
+                        "File changed during migration".to_string(),
+                    )),
+                };
+            }
+        }
 
-            final_path = dest_path.to_string_lossy().to_string();
-            safe_name = s_name;
-            file_id = f_id;
+        if file.quota_counted != quota_counted {
+            let delta = if quota_counted { file.size } else { -file.size };
+            sqlx::query("UPDATE tenants SET storage_usage = storage_usage + $1 WHERE id = $2")
+                .bind(delta)
+                .bind(&file.tenant_id)
+                .execute(&self.pool)
+                .await?;
         }
 
-        // 3. Register in DB
-        let res = self
-            .register_upload(
-                tenant_id,
-                &file_id,
-                file_name,
-                &safe_name,
-                &final_path,
-                content_type,
-                size as i64,
-                &config.driver,
-                user_id,
-                is_custom_storage, // bypass quota if custom
-            )
-            .await;
+        if moved {
+            source.delete(&file.path).await.ok();
+        }
+        Ok(())
+    }
 
-        if let Err(ref e) = res {
-            tracing::error!("[Storage] DB Registration Failed: {}", e);
-        } else {
-            tracing::info!("[Storage] DB Registration Success");
+    /// Background job moving files onto their tenant's current backend, a
+    /// batch per tenant every few minutes.
+    pub fn start_migration_worker(&self) {
+        let this = self.clone();
+        // Two instances copying the same file would delete each other's copy.
+        let leader = LeaderLock::new(self.pool.clone(), "storage_migration");
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(tokio::time::Duration::from_secs(MIGRATION_INTERVAL_SECS));
+            // Skip the immediate first tick; there is no rush at boot.
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if leader.acquire().await {
+                    this.migrate_all().await;
+                }
+            }
+        });
+    }
+
+    async fn migrate_all(&self) {
+        let tenant_ids: Vec<String> =
+            match sqlx::query_scalar("SELECT DISTINCT tenant_id FROM file_records")
+                .fetch_all(&self.pool)
+                .await
+            {
+                Ok(ids) => ids,
+                Err(e) => {
+                    tracing::warn!("Storage migration failed to list tenants: {}", e);
+                    return;
+                }
+            };
+
+        for tenant_id in tenant_ids {
+            match self.migrate_files(&tenant_id, MIGRATION_BATCH).await {
+                Ok(r) if r.migrated == 0 && r.failed == 0 => {}
+                Ok(r) => tracing::info!(
+                    "Migrated {} file(s) of tenant {} to its storage backend ({} failed)",
+                    r.migrated,
+                    tenant_id,
+                    r.failed
+                ),
+                Err(e) => {
+                    tracing::warn!("Storage migration for tenant {} failed: {}", tenant_id, e)
+                }
+            }
         }
+    }
+}
 
-        res
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn settings(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn tenant_buckets_and_prefixes() {
+        let global = settings(&[
+            ("storage_driver", "s3"),
+            ("storage_s3_bucket", "platform"),
+            ("storage_s3_access_key", "platform-key"),
+        ]);
+
+        // Platform S3 with the tenant's own bucket and prefix.
+        let config = resolve_storage_config(
+            &global,
+            &settings(&[
+                ("storage_s3_bucket", "acme"),
+                ("storage_s3_prefix", "files"),
+            ]),
+        );
+        assert_eq!(config.bucket, "acme");
+        assert_eq!(config.prefix, "files");
+        assert_eq!(config.access_key, "platform-key");
+        assert!(!config.tenant_owned);
+
+        // The tenant's own S3 account.
+        let config = resolve_storage_config(
+            &global,
+            &settings(&[
+                ("storage_driver", "s3"),
+                ("storage_s3_bucket", "acme-own"),
+                ("storage_s3_endpoint", "http://minio:9000"),
+                ("storage_s3_path_style", "true"),
+            ]),
+        );
+        assert_eq!(config.bucket, "acme-own");
+        assert!(config.force_path_style);
+        assert!(config.tenant_owned);
+
+        // A tenant bucket means nothing on local disk.
+        let config = resolve_storage_config(
+            &settings(&[("storage_driver", "local")]),
+            &settings(&[("storage_s3_bucket", "acme")]),
+        );
+        assert_eq!(config.driver, "local");
+        assert!(!config.tenant_owned);
+    }
+
+    #[test]
+    fn object_keys_are_dated() {
+        let at = Utc.with_ymd_and_hms(2026, 3, 9, 12, 0, 0).unwrap();
+        assert_eq!(object_key("t1", at, "f.pdf"), "t1/2026/03/f.pdf");
+    }
+
+    #[test]
+    fn signed_links_verify_until_they_expire() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+        let expires = now.timestamp() + 3600;
+        let signature = download_signature("secret", "file-1", expires);
+
+        assert!(verify_download_signature("secret", "file-1", expires, &signature, now).is_ok());
+        assert!(verify_download_signature(
+            "secret",
+            "file-1",
+            expires,
+            &signature.to_uppercase(),
+            now
+        )
+        .is_ok());
+        // Another file, a stretched expiry or another secret.
+        assert!(verify_download_signature("secret", "file-2", expires, &signature, now).is_err());
+        assert!(
+            verify_download_signature("secret", "file-1", expires + 60, &signature, now).is_err()
+        );
+        assert!(verify_download_signature("other", "file-1", expires, &signature, now).is_err());
+        // Past the expiry.
+        let later = now + chrono::Duration::hours(2);
+        assert!(verify_download_signature("secret", "file-1", expires, &signature, later).is_err());
+    }
+
+    #[test]
+    fn logical_paths_are_normalized() {
+        assert_eq!(
+            normalize_logical_path(" /contracts//acme.pdf ").unwrap(),
+            "contracts/acme.pdf"
+        );
+        assert_eq!(
+            normalize_logical_path("branding\\logo.png").unwrap(),
+            "branding/logo.png"
+        );
+        assert!(normalize_logical_path("  / ").is_err());
+        assert!(normalize_logical_path("contracts/../secret.pdf").is_err());
+        assert!(normalize_logical_path(&"a".repeat(MAX_LOGICAL_PATH_LEN + 1)).is_err());
     }
 }
@@ -0,0 +1,540 @@
+//! Support ticket escalation, auto-close and reopen rules.
+//!
+//! Escalation rules bump the priority of, or reassign, tickets that have had
+//! no public message for a while; each rule fires once per idle period.
+//! Pending tickets close themselves after `support_auto_close_days` of
+//! silence, and a customer reply within `support_reopen_window_days` of
+//! closing reopens the ticket instead of being refused.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{SupportEscalationRule, SupportTicket, UpsertSupportEscalationRuleDto};
+use crate::services::leader_lock::LeaderLock;
+#[cfg(feature = "postgres")]
+use crate::services::support_inbound_service::ticket_ref;
+use crate::services::{NotificationService, SettingsService, SupportRoutingService};
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+const SCHEDULER_INTERVAL_SECS: u64 = 300;
+const WATCHED_STATUSES: &[&str] = &["open", "pending"];
+const MAX_IDLE_MINUTES: i32 = 60 * 24 * 90;
+/// Tickets handled per rule per run; the rest are picked up next run.
+#[cfg(feature = "postgres")]
+const BATCH_LIMIT: i64 = 200;
+
+/// Next priority up; `urgent` stays `urgent`.
+pub fn bump_priority(priority: &str) -> &'static str {
+    match priority {
+        "low" => "normal",
+        "normal" => "high",
+        _ => "urgent",
+    }
+}
+
+/// Status a ticket moves to when someone posts a public reply, or `None` to
+/// leave it. Customer replies wake pending tickets and reopen tickets closed
+/// within the reopen window; any other reply to a closed ticket is refused.
+pub fn status_after_reply(
+    ticket: &SupportTicket,
+    by_customer: bool,
+    reopen_window_days: i64,
+    now: DateTime<Utc>,
+) -> AppResult<Option<&'static str>> {
+    match ticket.status.as_str() {
+        "closed" => {
+            let in_window = reopen_window_days > 0
+                && ticket
+                    .closed_at
+                    .is_some_and(|at| now - at <= Duration::days(reopen_window_days));
+            if by_customer && in_window {
+                Ok(Some("open"))
+            } else {
+                Err(AppError::Validation("Ticket is closed".to_string()))
+            }
+        }
+        "pending" if by_customer => Ok(Some("open")),
+        _ => Ok(None),
+    }
+}
+
+#[derive(Clone)]
+pub struct SupportEscalationService {
+    pool: DbPool,
+    settings_service: SettingsService,
+    notification_service: NotificationService,
+    routing: SupportRoutingService,
+}
+
+impl SupportEscalationService {
+    pub fn new(
+        pool: DbPool,
+        settings_service: SettingsService,
+        notification_service: NotificationService,
+    ) -> Self {
+        Self {
+            routing: SupportRoutingService::new(pool.clone()),
+            pool,
+            settings_service,
+            notification_service,
+        }
+    }
+
+    async fn setting_days(&self, tenant_id: &str, key: &str, default: i64) -> i64 {
+        self.settings_service
+            .get_value_fallback(Some(tenant_id), key)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .unwrap_or(default)
+            .clamp(0, 365)
+    }
+
+    /// Days after closing during which a customer reply reopens a ticket (0 = never).
+    pub async fn reopen_window_days(&self, tenant_id: &str) -> i64 {
+        self.setting_days(tenant_id, "support_reopen_window_days", 7)
+            .await
+    }
+
+    pub async fn list_rules(&self, tenant_id: &str) -> AppResult<Vec<SupportEscalationRule>> {
+        let rows: Vec<SupportEscalationRule> = sqlx::query_as(
+            "SELECT * FROM support_escalation_rules WHERE tenant_id = $1 ORDER BY idle_minutes, lower(name)",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn get_rule(&self, tenant_id: &str, id: &str) -> AppResult<SupportEscalationRule> {
+        let row: Option<SupportEscalationRule> = sqlx::query_as(
+            "SELECT * FROM support_escalation_rules WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.ok_or_else(|| AppError::NotFound("Escalation rule not found".to_string()))
+    }
+
+    pub async fn create_rule(
+        &self,
+        tenant_id: &str,
+        dto: UpsertSupportEscalationRuleDto,
+    ) -> AppResult<SupportEscalationRule> {
+        let id = Uuid::new_v4().to_string();
+        let r = self.validate(tenant_id, &id, dto).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO support_escalation_rules
+                (id, tenant_id, name, is_active, idle_minutes, statuses, queue_id,
+                 bump_priority, reassign_to, created_at, updated_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$10)
+        "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&r.name)
+        .bind(r.is_active)
+        .bind(r.idle_minutes)
+        .bind(&r.statuses)
+        .bind(&r.queue_id)
+        .bind(r.bump_priority)
+        .bind(&r.reassign_to)
+        .bind(r.updated_at)
+        .execute(&self.pool)
+        .await?;
+        self.get_rule(tenant_id, &id).await
+    }
+
+    pub async fn update_rule(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        dto: UpsertSupportEscalationRuleDto,
+    ) -> AppResult<SupportEscalationRule> {
+        self.get_rule(tenant_id, id).await?;
+        let r = self.validate(tenant_id, id, dto).await?;
+        sqlx::query(
+            r#"
+            UPDATE support_escalation_rules
+            SET name = $1, is_active = $2, idle_minutes = $3, statuses = $4, queue_id = $5,
+                bump_priority = $6, reassign_to = $7, updated_at = $8
+            WHERE tenant_id = $9 AND id = $10
+        "#,
+        )
+        .bind(&r.name)
+        .bind(r.is_active)
+        .bind(r.idle_minutes)
+        .bind(&r.statuses)
+        .bind(&r.queue_id)
+        .bind(r.bump_priority)
+        .bind(&r.reassign_to)
+        .bind(r.updated_at)
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        self.get_rule(tenant_id, id).await
+    }
+
+    pub async fn delete_rule(&self, tenant_id: &str, id: &str) -> AppResult<()> {
+        let res =
+            sqlx::query("DELETE FROM support_escalation_rules WHERE tenant_id = $1 AND id = $2")
+                .bind(tenant_id)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound("Escalation rule not found".to_string()));
+        }
+        Ok(())
+    }
+
+    async fn validate(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        dto: UpsertSupportEscalationRuleDto,
+    ) -> AppResult<SupportEscalationRule> {
+        let name = dto.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > 100 {
+            return Err(AppError::Validation(
+                "Rule name is required (max 100 characters)".to_string(),
+            ));
+        }
+        if !(5..=MAX_IDLE_MINUTES).contains(&dto.idle_minutes) {
+            return Err(AppError::Validation(format!(
+                "Idle time must be between 5 and {MAX_IDLE_MINUTES} minutes"
+            )));
+        }
+
+        let mut statuses: Vec<String> = Vec::new();
+        for s in dto.statuses.unwrap_or_else(|| vec!["open".to_string()]) {
+            let s = s.trim().to_lowercase();
+            if !WATCHED_STATUSES.contains(&s.as_str()) {
+                return Err(AppError::Validation(format!(
+                    "Invalid status '{s}' (expected one of: {})",
+                    WATCHED_STATUSES.join(", ")
+                )));
+            }
+            if !statuses.contains(&s) {
+                statuses.push(s);
+            }
+        }
+        if statuses.is_empty() {
+            return Err(AppError::Validation(
+                "Pick at least one ticket status".to_string(),
+            ));
+        }
+
+        let queue_id = dto
+            .queue_id
+            .map(|q| q.trim().to_string())
+            .filter(|q| !q.is_empty());
+        if let Some(q) = queue_id.as_deref() {
+            self.routing.ensure_queue(tenant_id, q).await?;
+        }
+        let reassign_to = dto
+            .reassign_to
+            .map(|u| u.trim().to_string())
+            .filter(|u| !u.is_empty());
+        if let Some(u) = reassign_to.as_deref() {
+            self.routing.ensure_agent(tenant_id, u).await?;
+        }
+
+        let bump_priority = dto.bump_priority.unwrap_or(false);
+        if !bump_priority && reassign_to.is_none() {
+            return Err(AppError::Validation(
+                "A rule must bump the priority, reassign the ticket, or both".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        Ok(SupportEscalationRule {
+            id: id.to_string(),
+            tenant_id: tenant_id.to_string(),
+            name,
+            is_active: dto.is_active.unwrap_or(true),
+            idle_minutes: dto.idle_minutes,
+            statuses,
+            queue_id,
+            bump_priority,
+            reassign_to,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    pub fn start_scheduler(&self) {
+        let svc = self.clone();
+        let leader = LeaderLock::new(self.pool.clone(), "support_escalation");
+        tokio::spawn(async move {
+            tracing::info!("Support escalation scheduler started.");
+            loop {
+                if leader.acquire().await {
+                    if let Err(err) = svc.run_for_all_tenants().await {
+                        tracing::warn!("support escalation scheduler failed: {}", err);
+                    }
+                }
+                tokio::time::sleep(std::time::Duration::from_secs(SCHEDULER_INTERVAL_SECS)).await;
+            }
+        });
+    }
+
+    pub async fn run_for_all_tenants(&self) -> AppResult<()> {
+        #[cfg(not(feature = "postgres"))]
+        {
+            let _ = (&self.pool, &self.notification_service);
+            Ok(())
+        }
+
+        #[cfg(feature = "postgres")]
+        {
+            let tenant_ids: Vec<String> =
+                sqlx::query_scalar("SELECT id FROM tenants WHERE is_active = true")
+                    .fetch_all(&self.pool)
+                    .await?;
+            for tenant_id in tenant_ids {
+                if let Err(e) = self.auto_close(&tenant_id).await {
+                    tracing::warn!("Support auto-close failed (tenant={}): {}", tenant_id, e);
+                }
+                if let Err(e) = self.escalate(&tenant_id).await {
+                    tracing::warn!("Support escalation failed (tenant={}): {}", tenant_id, e);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Close pending tickets that have been silent for `support_auto_close_days`.
+    #[cfg(feature = "postgres")]
+    async fn auto_close(&self, tenant_id: &str) -> AppResult<u64> {
+        let days = self
+            .setting_days(tenant_id, "support_auto_close_days", 0)
+            .await;
+        if days == 0 {
+            return Ok(0);
+        }
+        let now = Utc::now();
+        let closed: Vec<SupportTicket> = sqlx::query_as(
+            r#"
+            UPDATE support_tickets t
+            SET status = 'closed', closed_at = $2, updated_at = $2
+            WHERE t.tenant_id = $1
+              AND t.status = 'pending'
+              AND COALESCE(
+                    (SELECT MAX(m.created_at) FROM support_ticket_messages m
+                     WHERE m.ticket_id = t.id AND NOT m.is_internal),
+                    t.created_at
+                  ) < $3
+            RETURNING t.*
+        "#,
+        )
+        .bind(tenant_id)
+        .bind(now)
+        .bind(now - Duration::days(days))
+        .fetch_all(&self.pool)
+        .await?;
+
+        for ticket in &closed {
+            let Some(owner) = ticket.created_by.clone() else {
+                continue;
+            };
+            let _ = self
+                .notification_service
+                .create_notification(
+                    owner,
+                    Some(tenant_id.to_string()),
+                    format!("Ticket closed [#{}]", ticket_ref(&ticket.id)),
+                    format!(
+                        "\"{}\" was closed after {} days without a reply. Reply to reopen it.",
+                        ticket.subject, days
+                    ),
+                    "info".to_string(),
+                    "support".to_string(),
+                    Some(format!("/support/{}", ticket.id)),
+                )
+                .await;
+        }
+        Ok(closed.len() as u64)
+    }
+
+    #[cfg(feature = "postgres")]
+    async fn escalate(&self, tenant_id: &str) -> AppResult<u64> {
+        let rules: Vec<SupportEscalationRule> = sqlx::query_as(
+            "SELECT * FROM support_escalation_rules WHERE tenant_id = $1 AND is_active ORDER BY idle_minutes",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut escalated = 0_u64;
+        for rule in &rules {
+            let now = Utc::now();
+            // Idle since the last public message; an escalation row newer than
+            // that means this rule already fired for the current idle period.
+            let tickets: Vec<SupportTicket> = sqlx::query_as(
+                r#"
+                SELECT t.*
+                FROM support_tickets t
+                CROSS JOIN LATERAL (
+                    SELECT COALESCE(
+                        (SELECT MAX(m.created_at) FROM support_ticket_messages m
+                         WHERE m.ticket_id = t.id AND NOT m.is_internal),
+                        t.created_at
+                    ) AS at
+                ) last_activity
+                WHERE t.tenant_id = $1
+                  AND t.status = ANY($2)
+                  AND ($3::text IS NULL OR t.queue_id = $3)
+                  AND last_activity.at < $4
+                  AND NOT EXISTS (
+                      SELECT 1 FROM support_ticket_escalations e
+                      WHERE e.ticket_id = t.id AND e.rule_id = $5
+                        AND e.escalated_at >= last_activity.at
+                  )
+                ORDER BY last_activity.at ASC
+                LIMIT $6
+            "#,
+            )
+            .bind(tenant_id)
+            .bind(&rule.statuses)
+            .bind(&rule.queue_id)
+            .bind(now - Duration::minutes(rule.idle_minutes as i64))
+            .bind(&rule.id)
+            .bind(BATCH_LIMIT)
+            .fetch_all(&self.pool)
+            .await?;
+
+            for ticket in tickets {
+                self.apply_rule(tenant_id, rule, &ticket, now).await?;
+                escalated += 1;
+            }
+        }
+        Ok(escalated)
+    }
+
+    #[cfg(feature = "postgres")]
+    async fn apply_rule(
+        &self,
+        tenant_id: &str,
+        rule: &SupportEscalationRule,
+        ticket: &SupportTicket,
+        now: DateTime<Utc>,
+    ) -> AppResult<()> {
+        let priority = if rule.bump_priority {
+            bump_priority(&ticket.priority)
+        } else {
+            ticket.priority.as_str()
+        };
+        let assignee = rule.reassign_to.clone().or(ticket.assigned_to.clone());
+        let changed = priority != ticket.priority || assignee != ticket.assigned_to;
+
+        let mut tx = self.pool.begin().await?;
+        if changed {
+            sqlx::query(
+                "UPDATE support_tickets SET priority = $1, assigned_to = $2, updated_at = $3 WHERE id = $4",
+            )
+            .bind(priority)
+            .bind(&assignee)
+            .bind(now)
+            .bind(&ticket.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO support_ticket_escalations (ticket_id, rule_id, escalated_at)
+            VALUES ($1,$2,$3)
+            ON CONFLICT (ticket_id, rule_id) DO UPDATE SET escalated_at = EXCLUDED.escalated_at
+        "#,
+        )
+        .bind(&ticket.id)
+        .bind(&rule.id)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        if !changed {
+            return Ok(());
+        }
+        if let Some(assignee) = assignee {
+            let _ = self
+                .notification_service
+                .create_notification(
+                    assignee,
+                    Some(tenant_id.to_string()),
+                    format!("Ticket escalated [#{}]", ticket_ref(&ticket.id)),
+                    format!(
+                        "{} (rule \"{}\", priority {})",
+                        ticket.subject, rule.name, priority
+                    ),
+                    "warning".to_string(),
+                    "support".to_string(),
+                    Some(format!("/admin/support/{}", ticket.id)),
+                )
+                .await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket(status: &str, closed_days_ago: Option<i64>) -> SupportTicket {
+        let now = Utc::now();
+        SupportTicket {
+            id: "t1".to_string(),
+            tenant_id: "tenant".to_string(),
+            created_by: Some("customer".to_string()),
+            subject: "No internet".to_string(),
+            status: status.to_string(),
+            priority: "normal".to_string(),
+            assigned_to: None,
+            category: None,
+            queue_id: None,
+            created_at: now - Duration::days(30),
+            updated_at: now,
+            closed_at: closed_days_ago.map(|d| now - Duration::days(d)),
+        }
+    }
+
+    #[test]
+    fn bumps_priority_up_to_urgent() {
+        assert_eq!(bump_priority("low"), "normal");
+        assert_eq!(bump_priority("normal"), "high");
+        assert_eq!(bump_priority("high"), "urgent");
+        assert_eq!(bump_priority("urgent"), "urgent");
+    }
+
+    #[test]
+    fn customer_reply_reopens_within_window() {
+        let now = Utc::now();
+        let recent = ticket("closed", Some(2));
+        assert_eq!(
+            status_after_reply(&recent, true, 7, now).unwrap(),
+            Some("open")
+        );
+        assert!(status_after_reply(&recent, false, 7, now).is_err());
+        assert!(status_after_reply(&recent, true, 0, now).is_err());
+        assert!(status_after_reply(&ticket("closed", Some(10)), true, 7, now).is_err());
+
+        assert_eq!(
+            status_after_reply(&ticket("pending", None), true, 7, now).unwrap(),
+            Some("open")
+        );
+        assert_eq!(
+            status_after_reply(&ticket("pending", None), false, 7, now).unwrap(),
+            None
+        );
+        assert_eq!(
+            status_after_reply(&ticket("open", None), true, 7, now).unwrap(),
+            None
+        );
+    }
+}
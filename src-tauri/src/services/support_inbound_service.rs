@@ -0,0 +1,668 @@
+//! Inbound email-to-ticket gateway.
+//!
+//! The mail provider forwards each message sent to a tenant's support address
+//! as raw MIME to `/api/support/inbound/{token}`. A message becomes a reply
+//! when it references an earlier inbound email (In-Reply-To / References) or
+//! carries a `[#ticketref]` subject token; otherwise it opens a new ticket.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::SupportTicket;
+#[cfg(feature = "postgres")]
+use crate::services::event_outbox_service::{self, DomainEvent, EventOutboxService};
+#[cfg(feature = "postgres")]
+use crate::services::support_escalation_service::status_after_reply;
+use crate::services::{StorageService, SupportEscalationService};
+use mail_parser::{HeaderValue, MessageParser, MimeHeaders};
+use tracing::warn;
+
+/// Attachments beyond these limits are dropped rather than failing the email.
+const MAX_ATTACHMENTS: usize = 10;
+const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct InboundAttachment {
+    pub name: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InboundEmail {
+    pub message_id: Option<String>,
+    /// In-Reply-To followed by References, without angle brackets.
+    pub thread_ids: Vec<String>,
+    pub from_email: String,
+    pub subject: String,
+    pub text: String,
+    /// Auto-replies and bulk mail (out-of-office, mailer daemons).
+    pub automated: bool,
+    pub attachments: Vec<InboundAttachment>,
+}
+
+/// Result of handing one email to the gateway.
+pub enum InboundOutcome {
+    Posted {
+        ticket: SupportTicket,
+        message_id: String,
+        author_id: String,
+        created: bool,
+    },
+    Ignored(String),
+}
+
+/// Short ticket reference used in outgoing subjects, e.g. `[#1a2b3c4d]`.
+pub fn ticket_ref(ticket_id: &str) -> String {
+    ticket_id.chars().take(8).collect()
+}
+
+/// Find a `[#ref]` token in a subject line.
+pub fn subject_ref(subject: &str) -> Option<String> {
+    let mut rest = subject;
+    while let Some(start) = rest.find("[#") {
+        let after = &rest[start + 2..];
+        let end = after.find(']')?;
+        let token = after[..end].trim().to_lowercase();
+        if (8..=36).contains(&token.len())
+            && token.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+        {
+            return Some(token);
+        }
+        rest = &after[end..];
+    }
+    None
+}
+
+/// Subject for a new ticket: reply/forward prefixes and ref tokens removed.
+pub fn clean_subject(subject: &str) -> String {
+    let mut s = subject.to_string();
+    while let Some(start) = s.find("[#") {
+        match s[start..].find(']') {
+            Some(end) => s.replace_range(start..start + end + 1, ""),
+            None => break,
+        }
+    }
+    let mut s = s.trim();
+    loop {
+        let lower = s.to_lowercase();
+        let prefix = ["re:", "fw:", "fwd:", "aw:", "balas:"]
+            .iter()
+            .find(|p| lower.starts_with(*p));
+        match prefix {
+            Some(p) => s = s[p.len()..].trim_start(),
+            None => break,
+        }
+    }
+    let s = s.trim();
+    if s.is_empty() {
+        "(no subject)".to_string()
+    } else {
+        s.chars().take(200).collect()
+    }
+}
+
+/// Drop the quoted history and signature a mail client appends to a reply.
+pub fn strip_quoted_reply(text: &str) -> String {
+    let mut out: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        let t = line.trim();
+        let lower = t.to_lowercase();
+        let is_header = (lower.starts_with("on ") && lower.ends_with("wrote:"))
+            || (lower.starts_with("pada ") && lower.ends_with("menulis:"))
+            || lower.starts_with("-----original message-----")
+            || lower.starts_with("________________________________");
+        if is_header || line == "-- " {
+            break;
+        }
+        if t.starts_with('>') {
+            continue;
+        }
+        out.push(line.trim_end());
+    }
+    out.join("\n").trim().to_string()
+}
+
+fn header_ids(value: &HeaderValue) -> Vec<String> {
+    let raw: Vec<&str> = match value {
+        HeaderValue::Text(t) => vec![t.as_ref()],
+        HeaderValue::TextList(list) => list.iter().map(|t| t.as_ref()).collect(),
+        _ => Vec::new(),
+    };
+    raw.into_iter()
+        .flat_map(|v| v.split_whitespace())
+        .map(|v| v.trim_matches(|c| c == '<' || c == '>').to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// Parse a raw RFC 5322 message. Returns `None` when there is no sender.
+pub fn parse_raw(raw: &[u8]) -> Option<InboundEmail> {
+    let message = MessageParser::default().parse(raw)?;
+    let from_email = message
+        .from()
+        .and_then(|a| a.first())
+        .and_then(|a| a.address())
+        .map(|a| a.trim().to_lowercase())
+        .filter(|a| a.contains('@'))?;
+
+    let mut thread_ids = header_ids(message.in_reply_to());
+    for id in header_ids(message.references()) {
+        if !thread_ids.contains(&id) {
+            thread_ids.push(id);
+        }
+    }
+
+    let auto_submitted = message
+        .header_raw("Auto-Submitted")
+        .map(|v| !v.trim().eq_ignore_ascii_case("no"))
+        .unwrap_or(false);
+    let bulk = message
+        .header_raw("Precedence")
+        .map(|v| {
+            let v = v.trim().to_lowercase();
+            v == "bulk" || v == "junk" || v == "auto_reply"
+        })
+        .unwrap_or(false);
+
+    let attachments = message
+        .attachments()
+        .map(|part| InboundAttachment {
+            name: part
+                .attachment_name()
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| "attachment".to_string()),
+            content_type: part
+                .content_type()
+                .map(|ct| match ct.subtype() {
+                    Some(sub) => format!("{}/{}", ct.ctype(), sub),
+                    None => ct.ctype().to_string(),
+                })
+                .unwrap_or_else(|| "application/octet-stream".to_string()),
+            data: part.contents().to_vec(),
+        })
+        .collect();
+
+    Some(InboundEmail {
+        message_id: message.message_id().map(|m| m.to_string()),
+        thread_ids,
+        from_email,
+        subject: message.subject().unwrap_or_default().to_string(),
+        text: message
+            .body_text(0)
+            .map(|b| b.into_owned())
+            .unwrap_or_default(),
+        automated: auto_submitted || bulk,
+        attachments,
+    })
+}
+
+#[derive(Clone)]
+pub struct SupportInboundService {
+    pool: DbPool,
+    storage_service: StorageService,
+    escalation: SupportEscalationService,
+}
+
+impl SupportInboundService {
+    pub fn new(
+        pool: DbPool,
+        storage_service: StorageService,
+        escalation: SupportEscalationService,
+    ) -> Self {
+        Self {
+            pool,
+            storage_service,
+            escalation,
+        }
+    }
+
+    /// Turn one inbound email into a new ticket or a reply.
+    pub async fn handle(&self, token: &str, raw: &[u8]) -> AppResult<InboundOutcome> {
+        #[cfg(not(feature = "postgres"))]
+        {
+            let _ = (
+                token,
+                raw,
+                &self.pool,
+                &self.storage_service,
+                &self.escalation,
+            );
+            Err(AppError::Validation(
+                "Inbound email requires PostgreSQL".to_string(),
+            ))
+        }
+
+        #[cfg(feature = "postgres")]
+        {
+            let tenant_id = self.tenant_for_token(token).await?;
+            let email = parse_raw(raw).ok_or_else(|| {
+                AppError::Validation("Body is not an email message with a sender".to_string())
+            })?;
+
+            if email.automated {
+                return Ok(InboundOutcome::Ignored("automated message".to_string()));
+            }
+
+            if let Some(mid) = email.message_id.as_deref() {
+                let seen: bool = sqlx::query_scalar(
+                    "SELECT EXISTS(SELECT 1 FROM support_ticket_inbound_emails WHERE tenant_id = $1 AND email_message_id = $2)",
+                )
+                .bind(&tenant_id)
+                .bind(mid)
+                .fetch_one(&self.pool)
+                .await?;
+                if seen {
+                    return Ok(InboundOutcome::Ignored("duplicate message".to_string()));
+                }
+            }
+
+            let author_id: Option<String> = sqlx::query_scalar(
+                r#"
+                SELECT u.id
+                FROM users u
+                JOIN tenant_members tm ON tm.user_id = u.id
+                WHERE tm.tenant_id = $1
+                  AND LOWER(u.email) = $2
+                  AND u.is_active = true
+                LIMIT 1
+            "#,
+            )
+            .bind(&tenant_id)
+            .bind(&email.from_email)
+            .fetch_optional(&self.pool)
+            .await?;
+            let Some(author_id) = author_id else {
+                return Ok(InboundOutcome::Ignored(format!(
+                    "unknown sender {}",
+                    email.from_email
+                )));
+            };
+
+            let mut body = strip_quoted_reply(&email.text);
+            if body.is_empty() {
+                if email.attachments.is_empty() {
+                    return Ok(InboundOutcome::Ignored("empty message".to_string()));
+                }
+                body = "(attachment)".to_string();
+            }
+
+            let existing = self.find_ticket(&tenant_id, &email).await?;
+            let mut new_status = None;
+            let reply_to = match existing {
+                Some(ticket) => {
+                    if !self.can_reply(&tenant_id, &ticket, &author_id).await? {
+                        return Ok(InboundOutcome::Ignored(
+                            "sender cannot reply to this ticket".to_string(),
+                        ));
+                    }
+                    let by_customer = ticket.created_by.as_deref() == Some(author_id.as_str());
+                    let window = if by_customer && ticket.status == "closed" {
+                        self.escalation.reopen_window_days(&tenant_id).await
+                    } else {
+                        0
+                    };
+                    match status_after_reply(&ticket, by_customer, window, chrono::Utc::now()) {
+                        Ok(status) => {
+                            new_status = status;
+                            Some(ticket)
+                        }
+                        // Past the reopen window a reply opens a follow-up ticket instead.
+                        Err(_) => None,
+                    }
+                }
+                None => None,
+            };
+
+            let file_ids = self.store_attachments(&tenant_id, &author_id, &email).await;
+            self.post(
+                &tenant_id, &author_id, &email, &body, reply_to, new_status, &file_ids,
+            )
+            .await
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    async fn tenant_for_token(&self, token: &str) -> AppResult<String> {
+        if token.trim().is_empty() {
+            return Err(AppError::Forbidden("Invalid inbound token".to_string()));
+        }
+        let tenant_id: Option<String> = sqlx::query_scalar(
+            "SELECT tenant_id FROM settings WHERE key = 'support_inbound_token' AND value = $1 AND tenant_id IS NOT NULL LIMIT 1",
+        )
+        .bind(token.trim())
+        .fetch_optional(&self.pool)
+        .await?;
+        tenant_id.ok_or_else(|| AppError::Forbidden("Invalid inbound token".to_string()))
+    }
+
+    /// Thread by Message-ID first, then by the subject token.
+    #[cfg(feature = "postgres")]
+    async fn find_ticket(
+        &self,
+        tenant_id: &str,
+        email: &InboundEmail,
+    ) -> AppResult<Option<SupportTicket>> {
+        if !email.thread_ids.is_empty() {
+            let ticket: Option<SupportTicket> = sqlx::query_as(
+                r#"
+                SELECT t.*
+                FROM support_ticket_inbound_emails e
+                JOIN support_tickets t ON t.id = e.ticket_id
+                WHERE e.tenant_id = $1
+                  AND e.email_message_id = ANY($2)
+                ORDER BY e.created_at DESC
+                LIMIT 1
+            "#,
+            )
+            .bind(tenant_id)
+            .bind(&email.thread_ids)
+            .fetch_optional(&self.pool)
+            .await?;
+            if ticket.is_some() {
+                return Ok(ticket);
+            }
+        }
+
+        let Some(token) = subject_ref(&email.subject) else {
+            return Ok(None);
+        };
+        let mut matches: Vec<SupportTicket> = sqlx::query_as(
+            "SELECT * FROM support_tickets WHERE tenant_id = $1 AND id LIKE $2 LIMIT 2",
+        )
+        .bind(tenant_id)
+        .bind(format!("{}%", token))
+        .fetch_all(&self.pool)
+        .await?;
+        // An ambiguous short ref is treated as no match.
+        Ok(if matches.len() == 1 {
+            matches.pop()
+        } else {
+            None
+        })
+    }
+
+    /// Same rule as the reply endpoint: the ticket owner or support staff.
+    #[cfg(feature = "postgres")]
+    async fn can_reply(
+        &self,
+        tenant_id: &str,
+        ticket: &SupportTicket,
+        user_id: &str,
+    ) -> AppResult<bool> {
+        if ticket.created_by.as_deref() == Some(user_id) {
+            return Ok(true);
+        }
+        let staff: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1
+                FROM tenant_members tm
+                JOIN role_permissions rp ON rp.role_id = tm.role_id
+                WHERE tm.tenant_id = $1
+                  AND tm.user_id = $2
+                  AND rp.permission_id = ANY($3)
+            )
+        "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(["support:read_all", "support:reply"])
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(staff)
+    }
+
+    #[cfg(feature = "postgres")]
+    async fn store_attachments(
+        &self,
+        tenant_id: &str,
+        author_id: &str,
+        email: &InboundEmail,
+    ) -> Vec<String> {
+        let mut ids = Vec::new();
+        for att in email.attachments.iter().take(MAX_ATTACHMENTS) {
+            if att.data.is_empty() || att.data.len() > MAX_ATTACHMENT_BYTES {
+                warn!(
+                    "Inbound email attachment '{}' skipped ({} bytes)",
+                    att.name,
+                    att.data.len()
+                );
+                continue;
+            }
+            match self
+                .storage_service
+                .upload(
+                    tenant_id,
+                    &att.name,
+                    &att.content_type,
+                    &att.data,
+                    Some(author_id),
+                )
+                .await
+            {
+                Ok(file) => ids.push(file.id),
+                Err(e) => warn!("Inbound email attachment '{}' not stored: {}", att.name, e),
+            }
+        }
+        ids
+    }
+
+    #[cfg(feature = "postgres")]
+    #[allow(clippy::too_many_arguments)]
+    async fn post(
+        &self,
+        tenant_id: &str,
+        author_id: &str,
+        email: &InboundEmail,
+        body: &str,
+        reply_to: Option<SupportTicket>,
+        new_status: Option<&str>,
+        file_ids: &[String],
+    ) -> AppResult<InboundOutcome> {
+        let now = chrono::Utc::now();
+        let msg_id = uuid::Uuid::new_v4().to_string();
+        let created = reply_to.is_none();
+        let ticket_id = reply_to
+            .as_ref()
+            .map(|t| t.id.clone())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT set_config('app.current_tenant_id', $1, true)")
+            .bind(tenant_id)
+            .execute(&mut *tx)
+            .await?;
+
+        if created {
+            sqlx::query(
+                r#"
+                INSERT INTO support_tickets (
+                    id, tenant_id, created_by, subject, status, priority, assigned_to,
+                    created_at, updated_at, closed_at
+                )
+                VALUES ($1,$2,$3,$4,'open','normal',NULL,$5,$5,NULL)
+            "#,
+            )
+            .bind(&ticket_id)
+            .bind(tenant_id)
+            .bind(author_id)
+            .bind(clean_subject(&email.subject))
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            // A reopened ticket also clears closed_at.
+            sqlx::query(
+                r#"
+                UPDATE support_tickets
+                SET status = COALESCE($1, status),
+                    closed_at = CASE WHEN $1::text IS NULL THEN closed_at END,
+                    updated_at = $2
+                WHERE id = $3
+            "#,
+            )
+            .bind(new_status)
+            .bind(now)
+            .bind(&ticket_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO support_ticket_messages (id, ticket_id, author_id, body, is_internal, created_at)
+            VALUES ($1,$2,$3,$4,false,$5)
+        "#,
+        )
+        .bind(&msg_id)
+        .bind(&ticket_id)
+        .bind(author_id)
+        .bind(body)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        for fid in file_ids {
+            sqlx::query(
+                "INSERT INTO support_ticket_attachments (id, message_id, file_id, created_at) VALUES ($1,$2,$3,$4) ON CONFLICT DO NOTHING",
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(&msg_id)
+            .bind(fid)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Emails without a Message-ID still get a row so the ticket keeps a trail.
+        let email_message_id = email
+            .message_id
+            .clone()
+            .unwrap_or_else(|| format!("{}@inbound.local", msg_id));
+        sqlx::query(
+            r#"
+            INSERT INTO support_ticket_inbound_emails
+                (id, tenant_id, ticket_id, ticket_message_id, email_message_id, from_email, created_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7)
+        "#,
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(&ticket_id)
+        .bind(&msg_id)
+        .bind(&email_message_id)
+        .bind(&email.from_email)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        let ticket: SupportTicket = sqlx::query_as("SELECT * FROM support_tickets WHERE id = $1")
+            .bind(&ticket_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if created {
+            EventOutboxService::stage(
+                &mut tx,
+                Some(tenant_id),
+                &DomainEvent::ticket_created(&ticket),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        if created {
+            event_outbox_service::wake();
+        }
+
+        Ok(InboundOutcome::Posted {
+            ticket,
+            message_id: msg_id,
+            author_id: author_id.to_string(),
+            created,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_subject_tokens() {
+        assert_eq!(
+            subject_ref("Re: Internet down [#1a2b3c4d]").as_deref(),
+            Some("1a2b3c4d")
+        );
+        assert_eq!(
+            subject_ref("Invoice [#12] [#ABCDEF01]").as_deref(),
+            Some("abcdef01")
+        );
+        assert_eq!(subject_ref("Re: [#not-a-ref!] hello"), None);
+        assert_eq!(subject_ref("No token"), None);
+    }
+
+    #[test]
+    fn cleans_subjects_for_new_tickets() {
+        assert_eq!(clean_subject("RE: Fwd: Slow wifi [#1a2b3c4d]"), "Slow wifi");
+        assert_eq!(clean_subject("Balas: Tagihan"), "Tagihan");
+        assert_eq!(clean_subject("  Re:  "), "(no subject)");
+    }
+
+    #[test]
+    fn strips_quoted_history_and_signature() {
+        let text = "Still broken after restart.\n\nOn Mon, 1 Jan 2026 at 10:00, Support <s@isp.id> wrote:\n> Please restart the router.\n";
+        assert_eq!(strip_quoted_reply(text), "Still broken after restart.");
+
+        let text = "Sudah normal, terima kasih.\n> kutipan\n-- \nBudi\n";
+        assert_eq!(strip_quoted_reply(text), "Sudah normal, terima kasih.");
+
+        let text = "Oke.\n\nPada Sen, 2 Feb 2026 Support menulis:\n> halo";
+        assert_eq!(strip_quoted_reply(text), "Oke.");
+    }
+
+    #[test]
+    fn parses_raw_message_with_attachment() {
+        let raw = concat!(
+            "From: Budi <Budi@Example.com>\r\n",
+            "To: support@isp.example\r\n",
+            "Subject: Re: Slow connection [#1a2b3c4d]\r\n",
+            "Message-ID: <reply-1@example.com>\r\n",
+            "In-Reply-To: <orig-1@example.com>\r\n",
+            "References: <orig-0@example.com> <orig-1@example.com>\r\n",
+            "MIME-Version: 1.0\r\n",
+            "Content-Type: multipart/mixed; boundary=\"b1\"\r\n",
+            "\r\n",
+            "--b1\r\n",
+            "Content-Type: text/plain; charset=utf-8\r\n",
+            "\r\n",
+            "Speed test attached.\r\n",
+            "--b1\r\n",
+            "Content-Type: image/png\r\n",
+            "Content-Disposition: attachment; filename=\"speed.png\"\r\n",
+            "Content-Transfer-Encoding: base64\r\n",
+            "\r\n",
+            "iVBORw0KGgo=\r\n",
+            "--b1--\r\n",
+        );
+
+        let email = parse_raw(raw.as_bytes()).expect("parsed");
+        assert_eq!(email.from_email, "budi@example.com");
+        assert_eq!(email.message_id.as_deref(), Some("reply-1@example.com"));
+        assert_eq!(
+            email.thread_ids,
+            vec!["orig-1@example.com", "orig-0@example.com"]
+        );
+        assert_eq!(email.text.trim(), "Speed test attached.");
+        assert!(!email.automated);
+        assert_eq!(email.attachments.len(), 1);
+        assert_eq!(email.attachments[0].name, "speed.png");
+        assert_eq!(email.attachments[0].content_type, "image/png");
+        assert_eq!(email.attachments[0].data, b"\x89PNG\r\n\x1a\n");
+    }
+
+    #[test]
+    fn flags_auto_replies() {
+        let raw = "From: a@example.com\r\nSubject: Out of office\r\nAuto-Submitted: auto-replied\r\n\r\nAway.\r\n";
+        assert!(parse_raw(raw.as_bytes()).expect("parsed").automated);
+    }
+}
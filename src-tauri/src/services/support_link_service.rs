@@ -0,0 +1,263 @@
+//! Links between support tickets and other records.
+//!
+//! A ticket can point at a mikrotik incident, an installation work order or a
+//! (disputed) invoice. Each record can list the tickets that point at it.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{CreateSupportTicketLinkDto, LinkedSupportTicket, SupportTicketLink};
+use chrono::Utc;
+use uuid::Uuid;
+
+/// Kinds of record a ticket can link to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkEntity {
+    Incident,
+    WorkOrder,
+    Invoice,
+}
+
+impl LinkEntity {
+    pub const ALL: [LinkEntity; 3] = [
+        LinkEntity::Incident,
+        LinkEntity::WorkOrder,
+        LinkEntity::Invoice,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkEntity::Incident => "incident",
+            LinkEntity::WorkOrder => "work_order",
+            LinkEntity::Invoice => "invoice",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str() == s.trim())
+    }
+
+    fn exists_sql(&self) -> &'static str {
+        match self {
+            LinkEntity::Incident => {
+                "SELECT EXISTS(SELECT 1 FROM mikrotik_incidents WHERE id = $1 AND tenant_id = $2)"
+            }
+            LinkEntity::WorkOrder => {
+                "SELECT EXISTS(SELECT 1 FROM installation_work_orders WHERE id = $1 AND tenant_id = $2)"
+            }
+            LinkEntity::Invoice => {
+                "SELECT EXISTS(SELECT 1 FROM invoices WHERE id = $1 AND tenant_id = $2)"
+            }
+        }
+    }
+}
+
+fn parse_entity(entity_type: &str) -> AppResult<LinkEntity> {
+    LinkEntity::parse(entity_type).ok_or_else(|| {
+        AppError::Validation(format!(
+            "Unknown entity type '{}' (expected one of: incident, work_order, invoice)",
+            entity_type.trim()
+        ))
+    })
+}
+
+const LINK_SELECT: &str = r#"
+    SELECT
+        l.id, l.tenant_id, l.ticket_id, l.entity_type, l.entity_id, l.note, l.created_by, l.created_at,
+        CASE l.entity_type
+            WHEN 'incident' THEN i.title
+            WHEN 'work_order' THEN c.name
+            WHEN 'invoice' THEN inv.invoice_number
+        END AS entity_label,
+        CASE l.entity_type
+            WHEN 'incident' THEN i.status
+            WHEN 'work_order' THEN w.status
+            WHEN 'invoice' THEN inv.status
+        END AS entity_status
+    FROM support_ticket_links l
+    LEFT JOIN mikrotik_incidents i
+        ON l.entity_type = 'incident' AND i.id = l.entity_id AND i.tenant_id = l.tenant_id
+    LEFT JOIN installation_work_orders w
+        ON l.entity_type = 'work_order' AND w.id = l.entity_id AND w.tenant_id = l.tenant_id
+    LEFT JOIN customers c ON c.id = w.customer_id
+    LEFT JOIN invoices inv
+        ON l.entity_type = 'invoice' AND inv.id = l.entity_id AND inv.tenant_id = l.tenant_id
+"#;
+
+#[derive(Clone)]
+pub struct SupportLinkService {
+    pool: DbPool,
+}
+
+impl SupportLinkService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    async fn ensure_ticket(&self, tenant_id: &str, ticket_id: &str) -> AppResult<()> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM support_tickets WHERE id = $1 AND tenant_id = $2)",
+        )
+        .bind(ticket_id)
+        .bind(tenant_id)
+        .fetch_one(&self.pool)
+        .await?;
+        if !exists {
+            return Err(AppError::NotFound("Support ticket not found".to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn list_for_ticket(
+        &self,
+        tenant_id: &str,
+        ticket_id: &str,
+    ) -> AppResult<Vec<SupportTicketLink>> {
+        self.ensure_ticket(tenant_id, ticket_id).await?;
+        let rows: Vec<SupportTicketLink> = sqlx::query_as(&format!(
+            "{LINK_SELECT} WHERE l.tenant_id = $1 AND l.ticket_id = $2 ORDER BY l.created_at ASC"
+        ))
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn link(
+        &self,
+        tenant_id: &str,
+        ticket_id: &str,
+        created_by: &str,
+        dto: CreateSupportTicketLinkDto,
+    ) -> AppResult<SupportTicketLink> {
+        let entity = parse_entity(&dto.entity_type)?;
+        let entity_id = dto.entity_id.trim().to_string();
+        if entity_id.is_empty() {
+            return Err(AppError::Validation("Entity id is required".to_string()));
+        }
+        self.ensure_ticket(tenant_id, ticket_id).await?;
+
+        let exists: bool = sqlx::query_scalar(entity.exists_sql())
+            .bind(&entity_id)
+            .bind(tenant_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if !exists {
+            let what = match entity {
+                LinkEntity::Incident => "Incident",
+                LinkEntity::WorkOrder => "Work order",
+                LinkEntity::Invoice => "Invoice",
+            };
+            return Err(AppError::NotFound(format!("{what} not found")));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let note = dto
+            .note
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+        let res = sqlx::query(
+            r#"
+            INSERT INTO support_ticket_links
+                (id, tenant_id, ticket_id, entity_type, entity_id, note, created_by, created_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
+            ON CONFLICT (ticket_id, entity_type, entity_id) DO NOTHING
+        "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .bind(entity.as_str())
+        .bind(&entity_id)
+        .bind(&note)
+        .bind(created_by)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::Conflict(
+                "Ticket is already linked to this record".to_string(),
+            ));
+        }
+
+        let row: SupportTicketLink = sqlx::query_as(&format!("{LINK_SELECT} WHERE l.id = $1"))
+            .bind(&id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row)
+    }
+
+    /// Removes a link and returns it (for the audit log).
+    pub async fn unlink(
+        &self,
+        tenant_id: &str,
+        ticket_id: &str,
+        link_id: &str,
+    ) -> AppResult<SupportTicketLink> {
+        let row: Option<SupportTicketLink> = sqlx::query_as(&format!(
+            "{LINK_SELECT} WHERE l.tenant_id = $1 AND l.ticket_id = $2 AND l.id = $3"
+        ))
+        .bind(tenant_id)
+        .bind(ticket_id)
+        .bind(link_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let row = row.ok_or_else(|| AppError::NotFound("Link not found".to_string()))?;
+
+        sqlx::query("DELETE FROM support_ticket_links WHERE id = $1")
+            .bind(link_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(row)
+    }
+
+    /// Tickets linked to one record, newest link first.
+    pub async fn tickets_for_entity(
+        &self,
+        tenant_id: &str,
+        entity_type: &str,
+        entity_id: &str,
+    ) -> AppResult<Vec<LinkedSupportTicket>> {
+        let entity = parse_entity(entity_type)?;
+        let rows: Vec<LinkedSupportTicket> = sqlx::query_as(
+            r#"
+            SELECT
+                l.id AS link_id,
+                t.id AS ticket_id,
+                t.subject,
+                t.status,
+                t.priority,
+                t.assigned_to,
+                l.note,
+                l.created_at AS linked_at
+            FROM support_ticket_links l
+            JOIN support_tickets t ON t.id = l.ticket_id
+            WHERE l.tenant_id = $1 AND l.entity_type = $2 AND l.entity_id = $3
+            ORDER BY l.created_at DESC
+        "#,
+        )
+        .bind(tenant_id)
+        .bind(entity.as_str())
+        .bind(entity_id.trim())
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entity_types_round_trip() {
+        for e in LinkEntity::ALL {
+            assert_eq!(LinkEntity::parse(e.as_str()), Some(e));
+        }
+        assert_eq!(
+            LinkEntity::parse(" work_order "),
+            Some(LinkEntity::WorkOrder)
+        );
+        assert!(parse_entity("customer").is_err());
+    }
+}
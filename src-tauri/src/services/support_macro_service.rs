@@ -0,0 +1,434 @@
+//! Canned responses and support macros.
+//!
+//! A macro's body is a reply with `{{variable}}` placeholders. Macros may also
+//! set the status, priority or assignee; applying one posts the reply and the
+//! ticket changes in a single transaction.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{SupportMacro, SupportTicket, SupportTicketMessage, UpsertSupportMacroDto};
+use crate::services::support_inbound_service::ticket_ref;
+use crate::services::whatsapp_service::{render_template, template_variables};
+use crate::services::SupportRoutingService;
+use chrono::Utc;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Placeholders a macro body may use.
+pub const MACRO_VARIABLES: &[&str] = &[
+    "customer_name",
+    "agent_name",
+    "ticket_ref",
+    "ticket_subject",
+    "tenant_name",
+];
+
+const MAX_BODY_LEN: usize = 10_000;
+const STATUSES: &[&str] = &["open", "pending", "closed"];
+const PRIORITIES: &[&str] = &["low", "normal", "high", "urgent"];
+
+/// Placeholders in `body` that macros can't fill.
+fn unknown_variables(body: &str) -> Vec<String> {
+    template_variables(body)
+        .into_iter()
+        .filter(|v| !MACRO_VARIABLES.contains(&v.as_str()))
+        .collect()
+}
+
+/// Lowercased `value` if it is one of `allowed`; blank means "leave as is".
+fn normalize_choice(
+    value: Option<String>,
+    allowed: &[&str],
+    label: &str,
+) -> AppResult<Option<String>> {
+    match value
+        .map(|v| v.trim().to_lowercase())
+        .filter(|v| !v.is_empty())
+    {
+        None => Ok(None),
+        Some(v) if allowed.contains(&v.as_str()) => Ok(Some(v)),
+        Some(v) => Err(AppError::Validation(format!(
+            "Invalid {label} '{v}' (expected one of: {})",
+            allowed.join(", ")
+        ))),
+    }
+}
+
+fn macro_vars(
+    ticket: &SupportTicket,
+    customer_name: Option<String>,
+    agent_name: Option<String>,
+    tenant_name: Option<String>,
+) -> HashMap<&'static str, String> {
+    HashMap::from([
+        ("customer_name", customer_name.unwrap_or_default()),
+        ("agent_name", agent_name.unwrap_or_default()),
+        ("ticket_ref", ticket_ref(&ticket.id)),
+        ("ticket_subject", ticket.subject.clone()),
+        ("tenant_name", tenant_name.unwrap_or_default()),
+    ])
+}
+
+/// What applying a macro changed.
+pub struct AppliedMacro {
+    pub previous: SupportTicket,
+    pub ticket: SupportTicket,
+    pub message: Option<SupportTicketMessage>,
+}
+
+#[derive(Clone)]
+pub struct SupportMacroService {
+    pool: DbPool,
+    routing: SupportRoutingService,
+}
+
+impl SupportMacroService {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            routing: SupportRoutingService::new(pool.clone()),
+            pool,
+        }
+    }
+
+    pub async fn list(&self, tenant_id: &str) -> AppResult<Vec<SupportMacro>> {
+        let rows: Vec<SupportMacro> = sqlx::query_as(
+            "SELECT * FROM support_macros WHERE tenant_id = $1 ORDER BY lower(COALESCE(category, '')), lower(name)",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn get(&self, tenant_id: &str, id: &str) -> AppResult<SupportMacro> {
+        let row: Option<SupportMacro> =
+            sqlx::query_as("SELECT * FROM support_macros WHERE tenant_id = $1 AND id = $2")
+                .bind(tenant_id)
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+        row.ok_or_else(|| AppError::NotFound("Support macro not found".to_string()))
+    }
+
+    pub async fn create(
+        &self,
+        tenant_id: &str,
+        created_by: &str,
+        dto: UpsertSupportMacroDto,
+    ) -> AppResult<SupportMacro> {
+        let id = Uuid::new_v4().to_string();
+        let m = self.validate(tenant_id, &id, dto).await?;
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO support_macros
+                (id, tenant_id, name, category, body, is_internal, set_status, set_priority,
+                 assign_to, created_by, created_at, updated_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$11)
+        "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&m.name)
+        .bind(&m.category)
+        .bind(&m.body)
+        .bind(m.is_internal)
+        .bind(&m.set_status)
+        .bind(&m.set_priority)
+        .bind(&m.assign_to)
+        .bind(created_by)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        self.get(tenant_id, &id).await
+    }
+
+    pub async fn update(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        dto: UpsertSupportMacroDto,
+    ) -> AppResult<SupportMacro> {
+        self.get(tenant_id, id).await?;
+        let m = self.validate(tenant_id, id, dto).await?;
+        sqlx::query(
+            r#"
+            UPDATE support_macros
+            SET name = $1,
+                category = $2,
+                body = $3,
+                is_internal = $4,
+                set_status = $5,
+                set_priority = $6,
+                assign_to = $7,
+                updated_at = $8
+            WHERE tenant_id = $9 AND id = $10
+        "#,
+        )
+        .bind(&m.name)
+        .bind(&m.category)
+        .bind(&m.body)
+        .bind(m.is_internal)
+        .bind(&m.set_status)
+        .bind(&m.set_priority)
+        .bind(&m.assign_to)
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        self.get(tenant_id, id).await
+    }
+
+    pub async fn delete(&self, tenant_id: &str, id: &str) -> AppResult<()> {
+        let res = sqlx::query("DELETE FROM support_macros WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound("Support macro not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Normalised macro fields; `id` is only used to skip the macro itself in
+    /// the duplicate-name check.
+    async fn validate(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        dto: UpsertSupportMacroDto,
+    ) -> AppResult<ValidatedMacro> {
+        let name = dto.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > 100 {
+            return Err(AppError::Validation(
+                "Macro name is required (max 100 characters)".to_string(),
+            ));
+        }
+        let taken: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM support_macros WHERE tenant_id = $1 AND lower(name) = lower($2) AND id <> $3)",
+        )
+        .bind(tenant_id)
+        .bind(&name)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+        if taken {
+            return Err(AppError::Conflict(format!(
+                "A macro named '{}' already exists",
+                name
+            )));
+        }
+
+        let body = dto.body.map(|b| b.trim().to_string()).unwrap_or_default();
+        if body.len() > MAX_BODY_LEN {
+            return Err(AppError::Validation(format!(
+                "Macro body is too long (max {MAX_BODY_LEN} bytes)"
+            )));
+        }
+        let unknown = unknown_variables(&body);
+        if !unknown.is_empty() {
+            return Err(AppError::Validation(format!(
+                "Unknown variable(s): {}. Available: {}",
+                unknown.join(", "),
+                MACRO_VARIABLES.join(", ")
+            )));
+        }
+
+        let set_status = normalize_choice(dto.set_status, STATUSES, "status")?;
+        let set_priority = normalize_choice(dto.set_priority, PRIORITIES, "priority")?;
+        let assign_to = dto
+            .assign_to
+            .map(|a| a.trim().to_string())
+            .filter(|a| !a.is_empty());
+        if let Some(user_id) = assign_to.as_deref().filter(|a| *a != "me") {
+            self.routing.ensure_agent(tenant_id, user_id).await?;
+        }
+
+        if body.is_empty() && set_status.is_none() && set_priority.is_none() && assign_to.is_none()
+        {
+            return Err(AppError::Validation(
+                "A macro needs a reply body or at least one action".to_string(),
+            ));
+        }
+
+        Ok(ValidatedMacro {
+            name,
+            category: dto
+                .category
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty()),
+            body,
+            is_internal: dto.is_internal.unwrap_or(false),
+            set_status,
+            set_priority,
+            assign_to,
+        })
+    }
+
+    /// The macro body with this ticket's variables filled in.
+    pub async fn render(
+        &self,
+        tenant_id: &str,
+        m: &SupportMacro,
+        ticket: &SupportTicket,
+        agent_id: &str,
+    ) -> AppResult<String> {
+        let (customer_name, agent_name, tenant_name): (
+            Option<String>,
+            Option<String>,
+            Option<String>,
+        ) = sqlx::query_as(
+            r#"
+            SELECT
+                (SELECT name FROM users WHERE id = $1),
+                (SELECT name FROM users WHERE id = $2),
+                (SELECT name FROM tenants WHERE id = $3)
+        "#,
+        )
+        .bind(&ticket.created_by)
+        .bind(agent_id)
+        .bind(tenant_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let vars = macro_vars(ticket, customer_name, agent_name, tenant_name);
+        Ok(render_template(&m.body, &vars))
+    }
+
+    /// Post the macro's reply (if any) and apply its ticket changes.
+    pub async fn apply(
+        &self,
+        tenant_id: &str,
+        m: &SupportMacro,
+        ticket_id: &str,
+        agent_id: &str,
+    ) -> AppResult<AppliedMacro> {
+        let mut tx = self.pool.begin().await?;
+
+        let previous: Option<SupportTicket> = sqlx::query_as(
+            "SELECT * FROM support_tickets WHERE id = $1 AND tenant_id = $2 FOR UPDATE",
+        )
+        .bind(ticket_id)
+        .bind(tenant_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let previous =
+            previous.ok_or_else(|| AppError::NotFound("Support ticket not found".to_string()))?;
+
+        let assigned_to = match m.assign_to.as_deref() {
+            Some("me") => Some(agent_id.to_string()),
+            Some(user_id) => {
+                self.routing.ensure_agent(tenant_id, user_id).await?;
+                Some(user_id.to_string())
+            }
+            None => previous.assigned_to.clone(),
+        };
+
+        let now = Utc::now();
+        let body = self.render(tenant_id, m, &previous, agent_id).await?;
+        let body = body.trim();
+        let mut message = None;
+        if !body.is_empty() {
+            // Replying to a closed ticket is only allowed when the macro reopens it.
+            if previous.status == "closed" && m.set_status.as_deref() != Some("open") {
+                return Err(AppError::Validation("Ticket is closed".to_string()));
+            }
+            let msg_id = Uuid::new_v4().to_string();
+            let msg: SupportTicketMessage = sqlx::query_as(
+                r#"
+                INSERT INTO support_ticket_messages (id, ticket_id, author_id, body, is_internal, created_at)
+                VALUES ($1,$2,$3,$4,$5,$6)
+                RETURNING *
+            "#,
+            )
+            .bind(&msg_id)
+            .bind(ticket_id)
+            .bind(agent_id)
+            .bind(body)
+            .bind(m.is_internal)
+            .bind(now)
+            .fetch_one(&mut *tx)
+            .await?;
+            message = Some(msg);
+        }
+
+        let status = m
+            .set_status
+            .clone()
+            .unwrap_or_else(|| previous.status.clone());
+        let closed_at = match (status.as_str(), previous.status.as_str()) {
+            ("closed", "closed") => previous.closed_at.or(Some(now)),
+            ("closed", _) => Some(now),
+            _ => None,
+        };
+
+        let ticket: SupportTicket = sqlx::query_as(
+            r#"
+            UPDATE support_tickets
+            SET status = $1,
+                priority = $2,
+                assigned_to = $3,
+                updated_at = $4,
+                closed_at = $5
+            WHERE id = $6 AND tenant_id = $7
+            RETURNING *
+        "#,
+        )
+        .bind(&status)
+        .bind(m.set_priority.as_ref().unwrap_or(&previous.priority))
+        .bind(&assigned_to)
+        .bind(now)
+        .bind(closed_at)
+        .bind(ticket_id)
+        .bind(tenant_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(AppliedMacro {
+            previous,
+            ticket,
+            message,
+        })
+    }
+}
+
+struct ValidatedMacro {
+    name: String,
+    category: Option<String>,
+    body: String,
+    is_internal: bool,
+    set_status: Option<String>,
+    set_priority: Option<String>,
+    assign_to: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_unknown_placeholders_only() {
+        assert!(unknown_variables("Hi {{customer_name}}, ref #{{ticket_ref}}").is_empty());
+        assert_eq!(
+            unknown_variables("Hi {{ customer_name }}, your bill is {{amount}}"),
+            vec!["amount".to_string()]
+        );
+    }
+
+    #[test]
+    fn normalizes_status_and_priority_choices() {
+        assert_eq!(
+            normalize_choice(Some(" Pending ".into()), STATUSES, "status").unwrap(),
+            Some("pending".to_string())
+        );
+        assert_eq!(
+            normalize_choice(Some("  ".into()), STATUSES, "status").unwrap(),
+            None
+        );
+        assert!(normalize_choice(Some("solved".into()), STATUSES, "status").is_err());
+    }
+}
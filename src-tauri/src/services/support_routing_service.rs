@@ -0,0 +1,460 @@
+//! Support queues and ticket routing.
+//!
+//! A new ticket goes to the queue that lists its category, or to the tenant's
+//! default queue. Queues with auto-assign hand tickets to their members in
+//! round-robin order, preferring agents with a live session.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{SupportQueue, UpsertSupportQueueDto};
+use chrono::Utc;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+const QUEUE_SELECT: &str = r#"
+    SELECT
+        q.id, q.tenant_id, q.name, q.description, q.categories, q.is_default,
+        q.auto_assign, q.online_only, q.last_assigned_to,
+        COALESCE(
+            (SELECT array_agg(m.user_id ORDER BY m.user_id) FROM support_queue_members m WHERE m.queue_id = q.id),
+            '{}'
+        ) AS member_ids,
+        q.created_at, q.updated_at
+    FROM support_queues q
+"#;
+
+/// Where a ticket ended up after routing.
+#[derive(Debug, Clone)]
+pub struct TicketRoute {
+    pub queue_id: String,
+    pub assigned_to: Option<String>,
+}
+
+/// Lowercase, trimmed ticket category; `None` when blank.
+pub fn normalize_category(category: Option<&str>) -> Option<String> {
+    category
+        .map(|c| c.trim().to_lowercase())
+        .filter(|c| !c.is_empty())
+        .map(|c| c.chars().take(50).collect())
+}
+
+/// Next member after `last` (members sorted by id, wrapping around) that is
+/// eligible. A `last` who has since left the queue still keeps the rotation
+/// fair because the search starts at the first id that sorts after it.
+fn next_round_robin<'a>(
+    members: &'a [String],
+    last: Option<&str>,
+    eligible: &HashSet<String>,
+) -> Option<&'a String> {
+    if members.is_empty() {
+        return None;
+    }
+    let start = last
+        .and_then(|l| members.iter().position(|m| m.as_str() > l))
+        .unwrap_or(0);
+    (0..members.len())
+        .map(|k| &members[(start + k) % members.len()])
+        .find(|m| eligible.contains(*m))
+}
+
+#[derive(Clone)]
+pub struct SupportRoutingService {
+    pool: DbPool,
+}
+
+impl SupportRoutingService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list_queues(&self, tenant_id: &str) -> AppResult<Vec<SupportQueue>> {
+        let rows: Vec<SupportQueue> = sqlx::query_as(&format!(
+            "{QUEUE_SELECT} WHERE q.tenant_id = $1 ORDER BY q.is_default DESC, lower(q.name)"
+        ))
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn get_queue(&self, tenant_id: &str, id: &str) -> AppResult<SupportQueue> {
+        let row: Option<SupportQueue> = sqlx::query_as(&format!(
+            "{QUEUE_SELECT} WHERE q.tenant_id = $1 AND q.id = $2"
+        ))
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.ok_or_else(|| AppError::NotFound("Support queue not found".to_string()))
+    }
+
+    pub async fn create_queue(
+        &self,
+        tenant_id: &str,
+        dto: UpsertSupportQueueDto,
+    ) -> AppResult<SupportQueue> {
+        let id = Uuid::new_v4().to_string();
+        self.save_queue(tenant_id, &id, dto, true).await?;
+        self.get_queue(tenant_id, &id).await
+    }
+
+    pub async fn update_queue(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        dto: UpsertSupportQueueDto,
+    ) -> AppResult<SupportQueue> {
+        self.get_queue(tenant_id, id).await?;
+        self.save_queue(tenant_id, id, dto, false).await?;
+        self.get_queue(tenant_id, id).await
+    }
+
+    /// Tickets in a deleted queue keep their assignee and become unqueued.
+    pub async fn delete_queue(&self, tenant_id: &str, id: &str) -> AppResult<()> {
+        let res = sqlx::query("DELETE FROM support_queues WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound("Support queue not found".to_string()));
+        }
+        Ok(())
+    }
+
+    async fn save_queue(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        dto: UpsertSupportQueueDto,
+        is_new: bool,
+    ) -> AppResult<()> {
+        let name = dto.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > 100 {
+            return Err(AppError::Validation(
+                "Queue name is required (max 100 characters)".to_string(),
+            ));
+        }
+        let taken: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM support_queues WHERE tenant_id = $1 AND lower(name) = lower($2) AND id <> $3)",
+        )
+        .bind(tenant_id)
+        .bind(&name)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+        if taken {
+            return Err(AppError::Conflict(format!(
+                "A queue named '{}' already exists",
+                name
+            )));
+        }
+
+        let mut categories: Vec<String> = Vec::new();
+        for c in dto.categories.unwrap_or_default() {
+            if let Some(c) = normalize_category(Some(&c)) {
+                if !categories.contains(&c) {
+                    categories.push(c);
+                }
+            }
+        }
+
+        let mut member_ids: Vec<String> = dto
+            .member_ids
+            .unwrap_or_default()
+            .into_iter()
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty())
+            .collect();
+        member_ids.sort();
+        member_ids.dedup();
+        let agents = self.agent_ids(tenant_id, &member_ids).await?;
+        if agents.len() != member_ids.len() {
+            return Err(AppError::Validation(
+                "Queue members must be support agents in this tenant".to_string(),
+            ));
+        }
+
+        let description = dto
+            .description
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty());
+        let is_default = dto.is_default.unwrap_or(false);
+        let now = Utc::now();
+
+        let mut tx = self.pool.begin().await?;
+        if is_default {
+            sqlx::query(
+                "UPDATE support_queues SET is_default = false, updated_at = $1 WHERE tenant_id = $2 AND is_default AND id <> $3",
+            )
+            .bind(now)
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if is_new {
+            sqlx::query(
+                r#"
+                INSERT INTO support_queues
+                    (id, tenant_id, name, description, categories, is_default, auto_assign, online_only, created_at, updated_at)
+                VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$9)
+            "#,
+            )
+            .bind(id)
+            .bind(tenant_id)
+            .bind(&name)
+            .bind(&description)
+            .bind(&categories)
+            .bind(is_default)
+            .bind(dto.auto_assign.unwrap_or(true))
+            .bind(dto.online_only.unwrap_or(false))
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            sqlx::query(
+                r#"
+                UPDATE support_queues
+                SET name = $1,
+                    description = $2,
+                    categories = $3,
+                    is_default = $4,
+                    auto_assign = COALESCE($5, auto_assign),
+                    online_only = COALESCE($6, online_only),
+                    updated_at = $7
+                WHERE tenant_id = $8 AND id = $9
+            "#,
+            )
+            .bind(&name)
+            .bind(&description)
+            .bind(&categories)
+            .bind(is_default)
+            .bind(dto.auto_assign)
+            .bind(dto.online_only)
+            .bind(now)
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("DELETE FROM support_queue_members WHERE queue_id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for user_id in &member_ids {
+            sqlx::query(
+                "INSERT INTO support_queue_members (queue_id, user_id, created_at) VALUES ($1,$2,$3)",
+            )
+            .bind(id)
+            .bind(user_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The subset of `user_ids` who can work tickets in this tenant.
+    async fn agent_ids(&self, tenant_id: &str, user_ids: &[String]) -> AppResult<Vec<String>> {
+        if user_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT tm.user_id
+            FROM tenant_members tm
+            JOIN users u ON u.id = tm.user_id AND u.is_active = true
+            JOIN role_permissions rp ON rp.role_id = tm.role_id
+            WHERE tm.tenant_id = $1
+              AND tm.user_id = ANY($2)
+              AND rp.permission_id = ANY($3)
+        "#,
+        )
+        .bind(tenant_id)
+        .bind(user_ids)
+        .bind(["support:read_all", "support:reply"])
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids)
+    }
+
+    pub async fn ensure_agent(&self, tenant_id: &str, user_id: &str) -> AppResult<()> {
+        let agents = self.agent_ids(tenant_id, &[user_id.to_string()]).await?;
+        if agents.is_empty() {
+            return Err(AppError::Validation(
+                "Assignee must be a support agent in this tenant".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn ensure_queue(&self, tenant_id: &str, queue_id: &str) -> AppResult<()> {
+        self.get_queue(tenant_id, queue_id).await.map(|_| ())
+    }
+
+    /// Put a new ticket in its queue and, when the queue auto-assigns, pick
+    /// the next agent. Returns `None` when the tenant has no matching queue.
+    pub async fn route_ticket(
+        &self,
+        tenant_id: &str,
+        ticket_id: &str,
+        category: Option<&str>,
+    ) -> AppResult<Option<TicketRoute>> {
+        #[derive(sqlx::FromRow)]
+        struct QueueRow {
+            id: String,
+            auto_assign: bool,
+            online_only: bool,
+            last_assigned_to: Option<String>,
+        }
+
+        let category = normalize_category(category);
+        let mut tx = self.pool.begin().await?;
+
+        // Row lock serialises concurrent routing so the rotation doesn't skip or repeat.
+        let queue: Option<QueueRow> = sqlx::query_as(
+            r#"
+            SELECT id, auto_assign, online_only, last_assigned_to
+            FROM support_queues
+            WHERE tenant_id = $1
+              AND (is_default OR ($2::text IS NOT NULL AND $2 = ANY(categories)))
+            ORDER BY ($2::text IS NOT NULL AND $2 = ANY(categories)) DESC, created_at ASC
+            LIMIT 1
+            FOR UPDATE
+        "#,
+        )
+        .bind(tenant_id)
+        .bind(&category)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(queue) = queue else {
+            return Ok(None);
+        };
+
+        let mut assignee: Option<String> = None;
+        if queue.auto_assign {
+            let members: Vec<String> = sqlx::query_scalar(
+                r#"
+                SELECT m.user_id
+                FROM support_queue_members m
+                JOIN tenant_members tm ON tm.user_id = m.user_id AND tm.tenant_id = $2
+                JOIN users u ON u.id = m.user_id AND u.is_active = true
+                WHERE m.queue_id = $1
+                ORDER BY m.user_id
+            "#,
+            )
+            .bind(&queue.id)
+            .bind(tenant_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            // "Online" = holds an unexpired session; sessions slide while the agent is active.
+            let online: HashSet<String> = sqlx::query_scalar::<_, String>(
+                "SELECT DISTINCT user_id FROM sessions WHERE user_id = ANY($1) AND tenant_id = $2 AND expires_at > $3",
+            )
+            .bind(&members)
+            .bind(tenant_id)
+            .bind(Utc::now())
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .collect();
+
+            let last = queue.last_assigned_to.as_deref();
+            let everyone: HashSet<String> = members.iter().cloned().collect();
+            assignee = next_round_robin(&members, last, &online)
+                .or_else(|| {
+                    if queue.online_only {
+                        None
+                    } else {
+                        next_round_robin(&members, last, &everyone)
+                    }
+                })
+                .cloned();
+        }
+
+        let now = Utc::now();
+        let assigned_to: Option<String> = sqlx::query_scalar(
+            r#"
+            UPDATE support_tickets
+            SET queue_id = $1,
+                assigned_to = COALESCE(assigned_to, $2),
+                updated_at = $3
+            WHERE id = $4 AND tenant_id = $5
+            RETURNING assigned_to
+        "#,
+        )
+        .bind(&queue.id)
+        .bind(&assignee)
+        .bind(now)
+        .bind(ticket_id)
+        .bind(tenant_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let Some(user_id) = assignee.as_deref() {
+            sqlx::query(
+                "UPDATE support_queues SET last_assigned_to = $1, updated_at = $2 WHERE id = $3",
+            )
+            .bind(user_id)
+            .bind(now)
+            .bind(&queue.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(Some(TicketRoute {
+            queue_id: queue.id,
+            assigned_to,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn round_robin_rotates_and_wraps() {
+        let members = ids(&["a", "b", "c"]);
+        let all: HashSet<String> = members.iter().cloned().collect();
+
+        assert_eq!(next_round_robin(&members, None, &all).unwrap(), "a");
+        assert_eq!(next_round_robin(&members, Some("a"), &all).unwrap(), "b");
+        assert_eq!(next_round_robin(&members, Some("c"), &all).unwrap(), "a");
+        // "b" left the queue; rotation continues with the next id after it.
+        let members = ids(&["a", "c"]);
+        assert_eq!(next_round_robin(&members, Some("b"), &all).unwrap(), "c");
+    }
+
+    #[test]
+    fn round_robin_skips_ineligible_agents() {
+        let members = ids(&["a", "b", "c"]);
+        let online: HashSet<String> = ids(&["a"]).into_iter().collect();
+
+        assert_eq!(next_round_robin(&members, Some("a"), &online).unwrap(), "a");
+        assert!(next_round_robin(&members, None, &HashSet::new()).is_none());
+        assert!(next_round_robin(&[], None, &online).is_none());
+    }
+
+    #[test]
+    fn categories_are_normalized() {
+        assert_eq!(
+            normalize_category(Some("  Billing ")).as_deref(),
+            Some("billing")
+        );
+        assert_eq!(normalize_category(Some("   ")), None);
+        assert_eq!(normalize_category(None), None);
+    }
+}
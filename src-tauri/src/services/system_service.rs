@@ -1,5 +1,6 @@
 //! System Health & Monitoring Service
 
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 use sqlx::Pool;
@@ -7,17 +8,51 @@ use sqlx::Pool;
 use sqlx::Postgres;
 #[cfg(feature = "sqlite")]
 use sqlx::Sqlite;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use sysinfo::System;
 use tokio::sync::RwLock;
 
-use crate::services::SettingsService;
+use crate::db::migrations::{self, MigrationItem, MigrationStatus};
+use crate::db::{PoolConfig, QueryRouter};
+use crate::error::AppResult;
+use crate::models::BackgroundJob;
+use crate::services::alert_service::{disk_holding, list_disks};
+use crate::services::{JobHandler, JobQueue, SettingsService};
+
+/// Time-series tables and the column their rows are stamped with.
+const GROWTH_TABLES: &[(&str, &str)] = &[
+    ("mikrotik_router_metrics", "ts"),
+    ("mikrotik_interface_metrics", "ts"),
+    ("mikrotik_logs", "logged_at"),
+    ("audit_logs", "created_at"),
+];
+
+/// Delivery queues: table, due-time column, statuses still to be worked and
+/// statuses that gave up.
+const BACKLOG_QUEUES: &[(&str, &str, &str, &str)] = &[
+    (
+        "email_outbox",
+        "scheduled_at",
+        "'queued', 'sending'",
+        "'failed'",
+    ),
+    ("event_outbox", "next_attempt_at", "'pending'", "'failed'"),
+    ("background_jobs", "run_at", "'queued', 'failed'", "'dead'"),
+];
+
+/// Growth is measured against the oldest sample in this window...
+const GROWTH_WINDOW_DAYS: i64 = 7;
+/// ...once that sample is at least this old.
+const MIN_GROWTH_SPAN_HOURS: i64 = 12;
+const SAMPLE_RETENTION_DAYS: i64 = 30;
 
 #[derive(Debug, Serialize, Clone)]
 pub struct DatabaseStats {
     pub is_connected: bool,
     pub database_type: String,
+    pub read_replica_enabled: bool,
     pub database_size_bytes: i64,
     pub total_tables: i64,
     pub tenants_count: i64,
@@ -61,19 +96,11 @@ pub struct SystemHealth {
     pub collected_at: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub request_metrics: Option<crate::services::metrics_service::RequestMetrics>,
-}
-
-#[derive(Debug, Serialize, Clone)]
-pub struct MigrationItem {
-    pub version: i64,
-    pub description: String,
-    pub installed_on: DateTime<Utc>,
-    pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub execution_time_ms: Option<i64>,
+    pub pool_metrics: Option<crate::services::metrics_service::PoolMetrics>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, Default)]
 pub struct MigrationSummary {
     pub resolved_count: i64,
     pub applied_count: i64,
@@ -115,15 +142,165 @@ pub struct BackupSnapshot {
     pub tenant_retention_days: Option<i64>,
 }
 
+#[derive(Debug, Serialize, Clone)]
+pub struct ConnectionStateCount {
+    /// `active`, `idle`, `idle in transaction`, ... (`background` for workers without a state).
+    pub state: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ActiveQuery {
+    pub pid: i32,
+    pub state: String,
+    pub duration_seconds: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wait_event: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub application_name: Option<String>,
+    pub query: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SlowQuery {
+    /// Normalized statement text from pg_stat_statements (parameters replaced by `$n`).
+    pub query: String,
+    pub calls: i64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    pub rows: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_hit_ratio: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TableSize {
+    /// Partitions are folded into their parent table.
+    pub name: String,
+    pub total_bytes: i64,
+    pub table_bytes: i64,
+    pub index_bytes: i64,
+    pub estimated_rows: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ReplicationStatus {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub application_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_addr: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replay_lag_seconds: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replay_lag_bytes: Option<i64>,
+}
+
+/// "Why is it slow" data (Postgres only). Every part is best-effort; a query the
+/// database user may not run leaves its section empty instead of failing diagnostics.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct DatabasePerformance {
+    pub connections: Vec<ConnectionStateCount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_hit_ratio: Option<f64>,
+    /// Non-idle backends running (or holding a transaction open) for over 5 seconds.
+    pub long_running_queries: Vec<ActiveQuery>,
+    /// Top statements by total execution time; `None` when pg_stat_statements is unavailable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slow_queries: Option<Vec<SlowQuery>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slow_queries_unavailable: Option<String>,
+    pub largest_tables: Vec<TableSize>,
+    /// Standbys streaming from this primary (pg_stat_replication).
+    pub replicas: Vec<ReplicationStatus>,
+    /// Age of the last transaction replayed on the configured read replica. This
+    /// also grows while the primary is idle, so read it alongside `replicas`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_replica_lag_seconds: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TableGrowth {
+    pub name: String,
+    pub rows_last_24h: i64,
+    pub rows_last_7d: i64,
+    /// Daily average over the last 7 days.
+    pub rows_per_day: f64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct QueueBacklog {
+    pub name: String,
+    /// Items whose time has come but that have not been delivered or run.
+    pub due: i64,
+    pub oldest_due_at: Option<DateTime<Utc>>,
+    /// Items that ran out of attempts.
+    pub failed: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DiskSpace {
+    pub mount_point: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+/// Early warning for storage problems. Like `DatabasePerformance`, each part
+/// is best-effort.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct CapacityReport {
+    /// Every table, largest first.
+    pub table_sizes: Vec<TableSize>,
+    pub metric_growth: Vec<TableGrowth>,
+    pub backlogs: Vec<QueueBacklog>,
+    /// The disk holding the app data directory (SQLite file, backups, local
+    /// uploads). With a remote PostgreSQL server that is not the database's disk.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk: Option<DiskSpace>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_growth_bytes_per_day: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk_growth_bytes_per_day: Option<f64>,
+    /// Free space over the disk's growth rate. `None` until the hourly samples
+    /// span half a day, or while free space is not shrinking.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days_until_disk_full: Option<f64>,
+}
+
+/// The app's own connection pool: its tuning, which comes from `DB_POOL_*`
+/// and `DB_STATEMENT_TIMEOUT_MS` and is read-only here, and its current use.
+#[derive(Debug, Serialize, Clone)]
+pub struct PoolDiagnostics {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout_secs: u64,
+    pub idle_timeout_secs: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub statement_timeout_ms: Option<u64>,
+    pub open_connections: u32,
+    pub idle_connections: u32,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct SystemDiagnostics {
     pub database: DatabaseStats,
+    pub pool: PoolDiagnostics,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub database_server_version: Option<String>,
     pub migrations: MigrationSummary,
     pub applied_migrations: Vec<MigrationItem>,
     pub settings: SettingsSnapshot,
     pub backups: BackupSnapshot,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maintenance: Option<crate::services::db_maintenance_service::DatabaseMaintenanceSnapshot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub performance: Option<DatabasePerformance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<CapacityReport>,
     pub collected_at: DateTime<Utc>,
 }
 
@@ -133,6 +310,8 @@ pub struct SystemService {
     pub pool: Pool<Postgres>,
     #[cfg(feature = "sqlite")]
     pub pool: Pool<Sqlite>,
+    router: QueryRouter,
+    data_dir: Option<PathBuf>,
     start_time: Instant,
     cache: Arc<RwLock<Option<(SystemHealth, Instant)>>>,
     metrics: Arc<crate::services::metrics_service::MetricsService>,
@@ -144,8 +323,11 @@ impl SystemService {
         pool: Pool<Postgres>,
         metrics: Arc<crate::services::metrics_service::MetricsService>,
     ) -> Self {
+        let router = QueryRouter::new(pool.clone(), None);
         Self {
             pool,
+            router,
+            data_dir: None,
             start_time: Instant::now(),
             cache: Arc::new(RwLock::new(None)),
             metrics,
@@ -157,14 +339,40 @@ impl SystemService {
         pool: Pool<Sqlite>,
         metrics: Arc<crate::services::metrics_service::MetricsService>,
     ) -> Self {
+        let router = QueryRouter::new(pool.clone(), None);
         Self {
             pool,
+            router,
+            data_dir: None,
             start_time: Instant::now(),
             cache: Arc::new(RwLock::new(None)),
             metrics,
         }
     }
 
+    /// Route reporting queries (counts, table sizes, recent activity) through the given router.
+    pub fn with_query_router(mut self, router: QueryRouter) -> Self {
+        self.router = router;
+        self
+    }
+
+    /// Directory whose disk the capacity report watches.
+    pub fn with_data_dir(mut self, dir: PathBuf) -> Self {
+        self.data_dir = Some(dir);
+        self
+    }
+
+    /// Samples database size and free disk space hourly on the job queue.
+    pub async fn schedule_capacity_sampler(&self, queue: &JobQueue) {
+        queue
+            .register_recurring(
+                "system.capacity_sample",
+                Duration::from_secs(3600),
+                Arc::new(self.clone()),
+            )
+            .await;
+    }
+
     pub async fn get_system_health(&self) -> Result<SystemHealth, sqlx::Error> {
         const CACHE_TTL: Duration = Duration::from_secs(10);
 
@@ -192,6 +400,7 @@ impl SystemService {
             app_version: env!("CARGO_PKG_VERSION").to_string(),
             collected_at: Utc::now(),
             request_metrics: Some(self.metrics.get_metrics()),
+            pool_metrics: self.metrics.get_pool_metrics(),
         };
 
         *self.cache.write().await = Some((health.clone(), Instant::now()));
@@ -223,32 +432,21 @@ impl SystemService {
             None
         };
 
-        // Migrations: applied from DB + resolved from embedded migrator
-        let applied_migrations = self.get_applied_migrations().await.unwrap_or_default();
-        let latest_applied_version = applied_migrations.last().map(|m| m.version);
-
-        // Resolved migrations from embedded files. If this fails for any reason, treat as unknown.
-        let resolved_versions = self.get_resolved_migration_versions();
-
-        use std::collections::HashSet;
-        let applied_set: HashSet<i64> = applied_migrations.iter().map(|m| m.version).collect();
-        let resolved_set: HashSet<i64> = resolved_versions.iter().copied().collect();
-
-        let mut pending_versions: Vec<i64> =
-            resolved_set.difference(&applied_set).copied().collect();
-        pending_versions.sort_unstable();
-        let mut missing_versions: Vec<i64> =
-            applied_set.difference(&resolved_set).copied().collect();
-        missing_versions.sort_unstable();
-
-        let migrations = MigrationSummary {
-            resolved_count: resolved_versions.len() as i64,
-            applied_count: applied_migrations.len() as i64,
-            pending_count: pending_versions.len() as i64,
-            missing_count: missing_versions.len() as i64,
-            pending_versions,
-            missing_versions,
-            latest_applied_version,
+        // Migrations: applied from DB compared against the embedded migrator
+        let (migrations, applied_migrations) = match self.get_migration_status().await {
+            Ok(status) => (
+                MigrationSummary {
+                    resolved_count: status.resolved_count,
+                    applied_count: status.applied.len() as i64,
+                    pending_count: status.pending.len() as i64,
+                    missing_count: status.missing_versions.len() as i64,
+                    pending_versions: status.pending.iter().map(|m| m.version).collect(),
+                    missing_versions: status.missing_versions,
+                    latest_applied_version: status.latest_applied_version,
+                },
+                status.applied,
+            ),
+            Err(_) => (MigrationSummary::default(), Vec::new()),
         };
 
         let settings = SettingsSnapshot {
@@ -281,17 +479,455 @@ impl SystemService {
 
         let backups = self.get_backup_snapshot(settings_service).await;
 
+        // The environment is fixed for the process, so this is what the pool
+        // was built with.
+        let config = PoolConfig::from_env();
+        let pool = PoolDiagnostics {
+            max_connections: config.max_connections,
+            min_connections: config.min_connections,
+            acquire_timeout_secs: config.acquire_timeout.as_secs(),
+            idle_timeout_secs: config.idle_timeout.as_secs(),
+            statement_timeout_ms: config.statement_timeout.map(|t| t.as_millis() as u64),
+            open_connections: self.pool.size(),
+            idle_connections: self.pool.num_idle() as u32,
+        };
+
+        #[cfg(feature = "postgres")]
+        let performance = if database.is_connected {
+            Some(self.get_database_performance().await)
+        } else {
+            None
+        };
+        #[cfg(feature = "sqlite")]
+        let performance = None;
+
+        let capacity = if database.is_connected {
+            Some(self.get_capacity_report(database.database_size_bytes).await)
+        } else {
+            None
+        };
+
         Ok(SystemDiagnostics {
             database,
+            pool,
             database_server_version,
             migrations,
             applied_migrations,
             settings,
             backups,
+            maintenance: None,
+            performance,
+            capacity,
             collected_at: Utc::now(),
         })
     }
 
+    #[cfg(feature = "postgres")]
+    async fn get_database_performance(&self) -> DatabasePerformance {
+        const LONG_RUNNING_SECS: f64 = 5.0;
+        const TOP_N: i64 = 10;
+
+        let mut perf = DatabasePerformance::default();
+
+        match sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT COALESCE(state, 'background'), COUNT(*)
+            FROM pg_stat_activity
+            WHERE datname = current_database()
+            GROUP BY 1
+            ORDER BY 2 DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(rows) => {
+                perf.connections = rows
+                    .into_iter()
+                    .map(|(state, count)| ConnectionStateCount { state, count })
+                    .collect()
+            }
+            Err(e) => tracing::warn!("Diagnostics: connection states unavailable: {}", e),
+        }
+
+        perf.max_connections =
+            sqlx::query_scalar("SELECT current_setting('max_connections')::bigint")
+                .fetch_one(&self.pool)
+                .await
+                .ok();
+
+        perf.cache_hit_ratio = sqlx::query_scalar::<_, Option<f64>>(
+            r#"
+            SELECT blks_hit::float8 / NULLIF(blks_hit + blks_read, 0)
+            FROM pg_stat_database
+            WHERE datname = current_database()
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .ok()
+        .flatten();
+
+        #[derive(sqlx::FromRow)]
+        struct ActivityRow {
+            pid: i32,
+            state: String,
+            duration_seconds: f64,
+            wait_event: Option<String>,
+            application_name: Option<String>,
+            query: String,
+        }
+        match sqlx::query_as::<_, ActivityRow>(
+            r#"
+            SELECT
+                pid,
+                state,
+                EXTRACT(EPOCH FROM (now() - COALESCE(query_start, xact_start)))::float8 AS duration_seconds,
+                NULLIF(CONCAT_WS(':', wait_event_type, wait_event), '') AS wait_event,
+                NULLIF(application_name, '') AS application_name,
+                COALESCE(query, '') AS query
+            FROM pg_stat_activity
+            WHERE datname = current_database()
+              AND pid <> pg_backend_pid()
+              AND state IS NOT NULL
+              AND state <> 'idle'
+              AND now() - COALESCE(query_start, xact_start) > make_interval(secs => $1)
+            ORDER BY duration_seconds DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(LONG_RUNNING_SECS)
+        .bind(TOP_N)
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(rows) => {
+                perf.long_running_queries = rows
+                    .into_iter()
+                    .map(|r| ActiveQuery {
+                        pid: r.pid,
+                        state: r.state,
+                        duration_seconds: r.duration_seconds,
+                        wait_event: r.wait_event,
+                        application_name: r.application_name,
+                        query: truncate_query(&r.query),
+                    })
+                    .collect()
+            }
+            Err(e) => tracing::warn!("Diagnostics: active queries unavailable: {}", e),
+        }
+
+        let has_pg_stat_statements: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'pg_stat_statements')",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .unwrap_or(false);
+
+        if has_pg_stat_statements {
+            #[derive(sqlx::FromRow)]
+            struct StatementRow {
+                query: String,
+                calls: i64,
+                total_ms: f64,
+                mean_ms: f64,
+                max_ms: f64,
+                rows: i64,
+                cache_hit_ratio: Option<f64>,
+            }
+            // Column names are the Postgres 13+ ones.
+            match sqlx::query_as::<_, StatementRow>(
+                r#"
+                SELECT
+                    query,
+                    calls,
+                    total_exec_time AS total_ms,
+                    mean_exec_time AS mean_ms,
+                    max_exec_time AS max_ms,
+                    rows,
+                    shared_blks_hit::float8 / NULLIF(shared_blks_hit + shared_blks_read, 0) AS cache_hit_ratio
+                FROM pg_stat_statements
+                WHERE dbid = (SELECT oid FROM pg_database WHERE datname = current_database())
+                ORDER BY total_exec_time DESC
+                LIMIT $1
+                "#,
+            )
+            .bind(TOP_N)
+            .fetch_all(&self.pool)
+            .await
+            {
+                Ok(rows) => {
+                    perf.slow_queries = Some(
+                        rows.into_iter()
+                            .map(|r| SlowQuery {
+                                query: truncate_query(&r.query),
+                                calls: r.calls,
+                                total_ms: r.total_ms,
+                                mean_ms: r.mean_ms,
+                                max_ms: r.max_ms,
+                                rows: r.rows,
+                                cache_hit_ratio: r.cache_hit_ratio,
+                            })
+                            .collect(),
+                    )
+                }
+                // Typically "must be loaded via shared_preload_libraries".
+                Err(e) => perf.slow_queries_unavailable = Some(e.to_string()),
+            }
+        } else {
+            perf.slow_queries_unavailable =
+                Some("pg_stat_statements extension is not installed".to_string());
+        }
+
+        match self.get_table_sizes(TOP_N).await {
+            Ok(sizes) => perf.largest_tables = sizes,
+            Err(e) => tracing::warn!("Diagnostics: table sizes unavailable: {}", e),
+        }
+
+        #[derive(sqlx::FromRow)]
+        struct ReplicationRow {
+            application_name: Option<String>,
+            client_addr: Option<String>,
+            state: Option<String>,
+            replay_lag_seconds: Option<f64>,
+            replay_lag_bytes: Option<i64>,
+        }
+        match sqlx::query_as::<_, ReplicationRow>(
+            r#"
+            SELECT
+                NULLIF(application_name, '') AS application_name,
+                client_addr::text AS client_addr,
+                state,
+                EXTRACT(EPOCH FROM replay_lag)::float8 AS replay_lag_seconds,
+                pg_wal_lsn_diff(pg_current_wal_lsn(), replay_lsn)::bigint AS replay_lag_bytes
+            FROM pg_stat_replication
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(rows) => {
+                perf.replicas = rows
+                    .into_iter()
+                    .map(|r| ReplicationStatus {
+                        application_name: r.application_name,
+                        client_addr: r.client_addr,
+                        state: r.state,
+                        replay_lag_seconds: r.replay_lag_seconds,
+                        replay_lag_bytes: r.replay_lag_bytes,
+                    })
+                    .collect()
+            }
+            Err(e) => tracing::warn!("Diagnostics: replication status unavailable: {}", e),
+        }
+
+        if self.router.has_replica() {
+            perf.read_replica_lag_seconds = sqlx::query_scalar::<_, Option<f64>>(
+                r#"
+                SELECT CASE WHEN pg_is_in_recovery()
+                    THEN EXTRACT(EPOCH FROM (now() - pg_last_xact_replay_timestamp()))::float8
+                END
+                "#,
+            )
+            .fetch_one(self.router.reader())
+            .await
+            .ok()
+            .flatten();
+        }
+
+        perf
+    }
+
+    /// Table sizes, largest first. Postgres folds partitions into their parent.
+    async fn get_table_sizes(&self, limit: i64) -> Result<Vec<TableSize>, sqlx::Error> {
+        #[derive(sqlx::FromRow)]
+        struct SizeRow {
+            name: String,
+            total_bytes: i64,
+            table_bytes: i64,
+            index_bytes: i64,
+            estimated_rows: i64,
+        }
+
+        #[cfg(feature = "postgres")]
+        let sql = r#"
+            SELECT
+                COALESCE(parent.relname, c.relname)::text AS name,
+                SUM(pg_total_relation_size(c.oid))::bigint AS total_bytes,
+                SUM(pg_relation_size(c.oid))::bigint AS table_bytes,
+                SUM(pg_indexes_size(c.oid))::bigint AS index_bytes,
+                SUM(GREATEST(c.reltuples, 0))::bigint AS estimated_rows
+            FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            LEFT JOIN pg_inherits i ON i.inhrelid = c.oid
+            LEFT JOIN pg_class parent ON parent.oid = i.inhparent
+            WHERE n.nspname = 'public' AND c.relkind = 'r'
+            GROUP BY 1
+            ORDER BY total_bytes DESC
+            LIMIT $1
+            "#;
+        // dbstat reports each b-tree by its own name; indexes are charged to
+        // their table. SQLite keeps no row estimate (see `tables` for counts).
+        #[cfg(feature = "sqlite")]
+        let sql = r#"
+            SELECT
+                COALESCE(m.tbl_name, d.name) AS name,
+                SUM(d.pgsize) AS total_bytes,
+                SUM(CASE WHEN m.type = 'index' THEN 0 ELSE d.pgsize END) AS table_bytes,
+                SUM(CASE WHEN m.type = 'index' THEN d.pgsize ELSE 0 END) AS index_bytes,
+                0 AS estimated_rows
+            FROM dbstat d
+            LEFT JOIN sqlite_master m ON m.name = d.name
+            GROUP BY 1
+            ORDER BY total_bytes DESC
+            LIMIT $1
+            "#;
+
+        let rows = sqlx::query_as::<_, SizeRow>(sql)
+            .bind(limit)
+            .fetch_all(self.router.reader())
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|r| TableSize {
+                name: r.name,
+                total_bytes: r.total_bytes,
+                table_bytes: r.table_bytes,
+                index_bytes: r.index_bytes,
+                estimated_rows: r.estimated_rows,
+            })
+            .collect())
+    }
+
+    async fn get_capacity_report(&self, database_bytes: i64) -> CapacityReport {
+        const MAX_TABLES: i64 = 500;
+
+        let mut report = CapacityReport::default();
+        let now = Utc::now();
+
+        match self.get_table_sizes(MAX_TABLES).await {
+            Ok(sizes) => report.table_sizes = sizes,
+            Err(e) => tracing::warn!("Diagnostics: table sizes unavailable: {}", e),
+        }
+
+        for (table, column) in GROWTH_TABLES {
+            let sql = format!(
+                "SELECT COUNT(*), COALESCE(SUM(CASE WHEN {column} >= $1 THEN 1 ELSE 0 END), 0) \
+                 FROM {table} WHERE {column} >= $2"
+            );
+            match sqlx::query_as::<_, (i64, i64)>(&sql)
+                .bind(now - chrono::Duration::days(1))
+                .bind(now - chrono::Duration::days(GROWTH_WINDOW_DAYS))
+                .fetch_one(self.router.reader())
+                .await
+            {
+                Ok((week, day)) => report.metric_growth.push(TableGrowth {
+                    name: table.to_string(),
+                    rows_last_24h: day,
+                    rows_last_7d: week,
+                    rows_per_day: week as f64 / GROWTH_WINDOW_DAYS as f64,
+                }),
+                // Not every backend has every table (no MikroTik tables on SQLite).
+                Err(e) => tracing::debug!("Diagnostics: growth of {} unavailable: {}", table, e),
+            }
+        }
+
+        for (table, due_column, pending, failed) in BACKLOG_QUEUES {
+            let due = sqlx::query_as::<_, (i64, Option<DateTime<Utc>>)>(&format!(
+                "SELECT COUNT(*), MIN({due_column}) FROM {table} \
+                 WHERE status IN ({pending}) AND {due_column} <= $1"
+            ))
+            .bind(now)
+            .fetch_one(&self.pool)
+            .await;
+            let failed = sqlx::query_scalar::<_, i64>(&format!(
+                "SELECT COUNT(*) FROM {table} WHERE status IN ({failed})"
+            ))
+            .fetch_one(&self.pool)
+            .await;
+            match (due, failed) {
+                (Ok((due, oldest_due_at)), Ok(failed)) => report.backlogs.push(QueueBacklog {
+                    name: table.to_string(),
+                    due,
+                    oldest_due_at,
+                    failed,
+                }),
+                (Err(e), _) | (_, Err(e)) => {
+                    tracing::debug!("Diagnostics: backlog of {} unavailable: {}", table, e)
+                }
+            }
+        }
+
+        report.disk = self.current_disk();
+
+        let baseline = sqlx::query_as::<_, (DateTime<Utc>, i64, Option<i64>)>(
+            r#"
+            SELECT sampled_at, database_bytes, disk_available_bytes
+            FROM system_capacity_samples
+            WHERE sampled_at >= $1
+            ORDER BY sampled_at ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(now - chrono::Duration::days(GROWTH_WINDOW_DAYS))
+        .fetch_optional(&self.pool)
+        .await;
+        match baseline {
+            Ok(Some((sampled_at, then_database, then_available))) => {
+                report.database_growth_bytes_per_day =
+                    growth_per_day(sampled_at, then_database, now, database_bytes);
+                if let (Some(then_available), Some(disk)) = (then_available, &report.disk) {
+                    // Space used is what grows; free space shrinks by as much.
+                    report.disk_growth_bytes_per_day = growth_per_day(
+                        sampled_at,
+                        -then_available,
+                        now,
+                        -(disk.available_bytes as i64),
+                    );
+                    report.days_until_disk_full =
+                        days_until_full(disk.available_bytes, report.disk_growth_bytes_per_day);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Diagnostics: capacity samples unavailable: {}", e),
+        }
+
+        report
+    }
+
+    fn current_disk(&self) -> Option<DiskSpace> {
+        let dir = self.data_dir.as_ref()?;
+        let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.clone());
+        let disks = list_disks();
+        disk_holding(&disks, &dir).map(|(mount, total, available)| DiskSpace {
+            mount_point: mount.display().to_string(),
+            total_bytes: *total,
+            available_bytes: *available,
+        })
+    }
+
+    async fn record_capacity_sample(&self) -> AppResult<()> {
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO system_capacity_samples (id, sampled_at, database_bytes, disk_available_bytes)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(now)
+        .bind(self.get_database_size().await)
+        .bind(self.current_disk().map(|d| d.available_bytes as i64))
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("DELETE FROM system_capacity_samples WHERE sampled_at < $1")
+            .bind(now - chrono::Duration::days(SAMPLE_RETENTION_DAYS))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     async fn get_database_stats(&self) -> Result<DatabaseStats, sqlx::Error> {
         // Test connection with simple query
         let is_connected = sqlx::query_scalar::<_, i32>("SELECT 1")
@@ -306,7 +942,7 @@ impl SystemService {
                     (SELECT COUNT(*) FROM users) AS users_count, \
                     (SELECT COUNT(*) FROM audit_logs) AS audit_logs_count",
             )
-            .fetch_one(&self.pool)
+            .fetch_one(self.router.reader())
             .await
             .unwrap_or((0, 0, 0))
         } else {
@@ -328,6 +964,7 @@ impl SystemService {
         Ok(DatabaseStats {
             is_connected,
             database_type,
+            read_replica_enabled: self.router.has_replica(),
             database_size_bytes,
             total_tables,
             tenants_count,
@@ -336,53 +973,9 @@ impl SystemService {
         })
     }
 
-    fn get_resolved_migration_versions(&self) -> Vec<i64> {
-        // Best-effort: if the migrations aren't available (shouldn't happen in dev),
-        // diagnostics will still show applied migrations from DB.
-        static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations");
-
-        MIGRATOR.iter().map(|m| m.version).collect()
-    }
-
-    async fn get_applied_migrations(&self) -> Result<Vec<MigrationItem>, sqlx::Error> {
-        #[derive(sqlx::FromRow)]
-        struct Row {
-            version: i64,
-            description: String,
-            installed_on: DateTime<Utc>,
-            success: bool,
-            execution_time: Option<i64>,
-        }
-
-        let rows: Vec<Row> = match sqlx::query_as::<_, Row>(
-            "SELECT version, description, installed_on, success, execution_time FROM _sqlx_migrations ORDER BY version",
-        )
-        .fetch_all(&self.pool)
-        .await
-        {
-            Ok(r) => r,
-            Err(e) => {
-                // Missing table on fresh DB before migrations: treat as none.
-                #[cfg(feature = "postgres")]
-                if let sqlx::Error::Database(db) = &e {
-                    if db.code().as_deref() == Some("42P01") {
-                        return Ok(Vec::new());
-                    }
-                }
-                return Err(e);
-            }
-        };
-
-        Ok(rows
-            .into_iter()
-            .map(|r| MigrationItem {
-                version: r.version,
-                description: r.description,
-                installed_on: r.installed_on,
-                success: r.success,
-                execution_time_ms: r.execution_time.map(|v| v / 1_000_000),
-            })
-            .collect())
+    /// Applied vs. embedded migrations, including pending and failed versions.
+    pub async fn get_migration_status(&self) -> Result<MigrationStatus, sqlx::Error> {
+        migrations::migration_status(&self.pool).await
     }
 
     async fn get_backup_snapshot(&self, settings_service: &SettingsService) -> BackupSnapshot {
@@ -456,7 +1049,7 @@ impl SystemService {
             UNION ALL SELECT 'audit_logs', (SELECT COUNT(*) FROM audit_logs)";
 
         match sqlx::query_as::<_, TableRow>(union_query)
-            .fetch_all(&self.pool)
+            .fetch_all(self.router.reader())
             .await
         {
             Ok(rows) => Ok(rows
@@ -481,7 +1074,7 @@ impl SystemService {
 
                 for name in table_names {
                     let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", name))
-                        .fetch_one(&self.pool)
+                        .fetch_one(self.router.reader())
                         .await
                         .unwrap_or(0);
 
@@ -526,7 +1119,7 @@ impl SystemService {
                  ORDER BY a.created_at DESC LIMIT $1",
             )
             .bind(limit)
-            .fetch_all(&self.pool)
+            .fetch_all(self.router.reader())
             .await
             .unwrap_or_default();
 
@@ -560,7 +1153,7 @@ impl SystemService {
                  ORDER BY a.created_at DESC LIMIT $1",
             )
             .bind(limit)
-            .fetch_all(&self.pool)
+            .fetch_all(self.router.reader())
             .await
             .unwrap_or_default();
 
@@ -643,3 +1236,81 @@ impl SystemService {
         }
     }
 }
+
+#[async_trait]
+impl JobHandler for SystemService {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        self.record_capacity_sample().await
+    }
+}
+
+/// Change per day between two readings, once they are far enough apart to
+/// mean something.
+fn growth_per_day(
+    then: DateTime<Utc>,
+    then_value: i64,
+    now: DateTime<Utc>,
+    now_value: i64,
+) -> Option<f64> {
+    let span = now - then;
+    if span < chrono::Duration::hours(MIN_GROWTH_SPAN_HOURS) {
+        return None;
+    }
+    Some((now_value - then_value) as f64 * 86_400.0 / span.num_seconds() as f64)
+}
+
+fn days_until_full(available_bytes: u64, growth_per_day: Option<f64>) -> Option<f64> {
+    growth_per_day
+        .filter(|growth| *growth > 0.0)
+        .map(|growth| available_bytes as f64 / growth)
+}
+
+/// Statement text is capped so one generated mega-query can't bloat the payload.
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+fn truncate_query(query: &str) -> String {
+    const MAX_CHARS: usize = 500;
+    let query = query.trim();
+    match query.char_indices().nth(MAX_CHARS) {
+        Some((idx, _)) => format!("{}…", &query[..idx]),
+        None => query.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_query() {
+        assert_eq!(truncate_query("  SELECT 1  "), "SELECT 1");
+
+        let long = "é".repeat(600);
+        let cut = truncate_query(&long);
+        assert_eq!(cut.chars().count(), 501);
+        assert!(cut.ends_with('…'));
+
+        let exact = "x".repeat(500);
+        assert_eq!(truncate_query(&exact), exact);
+    }
+
+    #[test]
+    fn projects_growth_and_days_until_full() {
+        let now = Utc::now();
+        let gib: i64 = 1 << 30;
+
+        // 2 GiB more over two days is 1 GiB a day.
+        let growth = growth_per_day(now - chrono::Duration::days(2), 10 * gib, now, 12 * gib);
+        assert_eq!(growth, Some(gib as f64));
+        assert_eq!(days_until_full(30 * gib as u64, growth), Some(30.0));
+
+        // Samples too close together say nothing yet.
+        assert_eq!(
+            growth_per_day(now - chrono::Duration::hours(2), 0, now, gib),
+            None
+        );
+        // A disk that is not filling up is never full.
+        assert_eq!(days_until_full(gib as u64, Some(-5.0)), None);
+        assert_eq!(days_until_full(gib as u64, Some(0.0)), None);
+        assert_eq!(days_until_full(gib as u64, None), None);
+    }
+}
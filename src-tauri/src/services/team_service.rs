@@ -2,7 +2,7 @@
 
 use crate::db::DbPool;
 use crate::models::{TeamMemberWithUser, User};
-use crate::services::{AuditService, AuthService, PlanService};
+use crate::services::{AuditService, AuthService, PlanService, UsageMetric, UsageService};
 use chrono::Utc;
 use uuid::Uuid;
 
@@ -13,6 +13,7 @@ pub struct TeamService {
     auth_service: AuthService,
     audit_service: AuditService,
     plan_service: PlanService,
+    usage: UsageService,
 }
 
 impl TeamService {
@@ -23,6 +24,7 @@ impl TeamService {
         plan_service: PlanService,
     ) -> Self {
         Self {
+            usage: UsageService::new(pool.clone()),
             pool,
             auth_service,
             audit_service,
@@ -151,28 +153,11 @@ impl TeamService {
         ip_address: Option<&str>,
     ) -> Result<TeamMemberWithUser, String> {
         // 0. Check Plan Limits (max_users)
-        let limit = self
-            .plan_service
-            .get_feature_limit(tenant_id, "max_users")
+        self.usage
+            .enforce(tenant_id, UsageMetric::Users, 1)
             .await
             .map_err(|e| e.to_string())?;
 
-        if let Some(max_users) = limit {
-            let count: i64 =
-                sqlx::query_scalar("SELECT COUNT(*) FROM tenant_members WHERE tenant_id = $1")
-                    .bind(tenant_id)
-                    .fetch_one(&self.pool)
-                    .await
-                    .map_err(|e| e.to_string())?;
-
-            if count >= max_users {
-                return Err(format!(
-                    "Plan limit reached: Maximum {} users allowed.",
-                    max_users
-                ));
-            }
-        }
-
         // 1. Check if user exists
         let existing_user: Option<User> = sqlx::query_as("SELECT * FROM users WHERE email = $1")
             .bind(email)
@@ -0,0 +1,860 @@
+//! Telegram bot for tenant admins.
+//!
+//! One platform-wide bot (`telegram_bot_token`). A tenant member links their chat
+//! by sending the bot `/start <code>` with a one-time code from their settings page.
+//! Linked chats receive incident and payment alerts (each can be muted per link)
+//! and can run a few commands: `/status`, `/incidents`, `/ack`.
+//!
+//! Updates arrive by long polling, or through `POST /api/telegram/webhook` when
+//! `telegram_webhook_secret` is set (Telegram disables `getUpdates` while a
+//! webhook is registered).
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    MikrotikIncident, TelegramLink, TelegramLinkStatus, UpdateTelegramLinkRequest,
+};
+use crate::services::leader_lock::LeaderLock;
+use crate::services::{AuthService, MikrotikService, SettingsService};
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+const API_BASE: &str = "https://api.telegram.org";
+const LINK_CODE_TTL_MINUTES: i64 = 15;
+const LINK_CODE_LEN: usize = 8;
+/// No 0/O or 1/I, so codes survive being read off a screen.
+const LINK_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const POLL_TIMEOUT_SECS: u64 = 25;
+const MAX_INCIDENTS_LISTED: u32 = 10;
+
+/// Kinds of alerts a linked chat can mute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelegramAlert {
+    Incident,
+    Payment,
+}
+
+impl TelegramAlert {
+    fn column(&self) -> &'static str {
+        match self {
+            TelegramAlert::Incident => "alert_incidents",
+            TelegramAlert::Payment => "alert_payments",
+        }
+    }
+}
+
+/// Linking and outgoing messages. Lives in `NotificationService` so alert
+/// producers can reach it.
+#[derive(Clone)]
+pub struct TelegramService {
+    pool: DbPool,
+    settings_service: SettingsService,
+    http: reqwest::Client,
+}
+
+impl TelegramService {
+    pub fn new(pool: DbPool, settings_service: SettingsService) -> Self {
+        let http = reqwest::Client::builder()
+            // Long polls hold the request open for POLL_TIMEOUT_SECS.
+            .timeout(std::time::Duration::from_secs(POLL_TIMEOUT_SECS + 15))
+            .build()
+            .unwrap_or_default();
+        Self {
+            pool,
+            settings_service,
+            http,
+        }
+    }
+
+    async fn setting(&self, key: &str) -> Option<String> {
+        self.settings_service
+            .get_value(None, key)
+            .await
+            .ok()
+            .flatten()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    }
+
+    /// The bot token, or `None` when the bot is disabled or has no token.
+    async fn bot_token(&self) -> Option<String> {
+        let enabled = self.setting("telegram_enabled").await.is_some_and(|v| {
+            matches!(v.to_ascii_lowercase().as_str(), "true" | "1" | "yes" | "on")
+        });
+        if !enabled {
+            return None;
+        }
+        self.setting("telegram_bot_token").await
+    }
+
+    /// Whether the bot is enabled and has a token, i.e. messages actually go out.
+    pub async fn is_active(&self) -> bool {
+        self.bot_token().await.is_some()
+    }
+
+    async fn call(&self, token: &str, method: &str, body: &Value) -> Result<Value, String> {
+        let url = format!("{}/bot{}/{}", API_BASE, token, method);
+        let response = self
+            .http
+            .post(&url)
+            .json(body)
+            .send()
+            .await
+            // reqwest errors include the URL, which contains the token.
+            .map_err(|e| format!("{} request failed: {}", method, e.without_url()))?;
+        let payload: Value = response.json().await.unwrap_or(Value::Null);
+        if payload["ok"].as_bool() != Some(true) {
+            return Err(payload["description"]
+                .as_str()
+                .unwrap_or("unknown error")
+                .to_string());
+        }
+        Ok(payload["result"].clone())
+    }
+
+    /// Send plain text to a chat. Does nothing when the bot is off.
+    pub async fn send_message(&self, chat_id: &str, text: &str) -> Result<(), String> {
+        let Some(token) = self.bot_token().await else {
+            return Ok(());
+        };
+        self.call(
+            &token,
+            "sendMessage",
+            &json!({
+                "chat_id": chat_id,
+                "text": text,
+                "disable_web_page_preview": true,
+            }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Send an alert to the linked chats of `user_ids` that have not muted `kind`.
+    /// The caller picks the audience, as it does for in-app notifications.
+    pub async fn send_alert(
+        &self,
+        tenant_id: &str,
+        kind: TelegramAlert,
+        user_ids: &[String],
+        text: &str,
+    ) {
+        if user_ids.is_empty() || self.bot_token().await.is_none() {
+            return;
+        }
+        let sql = format!(
+            "SELECT user_id, chat_id FROM telegram_links WHERE tenant_id = $1 AND chat_id IS NOT NULL AND {} = $2",
+            kind.column()
+        );
+        let rows: Vec<(String, String)> = match sqlx::query_as(&sql)
+            .bind(tenant_id)
+            .bind(true)
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                warn!(
+                    "Failed to load Telegram links (tenant={}): {}",
+                    tenant_id, e
+                );
+                return;
+            }
+        };
+
+        for (user_id, chat_id) in rows {
+            if !user_ids.contains(&user_id) {
+                continue;
+            }
+            if let Err(e) = self.send_message(&chat_id, text).await {
+                warn!("Telegram alert to user {} failed: {}", user_id, e);
+            }
+        }
+    }
+
+    // ================= Linking =================
+
+    async fn find_link(&self, tenant_id: &str, user_id: &str) -> AppResult<Option<TelegramLink>> {
+        let link = sqlx::query_as::<_, TelegramLink>(
+            "SELECT * FROM telegram_links WHERE tenant_id = $1 AND user_id = $2",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(link)
+    }
+
+    async fn link_status(&self, link: Option<TelegramLink>) -> TelegramLinkStatus {
+        let bot_enabled = self.bot_token().await.is_some();
+        let bot_username = self
+            .setting("telegram_bot_username")
+            .await
+            .map(|u| u.trim_start_matches('@').to_string());
+        let now = Utc::now();
+
+        let (link_code, link_code_expires_at) = match &link {
+            Some(l) if l.link_code_expires_at.is_some_and(|at| at > now) => {
+                (l.link_code.clone(), l.link_code_expires_at)
+            }
+            _ => (None, None),
+        };
+        let link_url = match (&bot_username, &link_code) {
+            (Some(bot), Some(code)) => Some(format!("https://t.me/{}?start={}", bot, code)),
+            _ => None,
+        };
+
+        TelegramLinkStatus {
+            bot_enabled,
+            bot_username,
+            linked: link.as_ref().is_some_and(|l| l.chat_id.is_some()),
+            telegram_username: link.as_ref().and_then(|l| l.telegram_username.clone()),
+            linked_at: link.as_ref().and_then(|l| l.linked_at),
+            alert_incidents: link.as_ref().map(|l| l.alert_incidents).unwrap_or(true),
+            alert_payments: link.as_ref().map(|l| l.alert_payments).unwrap_or(true),
+            link_code,
+            link_code_expires_at,
+            link_url,
+        }
+    }
+
+    pub async fn get_link(&self, tenant_id: &str, user_id: &str) -> AppResult<TelegramLinkStatus> {
+        let link = self.find_link(tenant_id, user_id).await?;
+        Ok(self.link_status(link).await)
+    }
+
+    /// Issue a fresh one-time link code. Re-linking moves alerts to the new chat.
+    pub async fn create_link_code(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+    ) -> AppResult<TelegramLinkStatus> {
+        if self.bot_token().await.is_none() {
+            return Err(AppError::Validation(
+                "The Telegram bot is not enabled".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        let code = generate_link_code();
+        let expires_at = now + Duration::minutes(LINK_CODE_TTL_MINUTES);
+
+        if self.find_link(tenant_id, user_id).await?.is_some() {
+            sqlx::query(
+                r#"
+                UPDATE telegram_links
+                SET link_code = $1, link_code_expires_at = $2, updated_at = $3
+                WHERE tenant_id = $4 AND user_id = $5
+                "#,
+            )
+            .bind(&code)
+            .bind(expires_at)
+            .bind(now)
+            .bind(tenant_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        } else {
+            sqlx::query(
+                r#"
+                INSERT INTO telegram_links
+                (id, tenant_id, user_id, link_code, link_code_expires_at, alert_incidents, alert_payments, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(tenant_id)
+            .bind(user_id)
+            .bind(&code)
+            .bind(expires_at)
+            .bind(true)
+            .bind(true)
+            .bind(now)
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        self.get_link(tenant_id, user_id).await
+    }
+
+    pub async fn update_link(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        req: UpdateTelegramLinkRequest,
+    ) -> AppResult<TelegramLinkStatus> {
+        let link = self
+            .find_link(tenant_id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Telegram is not linked".to_string()))?;
+
+        sqlx::query(
+            r#"
+            UPDATE telegram_links
+            SET alert_incidents = $1, alert_payments = $2, updated_at = $3
+            WHERE id = $4
+            "#,
+        )
+        .bind(req.alert_incidents.unwrap_or(link.alert_incidents))
+        .bind(req.alert_payments.unwrap_or(link.alert_payments))
+        .bind(Utc::now())
+        .bind(&link.id)
+        .execute(&self.pool)
+        .await?;
+
+        self.get_link(tenant_id, user_id).await
+    }
+
+    pub async fn unlink(&self, tenant_id: &str, user_id: &str) -> AppResult<()> {
+        let link = self.find_link(tenant_id, user_id).await?;
+        sqlx::query("DELETE FROM telegram_links WHERE tenant_id = $1 AND user_id = $2")
+            .bind(tenant_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if let Some(chat_id) = link.and_then(|l| l.chat_id) {
+            let _ = self
+                .send_message(&chat_id, "This chat was unlinked from the ISP dashboard.")
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Bind the chat to the link that issued `code`. A chat belongs to one
+    /// tenant member at a time, so older links of the chat are dropped.
+    async fn redeem_link_code(
+        &self,
+        code: &str,
+        chat_id: &str,
+        username: Option<&str>,
+    ) -> AppResult<Option<TelegramLink>> {
+        let now = Utc::now();
+        let link =
+            sqlx::query_as::<_, TelegramLink>("SELECT * FROM telegram_links WHERE link_code = $1")
+                .bind(code.trim().to_ascii_uppercase())
+                .fetch_optional(&self.pool)
+                .await?;
+        let Some(link) = link.filter(|l| l.link_code_expires_at.is_some_and(|at| at > now)) else {
+            return Ok(None);
+        };
+
+        sqlx::query("DELETE FROM telegram_links WHERE chat_id = $1 AND id <> $2")
+            .bind(chat_id)
+            .bind(&link.id)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            r#"
+            UPDATE telegram_links
+            SET chat_id = $1, telegram_username = $2, linked_at = $3,
+                link_code = NULL, link_code_expires_at = NULL, updated_at = $4
+            WHERE id = $5
+            "#,
+        )
+        .bind(chat_id)
+        .bind(username)
+        .bind(now)
+        .bind(now)
+        .bind(&link.id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(Some(link))
+    }
+
+    async fn link_for_chat(&self, chat_id: &str) -> AppResult<Option<TelegramLink>> {
+        let link = sqlx::query_as::<_, TelegramLink>(
+            "SELECT * FROM telegram_links WHERE chat_id = $1 LIMIT 1",
+        )
+        .bind(chat_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(link)
+    }
+
+    async fn unlink_chat(&self, chat_id: &str) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM telegram_links WHERE chat_id = $1")
+            .bind(chat_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Handles bot updates (commands from linked chats).
+#[derive(Clone)]
+pub struct TelegramBot {
+    telegram: TelegramService,
+    auth_service: AuthService,
+    mikrotik_service: MikrotikService,
+    poll_offset: Arc<AtomicI64>,
+}
+
+impl TelegramBot {
+    pub fn new(
+        telegram: TelegramService,
+        auth_service: AuthService,
+        mikrotik_service: MikrotikService,
+    ) -> Self {
+        Self {
+            telegram,
+            auth_service,
+            mikrotik_service,
+            poll_offset: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    /// Long-poll `getUpdates` while the bot is enabled in polling mode.
+    pub fn start_polling(&self) {
+        let bot = self.clone();
+        let leader = LeaderLock::new(self.telegram.pool.clone(), "telegram_bot_poller");
+        tokio::spawn(async move {
+            info!("Telegram bot poller started");
+            loop {
+                // Telegram answers one getUpdates call at a time per bot; a
+                // second poller would only fight the leader for updates.
+                if !leader.acquire().await {
+                    tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+                    continue;
+                }
+                let Some(token) = bot.telegram.bot_token().await else {
+                    tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+                    continue;
+                };
+                if bot
+                    .telegram
+                    .setting("telegram_webhook_secret")
+                    .await
+                    .is_some()
+                {
+                    tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+                    continue;
+                }
+
+                let body = json!({
+                    "offset": bot.poll_offset.load(Ordering::Relaxed),
+                    "timeout": POLL_TIMEOUT_SECS,
+                    "allowed_updates": ["message"],
+                });
+                match bot.telegram.call(&token, "getUpdates", &body).await {
+                    Ok(Value::Array(updates)) => {
+                        for update in updates {
+                            if let Some(id) = update["update_id"].as_i64() {
+                                bot.poll_offset.fetch_max(id + 1, Ordering::Relaxed);
+                            }
+                            bot.handle_update(&update).await;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!("Telegram getUpdates failed: {}", e);
+                        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Handle a webhook update after checking Telegram's secret token header.
+    pub async fn handle_webhook(&self, secret_header: &str, update: &Value) -> AppResult<()> {
+        let Some(secret) = self.telegram.setting("telegram_webhook_secret").await else {
+            return Err(AppError::Forbidden("Webhook is not enabled".to_string()));
+        };
+        if !constant_time_eq(secret.as_bytes(), secret_header.as_bytes()) {
+            return Err(AppError::Forbidden("Invalid secret token".to_string()));
+        }
+        self.handle_update(update).await;
+        Ok(())
+    }
+
+    async fn handle_update(&self, update: &Value) {
+        let message = &update["message"];
+        let (Some(chat_id), Some(text)) =
+            (message["chat"]["id"].as_i64(), message["text"].as_str())
+        else {
+            return;
+        };
+        // Only private chats: group members would share one admin's permissions.
+        if message["chat"]["type"].as_str() != Some("private") {
+            return;
+        }
+        let chat_id = chat_id.to_string();
+        let username = message["from"]["username"].as_str();
+
+        let Some((command, args)) = parse_command(text) else {
+            return;
+        };
+        let reply = match self.run_command(&chat_id, username, &command, &args).await {
+            Ok(reply) => reply,
+            Err(e) => format!("Error: {}", e),
+        };
+        if let Err(e) = self.telegram.send_message(&chat_id, &reply).await {
+            warn!("Telegram reply failed: {}", e);
+        }
+    }
+
+    async fn run_command(
+        &self,
+        chat_id: &str,
+        username: Option<&str>,
+        command: &str,
+        args: &str,
+    ) -> AppResult<String> {
+        if command == "start" {
+            if args.is_empty() {
+                return Ok(HELP_UNLINKED.to_string());
+            }
+            return match self.telegram.redeem_link_code(args, chat_id, username).await? {
+                Some(link) => {
+                    let tenant = self.tenant_name(&link.tenant_id).await;
+                    Ok(format!(
+                        "Linked to {}. You will receive incident and payment alerts here.\n\n{}",
+                        tenant, HELP_LINKED
+                    ))
+                }
+                None => Ok("That link code is invalid or has expired. Create a new one in your notification settings.".to_string()),
+            };
+        }
+
+        let Some(link) = self.telegram.link_for_chat(chat_id).await? else {
+            return Ok(HELP_UNLINKED.to_string());
+        };
+        let tenant_id = link.tenant_id.as_str();
+        let user_id = link.user_id.as_str();
+
+        match command {
+            "help" => Ok(HELP_LINKED.to_string()),
+            "unlink" => {
+                self.telegram.unlink_chat(chat_id).await?;
+                Ok("This chat is no longer linked.".to_string())
+            }
+            "status" => {
+                self.auth_service
+                    .check_permission(user_id, tenant_id, "network_routers", "read")
+                    .await?;
+                self.router_status(tenant_id, args).await
+            }
+            "incidents" => {
+                self.auth_service
+                    .check_permission(user_id, tenant_id, "network_routers", "read")
+                    .await?;
+                let incidents = self
+                    .mikrotik_service
+                    .list_incidents(tenant_id, true, false, MAX_INCIDENTS_LISTED)
+                    .await?;
+                Ok(format_incidents(&incidents))
+            }
+            "ack" => {
+                self.auth_service
+                    .check_permission(user_id, tenant_id, "network_routers", "manage")
+                    .await?;
+                if args.is_empty() {
+                    return Ok("Usage: /ack <incident id> (see /incidents)".to_string());
+                }
+                let incidents = self
+                    .mikrotik_service
+                    .list_incidents(tenant_id, true, false, 200)
+                    .await?;
+                let incident = match_incident(&incidents, args)?;
+                self.mikrotik_service
+                    .ack_incident(tenant_id, &incident.id, user_id)
+                    .await?;
+                Ok(format!("Acknowledged: {}", incident.title))
+            }
+            _ => Ok(format!("Unknown command /{}.\n\n{}", command, HELP_LINKED)),
+        }
+    }
+
+    async fn router_status(&self, tenant_id: &str, query: &str) -> AppResult<String> {
+        let routers = self.mikrotik_service.list_routers(tenant_id).await?;
+        if routers.is_empty() {
+            return Ok("No routers configured.".to_string());
+        }
+
+        if query.is_empty() {
+            let online = routers.iter().filter(|r| r.is_online).count();
+            let mut out = format!("{}/{} routers online.", online, routers.len());
+            let offline: Vec<&str> = routers
+                .iter()
+                .filter(|r| r.enabled && !r.is_online)
+                .map(|r| r.name.as_str())
+                .collect();
+            if !offline.is_empty() {
+                out.push_str("\nOffline: ");
+                out.push_str(&offline.join(", "));
+            }
+            return Ok(out);
+        }
+
+        let needle = query.to_lowercase();
+        let matches: Vec<_> = routers
+            .iter()
+            .filter(|r| r.name.to_lowercase().contains(&needle))
+            .collect();
+        let router = match matches.as_slice() {
+            [] => return Ok(format!("No router matches \"{}\".", query)),
+            [one] => *one,
+            many => match many.iter().find(|r| r.name.to_lowercase() == needle) {
+                Some(exact) => *exact,
+                None => {
+                    let names: Vec<&str> = many.iter().map(|r| r.name.as_str()).collect();
+                    return Ok(format!("Several routers match: {}", names.join(", ")));
+                }
+            },
+        };
+
+        let mut out = format!(
+            "{}: {}",
+            router.name,
+            if router.is_online {
+                "online"
+            } else {
+                "OFFLINE"
+            }
+        );
+        if let Some(latency) = router.latency_ms {
+            out.push_str(&format!("\nLatency: {} ms", latency));
+        }
+        if let Some(seen) = router.last_seen_at {
+            out.push_str(&format!("\nLast seen: {}", format_age(seen, Utc::now())));
+        }
+        if let Some(err) = router.last_error.as_deref().filter(|_| !router.is_online) {
+            out.push_str(&format!("\nLast error: {}", err));
+        }
+        if router
+            .maintenance_until
+            .is_some_and(|until| until > Utc::now())
+        {
+            out.push_str("\nIn maintenance");
+        }
+        Ok(out)
+    }
+
+    async fn tenant_name(&self, tenant_id: &str) -> String {
+        sqlx::query_scalar::<_, String>("SELECT name FROM tenants WHERE id = $1")
+            .bind(tenant_id)
+            .fetch_optional(&self.telegram.pool)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| "your workspace".to_string())
+    }
+}
+
+const HELP_UNLINKED: &str = "This chat is not linked yet. Open your notification settings in the ISP dashboard, create a Telegram link code and send it here as /start <code>.";
+
+const HELP_LINKED: &str = "Commands:\n\
+/status - routers online/offline\n\
+/status <router> - one router's status\n\
+/incidents - open incidents\n\
+/ack <id> - acknowledge an incident\n\
+/unlink - stop alerts in this chat";
+
+fn generate_link_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..LINK_CODE_LEN)
+        .map(|_| LINK_CODE_ALPHABET[rng.gen_range(0..LINK_CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+/// Split `/cmd@BotName args` into `("cmd", "args")`. Non-commands yield `None`.
+fn parse_command(text: &str) -> Option<(String, String)> {
+    let text = text.trim();
+    let rest = text.strip_prefix('/')?;
+    let (head, args) = match rest.split_once(char::is_whitespace) {
+        Some((head, args)) => (head, args.trim()),
+        None => (rest, ""),
+    };
+    let command = head
+        .split('@')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if command.is_empty() {
+        return None;
+    }
+    Some((command, args.to_string()))
+}
+
+/// Short ids shown in `/incidents` (the first 8 characters of the UUID).
+fn short_id(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
+fn format_incidents(incidents: &[MikrotikIncident]) -> String {
+    if incidents.is_empty() {
+        return "No open incidents.".to_string();
+    }
+    let mut out = String::from("Open incidents:");
+    for i in incidents {
+        out.push_str(&format!(
+            "\n[{}] {} - {}{}",
+            short_id(&i.id),
+            i.severity.to_uppercase(),
+            i.title,
+            if i.status == "ack" { " (acked)" } else { "" }
+        ));
+    }
+    out.push_str("\n\nAcknowledge with /ack <id>");
+    out
+}
+
+/// Resolve an incident by full id or unambiguous id prefix.
+fn match_incident<'a>(
+    incidents: &'a [MikrotikIncident],
+    query: &str,
+) -> AppResult<&'a MikrotikIncident> {
+    let query = query.trim().to_ascii_lowercase();
+    if query.len() < 4 {
+        return Err(AppError::Validation(
+            "Use at least 4 characters of the incident id".to_string(),
+        ));
+    }
+    let matches: Vec<&MikrotikIncident> = incidents
+        .iter()
+        .filter(|i| i.id.to_ascii_lowercase().starts_with(&query))
+        .collect();
+    match matches.as_slice() {
+        [one] => Ok(one),
+        [] => Err(AppError::NotFound(
+            "No open incident with that id".to_string(),
+        )),
+        _ => Err(AppError::Validation(
+            "Several incidents match; use more characters of the id".to_string(),
+        )),
+    }
+}
+
+fn format_age(at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - at).num_seconds().max(0);
+    match secs {
+        0..=59 => format!("{}s ago", secs),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86_399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn incident(id: &str, title: &str) -> MikrotikIncident {
+        let now = Utc::now();
+        MikrotikIncident {
+            id: id.to_string(),
+            tenant_id: "t1".to_string(),
+            router_id: "r1".to_string(),
+            source: "routeros".to_string(),
+            interface_name: None,
+            incident_type: "offline".to_string(),
+            dedup_key: "r1:offline".to_string(),
+            severity: "critical".to_string(),
+            status: "open".to_string(),
+            title: title.to_string(),
+            message: String::new(),
+            value_num: None,
+            threshold_num: None,
+            first_seen_at: now,
+            last_seen_at: now,
+            resolved_at: None,
+            acked_at: None,
+            acked_by: None,
+            owner_user_id: None,
+            notes: None,
+            is_auto_escalated: false,
+            escalated_at: None,
+            affected_subscriptions: None,
+            affected_customers: None,
+            affected_areas: None,
+            impact_computed_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(
+            parse_command("/start ABCD2345"),
+            Some(("start".to_string(), "ABCD2345".to_string()))
+        );
+        assert_eq!(
+            parse_command("/Status@IspAdminBot  Core Router "),
+            Some(("status".to_string(), "Core Router".to_string()))
+        );
+        assert_eq!(
+            parse_command("/incidents"),
+            Some(("incidents".to_string(), String::new()))
+        );
+        assert_eq!(parse_command("hello"), None);
+        assert_eq!(parse_command("/"), None);
+    }
+
+    #[test]
+    fn test_generate_link_code() {
+        let code = generate_link_code();
+        assert_eq!(code.len(), LINK_CODE_LEN);
+        assert!(code.bytes().all(|b| LINK_CODE_ALPHABET.contains(&b)));
+    }
+
+    #[test]
+    fn test_match_incident_by_prefix() {
+        let incidents = vec![
+            incident("3f2a9c10-0000-4000-8000-000000000001", "Core offline"),
+            incident("3f2b0000-0000-4000-8000-000000000002", "POP-2 offline"),
+        ];
+        assert_eq!(
+            match_incident(&incidents, "3F2A9C").unwrap().title,
+            "Core offline"
+        );
+        assert!(matches!(
+            match_incident(&incidents, "3f2"),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(match_incident(&incidents, "3f2a"), Ok(i) if i.title == "Core offline"));
+        assert!(matches!(match_incident(&incidents, "3f2b"), Ok(i) if i.title == "POP-2 offline"));
+        assert!(matches!(
+            match_incident(&incidents, "ffff"),
+            Err(AppError::NotFound(_))
+        ));
+
+        let ambiguous = vec![
+            incident("aaaa1111-0000", "A"),
+            incident("aaaa2222-0000", "B"),
+        ];
+        assert!(matches!(
+            match_incident(&ambiguous, "aaaa"),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_incidents() {
+        assert_eq!(format_incidents(&[]), "No open incidents.");
+        let mut acked = incident("12345678-abcd", "Core offline");
+        acked.status = "ack".to_string();
+        let text = format_incidents(&[acked]);
+        assert!(text.contains("[12345678] CRITICAL - Core offline (acked)"));
+    }
+
+    #[test]
+    fn test_format_age() {
+        let now = Utc::now();
+        assert_eq!(format_age(now - Duration::seconds(42), now), "42s ago");
+        assert_eq!(format_age(now - Duration::minutes(5), now), "5m ago");
+        assert_eq!(format_age(now - Duration::hours(3), now), "3h ago");
+        assert_eq!(format_age(now - Duration::days(2), now), "2d ago");
+    }
+}
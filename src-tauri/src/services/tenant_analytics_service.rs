@@ -0,0 +1,188 @@
+//! Tenant dashboard analytics.
+//!
+//! One payload with the figures the tenant dashboard shows (customers,
+//! online subscribers, open incidents, this month's revenue, tickets and
+//! storage), read with two queries and kept per tenant for `CACHE_TTL_SECS`
+//! so a dashboard full of widgets costs one computation per minute.
+
+use crate::db::DbPool;
+use crate::error::AppResult;
+use crate::models::{
+    CustomerAnalytics, IncidentAnalytics, RevenueAnalytics, StorageAnalytics, SubscriberAnalytics,
+    TenantAnalytics, TicketAnalytics,
+};
+use crate::services::cache::MemoryCache;
+use crate::services::{UsageMetric, UsageService};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use std::sync::Arc;
+
+const CACHE_TTL_SECS: u64 = 60;
+
+/// Start of the (UTC) month `now` falls in.
+fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+#[derive(sqlx::FromRow)]
+struct AnalyticsCounts {
+    customers_total: i64,
+    customers_active: i64,
+    active_subscriptions: i64,
+    online_subscribers: i64,
+    open_incidents: i64,
+    critical_incidents: i64,
+    open_tickets: i64,
+    tickets_created: i64,
+    tickets_closed: i64,
+    storage_used: i64,
+}
+
+#[derive(Clone)]
+pub struct TenantAnalyticsService {
+    pool: DbPool,
+    usage: UsageService,
+    cache: Arc<MemoryCache<TenantAnalytics>>,
+}
+
+impl TenantAnalyticsService {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            usage: UsageService::new(pool.clone()),
+            pool,
+            cache: Arc::new(MemoryCache::new(CACHE_TTL_SECS)),
+        }
+    }
+
+    /// Dashboard figures for a tenant, from the cache unless `refresh` is set
+    /// or the cached ones are older than `CACHE_TTL_SECS`.
+    pub async fn get(&self, tenant_id: &str, refresh: bool) -> AppResult<TenantAnalytics> {
+        if !refresh {
+            if let Some(cached) = self.cache.get(tenant_id) {
+                return Ok(cached);
+            }
+        }
+
+        let analytics = self.compute(tenant_id).await?;
+        self.cache.set(tenant_id.to_string(), analytics.clone());
+        Ok(analytics)
+    }
+
+    async fn compute(&self, tenant_id: &str) -> AppResult<TenantAnalytics> {
+        let now = Utc::now();
+        let since = month_start(now);
+
+        let counts: AnalyticsCounts = sqlx::query_as(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM customers
+                  WHERE tenant_id = $1 AND deleted_at IS NULL) AS customers_total,
+                (SELECT COUNT(*) FROM customers
+                  WHERE tenant_id = $1 AND deleted_at IS NULL AND is_active = true) AS customers_active,
+                (SELECT COUNT(*) FROM customer_subscriptions
+                  WHERE tenant_id = $1 AND status = 'active') AS active_subscriptions,
+                (SELECT COUNT(*) FROM pppoe_accounts a
+                  JOIN mikrotik_routers r ON r.id = a.router_id
+                  WHERE a.tenant_id = $1 AND a.disabled = false AND a.router_present = true
+                    AND r.is_online = true AND r.deleted_at IS NULL) AS online_subscribers,
+                (SELECT COUNT(*) FROM mikrotik_incidents
+                  WHERE tenant_id = $1 AND status <> 'resolved') AS open_incidents,
+                (SELECT COUNT(*) FROM mikrotik_incidents
+                  WHERE tenant_id = $1 AND status <> 'resolved' AND severity = 'critical') AS critical_incidents,
+                (SELECT COUNT(*) FROM support_tickets
+                  WHERE tenant_id = $1 AND status <> 'closed') AS open_tickets,
+                (SELECT COUNT(*) FROM support_tickets
+                  WHERE tenant_id = $1 AND created_at >= $2) AS tickets_created,
+                (SELECT COUNT(*) FROM support_tickets
+                  WHERE tenant_id = $1 AND status = 'closed' AND closed_at >= $2) AS tickets_closed,
+                COALESCE((SELECT storage_usage FROM tenants WHERE id = $1), 0) AS storage_used
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?;
+
+        #[cfg(feature = "postgres")]
+        let revenue_query = r#"
+            SELECT currency_code,
+                   COALESCE(SUM(CASE WHEN status = 'paid' THEN amount ELSE 0 END), 0)::float8 AS paid,
+                   COUNT(*) FILTER (WHERE status = 'paid') AS paid_invoices,
+                   COALESCE(SUM(CASE WHEN status <> 'paid' THEN amount ELSE 0 END), 0)::float8 AS outstanding,
+                   COUNT(*) FILTER (WHERE status <> 'paid') AS outstanding_invoices
+            FROM invoices
+            WHERE tenant_id = $1
+              AND external_id LIKE 'pkgsub:%'
+              AND ((status = 'paid' AND paid_at >= $2)
+                   OR status IN ('pending', 'verification_pending'))
+            GROUP BY currency_code
+            ORDER BY currency_code
+        "#;
+        #[cfg(feature = "sqlite")]
+        let revenue_query = r#"
+            SELECT currency_code,
+                   CAST(COALESCE(SUM(CASE WHEN status = 'paid' THEN amount ELSE 0 END), 0) AS REAL) AS paid,
+                   SUM(CASE WHEN status = 'paid' THEN 1 ELSE 0 END) AS paid_invoices,
+                   CAST(COALESCE(SUM(CASE WHEN status <> 'paid' THEN amount ELSE 0 END), 0) AS REAL) AS outstanding,
+                   SUM(CASE WHEN status <> 'paid' THEN 1 ELSE 0 END) AS outstanding_invoices
+            FROM invoices
+            WHERE tenant_id = $1
+              AND external_id LIKE 'pkgsub:%'
+              AND ((status = 'paid' AND paid_at >= $2)
+                   OR status IN ('pending', 'verification_pending'))
+            GROUP BY currency_code
+            ORDER BY currency_code
+        "#;
+        let revenue: Vec<RevenueAnalytics> = sqlx::query_as(revenue_query)
+            .bind(tenant_id)
+            .bind(since)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let storage_limit = self.usage.limit(tenant_id, UsageMetric::Storage).await?;
+
+        Ok(TenantAnalytics {
+            tenant_id: tenant_id.to_string(),
+            period: since.format("%Y-%m").to_string(),
+            customers: CustomerAnalytics {
+                total: counts.customers_total,
+                active: counts.customers_active,
+            },
+            subscribers: SubscriberAnalytics {
+                active_subscriptions: counts.active_subscriptions,
+                online: counts.online_subscribers,
+            },
+            incidents: IncidentAnalytics {
+                open: counts.open_incidents,
+                critical: counts.critical_incidents,
+            },
+            revenue,
+            tickets: TicketAnalytics {
+                open: counts.open_tickets,
+                created_this_month: counts.tickets_created,
+                closed_this_month: counts.tickets_closed,
+            },
+            storage: StorageAnalytics {
+                used_bytes: counts.storage_used,
+                limit_bytes: storage_limit,
+            },
+            generated_at: now,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn month_starts_at_midnight_utc_on_the_first() {
+        let now = Utc.with_ymd_and_hms(2026, 4, 21, 17, 45, 3).unwrap();
+        assert_eq!(
+            month_start(now),
+            Utc.with_ymd_and_hms(2026, 4, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(month_start(month_start(now)), month_start(now));
+    }
+}
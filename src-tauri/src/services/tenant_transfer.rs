@@ -0,0 +1,989 @@
+//! Per-tenant export and import
+//!
+//! An export is a portable archive of everything one tenant owns: its `tenants`
+//! row, every table with a `tenant_id` column, rows that hang off those through
+//! foreign keys (ticket messages, role permissions, ...) and the users those rows
+//! point at. Tables are discovered from the live schema, so new tenant tables are
+//! exported without touching this module. The archive uses the backup layout
+//! (`<table>.json` + `manifest.json`, scope `tenant_export`) and is validated the
+//! same way before an import.
+//!
+//! An import writes the archive in a single transaction, either under the original
+//! ids (moving a tenant to another instance) or as a copy with fresh ids (staging
+//! copies, white-label splits). Users are matched by email. Rows referencing data
+//! this instance does not have, such as a plan that only exists on the source, are
+//! skipped and reported. Imported tenants always land in the shared tables; isolate
+//! them afterwards if needed.
+
+use crate::db::{slugify, DbPool};
+use crate::error::{AppError, AppResult};
+use crate::services::backup::{write_backup_zip, BackupService};
+use crate::services::backup_validation::{self, RestoreValidationReport, ValidationTarget};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use tracing::info;
+
+/// Manifest scope of tenant export archives.
+pub const EXPORT_SCOPE: &str = "tenant_export";
+
+/// Never exported: sessions and devices, delivery queues and bookkeeping tables.
+/// `tenants` and `users` are exported separately.
+const EXPORT_SKIP: &[&str] = &[
+    "sessions",
+    "trusted_devices",
+    "telegram_links",
+    "oauth_accounts",
+    "idempotency_keys",
+    "email_outbox",
+    "event_outbox",
+    "whatsapp_messages",
+    "tenant_schemas",
+    "_sqlx_migrations",
+    "tenants",
+    "users",
+];
+
+/// One-time tokens and lockout state cleared from exported users.
+const USER_EPHEMERAL_COLUMNS: &[&str] = &[
+    "verification_token",
+    "reset_token",
+    "reset_token_expires",
+    "email_otp_code",
+    "email_otp_expires",
+    "locked_until",
+];
+
+/// How many foreign key hops a child table may be from a tenant table.
+const MAX_CHILD_DEPTH: usize = 3;
+
+type Row = Map<String, Value>;
+
+#[cfg(feature = "postgres")]
+type Db = sqlx::Postgres;
+
+#[cfg(feature = "sqlite")]
+type Db = sqlx::Sqlite;
+
+/// Single-column foreign key `table.column -> ref_table.ref_column`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ForeignKey {
+    table: String,
+    column: String,
+    ref_table: String,
+    ref_column: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TenantExportSummary {
+    pub filename: String,
+    pub path: String,
+    pub tenant_id: String,
+    pub tables: usize,
+    pub rows: usize,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantImportOptions {
+    /// Give the tenant and its rows fresh ids so it can sit next to the original.
+    #[serde(default)]
+    pub as_copy: bool,
+    pub name: Option<String>,
+    pub slug: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportedTable {
+    pub name: String,
+    pub rows_in_archive: usize,
+    /// Lower than `rows_in_archive` when rows were skipped as orphans or duplicates.
+    pub rows_imported: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TenantImportReport {
+    pub tenant_id: String,
+    pub source_tenant_id: String,
+    pub name: String,
+    pub slug: String,
+    pub users_created: usize,
+    pub users_matched: usize,
+    pub tables: Vec<ImportedTable>,
+    pub warnings: Vec<String>,
+    pub validation: RestoreValidationReport,
+}
+
+#[derive(Clone)]
+pub struct TenantTransferService {
+    pool: DbPool,
+    backup: BackupService,
+}
+
+impl TenantTransferService {
+    pub fn new(pool: DbPool, backup: BackupService) -> Self {
+        Self { pool, backup }
+    }
+
+    /// Write the export archive of `tenant_id` into `dest_dir`. With schema
+    /// isolation, run this inside `TenantDbRouter::scope` so the tenant's own
+    /// schema is read.
+    pub async fn export_tenant(
+        &self,
+        tenant_id: &str,
+        dest_dir: &Path,
+    ) -> AppResult<TenantExportSummary> {
+        let tenant = self
+            .fetch_table("tenants", &text_match("id"), tenant_id)
+            .await?;
+        let slug = tenant
+            .first()
+            .and_then(|t| t.get("slug"))
+            .and_then(Value::as_str)
+            .map(slugify)
+            .ok_or_else(|| AppError::NotFound("Tenant not found".to_string()))?;
+
+        let tenant_tables = self.tenant_tables().await?;
+        let fks = self.foreign_keys().await?;
+
+        let mut data: BTreeMap<String, Vec<Row>> = BTreeMap::new();
+        data.insert("tenants".to_string(), tenant);
+        for (table, filter) in export_filters(&tenant_tables, &fks) {
+            let rows = self
+                .fetch_table(&table, &filter, tenant_id)
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to export {}: {}", table, e)))?;
+            let rows = if table == "settings" {
+                BackupService::redact_settings_rows(rows)
+            } else {
+                rows
+            };
+            data.insert(table, rows);
+        }
+
+        // Every user an exported row points at, so the import can match or create them.
+        let user_ids = referenced_ids(&data, &fks, "users");
+        let mut users = if user_ids.is_empty() {
+            Vec::new()
+        } else {
+            self.fetch_table("users", &id_list_match("id"), &json_list(&user_ids)?)
+                .await?
+        };
+        for user in &mut users {
+            for col in USER_EPHEMERAL_COLUMNS {
+                if user.contains_key(*col) {
+                    user.insert(col.to_string(), Value::Null);
+                }
+            }
+        }
+        data.insert("users".to_string(), users);
+
+        tokio::fs::create_dir_all(dest_dir)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let filename = format!(
+            "tenant_export_{}_{}.zip",
+            slug,
+            Utc::now().format("%Y%m%d_%H%M%S")
+        );
+        let path = dest_dir.join(&filename);
+
+        let tables = data.len();
+        let rows = data.values().map(Vec::len).sum();
+        let data_map: HashMap<String, Value> = data
+            .into_iter()
+            .map(|(table, rows)| {
+                (
+                    format!("{}.json", table),
+                    Value::Array(rows.into_iter().map(Value::Object).collect()),
+                )
+            })
+            .collect();
+        let schema_version = self.backup.current_schema_version().await;
+        write_backup_zip(
+            &path,
+            data_map,
+            EXPORT_SCOPE,
+            Some(tenant_id),
+            schema_version,
+            zip::CompressionMethod::Deflated,
+        )?;
+
+        info!(
+            "Exported tenant {} ({} tables, {} rows) to {:?}",
+            tenant_id, tables, rows, path
+        );
+        Ok(TenantExportSummary {
+            filename,
+            path: path.to_string_lossy().to_string(),
+            tenant_id: tenant_id.to_string(),
+            tables,
+            rows,
+        })
+    }
+
+    /// Import an export archive. Nothing is written unless the whole import succeeds.
+    pub async fn import_tenant(
+        &self,
+        zip_path: &Path,
+        options: &TenantImportOptions,
+    ) -> AppResult<TenantImportReport> {
+        let contents = backup_validation::read_archive(zip_path)?;
+        let source_tenant_id = contents
+            .manifest
+            .as_ref()
+            .filter(|m| m.scope == EXPORT_SCOPE)
+            .and_then(|m| m.tenant_id.clone())
+            .ok_or_else(|| AppError::Validation("Not a tenant export archive".to_string()))?;
+
+        let fks = self.foreign_keys().await?;
+        let names: BTreeSet<String> = contents.tables.keys().cloned().collect();
+        let order = insert_order(&names, &fks);
+        let order_refs: Vec<&str> = order.iter().map(String::as_str).collect();
+        let columns = self.backup.live_columns(&order_refs).await?;
+
+        let validation = backup_validation::check_archive(
+            &contents,
+            &ValidationTarget {
+                current_schema_version: self.backup.current_schema_version().await,
+                target_tenant_id: None,
+                restorable: &order_refs,
+                columns: &columns,
+            },
+        );
+        if !validation.ok {
+            return Err(AppError::Validation(format!(
+                "Import blocked: {}",
+                validation.error_summary()
+            )));
+        }
+
+        let mut data: HashMap<String, Vec<Row>> = HashMap::new();
+        for (table, raw) in &contents.tables {
+            let rows = serde_json::from_str(raw)
+                .map_err(|e| AppError::Validation(format!("Invalid JSON in {}: {}", table, e)))?;
+            data.insert(table.clone(), rows);
+        }
+
+        let mut tenant = match data.remove("tenants") {
+            Some(mut rows) if rows.len() == 1 => rows.remove(0),
+            _ => {
+                return Err(AppError::Validation(
+                    "Archive must contain exactly one tenant".to_string(),
+                ))
+            }
+        };
+        let users = data.remove("users").unwrap_or_default();
+
+        let tenant_id = if options.as_copy {
+            uuid::Uuid::new_v4().to_string()
+        } else {
+            source_tenant_id.clone()
+        };
+        let name = options
+            .name
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .or_else(|| tenant.get("name").and_then(Value::as_str))
+            .unwrap_or_default()
+            .to_string();
+        let source_slug = tenant
+            .get("slug")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let slug = match options.slug.as_deref().map(slugify) {
+            Some(s) if !s.is_empty() => s,
+            _ if options.as_copy => format!("{}-copy", source_slug),
+            _ => source_slug,
+        };
+        if name.is_empty() || slug.is_empty() {
+            return Err(AppError::Validation(
+                "Tenant name and slug are required".to_string(),
+            ));
+        }
+
+        let mut warnings = Vec::new();
+        let mut tx = self.pool.begin().await?;
+
+        if !options.as_copy && exists(&mut tx, "tenants", "id", &tenant_id).await? {
+            return Err(AppError::Conflict(format!(
+                "Tenant {} already exists on this instance; import it as a copy instead",
+                tenant_id
+            )));
+        }
+        if exists(&mut tx, "tenants", "slug", &slug).await? {
+            return Err(AppError::Conflict(format!(
+                "Slug '{}' is already in use",
+                slug
+            )));
+        }
+        if let Some(domain) = tenant
+            .get("custom_domain")
+            .and_then(Value::as_str)
+            .filter(|d| !d.is_empty())
+            .map(str::to_string)
+        {
+            if options.as_copy || exists(&mut tx, "tenants", "custom_domain", &domain).await? {
+                tenant.insert("custom_domain".to_string(), Value::Null);
+                warnings.push(format!("Custom domain {} was not carried over", domain));
+            }
+        }
+        tenant.insert("id".to_string(), Value::String(tenant_id.clone()));
+        tenant.insert("name".to_string(), Value::String(name.clone()));
+        tenant.insert("slug".to_string(), Value::String(slug.clone()));
+        insert_rows(&mut tx, "tenants", &[tenant], &columns).await?;
+
+        let mut id_map: HashMap<String, String> = HashMap::new();
+        if options.as_copy {
+            id_map.insert(source_tenant_id.clone(), tenant_id.clone());
+            for rows in data.values() {
+                for row in rows {
+                    if let Some(Value::String(id)) = row.get("id") {
+                        id_map
+                            .entry(id.clone())
+                            .or_insert_with(|| uuid::Uuid::new_v4().to_string());
+                    }
+                }
+            }
+        }
+
+        // Existing accounts (by email) are reused as-is; the rest are created.
+        let mut new_users = Vec::new();
+        let mut users_matched = 0;
+        for mut user in users {
+            let Some(old_id) = user.get("id").and_then(value_key) else {
+                continue;
+            };
+            let email = user
+                .get("email")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            if let Some(existing) = find_user_by_email(&mut tx, &email).await? {
+                id_map.insert(old_id, existing);
+                users_matched += 1;
+                continue;
+            }
+            if exists(&mut tx, "users", "id", &old_id).await? {
+                let id = uuid::Uuid::new_v4().to_string();
+                id_map.insert(old_id, id.clone());
+                user.insert("id".to_string(), Value::String(id));
+            }
+            user.insert("is_super_admin".to_string(), Value::Bool(false));
+            new_users.push(user);
+        }
+        let users_created = new_users.len();
+        if !new_users.is_empty() {
+            insert_rows(&mut tx, "users", &new_users, &columns).await?;
+        }
+
+        let mut tables = Vec::new();
+        for table in &order {
+            let Some(mut rows) = data.remove(table) else {
+                continue;
+            };
+            let rows_in_archive = rows.len();
+            remap_values(&mut rows, &id_map);
+
+            let rows = drop_orphans(&mut tx, table, rows, &fks).await?;
+            if rows.len() < rows_in_archive {
+                warnings.push(format!(
+                    "{}: skipped {} rows referencing data missing on this instance",
+                    table,
+                    rows_in_archive - rows.len()
+                ));
+            }
+            let rows_imported = if rows.is_empty() {
+                0
+            } else {
+                insert_rows(&mut tx, table, &rows, &columns).await?
+            };
+            tables.push(ImportedTable {
+                name: table.clone(),
+                rows_in_archive,
+                rows_imported,
+            });
+        }
+
+        tx.commit().await?;
+
+        info!(
+            "Imported tenant {} as {} ({} users created, {} matched)",
+            source_tenant_id, tenant_id, users_created, users_matched
+        );
+        Ok(TenantImportReport {
+            tenant_id,
+            source_tenant_id,
+            name,
+            slug,
+            users_created,
+            users_matched,
+            tables,
+            warnings,
+            validation,
+        })
+    }
+
+    /// Rows of `table` matching `filter`, whose placeholder is bound to `param`.
+    async fn fetch_table(&self, table: &str, filter: &str, param: &str) -> AppResult<Vec<Row>> {
+        #[cfg(feature = "postgres")]
+        {
+            let sql = format!(
+                "SELECT COALESCE(json_agg(row_to_json(t)), '[]'::json) FROM (SELECT * FROM {} WHERE {}) t",
+                quote(table),
+                filter
+            );
+            let rows: Value = sqlx::query_scalar(&sql)
+                .bind(param)
+                .fetch_one(&self.pool)
+                .await?;
+            serde_json::from_value(rows).map_err(|e| AppError::Internal(e.to_string()))
+        }
+
+        #[cfg(feature = "sqlite")]
+        {
+            let sql = format!("SELECT * FROM {} WHERE {}", quote(table), filter);
+            self.backup
+                .fetch_rows(&sql, "", vec![param.to_string()])
+                .await
+        }
+    }
+
+    /// Tables with a `tenant_id` column.
+    async fn tenant_tables(&self) -> AppResult<BTreeSet<String>> {
+        #[cfg(feature = "postgres")]
+        let sql = r#"
+            SELECT c.relname::text
+            FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            JOIN pg_attribute a ON a.attrelid = c.oid
+            WHERE n.nspname = 'public'
+              AND c.relkind IN ('r', 'p')
+              AND NOT c.relispartition
+              AND a.attname = 'tenant_id'
+              AND NOT a.attisdropped
+        "#;
+
+        #[cfg(feature = "sqlite")]
+        let sql = r#"
+            SELECT m.name
+            FROM sqlite_master m, pragma_table_info(m.name) p
+            WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite_%' AND p.name = 'tenant_id'
+        "#;
+
+        let tables: Vec<String> = sqlx::query_scalar(sql).fetch_all(&self.pool).await?;
+        Ok(tables.into_iter().collect())
+    }
+
+    async fn foreign_keys(&self) -> AppResult<Vec<ForeignKey>> {
+        #[cfg(feature = "postgres")]
+        let sql = r#"
+            SELECT cl.relname::text, a.attname::text, rcl.relname::text, ra.attname::text
+            FROM pg_constraint con
+            JOIN pg_class cl ON cl.oid = con.conrelid
+            JOIN pg_namespace n ON n.oid = cl.relnamespace
+            JOIN pg_class rcl ON rcl.oid = con.confrelid
+            JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = con.conkey[1]
+            JOIN pg_attribute ra ON ra.attrelid = con.confrelid AND ra.attnum = con.confkey[1]
+            WHERE con.contype = 'f'
+              AND n.nspname = 'public'
+              AND NOT cl.relispartition
+              AND cardinality(con.conkey) = 1
+        "#;
+
+        #[cfg(feature = "sqlite")]
+        let sql = r#"
+            SELECT m.name, f."from", f."table", COALESCE(f."to", 'id')
+            FROM sqlite_master m, pragma_foreign_key_list(m.name) f
+            WHERE m.type = 'table'
+        "#;
+
+        let rows: Vec<(String, String, String, String)> =
+            sqlx::query_as(sql).fetch_all(&self.pool).await?;
+        Ok(rows
+            .into_iter()
+            .map(|(table, column, ref_table, ref_column)| ForeignKey {
+                table,
+                column,
+                ref_table,
+                ref_column,
+            })
+            .collect())
+    }
+}
+
+fn quote(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// `column` compared as text with the single bound parameter.
+fn text_match(column: &str) -> String {
+    #[cfg(feature = "postgres")]
+    let sql = format!("{}::text = $1", quote(column));
+
+    #[cfg(feature = "sqlite")]
+    let sql = format!("{} = ?1", quote(column));
+
+    sql
+}
+
+/// `column` is one of the values in the bound JSON array.
+fn id_list_match(column: &str) -> String {
+    #[cfg(feature = "postgres")]
+    let sql = format!(
+        "{}::text IN (SELECT jsonb_array_elements_text($1::jsonb))",
+        quote(column)
+    );
+
+    #[cfg(feature = "sqlite")]
+    let sql = format!("{} IN (SELECT value FROM json_each(?1))", quote(column));
+
+    sql
+}
+
+fn json_list(values: &BTreeSet<String>) -> AppResult<String> {
+    serde_json::to_string(values).map_err(|e| AppError::Internal(e.to_string()))
+}
+
+/// Key/id values compare as text: strings as-is, numbers in decimal.
+fn value_key(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Export filter for every tenant-owned table: tables with a `tenant_id` column,
+/// then tables reachable from those (or from the tenant's users) through foreign keys.
+fn export_filters(
+    tenant_tables: &BTreeSet<String>,
+    fks: &[ForeignKey],
+) -> BTreeMap<String, String> {
+    let mut filters: BTreeMap<String, String> = tenant_tables
+        .iter()
+        .filter(|t| !EXPORT_SKIP.contains(&t.as_str()))
+        .map(|t| (t.clone(), text_match("tenant_id")))
+        .collect();
+
+    // Children may also hang off the tenant row itself or the tenant's members.
+    let mut parents = filters.clone();
+    parents.insert("tenants".to_string(), text_match("id"));
+    parents.insert(
+        "users".to_string(),
+        format!(
+            "{} IN (SELECT {} FROM {} WHERE {})",
+            quote("id"),
+            quote("user_id"),
+            quote("tenant_members"),
+            text_match("tenant_id")
+        ),
+    );
+
+    for _ in 0..MAX_CHILD_DEPTH {
+        let candidates: BTreeSet<&str> = fks
+            .iter()
+            .map(|fk| fk.table.as_str())
+            .filter(|t| !parents.contains_key(*t) && !EXPORT_SKIP.contains(t))
+            .collect();
+
+        let mut found = BTreeMap::new();
+        for table in candidates {
+            let links: Vec<&ForeignKey> = fks
+                .iter()
+                .filter(|fk| {
+                    fk.table == table
+                        && fk.ref_table != table
+                        && parents.contains_key(&fk.ref_table)
+                })
+                .collect();
+            // A user can belong to several tenants, so a row is only followed through
+            // its user when nothing tenant-owned points the way.
+            let owned: Vec<&ForeignKey> = links
+                .iter()
+                .copied()
+                .filter(|fk| fk.ref_table != "users")
+                .collect();
+            let chosen = if owned.is_empty() { links } else { owned };
+            if chosen.is_empty() {
+                continue;
+            }
+
+            let clause = chosen
+                .iter()
+                .map(|fk| {
+                    format!(
+                        "{} IN (SELECT {} FROM {} WHERE {})",
+                        quote(&fk.column),
+                        quote(&fk.ref_column),
+                        quote(&fk.ref_table),
+                        parents[&fk.ref_table]
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" OR ");
+            found.insert(table.to_string(), format!("({})", clause));
+        }
+
+        if found.is_empty() {
+            break;
+        }
+        parents.extend(found.clone());
+        filters.extend(found);
+    }
+
+    filters
+}
+
+/// Values of every column in `data` that references `target`.
+fn referenced_ids(
+    data: &BTreeMap<String, Vec<Row>>,
+    fks: &[ForeignKey],
+    target: &str,
+) -> BTreeSet<String> {
+    fks.iter()
+        .filter(|fk| fk.ref_table == target)
+        .flat_map(|fk| {
+            data.get(&fk.table)
+                .into_iter()
+                .flatten()
+                .filter_map(move |row| row.get(&fk.column).and_then(value_key))
+        })
+        .collect()
+}
+
+/// `tables` ordered so referenced tables come before the tables pointing at them.
+/// Cycles are broken alphabetically; the orphan check drops what cannot be linked.
+fn insert_order(tables: &BTreeSet<String>, fks: &[ForeignKey]) -> Vec<String> {
+    let mut deps: BTreeMap<&str, BTreeSet<&str>> = tables
+        .iter()
+        .map(|t| (t.as_str(), BTreeSet::new()))
+        .collect();
+    for fk in fks {
+        if fk.table != fk.ref_table && tables.contains(&fk.ref_table) {
+            if let Some(d) = deps.get_mut(fk.table.as_str()) {
+                d.insert(fk.ref_table.as_str());
+            }
+        }
+    }
+
+    let mut order = Vec::with_capacity(deps.len());
+    while !deps.is_empty() {
+        let mut ready: Vec<&str> = deps
+            .iter()
+            .filter(|(_, d)| d.is_empty())
+            .map(|(t, _)| *t)
+            .collect();
+        if ready.is_empty() {
+            ready.extend(deps.keys().next().copied());
+        }
+        for table in ready {
+            deps.remove(table);
+            for d in deps.values_mut() {
+                d.remove(table);
+            }
+            order.push(table.to_string());
+        }
+    }
+    order
+}
+
+/// Replace every string value that is a key of `id_map`.
+fn remap_values(rows: &mut [Row], id_map: &HashMap<String, String>) {
+    if id_map.is_empty() {
+        return;
+    }
+    for row in rows {
+        for value in row.values_mut() {
+            if let Value::String(s) = value {
+                if let Some(new_id) = id_map.get(s.as_str()) {
+                    *s = new_id.clone();
+                }
+            }
+        }
+    }
+}
+
+async fn exists(
+    tx: &mut sqlx::Transaction<'_, Db>,
+    table: &str,
+    column: &str,
+    value: &str,
+) -> AppResult<bool> {
+    let sql = format!(
+        "SELECT COUNT(*) FROM {} WHERE {}",
+        quote(table),
+        text_match(column)
+    );
+    let count: i64 = sqlx::query_scalar(&sql)
+        .bind(value)
+        .fetch_one(&mut **tx)
+        .await?;
+    Ok(count > 0)
+}
+
+async fn find_user_by_email(
+    tx: &mut sqlx::Transaction<'_, Db>,
+    email: &str,
+) -> AppResult<Option<String>> {
+    if email.is_empty() {
+        return Ok(None);
+    }
+
+    #[cfg(feature = "postgres")]
+    let sql = "SELECT id FROM users WHERE LOWER(email) = LOWER($1)";
+
+    #[cfg(feature = "sqlite")]
+    let sql = "SELECT id FROM users WHERE LOWER(email) = LOWER(?)";
+
+    Ok(sqlx::query_scalar(sql)
+        .bind(email)
+        .fetch_optional(&mut **tx)
+        .await?)
+}
+
+/// Drop rows whose foreign keys point at rows that do not exist (yet) in this
+/// database. Parents are inserted first, so this only catches data the archive
+/// does not carry.
+async fn drop_orphans(
+    tx: &mut sqlx::Transaction<'_, Db>,
+    table: &str,
+    mut rows: Vec<Row>,
+    fks: &[ForeignKey],
+) -> AppResult<Vec<Row>> {
+    for fk in fks
+        .iter()
+        .filter(|fk| fk.table == table && fk.ref_table != table)
+    {
+        let wanted: BTreeSet<String> = rows
+            .iter()
+            .filter_map(|r| r.get(&fk.column).and_then(value_key))
+            .collect();
+        if wanted.is_empty() {
+            continue;
+        }
+
+        #[cfg(feature = "postgres")]
+        let select = format!("{}::text", quote(&fk.ref_column));
+        #[cfg(feature = "sqlite")]
+        let select = format!("CAST({} AS TEXT)", quote(&fk.ref_column));
+
+        let sql = format!(
+            "SELECT {} FROM {} WHERE {}",
+            select,
+            quote(&fk.ref_table),
+            id_list_match(&fk.ref_column)
+        );
+        let present: HashSet<String> = sqlx::query_scalar::<_, String>(&sql)
+            .bind(json_list(&wanted)?)
+            .fetch_all(&mut **tx)
+            .await?
+            .into_iter()
+            .collect();
+
+        rows.retain(|r| {
+            r.get(&fk.column)
+                .and_then(value_key)
+                .is_none_or(|v| present.contains(&v))
+        });
+    }
+    Ok(rows)
+}
+
+/// Insert `rows` into `table`, keeping only columns the live table has. Rows that
+/// collide with existing keys are skipped. Returns how many were inserted.
+async fn insert_rows(
+    tx: &mut sqlx::Transaction<'_, Db>,
+    table: &str,
+    rows: &[Row],
+    columns: &HashMap<String, HashSet<String>>,
+) -> AppResult<u64> {
+    let live = columns.get(table).ok_or_else(|| {
+        AppError::Validation(format!("Table {} does not exist in this database", table))
+    })?;
+    let cols: BTreeSet<&str> = rows
+        .iter()
+        .flat_map(|r| r.keys())
+        .map(String::as_str)
+        .filter(|c| live.contains(*c))
+        .collect();
+    let col_list = cols.iter().map(|c| quote(c)).collect::<Vec<_>>().join(", ");
+    let payload = Value::Array(rows.iter().cloned().map(Value::Object).collect());
+
+    #[cfg(feature = "postgres")]
+    let result = sqlx::query(&format!(
+        "INSERT INTO {t} ({cols}) SELECT {cols} FROM jsonb_populate_recordset(NULL::{t}, $1) ON CONFLICT DO NOTHING",
+        t = quote(table),
+        cols = col_list
+    ))
+    .bind(&payload)
+    .execute(&mut **tx)
+    .await;
+
+    #[cfg(feature = "sqlite")]
+    let result = sqlx::query(&format!(
+        "INSERT INTO {} ({}) SELECT {} FROM json_each(?1) WHERE true ON CONFLICT DO NOTHING",
+        quote(table),
+        col_list,
+        cols.iter()
+            .map(|c| format!("json_extract(value, '$.\"{}\"')", c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+    .bind(payload.to_string())
+    .execute(&mut **tx)
+    .await;
+
+    result
+        .map(|r| r.rows_affected())
+        .map_err(|e| AppError::Internal(format!("Failed to import {}: {}", table, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fk(table: &str, column: &str, ref_table: &str) -> ForeignKey {
+        ForeignKey {
+            table: table.to_string(),
+            column: column.to_string(),
+            ref_table: ref_table.to_string(),
+            ref_column: "id".to_string(),
+        }
+    }
+
+    fn set(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_export_filters_follow_foreign_keys() {
+        let tenant_tables = set(&["support_tickets", "roles", "sessions", "customers"]);
+        let fks = vec![
+            fk("support_ticket_messages", "ticket_id", "support_tickets"),
+            fk("support_ticket_messages", "author_id", "users"),
+            fk(
+                "support_ticket_attachments",
+                "message_id",
+                "support_ticket_messages",
+            ),
+            fk("role_permissions", "role_id", "roles"),
+            fk("role_permissions", "permission_id", "permissions"),
+            fk("push_subscriptions", "user_id", "users"),
+            fk("plan_features", "plan_id", "plans"),
+            fk("trusted_devices", "user_id", "users"),
+        ];
+
+        let filters = export_filters(&tenant_tables, &fks);
+        let tables: Vec<&str> = filters.keys().map(String::as_str).collect();
+        assert_eq!(
+            tables,
+            vec![
+                "customers",
+                "push_subscriptions",
+                "role_permissions",
+                "roles",
+                "support_ticket_attachments",
+                "support_ticket_messages",
+                "support_tickets",
+            ]
+        );
+
+        assert_eq!(filters["roles"], text_match("tenant_id"));
+        // Messages are tenant-owned through their ticket, not through the author.
+        let messages = &filters["support_ticket_messages"];
+        assert!(messages.contains("\"support_tickets\""));
+        assert!(!messages.contains("\"author_id\""));
+        assert!(filters["support_ticket_attachments"].contains("\"support_ticket_messages\""));
+        assert!(filters["push_subscriptions"].contains("\"tenant_members\""));
+    }
+
+    #[test]
+    fn test_referenced_ids_collects_fk_values() {
+        let mut data = BTreeMap::new();
+        let row = |k: &str, v: Value| {
+            let mut m = Row::new();
+            m.insert(k.to_string(), v);
+            m
+        };
+        data.insert(
+            "tenant_members".to_string(),
+            vec![row("user_id", "u1".into()), row("user_id", "u2".into())],
+        );
+        data.insert(
+            "notifications".to_string(),
+            vec![row("user_id", "u2".into()), row("user_id", Value::Null)],
+        );
+        let fks = vec![
+            fk("tenant_members", "user_id", "users"),
+            fk("notifications", "user_id", "users"),
+            fk("tenant_members", "tenant_id", "tenants"),
+        ];
+
+        assert_eq!(referenced_ids(&data, &fks, "users"), set(&["u1", "u2"]));
+    }
+
+    #[test]
+    fn test_insert_order_puts_parents_first() {
+        let tables = set(&[
+            "role_permissions",
+            "roles",
+            "tenants",
+            "users",
+            "tenant_members",
+        ]);
+        let fks = vec![
+            fk("role_permissions", "role_id", "roles"),
+            fk("roles", "tenant_id", "tenants"),
+            fk("tenant_members", "tenant_id", "tenants"),
+            fk("tenant_members", "user_id", "users"),
+            fk("tenant_members", "role_id", "roles"),
+            fk("role_permissions", "permission_id", "permissions"),
+        ];
+
+        let order = insert_order(&tables, &fks);
+        let pos = |t: &str| order.iter().position(|o| o == t).unwrap();
+        assert_eq!(order.len(), 5);
+        assert!(pos("tenants") < pos("roles"));
+        assert!(pos("roles") < pos("role_permissions"));
+        assert!(pos("roles") < pos("tenant_members"));
+        assert!(pos("users") < pos("tenant_members"));
+    }
+
+    #[test]
+    fn test_insert_order_breaks_cycles() {
+        let tables = set(&["a", "b", "c"]);
+        let fks = vec![
+            fk("a", "b_id", "b"),
+            fk("b", "a_id", "a"),
+            fk("c", "a_id", "a"),
+        ];
+
+        assert_eq!(insert_order(&tables, &fks), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_remap_values_replaces_exact_matches() {
+        let mut row = Row::new();
+        row.insert("id".to_string(), "old-1".into());
+        row.insert("tenant_id".to_string(), "t-old".into());
+        row.insert("note".to_string(), "mentions old-1 inline".into());
+        row.insert("count".to_string(), 3.into());
+        let mut rows = vec![row];
+
+        let map: HashMap<String, String> = [("old-1", "new-1"), ("t-old", "t-new")]
+            .iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect();
+        remap_values(&mut rows, &map);
+
+        assert_eq!(rows[0]["id"], "new-1");
+        assert_eq!(rows[0]["tenant_id"], "t-new");
+        assert_eq!(rows[0]["note"], "mentions old-1 inline");
+        assert_eq!(rows[0]["count"], 3);
+    }
+}
@@ -0,0 +1,123 @@
+//! Trash purge scheduler
+//!
+//! Customers, routers, users and ISP packages are soft-deleted (`deleted_at`).
+//! This scheduler permanently removes rows that have been in the trash longer than
+//! the global `trash_retention_days` setting.
+
+use crate::db::DbPool;
+use crate::error::AppResult;
+use crate::models::BackgroundJob;
+use crate::services::{JobHandler, JobQueue, SettingsService};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Tables that use the `deleted_at` soft-delete convention.
+const SOFT_DELETE_TABLES: &[&str] = &["customers", "mikrotik_routers", "isp_packages", "users"];
+
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+#[derive(Clone)]
+pub struct TrashPurgeScheduler {
+    pool: DbPool,
+    settings_service: SettingsService,
+}
+
+impl TrashPurgeScheduler {
+    pub fn new(pool: DbPool, settings_service: SettingsService) -> Self {
+        Self {
+            pool,
+            settings_service,
+        }
+    }
+
+    /// Purges the trash hourly on the job queue.
+    pub async fn schedule(self, queue: &JobQueue) {
+        queue
+            .register_recurring(
+                "trash.purge",
+                std::time::Duration::from_secs(3600),
+                Arc::new(self),
+            )
+            .await;
+    }
+
+    /// Permanently delete soft-deleted rows older than `age`. Returns rows removed.
+    pub async fn purge_older_than(&self, age: Duration) -> u64 {
+        let cutoff = Utc::now() - age;
+        let mut total = 0;
+
+        for table in SOFT_DELETE_TABLES {
+            #[cfg(feature = "postgres")]
+            let res = sqlx::query(&format!(
+                "DELETE FROM {} WHERE deleted_at IS NOT NULL AND deleted_at < $1",
+                table
+            ))
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await;
+
+            #[cfg(feature = "sqlite")]
+            let res = sqlx::query(&format!(
+                "DELETE FROM {} WHERE deleted_at IS NOT NULL AND deleted_at < ?",
+                table
+            ))
+            .bind(cutoff.to_rfc3339())
+            .execute(&self.pool)
+            .await;
+
+            match res {
+                Ok(r) => total += r.rows_affected(),
+                // SQLite builds don't ship every table.
+                Err(e) if e.to_string().contains("no such table") => {}
+                Err(e) => warn!("Trash purge failed for {}: {}", table, e),
+            }
+        }
+
+        // Alerts and incidents also belong to SNMP devices, so they carry no
+        // foreign key to the router: clear the ones of routers purged above.
+        #[cfg(feature = "postgres")]
+        for table in ["mikrotik_alerts", "mikrotik_incidents"] {
+            let res = sqlx::query(&format!(
+                r#"
+                DELETE FROM {table} t
+                WHERE t.source = 'routeros'
+                  AND NOT EXISTS (SELECT 1 FROM mikrotik_routers r WHERE r.id = t.router_id)
+                "#
+            ))
+            .execute(&self.pool)
+            .await;
+            if let Err(e) = res {
+                warn!("Trash purge failed for orphaned {}: {}", table, e);
+            }
+        }
+
+        total
+    }
+}
+
+#[async_trait]
+impl JobHandler for TrashPurgeScheduler {
+    async fn run(&self, _job: &BackgroundJob) -> AppResult<()> {
+        let retention_days = self
+            .settings_service
+            .get_value(None, "trash_retention_days")
+            .await?
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .unwrap_or(DEFAULT_RETENTION_DAYS);
+
+        if retention_days <= 0 {
+            return Ok(());
+        }
+
+        let purged = self.purge_older_than(Duration::days(retention_days)).await;
+        if purged > 0 {
+            info!(
+                "Trash purge removed {} record(s) older than {} days",
+                purged, retention_days
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,584 @@
+//! Uplink capacity planning. Each uplink is a router interface with the
+//! bandwidth bought for it; the report takes the 95th percentile of the
+//! recorded throughput, the usual billing measure for transit, and compares
+//! the busier direction against that capacity. Daily percentiles show
+//! whether the link runs hot day after day and how fast it is filling up.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    UplinkCapacityReport, UplinkCapacityRow, UplinkDailyPercentile, UplinkInterface,
+    UpsertUplinkInterfaceRequest,
+};
+use crate::services::{AuditService, AuthService};
+use chrono::{Duration, NaiveDate, Utc};
+use uuid::Uuid;
+
+const DEFAULT_REPORT_DAYS: i64 = 7;
+const MAX_REPORT_DAYS: i64 = 90;
+const DEFAULT_WARNING_PCT: i32 = 70;
+const DEFAULT_CRITICAL_PCT: i32 = 90;
+/// Days in a row over the warning threshold that raise a warning even when
+/// the whole window's percentile is still under it.
+const SUSTAINED_DAYS: u32 = 3;
+/// Fewer daily points than this give no growth projection.
+const MIN_TREND_DAYS: usize = 3;
+
+const UPLINK_SELECT: &str = r#"
+    SELECT u.id, u.tenant_id, u.router_id, r.name AS router_name, u.interface_name,
+           u.label, u.capacity_mbps, u.warning_pct, u.critical_pct,
+           u.created_at, u.updated_at
+    FROM mikrotik_uplinks u
+    LEFT JOIN mikrotik_routers r ON r.id = u.router_id
+"#;
+
+#[derive(Debug, sqlx::FromRow)]
+struct WindowStats {
+    samples: i64,
+    rx_p95_bps: Option<f64>,
+    tx_p95_bps: Option<f64>,
+    rx_peak_bps: Option<i64>,
+    tx_peak_bps: Option<i64>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DailyStats {
+    day: NaiveDate,
+    samples: i64,
+    rx_p95_bps: Option<f64>,
+    tx_p95_bps: Option<f64>,
+}
+
+fn round_to(value: f64, places: i32) -> f64 {
+    let f = 10f64.powi(places);
+    (value * f).round() / f
+}
+
+/// Busier direction against the capacity, in percent.
+fn utilization_pct(rx_bps: Option<f64>, tx_bps: Option<f64>, capacity_mbps: i32) -> Option<f64> {
+    let busiest = rx_bps.into_iter().chain(tx_bps).reduce(f64::max)?;
+    Some(round_to(
+        busiest / (capacity_mbps as f64 * 1_000_000.0) * 100.0,
+        2,
+    ))
+}
+
+/// Days at the end of the window over the threshold; a day without data
+/// ends the run.
+fn trailing_days_over(daily: &[UplinkDailyPercentile], threshold_pct: i32) -> u32 {
+    daily
+        .iter()
+        .rev()
+        .take_while(|d| {
+            d.utilization_pct
+                .map(|u| u >= threshold_pct as f64)
+                .unwrap_or(false)
+        })
+        .count() as u32
+}
+
+/// Least-squares line through the daily utilization, projected forward to
+/// 100%. Zero when the trend line is already there.
+fn days_to_capacity(daily: &[UplinkDailyPercentile]) -> Option<f64> {
+    let first = daily.first()?.day;
+    let points: Vec<(f64, f64)> = daily
+        .iter()
+        .filter_map(|d| {
+            d.utilization_pct
+                .map(|u| ((d.day - first).num_days() as f64, u))
+        })
+        .collect();
+    if points.len() < MIN_TREND_DAYS {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    if sxx == 0.0 {
+        return None;
+    }
+    let slope = sxy / sxx;
+    if slope <= 0.0 {
+        return None;
+    }
+    let last_x = points.last()?.0;
+    let fitted = mean_y + slope * (last_x - mean_x);
+    Some(round_to(((100.0 - fitted) / slope).max(0.0), 1))
+}
+
+fn capacity_level(
+    utilization_pct: Option<f64>,
+    days_over_warning: u32,
+    warning_pct: i32,
+    critical_pct: i32,
+) -> &'static str {
+    match utilization_pct {
+        None => "no_data",
+        Some(u) if u >= critical_pct as f64 => "critical",
+        Some(u) if u >= warning_pct as f64 || days_over_warning >= SUSTAINED_DAYS => "warning",
+        Some(_) => "ok",
+    }
+}
+
+struct ValidUplink {
+    router_id: String,
+    interface_name: String,
+    label: Option<String>,
+    capacity_mbps: i32,
+    warning_pct: i32,
+    critical_pct: i32,
+}
+
+fn validate_uplink(dto: UpsertUplinkInterfaceRequest) -> AppResult<ValidUplink> {
+    let router_id = dto.router_id.trim().to_string();
+    if router_id.is_empty() {
+        return Err(AppError::Validation("Router is required".to_string()));
+    }
+    let interface_name = dto.interface_name.trim().to_string();
+    if interface_name.is_empty() || interface_name.chars().count() > 64 {
+        return Err(AppError::Validation(
+            "Interface name is required (max 64 characters)".to_string(),
+        ));
+    }
+    if dto.capacity_mbps <= 0 {
+        return Err(AppError::Validation(
+            "Capacity must be greater than 0 Mbps".to_string(),
+        ));
+    }
+    let warning_pct = dto.warning_pct.unwrap_or(DEFAULT_WARNING_PCT);
+    let critical_pct = dto.critical_pct.unwrap_or(DEFAULT_CRITICAL_PCT);
+    if !(1..=100).contains(&warning_pct) || !(1..=100).contains(&critical_pct) {
+        return Err(AppError::Validation(
+            "Thresholds must be between 1 and 100%".to_string(),
+        ));
+    }
+    if warning_pct >= critical_pct {
+        return Err(AppError::Validation(
+            "Warning threshold must be below the critical threshold".to_string(),
+        ));
+    }
+    Ok(ValidUplink {
+        router_id,
+        interface_name,
+        label: dto
+            .label
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty()),
+        capacity_mbps: dto.capacity_mbps,
+        warning_pct,
+        critical_pct,
+    })
+}
+
+#[derive(Clone)]
+pub struct UplinkCapacityService {
+    pool: DbPool,
+    auth_service: AuthService,
+    audit_service: AuditService,
+}
+
+impl UplinkCapacityService {
+    pub fn new(pool: DbPool, auth_service: AuthService, audit_service: AuditService) -> Self {
+        Self {
+            pool,
+            auth_service,
+            audit_service,
+        }
+    }
+
+    pub async fn list_uplinks(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+    ) -> AppResult<Vec<UplinkInterface>> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "network_routers", "read")
+            .await?;
+        self.fetch_uplinks(tenant_id).await
+    }
+
+    async fn fetch_uplinks(&self, tenant_id: &str) -> AppResult<Vec<UplinkInterface>> {
+        let rows: Vec<UplinkInterface> = sqlx::query_as(&format!(
+            "{UPLINK_SELECT} WHERE u.tenant_id = $1 ORDER BY r.name, u.interface_name"
+        ))
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn get_uplink(&self, tenant_id: &str, id: &str) -> AppResult<UplinkInterface> {
+        let row: Option<UplinkInterface> = sqlx::query_as(&format!(
+            "{UPLINK_SELECT} WHERE u.tenant_id = $1 AND u.id = $2"
+        ))
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.ok_or_else(|| AppError::NotFound("Uplink not found".to_string()))
+    }
+
+    async fn ensure_router(&self, tenant_id: &str, router_id: &str) -> AppResult<()> {
+        let exists: Option<i32> = sqlx::query_scalar(
+            "SELECT 1 FROM mikrotik_routers WHERE tenant_id = $1 AND id = $2 AND deleted_at IS NULL",
+        )
+        .bind(tenant_id)
+        .bind(router_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        if exists.is_none() {
+            return Err(AppError::Validation("Router not found".to_string()));
+        }
+        Ok(())
+    }
+
+    fn map_unique_violation(e: sqlx::Error, interface_name: &str) -> AppError {
+        if e.as_database_error()
+            .and_then(|d| d.code().map(|c| c == "23505"))
+            .unwrap_or(false)
+        {
+            AppError::Conflict(format!(
+                "{} is already an uplink on this router",
+                interface_name
+            ))
+        } else {
+            AppError::Database(e)
+        }
+    }
+
+    pub async fn create_uplink(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        dto: UpsertUplinkInterfaceRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<UplinkInterface> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "network_routers", "manage")
+            .await?;
+        let v = validate_uplink(dto)?;
+        self.ensure_router(tenant_id, &v.router_id).await?;
+        let id = Uuid::new_v4().to_string();
+
+        sqlx::query(
+            r#"
+            INSERT INTO mikrotik_uplinks
+                (id, tenant_id, router_id, interface_name, label, capacity_mbps,
+                 warning_pct, critical_pct, created_at, updated_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$9)
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(&v.router_id)
+        .bind(&v.interface_name)
+        .bind(&v.label)
+        .bind(v.capacity_mbps)
+        .bind(v.warning_pct)
+        .bind(v.critical_pct)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::map_unique_violation(e, &v.interface_name))?;
+
+        let uplink = self.get_uplink(tenant_id, &id).await?;
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "UPLINK_CREATE",
+                "mikrotik_uplinks",
+                Some(&id),
+                Some(&format!(
+                    "Added uplink {} on {} ({} Mbps)",
+                    uplink.interface_name,
+                    uplink.router_name.as_deref().unwrap_or(&uplink.router_id),
+                    uplink.capacity_mbps
+                )),
+                ip_address,
+            )
+            .await;
+        Ok(uplink)
+    }
+
+    pub async fn update_uplink(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        dto: UpsertUplinkInterfaceRequest,
+        ip_address: Option<&str>,
+    ) -> AppResult<UplinkInterface> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "network_routers", "manage")
+            .await?;
+        let current = self.get_uplink(tenant_id, id).await?;
+        let v = validate_uplink(dto)?;
+        self.ensure_router(tenant_id, &v.router_id).await?;
+
+        sqlx::query(
+            r#"
+            UPDATE mikrotik_uplinks
+            SET router_id = $1, interface_name = $2, label = $3, capacity_mbps = $4,
+                warning_pct = $5, critical_pct = $6, updated_at = $7
+            WHERE tenant_id = $8 AND id = $9
+            "#,
+        )
+        .bind(&v.router_id)
+        .bind(&v.interface_name)
+        .bind(&v.label)
+        .bind(v.capacity_mbps)
+        .bind(v.warning_pct)
+        .bind(v.critical_pct)
+        .bind(Utc::now())
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::map_unique_violation(e, &v.interface_name))?;
+
+        let details = if current.capacity_mbps == v.capacity_mbps {
+            format!("Updated uplink {}", v.interface_name)
+        } else {
+            format!(
+                "Updated uplink {} capacity {} -> {} Mbps",
+                v.interface_name, current.capacity_mbps, v.capacity_mbps
+            )
+        };
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "UPLINK_UPDATE",
+                "mikrotik_uplinks",
+                Some(id),
+                Some(&details),
+                ip_address,
+            )
+            .await;
+
+        self.get_uplink(tenant_id, id).await
+    }
+
+    pub async fn delete_uplink(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        id: &str,
+        ip_address: Option<&str>,
+    ) -> AppResult<()> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "network_routers", "manage")
+            .await?;
+        let current = self.get_uplink(tenant_id, id).await?;
+        sqlx::query("DELETE FROM mikrotik_uplinks WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        self.audit_service
+            .log(
+                Some(actor_id),
+                Some(tenant_id),
+                "UPLINK_DELETE",
+                "mikrotik_uplinks",
+                Some(id),
+                Some(&format!(
+                    "Removed uplink {} on {}",
+                    current.interface_name,
+                    current.router_name.as_deref().unwrap_or(&current.router_id)
+                )),
+                ip_address,
+            )
+            .await;
+        Ok(())
+    }
+
+    /// 95th percentile utilization of every uplink over the last `days`
+    /// days (7 by default, at most 90), busiest first. Interface metrics
+    /// retention caps how far back data actually goes.
+    pub async fn report(
+        &self,
+        actor_id: &str,
+        tenant_id: &str,
+        days: Option<i64>,
+    ) -> AppResult<UplinkCapacityReport> {
+        self.auth_service
+            .check_permission(actor_id, tenant_id, "network_routers", "read")
+            .await?;
+        let days = days.unwrap_or(DEFAULT_REPORT_DAYS);
+        if !(1..=MAX_REPORT_DAYS).contains(&days) {
+            return Err(AppError::Validation(format!(
+                "days must be between 1 and {}",
+                MAX_REPORT_DAYS
+            )));
+        }
+        let to = Utc::now();
+        let from = to - Duration::days(days);
+
+        let mut rows = Vec::new();
+        for uplink in self.fetch_uplinks(tenant_id).await? {
+            rows.push(self.uplink_row(uplink, from, to).await?);
+        }
+        rows.sort_by(|a, b| {
+            b.utilization_pct
+                .unwrap_or(-1.0)
+                .total_cmp(&a.utilization_pct.unwrap_or(-1.0))
+        });
+
+        Ok(UplinkCapacityReport {
+            from,
+            to,
+            days,
+            uplinks: rows,
+            generated_at: Utc::now(),
+        })
+    }
+
+    async fn uplink_row(
+        &self,
+        uplink: UplinkInterface,
+        from: chrono::DateTime<Utc>,
+        to: chrono::DateTime<Utc>,
+    ) -> AppResult<UplinkCapacityRow> {
+        let window: WindowStats = sqlx::query_as(
+            r#"
+            SELECT
+              COUNT(*) FILTER (WHERE rx_bps IS NOT NULL OR tx_bps IS NOT NULL) AS samples,
+              percentile_cont(0.95) WITHIN GROUP (ORDER BY rx_bps)::float8 AS rx_p95_bps,
+              percentile_cont(0.95) WITHIN GROUP (ORDER BY tx_bps)::float8 AS tx_p95_bps,
+              MAX(rx_bps) AS rx_peak_bps,
+              MAX(tx_bps) AS tx_peak_bps
+            FROM mikrotik_interface_metrics
+            WHERE router_id = $1
+              AND lower(trim(interface_name)) = lower(trim($2))
+              AND ts >= $3 AND ts < $4
+            "#,
+        )
+        .bind(&uplink.router_id)
+        .bind(&uplink.interface_name)
+        .bind(from)
+        .bind(to)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let daily: Vec<DailyStats> = sqlx::query_as(
+            r#"
+            SELECT
+              (ts AT TIME ZONE 'UTC')::date AS day,
+              COUNT(*) FILTER (WHERE rx_bps IS NOT NULL OR tx_bps IS NOT NULL) AS samples,
+              percentile_cont(0.95) WITHIN GROUP (ORDER BY rx_bps)::float8 AS rx_p95_bps,
+              percentile_cont(0.95) WITHIN GROUP (ORDER BY tx_bps)::float8 AS tx_p95_bps
+            FROM mikrotik_interface_metrics
+            WHERE router_id = $1
+              AND lower(trim(interface_name)) = lower(trim($2))
+              AND ts >= $3 AND ts < $4
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+        )
+        .bind(&uplink.router_id)
+        .bind(&uplink.interface_name)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let daily: Vec<UplinkDailyPercentile> = daily
+            .into_iter()
+            .map(|d| UplinkDailyPercentile {
+                day: d.day,
+                samples: d.samples,
+                rx_p95_bps: d.rx_p95_bps,
+                tx_p95_bps: d.tx_p95_bps,
+                utilization_pct: utilization_pct(d.rx_p95_bps, d.tx_p95_bps, uplink.capacity_mbps),
+            })
+            .collect();
+
+        let utilization =
+            utilization_pct(window.rx_p95_bps, window.tx_p95_bps, uplink.capacity_mbps);
+        let days_over_warning = trailing_days_over(&daily, uplink.warning_pct);
+        let level = capacity_level(
+            utilization,
+            days_over_warning,
+            uplink.warning_pct,
+            uplink.critical_pct,
+        );
+
+        Ok(UplinkCapacityRow {
+            samples: window.samples,
+            rx_p95_bps: window.rx_p95_bps,
+            tx_p95_bps: window.tx_p95_bps,
+            rx_peak_bps: window.rx_peak_bps,
+            tx_peak_bps: window.tx_peak_bps,
+            utilization_pct: utilization,
+            level: level.to_string(),
+            days_over_warning,
+            days_to_capacity: days_to_capacity(&daily),
+            daily,
+            uplink,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{capacity_level, days_to_capacity, trailing_days_over, utilization_pct};
+    use crate::models::UplinkDailyPercentile;
+    use chrono::NaiveDate;
+
+    fn days(utilization: &[Option<f64>]) -> Vec<UplinkDailyPercentile> {
+        let start = NaiveDate::from_ymd_opt(2026, 5, 1).unwrap();
+        utilization
+            .iter()
+            .enumerate()
+            .map(|(i, u)| UplinkDailyPercentile {
+                day: start + chrono::Duration::days(i as i64),
+                samples: if u.is_some() { 100 } else { 0 },
+                rx_p95_bps: None,
+                tx_p95_bps: None,
+                utilization_pct: *u,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn utilization_uses_the_busier_direction() {
+        assert_eq!(
+            utilization_pct(Some(400_000_000.0), Some(50_000_000.0), 1000),
+            Some(40.0)
+        );
+        assert_eq!(utilization_pct(None, Some(50_000_000.0), 100), Some(50.0));
+        assert_eq!(utilization_pct(None, None, 100), None);
+    }
+
+    #[test]
+    fn sustained_days_over_warning_raise_a_warning() {
+        let daily = days(&[Some(20.0), Some(75.0), Some(72.0), Some(80.0)]);
+        assert_eq!(trailing_days_over(&daily, 70), 3);
+        assert_eq!(capacity_level(Some(65.0), 3, 70, 90), "warning");
+        assert_eq!(capacity_level(Some(65.0), 2, 70, 90), "ok");
+        assert_eq!(capacity_level(Some(91.0), 0, 70, 90), "critical");
+        assert_eq!(capacity_level(None, 0, 70, 90), "no_data");
+
+        let gap = days(&[Some(80.0), None, Some(80.0)]);
+        assert_eq!(trailing_days_over(&gap, 70), 1);
+    }
+
+    #[test]
+    fn growth_is_projected_to_full_capacity() {
+        // +5 points a day, ending at 60%: 8 days to go.
+        let daily = days(&[Some(40.0), Some(45.0), Some(50.0), Some(55.0), Some(60.0)]);
+        assert_eq!(days_to_capacity(&daily), Some(8.0));
+
+        let flat = days(&[Some(50.0), Some(50.0), Some(50.0)]);
+        assert_eq!(days_to_capacity(&flat), None);
+        assert_eq!(days_to_capacity(&days(&[Some(40.0), Some(90.0)])), None);
+
+        let over = days(&[Some(90.0), Some(100.0), Some(110.0)]);
+        assert_eq!(days_to_capacity(&over), Some(0.0));
+    }
+}
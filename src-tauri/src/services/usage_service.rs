@@ -0,0 +1,328 @@
+//! Usage metering and plan quota enforcement.
+//!
+//! Users, customers, routers and storage are gauges read from the rows that
+//! already exist (storage from `tenants.storage_usage`, kept by the storage
+//! service). Emails are a monthly counter in `tenant_usage_counters` that the
+//! email outbox bumps per message. Limits come from the plan features
+//! (`max_users`, `max_customers`, ...); the `quota_enforcement` feature picks
+//! whether going over is blocked ("hard") or only flagged ("soft").
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{TenantUsage, UsageMeter};
+use crate::services::PlanService;
+use chrono::{DateTime, Utc};
+use tracing::warn;
+
+/// Share of a limit at which a meter reports "warning".
+const WARNING_RATIO: f64 = 0.8;
+
+const BYTES_PER_GB: i64 = 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageMetric {
+    Users,
+    Customers,
+    Routers,
+    Storage,
+    EmailsMonthly,
+}
+
+impl UsageMetric {
+    pub const ALL: [UsageMetric; 5] = [
+        UsageMetric::Users,
+        UsageMetric::Customers,
+        UsageMetric::Routers,
+        UsageMetric::Storage,
+        UsageMetric::EmailsMonthly,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            UsageMetric::Users => "users",
+            UsageMetric::Customers => "customers",
+            UsageMetric::Routers => "routers",
+            UsageMetric::Storage => "storage",
+            UsageMetric::EmailsMonthly => "emails_month",
+        }
+    }
+
+    fn feature_code(self) -> &'static str {
+        match self {
+            UsageMetric::Users => "max_users",
+            UsageMetric::Customers => "max_customers",
+            UsageMetric::Routers => "max_routers",
+            UsageMetric::Storage => "max_storage_gb",
+            UsageMetric::EmailsMonthly => "max_emails_per_month",
+        }
+    }
+
+    fn unit(self) -> &'static str {
+        match self {
+            UsageMetric::Storage => "bytes",
+            _ => "count",
+        }
+    }
+
+    /// Plan limits are stored in GB for storage; everything is compared in
+    /// the meter's own unit.
+    fn limit_from_feature(self, value: i64) -> i64 {
+        match self {
+            UsageMetric::Storage => value.saturating_mul(BYTES_PER_GB),
+            _ => value,
+        }
+    }
+}
+
+/// "ok", "warning" (at 80% of the limit) or "exceeded" (at or past it).
+fn quota_status(used: i64, limit: Option<i64>) -> &'static str {
+    match limit {
+        None => "ok",
+        Some(limit) if used >= limit => "exceeded",
+        Some(limit) if (used as f64) >= (limit as f64) * WARNING_RATIO => "warning",
+        Some(_) => "ok",
+    }
+}
+
+/// Whether adding `adding` units on top of `used` goes past `limit`.
+fn would_exceed(used: i64, adding: i64, limit: i64) -> bool {
+    used.saturating_add(adding) > limit
+}
+
+fn describe(metric: UsageMetric, amount: i64) -> String {
+    match metric {
+        UsageMetric::Users => format!("{} users", amount),
+        UsageMetric::Customers => format!("{} customers", amount),
+        UsageMetric::Routers => format!("{} routers", amount),
+        UsageMetric::Storage => format!("{} GB of storage", amount / BYTES_PER_GB),
+        UsageMetric::EmailsMonthly => format!("{} emails this month", amount),
+    }
+}
+
+fn limit_message(metric: UsageMetric, used: i64, limit: i64) -> String {
+    let used = match metric {
+        UsageMetric::Storage => format!("{:.2} GB", used as f64 / BYTES_PER_GB as f64),
+        _ => used.to_string(),
+    };
+    format!(
+        "{} used of {} allowed on the current plan. Upgrade the plan to continue.",
+        used,
+        describe(metric, limit)
+    )
+}
+
+fn month_period(now: DateTime<Utc>) -> String {
+    now.format("%Y-%m").to_string()
+}
+
+#[derive(Clone)]
+pub struct UsageService {
+    pool: DbPool,
+    plan_service: PlanService,
+}
+
+impl UsageService {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            plan_service: PlanService::new(pool.clone()),
+            pool,
+        }
+    }
+
+    /// Current usage of one metric, in the metric's unit.
+    pub async fn usage(&self, tenant_id: &str, metric: UsageMetric) -> AppResult<i64> {
+        let used: i64 = match metric {
+            UsageMetric::Users => {
+                sqlx::query_scalar(
+                    r#"
+                    SELECT COUNT(*) FROM tenant_members
+                    WHERE tenant_id = $1 AND LOWER(COALESCE(role, '')) <> 'customer'
+                    "#,
+                )
+                .bind(tenant_id)
+                .fetch_one(&self.pool)
+                .await?
+            }
+            UsageMetric::Customers => {
+                sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM customers WHERE tenant_id = $1 AND deleted_at IS NULL",
+                )
+                .bind(tenant_id)
+                .fetch_one(&self.pool)
+                .await?
+            }
+            UsageMetric::Routers => sqlx::query_scalar(
+                "SELECT COUNT(*) FROM mikrotik_routers WHERE tenant_id = $1 AND deleted_at IS NULL",
+            )
+            .bind(tenant_id)
+            .fetch_one(&self.pool)
+            .await?,
+            UsageMetric::Storage => {
+                sqlx::query_scalar("SELECT COALESCE(storage_usage, 0) FROM tenants WHERE id = $1")
+                    .bind(tenant_id)
+                    .fetch_optional(&self.pool)
+                    .await?
+                    .unwrap_or(0)
+            }
+            UsageMetric::EmailsMonthly => sqlx::query_scalar(
+                r#"
+                SELECT value FROM tenant_usage_counters
+                WHERE tenant_id = $1 AND metric = $2 AND period = $3
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(metric.key())
+            .bind(month_period(Utc::now()))
+            .fetch_optional(&self.pool)
+            .await?
+            .unwrap_or(0),
+        };
+        Ok(used)
+    }
+
+    /// Plan limit for a metric in the metric's unit; None means unlimited.
+    pub async fn limit(&self, tenant_id: &str, metric: UsageMetric) -> AppResult<Option<i64>> {
+        let limit = self
+            .plan_service
+            .get_feature_limit(tenant_id, metric.feature_code())
+            .await?;
+        Ok(limit.map(|v| metric.limit_from_feature(v)))
+    }
+
+    /// Bump a counter-backed metric. Gauges are counted from their rows, so
+    /// recording them is a no-op.
+    pub async fn record(&self, tenant_id: &str, metric: UsageMetric, delta: i64) -> AppResult<()> {
+        if metric != UsageMetric::EmailsMonthly || delta == 0 {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_usage_counters (tenant_id, metric, period, value, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (tenant_id, metric, period)
+            DO UPDATE SET value = tenant_usage_counters.value + EXCLUDED.value,
+                          updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(metric.key())
+        .bind(month_period(now))
+        .bind(delta)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Check that `adding` more units fit in the plan. Hard enforcement
+    /// returns `QuotaExceeded` naming the limit; soft enforcement lets the
+    /// action through and only logs the overage.
+    pub async fn enforce(
+        &self,
+        tenant_id: &str,
+        metric: UsageMetric,
+        adding: i64,
+    ) -> AppResult<()> {
+        let Some(limit) = self.limit(tenant_id, metric).await? else {
+            return Ok(());
+        };
+        let used = self.usage(tenant_id, metric).await?;
+        if !would_exceed(used, adding, limit) {
+            return Ok(());
+        }
+
+        if self.plan_service.soft_quota(tenant_id).await? {
+            warn!(
+                "Tenant {} is over its {} quota ({} + {} > {}); soft enforcement, allowing",
+                tenant_id,
+                metric.key(),
+                used,
+                adding,
+                limit
+            );
+            return Ok(());
+        }
+
+        Err(AppError::QuotaExceeded(limit_message(metric, used, limit)))
+    }
+
+    /// Usage vs limits for every metered quota (`/api/tenant/usage`).
+    pub async fn tenant_usage(&self, tenant_id: &str) -> AppResult<TenantUsage> {
+        let (plan_name, plan_slug) =
+            match self.plan_service.get_tenant_subscription(tenant_id).await? {
+                Some(sub) => {
+                    let plan = self.plan_service.get_plan(&sub.plan_id).await?;
+                    (plan.name, plan.slug)
+                }
+                None => ("Free".to_string(), "free".to_string()),
+            };
+        let soft = self.plan_service.soft_quota(tenant_id).await?;
+
+        let mut meters = Vec::with_capacity(UsageMetric::ALL.len());
+        for metric in UsageMetric::ALL {
+            let used = self.usage(tenant_id, metric).await?;
+            let limit = self.limit(tenant_id, metric).await?;
+            meters.push(UsageMeter {
+                metric: metric.key().to_string(),
+                unit: metric.unit().to_string(),
+                used,
+                limit,
+                status: quota_status(used, limit).to_string(),
+            });
+        }
+
+        Ok(TenantUsage {
+            plan_name,
+            plan_slug,
+            enforcement: if soft { "soft" } else { "hard" }.to_string(),
+            period: month_period(Utc::now()),
+            meters,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn status_follows_the_limit() {
+        assert_eq!(quota_status(1_000, None), "ok");
+        assert_eq!(quota_status(7, Some(10)), "ok");
+        assert_eq!(quota_status(8, Some(10)), "warning");
+        assert_eq!(quota_status(10, Some(10)), "exceeded");
+        assert_eq!(quota_status(0, Some(0)), "exceeded");
+    }
+
+    #[test]
+    fn exceeding_counts_the_pending_units() {
+        assert!(!would_exceed(9, 1, 10));
+        assert!(would_exceed(10, 1, 10));
+        assert!(would_exceed(3, 8, 10));
+        assert!(!would_exceed(i64::MAX, 0, i64::MAX));
+    }
+
+    #[test]
+    fn storage_limits_are_compared_in_bytes() {
+        assert_eq!(UsageMetric::Storage.limit_from_feature(2), 2 * BYTES_PER_GB);
+        assert_eq!(UsageMetric::Routers.limit_from_feature(2), 2);
+        assert_eq!(
+            limit_message(UsageMetric::Storage, BYTES_PER_GB / 2, 2 * BYTES_PER_GB),
+            "0.50 GB used of 2 GB of storage allowed on the current plan. Upgrade the plan to continue."
+        );
+        assert_eq!(
+            limit_message(UsageMetric::Routers, 3, 3),
+            "3 used of 3 routers allowed on the current plan. Upgrade the plan to continue."
+        );
+    }
+
+    #[test]
+    fn monthly_period_is_the_utc_month() {
+        let at = Utc.with_ymd_and_hms(2026, 10, 31, 23, 59, 0).unwrap();
+        assert_eq!(month_period(at), "2026-10");
+    }
+}
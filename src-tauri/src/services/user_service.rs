@@ -3,11 +3,12 @@
 use crate::db::connection::DbPool;
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    CreateUserAddressDto, CreateUserDto, UpdateUserAddressDto, UpdateUserDto, User, UserAddress,
-    UserResponse,
+    CreateUserAddressDto, CreateUserDto, TrashItem, UpdateUserAddressDto, UpdateUserDto, User,
+    UserAddress, UserLocale, UserResponse,
 };
 use crate::services::audit_service::AuditService;
 use crate::services::auth_service::AuthService;
+use crate::services::locale::normalize_locale;
 use chrono::Utc;
 
 /// User service for managing users
@@ -63,6 +64,7 @@ impl UserService {
             LEFT JOIN tenant_members tm ON u.id = tm.user_id
             LEFT JOIN tenants t ON tm.tenant_id = t.id
             LEFT JOIN roles r ON tm.role_id = r.id
+            WHERE u.deleted_at IS NULL
             ORDER BY u.created_at DESC 
             LIMIT $1 OFFSET $2
         "#;
@@ -73,11 +75,12 @@ impl UserService {
                 u.*, 
                 t.slug as tenant_slug,
                 r.name as tenant_role_name,
-                (SELECT COUNT(*) FROM users) as total_count
+                (SELECT COUNT(*) FROM users WHERE deleted_at IS NULL) as total_count
             FROM users u
             LEFT JOIN tenant_members tm ON u.id = tm.user_id
             LEFT JOIN tenants t ON tm.tenant_id = t.id
             LEFT JOIN roles r ON tm.role_id = r.id
+            WHERE u.deleted_at IS NULL
             ORDER BY u.created_at DESC 
             LIMIT ? OFFSET ?
         "#;
@@ -116,7 +119,7 @@ impl UserService {
 
     /// Get user by ID
     pub async fn get_by_id(&self, id: &str) -> AppResult<UserResponse> {
-        let user: User = sqlx::query_as("SELECT * FROM users WHERE id = $1")
+        let user: User = sqlx::query_as("SELECT * FROM users WHERE id = $1 AND deleted_at IS NULL")
             .bind(id)
             .fetch_optional(&self.pool)
             .await?
@@ -193,11 +196,12 @@ impl UserService {
         actor_id: Option<&str>,
         ip_address: Option<&str>,
     ) -> AppResult<UserResponse> {
-        let mut user: User = sqlx::query_as("SELECT * FROM users WHERE id = $1")
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await?
-            .ok_or(AppError::UserNotFound)?;
+        let mut user: User =
+            sqlx::query_as("SELECT * FROM users WHERE id = $1 AND deleted_at IS NULL")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or(AppError::UserNotFound)?;
 
         let before_email = user.email.clone();
         let before_name = user.name.clone();
@@ -288,17 +292,34 @@ impl UserService {
         Ok(user.into())
     }
 
-    /// Delete user
+    /// Move a user to the trash. The account is deactivated until restored,
+    /// remembering whether it was active.
     pub async fn delete(
         &self,
         id: &str,
         actor_id: Option<&str>,
         ip_address: Option<&str>,
     ) -> AppResult<()> {
-        let result = sqlx::query("DELETE FROM users WHERE id = $1")
-            .bind(id)
-            .execute(&self.pool)
-            .await?;
+        let now = Utc::now();
+
+        #[cfg(feature = "postgres")]
+        let result = sqlx::query(
+            "UPDATE users SET deleted_at = $2, active_before_delete = is_active, is_active = false, updated_at = $2 WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let result = sqlx::query(
+            "UPDATE users SET deleted_at = ?, active_before_delete = is_active, is_active = 0, updated_at = ? WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
 
         if result.rows_affected() == 0 {
             return Err(AppError::UserNotFound);
@@ -312,7 +333,7 @@ impl UserService {
                 "USER_DELETE",
                 "user",
                 Some(id),
-                Some("Deleted user"),
+                Some("Moved user to trash"),
                 ip_address,
             )
             .await;
@@ -320,14 +341,114 @@ impl UserService {
         Ok(())
     }
 
+    /// List users in the trash
+    pub async fn list_deleted(&self) -> AppResult<Vec<TrashItem>> {
+        let rows: Vec<TrashItem> = sqlx::query_as(
+            "SELECT id, name, email AS detail, deleted_at FROM users WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Restore a user from the trash. The account is active again only if it
+    /// was when deleted.
+    pub async fn restore(
+        &self,
+        id: &str,
+        actor_id: Option<&str>,
+        ip_address: Option<&str>,
+    ) -> AppResult<UserResponse> {
+        let now = Utc::now();
+
+        #[cfg(feature = "postgres")]
+        let result = sqlx::query(
+            "UPDATE users SET deleted_at = NULL, is_active = COALESCE(active_before_delete, is_active), active_before_delete = NULL, updated_at = $2 WHERE id = $1 AND deleted_at IS NOT NULL",
+        )
+        .bind(id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        let result = sqlx::query(
+            "UPDATE users SET deleted_at = NULL, is_active = COALESCE(active_before_delete, is_active), active_before_delete = NULL, updated_at = ? WHERE id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(now.to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::UserNotFound);
+        }
+
+        self.audit_service
+            .log(
+                actor_id,
+                None,
+                "USER_RESTORE",
+                "user",
+                Some(id),
+                Some("Restored user from trash"),
+                ip_address,
+            )
+            .await;
+
+        self.get_by_id(id).await
+    }
+
     /// Count all users
     pub async fn count(&self) -> AppResult<i64> {
-        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
+        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users WHERE deleted_at IS NULL")
             .fetch_one(&self.pool)
             .await?;
         Ok(count.0)
     }
 
+    pub async fn get_locale(&self, user_id: &str) -> AppResult<UserLocale> {
+        #[cfg(feature = "postgres")]
+        let query = "SELECT locale FROM users WHERE id = $1";
+        #[cfg(feature = "sqlite")]
+        let query = "SELECT locale FROM users WHERE id = ?";
+
+        let locale: Option<Option<String>> = sqlx::query_scalar(query)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(locale) = locale else {
+            return Err(AppError::NotFound("User not found".to_string()));
+        };
+        Ok(UserLocale { locale })
+    }
+
+    /// Sets the locale used to pick announcement variants; `None` clears it.
+    pub async fn set_locale(&self, user_id: &str, locale: Option<String>) -> AppResult<UserLocale> {
+        let locale = match locale.as_deref().map(str::trim).filter(|l| !l.is_empty()) {
+            Some(tag) => Some(
+                normalize_locale(tag)
+                    .ok_or_else(|| AppError::Validation(format!("Invalid locale '{}'", tag)))?,
+            ),
+            None => None,
+        };
+
+        #[cfg(feature = "postgres")]
+        let query = "UPDATE users SET locale = $1, updated_at = $2 WHERE id = $3";
+        #[cfg(feature = "sqlite")]
+        let query = "UPDATE users SET locale = ?, updated_at = ? WHERE id = ?";
+
+        let res = sqlx::query(query)
+            .bind(&locale)
+            .bind(Utc::now())
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound("User not found".to_string()));
+        }
+        Ok(UserLocale { locale })
+    }
+
     // --- User Addresses (Multi Address Support) ---
 
     pub async fn list_addresses(&self, user_id: &str) -> AppResult<Vec<UserAddress>> {
@@ -743,3 +864,64 @@ impl UserService {
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::db::migrations::MIGRATOR;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn user_service() -> UserService {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        UserService::new(pool.clone(), AuditService::new(pool, None))
+    }
+
+    fn create_dto(email: &str) -> CreateUserDto {
+        CreateUserDto {
+            email: email.to_string(),
+            password: "correct-horse-battery".to_string(),
+            name: "Test User".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restore_keeps_active_state_from_before_delete() {
+        let users = user_service().await;
+
+        let inactive = users
+            .create(create_dto("inactive@example.com"), None, None)
+            .await
+            .unwrap();
+        users
+            .update(
+                &inactive.id,
+                UpdateUserDto {
+                    email: None,
+                    name: None,
+                    role: None,
+                    is_super_admin: None,
+                    is_active: Some(false),
+                },
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        users.delete(&inactive.id, None, None).await.unwrap();
+        let restored = users.restore(&inactive.id, None, None).await.unwrap();
+        assert!(!restored.is_active);
+
+        let active = users
+            .create(create_dto("active@example.com"), None, None)
+            .await
+            .unwrap();
+        users.delete(&active.id, None, None).await.unwrap();
+        let restored = users.restore(&active.id, None, None).await.unwrap();
+        assert!(restored.is_active);
+    }
+}
@@ -0,0 +1,550 @@
+//! Web Push delivery: VAPID (RFC 8292) with aes128gcm payload encryption (RFC 8291).
+//!
+//! The application server key pair comes from `VAPID_PRIVATE_KEY` when set, otherwise
+//! from the global `vapid_private_key` setting. When neither exists a P-256 pair is
+//! generated on first use and stored in settings, so pushes work without manual setup.
+//! Browsers read the public half from `GET /api/notifications/push/vapid-public-key`.
+//!
+//! Each subscription remembers the public key it was created under. Subscriptions made
+//! under another key, past their `expiration_time`, answered with 404/410, or failing
+//! `MAX_CONSECUTIVE_FAILURES` times in a row are removed.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreatePushSubscriptionRequest, Notification, PushSubscription, UpsertSettingDto,
+};
+use crate::services::SettingsService;
+use axum::http::Uri;
+use base64ct::{Base64UrlUnpadded, Encoding};
+use chrono::{DateTime, TimeZone, Utc};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+use web_push_native::{
+    jwt_simple::algorithms::ES256KeyPair, p256::PublicKey, Auth, WebPushBuilder,
+};
+
+/// Used when neither the `vapid_subject` setting nor `VAPID_SUBJECT` is set.
+const DEFAULT_SUBJECT: &str = "mailto:admin@example.com";
+/// Push services keep undelivered messages this long (`TTL` header).
+const PUSH_TTL_SECS: u64 = 24 * 60 * 60;
+/// 4096-byte record limit minus the aes128gcm header (86), tag (16) and padding delimiter (1).
+const MAX_PAYLOAD_BYTES: usize = 3993;
+const MAX_CONSECUTIVE_FAILURES: i32 = 5;
+
+/// The application server key pair and the contact sent in the VAPID JWT.
+pub struct VapidKeys {
+    key_pair: ES256KeyPair,
+    /// Uncompressed SEC1 point, base64url (the browser's `applicationServerKey`).
+    pub public_key: String,
+    pub subject: String,
+}
+
+impl VapidKeys {
+    fn from_private(private_key: &str, subject: String) -> Result<Self, String> {
+        let bytes = decode_key(private_key).ok_or("VAPID private key is not valid base64")?;
+        let key_pair = ES256KeyPair::from_bytes(&bytes)
+            .map_err(|e| format!("VAPID private key is not a P-256 scalar: {}", e))?;
+        Ok(Self::with_key_pair(key_pair, subject))
+    }
+
+    fn generate(subject: String) -> Self {
+        Self::with_key_pair(ES256KeyPair::generate(), subject)
+    }
+
+    fn with_key_pair(key_pair: ES256KeyPair, subject: String) -> Self {
+        let public_key =
+            Base64UrlUnpadded::encode_string(&key_pair.public_key().to_bytes_uncompressed());
+        Self {
+            key_pair,
+            public_key,
+            subject,
+        }
+    }
+
+    fn private_key(&self) -> String {
+        Base64UrlUnpadded::encode_string(&self.key_pair.to_bytes())
+    }
+}
+
+/// What a push service response means for the subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PushOutcome {
+    Delivered,
+    /// The subscription no longer exists (404/410).
+    Gone,
+    /// Worth retrying on the next notification; counts toward the failure limit.
+    Failed,
+}
+
+fn classify_status(status: u16) -> PushOutcome {
+    match status {
+        200..=299 => PushOutcome::Delivered,
+        404 | 410 => PushOutcome::Gone,
+        _ => PushOutcome::Failed,
+    }
+}
+
+/// Subscription keys arrive base64url from `PushSubscription.getKey()`, but older
+/// clients sent standard base64 with padding.
+fn decode_key(value: &str) -> Option<Vec<u8>> {
+    let normalized = value.trim().replace('+', "-").replace('/', "_");
+    Base64UrlUnpadded::decode_vec(normalized.trim_end_matches('=')).ok()
+}
+
+/// The VAPID `sub` claim must be a `mailto:` or `https:` URI.
+fn normalize_subject(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    if raw.starts_with("mailto:") || raw.starts_with("https://") {
+        Some(raw.to_string())
+    } else if raw.contains('@') && !raw.contains(':') {
+        Some(format!("mailto:{}", raw))
+    } else {
+        None
+    }
+}
+
+/// Expired, or created under a different application server key (the push
+/// service would reject our VAPID token for it).
+fn is_stale(sub: &PushSubscription, now: DateTime<Utc>, current_key: &str) -> bool {
+    sub.expiration_time.is_some_and(|at| at <= now)
+        || sub
+            .vapid_public_key
+            .as_deref()
+            .is_some_and(|key| key != current_key)
+}
+
+fn urgency(notif: &Notification) -> &'static str {
+    if notif.category == "security" || notif.notification_type == "error" {
+        "high"
+    } else {
+        "normal"
+    }
+}
+
+/// JSON read by `static/sw.js`. The message is shortened until the encrypted
+/// record fits in 4 KB.
+fn encode_payload(notif: &Notification) -> Vec<u8> {
+    let mut message = notif.message.clone();
+    loop {
+        let payload = serde_json::json!({
+            "id": notif.id,
+            "title": notif.title,
+            "message": message,
+            "action_url": notif.action_url,
+            "category": notif.category,
+            "notification_type": notif.notification_type,
+        })
+        .to_string()
+        .into_bytes();
+        if payload.len() <= MAX_PAYLOAD_BYTES || message.is_empty() {
+            return payload;
+        }
+        let overflow = payload.len() - MAX_PAYLOAD_BYTES;
+        let keep = message.chars().count().saturating_sub(overflow + 1);
+        message = message.chars().take(keep).collect::<String>() + "…";
+        if keep == 0 {
+            message.clear();
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WebPushService {
+    pool: DbPool,
+    settings_service: SettingsService,
+    http: reqwest::Client,
+    /// Serializes first-use key generation so concurrent sends agree on one pair.
+    key_lock: Arc<Mutex<()>>,
+}
+
+impl WebPushService {
+    pub fn new(pool: DbPool, settings_service: SettingsService) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .unwrap_or_default();
+        Self {
+            pool,
+            settings_service,
+            http,
+            key_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    async fn setting(&self, key: &str) -> Option<String> {
+        self.settings_service
+            .get_value(None, key)
+            .await
+            .ok()
+            .flatten()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    }
+
+    async fn store_setting(&self, key: &str, value: &str, description: &str) -> AppResult<()> {
+        let dto = UpsertSettingDto {
+            key: key.to_string(),
+            value: value.to_string(),
+            description: Some(description.to_string()),
+            expected_updated_at: None,
+        };
+        self.settings_service.upsert(None, dto, None, None).await?;
+        Ok(())
+    }
+
+    async fn subject(&self) -> String {
+        self.setting("vapid_subject")
+            .await
+            .or_else(|| std::env::var("VAPID_SUBJECT").ok())
+            .as_deref()
+            .and_then(normalize_subject)
+            .unwrap_or_else(|| DEFAULT_SUBJECT.to_string())
+    }
+
+    /// Load the key pair, generating and storing one on first use.
+    pub async fn vapid_keys(&self) -> AppResult<VapidKeys> {
+        let subject = self.subject().await;
+
+        if let Some(private_key) = std::env::var("VAPID_PRIVATE_KEY")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+        {
+            return VapidKeys::from_private(&private_key, subject).map_err(AppError::Internal);
+        }
+
+        let _guard = self.key_lock.lock().await;
+
+        if let Some(private_key) = self.setting("vapid_private_key").await {
+            let keys =
+                VapidKeys::from_private(&private_key, subject).map_err(AppError::Internal)?;
+            // Keep the displayed public key in sync if someone pasted only the private half.
+            if self.setting("vapid_public_key").await.as_deref() != Some(keys.public_key.as_str()) {
+                self.store_setting(
+                    "vapid_public_key",
+                    &keys.public_key,
+                    "Web Push application server public key (base64url)",
+                )
+                .await?;
+            }
+            return Ok(keys);
+        }
+
+        let keys = VapidKeys::generate(subject);
+        self.store_setting(
+            "vapid_private_key",
+            &keys.private_key(),
+            "Web Push application server private key (base64url); generated when empty",
+        )
+        .await?;
+        self.store_setting(
+            "vapid_public_key",
+            &keys.public_key,
+            "Web Push application server public key (base64url)",
+        )
+        .await?;
+        info!("Generated a new VAPID key pair for Web Push");
+        Ok(keys)
+    }
+
+    pub async fn public_key(&self) -> AppResult<String> {
+        Ok(self.vapid_keys().await?.public_key)
+    }
+
+    /// Store a browser subscription. The endpoint is unique per browser profile, so
+    /// re-subscribing (new keys, another user signing in) replaces the row.
+    pub async fn subscribe(
+        &self,
+        user_id: &str,
+        req: CreatePushSubscriptionRequest,
+    ) -> AppResult<()> {
+        let endpoint = req.endpoint.trim();
+        if !endpoint.starts_with("https://") || endpoint.parse::<Uri>().is_err() {
+            return Err(AppError::Validation(
+                "Push endpoint must be an https URL".to_string(),
+            ));
+        }
+        let p256dh = decode_key(&req.p256dh)
+            .filter(|b| PublicKey::from_sec1_bytes(b).is_ok())
+            .ok_or_else(|| AppError::Validation("Invalid p256dh key".to_string()))?;
+        let auth = decode_key(&req.auth)
+            .filter(|b| b.len() == 16)
+            .ok_or_else(|| AppError::Validation("Invalid auth secret".to_string()))?;
+
+        let expiration_time = req
+            .expiration_time
+            .and_then(|ms| Utc.timestamp_millis_opt(ms).single());
+        let vapid_public_key = self.public_key().await.ok();
+        let now = Utc::now();
+
+        sqlx::query(
+            r#"
+            INSERT INTO push_subscriptions
+                (id, user_id, endpoint, p256dh, auth, created_at,
+                 expiration_time, vapid_public_key, failure_count, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 0, $6)
+            ON CONFLICT (endpoint) DO UPDATE SET
+                user_id = EXCLUDED.user_id,
+                p256dh = EXCLUDED.p256dh,
+                auth = EXCLUDED.auth,
+                expiration_time = EXCLUDED.expiration_time,
+                vapid_public_key = EXCLUDED.vapid_public_key,
+                failure_count = 0,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(user_id)
+        .bind(endpoint)
+        .bind(Base64UrlUnpadded::encode_string(&p256dh))
+        .bind(Base64UrlUnpadded::encode_string(&auth))
+        .bind(now)
+        .bind(expiration_time)
+        .bind(vapid_public_key)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn remove(&self, id: &str) -> AppResult<()> {
+        sqlx::query("DELETE FROM push_subscriptions WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn record_outcome(&self, sub: &PushSubscription, outcome: PushOutcome) -> AppResult<()> {
+        let now = Utc::now();
+        match outcome {
+            PushOutcome::Delivered => {
+                sqlx::query(
+                    "UPDATE push_subscriptions SET failure_count = 0, last_success_at = $1, updated_at = $1 WHERE id = $2",
+                )
+                .bind(now)
+                .bind(&sub.id)
+                .execute(&self.pool)
+                .await?;
+            }
+            PushOutcome::Gone => {
+                self.remove(&sub.id).await?;
+                info!("Removed expired push subscription {}", sub.endpoint);
+            }
+            PushOutcome::Failed => {
+                sqlx::query(
+                    "UPDATE push_subscriptions SET failure_count = failure_count + 1, updated_at = $1 WHERE id = $2",
+                )
+                .bind(now)
+                .bind(&sub.id)
+                .execute(&self.pool)
+                .await?;
+                if sub.failure_count + 1 >= MAX_CONSECUTIVE_FAILURES {
+                    self.remove(&sub.id).await?;
+                    warn!(
+                        "Removed push subscription {} after {} consecutive failures",
+                        sub.endpoint, MAX_CONSECUTIVE_FAILURES
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn push_one(
+        &self,
+        keys: &VapidKeys,
+        sub: &PushSubscription,
+        payload: Vec<u8>,
+        urgency: &str,
+    ) -> Result<PushOutcome, String> {
+        let public_key = decode_key(&sub.p256dh)
+            .and_then(|b| PublicKey::from_sec1_bytes(&b).ok())
+            .ok_or("invalid p256dh key")?;
+        let auth = decode_key(&sub.auth)
+            .filter(|b| b.len() == 16)
+            .ok_or("invalid auth secret")?;
+        let endpoint: Uri = sub
+            .endpoint
+            .parse()
+            .map_err(|e| format!("invalid endpoint: {}", e))?;
+
+        let request = WebPushBuilder::new(endpoint, public_key, Auth::clone_from_slice(&auth))
+            .with_vapid(&keys.key_pair, &keys.subject)
+            .build(payload)
+            .map_err(|e| format!("failed to build push request: {:?}", e))?;
+
+        let (parts, body) = request.into_parts();
+        let mut builder = self.http.post(parts.uri.to_string());
+        for (name, value) in parts.headers.iter() {
+            if matches!(name.as_str(), "ttl" | "urgency") {
+                continue;
+            }
+            if let Ok(v) = value.to_str() {
+                builder = builder.header(name.as_str(), v);
+            }
+        }
+        let response = builder
+            .header("TTL", PUSH_TTL_SECS.to_string())
+            .header("Urgency", urgency)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("request failed: {}", e))?;
+
+        let status = response.status().as_u16();
+        let outcome = classify_status(status);
+        if outcome == PushOutcome::Failed {
+            let detail = response.text().await.unwrap_or_default();
+            warn!(
+                "Push to {} failed with status {}: {}",
+                sub.endpoint,
+                status,
+                detail.chars().take(200).collect::<String>()
+            );
+        }
+        Ok(outcome)
+    }
+
+    /// Push a notification to every browser the user subscribed. Returns how many
+    /// push services accepted it.
+    pub async fn send_notification(&self, notif: &Notification) -> AppResult<usize> {
+        let subscriptions = sqlx::query_as::<_, PushSubscription>(
+            "SELECT * FROM push_subscriptions WHERE user_id = $1",
+        )
+        .bind(&notif.user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        if subscriptions.is_empty() {
+            return Ok(0);
+        }
+
+        let keys = self.vapid_keys().await?;
+        let payload = encode_payload(notif);
+        let urgency = urgency(notif);
+        let now = Utc::now();
+        let mut delivered = 0;
+
+        for sub in subscriptions {
+            if is_stale(&sub, now, &keys.public_key) {
+                self.remove(&sub.id).await?;
+                debug!("Dropped stale push subscription {}", sub.endpoint);
+                continue;
+            }
+            let outcome = match self.push_one(&keys, &sub, payload.clone(), urgency).await {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    warn!("Push to {} failed: {}", sub.endpoint, e);
+                    PushOutcome::Failed
+                }
+            };
+            if outcome == PushOutcome::Delivered {
+                delivered += 1;
+            }
+            self.record_outcome(&sub, outcome).await?;
+        }
+
+        Ok(delivered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription() -> PushSubscription {
+        PushSubscription::new(
+            "user-1".to_string(),
+            "https://push.example.com/abc".to_string(),
+            "p256dh".to_string(),
+            "auth".to_string(),
+        )
+    }
+
+    #[test]
+    fn decodes_url_safe_and_standard_base64() {
+        assert_eq!(decode_key("-_8"), Some(vec![0xfb, 0xff]));
+        assert_eq!(decode_key("+/8="), Some(vec![0xfb, 0xff]));
+        assert_eq!(decode_key("not base64!"), None);
+    }
+
+    #[test]
+    fn generated_keys_round_trip() {
+        let keys = VapidKeys::generate(DEFAULT_SUBJECT.to_string());
+        let public = decode_key(&keys.public_key).unwrap();
+        assert_eq!(public.len(), 65);
+        assert_eq!(public[0], 0x04);
+
+        let reloaded =
+            VapidKeys::from_private(&keys.private_key(), DEFAULT_SUBJECT.to_string()).unwrap();
+        assert_eq!(reloaded.public_key, keys.public_key);
+        assert!(VapidKeys::from_private("AAAA", DEFAULT_SUBJECT.to_string()).is_err());
+    }
+
+    #[test]
+    fn normalizes_subject() {
+        assert_eq!(
+            normalize_subject(" ops@isp.example "),
+            Some("mailto:ops@isp.example".to_string())
+        );
+        assert_eq!(
+            normalize_subject("https://isp.example"),
+            Some("https://isp.example".to_string())
+        );
+        assert_eq!(normalize_subject("http://isp.example"), None);
+        assert_eq!(normalize_subject(""), None);
+    }
+
+    #[test]
+    fn classifies_push_service_responses() {
+        assert_eq!(classify_status(201), PushOutcome::Delivered);
+        assert_eq!(classify_status(404), PushOutcome::Gone);
+        assert_eq!(classify_status(410), PushOutcome::Gone);
+        assert_eq!(classify_status(403), PushOutcome::Failed);
+        assert_eq!(classify_status(429), PushOutcome::Failed);
+        assert_eq!(classify_status(503), PushOutcome::Failed);
+    }
+
+    #[test]
+    fn stale_when_expired_or_created_under_another_key() {
+        let now = Utc::now();
+        let mut sub = subscription();
+        assert!(!is_stale(&sub, now, "key-a"));
+
+        sub.vapid_public_key = Some("key-a".to_string());
+        assert!(!is_stale(&sub, now, "key-a"));
+        assert!(is_stale(&sub, now, "key-b"));
+
+        sub.expiration_time = Some(now + chrono::Duration::hours(1));
+        assert!(!is_stale(&sub, now, "key-a"));
+        sub.expiration_time = Some(now - chrono::Duration::seconds(1));
+        assert!(is_stale(&sub, now, "key-a"));
+    }
+
+    #[test]
+    fn payload_is_truncated_to_fit_one_record() {
+        let mut notif = Notification::new(
+            "user-1".to_string(),
+            None,
+            "Router offline".to_string(),
+            "é".repeat(5000),
+            "error".to_string(),
+            "system".to_string(),
+            Some("/admin/network".to_string()),
+        );
+        let payload = encode_payload(&notif);
+        assert!(payload.len() <= MAX_PAYLOAD_BYTES);
+        let json: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(json["id"], notif.id.as_str());
+        assert!(json["message"].as_str().unwrap().ends_with('…'));
+        assert_eq!(urgency(&notif), "high");
+
+        notif.message = "short".to_string();
+        notif.notification_type = "info".to_string();
+        let json: serde_json::Value = serde_json::from_slice(&encode_payload(&notif)).unwrap();
+        assert_eq!(json["message"], "short");
+        assert_eq!(urgency(&notif), "normal");
+    }
+}
@@ -0,0 +1,1245 @@
+//! WhatsApp notification channel.
+//!
+//! Customers receive invoices, payment reminders and outage notices on WhatsApp.
+//! Each event is routed to a tenant template through the `whatsapp_route_<event>`
+//! setting (empty = not sent). Templates go out through the Meta Cloud API or a
+//! generic HTTP gateway (`whatsapp_provider`), every send is logged in
+//! `whatsapp_messages`, and provider status callbacks move it through
+//! sent -> delivered -> read (or failed).
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    UpsertWhatsappTemplateRequest, WhatsappMessage, WhatsappRoute, WhatsappTemplate,
+};
+use crate::services::event_outbox_service::hmac_sha256_hex;
+use crate::services::SettingsService;
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+const DEFAULT_META_API_VERSION: &str = "v21.0";
+
+/// Events that can be routed to a WhatsApp template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhatsappEvent {
+    InvoiceCreated,
+    InvoiceReminder,
+    OutageNotice,
+}
+
+impl WhatsappEvent {
+    pub const ALL: [WhatsappEvent; 3] = [
+        WhatsappEvent::InvoiceCreated,
+        WhatsappEvent::InvoiceReminder,
+        WhatsappEvent::OutageNotice,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WhatsappEvent::InvoiceCreated => "invoice_created",
+            WhatsappEvent::InvoiceReminder => "invoice_reminder",
+            WhatsappEvent::OutageNotice => "outage_notice",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str() == s.trim())
+    }
+
+    /// Variables the event fills in for its template.
+    pub fn variables(&self) -> &'static [&'static str] {
+        match self {
+            WhatsappEvent::InvoiceCreated => &[
+                "customer_name",
+                "invoice_number",
+                "amount",
+                "due_date",
+                "pay_url",
+            ],
+            WhatsappEvent::InvoiceReminder => &[
+                "customer_name",
+                "invoice_number",
+                "due_date",
+                "reminder",
+                "pay_url",
+            ],
+            WhatsappEvent::OutageNotice => &["customer_name", "area", "message"],
+        }
+    }
+
+    fn route_key(&self) -> String {
+        format!("whatsapp_route_{}", self.as_str())
+    }
+}
+
+/// Who a message goes to.
+#[derive(Debug, Clone)]
+pub struct WhatsappRecipient {
+    pub customer_id: Option<String>,
+    pub name: String,
+    pub phone: String,
+}
+
+enum Provider {
+    Meta {
+        phone_number_id: String,
+        access_token: String,
+        api_version: String,
+    },
+    Gateway {
+        url: String,
+        token: Option<String>,
+    },
+}
+
+impl Provider {
+    fn name(&self) -> &'static str {
+        match self {
+            Provider::Meta { .. } => "meta",
+            Provider::Gateway { .. } => "gateway",
+        }
+    }
+}
+
+/// A delivery status reported by a provider callback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusUpdate {
+    pub provider_message_id: String,
+    pub status: String,
+    pub error: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct WhatsappService {
+    pool: DbPool,
+    settings_service: SettingsService,
+    http: reqwest::Client,
+}
+
+impl WhatsappService {
+    pub fn new(pool: DbPool, settings_service: SettingsService) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(20))
+            .build()
+            .unwrap_or_default();
+        Self {
+            pool,
+            settings_service,
+            http,
+        }
+    }
+
+    // ================= Sending =================
+
+    /// Send the template routed for `event` to one recipient. Returns `None` when
+    /// the channel is off for the tenant, the event is not routed, or the phone
+    /// number is unusable; provider errors are recorded on the returned message.
+    pub async fn send_event(
+        &self,
+        tenant_id: &str,
+        event: WhatsappEvent,
+        recipient: &WhatsappRecipient,
+        vars: &HashMap<&str, String>,
+    ) -> AppResult<Option<WhatsappMessage>> {
+        let Some(template_name) = self.route(tenant_id, event).await? else {
+            return Ok(None);
+        };
+        let Some(provider) = self.provider(tenant_id).await? else {
+            return Ok(None);
+        };
+        let Some(template) = self.find_active_template(tenant_id, &template_name).await? else {
+            warn!(
+                "WhatsApp template '{}' routed for {} is missing or inactive (tenant={})",
+                template_name,
+                event.as_str(),
+                tenant_id
+            );
+            return Ok(None);
+        };
+        let Some(phone) = normalize_phone(&recipient.phone) else {
+            warn!(
+                "Skipping WhatsApp {}: invalid phone number for customer {:?}",
+                event.as_str(),
+                recipient.customer_id
+            );
+            return Ok(None);
+        };
+
+        let message = self
+            .deliver(
+                tenant_id,
+                event.as_str(),
+                &provider,
+                &template,
+                &phone,
+                recipient.customer_id.as_deref(),
+                vars,
+            )
+            .await?;
+        Ok(Some(message))
+    }
+
+    /// Send a template with placeholder values to a phone number, ignoring routing.
+    pub async fn send_test(
+        &self,
+        tenant_id: &str,
+        template_id: &str,
+        phone: &str,
+    ) -> AppResult<WhatsappMessage> {
+        let template = self.get_template(tenant_id, template_id).await?;
+        let provider = self.provider(tenant_id).await?.ok_or_else(|| {
+            AppError::Validation("WhatsApp is not enabled or not configured".to_string())
+        })?;
+        let phone = normalize_phone(phone)
+            .ok_or_else(|| AppError::Validation("Invalid phone number".to_string()))?;
+
+        let names = template_variables(&template.body);
+        let vars: HashMap<&str, String> = names
+            .iter()
+            .map(|n| (n.as_str(), format!("[{}]", n)))
+            .collect();
+        self.deliver(tenant_id, "test", &provider, &template, &phone, None, &vars)
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn deliver(
+        &self,
+        tenant_id: &str,
+        event: &str,
+        provider: &Provider,
+        template: &WhatsappTemplate,
+        phone: &str,
+        customer_id: Option<&str>,
+        vars: &HashMap<&str, String>,
+    ) -> AppResult<WhatsappMessage> {
+        let now = Utc::now();
+        let mut message = WhatsappMessage {
+            id: Uuid::new_v4().to_string(),
+            tenant_id: tenant_id.to_string(),
+            event: event.to_string(),
+            template_name: template.name.clone(),
+            to_phone: phone.to_string(),
+            customer_id: customer_id.map(str::to_string),
+            provider: provider.name().to_string(),
+            provider_message_id: None,
+            status: "pending".to_string(),
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO whatsapp_messages
+            (id, tenant_id, event, template_name, to_phone, customer_id, provider, status, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(&message.id)
+        .bind(&message.tenant_id)
+        .bind(&message.event)
+        .bind(&message.template_name)
+        .bind(&message.to_phone)
+        .bind(&message.customer_id)
+        .bind(&message.provider)
+        .bind(&message.status)
+        .bind(message.created_at)
+        .bind(message.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        let params: Vec<String> = template_variables(&template.body)
+            .iter()
+            .map(|name| vars.get(name.as_str()).cloned().unwrap_or_default())
+            .collect();
+
+        let result = match provider {
+            Provider::Meta {
+                phone_number_id,
+                access_token,
+                api_version,
+            } => {
+                self.send_meta(
+                    phone_number_id,
+                    access_token,
+                    api_version,
+                    phone,
+                    template,
+                    &params,
+                )
+                .await
+            }
+            Provider::Gateway { url, token } => {
+                let text = render_template(&template.body, vars);
+                self.send_gateway(
+                    url,
+                    token.as_deref(),
+                    phone,
+                    template,
+                    &params,
+                    &text,
+                    &message.id,
+                )
+                .await
+            }
+        };
+
+        match result {
+            Ok(provider_id) => {
+                message.status = "sent".to_string();
+                message.provider_message_id = Some(provider_id);
+            }
+            Err(e) => {
+                warn!(
+                    "WhatsApp {} via {} failed (tenant={}): {}",
+                    event, message.provider, tenant_id, e
+                );
+                message.status = "failed".to_string();
+                message.error = Some(e);
+            }
+        }
+        message.updated_at = Utc::now();
+
+        sqlx::query(
+            "UPDATE whatsapp_messages SET status = $1, provider_message_id = $2, error = $3, updated_at = $4 WHERE id = $5",
+        )
+        .bind(&message.status)
+        .bind(&message.provider_message_id)
+        .bind(&message.error)
+        .bind(message.updated_at)
+        .bind(&message.id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(message)
+    }
+
+    /// Returns the WhatsApp message id (`wamid...`).
+    async fn send_meta(
+        &self,
+        phone_number_id: &str,
+        access_token: &str,
+        api_version: &str,
+        to: &str,
+        template: &WhatsappTemplate,
+        params: &[String],
+    ) -> Result<String, String> {
+        let url = format!(
+            "https://graph.facebook.com/{}/{}/messages",
+            api_version, phone_number_id
+        );
+        let mut template_obj = json!({
+            "name": template.name,
+            "language": { "code": template.language },
+        });
+        if !params.is_empty() {
+            let parameters: Vec<Value> = params
+                .iter()
+                .map(|p| json!({ "type": "text", "text": p }))
+                .collect();
+            template_obj["components"] = json!([{ "type": "body", "parameters": parameters }]);
+        }
+        let body = json!({
+            "messaging_product": "whatsapp",
+            "to": to,
+            "type": "template",
+            "template": template_obj,
+        });
+
+        let response = self
+            .http
+            .post(&url)
+            .bearer_auth(access_token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("request failed: {}", e))?;
+        let status = response.status();
+        let payload: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let reason = payload["error"]["message"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("HTTP {}", status));
+            return Err(reason);
+        }
+        payload["messages"][0]["id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "response has no message id".to_string())
+    }
+
+    /// Gateways receive both the rendered text and the template name/params.
+    /// `reference` is our message id; gateways that return no id of their own
+    /// report status against it.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_gateway(
+        &self,
+        url: &str,
+        token: Option<&str>,
+        to: &str,
+        template: &WhatsappTemplate,
+        params: &[String],
+        text: &str,
+        reference: &str,
+    ) -> Result<String, String> {
+        let body = json!({
+            "to": to,
+            "message": text,
+            "template": template.name,
+            "language": template.language,
+            "params": params,
+            "reference": reference,
+        });
+        let mut request = self.http.post(url).json(&body);
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("request failed: {}", e))?;
+        let status = response.status();
+        let payload: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let reason = payload["error"]
+                .as_str()
+                .or_else(|| payload["message"].as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("HTTP {}", status));
+            return Err(reason);
+        }
+        Ok(payload["id"]
+            .as_str()
+            .or_else(|| payload["message_id"].as_str())
+            .unwrap_or(reference)
+            .to_string())
+    }
+
+    // ================= Configuration =================
+
+    async fn setting(&self, tenant_id: &str, key: &str) -> AppResult<Option<String>> {
+        Ok(self
+            .settings_service
+            .get_value_fallback(Some(tenant_id), key)
+            .await?
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty()))
+    }
+
+    /// `None` when the channel is disabled or its credentials are incomplete.
+    async fn provider(&self, tenant_id: &str) -> AppResult<Option<Provider>> {
+        let enabled = self
+            .setting(tenant_id, "whatsapp_enabled")
+            .await?
+            .is_some_and(|v| {
+                matches!(v.to_ascii_lowercase().as_str(), "true" | "1" | "yes" | "on")
+            });
+        if !enabled {
+            return Ok(None);
+        }
+
+        let provider = self
+            .setting(tenant_id, "whatsapp_provider")
+            .await?
+            .unwrap_or_else(|| "meta".to_string());
+        match provider.as_str() {
+            "meta" => {
+                let phone_number_id = self
+                    .setting(tenant_id, "whatsapp_meta_phone_number_id")
+                    .await?;
+                let access_token = self
+                    .setting(tenant_id, "whatsapp_meta_access_token")
+                    .await?;
+                let (Some(phone_number_id), Some(access_token)) = (phone_number_id, access_token)
+                else {
+                    warn!(
+                        "WhatsApp (Meta) is enabled but not configured (tenant={})",
+                        tenant_id
+                    );
+                    return Ok(None);
+                };
+                let api_version = self
+                    .setting(tenant_id, "whatsapp_meta_api_version")
+                    .await?
+                    .unwrap_or_else(|| DEFAULT_META_API_VERSION.to_string());
+                Ok(Some(Provider::Meta {
+                    phone_number_id,
+                    access_token,
+                    api_version,
+                }))
+            }
+            "gateway" => {
+                let Some(url) = self.setting(tenant_id, "whatsapp_gateway_url").await? else {
+                    warn!(
+                        "WhatsApp gateway is enabled but has no URL (tenant={})",
+                        tenant_id
+                    );
+                    return Ok(None);
+                };
+                let token = self.setting(tenant_id, "whatsapp_gateway_token").await?;
+                Ok(Some(Provider::Gateway { url, token }))
+            }
+            other => {
+                warn!(
+                    "Unknown whatsapp_provider '{}' (tenant={})",
+                    other, tenant_id
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    async fn route(&self, tenant_id: &str, event: WhatsappEvent) -> AppResult<Option<String>> {
+        Ok(self
+            .settings_service
+            .get_value(Some(tenant_id), &event.route_key())
+            .await?
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty()))
+    }
+
+    pub async fn list_routes(&self, tenant_id: &str) -> AppResult<Vec<WhatsappRoute>> {
+        let mut routes = Vec::new();
+        for event in WhatsappEvent::ALL {
+            routes.push(WhatsappRoute {
+                event: event.as_str().to_string(),
+                template_name: self.route(tenant_id, event).await?,
+                variables: event.variables().iter().map(|v| v.to_string()).collect(),
+            });
+        }
+        Ok(routes)
+    }
+
+    /// Route `event` to a template by name, or stop sending it with `None`.
+    pub async fn set_route(
+        &self,
+        tenant_id: &str,
+        event: &str,
+        template_name: Option<&str>,
+        actor_id: Option<&str>,
+    ) -> AppResult<WhatsappRoute> {
+        let event = WhatsappEvent::parse(event)
+            .ok_or_else(|| AppError::Validation(format!("Unknown WhatsApp event '{}'", event)))?;
+        let template_name = template_name.map(str::trim).filter(|n| !n.is_empty());
+        if let Some(name) = template_name {
+            let exists: Option<String> = sqlx::query_scalar(
+                "SELECT id FROM whatsapp_templates WHERE tenant_id = $1 AND name = $2 LIMIT 1",
+            )
+            .bind(tenant_id)
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+            if exists.is_none() {
+                return Err(AppError::Validation(format!(
+                    "WhatsApp template '{}' does not exist",
+                    name
+                )));
+            }
+        }
+
+        self.settings_service
+            .upsert(
+                Some(tenant_id.to_string()),
+                crate::models::UpsertSettingDto {
+                    key: event.route_key(),
+                    value: template_name.unwrap_or_default().to_string(),
+                    description: Some(format!(
+                        "WhatsApp template for {} (empty = not sent)",
+                        event.as_str()
+                    )),
+                    expected_updated_at: None,
+                },
+                actor_id,
+                None,
+            )
+            .await?;
+
+        Ok(WhatsappRoute {
+            event: event.as_str().to_string(),
+            template_name: template_name.map(str::to_string),
+            variables: event.variables().iter().map(|v| v.to_string()).collect(),
+        })
+    }
+
+    // ================= Templates =================
+
+    pub async fn list_templates(&self, tenant_id: &str) -> AppResult<Vec<WhatsappTemplate>> {
+        let rows = sqlx::query_as::<_, WhatsappTemplate>(
+            "SELECT * FROM whatsapp_templates WHERE tenant_id = $1 ORDER BY name, language",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn get_template(&self, tenant_id: &str, id: &str) -> AppResult<WhatsappTemplate> {
+        sqlx::query_as::<_, WhatsappTemplate>(
+            "SELECT * FROM whatsapp_templates WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("WhatsApp template not found".to_string()))
+    }
+
+    async fn find_active_template(
+        &self,
+        tenant_id: &str,
+        name: &str,
+    ) -> AppResult<Option<WhatsappTemplate>> {
+        let language = self
+            .setting(tenant_id, "whatsapp_default_language")
+            .await?
+            .unwrap_or_else(|| "id".to_string());
+        // Prefer the default language, then any active translation.
+        let rows = sqlx::query_as::<_, WhatsappTemplate>(
+            "SELECT * FROM whatsapp_templates WHERE tenant_id = $1 AND name = $2 AND is_active = $3",
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .bind(true)
+        .fetch_all(&self.pool)
+        .await?;
+        let preferred = rows
+            .iter()
+            .position(|t| t.language == language)
+            .unwrap_or(0);
+        Ok(rows.into_iter().nth(preferred))
+    }
+
+    pub async fn create_template(
+        &self,
+        tenant_id: &str,
+        req: UpsertWhatsappTemplateRequest,
+    ) -> AppResult<WhatsappTemplate> {
+        let (name, language, body) = validate_template(&req)?;
+        let now = Utc::now();
+        let template = WhatsappTemplate {
+            id: Uuid::new_v4().to_string(),
+            tenant_id: tenant_id.to_string(),
+            name,
+            language,
+            body,
+            is_active: req.is_active.unwrap_or(true),
+            created_at: now,
+            updated_at: now,
+        };
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO whatsapp_templates (id, tenant_id, name, language, body, is_active, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(&template.id)
+        .bind(&template.tenant_id)
+        .bind(&template.name)
+        .bind(&template.language)
+        .bind(&template.body)
+        .bind(template.is_active)
+        .bind(template.created_at)
+        .bind(template.updated_at)
+        .execute(&self.pool)
+        .await;
+        map_unique_violation(result, &template)?;
+        Ok(template)
+    }
+
+    pub async fn update_template(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        req: UpsertWhatsappTemplateRequest,
+    ) -> AppResult<WhatsappTemplate> {
+        let mut template = self.get_template(tenant_id, id).await?;
+        let (name, language, body) = validate_template(&req)?;
+        template.name = name;
+        template.language = language;
+        template.body = body;
+        if let Some(active) = req.is_active {
+            template.is_active = active;
+        }
+        template.updated_at = Utc::now();
+
+        let result = sqlx::query(
+            r#"
+            UPDATE whatsapp_templates
+            SET name = $1, language = $2, body = $3, is_active = $4, updated_at = $5
+            WHERE tenant_id = $6 AND id = $7
+            "#,
+        )
+        .bind(&template.name)
+        .bind(&template.language)
+        .bind(&template.body)
+        .bind(template.is_active)
+        .bind(template.updated_at)
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await;
+        map_unique_violation(result, &template)?;
+        Ok(template)
+    }
+
+    pub async fn delete_template(&self, tenant_id: &str, id: &str) -> AppResult<()> {
+        let template = self.get_template(tenant_id, id).await?;
+        for event in WhatsappEvent::ALL {
+            if self.route(tenant_id, event).await?.as_deref() == Some(template.name.as_str()) {
+                return Err(AppError::Conflict(format!(
+                    "Template is used by {}; route the event elsewhere first",
+                    event.as_str()
+                )));
+            }
+        }
+
+        sqlx::query("DELETE FROM whatsapp_templates WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // ================= Delivery log =================
+
+    pub async fn list_messages(
+        &self,
+        tenant_id: &str,
+        status: Option<&str>,
+        limit: u32,
+    ) -> AppResult<Vec<WhatsappMessage>> {
+        let rows = sqlx::query_as::<_, WhatsappMessage>(
+            r#"
+            SELECT * FROM whatsapp_messages
+            WHERE tenant_id = $1 AND ($2 IS NULL OR status = $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(status)
+        .bind(limit.clamp(1, 500) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    // ================= Status callbacks =================
+
+    /// Answer Meta's webhook verification handshake with `challenge` when
+    /// `token` matches a configured `whatsapp_meta_verify_token`.
+    pub async fn verify_meta_subscription(
+        &self,
+        mode: &str,
+        token: &str,
+        challenge: &str,
+    ) -> AppResult<String> {
+        if mode != "subscribe" || token.is_empty() {
+            return Err(AppError::Forbidden("Verification failed".to_string()));
+        }
+        let known: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM settings WHERE key = 'whatsapp_meta_verify_token' AND value = $1 LIMIT 1",
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?;
+        if known.is_none() {
+            return Err(AppError::Forbidden("Verification failed".to_string()));
+        }
+        Ok(challenge.to_string())
+    }
+
+    /// Apply a Meta webhook (`X-Hub-Signature-256` signed with the app secret of
+    /// the tenant that sent each message). Returns the number of messages updated.
+    pub async fn handle_meta_callback(&self, body: &[u8], signature: &str) -> AppResult<usize> {
+        let payload: Value = serde_json::from_slice(body)
+            .map_err(|e| AppError::Validation(format!("Invalid payload: {}", e)))?;
+        let updates = parse_meta_statuses(&payload);
+        self.apply_signed_updates(updates, body, signature, "whatsapp_meta_app_secret")
+            .await
+    }
+
+    /// Apply a gateway status callback (`{"id", "status", "error"}`), signed like
+    /// Meta's with the tenant's `whatsapp_gateway_webhook_secret`.
+    pub async fn handle_gateway_callback(&self, body: &[u8], signature: &str) -> AppResult<usize> {
+        let payload: Value = serde_json::from_slice(body)
+            .map_err(|e| AppError::Validation(format!("Invalid payload: {}", e)))?;
+        let id = payload["id"]
+            .as_str()
+            .or_else(|| payload["reference"].as_str())
+            .unwrap_or_default();
+        let status = payload["status"].as_str().unwrap_or_default();
+        if id.is_empty() || status.is_empty() {
+            return Err(AppError::Validation(
+                "id and status are required".to_string(),
+            ));
+        }
+        let update = StatusUpdate {
+            provider_message_id: id.to_string(),
+            status: status.to_ascii_lowercase(),
+            error: payload["error"].as_str().map(str::to_string),
+        };
+        self.apply_signed_updates(
+            vec![update],
+            body,
+            signature,
+            "whatsapp_gateway_webhook_secret",
+        )
+        .await
+    }
+
+    async fn apply_signed_updates(
+        &self,
+        updates: Vec<StatusUpdate>,
+        body: &[u8],
+        signature: &str,
+        secret_key: &str,
+    ) -> AppResult<usize> {
+        let mut verified: HashMap<String, bool> = HashMap::new();
+        let mut applied = 0;
+
+        for update in updates {
+            let row: Option<(String, String)> = sqlx::query_as(
+                "SELECT id, tenant_id FROM whatsapp_messages WHERE provider_message_id = $1 LIMIT 1",
+            )
+            .bind(&update.provider_message_id)
+            .fetch_optional(&self.pool)
+            .await?;
+            let Some((id, tenant_id)) = row else {
+                continue;
+            };
+
+            let ok = match verified.get(&tenant_id) {
+                Some(ok) => *ok,
+                None => {
+                    let ok = match self.setting(&tenant_id, secret_key).await? {
+                        Some(secret) => signature_matches(secret.as_bytes(), body, signature),
+                        None => false,
+                    };
+                    verified.insert(tenant_id.clone(), ok);
+                    ok
+                }
+            };
+            if !ok {
+                warn!(
+                    "Rejected WhatsApp status callback with a bad signature (tenant={})",
+                    tenant_id
+                );
+                continue;
+            }
+
+            if self.apply_status(&id, &update).await? {
+                applied += 1;
+            }
+        }
+
+        if applied > 0 {
+            info!("Applied {} WhatsApp delivery status update(s)", applied);
+        }
+        Ok(applied)
+    }
+
+    async fn apply_status(&self, id: &str, update: &StatusUpdate) -> AppResult<bool> {
+        let current: Option<String> =
+            sqlx::query_scalar("SELECT status FROM whatsapp_messages WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+        let Some(current) = current else {
+            return Ok(false);
+        };
+        let Some(next) = next_status(&current, &update.status) else {
+            return Ok(false);
+        };
+
+        sqlx::query(
+            "UPDATE whatsapp_messages SET status = $1, error = COALESCE($2, error), updated_at = $3 WHERE id = $4",
+        )
+        .bind(next)
+        .bind(&update.error)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(true)
+    }
+
+    // ================= Recipients =================
+
+    /// The customer behind a subscription, if they have a phone number.
+    pub async fn subscription_recipient(
+        &self,
+        tenant_id: &str,
+        subscription_id: &str,
+    ) -> AppResult<Option<WhatsappRecipient>> {
+        let row: Option<(String, String, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT c.id, c.name, c.phone
+            FROM customer_subscriptions cs
+            INNER JOIN customers c ON c.id = cs.customer_id AND c.tenant_id = cs.tenant_id
+            WHERE cs.tenant_id = $1 AND cs.id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(subscription_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.and_then(|(id, name, phone)| {
+            phone
+                .filter(|p| !p.trim().is_empty())
+                .map(|phone| WhatsappRecipient {
+                    customer_id: Some(id),
+                    name,
+                    phone,
+                })
+        }))
+    }
+
+    /// Active customers whose service runs through a router.
+    pub async fn router_recipients(
+        &self,
+        tenant_id: &str,
+        router_id: &str,
+    ) -> AppResult<Vec<WhatsappRecipient>> {
+        let rows: Vec<(String, String, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT c.id, c.name, c.phone
+            FROM customer_subscriptions cs
+            INNER JOIN customers c ON c.id = cs.customer_id AND c.tenant_id = cs.tenant_id
+            WHERE cs.tenant_id = $1
+              AND cs.router_id = $2
+              AND cs.status = 'active'
+              AND c.is_active = $3
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(router_id)
+        .bind(true)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, name, phone)| {
+                phone
+                    .filter(|p| !p.trim().is_empty())
+                    .map(|phone| WhatsappRecipient {
+                        customer_id: Some(id),
+                        name,
+                        phone,
+                    })
+            })
+            .collect())
+    }
+
+    /// `path` on the public app URL, for links in messages.
+    pub async fn public_url(&self, path: &str) -> String {
+        let base = self
+            .settings_service
+            .get_value(None, "app_public_url")
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        format!("{}{}", base.trim_end_matches('/'), path)
+    }
+}
+
+fn validate_template(req: &UpsertWhatsappTemplateRequest) -> AppResult<(String, String, String)> {
+    let name = req.name.trim().to_string();
+    // Meta template names: lowercase letters, digits and underscores.
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(AppError::Validation(
+            "Template name may only contain lowercase letters, digits and underscores".to_string(),
+        ));
+    }
+    let language = req
+        .language
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .unwrap_or("id")
+        .to_string();
+    let body = req.body.trim().to_string();
+    if body.is_empty() {
+        return Err(AppError::Validation(
+            "Template body is required".to_string(),
+        ));
+    }
+    Ok((name, language, body))
+}
+
+fn map_unique_violation(
+    result: Result<impl Sized, sqlx::Error>,
+    template: &WhatsappTemplate,
+) -> AppResult<()> {
+    match result {
+        Ok(_) => Ok(()),
+        Err(sqlx::Error::Database(db)) if db.is_unique_violation() => {
+            Err(AppError::Conflict(format!(
+                "Template '{}' ({}) already exists",
+                template.name, template.language
+            )))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Normalize a phone number to international digits (`628123...`). Local
+/// Indonesian numbers (`08...`, `8...`) get the 62 country code.
+pub fn normalize_phone(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    let has_plus = trimmed.starts_with('+');
+    let digits: String = trimmed.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digits.is_empty() {
+        return None;
+    }
+
+    let normalized = if has_plus || digits.starts_with("62") {
+        digits
+    } else if let Some(rest) = digits.strip_prefix('0') {
+        format!("62{}", rest)
+    } else if digits.starts_with('8') {
+        format!("62{}", digits)
+    } else {
+        digits
+    };
+
+    (10..=15).contains(&normalized.len()).then_some(normalized)
+}
+
+/// `{{name}}` placeholders in order of first appearance.
+pub fn template_variables(body: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let name = after[..end].trim();
+        if !name.is_empty() && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = &after[end + 2..];
+    }
+    names
+}
+
+/// Replace `{{name}}` placeholders; unknown names become empty.
+pub fn render_template(body: &str, vars: &HashMap<&str, String>) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        if let Some(value) = vars.get(after[..end].trim()) {
+            out.push_str(value);
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Status updates in a Meta webhook payload.
+pub fn parse_meta_statuses(payload: &Value) -> Vec<StatusUpdate> {
+    let mut updates = Vec::new();
+    let entries = payload["entry"].as_array().cloned().unwrap_or_default();
+    for entry in entries {
+        let changes = entry["changes"].as_array().cloned().unwrap_or_default();
+        for change in changes {
+            let statuses = change["value"]["statuses"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            for s in statuses {
+                let (Some(id), Some(status)) = (s["id"].as_str(), s["status"].as_str()) else {
+                    continue;
+                };
+                let error = s["errors"][0]["message"]
+                    .as_str()
+                    .or_else(|| s["errors"][0]["title"].as_str())
+                    .map(str::to_string);
+                updates.push(StatusUpdate {
+                    provider_message_id: id.to_string(),
+                    status: status.to_string(),
+                    error,
+                });
+            }
+        }
+    }
+    updates
+}
+
+/// The status to store after `incoming`, or `None` to keep `current`. Callbacks
+/// can arrive out of order, so statuses only move forward; `failed` always wins.
+fn next_status(current: &str, incoming: &str) -> Option<&'static str> {
+    fn rank(s: &str) -> Option<u8> {
+        match s {
+            "pending" => Some(0),
+            "sent" => Some(1),
+            "delivered" => Some(2),
+            "read" => Some(3),
+            _ => None,
+        }
+    }
+
+    if incoming == "failed" {
+        return (current != "failed").then_some("failed");
+    }
+    let next = match incoming {
+        "sent" => "sent",
+        "delivered" => "delivered",
+        "read" => "read",
+        _ => return None,
+    };
+    match rank(current) {
+        Some(cur) if rank(next)? > cur => Some(next),
+        _ => None,
+    }
+}
+
+/// `signature` is `sha256=<hex>` as sent by Meta.
+fn signature_matches(secret: &[u8], body: &[u8], signature: &str) -> bool {
+    let Some(hex) = signature.trim().strip_prefix("sha256=") else {
+        return false;
+    };
+    let expected = hmac_sha256_hex(secret, body);
+    let hex = hex.to_ascii_lowercase();
+    expected.len() == hex.len()
+        && expected
+            .bytes()
+            .zip(hex.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_phone() {
+        assert_eq!(
+            normalize_phone("0812-3456-7890"),
+            Some("6281234567890".to_string())
+        );
+        assert_eq!(
+            normalize_phone("+62 812 3456 7890"),
+            Some("6281234567890".to_string())
+        );
+        assert_eq!(
+            normalize_phone("81234567890"),
+            Some("6281234567890".to_string())
+        );
+        assert_eq!(
+            normalize_phone("+1 415 555 0100"),
+            Some("14155550100".to_string())
+        );
+        assert_eq!(normalize_phone("12345"), None);
+        assert_eq!(normalize_phone("n/a"), None);
+    }
+
+    #[test]
+    fn test_template_variables_and_render() {
+        let body =
+            "Halo {{customer_name}}, tagihan {{ invoice_number }} jatuh tempo {{due_date}}. \
+                    Bayar: {{pay_url}} ({{invoice_number}})";
+        assert_eq!(
+            template_variables(body),
+            vec!["customer_name", "invoice_number", "due_date", "pay_url"]
+        );
+
+        let vars: HashMap<&str, String> = [
+            ("customer_name", "Budi".to_string()),
+            ("invoice_number", "INV-7".to_string()),
+            ("due_date", "2026-03-01".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            render_template(
+                "Halo {{customer_name}}, {{invoice_number}} {{pay_url}}.",
+                &vars
+            ),
+            "Halo Budi, INV-7 ."
+        );
+        assert_eq!(render_template("unclosed {{name", &vars), "unclosed {{name");
+    }
+
+    #[test]
+    fn test_parse_meta_statuses() {
+        let payload = json!({
+            "object": "whatsapp_business_account",
+            "entry": [{
+                "changes": [{
+                    "field": "messages",
+                    "value": {
+                        "statuses": [
+                            { "id": "wamid.A", "status": "delivered" },
+                            { "id": "wamid.B", "status": "failed",
+                              "errors": [{ "code": 131026, "title": "Message undeliverable" }] }
+                        ]
+                    }
+                }]
+            }]
+        });
+        assert_eq!(
+            parse_meta_statuses(&payload),
+            vec![
+                StatusUpdate {
+                    provider_message_id: "wamid.A".to_string(),
+                    status: "delivered".to_string(),
+                    error: None,
+                },
+                StatusUpdate {
+                    provider_message_id: "wamid.B".to_string(),
+                    status: "failed".to_string(),
+                    error: Some("Message undeliverable".to_string()),
+                },
+            ]
+        );
+        assert!(parse_meta_statuses(&json!({ "entry": [] })).is_empty());
+    }
+
+    #[test]
+    fn test_next_status_only_moves_forward() {
+        assert_eq!(next_status("sent", "delivered"), Some("delivered"));
+        assert_eq!(next_status("pending", "read"), Some("read"));
+        assert_eq!(next_status("read", "delivered"), None);
+        assert_eq!(next_status("delivered", "delivered"), None);
+        assert_eq!(next_status("read", "failed"), Some("failed"));
+        assert_eq!(next_status("failed", "delivered"), None);
+        assert_eq!(next_status("sent", "unknown"), None);
+    }
+
+    #[test]
+    fn test_signature_matches() {
+        let body = br#"{"entry":[]}"#;
+        let sig = format!("sha256={}", hmac_sha256_hex(b"secret", body));
+        assert!(signature_matches(b"secret", body, &sig));
+        assert!(signature_matches(
+            b"secret",
+            body,
+            &sig.to_uppercase().replace("SHA256=", "sha256=")
+        ));
+        assert!(!signature_matches(b"other", body, &sig));
+        assert!(!signature_matches(b"secret", body, "deadbeef"));
+    }
+}
@@ -0,0 +1,629 @@
+//! Checklists and photo evidence for installation work orders.
+//!
+//! Templates list the steps a technician has to go through (tick boxes,
+//! photos such as the ODP or the router serial, free-form readings). The
+//! tenant's default template is copied onto a work order the first time its
+//! checklist is needed, and required items must be filled in before the work
+//! order can be completed.
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    UpdateWorkOrderChecklistItemRequest, UpsertWorkOrderTemplateRequest, WorkOrderChecklist,
+    WorkOrderChecklistItem, WorkOrderTemplate, WorkOrderTemplateItem, WorkOrderTemplateItemInput,
+};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+const ITEM_KINDS: &[&str] = &["check", "photo", "text"];
+const MAX_TEMPLATE_ITEMS: usize = 50;
+const MAX_LABEL_LEN: usize = 120;
+const MAX_VALUE_LEN: usize = 500;
+
+const CHECKLIST_SELECT: &str = r#"
+    SELECT
+        i.id, i.work_order_id, i.template_id, i.position, i.label, i.kind, i.is_required,
+        i.is_done, i.value, i.file_id, f.original_name AS file_name,
+        i.completed_by, u.name AS completed_by_name, i.completed_at
+    FROM installation_work_order_checklist_items i
+    LEFT JOIN file_records f ON f.id = i.file_id
+    LEFT JOIN users u ON u.id = i.completed_by
+"#;
+
+/// Whether an item has what its kind asks for.
+fn item_satisfied(item: &WorkOrderChecklistItem) -> bool {
+    match item.kind.as_str() {
+        "photo" => item.file_id.is_some(),
+        "text" => item.value.as_deref().is_some_and(|v| !v.trim().is_empty()),
+        _ => item.is_done,
+    }
+}
+
+/// Labels of required items that are not filled in yet.
+fn missing_required(items: &[WorkOrderChecklistItem]) -> Vec<String> {
+    items
+        .iter()
+        .filter(|i| i.is_required && !item_satisfied(i))
+        .map(|i| i.label.clone())
+        .collect()
+}
+
+fn normalize_items(
+    items: Vec<WorkOrderTemplateItemInput>,
+) -> AppResult<Vec<(String, String, bool)>> {
+    if items.is_empty() || items.len() > MAX_TEMPLATE_ITEMS {
+        return Err(AppError::Validation(format!(
+            "A template needs between 1 and {MAX_TEMPLATE_ITEMS} items"
+        )));
+    }
+    items
+        .into_iter()
+        .map(|i| {
+            let label = i.label.trim().to_string();
+            if label.is_empty() || label.chars().count() > MAX_LABEL_LEN {
+                return Err(AppError::Validation(format!(
+                    "Item label is required (max {MAX_LABEL_LEN} characters)"
+                )));
+            }
+            let kind = i
+                .kind
+                .map(|k| k.trim().to_lowercase())
+                .filter(|k| !k.is_empty())
+                .unwrap_or_else(|| "check".to_string());
+            if !ITEM_KINDS.contains(&kind.as_str()) {
+                return Err(AppError::Validation(format!(
+                    "Invalid item kind '{kind}' (expected one of: {})",
+                    ITEM_KINDS.join(", ")
+                )));
+            }
+            Ok((label, kind, i.is_required.unwrap_or(true)))
+        })
+        .collect()
+}
+
+#[derive(sqlx::FromRow)]
+struct TemplateRow {
+    id: String,
+    tenant_id: String,
+    name: String,
+    description: Option<String>,
+    is_default: bool,
+    is_active: bool,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct WorkOrderChecklistService {
+    pool: DbPool,
+}
+
+impl WorkOrderChecklistService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    async fn template_items(&self, template_id: &str) -> AppResult<Vec<WorkOrderTemplateItem>> {
+        let items: Vec<WorkOrderTemplateItem> = sqlx::query_as(
+            "SELECT id, position, label, kind, is_required FROM work_order_template_items WHERE template_id = $1 ORDER BY position",
+        )
+        .bind(template_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(items)
+    }
+
+    async fn with_items(&self, row: TemplateRow) -> AppResult<WorkOrderTemplate> {
+        let items = self.template_items(&row.id).await?;
+        Ok(WorkOrderTemplate {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            name: row.name,
+            description: row.description,
+            is_default: row.is_default,
+            is_active: row.is_active,
+            items,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+
+    pub async fn list_templates(&self, tenant_id: &str) -> AppResult<Vec<WorkOrderTemplate>> {
+        let rows: Vec<TemplateRow> = sqlx::query_as(
+            "SELECT id, tenant_id, name, description, is_default, is_active, created_at, updated_at FROM work_order_templates WHERE tenant_id = $1 ORDER BY is_default DESC, lower(name)",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut out = Vec::with_capacity(rows.len());
+        for row in rows {
+            out.push(self.with_items(row).await?);
+        }
+        Ok(out)
+    }
+
+    pub async fn get_template(&self, tenant_id: &str, id: &str) -> AppResult<WorkOrderTemplate> {
+        let row: Option<TemplateRow> = sqlx::query_as(
+            "SELECT id, tenant_id, name, description, is_default, is_active, created_at, updated_at FROM work_order_templates WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        match row {
+            Some(row) => self.with_items(row).await,
+            None => Err(AppError::NotFound(
+                "Work order template not found".to_string(),
+            )),
+        }
+    }
+
+    pub async fn create_template(
+        &self,
+        tenant_id: &str,
+        created_by: &str,
+        dto: UpsertWorkOrderTemplateRequest,
+    ) -> AppResult<WorkOrderTemplate> {
+        let id = Uuid::new_v4().to_string();
+        self.save_template(tenant_id, &id, Some(created_by), dto)
+            .await?;
+        self.get_template(tenant_id, &id).await
+    }
+
+    pub async fn update_template(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        dto: UpsertWorkOrderTemplateRequest,
+    ) -> AppResult<WorkOrderTemplate> {
+        self.get_template(tenant_id, id).await?;
+        self.save_template(tenant_id, id, None, dto).await?;
+        self.get_template(tenant_id, id).await
+    }
+
+    /// Insert (`created_by` set) or update a template and replace its items.
+    async fn save_template(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        created_by: Option<&str>,
+        dto: UpsertWorkOrderTemplateRequest,
+    ) -> AppResult<()> {
+        let name = dto.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > 100 {
+            return Err(AppError::Validation(
+                "Template name is required (max 100 characters)".to_string(),
+            ));
+        }
+        let description = dto
+            .description
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty());
+        let is_active = dto.is_active.unwrap_or(true);
+        let is_default = dto.is_default.unwrap_or(false);
+        if is_default && !is_active {
+            return Err(AppError::Validation(
+                "The default template must be active".to_string(),
+            ));
+        }
+        let items = normalize_items(dto.items)?;
+
+        let taken: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM work_order_templates WHERE tenant_id = $1 AND lower(name) = lower($2) AND id <> $3)",
+        )
+        .bind(tenant_id)
+        .bind(&name)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+        if taken {
+            return Err(AppError::Conflict(format!(
+                "A template named '{}' already exists",
+                name
+            )));
+        }
+
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        if is_default {
+            sqlx::query(
+                "UPDATE work_order_templates SET is_default = false, updated_at = $1 WHERE tenant_id = $2 AND is_default AND id <> $3",
+            )
+            .bind(now)
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if let Some(created_by) = created_by {
+            sqlx::query(
+                r#"
+                INSERT INTO work_order_templates
+                    (id, tenant_id, name, description, is_default, is_active, created_by, created_at, updated_at)
+                VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$8)
+            "#,
+            )
+            .bind(id)
+            .bind(tenant_id)
+            .bind(&name)
+            .bind(&description)
+            .bind(is_default)
+            .bind(is_active)
+            .bind(created_by)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            sqlx::query(
+                r#"
+                UPDATE work_order_templates
+                SET name = $1, description = $2, is_default = $3, is_active = $4, updated_at = $5
+                WHERE tenant_id = $6 AND id = $7
+            "#,
+            )
+            .bind(&name)
+            .bind(&description)
+            .bind(is_default)
+            .bind(is_active)
+            .bind(now)
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("DELETE FROM work_order_template_items WHERE template_id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for (position, (label, kind, is_required)) in items.into_iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO work_order_template_items (id, template_id, position, label, kind, is_required) VALUES ($1,$2,$3,$4,$5,$6)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(id)
+            .bind(position as i32)
+            .bind(label)
+            .bind(kind)
+            .bind(is_required)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Checklists already copied onto work orders are kept.
+    pub async fn delete_template(&self, tenant_id: &str, id: &str) -> AppResult<()> {
+        let res = sqlx::query("DELETE FROM work_order_templates WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound(
+                "Work order template not found".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn work_order_status(&self, tenant_id: &str, work_order_id: &str) -> AppResult<String> {
+        let status: Option<String> = sqlx::query_scalar(
+            "SELECT status FROM installation_work_orders WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        status.ok_or_else(|| AppError::NotFound("Work order not found".to_string()))
+    }
+
+    async fn ensure_open(&self, tenant_id: &str, work_order_id: &str) -> AppResult<()> {
+        let status = self.work_order_status(tenant_id, work_order_id).await?;
+        if status == "completed" || status == "cancelled" {
+            return Err(AppError::Validation(format!(
+                "Checklist of a {} work order can't be changed",
+                status
+            )));
+        }
+        Ok(())
+    }
+
+    async fn items(
+        &self,
+        tenant_id: &str,
+        work_order_id: &str,
+    ) -> AppResult<Vec<WorkOrderChecklistItem>> {
+        let rows: Vec<WorkOrderChecklistItem> = sqlx::query_as(&format!(
+            "{CHECKLIST_SELECT} WHERE i.tenant_id = $1 AND i.work_order_id = $2 ORDER BY i.position"
+        ))
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Replace the work order's checklist with a copy of `template_id`.
+    /// Progress on the previous checklist is discarded.
+    async fn copy_template(
+        &self,
+        tenant_id: &str,
+        work_order_id: &str,
+        template_id: &str,
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "DELETE FROM installation_work_order_checklist_items WHERE tenant_id = $1 AND work_order_id = $2",
+        )
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .execute(&mut *tx)
+        .await?;
+        for item in self.template_items(template_id).await? {
+            sqlx::query(
+                r#"
+                INSERT INTO installation_work_order_checklist_items
+                    (id, tenant_id, work_order_id, template_id, position, label, kind, is_required)
+                VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
+            "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(tenant_id)
+            .bind(work_order_id)
+            .bind(template_id)
+            .bind(item.position)
+            .bind(item.label)
+            .bind(item.kind)
+            .bind(item.is_required)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Copy the tenant's default template onto an open work order that has no
+    /// checklist yet. Returns the current items.
+    async fn ensure_checklist(
+        &self,
+        tenant_id: &str,
+        work_order_id: &str,
+    ) -> AppResult<Vec<WorkOrderChecklistItem>> {
+        let items = self.items(tenant_id, work_order_id).await?;
+        if !items.is_empty() {
+            return Ok(items);
+        }
+        let status = self.work_order_status(tenant_id, work_order_id).await?;
+        if status == "completed" || status == "cancelled" {
+            return Ok(items);
+        }
+        let default_id: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM work_order_templates WHERE tenant_id = $1 AND is_default AND is_active",
+        )
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(template_id) = default_id else {
+            return Ok(items);
+        };
+        self.copy_template(tenant_id, work_order_id, &template_id)
+            .await?;
+        self.items(tenant_id, work_order_id).await
+    }
+
+    pub async fn get_checklist(
+        &self,
+        tenant_id: &str,
+        work_order_id: &str,
+    ) -> AppResult<WorkOrderChecklist> {
+        let items = self.ensure_checklist(tenant_id, work_order_id).await?;
+        Ok(WorkOrderChecklist {
+            work_order_id: work_order_id.to_string(),
+            missing: missing_required(&items),
+            items,
+        })
+    }
+
+    pub async fn apply_template(
+        &self,
+        tenant_id: &str,
+        work_order_id: &str,
+        template_id: &str,
+    ) -> AppResult<WorkOrderChecklist> {
+        self.ensure_open(tenant_id, work_order_id).await?;
+        let template = self.get_template(tenant_id, template_id).await?;
+        if !template.is_active {
+            return Err(AppError::Validation(
+                "Work order template is inactive".to_string(),
+            ));
+        }
+        self.copy_template(tenant_id, work_order_id, &template.id)
+            .await?;
+        self.get_checklist(tenant_id, work_order_id).await
+    }
+
+    pub async fn update_item(
+        &self,
+        tenant_id: &str,
+        work_order_id: &str,
+        item_id: &str,
+        actor_id: &str,
+        dto: UpdateWorkOrderChecklistItemRequest,
+    ) -> AppResult<WorkOrderChecklist> {
+        self.ensure_open(tenant_id, work_order_id).await?;
+        let current = self
+            .items(tenant_id, work_order_id)
+            .await?
+            .into_iter()
+            .find(|i| i.id == item_id)
+            .ok_or_else(|| AppError::NotFound("Checklist item not found".to_string()))?;
+
+        let mut next = current.clone();
+        if let Some(is_done) = dto.is_done {
+            next.is_done = is_done;
+        }
+        if let Some(value) = dto.value {
+            let value = value.trim().to_string();
+            if value.chars().count() > MAX_VALUE_LEN {
+                return Err(AppError::Validation(format!(
+                    "Value is too long (max {MAX_VALUE_LEN} characters)"
+                )));
+            }
+            next.value = Some(value).filter(|v| !v.is_empty());
+        }
+        if let Some(file_id) = dto.file_id {
+            let file_id = file_id.trim().to_string();
+            next.file_id = if file_id.is_empty() {
+                None
+            } else {
+                self.ensure_image(tenant_id, &file_id).await?;
+                Some(file_id)
+            };
+        }
+        // Photos and values count as done once they are filled in.
+        if next.kind != "check" {
+            next.is_done = item_satisfied(&next);
+        }
+
+        let (completed_by, completed_at) = match (current.is_done, next.is_done) {
+            (false, true) => (Some(actor_id.to_string()), Some(Utc::now())),
+            (true, true) => (current.completed_by, current.completed_at),
+            _ => (None, None),
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE installation_work_order_checklist_items
+            SET is_done = $1, value = $2, file_id = $3, completed_by = $4, completed_at = $5
+            WHERE tenant_id = $6 AND work_order_id = $7 AND id = $8
+        "#,
+        )
+        .bind(next.is_done)
+        .bind(&next.value)
+        .bind(&next.file_id)
+        .bind(completed_by)
+        .bind(completed_at)
+        .bind(tenant_id)
+        .bind(work_order_id)
+        .bind(item_id)
+        .execute(&self.pool)
+        .await?;
+
+        self.get_checklist(tenant_id, work_order_id).await
+    }
+
+    async fn ensure_image(&self, tenant_id: &str, file_id: &str) -> AppResult<()> {
+        let content_type: Option<String> = sqlx::query_scalar(
+            "SELECT content_type FROM file_records WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(file_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        match content_type {
+            None => Err(AppError::NotFound("File not found".to_string())),
+            Some(ct) if ct.to_lowercase().starts_with("image/") => Ok(()),
+            Some(_) => Err(AppError::Validation(
+                "Photo evidence must be an image".to_string(),
+            )),
+        }
+    }
+
+    /// Refuse completion while required checklist items are missing.
+    pub async fn ensure_ready_to_complete(
+        &self,
+        tenant_id: &str,
+        work_order_id: &str,
+    ) -> AppResult<()> {
+        let items = self.ensure_checklist(tenant_id, work_order_id).await?;
+        let missing = missing_required(&items);
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(AppError::Validation(format!(
+            "Complete the checklist first: {}",
+            missing.join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{missing_required, normalize_items};
+    use crate::models::{WorkOrderChecklistItem, WorkOrderTemplateItemInput};
+
+    fn item(label: &str, kind: &str, required: bool) -> WorkOrderChecklistItem {
+        WorkOrderChecklistItem {
+            id: label.to_string(),
+            work_order_id: "wo".to_string(),
+            template_id: None,
+            position: 0,
+            label: label.to_string(),
+            kind: kind.to_string(),
+            is_required: required,
+            is_done: false,
+            value: None,
+            file_id: None,
+            file_name: None,
+            completed_by: None,
+            completed_by_name: None,
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn required_items_block_until_filled_in() {
+        let mut items = vec![
+            item("Cable tidy", "check", true),
+            item("ODP photo", "photo", true),
+            item("Signal reading", "text", true),
+            item("Customer feedback", "text", false),
+        ];
+        assert_eq!(
+            missing_required(&items),
+            vec!["Cable tidy", "ODP photo", "Signal reading"]
+        );
+
+        items[0].is_done = true;
+        // A photo item only counts once a file is attached.
+        items[1].is_done = true;
+        items[2].value = Some("  ".to_string());
+        assert_eq!(
+            missing_required(&items),
+            vec!["ODP photo", "Signal reading"]
+        );
+
+        items[1].file_id = Some("f1".to_string());
+        items[2].value = Some("-21.4 dBm".to_string());
+        assert!(missing_required(&items).is_empty());
+    }
+
+    #[test]
+    fn template_items_are_validated() {
+        let input = |label: &str, kind: Option<&str>| WorkOrderTemplateItemInput {
+            label: label.to_string(),
+            kind: kind.map(str::to_string),
+            is_required: None,
+        };
+
+        let items = normalize_items(vec![
+            input(" Router serial ", Some("PHOTO")),
+            input("Test", None),
+        ])
+        .unwrap();
+        assert_eq!(
+            items,
+            vec![
+                ("Router serial".to_string(), "photo".to_string(), true),
+                ("Test".to_string(), "check".to_string(), true),
+            ]
+        );
+
+        assert!(normalize_items(vec![]).is_err());
+        assert!(normalize_items(vec![input("", None)]).is_err());
+        assert!(normalize_items(vec![input("Video", Some("video"))]).is_err());
+    }
+}
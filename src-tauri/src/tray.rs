@@ -0,0 +1,171 @@
+//! System tray and background mode.
+//!
+//! The desktop app hosts the HTTP API, the pollers and the billing jobs. With
+//! background mode on, closing the window only hides it so all of that keeps
+//! running; the tray icon brings the window back or quits for real.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{App, AppHandle, Manager, Window, WindowEvent};
+use tauri_plugin_notification::NotificationExt;
+
+const PREFS_FILE: &str = "desktop_prefs.json";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DesktopPrefs {
+    run_in_background: bool,
+}
+
+/// Whether closing the window keeps the app running; saved in the app data dir.
+pub struct BackgroundMode {
+    enabled: AtomicBool,
+    quitting: AtomicBool,
+    hint_shown: AtomicBool,
+    file: PathBuf,
+}
+
+impl BackgroundMode {
+    /// On unless the user turned it off, so a server install survives a closed window.
+    pub fn load(app_data_dir: &Path) -> Self {
+        let file = app_data_dir.join(PREFS_FILE);
+        let enabled = std::fs::read_to_string(&file)
+            .ok()
+            .and_then(|raw| serde_json::from_str::<DesktopPrefs>(&raw).ok())
+            .map(|prefs| prefs.run_in_background)
+            .unwrap_or(true);
+        Self {
+            enabled: AtomicBool::new(enabled),
+            quitting: AtomicBool::new(false),
+            hint_shown: AtomicBool::new(false),
+            file,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+        let prefs = DesktopPrefs {
+            run_in_background: enabled,
+        };
+        if let Err(e) = serde_json::to_string(&prefs)
+            .map_err(std::io::Error::other)
+            .and_then(|raw| std::fs::write(&self.file, raw))
+        {
+            tracing::warn!("Failed to save {:?}: {}", self.file, e);
+        }
+    }
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Stops the background services along with the app.
+pub fn quit(app: &AppHandle) {
+    if let Some(mode) = app.try_state::<BackgroundMode>() {
+        mode.quitting.store(true, Ordering::SeqCst);
+    }
+    app.exit(0);
+}
+
+/// Adds the tray icon. Background mode is only armed once the icon exists, as
+/// a hidden window could not be brought back otherwise.
+pub fn init(app: &App, app_data_dir: &Path) -> tauri::Result<()> {
+    let mode = BackgroundMode::load(app_data_dir);
+    let run_in_background = mode.is_enabled();
+
+    let open = MenuItem::with_id(app, "open", "Open", true, None::<&str>)?;
+    let background = CheckMenuItem::with_id(
+        app,
+        "background",
+        "Run in background",
+        true,
+        run_in_background,
+        None::<&str>,
+    )?;
+    let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &open,
+            &background,
+            &PredefinedMenuItem::separator(app)?,
+            &quit_item,
+        ],
+    )?;
+
+    let toggle = background.clone();
+    let mut tray = TrayIconBuilder::with_id("main")
+        .tooltip(app.package_info().name.clone())
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(move |app, event| match event.id().as_ref() {
+            "open" => show_main_window(app),
+            "background" => {
+                // The item has already flipped its own check mark.
+                let enabled = toggle.is_checked().unwrap_or(true);
+                app.state::<BackgroundMode>().set_enabled(enabled);
+            }
+            "quit" => quit(app),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+    app.manage(mode);
+    Ok(())
+}
+
+/// Hides the main window instead of closing it while background mode is on.
+/// Other windows (the wallboard kiosk) just close.
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    let WindowEvent::CloseRequested { api, .. } = event else {
+        return;
+    };
+    if window.label() != "main" {
+        return;
+    }
+    let app = window.app_handle();
+    let mode = app.try_state::<BackgroundMode>();
+    let keep_running = mode
+        .as_ref()
+        .is_some_and(|m| m.is_enabled() && !m.quitting.load(Ordering::SeqCst));
+    if !keep_running {
+        // Make sure the HTTP server and schedulers go down with the window.
+        app.exit(0);
+        return;
+    }
+
+    api.prevent_close();
+    let _ = window.hide();
+    if let Some(mode) = mode {
+        if !mode.hint_shown.swap(true, Ordering::SeqCst) {
+            let _ = app
+                .notification()
+                .builder()
+                .title(app.package_info().name.clone())
+                .body("Still running in the background. Use Quit in the tray icon to exit.")
+                .show();
+        }
+    }
+}
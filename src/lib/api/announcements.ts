@@ -1,6 +1,7 @@
 import { getTokenOrThrow, safeInvoke } from './core';
 import type {
   Announcement,
+  AnnouncementStats,
   CreateAnnouncementDto,
   PaginatedResponse,
   UpdateAnnouncementDto,
@@ -44,4 +45,7 @@ export const announcements = {
 
   deleteAdmin: (id: string): Promise<void> =>
     safeInvoke('delete_announcement_admin', { token: getTokenOrThrow(), id }),
+
+  statsAdmin: (id: string, occurrence?: number): Promise<AnnouncementStats> =>
+    safeInvoke('get_announcement_stats_admin', { token: getTokenOrThrow(), id, occurrence }),
 };
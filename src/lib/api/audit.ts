@@ -1,20 +1,62 @@
 import { getTokenOrThrow, safeInvoke } from './core';
-import type { AuditLog, PaginatedResponse } from './types';
+import type {
+  AuditChainReport,
+  AuditExportFormat,
+  AuditFilterPreset,
+  AuditLog,
+  AuditLogExport,
+  AuditLogFilters,
+  PaginatedResponse,
+} from './types';
+
+/** `global` is the super admin view across tenants, `tenant` the current tenant's. */
+export type AuditScope = 'global' | 'tenant';
+
+const scoped = (scope: AuditScope, global: string, tenant: string) =>
+  scope === 'global' ? global : tenant;
 
 export const audit = {
   listTenant: (
     page?: number,
     perPage?: number,
-    filters?: {
-      user_id?: string;
-      customer_id?: string;
-      resource?: string;
-      resource_id?: string;
-      action?: string;
-      date_from?: string;
-      date_to?: string;
-      search?: string;
-    },
+    filters?: Omit<AuditLogFilters, 'tenant_id'>,
   ): Promise<PaginatedResponse<AuditLog>> =>
     safeInvoke('list_tenant_audit_logs', { token: getTokenOrThrow(), page, perPage, ...filters }),
+
+  export: (
+    scope: AuditScope,
+    format: AuditExportFormat,
+    filters?: AuditLogFilters,
+  ): Promise<AuditLogExport> =>
+    safeInvoke(scoped(scope, 'export_audit_logs', 'export_tenant_audit_logs'), {
+      token: getTokenOrThrow(),
+      format,
+      ...filters,
+    }),
+
+  listPresets: (scope: AuditScope): Promise<AuditFilterPreset[]> =>
+    safeInvoke(scoped(scope, 'list_audit_filter_presets', 'list_tenant_audit_filter_presets'), {
+      token: getTokenOrThrow(),
+    }),
+
+  savePreset: (
+    scope: AuditScope,
+    name: string,
+    filters: AuditLogFilters,
+  ): Promise<AuditFilterPreset> =>
+    safeInvoke(scoped(scope, 'save_audit_filter_preset', 'save_tenant_audit_filter_preset'), {
+      token: getTokenOrThrow(),
+      name,
+      filters,
+    }),
+
+  deletePreset: (scope: AuditScope, id: string): Promise<void> =>
+    safeInvoke(scoped(scope, 'delete_audit_filter_preset', 'delete_tenant_audit_filter_preset'), {
+      token: getTokenOrThrow(),
+      id,
+    }),
+
+  /** Super admin only: checks the tamper-evident chain over all entries. */
+  verifyChain: (): Promise<AuditChainReport> =>
+    safeInvoke('verify_audit_chain', { token: getTokenOrThrow() }),
 };
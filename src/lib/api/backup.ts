@@ -1,7 +1,15 @@
 import { invoke } from '@tauri-apps/api/core';
 import { getApiBaseUrl } from '$lib/utils/apiUrl';
 import { getTokenOrThrow, isTauriRuntime, safeInvoke } from './core';
-import type { BackupRecord } from './types';
+import type {
+  BackupRecord,
+  BackupRunTarget,
+  BackupSchedulePreview,
+  CronPreview,
+  RemoteBackupRecord,
+  RestoreValidationReport,
+  ScratchRestoreReport,
+} from './types';
 
 export const backup = {
   list: async (opts?: { scope?: 'all' | 'tenant' }): Promise<BackupRecord[]> => {
@@ -132,4 +140,90 @@ export const backup = {
     const token = getTokenOrThrow();
     return await safeInvoke('restore_local_backup_command', { token, filename });
   },
+
+  validate: async (file?: File): Promise<RestoreValidationReport> => {
+    const isTauri = isTauriRuntime();
+    const token = getTokenOrThrow();
+
+    if (isTauri && !file) {
+      const { open } = await import('@tauri-apps/plugin-dialog');
+      const selected = await open({
+        filters: [{ name: 'Archive', extensions: ['zip'] }],
+      });
+
+      if (selected && typeof selected === 'string') {
+        return await safeInvoke('validate_backup_file', { token, path: selected });
+      }
+      throw new Error('No file selected');
+    } else if (file) {
+      const apiBase = getApiBaseUrl();
+      const formData = new FormData();
+      formData.append('file', file);
+
+      const response = await fetch(`${apiBase}/backups/validate`, {
+        method: 'POST',
+        headers: { Authorization: `Bearer ${token}` },
+        body: formData,
+      });
+
+      if (!response.ok) {
+        const error = await response.json().catch(() => ({}));
+        throw new Error(error.error || 'Validation failed');
+      }
+      return await response.json();
+    } else {
+      throw new Error('File required for web validation');
+    }
+  },
+
+  validateLocal: async (filename: string): Promise<RestoreValidationReport> => {
+    const token = getTokenOrThrow();
+    return await safeInvoke('validate_local_backup', { token, filename });
+  },
+
+  dryRunRestore: async (filename: string): Promise<ScratchRestoreReport> => {
+    const token = getTokenOrThrow();
+    return await safeInvoke('dry_run_restore_backup', { token, filename });
+  },
+
+  dropScratch: async (schema: string): Promise<void> => {
+    const token = getTokenOrThrow();
+    return await safeInvoke('drop_restore_scratch', { token, schema });
+  },
+
+  setPinned: async (filename: string, pinned: boolean): Promise<void> => {
+    const token = getTokenOrThrow();
+    return await safeInvoke(pinned ? 'pin_backup' : 'unpin_backup', { token, filename });
+  },
+
+  listRemote: async (): Promise<RemoteBackupRecord[]> => {
+    const token = getTokenOrThrow();
+    return await safeInvoke('list_remote_backups', { token });
+  },
+
+  uploadOffsite: async (filename: string): Promise<string> => {
+    const token = getTokenOrThrow();
+    return await safeInvoke('upload_backup_offsite', { token, filename });
+  },
+
+  restoreRemote: async (filename: string): Promise<void> => {
+    const token = getTokenOrThrow();
+    return await safeInvoke('restore_remote_backup_command', { token, filename });
+  },
+
+  getSchedule: async (): Promise<BackupSchedulePreview> => {
+    const token = getTokenOrThrow();
+    return await safeInvoke('get_backup_schedule', { token });
+  },
+
+  previewCron: async (expression: string, count?: number): Promise<CronPreview> => {
+    const token = getTokenOrThrow();
+    return await safeInvoke('preview_backup_cron', { token, expression, count });
+  },
+
+  /** Resolves with the run id; follow progress via the `backup_run_progress` window event. */
+  runNow: async (target: BackupRunTarget, tenantId?: string): Promise<string> => {
+    const token = getTokenOrThrow();
+    return await safeInvoke('run_backup_now', { token, target, tenantId });
+  },
 };
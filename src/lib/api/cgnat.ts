@@ -0,0 +1,16 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type {
+  CgnatImportResult,
+  CgnatLookupRequest,
+  CgnatPortBlock,
+  ImportCgnatLogRequest,
+} from './types';
+
+export const cgnat = {
+  importLog: (dto: ImportCgnatLogRequest): Promise<CgnatImportResult> =>
+    safeInvoke('import_cgnat_log', { token: getTokenOrThrow(), ...dto }),
+
+  /** Subscribers that held the public IP and port at the time, current holder first. */
+  lookup: (params: CgnatLookupRequest): Promise<CgnatPortBlock[]> =>
+    safeInvoke('lookup_cgnat_subscriber', { token: getTokenOrThrow(), ...params }),
+};
@@ -2,49 +2,85 @@ import { announcements } from './announcements';
 import { audit } from './audit';
 import { auth } from './auth';
 import { backup } from './backup';
+import { cgnat } from './cgnat';
 import { customers } from './customers';
+import { emailDkim } from './emailDkim';
 import { emailOutbox } from './emailOutbox';
+import { emailSuppressions } from './emailSuppressions';
+import { emailTemplates } from './emailTemplates';
+import { featureFlags } from './featureFlags';
+import { fieldSync } from './fieldSync';
 import { install } from './install';
+import { integrations } from './integrations';
+import { inventory } from './inventory';
+import { ipam } from './ipam';
 import { ispPackages } from './ispPackages';
+import { legacyImport } from './legacyImport';
 import { mikrotik } from './mikrotik';
 import { networkMapping } from './networkMapping';
+import { notificationRouting } from './notificationRouting';
+import { notificationTemplates } from './notificationTemplates';
 import { notifications } from './notifications';
+import { onboarding } from './onboarding';
 import { payment } from './payment';
 import { plans } from './plans';
 import { pppoe } from './pppoe';
 import { publicApi } from './public';
+import { reportSchedules } from './reportSchedules';
 import { roles } from './roles';
 import { settings } from './settings';
+import { statusPage } from './statusPage';
 import { storage } from './storage';
 import { support } from './support';
 import { superadmin } from './superadmin';
 import { tenant } from './tenant';
 import { team } from './team';
+import { telegram } from './telegram';
+import { uplinkCapacity } from './uplinkCapacity';
 import { users } from './users';
+import { whatsapp } from './whatsapp';
 import { workOrders } from './workOrders';
 export { announcements } from './announcements';
 export { audit } from './audit';
 export { auth } from './auth';
 export { backup } from './backup';
+export { cgnat } from './cgnat';
 export { customers } from './customers';
+export { emailDkim } from './emailDkim';
 export { emailOutbox } from './emailOutbox';
+export { emailSuppressions } from './emailSuppressions';
+export { emailTemplates } from './emailTemplates';
+export { featureFlags } from './featureFlags';
+export { fieldSync } from './fieldSync';
 export { install } from './install';
+export { integrations } from './integrations';
+export { inventory } from './inventory';
+export { ipam } from './ipam';
 export { ispPackages } from './ispPackages';
+export { legacyImport } from './legacyImport';
 export { mikrotik } from './mikrotik';
 export { networkMapping } from './networkMapping';
+export { notificationRouting } from './notificationRouting';
+export { notificationTemplates } from './notificationTemplates';
 export { notifications } from './notifications';
+export { onboarding } from './onboarding';
 export { payment } from './payment';
 export { plans } from './plans';
 export { pppoe } from './pppoe';
 export { publicApi } from './public';
+export { reportSchedules } from './reportSchedules';
 export { roles } from './roles';
 export { settings } from './settings';
+export { statusPage } from './statusPage';
 export { storage } from './storage';
 export { support } from './support';
 export { superadmin } from './superadmin';
 export { tenant } from './tenant';
 export { team } from './team';
+export { telegram } from './telegram';
+export { uplinkCapacity } from './uplinkCapacity';
 export { users } from './users';
+export { whatsapp } from './whatsapp';
 export { workOrders } from './workOrders';
 export type * from './types';
 // Combined API object
@@ -55,9 +91,17 @@ export const api = {
   team,
   customers,
   workOrders,
+  inventory,
+  fieldSync,
   pppoe,
   ispPackages,
   networkMapping,
+  ipam,
+  cgnat,
+  legacyImport,
+  integrations,
+  onboarding,
+  uplinkCapacity,
   superadmin,
   audit,
   mikrotik,
@@ -70,7 +114,17 @@ export const api = {
   payment,
   tenant,
   notifications,
+  notificationRouting,
+  notificationTemplates,
+  statusPage,
+  reportSchedules,
+  emailTemplates,
+  emailDkim,
   emailOutbox,
+  emailSuppressions,
+  featureFlags,
+  whatsapp,
+  telegram,
   backup,
 };
 
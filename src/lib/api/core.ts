@@ -1,5 +1,12 @@
 import { invoke } from '@tauri-apps/api/core';
 import { getApiBaseUrl } from '$lib/utils/apiUrl';
+import {
+  OfflineError,
+  clearOfflineCache,
+  replayOfflineWrites,
+  startOfflineSync,
+  withOfflineSupport,
+} from './offline';
 
 const AUTH_STORAGE_KEYS = ['auth_token', 'auth_user', 'auth_tenant', 'active_tenant_slug'] as const;
 
@@ -32,6 +39,10 @@ const commandMap: Record<string, { method: string; path: string }> = {
   create_user: { method: 'POST', path: '/users' },
   update_user: { method: 'PUT', path: '/users/:id' },
   delete_user: { method: 'DELETE', path: '/users/:id' },
+  list_deleted_users: { method: 'GET', path: '/users/trash' },
+  restore_user: { method: 'POST', path: '/users/:id/restore' },
+  get_my_locale: { method: 'GET', path: '/users/me/locale' },
+  set_my_locale: { method: 'PUT', path: '/users/me/locale' },
   list_my_addresses: { method: 'GET', path: '/users/me/addresses' },
   create_my_address: { method: 'POST', path: '/users/me/addresses' },
   update_my_address: { method: 'PUT', path: '/users/me/addresses/:addressId' },
@@ -39,21 +50,61 @@ const commandMap: Record<string, { method: string; path: string }> = {
   list_tenants: { method: 'GET', path: '/superadmin/tenants' },
   create_tenant: { method: 'POST', path: '/superadmin/tenants' },
   delete_tenant: { method: 'DELETE', path: '/superadmin/tenants/:id' },
+  export_tenant_archive: { method: 'GET', path: '/superadmin/tenants/:id/export' },
+  import_tenant_archive: { method: 'POST', path: '/superadmin/tenants/import' },
   list_audit_logs: { method: 'GET', path: '/superadmin/audit-logs' },
+  export_audit_logs: { method: 'GET', path: '/superadmin/audit-logs/export' },
+  list_audit_filter_presets: { method: 'GET', path: '/superadmin/audit-logs/presets' },
+  save_audit_filter_preset: { method: 'POST', path: '/superadmin/audit-logs/presets' },
+  delete_audit_filter_preset: { method: 'DELETE', path: '/superadmin/audit-logs/presets/:id' },
+  verify_audit_chain: { method: 'GET', path: '/superadmin/audit-logs/verify' },
   get_system_health: { method: 'GET', path: '/superadmin/system' },
   get_system_diagnostics: { method: 'GET', path: '/superadmin/diagnostics' },
+  get_migration_status: { method: 'GET', path: '/superadmin/migrations' },
+  run_db_maintenance: { method: 'POST', path: '/superadmin/maintenance' },
+  list_background_jobs: { method: 'GET', path: '/superadmin/jobs' },
+  get_background_job_stats: { method: 'GET', path: '/superadmin/jobs/stats' },
+  retry_background_job: { method: 'POST', path: '/superadmin/jobs/:id/retry' },
+  list_alert_rules: { method: 'GET', path: '/superadmin/alert-rules' },
+  create_alert_rule: { method: 'POST', path: '/superadmin/alert-rules' },
+  update_alert_rule: { method: 'PUT', path: '/superadmin/alert-rules/:id' },
+  delete_alert_rule: { method: 'DELETE', path: '/superadmin/alert-rules/:id' },
+  test_alert_rule: { method: 'POST', path: '/superadmin/alert-rules/:id/test' },
   list_support_tickets: { method: 'GET', path: '/support/tickets' },
   get_support_ticket_stats: { method: 'GET', path: '/support/tickets/stats' },
   create_support_ticket: { method: 'POST', path: '/support/tickets' },
   get_support_ticket: { method: 'GET', path: '/support/tickets/:id' },
   reply_support_ticket: { method: 'POST', path: '/support/tickets/:id/messages' },
   update_support_ticket: { method: 'PUT', path: '/support/tickets/:id' },
+  list_support_queues: { method: 'GET', path: '/support/queues' },
+  create_support_queue: { method: 'POST', path: '/support/queues' },
+  update_support_queue: { method: 'PUT', path: '/support/queues/:id' },
+  delete_support_queue: { method: 'DELETE', path: '/support/queues/:id' },
+  list_support_escalation_rules: { method: 'GET', path: '/support/escalation-rules' },
+  create_support_escalation_rule: { method: 'POST', path: '/support/escalation-rules' },
+  update_support_escalation_rule: { method: 'PUT', path: '/support/escalation-rules/:id' },
+  delete_support_escalation_rule: { method: 'DELETE', path: '/support/escalation-rules/:id' },
+  list_support_macros: { method: 'GET', path: '/support/macros' },
+  create_support_macro: { method: 'POST', path: '/support/macros' },
+  update_support_macro: { method: 'PUT', path: '/support/macros/:id' },
+  delete_support_macro: { method: 'DELETE', path: '/support/macros/:id' },
+  preview_support_macro: { method: 'GET', path: '/support/tickets/:id/macros/:macro_id/preview' },
+  apply_support_macro: { method: 'POST', path: '/support/tickets/:id/macros/:macro_id' },
+  list_support_ticket_links: { method: 'GET', path: '/support/tickets/:id/links' },
+  link_support_ticket: { method: 'POST', path: '/support/tickets/:id/links' },
+  unlink_support_ticket: { method: 'DELETE', path: '/support/tickets/:id/links/:link_id' },
+  list_linked_support_tickets: { method: 'GET', path: '/support/links' },
   list_customers: { method: 'GET', path: '/customers' },
   get_customer: { method: 'GET', path: '/customers/:customerId' },
   create_customer: { method: 'POST', path: '/customers' },
   create_customer_with_portal: { method: 'POST', path: '/customers/with-portal' },
   update_customer: { method: 'PUT', path: '/customers/:customerId' },
+  list_customer_tags: { method: 'GET', path: '/customers/tags' },
+  get_customer_tags: { method: 'GET', path: '/customers/:customerId/tags' },
+  set_customer_tags: { method: 'PUT', path: '/customers/:customerId/tags' },
   delete_customer: { method: 'DELETE', path: '/customers/:customerId' },
+  list_deleted_customers: { method: 'GET', path: '/customers/trash' },
+  restore_customer: { method: 'POST', path: '/customers/:customerId/restore' },
   list_customer_registration_invites: { method: 'GET', path: '/customers/invites' },
   create_customer_registration_invite: { method: 'POST', path: '/customers/invites' },
   get_customer_registration_invite_policy: { method: 'GET', path: '/customers/invites/policy' },
@@ -67,10 +118,15 @@ const commandMap: Record<string, { method: string; path: string }> = {
     path: '/customers/invites/:inviteId',
   },
   list_customer_locations: { method: 'GET', path: '/customers/:customerId/locations' },
+  list_customer_completion_reports: {
+    method: 'GET',
+    path: '/customers/:customerId/completion-reports',
+  },
   create_customer_location: { method: 'POST', path: '/customers/locations' },
   update_customer_location: { method: 'PUT', path: '/customers/locations/:locationId' },
   delete_customer_location: { method: 'DELETE', path: '/customers/locations/:locationId' },
   list_customer_portal_users: { method: 'GET', path: '/customers/:customerId/portal-users' },
+  get_customer_usage: { method: 'GET', path: '/customers/:customerId/usage' },
   add_customer_portal_user: { method: 'POST', path: '/customers/portal-users/add' },
   create_customer_portal_user: { method: 'POST', path: '/customers/portal-users/create' },
   remove_customer_portal_user: {
@@ -90,6 +146,14 @@ const commandMap: Record<string, { method: string; path: string }> = {
     method: 'DELETE',
     path: '/customers/subscriptions/:subscriptionId',
   },
+  list_customer_subscription_components: {
+    method: 'GET',
+    path: '/customers/subscriptions/:subscriptionId/components',
+  },
+  update_customer_subscription_component: {
+    method: 'PUT',
+    path: '/customers/subscription-components/:componentId',
+  },
   list_my_customer_locations: { method: 'GET', path: '/customers/portal/my-locations' },
   create_my_customer_location: { method: 'POST', path: '/customers/portal/my-locations' },
   update_my_customer_location: {
@@ -127,14 +191,61 @@ const commandMap: Record<string, { method: string; path: string }> = {
     path: '/customers/portal/checkout',
   },
   list_installation_work_orders: { method: 'GET', path: '/admin/work-orders' },
+  create_work_order: { method: 'POST', path: '/admin/work-orders' },
   list_installation_assignees: { method: 'GET', path: '/admin/work-orders/assignees' },
   assign_installation_work_order: { method: 'POST', path: '/admin/work-orders/:id/assign' },
+  get_work_order_agenda: { method: 'GET', path: '/admin/work-orders/agenda' },
+  plan_technician_route: { method: 'GET', path: '/admin/work-orders/route-plan' },
   claim_installation_work_order: { method: 'POST', path: '/admin/work-orders/:id/claim' },
   release_installation_work_order: { method: 'POST', path: '/admin/work-orders/:id/release' },
   start_installation_work_order: { method: 'POST', path: '/admin/work-orders/:id/start' },
+  hold_work_order: { method: 'POST', path: '/admin/work-orders/:id/hold' },
   complete_installation_work_order: { method: 'POST', path: '/admin/work-orders/:id/complete' },
   cancel_installation_work_order: { method: 'POST', path: '/admin/work-orders/:id/cancel' },
   reopen_installation_work_order: { method: 'POST', path: '/admin/work-orders/:id/reopen' },
+  list_work_order_completion_reports: {
+    method: 'GET',
+    path: '/admin/work-orders/:id/completion-reports',
+  },
+  sign_work_order_completion: { method: 'POST', path: '/admin/work-orders/:id/completion-reports' },
+  email_work_order_completion_report: {
+    method: 'POST',
+    path: '/admin/work-orders/completion-reports/:report_id/email',
+  },
+  list_work_order_visits: { method: 'GET', path: '/admin/work-orders/:id/visits' },
+  check_in_work_order: { method: 'POST', path: '/admin/work-orders/:id/check-in' },
+  check_out_work_order: { method: 'POST', path: '/admin/work-orders/:id/check-out' },
+  get_work_order_checklist: { method: 'GET', path: '/admin/work-orders/:id/checklist' },
+  apply_work_order_checklist_template: {
+    method: 'POST',
+    path: '/admin/work-orders/:id/checklist/template',
+  },
+  update_work_order_checklist_item: {
+    method: 'PUT',
+    path: '/admin/work-orders/:id/checklist/items/:item_id',
+  },
+  list_work_order_templates: { method: 'GET', path: '/admin/work-orders/templates' },
+  create_work_order_template: { method: 'POST', path: '/admin/work-orders/templates' },
+  update_work_order_template: { method: 'PUT', path: '/admin/work-orders/templates/:id' },
+  delete_work_order_template: { method: 'DELETE', path: '/admin/work-orders/templates/:id' },
+  list_work_order_materials: { method: 'GET', path: '/admin/work-orders/:id/materials' },
+  consume_work_order_materials: { method: 'POST', path: '/admin/work-orders/:id/materials' },
+  install_work_order_serial: { method: 'POST', path: '/admin/work-orders/:id/materials/serial' },
+  field_sync_pull: { method: 'POST', path: '/admin/field-sync/pull' },
+  field_sync_push: { method: 'POST', path: '/admin/field-sync/push' },
+  list_inventory_items: { method: 'GET', path: '/admin/inventory/items' },
+  create_inventory_item: { method: 'POST', path: '/admin/inventory/items' },
+  update_inventory_item: { method: 'PUT', path: '/admin/inventory/items/:id' },
+  list_inventory_locations: { method: 'GET', path: '/admin/inventory/locations' },
+  create_inventory_location: { method: 'POST', path: '/admin/inventory/locations' },
+  update_inventory_location: { method: 'PUT', path: '/admin/inventory/locations/:id' },
+  list_inventory_stock: { method: 'GET', path: '/admin/inventory/stock' },
+  list_inventory_movements: { method: 'GET', path: '/admin/inventory/movements' },
+  create_inventory_movement: { method: 'POST', path: '/admin/inventory/movements' },
+  resolve_inventory_scan: { method: 'GET', path: '/admin/inventory/scan' },
+  list_inventory_serials: { method: 'GET', path: '/admin/inventory/serials' },
+  receive_inventory_serials: { method: 'POST', path: '/admin/inventory/serials' },
+  transfer_inventory_serials: { method: 'POST', path: '/admin/inventory/serials/transfer' },
   get_pending_work_order_reschedule_request: {
     method: 'GET',
     path: '/admin/work-orders/:id/reschedule-request',
@@ -173,6 +284,8 @@ const commandMap: Record<string, { method: string; path: string }> = {
   get_permissions: { method: 'GET', path: '/permissions' },
   get_tenant_by_slug: { method: 'GET', path: '/public/tenants/:slug' },
   get_tenant_by_domain: { method: 'GET', path: '/public/domains/:domain' },
+  get_public_status_page: { method: 'GET', path: '/public/status/:slug' },
+  check_client_compatibility: { method: 'GET', path: '/public/client-compatibility' },
   get_customer_registration_status_by_domain: {
     method: 'GET',
     path: '/public/customer-registration-status',
@@ -195,12 +308,25 @@ const commandMap: Record<string, { method: string; path: string }> = {
   get_tenant_subscription: { method: 'GET', path: '/plans/subscriptions/:tenant_id' },
   get_tenant_subscription_details: { method: 'GET', path: '/plans/subscriptions/details' },
   assign_plan_to_tenant: { method: 'POST', path: '/plans/subscriptions/:tenant_id/assign' },
+  start_tenant_trial: { method: 'POST', path: '/plans/subscriptions/:tenant_id/trial' },
   check_feature_access: { method: 'GET', path: '/plans/access/:tenant_id/:feature_code' },
   send_test: { method: 'POST', path: '/notifications/test' },
   list_files_admin: { method: 'GET', path: '/storage/files' },
   list_files_tenant: { method: 'GET', path: '/storage/files' },
   delete_file_admin: { method: 'DELETE', path: '/storage/files/:file_id' },
   delete_file_tenant: { method: 'DELETE', path: '/storage/files/:file_id' },
+  list_file_versions: { method: 'GET', path: '/storage/files/:file_id/versions' },
+  restore_file_version: {
+    method: 'POST',
+    path: '/storage/files/:file_id/versions/:version_id/restore',
+  },
+  create_file_link: { method: 'POST', path: '/storage/files/:file_id/links' },
+  get_storage_usage: { method: 'GET', path: '/storage/usage' },
+  list_storage_cleanup_rules: { method: 'GET', path: '/storage/cleanup-rules' },
+  create_storage_cleanup_rule: { method: 'POST', path: '/storage/cleanup-rules' },
+  update_storage_cleanup_rule: { method: 'PUT', path: '/storage/cleanup-rules/:id' },
+  delete_storage_cleanup_rule: { method: 'DELETE', path: '/storage/cleanup-rules/:id' },
+  run_storage_cleanup_rule: { method: 'POST', path: '/storage/cleanup-rules/:id/run' },
   upload_init: { method: 'POST', path: '/storage/upload/init' },
   upload_chunk: { method: 'POST', path: '/storage/upload/chunk' },
   upload_complete: { method: 'POST', path: '/storage/upload/complete' },
@@ -208,6 +334,8 @@ const commandMap: Record<string, { method: string; path: string }> = {
   create_bank_account: { method: 'POST', path: '/payment/banks' },
   delete_bank_account: { method: 'DELETE', path: '/payment/banks/:id' },
   create_invoice_for_plan: { method: 'POST', path: '/payment/invoices/plan' },
+  quote_plan_upgrade: { method: 'POST', path: '/payment/upgrade/quote' },
+  checkout_plan_upgrade: { method: 'POST', path: '/payment/upgrade/checkout' },
   create_invoice_for_customer_subscription: {
     method: 'POST',
     path: '/payment/invoices/customer-package/create',
@@ -233,6 +361,10 @@ const commandMap: Record<string, { method: string; path: string }> = {
     method: 'POST',
     path: '/payment/invoices/:id/customer-package/verify',
   },
+  reprice_customer_package_invoice: {
+    method: 'POST',
+    path: '/payment/invoices/:id/customer-package/reprice',
+  },
   verify_payment: { method: 'POST', path: '/payment/invoices/:invoiceId/verify' },
   list_all_invoices: { method: 'GET', path: '/payment/invoices/all' },
   get_fx_rate: { method: 'GET', path: '/payment/fx-rate' },
@@ -243,9 +375,14 @@ const commandMap: Record<string, { method: string; path: string }> = {
   get_unread_count: { method: 'GET', path: '/notifications/unread-count' },
   mark_as_read: { method: 'POST', path: '/notifications/:id/read' },
   mark_all_as_read: { method: 'POST', path: '/notifications/read-all' },
+  archive_notifications: { method: 'POST', path: '/notifications/archive' },
   delete_notification: { method: 'DELETE', path: '/notifications/:id' },
   get_preferences: { method: 'GET', path: '/notifications/preferences' },
   update_preference: { method: 'PUT', path: '/notifications/preferences' },
+  get_quiet_hours: { method: 'GET', path: '/notifications/quiet-hours' },
+  update_quiet_hours: { method: 'PUT', path: '/notifications/quiet-hours' },
+  get_notification_delivery_report: { method: 'GET', path: '/notifications/delivery-report' },
+  get_vapid_public_key: { method: 'GET', path: '/notifications/push/vapid-public-key' },
   subscribe_push: { method: 'POST', path: '/notifications/push/subscribe' },
   unsubscribe_push: { method: 'POST', path: '/notifications/push/unsubscribe' },
   send_test_notification: { method: 'POST', path: '/notifications/test' },
@@ -253,10 +390,70 @@ const commandMap: Record<string, { method: string; path: string }> = {
   get_email_outbox: { method: 'GET', path: '/email-outbox/:id' },
   get_email_outbox_stats: { method: 'GET', path: '/email-outbox/stats' },
   retry_email_outbox: { method: 'POST', path: '/email-outbox/:id/retry' },
+  schedule_email_outbox: { method: 'POST', path: '/email-outbox/:id/schedule' },
   delete_email_outbox: { method: 'DELETE', path: '/email-outbox/:id' },
   bulk_retry_email_outbox: { method: 'POST', path: '/email-outbox/bulk/retry' },
   bulk_delete_email_outbox: { method: 'POST', path: '/email-outbox/bulk/delete' },
   export_email_outbox_csv: { method: 'GET', path: '/email-outbox/export' },
+  list_email_suppressions: { method: 'GET', path: '/email-suppressions' },
+  add_email_suppression: { method: 'POST', path: '/email-suppressions' },
+  remove_email_suppression: { method: 'DELETE', path: '/email-suppressions/:id' },
+  evaluate_feature_flags: { method: 'GET', path: '/feature-flags/evaluate' },
+  list_feature_flags: { method: 'GET', path: '/feature-flags' },
+  upsert_feature_flag: { method: 'PUT', path: '/feature-flags/:key' },
+  delete_feature_flag: { method: 'DELETE', path: '/feature-flags/:key' },
+  set_feature_flag_override: { method: 'PUT', path: '/feature-flags/:key/overrides/:tenant_id' },
+  remove_feature_flag_override: {
+    method: 'DELETE',
+    path: '/feature-flags/:key/overrides/:tenant_id',
+  },
+  list_notification_templates: { method: 'GET', path: '/notification-templates' },
+  update_notification_template: { method: 'PUT', path: '/notification-templates/:code' },
+  reset_notification_template: { method: 'DELETE', path: '/notification-templates/:code' },
+  preview_notification_template: { method: 'POST', path: '/notification-templates/:code/preview' },
+  list_email_templates: { method: 'GET', path: '/email-templates' },
+  get_email_template: { method: 'GET', path: '/email-templates/:code' },
+  list_email_template_variables: { method: 'GET', path: '/email-templates/:code/variables' },
+  update_email_template: { method: 'PUT', path: '/email-templates/:code' },
+  reset_email_template: { method: 'DELETE', path: '/email-templates/:code' },
+  preview_email_template: { method: 'POST', path: '/email-templates/:code/preview' },
+  test_send_email_template: { method: 'POST', path: '/email-templates/:code/test-send' },
+  get_email_dkim: { method: 'GET', path: '/email-dkim' },
+  generate_email_dkim_key: { method: 'POST', path: '/email-dkim/generate' },
+  update_email_dkim: { method: 'PUT', path: '/email-dkim' },
+  delete_email_dkim_key: { method: 'DELETE', path: '/email-dkim' },
+  list_notification_rules: { method: 'GET', path: '/notification-rules' },
+  create_notification_rule: { method: 'POST', path: '/notification-rules' },
+  update_notification_rule: { method: 'PUT', path: '/notification-rules/:id' },
+  delete_notification_rule: { method: 'DELETE', path: '/notification-rules/:id' },
+  evaluate_notification_routing: { method: 'POST', path: '/notification-rules/evaluate' },
+  preview_status_page: { method: 'GET', path: '/status-page/preview' },
+  list_status_components: { method: 'GET', path: '/status-page/components' },
+  create_status_component: { method: 'POST', path: '/status-page/components' },
+  update_status_component: { method: 'PUT', path: '/status-page/components/:id' },
+  delete_status_component: { method: 'DELETE', path: '/status-page/components/:id' },
+  list_status_posts: { method: 'GET', path: '/status-page/posts' },
+  create_status_post: { method: 'POST', path: '/status-page/posts' },
+  update_status_post: { method: 'PUT', path: '/status-page/posts/:id' },
+  delete_status_post: { method: 'DELETE', path: '/status-page/posts/:id' },
+  add_status_post_update: { method: 'POST', path: '/status-page/posts/:id/updates' },
+  list_report_schedules: { method: 'GET', path: '/report-schedules' },
+  create_report_schedule: { method: 'POST', path: '/report-schedules' },
+  update_report_schedule: { method: 'PUT', path: '/report-schedules/:id' },
+  delete_report_schedule: { method: 'DELETE', path: '/report-schedules/:id' },
+  send_report_schedule: { method: 'POST', path: '/report-schedules/:id/send' },
+  list_whatsapp_templates: { method: 'GET', path: '/whatsapp/templates' },
+  create_whatsapp_template: { method: 'POST', path: '/whatsapp/templates' },
+  update_whatsapp_template: { method: 'PUT', path: '/whatsapp/templates/:id' },
+  delete_whatsapp_template: { method: 'DELETE', path: '/whatsapp/templates/:id' },
+  send_whatsapp_test: { method: 'POST', path: '/whatsapp/templates/:id/test' },
+  list_whatsapp_routes: { method: 'GET', path: '/whatsapp/routes' },
+  set_whatsapp_route: { method: 'PUT', path: '/whatsapp/routes/:event' },
+  list_whatsapp_messages: { method: 'GET', path: '/whatsapp/messages' },
+  get_telegram_link: { method: 'GET', path: '/telegram/link' },
+  create_telegram_link_code: { method: 'POST', path: '/telegram/link/code' },
+  update_telegram_link: { method: 'PUT', path: '/telegram/link' },
+  unlink_telegram: { method: 'DELETE', path: '/telegram/link' },
   list_active_announcements: { method: 'GET', path: '/announcements/active' },
   list_recent_announcements: { method: 'GET', path: '/announcements/recent' },
   get_announcement: { method: 'GET', path: '/announcements/:id' },
@@ -265,9 +462,16 @@ const commandMap: Record<string, { method: string; path: string }> = {
   create_announcement_admin: { method: 'POST', path: '/announcements/admin' },
   update_announcement_admin: { method: 'PUT', path: '/announcements/admin/:id' },
   delete_announcement_admin: { method: 'DELETE', path: '/announcements/admin/:id' },
+  get_announcement_stats_admin: { method: 'GET', path: '/announcements/admin/:id/stats' },
   get_current_tenant: { method: 'GET', path: '/tenant/me' },
   update_current_tenant: { method: 'PUT', path: '/tenant/me' },
+  get_tenant_usage: { method: 'GET', path: '/tenant/usage' },
+  get_tenant_analytics: { method: 'GET', path: '/tenant/analytics' },
   list_tenant_audit_logs: { method: 'GET', path: '/admin/audit-logs' },
+  export_tenant_audit_logs: { method: 'GET', path: '/admin/audit-logs/export' },
+  list_tenant_audit_filter_presets: { method: 'GET', path: '/admin/audit-logs/presets' },
+  save_tenant_audit_filter_preset: { method: 'POST', path: '/admin/audit-logs/presets' },
+  delete_tenant_audit_filter_preset: { method: 'DELETE', path: '/admin/audit-logs/presets/:id' },
   list_mikrotik_routers: { method: 'GET', path: '/admin/mikrotik/routers' },
   list_mikrotik_noc: { method: 'GET', path: '/admin/mikrotik/noc' },
   list_mikrotik_alerts: { method: 'GET', path: '/admin/mikrotik/alerts' },
@@ -277,6 +481,11 @@ const commandMap: Record<string, { method: string; path: string }> = {
   resolve_mikrotik_alert: { method: 'POST', path: '/admin/mikrotik/alerts/:id/resolve' },
   ack_mikrotik_incident: { method: 'POST', path: '/admin/mikrotik/incidents/:id/ack' },
   resolve_mikrotik_incident: { method: 'POST', path: '/admin/mikrotik/incidents/:id/resolve' },
+  get_mikrotik_incident_impact: { method: 'GET', path: '/admin/mikrotik/incidents/:id/impact' },
+  refresh_mikrotik_incident_impact: {
+    method: 'POST',
+    path: '/admin/mikrotik/incidents/:id/impact/refresh',
+  },
   update_mikrotik_incident: { method: 'PUT', path: '/admin/mikrotik/incidents/:id' },
   simulate_mikrotik_incident: { method: 'POST', path: '/admin/mikrotik/incidents/simulate' },
   run_mikrotik_incident_auto_escalation: {
@@ -286,6 +495,8 @@ const commandMap: Record<string, { method: string; path: string }> = {
   create_mikrotik_router: { method: 'POST', path: '/admin/mikrotik/routers' },
   update_mikrotik_router: { method: 'PUT', path: '/admin/mikrotik/routers/:id' },
   delete_mikrotik_router: { method: 'DELETE', path: '/admin/mikrotik/routers/:id' },
+  list_deleted_mikrotik_routers: { method: 'GET', path: '/admin/mikrotik/routers/trash' },
+  restore_mikrotik_router: { method: 'POST', path: '/admin/mikrotik/routers/:id/restore' },
   test_mikrotik_router: { method: 'POST', path: '/admin/mikrotik/routers/:id/test' },
   get_mikrotik_router: { method: 'GET', path: '/admin/mikrotik/routers/:id' },
   get_mikrotik_router_snapshot: {
@@ -322,6 +533,12 @@ const commandMap: Record<string, { method: string; path: string }> = {
     path: '/admin/mikrotik/routers/:routerId/ip-pools/sync',
   },
   sync_mikrotik_logs: { method: 'POST', path: '/admin/mikrotik/routers/:routerId/logs/sync' },
+  list_monitored_devices: { method: 'GET', path: '/admin/mikrotik/devices' },
+  get_monitored_device: { method: 'GET', path: '/admin/mikrotik/devices/:id' },
+  create_monitored_device: { method: 'POST', path: '/admin/mikrotik/devices' },
+  update_monitored_device: { method: 'PUT', path: '/admin/mikrotik/devices/:id' },
+  delete_monitored_device: { method: 'DELETE', path: '/admin/mikrotik/devices/:id' },
+  test_monitored_device: { method: 'POST', path: '/admin/mikrotik/devices/:id/test' },
   list_pppoe_accounts: { method: 'GET', path: '/admin/pppoe/accounts' },
   get_pppoe_account: { method: 'GET', path: '/admin/pppoe/accounts/:id' },
   create_pppoe_account: { method: 'POST', path: '/admin/pppoe/accounts' },
@@ -338,6 +555,12 @@ const commandMap: Record<string, { method: string; path: string }> = {
   create_isp_package: { method: 'POST', path: '/admin/isp-packages/packages' },
   update_isp_package: { method: 'PUT', path: '/admin/isp-packages/packages/:id' },
   delete_isp_package: { method: 'DELETE', path: '/admin/isp-packages/packages/:id' },
+  list_deleted_isp_packages: { method: 'GET', path: '/admin/isp-packages/packages/trash' },
+  restore_isp_package: { method: 'POST', path: '/admin/isp-packages/packages/:id/restore' },
+  list_isp_package_price_history: {
+    method: 'GET',
+    path: '/admin/isp-packages/packages/:id/price-history',
+  },
   list_isp_package_router_mappings: {
     method: 'GET',
     path: '/admin/isp-packages/router-mappings',
@@ -390,9 +613,55 @@ const commandMap: Record<string, { method: string; path: string }> = {
     method: 'GET',
     path: '/admin/network-mapping/impact/customers',
   },
+  list_fiber_points: { method: 'GET', path: '/admin/network-mapping/fiber-points' },
+  create_fiber_point: { method: 'POST', path: '/admin/network-mapping/fiber-points' },
+  update_fiber_point: { method: 'PATCH', path: '/admin/network-mapping/fiber-points/:id' },
+  delete_fiber_point: { method: 'DELETE', path: '/admin/network-mapping/fiber-points/:id' },
+  get_network_asset_map: { method: 'GET', path: '/admin/network-mapping/map' },
+  list_ipam_subnets: { method: 'GET', path: '/admin/ipam/subnets' },
+  create_ipam_subnet: { method: 'POST', path: '/admin/ipam/subnets' },
+  update_ipam_subnet: { method: 'PUT', path: '/admin/ipam/subnets/:id' },
+  delete_ipam_subnet: { method: 'DELETE', path: '/admin/ipam/subnets/:id' },
+  get_ipam_plan: { method: 'GET', path: '/admin/ipam/plan' },
+  import_cgnat_log: { method: 'POST', path: '/admin/cgnat/import' },
+  lookup_cgnat_subscriber: { method: 'GET', path: '/admin/cgnat/lookup' },
+  list_legacy_import_sources: { method: 'GET', path: '/admin/legacy-import/sources' },
+  run_legacy_import: { method: 'POST', path: '/admin/legacy-import' },
+  get_integrations_overview: { method: 'GET', path: '/integrations' },
+  list_api_keys: { method: 'GET', path: '/integrations/api-keys' },
+  create_api_key: { method: 'POST', path: '/integrations/api-keys' },
+  revoke_api_key: { method: 'DELETE', path: '/integrations/api-keys/:id' },
+  list_tenant_webhooks: { method: 'GET', path: '/integrations/webhooks' },
+  create_tenant_webhook: { method: 'POST', path: '/integrations/webhooks' },
+  update_tenant_webhook: { method: 'PUT', path: '/integrations/webhooks/:id' },
+  delete_tenant_webhook: { method: 'DELETE', path: '/integrations/webhooks/:id' },
+  test_tenant_webhook: { method: 'POST', path: '/integrations/webhooks/:id/test' },
+  list_webhook_deliveries: { method: 'GET', path: '/integrations/deliveries' },
+  redeliver_webhook_delivery: { method: 'POST', path: '/integrations/deliveries/:id/redeliver' },
+  get_api_key_rate_limits: { method: 'GET', path: '/integrations/rate-limits' },
+  get_onboarding_progress: { method: 'GET', path: '/onboarding' },
+  skip_onboarding_step: { method: 'POST', path: '/onboarding/steps/:step/skip' },
+  resume_onboarding_step: { method: 'DELETE', path: '/onboarding/steps/:step/skip' },
+  list_uplink_interfaces: { method: 'GET', path: '/admin/uplink-capacity/uplinks' },
+  create_uplink_interface: { method: 'POST', path: '/admin/uplink-capacity/uplinks' },
+  update_uplink_interface: { method: 'PUT', path: '/admin/uplink-capacity/uplinks/:id' },
+  delete_uplink_interface: { method: 'DELETE', path: '/admin/uplink-capacity/uplinks/:id' },
+  get_uplink_capacity_report: { method: 'GET', path: '/admin/uplink-capacity/report' },
   list_backups: { method: 'GET', path: '/backups' },
   create_backup: { method: 'POST', path: '/backups' },
   delete_backup: { method: 'DELETE', path: '/backups/:filename' },
+  restore_local_backup_command: { method: 'POST', path: '/backups/:filename/restore' },
+  list_remote_backups: { method: 'GET', path: '/backups/remote' },
+  upload_backup_offsite: { method: 'POST', path: '/backups/:filename/upload' },
+  pin_backup: { method: 'POST', path: '/backups/:filename/pin' },
+  unpin_backup: { method: 'DELETE', path: '/backups/:filename/pin' },
+  validate_local_backup: { method: 'POST', path: '/backups/:filename/validate' },
+  dry_run_restore_backup: { method: 'POST', path: '/backups/:filename/dry-run' },
+  drop_restore_scratch: { method: 'DELETE', path: '/backups/scratch/:schema' },
+  get_backup_schedule: { method: 'GET', path: '/backups/schedule' },
+  preview_backup_cron: { method: 'GET', path: '/backups/schedule/preview' },
+  run_backup_now: { method: 'POST', path: '/backups/run' },
+  restore_remote_backup_command: { method: 'POST', path: '/backups/remote/:filename/restore' },
 };
 
 function hasStoredAuthToken(): boolean {
@@ -406,6 +675,7 @@ function clearStoredAuthData() {
     localStorage.removeItem(key);
     sessionStorage.removeItem(key);
   }
+  clearOfflineCache();
 }
 
 function handleAuthExpired(reason: string) {
@@ -440,6 +710,170 @@ export function isTauriRuntime(): boolean {
   );
 }
 
+/** Calls the remote HTTP API for a mapped command. */
+async function invokeHttp<T>(
+  command: string,
+  route: { method: string; path: string },
+  args?: any,
+): Promise<T> {
+  const API_BASE = getApiBaseUrl();
+  const toSnakeCase = (str: string) =>
+    str.replace(/[A-Z]/g, (letter) => `_${letter.toLowerCase()}`);
+  const toCamelCase = (str: string) => str.replace(/_([a-z])/g, (_m, c: string) => c.toUpperCase());
+
+  let path = route.path;
+  const queryParams: Record<string, string> = {};
+  const consumedPathKeys = new Set<string>();
+
+  if (args) {
+    for (const [key, value] of Object.entries(args)) {
+      if (key.startsWith('__')) continue;
+      if (value === null || value === undefined) continue;
+
+      const snakeKey = toSnakeCase(key);
+      if (path.includes(`:${key}`)) {
+        path = path.replace(`:${key}`, String(value));
+        consumedPathKeys.add(key);
+        consumedPathKeys.add(snakeKey);
+        consumedPathKeys.add(toCamelCase(key));
+      } else if (path.includes(`:${snakeKey}`)) {
+        path = path.replace(`:${snakeKey}`, String(value));
+        consumedPathKeys.add(key);
+        consumedPathKeys.add(snakeKey);
+        consumedPathKeys.add(toCamelCase(key));
+      } else if (route.method === 'GET' && key !== 'token') {
+        queryParams[snakeKey] = String(value);
+      }
+    }
+  }
+
+  const queryString =
+    Object.keys(queryParams).length > 0 ? '?' + new URLSearchParams(queryParams).toString() : '';
+
+  const headers: Record<string, string> = {
+    'Content-Type': 'application/json',
+  };
+
+  const token =
+    args?.token || localStorage.getItem('auth_token') || sessionStorage.getItem('auth_token');
+  if (token) {
+    headers.Authorization = `Bearer ${token}`;
+  }
+  if (typeof args?.__idempotency_key === 'string') {
+    headers['Idempotency-Key'] = args.__idempotency_key;
+  }
+
+  const controller = typeof AbortController !== 'undefined' ? new AbortController() : null;
+  const timeoutMs =
+    typeof args?.__timeout_ms === 'number' && Number.isFinite(args.__timeout_ms)
+      ? Math.max(1, args.__timeout_ms)
+      : 15000;
+  const externalSignal: AbortSignal | undefined = args?.__signal;
+  let abortedByExternalSignal = false;
+  let abortedByTimeout = false;
+
+  const onExternalAbort = () => {
+    abortedByExternalSignal = true;
+    controller?.abort();
+  };
+
+  if (externalSignal) {
+    if (externalSignal.aborted) onExternalAbort();
+    else externalSignal.addEventListener('abort', onExternalAbort, { once: true });
+  }
+
+  const timeout = setTimeout(() => {
+    abortedByTimeout = true;
+    controller?.abort();
+  }, timeoutMs);
+
+  let response: Response;
+  try {
+    const bodyPayload =
+      route.method !== 'GET'
+        ? (() => {
+            const rawEntries = Object.entries(args || {}).filter(
+              ([key, value]) =>
+                key !== 'token' &&
+                !key.startsWith('__') &&
+                value !== undefined &&
+                !consumedPathKeys.has(key),
+            );
+
+            const keySet = new Set(rawEntries.map(([key]) => key));
+            const deduped = rawEntries.filter(([key]) => {
+              if (!key.includes('_')) return true;
+              const camelKey = toCamelCase(key);
+              return !keySet.has(camelKey);
+            });
+
+            return Object.fromEntries(deduped);
+          })()
+        : undefined;
+
+    response = await fetch(`${API_BASE}${path}${queryString}`, {
+      method: route.method,
+      headers,
+      body: bodyPayload ? JSON.stringify(bodyPayload) : undefined,
+      signal: controller?.signal,
+    });
+  } catch (e: any) {
+    const name = String(e?.name || '');
+    if (name === 'AbortError') {
+      if (abortedByExternalSignal) throw new Error('Request canceled');
+      if (abortedByTimeout) {
+        throw new OfflineError('Remote API request timed out. Is the server running?');
+      }
+      throw new Error('Request aborted');
+    }
+    // fetch only rejects when the server could not be reached at all.
+    throw new OfflineError(String(e?.message || e));
+  } finally {
+    clearTimeout(timeout);
+    if (externalSignal) externalSignal.removeEventListener('abort', onExternalAbort);
+  }
+
+  const contentType = response.headers.get('content-type') || '';
+  const isJson = contentType.toLowerCase().includes('application/json');
+
+  if (!response.ok) {
+    if (response.status === 401) {
+      handleAuthExpired(`HTTP ${response.status} from ${command}`);
+    }
+    if (isJson) {
+      const errorBody = await response.json().catch(() => ({}));
+      const message =
+        errorBody?.error ||
+        errorBody?.message ||
+        errorBody?.detail ||
+        errorBody?.details ||
+        `HTTP Error ${response.status}`;
+      throw Object.assign(new Error(message), { status: response.status });
+    }
+    const errorText = (await response.text().catch(() => '')).trim();
+    throw Object.assign(new Error(errorText || `HTTP Error ${response.status}`), {
+      status: response.status,
+    });
+  }
+
+  if (response.status === 204) return undefined as T;
+
+  const raw = await response.text();
+  if (!raw || !raw.trim()) return undefined as T;
+  if (isJson) return JSON.parse(raw) as T;
+
+  return raw as T;
+}
+
+/** Sends queued offline writes straight to the API. */
+const replayOfflineQueue = (command: string, args: Record<string, unknown>) =>
+  safeInvoke(command, { ...args, __offline_replay: true });
+
+/** Replays writes queued while offline, e.g. from a "sync now" button. */
+export function syncOfflineQueue(): Promise<void> {
+  return replayOfflineWrites(replayOfflineQueue);
+}
+
 export async function safeInvoke<T>(command: string, args?: any): Promise<T> {
   try {
     const forceRemote = import.meta.env.VITE_USE_REMOTE_API === 'true';
@@ -468,153 +902,13 @@ export async function safeInvoke<T>(command: string, args?: any): Promise<T> {
       }
     }
 
-    const API_BASE = getApiBaseUrl();
     const route = commandMap[command];
 
     if (route) {
-      const toSnakeCase = (str: string) =>
-        str.replace(/[A-Z]/g, (letter) => `_${letter.toLowerCase()}`);
-      const toCamelCase = (str: string) =>
-        str.replace(/_([a-z])/g, (_m, c: string) => c.toUpperCase());
-
-      let path = route.path;
-      const queryParams: Record<string, string> = {};
-      const consumedPathKeys = new Set<string>();
-
-      if (args) {
-        for (const [key, value] of Object.entries(args)) {
-          if (key.startsWith('__')) continue;
-          if (value === null || value === undefined) continue;
-
-          const snakeKey = toSnakeCase(key);
-          if (path.includes(`:${key}`)) {
-            path = path.replace(`:${key}`, String(value));
-            consumedPathKeys.add(key);
-            consumedPathKeys.add(snakeKey);
-            consumedPathKeys.add(toCamelCase(key));
-          } else if (path.includes(`:${snakeKey}`)) {
-            path = path.replace(`:${snakeKey}`, String(value));
-            consumedPathKeys.add(key);
-            consumedPathKeys.add(snakeKey);
-            consumedPathKeys.add(toCamelCase(key));
-          } else if (route.method === 'GET' && key !== 'token') {
-            queryParams[snakeKey] = String(value);
-          }
-        }
-      }
-
-      const queryString =
-        Object.keys(queryParams).length > 0
-          ? '?' + new URLSearchParams(queryParams).toString()
-          : '';
-
-      const headers: Record<string, string> = {
-        'Content-Type': 'application/json',
-      };
-
-      const token =
-        args?.token || localStorage.getItem('auth_token') || sessionStorage.getItem('auth_token');
-      if (token) {
-        headers.Authorization = `Bearer ${token}`;
-      }
-
-      const controller = typeof AbortController !== 'undefined' ? new AbortController() : null;
-      const timeoutMs =
-        typeof args?.__timeout_ms === 'number' && Number.isFinite(args.__timeout_ms)
-          ? Math.max(1, args.__timeout_ms)
-          : 15000;
-      const externalSignal: AbortSignal | undefined = args?.__signal;
-      let abortedByExternalSignal = false;
-      let abortedByTimeout = false;
-
-      const onExternalAbort = () => {
-        abortedByExternalSignal = true;
-        controller?.abort();
-      };
-
-      if (externalSignal) {
-        if (externalSignal.aborted) onExternalAbort();
-        else externalSignal.addEventListener('abort', onExternalAbort, { once: true });
-      }
-
-      const timeout = setTimeout(() => {
-        abortedByTimeout = true;
-        controller?.abort();
-      }, timeoutMs);
-
-      let response: Response;
-      try {
-        const bodyPayload =
-          route.method !== 'GET'
-            ? (() => {
-                const rawEntries = Object.entries(args || {}).filter(
-                  ([key, value]) =>
-                    key !== 'token' &&
-                    !key.startsWith('__') &&
-                    value !== undefined &&
-                    !consumedPathKeys.has(key),
-                );
-
-                const keySet = new Set(rawEntries.map(([key]) => key));
-                const deduped = rawEntries.filter(([key]) => {
-                  if (!key.includes('_')) return true;
-                  const camelKey = toCamelCase(key);
-                  return !keySet.has(camelKey);
-                });
-
-                return Object.fromEntries(deduped);
-              })()
-            : undefined;
-
-        response = await fetch(`${API_BASE}${path}${queryString}`, {
-          method: route.method,
-          headers,
-          body: bodyPayload ? JSON.stringify(bodyPayload) : undefined,
-          signal: controller?.signal,
-        });
-      } catch (e: any) {
-        const name = String(e?.name || '');
-        if (name === 'AbortError') {
-          if (abortedByExternalSignal) throw new Error('Request canceled');
-          if (abortedByTimeout) {
-            throw new Error('Remote API request timed out. Is the server running?');
-          }
-          throw new Error('Request aborted');
-        }
-        throw e;
-      } finally {
-        clearTimeout(timeout);
-        if (externalSignal) externalSignal.removeEventListener('abort', onExternalAbort);
-      }
-
-      const contentType = response.headers.get('content-type') || '';
-      const isJson = contentType.toLowerCase().includes('application/json');
-
-      if (!response.ok) {
-        if (response.status === 401) {
-          handleAuthExpired(`HTTP ${response.status} from ${command}`);
-        }
-        if (isJson) {
-          const errorBody = await response.json().catch(() => ({}));
-          const message =
-            errorBody?.error ||
-            errorBody?.message ||
-            errorBody?.detail ||
-            errorBody?.details ||
-            `HTTP Error ${response.status}`;
-          throw new Error(message);
-        }
-        const errorText = (await response.text().catch(() => '')).trim();
-        throw new Error(errorText || `HTTP Error ${response.status}`);
-      }
-
-      if (response.status === 204) return undefined as T;
-
-      const raw = await response.text();
-      if (!raw || !raw.trim()) return undefined as T;
-      if (isJson) return JSON.parse(raw) as T;
-
-      return raw as T;
+      const request = () => invokeHttp<T>(command, route, args);
+      if (!looksLikeTauri || args?.__offline_replay) return await request();
+      startOfflineSync(replayOfflineQueue);
+      return await withOfflineSupport(command, args, request, replayOfflineQueue);
     }
 
     console.warn(`[Mock] Calling ${command} with`, args);
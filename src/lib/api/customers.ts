@@ -12,9 +12,14 @@ import type {
   CustomerRegistrationInviteSummary,
   CustomerRegistrationInviteView,
   CustomerSubscription,
+  CustomerSubscriptionComponent,
   CustomerSubscriptionView,
+  CustomerTagSummary,
+  CustomerUsage,
   IspPackage,
   PaginatedResponse,
+  TrashItem,
+  WorkOrderCompletionReport,
 } from './types';
 
 export const customers = {
@@ -72,6 +77,7 @@ export const customers = {
       phone?: string | null;
       notes?: string | null;
       is_active?: boolean;
+      expected_updated_at?: string | null;
     },
   ): Promise<Customer> =>
     safeInvoke('update_customer', {
@@ -81,6 +87,26 @@ export const customers = {
       ...dto,
     }),
 
+  tags: {
+    list: (): Promise<CustomerTagSummary[]> =>
+      safeInvoke('list_customer_tags', { token: getTokenOrThrow() }),
+
+    get: (customerId: string): Promise<string[]> =>
+      safeInvoke('get_customer_tags', {
+        token: getTokenOrThrow(),
+        customerId,
+        customer_id: customerId,
+      }),
+
+    set: (customerId: string, tags: string[]): Promise<string[]> =>
+      safeInvoke('set_customer_tags', {
+        token: getTokenOrThrow(),
+        customerId,
+        customer_id: customerId,
+        tags,
+      }),
+  },
+
   delete: (customerId: string): Promise<void> =>
     safeInvoke('delete_customer', {
       token: getTokenOrThrow(),
@@ -88,6 +114,16 @@ export const customers = {
       customer_id: customerId,
     }),
 
+  trash: (): Promise<TrashItem[]> =>
+    safeInvoke('list_deleted_customers', { token: getTokenOrThrow() }),
+
+  restore: (customerId: string): Promise<Customer> =>
+    safeInvoke('restore_customer', {
+      token: getTokenOrThrow(),
+      customerId,
+      customer_id: customerId,
+    }),
+
   invites: {
     list: (params?: {
       include_inactive?: boolean;
@@ -191,6 +227,23 @@ export const customers = {
       }),
   },
 
+  /** Signed work order completion reports, newest first. */
+  completionReports: (customerId: string): Promise<WorkOrderCompletionReport[]> =>
+    safeInvoke('list_customer_completion_reports', {
+      token: getTokenOrThrow(),
+      customerId,
+      customer_id: customerId,
+    }),
+
+  /** PPPoE traffic over the last `days` (default 30); portal users may read their own. */
+  usage: (customerId: string, days?: number): Promise<CustomerUsage> =>
+    safeInvoke('get_customer_usage', {
+      token: getTokenOrThrow(),
+      customerId,
+      customer_id: customerId,
+      days,
+    }),
+
   portalUsers: {
     list: (customerId: string): Promise<CustomerPortalUser[]> =>
       safeInvoke('list_customer_portal_users', {
@@ -281,6 +334,22 @@ export const customers = {
         subscriptionId,
         subscription_id: subscriptionId,
       }),
+    components: (subscriptionId: string): Promise<CustomerSubscriptionComponent[]> =>
+      safeInvoke('list_customer_subscription_components', {
+        token: getTokenOrThrow(),
+        subscriptionId,
+        subscription_id: subscriptionId,
+      }),
+    updateComponent: (
+      componentId: string,
+      dto: { status?: string; detail?: string | null },
+    ): Promise<CustomerSubscriptionComponent> =>
+      safeInvoke('update_customer_subscription_component', {
+        token: getTokenOrThrow(),
+        componentId,
+        component_id: componentId,
+        ...dto,
+      }),
   },
 
   portal: {
@@ -329,8 +398,13 @@ export const customers = {
         locationId,
         location_id: locationId,
       }),
-    myPackages: (): Promise<IspPackage[]> =>
-      safeInvoke('list_my_customer_packages', { token: getTokenOrThrow() }),
+    /** With a location, packages restricted to other service zones are left out. */
+    myPackages: (params?: { location_id?: string }): Promise<IspPackage[]> =>
+      safeInvoke('list_my_customer_packages', {
+        token: getTokenOrThrow(),
+        locationId: params?.location_id,
+        location_id: params?.location_id,
+      }),
     mySubscriptionStats: (): Promise<CustomerPortalSubscriptionStats> =>
       safeInvoke('get_my_customer_subscription_stats', { token: getTokenOrThrow() }),
     mySubscriptions: (params?: {
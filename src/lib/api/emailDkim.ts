@@ -0,0 +1,15 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type { EmailDkimStatus } from './types';
+
+export const emailDkim = {
+  get: (): Promise<EmailDkimStatus> => safeInvoke('get_email_dkim', { token: getTokenOrThrow() }),
+
+  /** Creates a new key (replacing the current one). Domain defaults to the sender address's. */
+  generate: (domain?: string, selector?: string): Promise<EmailDkimStatus> =>
+    safeInvoke('generate_email_dkim_key', { token: getTokenOrThrow(), domain, selector }),
+
+  setEnabled: (enabled: boolean): Promise<EmailDkimStatus> =>
+    safeInvoke('update_email_dkim', { token: getTokenOrThrow(), enabled }),
+
+  remove: (): Promise<void> => safeInvoke('delete_email_dkim_key', { token: getTokenOrThrow() }),
+};
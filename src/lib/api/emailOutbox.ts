@@ -27,6 +27,14 @@ export const emailOutbox = {
   retry: (id: string): Promise<void> =>
     safeInvoke('retry_email_outbox', { token: getTokenOrThrow(), id }),
 
+  /** Move a queued email to a new send time (ISO 8601). */
+  schedule: (id: string, scheduledAt: string): Promise<void> =>
+    safeInvoke('schedule_email_outbox', {
+      token: getTokenOrThrow(),
+      id,
+      scheduled_at: scheduledAt,
+    }),
+
   delete: (id: string): Promise<void> =>
     safeInvoke('delete_email_outbox', { token: getTokenOrThrow(), id }),
 
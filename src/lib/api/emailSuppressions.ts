@@ -0,0 +1,25 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type { EmailSuppression, EmailSuppressionReason, PaginatedResponse } from './types';
+
+export const emailSuppressions = {
+  list: (params?: {
+    page?: number;
+    perPage?: number;
+    reason?: EmailSuppressionReason | 'all';
+    search?: string;
+  }): Promise<PaginatedResponse<EmailSuppression>> =>
+    safeInvoke('list_email_suppressions', {
+      token: getTokenOrThrow(),
+      page: params?.page,
+      per_page: params?.perPage,
+      reason: params?.reason,
+      search: params?.search,
+    }),
+
+  add: (email: string, detail?: string): Promise<EmailSuppression> =>
+    safeInvoke('add_email_suppression', { token: getTokenOrThrow(), email, detail }),
+
+  /** Lifts the suppression so the address receives mail again. */
+  remove: (id: string): Promise<void> =>
+    safeInvoke('remove_email_suppression', { token: getTokenOrThrow(), id }),
+};
@@ -0,0 +1,37 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type { EmailTemplate, EmailTemplateDraft, EmailTemplateVariable, RenderedEmail } from './types';
+
+export const emailTemplates = {
+  list: (): Promise<EmailTemplate[]> =>
+    safeInvoke('list_email_templates', { token: getTokenOrThrow() }),
+
+  get: (code: string): Promise<EmailTemplate> =>
+    safeInvoke('get_email_template', { token: getTokenOrThrow(), code }),
+
+  variables: (code: string): Promise<EmailTemplateVariable[]> =>
+    safeInvoke('list_email_template_variables', { token: getTokenOrThrow(), code }),
+
+  update: (
+    code: string,
+    subject: string,
+    bodyHtml: string,
+    bodyText?: string | null,
+  ): Promise<EmailTemplate> =>
+    safeInvoke('update_email_template', {
+      token: getTokenOrThrow(),
+      code,
+      subject,
+      body_html: bodyHtml,
+      body_text: bodyText ?? undefined,
+    }),
+
+  reset: (code: string): Promise<EmailTemplate> =>
+    safeInvoke('reset_email_template', { token: getTokenOrThrow(), code }),
+
+  preview: (code: string, draft: EmailTemplateDraft = {}): Promise<RenderedEmail> =>
+    safeInvoke('preview_email_template', { token: getTokenOrThrow(), code, ...draft }),
+
+  /** Sends the rendered sample to `to`, or to the caller when omitted. */
+  testSend: (code: string, to?: string, draft: EmailTemplateDraft = {}): Promise<RenderedEmail> =>
+    safeInvoke('test_send_email_template', { token: getTokenOrThrow(), code, to, ...draft }),
+};
@@ -0,0 +1,26 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type { FeatureFlag, FeatureFlagOverride, FeatureFlagWithOverrides } from './types';
+
+export const featureFlags = {
+  /** Every flag evaluated for the current tenant (`key -> on`). */
+  evaluate: (): Promise<Record<string, boolean>> =>
+    safeInvoke('evaluate_feature_flags', { token: getTokenOrThrow() }),
+
+  list: (): Promise<FeatureFlagWithOverrides[]> =>
+    safeInvoke('list_feature_flags', { token: getTokenOrThrow() }),
+
+  upsert: (
+    key: string,
+    flag: { description?: string | null; enabled: boolean; rolloutPercentage: number },
+  ): Promise<FeatureFlag> =>
+    safeInvoke('upsert_feature_flag', { token: getTokenOrThrow(), key, ...flag }),
+
+  delete: (key: string): Promise<void> =>
+    safeInvoke('delete_feature_flag', { token: getTokenOrThrow(), key }),
+
+  setOverride: (key: string, tenantId: string, enabled: boolean): Promise<FeatureFlagOverride> =>
+    safeInvoke('set_feature_flag_override', { token: getTokenOrThrow(), key, tenantId, enabled }),
+
+  removeOverride: (key: string, tenantId: string): Promise<void> =>
+    safeInvoke('remove_feature_flag_override', { token: getTokenOrThrow(), key, tenantId }),
+};
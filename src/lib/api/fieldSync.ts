@@ -0,0 +1,17 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type {
+  FieldSyncChanges,
+  FieldSyncMutation,
+  FieldSyncPullRequest,
+  FieldSyncPushResponse,
+} from './types';
+
+export const fieldSync = {
+  /** Keep pulling while `has_more` is set, passing `next_cursor` back. */
+  pull: (dto: FieldSyncPullRequest = {}): Promise<FieldSyncChanges> =>
+    safeInvoke('field_sync_pull', { token: getTokenOrThrow(), ...dto }),
+
+  /** Mutations are applied in order; results come back in the same order. */
+  push: (mutations: FieldSyncMutation[]): Promise<FieldSyncPushResponse> =>
+    safeInvoke('field_sync_push', { token: getTokenOrThrow(), mutations }),
+};
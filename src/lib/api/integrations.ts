@@ -0,0 +1,61 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type {
+  ApiKeyRateLimit,
+  CreateApiKeyRequest,
+  CreateWebhookRequest,
+  CreatedApiKey,
+  CreatedWebhook,
+  IntegrationsOverview,
+  TenantApiKey,
+  TenantWebhook,
+  UpdateWebhookRequest,
+  WebhookDelivery,
+  WebhookDeliveryQuery,
+} from './types';
+
+export const integrations = {
+  /** Keys, webhooks, the latest deliveries and rate-limit use in one call. */
+  overview: (): Promise<IntegrationsOverview> =>
+    safeInvoke('get_integrations_overview', { token: getTokenOrThrow() }),
+
+  apiKeys: {
+    list: (): Promise<TenantApiKey[]> => safeInvoke('list_api_keys', { token: getTokenOrThrow() }),
+
+    /** The returned `token` is shown this once. */
+    create: (dto: CreateApiKeyRequest): Promise<CreatedApiKey> =>
+      safeInvoke('create_api_key', { token: getTokenOrThrow(), ...dto }),
+
+    revoke: (id: string): Promise<TenantApiKey> =>
+      safeInvoke('revoke_api_key', { token: getTokenOrThrow(), id }),
+  },
+
+  webhooks: {
+    list: (): Promise<TenantWebhook[]> =>
+      safeInvoke('list_tenant_webhooks', { token: getTokenOrThrow() }),
+
+    /** The returned `secret` is shown this once. */
+    create: (dto: CreateWebhookRequest): Promise<CreatedWebhook> =>
+      safeInvoke('create_tenant_webhook', { token: getTokenOrThrow(), ...dto }),
+
+    update: (id: string, dto: UpdateWebhookRequest): Promise<TenantWebhook> =>
+      safeInvoke('update_tenant_webhook', { token: getTokenOrThrow(), id, ...dto }),
+
+    delete: (id: string): Promise<void> =>
+      safeInvoke('delete_tenant_webhook', { token: getTokenOrThrow(), id }),
+
+    /** Queues a signed `webhook.test` event for this webhook; see the delivery log. */
+    test: (id: string): Promise<WebhookDelivery> =>
+      safeInvoke('test_tenant_webhook', { token: getTokenOrThrow(), id }),
+  },
+
+  deliveries: {
+    list: (q: WebhookDeliveryQuery = {}): Promise<WebhookDelivery[]> =>
+      safeInvoke('list_webhook_deliveries', { token: getTokenOrThrow(), ...q }),
+
+    redeliver: (id: string): Promise<WebhookDelivery> =>
+      safeInvoke('redeliver_webhook_delivery', { token: getTokenOrThrow(), id }),
+  },
+
+  rateLimits: (): Promise<ApiKeyRateLimit[]> =>
+    safeInvoke('get_api_key_rate_limits', { token: getTokenOrThrow() }),
+};
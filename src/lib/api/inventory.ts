@@ -0,0 +1,74 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type {
+  CreateInventoryMovementRequest,
+  InventoryItem,
+  InventoryLocation,
+  InventoryMovement,
+  InventoryScanResult,
+  InventorySerial,
+  InventorySerialBatchResult,
+  InventoryStockLevel,
+  ReceiveInventorySerialsRequest,
+  TransferInventorySerialsRequest,
+  UpsertInventoryItemRequest,
+  UpsertInventoryLocationRequest,
+} from './types';
+
+export const inventory = {
+  items: {
+    list: (params?: { include_inactive?: boolean }): Promise<InventoryItem[]> =>
+      safeInvoke('list_inventory_items', { token: getTokenOrThrow(), ...(params || {}) }),
+
+    create: (dto: UpsertInventoryItemRequest): Promise<InventoryItem> =>
+      safeInvoke('create_inventory_item', { token: getTokenOrThrow(), ...dto }),
+
+    update: (id: string, dto: UpsertInventoryItemRequest): Promise<InventoryItem> =>
+      safeInvoke('update_inventory_item', { token: getTokenOrThrow(), id, ...dto }),
+  },
+
+  locations: {
+    list: (): Promise<InventoryLocation[]> =>
+      safeInvoke('list_inventory_locations', { token: getTokenOrThrow() }),
+
+    create: (dto: UpsertInventoryLocationRequest): Promise<InventoryLocation> =>
+      safeInvoke('create_inventory_location', { token: getTokenOrThrow(), ...dto }),
+
+    update: (id: string, dto: UpsertInventoryLocationRequest): Promise<InventoryLocation> =>
+      safeInvoke('update_inventory_location', { token: getTokenOrThrow(), id, ...dto }),
+  },
+
+  /** Technicians without inventory:read only get their own location. */
+  stock: (params?: { location_id?: string; item_id?: string }): Promise<InventoryStockLevel[]> =>
+    safeInvoke('list_inventory_stock', { token: getTokenOrThrow(), ...(params || {}) }),
+
+  movements: {
+    list: (params?: {
+      item_id?: string;
+      location_id?: string;
+      limit?: number;
+    }): Promise<InventoryMovement[]> =>
+      safeInvoke('list_inventory_movements', { token: getTokenOrThrow(), ...(params || {}) }),
+
+    create: (dto: CreateInventoryMovementRequest): Promise<InventoryMovement> =>
+      safeInvoke('create_inventory_movement', { token: getTokenOrThrow(), ...dto }),
+  },
+
+  /** Resolve a scanned barcode to a registered serial or an item SKU. */
+  scan: (code: string): Promise<InventoryScanResult> =>
+    safeInvoke('resolve_inventory_scan', { token: getTokenOrThrow(), code }),
+
+  serials: {
+    list: (params?: {
+      item_id?: string;
+      location_id?: string;
+      status?: InventorySerial['status'];
+    }): Promise<InventorySerial[]> =>
+      safeInvoke('list_inventory_serials', { token: getTokenOrThrow(), ...(params || {}) }),
+
+    receive: (dto: ReceiveInventorySerialsRequest): Promise<InventorySerialBatchResult> =>
+      safeInvoke('receive_inventory_serials', { token: getTokenOrThrow(), ...dto }),
+
+    transfer: (dto: TransferInventorySerialsRequest): Promise<InventorySerialBatchResult> =>
+      safeInvoke('transfer_inventory_serials', { token: getTokenOrThrow(), ...dto }),
+  },
+};
@@ -0,0 +1,21 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type { IpamPlan, IpamSubnet, UpsertIpamSubnetRequest } from './types';
+
+export const ipam = {
+  subnets: {
+    list: (): Promise<IpamSubnet[]> =>
+      safeInvoke('list_ipam_subnets', { token: getTokenOrThrow() }),
+
+    create: (dto: UpsertIpamSubnetRequest): Promise<IpamSubnet> =>
+      safeInvoke('create_ipam_subnet', { token: getTokenOrThrow(), ...dto }),
+
+    update: (id: string, dto: UpsertIpamSubnetRequest): Promise<IpamSubnet> =>
+      safeInvoke('update_ipam_subnet', { token: getTokenOrThrow(), id, ...dto }),
+
+    delete: (id: string): Promise<void> =>
+      safeInvoke('delete_ipam_subnet', { token: getTokenOrThrow(), id }),
+  },
+
+  /** Subnet tree with utilization and heatmap cells for the planner. */
+  plan: (): Promise<IpamPlan> => safeInvoke('get_ipam_plan', { token: getTokenOrThrow() }),
+};
@@ -1,5 +1,13 @@
 import { getTokenOrThrow, safeInvoke } from './core';
-import type { IspPackage, IspPackageRouterMappingView, PaginatedResponse } from './types';
+import type {
+  BandwidthBurst,
+  IspPackage,
+  IspPackageBandwidth,
+  IspPackagePriceHistory,
+  IspPackageRouterMappingView,
+  PaginatedResponse,
+  TrashItem,
+} from './types';
 
 export const ispPackages = {
   packages: {
@@ -18,8 +26,11 @@ export const ispPackages = {
       description?: string | null;
       features?: string[];
       is_active?: boolean;
+      zone_restricted?: boolean;
       price_monthly?: number;
       price_yearly?: number;
+      bandwidth?: IspPackageBandwidth;
+      components?: string[];
     }): Promise<IspPackage> =>
       safeInvoke('create_isp_package', {
         token: getTokenOrThrow(),
@@ -28,8 +39,11 @@ export const ispPackages = {
         description: dto.description ?? null,
         features: dto.features ?? [],
         is_active: dto.is_active ?? true,
+        zone_restricted: dto.zone_restricted ?? false,
         price_monthly: dto.price_monthly ?? 0,
         price_yearly: dto.price_yearly ?? 0,
+        bandwidth: dto.bandwidth ?? null,
+        components: dto.components ?? null,
       }),
 
     update: (
@@ -40,8 +54,11 @@ export const ispPackages = {
         description?: string | null;
         features?: string[];
         is_active?: boolean;
+        zone_restricted?: boolean;
         price_monthly?: number;
         price_yearly?: number;
+        bandwidth?: IspPackageBandwidth;
+        components?: string[];
       },
     ): Promise<IspPackage> =>
       safeInvoke('update_isp_package', {
@@ -52,12 +69,24 @@ export const ispPackages = {
         description: dto.description ?? undefined,
         features: dto.features,
         is_active: dto.is_active,
+        zone_restricted: dto.zone_restricted,
         price_monthly: dto.price_monthly,
         price_yearly: dto.price_yearly,
+        bandwidth: dto.bandwidth,
+        components: dto.components,
       }),
 
     delete: (id: string): Promise<void> =>
       safeInvoke('delete_isp_package', { token: getTokenOrThrow(), id }),
+
+    trash: (): Promise<TrashItem[]> =>
+      safeInvoke('list_deleted_isp_packages', { token: getTokenOrThrow() }),
+
+    restore: (id: string): Promise<void> =>
+      safeInvoke('restore_isp_package', { token: getTokenOrThrow(), id }),
+
+    priceHistory: (id: string): Promise<IspPackagePriceHistory[]> =>
+      safeInvoke('list_isp_package_price_history', { token: getTokenOrThrow(), id }),
   },
 
   routerMappings: {
@@ -72,6 +101,7 @@ export const ispPackages = {
       package_id: string;
       router_profile_name: string;
       address_pool?: string | null;
+      burst?: Partial<BandwidthBurst> | null;
     }): Promise<any> =>
       safeInvoke('upsert_isp_package_router_mapping', {
         token: getTokenOrThrow(),
@@ -79,6 +109,7 @@ export const ispPackages = {
         package_id: dto.package_id,
         router_profile_name: dto.router_profile_name,
         address_pool: dto.address_pool ?? null,
+        burst: dto.burst ?? undefined,
       }),
   },
 };
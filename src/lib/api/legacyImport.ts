@@ -0,0 +1,11 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type { LegacyImportReport, LegacyImportRequest, LegacyImportSource } from './types';
+
+export const legacyImport = {
+  sources: (): Promise<LegacyImportSource[]> =>
+    safeInvoke('list_legacy_import_sources', { token: getTokenOrThrow() }),
+
+  /** Validation report for the exports; with `commit` and no errors, also writes them. */
+  run: (dto: LegacyImportRequest): Promise<LegacyImportReport> =>
+    safeInvoke('run_legacy_import', { token: getTokenOrThrow(), ...dto }),
+};
@@ -1,5 +1,13 @@
 import { getTokenOrThrow, safeInvoke } from './core';
-import type { PaginatedResponse } from './types';
+import type {
+  CreateMonitoredDeviceRequest,
+  MikrotikIncidentImpact,
+  MonitoredDevice,
+  MonitoredDeviceTestResult,
+  PaginatedResponse,
+  TrashItem,
+  UpdateMonitoredDeviceRequest,
+} from './types';
 
 export const mikrotik = {
   routers: {
@@ -53,6 +61,8 @@ export const mikrotik = {
         maintenance_reason?: string | null;
         latitude?: number | null;
         longitude?: number | null;
+        /** The router's `config_updated_at` as last read; a newer edit fails with 409. */
+        expected_updated_at?: string | null;
       },
     ): Promise<any> =>
       safeInvoke('update_mikrotik_router', {
@@ -72,9 +82,15 @@ export const mikrotik = {
         maintenanceReason: router.maintenance_reason ?? null,
         latitude: router.latitude ?? null,
         longitude: router.longitude ?? null,
+        expected_updated_at: router.expected_updated_at ?? null,
+        expectedUpdatedAt: router.expected_updated_at ?? null,
       }),
     delete: (id: string): Promise<void> =>
       safeInvoke('delete_mikrotik_router', { token: getTokenOrThrow(), id }),
+    trash: (): Promise<TrashItem[]> =>
+      safeInvoke('list_deleted_mikrotik_routers', { token: getTokenOrThrow() }),
+    restore: (id: string): Promise<any> =>
+      safeInvoke('restore_mikrotik_router', { token: getTokenOrThrow(), id }),
     test: (id: string): Promise<any> =>
       safeInvoke('test_mikrotik_router', { token: getTokenOrThrow(), id }),
     metrics: (routerId: string, limit?: number): Promise<any[]> =>
@@ -133,6 +149,27 @@ export const mikrotik = {
         router_id: routerId,
       }),
   },
+  /** OLTs, switches and other gear polled over SNMP, next to the routers. */
+  devices: {
+    list: (): Promise<MonitoredDevice[]> =>
+      safeInvoke('list_monitored_devices', { token: getTokenOrThrow() }),
+    get: (id: string): Promise<MonitoredDevice> =>
+      safeInvoke('get_monitored_device', { token: getTokenOrThrow(), id }),
+    create: (dto: CreateMonitoredDeviceRequest): Promise<MonitoredDevice> =>
+      safeInvoke('create_monitored_device', { token: getTokenOrThrow(), ...dto }),
+    /** Sends `maintenance_until` every time; null ends the maintenance. */
+    update: (id: string, dto: UpdateMonitoredDeviceRequest): Promise<MonitoredDevice> =>
+      safeInvoke('update_monitored_device', {
+        token: getTokenOrThrow(),
+        id,
+        ...dto,
+        maintenance_until: dto.maintenance_until ?? null,
+      }),
+    delete: (id: string): Promise<void> =>
+      safeInvoke('delete_monitored_device', { token: getTokenOrThrow(), id }),
+    test: (id: string): Promise<MonitoredDeviceTestResult> =>
+      safeInvoke('test_monitored_device', { token: getTokenOrThrow(), id }),
+  },
   alerts: {
     list: (params?: { activeOnly?: boolean; limit?: number }): Promise<any[]> =>
       safeInvoke('list_mikrotik_alerts', {
@@ -147,13 +184,24 @@ export const mikrotik = {
       safeInvoke('resolve_mikrotik_alert', { token: getTokenOrThrow(), id }),
   },
   incidents: {
-    list: (params?: { activeOnly?: boolean; limit?: number }): Promise<any[]> =>
+    list: (params?: {
+      activeOnly?: boolean;
+      limit?: number;
+      /** `impact`: most affected subscriptions first. */
+      sort?: 'impact';
+    }): Promise<any[]> =>
       safeInvoke('list_mikrotik_incidents', {
         token: getTokenOrThrow(),
         active_only: params?.activeOnly,
         activeOnly: params?.activeOnly,
         limit: params?.limit,
+        sort: params?.sort,
       }),
+    /** Subscriptions recorded as affected when the incident opened. */
+    impact: (id: string): Promise<MikrotikIncidentImpact[]> =>
+      safeInvoke('get_mikrotik_incident_impact', { token: getTokenOrThrow(), id }),
+    refreshImpact: (id: string): Promise<any> =>
+      safeInvoke('refresh_mikrotik_incident_impact', { token: getTokenOrThrow(), id }),
     ack: (id: string): Promise<any> =>
       safeInvoke('ack_mikrotik_incident', { token: getTokenOrThrow(), id }),
     resolve: (id: string): Promise<any> =>
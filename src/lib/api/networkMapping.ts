@@ -1,5 +1,12 @@
 import { getTokenOrThrow, safeInvoke } from './core';
-import type { PaginatedResponse } from './types';
+import type {
+  AssetMapFeatureCollection,
+  CreateFiberPointRequest,
+  FiberPoint,
+  FiberPointKind,
+  PaginatedResponse,
+  UpdateFiberPointRequest,
+} from './types';
 
 type JsonGeometry = {
   type: string;
@@ -158,4 +165,24 @@ export const networkMapping = {
     listCustomers: (params?: { node_id?: string; link_id?: string; router_id?: string }): Promise<any> =>
       safeInvoke('list_network_impacted_customers', { token: getTokenOrThrow(), ...(params || {}) }),
   },
+  fiberPoints: {
+    list: (params?: { kind?: FiberPointKind; bbox?: string }): Promise<FiberPoint[]> =>
+      safeInvoke('list_fiber_points', { token: getTokenOrThrow(), ...(params || {}) }),
+    create: (dto: CreateFiberPointRequest): Promise<FiberPoint> =>
+      safeInvoke('create_fiber_point', { token: getTokenOrThrow(), ...dto }),
+    update: (id: string, dto: UpdateFiberPointRequest): Promise<FiberPoint> =>
+      safeInvoke('update_fiber_point', { token: getTokenOrThrow(), id, ...dto }),
+    delete: (id: string): Promise<void> =>
+      safeInvoke('delete_fiber_point', { token: getTokenOrThrow(), id }),
+  },
+  /** Routers, customer locations, ODC/ODP points and links as GeoJSON. */
+  map: (
+    params?: { bbox?: string },
+    options?: { signal?: AbortSignal },
+  ): Promise<AssetMapFeatureCollection> =>
+    safeInvoke('get_network_asset_map', {
+      token: getTokenOrThrow(),
+      ...(params || {}),
+      __signal: options?.signal,
+    }),
 };
@@ -0,0 +1,44 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type {
+  NotificationRoutingEvaluation,
+  NotificationRoutingRule,
+  NotificationRoutingRuleInput,
+  NotificationSeverity,
+} from './types';
+
+const ruleArgs = (rule: NotificationRoutingRuleInput) => ({
+  name: rule.name,
+  priority: rule.priority,
+  is_enabled: rule.isEnabled,
+  category: rule.category ?? undefined,
+  min_severity: rule.minSeverity,
+  time_window: rule.timeWindow,
+  channels: rule.channels,
+  recipients: rule.recipients,
+});
+
+export const notificationRouting = {
+  list: (): Promise<NotificationRoutingRule[]> =>
+    safeInvoke('list_notification_rules', { token: getTokenOrThrow() }),
+
+  create: (rule: NotificationRoutingRuleInput): Promise<NotificationRoutingRule> =>
+    safeInvoke('create_notification_rule', { token: getTokenOrThrow(), ...ruleArgs(rule) }),
+
+  update: (id: string, rule: NotificationRoutingRuleInput): Promise<NotificationRoutingRule> =>
+    safeInvoke('update_notification_rule', { token: getTokenOrThrow(), id, ...ruleArgs(rule) }),
+
+  delete: (id: string): Promise<void> =>
+    safeInvoke('delete_notification_rule', { token: getTokenOrThrow(), id }),
+
+  evaluate: (
+    category: string,
+    severity: NotificationSeverity,
+    at?: string,
+  ): Promise<NotificationRoutingEvaluation> =>
+    safeInvoke('evaluate_notification_routing', {
+      token: getTokenOrThrow(),
+      category,
+      severity,
+      at,
+    }),
+};
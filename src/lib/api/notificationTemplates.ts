@@ -0,0 +1,24 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type { NotificationTemplate, RenderedNotification } from './types';
+
+export const notificationTemplates = {
+  list: (): Promise<NotificationTemplate[]> =>
+    safeInvoke('list_notification_templates', { token: getTokenOrThrow() }),
+
+  update: (code: string, title: string, body: string): Promise<NotificationTemplate> =>
+    safeInvoke('update_notification_template', { token: getTokenOrThrow(), code, title, body }),
+
+  reset: (code: string): Promise<NotificationTemplate> =>
+    safeInvoke('reset_notification_template', { token: getTokenOrThrow(), code }),
+
+  preview: (
+    code: string,
+    draft?: { title?: string; body?: string },
+  ): Promise<RenderedNotification> =>
+    safeInvoke('preview_notification_template', {
+      token: getTokenOrThrow(),
+      code,
+      title: draft?.title,
+      body: draft?.body,
+    }),
+};
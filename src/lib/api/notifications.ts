@@ -1,9 +1,33 @@
 import { getTokenOrThrow, safeInvoke } from './core';
-import type { Notification, NotificationPreference, PaginatedResponse } from './types';
+import type {
+  DesktopNotificationPrefs,
+  Notification,
+  NotificationDeliveryFilter,
+  NotificationDeliveryReport,
+  NotificationFilter,
+  NotificationPreference,
+  NotificationQuietHours,
+  PaginatedResponse,
+} from './types';
 
 export const notifications = {
-  list: (page?: number, perPage?: number): Promise<PaginatedResponse<Notification>> =>
-    safeInvoke('list_notifications', { token: getTokenOrThrow(), page, perPage }),
+  list: (
+    page?: number,
+    perPage?: number,
+    filter: NotificationFilter = {},
+  ): Promise<PaginatedResponse<Notification>> =>
+    safeInvoke('list_notifications', {
+      token: getTokenOrThrow(),
+      page,
+      perPage,
+      category: filter.category,
+      notification_type: filter.notificationType,
+      is_read: filter.isRead,
+      archived: filter.archived,
+      from: filter.from,
+      to: filter.to,
+      search: filter.search,
+    }),
 
   getUnreadCount: (): Promise<{ count: number }> =>
     safeInvoke('get_unread_count', { token: getTokenOrThrow() }),
@@ -13,6 +37,9 @@ export const notifications = {
 
   markAllAsRead: (): Promise<void> => safeInvoke('mark_all_as_read', { token: getTokenOrThrow() }),
 
+  archive: (ids: string[], archived: boolean = true): Promise<{ count: number }> =>
+    safeInvoke('archive_notifications', { token: getTokenOrThrow(), ids, archived }),
+
   delete: (id: string): Promise<void> =>
     safeInvoke('delete_notification', { token: getTokenOrThrow(), id }),
 
@@ -22,11 +49,72 @@ export const notifications = {
   updatePreference: (channel: string, category: string, enabled: boolean): Promise<void> =>
     safeInvoke('update_preference', { token: getTokenOrThrow(), channel, category, enabled }),
 
-  subscribePush: (endpoint: string, p256dh: string, auth: string): Promise<void> =>
-    safeInvoke('subscribe_push', { token: getTokenOrThrow(), endpoint, p256dh, auth }),
+  getQuietHours: (): Promise<NotificationQuietHours> =>
+    safeInvoke('get_quiet_hours', { token: getTokenOrThrow() }),
+
+  updateQuietHours: (
+    enabled: boolean,
+    start: string,
+    end: string,
+    timezone?: string | null,
+  ): Promise<NotificationQuietHours> =>
+    safeInvoke('update_quiet_hours', {
+      token: getTokenOrThrow(),
+      enabled,
+      start,
+      end,
+      timezone: timezone ?? undefined,
+    }),
+
+  deliveryReport: (
+    page?: number,
+    perPage?: number,
+    filter: NotificationDeliveryFilter = {},
+  ): Promise<NotificationDeliveryReport> =>
+    safeInvoke('get_notification_delivery_report', {
+      token: getTokenOrThrow(),
+      page,
+      perPage,
+      channel: filter.channel,
+      status: filter.status,
+      category: filter.category,
+      user_id: filter.userId,
+      notification_id: filter.notificationId,
+      from: filter.from,
+      to: filter.to,
+    }),
+
+  getVapidPublicKey: (): Promise<{ public_key: string }> =>
+    safeInvoke('get_vapid_public_key', { token: getTokenOrThrow() }),
+
+  subscribePush: (
+    endpoint: string,
+    p256dh: string,
+    auth: string,
+    expirationTime?: number | null,
+  ): Promise<void> =>
+    safeInvoke('subscribe_push', {
+      token: getTokenOrThrow(),
+      endpoint,
+      p256dh,
+      auth,
+      expiration_time: expirationTime ?? undefined,
+    }),
 
   unsubscribePush: (endpoint: string): Promise<void> =>
     safeInvoke('unsubscribe_push', { token: getTokenOrThrow(), endpoint }),
 
   sendTest: (): Promise<void> => safeInvoke('send_test', { token: getTokenOrThrow() }),
+
+  /** OS notifications shown by the desktop app itself (desktop only). */
+  desktop: {
+    /** Pass `null` on sign-out. */
+    setSession: (token: string | null): Promise<void> =>
+      safeInvoke('set_desktop_notification_session', { token }),
+
+    getPrefs: (): Promise<DesktopNotificationPrefs> => safeInvoke('get_desktop_notification_prefs'),
+
+    updatePrefs: (prefs: DesktopNotificationPrefs): Promise<DesktopNotificationPrefs> =>
+      safeInvoke('update_desktop_notification_prefs', { prefs }),
+  },
 };
@@ -0,0 +1,129 @@
+import { beforeEach, describe, expect, it, vi } from 'vitest';
+import { get } from 'svelte/store';
+
+class MemoryStorage implements Storage {
+  private map = new Map<string, string>();
+
+  get length(): number {
+    return this.map.size;
+  }
+
+  clear(): void {
+    this.map.clear();
+  }
+
+  getItem(key: string): string | null {
+    return this.map.has(key) ? this.map.get(key)! : null;
+  }
+
+  key(index: number): string | null {
+    return Array.from(this.map.keys())[index] ?? null;
+  }
+
+  removeItem(key: string): void {
+    this.map.delete(key);
+  }
+
+  setItem(key: string, value: string): void {
+    this.map.set(key, String(value));
+  }
+}
+
+type Send = (command: string, args: Record<string, unknown>) => Promise<unknown>;
+
+async function loadOffline() {
+  vi.resetModules();
+  const local = new MemoryStorage();
+  local.setItem('auth_user', JSON.stringify({ id: 'user-1' }));
+  local.setItem('active_tenant_slug', 'acme');
+  vi.stubGlobal('localStorage', local);
+  vi.stubGlobal('sessionStorage', new MemoryStorage());
+  return import('./offline');
+}
+
+describe('offline support', () => {
+  beforeEach(() => {
+    vi.unstubAllGlobals();
+  });
+
+  it('serves cached reads while the API is unreachable', async () => {
+    const { OfflineError, withOfflineSupport } = await loadOffline();
+    const send = vi.fn();
+    const customers = [{ id: 'c1', name: 'Budi' }];
+
+    await withOfflineSupport('list_customers', { page: 1 }, async () => customers, send);
+    const offline = async () => {
+      throw new OfflineError('Failed to fetch');
+    };
+
+    const cached = await withOfflineSupport('list_customers', { page: 1 }, offline, send);
+    expect(cached).toEqual(customers);
+    const uncached = withOfflineSupport('list_customers', { page: 2 }, offline, send);
+    await expect(uncached).rejects.toThrow('Failed to fetch');
+  });
+
+  it('queues writes made offline and replays them in order with idempotency keys', async () => {
+    const offlineModule = await loadOffline();
+    const { OfflineError, QueuedOfflineError, offlineSync, replayOfflineWrites } = offlineModule;
+    const { withOfflineSupport } = offlineModule;
+    const offline = async () => {
+      throw new OfflineError('Failed to fetch');
+    };
+    const send = vi.fn<Send>(async () => ({}));
+
+    await expect(
+      withOfflineSupport('create_support_ticket', { subject: 'No signal' }, offline, send),
+    ).rejects.toBeInstanceOf(QueuedOfflineError);
+    const reply = { id: 't1', message: 'Still down' };
+    await expect(
+      withOfflineSupport('reply_support_ticket', reply, offline, send),
+    ).rejects.toBeInstanceOf(QueuedOfflineError);
+    expect(get(offlineSync)).toMatchObject({ online: false, pending: 2 });
+
+    await replayOfflineWrites(send);
+
+    expect(send.mock.calls.map(([command]) => command)).toEqual([
+      'create_support_ticket',
+      'reply_support_ticket',
+    ]);
+    const keys = send.mock.calls.map(([, args]) => args.__idempotency_key);
+    expect(new Set(keys).size).toBe(2);
+    expect(get(offlineSync)).toMatchObject({ online: true, pending: 0, conflicts: [] });
+  });
+
+  it('turns rejected writes and concurrent edits into conflicts', async () => {
+    const { OfflineError, offlineSync, replayOfflineWrites, withOfflineSupport, discardConflict } =
+      await loadOffline();
+    const offline = async () => {
+      throw new OfflineError('Failed to fetch');
+    };
+    const noop = vi.fn();
+
+    await withOfflineSupport(
+      'get_customer',
+      { customerId: 'c1' },
+      async () => ({ id: 'c1', updated_at: '2026-01-01T00:00:00Z' }),
+      noop,
+    );
+    const edit = { customerId: 'c1', name: 'Budi' };
+    await withOfflineSupport('update_customer', edit, offline, noop).catch(() => {});
+    await withOfflineSupport('create_customer', { name: '' }, offline, noop).catch(() => {});
+
+    const send = vi.fn<Send>(async (command) => {
+      if (command === 'get_customer') return { id: 'c1', updated_at: '2026-01-02T00:00:00Z' };
+      throw Object.assign(new Error('Name is required'), { status: 400 });
+    });
+    await replayOfflineWrites(send);
+
+    const { pending, conflicts } = get(offlineSync);
+    expect(pending).toBe(0);
+    expect(conflicts.map((c) => [c.command, c.status])).toEqual([
+      ['update_customer', 409],
+      ['create_customer', 400],
+    ]);
+    expect(send).not.toHaveBeenCalledWith('update_customer', expect.anything());
+
+    discardConflict(conflicts[0].id);
+    expect(get(offlineSync).conflicts).toHaveLength(1);
+  });
+});
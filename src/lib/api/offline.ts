@@ -0,0 +1,409 @@
+import { writable } from 'svelte/store';
+
+/**
+ * Offline support for the desktop client talking to a remote API.
+ *
+ * Reads of customers, tickets and work orders are kept in a local cache and
+ * served from it when the API can't be reached. Writes to them made while
+ * offline go into a queue that is replayed in order once the API answers
+ * again, each with its queue id as `Idempotency-Key` so a replay that is cut
+ * off half way never applies twice. Writes the server refuses on replay, and
+ * edits to records changed elsewhere in the meantime, become conflicts for
+ * the user to apply anyway or discard.
+ */
+
+const READ_CACHE_KEY = 'offline_read_cache';
+const WRITE_QUEUE_KEY = 'offline_write_queue';
+const CONFLICTS_KEY = 'offline_sync_conflicts';
+const MAX_CACHED_READS = 200;
+const RETRY_INTERVAL_MS = 30_000;
+
+const CACHED_READS = new Set([
+  'list_customers',
+  'get_customer',
+  'list_customer_locations',
+  'list_support_tickets',
+  'get_support_ticket',
+  'list_installation_work_orders',
+  'get_work_order_agenda',
+  'get_work_order_checklist',
+]);
+
+interface VersionCheck {
+  /** Read that returns the record's current state. */
+  command: string;
+  idArg: string;
+  updatedAt: (record: any) => string | undefined;
+}
+
+/** `check` spots records edited elsewhere while the write sat in the queue. */
+const QUEUED_WRITES: Record<string, { check?: VersionCheck }> = {
+  create_customer: {},
+  update_customer: {
+    check: { command: 'get_customer', idArg: 'customerId', updatedAt: (c) => c?.updated_at },
+  },
+  create_customer_location: {},
+  update_customer_location: {},
+  create_support_ticket: {},
+  reply_support_ticket: {},
+  update_support_ticket: {
+    check: { command: 'get_support_ticket', idArg: 'id', updatedAt: (d) => d?.ticket?.updated_at },
+  },
+  create_work_order: {},
+  start_installation_work_order: {},
+  hold_work_order: {},
+  complete_installation_work_order: {},
+  check_in_work_order: {},
+  check_out_work_order: {},
+  update_work_order_checklist_item: {},
+};
+
+export interface QueuedWrite {
+  id: string;
+  command: string;
+  args: Record<string, unknown>;
+  userId: string;
+  queuedAt: string;
+  /** `updated_at` of the record when it was edited offline, if it was cached. */
+  baseUpdatedAt?: string;
+  attempts: number;
+  lastError?: string;
+}
+
+export interface SyncConflict {
+  id: string;
+  command: string;
+  args: Record<string, unknown>;
+  userId: string;
+  queuedAt: string;
+  detectedAt: string;
+  reason: string;
+  status?: number;
+  /** The record as the server has it, when the conflict is a concurrent edit. */
+  server?: unknown;
+}
+
+export interface OfflineSyncState {
+  online: boolean;
+  syncing: boolean;
+  pending: number;
+  conflicts: SyncConflict[];
+  lastSyncedAt: string | null;
+}
+
+/** The API could not be reached: no network, server down, or a timeout. */
+export class OfflineError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = 'OfflineError';
+  }
+}
+
+/** A write that was queued for later instead of sent. */
+export class QueuedOfflineError extends Error {
+  readonly queuedId: string;
+
+  constructor(queuedId: string) {
+    super('Offline: the change is saved and will be sent when the connection returns.');
+    this.name = 'QueuedOfflineError';
+    this.queuedId = queuedId;
+  }
+}
+
+type Send = (command: string, args: Record<string, unknown>) => Promise<unknown>;
+
+export const offlineSync = writable<OfflineSyncState>({
+  online: true,
+  syncing: false,
+  pending: 0,
+  conflicts: [],
+  lastSyncedAt: null,
+});
+
+function load<T>(key: string, fallback: T): T {
+  try {
+    const raw = localStorage.getItem(key);
+    return raw ? (JSON.parse(raw) as T) : fallback;
+  } catch {
+    return fallback;
+  }
+}
+
+function save(key: string, value: unknown): boolean {
+  try {
+    localStorage.setItem(key, JSON.stringify(value));
+    return true;
+  } catch {
+    // Quota exceeded; the caller decides what to drop.
+    return false;
+  }
+}
+
+function currentUserId(): string {
+  const raw = localStorage.getItem('auth_user') || sessionStorage.getItem('auth_user');
+  try {
+    return raw ? String(JSON.parse(raw)?.id ?? '') : '';
+  } catch {
+    return '';
+  }
+}
+
+function newId(): string {
+  if (typeof crypto !== 'undefined' && typeof crypto.randomUUID === 'function') {
+    return crypto.randomUUID();
+  }
+  return `${Date.now().toString(36)}-${Math.random().toString(36).slice(2, 10)}`;
+}
+
+/** Arguments worth storing: no token, no `__` options, no snake_case twins of camelCase keys. */
+function storedArgs(args: Record<string, unknown> | undefined): Record<string, unknown> {
+  const entries = Object.entries(args || {}).filter(
+    ([key, value]) => key !== 'token' && !key.startsWith('__') && value !== undefined,
+  );
+  const keys = new Set(entries.map(([key]) => key));
+  const camel = (key: string) => key.replace(/_([a-z])/g, (_m, c: string) => c.toUpperCase());
+  return Object.fromEntries(entries.filter(([key]) => !key.includes('_') || !keys.has(camel(key))));
+}
+
+function cacheKey(command: string, args: Record<string, unknown> | undefined): string {
+  const tenant = localStorage.getItem('active_tenant_slug') || '';
+  const stored = storedArgs(args);
+  const sorted = Object.keys(stored)
+    .sort()
+    .map((key) => [key, stored[key]]);
+  return `${tenant}|${command}|${JSON.stringify(sorted)}`;
+}
+
+function rememberRead(command: string, args: Record<string, unknown> | undefined, data: unknown) {
+  const cache = load<Record<string, { at: number; data: unknown }>>(READ_CACHE_KEY, {});
+  cache[cacheKey(command, args)] = { at: Date.now(), data };
+
+  let keys = Object.keys(cache).sort((a, b) => cache[a].at - cache[b].at);
+  while (keys.length > MAX_CACHED_READS) delete cache[keys.shift()!];
+  // Out of space: give up the older half and try once more.
+  if (!save(READ_CACHE_KEY, cache)) {
+    keys = Object.keys(cache).sort((a, b) => cache[a].at - cache[b].at);
+    for (const key of keys.slice(0, Math.ceil(keys.length / 2))) delete cache[key];
+    save(READ_CACHE_KEY, cache);
+  }
+}
+
+function cachedRead(command: string, args: Record<string, unknown> | undefined): unknown {
+  const cache = load<Record<string, { at: number; data: unknown }>>(READ_CACHE_KEY, {});
+  return cache[cacheKey(command, args)]?.data;
+}
+
+/** Drops cached records, e.g. on logout. Queued writes are kept for their user. */
+export function clearOfflineCache() {
+  localStorage.removeItem(READ_CACHE_KEY);
+}
+
+function loadQueue(): QueuedWrite[] {
+  return load<QueuedWrite[]>(WRITE_QUEUE_KEY, []);
+}
+
+function loadConflicts(): SyncConflict[] {
+  return load<SyncConflict[]>(CONFLICTS_KEY, []);
+}
+
+function publish(patch: Partial<OfflineSyncState> = {}) {
+  const userId = currentUserId();
+  offlineSync.update((state) => ({
+    ...state,
+    ...patch,
+    pending: loadQueue().filter((w) => w.userId === userId).length,
+    conflicts: loadConflicts().filter((c) => c.userId === userId),
+  }));
+}
+
+function queueWrite(command: string, args: Record<string, unknown> | undefined): QueuedWrite {
+  const stored = storedArgs(args);
+  const check = QUEUED_WRITES[command]?.check;
+  const base = check
+    ? check.updatedAt(cachedRead(check.command, { [check.idArg]: stored[check.idArg] }))
+    : undefined;
+  const write: QueuedWrite = {
+    id: newId(),
+    command,
+    args: stored,
+    userId: currentUserId(),
+    queuedAt: new Date().toISOString(),
+    baseUpdatedAt: base,
+    attempts: 0,
+  };
+  if (!save(WRITE_QUEUE_KEY, [...loadQueue(), write])) {
+    throw new Error('Offline and out of local storage: the change could not be saved.');
+  }
+  publish();
+  return write;
+}
+
+function updateQueue(fn: (queue: QueuedWrite[]) => QueuedWrite[]) {
+  save(WRITE_QUEUE_KEY, fn(loadQueue()));
+}
+
+function addConflict(write: QueuedWrite, reason: string, status?: number, server?: unknown) {
+  const conflict: SyncConflict = {
+    id: write.id,
+    command: write.command,
+    args: write.args,
+    userId: write.userId,
+    queuedAt: write.queuedAt,
+    detectedAt: new Date().toISOString(),
+    reason,
+    status,
+    server,
+  };
+  save(CONFLICTS_KEY, [...loadConflicts().filter((c) => c.id !== write.id), conflict]);
+  updateQueue((queue) => queue.filter((w) => w.id !== write.id));
+}
+
+/** Statuses that mean the server will never take the write as it is. */
+function isRejection(status: unknown): status is number {
+  return (
+    typeof status === 'number' &&
+    status >= 400 &&
+    status < 500 &&
+    status !== 401 &&
+    status !== 408 &&
+    status !== 429
+  );
+}
+
+let replaying: Promise<void> | null = null;
+
+/** Sends the current user's queued writes in order. Concurrent calls share one run. */
+export function replayOfflineWrites(send: Send): Promise<void> {
+  if (!replaying) {
+    replaying = replay(send).finally(() => {
+      replaying = null;
+    });
+  }
+  return replaying;
+}
+
+async function replay(send: Send) {
+  const userId = currentUserId();
+  if (!userId || !loadQueue().some((w) => w.userId === userId)) return;
+  publish({ syncing: true });
+
+  let online = true;
+  for (;;) {
+    const write = loadQueue().find((w) => w.userId === userId);
+    if (!write) break;
+
+    try {
+      const check = QUEUED_WRITES[write.command]?.check;
+      if (check && write.baseUpdatedAt) {
+        const current = await send(check.command, { [check.idArg]: write.args[check.idArg] });
+        const updatedAt = check.updatedAt(current);
+        if (updatedAt && updatedAt !== write.baseUpdatedAt) {
+          addConflict(write, 'Changed by someone else while this edit was offline', 409, current);
+          continue;
+        }
+      }
+      await send(write.command, { ...write.args, __idempotency_key: write.id });
+      updateQueue((queue) => queue.filter((w) => w.id !== write.id));
+    } catch (e: any) {
+      if (e instanceof OfflineError) {
+        online = false;
+        break;
+      }
+      if (isRejection(e?.status)) {
+        addConflict(write, String(e?.message || e), e.status);
+        continue;
+      }
+      // Server trouble or an expired session: keep it for the next round.
+      updateQueue((queue) =>
+        queue.map((w) =>
+          w.id === write.id
+            ? { ...w, attempts: w.attempts + 1, lastError: String(e?.message || e) }
+            : w,
+        ),
+      );
+      break;
+    }
+  }
+
+  publish({
+    online,
+    syncing: false,
+    ...(online ? { lastSyncedAt: new Date().toISOString() } : {}),
+  });
+}
+
+/**
+ * Runs `request` for `command`, caching reads and queueing writes when the API
+ * is unreachable. Commands without offline support pass straight through.
+ */
+export async function withOfflineSupport<T>(
+  command: string,
+  args: Record<string, unknown> | undefined,
+  request: () => Promise<T>,
+  send: Send,
+): Promise<T> {
+  const isRead = CACHED_READS.has(command);
+  const isWrite = command in QUEUED_WRITES;
+  if (!isRead && !isWrite) return request();
+
+  if (isWrite && currentUserId()) {
+    // Writes go out in the order they were made: flush older ones first.
+    const userId = currentUserId();
+    if (loadQueue().some((w) => w.userId === userId)) {
+      await replayOfflineWrites(send);
+      if (loadQueue().some((w) => w.userId === userId)) {
+        throw new QueuedOfflineError(queueWrite(command, args).id);
+      }
+    }
+  }
+
+  try {
+    const result = await request();
+    if (isRead) rememberRead(command, args, result);
+    publish({ online: true });
+    return result;
+  } catch (e) {
+    if (!(e instanceof OfflineError)) throw e;
+    publish({ online: false });
+    if (isWrite) throw new QueuedOfflineError(queueWrite(command, args).id);
+    const cached = cachedRead(command, args);
+    if (cached !== undefined) return cached as T;
+    throw e;
+  }
+}
+
+/** Queues a conflicting write again, this time without the version check. */
+export function applyConflictAnyway(id: string) {
+  const conflict = loadConflicts().find((c) => c.id === id);
+  if (!conflict) return;
+  save(CONFLICTS_KEY, loadConflicts().filter((c) => c.id !== id));
+  const write: QueuedWrite = {
+    id: newId(),
+    command: conflict.command,
+    args: conflict.args,
+    userId: conflict.userId,
+    queuedAt: conflict.queuedAt,
+    attempts: 0,
+  };
+  save(WRITE_QUEUE_KEY, [...loadQueue(), write]);
+  publish();
+}
+
+export function discardConflict(id: string) {
+  save(CONFLICTS_KEY, loadConflicts().filter((c) => c.id !== id));
+  publish();
+}
+
+let started = false;
+
+/** Replays the queue when the connection comes back and every 30 seconds while writes wait. */
+export function startOfflineSync(send: Send) {
+  if (started || typeof window === 'undefined') return;
+  started = true;
+  publish();
+  window.addEventListener('online', () => void replayOfflineWrites(send));
+  setInterval(() => {
+    const userId = currentUserId();
+    if (loadQueue().some((w) => w.userId === userId)) void replayOfflineWrites(send);
+  }, RETRY_INTERVAL_MS);
+}
@@ -0,0 +1,14 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type { OnboardingProgress, OnboardingStep } from './types';
+
+export const onboarding = {
+  /** The checklist, with steps done since the last call marked done. */
+  progress: (): Promise<OnboardingProgress> =>
+    safeInvoke('get_onboarding_progress', { token: getTokenOrThrow() }),
+
+  skipStep: (step: OnboardingStep['step']): Promise<OnboardingProgress> =>
+    safeInvoke('skip_onboarding_step', { token: getTokenOrThrow(), step }),
+
+  resumeStep: (step: OnboardingStep['step']): Promise<OnboardingProgress> =>
+    safeInvoke('resume_onboarding_step', { token: getTokenOrThrow(), step }),
+};
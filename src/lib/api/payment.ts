@@ -6,7 +6,11 @@ import type {
   BulkGenerateInvoicesResult,
   FxRate,
   Invoice,
+  InvoicePrintKind,
   InvoiceReminderLogView,
+  PlanUpgradeCheckout,
+  PlanUpgradeQuote,
+  PrintResult,
 } from './types';
 
 export const payment = {
@@ -31,6 +35,18 @@ export const payment = {
   createInvoiceForPlan: (planId: string, billingCycle: 'monthly' | 'yearly'): Promise<Invoice> =>
     safeInvoke('create_invoice_for_plan', { token: getTokenOrThrow(), planId, billingCycle }),
 
+  quotePlanUpgrade: (
+    planId: string,
+    billingCycle: 'monthly' | 'yearly',
+  ): Promise<PlanUpgradeQuote> =>
+    safeInvoke('quote_plan_upgrade', { token: getTokenOrThrow(), planId, billingCycle }),
+
+  checkoutPlanUpgrade: (
+    planId: string,
+    billingCycle: 'monthly' | 'yearly',
+  ): Promise<PlanUpgradeCheckout> =>
+    safeInvoke('checkout_plan_upgrade', { token: getTokenOrThrow(), planId, billingCycle }),
+
   createInvoiceForCustomerSubscription: (subscriptionId: string): Promise<Invoice> =>
     safeInvoke('create_invoice_for_customer_subscription', {
       token: getTokenOrThrow(),
@@ -139,4 +155,22 @@ export const payment = {
       rejectionReason,
       rejection_reason: rejectionReason,
     }),
+
+  /** Recomputes a pending package invoice from the price in effect for its period. */
+  repriceCustomerPackageInvoice: (invoiceId: string): Promise<Invoice> =>
+    safeInvoke('reprice_customer_package_invoice', {
+      token: getTokenOrThrow(),
+      id: invoiceId,
+      invoiceId,
+      invoice_id: invoiceId,
+    }),
+
+  previewInvoicePrint: (invoiceId: string, kind: InvoicePrintKind): Promise<string> =>
+    safeInvoke('preview_invoice_print', { token: getTokenOrThrow(), invoiceId, kind }),
+
+  printInvoice: (invoiceId: string, kind: InvoicePrintKind): Promise<PrintResult> =>
+    safeInvoke('print_invoice', { token: getTokenOrThrow(), invoiceId, kind }),
+
+  printTestReceipt: (): Promise<PrintResult> =>
+    safeInvoke('print_test_receipt', { token: getTokenOrThrow() }),
 };
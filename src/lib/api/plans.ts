@@ -16,6 +16,8 @@ export const plans = {
     is_active?: boolean,
     is_default?: boolean,
     sort_order?: number,
+    trial_days?: number,
+    trial_expiry_action?: 'downgrade' | 'lock',
   ): Promise<any> =>
     safeInvoke('create_plan', {
       token: getTokenOrThrow(),
@@ -27,11 +29,15 @@ export const plans = {
       isActive: is_active,
       isDefault: is_default,
       sortOrder: sort_order,
+      trialDays: trial_days,
+      trialExpiryAction: trial_expiry_action,
       price_monthly,
       price_yearly,
       is_active,
       is_default,
       sort_order,
+      trial_days,
+      trial_expiry_action,
     }),
 
   update: (
@@ -44,6 +50,8 @@ export const plans = {
     is_active?: boolean,
     is_default?: boolean,
     sort_order?: number,
+    trial_days?: number,
+    trial_expiry_action?: 'downgrade' | 'lock',
   ): Promise<any> =>
     safeInvoke('update_plan', {
       token: getTokenOrThrow(),
@@ -56,11 +64,15 @@ export const plans = {
       isActive: is_active,
       isDefault: is_default,
       sortOrder: sort_order,
+      trialDays: trial_days,
+      trialExpiryAction: trial_expiry_action,
       price_monthly,
       price_yearly,
       is_active,
       is_default,
       sort_order,
+      trial_days,
+      trial_expiry_action,
     }),
 
   delete: (planId: string): Promise<void> =>
@@ -103,6 +115,9 @@ export const plans = {
   assignPlan: (tenantId: string, planId: string): Promise<any> =>
     safeInvoke('assign_plan_to_tenant', { token: getTokenOrThrow(), tenantId, planId }),
 
+  startTrial: (tenantId: string, planId: string): Promise<any> =>
+    safeInvoke('start_tenant_trial', { token: getTokenOrThrow(), tenantId, planId }),
+
   checkAccess: (tenantId: string, featureCode: string): Promise<any> =>
     safeInvoke('check_feature_access', { token: getTokenOrThrow(), tenantId, featureCode }),
 };
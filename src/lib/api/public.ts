@@ -1,10 +1,19 @@
 import { safeInvoke } from './core';
-import type { AuthResponse, CustomerRegistrationInviteValidation } from './types';
+import type {
+  AuthResponse,
+  ClientCompatibility,
+  CustomerRegistrationInviteValidation,
+  PublicStatusPage,
+} from './types';
 
 export const publicApi = {
   getTenant: (slug: string): Promise<any> => safeInvoke('get_tenant_by_slug', { slug }),
   getTenantByDomain: (domain: string): Promise<any> =>
     safeInvoke('get_tenant_by_domain', { domain }),
+  getStatusPage: (slug: string): Promise<PublicStatusPage> =>
+    safeInvoke('get_public_status_page', { slug }),
+  getClientCompatibility: (version: string): Promise<ClientCompatibility> =>
+    safeInvoke('check_client_compatibility', { version }),
   getCustomerRegistrationStatusByDomain: (
     domain: string,
   ): Promise<{
@@ -0,0 +1,34 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type { ReportSchedule, ReportScheduleInput, ReportSendResult } from './types';
+
+const scheduleArgs = (schedule: ReportScheduleInput) => ({
+  name: schedule.name,
+  report: schedule.report,
+  format: schedule.format,
+  cadence: schedule.cadence,
+  send_hour: schedule.sendHour,
+  recipients: schedule.recipients,
+  enabled: schedule.enabled,
+});
+
+export const reportSchedules = {
+  list: (): Promise<ReportSchedule[]> =>
+    safeInvoke('list_report_schedules', { token: getTokenOrThrow() }),
+
+  create: (schedule: ReportScheduleInput): Promise<ReportSchedule> =>
+    safeInvoke('create_report_schedule', { token: getTokenOrThrow(), ...scheduleArgs(schedule) }),
+
+  update: (id: string, schedule: ReportScheduleInput): Promise<ReportSchedule> =>
+    safeInvoke('update_report_schedule', {
+      token: getTokenOrThrow(),
+      id,
+      ...scheduleArgs(schedule),
+    }),
+
+  delete: (id: string): Promise<void> =>
+    safeInvoke('delete_report_schedule', { token: getTokenOrThrow(), id }),
+
+  /** Emails the last complete period's report now. */
+  sendNow: (id: string): Promise<ReportSendResult> =>
+    safeInvoke('send_report_schedule', { token: getTokenOrThrow(), id }),
+};
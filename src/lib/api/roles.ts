@@ -30,6 +30,7 @@ export const roles = {
     description?: string,
     level?: number,
     permissions?: string[],
+    expectedUpdatedAt?: string,
   ): Promise<Role> =>
     safeInvoke('update_existing_role', {
       token: getTokenOrThrow(),
@@ -39,6 +40,7 @@ export const roles = {
       description,
       level,
       permissions,
+      expectedUpdatedAt,
     }),
 
   delete: (id: string): Promise<boolean> =>
@@ -30,8 +30,19 @@ export const settings = {
   getEmailVerificationReadiness: (): Promise<EmailVerificationReadiness> =>
     safeInvoke('get_email_verification_readiness', { token: getTokenOrThrow() }),
 
-  upsert: (key: string, value: string, description?: string): Promise<Setting> =>
-    safeInvoke('upsert_setting', { token: getTokenOrThrow(), key, value, description }),
+  upsert: (
+    key: string,
+    value: string,
+    description?: string,
+    expectedUpdatedAt?: string,
+  ): Promise<Setting> =>
+    safeInvoke('upsert_setting', {
+      token: getTokenOrThrow(),
+      key,
+      value,
+      description,
+      expectedUpdatedAt,
+    }),
 
   uploadLogo: (fileBase64: string): Promise<string> =>
     safeInvoke('upload_logo', { token: getTokenOrThrow(), content: fileBase64 }),
@@ -0,0 +1,86 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type {
+  PublicStatusPage,
+  StatusComponent,
+  StatusComponentInput,
+  StatusPost,
+  StatusPostImpact,
+  StatusPostInput,
+  StatusPostStatus,
+} from './types';
+
+const componentArgs = (component: StatusComponentInput) => ({
+  name: component.name,
+  description: component.description ?? undefined,
+  kind: component.kind,
+  router_ids: component.routerIds,
+  status_override: component.statusOverride ?? undefined,
+  position: component.position,
+  is_visible: component.isVisible,
+});
+
+export const statusPage = {
+  preview: (): Promise<PublicStatusPage> =>
+    safeInvoke('preview_status_page', { token: getTokenOrThrow() }),
+
+  listComponents: (): Promise<StatusComponent[]> =>
+    safeInvoke('list_status_components', { token: getTokenOrThrow() }),
+
+  createComponent: (component: StatusComponentInput): Promise<StatusComponent> =>
+    safeInvoke('create_status_component', {
+      token: getTokenOrThrow(),
+      ...componentArgs(component),
+    }),
+
+  updateComponent: (id: string, component: StatusComponentInput): Promise<StatusComponent> =>
+    safeInvoke('update_status_component', {
+      token: getTokenOrThrow(),
+      id,
+      ...componentArgs(component),
+    }),
+
+  deleteComponent: (id: string): Promise<void> =>
+    safeInvoke('delete_status_component', { token: getTokenOrThrow(), id }),
+
+  listPosts: (includeClosed = true): Promise<StatusPost[]> =>
+    safeInvoke('list_status_posts', { token: getTokenOrThrow(), includeClosed }),
+
+  createPost: (post: StatusPostInput): Promise<StatusPost> =>
+    safeInvoke('create_status_post', {
+      token: getTokenOrThrow(),
+      kind: post.kind,
+      title: post.title,
+      message: post.message,
+      impact: post.impact,
+      status: post.status,
+      component_ids: post.componentIds,
+      scheduled_start: post.scheduledStart ?? undefined,
+      scheduled_end: post.scheduledEnd ?? undefined,
+    }),
+
+  updatePost: (
+    id: string,
+    changes: {
+      title?: string;
+      impact?: StatusPostImpact;
+      componentIds?: string[];
+      scheduledStart?: string;
+      scheduledEnd?: string;
+    },
+  ): Promise<StatusPost> =>
+    safeInvoke('update_status_post', {
+      token: getTokenOrThrow(),
+      id,
+      title: changes.title,
+      impact: changes.impact,
+      component_ids: changes.componentIds,
+      scheduled_start: changes.scheduledStart,
+      scheduled_end: changes.scheduledEnd,
+    }),
+
+  deletePost: (id: string): Promise<void> =>
+    safeInvoke('delete_status_post', { token: getTokenOrThrow(), id }),
+
+  addUpdate: (id: string, status: StatusPostStatus, message: string): Promise<StatusPost> =>
+    safeInvoke('add_status_post_update', { token: getTokenOrThrow(), id, status, message }),
+};
@@ -1,6 +1,15 @@
 import { getApiBaseUrl } from '$lib/utils/apiUrl';
 import { getTokenOrThrow, safeInvoke } from './core';
-import type { FileRecord, PaginatedResponse } from './types';
+import type {
+  FileRecord,
+  FileVersion,
+  PaginatedResponse,
+  SignedFileLink,
+  StorageCleanupResult,
+  StorageCleanupRule,
+  StorageUsageReport,
+  UpsertStorageCleanupRuleDto,
+} from './types';
 
 export const storage = {
   listFiles: (
@@ -33,6 +42,36 @@ export const storage = {
   deleteFileTenant: (fileId: string): Promise<void> =>
     safeInvoke('delete_file_tenant', { token: getTokenOrThrow(), fileId }),
 
+  listVersions: (fileId: string): Promise<FileVersion[]> =>
+    safeInvoke('list_file_versions', { token: getTokenOrThrow(), fileId }),
+
+  restoreVersion: (fileId: string, versionId: string): Promise<FileRecord> =>
+    safeInvoke('restore_file_version', { token: getTokenOrThrow(), fileId, versionId }),
+
+  createLink: (fileId: string, expiresInMinutes?: number): Promise<SignedFileLink> =>
+    safeInvoke('create_file_link', { token: getTokenOrThrow(), fileId, expiresInMinutes }),
+
+  usage: (): Promise<StorageUsageReport> =>
+    safeInvoke('get_storage_usage', { token: getTokenOrThrow() }),
+
+  cleanupRules: {
+    list: (): Promise<StorageCleanupRule[]> =>
+      safeInvoke('list_storage_cleanup_rules', { token: getTokenOrThrow() }),
+
+    create: (dto: UpsertStorageCleanupRuleDto): Promise<StorageCleanupRule> =>
+      safeInvoke('create_storage_cleanup_rule', { token: getTokenOrThrow(), ...dto }),
+
+    update: (id: string, dto: UpsertStorageCleanupRuleDto): Promise<StorageCleanupRule> =>
+      safeInvoke('update_storage_cleanup_rule', { token: getTokenOrThrow(), id, ...dto }),
+
+    delete: (id: string): Promise<void> =>
+      safeInvoke('delete_storage_cleanup_rule', { token: getTokenOrThrow(), id }),
+
+    /** Runs the rule now instead of waiting for the daily run. */
+    run: (id: string): Promise<StorageCleanupResult> =>
+      safeInvoke('run_storage_cleanup_rule', { token: getTokenOrThrow(), id }),
+  },
+
   uploadFile: async (
     file: File,
     options?: { paymentInvoiceId?: string | null },
@@ -1,5 +1,22 @@
-import { getTokenOrThrow, safeInvoke } from './core';
-import type { AuditLog, PaginatedResponse } from './types';
+import { getApiBaseUrl } from '$lib/utils/apiUrl';
+import { getTokenOrThrow, isTauriRuntime, safeInvoke } from './core';
+import type {
+  AlertDeliveryResult,
+  AuditLog,
+  AuditLogFilters,
+  BackgroundJob,
+  BackgroundJobStats,
+  BackgroundJobStatus,
+  DiagnosticsBundleSummary,
+  HttpServerConfig,
+  HttpServerConfigInput,
+  PaginatedResponse,
+  SystemAlertRule,
+  SystemAlertRuleInput,
+  TenantExportSummary,
+  TenantImportOptions,
+  TenantImportReport,
+} from './types';
 
 export const superadmin = {
   listTenants: (): Promise<{ data: any[]; total: number }> =>
@@ -42,20 +59,73 @@ export const superadmin = {
       isActive,
     }),
 
+  /** Desktop: writes the archive into a chosen folder. Web: downloads it. */
+  exportTenant: async (id: string): Promise<TenantExportSummary | void> => {
+    const token = getTokenOrThrow();
+
+    if (isTauriRuntime()) {
+      const { open } = await import('@tauri-apps/plugin-dialog');
+      const dir = await open({ directory: true });
+      if (!dir || typeof dir !== 'string') throw new Error('No folder selected');
+      return await safeInvoke('export_tenant_archive', { token, id, dir });
+    }
+
+    const response = await fetch(`${getApiBaseUrl()}/superadmin/tenants/${id}/export`, {
+      headers: { Authorization: `Bearer ${token}` },
+    });
+    if (!response.ok) {
+      const error = await response.json().catch(() => ({}));
+      throw new Error(error.error || 'Export failed');
+    }
+
+    const disposition = response.headers.get('content-disposition') || '';
+    const filename = /filename="([^"]+)"/.exec(disposition)?.[1] || `tenant_export_${id}.zip`;
+    const url = window.URL.createObjectURL(await response.blob());
+    const link = document.createElement('a');
+    link.href = url;
+    link.setAttribute('download', filename);
+    document.body.appendChild(link);
+    link.click();
+    link.remove();
+    window.URL.revokeObjectURL(url);
+  },
+
+  importTenant: async (
+    file?: File,
+    options: TenantImportOptions = {},
+  ): Promise<TenantImportReport> => {
+    const token = getTokenOrThrow();
+
+    if (isTauriRuntime() && !file) {
+      const { open } = await import('@tauri-apps/plugin-dialog');
+      const selected = await open({ filters: [{ name: 'Archive', extensions: ['zip'] }] });
+      if (!selected || typeof selected !== 'string') throw new Error('No file selected');
+      return await safeInvoke('import_tenant_archive', { token, path: selected, ...options });
+    }
+    if (!file) throw new Error('File required for web import');
+
+    const formData = new FormData();
+    formData.append('file', file);
+    if (options.asCopy) formData.append('asCopy', 'true');
+    if (options.name) formData.append('name', options.name);
+    if (options.slug) formData.append('slug', options.slug);
+
+    const response = await fetch(`${getApiBaseUrl()}/superadmin/tenants/import`, {
+      method: 'POST',
+      headers: { Authorization: `Bearer ${token}` },
+      body: formData,
+    });
+    if (!response.ok) {
+      const error = await response.json().catch(() => ({}));
+      throw new Error(error.error || 'Import failed');
+    }
+    return await response.json();
+  },
+
   listAuditLogs: (
     page?: number,
     perPage?: number,
-    filters?: {
-      user_id?: string;
-      tenant_id?: string;
-      customer_id?: string;
-      resource?: string;
-      resource_id?: string;
-      action?: string;
-      date_from?: string;
-      date_to?: string;
-      search?: string;
-    },
+    filters?: AuditLogFilters,
   ): Promise<PaginatedResponse<AuditLog>> =>
     safeInvoke('list_audit_logs', { token: getTokenOrThrow(), page, perPage, ...filters }),
 
@@ -64,4 +134,53 @@ export const superadmin = {
 
   getSystemDiagnostics: (): Promise<any> =>
     safeInvoke('get_system_diagnostics', { token: getTokenOrThrow() }),
+
+  getMigrationStatus: (): Promise<any> =>
+    safeInvoke('get_migration_status', { token: getTokenOrThrow() }),
+
+  runDbMaintenance: (): Promise<any> =>
+    safeInvoke('run_db_maintenance', { token: getTokenOrThrow() }),
+
+  /** Desktop only: writes a support ZIP into a chosen folder. */
+  exportDiagnosticsBundle: async (): Promise<DiagnosticsBundleSummary | void> => {
+    const { open } = await import('@tauri-apps/plugin-dialog');
+    const dir = await open({ directory: true });
+    if (!dir || typeof dir !== 'string') return;
+    return await safeInvoke('export_diagnostics_bundle', { token: getTokenOrThrow(), dir });
+  },
+
+  listBackgroundJobs: (
+    page?: number,
+    perPage?: number,
+    filters?: { status?: BackgroundJobStatus; jobType?: string },
+  ): Promise<PaginatedResponse<BackgroundJob>> =>
+    safeInvoke('list_background_jobs', { token: getTokenOrThrow(), page, perPage, ...filters }),
+
+  getBackgroundJobStats: (): Promise<BackgroundJobStats> =>
+    safeInvoke('get_background_job_stats', { token: getTokenOrThrow() }),
+
+  retryBackgroundJob: (id: string): Promise<BackgroundJob> =>
+    safeInvoke('retry_background_job', { token: getTokenOrThrow(), id }),
+
+  listAlertRules: (): Promise<SystemAlertRule[]> =>
+    safeInvoke('list_alert_rules', { token: getTokenOrThrow() }),
+
+  createAlertRule: (rule: SystemAlertRuleInput): Promise<SystemAlertRule> =>
+    safeInvoke('create_alert_rule', { token: getTokenOrThrow(), ...rule }),
+
+  updateAlertRule: (id: string, rule: SystemAlertRuleInput): Promise<SystemAlertRule> =>
+    safeInvoke('update_alert_rule', { token: getTokenOrThrow(), id, ...rule }),
+
+  deleteAlertRule: (id: string): Promise<void> =>
+    safeInvoke('delete_alert_rule', { token: getTokenOrThrow(), id }),
+
+  testAlertRule: (id: string): Promise<AlertDeliveryResult[]> =>
+    safeInvoke('test_alert_rule', { token: getTokenOrThrow(), id }),
+
+  /** Desktop only: the embedded HTTP API runs inside the app. */
+  getHttpServerConfig: (): Promise<HttpServerConfig> =>
+    safeInvoke('get_http_server_config', { token: getTokenOrThrow() }),
+
+  updateHttpServerConfig: (config: HttpServerConfigInput): Promise<HttpServerConfig> =>
+    safeInvoke('update_http_server_config', { token: getTokenOrThrow(), ...config }),
 };
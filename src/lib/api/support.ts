@@ -1,11 +1,21 @@
 import { getTokenOrThrow, safeInvoke } from './core';
 import type {
+  ApplySupportMacroResult,
+  LinkedSupportTicket,
   PaginatedResponse,
+  SupportEscalationRule,
+  SupportMacro,
+  SupportQueue,
   SupportTicket,
+  SupportLinkEntityType,
   SupportTicketDetail,
+  SupportTicketLink,
   SupportTicketListItem,
   SupportTicketMessage,
   SupportTicketStats,
+  UpsertSupportEscalationRuleDto,
+  UpsertSupportMacroDto,
+  UpsertSupportQueueDto,
 } from './types';
 
 export const support = {
@@ -14,6 +24,8 @@ export const support = {
     search?: string;
     page?: number;
     perPage?: number;
+    queueId?: string;
+    assigned?: 'me' | 'unassigned';
   }): Promise<PaginatedResponse<SupportTicketListItem>> =>
     safeInvoke('list_support_tickets', {
       token: getTokenOrThrow(),
@@ -21,6 +33,8 @@ export const support = {
       search: params?.search,
       page: params?.page,
       per_page: params?.perPage,
+      queue_id: params?.queueId,
+      assigned: params?.assigned,
     }),
 
   stats: (): Promise<SupportTicketStats> =>
@@ -31,12 +45,14 @@ export const support = {
     message: string,
     priority?: string,
     attachmentIds?: string[],
+    category?: string,
   ): Promise<SupportTicketDetail> =>
     safeInvoke('create_support_ticket', {
       token: getTokenOrThrow(),
       subject,
       message,
       priority,
+      category,
       attachmentIds,
       attachment_ids: attachmentIds,
     }),
@@ -62,7 +78,13 @@ export const support = {
 
   update: (
     id: string,
-    data: { status?: string; priority?: string; assignedTo?: string | null },
+    data: {
+      status?: string;
+      priority?: string;
+      assignedTo?: string | null;
+      queueId?: string;
+      category?: string;
+    },
   ): Promise<SupportTicket> =>
     safeInvoke('update_support_ticket', {
       token: getTokenOrThrow(),
@@ -71,5 +93,90 @@ export const support = {
       priority: data.priority,
       assignedTo: data.assignedTo ?? undefined,
       assigned_to: data.assignedTo ?? undefined,
+      queueId: data.queueId,
+      queue_id: data.queueId,
+      category: data.category,
     }),
+
+  queues: {
+    list: (): Promise<SupportQueue[]> =>
+      safeInvoke('list_support_queues', { token: getTokenOrThrow() }),
+
+    create: (dto: UpsertSupportQueueDto): Promise<SupportQueue> =>
+      safeInvoke('create_support_queue', { token: getTokenOrThrow(), ...dto }),
+
+    update: (id: string, dto: UpsertSupportQueueDto): Promise<SupportQueue> =>
+      safeInvoke('update_support_queue', { token: getTokenOrThrow(), id, ...dto }),
+
+    delete: (id: string): Promise<void> =>
+      safeInvoke('delete_support_queue', { token: getTokenOrThrow(), id }),
+  },
+
+  escalationRules: {
+    list: (): Promise<SupportEscalationRule[]> =>
+      safeInvoke('list_support_escalation_rules', { token: getTokenOrThrow() }),
+
+    create: (dto: UpsertSupportEscalationRuleDto): Promise<SupportEscalationRule> =>
+      safeInvoke('create_support_escalation_rule', { token: getTokenOrThrow(), ...dto }),
+
+    update: (id: string, dto: UpsertSupportEscalationRuleDto): Promise<SupportEscalationRule> =>
+      safeInvoke('update_support_escalation_rule', { token: getTokenOrThrow(), id, ...dto }),
+
+    delete: (id: string): Promise<void> =>
+      safeInvoke('delete_support_escalation_rule', { token: getTokenOrThrow(), id }),
+  },
+
+  macros: {
+    list: (): Promise<SupportMacro[]> =>
+      safeInvoke('list_support_macros', { token: getTokenOrThrow() }),
+
+    create: (dto: UpsertSupportMacroDto): Promise<SupportMacro> =>
+      safeInvoke('create_support_macro', { token: getTokenOrThrow(), ...dto }),
+
+    update: (id: string, dto: UpsertSupportMacroDto): Promise<SupportMacro> =>
+      safeInvoke('update_support_macro', { token: getTokenOrThrow(), id, ...dto }),
+
+    delete: (id: string): Promise<void> =>
+      safeInvoke('delete_support_macro', { token: getTokenOrThrow(), id }),
+
+    /** Rendered canned reply for a ticket, to paste into the reply box. */
+    preview: (ticketId: string, macroId: string): Promise<{ body: string }> =>
+      safeInvoke('preview_support_macro', { token: getTokenOrThrow(), id: ticketId, macroId }),
+
+    apply: (ticketId: string, macroId: string): Promise<ApplySupportMacroResult> =>
+      safeInvoke('apply_support_macro', { token: getTokenOrThrow(), id: ticketId, macroId }),
+  },
+
+  links: {
+    list: (ticketId: string): Promise<SupportTicketLink[]> =>
+      safeInvoke('list_support_ticket_links', { token: getTokenOrThrow(), id: ticketId }),
+
+    create: (
+      ticketId: string,
+      entityType: SupportLinkEntityType,
+      entityId: string,
+      note?: string,
+    ): Promise<SupportTicketLink> =>
+      safeInvoke('link_support_ticket', {
+        token: getTokenOrThrow(),
+        id: ticketId,
+        entityType,
+        entityId,
+        note,
+      }),
+
+    delete: (ticketId: string, linkId: string): Promise<void> =>
+      safeInvoke('unlink_support_ticket', { token: getTokenOrThrow(), id: ticketId, linkId }),
+
+    /** Tickets linked to an incident, work order or invoice. */
+    forEntity: (
+      entityType: SupportLinkEntityType,
+      entityId: string,
+    ): Promise<LinkedSupportTicket[]> =>
+      safeInvoke('list_linked_support_tickets', {
+        token: getTokenOrThrow(),
+        entityType,
+        entityId,
+      }),
+  },
 };
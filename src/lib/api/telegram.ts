@@ -0,0 +1,22 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type { TelegramLinkStatus } from './types';
+
+export const telegram = {
+  getLink: (): Promise<TelegramLinkStatus> =>
+    safeInvoke('get_telegram_link', { token: getTokenOrThrow() }),
+
+  createLinkCode: (): Promise<TelegramLinkStatus> =>
+    safeInvoke('create_telegram_link_code', { token: getTokenOrThrow() }),
+
+  updateLink: (prefs: {
+    alertIncidents?: boolean;
+    alertPayments?: boolean;
+  }): Promise<TelegramLinkStatus> =>
+    safeInvoke('update_telegram_link', {
+      token: getTokenOrThrow(),
+      alert_incidents: prefs.alertIncidents,
+      alert_payments: prefs.alertPayments,
+    }),
+
+  unlink: (): Promise<void> => safeInvoke('unlink_telegram', { token: getTokenOrThrow() }),
+};
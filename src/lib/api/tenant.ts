@@ -1,4 +1,5 @@
 import { getTokenOrThrow, safeInvoke } from './core';
+import type { TenantAnalytics, TenantUsage } from './types';
 
 export const tenant = {
   getSelf: (): Promise<any> => safeInvoke('get_current_tenant', { token: getTokenOrThrow() }),
@@ -14,4 +15,9 @@ export const tenant = {
       customDomain: data.customDomain,
       enforce2fa: data.enforce2fa,
     }),
+
+  usage: (): Promise<TenantUsage> => safeInvoke('get_tenant_usage', { token: getTokenOrThrow() }),
+
+  analytics: (refresh = false): Promise<TenantAnalytics> =>
+    safeInvoke('get_tenant_analytics', { token: getTokenOrThrow(), refresh }),
 };
@@ -18,6 +18,11 @@ export interface User {
   preferred_2fa_method?: string;
 }
 
+/** `locale: null` follows the `default_locale` setting. */
+export interface UserLocale {
+  locale: string | null;
+}
+
 export interface UserAddress {
   id: string;
   user_id: string;
@@ -146,6 +151,146 @@ export interface AuditLog {
   user_name?: string;
   user_email?: string;
   tenant_name?: string;
+  old_value?: Record<string, unknown> | null;
+  new_value?: Record<string, unknown> | null;
+  changes?: AuditFieldChange[];
+  chain_seq?: number | null;
+  entry_hash?: string | null;
+}
+
+export interface AuditFieldChange {
+  field: string;
+  old: unknown;
+  new: unknown;
+}
+
+export interface AuditLogFilters {
+  user_id?: string;
+  tenant_id?: string;
+  customer_id?: string;
+  resource?: string;
+  resource_id?: string;
+  action?: string;
+  date_from?: string;
+  date_to?: string;
+  search?: string;
+  actor?: string;
+  ip_address?: string;
+}
+
+export type AuditExportFormat = 'csv' | 'ndjson';
+
+export interface AuditLogExport {
+  format: AuditExportFormat;
+  content: string;
+  rows: number;
+  truncated: boolean;
+}
+
+export type AuditChainIssueKind = 'modified' | 'gap' | 'broken_link' | 'duplicate' | 'truncated';
+
+export interface AuditChainIssue {
+  kind: AuditChainIssueKind;
+  seq: number;
+  log_id: string | null;
+  detail: string;
+}
+
+export interface AuditChainReport {
+  valid: boolean;
+  checked: number;
+  first_seq: number | null;
+  last_seq: number | null;
+  head_seq: number;
+  unchained: number;
+  issues: AuditChainIssue[];
+  issues_truncated: boolean;
+  verified_at: string;
+}
+
+export interface AuditFilterPreset {
+  id: string;
+  user_id: string;
+  tenant_id: string | null;
+  name: string;
+  filters: AuditLogFilters;
+  created_at: string;
+  updated_at: string;
+}
+
+export type BackgroundJobStatus = 'queued' | 'running' | 'succeeded' | 'failed' | 'dead';
+
+export interface BackgroundJob {
+  id: string;
+  job_type: string;
+  payload: Record<string, unknown>;
+  status: BackgroundJobStatus;
+  recurring_secs: number | null;
+  attempts: number;
+  max_attempts: number;
+  run_at: string;
+  locked_at: string | null;
+  locked_by: string | null;
+  last_error: string | null;
+  last_run_at: string | null;
+  finished_at: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export type BackgroundJobStats = Record<BackgroundJobStatus, number>;
+
+export type AlertMetric =
+  | 'error_rate'
+  | 'latency_p95'
+  | 'rate_limited'
+  | 'db_pool_saturation'
+  | 'disk_usage'
+  | 'outbox_backlog';
+
+export type AlertChannel = 'email' | 'telegram' | 'webhook';
+
+export interface SystemAlertRule {
+  id: string;
+  name: string;
+  metric: AlertMetric;
+  threshold: number;
+  channels: AlertChannel[];
+  cooldown_minutes: number;
+  enabled: boolean;
+  last_triggered_at: string | null;
+  last_value: number | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface SystemAlertRuleInput {
+  name: string;
+  metric: AlertMetric;
+  threshold: number;
+  channels: AlertChannel[];
+  cooldownMinutes: number;
+  enabled: boolean;
+}
+
+export interface HttpServerConfig {
+  enabled: boolean;
+  bind_address: string;
+  port: number;
+  listening_on: string | null;
+  error: string | null;
+}
+
+export interface HttpServerConfigInput {
+  enabled: boolean;
+  bindAddress: string;
+  port: number;
+}
+
+export interface AlertDeliveryResult {
+  channel: AlertChannel;
+  ok: boolean;
+  error: string | null;
 }
 
 export interface FileRecord {
@@ -156,11 +301,90 @@ export interface FileRecord {
   path: string;
   size: number;
   content_type: string;
+  storage_provider?: string;
+  storage_bucket?: string | null;
+  quota_counted?: boolean;
+  scan_status?: 'unscanned' | 'clean' | 'infected' | 'error';
+  scan_signature?: string | null;
+  scanned_at?: string | null;
+  logical_path?: string | null;
+  version?: number;
+  uploaded_by: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface FileVersion {
+  id: string;
+  file_id: string;
+  tenant_id: string;
+  version: number;
+  name: string;
+  original_name: string;
+  path: string;
+  size: number;
+  content_type: string;
+  storage_provider: string;
+  storage_bucket: string | null;
+  quota_counted: boolean;
+  scan_status: 'unscanned' | 'clean' | 'infected' | 'error';
+  scan_signature: string | null;
+  scanned_at: string | null;
   uploaded_by: string | null;
   created_at: string;
+  replaced_at: string;
+}
+
+export interface SignedFileLink {
+  file_id: string;
+  url: string;
+  expires_at: string;
+}
+
+export interface StorageCleanupRule {
+  id: string;
+  tenant_id: string;
+  name: string;
+  name_pattern: string | null;
+  content_type: string | null;
+  older_than_days: number;
+  enabled: boolean;
+  last_run_at: string | null;
+  last_deleted_count: number;
+  last_deleted_bytes: number;
+  created_by: string | null;
+  created_at: string;
   updated_at: string;
 }
 
+export interface UpsertStorageCleanupRuleDto {
+  name: string;
+  namePattern?: string | null;
+  contentType?: string | null;
+  olderThanDays: number;
+  enabled?: boolean;
+}
+
+export interface StorageCleanupResult {
+  rule_id: string;
+  deleted_count: number;
+  deleted_bytes: number;
+  failed: number;
+}
+
+export interface StorageUsageReport {
+  tenant_id: string;
+  used_bytes: number;
+  limit_bytes: number | null;
+  percent: number | null;
+  alert_threshold: number;
+  file_count: number;
+  file_bytes: number;
+  version_count: number;
+  version_bytes: number;
+  categories: { category: string; files: number; bytes: number }[];
+}
+
 export interface SupportTicketListItem {
   id: string;
   tenant_id: string;
@@ -170,6 +394,8 @@ export interface SupportTicketListItem {
   status: 'open' | 'pending' | 'closed' | string;
   priority: 'low' | 'normal' | 'high' | 'urgent' | string;
   assigned_to: string | null;
+  category: string | null;
+  queue_id: string | null;
   created_at: string;
   updated_at: string;
   closed_at: string | null;
@@ -182,6 +408,8 @@ export interface SupportTicketStats {
   open: number;
   pending: number;
   closed: number;
+  mine: number;
+  unassigned: number;
 }
 
 export interface SupportTicketMessage {
@@ -202,6 +430,8 @@ export interface SupportTicket {
   status: string;
   priority: string;
   assigned_to: string | null;
+  category: string | null;
+  queue_id: string | null;
   created_at: string;
   updated_at: string;
   closed_at: string | null;
@@ -212,6 +442,113 @@ export interface SupportTicketDetail {
   messages: SupportTicketMessage[];
 }
 
+export interface SupportQueue {
+  id: string;
+  tenant_id: string;
+  name: string;
+  description: string | null;
+  categories: string[];
+  is_default: boolean;
+  auto_assign: boolean;
+  online_only: boolean;
+  last_assigned_to: string | null;
+  member_ids: string[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface UpsertSupportQueueDto {
+  name: string;
+  description?: string | null;
+  categories?: string[];
+  isDefault?: boolean;
+  autoAssign?: boolean;
+  onlineOnly?: boolean;
+  memberIds?: string[];
+}
+
+export interface SupportEscalationRule {
+  id: string;
+  tenant_id: string;
+  name: string;
+  is_active: boolean;
+  idle_minutes: number;
+  statuses: ('open' | 'pending')[];
+  queue_id: string | null;
+  bump_priority: boolean;
+  reassign_to: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface UpsertSupportEscalationRuleDto {
+  name: string;
+  isActive?: boolean;
+  idleMinutes: number;
+  statuses?: ('open' | 'pending')[];
+  queueId?: string | null;
+  bumpPriority?: boolean;
+  reassignTo?: string | null;
+}
+
+export interface SupportMacro {
+  id: string;
+  tenant_id: string;
+  name: string;
+  category: string | null;
+  body: string;
+  is_internal: boolean;
+  set_status: 'open' | 'pending' | 'closed' | null;
+  set_priority: 'low' | 'normal' | 'high' | 'urgent' | null;
+  /** User id, or 'me' for the agent applying the macro. */
+  assign_to: string | null;
+  created_by: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface UpsertSupportMacroDto {
+  name: string;
+  category?: string | null;
+  body?: string;
+  isInternal?: boolean;
+  setStatus?: string | null;
+  setPriority?: string | null;
+  assignTo?: string | null;
+}
+
+export interface ApplySupportMacroResult {
+  ticket: SupportTicket;
+  message: SupportTicketMessage | null;
+}
+
+export type SupportLinkEntityType = 'incident' | 'work_order' | 'invoice';
+
+export interface SupportTicketLink {
+  id: string;
+  tenant_id: string;
+  ticket_id: string;
+  entity_type: SupportLinkEntityType;
+  entity_id: string;
+  note: string | null;
+  created_by: string | null;
+  created_at: string;
+  /** Null once the linked record has been deleted. */
+  entity_label: string | null;
+  entity_status: string | null;
+}
+
+export interface LinkedSupportTicket {
+  link_id: string;
+  ticket_id: string;
+  subject: string;
+  status: string;
+  priority: string;
+  assigned_to: string | null;
+  note: string | null;
+  linked_at: string;
+}
+
 export interface Customer {
   id: string;
   tenant_id: string;
@@ -224,6 +561,18 @@ export interface Customer {
   updated_at: string;
 }
 
+export interface CustomerTagSummary {
+  tag: string;
+  customer_count: number;
+}
+
+export interface TrashItem {
+  id: string;
+  name: string;
+  detail: string | null;
+  deleted_at: string;
+}
+
 export interface CustomerLocation {
   id: string;
   tenant_id: string;
@@ -327,6 +676,23 @@ export interface CustomerSubscriptionView extends CustomerSubscription {
   latest_reschedule_requested_at: string | null;
 }
 
+/** A bundle component of a subscription, provisioned on its own. */
+export interface CustomerSubscriptionComponent {
+  id: string;
+  tenant_id: string;
+  subscription_id: string;
+  component_package_id: string;
+  package_name: string | null;
+  service_type: string;
+  status: 'pending' | 'provisioned' | 'failed' | 'disabled' | string;
+  /** Static IP address, IPTV account or VoIP number. */
+  detail: string | null;
+  last_error: string | null;
+  provisioned_at: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
 export interface CustomerPortalSubscriptionStats {
   total: number;
   active: number;
@@ -343,9 +709,13 @@ export interface InstallationWorkOrderView {
   location_id: string;
   package_id: string | null;
   router_id: string | null;
-  status: 'pending' | 'in_progress' | 'completed' | 'cancelled' | string;
+  work_type: WorkOrderType;
+  /** Type-specific fields, e.g. `problem` for repairs or `target_location_id` for relocations. */
+  details: Record<string, unknown>;
+  status: 'pending' | 'in_progress' | 'on_hold' | 'completed' | 'cancelled' | string;
   assigned_to: string | null;
   scheduled_at: string | null;
+  scheduled_end_at: string | null;
   completed_at: string | null;
   notes: string | null;
   created_at: string;
@@ -370,96 +740,1052 @@ export interface InstallationWorkOrderView {
   path_link_ids: unknown[] | null;
 }
 
-export interface WorkOrderRescheduleRequestView {
-  id: string;
+export type WorkOrderType = 'installation' | 'repair' | 'relocation' | 'dismantle' | string;
+
+export interface CreateWorkOrderRequest {
+  work_type: 'repair' | 'relocation' | 'dismantle';
+  subscription_id: string;
+  /** repair: what the customer reported (required). */
+  problem?: string;
+  support_ticket_id?: string;
+  /** relocation: the customer's new location (required). */
+  target_location_id?: string;
+  reason?: string;
+  notes?: string;
+}
+
+export interface WorkOrderAgendaItem {
   work_order_id: string;
-  requested_schedule_at: string;
-  reason: string | null;
-  status: 'pending' | 'approved' | 'rejected' | 'cancelled' | string;
-  requested_by_name: string | null;
-  requested_by_email: string | null;
-  reviewed_by_name: string | null;
-  reviewed_at: string | null;
-  review_notes: string | null;
-  created_at: string;
+  status: string;
+  assigned_to: string | null;
+  assigned_to_name: string | null;
+  scheduled_at: string;
+  scheduled_end_at: string;
+  customer_id: string;
+  customer_name: string | null;
+  location_label: string | null;
+  package_name: string | null;
+  has_conflict: boolean;
 }
 
-export interface Invoice {
+export interface WorkOrderVisit {
   id: string;
-  tenant_id?: string;
-  invoice_number: string;
-  amount: number;
-  currency_code?: string;
-  base_currency_code?: string;
-  fx_rate?: number | null;
-  fx_source?: string | null;
-  fx_fetched_at?: string | null;
-  status: string;
-  description: string | null;
-  due_date: string;
-  paid_at: string | null;
-  payment_method: string | null;
-  external_id?: string | null;
-  merchant_id?: string | null;
-  proof_attachment?: string | null;
-  rejection_reason?: string | null;
-  created_at?: string;
-  updated_at?: string;
+  work_order_id: string;
+  technician_id: string | null;
+  technician_name: string | null;
+  check_in_at: string;
+  check_in_latitude: number;
+  check_in_longitude: number;
+  check_in_accuracy_m: number | null;
+  /** Metres from the customer location; null when the location has no coordinates. */
+  check_in_distance_m: number | null;
+  check_out_at: string | null;
+  check_out_latitude: number | null;
+  check_out_longitude: number | null;
+  check_out_accuracy_m: number | null;
+  check_out_distance_m: number | null;
+  notes: string | null;
 }
 
-export interface CustomerPortalCheckoutResponse {
-  subscription: CustomerSubscription;
-  invoice: Invoice;
+export interface WorkOrderSignatureInput {
+  signer_name: string;
+  /** Signature pad strokes as `[x, y]` points, canvas coordinates. */
+  strokes?: [number, number][][];
+  /** JPEG as base64 or a data URL, used when no strokes are sent. */
+  image_base64?: string;
 }
 
-export interface CustomerPortalOrderRequestResponse {
-  subscription: CustomerSubscription;
-  work_order: InstallationWorkOrderView;
+/** Signed completion report (berita acara); `file_id` is the PDF. */
+export interface WorkOrderCompletionReport {
+  id: string;
+  work_order_id: string;
+  customer_id: string;
+  report_number: string;
+  signer_name: string;
+  signature_kind: 'strokes' | 'image';
+  signature_file_id: string | null;
+  file_id: string | null;
+  signed_at: string;
+  emailed_to: string | null;
+  emailed_at: string | null;
+  email_error: string | null;
+  created_by: string | null;
+  created_by_name: string | null;
+  created_at: string;
 }
 
-export interface CustomerPortalInstallationTrackerResponse {
-  subscription: CustomerSubscriptionView;
-  work_order: InstallationWorkOrderView | null;
-  reschedule_request: WorkOrderRescheduleRequestView | null;
+export interface WorkOrderRouteStop {
+  /** 1-based position in the planned sequence. */
+  sequence: number;
+  work_order_id: string;
+  status: string;
+  scheduled_at: string;
+  customer_id: string;
+  customer_name: string | null;
+  location_label: string | null;
+  address: string | null;
+  latitude: number;
+  longitude: number;
+  leg_distance_m: number;
+  cumulative_distance_m: number;
 }
 
-export interface PppoeAccountPublic {
+export interface WorkOrderRoutePlan {
+  technician_id: string;
+  technician_name: string | null;
+  date: string;
+  start_latitude: number | null;
+  start_longitude: number | null;
+  stops: WorkOrderRouteStop[];
+  /** Work orders whose location has no coordinates. */
+  skipped: {
+    work_order_id: string;
+    scheduled_at: string;
+    customer_name: string | null;
+    location_label: string | null;
+  }[];
+  total_distance_m: number;
+  scheduled_order_distance_m: number;
+  /** GeoJSON LineString ([lng, lat]); null with fewer than two points. */
+  path: { type: 'LineString'; coordinates: [number, number][] } | null;
+}
+
+export type WorkOrderChecklistItemKind = 'check' | 'photo' | 'text';
+
+export interface WorkOrderTemplateItem {
   id: string;
-  tenant_id: string;
-  router_id: string;
-  customer_id: string;
-  location_id: string;
-  username: string;
-  package_id: string | null;
-  profile_id: string | null;
-  router_profile_name: string | null;
-  remote_address: string | null;
-  address_pool: string | null;
-  disabled: boolean;
-  comment: string | null;
-  router_present: boolean;
-  router_secret_id: string | null;
-  last_sync_at: string | null;
-  last_error: string | null;
-  created_at: string;
-  updated_at: string;
+  position: number;
+  label: string;
+  kind: WorkOrderChecklistItemKind;
+  is_required: boolean;
 }
 
-export interface IspPackage {
+export interface WorkOrderTemplate {
   id: string;
   tenant_id: string;
-  service_type: string;
   name: string;
   description: string | null;
-  features: string[];
+  is_default: boolean;
   is_active: boolean;
+  items: WorkOrderTemplateItem[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface UpsertWorkOrderTemplateRequest {
+  name: string;
+  description?: string | null;
+  is_default?: boolean;
+  is_active?: boolean;
+  items: { label: string; kind?: WorkOrderChecklistItemKind; is_required?: boolean }[];
+}
+
+export interface WorkOrderChecklistItem {
+  id: string;
+  work_order_id: string;
+  template_id: string | null;
+  position: number;
+  label: string;
+  kind: WorkOrderChecklistItemKind;
+  is_required: boolean;
+  is_done: boolean;
+  value: string | null;
+  file_id: string | null;
+  file_name: string | null;
+  completed_by: string | null;
+  completed_by_name: string | null;
+  completed_at: string | null;
+}
+
+export interface WorkOrderChecklist {
+  work_order_id: string;
+  items: WorkOrderChecklistItem[];
+  /** Labels of required items that still block completion. */
+  missing: string[];
+}
+
+export interface UpdateWorkOrderChecklistItemRequest {
+  is_done?: boolean;
+  value?: string;
+  /** Uploaded image id; an empty string clears it. */
+  file_id?: string;
+}
+
+export interface WorkOrderRescheduleRequestView {
+  id: string;
+  work_order_id: string;
+  requested_schedule_at: string;
+  reason: string | null;
+  status: 'pending' | 'approved' | 'rejected' | 'cancelled' | string;
+  requested_by_name: string | null;
+  requested_by_email: string | null;
+  reviewed_by_name: string | null;
+  reviewed_at: string | null;
+  review_notes: string | null;
+  created_at: string;
+}
+
+export interface InventoryItem {
+  id: string;
+  tenant_id: string;
+  sku: string;
+  name: string;
+  unit: string;
+  low_stock_threshold: number | null;
+  is_active: boolean;
+  /** Stock across all locations. */
+  total_quantity: number;
+  is_low_stock: boolean;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface UpsertInventoryItemRequest {
+  sku: string;
+  name: string;
+  unit?: string;
+  low_stock_threshold?: number | null;
+  is_active?: boolean;
+}
+
+export type InventoryLocationKind = 'warehouse' | 'technician';
+
+export interface InventoryLocation {
+  id: string;
+  tenant_id: string;
+  name: string;
+  kind: InventoryLocationKind;
+  technician_id: string | null;
+  technician_name: string | null;
+  is_active: boolean;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface UpsertInventoryLocationRequest {
+  name: string;
+  /** Fixed after creation. */
+  kind?: InventoryLocationKind;
+  technician_id?: string;
+  is_active?: boolean;
+}
+
+export interface InventoryStockLevel {
+  item_id: string;
+  sku: string;
+  item_name: string;
+  unit: string;
+  location_id: string;
+  location_name: string;
+  location_kind: InventoryLocationKind;
+  quantity: number;
+  updated_at: string;
+}
+
+export type InventoryMovementKind = 'receive' | 'transfer' | 'adjust' | 'consume';
+
+export interface InventoryMovement {
+  id: string;
+  item_id: string;
+  sku: string | null;
+  item_name: string | null;
+  unit: string | null;
+  kind: InventoryMovementKind;
+  from_location_id: string | null;
+  from_location_name: string | null;
+  to_location_id: string | null;
+  to_location_name: string | null;
+  quantity: number;
+  work_order_id: string | null;
+  note: string | null;
+  created_by: string | null;
+  created_by_name: string | null;
+  created_at: string;
+}
+
+export interface CreateInventoryMovementRequest {
+  item_id: string;
+  kind: Exclude<InventoryMovementKind, 'consume'>;
+  from_location_id?: string;
+  to_location_id?: string;
+  quantity: number;
+  note?: string;
+}
+
+export interface ConsumeWorkOrderMaterialsRequest {
+  /** Defaults to the technician's own stock location. */
+  location_id?: string;
+  items: { item_id: string; quantity: number }[];
+  note?: string;
+}
+
+export interface InventorySerial {
+  id: string;
+  item_id: string;
+  sku: string;
+  item_name: string;
+  serial_number: string;
+  status: 'in_stock' | 'installed';
+  /** Where the unit is kept; null once installed. */
+  location_id: string | null;
+  location_name: string | null;
+  work_order_id: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface InventoryScanResult {
+  /** The scanned code, normalized. */
+  code: string;
+  matched: 'serial' | 'item' | 'unknown';
+  serial: InventorySerial | null;
+  item: InventoryItem | null;
+}
+
+export interface ReceiveInventorySerialsRequest {
+  item_id: string;
+  location_id: string;
+  serials: string[];
+  note?: string;
+}
+
+export interface TransferInventorySerialsRequest {
+  to_location_id: string;
+  serials: string[];
+  note?: string;
+}
+
+export interface InventorySerialBatchResult {
+  serials: InventorySerial[];
+  /** Scans that were not applied, e.g. already registered or scanned twice. */
+  skipped: { serial_number: string; reason: string }[];
+}
+
+export interface InstallInventorySerialRequest {
+  serial_number: string;
+  note?: string;
+}
+
+export type IpamPurpose =
+  | 'customer'
+  | 'pppoe'
+  | 'static'
+  | 'infrastructure'
+  | 'management'
+  | 'transit'
+  | 'reserved';
+
+export interface IpamSubnet {
+  id: string;
+  tenant_id: string;
+  /** Network address and prefix, e.g. `10.20.0.0/22`. */
+  cidr: string;
+  name: string;
+  vlan_id: number | null;
+  purpose: IpamPurpose | null;
+  tags: string[];
+  router_id: string | null;
+  router_name: string | null;
+  notes: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface UpsertIpamSubnetRequest {
+  cidr: string;
+  name: string;
+  vlan_id?: number | null;
+  purpose?: IpamPurpose | null;
+  tags?: string[];
+  router_id?: string | null;
+  notes?: string | null;
+}
+
+export interface IpamHeatCell {
+  cidr: string;
+  size: number;
+  assigned: number;
+  utilization_pct: number;
+  /** Child subnet the block falls in, if any. */
+  subnet_id: string | null;
+}
+
+export interface IpamSubnetNode extends IpamSubnet {
+  parent_id: string | null;
+  depth: number;
+  first_address: string;
+  last_address: string;
+  size: number;
+  usable: number;
+  /** Addresses held by PPPoE accounts and provisioned static IPs. */
+  assigned: number;
+  /** Addresses inside router IP pools. */
+  pooled: number;
+  /** Addresses covered by child subnets. */
+  allocated: number;
+  utilization_pct: number;
+  allocation_pct: number;
+  free_blocks: string[];
+  /** Up to 256 equal blocks of the subnet, lowest address first. */
+  cells: IpamHeatCell[];
+  children: IpamSubnetNode[];
+}
+
+export interface IpamPlan {
+  subnets: IpamSubnetNode[];
+  /** Assigned addresses outside every planned subnet. */
+  unplanned_assigned: number;
+  generated_at: string;
+}
+
+export interface CgnatPortBlock {
+  id: string;
+  router_id: string | null;
+  router_name: string | null;
+  public_ip: string;
+  port_start: number;
+  port_end: number;
+  private_ip: string;
+  pppoe_account_id: string | null;
+  customer_id: string | null;
+  /** PPPoE username and customer name as they were at import. */
+  username: string | null;
+  customer_name: string | null;
+  assigned_at: string;
+  released_at: string | null;
+}
+
+export interface CgnatLogEntry {
+  public_ip: string;
+  port_start: number;
+  port_end: number;
+  private_ip: string;
+  assigned_at: string;
+  released_at?: string | null;
+  /** Matched before the private IP when present. */
+  username?: string | null;
+}
+
+export interface ImportCgnatLogRequest {
+  router_id?: string | null;
+  entries: CgnatLogEntry[];
+}
+
+export interface CgnatImportResult {
+  imported: number;
+  updated: number;
+  unmatched: number;
+  skipped: { index: number; reason: string }[];
+}
+
+export interface CgnatLookupRequest {
+  public_ip: string;
+  port: number;
+  /** RFC 3339 timestamp of the reported traffic. */
+  at: string;
+  /** Case or ticket number, kept in the audit log. */
+  reference?: string;
+}
+
+export type LegacyImportSourceCode = 'phpnuxbill' | 'mikbill' | 'csv';
+
+export interface LegacyImportSource {
+  code: LegacyImportSourceCode;
+  name: string;
+  files: {
+    /** Request field without `_csv`. */
+    file: 'customers' | 'packages' | 'secrets' | 'balances';
+    /** Table or export of the source system that goes there. */
+    table: string;
+    /** Columns read; `a / b` takes the first one present. */
+    columns: string[];
+  }[];
+}
+
+export interface LegacyImportRequest {
+  source: LegacyImportSourceCode;
+  /** CSV text of each export. */
+  customers_csv?: string;
+  packages_csv?: string;
+  secrets_csv?: string;
+  balances_csv?: string;
+  /** Router the PPPoE secrets belong to; without it secrets are skipped. */
+  router_id?: string;
+  /** Write the data. Without it only the validation report is returned. */
+  commit?: boolean;
+}
+
+export interface LegacyImportIssue {
+  file: string;
+  /** CSV line, the header being 1; 0 for the file as a whole. */
+  line: number;
+  severity: 'error' | 'warning';
+  message: string;
+}
+
+export interface LegacyImportCounts {
+  customers: number;
+  packages: number;
+  subscriptions: number;
+  secrets: number;
+  invoices: number;
+}
+
+export interface LegacyImportReport {
+  source: LegacyImportSourceCode;
+  committed: boolean;
+  created: LegacyImportCounts;
+  existing: LegacyImportCounts;
+  unpaid_total: number;
+  currency_code: string;
+  issues: LegacyImportIssue[];
+}
+
+export interface TenantApiKey {
+  id: string;
+  name: string;
+  /** Start of the key, to tell keys apart. */
+  key_prefix: string;
+  /** User the key acts as; requests have their permissions. */
+  user_id: string;
+  user_name: string | null;
+  expires_at: string | null;
+  last_used_at: string | null;
+  revoked_at: string | null;
+  created_at: string;
+}
+
+export interface CreateApiKeyRequest {
+  name: string;
+  /** Never expires when absent. */
+  expires_at?: string;
+}
+
+export interface CreatedApiKey extends TenantApiKey {
+  /** Send as `Authorization: Bearer <token>`. Not retrievable later. */
+  token: string;
+}
+
+export interface TenantWebhook {
+  id: string;
+  url: string;
+  description: string | null;
+  /**
+   * Event types sent; empty sends all of them. Besides the real-time events there are
+   * `customer.created`, `invoice.paid`, `router.offline` and `ticket.created`.
+   */
+  event_types: string[];
+  is_active: boolean;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface CreateWebhookRequest {
+  url: string;
+  description?: string;
+  event_types?: string[];
+}
+
+export interface UpdateWebhookRequest {
+  url?: string;
+  description?: string;
+  event_types?: string[];
+  is_active?: boolean;
+}
+
+export interface CreatedWebhook extends TenantWebhook {
+  /** Signing secret for `X-Event-Signature`. Not retrievable later. */
+  secret: string;
+}
+
+export interface WebhookDelivery {
+  id: string;
+  webhook_id: string;
+  event_id: string;
+  event_type: string;
+  payload: string;
+  /** `failed` once retries are used up. */
+  status: 'pending' | 'delivered' | 'failed';
+  attempts: number;
+  response_status: number | null;
+  duration_ms: number | null;
+  last_error: string | null;
+  next_attempt_at: string;
+  delivered_at: string | null;
+  created_at: string;
+}
+
+export interface WebhookDeliveryQuery {
+  webhook_id?: string;
+  status?: WebhookDelivery['status'];
+  /** 50 by default, at most 200. */
+  limit?: number;
+}
+
+export interface ApiKeyRateLimit {
+  api_key_id: string;
+  name: string;
+  limit: number;
+  used: number;
+  remaining: number;
+  window_secs: number;
+  reset_in_secs: number;
+}
+
+export interface IntegrationsOverview {
+  api_keys: TenantApiKey[];
+  webhooks: TenantWebhook[];
+  recent_deliveries: WebhookDelivery[];
+  rate_limits: ApiKeyRateLimit[];
+}
+
+export type OnboardingStepStatus = 'done' | 'skipped' | 'pending';
+
+export interface OnboardingStep {
+  step: 'smtp' | 'router' | 'packages' | 'payment_gateway' | 'customers';
+  status: OnboardingStepStatus;
+  completed_at: string | null;
+}
+
+export interface OnboardingProgress {
+  steps: OnboardingStep[];
+  completed: number;
+  total: number;
+  percent: number;
+  next_step: OnboardingStep['step'] | null;
+}
+
+export type MonitoredDeviceType = 'olt' | 'switch' | 'router' | 'other';
+
+/** A device the NOC poller watches; `routeros` ones mirror their router. */
+export interface MonitoredDevice {
+  id: string;
+  tenant_id: string;
+  name: string;
+  host: string;
+  device_type: MonitoredDeviceType;
+  protocol: 'snmp' | 'routeros';
+  router_id: string | null;
+  snmp_version: 'v2c' | 'v3' | null;
+  snmp_port: number;
+  snmp_username: string | null;
+  snmp_auth_protocol: 'md5' | 'sha1' | null;
+  snmp_priv_protocol: 'des' | 'aes128' | null;
+  cpu_oid: string | null;
+  enabled: boolean;
+  maintenance_until: string | null;
+  is_online: boolean;
+  last_seen_at: string | null;
+  latency_ms: number | null;
+  last_error: string | null;
+  sys_name: string | null;
+  sys_descr: string | null;
+  uptime_seconds: number | null;
+  cpu_load: number | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface MonitoredDeviceSnmpSettings {
+  snmp_version?: 'v2c' | 'v3';
+  snmp_port?: number;
+  snmp_community?: string;
+  snmp_username?: string;
+  snmp_auth_protocol?: 'md5' | 'sha1' | '';
+  snmp_auth_password?: string;
+  snmp_priv_protocol?: 'des' | 'aes128' | '';
+  snmp_priv_password?: string;
+  /** Vendor OID answering the CPU load in percent; empty clears it. */
+  cpu_oid?: string;
+  enabled?: boolean;
+}
+
+export interface CreateMonitoredDeviceRequest extends MonitoredDeviceSnmpSettings {
+  name: string;
+  protocol: MonitoredDevice['protocol'];
+  /** Required for `snmp`. */
+  host?: string;
+  /** Required for `routeros`. */
+  router_id?: string;
+  device_type?: MonitoredDeviceType;
+  maintenance_until?: string | null;
+}
+
+/** Empty secrets keep the stored ones. */
+export interface UpdateMonitoredDeviceRequest extends MonitoredDeviceSnmpSettings {
+  name?: string;
+  host?: string;
+  device_type?: MonitoredDeviceType;
+  maintenance_until?: string | null;
+}
+
+export interface MonitoredDeviceTestResult {
+  ok: boolean;
+  sys_name: string | null;
+  sys_descr: string | null;
+  latency_ms: number | null;
+  error: string | null;
+}
+
+export interface MikrotikIncidentImpact {
+  incident_id: string;
+  subscription_id: string;
+  customer_id: string;
+  customer_name: string;
+  location_id: string;
+  location_label: string | null;
+  /** Service zone containing the location, else its city. */
+  area: string;
+  /** How the subscription was traced to the incident's router. */
+  via: 'router' | 'pppoe' | 'topology';
+}
+
+/** Entry of an incident's `affected_areas`, largest first. */
+export interface MikrotikIncidentArea {
+  area: string;
+  subscriptions: number;
+  customers: number;
+}
+
+export interface UplinkInterface {
+  id: string;
+  tenant_id: string;
+  router_id: string;
+  router_name: string | null;
+  interface_name: string;
+  label: string | null;
+  /** Bandwidth bought for the link, the same in both directions. */
+  capacity_mbps: number;
+  warning_pct: number;
+  critical_pct: number;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface UpsertUplinkInterfaceRequest {
+  router_id: string;
+  interface_name: string;
+  label?: string | null;
+  capacity_mbps: number;
+  /** Defaults to 70. */
+  warning_pct?: number;
+  /** Defaults to 90. */
+  critical_pct?: number;
+}
+
+export interface UplinkDailyPercentile {
+  /** UTC date, `YYYY-MM-DD`. */
+  day: string;
+  samples: number;
+  rx_p95_bps: number | null;
+  tx_p95_bps: number | null;
+  utilization_pct: number | null;
+}
+
+export type UplinkCapacityLevel = 'ok' | 'warning' | 'critical' | 'no_data';
+
+export interface UplinkCapacityRow extends UplinkInterface {
+  samples: number;
+  rx_p95_bps: number | null;
+  tx_p95_bps: number | null;
+  rx_peak_bps: number | null;
+  tx_peak_bps: number | null;
+  /** 95th percentile of the busier direction against the capacity. */
+  utilization_pct: number | null;
+  level: UplinkCapacityLevel;
+  /** Trailing days whose own 95th percentile is over the warning threshold. */
+  days_over_warning: number;
+  /** Days until the daily trend reaches the capacity; null when flat or falling. */
+  days_to_capacity: number | null;
+  daily: UplinkDailyPercentile[];
+}
+
+export interface UplinkCapacityReport {
+  from: string;
+  to: string;
+  days: number;
+  /** Busiest first. */
+  uplinks: UplinkCapacityRow[];
+  generated_at: string;
+}
+
+export type FiberPointKind = 'odc' | 'odp';
+
+/** An ODC/ODP cabinet, mirrored as an `odc`/`odp` node in the topology. */
+export interface FiberPoint {
+  id: string;
+  tenant_id: string;
+  kind: FiberPointKind;
+  code: string;
+  name: string;
+  status: 'active' | 'inactive' | 'maintenance' | 'planning';
+  lat: number;
+  lng: number;
+  /** Feeding ODC of an ODP. */
+  parent_id: string | null;
+  router_id: string | null;
+  port_capacity: number | null;
+  /** Links from the point's node to customer premises. */
+  ports_used: number;
+  node_id: string | null;
+  notes: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface CreateFiberPointRequest {
+  kind: FiberPointKind;
+  code: string;
+  name: string;
+  status?: FiberPoint['status'];
+  lat: number;
+  lng: number;
+  parent_id?: string | null;
+  router_id?: string | null;
+  port_capacity?: number | null;
+  notes?: string | null;
+}
+
+/** Omitted fields are kept; `null` clears the nullable ones. */
+export type UpdateFiberPointRequest = Partial<Omit<CreateFiberPointRequest, 'kind'>>;
+
+export type AssetMapLayer = 'router' | 'customer_location' | 'fiber_point' | 'link' | 'feeder';
+
+export interface AssetMapFeature {
+  type: 'Feature';
+  /** `<layer>:<id>`; link and feeder `from`/`to` properties refer to these. */
+  id: string;
+  geometry: { type: string; coordinates: unknown };
+  properties: { layer: AssetMapLayer; id: string } & Record<string, any>;
+}
+
+export interface AssetMapFeatureCollection {
+  type: 'FeatureCollection';
+  features: AssetMapFeature[];
+}
+
+export interface FieldSyncPullRequest {
+  /** `next_cursor` of the previous pull; omit for a full download. */
+  cursor?: string;
+  limit?: number;
+  /** Work orders held on the device, to learn which ones to drop. */
+  known_work_order_ids?: string[];
+}
+
+export interface FieldSyncChanges {
+  work_orders: InstallationWorkOrderView[];
+  checklists: WorkOrderChecklist[];
+  customers: Customer[];
+  locations: CustomerLocation[];
+  /** Reassigned or closed work orders; only filled on the last page. */
+  revoked_work_order_ids: string[];
+  next_cursor: string;
+  has_more: boolean;
+  server_time: string;
+}
+
+export type FieldSyncMutationKind =
+  | 'note'
+  | 'start'
+  | 'hold'
+  | 'complete'
+  | 'check_in'
+  | 'check_out'
+  | 'checklist_item'
+  | 'location';
+
+export interface FieldSyncMutation {
+  /** Generated on the device; a mutation is applied at most once. */
+  client_mutation_id: string;
+  kind: FieldSyncMutationKind;
+  work_order_id?: string;
+  item_id?: string;
+  location_id?: string;
+  /** `updated_at` of the record when the change was made offline. */
+  base_updated_at?: string;
+  payload?: Record<string, unknown>;
+}
+
+export interface FieldSyncMutationResult {
+  client_mutation_id: string;
+  status: 'applied' | 'conflict' | 'rejected';
+  error: string | null;
+  /** The updated record, or the server copy on conflict. */
+  data: unknown;
+  replayed: boolean;
+}
+
+export interface FieldSyncPushResponse {
+  results: FieldSyncMutationResult[];
+  server_time: string;
+}
+
+export interface Invoice {
+  id: string;
+  tenant_id?: string;
+  invoice_number: string;
+  amount: number;
+  currency_code?: string;
+  base_currency_code?: string;
+  fx_rate?: number | null;
+  fx_source?: string | null;
+  fx_fetched_at?: string | null;
+  status: string;
+  description: string | null;
+  due_date: string;
+  paid_at: string | null;
+  payment_method: string | null;
+  external_id?: string | null;
+  merchant_id?: string | null;
+  proof_attachment?: string | null;
+  rejection_reason?: string | null;
+  created_at?: string;
+  updated_at?: string;
+}
+
+export interface PlanUpgradeQuote {
+  current_plan_id?: string | null;
+  current_plan_name?: string | null;
+  target_plan_id: string;
+  target_plan_name: string;
+  billing_cycle: 'monthly' | 'yearly';
+  currency_code: string;
+  plan_price: number;
+  credit: number;
+  amount_due: number;
+  remaining_days: number;
+  period_days: number;
+}
+
+export interface PlanUpgradeCheckout {
+  quote: PlanUpgradeQuote;
+  invoice: Invoice;
+  gateway: 'midtrans' | 'manual';
+  snap_token?: string | null;
+}
+
+export interface CustomerPortalCheckoutResponse {
+  subscription: CustomerSubscription;
+  invoice: Invoice;
+}
+
+export interface CustomerPortalOrderRequestResponse {
+  subscription: CustomerSubscription;
+  work_order: InstallationWorkOrderView;
+}
+
+export interface CustomerPortalInstallationTrackerResponse {
+  subscription: CustomerSubscriptionView;
+  work_order: InstallationWorkOrderView | null;
+  reschedule_request: WorkOrderRescheduleRequestView | null;
+}
+
+export interface PppoeAccountPublic {
+  id: string;
+  tenant_id: string;
+  router_id: string;
+  customer_id: string;
+  location_id: string;
+  username: string;
+  package_id: string | null;
+  profile_id: string | null;
+  router_profile_name: string | null;
+  remote_address: string | null;
+  address_pool: string | null;
+  disabled: boolean;
+  comment: string | null;
+  router_present: boolean;
+  router_secret_id: string | null;
+  last_sync_at: string | null;
+  last_error: string | null;
+  /** Set while the account runs on its package's fair-usage speeds. */
+  fup_throttled_at: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+/** A customer's PPPoE traffic in one hour or day, seen from the subscriber. */
+export interface UsagePoint {
+  ts: string;
+  upload_bytes: number;
+  download_bytes: number;
+}
+
+export interface PppoeAccountUsage {
+  account_id: string;
+  username: string;
+  package_name: string | null;
+  period_start: string;
+  upload_bytes: number;
+  download_bytes: number;
+  quota_gb: number | null;
+  throttled: boolean;
+}
+
+export interface CustomerUsage {
+  customer_id: string;
+  days: number;
+  bucket: 'hour' | 'day';
+  points: UsagePoint[];
+  /** Current usage period of each of the customer's accounts. */
+  accounts: PppoeAccountUsage[];
+}
+
+/** MikroTik burst settings; speeds in kbps, priority 1 (highest) to 8. */
+export interface BandwidthBurst {
+  burst_limit_up_kbps: number | null;
+  burst_limit_down_kbps: number | null;
+  burst_threshold_up_kbps: number | null;
+  burst_threshold_down_kbps: number | null;
+  burst_time_secs: number | null;
+  burst_priority: number | null;
+}
+
+/** Past `fup_quota_gb` in a usage period the subscriber gets the `fup_rate_*` speeds (kbps). */
+export interface FairUsagePolicy {
+  fup_quota_gb: number | null;
+  fup_rate_up_kbps: number | null;
+  fup_rate_down_kbps: number | null;
+}
+
+export interface IspPackageBandwidth extends BandwidthBurst, FairUsagePolicy {
+  rate_limit_up_kbps: number | null;
+  rate_limit_down_kbps: number | null;
+}
+
+export interface IspPackage extends IspPackageBandwidth {
+  id: string;
+  tenant_id: string;
+  service_type: string;
+  name: string;
+  description: string | null;
+  features: string[];
+  is_active: boolean;
+  /** Only sold in service zones that have an active offer for it. */
+  zone_restricted: boolean;
   price_monthly: number;
   price_yearly: number;
+  /** Packages combined by a `bundle`, the internet package first. */
+  component_ids: string[];
   created_at: string;
   updated_at: string;
 }
 
-export interface IspPackageRouterMappingView {
+/** List prices of a package from `effective_from` until the next entry. */
+export interface IspPackagePriceHistory {
+  id: string;
+  tenant_id: string;
+  package_id: string;
+  price_monthly: number;
+  price_yearly: number;
+  effective_from: string;
+  changed_by: string | null;
+  changed_by_name: string | null;
+  created_at: string;
+}
+
+/** Burst fields override the package values on this router; `null` keeps them. */
+export interface IspPackageRouterMappingView extends BandwidthBurst {
   id: string;
   tenant_id: string;
   router_id: string;
@@ -481,6 +1807,9 @@ export interface Announcement {
   body: string;
   severity: string;
   audience: string;
+  audience_targets?: AudienceTargets | null;
+  /** Locale -> variant; `title`/`body` are the default language. */
+  translations?: Record<string, AnnouncementTranslation> | null;
   mode: 'post' | 'banner';
   format: 'plain' | 'markdown' | 'html';
   deliver_in_app: boolean;
@@ -489,10 +1818,68 @@ export interface Announcement {
   starts_at: string;
   ends_at: string | null;
   notified_at: string | null;
+  recurrence?: AnnouncementRecurrence | null;
+  /** Occurrences published so far; dismissals apply to one occurrence. */
+  occurrence?: number;
+  next_occurrence_at?: string | null;
+  occurrence_ends_at?: string | null;
   created_at: string;
   updated_at: string;
 }
 
+/** Repeat rule, evaluated in the tenant's `app_timezone`. */
+export interface AnnouncementRecurrence {
+  frequency: 'daily' | 'weekly' | 'monthly';
+  /** Local time, HH:MM. */
+  time: string;
+  /** Weekly: 0 (Sunday) to 6. */
+  weekdays?: number[];
+  /** Monthly: either a day of the month... */
+  day_of_month?: number | null;
+  /** ...or the nth (1-5, -1 for last) `weekday` of the month. */
+  week_of_month?: number | null;
+  weekday?: number | null;
+  /** How long each occurrence stays up; until dismissed when unset. */
+  duration_minutes?: number | null;
+}
+
+/** Reach of one occurrence; `viewed` includes readers who dismissed it. */
+export interface AnnouncementStats {
+  announcement_id: string;
+  occurrence: number;
+  delivered: number;
+  viewed: number;
+  dismissed: number;
+  view_rate: number | null;
+  segment_by: 'role' | 'tenant';
+  segments: AnnouncementSegmentStats[];
+}
+
+export interface AnnouncementSegmentStats {
+  key: string;
+  label: string;
+  delivered: number;
+  viewed: number;
+  dismissed: number;
+  view_rate: number | null;
+}
+
+export interface AnnouncementTranslation {
+  title: string;
+  body: string;
+}
+
+/** Selectors for `audience: 'segment'`; a user matching any of them is included. */
+export interface AudienceTargets {
+  role_ids?: string[];
+  package_ids?: string[];
+  customer_tags?: string[];
+  router_ids?: string[];
+  /** Global announcements only. */
+  plan_ids?: string[];
+  tenant_ids?: string[];
+}
+
 export interface CreateAnnouncementDto {
   scope?: 'tenant' | 'global';
   tenant_id?: string | null;
@@ -500,7 +1887,9 @@ export interface CreateAnnouncementDto {
   title: string;
   body: string;
   severity?: 'info' | 'success' | 'warning' | 'error';
-  audience?: 'all' | 'admins';
+  audience?: 'all' | 'admins' | 'segment';
+  audience_targets?: AudienceTargets | null;
+  translations?: Record<string, AnnouncementTranslation> | null;
   mode?: 'post' | 'banner';
   format?: 'plain' | 'markdown' | 'html';
   deliver_in_app?: boolean;
@@ -508,6 +1897,7 @@ export interface CreateAnnouncementDto {
   deliver_email_force?: boolean;
   starts_at?: string | null;
   ends_at?: string | null;
+  recurrence?: AnnouncementRecurrence | null;
 }
 
 export interface UpdateAnnouncementDto {
@@ -515,7 +1905,9 @@ export interface UpdateAnnouncementDto {
   title?: string;
   body?: string;
   severity?: 'info' | 'success' | 'warning' | 'error';
-  audience?: 'all' | 'admins';
+  audience?: 'all' | 'admins' | 'segment';
+  audience_targets?: AudienceTargets | null;
+  translations?: Record<string, AnnouncementTranslation> | null;
   mode?: 'post' | 'banner';
   format?: 'plain' | 'markdown' | 'html';
   deliver_in_app?: boolean;
@@ -523,12 +1915,15 @@ export interface UpdateAnnouncementDto {
   deliver_email_force?: boolean;
   starts_at?: string | null;
   ends_at?: string | null;
+  /** `null` stops the series; omit to keep it. */
+  recurrence?: AnnouncementRecurrence | null;
 }
 
 export interface TenantSubscriptionDetails {
   plan_name: string;
   plan_slug: string;
   status: string;
+  trial_ends_at: string | null;
   current_period_end: string | null;
   storage_usage: number;
   storage_limit: number | null;
@@ -536,6 +1931,43 @@ export interface TenantSubscriptionDetails {
   member_limit: number | null;
 }
 
+export interface UsageMeter {
+  metric: 'users' | 'customers' | 'routers' | 'storage' | 'emails_month';
+  unit: 'count' | 'bytes';
+  used: number;
+  limit: number | null;
+  status: 'ok' | 'warning' | 'exceeded';
+}
+
+export interface TenantUsage {
+  plan_name: string;
+  plan_slug: string;
+  enforcement: 'hard' | 'soft';
+  period: string;
+  meters: UsageMeter[];
+}
+
+export interface RevenueAnalytics {
+  currency_code: string;
+  paid: number;
+  paid_invoices: number;
+  outstanding: number;
+  outstanding_invoices: number;
+}
+
+/** Tenant dashboard figures in one payload (cached server-side for a minute). */
+export interface TenantAnalytics {
+  tenant_id: string;
+  period: string;
+  customers: { total: number; active: number };
+  subscribers: { active_subscriptions: number; online: number };
+  incidents: { open: number; critical: number };
+  revenue: RevenueAnalytics[];
+  tickets: { open: number; created_this_month: number; closed_this_month: number };
+  storage: { used_bytes: number; limit_bytes: number | null };
+  generated_at: string;
+}
+
 export interface BankAccount {
   id: string;
   bank_name: string;
@@ -555,6 +1987,85 @@ export interface Notification {
   action_url: string | null;
   is_read: boolean;
   created_at: string;
+  archived_at: string | null;
+}
+
+export interface NotificationQuietHours {
+  enabled: boolean;
+  /** HH:MM, local */
+  start: string;
+  end: string;
+  /** Explicit IANA zone; null follows the tenant timezone */
+  timezone: string | null;
+  effective_timezone: string;
+  /** Set while quiet hours are in effect */
+  resumes_at: string | null;
+}
+
+/** Per-device opt-outs for native desktop notifications. */
+export interface DesktopNotificationPrefs {
+  enabled: boolean;
+  incidents: boolean;
+  payments: boolean;
+  work_orders: boolean;
+  other: boolean;
+}
+
+export interface NotificationFilter {
+  category?: string;
+  notificationType?: Notification['notification_type'];
+  isRead?: boolean;
+  /** `true` lists only archived notifications */
+  archived?: boolean;
+  /** RFC 3339 timestamps */
+  from?: string;
+  to?: string;
+  search?: string;
+}
+
+export type NotificationDeliveryChannel = 'in_app' | 'email' | 'push';
+export type NotificationDeliveryStatus = 'queued' | 'sent' | 'delivered' | 'failed' | 'read';
+
+export interface NotificationDelivery {
+  id: string;
+  notification_id: string;
+  tenant_id: string | null;
+  user_id: string;
+  user_email: string | null;
+  category: string;
+  notification_type: string;
+  title: string;
+  channel: NotificationDeliveryChannel;
+  status: NotificationDeliveryStatus;
+  error: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface NotificationDeliveryStat {
+  channel: NotificationDeliveryChannel;
+  status: NotificationDeliveryStatus;
+  count: number;
+}
+
+export interface NotificationDeliveryReport {
+  /** Totals per channel/status over the whole filtered range */
+  summary: NotificationDeliveryStat[];
+  data: NotificationDelivery[];
+  total: number;
+  page: number;
+  per_page: number;
+}
+
+export interface NotificationDeliveryFilter {
+  channel?: NotificationDeliveryChannel;
+  status?: NotificationDeliveryStatus;
+  category?: string;
+  userId?: string;
+  notificationId?: string;
+  /** RFC 3339 timestamps */
+  from?: string;
+  to?: string;
 }
 
 export interface BackupRecord {
@@ -564,6 +2075,78 @@ export interface BackupRecord {
   created_at: string;
   backup_type: string;
   tenant_id?: string;
+  pinned: boolean;
+}
+
+export interface RestoreIssue {
+  severity: 'error' | 'warning';
+  table?: string;
+  message: string;
+}
+
+export interface RestoreValidationReport {
+  ok: boolean;
+  manifest?: {
+    format_version: number;
+    scope: 'global' | 'tenant' | 'tenant_export';
+    tenant_id?: string;
+    created_at: string;
+    database: string;
+    app_version: string;
+    schema_version?: number | null;
+    tables: Record<string, { rows: number; sha256: string }>;
+  };
+  current_schema_version?: number;
+  tables: { name: string; rows: number; checksum_ok?: boolean; will_restore: boolean }[];
+  issues: RestoreIssue[];
+}
+
+export interface ScratchRestoreReport {
+  schema: string;
+  validation: RestoreValidationReport;
+  tables: { name: string; rows_in_archive: number; rows_restored: number }[];
+}
+
+export interface RemoteBackupRecord {
+  name: string;
+  key: string;
+  size: number;
+  created_at: string;
+  backup_type: string;
+  tenant_id?: string;
+}
+
+export interface DestinationSchedule {
+  destination: 'global' | 'tenant' | 'offsite';
+  enabled: boolean;
+  expression: string | null;
+  source: 'cron' | 'legacy' | 'after_backup' | 'invalid';
+  error: string | null;
+  last_run: string | null;
+  next_runs: string[];
+}
+
+export interface BackupSchedulePreview {
+  timezone: string;
+  destinations: DestinationSchedule[];
+}
+
+export interface CronPreview {
+  expression: string;
+  timezone: string;
+  next_runs: string[];
+}
+
+export type BackupRunTarget = 'global' | 'tenants' | 'offsite';
+
+export interface BackupRunProgress {
+  run_id: string;
+  target: BackupRunTarget;
+  message: string;
+  completed: number;
+  total: number;
+  done: boolean;
+  error: string | null;
 }
 
 export interface NotificationPreference {
@@ -575,6 +2158,277 @@ export interface NotificationPreference {
   updated_at: string;
 }
 
+export interface NotificationTemplate {
+  code: string;
+  category: 'network' | 'billing' | 'operations' | string;
+  description: string;
+  title: string;
+  body: string;
+  default_title: string;
+  default_body: string;
+  variables: string[];
+  is_customized: boolean;
+  updated_at: string | null;
+}
+
+export interface RenderedNotification {
+  title: string;
+  body: string;
+}
+
+export type EmailTemplateCode =
+  | 'verification'
+  | 'password_reset'
+  | 'invoice'
+  | 'reminder'
+  | 'alert';
+
+export interface EmailTemplateVariable {
+  name: string;
+  description: string;
+  /** Value used by previews and test sends */
+  sample: string;
+}
+
+export interface EmailTemplate {
+  code: EmailTemplateCode | string;
+  description: string;
+  subject: string;
+  body_html: string;
+  /** null: the text part is derived from the HTML */
+  body_text: string | null;
+  default_subject: string;
+  default_body_html: string;
+  default_body_text: string;
+  variables: EmailTemplateVariable[];
+  is_customized: boolean;
+  updated_at: string | null;
+}
+
+export interface EmailTemplateDraft {
+  subject?: string;
+  body_html?: string;
+  body_text?: string;
+}
+
+export interface RenderedEmail {
+  subject: string;
+  html: string;
+  text: string;
+}
+
+export interface DkimDnsRecord {
+  record_type: 'TXT';
+  name: string;
+  value: string;
+  /** `value` in pieces of at most 255 characters. */
+  chunks: string[];
+}
+
+export interface EmailDkimStatus {
+  configured: boolean;
+  enabled: boolean;
+  domain: string | null;
+  selector: string | null;
+  algorithm: string;
+  dns_record: DkimDnsRecord | null;
+  from_domain: string | null;
+  domain_matches_from: boolean;
+  provider: string;
+  signing_active: boolean;
+  instructions: string[];
+  created_at: string | null;
+  updated_at: string | null;
+}
+
+export type NotificationSeverity = 'info' | 'warning' | 'critical';
+export type NotificationRoutingChannel = 'in_app' | 'email' | 'telegram';
+
+export interface NotificationRoutingRule {
+  id: string;
+  name: string;
+  priority: number;
+  is_enabled: boolean;
+  category: 'network' | 'billing' | 'operations' | string | null;
+  min_severity: NotificationSeverity;
+  time_window: 'any' | 'business_hours' | 'after_hours';
+  channels: NotificationRoutingChannel[];
+  /** `default`, `user:<id>` or `role:<id>` */
+  recipients: string[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface NotificationRoutingRuleInput {
+  name: string;
+  priority?: number;
+  isEnabled?: boolean;
+  category?: string | null;
+  minSeverity?: NotificationSeverity;
+  timeWindow?: NotificationRoutingRule['time_window'];
+  channels: NotificationRoutingChannel[];
+  recipients?: string[];
+}
+
+export interface NotificationRoutingEvaluation {
+  rule: NotificationRoutingRule | null;
+  is_business_hours: boolean;
+  channels: string[];
+}
+
+export type StatusComponentKind = 'api' | 'billing' | 'network' | 'custom';
+export type StatusComponentStatus =
+  | 'operational'
+  | 'maintenance'
+  | 'degraded'
+  | 'partial_outage'
+  | 'major_outage';
+export type StatusPostKind = 'incident' | 'maintenance';
+export type StatusPostImpact = 'none' | 'minor' | 'major' | 'critical';
+export type StatusPostStatus =
+  | 'investigating'
+  | 'identified'
+  | 'monitoring'
+  | 'resolved'
+  | 'scheduled'
+  | 'in_progress'
+  | 'completed';
+
+export interface StatusComponent {
+  id: string;
+  name: string;
+  description: string | null;
+  kind: StatusComponentKind;
+  /** Routers of a network component; empty means all of the tenant's routers. */
+  router_ids: string[];
+  status_override: StatusComponentStatus | null;
+  position: number;
+  is_visible: boolean;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface StatusComponentInput {
+  name: string;
+  description?: string | null;
+  kind: StatusComponentKind;
+  routerIds?: string[];
+  statusOverride?: StatusComponentStatus | null;
+  position?: number;
+  isVisible?: boolean;
+}
+
+export interface StatusPostUpdate {
+  id: string;
+  post_id: string;
+  status: StatusPostStatus;
+  message: string;
+  created_at: string;
+}
+
+export interface StatusPost {
+  id: string;
+  kind: StatusPostKind;
+  title: string;
+  impact: StatusPostImpact;
+  status: StatusPostStatus;
+  component_ids: string[];
+  scheduled_start: string | null;
+  scheduled_end: string | null;
+  resolved_at: string | null;
+  updates: StatusPostUpdate[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface StatusPostInput {
+  kind: StatusPostKind;
+  title: string;
+  message: string;
+  impact?: StatusPostImpact;
+  status?: StatusPostStatus;
+  componentIds?: string[];
+  scheduledStart?: string | null;
+  scheduledEnd?: string | null;
+}
+
+export interface PublicStatusPage {
+  tenant_name: string;
+  slug: string;
+  status: StatusComponentStatus;
+  components: {
+    id: string;
+    name: string;
+    description: string | null;
+    kind: StatusComponentKind;
+    status: StatusComponentStatus;
+  }[];
+  incidents: StatusPost[];
+  maintenance: StatusPost[];
+  generated_at: string;
+}
+
+export type UpdateChannel = 'stable' | 'beta';
+
+export interface ClientCompatibility {
+  server_version: string;
+  client_version: string | null;
+  /** `null` when any client version is accepted. */
+  minimum_version: string | null;
+  supported: boolean;
+  update_channel: UpdateChannel;
+}
+
+export interface AvailableUpdate {
+  version: string;
+  current_version: string;
+  channel: UpdateChannel;
+  date: string | null;
+  notes: string | null;
+}
+
+export type ReportKind = 'revenue' | 'aging' | 'sla' | 'incidents';
+export type ReportFormat = 'csv' | 'pdf';
+export type ReportCadence = 'daily' | 'weekly' | 'monthly';
+
+export interface ReportSchedule {
+  id: string;
+  tenant_id: string;
+  name: string;
+  report: ReportKind;
+  format: ReportFormat;
+  cadence: ReportCadence;
+  /** Local hour (tenant timezone) the report is sent. */
+  send_hour: number;
+  recipients: string[];
+  enabled: boolean;
+  next_run_at: string;
+  last_run_at: string | null;
+  last_status: 'sent' | 'failed' | null;
+  last_error: string | null;
+  created_by: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface ReportScheduleInput {
+  name: string;
+  report: ReportKind;
+  format: ReportFormat;
+  cadence: ReportCadence;
+  sendHour?: number;
+  recipients: string[];
+  enabled?: boolean;
+}
+
+export interface ReportSendResult {
+  filename: string;
+  period_start: string;
+  period_end: string;
+  rows: number;
+  recipients: number;
+}
+
 export interface EmailOutboxItem {
   id: string;
   tenant_id: string | null;
@@ -585,6 +2439,8 @@ export interface EmailOutboxItem {
   status: 'queued' | 'sending' | 'sent' | 'failed' | string;
   attempts: number;
   max_attempts: number;
+  /** 0 = priority (OTP, password reset), 5 = normal, 9 = bulk. */
+  priority: number;
   scheduled_at: string;
   last_error: string | null;
   sent_at: string | null;
@@ -600,6 +2456,98 @@ export interface EmailOutboxStats {
   failed: number;
 }
 
+export type EmailSuppressionReason = 'hard_bounce' | 'soft_bounce' | 'complaint' | 'manual';
+
+export interface EmailSuppression {
+  id: string;
+  tenant_id: string | null;
+  email: string;
+  reason: EmailSuppressionReason | string;
+  source: 'smtp' | 'ses' | 'sendgrid' | 'mailgun' | 'manual' | string;
+  detail: string | null;
+  bounce_count: number;
+  /** Soft bounces are listed before they reach the limit but do not block mail yet. */
+  is_suppressed: boolean;
+  last_event_at: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface FeatureFlag {
+  key: string;
+  description?: string | null;
+  /** Kill switch: false turns the flag off everywhere, overrides included. */
+  enabled: boolean;
+  rollout_percentage: number;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface FeatureFlagOverride {
+  flag_key: string;
+  tenant_id: string;
+  enabled: boolean;
+  created_at: string;
+}
+
+export interface FeatureFlagWithOverrides extends FeatureFlag {
+  overrides: FeatureFlagOverride[];
+}
+
+export interface WhatsappTemplate {
+  id: string;
+  tenant_id: string;
+  name: string;
+  language: string;
+  body: string;
+  is_active: boolean;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface UpsertWhatsappTemplate {
+  name: string;
+  language?: string;
+  body: string;
+  is_active?: boolean;
+}
+
+export type WhatsappEvent = 'invoice_created' | 'invoice_reminder' | 'outage_notice';
+
+export interface WhatsappRoute {
+  event: WhatsappEvent;
+  template_name: string | null;
+  variables: string[];
+}
+
+export interface WhatsappMessage {
+  id: string;
+  tenant_id: string;
+  event: WhatsappEvent | 'test' | string;
+  template_name: string;
+  to_phone: string;
+  customer_id: string | null;
+  provider: 'meta' | 'gateway' | string;
+  provider_message_id: string | null;
+  status: 'pending' | 'sent' | 'delivered' | 'read' | 'failed' | string;
+  error: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface TelegramLinkStatus {
+  bot_enabled: boolean;
+  bot_username: string | null;
+  linked: boolean;
+  telegram_username: string | null;
+  linked_at: string | null;
+  alert_incidents: boolean;
+  alert_payments: boolean;
+  link_code: string | null;
+  link_code_expires_at: string | null;
+  link_url: string | null;
+}
+
 export interface SmtpConnectionTestResult {
   ok: boolean;
   provider: string;
@@ -664,3 +2612,42 @@ export interface InvoiceReminderLogView {
   invoice_number: string | null;
   customer_name: string | null;
 }
+
+export type InvoicePrintKind = 'receipt' | 'invoice';
+
+export interface PrintResult {
+  printer: string;
+  bytes: number;
+}
+
+export interface DiagnosticsBundleSummary {
+  filename: string;
+  path: string;
+  size_bytes: number;
+}
+
+export interface TenantExportSummary {
+  filename: string;
+  path: string;
+  tenant_id: string;
+  tables: number;
+  rows: number;
+}
+
+export interface TenantImportOptions {
+  asCopy?: boolean;
+  name?: string;
+  slug?: string;
+}
+
+export interface TenantImportReport {
+  tenant_id: string;
+  source_tenant_id: string;
+  name: string;
+  slug: string;
+  users_created: number;
+  users_matched: number;
+  tables: { name: string; rows_in_archive: number; rows_imported: number }[];
+  warnings: string[];
+  validation: RestoreValidationReport;
+}
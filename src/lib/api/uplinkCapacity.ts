@@ -0,0 +1,22 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type { UplinkCapacityReport, UplinkInterface, UpsertUplinkInterfaceRequest } from './types';
+
+export const uplinkCapacity = {
+  uplinks: {
+    list: (): Promise<UplinkInterface[]> =>
+      safeInvoke('list_uplink_interfaces', { token: getTokenOrThrow() }),
+
+    create: (dto: UpsertUplinkInterfaceRequest): Promise<UplinkInterface> =>
+      safeInvoke('create_uplink_interface', { token: getTokenOrThrow(), ...dto }),
+
+    update: (id: string, dto: UpsertUplinkInterfaceRequest): Promise<UplinkInterface> =>
+      safeInvoke('update_uplink_interface', { token: getTokenOrThrow(), id, ...dto }),
+
+    delete: (id: string): Promise<void> =>
+      safeInvoke('delete_uplink_interface', { token: getTokenOrThrow(), id }),
+  },
+
+  /** 95th percentile utilization per uplink over the last `days` days (default 7). */
+  report: (params?: { days?: number }): Promise<UplinkCapacityReport> =>
+    safeInvoke('get_uplink_capacity_report', { token: getTokenOrThrow(), ...(params || {}) }),
+};
@@ -1,5 +1,5 @@
 import { getTokenOrThrow, safeInvoke } from './core';
-import type { PaginatedResponse, User, UserAddress } from './types';
+import type { PaginatedResponse, TrashItem, User, UserAddress, UserLocale } from './types';
 
 export const users = {
   list: (page?: number, perPage?: number): Promise<PaginatedResponse<User>> =>
@@ -32,6 +32,19 @@ export const users = {
   delete: (id: string): Promise<void> =>
     safeInvoke('delete_user', { token: getTokenOrThrow(), id }),
 
+  trash: (): Promise<TrashItem[]> =>
+    safeInvoke('list_deleted_users', { token: getTokenOrThrow() }),
+
+  restore: (id: string): Promise<User> =>
+    safeInvoke('restore_user', { token: getTokenOrThrow(), id }),
+
+  getMyLocale: (): Promise<UserLocale> =>
+    safeInvoke('get_my_locale', { token: getTokenOrThrow() }),
+
+  /** `null` falls back to the default locale. */
+  setMyLocale: (locale: string | null): Promise<UserLocale> =>
+    safeInvoke('set_my_locale', { token: getTokenOrThrow(), locale }),
+
   listMyAddresses: (): Promise<UserAddress[]> =>
     safeInvoke('list_my_addresses', { token: getTokenOrThrow() }),
 
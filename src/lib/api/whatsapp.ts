@@ -0,0 +1,42 @@
+import { getTokenOrThrow, safeInvoke } from './core';
+import type {
+  UpsertWhatsappTemplate,
+  WhatsappEvent,
+  WhatsappMessage,
+  WhatsappRoute,
+  WhatsappTemplate,
+} from './types';
+
+export const whatsapp = {
+  listTemplates: (): Promise<WhatsappTemplate[]> =>
+    safeInvoke('list_whatsapp_templates', { token: getTokenOrThrow() }),
+
+  createTemplate: (template: UpsertWhatsappTemplate): Promise<WhatsappTemplate> =>
+    safeInvoke('create_whatsapp_template', { token: getTokenOrThrow(), ...template }),
+
+  updateTemplate: (id: string, template: UpsertWhatsappTemplate): Promise<WhatsappTemplate> =>
+    safeInvoke('update_whatsapp_template', { token: getTokenOrThrow(), id, ...template }),
+
+  deleteTemplate: (id: string): Promise<void> =>
+    safeInvoke('delete_whatsapp_template', { token: getTokenOrThrow(), id }),
+
+  sendTest: (id: string, phone: string): Promise<WhatsappMessage> =>
+    safeInvoke('send_whatsapp_test', { token: getTokenOrThrow(), id, phone }),
+
+  listRoutes: (): Promise<WhatsappRoute[]> =>
+    safeInvoke('list_whatsapp_routes', { token: getTokenOrThrow() }),
+
+  setRoute: (event: WhatsappEvent, templateName: string | null): Promise<WhatsappRoute> =>
+    safeInvoke('set_whatsapp_route', {
+      token: getTokenOrThrow(),
+      event,
+      template_name: templateName,
+    }),
+
+  listMessages: (params?: { status?: string; limit?: number }): Promise<WhatsappMessage[]> =>
+    safeInvoke('list_whatsapp_messages', {
+      token: getTokenOrThrow(),
+      status: params?.status,
+      limit: params?.limit,
+    }),
+};
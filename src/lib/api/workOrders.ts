@@ -1,14 +1,38 @@
 import { getTokenOrThrow, safeInvoke } from './core';
 import type {
+  ConsumeWorkOrderMaterialsRequest,
+  CreateWorkOrderRequest,
+  InstallInventorySerialRequest,
   InstallationWorkOrderView,
+  InventoryMovement,
+  InventorySerial,
   TeamMember,
+  UpdateWorkOrderChecklistItemRequest,
+  UpsertWorkOrderTemplateRequest,
+  WorkOrderAgendaItem,
+  WorkOrderChecklist,
+  WorkOrderCompletionReport,
   WorkOrderRescheduleRequestView,
+  WorkOrderRoutePlan,
+  WorkOrderSignatureInput,
+  WorkOrderTemplate,
+  WorkOrderVisit,
 } from './types';
 
+type WorkOrderCheckPayload = {
+  latitude: number;
+  longitude: number;
+  accuracy_m?: number;
+  notes?: string;
+  /** Device time of the fix when the visit was recorded offline. */
+  recorded_at?: string;
+};
+
 export const workOrders = {
   list: (params?: {
     status?: string;
     assigned_to?: string;
+    work_type?: string;
     include_closed?: boolean;
     limit?: number;
   }): Promise<InstallationWorkOrderView[]> =>
@@ -17,18 +41,54 @@ export const workOrders = {
       ...(params || {}),
     }),
 
+  /** Repair, relocation or dismantle; installations come from customer orders. */
+  create: (dto: CreateWorkOrderRequest) =>
+    safeInvoke('create_work_order', { token: getTokenOrThrow(), ...dto }),
+
   assignees: (): Promise<TeamMember[]> =>
     safeInvoke('list_installation_assignees', {
       token: getTokenOrThrow(),
     }),
 
-  assign: (id: string, payload: { assigned_to: string; scheduled_at?: string; notes?: string }) =>
+  assign: (
+    id: string,
+    payload: {
+      assigned_to: string;
+      scheduled_at?: string;
+      scheduled_end_at?: string;
+      notes?: string;
+      allow_overlap?: boolean;
+    },
+  ) =>
     safeInvoke('assign_installation_work_order', {
       token: getTokenOrThrow(),
       id,
       ...payload,
     }),
 
+  agenda: (params?: {
+    from?: string;
+    to?: string;
+    technician_id?: string;
+  }): Promise<WorkOrderAgendaItem[]> =>
+    safeInvoke('get_work_order_agenda', {
+      token: getTokenOrThrow(),
+      ...(params || {}),
+    }),
+
+  /** Open work orders of one day, ordered by travel distance. */
+  routePlan: (params: {
+    technician_id?: string;
+    /** YYYY-MM-DD in the app timezone; defaults to today. */
+    date?: string;
+    start_latitude?: number;
+    start_longitude?: number;
+  }): Promise<WorkOrderRoutePlan> =>
+    safeInvoke('plan_technician_route', {
+      token: getTokenOrThrow(),
+      ...params,
+    }),
+
   claim: (id: string, notes?: string) =>
     safeInvoke('claim_installation_work_order', {
       token: getTokenOrThrow(),
@@ -50,11 +110,44 @@ export const workOrders = {
       notes: notes ?? undefined,
     }),
 
-  complete: (id: string, notes?: string) =>
+  /** Repairs only; resume with `start`. */
+  hold: (id: string, notes: string) =>
+    safeInvoke('hold_work_order', {
+      token: getTokenOrThrow(),
+      id,
+      notes,
+    }),
+
+  /** With a signature, a completion report is generated and emailed to the customer. */
+  complete: (id: string, notes?: string, signature?: WorkOrderSignatureInput) =>
     safeInvoke('complete_installation_work_order', {
       token: getTokenOrThrow(),
       id,
       notes: notes ?? undefined,
+      signature,
+    }),
+
+  completionReports: (id: string): Promise<WorkOrderCompletionReport[]> =>
+    safeInvoke('list_work_order_completion_reports', {
+      token: getTokenOrThrow(),
+      id,
+    }),
+
+  /** Sign off a work order that was completed without a signature. */
+  signCompletion: (
+    id: string,
+    signature: WorkOrderSignatureInput,
+  ): Promise<WorkOrderCompletionReport> =>
+    safeInvoke('sign_work_order_completion', {
+      token: getTokenOrThrow(),
+      id,
+      ...signature,
+    }),
+
+  emailCompletionReport: (reportId: string): Promise<WorkOrderCompletionReport> =>
+    safeInvoke('email_work_order_completion_report', {
+      token: getTokenOrThrow(),
+      reportId,
     }),
 
   cancel: (id: string, notes?: string) =>
@@ -90,4 +183,90 @@ export const workOrders = {
       id,
       ...payload,
     }),
+
+  visits: (id: string): Promise<WorkOrderVisit[]> =>
+    safeInvoke('list_work_order_visits', {
+      token: getTokenOrThrow(),
+      id,
+    }),
+
+  checkIn: (id: string, payload: WorkOrderCheckPayload): Promise<WorkOrderVisit> =>
+    safeInvoke('check_in_work_order', {
+      token: getTokenOrThrow(),
+      id,
+      ...payload,
+    }),
+
+  checkOut: (id: string, payload: WorkOrderCheckPayload): Promise<WorkOrderVisit> =>
+    safeInvoke('check_out_work_order', {
+      token: getTokenOrThrow(),
+      id,
+      ...payload,
+    }),
+
+  /** The default template is copied onto the work order on first read. */
+  checklist: (id: string): Promise<WorkOrderChecklist> =>
+    safeInvoke('get_work_order_checklist', {
+      token: getTokenOrThrow(),
+      id,
+    }),
+
+  /** Replaces the checklist (and any progress on it) with a template. */
+  applyChecklistTemplate: (id: string, templateId: string): Promise<WorkOrderChecklist> =>
+    safeInvoke('apply_work_order_checklist_template', {
+      token: getTokenOrThrow(),
+      id,
+      template_id: templateId,
+    }),
+
+  updateChecklistItem: (
+    id: string,
+    itemId: string,
+    payload: UpdateWorkOrderChecklistItemRequest,
+  ): Promise<WorkOrderChecklist> =>
+    safeInvoke('update_work_order_checklist_item', {
+      token: getTokenOrThrow(),
+      id,
+      item_id: itemId,
+      ...payload,
+    }),
+
+  materials: (id: string): Promise<InventoryMovement[]> =>
+    safeInvoke('list_work_order_materials', {
+      token: getTokenOrThrow(),
+      id,
+    }),
+
+  /** All lines are taken from stock together, or none if any is short. */
+  consumeMaterials: (
+    id: string,
+    payload: ConsumeWorkOrderMaterialsRequest,
+  ): Promise<InventoryMovement[]> =>
+    safeInvoke('consume_work_order_materials', {
+      token: getTokenOrThrow(),
+      id,
+      ...payload,
+    }),
+
+  /** Consumes the scanned unit from the stock location it is kept at. */
+  installSerial: (id: string, payload: InstallInventorySerialRequest): Promise<InventorySerial> =>
+    safeInvoke('install_work_order_serial', {
+      token: getTokenOrThrow(),
+      id,
+      ...payload,
+    }),
+
+  templates: {
+    list: (): Promise<WorkOrderTemplate[]> =>
+      safeInvoke('list_work_order_templates', { token: getTokenOrThrow() }),
+
+    create: (dto: UpsertWorkOrderTemplateRequest): Promise<WorkOrderTemplate> =>
+      safeInvoke('create_work_order_template', { token: getTokenOrThrow(), ...dto }),
+
+    update: (id: string, dto: UpsertWorkOrderTemplateRequest): Promise<WorkOrderTemplate> =>
+      safeInvoke('update_work_order_template', { token: getTokenOrThrow(), id, ...dto }),
+
+    delete: (id: string): Promise<void> =>
+      safeInvoke('delete_work_order_template', { token: getTokenOrThrow(), id }),
+  },
 };
@@ -0,0 +1,91 @@
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import { isTauriRuntime, safeInvoke } from '$lib/api/core';
+
+/** Label of the desktop window opened by `open_wallboard_window`. */
+export const WALLBOARD_WINDOW_LABEL = 'wallboard';
+
+/** Pixel shift steps; the board walks this loop to spread wear on TV panels. */
+const BURN_IN_STEPS: readonly [number, number][] = [
+  [0, 0],
+  [2, 0],
+  [2, 2],
+  [0, 2],
+  [-2, 2],
+  [-2, 0],
+  [-2, -2],
+  [0, -2],
+  [2, -2],
+];
+
+export function isWallboardKioskWindow() {
+  if (!isTauriRuntime()) return false;
+  try {
+    return getCurrentWindow().label === WALLBOARD_WINDOW_LABEL;
+  } catch {
+    return false;
+  }
+}
+
+/** Opens (or focuses) the frameless always-on-top wallboard window, on a second screen if any. */
+export function openWallboardWindow(route: string): Promise<void> {
+  return safeInvoke('open_wallboard_window', { route });
+}
+
+export function closeWallboardWindow(): Promise<void> {
+  return safeInvoke('close_wallboard_window');
+}
+
+export function burnInOffset(step: number) {
+  const [x, y] = BURN_IN_STEPS[Math.abs(step) % BURN_IN_STEPS.length];
+  return { x, y };
+}
+
+/** Moves the board a couple of pixels every `intervalMs`. Returns the uninstall function. */
+export function installBurnInShift(
+  apply: (offset: { x: number; y: number }) => void,
+  intervalMs = 5 * 60_000,
+) {
+  let step = 0;
+  const handle = setInterval(() => apply(burnInOffset(++step)), intervalMs);
+  return () => {
+    clearInterval(handle);
+    apply(burnInOffset(0));
+  };
+}
+
+/**
+ * Keeps an unattended screen live: polls again as soon as the network comes
+ * back, and reloads the page after `reloadAfterMs` of continuous failures
+ * (stale chunks after an update, a wedged webview, ...).
+ */
+export function installKioskReconnect(args: {
+  isFailing: () => boolean;
+  onReconnect: () => void;
+  reloadAfterMs?: number;
+  checkEveryMs?: number;
+}) {
+  if (typeof window === 'undefined') return null;
+  const reloadAfterMs = args.reloadAfterMs ?? 5 * 60_000;
+  let failingSince: number | null = null;
+
+  const onOnline = () => args.onReconnect();
+  const handle = setInterval(() => {
+    const failing = !navigator.onLine || args.isFailing();
+    if (!failing) {
+      failingSince = null;
+      return;
+    }
+    failingSince ??= Date.now();
+    if (Date.now() - failingSince >= reloadAfterMs && navigator.onLine) {
+      window.location.reload();
+      return;
+    }
+    args.onReconnect();
+  }, args.checkEveryMs ?? 30_000);
+
+  window.addEventListener('online', onOnline);
+  return () => {
+    clearInterval(handle);
+    window.removeEventListener('online', onOnline);
+  };
+}
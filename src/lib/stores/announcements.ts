@@ -9,7 +9,7 @@ export type Announcement = {
   title: string;
   body: string;
   severity: 'info' | 'success' | 'warning' | 'error' | string;
-  audience: 'all' | 'admins' | string;
+  audience: 'all' | 'admins' | 'segment' | string;
   mode?: 'post' | 'banner' | string;
   format?: 'plain' | 'markdown' | 'html' | string;
   deliver_in_app?: boolean;
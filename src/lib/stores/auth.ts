@@ -4,8 +4,10 @@
  */
 import { writable, derived, get } from 'svelte/store';
 import { api, auth, publicApi, type User, type Tenant, type AuthResponse } from '$lib/api/client';
+import { clearOfflineCache } from '$lib/api/offline';
 import { appSettings } from './settings';
 import { appLogo } from './logo';
+import { featureFlags } from './featureFlags';
 
 // Tracks whether backend/API is reachable. We keep sessions during transient outages.
 export const backendAvailable = writable(true);
@@ -110,9 +112,11 @@ token.subscribe((value) => {
     if (value) {
       appLogo.refresh(value);
       appSettings.refresh();
+      void featureFlags.refresh();
     } else {
       // On logout, reset settings to default (secure by default)
       appSettings.reset();
+      featureFlags.reset();
     }
   }
 });
@@ -257,6 +261,7 @@ export function logout(): void {
   sessionStorage.removeItem(USER_KEY);
   sessionStorage.removeItem(TENANT_KEY);
   sessionStorage.removeItem(ACTIVE_TENANT_SLUG_KEY);
+  clearOfflineCache();
   checkAuthInFlight = null;
   lastCheckAuthAt = 0;
   lastCheckAuthResult = false;
@@ -0,0 +1,64 @@
+import { get, writable } from 'svelte/store';
+import { invoke } from '@tauri-apps/api/core';
+import { getVersion } from '@tauri-apps/api/app';
+import { publicApi, type AvailableUpdate, type ClientCompatibility } from '$lib/api/client';
+import { isTauriRuntime } from '$lib/api/core';
+
+export interface ClientUpdateState {
+  compatibility: ClientCompatibility | null;
+  available: AvailableUpdate | null;
+  installing: boolean;
+  error: string | null;
+}
+
+export const clientUpdate = writable<ClientUpdateState>({
+  compatibility: null,
+  available: null,
+  installing: false,
+  error: null,
+});
+
+/**
+ * Asks the API whether this desktop build may still use it. Only an explicit
+ * "no" blocks the app; an unreachable server is left to the offline mode.
+ */
+export async function checkClientCompatibility(): Promise<boolean> {
+  if (!isTauriRuntime()) return true;
+  try {
+    const compatibility = await publicApi.getClientCompatibility(await getVersion());
+    clientUpdate.update((s) => ({ ...s, compatibility }));
+    return compatibility.supported;
+  } catch (e) {
+    console.warn('[Updater] Compatibility check failed:', e);
+    return true;
+  }
+}
+
+/** Looks for a newer release on the channel the server asks for. */
+export async function checkForClientUpdate() {
+  if (!isTauriRuntime()) return;
+  const channel = get(clientUpdate).compatibility?.update_channel ?? 'stable';
+  try {
+    const available = await invoke<AvailableUpdate | null>('check_for_update', { channel });
+    clientUpdate.update((s) => ({ ...s, available }));
+  } catch (e) {
+    // No release for this platform yet, no network, ...: not worth a prompt.
+    console.warn('[Updater] Update check failed:', e);
+  }
+}
+
+/** Installs the release found by `checkForClientUpdate`; the app restarts when done. */
+export async function installClientUpdate() {
+  const state = get(clientUpdate);
+  const channel = state.available?.channel ?? state.compatibility?.update_channel ?? 'stable';
+  clientUpdate.update((s) => ({ ...s, installing: true, error: null }));
+  try {
+    await invoke('install_update', { channel });
+  } catch (e: any) {
+    clientUpdate.update((s) => ({ ...s, installing: false, error: String(e?.message || e) }));
+  }
+}
+
+export function dismissClientUpdate() {
+  clientUpdate.update((s) => ({ ...s, available: null }));
+}
@@ -0,0 +1,24 @@
+import { derived, writable } from 'svelte/store';
+import { api } from '$lib/api/client';
+
+function createFeatureFlagsStore() {
+  const { subscribe, set } = writable<Record<string, boolean>>({});
+
+  return {
+    subscribe,
+    refresh: async () => {
+      try {
+        set(await api.featureFlags.evaluate());
+      } catch (e) {
+        // Unknown flags read as off, which is the safe default.
+        console.warn('Failed to load feature flags:', e);
+      }
+    },
+    reset: () => set({}),
+  };
+}
+
+export const featureFlags = createFeatureFlagsStore();
+
+/** `$flagEnabled('key')`: unknown or unloaded flags are off. */
+export const flagEnabled = derived(featureFlags, ($flags) => (key: string) => $flags[key] === true);
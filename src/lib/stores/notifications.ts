@@ -2,6 +2,7 @@ import { writable, get } from 'svelte/store';
 import {
   notifications as api,
   type Notification,
+  type NotificationFilter,
   type NotificationPreference,
 } from '$lib/api/client';
 import { toast } from 'svelte-sonner';
@@ -11,6 +12,8 @@ import { t } from 'svelte-i18n';
 
 const UNREAD_REFRESH_MIN_INTERVAL_MS = 15_000;
 let lastUnreadRefreshAt = 0;
+// Set while the desktop backend shows OS notifications for this user.
+let desktopBridgeActive = false;
 
 // Helper to convert VAPID key
 function urlBase64ToUint8Array(base64String: string) {
@@ -26,6 +29,41 @@ function urlBase64ToUint8Array(base64String: string) {
   return outputArray;
 }
 
+function toBase64Url(arr: ArrayBuffer) {
+  return btoa(String.fromCharCode.apply(null, Array.from(new Uint8Array(arr))))
+    .replace(/\+/g, '-')
+    .replace(/\//g, '_')
+    .replace(/=+$/, '');
+}
+
+// The server owns the VAPID key pair (it may generate or rotate it); the build-time
+// key is only a fallback for servers that predate the endpoint.
+async function getVapidPublicKey(): Promise<string | undefined> {
+  try {
+    const res = await api.getVapidPublicKey();
+    if (res?.public_key) return res.public_key;
+  } catch (e) {
+    console.warn('Failed to load VAPID public key from server:', e);
+  }
+  return import.meta.env.VITE_VAPID_PUBLIC_KEY;
+}
+
+async function sendSubscription(subscription: PushSubscription): Promise<boolean> {
+  const p256dh = subscription.getKey('p256dh');
+  const auth = subscription.getKey('auth');
+  if (!p256dh || !auth) {
+    console.warn('Push subscription missing keys');
+    return false;
+  }
+  await api.subscribePush(
+    subscription.endpoint,
+    toBase64Url(p256dh),
+    toBase64Url(auth),
+    subscription.expirationTime,
+  );
+  return true;
+}
+
 // Stores
 export const notifications = writable<Notification[]>([]);
 export const unreadCount = writable<number>(0);
@@ -33,6 +71,9 @@ export const loading = writable<boolean>(false);
 export const preferences = writable<NotificationPreference[]>([]);
 export const pushEnabled = writable<boolean>(false); // Tracks active subscription status
 
+// Server-side filters for the notification center
+export const filters = writable<NotificationFilter>({});
+
 // State for pagination
 export const pagination = writable({
   page: 1,
@@ -48,7 +89,7 @@ export async function loadNotifications(page: number = 1, append: boolean = fals
   loading.set(true);
   try {
     const perPage = get(pagination).perPage;
-    const res = await api.list(page, perPage);
+    const res = await api.list(page, perPage, get(filters));
 
     if (append) {
       notifications.update((curr) => [...curr, ...res.data]);
@@ -143,6 +184,32 @@ export async function deleteNotification(id: string) {
   }
 }
 
+/**
+ * Apply notification center filters and reload from the first page
+ */
+export async function setFilters(next: NotificationFilter) {
+  filters.set(next);
+  await loadNotifications(1);
+}
+
+/**
+ * Archive (or restore, with `archived = false`) notifications
+ */
+export async function archiveNotifications(ids: string[], archived: boolean = true) {
+  if (ids.length === 0) return;
+  // Archiving or restoring moves rows out of the current view either way
+  const current = get(notifications);
+  notifications.set(current.filter((n) => !ids.includes(n.id)));
+
+  try {
+    await api.archive(ids, archived);
+  } catch (e) {
+    console.error('Failed to archive notifications:', e);
+    loadNotifications(1); // Reload
+  }
+  refreshUnreadCount(true);
+}
+
 /**
  * Load user preferences
  */
@@ -190,7 +257,22 @@ export async function checkSubscription() {
   try {
     const registration = await navigator.serviceWorker.ready;
     const subscription = await registration.pushManager.getSubscription();
-    pushEnabled.set(!!subscription);
+    if (!subscription) {
+      pushEnabled.set(false);
+      return;
+    }
+
+    // A subscription made under an old server key can no longer receive pushes.
+    const vapidPublicKey = await getVapidPublicKey();
+    const subscribedKey = subscription.options.applicationServerKey;
+    if (vapidPublicKey && subscribedKey && toBase64Url(subscribedKey) !== vapidPublicKey) {
+      await subscription.unsubscribe();
+      pushEnabled.set(false);
+      return;
+    }
+
+    // Re-register so the server has current keys/expiry and the signed-in user.
+    pushEnabled.set(await sendSubscription(subscription));
   } catch (e) {
     console.error('Failed to check push subscription:', e);
     pushEnabled.set(false);
@@ -229,7 +311,7 @@ export async function subscribePush() {
     }
 
     const registration = await navigator.serviceWorker.ready;
-    const vapidPublicKey = import.meta.env.VITE_VAPID_PUBLIC_KEY;
+    const vapidPublicKey = await getVapidPublicKey();
 
     if (!vapidPublicKey) {
       console.error('VAPID public key not found');
@@ -239,31 +321,24 @@ export async function subscribePush() {
       return;
     }
 
-    // 3. Subscribe
+    // 3. Subscribe (the browser refuses a new key while an old subscription exists)
+    const existing = await registration.pushManager.getSubscription();
+    const existingKey = existing?.options.applicationServerKey;
+    if (existing && existingKey && toBase64Url(existingKey) !== vapidPublicKey) {
+      await existing.unsubscribe();
+    }
+
     const subscription = await registration.pushManager.subscribe({
       userVisibleOnly: true,
       applicationServerKey: urlBase64ToUint8Array(vapidPublicKey),
     });
 
     // Send to backend
-    const p256dh = subscription.getKey('p256dh');
-    const auth = subscription.getKey('auth');
-
-    if (p256dh && auth) {
-      const toBase64Url = (arr: ArrayBuffer) => {
-        return btoa(String.fromCharCode.apply(null, Array.from(new Uint8Array(arr))))
-          .replace(/\+/g, '-')
-          .replace(/\//g, '_')
-          .replace(/=+$/, '');
-      };
-
-      await api.subscribePush(subscription.endpoint, toBase64Url(p256dh), toBase64Url(auth));
+    if (await sendSubscription(subscription)) {
       pushEnabled.set(true);
       toast.success(
         get(t)('notifications.toasts.enabled') || 'Push notifications enabled successfully!',
       );
-    } else {
-      console.warn('Push subscription missing keys');
     }
   } catch (e) {
     console.error('Failed to subscribe to push:', e);
@@ -310,6 +385,22 @@ export async function sendTestNotification() {
   }
 }
 
+/**
+ * Tells the desktop backend who is signed in, so it can show OS notifications
+ * while the window is minimized. Falls back to webview notifications when the
+ * app talks to a remote server.
+ */
+export async function syncDesktopNotificationSession(token: string | null) {
+  if (!isTauri()) return;
+  try {
+    await api.desktop.setSession(token);
+    desktopBridgeActive = !!token;
+  } catch (e) {
+    desktopBridgeActive = false;
+    console.warn('Desktop notifications unavailable:', e);
+  }
+}
+
 // --- WebSocket Event Handlers ---
 
 export function handleNotificationReceived(notification: Notification) {
@@ -324,16 +415,18 @@ export function handleNotificationReceived(notification: Notification) {
   else if (notification.notification_type === 'error') toast.error(notification.title);
   else toast.info(notification.title);
 
-  // If Desktop, also trigger system notification
-  // If Desktop, also trigger system notification
+  // If Desktop, also trigger system notification, unless the backend already
+  // shows it (with the user's per-category opt-outs).
   if (isTauri()) {
-    try {
-      sendNotification({
-        title: notification.title,
-        body: notification.message || 'New notification received',
-      });
-    } catch (e) {
-      console.error('Failed to send system notification:', e);
+    if (!desktopBridgeActive) {
+      try {
+        sendNotification({
+          title: notification.title,
+          body: notification.message || 'New notification received',
+        });
+      } catch (e) {
+        console.error('Failed to send system notification:', e);
+      }
     }
   } else if (Notification.permission === 'granted') {
     // Validation for Browser: Trigger standard Web Notification
@@ -24,10 +24,22 @@ export interface RequestMetrics {
   p95_response_time_ms: number;
 }
 
+export interface PoolMetrics {
+  max_connections: number;
+  size: number;
+  idle: number;
+  active: number;
+  utilization_pct: number;
+  avg_acquire_wait_ms: number;
+  max_acquire_wait_ms: number;
+  acquire_timeouts: number;
+}
+
 export interface SystemHealth {
   database: {
     is_connected: boolean;
     database_type: string;
+    read_replica_enabled: boolean;
     database_size_bytes: number;
     total_tables: number;
     tenants_count: number;
@@ -48,6 +60,7 @@ export interface SystemHealth {
   app_version: string;
   collected_at: string;
   request_metrics?: RequestMetrics;
+  pool_metrics?: PoolMetrics;
 }
 
 export const systemHealthCache = writable<{
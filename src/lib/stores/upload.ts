@@ -104,6 +104,7 @@ function createUploadStore() {
             upload_id,
             file_name: file.name,
             content_type: file.type || 'application/octet-stream',
+            path: file.name,
           }),
         });
 
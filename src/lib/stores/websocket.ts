@@ -53,6 +53,16 @@ type WsEvent =
       tenant_id: string | null;
       ticket_id: string;
       message_id: string;
+    }
+  | {
+      type: 'backup_run_progress';
+      run_id: string;
+      target: 'global' | 'tenants' | 'offsite';
+      message: string;
+      completed: number;
+      total: number;
+      done: boolean;
+      error: string | null;
     };
 
 let ws: WebSocket | null = null;
@@ -400,6 +410,15 @@ async function handleWsEvent(event: WsEvent) {
       }
       break;
 
+    case 'backup_run_progress':
+      if (!get(isSuperAdmin)) return;
+      try {
+        window.dispatchEvent(new CustomEvent('backup_run_progress', { detail: event }));
+      } catch {
+        // ignore
+      }
+      break;
+
     default:
       // Unknown event type
       break;
@@ -0,0 +1,45 @@
+import { describe, expect, it, vi } from 'vitest';
+
+vi.mock('@tauri-apps/plugin-deep-link', () => ({
+  getCurrent: vi.fn(async () => null),
+  onOpenUrl: vi.fn(async () => () => {}),
+}));
+
+import { resolveDeepLink } from './deepLink';
+
+describe('resolveDeepLink', () => {
+  it('routes password reset links with their token', () => {
+    expect(resolveDeepLink('ispmanagement://forgot-password/reset?token=abc')).toBe(
+      '/forgot-password/reset?token=abc',
+    );
+    expect(resolveDeepLink('ispmanagement://reset-password?token=a%2Bb')).toBe(
+      '/forgot-password/reset?token=a%2Bb',
+    );
+    expect(resolveDeepLink('https://isp.example.com/forgot-password/reset?token=xyz')).toBe(
+      '/forgot-password/reset?token=xyz',
+    );
+    expect(resolveDeepLink('ispmanagement://reset-password')).toBeNull();
+  });
+
+  it('keeps the tenant domain of invite links', () => {
+    expect(resolveDeepLink('https://net.example.com/register?invite=tok')).toBe(
+      '/register?invite=tok&domain=net.example.com',
+    );
+    expect(resolveDeepLink('ispmanagement://register?invite=tok&domain=net.example.com')).toBe(
+      '/register?invite=tok&domain=net.example.com',
+    );
+    expect(resolveDeepLink('ispmanagement://register?invite=tok')).toBe('/register?invite=tok');
+    expect(resolveDeepLink('ispmanagement://register')).toBeNull();
+  });
+
+  it('routes payment returns and ignores everything else', () => {
+    expect(resolveDeepLink('ispmanagement://pay/inv-1')).toBe('/pay/inv-1');
+    expect(resolveDeepLink('https://isp.example.com/pay/inv-1?status=pending')).toBe(
+      '/pay/inv-1?status=pending',
+    );
+    expect(resolveDeepLink('ispmanagement://pay/inv-1/extra')).toBeNull();
+    expect(resolveDeepLink('ispmanagement://admin/users')).toBeNull();
+    expect(resolveDeepLink('javascript:alert(1)')).toBeNull();
+    expect(resolveDeepLink('not a url')).toBeNull();
+  });
+});
@@ -0,0 +1,93 @@
+import { getCurrent, onOpenUrl } from '@tauri-apps/plugin-deep-link';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+
+/** Custom URL scheme the desktop app registers, e.g. `ispmanagement://pay/<id>`. */
+export const DEEP_LINK_SCHEME = 'ispmanagement';
+
+function clean(value: string | null) {
+  const trimmed = (value || '').trim();
+  return trimmed || null;
+}
+
+/**
+ * Maps an incoming link to the in-app route, or null if the app has no screen
+ * for it. Accepts the custom scheme as well as the https links sent in emails:
+ *
+ * - `ispmanagement://forgot-password/reset?token=...` (also `reset-password`)
+ * - `ispmanagement://register?invite=...&domain=...`
+ * - `ispmanagement://pay/<invoice id>?status=...`
+ */
+export function resolveDeepLink(raw: string): string | null {
+  let url: URL;
+  try {
+    url = new URL(raw.trim());
+  } catch {
+    return null;
+  }
+
+  let segments: string[];
+  let domain: string | null = null;
+  if (url.protocol === `${DEEP_LINK_SCHEME}:`) {
+    // `ispmanagement://pay/1` parses with `pay` as the host.
+    segments = [url.hostname, ...url.pathname.split('/')];
+    domain = clean(url.searchParams.get('domain'));
+  } else if (url.protocol === 'https:' || url.protocol === 'http:') {
+    segments = url.pathname.split('/');
+    domain = url.host;
+  } else {
+    return null;
+  }
+  const path = segments
+    .filter(Boolean)
+    .map((s) => decodeURIComponent(s))
+    .join('/');
+
+  if (path === 'forgot-password/reset' || path === 'reset-password') {
+    const token = clean(url.searchParams.get('token'));
+    return token ? `/forgot-password/reset?token=${encodeURIComponent(token)}` : null;
+  }
+
+  if (path === 'register') {
+    const invite = clean(url.searchParams.get('invite'));
+    if (!invite) return null;
+    const params = new URLSearchParams({ invite });
+    if (domain) params.set('domain', domain);
+    return `/register?${params}`;
+  }
+
+  const pay = path.match(/^pay\/([^/]+)$/);
+  if (pay) {
+    const status = clean(url.searchParams.get('status'));
+    const route = `/pay/${encodeURIComponent(pay[1])}`;
+    return status ? `${route}?status=${encodeURIComponent(status)}` : route;
+  }
+
+  return null;
+}
+
+/**
+ * Routes the link the app was launched with, then every link opened while it
+ * runs. Returns the unlisten function. Only the main window follows links, not
+ * e.g. the wallboard kiosk window.
+ */
+export async function listenForDeepLinks(navigate: (route: string) => void) {
+  const open = (urls: string[] | null) => {
+    for (const url of urls || []) {
+      const route = resolveDeepLink(url);
+      if (route) {
+        navigate(route);
+        return;
+      }
+      console.warn('[DeepLink] Ignoring unsupported link:', url);
+    }
+  };
+
+  try {
+    if (getCurrentWindow().label !== 'main') return () => {};
+    open(await getCurrent());
+    return await onOpenUrl(open);
+  } catch (e) {
+    console.warn('[DeepLink] Deep links unavailable:', e);
+    return () => {};
+  }
+}
@@ -24,7 +24,7 @@ function sanitizeFilePart(input: string) {
     .slice(0, 80);
 }
 
-export function buildTimestampedFilename(prefix: string, ext: 'csv' | 'xls') {
+export function buildTimestampedFilename(prefix: string, ext: 'csv' | 'xls' | 'ndjson') {
   const safePrefix = sanitizeFilePart(prefix) || 'export';
   const stamp = new Date().toISOString().replace(/[:.]/g, '-');
   return `${safePrefix}-${stamp}.${ext}`;